@@ -0,0 +1,454 @@
+//! `graph-browser --headless`: batch operations on graph files without opening
+//! a window, eg. to generate images in CI.
+
+use graph_engine::algo::metrics::{Metric, Progress};
+use graph_engine::context::EngineContext;
+use graph_engine::generate::{DEFAULT_SEED, Generator};
+use graph_engine::graph::Graph;
+use graph_engine::io::{self, ID_ATTR};
+use graph_engine::layout::{Layout, X_ATTR, Y_ATTR};
+use graph_engine::style::{Style, StyleSheet};
+use graph_engine::subsystems::export::{ExportArea, ExportOptions};
+use graph_engine::subsystems::input::{InputMap, InputMapper};
+use graph_engine::subsystems::scene::{Camera, VectorFormat};
+
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::str::FromStr;
+
+use crate::draw_graph;
+
+const USAGE: &str = "\
+Usage: graph-browser --headless <command> [options]
+
+Commands:
+  convert <input> <output>     Write the graph in the format of <output>
+  layout <input> <output>      Place the nodes, writing their positions as x and y
+      --layout <name>          Layout to run (default: force-directed)
+  metrics <input> [<output>]   Compute node metrics into <output>, or as CSV on stdout
+      --metric <name>          Metric to compute, repeatable (default: all)
+  render <input> <output>      Draw the whole graph to an SVG or PDF file
+      --layout <name>          Layout to run first (default: the file's positions)
+      --style <file>           Style sheet to draw with
+      --size <width>x<height>  Page size, in points (default: 1280x720)
+      --scale <factor>         Page units per point (default: 1)
+      --transparent            Leave the background transparent
+      --dpi <dpi>              Page units per inch, giving the page a printed size
+      --title <text>           Title written above the graph
+      --caption <text>         Caption written below the graph
+      --legend                 Explain the style's colors and shapes in a corner
+  generate <model> <output>    Write a generated graph
+      --nodes <count>          Number of nodes (the random models)
+      --probability <p>        Chance of each pair being linked (erdos-renyi)
+      --edges <count>          Edges from each added node (barabasi-albert)
+      --neighbors <count>      Even number of neighbors on the ring (watts-strogatz)
+      --rewiring <p>           Chance of each edge being rewired (watts-strogatz)
+      --rows <count>           Rows of the grid (grid)
+      --columns <count>        Columns of the grid (grid)
+      --branching <count>      Children of each node (tree)
+      --depth <count>          Levels below the root (tree)
+      --seed <seed>            Seed of the random models (default: 1)
+
+Layouts: force-directed, hierarchical, circular, grid, radial-tree, groups
+Metrics: degree, betweenness, closeness, pagerank, clustering
+Models: erdos-renyi, barabasi-albert, watts-strogatz, grid, tree
+";
+/// Page size of `render` unless set with `--size`, in points.
+const DEFAULT_SIZE: [f32; 2] = [1280.0, 720.0];
+
+/// The arguments following a command.
+#[derive(Default)]
+struct Args {
+    positional: Vec<String>,
+    /// Options with a value, in order, without their leading dashes.
+    options: Vec<(String, String)>,
+    /// Options without a value.
+    flags: Vec<String>,
+}
+
+/// Run the command in `args`, the arguments after `--headless`.
+pub fn run(args: &[String]) -> ExitCode {
+    let Some((command, rest)) = args.split_first() else {
+        eprint!("{USAGE}");
+        return ExitCode::from(2);
+    };
+    let args = match Args::parse(rest) {
+        Ok(args) => args,
+        Err(e) => return usage_error(&e),
+    };
+    let result = match command.as_str() {
+        "convert" => convert(&args),
+        "layout" => layout(&args),
+        "metrics" => metrics(&args),
+        "render" => render(&args),
+        "generate" => generate(&args),
+        "help" | "--help" | "-h" => {
+            print!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        other => return usage_error(&format!("Unknown command '{other}'")),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn usage_error(message: &str) -> ExitCode {
+    eprintln!("error: {message}\n\n{USAGE}");
+    ExitCode::from(2)
+}
+
+impl Args {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        const FLAGS: [&str; 2] = ["transparent", "legend"];
+        let mut parsed = Self::default();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let Some(name) = arg.strip_prefix("--") else {
+                parsed.positional.push(arg.clone());
+                continue;
+            };
+            if FLAGS.contains(&name) {
+                parsed.flags.push(name.to_owned());
+                continue;
+            }
+            let value = args
+                .next()
+                .ok_or_else(|| format!("Option '--{name}' needs a value"))?;
+            parsed.options.push((name.to_owned(), value.clone()));
+        }
+        Ok(parsed)
+    }
+
+    /// The positional argument at `index`, named `name` in errors.
+    fn path(&self, index: usize, name: &str) -> Result<PathBuf, String> {
+        self.positional
+            .get(index)
+            .map(PathBuf::from)
+            .ok_or_else(|| format!("Missing <{name}>"))
+    }
+
+    /// The last value of the option `name`.
+    fn option(&self, name: &str) -> Option<&str> {
+        self.options
+            .iter()
+            .rev()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Every value of the option `name`, in order.
+    fn options<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> {
+        self.options
+            .iter()
+            .filter(move |(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.flags.iter().any(|f| f == name)
+    }
+
+    /// Fail on options none of `known` and on more than `max` positional
+    /// arguments, so typos do not go unnoticed.
+    fn check(&self, known: &[&str], max: usize) -> Result<(), String> {
+        if let Some(extra) = self.positional.get(max) {
+            return Err(format!("Unexpected argument '{extra}'"));
+        }
+        let unknown = self
+            .options
+            .iter()
+            .map(|(n, _)| n)
+            .chain(&self.flags)
+            .find(|n| !known.contains(&n.as_str()));
+        match unknown {
+            Some(name) => Err(format!("Unknown option '--{name}'")),
+            None => Ok(()),
+        }
+    }
+}
+
+fn convert(args: &Args) -> Result<(), String> {
+    args.check(&[], 2)?;
+    let (input, output) = (args.path(0, "input")?, args.path(1, "output")?);
+    let graph = load(&input)?;
+    save(&graph, &output)
+}
+
+fn layout(args: &Args) -> Result<(), String> {
+    args.check(&["layout"], 2)?;
+    let (input, output) = (args.path(0, "input")?, args.path(1, "output")?);
+    let mut ctx = open(&input)?;
+    run_layout(&mut ctx, args.option("layout").unwrap_or("force-directed"))?;
+    let mut graph = ctx.graph.clone();
+    for node in ctx.graph.nodes() {
+        if let Some([x, y]) = ctx.positions.get(node) {
+            let attrs = graph.node_mut(node).unwrap();
+            attrs.insert(X_ATTR, x as f64);
+            attrs.insert(Y_ATTR, y as f64);
+        }
+    }
+    save(&graph, &output)
+}
+
+fn metrics(args: &Args) -> Result<(), String> {
+    args.check(&["metric"], 2)?;
+    let input = args.path(0, "input")?;
+    let mut metrics = Vec::new();
+    for name in args.options("metric") {
+        let metric = Metric::ALL
+            .into_iter()
+            .find(|m| matches(m.label(), name) || matches(m.attribute(), name))
+            .ok_or_else(|| format!("Unknown metric '{name}'"))?;
+        if !metrics.contains(&metric) {
+            metrics.push(metric);
+        }
+    }
+    if metrics.is_empty() {
+        metrics = Metric::ALL.to_vec();
+    }
+
+    let mut graph = load(&input)?;
+    let progress = Progress::new();
+    for &metric in &metrics {
+        let scores = metric.compute(&graph, &progress);
+        let nodes: Vec<_> = graph.nodes().collect();
+        for node in nodes {
+            let score = scores[node.index()];
+            graph
+                .node_mut(node)
+                .unwrap()
+                .insert(metric.attribute(), score);
+        }
+    }
+
+    if let Some(output) = args.positional.get(1) {
+        return save(&graph, Path::new(output));
+    }
+    let mut header = vec![ID_ATTR];
+    header.extend(metrics.iter().map(|m| m.attribute()));
+    println!("{}", header.join(","));
+    for node in graph.nodes() {
+        let attrs = graph.node(node).unwrap();
+        let id = attrs
+            .get(ID_ATTR)
+            .map_or_else(|| node.to_string(), |v| v.to_string());
+        let mut row = vec![csv_field(&id)];
+        row.extend(metrics.iter().map(|m| {
+            attrs
+                .get(m.attribute())
+                .map(|v| v.to_string())
+                .unwrap_or_default()
+        }));
+        println!("{}", row.join(","));
+    }
+    Ok(())
+}
+
+fn render(args: &Args) -> Result<(), String> {
+    args.check(
+        &[
+            "layout",
+            "style",
+            "size",
+            "scale",
+            "transparent",
+            "dpi",
+            "title",
+            "caption",
+            "legend",
+        ],
+        2,
+    )?;
+    let (input, output) = (args.path(0, "input")?, args.path(1, "output")?);
+    let format = VectorFormat::from_path(&output).ok_or_else(|| {
+        format!(
+            "Cannot render to '{}': only SVG and PDF files can be written without a window",
+            output.display()
+        )
+    })?;
+    let size = match args.option("size") {
+        Some(size) => parse_size(size)?,
+        None => DEFAULT_SIZE,
+    };
+    let scale = match args.option("scale") {
+        Some(scale) => scale
+            .parse::<f32>()
+            .ok()
+            .filter(|s| *s > 0.0)
+            .ok_or_else(|| format!("Invalid scale '{scale}'"))?,
+        None => 1.0,
+    };
+    let dpi = match args.option("dpi") {
+        Some(dpi) => Some(
+            dpi.parse::<f32>()
+                .ok()
+                .filter(|d| *d > 0.0)
+                .ok_or_else(|| format!("Invalid DPI '{dpi}'"))?,
+        ),
+        None => None,
+    };
+
+    let mut ctx = open(&input)?;
+    if let Some(path) = args.option("style") {
+        let sheet =
+            StyleSheet::load(path).map_err(|e| format!("Failed to read style '{path}': {e}"))?;
+        ctx.style = Style::new(sheet);
+    }
+    if let Some(name) = args.option("layout") {
+        run_layout(&mut ctx, name)?;
+    }
+    ctx.refresh();
+
+    let options = ExportOptions {
+        path: output,
+        format,
+        area: ExportArea::Graph,
+        background: (!args.flag("transparent"))
+            .then_some(ctx.style.sheet().theme.colors().background),
+        scale,
+        dpi,
+        title: args.option("title").unwrap_or_default().to_owned(),
+        caption: args.option("caption").unwrap_or_default().to_owned(),
+        legend: args.flag("legend"),
+    };
+    let mut view = Camera::new([0.0, 0.0], 1.0);
+    view.set_viewport(size);
+    let bounds = ctx.positions.bounds(ctx.visible_graph());
+    let mut page = options.page(&view, bounds);
+    if options.legend {
+        page.set_legend(ctx.style.legend());
+    }
+    draw_graph(&mut ctx, &mut page);
+    page.save(&options.path, options.format)
+        .map_err(|e| format!("Failed to write '{}': {e}", options.path.display()))
+}
+
+fn generate(args: &Args) -> Result<(), String> {
+    let name = args.positional.first().ok_or("Missing <model>")?;
+    let mut generator = Generator::ALL
+        .into_iter()
+        .find(|g| matches(g.name(), name))
+        .ok_or_else(|| format!("Unknown model '{name}'"))?;
+    let known: &[&str] = match generator {
+        Generator::ErdosRenyi { .. } => &["nodes", "probability", "seed"],
+        Generator::BarabasiAlbert { .. } => &["nodes", "edges", "seed"],
+        Generator::WattsStrogatz { .. } => &["nodes", "neighbors", "rewiring", "seed"],
+        Generator::Grid { .. } => &["rows", "columns"],
+        Generator::Tree { .. } => &["branching", "depth"],
+    };
+    args.check(known, 2)?;
+    let output = args.path(1, "output")?;
+    match &mut generator {
+        Generator::ErdosRenyi { nodes, probability } => {
+            parse_option(args, "nodes", nodes)?;
+            parse_option(args, "probability", probability)?;
+        }
+        Generator::BarabasiAlbert { nodes, edges } => {
+            parse_option(args, "nodes", nodes)?;
+            parse_option(args, "edges", edges)?;
+        }
+        Generator::WattsStrogatz {
+            nodes,
+            neighbors,
+            rewiring,
+        } => {
+            parse_option(args, "nodes", nodes)?;
+            parse_option(args, "neighbors", neighbors)?;
+            parse_option(args, "rewiring", rewiring)?;
+        }
+        Generator::Grid { rows, columns } => {
+            parse_option(args, "rows", rows)?;
+            parse_option(args, "columns", columns)?;
+        }
+        Generator::Tree { branching, depth } => {
+            parse_option(args, "branching", branching)?;
+            parse_option(args, "depth", depth)?;
+        }
+    }
+    let mut seed = DEFAULT_SEED;
+    parse_option(args, "seed", &mut seed)?;
+
+    let graph = generator.generate(seed).map_err(|e| e.to_string())?;
+    save(&graph, &output)
+}
+
+/// Set `value` to the option `name`, if given.
+fn parse_option<T: FromStr>(args: &Args, name: &str, value: &mut T) -> Result<(), String> {
+    if let Some(text) = args.option(name) {
+        *value = text
+            .parse()
+            .map_err(|_| format!("Invalid {name} '{text}'"))?;
+    }
+    Ok(())
+}
+
+/// Read the file at `path`, detecting its format.
+fn load(path: &Path) -> Result<Graph, String> {
+    io::load(path).map_err(|e| format!("Failed to read '{}': {e}", path.display()))
+}
+
+/// Write `graph` in the format of `path`'s extension.
+fn save(graph: &Graph, path: &Path) -> Result<(), String> {
+    io::save(graph, path).map_err(|e| format!("Failed to write '{}': {e}", path.display()))
+}
+
+/// A context with the file at `path` open, as the browser would show it.
+fn open(path: &Path) -> Result<EngineContext, String> {
+    let graph = load(path)?;
+    let mut ctx = EngineContext::new(InputMapper::new(InputMap::default()));
+    ctx.open_document(graph, Some(path.to_owned()));
+    ctx.refresh();
+    Ok(ctx)
+}
+
+/// Run the layout `name` of `ctx` to completion.
+fn run_layout(ctx: &mut EngineContext, name: &str) -> Result<(), String> {
+    let graph = ctx.filters.graph(&ctx.graph);
+    if matches(Layout::name(&ctx.force), name) {
+        ctx.force.compute(graph, &mut ctx.positions);
+        return Ok(());
+    }
+    let index = ctx
+        .layouts
+        .iter()
+        .position(|l| matches(l.name(), name))
+        .ok_or_else(|| format!("Unknown layout '{name}'"))?;
+    ctx.apply_layout(index);
+    Ok(())
+}
+
+/// Returns `true` if `name` names `label`, ignoring case, spaces, and dashes.
+fn matches(label: &str, name: &str) -> bool {
+    let normalize = |s: &str| {
+        s.chars()
+            .filter(|c| c.is_alphanumeric())
+            .flat_map(char::to_lowercase)
+            .collect::<String>()
+    };
+    normalize(label) == normalize(name)
+}
+
+/// Parse a page size written as `<width>x<height>`.
+fn parse_size(size: &str) -> Result<[f32; 2], String> {
+    let invalid = || format!("Invalid size '{size}', expected eg. 1280x720");
+    let (width, height) = size.split_once(['x', 'X']).ok_or_else(invalid)?;
+    let parse = |v: &str| v.trim().parse::<f32>().ok().filter(|v| *v > 0.0);
+    match (parse(width), parse(height)) {
+        (Some(width), Some(height)) => Ok([width, height]),
+        _ => Err(invalid()),
+    }
+}
+
+/// `value` quoted for a CSV field if needed.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
+}
@@ -1,54 +1,1966 @@
+mod headless;
+
+use graph_engine::algo::diff::DiffPanel;
+use graph_engine::algo::metrics::MetricsPanel;
+use graph_engine::algo::{Algorithm, AlgorithmsPanel};
+use graph_engine::anim;
+use graph_engine::bookmarks::{self, BookmarksPanel};
+use graph_engine::clipboard;
+use graph_engine::context::EngineContext;
+use graph_engine::document::{RECENT_FILE, RecentFiles};
+use graph_engine::explore::{self, DEFAULT_DEPTH, DEFAULT_LIMIT, ExplorePanel};
+use graph_engine::filter::FilterPanel;
+use graph_engine::generate::{GenerateDialog, GenerateOutcome};
+use graph_engine::graph::Graph;
+use graph_engine::group::GroupPanel;
+use graph_engine::history::{Command, HistoryPanel};
+use graph_engine::hover;
+use graph_engine::i18n::{self, LOCALES_DIR, tr};
+use graph_engine::inspector::InspectorPanel;
+use graph_engine::io::Format;
+#[cfg(feature = "arrow")]
+use graph_engine::io::columnar::{ColumnarFormat, ColumnarLoader};
+use graph_engine::io::live::LivePanel;
+use graph_engine::io::loader;
+#[cfg(feature = "neo4j")]
+use graph_engine::io::neo4j::{ENDPOINTS_FILE, Neo4jPanel};
+#[cfg(feature = "sparql")]
+use graph_engine::io::sparql::SparqlPanel;
+use graph_engine::jobs::JobsPanel;
+use graph_engine::layout::LayoutPanel;
+use graph_engine::layout::arrange::Arrange;
+use graph_engine::layout::force::ForceLayoutPanel;
+use graph_engine::matrix::{self, MatrixPanel};
+use graph_engine::memory::MemoryPanel;
+use graph_engine::minimap::{self, MinimapPanel};
+use graph_engine::palette::{self, PaletteCommand, Picked};
+use graph_engine::pathfind::PathFinderPanel;
+use graph_engine::plugin::{self, PLUGINS_DIR, PanelChange, PluginManagerPanel};
+#[cfg(feature = "http")]
+use graph_engine::remote::{RemoteCall, RemoteCommand, RemotePanel, RemoteReply, RenderFormat};
+use graph_engine::renderer::imgui::{Condition, TabBar, TabItem, TabItemFlags, Ui, WindowFlags};
+use graph_engine::script::ScriptConsolePanel;
+#[cfg(feature = "teal")]
+use graph_engine::script::teal::TealRuntime;
+use graph_engine::scripts::{self, SCRIPTS_DIR, ScriptsPanel};
+use graph_engine::search::SearchPanel;
+use graph_engine::selection::SelectMode;
+use graph_engine::session::{
+    AUTOSAVE_FILE, Session, SessionError, WORKSPACE_FILE, WorkspaceAction, WorkspaceDialog,
+    WorkspaceOutcome,
+};
+use graph_engine::stats::StatisticsPanel;
+use graph_engine::status;
+#[cfg(feature = "sqlite")]
+use graph_engine::store::{STORE_EXTENSION, StorePanel};
+use graph_engine::style::{STYLE_FILE, Style, StylePanel, StyleSheet};
+use graph_engine::subsystems::app::{AppContext, GraphApp};
+use graph_engine::subsystems::capture::{self, CAPTURE_DIR, FrameRecorder};
+use graph_engine::subsystems::config::{
+    CONFIG_FILE, ConfigChange, ConfigManager, PreferencesPanel,
+};
+use graph_engine::subsystems::crash::{self, CRASH_DIR, CrashReporter, CrashReportsPanel};
+use graph_engine::subsystems::export::{ExportArea, ExportDialog, ExportOptions, ExportOutcome};
+#[cfg(feature = "arrow")]
+use graph_engine::subsystems::import::{ColumnarImportDialog, ColumnarImportOutcome};
+use graph_engine::subsystems::import::{CsvImportDialog, ImportOutcome};
+use graph_engine::subsystems::input::{Action, ActionState, InputMapper, KEYMAP_FILE};
+use graph_engine::subsystems::panels::{KeyBindingsPanel, LogPanel, PanelManager};
+use graph_engine::subsystems::profiler::{self, ProfilerPanel};
+#[cfg(feature = "http")]
+use graph_engine::subsystems::scene::VectorFormat;
+use graph_engine::subsystems::scene::{Camera, Canvas, EdgeStyle, Hit, Label, Picker, Scene};
+use graph_engine::subsystems::ui_state::UiStateManager;
 use graph_engine::subsystems::window::GraphWindow;
+use graph_engine::table::TablePanel;
+use graph_engine::timeline::TimelinePanel;
+use graph_engine::view3d;
 
 use common::renderer::SDL;
 use common::renderer::sdl3::event::Event;
-use common::renderer::sdl3::video::GLProfile;
 
-fn main() {
-    let vid = SDL.video();
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Instant;
+
+/// Spacing of the background grid, in world units.
+const GRID_SPACING: f32 = 100.0;
+/// Radius of nodes drawn by the GPU layout, which ignores the style, in world units.
+const NODE_RADIUS: f32 = 6.0;
+const NODE_COLOR: [f32; 4] = [0.35, 0.65, 1.0, 1.0];
+const EDGE_COLOR: [f32; 4] = [0.8, 0.8, 0.8, 0.4];
+/// Width of the outline of pinned nodes, in points.
+const PINNED_WIDTH: f32 = 1.5;
+/// Width of the highlight around selected nodes, and of selected edges, in points.
+const SELECTED_WIDTH: f32 = 2.5;
+/// Radius of the marker traversing a path found by the find path tool, in
+/// points.
+const PATH_MARKER_RADIUS: f32 = 6.0;
+/// Opacity of the paths found besides the cheapest one.
+const ALTERNATIVE_PATH_ALPHA: f32 = 0.4;
+/// How far from a node or edge a click still hits it, in points.
+const PICK_TOLERANCE: f32 = 4.0;
+/// How far the cursor must move while pressed to start a selection rectangle, in points.
+const DRAG_THRESHOLD: f32 = 4.0;
+/// Margin between a group's box and its members, in world units.
+const GROUP_PADDING: f32 = 16.0;
+/// Title of the prompt shown when closing a document with unsaved changes.
+const UNSAVED_TITLE: &str = "Unsaved Changes";
+/// Title of the prompt offering to recover the autosaved workspace.
+const RECOVERY_TITLE: &str = "Recover Workspace";
+/// Title of the prompt offering a safe-mode start after a crash.
+const CRASH_TITLE: &str = "Previous Session Crashed";
+/// Prefix of the ids of palette commands opening a recent file, followed by
+/// its path.
+const OPEN_RECENT_COMMAND: &str = "file.open_recent:";
+/// Prefix of the ids of palette commands toggling a panel, followed by its id.
+const PANEL_COMMAND: &str = "view.panel:";
+
+/// The graph browser application.
+struct Browser {
+    ctx: EngineContext,
+    panels: PanelManager,
+    /// Show the frame statistics overlay.
+    show_overlay: bool,
+    /// The column-mapping dialog of a CSV file being opened, if any.
+    csv_dialog: Option<CsvImportDialog>,
+    /// The column-mapping dialog of an Arrow or Parquet file being opened, if
+    /// any.
+    #[cfg(feature = "arrow")]
+    columnar_dialog: Option<ColumnarImportDialog>,
+    /// The dialog generating a graph into a new document, if any.
+    generate_dialog: Option<GenerateDialog>,
+    /// Where a selection started, while its button is held.
+    selecting: Option<(SelectMode, [f32; 2])>,
+    /// Id of the document whose tab was selected last frame, differing from
+    /// the active one until its tab catches up.
+    shown_document: u64,
+    /// Id of the document waiting for the unsaved changes prompt to close.
+    closing: Option<u64>,
+    /// Whether the unsaved changes prompt was opened for [`Self::closing`].
+    prompting: bool,
+    /// The dialog picking a workspace file to save or open, if any.
+    workspace_dialog: Option<WorkspaceDialog>,
+    /// A workspace to save or open once the frame ends, as it involves the
+    /// ui's layout.
+    workspace: Option<(WorkspaceAction, PathBuf)>,
+    /// The dialog choosing how to export the scene to a vector file, if any.
+    export_dialog: Option<ExportDialog>,
+    /// A vector export to write once the frame's camera is known.
+    export: Option<ExportOptions>,
+    /// The settings of the last export, offered again by the export dialog and
+    /// saved with the workspace.
+    export_settings: Option<ExportOptions>,
+    /// A command picked in the palette, run once the frame's camera is known.
+    picked: Option<Picked>,
+    /// The workspace file last saved or opened.
+    workspace_path: PathBuf,
+    /// Some while the previous run's autosave waits for the recovery prompt,
+    /// `true` once the prompt was opened.
+    recovery: Option<bool>,
+    /// Where the workspace is autosaved, removed on a clean exit.
+    autosave_path: PathBuf,
+    last_autosave: Instant,
+    /// Writes a report if the application panics.
+    crash: CrashReporter,
+    /// The report of the previous run's crash while the prompt offering a
+    /// safe-mode start waits, with whether the prompt was opened.
+    crashed: Option<(PathBuf, bool)>,
+    /// Set to start in safe mode once the frame ends, as it resets the ui's
+    /// layout.
+    safe_mode: bool,
+    /// Where plugins are discovered, unless in safe mode.
+    plugins_dir: PathBuf,
+
+    /// Where the style sheet is saved on exit.
+    style_path: PathBuf,
+    /// Where screenshots and recordings are saved.
+    capture_dir: PathBuf,
+    /// Set when a screenshot should be taken at the end of the frame.
+    screenshot: bool,
+    /// Remote requests for the window's pixels, answered at the end of the
+    /// frame.
+    #[cfg(feature = "http")]
+    remote_captures: Vec<RemoteCall>,
+    /// The in-progress frame recording, if any.
+    recorder: Option<FrameRecorder>,
+    /// Generations of the graph, positions and selection, the active document,
+    /// the number of log entries, the element whose hover card shows and the
+    /// number of toasts as of the last frame, to redraw once they change.
+    shown: (u64, u64, u64, usize, usize, Option<Hit>, usize),
+}
+
+impl Browser {
+    /// Open a graph file in the background, eg. one dropped onto the window.
+    fn open_file(&mut self, path: PathBuf) {
+        #[cfg(feature = "sqlite")]
+        if path
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case(STORE_EXTENSION))
+        {
+            match self.ctx.open_store(&path) {
+                Ok(()) => self.ctx.documents.recent.push(&path),
+                Err(e) => {
+                    self.ctx
+                        .log
+                        .error(format!("Failed to open '{}': {e}", path.display()));
+                    self.ctx.documents.recent.remove(&path);
+                }
+            }
+            return;
+        }
+        // tables need their columns chosen too, then are read in the background
+        #[cfg(feature = "arrow")]
+        if ColumnarFormat::from_path(&path).is_some() {
+            match ColumnarImportDialog::new(&path) {
+                Ok(dialog) => self.columnar_dialog = Some(dialog),
+                Err(e) => self
+                    .ctx
+                    .log
+                    .error(format!("Failed to open '{}': {e}", path.display())),
+            }
+            return;
+        }
+        if let Some(loader) = self.ctx.loader_for(&path) {
+            self.ctx.load_document_with(path, loader);
+            return;
+        }
+        let format = match Format::detect(&path) {
+            Ok(Some(format)) => format,
+            Ok(None) => {
+                self.ctx.log.warn(format!(
+                    "Cannot open '{}': unsupported file type",
+                    path.display()
+                ));
+                return;
+            }
+            Err(e) => {
+                self.ctx
+                    .log
+                    .error(format!("Failed to open '{}': {e}", path.display()));
+                self.ctx.documents.recent.remove(&path);
+                return;
+            }
+        };
+
+        // edge lists need their columns chosen before they can be read
+        if format == Format::Csv {
+            match CsvImportDialog::new(&path) {
+                Ok(dialog) => self.csv_dialog = Some(dialog),
+                Err(e) => self
+                    .ctx
+                    .log
+                    .error(format!("Failed to open '{}': {e}", path.display())),
+            }
+            return;
+        }
+
+        self.ctx.load_document(path, format);
+    }
+
+    /// Select what lies under `end`, or inside the rectangle from `start` to `end`
+    /// if the cursor was dragged. Both are in screen coordinates.
+    ///
+    /// While the find path tool is active, a node clicked is picked as an
+    /// endpoint of the path instead.
+    fn finish_selection(
+        &mut self,
+        camera: &Camera,
+        mode: SelectMode,
+        start: [f32; 2],
+        end: [f32; 2],
+    ) {
+        self.ctx.sync_spatial();
+        let picker = Picker::new(
+            self.ctx.visible_graph(),
+            &self.ctx.positions,
+            &self.ctx.spatial,
+        );
+
+        if is_drag(start, end) {
+            let (nodes, edges) =
+                picker.pick_rect(camera.screen_to_world(start), camera.screen_to_world(end));
+            self.ctx.selection.select(nodes, edges, mode);
+            return;
+        }
+
+        match pick(&picker, camera, self.ctx.style.max_node_radius(), end) {
+            // the find path tool takes the clicked nodes as endpoints instead
+            Some(Hit::Node(node)) if self.ctx.path_finder.is_active() => {
+                self.ctx.path_finder.pick(node);
+            }
+            Some(Hit::Node(node)) => self.ctx.selection.select_node(node, mode),
+            Some(Hit::Edge(edge)) => self.ctx.selection.select_edge(edge, mode),
+            // clicking the background deselects, unless extending the selection
+            None if mode == SelectMode::Replace => self.ctx.selection.clear(),
+            None => {}
+        }
+    }
+
+    /// Find the element under the cursor for its hover card, unless the cursor
+    /// is over the ui or busy selecting or panning.
+    fn update_hover(&mut self, ctx: &AppContext, camera: Option<&Camera>) {
+        let capture = ctx
+            .window()
+            .get_ui()
+            .map(|ui| ui.input_capture())
+            .unwrap_or_default();
+        let hit = match camera {
+            Some(camera)
+                if self.ctx.style.sheet().hover.enabled
+                    && !capture.mouse
+                    && self.selecting.is_none()
+                    && !self.ctx.camera.is_panning() =>
+            {
+                self.ctx.sync_spatial();
+                let picker = Picker::new(
+                    self.ctx.visible_graph(),
+                    &self.ctx.positions,
+                    &self.ctx.spatial,
+                );
+                let radius = self.ctx.style.max_node_radius();
+                pick(&picker, camera, radius, self.ctx.input.cursor())
+            }
+            _ => None,
+        };
+        self.ctx.hover.update(hit);
+    }
+
+    /// Glide the camera to show the whole graph.
+    fn fit_to_graph(&mut self) {
+        self.ctx.force.sync_positions(&mut self.ctx.positions);
+        let graph = self.ctx.filters.graph(&self.ctx.graph);
+        self.ctx.camera.fit_to_graph(graph, &self.ctx.positions);
+    }
+
+    /// Open the export dialog on a new file in the capture directory, with the
+    /// settings of the last export if any.
+    fn open_export_dialog(&mut self) {
+        let path = capture::timestamped_path(&self.capture_dir, "graph", "svg");
+        self.export_dialog = Some(match &self.export_settings {
+            Some(settings) => ExportDialog::with_settings(&path, settings),
+            None => ExportDialog::new(&path, self.ctx.style.sheet().theme.colors().background),
+        });
+    }
+
+    /// Draw the visible graph into a vector file, as chosen in `options`, seen
+    /// from the camera of the `view` unless it exports another area.
+    fn export_image(&mut self, view: &Camera, options: &ExportOptions) {
+        self.ctx.force.sync_positions(&mut self.ctx.positions);
+        let positions = &self.ctx.positions;
+        let bounds = match options.area {
+            ExportArea::View => None,
+            ExportArea::Graph => positions.bounds(self.ctx.visible_graph()),
+            ExportArea::Selection => self
+                .ctx
+                .selection
+                .nodes()
+                .filter_map(|node| positions.get(node))
+                .fold(None, |bounds, [x, y]| match bounds {
+                    None => Some(([x, y], [x, y])),
+                    Some((min, max)) => Some((
+                        [f32::min(min[0], x), f32::min(min[1], y)],
+                        [f32::max(max[0], x), f32::max(max[1], y)],
+                    )),
+                }),
+        };
+        let mut page = options.page(view, bounds);
+        if options.legend {
+            page.set_legend(self.ctx.style.legend());
+        }
+        draw_graph(&mut self.ctx, &mut page);
+        match page.save(&options.path, options.format) {
+            Ok(()) => self
+                .ctx
+                .log
+                .info(format!("Exported to '{}'", options.path.display())),
+            Err(e) => self.ctx.log.error(format!(
+                "Failed to export to '{}': {e}",
+                options.path.display()
+            )),
+        }
+    }
+
+    /// Answer the remote requests which need the application: open files,
+    /// render the view seen from the camera of the `view`, and leave captures
+    /// of the window to the end of the frame.
+    #[cfg(feature = "http")]
+    fn answer_remote(&mut self, view: Option<&Camera>) {
+        let Some(server) = &mut self.ctx.remote else {
+            return;
+        };
+        for call in server.take_app_calls() {
+            match &call.command {
+                RemoteCommand::Open { path } => {
+                    let reply = RemoteReply::opening(path);
+                    self.open_file(path.clone());
+                    call.reply(reply);
+                }
+                RemoteCommand::Render {
+                    format: RenderFormat::Png,
+                } => self.remote_captures.push(call),
+                RemoteCommand::Render {
+                    format: RenderFormat::Svg,
+                } => {
+                    let Some(view) = view else {
+                        call.reply(RemoteReply::error(503, "The view is not shown yet"));
+                        continue;
+                    };
+                    self.ctx.force.sync_positions(&mut self.ctx.positions);
+                    let options = ExportOptions {
+                        background: Some(self.ctx.style.sheet().theme.colors().background),
+                        ..ExportOptions::default()
+                    };
+                    let mut page = options.page(view, None);
+                    draw_graph(&mut self.ctx, &mut page);
+                    let mut svg = Vec::new();
+                    call.reply(match page.write(VectorFormat::Svg, &mut svg) {
+                        Ok(()) => RemoteReply::bytes(RenderFormat::Svg.content_type(), svg),
+                        Err(e) => RemoteReply::error(500, format!("Failed to render: {e}")),
+                    });
+                }
+                _ => call.reply(RemoteReply::error(500, "Not answered by the browser")),
+            }
+        }
+    }
+
+    /// Do what the binding of `action` does, once pressed with the cursor at
+    /// `position` for actions triggered by the mouse.
+    fn run_action(&mut self, action: Action, position: Option<[f32; 2]>, camera: Option<&Camera>) {
+        match action {
+            Action::ZoomIn => self.ctx.camera.zoom_at(position, 1.0),
+            Action::ZoomOut => self.ctx.camera.zoom_at(position, -1.0),
+            Action::FitToGraph => self.fit_to_graph(),
+            Action::NextBookmark => bookmarks::step(&mut self.ctx, 1),
+            Action::PreviousBookmark => bookmarks::step(&mut self.ctx, -1),
+            Action::CommandPalette => self.open_palette(),
+            Action::Undo => {
+                self.ctx.undo();
+            }
+            Action::Redo => {
+                self.ctx.redo();
+            }
+            Action::DeleteSelection => self.delete_selection(),
+            Action::Copy => clipboard::copy(&mut self.ctx),
+            Action::Paste => {
+                if let Some(camera) = camera {
+                    let at = camera.screen_to_world(self.ctx.input.cursor());
+                    clipboard::paste(&mut self.ctx, at);
+                }
+            }
+            Action::ExpandSelection => self.expand_selection(),
+            Action::FindPath => {
+                let active = self.ctx.path_finder.is_active();
+                self.ctx.path_finder.set_active(!active);
+            }
+            Action::CollapseSelection => {
+                let nodes: Vec<_> = self.ctx.selection.nodes().collect();
+                explore::collapse(&mut self.ctx, &nodes);
+            }
+            Action::Screenshot => self.screenshot = true,
+            Action::ToggleRecording => {
+                self.recorder = match self.recorder.take() {
+                    Some(recorder) => {
+                        self.ctx.log.info(format!(
+                            "Recorded {} frames to '{}'",
+                            recorder.frames(),
+                            recorder.dir().display()
+                        ));
+                        None
+                    }
+                    None => Some(FrameRecorder::builder(&self.capture_dir).build()),
+                };
+            }
+            // no graph view to act on yet
+            _ => {}
+        }
+    }
+
+    /// Open the command palette, listing the recent files as of now.
+    fn open_palette(&mut self) {
+        let stale: Vec<String> = self
+            .ctx
+            .palette
+            .commands()
+            .filter(|c| c.id.starts_with(OPEN_RECENT_COMMAND))
+            .map(|c| c.id.clone())
+            .collect();
+        for id in stale {
+            self.ctx.palette.unregister(&id);
+        }
+        for path in self.ctx.documents.recent.files() {
+            self.ctx.palette.register(PaletteCommand::app(
+                format!("{OPEN_RECENT_COMMAND}{}", path.display()),
+                format!("Open Recent: {}", path.display()),
+            ));
+        }
+        self.ctx.palette.open();
+    }
+
+    /// Add the panels of plugins enabled since last frame, with a palette
+    /// command toggling each, and remove those of plugins disabled.
+    fn apply_panel_changes(&mut self, window: &GraphWindow) {
+        for change in self.ctx.plugins.take_panel_changes() {
+            match change {
+                PanelChange::Register(panel) => {
+                    let id = panel.id();
+                    if self.panels.ids().any(|panel| panel == id) {
+                        self.ctx
+                            .log
+                            .warn(format!("A panel '{id}' is already registered"));
+                        continue;
+                    }
+                    self.panels.register_boxed(panel);
+                    if let Some(ui) = window.get_ui()
+                        && let Some(open) = ui.state().and_then(|s| s.panel_open(id))
+                    {
+                        self.panels.set_open(id, open);
+                    }
+                    let command = format!("{PANEL_COMMAND}{id}");
+                    if !self.ctx.palette.contains(&command) {
+                        self.ctx
+                            .palette
+                            .register(PaletteCommand::app(command, format!("View: Toggle {id}")));
+                    }
+                }
+                PanelChange::Unregister(id) => {
+                    self.panels.unregister(id);
+                    self.ctx.palette.unregister(&format!("{PANEL_COMMAND}{id}"));
+                }
+            }
+        }
+    }
+
+    /// Run the command `id` of the palette registered by [`register_commands()`].
+    fn run_command(&mut self, id: &str) {
+        if let Some(path) = id.strip_prefix(OPEN_RECENT_COMMAND) {
+            self.open_file(PathBuf::from(path));
+            return;
+        }
+        if let Some(panel) = id.strip_prefix(PANEL_COMMAND) {
+            if self.panels.ids().any(|id| id == panel) {
+                self.panels.toggle(panel);
+            }
+            return;
+        }
+        match id {
+            "file.new" => self.ctx.open_document(Graph::default(), None),
+            "file.generate" => self.generate_dialog = Some(GenerateDialog::new()),
+            "file.save" => {
+                self.save_document();
+            }
+            "file.close" => self.close_document(self.ctx.documents.active()),
+            "file.open_workspace" => {
+                self.workspace_dialog = Some(WorkspaceDialog::new(
+                    WorkspaceAction::Open,
+                    &self.workspace_path,
+                ));
+            }
+            "file.save_workspace" => {
+                self.workspace_dialog = Some(WorkspaceDialog::new(
+                    WorkspaceAction::Save,
+                    &self.workspace_path,
+                ));
+            }
+            "file.export" => self.open_export_dialog(),
+            "view.overlay" => self.show_overlay = !self.show_overlay,
+            "view.3d" => {
+                let enabled = self.ctx.view3d.is_enabled();
+                view3d::set_enabled(&mut self.ctx, !enabled);
+            }
+            _ => self.ctx.log.warn(format!("Unknown command '{id}'")),
+        }
+    }
+
+    /// Remove the selected nodes and edges, as a single undoable step.
+    fn delete_selection(&mut self) {
+        let selection = &self.ctx.selection;
+        let commands: Vec<Command> = selection
+            .edges()
+            .map(|edge| Command::RemoveEdge { edge })
+            .chain(selection.nodes().map(|node| Command::RemoveNode { node }))
+            .collect();
+        if !commands.is_empty() {
+            self.ctx.execute(Command::Batch {
+                label: "Delete Selection".into(),
+                commands,
+            });
+        }
+    }
+
+    /// Fetch the neighbors of the selected nodes if the document is being
+    /// explored, otherwise start exploring from them in a new document.
+    fn expand_selection(&mut self) {
+        let nodes: Vec<_> = self.ctx.selection.nodes().collect();
+        let document = self.ctx.documents.active_document().id();
+        if self
+            .ctx
+            .exploration
+            .as_ref()
+            .is_some_and(|e| e.document() == document)
+        {
+            explore::expand(&mut self.ctx, &nodes);
+        } else {
+            explore::explore(&mut self.ctx, &nodes, DEFAULT_DEPTH, DEFAULT_LIMIT);
+        }
+    }
+
+    /// Open a freshly loaded graph as a new document, logging the outcome.
+    fn finish_open(&mut self, path: &Path, result: Result<Graph, impl std::fmt::Display>) {
+        match result {
+            Ok(graph) => {
+                self.ctx.log.info(format!(
+                    "Opened '{}': {} nodes, {} edges",
+                    path.display(),
+                    graph.node_count(),
+                    graph.edge_count()
+                ));
+                self.ctx.open_document(graph, Some(path.to_owned()));
+                self.ctx.documents.recent.push(path);
+                self.ctx.apply_default_layout();
+                self.fit_to_graph();
+            }
+            Err(e) => {
+                self.ctx
+                    .log
+                    .error(format!("Failed to open '{}': {e}", path.display()));
+                self.ctx.documents.recent.remove(path);
+            }
+        }
+    }
+
+    /// Save the active document to its file, logging the outcome. Returns
+    /// `true` once saved.
+    fn save_document(&mut self) -> bool {
+        match self.ctx.save_document(None) {
+            Ok(path) => {
+                self.ctx.log.info(format!("Saved '{}'", path.display()));
+                true
+            }
+            Err(e) => {
+                self.ctx.log.error(format!("Failed to save: {e}"));
+                false
+            }
+        }
+    }
+
+    /// Close the document at `index`, first asking what to do with its
+    /// unsaved changes, if any.
+    fn close_document(&mut self, index: usize) {
+        if !self.ctx.is_document_dirty(index) {
+            self.ctx.close_document(index);
+            return;
+        }
+        // show the document behind the prompt
+        self.ctx.switch_document(index);
+        self.closing = Some(self.ctx.documents.active_document().id());
+        self.prompting = false;
+    }
+
+    /// Returns `true` while nothing is open: a single untitled document with
+    /// an empty graph, and nothing loading or being imported.
+    fn is_empty_start(&self) -> bool {
+        #[cfg(feature = "arrow")]
+        if self.columnar_dialog.is_some() {
+            return false;
+        }
+        self.ctx.documents.documents().len() == 1
+            && self.ctx.documents.active_document().path().is_none()
+            && self.ctx.graph.is_empty()
+            && !self.ctx.is_busy()
+            && self.csv_dialog.is_none()
+            && self.generate_dialog.is_none()
+    }
+
+    /// The start screen shown while nothing is open, listing the recent files.
+    fn draw_start_screen(&mut self, ui: &Ui) {
+        if !self.is_empty_start() {
+            return;
+        }
+        let [width, height] = ui.io().display_size;
+        let mut open = None;
+        ui.window("##start")
+            .position([width / 2.0, height / 2.0], Condition::Always)
+            .position_pivot([0.5, 0.5])
+            .flags(
+                WindowFlags::NO_DECORATION
+                    | WindowFlags::NO_MOVE
+                    | WindowFlags::NO_SAVED_SETTINGS
+                    | WindowFlags::NO_DOCKING
+                    | WindowFlags::ALWAYS_AUTO_RESIZE
+                    | WindowFlags::NO_BRING_TO_FRONT_ON_FOCUS,
+            )
+            .build(|| {
+                ui.text(tr("Open a graph by dropping its file onto the window."));
+                let recent = self.ctx.documents.recent.files();
+                if recent.is_empty() {
+                    return;
+                }
+                ui.separator();
+                ui.text_disabled(tr("Recent Files"));
+                for (index, path) in recent.iter().enumerate() {
+                    let _id = ui.push_id_usize(index);
+                    let name = path.file_name().map_or_else(
+                        || path.display().to_string(),
+                        |name| name.to_string_lossy().into_owned(),
+                    );
+                    if ui.selectable(name) {
+                        open = Some(path.clone());
+                    }
+                    if let Some(dir) = path.parent() {
+                        ui.same_line();
+                        ui.text_disabled(dir.display().to_string());
+                    }
+                }
+            });
+        if let Some(path) = open {
+            self.open_file(path);
+        }
+    }
+
+    /// Tabs of the open documents, under the main menu bar.
+    fn draw_documents(&mut self, ui: &Ui) {
+        let [width, _] = ui.io().display_size;
+        let active = self.ctx.documents.active_document().id();
+        let mut selected = None;
+        let mut close = None;
+        ui.window("##documents")
+            .position([0.0, ui.frame_height()], Condition::Always)
+            .size([width, 0.0], Condition::Always)
+            .flags(
+                WindowFlags::NO_DECORATION
+                    | WindowFlags::NO_MOVE
+                    | WindowFlags::NO_SAVED_SETTINGS
+                    | WindowFlags::NO_SCROLL_WITH_MOUSE
+                    | WindowFlags::NO_DOCKING,
+            )
+            .build(|| {
+                let Some(_bar) = TabBar::new("##tabs").begin(ui) else {
+                    return;
+                };
+                for (index, document) in self.ctx.documents.documents().iter().enumerate() {
+                    let mut flags = TabItemFlags::empty();
+                    if self.ctx.is_document_dirty(index) {
+                        flags |= TabItemFlags::UNSAVED_DOCUMENT;
+                    }
+                    // the active document changed through a menu or a prompt
+                    if document.id() == active && self.shown_document != active {
+                        flags |= TabItemFlags::SET_SELECTED;
+                    }
+                    let mut open = true;
+                    let label = format!("{}###doc{}", document.title(), document.id());
+                    if TabItem::new(label)
+                        .opened(&mut open)
+                        .flags(flags)
+                        .begin(ui)
+                        .is_some()
+                    {
+                        selected = Some((index, document.id()));
+                    }
+                    if ui.is_item_hovered()
+                        && let Some(path) = document.path()
+                    {
+                        ui.tooltip_text(path.display().to_string());
+                    }
+                    if !open {
+                        close = Some(index);
+                    }
+                }
+            });
+
+        // tabs only show a new selection the frame after it was set
+        if let Some((index, id)) = selected {
+            if self.shown_document != active {
+                if id == active {
+                    self.shown_document = id;
+                }
+            } else if id != active {
+                self.ctx.switch_document(index);
+                self.shown_document = id;
+            }
+        }
+        if let Some(index) = close {
+            self.close_document(index);
+        }
+    }
+
+    /// Hand the settings applied in the Preferences panel to what they concern.
+    fn apply_config_changes(&mut self, window: &GraphWindow) {
+        for change in self.ctx.config.take_changes() {
+            match change {
+                ConfigChange::Backend(backend) => self.ctx.log.info(format!(
+                    "The {} backend will be used from the next start",
+                    backend.label()
+                )),
+                ConfigChange::Vsync(_) => {
+                    let interval = self.ctx.config.get().swap_interval();
+                    if let Err(e) = window.set_swap_interval(interval) {
+                        self.ctx.log.error(format!("Failed to change vsync: {e}"));
+                    }
+                }
+                ConfigChange::UiScale(scale) => {
+                    if let Some(mut ui) = window.get_ui() {
+                        ui.set_ui_scale(scale);
+                    }
+                }
+                ConfigChange::AutosaveInterval(_) => self.last_autosave = Instant::now(),
+                // read whenever a graph is opened
+                ConfigChange::DefaultLayout(_) => {}
+                ConfigChange::MemoryBudget(_) => self.ctx.memory.invalidate(),
+            }
+        }
+    }
+
+    /// Save the workspace to `path`, including the layout of `window`'s ui.
+    fn save_workspace(&mut self, window: &GraphWindow, path: &Path) -> Result<(), SessionError> {
+        let mut session = Session::capture(&mut self.ctx);
+        session.panels = self
+            .panels
+            .ids()
+            .map(|id| (id.to_owned(), self.panels.is_open(id)))
+            .collect();
+        if let Some(mut ui) = window.get_ui() {
+            session.layout = ui.save_layout();
+        }
+        session.export = self.export_settings.clone();
+        session.save(path)
+    }
+
+    /// Replace the workspace by the one saved at `path`, including the layout
+    /// of `window`'s ui.
+    fn open_workspace(&mut self, window: &GraphWindow, path: &Path) -> Result<(), SessionError> {
+        let mut session = Session::load(path)?;
+        for (id, &open) in &session.panels {
+            if self.panels.ids().any(|panel| panel == id) {
+                self.panels.set_open(id, open);
+            }
+        }
+        if !session.layout.is_empty()
+            && let Some(mut ui) = window.get_ui()
+        {
+            ui.load_layout(&std::mem::take(&mut session.layout));
+        }
+        self.export_settings = session.export.take();
+        session.restore(&mut self.ctx);
+        self.closing = None;
+        Ok(())
+    }
+
+    /// Start without plugins, with the default layout and panels, eg. after a
+    /// crash.
+    fn start_safe_mode(&mut self, window: &GraphWindow) {
+        if let Some(mut ui) = window.get_ui() {
+            ui.reset_layout();
+        }
+        self.panels.reset();
+        self.ctx
+            .log
+            .warn("Started in safe mode: plugins are not loaded, and the layout is reset");
+    }
+
+    /// The prompt offering a safe-mode start after the previous run crashed.
+    fn draw_crash_prompt(&mut self, ui: &Ui) {
+        let Some((report, opened)) = self.crashed.as_mut() else {
+            return;
+        };
+        if !*opened {
+            ui.open_popup(popup_title(CRASH_TITLE));
+            *opened = true;
+        }
+
+        let mut safe_mode = None;
+        ui.modal_popup_config(popup_title(CRASH_TITLE))
+            .always_auto_resize(true)
+            .build(|| {
+                ui.text(tr("The previous session crashed. A report was written to"));
+                ui.text(report.display().to_string());
+                ui.text_disabled(tr("Past reports are listed in View > Crash Reports."));
+                ui.separator();
+                if ui.button(tr("Start in Safe Mode")) {
+                    safe_mode = Some(true);
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip_text(tr("Skip plugins, and reset the layout and panels"));
+                }
+                ui.same_line();
+                if ui.button(tr("Start Normally")) {
+                    safe_mode = Some(false);
+                }
+                if safe_mode.is_some() {
+                    ui.close_current_popup();
+                }
+            });
+        match safe_mode {
+            Some(true) => self.safe_mode = true,
+            Some(false) => plugin::discover(&mut self.ctx, self.plugins_dir.clone()),
+            None => return,
+        }
+        self.crashed = None;
+    }
+
+    /// The prompt offering to restore the workspace autosaved by a run which
+    /// did not exit cleanly.
+    fn draw_recovery_prompt(&mut self, ui: &Ui) {
+        // one prompt at a time, the crash one first
+        if self.crashed.is_some() {
+            return;
+        }
+        let Some(opened) = self.recovery.as_mut() else {
+            return;
+        };
+        if !*opened {
+            ui.open_popup(popup_title(RECOVERY_TITLE));
+            *opened = true;
+        }
+
+        let mut open = true;
+        let closed = ui
+            .modal_popup_config(popup_title(RECOVERY_TITLE))
+            .opened(&mut open)
+            .always_auto_resize(true)
+            .build(|| {
+                ui.text(tr("The previous session did not exit cleanly."));
+                ui.text(tr("Restore its autosaved workspace?"));
+                ui.separator();
+                let mut closed = false;
+                if ui.button(tr("Restore")) {
+                    self.workspace = Some((WorkspaceAction::Open, self.autosave_path.clone()));
+                    closed = true;
+                }
+                ui.same_line();
+                if ui.button(tr("Discard")) {
+                    closed = true;
+                }
+                if closed {
+                    ui.close_current_popup();
+                }
+                closed
+            });
+        if !open || closed != Some(false) {
+            self.recovery = None;
+            self.last_autosave = Instant::now();
+        }
+    }
+
+    /// The View menu's list of the languages found, switching to the one picked.
+    fn draw_language_menu(&mut self, ui: &Ui) {
+        let Some(_menu) = ui.begin_menu(tr("Language")) else {
+            return;
+        };
+        let active = self.ctx.locales.active();
+        let mut picked = None;
+        if ui
+            .menu_item_config("English")
+            .selected(active.is_none())
+            .build()
+        {
+            picked = Some(None);
+        }
+        for locale in self.ctx.locales.list() {
+            if ui
+                .menu_item_config(&locale.name)
+                .selected(active == Some(locale.code.as_str()))
+                .build()
+            {
+                picked = Some(Some(locale.code.clone()));
+            }
+        }
+        ui.separator();
+        if ui.menu_item(tr("Reload Languages")) {
+            for error in self.ctx.locales.reload() {
+                self.ctx.log.warn(error);
+            }
+        }
+        if let Some(code) = picked
+            && let Err(e) = self.ctx.locales.select(code.as_deref())
+        {
+            self.ctx.log.error(e);
+        }
+    }
+
+    /// The prompt asking whether to save a document before closing it.
+    fn draw_unsaved_prompt(&mut self, ui: &Ui) {
+        let Some(id) = self.closing else {
+            return;
+        };
+        let Some(index) = self
+            .ctx
+            .documents
+            .documents()
+            .iter()
+            .position(|document| document.id() == id)
+        else {
+            self.closing = None;
+            return;
+        };
+        // popups are opened from the id stack they are drawn in, not the menu's
+        if !self.prompting {
+            ui.open_popup(popup_title(UNSAVED_TITLE));
+            self.prompting = true;
+        }
+        let document = &self.ctx.documents.documents()[index];
+        let title = document.title();
+        let can_save = document
+            .path()
+            .and_then(|path| path.extension())
+            .and_then(|e| e.to_str())
+            .and_then(Format::from_extension)
+            .is_some_and(Format::is_writable);
+
+        let mut open = true;
+        let closed = ui
+            .modal_popup_config(popup_title(UNSAVED_TITLE))
+            .opened(&mut open)
+            .always_auto_resize(true)
+            .build(|| {
+                ui.text(
+                    tr("Save the changes to '{title}' before closing it?")
+                        .replace("{title}", &title),
+                );
+                if !can_save {
+                    ui.text_disabled(tr("The file's format cannot be written"));
+                }
+                ui.separator();
+                let mut closed = false;
+                ui.enabled(can_save, || {
+                    if ui.button(tr("Save")) && self.save_document() {
+                        self.ctx.close_document(index);
+                        closed = true;
+                    }
+                });
+                ui.same_line();
+                if ui.button(tr("Discard")) {
+                    self.ctx.close_document(index);
+                    closed = true;
+                }
+                ui.same_line();
+                if ui.button(tr("Cancel")) {
+                    closed = true;
+                }
+                if closed {
+                    ui.close_current_popup();
+                }
+                closed
+            });
+        if !open || closed != Some(false) {
+            self.closing = None;
+        }
+    }
+}
+
+impl GraphApp for Browser {
+    fn on_event(&mut self, ctx: &mut AppContext, event: &Event) {
+        let capture = ctx
+            .window()
+            .get_ui()
+            .map(|ui| ui.input_capture())
+            .unwrap_or_default();
+        self.ctx.input.handle(event, capture);
+        self.ctx.camera.handle(event, capture);
+    }
+
+    fn on_file_dropped(&mut self, _ctx: &mut AppContext, path: PathBuf) {
+        self.open_file(path);
+    }
+
+    fn update(&mut self, ctx: &mut AppContext, dt: std::time::Duration) {
+        if std::mem::take(&mut self.safe_mode) {
+            self.start_safe_mode(ctx.window());
+        }
+        self.apply_panel_changes(ctx.window());
+        self.apply_config_changes(ctx.window());
+        if let Some((action, path)) = self.workspace.take() {
+            let result = match action {
+                WorkspaceAction::Save => self.save_workspace(ctx.window(), &path),
+                WorkspaceAction::Open => self.open_workspace(ctx.window(), &path),
+            };
+            match result {
+                Ok(()) if path == self.autosave_path => {
+                    self.ctx.log.info("Recovered the autosaved workspace");
+                }
+                Ok(()) => {
+                    let verb = match action {
+                        WorkspaceAction::Save => "Saved",
+                        WorkspaceAction::Open => "Opened",
+                    };
+                    self.ctx
+                        .log
+                        .info(format!("{verb} workspace '{}'", path.display()));
+                    self.workspace_path = path;
+                }
+                Err(e) => self
+                    .ctx
+                    .log
+                    .error(format!("Workspace '{}': {e}", path.display())),
+            }
+        }
+        // the autosave is kept until the user decided whether to recover it
+        if self.recovery.is_none()
+            && let Some(interval) = self.ctx.config.get().autosave_interval()
+            && self.last_autosave.elapsed() >= interval
+        {
+            self.last_autosave = Instant::now();
+            let path = self.autosave_path.clone();
+            if let Err(e) = self.save_workspace(ctx.window(), &path) {
+                self.ctx.log.error(format!("Failed to autosave: {e}"));
+            }
+        }
 
+        let camera = ctx.window().scene().map(|scene| *scene.camera());
+        let events: Vec<_> = self.ctx.input.drain().collect();
+        for event in events {
+            let mode = match event.action {
+                Action::SelectNode => Some(SelectMode::Replace),
+                Action::AddToSelection => Some(SelectMode::Add),
+                Action::ToggleSelection => Some(SelectMode::Toggle),
+                _ => None,
+            };
+            if let Some(mode) = mode
+                && let Some(position) = event.position
+            {
+                match event.state {
+                    ActionState::Pressed => self.selecting = Some((mode, position)),
+                    ActionState::Released => {
+                        if let Some((mode, start)) = self.selecting.take()
+                            && let Some(camera) = &camera
+                        {
+                            self.finish_selection(camera, mode, start, position);
+                        }
+                    }
+                }
+                continue;
+            }
+
+            if event.action == Action::Pan {
+                let cursor = event.position.unwrap_or(self.ctx.input.cursor());
+                // dragging turns the 3D view rather than moving the projection
+                match (event.state, self.ctx.view3d.is_enabled()) {
+                    (ActionState::Pressed, true) => self.ctx.view3d.begin_orbit(cursor),
+                    (ActionState::Pressed, false) => self.ctx.camera.begin_pan(cursor),
+                    (ActionState::Released, _) => {
+                        self.ctx.view3d.end_orbit();
+                        self.ctx.camera.end_pan();
+                    }
+                }
+                continue;
+            }
+
+            if event.state == ActionState::Pressed {
+                self.run_action(event.action, event.position, camera.as_ref());
+            }
+        }
+        // palette commands bound in the keymap, eg. saved scripts
+        let commands: Vec<String> = self.ctx.input.drain_commands().collect();
+        for id in commands {
+            match palette::run(&mut self.ctx, &id) {
+                Some(Picked::Action(action)) => self.run_action(action, None, camera.as_ref()),
+                Some(Picked::Command(id)) => self.run_command(&id),
+                None => {}
+            }
+        }
+        match self.picked.take() {
+            Some(Picked::Action(action)) => self.run_action(action, None, camera.as_ref()),
+            Some(Picked::Command(id)) => self.run_command(&id),
+            None => {}
+        }
+
+        self.ctx.refresh();
+        if let Some(mut ui) = ctx.window().get_ui() {
+            ui.set_clear_color(self.ctx.style.sheet().theme.colors().background);
+            if let Err(e) = self.ctx.locales.update_font(&mut ui) {
+                self.ctx.log.warn(e);
+            }
+        }
+        self.update_hover(ctx, camera.as_ref());
+        if let Some(options) = self.export.take()
+            && let Some(view) = &camera
+        {
+            self.export_image(view, &options);
+        }
+        #[cfg(feature = "http")]
+        self.answer_remote(camera.as_ref());
+        {
+            let _scope = profiler::scope("layout");
+            let graph = self.ctx.filters.graph(&self.ctx.graph);
+            self.ctx.force.step(graph, &mut self.ctx.positions);
+            view3d::update(&mut self.ctx);
+            anim::update(&mut self.ctx, dt);
+        }
+        {
+            let _scope = profiler::scope("minimap");
+            minimap::render(&mut self.ctx, ctx.window());
+        }
+        {
+            let _scope = profiler::scope("matrix");
+            matrix::render(&mut self.ctx, ctx.window());
+        }
+        self.crash.update(&self.ctx);
+    }
+
+    fn needs_redraw(&mut self) -> bool {
+        let shown = (
+            self.ctx.graph.generation(),
+            self.ctx.positions.generation(),
+            self.ctx.selection.generation(),
+            self.ctx.documents.active(),
+            self.ctx.log.len(),
+            self.ctx.hover.shown(&self.ctx.style.sheet().hover),
+            status::toasts(&self.ctx.log).count(),
+        );
+        let changed = std::mem::replace(&mut self.shown, shown) != shown;
+        #[cfg(feature = "http")]
+        if !self.remote_captures.is_empty() {
+            return true;
+        }
+        changed || self.ctx.is_busy() || self.recorder.is_some()
+    }
+
+    fn on_exit(&mut self, window: &GraphWindow) {
+        plugin::shutdown(&mut self.ctx);
+        #[cfg(feature = "sqlite")]
+        self.ctx.close_store();
+        if let Some(mut ui) = window.get_ui()
+            && let Some(state) = ui.state_mut()
+        {
+            self.panels.store(state);
+        }
+        if let Err(e) = self.ctx.style.sheet().save(&self.style_path) {
+            eprintln!("Failed to save style '{}': {e}", self.style_path.display());
+        }
+        // a clean exit leaves nothing to recover
+        if self.recovery.is_none()
+            && let Err(e) = std::fs::remove_file(&self.autosave_path)
+            && e.kind() != std::io::ErrorKind::NotFound
+        {
+            eprintln!(
+                "Failed to remove autosave '{}': {e}",
+                self.autosave_path.display()
+            );
+        }
+    }
+
+    fn post_render(&mut self, ctx: &mut AppContext) {
+        if std::mem::take(&mut self.screenshot) {
+            match ctx.window().save_screenshot(&self.capture_dir) {
+                Ok(path) => self
+                    .ctx
+                    .log
+                    .info(format!("Saved screenshot to '{}'", path.display())),
+                Err(e) => self
+                    .ctx
+                    .log
+                    .error(format!("Failed to save screenshot: {e}")),
+            }
+        }
+
+        #[cfg(feature = "http")]
+        for call in std::mem::take(&mut self.remote_captures) {
+            let reply = ctx
+                .window()
+                .capture_frame()
+                .and_then(|image| capture::encode_png(&image));
+            call.reply(match reply {
+                Ok(png) => RemoteReply::bytes(RenderFormat::Png.content_type(), png),
+                Err(e) => RemoteReply::error(500, format!("Failed to capture the window: {e}")),
+            });
+        }
+
+        if let Some(recorder) = self.recorder.as_mut()
+            && let Err(e) = recorder.record(ctx.window())
+        {
+            self.ctx
+                .log
+                .error(format!("Failed to record frame, stopping recording: {e}"));
+            self.recorder = None;
+        }
+    }
+
+    fn draw_scene(&mut self, scene: &mut Scene) {
+        self.ctx
+            .camera
+            .apply(scene.camera_mut(), self.ctx.input.cursor());
+        let colors = self.ctx.style.sheet().theme.colors();
+
+        if let Some((_, start)) = self.selecting {
+            let end = self.ctx.input.cursor();
+            if is_drag(start, end) {
+                let min = [start[0].min(end[0]), start[1].min(end[1])];
+                let max = [start[0].max(end[0]), start[1].max(end[1])];
+                scene.screen().rect(min, max, colors.marquee_fill);
+                scene
+                    .screen()
+                    .rect_outline(min, max, 1.0, colors.marquee_outline);
+            }
+        }
+
+        let (min, max) = scene.camera().visible_rect();
+        let width = 1.0 / scene.camera().zoom();
+        let world = scene.world();
+
+        let mut x = (min[0] / GRID_SPACING).floor() * GRID_SPACING;
+        while x <= max[0] {
+            world.line([x, min[1]], [x, max[1]], width, colors.grid);
+            x += GRID_SPACING;
+        }
+        let mut y = (min[1] / GRID_SPACING).floor() * GRID_SPACING;
+        while y <= max[1] {
+            world.line([min[0], y], [max[0], y], width, colors.grid);
+            y += GRID_SPACING;
+        }
+
+        // positions are still on the GPU while its layout runs, so the
+        // selection cannot be highlighted until they are read back
+        if let Some(buffers) = self
+            .ctx
+            .force
+            .gpu_buffers(NODE_RADIUS * 2.0, NODE_COLOR, EDGE_COLOR)
+        {
+            scene.draw_buffers(buffers);
+            return;
+        }
+
+        draw_graph(&mut self.ctx, scene);
+    }
+
+    fn draw(&mut self, ui: &mut Ui) {
+        if let Some(_bar) = ui.begin_main_menu_bar() {
+            if let Some(_menu) = ui.begin_menu(tr("File")) {
+                if let Some(_menu) = ui.begin_menu(tr("New")) {
+                    if ui.menu_item(tr("Empty Graph")) {
+                        self.ctx.open_document(Graph::default(), None);
+                    }
+                    if ui.menu_item(tr("Generated Graph...")) {
+                        self.generate_dialog = Some(GenerateDialog::new());
+                    }
+                }
+                let recent = self.ctx.documents.recent.files().to_vec();
+                if let Some(_menu) =
+                    ui.begin_menu_with_enabled(tr("Open Recent"), !recent.is_empty())
+                {
+                    for path in recent {
+                        if ui.menu_item(path.display().to_string()) {
+                            self.open_file(path);
+                        }
+                    }
+                    ui.separator();
+                    if ui.menu_item(tr("Clear Recent")) {
+                        self.ctx.documents.recent.clear();
+                    }
+                }
+                ui.separator();
+                let writable = self
+                    .ctx
+                    .documents
+                    .active_document()
+                    .path()
+                    .and_then(|path| path.extension())
+                    .and_then(|e| e.to_str())
+                    .and_then(Format::from_extension)
+                    .is_some_and(Format::is_writable);
+                if ui.menu_item_config(tr("Save")).enabled(writable).build() {
+                    self.save_document();
+                }
+                if ui.menu_item(tr("Close")) {
+                    self.close_document(self.ctx.documents.active());
+                }
+                ui.separator();
+                if ui.menu_item(tr("Open Workspace...")) {
+                    self.workspace_dialog = Some(WorkspaceDialog::new(
+                        WorkspaceAction::Open,
+                        &self.workspace_path,
+                    ));
+                }
+                if ui.menu_item(tr("Save Workspace...")) {
+                    self.workspace_dialog = Some(WorkspaceDialog::new(
+                        WorkspaceAction::Save,
+                        &self.workspace_path,
+                    ));
+                }
+                ui.separator();
+                if ui.menu_item(tr("Export Image...")) {
+                    self.open_export_dialog();
+                }
+            }
+            if let Some(_menu) = ui.begin_menu(tr("View")) {
+                self.panels.menu_items(ui);
+                ui.separator();
+                ui.menu_item_config(tr("Frame Stats Overlay"))
+                    .build_with_ref(&mut self.show_overlay);
+                let enabled = self.ctx.view3d.is_enabled();
+                if ui.menu_item_config(tr("3D View")).selected(enabled).build() {
+                    view3d::set_enabled(&mut self.ctx, !enabled);
+                }
+                let shortcut = self
+                    .ctx
+                    .input
+                    .map()
+                    .bindings(Action::CommandPalette)
+                    .first()
+                    .map(|binding| binding.to_string())
+                    .unwrap_or_default();
+                if ui
+                    .menu_item_config(tr("Command Palette"))
+                    .shortcut(shortcut)
+                    .build()
+                {
+                    self.open_palette();
+                }
+                ui.separator();
+                let shortcut = self
+                    .ctx
+                    .input
+                    .map()
+                    .bindings(Action::FitToGraph)
+                    .first()
+                    .map(|binding| binding.to_string())
+                    .unwrap_or_default();
+                if ui
+                    .menu_item_config(tr("Fit to Graph"))
+                    .shortcut(shortcut)
+                    .build()
+                {
+                    self.fit_to_graph();
+                }
+                let selected = self.ctx.selection.nodes().next();
+                if ui
+                    .menu_item_config(tr("Center on Selection"))
+                    .enabled(selected.is_some())
+                    .build()
+                    && let Some(node) = selected
+                {
+                    self.ctx.force.sync_positions(&mut self.ctx.positions);
+                    self.ctx.camera.center_on_node(node, &self.ctx.positions);
+                    let color = self.ctx.style.sheet().theme.colors().selected;
+                    self.ctx.animations.flash_node(node, color);
+                }
+                ui.separator();
+                self.draw_language_menu(ui);
+            }
+            if let Some(_menu) = ui.begin_menu(tr("Layout")) {
+                let force = !self.ctx.force.is_paused();
+                if ui
+                    .menu_item_config(tr("Force-Directed"))
+                    .selected(force)
+                    .build()
+                {
+                    self.ctx.force.set_paused(false);
+                    self.ctx.force.reheat();
+                }
+                for index in 0..self.ctx.layouts.len() {
+                    if ui.menu_item(tr(self.ctx.layouts[index].name())) {
+                        self.ctx.apply_layout(index);
+                        self.fit_to_graph();
+                    }
+                }
+                ui.separator();
+                if let Some(_menu) = ui.begin_menu(tr("Arrange Selection")) {
+                    let selected = self.ctx.selection.node_count();
+                    for arrange in Arrange::ALL {
+                        if matches!(arrange, Arrange::SnapToGrid | Arrange::Pin) {
+                            ui.separator();
+                        }
+                        if ui
+                            .menu_item_config(tr(arrange.label()))
+                            .enabled(selected >= arrange.min_nodes())
+                            .build()
+                        {
+                            let status = arrange.run(&mut self.ctx);
+                            self.ctx.log.info(status);
+                        }
+                    }
+                }
+            }
+            ui.separator();
+            scripts::draw_toolbar(ui, &mut self.ctx);
+        }
+
+        self.draw_documents(ui);
+        self.draw_start_screen(ui);
+        self.draw_unsaved_prompt(ui);
+        self.draw_crash_prompt(ui);
+        self.draw_recovery_prompt(ui);
+
+        if self.show_overlay {
+            profiler::draw_overlay(ui);
+        }
+        loader::draw_progress(ui, &self.ctx);
+        hover::draw(ui, &self.ctx);
+        status::draw(ui, &self.ctx);
+
+        self.panels.draw(ui, &mut self.ctx);
+        if let Some(picked) = palette::draw(ui, &mut self.ctx) {
+            self.picked = Some(picked);
+        }
+
+        if let Some(dialog) = self.csv_dialog.as_mut() {
+            match dialog.draw(ui) {
+                ImportOutcome::Open => {}
+                ImportOutcome::Cancelled => self.csv_dialog = None,
+                ImportOutcome::Imported(result) => {
+                    let path = dialog.path().to_owned();
+                    self.csv_dialog = None;
+                    self.finish_open(&path, result);
+                }
+            }
+        }
+
+        #[cfg(feature = "arrow")]
+        if let Some(dialog) = self.columnar_dialog.as_mut() {
+            match dialog.draw(ui) {
+                ColumnarImportOutcome::Open => {}
+                ColumnarImportOutcome::Cancelled => self.columnar_dialog = None,
+                ColumnarImportOutcome::Confirmed(options) => {
+                    let path = dialog.path().to_owned();
+                    self.columnar_dialog = None;
+                    self.ctx.load_document_with(
+                        path,
+                        std::sync::Arc::new(ColumnarLoader::new(options)),
+                    );
+                }
+            }
+        }
+
+        if let Some(dialog) = self.generate_dialog.as_mut() {
+            match dialog.draw(ui) {
+                GenerateOutcome::Open => {}
+                GenerateOutcome::Cancelled => self.generate_dialog = None,
+                GenerateOutcome::Generated(graph) => {
+                    self.generate_dialog = None;
+                    self.ctx.open_document(graph, None);
+                }
+            }
+        }
+        if let Some(dialog) = self.export_dialog.as_mut() {
+            match dialog.draw(ui) {
+                ExportOutcome::Open => {}
+                ExportOutcome::Cancelled => self.export_dialog = None,
+                ExportOutcome::Confirmed(options) => {
+                    self.export_settings = Some(options.clone());
+                    self.export = Some(options);
+                    self.export_dialog = None;
+                }
+            }
+        }
+        if let Some(dialog) = self.workspace_dialog.as_mut() {
+            match dialog.draw(ui) {
+                WorkspaceOutcome::Open => {}
+                WorkspaceOutcome::Cancelled => self.workspace_dialog = None,
+                WorkspaceOutcome::Confirmed(path) => {
+                    self.workspace = Some((dialog.action(), path));
+                    self.workspace_dialog = None;
+                }
+            }
+        }
+
+        ui.show_demo_window(&mut true);
+    }
+}
+
+/// The imgui name of the popup titled `title`, shown translated while its id
+/// stays the same in every language.
+fn popup_title(title: &str) -> String {
+    format!("{}###{title}", tr(title))
+}
+
+/// Register the commands of the browser in the palette of `ctx`, see
+/// [`Browser::run_command()`].
+fn register_commands(ctx: &mut EngineContext, panels: &PanelManager) {
+    let palette = &mut ctx.palette;
+    palette.register(PaletteCommand::app("file.new", "File: New"));
+    palette.register(PaletteCommand::app(
+        "file.generate",
+        "File: New Generated Graph...",
+    ));
+    palette.register(PaletteCommand::app("file.save", "File: Save"));
+    palette.register(PaletteCommand::app("file.close", "File: Close"));
+    palette.register(PaletteCommand::app(
+        "file.open_workspace",
+        "File: Open Workspace...",
+    ));
+    palette.register(PaletteCommand::app(
+        "file.save_workspace",
+        "File: Save Workspace...",
+    ));
+    palette.register(PaletteCommand::app("file.export", "File: Export Image..."));
+    for action in [
+        Action::Undo,
+        Action::Redo,
+        Action::Copy,
+        Action::Paste,
+        Action::DeleteSelection,
+        Action::ExpandSelection,
+        Action::CollapseSelection,
+        Action::FitToGraph,
+        Action::ZoomIn,
+        Action::ZoomOut,
+        Action::NextBookmark,
+        Action::PreviousBookmark,
+        Action::FindPath,
+        Action::Screenshot,
+        Action::ToggleRecording,
+    ] {
+        palette.register(PaletteCommand::action(action));
+    }
+    for id in panels.ids() {
+        palette.register(PaletteCommand::app(
+            format!("{PANEL_COMMAND}{id}"),
+            format!("View: Toggle {id}"),
+        ));
+    }
+    palette.register(PaletteCommand::app(
+        "view.overlay",
+        "View: Toggle Frame Stats Overlay",
+    ));
+    palette.register(PaletteCommand::app("view.3d", "View: Toggle 3D View"));
+    palette.register(PaletteCommand::new(
+        "layout.force",
+        "Layout: Force-Directed",
+        |ctx| {
+            ctx.force.set_paused(false);
+            ctx.force.reheat();
+        },
+    ));
+    for index in 0..ctx.layouts.len() {
+        let name = ctx.layouts[index].name();
+        ctx.palette.register(PaletteCommand::new(
+            format!("layout.{index}"),
+            format!("Layout: {name}"),
+            move |ctx| {
+                ctx.apply_layout(index);
+                let graph = ctx.filters.graph(&ctx.graph);
+                ctx.camera.fit_to_graph(graph, &ctx.positions);
+            },
+        ));
+    }
+    bookmarks::register_commands(ctx);
+    for arrange in Arrange::ALL {
+        ctx.palette.register(PaletteCommand::new(
+            format!("arrange.{}", arrange.label()),
+            format!("Arrange: {}", arrange.label()),
+            move |ctx| {
+                let status = arrange.run(ctx);
+                ctx.log.info(status);
+            },
+        ));
+    }
+    for algorithm in Algorithm::ALL {
+        ctx.palette.register(PaletteCommand::new(
+            format!("algorithm.{}", algorithm.label()),
+            format!("Algorithm: {}", algorithm.label()),
+            move |ctx| {
+                let status = algorithm.run(ctx, None);
+                ctx.log.info(status);
+            },
+        ));
+    }
+}
+
+/// Record the visible graph of `ctx`, with its groups and labels, into `canvas`.
+fn draw_graph(ctx: &mut EngineContext, canvas: &mut impl Canvas) {
+    let camera = *canvas.camera();
+    let width = 1.0 / camera.zoom();
+    let colors = ctx.style.sheet().theme.colors();
+    let graph = ctx.filters.graph(&ctx.graph);
+    ctx.spatial.sync(graph, &ctx.positions);
+    // outer groups first, so inner boxes are drawn over them
+    for (_, (min, max)) in ctx.groups.bounds(graph, &ctx.positions) {
+        let min = [min[0] - GROUP_PADDING, min[1] - GROUP_PADDING];
+        let max = [max[0] + GROUP_PADDING, max[1] + GROUP_PADDING];
+        canvas.world().rect(min, max, colors.group_fill);
+        canvas
+            .world()
+            .rect_outline(min, max, width, colors.group_outline);
+    }
+    // the marker traversing the cheapest path found, under the graph
+    if let Some((from, to, t)) = ctx.path_finder.front()
+        && let (Some(a), Some(b)) = (ctx.positions.get(from), ctx.positions.get(to))
     {
-        let vb = vid.borrow();
-        let gl_attr = vb.gl_attr();
-        gl_attr.set_context_version(4, 0);
-        gl_attr.set_context_profile(GLProfile::Core);
+        let at = [a[0] + (b[0] - a[0]) * t, a[1] + (b[1] - a[1]) * t];
+        canvas
+            .world()
+            .circle(at, PATH_MARKER_RADIUS * width, colors.path);
+    }
+    let (style, force, selection) = (&ctx.style, &ctx.force, &ctx.selection);
+    let (timeline, animations, paths) = (&ctx.timeline, &ctx.animations, &ctx.path_finder);
+    let view3d = &ctx.view3d;
+    let path_color = |path: usize| match path {
+        0 => colors.path,
+        _ => with_alpha(colors.path, ALTERNATIVE_PATH_ALPHA),
+    };
+    canvas.graph().add_graph(
+        graph,
+        &ctx.positions,
+        &ctx.spatial,
+        &ctx.routes,
+        &camera,
+        |node| {
+            let mut look = style.node_style(graph, node);
+            if selection.contains_node(node) {
+                look.outline_width = SELECTED_WIDTH;
+                look.outline_color = colors.selected;
+            } else if paths.source() == Some(node) || paths.target() == Some(node) {
+                look.outline_width = SELECTED_WIDTH;
+                look.outline_color = colors.path;
+            } else if let Some(path) = paths.node_path(node) {
+                look.outline_width = SELECTED_WIDTH;
+                look.outline_color = path_color(path);
+            } else if force.is_pinned(node) {
+                look.outline_width = PINNED_WIDTH;
+                look.outline_color = colors.pinned;
+            }
+            look.color = animations.node_color(node, look.color);
+            // nodes grow and fade in as they appear on the timeline
+            let presence = timeline.node_presence(node);
+            // in 3D, nearer nodes are larger and farther ones fainter
+            look.radius *= presence * view3d.node_scale(node);
+            let alpha = presence * animations.node_opacity(node) * view3d.node_fade(node);
+            look.color = with_alpha(look.color, alpha);
+            look.outline_color = with_alpha(look.outline_color, alpha);
+            look
+        },
+        |edge| {
+            let mut look = match (selection.contains_edge(edge), paths.edge_path(edge)) {
+                (true, _) => EdgeStyle {
+                    width: style.edge_style(graph, edge).width.max(SELECTED_WIDTH),
+                    color: colors.selected,
+                },
+                (false, Some(path)) => EdgeStyle {
+                    width: style.edge_style(graph, edge).width.max(SELECTED_WIDTH),
+                    color: path_color(path),
+                },
+                (false, None) => style.edge_style(graph, edge),
+            };
+            let (source, target) = graph.endpoints(edge).unwrap();
+            let alpha = timeline.edge_presence(edge) * view3d.edge_fade(source, target);
+            look.color = with_alpha(look.color, alpha);
+            look
+        },
+    );
+
+    // labels of selected nodes stay at any zoom, the rest fade out with it
+    let lod = camera.level_of_detail();
+    let labels = canvas.labels();
+    let mut add_label = |node, anchor| {
+        let selected = selection.contains_node(node);
+        let Some(text) = style.node_label(graph, node, selected) else {
+            return;
+        };
+        let alpha = if selected { 1.0 } else { lod.label_alpha };
+        labels.push(Label {
+            anchor,
+            clearance: style.node_style(graph, node).radius,
+            priority: match selected {
+                true => f32::INFINITY,
+                false => graph.degree(node) as f32,
+            },
+            color: with_alpha(colors.label, alpha),
+            halo: Some(with_alpha(colors.label_halo, alpha)),
+            text,
+        });
+    };
+    if lod.labels {
+        let (min, max) = camera.visible_rect();
+        ctx.spatial.nodes_in(min, max, &mut add_label);
+    } else {
+        for node in selection.nodes() {
+            if let Some(anchor) = ctx.positions.get(node) {
+                add_label(node, anchor);
+            }
+        }
+    }
+}
+
+/// The element drawn under `point` in screen coordinates, for nodes at most
+/// `radius` large in world units, within [`PICK_TOLERANCE`] of it.
+fn pick(picker: &Picker, camera: &Camera, radius: f32, point: [f32; 2]) -> Option<Hit> {
+    let tolerance = PICK_TOLERANCE / camera.zoom();
+    picker.pick(
+        camera.screen_to_world(point),
+        radius + tolerance,
+        SELECTED_WIDTH / camera.zoom() + tolerance,
+    )
+}
+
+/// `color` with its opacity scaled by `alpha`.
+fn with_alpha(color: [f32; 4], alpha: f32) -> [f32; 4] {
+    [color[0], color[1], color[2], color[3] * alpha]
+}
+
+/// Returns `true` if the cursor moved far enough from `start` to `end` to
+/// select a rectangle rather than click.
+fn is_drag(start: [f32; 2], end: [f32; 2]) -> bool {
+    (end[0] - start[0]).abs().max((end[1] - start[1]).abs()) > DRAG_THRESHOLD
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().is_some_and(|arg| arg == "--headless") {
+        return headless::run(&args[1..]);
     }
 
+    let ui_state = UiStateManager::new("graph-browser").unwrap();
+    let config = ConfigManager::load(ui_state.dir().join(CONFIG_FILE));
+    let backend = config.get().backend;
+    if !backend.is_available() {
+        eprintln!(
+            "The {} backend is not available yet, using OpenGL",
+            backend.label()
+        );
+    }
+    // OpenGL is the only backend with a renderer so far
     let mut window = GraphWindow::builder("Graph Browser", 480, 270)
         .position_centered()
         .resizable()
+        .swap_interval(config.get().swap_interval())
         .opengl()
         .build()
         .unwrap();
 
-    // TODO: Why does this work here but not in GraphWindowBuilder::build() which happens right before this?
-    let gl_context = window.gl_create_context().unwrap();
-    window.gl_make_current(&gl_context).unwrap();
-    vid.borrow().gl_set_swap_interval(1).unwrap();
+    let input = InputMapper::from_file(ui_state.dir().join(KEYMAP_FILE));
+    let style_path = ui_state.dir().join(STYLE_FILE);
+    let capture_dir = ui_state.dir().join(CAPTURE_DIR);
+    #[cfg(feature = "neo4j")]
+    let endpoints_path = ui_state.dir().join(ENDPOINTS_FILE);
+    let recent = RecentFiles::load_or_default(ui_state.dir().join(RECENT_FILE));
+    let workspace_path = ui_state.dir().join(WORKSPACE_FILE);
+    let autosave_path = ui_state.dir().join(AUTOSAVE_FILE);
+    let plugins_dir = ui_state.dir().join(PLUGINS_DIR);
+    let scripts_dir = ui_state.dir().join(SCRIPTS_DIR);
+    let locales_dir = ui_state.dir().join(LOCALES_DIR);
+    let crash_dir = ui_state.dir().join(CRASH_DIR);
+    let crash = CrashReporter::install(&crash_dir);
+    let crashed = crash::take_pending(&crash_dir);
+    let safe_mode = args.iter().any(|arg| arg == "--safe-mode");
+    // only a run which did not exit cleanly leaves its autosave behind
+    let recovery = autosave_path.exists().then_some(false);
 
     window
         .new_ui()
         .platform("imgui_impl_sdl3")
-        .renderer("imgui_impl_opengl3")
+        .state(ui_state)
         .build();
+    if let Some(mut ui) = window.get_ui() {
+        ui.set_ui_scale(config.get().ui_scale);
+    }
+    if let Err(e) = window.new_scene() {
+        eprintln!("Failed to create the scene, only the ui will be drawn: {e}");
+    }
 
-    'main: loop {
-        for event in window.poll_events() {
-            if let Event::Quit { .. } = event {
-                break 'main;
-            }
-        }
+    let mut ctx = EngineContext::new(input);
+    ctx.config = config;
+    ctx.style = Style::new(StyleSheet::load_or_default(&style_path));
+    ctx.documents.recent = recent;
+    #[cfg(feature = "teal")]
+    {
+        ctx.script_runtime = Some(Box::new(TealRuntime::new()));
+    }
+    let gl = window.get_ui().and_then(|mut ui| ui.renderer().gl());
+    if let Some(gl) = gl
+        && let Err(e) = ctx.force.enable_gpu(gl)
+    {
+        ctx.log
+            .info(format!("Using the CPU for large layouts: {e}"));
+    }
 
-        let mut ui_frame = window.ui_frame_begin();
-        {
-            let gui = ui_frame.get();
+    let mut panels = PanelManager::new();
+    panels.register(LogPanel::new());
+    panels.register(KeyBindingsPanel);
+    panels.register(PreferencesPanel::new());
+    panels.register(ProfilerPanel::new());
+    panels.register(ForceLayoutPanel);
+    panels.register(LayoutPanel::new());
+    panels.register(MinimapPanel);
+    panels.register(MatrixPanel::new());
+    panels.register(StylePanel);
+    panels.register(SearchPanel::new());
+    panels.register(FilterPanel);
+    panels.register(GroupPanel);
+    panels.register(TimelinePanel);
+    panels.register(AlgorithmsPanel::new());
+    panels.register(PathFinderPanel);
+    panels.register(MetricsPanel::new());
+    panels.register(StatisticsPanel::new());
+    panels.register(JobsPanel);
+    panels.register(DiffPanel::new());
+    panels.register(ExplorePanel::new());
+    panels.register(InspectorPanel::new());
+    panels.register(TablePanel::new());
+    panels.register(BookmarksPanel::new());
+    panels.register(HistoryPanel);
+    panels.register(ScriptConsolePanel::new());
+    panels.register(ScriptsPanel::new());
+    panels.register(PluginManagerPanel);
+    panels.register(CrashReportsPanel::new(crash_dir));
+    panels.register(MemoryPanel);
+    panels.register(LivePanel::new());
+    #[cfg(feature = "http")]
+    panels.register(RemotePanel::new());
+    #[cfg(feature = "neo4j")]
+    panels.register(Neo4jPanel::new(endpoints_path));
+    #[cfg(feature = "sparql")]
+    panels.register(SparqlPanel::new());
+    #[cfg(feature = "sqlite")]
+    panels.register(StorePanel::new());
+    if let Some(ui) = window.get_ui()
+        && let Some(state) = ui.state()
+    {
+        panels.restore(state);
+    }
+    register_commands(&mut ctx, &panels);
+    scripts::discover(&mut ctx, scripts_dir);
+    i18n::discover(&mut ctx, locales_dir);
+    // after a crash, plugins wait for the user to decide on safe mode
+    if !safe_mode && crashed.is_none() {
+        plugin::discover(&mut ctx, plugins_dir.clone());
+    }
 
-            gui.show_demo_window(&mut true);
-        }
-        ui_frame.end();
+    let mut browser = Browser {
+        ctx,
+        panels,
+        show_overlay: false,
+        csv_dialog: None,
+        #[cfg(feature = "arrow")]
+        columnar_dialog: None,
+        generate_dialog: None,
+        selecting: None,
+        shown_document: 0,
+        closing: None,
+        prompting: false,
+        workspace_dialog: None,
+        export_dialog: None,
+        export: None,
+        export_settings: None,
+        picked: None,
+        workspace: None,
+        workspace_path,
+        recovery,
+        autosave_path,
+        last_autosave: Instant::now(),
+        crash,
+        crashed: crashed.filter(|_| !safe_mode).map(|report| (report, false)),
+        safe_mode,
+        plugins_dir,
 
-        window.gl_swap_window();
+        style_path,
+        capture_dir,
+        screenshot: false,
+        #[cfg(feature = "http")]
+        remote_captures: Vec::new(),
+        recorder: None,
+        shown: Default::default(),
+    };
+    // files opened through the os, eg. by a file association
+    for path in args.iter().filter(|arg| !arg.starts_with("--")) {
+        match std::path::absolute(path) {
+            Ok(path) => browser.open_file(path),
+            Err(e) => browser
+                .ctx
+                .log
+                .error(format!("Failed to open '{path}': {e}")),
+        }
     }
+    window.run(browser);
 
     SDL.close();
+    ExitCode::SUCCESS
 }
@@ -1,2 +1,3 @@
 pub mod renderer;
+pub mod time;
 pub mod util;
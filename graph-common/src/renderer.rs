@@ -4,7 +4,7 @@
 //! [`imgui`] modules (and more), it is idiomatic for dependent crates within
 //! the graph workspace to include the following in their `lib.rs`/`main.rs`:
 //!
-//! ```rust
+//! ```ignore
 //! use common::renderer::imgui;
 //! use common::renderer::sdl3;
 //! ```
@@ -14,6 +14,9 @@
 
 pub use sdl3;
 
+pub mod target;
+pub mod texture;
+
 use parking_lot::RwLock;
 
 use std::cell::{Cell, RefCell};
@@ -78,7 +81,7 @@ pub mod imgui {
 ///   use the [`SdlContext::close()`] function to drop each of the SDL subsystems.
 ///
 /// Example:
-/// ```rust
+/// ```ignore
 /// // In any crate depending on `common`:
 /// use common::renderer::SDL;
 /// // ...
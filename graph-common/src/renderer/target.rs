@@ -0,0 +1,253 @@
+//! # Graph's Offscreen Render Targets
+//!
+//! A [`RenderTarget`] is a framebuffer object with an RGBA8 color texture and a
+//! depth/stencil buffer attached. Scene rendering can draw into it instead of
+//! the window, and the result can then be:
+//! - shown in imgui panels through [`RenderTarget::id()`] (eg. a minimap)
+//! - read back with [`RenderTarget::read_pixels()`] and saved to disk (eg. thumbnails)
+//!
+//! ```ignore
+//! let mut target = window.create_render_target(256, 256)?;
+//!
+//! {
+//!     let _bound = target.bind();
+//!     // draw the scene; the viewport is already set to the target's size
+//! }
+//!
+//! ui.image(target.id(), target.texture().size_f32()).build();
+//! target.save("thumbnail.png")?;
+//! ```
+
+use image::RgbaImage;
+
+use std::path::Path;
+use std::rc::Rc;
+
+use super::imgui::TextureId;
+use super::imgui::renderers::glow::TextureMap;
+use super::imgui::renderers::glow::inner::{self as glow, Context, HasContext, PixelPackData};
+use super::texture::{Texture2d, TextureError, TextureFilter};
+
+/// An offscreen framebuffer with a color texture and depth/stencil buffer.
+///
+/// The GL objects are deleted when this object is dropped, so it must not
+/// outlive the GL context it was created with.
+pub struct RenderTarget {
+    fbo: glow::Framebuffer,
+    depth: glow::Renderbuffer,
+    color: Texture2d,
+}
+
+/// RAII guard for a bound [`RenderTarget`].
+///
+/// Created via [`RenderTarget::bind()`]. Restores the previously bound
+/// framebuffer and viewport when dropped.
+#[must_use = "The render target is unbound as soon as the guard is dropped"]
+pub struct BoundTarget<'a> {
+    target: &'a RenderTarget,
+    previous_fbo: Option<glow::Framebuffer>,
+    previous_viewport: [i32; 4],
+}
+
+/// Read `width` x `height` pixels from the currently bound framebuffer, starting
+/// at its bottom-left corner.
+///
+/// GL's origin is the bottom-left, so rows are flipped to produce a top-down image.
+pub fn read_framebuffer(gl: &Context, width: u32, height: u32) -> RgbaImage {
+    let mut pixels = vec![0u8; width as usize * height as usize * 4];
+    unsafe {
+        gl.pixel_store_i32(glow::PACK_ALIGNMENT, 1);
+        gl.read_pixels(
+            0,
+            0,
+            width as _,
+            height as _,
+            glow::RGBA,
+            glow::UNSIGNED_BYTE,
+            PixelPackData::Slice(&mut pixels),
+        );
+    }
+
+    let mut image = RgbaImage::from_raw(width, height, pixels)
+        .expect("Pixel buffer was allocated to the image's exact size");
+    image::imageops::flip_vertical_in_place(&mut image);
+    image
+}
+
+impl RenderTarget {
+    /// Create a render target of `width` x `height` pixels.
+    ///
+    /// Errors if the GL driver fails to create any of the attachments, or if the
+    /// resulting framebuffer is incomplete.
+    pub fn new(
+        gl: &Rc<Context>,
+        textures: &mut impl TextureMap,
+        width: u32,
+        height: u32,
+    ) -> Result<Self, TextureError> {
+        let color = Texture2d::empty(gl, textures, width, height, TextureFilter::Linear)?;
+
+        unsafe {
+            let previous = gl.get_parameter_framebuffer(glow::FRAMEBUFFER_BINDING);
+
+            let fbo = gl.create_framebuffer().map_err(TextureError::Gl)?;
+            let depth = match gl.create_renderbuffer() {
+                Ok(depth) => depth,
+                Err(e) => {
+                    gl.delete_framebuffer(fbo);
+                    return Err(TextureError::Gl(e));
+                }
+            };
+
+            gl.bind_renderbuffer(glow::RENDERBUFFER, Some(depth));
+            gl.renderbuffer_storage(
+                glow::RENDERBUFFER,
+                glow::DEPTH24_STENCIL8,
+                width as _,
+                height as _,
+            );
+            gl.bind_renderbuffer(glow::RENDERBUFFER, None);
+
+            gl.bind_framebuffer(glow::FRAMEBUFFER, Some(fbo));
+            gl.framebuffer_texture_2d(
+                glow::FRAMEBUFFER,
+                glow::COLOR_ATTACHMENT0,
+                glow::TEXTURE_2D,
+                Some(color.raw()),
+                0,
+            );
+            gl.framebuffer_renderbuffer(
+                glow::FRAMEBUFFER,
+                glow::DEPTH_STENCIL_ATTACHMENT,
+                glow::RENDERBUFFER,
+                Some(depth),
+            );
+            let status = gl.check_framebuffer_status(glow::FRAMEBUFFER);
+            gl.bind_framebuffer(glow::FRAMEBUFFER, previous);
+
+            let target = Self { fbo, depth, color };
+            if status != glow::FRAMEBUFFER_COMPLETE {
+                return Err(TextureError::Gl(format!(
+                    "Framebuffer is incomplete (status {status:#x})"
+                )));
+            }
+            Ok(target)
+        }
+    }
+
+    /// Bind the target for drawing, setting the viewport to cover it.
+    ///
+    /// The previous framebuffer and viewport are restored when the returned
+    /// guard is dropped.
+    pub fn bind(&self) -> BoundTarget<'_> {
+        let gl = self.color.gl();
+        let mut previous_viewport = [0; 4];
+        let previous_fbo = unsafe {
+            gl.get_parameter_i32_slice(glow::VIEWPORT, &mut previous_viewport);
+            let previous = gl.get_parameter_framebuffer(glow::FRAMEBUFFER_BINDING);
+            gl.bind_framebuffer(glow::FRAMEBUFFER, Some(self.fbo));
+            gl.viewport(0, 0, self.width() as _, self.height() as _);
+            previous
+        };
+
+        BoundTarget {
+            target: self,
+            previous_fbo,
+            previous_viewport,
+        }
+    }
+
+    /// Recreate the target's attachments at a new size.
+    ///
+    /// Does nothing if the size is unchanged. The target's [`Self::id()`] may change.
+    pub fn resize(
+        &mut self,
+        textures: &mut impl TextureMap,
+        width: u32,
+        height: u32,
+    ) -> Result<(), TextureError> {
+        if (width, height) == (self.width(), self.height()) {
+            return Ok(());
+        }
+        let gl = self.color.gl().clone();
+        *self = Self::new(&gl, textures, width, height)?;
+        Ok(())
+    }
+
+    /// Read the target's contents back from the GPU.
+    pub fn read_pixels(&self) -> RgbaImage {
+        let bound = self.bind();
+        let image = read_framebuffer(self.color.gl(), self.width(), self.height());
+        drop(bound);
+        image
+    }
+
+    /// Read the target's contents back from the GPU and save them as an image,
+    /// with the format chosen from the file extension (eg. `.png`).
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), TextureError> {
+        Ok(self.read_pixels().save(path)?)
+    }
+
+    /// The color texture the target renders into.
+    pub fn texture(&self) -> &Texture2d {
+        &self.color
+    }
+
+    /// The imgui texture id of the color texture, for use with imgui's image widgets.
+    pub fn id(&self) -> TextureId {
+        self.color.id()
+    }
+
+    /// The underlying GL framebuffer.
+    pub fn raw(&self) -> glow::Framebuffer {
+        self.fbo
+    }
+
+    /// Width of the target in pixels.
+    pub fn width(&self) -> u32 {
+        self.color.width()
+    }
+
+    /// Height of the target in pixels.
+    pub fn height(&self) -> u32 {
+        self.color.height()
+    }
+}
+
+impl BoundTarget<'_> {
+    /// The bound render target.
+    pub fn target(&self) -> &RenderTarget {
+        self.target
+    }
+
+    /// Clear the target's color to `color` (RGBA, `0.0..=1.0`), and its depth
+    /// and stencil buffers.
+    pub fn clear(&self, color: [f32; 4]) {
+        let gl = self.target.color.gl();
+        unsafe {
+            gl.clear_color(color[0], color[1], color[2], color[3]);
+            gl.clear(glow::COLOR_BUFFER_BIT | glow::DEPTH_BUFFER_BIT | glow::STENCIL_BUFFER_BIT);
+        }
+    }
+}
+
+impl std::ops::Drop for BoundTarget<'_> {
+    fn drop(&mut self) {
+        let gl = self.target.color.gl();
+        let [x, y, width, height] = self.previous_viewport;
+        unsafe {
+            gl.bind_framebuffer(glow::FRAMEBUFFER, self.previous_fbo);
+            gl.viewport(x, y, width, height);
+        }
+    }
+}
+
+impl std::ops::Drop for RenderTarget {
+    fn drop(&mut self) {
+        let gl = self.color.gl();
+        unsafe {
+            gl.delete_framebuffer(self.fbo);
+            gl.delete_renderbuffer(self.depth);
+        }
+    }
+}
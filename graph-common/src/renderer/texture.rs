@@ -0,0 +1,348 @@
+//! # Graph's Texture Helpers
+//!
+//! Image decoding (PNG/JPEG, via the [`image`] crate) and GPU texture upload
+//! for the glow backend.
+//!
+//! A [`Texture2d`] owns a single GL texture and is registered with imgui's
+//! texture map on creation, so the same texture can be:
+//! - drawn in imgui panels through [`Texture2d::id()`] (eg. `ui.image(...)`)
+//! - bound directly by scene rendering through [`Texture2d::raw()`]
+//!
+//! ```ignore
+//! use common::renderer::texture::Texture2d;
+//!
+//! // `renderer` is the imgui_glow_renderer::AutoRenderer of the window's ui
+//! let gl = renderer.gl_context().clone();
+//! let icon = Texture2d::load(&gl, renderer.texture_map_mut(), "icons/node.png")?;
+//!
+//! ui.image(icon.id(), icon.size_f32()).build();
+//! ```
+
+pub use image;
+
+use image::RgbaImage;
+
+use std::path::Path;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use super::imgui::TextureId;
+use super::imgui::renderers::glow::TextureMap;
+use super::imgui::renderers::glow::inner::{self as glow, Context, HasContext, PixelUnpackData};
+
+/// Bytes of GPU memory held by the live [`Texture2d`]s, see [`allocated_bytes()`].
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+/// Sampling filter used when a texture is minified or magnified.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum TextureFilter {
+    /// Nearest-neighbour sampling, for pixel-art icons.
+    Nearest,
+    /// Bilinear sampling.
+    #[default]
+    Linear,
+    /// Trilinear sampling, generating mipmaps on upload.
+    Mipmapped,
+}
+
+/// Errors which can occur while loading or uploading a [`Texture2d`].
+#[derive(Debug)]
+pub enum TextureError {
+    /// The image could not be read or decoded.
+    Decode(image::ImageError),
+    /// The GL driver failed to allocate a texture object.
+    Gl(String),
+    /// The renderer's texture map refused to register the texture.
+    Unregistered,
+    /// The rendering backend does not support textures.
+    Unsupported,
+    /// The pixel buffer does not match the requested dimensions.
+    SizeMismatch { expected: usize, actual: usize },
+}
+
+/// An RGBA8 2D texture living on the GPU, registered with imgui.
+///
+/// The GL texture is deleted when this object is dropped, so it must not
+/// outlive the GL context it was created with.
+pub struct Texture2d {
+    gl: Rc<Context>,
+    raw: glow::Texture,
+    id: TextureId,
+    width: u32,
+    height: u32,
+    /// GPU memory the texture takes, mipmaps included.
+    bytes: usize,
+}
+
+/// Bytes of GPU memory held by all live textures, estimated from their sizes.
+pub fn allocated_bytes() -> usize {
+    ALLOCATED.load(Ordering::Relaxed)
+}
+
+/// Decode PNG or JPEG bytes into an RGBA8 image.
+///
+/// The format is guessed from the data itself.
+pub fn decode_image(bytes: &[u8]) -> Result<RgbaImage, TextureError> {
+    Ok(image::load_from_memory(bytes)?.into_rgba8())
+}
+
+/// Read and decode a PNG or JPEG file into an RGBA8 image.
+///
+/// The format is guessed from the file extension, falling back to the file contents.
+pub fn load_image(path: impl AsRef<Path>) -> Result<RgbaImage, TextureError> {
+    let reader = image::ImageReader::open(path)
+        .map_err(image::ImageError::IoError)?
+        .with_guessed_format()
+        .map_err(image::ImageError::IoError)?;
+    Ok(reader.decode()?.into_rgba8())
+}
+
+impl Texture2d {
+    /// Upload raw, tightly packed RGBA8 `pixels` as a new texture.
+    ///
+    /// Errors if `pixels` is not exactly `width * height * 4` bytes, or if the GL
+    /// driver or texture map fail to create the texture.
+    pub fn from_rgba(
+        gl: &Rc<Context>,
+        textures: &mut impl TextureMap,
+        width: u32,
+        height: u32,
+        pixels: &[u8],
+        filter: TextureFilter,
+    ) -> Result<Self, TextureError> {
+        check_size(width, height, pixels)?;
+        Self::allocate(gl, textures, width, height, Some(pixels), filter)
+    }
+
+    /// Allocate a new texture of `width` x `height` with undefined contents.
+    ///
+    /// Useful as a color attachment for offscreen rendering (see
+    /// [`RenderTarget`](super::target::RenderTarget)).
+    pub fn empty(
+        gl: &Rc<Context>,
+        textures: &mut impl TextureMap,
+        width: u32,
+        height: u32,
+        filter: TextureFilter,
+    ) -> Result<Self, TextureError> {
+        Self::allocate(gl, textures, width, height, None, filter)
+    }
+
+    /// Create, configure, and register a texture, uploading `pixels` if given.
+    fn allocate(
+        gl: &Rc<Context>,
+        textures: &mut impl TextureMap,
+        width: u32,
+        height: u32,
+        pixels: Option<&[u8]>,
+        filter: TextureFilter,
+    ) -> Result<Self, TextureError> {
+        let raw = unsafe {
+            let raw = gl.create_texture().map_err(TextureError::Gl)?;
+            gl.bind_texture(glow::TEXTURE_2D, Some(raw));
+
+            let (min, mag) = match filter {
+                TextureFilter::Nearest => (glow::NEAREST, glow::NEAREST),
+                TextureFilter::Linear => (glow::LINEAR, glow::LINEAR),
+                TextureFilter::Mipmapped => (glow::LINEAR_MIPMAP_LINEAR, glow::LINEAR),
+            };
+            gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_MIN_FILTER, min as _);
+            gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_MAG_FILTER, mag as _);
+            gl.tex_parameter_i32(
+                glow::TEXTURE_2D,
+                glow::TEXTURE_WRAP_S,
+                glow::CLAMP_TO_EDGE as _,
+            );
+            gl.tex_parameter_i32(
+                glow::TEXTURE_2D,
+                glow::TEXTURE_WRAP_T,
+                glow::CLAMP_TO_EDGE as _,
+            );
+
+            gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 1);
+            gl.tex_image_2d(
+                glow::TEXTURE_2D,
+                0,
+                glow::RGBA8 as _,
+                width as _,
+                height as _,
+                0,
+                glow::RGBA,
+                glow::UNSIGNED_BYTE,
+                pixels,
+            );
+
+            if filter == TextureFilter::Mipmapped && pixels.is_some() {
+                gl.generate_mipmap(glow::TEXTURE_2D);
+            }
+
+            gl.bind_texture(glow::TEXTURE_2D, None);
+            raw
+        };
+
+        let Some(id) = textures.register(raw) else {
+            unsafe { gl.delete_texture(raw) };
+            return Err(TextureError::Unregistered);
+        };
+
+        // mipmaps add a third of the base level
+        let mut bytes = width as usize * height as usize * 4;
+        if filter == TextureFilter::Mipmapped {
+            bytes += bytes / 3;
+        }
+        ALLOCATED.fetch_add(bytes, Ordering::Relaxed);
+        Ok(Self {
+            gl: gl.clone(),
+            raw,
+            id,
+            width,
+            height,
+            bytes,
+        })
+    }
+
+    /// Upload a decoded image as a new texture.
+    pub fn from_image(
+        gl: &Rc<Context>,
+        textures: &mut impl TextureMap,
+        image: &RgbaImage,
+        filter: TextureFilter,
+    ) -> Result<Self, TextureError> {
+        Self::from_rgba(
+            gl,
+            textures,
+            image.width(),
+            image.height(),
+            image.as_raw(),
+            filter,
+        )
+    }
+
+    /// Decode PNG or JPEG bytes and upload them as a new texture.
+    ///
+    /// Useful for icons embedded with [`include_bytes!`].
+    pub fn decode(
+        gl: &Rc<Context>,
+        textures: &mut impl TextureMap,
+        bytes: &[u8],
+    ) -> Result<Self, TextureError> {
+        Self::from_image(
+            gl,
+            textures,
+            &decode_image(bytes)?,
+            TextureFilter::default(),
+        )
+    }
+
+    /// Read and decode a PNG or JPEG file and upload it as a new texture.
+    pub fn load(
+        gl: &Rc<Context>,
+        textures: &mut impl TextureMap,
+        path: impl AsRef<Path>,
+    ) -> Result<Self, TextureError> {
+        Self::from_image(gl, textures, &load_image(path)?, TextureFilter::default())
+    }
+
+    /// Replace the contents of the texture with new RGBA8 `pixels` of the same size.
+    pub fn update(&mut self, pixels: &[u8]) -> Result<(), TextureError> {
+        check_size(self.width, self.height, pixels)?;
+
+        unsafe {
+            self.gl.bind_texture(glow::TEXTURE_2D, Some(self.raw));
+            self.gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 1);
+            self.gl.tex_sub_image_2d(
+                glow::TEXTURE_2D,
+                0,
+                0,
+                0,
+                self.width as _,
+                self.height as _,
+                glow::RGBA,
+                glow::UNSIGNED_BYTE,
+                PixelUnpackData::Slice(pixels),
+            );
+            self.gl.bind_texture(glow::TEXTURE_2D, None);
+        }
+
+        Ok(())
+    }
+
+    /// The imgui texture id, for use with imgui's image widgets and draw lists.
+    pub fn id(&self) -> TextureId {
+        self.id
+    }
+
+    /// The underlying GL texture, for binding in scene rendering.
+    pub fn raw(&self) -> glow::Texture {
+        self.raw
+    }
+
+    /// Width of the texture in pixels.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Height of the texture in pixels.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The GL context the texture was created with.
+    pub fn gl(&self) -> &Rc<Context> {
+        &self.gl
+    }
+
+    /// Size of the texture in pixels, as expected by imgui widgets.
+    pub fn size_f32(&self) -> [f32; 2] {
+        [self.width as f32, self.height as f32]
+    }
+}
+
+/// Ensure `pixels` holds exactly one RGBA8 texel per pixel.
+fn check_size(width: u32, height: u32, pixels: &[u8]) -> Result<(), TextureError> {
+    let expected = width as usize * height as usize * 4;
+    if pixels.len() != expected {
+        return Err(TextureError::SizeMismatch {
+            expected,
+            actual: pixels.len(),
+        });
+    }
+    Ok(())
+}
+
+impl std::ops::Drop for Texture2d {
+    fn drop(&mut self) {
+        unsafe { self.gl.delete_texture(self.raw) };
+        ALLOCATED.fetch_sub(self.bytes, Ordering::Relaxed);
+    }
+}
+
+impl From<image::ImageError> for TextureError {
+    fn from(value: image::ImageError) -> Self {
+        Self::Decode(value)
+    }
+}
+
+impl std::fmt::Display for TextureError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Decode(e) => write!(f, "Failed to decode image: {e}"),
+            Self::Gl(e) => write!(f, "Failed to create GL texture: {e}"),
+            Self::Unregistered => write!(f, "Texture map refused to register the texture"),
+            Self::Unsupported => write!(f, "Rendering backend does not support textures"),
+            Self::SizeMismatch { expected, actual } => write!(
+                f,
+                "Expected {expected} bytes of RGBA8 pixel data but got {actual}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TextureError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Decode(e) => Some(e),
+            _ => None,
+        }
+    }
+}
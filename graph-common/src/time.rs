@@ -0,0 +1,375 @@
+//! # Graph's Frame Timing Utilities
+//!
+//! The main loop of every graph application is driven by a [`FrameClock`].
+//! Calling [`FrameClock::tick()`] once at the start of each frame provides:
+//! - the delta time since the previous frame
+//! - a smoothed frames-per-second estimate
+//! - fixed-timestep accumulation for deterministic simulation (eg. layouts)
+//! - an optional frame limiter, configured independently of vsync
+//!
+//! ```ignore
+//! use common::time::FrameClock;
+//!
+//! let mut clock = FrameClock::builder()
+//!     .fixed_timestep(60.0)
+//!     .frame_limit(144.0)
+//!     .build();
+//!
+//! loop {
+//!     let dt = clock.tick();
+//!
+//!     for step in clock.fixed_steps() {
+//!         // advance the simulation by `step`
+//!     }
+//!
+//!     // render, interpolating with `clock.alpha()`
+//!     # break;
+//! }
+//! ```
+
+use std::time::{Duration, Instant};
+
+/// Default smoothing factor for the exponential moving average of the frame rate.
+const DEFAULT_FPS_SMOOTHING: f64 = 0.1;
+
+/// Default upper bound on the delta time fed into the fixed-timestep accumulator.
+///
+/// Prevents a "spiral of death" after long stalls (eg. window drags or breakpoints),
+/// where the simulation can never catch up to real time.
+const DEFAULT_MAX_DELTA: Duration = Duration::from_millis(250);
+
+/// Default portion of the frame budget that the limiter busy-waits instead of sleeping.
+const DEFAULT_SPIN_THRESHOLD: Duration = Duration::from_micros(1500);
+
+/// Per-frame timing state for a main loop.
+///
+/// See the [module-level documentation](self) for an overview.
+pub struct FrameClock {
+    /// Time at which the clock was created.
+    start: Instant,
+    /// Time at which the current frame began (ie. the last [`Self::tick()`]).
+    frame_start: Instant,
+    /// Time between the two most recent ticks.
+    delta: Duration,
+    /// Number of times [`Self::tick()`] has been called.
+    frame_count: u64,
+
+    /// Exponential moving average of the frame rate.
+    fps: f64,
+    /// Weight of the newest sample in [`Self::fps`], in `(0, 1]`.
+    fps_smoothing: f64,
+
+    /// Length of a single fixed step, if fixed-timestep accumulation is enabled.
+    fixed_step: Option<Duration>,
+    /// Unconsumed time waiting to be handed out as fixed steps.
+    accumulator: Duration,
+    /// Upper bound on the delta time added to the accumulator per tick.
+    max_delta: Duration,
+
+    /// Optional frame rate limiter.
+    limiter: Option<FrameLimiter>,
+}
+
+/// An idiomatic builder for [`FrameClock`].
+///
+/// Allows optional configuration of:
+/// - fixed timestep rate (in Hz)
+/// - frame limit (in frames per second)
+/// - fps smoothing factor
+/// - maximum accumulated delta per tick
+/// - limiter spin threshold
+pub struct FrameClockBuilder {
+    fixed_step: Option<Duration>,
+    max_delta: Duration,
+    fps_smoothing: f64,
+    limiter: Option<FrameLimiter>,
+    spin_threshold: Duration,
+}
+
+/// Sleep + spin frame rate limiter.
+///
+/// Sleeping alone is too coarse on most platforms (often 1-15ms of jitter), and
+/// spinning alone burns a whole core. The limiter sleeps for the bulk of the
+/// remaining frame budget and busy-waits only for the final
+/// [`spin_threshold`](FrameLimiter::spin_threshold).
+///
+/// The limiter is independent of vsync: with vsync enabled, the swap interval
+/// still caps the frame rate and the limiter only applies when its target is lower.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FrameLimiter {
+    /// Minimum duration of a single frame.
+    target: Duration,
+    /// Remaining duration below which the limiter spins rather than sleeps.
+    spin_threshold: Duration,
+}
+
+/// Iterator over the fixed steps accumulated in a [`FrameClock`].
+///
+/// Created via [`FrameClock::fixed_steps()`]. Each call to `next()` consumes one
+/// fixed step from the clock's accumulator. Any remainder smaller than a step is
+/// kept for the next frame, and is reported by [`FrameClock::alpha()`].
+#[must_use = "Iterators are lazy and do nothing unless consumed"]
+pub struct FixedSteps<'a> {
+    clock: &'a mut FrameClock,
+}
+
+impl FrameClock {
+    /// Create a new [`FrameClock`] with default configuration: no fixed timestep
+    /// and no frame limiter.
+    pub fn new() -> Self {
+        FrameClockBuilder::new().build()
+    }
+
+    /// Begin building a [`FrameClock`].
+    pub fn builder() -> FrameClockBuilder {
+        FrameClockBuilder::new()
+    }
+
+    /// Mark the beginning of a new frame, returning the delta time since the
+    /// previous call.
+    ///
+    /// If a [`FrameLimiter`] is set, this blocks until at least the limiter's
+    /// target frame duration has passed since the previous tick.
+    pub fn tick(&mut self) -> Duration {
+        if let Some(limiter) = self.limiter {
+            limiter.wait_until(self.frame_start + limiter.target);
+        }
+
+        let now = Instant::now();
+        self.delta = now - self.frame_start;
+        self.frame_start = now;
+        self.frame_count += 1;
+
+        let secs = self.delta.as_secs_f64();
+        if secs > 0.0 {
+            let sample = 1.0 / secs;
+            self.fps = if self.frame_count == 1 {
+                sample
+            } else {
+                self.fps + (sample - self.fps) * self.fps_smoothing
+            };
+        }
+
+        if self.fixed_step.is_some() {
+            self.accumulator += self.delta.min(self.max_delta);
+        }
+
+        self.delta
+    }
+
+    /// Time between the two most recent calls to [`Self::tick()`].
+    pub fn delta(&self) -> Duration {
+        self.delta
+    }
+
+    /// [`Self::delta()`] in seconds, as consumed by most animation code.
+    pub fn delta_secs(&self) -> f32 {
+        self.delta.as_secs_f32()
+    }
+
+    /// Time since the clock was created.
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    /// Number of frames ticked so far.
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
+    /// Smoothed frames-per-second estimate.
+    pub fn fps(&self) -> f64 {
+        self.fps
+    }
+
+    /// Returns an iterator handing out each fixed step accumulated so far.
+    ///
+    /// Yields nothing if fixed-timestep accumulation is disabled.
+    pub fn fixed_steps(&mut self) -> FixedSteps<'_> {
+        FixedSteps { clock: self }
+    }
+
+    /// The fraction of a fixed step left in the accumulator, in `[0, 1)`.
+    ///
+    /// Used to interpolate between the previous and current simulation states
+    /// when rendering. Returns `1.0` if fixed-timestep accumulation is disabled.
+    pub fn alpha(&self) -> f32 {
+        self.fixed_step.map_or(1.0, |step| {
+            (self.accumulator.as_secs_f64() / step.as_secs_f64()) as f32
+        })
+    }
+
+    /// Length of a single fixed step, if fixed-timestep accumulation is enabled.
+    pub fn fixed_step(&self) -> Option<Duration> {
+        self.fixed_step
+    }
+
+    /// Enable fixed-timestep accumulation at `hz` steps per second, or disable it
+    /// with [None].
+    pub fn set_fixed_timestep(&mut self, hz: Option<f64>) {
+        self.fixed_step = hz.map(hz_to_duration);
+        self.accumulator = Duration::ZERO;
+    }
+
+    /// The current frame limiter, if any.
+    pub fn frame_limiter(&self) -> Option<FrameLimiter> {
+        self.limiter
+    }
+
+    /// Limit the frame rate to `fps` frames per second, or remove the limit with [None].
+    pub fn set_frame_limit(&mut self, fps: Option<f64>) {
+        let spin_threshold = self
+            .limiter
+            .map_or(DEFAULT_SPIN_THRESHOLD, |l| l.spin_threshold);
+        self.limiter = fps.map(|fps| FrameLimiter::new(fps).spin_threshold(spin_threshold));
+    }
+}
+
+impl FrameClockBuilder {
+    /// Create a new [`FrameClockBuilder`] with default configuration.
+    pub fn new() -> Self {
+        Self {
+            fixed_step: None,
+            max_delta: DEFAULT_MAX_DELTA,
+            fps_smoothing: DEFAULT_FPS_SMOOTHING,
+            limiter: None,
+            spin_threshold: DEFAULT_SPIN_THRESHOLD,
+        }
+    }
+
+    /// Build and return the configured [`FrameClock`].
+    pub fn build(self) -> FrameClock {
+        let now = Instant::now();
+        FrameClock {
+            start: now,
+            frame_start: now,
+            delta: Duration::ZERO,
+            frame_count: 0,
+
+            fps: 0.0,
+            fps_smoothing: self.fps_smoothing,
+
+            fixed_step: self.fixed_step,
+            accumulator: Duration::ZERO,
+            max_delta: self.max_delta,
+
+            limiter: self.limiter.map(|l| l.spin_threshold(self.spin_threshold)),
+        }
+    }
+
+    /// Enable fixed-timestep accumulation at `hz` steps per second.
+    ///
+    /// See [`FrameClock::fixed_steps()`] for more information.
+    pub fn fixed_timestep(mut self, hz: f64) -> Self {
+        self.fixed_step = Some(hz_to_duration(hz));
+        self
+    }
+
+    /// Sets the maximum delta time added to the fixed-timestep accumulator per tick.
+    ///
+    /// Defaults to 250ms.
+    pub fn max_delta(mut self, max: Duration) -> Self {
+        self.max_delta = max;
+        self
+    }
+
+    /// Sets the weight of each new sample in the smoothed frame rate.
+    ///
+    /// Clamped to `(0, 1]`; `1.0` disables smoothing. Defaults to `0.1`.
+    pub fn fps_smoothing(mut self, alpha: f64) -> Self {
+        self.fps_smoothing = alpha.clamp(f64::EPSILON, 1.0);
+        self
+    }
+
+    /// Limit the frame rate to `fps` frames per second.
+    ///
+    /// See [`FrameLimiter`] for more information.
+    pub fn frame_limit(mut self, fps: f64) -> Self {
+        self.limiter = Some(FrameLimiter::new(fps));
+        self
+    }
+
+    /// Sets the remaining frame time below which the limiter spins instead of sleeping.
+    ///
+    /// Defaults to 1.5ms.
+    pub fn spin_threshold(mut self, threshold: Duration) -> Self {
+        self.spin_threshold = threshold;
+        self
+    }
+}
+
+impl FrameLimiter {
+    /// Create a limiter targeting `fps` frames per second.
+    pub fn new(fps: f64) -> Self {
+        Self {
+            target: hz_to_duration(fps),
+            spin_threshold: DEFAULT_SPIN_THRESHOLD,
+        }
+    }
+
+    /// Sets the remaining frame time below which the limiter spins instead of sleeping.
+    pub fn spin_threshold(mut self, threshold: Duration) -> Self {
+        self.spin_threshold = threshold;
+        self
+    }
+
+    /// Minimum duration of a single frame.
+    pub fn target(&self) -> Duration {
+        self.target
+    }
+
+    /// Block the current thread until `deadline`, sleeping while far from it
+    /// and spinning once within the spin threshold.
+    fn wait_until(&self, deadline: Instant) {
+        loop {
+            let now = Instant::now();
+            if now >= deadline {
+                break;
+            }
+
+            let remaining = deadline - now;
+            if remaining > self.spin_threshold {
+                std::thread::sleep(remaining - self.spin_threshold);
+            } else {
+                std::hint::spin_loop();
+            }
+        }
+    }
+}
+
+impl Iterator for FixedSteps<'_> {
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let step = self.clock.fixed_step?;
+        if self.clock.accumulator >= step {
+            self.clock.accumulator -= step;
+            Some(step)
+        } else {
+            None
+        }
+    }
+}
+
+/// Convert a rate in Hz to the duration of a single period.
+///
+/// Panics if `hz` is not a positive, finite number.
+fn hz_to_duration(hz: f64) -> Duration {
+    assert!(
+        hz.is_finite() && hz > 0.0,
+        "Expected a positive rate in Hz but got '{hz}'"
+    );
+    Duration::from_secs_f64(1.0 / hz)
+}
+
+impl std::default::Default for FrameClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::default::Default for FrameClockBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
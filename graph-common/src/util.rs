@@ -52,8 +52,8 @@ impl<C: ClipboardBackend> ImguiBuilder<C> {
             imgui::Context::create_with_shared_font_atlas,
         );
 
-        if self.clipboard.is_some() {
-            ctx.set_clipboard_backend(self.clipboard.unwrap());
+        if let Some(clipboard) = self.clipboard {
+            ctx.set_clipboard_backend(clipboard);
         }
         ctx.set_ini_filename(self.ini_file);
         ctx.set_log_filename(self.log_file);
@@ -89,6 +89,9 @@ impl<C: ClipboardBackend> ImguiBuilder<C> {
 
     /// This method is used to provide the ini file's path for imgui
     /// to save and load layout data.
+    ///
+    /// Windows built by graph-engine should prefer its `UiStateManager`, which
+    /// persists the imgui layout along with the rest of the ui state.
     pub fn ini(mut self, path: impl Into<PathBuf>) -> Self {
         self.ini_file = Some(path.into());
         self
@@ -0,0 +1,16 @@
+use std::process::Command;
+
+/// Record the compiler's version for `plugin::RUSTC_VERSION`, which plugins
+/// must be built with.
+fn main() {
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".into());
+    let version = Command::new(rustc)
+        .arg("--version")
+        .output()
+        .ok()
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .unwrap_or_default();
+    println!("cargo:rustc-env=GRAPH_ENGINE_RUSTC={}", version.trim());
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-env-changed=RUSTC");
+}
@@ -0,0 +1,371 @@
+//! # Graph Algorithms
+//!
+//! Classic algorithms over a [`Graph`], returning plain ids so results can be
+//! shown in the scene, eg. by [selecting](crate::selection::Selection) them:
+//! - [`Bfs`] and [`Dfs`] iterate over the nodes reachable from a start node,
+//!   with the edge each was reached by
+//! - [`connected_components()`] groups nodes connected by any path, ignoring
+//!   edge directions
+//! - [`shortest_path()`] (Dijkstra) and [`astar()`] find the cheapest path
+//!   between two nodes, optionally weighted by an edge attribute, and
+//!   [`k_shortest_paths()`] the next cheapest ones
+//! - [`topological_sort()`] orders the nodes of a directed acyclic graph
+//! - [`leiden()`] detects communities of densely connected nodes, which
+//!   [`collapse()`] turns into a graph of meta-nodes
+//! - [`metrics`] scores how central or clustered each node is, on a worker
+//!   thread, and writes the scores as node attributes
+//! - [`diff`] compares two versions of a graph, matching nodes by id or a key
+//!   attribute
+//!
+//! The [`AlgorithmsPanel`] runs them on the visible graph and selects the result,
+//! as does each [`Algorithm`] run on its own, while the
+//! [`MetricsPanel`](metrics::MetricsPanel) computes metrics and the
+//! [`DiffPanel`](diff::DiffPanel) compares documents.
+//!
+//! ```ignore
+//! let path = algo::shortest_path(&graph, a, b, Some("weight")).unwrap();
+//! ctx.selection.select(path.nodes, path.edges, SelectMode::Replace);
+//!
+//! let reachable: Vec<NodeId> = Bfs::new(&graph, a, Direction::Forward)
+//!     .map(|visit| visit.node)
+//!     .collect();
+//! ```
+
+pub mod diff;
+pub mod metrics;
+
+mod community;
+mod components;
+mod paths;
+mod topological;
+mod traversal;
+
+pub use community::{COMMUNITY_ATTR, Communities, collapse, leiden};
+pub use components::{Components, connected_components};
+pub use paths::{Path, astar, k_shortest_paths, shortest_path};
+pub use topological::{TopologicalError, topological_sort};
+pub use traversal::{Bfs, Dfs, Visit};
+
+use crate::context::EngineContext;
+use crate::graph::{EdgeId, Graph, NodeId};
+use crate::history::Command;
+use crate::i18n::tr;
+use crate::imgui::{ItemHoveredFlags, Ui};
+use crate::layout::Positions;
+use crate::selection::SelectMode;
+use crate::style::ColorMapping;
+use crate::subsystems::panels::Panel;
+
+/// Which edges a traversal follows out of a node.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// Along the edges' direction. Undirected edges are followed both ways.
+    #[default]
+    Forward,
+    /// Against the edges' direction. Undirected edges are followed both ways.
+    Backward,
+    /// Both ways, ignoring the edges' direction.
+    Both,
+}
+
+/// Edges followed out of `node` in `direction`, with the node each leads to.
+fn steps(
+    graph: &Graph,
+    node: NodeId,
+    direction: Direction,
+) -> impl Iterator<Item = (EdgeId, NodeId)> + '_ {
+    let (forward, backward) = match (graph.is_directed(), direction) {
+        (false, _) | (true, Direction::Both) => (true, true),
+        (true, Direction::Forward) => (true, false),
+        (true, Direction::Backward) => (false, true),
+    };
+    let outgoing = graph
+        .out_edges(node)
+        .filter(move |_| forward)
+        .filter_map(move |e| Some((e, graph.endpoints(e)?.1)));
+    // self-loops are already followed as outgoing edges
+    let incoming = graph
+        .in_edges(node)
+        .filter(move |_| backward)
+        .filter_map(move |e| Some((e, graph.endpoints(e)?.0)))
+        .filter(move |&(_, source)| !forward || source != node);
+    outgoing.chain(incoming)
+}
+
+/// An algorithm run on the [visible graph](EngineContext::visible_graph) of
+/// a context, eg. from the [`AlgorithmsPanel`] or the
+/// [command palette](crate::palette).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Algorithm {
+    /// Select the cheapest path between the two selected nodes.
+    ShortestPath,
+    /// Select the components of the selected nodes.
+    SelectComponents,
+    /// Select the nodes on or after a cycle, if any.
+    TopologicalSort,
+    /// Write the community of each node to [`COMMUNITY_ATTR`].
+    DetectCommunities,
+    /// Color nodes by community, detecting them first if needed.
+    ColorByCommunity,
+    /// Replace the graph by one with a node per community.
+    CollapseCommunities,
+}
+
+/// Runs algorithms on the [visible graph](EngineContext::visible_graph) and
+/// selects their results.
+pub struct AlgorithmsPanel {
+    /// Edge attribute weighting shortest paths and communities, `None`
+    /// counting hops.
+    weight: Option<String>,
+    /// Outcome of the last run, shown under the buttons.
+    status: Option<String>,
+}
+
+impl Algorithm {
+    pub const ALL: [Algorithm; 6] = [
+        Self::ShortestPath,
+        Self::SelectComponents,
+        Self::TopologicalSort,
+        Self::DetectCommunities,
+        Self::ColorByCommunity,
+        Self::CollapseCommunities,
+    ];
+
+    /// Human-readable name of the algorithm.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::ShortestPath => "Shortest Path",
+            Self::SelectComponents => "Select Components",
+            Self::TopologicalSort => "Topological Sort",
+            Self::DetectCommunities => "Detect Communities",
+            Self::ColorByCommunity => "Color by Community",
+            Self::CollapseCommunities => "Collapse Communities",
+        }
+    }
+
+    /// Run the algorithm on the visible graph of `ctx`, with edges weighted by
+    /// the `weight` attribute or counting hops. Returns its outcome, for the
+    /// user.
+    pub fn run(self, ctx: &mut EngineContext, weight: Option<&str>) -> String {
+        match self {
+            Self::ShortestPath => run_shortest_path(ctx, weight),
+            Self::SelectComponents => run_select_components(ctx),
+            Self::TopologicalSort => run_topological_sort(ctx),
+            Self::DetectCommunities => run_detect_communities(ctx, weight),
+            Self::ColorByCommunity => run_color_by_community(ctx, weight),
+            Self::CollapseCommunities => run_collapse_communities(ctx, weight),
+        }
+    }
+}
+
+fn run_shortest_path(ctx: &mut EngineContext, weight: Option<&str>) -> String {
+    let nodes: Vec<NodeId> = ctx.selection.nodes().collect();
+    let &[a, b] = nodes.as_slice() else {
+        return "Select two nodes".into();
+    };
+    let graph = ctx.visible_graph();
+    // the selection does not know which node was picked first, so try both
+    // ways along directed edges
+    let path = shortest_path(graph, a, b, weight).or_else(|| shortest_path(graph, b, a, weight));
+    match path {
+        Some(path) => {
+            let status = match weight {
+                Some(_) => format!("{} hops, cost {}", path.edges.len(), path.cost),
+                None => format!("{} hops", path.edges.len()),
+            };
+            ctx.selection
+                .select(path.nodes, path.edges, SelectMode::Replace);
+            status
+        }
+        None => "No path between the selected nodes".into(),
+    }
+}
+
+fn run_select_components(ctx: &mut EngineContext) -> String {
+    let components = connected_components(ctx.visible_graph());
+    let mut picked: Vec<usize> = ctx
+        .selection
+        .nodes()
+        .filter_map(|node| components.component(node))
+        .collect();
+    picked.sort_unstable();
+    picked.dedup();
+    let nodes: Vec<NodeId> = picked
+        .iter()
+        .flat_map(|&c| components.nodes(c).iter().copied())
+        .collect();
+    let status = format!(
+        "{} of {} components, {} nodes",
+        picked.len(),
+        components.count(),
+        nodes.len()
+    );
+    ctx.selection.select(nodes, [], SelectMode::Replace);
+    status
+}
+
+fn run_detect_communities(ctx: &mut EngineContext, weight: Option<&str>) -> String {
+    let communities = leiden(ctx.visible_graph(), weight);
+    ctx.execute(communities.command());
+    format!(
+        "{} communities, modularity {:.3}, written to \"{COMMUNITY_ATTR}\"",
+        communities.count(),
+        communities.modularity()
+    )
+}
+
+fn run_color_by_community(ctx: &mut EngineContext, weight: Option<&str>) -> String {
+    let status = match ctx
+        .style
+        .node_attributes()
+        .iter()
+        .any(|a| a == COMMUNITY_ATTR)
+    {
+        true => format!("Colored by \"{COMMUNITY_ATTR}\""),
+        false => run_detect_communities(ctx, weight),
+    };
+    let mut sheet = ctx.style.sheet().clone();
+    sheet.node_color = ColorMapping::Categories {
+        attribute: COMMUNITY_ATTR.into(),
+    };
+    ctx.execute(Command::SetStyle(Box::new(sheet)));
+    status
+}
+
+/// Replace the graph by one with a node per community of the visible graph,
+/// placed at the center of its members.
+fn run_collapse_communities(ctx: &mut EngineContext, weight: Option<&str>) -> String {
+    ctx.force.sync_positions(&mut ctx.positions);
+    let graph = ctx.visible_graph();
+    let communities = leiden(graph, weight);
+    let collapsed = collapse(graph, &communities);
+    let mut positions = Positions::new(&collapsed);
+    for (node, members) in collapsed.nodes().zip(communities.iter()) {
+        let center = {
+            let points: Vec<[f32; 2]> = members
+                .iter()
+                .filter_map(|&n| ctx.positions.get(n))
+                .collect();
+            let n = points.len() as f32;
+            (n > 0.0).then(|| {
+                let sum = points
+                    .iter()
+                    .fold([0.0; 2], |s, p| [s[0] + p[0], s[1] + p[1]]);
+                [sum[0] / n, sum[1] / n]
+            })
+        };
+        if let Some(center) = center {
+            positions.set(node, center);
+        }
+    }
+    let status = format!(
+        "Collapsed {} nodes into {} communities",
+        graph.node_count(),
+        communities.count()
+    );
+    ctx.filters.clear();
+    ctx.execute(Command::Batch {
+        label: "Collapse Communities".into(),
+        commands: vec![Command::ReplaceGraph {
+            graph: Box::new(collapsed),
+            positions: Box::new(positions),
+        }],
+    });
+    status
+}
+
+fn run_topological_sort(ctx: &mut EngineContext) -> String {
+    match topological_sort(ctx.visible_graph()) {
+        Ok(order) => format!("Acyclic, {} nodes in order", order.len()),
+        Err(TopologicalError::Cycle(nodes)) => {
+            let status = format!(
+                "{} nodes are on or after a cycle, now selected",
+                nodes.len()
+            );
+            ctx.selection.select(nodes, [], SelectMode::Replace);
+            status
+        }
+        Err(e) => e.to_string(),
+    }
+}
+
+impl AlgorithmsPanel {
+    /// Create a panel counting hops for shortest paths.
+    pub fn new() -> Self {
+        Self {
+            weight: None,
+            status: None,
+        }
+    }
+
+    fn run(&mut self, algorithm: Algorithm, ctx: &mut EngineContext) {
+        self.status = Some(algorithm.run(ctx, self.weight.as_deref()));
+    }
+}
+
+impl Panel for AlgorithmsPanel {
+    fn id(&self) -> &'static str {
+        "Algorithms"
+    }
+
+    fn draw(&mut self, ui: &Ui, ctx: &mut EngineContext) {
+        let preview = self.weight.as_deref().unwrap_or("(hops)");
+        if let Some(_combo) = ui.begin_combo(tr("Weight"), preview) {
+            if ui
+                .selectable_config(tr("(hops)"))
+                .selected(self.weight.is_none())
+                .build()
+            {
+                self.weight = None;
+            }
+            for name in ctx.style.edge_attributes() {
+                if ui
+                    .selectable_config(name)
+                    .selected(self.weight.as_ref() == Some(name))
+                    .build()
+                {
+                    self.weight = Some(name.clone());
+                }
+            }
+        }
+
+        let two_nodes = ctx.selection.node_count() == 2;
+        ui.enabled(two_nodes, || {
+            if ui.button(Algorithm::ShortestPath.label()) {
+                self.run(Algorithm::ShortestPath, ctx);
+            }
+        });
+        if !two_nodes && ui.is_item_hovered_with_flags(ItemHoveredFlags::ALLOW_WHEN_DISABLED) {
+            ui.tooltip_text(tr("Select two nodes"));
+        }
+        for algorithm in [Algorithm::SelectComponents, Algorithm::TopologicalSort] {
+            ui.same_line();
+            if ui.button(algorithm.label()) {
+                self.run(algorithm, ctx);
+            }
+        }
+        if ui.button(Algorithm::DetectCommunities.label()) {
+            self.run(Algorithm::DetectCommunities, ctx);
+        }
+        ui.same_line();
+        if ui.button(Algorithm::ColorByCommunity.label()) {
+            self.run(Algorithm::ColorByCommunity, ctx);
+        }
+        ui.same_line();
+        if ui.button(Algorithm::CollapseCommunities.label()) {
+            self.run(Algorithm::CollapseCommunities, ctx);
+        }
+        if ui.is_item_hovered() {
+            ui.tooltip_text(tr("Replace the graph by one node per community"));
+        }
+        if let Some(status) = &self.status {
+            ui.text_wrapped(status);
+        }
+    }
+}
+
+impl std::default::Default for AlgorithmsPanel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,402 @@
+use std::collections::HashMap;
+
+use crate::graph::{AttrValue, EdgeId, Graph, NodeId};
+use crate::history::Command;
+
+/// Name of the node attribute communities are written to.
+pub const COMMUNITY_ATTR: &str = "community";
+
+/// Communities of a graph, see [`leiden()`].
+///
+/// Communities are numbered from the largest to the smallest.
+#[derive(Clone, Debug, Default)]
+pub struct Communities {
+    /// Community of each node slot, `u32::MAX` for removed nodes.
+    of: Vec<u32>,
+    /// Nodes of each community, in id order.
+    members: Vec<Vec<NodeId>>,
+    modularity: f64,
+}
+
+/// A weighted undirected graph over dense indices, merged level by level.
+struct Level {
+    /// Neighbors of each vertex with the weight of the edges to them,
+    /// excluding self-loops.
+    adjacent: Vec<Vec<(usize, f64)>>,
+    /// Weight of each vertex's self-loops.
+    loops: Vec<f64>,
+}
+
+/// Group the nodes of `graph` into densely connected communities, maximizing
+/// modularity with the Leiden method. Edge directions are ignored.
+///
+/// Like the Louvain method, it moves nodes between communities and merges
+/// each community into a node, level by level. Before merging, it refines
+/// each community into well-connected parts, so that communities stay
+/// connected. Parts are grown greedily rather than at random.
+///
+/// Edges weigh their numeric `weight` attribute, or `1.0` with no attribute
+/// given. Edges without the attribute, or with a non-positive one, also weigh
+/// `1.0`. The result is deterministic for a given graph.
+pub fn leiden(graph: &Graph, weight: Option<&str>) -> Communities {
+    let nodes: Vec<NodeId> = graph.nodes().collect();
+    let mut dense = vec![usize::MAX; graph.node_bound()];
+    for (i, node) in nodes.iter().enumerate() {
+        dense[node.index()] = i;
+    }
+    let weight_of = |edge: EdgeId| {
+        weight
+            .and_then(|name| graph.edge(edge)?.get(name)?.as_f64())
+            .filter(|w| *w > 0.0)
+            .unwrap_or(1.0)
+    };
+
+    let mut level = Level {
+        adjacent: vec![Vec::new(); nodes.len()],
+        loops: vec![0.0; nodes.len()],
+    };
+    for edge in graph.edges() {
+        let (s, t) = graph.endpoints(edge).unwrap();
+        let (s, t) = (dense[s.index()], dense[t.index()]);
+        let w = weight_of(edge);
+        if s == t {
+            level.loops[s] += w;
+        } else {
+            level.adjacent[s].push((t, w));
+            level.adjacent[t].push((s, w));
+        }
+    }
+
+    // vertex of each node at the current level, and community of each vertex
+    let mut of: Vec<usize> = (0..nodes.len()).collect();
+    let mut community: Vec<usize> = of.clone();
+    loop {
+        let (moved, moves) = level.local_moves(&community);
+        community = moves;
+        let parts = level.refine(&community);
+        let count = parts.iter().max().map_or(0, |p| p + 1);
+        if !moved && count == level.len() {
+            break;
+        }
+        // merged vertices start out in the community their part is in
+        let mut merged = vec![0; count];
+        for (v, &p) in parts.iter().enumerate() {
+            merged[p] = community[v];
+        }
+        for v in &mut of {
+            *v = parts[*v];
+        }
+        level = level.merge(&parts);
+        community = merged;
+    }
+    let modularity = level.modularity(&community);
+
+    let mut members = vec![Vec::new(); community.iter().max().map_or(0, |c| c + 1)];
+    for (i, &v) in of.iter().enumerate() {
+        members[community[v]].push(nodes[i]);
+    }
+    // stable, so equally large communities keep the order of their first node
+    members.sort_by_key(|nodes| std::cmp::Reverse(nodes.len()));
+    let mut of = vec![u32::MAX; graph.node_bound()];
+    for (i, nodes) in members.iter().enumerate() {
+        for node in nodes {
+            of[node.index()] = i as u32;
+        }
+    }
+    Communities {
+        of,
+        members,
+        modularity,
+    }
+}
+
+/// A graph with one node per community of `graph`, carrying the community's
+/// number as [`COMMUNITY_ATTR`] and its node count as `size`, and one edge per
+/// pair of connected communities, carrying the number of edges it stands for
+/// as `weight`.
+///
+/// Node `i` of the result stands for community `i`. Edges within a community
+/// are dropped.
+pub fn collapse(graph: &Graph, communities: &Communities) -> Graph {
+    let mut collapsed = Graph::new(graph.directedness());
+    for (i, members) in communities.iter().enumerate() {
+        let node = collapsed.add_node();
+        let attrs = collapsed.node_mut(node).unwrap();
+        attrs.insert(COMMUNITY_ATTR, i as i64);
+        attrs.insert("size", members.len() as i64);
+        attrs.insert("label", format!("Community {i}"));
+    }
+
+    let mut links: HashMap<(usize, usize), i64> = HashMap::new();
+    for edge in graph.edges() {
+        let (s, t) = graph.endpoints(edge).unwrap();
+        let (Some(s), Some(t)) = (communities.community(s), communities.community(t)) else {
+            continue;
+        };
+        if s == t {
+            continue;
+        }
+        let key = match graph.is_directed() {
+            true => (s, t),
+            false => (s.min(t), s.max(t)),
+        };
+        *links.entry(key).or_default() += 1;
+    }
+    let mut links: Vec<_> = links.into_iter().collect();
+    links.sort_unstable();
+    let ids: Vec<NodeId> = collapsed.nodes().collect();
+    for ((s, t), count) in links {
+        let edge = collapsed.add_edge(ids[s], ids[t]).unwrap();
+        collapsed.edge_mut(edge).unwrap().insert("weight", count);
+    }
+    collapsed
+}
+
+impl Communities {
+    /// Number of communities.
+    pub fn count(&self) -> usize {
+        self.members.len()
+    }
+
+    /// The community `node` belongs to, if it exists.
+    pub fn community(&self, node: NodeId) -> Option<usize> {
+        self.of
+            .get(node.index())
+            .filter(|c| **c != u32::MAX)
+            .map(|c| *c as usize)
+    }
+
+    /// The nodes of `community`, in id order.
+    ///
+    /// Panics if `community` is out of bounds.
+    pub fn nodes(&self, community: usize) -> &[NodeId] {
+        &self.members[community]
+    }
+
+    /// Iterate over the communities, from the largest.
+    pub fn iter(&self) -> impl Iterator<Item = &[NodeId]> {
+        self.members.iter().map(Vec::as_slice)
+    }
+
+    /// Modularity of the partition, from `-0.5` to `1.0`: how much denser
+    /// communities are connected inside than a random graph with the same
+    /// degrees would be.
+    pub fn modularity(&self) -> f64 {
+        self.modularity
+    }
+
+    /// A command writing each node's community to its [`COMMUNITY_ATTR`]
+    /// attribute.
+    pub fn command(&self) -> Command {
+        Command::set_node_attribute(
+            COMMUNITY_ATTR,
+            self.iter().enumerate().flat_map(|(c, nodes)| {
+                nodes
+                    .iter()
+                    .map(move |&node| (node, AttrValue::Int(c as i64)))
+            }),
+        )
+    }
+}
+
+/// `labels` numbered densely, in order of first appearance.
+fn renumber(mut labels: Vec<usize>) -> Vec<usize> {
+    let mut renumber = vec![usize::MAX; labels.len()];
+    let mut next = 0;
+    for label in &mut labels {
+        if renumber[*label] == usize::MAX {
+            renumber[*label] = next;
+            next += 1;
+        }
+        *label = renumber[*label];
+    }
+    labels
+}
+
+impl Level {
+    fn len(&self) -> usize {
+        self.loops.len()
+    }
+
+    /// Weighted degree of each vertex, counting self-loops twice.
+    fn degrees(&self) -> Vec<f64> {
+        (0..self.len())
+            .map(|v| self.adjacent[v].iter().map(|(_, w)| w).sum::<f64>() + 2.0 * self.loops[v])
+            .collect()
+    }
+
+    /// Modularity of assigning each vertex to `community`.
+    fn modularity(&self, community: &[usize]) -> f64 {
+        let degrees = self.degrees();
+        let total: f64 = degrees.iter().sum();
+        if total == 0.0 {
+            return 0.0;
+        }
+        let mut inside = vec![0.0; self.len()];
+        let mut tot = vec![0.0; self.len()];
+        for v in 0..self.len() {
+            let c = community[v];
+            tot[c] += degrees[v];
+            inside[c] += 2.0 * self.loops[v];
+            for &(u, w) in &self.adjacent[v] {
+                if community[u] == c {
+                    inside[c] += w;
+                }
+            }
+        }
+        (0..self.len())
+            .map(|c| inside[c] / total - (tot[c] / total).powi(2))
+            .sum()
+    }
+
+    /// Move vertices from their `initial` community to the neighboring one
+    /// increasing modularity the most, until none does. Returns whether any
+    /// moved, and each vertex's community numbered densely.
+    fn local_moves(&self, initial: &[usize]) -> (bool, Vec<usize>) {
+        let degrees = self.degrees();
+        let total: f64 = degrees.iter().sum();
+        let mut community = initial.to_vec();
+        let mut tot = vec![0.0; self.len()];
+        for (v, &c) in community.iter().enumerate() {
+            tot[c] += degrees[v];
+        }
+        let mut links: HashMap<usize, f64> = HashMap::new();
+        let mut moved = false;
+        if total == 0.0 {
+            return (false, renumber(community));
+        }
+
+        loop {
+            let mut improved = false;
+            for v in 0..self.len() {
+                let own = community[v];
+                links.clear();
+                for &(u, w) in &self.adjacent[v] {
+                    *links.entry(community[u]).or_default() += w;
+                }
+                tot[own] -= degrees[v];
+                // gain of joining `c`, up to terms shared by every community
+                let gain = |c: usize, link: f64| link - tot[c] * degrees[v] / total;
+                let mut best = (own, gain(own, links.get(&own).copied().unwrap_or(0.0)));
+                let mut candidates: Vec<(usize, f64)> =
+                    links.iter().map(|(&c, &l)| (c, l)).collect();
+                candidates.sort_unstable_by_key(|&(c, _)| c);
+                for (c, link) in candidates {
+                    let gain = gain(c, link);
+                    if gain > best.1 + 1e-12 {
+                        best = (c, gain);
+                    }
+                }
+                tot[best.0] += degrees[v];
+                if best.0 != own {
+                    community[v] = best.0;
+                    improved = true;
+                    moved = true;
+                }
+            }
+            if !improved {
+                break;
+            }
+        }
+        (moved, renumber(community))
+    }
+
+    /// Split each `community` into parts, each vertex joining the part in its
+    /// community increasing modularity the most, if any does. Only vertices
+    /// alone in their part move, and only between parts well connected to
+    /// the rest of their community. Returns each vertex's part numbered
+    /// densely.
+    fn refine(&self, community: &[usize]) -> Vec<usize> {
+        let degrees = self.degrees();
+        let total: f64 = degrees.iter().sum();
+        let mut part: Vec<usize> = (0..self.len()).collect();
+        if total == 0.0 {
+            return part;
+        }
+        let mut community_tot = vec![0.0; self.len()];
+        for (v, &c) in community.iter().enumerate() {
+            community_tot[c] += degrees[v];
+        }
+        // degree of each part, and the weight of its edges to the rest of its
+        // community
+        let mut tot = degrees.clone();
+        let mut outside: Vec<f64> = (0..self.len())
+            .map(|v| {
+                let inside = self.adjacent[v]
+                    .iter()
+                    .filter(|(u, _)| community[*u] == community[v]);
+                inside.map(|(_, w)| w).sum()
+            })
+            .collect();
+        let well_connected = |outside: f64, tot: f64, c: usize| {
+            outside >= tot * (community_tot[c] - tot) / total - 1e-12
+        };
+        let mut alone = vec![true; self.len()];
+        let mut links: HashMap<usize, f64> = HashMap::new();
+
+        for v in 0..self.len() {
+            let c = community[v];
+            if !alone[v] || !well_connected(outside[v], degrees[v], c) {
+                continue;
+            }
+            links.clear();
+            for &(u, w) in &self.adjacent[v] {
+                if community[u] == c {
+                    *links.entry(part[u]).or_default() += w;
+                }
+            }
+            let mut candidates: Vec<(usize, f64)> = links.iter().map(|(&p, &l)| (p, l)).collect();
+            candidates.sort_unstable_by_key(|&(p, _)| p);
+            let mut best = (v, 0.0, 0.0);
+            for (p, link) in candidates {
+                if p == v || !well_connected(outside[p], tot[p], c) {
+                    continue;
+                }
+                let gain = link - tot[p] * degrees[v] / total;
+                if gain > best.1 + 1e-12 {
+                    best = (p, gain, link);
+                }
+            }
+            let (p, _, link) = best;
+            if p != v {
+                // the edges between `v` and `p` are now inside the part
+                outside[p] += outside[v] - 2.0 * link;
+                tot[p] += degrees[v];
+                part[v] = p;
+                alone[v] = false;
+                alone[p] = false;
+            }
+        }
+        renumber(part)
+    }
+
+    /// A level with one vertex per community, self-loops weighing the edges
+    /// inside them.
+    fn merge(&self, community: &[usize]) -> Level {
+        let count = community.iter().max().map_or(0, |c| c + 1);
+        let mut loops = vec![0.0; count];
+        let mut between: Vec<HashMap<usize, f64>> = vec![HashMap::new(); count];
+        for v in 0..self.len() {
+            let c = community[v];
+            loops[c] += self.loops[v];
+            for &(u, w) in &self.adjacent[v] {
+                let d = community[u];
+                if c == d {
+                    // found from both ends
+                    loops[c] += w / 2.0;
+                } else {
+                    *between[c].entry(d).or_default() += w;
+                }
+            }
+        }
+        let adjacent = between
+            .into_iter()
+            .map(|links| {
+                let mut links: Vec<(usize, f64)> = links.into_iter().collect();
+                links.sort_unstable_by_key(|&(u, _)| u);
+                links
+            })
+            .collect();
+        Level { adjacent, loops }
+    }
+}
@@ -0,0 +1,70 @@
+use super::Direction;
+use super::traversal::Bfs;
+use crate::graph::{Graph, NodeId};
+
+/// Connected components of a graph, see [`connected_components()`].
+///
+/// Components are numbered from the largest to the smallest.
+#[derive(Clone, Debug, Default)]
+pub struct Components {
+    /// Component of each node slot, `u32::MAX` for removed nodes.
+    of: Vec<u32>,
+    /// Nodes of each component, in id order.
+    members: Vec<Vec<NodeId>>,
+}
+
+/// Group the nodes of `graph` into sets connected by paths, ignoring the
+/// direction of edges.
+pub fn connected_components(graph: &Graph) -> Components {
+    let mut of = vec![u32::MAX; graph.node_bound()];
+    let mut members = Vec::new();
+    for start in graph.nodes() {
+        if of[start.index()] != u32::MAX {
+            continue;
+        }
+        let mut nodes: Vec<NodeId> = Bfs::new(graph, start, Direction::Both)
+            .map(|visit| visit.node)
+            .collect();
+        for node in &nodes {
+            of[node.index()] = 0;
+        }
+        nodes.sort_unstable();
+        members.push(nodes);
+    }
+
+    // stable, so equally large components keep the order of their first node
+    members.sort_by_key(|nodes| std::cmp::Reverse(nodes.len()));
+    for (i, nodes) in members.iter().enumerate() {
+        for node in nodes {
+            of[node.index()] = i as u32;
+        }
+    }
+    Components { of, members }
+}
+
+impl Components {
+    /// Number of components.
+    pub fn count(&self) -> usize {
+        self.members.len()
+    }
+
+    /// The component `node` belongs to, if it exists.
+    pub fn component(&self, node: NodeId) -> Option<usize> {
+        self.of
+            .get(node.index())
+            .filter(|c| **c != u32::MAX)
+            .map(|c| *c as usize)
+    }
+
+    /// The nodes of `component`, in id order.
+    ///
+    /// Panics if `component` is out of bounds.
+    pub fn nodes(&self, component: usize) -> &[NodeId] {
+        &self.members[component]
+    }
+
+    /// Iterate over the components, from the largest.
+    pub fn iter(&self) -> impl Iterator<Item = &[NodeId]> {
+        self.members.iter().map(Vec::as_slice)
+    }
+}
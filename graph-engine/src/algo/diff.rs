@@ -0,0 +1,633 @@
+//! Differences between two versions of a graph.
+//!
+//! [`diff()`] matches the nodes of an old and a new graph by their
+//! [`ID_ATTR`](crate::io::ID_ATTR), or by another key attribute, see
+//! [`Matching`]. Edges are matched by the keys of their endpoints, parallel
+//! edges in the order they were added.
+//! Each element is then [added, removed, changed, or unchanged](Status), and
+//! changed ones list the attributes which differ.
+//!
+//! A [`GraphDiff`] can be [merged](GraphDiff::merged) into a single graph
+//! holding the elements of both, with their status in [`DIFF_ATTR`], and
+//! [summarized](GraphDiff::summary) as text. The [`DiffPanel`] compares the
+//! active document with another one this way.
+
+use std::collections::{HashMap, VecDeque};
+
+use crate::context::EngineContext;
+use crate::graph::{AttrValue, Attributes, EdgeId, Graph, NodeId};
+use crate::history::Command;
+use crate::i18n::tr;
+use crate::imgui::Ui;
+use crate::io::node_name;
+use crate::layout::Positions;
+use crate::style::ColorMapping;
+use crate::subsystems::panels::Panel;
+
+/// Attribute holding the [`Status`] of each element of a
+/// [merged](GraphDiff::merged) graph.
+pub const DIFF_ATTR: &str = "diff";
+/// Attribute listing the attributes which differ on changed elements of a
+/// [merged](GraphDiff::merged) graph, separated by commas.
+pub const CHANGES_ATTR: &str = "diff_changes";
+
+/// How the nodes of two graphs are matched.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Matching {
+    /// By [`ID_ATTR`](crate::io::ID_ATTR), or their [`NodeId`] if they have none.
+    Id,
+    /// By the value of an attribute. Nodes without it never match.
+    Attribute(String),
+}
+
+/// How an element differs between the old and new graph.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Status {
+    /// Only in the new graph.
+    Added,
+    /// Only in the old graph.
+    Removed,
+    /// In both, with different attributes.
+    Changed,
+    Unchanged,
+}
+
+/// Element counts by [`Status`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Counts {
+    pub added: usize,
+    pub removed: usize,
+    pub changed: usize,
+    pub unchanged: usize,
+}
+
+/// An attribute which differs between the old and new version of an element.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AttrChange {
+    pub key: String,
+    /// Value in the old graph, `None` if unset.
+    pub old: Option<AttrValue>,
+    /// Value in the new graph, `None` if unset.
+    pub new: Option<AttrValue>,
+}
+
+/// A node of either graph, matched with its counterpart in the other.
+#[derive(Clone, Debug)]
+pub struct NodeDiff {
+    /// The key the node was matched by, or its id if it has none.
+    pub key: String,
+    pub old: Option<NodeId>,
+    pub new: Option<NodeId>,
+    pub status: Status,
+    /// Attributes which differ, sorted by key.
+    pub changes: Vec<AttrChange>,
+}
+
+/// An edge of either graph, matched with its counterpart in the other.
+#[derive(Clone, Debug)]
+pub struct EdgeDiff {
+    /// Keys of the endpoints, as in [`NodeDiff::key`].
+    pub source: String,
+    pub target: String,
+    pub old: Option<EdgeId>,
+    pub new: Option<EdgeId>,
+    pub status: Status,
+    /// Attributes which differ, sorted by key.
+    pub changes: Vec<AttrChange>,
+}
+
+/// Differences between an old and a new graph, see [`diff()`].
+#[derive(Clone, Debug)]
+pub struct GraphDiff {
+    /// Nodes of the new graph in order, followed by removed ones.
+    pub nodes: Vec<NodeDiff>,
+    /// Edges of the new graph in order, followed by removed ones.
+    pub edges: Vec<EdgeDiff>,
+    /// Whether both graphs are directed. Otherwise edges match either way.
+    pub directed: bool,
+}
+
+/// Compare `old` to `new`, matching nodes by `matching`.
+pub fn diff(old: &Graph, new: &Graph, matching: &Matching) -> GraphDiff {
+    let directed = old.is_directed() && new.is_directed();
+    let old_keys = node_keys(old, matching);
+    let new_keys = node_keys(new, matching);
+
+    let pairs = pair(
+        old.nodes().map(|n| (old_keys[n.index()].clone(), n)),
+        new.nodes().map(|n| (new_keys[n.index()].clone(), n)),
+    );
+    let nodes = pairs
+        .into_iter()
+        .map(|(old_node, new_node)| {
+            let (graph, node, keys) = match new_node {
+                Some(node) => (new, node, &new_keys),
+                None => (old, old_node.unwrap(), &old_keys),
+            };
+            let key = keys[node.index()]
+                .clone()
+                .unwrap_or_else(|| node_name(graph, node));
+            let (status, changes) = compare(
+                old_node.and_then(|n| old.node(n)),
+                new_node.and_then(|n| new.node(n)),
+            );
+            NodeDiff {
+                key,
+                old: old_node,
+                new: new_node,
+                status,
+                changes,
+            }
+        })
+        .collect();
+
+    let edge_key = |graph: &Graph, keys: &[Option<String>], edge: EdgeId| {
+        let (source, target) = graph.endpoints(edge)?;
+        let source = keys[source.index()].as_deref()?;
+        let target = keys[target.index()].as_deref()?;
+        let (source, target) = match directed || source <= target {
+            true => (source, target),
+            false => (target, source),
+        };
+        // the separator cannot be typed into an attribute
+        Some(format!("{source}\0{target}"))
+    };
+    let pairs = pair(
+        old.edges().map(|e| (edge_key(old, &old_keys, e), e)),
+        new.edges().map(|e| (edge_key(new, &new_keys, e), e)),
+    );
+    let edges = pairs
+        .into_iter()
+        .map(|(old_edge, new_edge)| {
+            let (graph, edge, keys) = match new_edge {
+                Some(edge) => (new, edge, &new_keys),
+                None => (old, old_edge.unwrap(), &old_keys),
+            };
+            let (source, target) = graph.endpoints(edge).unwrap();
+            let name = |node: NodeId| {
+                keys[node.index()]
+                    .clone()
+                    .unwrap_or_else(|| node_name(graph, node))
+            };
+            let (status, changes) = compare(
+                old_edge.and_then(|e| old.edge(e)),
+                new_edge.and_then(|e| new.edge(e)),
+            );
+            EdgeDiff {
+                source: name(source),
+                target: name(target),
+                old: old_edge,
+                new: new_edge,
+                status,
+                changes,
+            }
+        })
+        .collect();
+
+    GraphDiff {
+        nodes,
+        edges,
+        directed,
+    }
+}
+
+/// The key of each node slot of `graph`, `None` for nodes which cannot match.
+fn node_keys(graph: &Graph, matching: &Matching) -> Vec<Option<String>> {
+    let mut keys = vec![None; graph.node_bound()];
+    for node in graph.nodes() {
+        keys[node.index()] = match matching {
+            Matching::Id => Some(node_name(graph, node)),
+            Matching::Attribute(key) => graph
+                .node(node)
+                .and_then(|attrs| attrs.get(key.as_str()))
+                .map(AttrValue::to_string),
+        };
+    }
+    keys
+}
+
+/// Pair up old and new items with equal keys, the first old one with the first
+/// new one and so on. Unpaired new items are paired with `None` in place, and
+/// unpaired old items are appended.
+fn pair<T: Copy>(
+    old: impl Iterator<Item = (Option<String>, T)>,
+    new: impl Iterator<Item = (Option<String>, T)>,
+) -> Vec<(Option<T>, Option<T>)> {
+    let mut unmatched: Vec<Option<T>> = Vec::new();
+    let mut by_key: HashMap<String, VecDeque<usize>> = HashMap::new();
+    for (key, item) in old {
+        if let Some(key) = key {
+            by_key.entry(key).or_default().push_back(unmatched.len());
+        }
+        unmatched.push(Some(item));
+    }
+    let mut pairs: Vec<(Option<T>, Option<T>)> = new
+        .map(|(key, item)| {
+            let old = key
+                .and_then(|key| by_key.get_mut(&key)?.pop_front())
+                .and_then(|index| unmatched[index].take());
+            (old, Some(item))
+        })
+        .collect();
+    pairs.extend(
+        unmatched
+            .into_iter()
+            .flatten()
+            .map(|item| (Some(item), None)),
+    );
+    pairs
+}
+
+/// The status of an element with `old` and `new` attributes, and the
+/// attributes which differ if it is in both graphs.
+fn compare(old: Option<&Attributes>, new: Option<&Attributes>) -> (Status, Vec<AttrChange>) {
+    let (old, new) = match (old, new) {
+        (Some(old), Some(new)) => (old, new),
+        (None, _) => return (Status::Added, Vec::new()),
+        (_, None) => return (Status::Removed, Vec::new()),
+    };
+    let mut keys: Vec<&'static str> = old
+        .iter()
+        .chain(new.iter())
+        .map(|(key, _)| key.as_str())
+        // earlier comparisons are not part of the element
+        .filter(|key| *key != DIFF_ATTR && *key != CHANGES_ATTR)
+        .collect();
+    keys.sort_unstable();
+    keys.dedup();
+    let changes: Vec<AttrChange> = keys
+        .into_iter()
+        .filter_map(|key| {
+            let (old, new) = (old.get(key), new.get(key));
+            (old != new).then(|| AttrChange {
+                key: key.into(),
+                old: old.cloned(),
+                new: new.cloned(),
+            })
+        })
+        .collect();
+    match changes.is_empty() {
+        true => (Status::Unchanged, changes),
+        false => (Status::Changed, changes),
+    }
+}
+
+impl Status {
+    pub const ALL: [Status; 4] = [
+        Status::Added,
+        Status::Removed,
+        Status::Changed,
+        Status::Unchanged,
+    ];
+
+    /// The value of [`DIFF_ATTR`] for the status.
+    pub fn label(self) -> &'static str {
+        match self {
+            Status::Added => "added",
+            Status::Removed => "removed",
+            Status::Changed => "changed",
+            Status::Unchanged => "unchanged",
+        }
+    }
+
+    /// The sign prefixing elements with the status in a
+    /// [summary](GraphDiff::summary).
+    fn sign(self) -> char {
+        match self {
+            Status::Added => '+',
+            Status::Removed => '-',
+            Status::Changed => '~',
+            Status::Unchanged => ' ',
+        }
+    }
+}
+
+impl Counts {
+    fn of(statuses: impl Iterator<Item = Status>) -> Self {
+        let mut counts = Self::default();
+        for status in statuses {
+            *match status {
+                Status::Added => &mut counts.added,
+                Status::Removed => &mut counts.removed,
+                Status::Changed => &mut counts.changed,
+                Status::Unchanged => &mut counts.unchanged,
+            } += 1;
+        }
+        counts
+    }
+}
+
+impl GraphDiff {
+    pub fn node_counts(&self) -> Counts {
+        Counts::of(self.nodes.iter().map(|n| n.status))
+    }
+
+    pub fn edge_counts(&self) -> Counts {
+        Counts::of(self.edges.iter().map(|e| e.status))
+    }
+
+    /// Returns `true` if both graphs hold the same elements and attributes.
+    pub fn is_empty(&self) -> bool {
+        let unchanged = |status| status == Status::Unchanged;
+        self.nodes.iter().all(|n| unchanged(n.status))
+            && self.edges.iter().all(|e| unchanged(e.status))
+    }
+
+    /// A graph holding the elements of both `old` and `new`, with their
+    /// [`Status`] in [`DIFF_ATTR`] and what changed in [`CHANGES_ATTR`], and
+    /// positions for it. Elements in the new graph have its attributes and
+    /// positions, removed ones those of the old graph.
+    pub fn merged(
+        &self,
+        (old, old_positions): (&Graph, &Positions),
+        (new, new_positions): (&Graph, &Positions),
+    ) -> (Graph, Positions) {
+        let mut graph = Graph::new(new.directedness());
+        let mut positions = Positions::default();
+        let mut old_nodes = vec![None; old.node_bound()];
+        let mut new_nodes = vec![None; new.node_bound()];
+        for diff in &self.nodes {
+            let (attrs, point) = match (diff.old, diff.new) {
+                (_, Some(n)) => (new.node(n), new_positions.get(n)),
+                (Some(n), None) => (old.node(n), old_positions.get(n)),
+                (None, None) => continue,
+            };
+            let attrs = marked(
+                attrs.cloned().unwrap_or_default(),
+                diff.status,
+                &diff.changes,
+            );
+            let node = graph.add_node_with(attrs);
+            if let Some(point) = point {
+                positions.set(node, point);
+            }
+            if let Some(n) = diff.old {
+                old_nodes[n.index()] = Some(node);
+            }
+            if let Some(n) = diff.new {
+                new_nodes[n.index()] = Some(node);
+            }
+        }
+        for diff in &self.edges {
+            let (graph_from, nodes, edge) = match (diff.old, diff.new) {
+                (_, Some(e)) => (new, &new_nodes, e),
+                (Some(e), None) => (old, &old_nodes, e),
+                (None, None) => continue,
+            };
+            let Some((source, target)) = graph_from.endpoints(edge) else {
+                continue;
+            };
+            let (Some(source), Some(target)) = (nodes[source.index()], nodes[target.index()])
+            else {
+                continue;
+            };
+            let attrs = graph_from.edge(edge).cloned().unwrap_or_default();
+            let attrs = marked(attrs, diff.status, &diff.changes);
+            graph.add_edge_with(source, target, attrs).ok();
+        }
+        positions.sync(&graph);
+        (graph, positions)
+    }
+
+    /// The differences as text: counts, then one line per added, removed, or
+    /// changed element.
+    pub fn summary(&self) -> String {
+        let mut text = format!(
+            "Nodes: {}\nEdges: {}\n",
+            self.node_counts(),
+            self.edge_counts()
+        );
+        if self.is_empty() {
+            text.push_str("\nNo differences\n");
+            return text;
+        }
+        let arrow = if self.directed { "->" } else { "--" };
+        for status in [Status::Added, Status::Removed, Status::Changed] {
+            let nodes: Vec<&NodeDiff> = self.nodes.iter().filter(|n| n.status == status).collect();
+            if !nodes.is_empty() {
+                text.push_str(&format!("\n{} nodes:\n", capitalized(status.label())));
+            }
+            for node in nodes {
+                text.push_str(&format!("{} {}\n", status.sign(), node.key));
+                push_changes(&mut text, &node.changes);
+            }
+            let edges: Vec<&EdgeDiff> = self.edges.iter().filter(|e| e.status == status).collect();
+            if !edges.is_empty() {
+                text.push_str(&format!("\n{} edges:\n", capitalized(status.label())));
+            }
+            for edge in edges {
+                let sign = status.sign();
+                text.push_str(&format!("{sign} {} {arrow} {}\n", edge.source, edge.target));
+                push_changes(&mut text, &edge.changes);
+            }
+        }
+        text
+    }
+}
+
+/// `attrs` with the [`DIFF_ATTR`] and [`CHANGES_ATTR`] of an element.
+fn marked(mut attrs: Attributes, status: Status, changes: &[AttrChange]) -> Attributes {
+    attrs.insert(DIFF_ATTR, status.label());
+    if changes.is_empty() {
+        attrs.remove(CHANGES_ATTR);
+    } else {
+        let keys: Vec<&str> = changes.iter().map(|c| c.key.as_str()).collect();
+        attrs.insert(CHANGES_ATTR, keys.join(", "));
+    }
+    attrs
+}
+
+fn push_changes(text: &mut String, changes: &[AttrChange]) {
+    let value = |value: &Option<AttrValue>| match value {
+        Some(value) => format!("\"{value}\""),
+        None => "(unset)".into(),
+    };
+    for change in changes {
+        text.push_str(&format!(
+            "    {}: {} -> {}\n",
+            change.key,
+            value(&change.old),
+            value(&change.new)
+        ));
+    }
+}
+
+fn capitalized(text: &str) -> String {
+    let mut chars = text.chars();
+    chars
+        .next()
+        .map(|c| c.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+/// Compares the active document with another open one, opening the
+/// [merged](GraphDiff::merged) graph as a new document colored by status.
+pub struct DiffPanel {
+    /// [`Document::id()`](crate::document::Document::id) of the document to
+    /// compare with, the old version.
+    other: Option<u64>,
+    /// Node attribute to match by, `None` matching by id.
+    key: Option<String>,
+    /// Summary of the last comparison.
+    summary: Option<String>,
+    /// File to export the summary to.
+    export_path: String,
+    /// Outcome of the last export, and whether it failed.
+    status: Option<(String, bool)>,
+}
+
+impl DiffPanel {
+    pub fn new() -> Self {
+        Self {
+            other: None,
+            key: None,
+            summary: None,
+            export_path: "changes.txt".into(),
+            status: None,
+        }
+    }
+
+    /// Compare the document at `index` to the active one.
+    fn compare(&mut self, ctx: &mut EngineContext, index: usize) {
+        let Some(stash) = ctx.documents.stash(index) else {
+            return;
+        };
+        let matching = match &self.key {
+            Some(key) => Matching::Attribute(key.clone()),
+            None => Matching::Id,
+        };
+        ctx.force.sync_positions(&mut ctx.positions);
+        let diff = diff(&stash.graph, &ctx.graph, &matching);
+        let (graph, positions) = diff.merged(
+            (&stash.graph, &stash.positions),
+            (&ctx.graph, &ctx.positions),
+        );
+        let documents = ctx.documents.documents();
+        self.summary = Some(format!(
+            "Changes from '{}' to '{}'\n\n{}",
+            documents[index].title(),
+            ctx.documents.active_document().title(),
+            diff.summary()
+        ));
+        self.status = None;
+
+        ctx.open_document(graph, None);
+        ctx.positions = positions;
+        ctx.force.graph_changed(&mut ctx.positions);
+        // keep both versions where they were laid out
+        ctx.force.set_paused(true);
+        let mut sheet = ctx.style.sheet().clone();
+        sheet.node_color = ColorMapping::Categories {
+            attribute: DIFF_ATTR.into(),
+        };
+        sheet.edge_color = ColorMapping::Categories {
+            attribute: DIFF_ATTR.into(),
+        };
+        ctx.execute(Command::SetStyle(Box::new(sheet)));
+        ctx.camera.fit_to_graph(&ctx.graph, &ctx.positions);
+    }
+}
+
+impl Panel for DiffPanel {
+    fn id(&self) -> &'static str {
+        "Compare"
+    }
+
+    fn draw(&mut self, ui: &Ui, ctx: &mut EngineContext) {
+        let active = ctx.documents.active();
+        let others: Vec<(usize, u64, String)> = ctx
+            .documents
+            .documents()
+            .iter()
+            .enumerate()
+            .filter(|&(index, _)| index != active)
+            .map(|(index, document)| (index, document.id(), document.title()))
+            .collect();
+        let selected = others.iter().find(|(_, id, _)| Some(*id) == self.other);
+        if selected.is_none() {
+            self.other = others.first().map(|(_, id, _)| *id);
+        }
+        let selected = others.iter().find(|(_, id, _)| Some(*id) == self.other);
+
+        if others.is_empty() {
+            ui.text_disabled(tr("Open another version of the graph to compare with"));
+        }
+        let preview = selected.map_or("", |(_, _, title)| title.as_str());
+        if let Some(_combo) = ui.begin_combo(tr("Compare With"), preview) {
+            for (_, id, title) in &others {
+                if ui
+                    .selectable_config(format!("{title}##{id}"))
+                    .selected(self.other == Some(*id))
+                    .build()
+                {
+                    self.other = Some(*id);
+                }
+            }
+        }
+        let preview = self.key.as_deref().unwrap_or("(id)");
+        if let Some(_combo) = ui.begin_combo(tr("Match By"), preview) {
+            if ui
+                .selectable_config(tr("(id)"))
+                .selected(self.key.is_none())
+                .build()
+            {
+                self.key = None;
+            }
+            for name in ctx.style.node_attributes() {
+                if ui
+                    .selectable_config(name)
+                    .selected(self.key.as_ref() == Some(name))
+                    .build()
+                {
+                    self.key = Some(name.clone());
+                }
+            }
+        }
+        let index = selected.map(|(index, _, _)| *index);
+        ui.enabled(index.is_some(), || {
+            if ui.button(tr("Compare")) {
+                self.compare(ctx, index.unwrap());
+            }
+        });
+
+        let Some(summary) = &self.summary else {
+            return;
+        };
+        ui.separator();
+        if ui.button(tr("Copy Summary")) {
+            ui.set_clipboard_text(summary);
+        }
+        ui.input_text("##export", &mut self.export_path).build();
+        ui.same_line();
+        if ui.button(tr("Export Summary")) {
+            self.status = Some(match std::fs::write(&self.export_path, summary) {
+                Ok(()) => (format!("Exported to '{}'", self.export_path), false),
+                Err(e) => (format!("Export failed: {e}"), true),
+            });
+        }
+        match &self.status {
+            Some((error, true)) => ui.text_colored([1.0, 0.4, 0.4, 1.0], error),
+            Some((status, false)) => ui.text_disabled(status),
+            None => {}
+        }
+        ui.child_window("##summary")
+            .horizontal_scrollbar(true)
+            .build(|| ui.text(summary));
+    }
+}
+
+impl std::fmt::Display for Counts {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} added, {} removed, {} changed, {} unchanged",
+            self.added, self.removed, self.changed, self.unchanged
+        )
+    }
+}
+
+impl std::default::Default for DiffPanel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,490 @@
+//! Node metrics: how central or clustered each node is.
+//!
+//! Each metric is a plain function returning one score per node slot, indexed
+//! by [`NodeId::index()`], which reports its progress and stops early once
+//! cancelled through a [`Progress`]. A [`MetricsJob`] computes several of them
+//! as a [background job](crate::jobs), then writes them back as node attributes named after
+//! [`Metric::attribute()`] through an undoable [`Command`], where they can drive
+//! styles, searches, and filters.
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::context::EngineContext;
+use crate::graph::{AttrValue, Graph, NodeId};
+use crate::history::Command;
+use crate::i18n::tr;
+use crate::imgui::{ProgressBar, Ui};
+use crate::jobs::{JobHandle, JobPool, Priority};
+use crate::subsystems::panels::Panel;
+
+pub use crate::jobs::Progress;
+
+/// Damping factor of [`pagerank()`], the chance of following an edge rather
+/// than jumping to a random node.
+const PAGERANK_DAMPING: f64 = 0.85;
+/// Total change in scores below which [`pagerank()`] stops iterating.
+const PAGERANK_TOLERANCE: f64 = 1e-9;
+const PAGERANK_MAX_ITERATIONS: usize = 200;
+
+/// A per-node metric.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Metric {
+    Degree,
+    Betweenness,
+    Closeness,
+    PageRank,
+    Clustering,
+}
+
+/// Metrics being computed by a [job](crate::jobs), see [`MetricsJob::spawn()`].
+pub struct MetricsJob {
+    metrics: Vec<Metric>,
+    progress: Arc<Progress>,
+    /// Index of the metric being computed.
+    current: Arc<AtomicUsize>,
+    handle: Option<JobHandle<Vec<Vec<f64>>>>,
+    start: Instant,
+}
+
+/// Computes metrics of the [visible graph](EngineContext::visible_graph) into
+/// [`EngineContext::metrics`].
+pub struct MetricsPanel {
+    /// Metrics to compute, in the order of [`Metric::ALL`].
+    picked: [bool; Metric::ALL.len()],
+}
+
+/// Metrics computed by a [`MetricsJob`].
+#[derive(Clone, Debug)]
+pub struct MetricsResult {
+    /// Each metric with its scores, indexed by [`NodeId::index()`].
+    pub scores: Vec<(Metric, Vec<f64>)>,
+    /// How long computing them took.
+    pub elapsed: Duration,
+}
+
+impl Metric {
+    pub const ALL: [Metric; 5] = [
+        Self::Degree,
+        Self::Betweenness,
+        Self::Closeness,
+        Self::PageRank,
+        Self::Clustering,
+    ];
+
+    /// Human-readable name of the metric.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Degree => "Degree",
+            Self::Betweenness => "Betweenness",
+            Self::Closeness => "Closeness",
+            Self::PageRank => "PageRank",
+            Self::Clustering => "Clustering Coefficient",
+        }
+    }
+
+    /// Name of the node attribute the metric is written to.
+    pub fn attribute(self) -> &'static str {
+        match self {
+            Self::Degree => "degree",
+            Self::Betweenness => "betweenness",
+            Self::Closeness => "closeness",
+            Self::PageRank => "pagerank",
+            Self::Clustering => "clustering",
+        }
+    }
+
+    /// Compute the metric for every node of `graph`, see the function of the
+    /// same name.
+    pub fn compute(self, graph: &Graph, progress: &Progress) -> Vec<f64> {
+        match self {
+            Self::Degree => degree(graph),
+            Self::Betweenness => betweenness(graph, progress),
+            Self::Closeness => closeness(graph, progress),
+            Self::PageRank => pagerank(graph, progress),
+            Self::Clustering => clustering(graph, progress),
+        }
+    }
+}
+
+impl MetricsJob {
+    /// Start computing `metrics` for a copy of `graph` as a job of `jobs`.
+    pub fn spawn(jobs: &JobPool, graph: &Graph, metrics: &[Metric]) -> Self {
+        let graph = graph.clone();
+        let progress = Arc::new(Progress::new());
+        let shared = progress.clone();
+        let list = metrics.to_vec();
+        let current = Arc::new(AtomicUsize::new(0));
+        let step = current.clone();
+        let labels: Vec<_> = metrics.iter().map(|m| m.label()).collect();
+        let name = format!("Metrics: {}", labels.join(", "));
+        let handle = jobs.spawn(name, Priority::Low, &progress, move || {
+            let mut scores = Vec::new();
+            for (i, metric) in list.iter().enumerate() {
+                if shared.is_cancelled() {
+                    break;
+                }
+                shared.set(0.0);
+                step.store(i, Ordering::Relaxed);
+                scores.push(metric.compute(&graph, &shared));
+            }
+            scores
+        });
+        Self {
+            metrics: metrics.to_vec(),
+            progress,
+            current,
+            handle: Some(handle),
+            start: Instant::now(),
+        }
+    }
+
+    /// The metrics being computed.
+    pub fn metrics(&self) -> &[Metric] {
+        &self.metrics
+    }
+
+    /// The metric being computed.
+    pub fn current(&self) -> Metric {
+        self.metrics[self.current.load(Ordering::Relaxed)]
+    }
+
+    /// Fraction of the work done over all metrics, from `0.0` to `1.0`.
+    pub fn progress(&self) -> f32 {
+        let done = self.current.load(Ordering::Relaxed) as f32 + self.progress.fraction();
+        done / self.metrics.len().max(1) as f32
+    }
+
+    /// Stop the computation; [`Self::try_finish()`] then returns no scores.
+    pub fn cancel(&self) {
+        self.progress.cancel();
+    }
+
+    /// Returns `true` if [`Self::cancel()`] was called.
+    pub fn is_cancelled(&self) -> bool {
+        self.progress.is_cancelled()
+    }
+
+    /// The computed metrics, once the worker is done. Returns `None` while it
+    /// runs, and empty scores if it was cancelled.
+    pub fn try_finish(&mut self) -> Option<MetricsResult> {
+        if !self.handle.as_ref()?.is_finished() {
+            return None;
+        }
+        let scores = self.handle.take()?.join().unwrap_or_default();
+        let scores = match self.is_cancelled() {
+            true => Vec::new(),
+            false => self.metrics.iter().copied().zip(scores).collect(),
+        };
+        Some(MetricsResult {
+            scores,
+            elapsed: self.start.elapsed(),
+        })
+    }
+}
+
+impl MetricsResult {
+    /// A command writing each metric to its [attribute](Metric::attribute) on
+    /// the nodes of `graph` it was computed for.
+    pub fn command(&self, graph: &Graph) -> Command {
+        let commands = self
+            .scores
+            .iter()
+            .map(|(metric, scores)| {
+                Command::set_node_attribute(
+                    metric.attribute(),
+                    graph.nodes().filter_map(|node| {
+                        let score = *scores.get(node.index())?;
+                        score.is_finite().then_some((node, AttrValue::Float(score)))
+                    }),
+                )
+            })
+            .collect();
+        Command::Batch {
+            label: "Compute Metrics".into(),
+            commands,
+        }
+    }
+}
+
+/// Number of edges attached to each node, counting self-loops twice.
+pub fn degree(graph: &Graph) -> Vec<f64> {
+    let mut scores = vec![f64::NAN; graph.node_bound()];
+    for node in graph.nodes() {
+        scores[node.index()] = graph.degree(node) as f64;
+    }
+    scores
+}
+
+/// Fraction of shortest paths between other nodes passing through each node,
+/// with Brandes' algorithm, following edge directions and counting hops.
+///
+/// Normalized to `0.0..=1.0` by the number of node pairs not involving the node.
+pub fn betweenness(graph: &Graph, progress: &Progress) -> Vec<f64> {
+    let bound = graph.node_bound();
+    let mut scores = vec![f64::NAN; bound];
+    let nodes: Vec<NodeId> = graph.nodes().collect();
+    for &node in &nodes {
+        scores[node.index()] = 0.0;
+    }
+
+    let mut order: Vec<NodeId> = Vec::with_capacity(nodes.len());
+    let mut preds: Vec<Vec<NodeId>> = vec![Vec::new(); bound];
+    let mut paths = vec![0.0; bound];
+    let mut dist = vec![u32::MAX; bound];
+    let mut delta = vec![0.0; bound];
+    let mut queue = VecDeque::new();
+    for (i, &source) in nodes.iter().enumerate() {
+        if progress.is_cancelled() {
+            break;
+        }
+        for &node in &order {
+            preds[node.index()].clear();
+            paths[node.index()] = 0.0;
+            dist[node.index()] = u32::MAX;
+            delta[node.index()] = 0.0;
+        }
+        order.clear();
+
+        paths[source.index()] = 1.0;
+        dist[source.index()] = 0;
+        queue.push_back(source);
+        while let Some(node) = queue.pop_front() {
+            order.push(node);
+            for next in graph.neighbors(node) {
+                if dist[next.index()] == u32::MAX {
+                    dist[next.index()] = dist[node.index()] + 1;
+                    queue.push_back(next);
+                }
+                if dist[next.index()] == dist[node.index()] + 1 {
+                    paths[next.index()] += paths[node.index()];
+                    preds[next.index()].push(node);
+                }
+            }
+        }
+        for &node in order.iter().rev() {
+            for &pred in &preds[node.index()] {
+                delta[pred.index()] +=
+                    paths[pred.index()] / paths[node.index()] * (1.0 + delta[node.index()]);
+            }
+            if node != source {
+                scores[node.index()] += delta[node.index()];
+            }
+        }
+        progress.set((i + 1) as f32 / nodes.len() as f32);
+    }
+
+    // undirected paths are found from both of their ends, as are the pairs
+    // they are normalized by, so both kinds of graph scale the same
+    let n = nodes.len() as f64;
+    let pairs = (n - 1.0) * (n - 2.0);
+    let scale = match pairs > 0.0 {
+        true => 1.0 / pairs,
+        false => 0.0,
+    };
+    for &node in &nodes {
+        scores[node.index()] *= scale;
+    }
+    scores
+}
+
+/// How close each node is to the nodes it can reach, following edge directions:
+/// the inverse of the average distance in hops, scaled by the fraction of
+/// other nodes it reaches so nodes in small components do not score high.
+pub fn closeness(graph: &Graph, progress: &Progress) -> Vec<f64> {
+    let mut scores = vec![f64::NAN; graph.node_bound()];
+    let nodes: Vec<NodeId> = graph.nodes().collect();
+    let others = nodes.len().saturating_sub(1) as f64;
+    for (i, &source) in nodes.iter().enumerate() {
+        if progress.is_cancelled() {
+            break;
+        }
+        let (mut reached, mut total) = (0.0, 0.0);
+        for visit in super::Bfs::new(graph, source, super::Direction::Forward).skip(1) {
+            reached += 1.0;
+            total += visit.depth as f64;
+        }
+        scores[source.index()] = match total > 0.0 {
+            true => (reached / others) * (reached / total),
+            false => 0.0,
+        };
+        if i % 64 == 0 {
+            progress.set(i as f32 / nodes.len() as f32);
+        }
+    }
+    scores
+}
+
+/// Stationary probability of a random walk following edge directions being at
+/// each node, jumping to a random node instead with a chance of
+/// `1 - PAGERANK_DAMPING`. Nodes without outgoing edges jump to any node.
+///
+/// Scores sum to `1.0`.
+pub fn pagerank(graph: &Graph, progress: &Progress) -> Vec<f64> {
+    let nodes: Vec<NodeId> = graph.nodes().collect();
+    let n = nodes.len() as f64;
+    let mut scores = vec![f64::NAN; graph.node_bound()];
+    if nodes.is_empty() {
+        return scores;
+    }
+    let mut out_degree = vec![0; graph.node_bound()];
+    for &node in &nodes {
+        out_degree[node.index()] = graph.neighbors(node).count();
+    }
+
+    let mut rank = vec![0.0; graph.node_bound()];
+    let mut next = vec![0.0; graph.node_bound()];
+    for &node in &nodes {
+        rank[node.index()] = 1.0 / n;
+    }
+    for iteration in 0..PAGERANK_MAX_ITERATIONS {
+        if progress.is_cancelled() {
+            break;
+        }
+        let dangling: f64 = nodes
+            .iter()
+            .filter(|node| out_degree[node.index()] == 0)
+            .map(|node| rank[node.index()])
+            .sum();
+        let base = (1.0 - PAGERANK_DAMPING) / n + PAGERANK_DAMPING * dangling / n;
+        for &node in &nodes {
+            next[node.index()] = base;
+        }
+        for &node in &nodes {
+            let degree = out_degree[node.index()];
+            if degree == 0 {
+                continue;
+            }
+            let share = PAGERANK_DAMPING * rank[node.index()] / degree as f64;
+            for target in graph.neighbors(node) {
+                next[target.index()] += share;
+            }
+        }
+        let change: f64 = nodes
+            .iter()
+            .map(|node| (next[node.index()] - rank[node.index()]).abs())
+            .sum();
+        std::mem::swap(&mut rank, &mut next);
+        progress.set((iteration + 1) as f32 / PAGERANK_MAX_ITERATIONS as f32);
+        if change < PAGERANK_TOLERANCE {
+            break;
+        }
+    }
+    for &node in &nodes {
+        scores[node.index()] = rank[node.index()];
+    }
+    scores
+}
+
+/// Fraction of the pairs of each node's neighbors which are themselves
+/// adjacent, ignoring edge directions, self-loops, and parallel edges.
+///
+/// Nodes with fewer than two neighbors score `0.0`.
+pub fn clustering(graph: &Graph, progress: &Progress) -> Vec<f64> {
+    let mut scores = vec![f64::NAN; graph.node_bound()];
+    let nodes: Vec<NodeId> = graph.nodes().collect();
+
+    let mut adjacent: Vec<Vec<NodeId>> = vec![Vec::new(); graph.node_bound()];
+    for &node in &nodes {
+        let list = &mut adjacent[node.index()];
+        list.extend(
+            graph
+                .incident_edges(node)
+                .filter_map(|e| graph.opposite(e, node))
+                .filter(|&other| other != node),
+        );
+        list.sort_unstable();
+        list.dedup();
+    }
+
+    for (i, &node) in nodes.iter().enumerate() {
+        if progress.is_cancelled() {
+            break;
+        }
+        let neighbors = &adjacent[node.index()];
+        let k = neighbors.len();
+        if k < 2 {
+            scores[node.index()] = 0.0;
+            continue;
+        }
+        let mut links = 0;
+        for (j, &a) in neighbors.iter().enumerate() {
+            for &b in &neighbors[j + 1..] {
+                if adjacent[a.index()].binary_search(&b).is_ok() {
+                    links += 1;
+                }
+            }
+        }
+        scores[node.index()] = links as f64 / (k * (k - 1) / 2) as f64;
+        if i % 256 == 0 {
+            progress.set(i as f32 / nodes.len() as f32);
+        }
+    }
+    scores
+}
+
+impl MetricsPanel {
+    /// Create a panel computing degree and PageRank.
+    pub fn new() -> Self {
+        Self {
+            picked: Metric::ALL.map(|m| matches!(m, Metric::Degree | Metric::PageRank)),
+        }
+    }
+}
+
+impl Panel for MetricsPanel {
+    fn id(&self) -> &'static str {
+        "Metrics"
+    }
+
+    fn draw(&mut self, ui: &Ui, ctx: &mut EngineContext) {
+        let running = ctx.metrics.is_some();
+        ui.enabled(!running, || {
+            for (metric, picked) in Metric::ALL.iter().zip(&mut self.picked) {
+                ui.checkbox(metric.label(), picked);
+                if ui.is_item_hovered() {
+                    ui.tooltip_text(
+                        tr("Written to the \"{attribute}\" attribute")
+                            .replace("{attribute}", metric.attribute()),
+                    );
+                }
+            }
+        });
+
+        match &ctx.metrics {
+            Some(job) => {
+                let label = match job.is_cancelled() {
+                    true => "Cancelling...".to_string(),
+                    false => job.current().label().to_string(),
+                };
+                ProgressBar::new(job.progress())
+                    .overlay_text(label)
+                    .build(ui);
+                if ui.button(tr("Cancel")) {
+                    job.cancel();
+                }
+            }
+            None => {
+                let metrics: Vec<Metric> = Metric::ALL
+                    .into_iter()
+                    .zip(self.picked)
+                    .filter_map(|(m, picked)| picked.then_some(m))
+                    .collect();
+                ui.enabled(!metrics.is_empty(), || {
+                    if ui.button(tr("Compute")) {
+                        let job = MetricsJob::spawn(&ctx.jobs, ctx.visible_graph(), &metrics);
+                        ctx.metrics = Some(job);
+                    }
+                });
+            }
+        }
+    }
+}
+
+impl std::default::Default for MetricsPanel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,218 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashSet};
+
+use super::{Direction, steps};
+use crate::graph::{EdgeId, Graph, NodeId};
+
+/// A path through a graph, see [`shortest_path()`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Path {
+    /// Nodes along the path, from the start to the goal.
+    pub nodes: Vec<NodeId>,
+    /// Edges along the path; one fewer than the nodes.
+    pub edges: Vec<EdgeId>,
+    /// Sum of the weights of the edges.
+    pub cost: f64,
+}
+
+/// A node waiting to be expanded, ordered so the heap pops the lowest estimate.
+#[derive(Copy, Clone, Debug)]
+struct Open {
+    estimate: f64,
+    node: NodeId,
+}
+
+/// The cheapest path from `from` to `to` along the direction of edges, found
+/// with Dijkstra's algorithm.
+///
+/// Edges cost their numeric `weight` attribute, or `1.0` with no attribute
+/// given. Edges without the attribute, or with a negative one, also cost `1.0`.
+/// Returns `None` if `to` cannot be reached.
+pub fn shortest_path(
+    graph: &Graph,
+    from: NodeId,
+    to: NodeId,
+    weight: Option<&str>,
+) -> Option<Path> {
+    astar(graph, from, to, weight, |_| 0.0)
+}
+
+/// The cheapest path from `from` to `to`, like [`shortest_path()`], guided by
+/// `heuristic`'s estimate of the cost from a node to `to`.
+///
+/// The path is only guaranteed to be the cheapest if the heuristic never
+/// overestimates, eg. the straight-line distance when edges are weighted by
+/// their length.
+pub fn astar(
+    graph: &Graph,
+    from: NodeId,
+    to: NodeId,
+    weight: Option<&str>,
+    heuristic: impl Fn(NodeId) -> f64,
+) -> Option<Path> {
+    search(graph, from, to, weight, heuristic, |_, _| false)
+}
+
+/// Up to `k` of the cheapest paths from `from` to `to`, cheapest first, found
+/// with Yen's algorithm. Edges cost as in [`shortest_path()`].
+///
+/// Paths never visit a node twice, and differ by at least one edge, so
+/// parallel edges make paths of their own.
+pub fn k_shortest_paths(
+    graph: &Graph,
+    from: NodeId,
+    to: NodeId,
+    k: usize,
+    weight: Option<&str>,
+) -> Vec<Path> {
+    let mut found: Vec<Path> = shortest_path(graph, from, to, weight)
+        .into_iter()
+        .take(k)
+        .collect();
+    let mut candidates: Vec<Path> = Vec::new();
+    while found.len() < k {
+        let Some(last) = found.last().cloned() else {
+            break;
+        };
+        for i in 0..last.edges.len() {
+            let (root_nodes, root_edges) = (&last.nodes[..=i], &last.edges[..i]);
+            // leave the spur node other than along the paths sharing its root
+            let taken: HashSet<EdgeId> = found
+                .iter()
+                .filter(|path| path.edges.len() > i && path.edges[..i] == *root_edges)
+                .map(|path| path.edges[i])
+                .collect();
+            let visited: HashSet<NodeId> = root_nodes[..i].iter().copied().collect();
+            let Some(spur) = search(
+                graph,
+                root_nodes[i],
+                to,
+                weight,
+                |_| 0.0,
+                |edge, next| taken.contains(&edge) || visited.contains(&next),
+            ) else {
+                continue;
+            };
+            let mut path = Path {
+                nodes: root_nodes[..i].to_vec(),
+                edges: root_edges.to_vec(),
+                cost: root_edges
+                    .iter()
+                    .map(|&e| edge_cost(graph, e, weight))
+                    .sum(),
+            };
+            path.nodes.extend(spur.nodes);
+            path.edges.extend(spur.edges);
+            path.cost += spur.cost;
+            let known = |other: &Path| other.edges == path.edges;
+            if !found.iter().any(known) && !candidates.iter().any(known) {
+                candidates.push(path);
+            }
+        }
+        let cheapest = candidates
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| a.cost.total_cmp(&b.cost))
+            .map(|(i, _)| i);
+        match cheapest {
+            Some(i) => found.push(candidates.remove(i)),
+            None => break,
+        }
+    }
+    found
+}
+
+/// Cost of crossing `edge`: its numeric `weight` attribute, or `1.0` if it
+/// has none or a negative one.
+fn edge_cost(graph: &Graph, edge: EdgeId, weight: Option<&str>) -> f64 {
+    weight
+        .and_then(|name| graph.edge(edge)?.get(name)?.as_f64())
+        .filter(|w| *w >= 0.0)
+        .unwrap_or(1.0)
+}
+
+/// A* search from `from` to `to`, never following an edge to a node for which
+/// `blocked(edge, node)` is `true`.
+fn search(
+    graph: &Graph,
+    from: NodeId,
+    to: NodeId,
+    weight: Option<&str>,
+    heuristic: impl Fn(NodeId) -> f64,
+    blocked: impl Fn(EdgeId, NodeId) -> bool,
+) -> Option<Path> {
+    if !graph.contains_node(from) || !graph.contains_node(to) {
+        return None;
+    }
+    let cost_of = |edge: EdgeId| edge_cost(graph, edge, weight);
+
+    let mut cost = vec![f64::INFINITY; graph.node_bound()];
+    let mut via: Vec<Option<EdgeId>> = vec![None; graph.node_bound()];
+    let mut open = BinaryHeap::new();
+    cost[from.index()] = 0.0;
+    open.push(Open {
+        estimate: heuristic(from),
+        node: from,
+    });
+    while let Some(Open { estimate, node }) = open.pop() {
+        if node == to {
+            break;
+        }
+        // skip entries made stale by a cheaper path found since
+        if estimate > cost[node.index()] + heuristic(node) {
+            continue;
+        }
+        for (edge, next) in steps(graph, node, Direction::Forward) {
+            if blocked(edge, next) {
+                continue;
+            }
+            let next_cost = cost[node.index()] + cost_of(edge);
+            if next_cost < cost[next.index()] {
+                cost[next.index()] = next_cost;
+                via[next.index()] = Some(edge);
+                open.push(Open {
+                    estimate: next_cost + heuristic(next),
+                    node: next,
+                });
+            }
+        }
+    }
+    if cost[to.index()].is_infinite() {
+        return None;
+    }
+
+    let mut path = Path {
+        nodes: vec![to],
+        edges: Vec::new(),
+        cost: cost[to.index()],
+    };
+    let mut node = to;
+    while let Some(edge) = via[node.index()] {
+        node = graph.opposite(edge, node)?;
+        path.edges.push(edge);
+        path.nodes.push(node);
+    }
+    path.nodes.reverse();
+    path.edges.reverse();
+    Some(path)
+}
+
+impl PartialEq for Open {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Open {}
+
+impl PartialOrd for Open {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Open {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.estimate.total_cmp(&self.estimate)
+    }
+}
@@ -0,0 +1,67 @@
+use crate::graph::{Graph, NodeId};
+
+/// Errors which can occur while sorting a graph topologically.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TopologicalError {
+    /// Only directed graphs have a topological order.
+    Undirected,
+    /// The graph has a cycle; holds every node on a cycle or reachable from one,
+    /// in id order.
+    Cycle(Vec<NodeId>),
+}
+
+/// Order the nodes of a directed graph so every edge points from an earlier
+/// node to a later one, with Kahn's algorithm.
+///
+/// Among nodes free to go next, lower ids go first, so the order is stable.
+pub fn topological_sort(graph: &Graph) -> Result<Vec<NodeId>, TopologicalError> {
+    if !graph.is_directed() {
+        return Err(TopologicalError::Undirected);
+    }
+
+    let mut remaining = vec![0; graph.node_bound()];
+    let mut ready = std::collections::BinaryHeap::new();
+    for node in graph.nodes() {
+        remaining[node.index()] = graph.in_degree(node);
+        if remaining[node.index()] == 0 {
+            ready.push(std::cmp::Reverse(node));
+        }
+    }
+
+    let mut order = Vec::with_capacity(graph.node_count());
+    while let Some(std::cmp::Reverse(node)) = ready.pop() {
+        order.push(node);
+        for next in graph.successors(node) {
+            remaining[next.index()] -= 1;
+            if remaining[next.index()] == 0 {
+                ready.push(std::cmp::Reverse(next));
+            }
+        }
+    }
+
+    if order.len() < graph.node_count() {
+        let cyclic = graph
+            .nodes()
+            .filter(|node| remaining[node.index()] > 0)
+            .collect();
+        return Err(TopologicalError::Cycle(cyclic));
+    }
+    Ok(order)
+}
+
+impl std::fmt::Display for TopologicalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Undirected => write!(f, "Only directed graphs can be sorted topologically"),
+            Self::Cycle(nodes) => {
+                write!(
+                    f,
+                    "The graph has a cycle, {} nodes cannot be ordered",
+                    nodes.len()
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for TopologicalError {}
@@ -0,0 +1,123 @@
+use std::collections::VecDeque;
+
+use super::{Direction, steps};
+use crate::graph::{EdgeId, Graph, NodeId};
+
+/// A node reached by a traversal.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Visit {
+    pub node: NodeId,
+    /// The edge the node was first reached by, `None` for the start node.
+    pub edge: Option<EdgeId>,
+    /// Number of edges between the start node and this one, along the
+    /// traversal's tree.
+    pub depth: usize,
+}
+
+/// Breadth-first traversal, visiting nodes in order of their distance in hops
+/// from the start node.
+pub struct Bfs<'a> {
+    graph: &'a Graph,
+    direction: Direction,
+    queue: VecDeque<Visit>,
+    seen: Vec<bool>,
+}
+
+/// Depth-first traversal, visiting nodes in preorder.
+pub struct Dfs<'a> {
+    graph: &'a Graph,
+    direction: Direction,
+    stack: Vec<Visit>,
+    seen: Vec<bool>,
+}
+
+impl<'a> Bfs<'a> {
+    /// Traverse `graph` from `start`, following edges in `direction`.
+    ///
+    /// Visits nothing if `start` does not exist.
+    pub fn new(graph: &'a Graph, start: NodeId, direction: Direction) -> Self {
+        let mut seen = vec![false; graph.node_bound()];
+        let mut queue = VecDeque::new();
+        if graph.contains_node(start) {
+            seen[start.index()] = true;
+            queue.push_back(Visit {
+                node: start,
+                edge: None,
+                depth: 0,
+            });
+        }
+        Self {
+            graph,
+            direction,
+            queue,
+            seen,
+        }
+    }
+}
+
+impl<'a> Dfs<'a> {
+    /// Traverse `graph` from `start`, following edges in `direction`.
+    ///
+    /// Visits nothing if `start` does not exist.
+    pub fn new(graph: &'a Graph, start: NodeId, direction: Direction) -> Self {
+        let mut stack = Vec::new();
+        if graph.contains_node(start) {
+            stack.push(Visit {
+                node: start,
+                edge: None,
+                depth: 0,
+            });
+        }
+        Self {
+            graph,
+            direction,
+            stack,
+            seen: vec![false; graph.node_bound()],
+        }
+    }
+}
+
+impl Iterator for Bfs<'_> {
+    type Item = Visit;
+
+    fn next(&mut self) -> Option<Visit> {
+        let visit = self.queue.pop_front()?;
+        for (edge, node) in steps(self.graph, visit.node, self.direction) {
+            if !std::mem::replace(&mut self.seen[node.index()], true) {
+                self.queue.push_back(Visit {
+                    node,
+                    edge: Some(edge),
+                    depth: visit.depth + 1,
+                });
+            }
+        }
+        Some(visit)
+    }
+}
+
+impl Iterator for Dfs<'_> {
+    type Item = Visit;
+
+    fn next(&mut self) -> Option<Visit> {
+        // nodes can be pushed several times before being visited; the first pop wins
+        while let Some(visit) = self.stack.pop() {
+            if std::mem::replace(&mut self.seen[visit.node.index()], true) {
+                continue;
+            }
+            let first = self.stack.len();
+            for (edge, node) in steps(self.graph, visit.node, self.direction) {
+                if !self.seen[node.index()] {
+                    self.stack.push(Visit {
+                        node,
+                        edge: Some(edge),
+                        depth: visit.depth + 1,
+                    });
+                }
+            }
+            // visit neighbors in edge order
+            self.stack[first..].reverse();
+            return Some(visit);
+        }
+        None
+    }
+}
@@ -0,0 +1,357 @@
+//! # Animation
+//!
+//! A [`Tween`] moves a value from one to another over a duration, along an
+//! [`Easing`] curve, for anything which can be interpolated ([`Lerp`]):
+//! numbers, such as opacities, points, colors, and camera views.
+//!
+//! The [`EngineContext::animations`] are the engine's own, advanced once per
+//! frame by [`update()`]:
+//! - layout transitions: [`EngineContext::apply_layout()`] sets the final
+//!   positions right away, and nodes then glide there from where they were
+//! - fades of the opacity of nodes, see [`Animations::fade_node()`]
+//! - flashes of the color of nodes, eg. to point one out, see
+//!   [`Animations::flash_node()`]
+//!
+//! The camera's moves, eg. [`CameraController::fit_to_graph()`](crate::subsystems::scene::CameraController::fit_to_graph),
+//! are animated by the camera itself.
+//!
+//! ```ignore
+//! // each frame, before drawing
+//! anim::update(&mut ctx, dt);
+//! let color = ctx.animations.node_color(node, look.color);
+//! let alpha = ctx.animations.node_opacity(node);
+//!
+//! let mut fade = Tween::new(0.0, 1.0, Duration::from_millis(200)).easing(Easing::EaseIn);
+//! fade.advance(dt);
+//! ```
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::context::EngineContext;
+use crate::graph::{EdgeId, Graph, NodeId};
+use crate::layout::Positions;
+
+/// How long nodes take to glide to the positions of a layout.
+pub const LAYOUT_DURATION: Duration = Duration::from_millis(600);
+/// How long [`Animations::fade_node()`] takes.
+pub const FADE_DURATION: Duration = Duration::from_millis(300);
+/// How long [`Animations::flash_node()`] takes to fade back to a node's color.
+pub const FLASH_DURATION: Duration = Duration::from_millis(800);
+
+/// How a [`Tween`] progresses over its duration.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Easing {
+    /// At a constant pace.
+    Linear,
+    /// Starting slow.
+    EaseIn,
+    /// Ending slow, eg. for moves starting right away.
+    #[default]
+    EaseOut,
+    /// Starting and ending slow, eg. for moves between two resting states.
+    EaseInOut,
+    /// Ending slow, after overshooting the end a little.
+    Back,
+}
+
+/// A value which can be interpolated, see [`Tween`].
+pub trait Lerp: Copy {
+    /// The value a fraction `t` of the way from `self` to `to`, `self` at
+    /// `0.0` and `to` at `1.0`.
+    fn lerp(self, to: Self, t: f32) -> Self;
+}
+
+/// A value moving from one to another over a duration, see the
+/// [module documentation](self).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Tween<T> {
+    from: T,
+    to: T,
+    duration: Duration,
+    elapsed: Duration,
+    easing: Easing,
+}
+
+/// The animations of the engine, see the [module documentation](self).
+#[derive(Clone, Debug, Default)]
+pub struct Animations {
+    layout: Option<LayoutTransition>,
+    /// Opacities of the nodes fading, kept once faded unless fully opaque.
+    fades: HashMap<NodeId, Tween<f32>>,
+    /// Colors of the nodes flashing, and how much of it is shown.
+    flashes: HashMap<NodeId, ([f32; 4], Tween<f32>)>,
+}
+
+/// Nodes gliding to the positions of a layout.
+#[derive(Clone, Debug)]
+struct LayoutTransition {
+    /// Each node moving, with where it moves from and to.
+    moves: Vec<(NodeId, [f32; 2], [f32; 2])>,
+    /// The bend points of the edges routed by the layout, which are only set
+    /// once the nodes arrived.
+    bends: Vec<(EdgeId, Vec<[f32; 2]>)>,
+    /// How far along the nodes are, from `0.0` to `1.0`.
+    progress: Tween<f32>,
+}
+
+impl Easing {
+    pub const ALL: [Easing; 5] = [
+        Easing::Linear,
+        Easing::EaseIn,
+        Easing::EaseOut,
+        Easing::EaseInOut,
+        Easing::Back,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Easing::Linear => "Linear",
+            Easing::EaseIn => "Ease In",
+            Easing::EaseOut => "Ease Out",
+            Easing::EaseInOut => "Ease In-Out",
+            Easing::Back => "Back",
+        }
+    }
+
+    /// How far along the curve is a fraction `t` of the way through, `0.0` at
+    /// the start and `1.0` at the end. `t` is clamped to `0.0..=1.0`.
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t.powi(3),
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut if t < 0.5 => 4.0 * t.powi(3),
+            Easing::EaseInOut => 1.0 - (-2.0 * t + 2.0).powi(3) / 2.0,
+            Easing::Back => {
+                // how far it overshoots, about 10%
+                const OVERSHOOT: f32 = 1.70158;
+                let t = t - 1.0;
+                1.0 + (OVERSHOOT + 1.0) * t.powi(3) + OVERSHOOT * t.powi(2)
+            }
+        }
+    }
+}
+
+impl Lerp for f32 {
+    fn lerp(self, to: Self, t: f32) -> Self {
+        self + (to - self) * t
+    }
+}
+
+impl<const N: usize> Lerp for [f32; N] {
+    fn lerp(self, to: Self, t: f32) -> Self {
+        std::array::from_fn(|i| self[i].lerp(to[i], t))
+    }
+}
+
+/// A camera's center and zoom, the zoom interpolated geometrically so that it
+/// changes at a steady pace.
+impl Lerp for ([f32; 2], f32) {
+    fn lerp(self, to: Self, t: f32) -> Self {
+        (self.0.lerp(to.0, t), self.1 * (to.1 / self.1).powf(t))
+    }
+}
+
+impl<T: Lerp> Tween<T> {
+    /// A tween from `from` to `to` over `duration`, easing out.
+    pub fn new(from: T, to: T, duration: Duration) -> Self {
+        Self {
+            from,
+            to,
+            duration,
+            elapsed: Duration::ZERO,
+            easing: Easing::default(),
+        }
+    }
+
+    /// Progress along `easing` instead.
+    pub fn easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    pub fn from(&self) -> T {
+        self.from
+    }
+
+    pub fn to(&self) -> T {
+        self.to
+    }
+
+    /// The value as of the time elapsed.
+    pub fn value(&self) -> T {
+        self.from.lerp(self.to, self.easing.apply(self.fraction()))
+    }
+
+    /// The fraction of the duration elapsed, from `0.0` to `1.0`.
+    pub fn fraction(&self) -> f32 {
+        match self.duration.is_zero() {
+            true => 1.0,
+            false => (self.elapsed.as_secs_f32() / self.duration.as_secs_f32()).min(1.0),
+        }
+    }
+
+    /// Returns `true` once the whole duration elapsed.
+    pub fn is_finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+
+    /// Let `dt` more time elapse, eg. the duration of a frame.
+    pub fn advance(&mut self, dt: Duration) {
+        self.seek(self.elapsed.saturating_add(dt));
+    }
+
+    /// Set the time elapsed since the start, eg. measured with an
+    /// [`Instant`](std::time::Instant).
+    pub fn seek(&mut self, elapsed: Duration) {
+        self.elapsed = elapsed.min(self.duration);
+    }
+
+    /// Head to `to` from the current value, starting over.
+    pub fn retarget(&mut self, to: T) {
+        self.from = self.value();
+        self.to = to;
+        self.elapsed = Duration::ZERO;
+    }
+}
+
+impl Animations {
+    /// Create animations with nothing animating.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns `true` while anything animates, eg. to keep redrawing.
+    pub fn is_animating(&self) -> bool {
+        self.layout.is_some()
+            || self.fades.values().any(|fade| !fade.is_finished())
+            || !self.flashes.is_empty()
+    }
+
+    /// Returns `true` while nodes glide to the positions of a layout.
+    pub fn is_transitioning(&self) -> bool {
+        self.layout.is_some()
+    }
+
+    /// Glide the nodes of `graph` from their positions `from` to those `to`,
+    /// which they take once the transition ends. Edges are drawn straight
+    /// until then.
+    pub fn transition(&mut self, graph: &Graph, from: &Positions, to: &Positions) {
+        let moves: Vec<_> = graph
+            .nodes()
+            .filter_map(|node| Some((node, from.get(node)?, to.get(node)?)))
+            .filter(|(_, from, to)| from != to)
+            .collect();
+        if moves.is_empty() {
+            self.layout = None;
+            return;
+        }
+        let bends = graph
+            .edges()
+            .filter(|&edge| !to.bends(edge).is_empty())
+            .map(|edge| (edge, to.bends(edge).to_vec()))
+            .collect();
+        self.layout = Some(LayoutTransition {
+            moves,
+            bends,
+            progress: Tween::new(0.0, 1.0, LAYOUT_DURATION).easing(Easing::EaseInOut),
+        });
+    }
+
+    /// Fade the opacity of `node` from `from` to `to`, eg. `0.0` to `1.0` to
+    /// fade it in, starting from its current opacity if already fading.
+    pub fn fade_node(&mut self, node: NodeId, from: f32, to: f32) {
+        match self.fades.get_mut(&node) {
+            Some(fade) => fade.retarget(to),
+            None => {
+                let fade = Tween::new(from, to, FADE_DURATION).easing(Easing::EaseInOut);
+                self.fades.insert(node, fade);
+            }
+        }
+    }
+
+    /// Show `node` in `color`, fading back to its own.
+    pub fn flash_node(&mut self, node: NodeId, color: [f32; 4]) {
+        let fade = Tween::new(1.0, 0.0, FLASH_DURATION).easing(Easing::EaseIn);
+        self.flashes.insert(node, (color, fade));
+    }
+
+    /// The opacity of `node`, `1.0` unless faded.
+    pub fn node_opacity(&self, node: NodeId) -> f32 {
+        self.fades.get(&node).map_or(1.0, Tween::value)
+    }
+
+    /// The `color` of `node`, mixed with that it flashes in if any.
+    pub fn node_color(&self, node: NodeId, color: [f32; 4]) -> [f32; 4] {
+        match self.flashes.get(&node) {
+            Some((flash, fade)) => color.lerp(*flash, fade.value()),
+            None => color,
+        }
+    }
+
+    /// Move the fades and flashes along with `step`, dropping those finished.
+    fn advance(&mut self, step: impl Fn(&mut Tween<f32>)) {
+        for fade in self.fades.values_mut() {
+            step(fade);
+        }
+        self.fades
+            .retain(|_, fade| !fade.is_finished() || fade.to() < 1.0);
+        for (_, fade) in self.flashes.values_mut() {
+            step(fade);
+        }
+        self.flashes.retain(|_, (_, fade)| !fade.is_finished());
+    }
+
+    /// Stop every animation where it is, eg. when the graph is replaced. A
+    /// layout transition ending early leaves the nodes where they were.
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// Advance the [`EngineContext::animations`] by `dt`, the time since the last
+/// call, moving the nodes of layout transitions. Must be called once per frame,
+/// before the graph is drawn.
+pub fn update(ctx: &mut EngineContext, dt: Duration) {
+    let animations = &mut ctx.animations;
+    // the force layout moves the nodes from wherever they are
+    if !ctx.force.is_paused() {
+        animations.layout = None;
+    }
+    if let Some(transition) = &mut animations.layout {
+        if transition.progress.fraction() == 0.0 {
+            ctx.positions.clear_bends();
+        }
+        transition.progress.advance(dt);
+        let t = transition.progress.value();
+        for &(node, from, to) in &transition.moves {
+            ctx.positions.set(node, from.lerp(to, t));
+        }
+        if transition.progress.is_finished()
+            && let Some(transition) = animations.layout.take()
+        {
+            for (edge, bends) in transition.bends {
+                ctx.positions.set_bends(edge, bends);
+            }
+        }
+    }
+    animations.advance(|tween| tween.advance(dt));
+}
+
+/// Skip to the end of every animation, eg. before [`EngineContext::positions`]
+/// are saved, leaving the nodes of a layout transition at their new positions.
+pub fn finish(ctx: &mut EngineContext) {
+    let animations = &mut ctx.animations;
+    if let Some(transition) = animations.layout.take() {
+        for (node, _, to) in transition.moves {
+            ctx.positions.set(node, to);
+        }
+        ctx.positions.clear_bends();
+        for (edge, bends) in transition.bends {
+            ctx.positions.set_bends(edge, bends);
+        }
+    }
+    animations.advance(|tween| tween.seek(Duration::MAX));
+}
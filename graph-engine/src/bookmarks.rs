@@ -0,0 +1,394 @@
+//! # Bookmarks
+//!
+//! A [`Bookmark`] is a named view of a document: where the camera looks, which
+//! filters apply, and what is selected. Going to one glides the camera there
+//! and brings its filters and selection back, eg. to present different regions
+//! of a big graph in turn.
+//!
+//! Each document keeps its own [`Bookmarks`], saved along with it in the
+//! [workspace session](crate::session):
+//! - the Bookmarks panel adds the current view, goes to, updates, renames,
+//!   reorders, and removes them
+//! - [`Action::NextBookmark`] and [`Action::PreviousBookmark`] (Page Down and
+//!   Page Up by default, as sent by presentation remotes) step through them
+//! - the [palette](crate::palette) commands `Bookmarks: Go to 1` to `9` go to
+//!   the first nine, bound to `Alt+1` to `Alt+9` by default
+//!
+//! ```ignore
+//! if let Some(bookmark) = Bookmark::capture("Overview", &ctx) {
+//!     ctx.bookmarks.push(bookmark);
+//! }
+//! bookmarks::step(&mut ctx, 1);
+//! ```
+//!
+//! [`Action::NextBookmark`]: crate::subsystems::input::Action::NextBookmark
+//! [`Action::PreviousBookmark`]: crate::subsystems::input::Action::PreviousBookmark
+
+use crate::context::EngineContext;
+use crate::filter::Filter;
+use crate::graph::{EdgeId, NodeId};
+use crate::i18n::tr;
+use crate::imgui::{MouseButton, TableFlags, Ui};
+use crate::palette::PaletteCommand;
+use crate::selection::SelectMode;
+use crate::subsystems::panels::Panel;
+
+/// Prefix of the ids of palette commands going to a bookmark, followed by its
+/// number, counting from 1.
+pub const GOTO_COMMAND: &str = "bookmark.goto:";
+/// Number of bookmarks with a palette command, see [`register_commands()`].
+pub const GOTO_COMMANDS: usize = 9;
+
+/// A named view of a document, see the [module documentation](self).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bookmark {
+    pub name: String,
+    /// The camera's center and zoom.
+    pub view: ([f32; 2], f32),
+    pub filters: Vec<Filter>,
+    /// The selected nodes and edges, in id order.
+    pub nodes: Vec<NodeId>,
+    pub edges: Vec<EdgeId>,
+}
+
+/// The bookmarks of a document, in the order they are stepped through.
+#[derive(Clone, Debug, Default)]
+pub struct Bookmarks {
+    list: Vec<Bookmark>,
+    /// Index of the bookmark gone to last, if it still exists.
+    current: Option<usize>,
+}
+
+/// Lists the bookmarks of the active document, adding, going to, and editing
+/// them.
+pub struct BookmarksPanel {
+    /// Name of the bookmark being added.
+    name: String,
+    /// Index of the bookmark being renamed, with its new name.
+    renaming: Option<(usize, String)>,
+}
+
+impl Bookmark {
+    /// Capture the current view of `ctx` as `name`.
+    ///
+    /// Returns [None] until the camera was first shown.
+    pub fn capture(name: impl Into<String>, ctx: &EngineContext) -> Option<Self> {
+        Some(Self {
+            name: name.into(),
+            view: ctx.camera.view()?,
+            filters: ctx.filters.filters().to_vec(),
+            nodes: ctx.selection.nodes().collect(),
+            edges: ctx.selection.edges().collect(),
+        })
+    }
+
+    /// Bring the view back in `ctx`: glide the camera there, and apply the
+    /// filters and the selection. Elements removed since are left out.
+    pub fn show(&self, ctx: &mut EngineContext) {
+        ctx.camera.glide_to(self.view.0, self.view.1);
+        if ctx.filters.filters() != self.filters {
+            *ctx.filters.filters_mut() = self.filters.clone();
+        }
+        let graph = &ctx.graph;
+        let nodes = self.nodes.iter().filter(|&&n| graph.contains_node(n));
+        let edges = self.edges.iter().filter(|&&e| graph.contains_edge(e));
+        ctx.selection
+            .select(nodes.copied(), edges.copied(), SelectMode::Replace);
+    }
+}
+
+impl Bookmarks {
+    /// Bookmarks of `list`, in order, none of them current.
+    pub fn new(list: Vec<Bookmark>) -> Self {
+        Self {
+            list,
+            current: None,
+        }
+    }
+
+    /// Every bookmark, in order.
+    pub fn list(&self) -> &[Bookmark] {
+        &self.list
+    }
+
+    /// Returns `true` if there are no bookmarks.
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Number of bookmarks.
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    /// Mutable access to the bookmark at `index`, if any.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut Bookmark> {
+        self.list.get_mut(index)
+    }
+
+    /// Add `bookmark` last, making it the current one.
+    pub fn push(&mut self, bookmark: Bookmark) {
+        self.list.push(bookmark);
+        self.current = Some(self.list.len() - 1);
+    }
+
+    /// Remove and return the bookmark at `index`.
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn remove(&mut self, index: usize) -> Bookmark {
+        self.current = match self.current {
+            Some(current) if current == index => None,
+            Some(current) if current > index => Some(current - 1),
+            current => current,
+        };
+        self.list.remove(index)
+    }
+
+    /// Swap the bookmarks at `a` and `b`, eg. to move one up.
+    ///
+    /// Panics if either is out of bounds.
+    pub fn swap(&mut self, a: usize, b: usize) {
+        self.list.swap(a, b);
+        self.current = self.current.map(|current| match current {
+            _ if current == a => b,
+            _ if current == b => a,
+            _ => current,
+        });
+    }
+
+    /// Index of the bookmark gone to or added last, if any.
+    pub fn current(&self) -> Option<usize> {
+        self.current
+    }
+}
+
+/// Go to the bookmark at `index` of the active document. Returns `false` if
+/// there is none.
+pub fn go_to(ctx: &mut EngineContext, index: usize) -> bool {
+    let Some(bookmark) = ctx.bookmarks.list.get(index).cloned() else {
+        return false;
+    };
+    ctx.bookmarks.current = Some(index);
+    bookmark.show(ctx);
+    true
+}
+
+/// Go to the bookmark `delta` places after the current one, wrapping around,
+/// or to the first one if none is current.
+pub fn step(ctx: &mut EngineContext, delta: isize) {
+    let len = ctx.bookmarks.len() as isize;
+    if len == 0 {
+        return;
+    }
+    let index = match ctx.bookmarks.current {
+        Some(current) => (current as isize + delta).rem_euclid(len),
+        None if delta < 0 => len - 1,
+        None => 0,
+    };
+    go_to(ctx, index as usize);
+}
+
+/// Register the palette commands going to the first [`GOTO_COMMANDS`]
+/// bookmarks, see [`GOTO_COMMAND`].
+pub fn register_commands(ctx: &mut EngineContext) {
+    for number in 1..=GOTO_COMMANDS {
+        ctx.palette.register(PaletteCommand::new(
+            format!("{GOTO_COMMAND}{number}"),
+            format!("Bookmarks: Go to {number}"),
+            move |ctx| {
+                if !go_to(ctx, number - 1) {
+                    ctx.log.warn(format!("No bookmark {number}"));
+                }
+            },
+        ));
+    }
+}
+
+impl BookmarksPanel {
+    /// Create a panel with an empty name for the next bookmark.
+    pub fn new() -> Self {
+        Self {
+            name: String::new(),
+            renaming: None,
+        }
+    }
+
+    /// Rows of the bookmarks, with their buttons.
+    fn list_ui(&mut self, ui: &Ui, ctx: &mut EngineContext) {
+        let Some(_table) = ui.begin_table_with_flags(
+            "##bookmarks",
+            3,
+            TableFlags::ROW_BG | TableFlags::BORDERS_INNER_H | TableFlags::SIZING_STRETCH_PROP,
+        ) else {
+            return;
+        };
+        let count = ctx.bookmarks.len();
+        let mut go = None;
+        let mut edit = None;
+        for (index, bookmark) in ctx.bookmarks.list.iter().enumerate() {
+            let _id = ui.push_id_usize(index);
+            ui.table_next_row();
+
+            ui.table_next_column();
+            match &mut self.renaming {
+                Some((renamed, name)) if *renamed == index => {
+                    ui.set_next_item_width(-1.0);
+                    if ui
+                        .input_text("##name", name)
+                        .enter_returns_true(true)
+                        .build()
+                    {
+                        edit = Some((index, Edit::Rename(std::mem::take(name))));
+                    }
+                }
+                _ => {
+                    let label = match index < GOTO_COMMANDS {
+                        true => format!("{}. {}", index + 1, bookmark.name),
+                        false => bookmark.name.clone(),
+                    };
+                    if ui
+                        .selectable_config(label)
+                        .selected(ctx.bookmarks.current == Some(index))
+                        .allow_double_click(true)
+                        .build()
+                    {
+                        go = Some(index);
+                        if ui.is_mouse_double_clicked(MouseButton::Left) {
+                            self.renaming = Some((index, bookmark.name.clone()));
+                        }
+                    }
+                }
+            }
+
+            ui.table_next_column();
+            ui.text_disabled(describe(bookmark));
+
+            ui.table_next_column();
+            ui.enabled(index > 0, || {
+                if ui.small_button("^") {
+                    edit = Some((index, Edit::Swap(index - 1)));
+                }
+            });
+            ui.same_line();
+            ui.enabled(index + 1 < count, || {
+                if ui.small_button(tr("v")) {
+                    edit = Some((index, Edit::Swap(index + 1)));
+                }
+            });
+            ui.same_line();
+            if ui.small_button(tr("Update")) {
+                edit = Some((index, Edit::Update));
+            }
+            ui.same_line();
+            if ui.small_button(tr("Remove")) {
+                edit = Some((index, Edit::Remove));
+            }
+        }
+        if let Some((index, edit)) = edit {
+            self.edit(ctx, index, edit);
+        } else if let Some(index) = go {
+            go_to(ctx, index);
+        }
+    }
+
+    fn edit(&mut self, ctx: &mut EngineContext, index: usize, edit: Edit) {
+        match edit {
+            Edit::Rename(name) => {
+                self.renaming = None;
+                if !name.trim().is_empty() {
+                    ctx.bookmarks.list[index].name = name.trim().to_string();
+                }
+            }
+            Edit::Swap(other) => ctx.bookmarks.swap(index, other),
+            Edit::Update => {
+                let name = ctx.bookmarks.list[index].name.clone();
+                if let Some(bookmark) = Bookmark::capture(name, ctx) {
+                    ctx.bookmarks.list[index] = bookmark;
+                    ctx.bookmarks.current = Some(index);
+                }
+            }
+            Edit::Remove => {
+                self.renaming = None;
+                ctx.bookmarks.remove(index);
+            }
+        }
+    }
+}
+
+/// A change to a bookmark picked in the [`BookmarksPanel`].
+enum Edit {
+    Rename(String),
+    /// Swap places with the bookmark at this index.
+    Swap(usize),
+    /// Capture the current view in its place.
+    Update,
+    Remove,
+}
+
+impl Panel for BookmarksPanel {
+    fn id(&self) -> &'static str {
+        "Bookmarks"
+    }
+
+    fn draw(&mut self, ui: &Ui, ctx: &mut EngineContext) {
+        let default_name = format!("View {}", ctx.bookmarks.len() + 1);
+        ui.set_next_item_width(-80.0);
+        let entered = ui
+            .input_text("##name", &mut self.name)
+            .hint(&default_name)
+            .enter_returns_true(true)
+            .build();
+        ui.same_line();
+        if (ui.button(tr("Add")) || entered)
+            && let Some(bookmark) = Bookmark::capture(
+                match self.name.trim() {
+                    "" => default_name,
+                    name => name.to_string(),
+                },
+                ctx,
+            )
+        {
+            ctx.bookmarks.push(bookmark);
+            self.name.clear();
+        }
+
+        ui.enabled(!ctx.bookmarks.is_empty(), || {
+            if ui.button(tr("Previous")) {
+                step(ctx, -1);
+            }
+            ui.same_line();
+            if ui.button(tr("Next")) {
+                step(ctx, 1);
+            }
+        });
+        ui.separator();
+        if ctx.bookmarks.is_empty() {
+            ui.text_disabled(tr(
+                "No bookmarks. Add one to come back to the current view.",
+            ));
+            return;
+        }
+        self.list_ui(ui, ctx);
+    }
+}
+
+/// What a bookmark brings back besides the view, eg. `2 filters, 5 selected`.
+fn describe(bookmark: &Bookmark) -> String {
+    let mut parts = Vec::new();
+    match bookmark.filters.len() {
+        0 => {}
+        1 => parts.push("1 filter".to_string()),
+        n => parts.push(format!("{n} filters")),
+    }
+    let selected = bookmark.nodes.len() + bookmark.edges.len();
+    if selected > 0 {
+        parts.push(format!("{selected} selected"));
+    }
+    parts.join(", ")
+}
+
+impl std::default::Default for BookmarksPanel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
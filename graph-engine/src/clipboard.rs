@@ -0,0 +1,273 @@
+//! # Clipboard
+//!
+//! [`copy()`] keeps the selected nodes of the active document, the edges
+//! between them, and their positions as a [`Subgraph`], and puts it on the
+//! system clipboard as [GraphML](crate::io::graphml) for other applications
+//! and instances. [`paste()`] adds it to the active document, the same or
+//! another one, as a single undoable step:
+//! - while the system clipboard still holds the copy, the kept subgraph is
+//!   pasted, otherwise GraphML on the system clipboard is read and placed by
+//!   its [`X_ATTR`] and [`Y_ATTR`] coordinates
+//! - pasted nodes and edges get new ids, and [`ID_ATTR`]s already used in
+//!   the document are suffixed, their members' [`PARENT_ATTR`]s following
+//!   renamed groups
+//! - pasted nodes are centered on the given point, and selected
+//!
+//! ```ignore
+//! clipboard::copy(&mut ctx);
+//! clipboard::paste(&mut ctx, camera.screen_to_world(cursor));
+//! ```
+
+use std::collections::{HashMap, HashSet};
+
+use crate::context::EngineContext;
+use crate::graph::{Attributes, EdgeId, Graph, NodeId};
+use crate::history::Command;
+use crate::io::graphml::{self, GraphMlError};
+use crate::io::{ID_ATTR, PARENT_ATTR, node_name};
+use crate::layout::{Positions, X_ATTR, Y_ATTR};
+use crate::selection::SelectMode;
+use crate::subsystems::clipboard;
+
+/// Nodes and edges copied out of a graph, with the positions of the nodes.
+#[derive(Clone, Debug, Default)]
+pub struct Subgraph {
+    pub graph: Graph,
+    pub positions: Positions,
+}
+
+/// The last [`copy()`], shared by every document, see the
+/// [module documentation](self).
+#[derive(Debug, Default)]
+pub struct GraphClipboard {
+    /// The copied subgraph, and the GraphML put on the system clipboard for it.
+    copied: Option<(Subgraph, String)>,
+}
+
+impl Subgraph {
+    /// The `nodes` of `graph`, the edges between them, and their `positions`.
+    ///
+    /// Nodes keep their name as [`ID_ATTR`], so groups among them stay
+    /// groups.
+    pub fn extract(
+        graph: &Graph,
+        positions: &Positions,
+        nodes: impl IntoIterator<Item = NodeId>,
+    ) -> Self {
+        let mut subgraph = Self {
+            graph: Graph::new(graph.directedness()),
+            positions: Positions::default(),
+        };
+        let mut mapped = HashMap::new();
+        let mut order = Vec::new();
+        for node in nodes {
+            let Some(attrs) = graph.node(node) else {
+                continue;
+            };
+            let mut attrs = attrs.clone();
+            attrs.insert(ID_ATTR, node_name(graph, node));
+            let copy = subgraph.graph.add_node_with(attrs);
+            if let Some(point) = positions.get(node) {
+                subgraph.positions.set(copy, point);
+            }
+            mapped.insert(node, copy);
+            order.push(node);
+        }
+        for node in order {
+            for edge in graph.out_edges(node) {
+                let (_, target) = graph.endpoints(edge).unwrap();
+                if let Some(&target) = mapped.get(&target) {
+                    let attrs = graph.edge(edge).unwrap().clone();
+                    let _ = subgraph.graph.add_edge_with(mapped[&node], target, attrs);
+                }
+            }
+        }
+        subgraph
+    }
+
+    /// Read a subgraph from GraphML, placing its nodes by their coordinates if
+    /// set, or on a spiral.
+    pub fn from_graphml(text: &str) -> Result<Self, GraphMlError> {
+        let graph = graphml::read(text.as_bytes())?;
+        let positions = Positions::new(&graph);
+        Ok(Self { graph, positions })
+    }
+
+    /// The subgraph as GraphML, with the positions of its nodes as their
+    /// [`X_ATTR`] and [`Y_ATTR`].
+    pub fn to_graphml(&self) -> std::io::Result<String> {
+        let mut graph = self.graph.clone();
+        for node in self.graph.nodes() {
+            if let Some([x, y]) = self.positions.get(node) {
+                let attrs = graph.node_mut(node).unwrap();
+                attrs.insert(X_ATTR, x as f64);
+                attrs.insert(Y_ATTR, y as f64);
+            }
+        }
+        let mut out = Vec::new();
+        graphml::write(&graph, &mut out)?;
+        Ok(String::from_utf8_lossy(&out).into_owned())
+    }
+
+    /// Add the subgraph to `graph`, its nodes centered on `at` in `positions`,
+    /// renaming the [`ID_ATTR`]s `graph` already uses. Returns the added nodes
+    /// and edges.
+    pub fn insert(
+        &self,
+        graph: &mut Graph,
+        positions: &mut Positions,
+        at: [f32; 2],
+    ) -> (Vec<NodeId>, Vec<EdgeId>) {
+        let mut names: HashSet<String> = graph.nodes().map(|n| node_name(graph, n)).collect();
+        let mut edge_names: HashSet<String> = graph
+            .edges()
+            .filter_map(|e| Some(graph.edge(e)?.get(ID_ATTR)?.to_string()))
+            .collect();
+
+        let mut renamed = HashMap::new();
+        let mut nodes: Vec<(NodeId, Attributes)> = self
+            .graph
+            .nodes()
+            .map(|node| {
+                let mut attrs = self.graph.node(node).unwrap().clone();
+                let name = node_name(&self.graph, node);
+                let free = unique(&name, &names);
+                attrs.insert(ID_ATTR, free.as_str());
+                names.insert(free.clone());
+                if free != name {
+                    renamed.insert(name, free);
+                }
+                (node, attrs)
+            })
+            .collect();
+        for (_, attrs) in &mut nodes {
+            if let Some(parent) = attrs.get(PARENT_ATTR).map(|v| v.to_string())
+                && let Some(free) = renamed.get(&parent)
+            {
+                attrs.insert(PARENT_ATTR, free.as_str());
+            }
+        }
+
+        let points: Vec<[f32; 2]> = nodes
+            .iter()
+            .filter_map(|(node, _)| self.positions.get(*node))
+            .collect();
+        let sum = points
+            .iter()
+            .fold([0.0; 2], |s, p| [s[0] + p[0], s[1] + p[1]]);
+        let n = points.len().max(1) as f32;
+        let offset = [at[0] - sum[0] / n, at[1] - sum[1] / n];
+
+        let mut mapped = HashMap::new();
+        let mut added = Vec::with_capacity(nodes.len());
+        for (node, attrs) in nodes {
+            let new = graph.add_node_with(attrs);
+            let point = match self.positions.get(node) {
+                Some([x, y]) => [x + offset[0], y + offset[1]],
+                None => at,
+            };
+            positions.set(new, point);
+            mapped.insert(node, new);
+            added.push(new);
+        }
+        let mut added_edges = Vec::new();
+        for edge in self.graph.edges() {
+            let (source, target) = self.graph.endpoints(edge).unwrap();
+            let mut attrs = self.graph.edge(edge).unwrap().clone();
+            if let Some(name) = attrs.get(ID_ATTR).map(|v| v.to_string()) {
+                let free = unique(&name, &edge_names);
+                attrs.insert(ID_ATTR, free.as_str());
+                edge_names.insert(free);
+            }
+            if let Ok(edge) = graph.add_edge_with(mapped[&source], mapped[&target], attrs) {
+                added_edges.push(edge);
+            }
+        }
+        positions.sync(graph);
+        (added, added_edges)
+    }
+}
+
+/// Copy the selected nodes of the active document and the edges between them,
+/// see the [module documentation](self).
+pub fn copy(ctx: &mut EngineContext) {
+    let nodes: Vec<NodeId> = ctx.selection.nodes().collect();
+    if nodes.is_empty() {
+        ctx.log.info("Select nodes to copy them");
+        return;
+    }
+    ctx.force.sync_positions(&mut ctx.positions);
+    let subgraph = Subgraph::extract(&ctx.graph, &ctx.positions, nodes);
+    let text = match subgraph.to_graphml() {
+        Ok(text) => text,
+        Err(e) => {
+            ctx.log.error(format!("Failed to copy: {e}"));
+            return;
+        }
+    };
+    if let Err(e) = clipboard::set_text(&text) {
+        ctx.log
+            .error(format!("Failed to copy to the system clipboard: {e}"));
+    }
+    ctx.log.info(format!(
+        "Copied {} nodes and {} edges",
+        subgraph.graph.node_count(),
+        subgraph.graph.edge_count()
+    ));
+    ctx.clipboard.copied = Some((subgraph, text));
+}
+
+/// Paste the last copy, or GraphML from the system clipboard, into the active
+/// document with its nodes centered on `at`, see the
+/// [module documentation](self).
+pub fn paste(ctx: &mut EngineContext, at: [f32; 2]) {
+    let subgraph = match (&ctx.clipboard.copied, clipboard::text()) {
+        (Some((subgraph, copied)), Some(text)) if *copied == text => subgraph.clone(),
+        (Some((subgraph, _)), None) => subgraph.clone(),
+        (_, Some(text)) => match Subgraph::from_graphml(&text) {
+            Ok(subgraph) => subgraph,
+            Err(e) => {
+                ctx.log
+                    .error(format!("The clipboard holds no graph to paste: {e}"));
+                return;
+            }
+        },
+        (None, None) => {
+            ctx.log.info("Nothing to paste");
+            return;
+        }
+    };
+    if subgraph.graph.is_empty() {
+        ctx.log.info("Nothing to paste");
+        return;
+    }
+
+    ctx.force.sync_positions(&mut ctx.positions);
+    let mut graph = ctx.graph.clone();
+    let mut positions = ctx.positions.clone();
+    let (nodes, edges) = subgraph.insert(&mut graph, &mut positions, at);
+    ctx.execute(Command::Batch {
+        label: "Paste".into(),
+        commands: vec![Command::ReplaceGraph {
+            graph: Box::new(graph),
+            positions: Box::new(positions),
+        }],
+    });
+    ctx.log.info(format!(
+        "Pasted {} nodes and {} edges",
+        nodes.len(),
+        edges.len()
+    ));
+    ctx.selection.select(nodes, edges, SelectMode::Replace);
+}
+
+/// `name`, or `name` suffixed with the first number making it none of `taken`.
+fn unique(name: &str, taken: &HashSet<String>) -> String {
+    if !taken.contains(name) {
+        return name.to_owned();
+    }
+    (2..)
+        .map(|i| format!("{name}-{i}"))
+        .find(|n| !taken.contains(n))
+        .unwrap()
+}
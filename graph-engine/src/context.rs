@@ -0,0 +1,726 @@
+//! # Graph's Engine Context
+//!
+//! [`EngineContext`] holds the application state shared between the engine's
+//! subsystems and every registered [`Panel`](crate::subsystems::panels::Panel).
+//! Panels receive it mutably while drawing, so a feature shipping its own panel
+//! can read and change shared state without holding references to it.
+
+use crate::algo::metrics::MetricsJob;
+use crate::anim::{self, Animations};
+use crate::bookmarks::Bookmarks;
+use crate::clipboard::GraphClipboard;
+use crate::document::{DocumentManager, Stash};
+use crate::explore::{self, Exploration};
+use crate::filter::FilterStack;
+use crate::graph::Graph;
+use crate::group::Groups;
+use crate::history::{Command, CommandStack};
+use crate::hover::Hover;
+use crate::i18n::Locales;
+use crate::io::live::LiveServer;
+use crate::io::loader::{GraphLoader, LOAD_BUDGET, LoadJob, LoadStep};
+use crate::io::{self, Format};
+use crate::jobs::{JobOutcome, JobPool};
+use crate::layout::circular::CircularLayout;
+use crate::layout::force::ForceLayout;
+use crate::layout::grid::GridLayout;
+use crate::layout::group::GroupLayout;
+use crate::layout::layered::LayeredLayout;
+use crate::layout::radial::RadialLayout;
+use crate::layout::{Layout, Positions};
+use crate::matrix::MatrixState;
+use crate::memory::{self, MemoryMonitor};
+use crate::minimap::Minimap;
+use crate::palette::CommandPalette;
+use crate::pathfind::PathFinder;
+use crate::plugin::PluginManager;
+#[cfg(feature = "http")]
+use crate::remote::{self, RemoteServer};
+use crate::routing::EdgeRoutes;
+use crate::script::ScriptRuntime;
+use crate::scripts::ScriptLibrary;
+use crate::search::AttributeIndex;
+use crate::selection::Selection;
+use crate::spatial::SpatialIndex;
+#[cfg(feature = "sqlite")]
+use crate::store::{GraphStore, StoreCache, StoreError};
+use crate::style::Style;
+use crate::subsystems::config::ConfigManager;
+use crate::subsystems::input::InputMapper;
+use crate::subsystems::log::Log;
+use crate::subsystems::scene::CameraController;
+use crate::timeline::Timeline;
+use crate::view3d::View3d;
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Shared application state, handed to panels while they draw.
+pub struct EngineContext {
+    /// The open documents. Fields holding per-document state, from
+    /// [`Self::graph`] to [`Self::metrics`], belong to the active one.
+    pub documents: DocumentManager,
+    /// The graph being browsed.
+    pub graph: Graph,
+    /// Hides parts of [`Self::graph`], see [`Self::visible_graph()`].
+    pub filters: FilterStack,
+    /// The [groups](crate::group) of [`Self::graph`], as of the last
+    /// [`Self::refresh()`].
+    pub groups: Groups,
+    /// Where each node of [`Self::graph`] is drawn.
+    pub positions: Positions,
+    /// Replays [`Self::graph`] through time, hiding what is out of its window,
+    /// see [`timeline`](crate::timeline).
+    pub timeline: Timeline,
+    /// How the edges of the visible graph are drawn, see
+    /// [`routing`](crate::routing).
+    pub routes: EdgeRoutes,
+    /// The live layout moving [`Self::positions`].
+    pub force: ForceLayout,
+    /// One-shot layouts offered to the user, see [`Self::apply_layout()`].
+    pub layouts: Vec<Box<dyn Layout>>,
+    /// [`Self::graph`] indexed by [`Self::positions`], see [`Self::sync_spatial()`].
+    pub spatial: SpatialIndex,
+    /// Answers searches over [`Self::graph`]'s attributes.
+    pub attribute_index: AttributeIndex,
+    /// Undoable changes made through [`Self::execute()`].
+    pub history: CommandStack,
+    /// The nodes and edges picked by the user.
+    pub selection: Selection,
+    /// The last [copy](crate::clipboard::copy()), which can be pasted into any
+    /// document.
+    pub clipboard: GraphClipboard,
+    /// How [`Self::graph`] is drawn, edited by the [Style panel](crate::style::StylePanel).
+    pub style: Style,
+    /// Runs work in the background, eg. metrics and file loads, see
+    /// [`jobs`](crate::jobs).
+    pub jobs: JobPool,
+    /// Metrics being computed, written to [`Self::graph`] by [`Self::refresh()`]
+    /// once done.
+    pub metrics: Option<MetricsJob>,
+    /// Files being read in the background, see [`Self::load_document()`].
+    loads: Vec<LoadJob>,
+    /// Read files of formats the engine does not know, see
+    /// [`Self::loader_for()`].
+    pub loaders: Vec<Arc<dyn GraphLoader>>,
+    /// Applies changes streamed over a socket to one of the documents.
+    pub live: Option<LiveServer>,
+    /// Answers HTTP requests controlling the browser, see [`remote`].
+    #[cfg(feature = "http")]
+    pub remote: Option<RemoteServer>,
+    /// Estimates the memory held, warning when it goes over budget.
+    pub memory: MemoryMonitor,
+    /// Keeps the part of an on-disk graph around the camera in one of the
+    /// documents, see [`Self::open_store()`].
+    #[cfg(feature = "sqlite")]
+    pub store: Option<StoreCache>,
+    /// Fetches the neighborhoods of nodes in one of the documents, see
+    /// [`explore`].
+    pub exploration: Option<Exploration>,
+    /// Moves the scene's camera, eg. to show a node picked in a panel.
+    pub camera: CameraController,
+    /// Named views of the active document, see [`bookmarks`](crate::bookmarks).
+    pub bookmarks: Bookmarks,
+    /// The element under the cursor, see [`hover`](crate::hover).
+    pub hover: Hover,
+    /// The "find path" tool, see [`pathfind`](crate::pathfind).
+    pub path_finder: PathFinder,
+    /// Layout transitions, fades and flashes, see [`anim`].
+    pub animations: Animations,
+    /// The whole graph rendered at low detail, see [`minimap`](crate::minimap).
+    pub minimap: Minimap,
+    /// The visible graph as an adjacency matrix, see [`matrix`](crate::matrix).
+    pub matrix: MatrixState,
+    /// The active document's 3D view, see [`view3d`](crate::view3d).
+    pub view3d: View3d,
+    /// Settings of the application, see [`config`](crate::subsystems::config).
+    pub config: ConfigManager,
+    /// Maps raw input to [`Action`](crate::subsystems::input::Action)s.
+    pub input: InputMapper,
+    /// Commands searchable from the keyboard, see [`palette`](crate::palette).
+    pub palette: CommandPalette,
+    /// Extensions loaded from dynamic libraries, see [`plugin`](crate::plugin).
+    pub plugins: PluginManager,
+    /// Runs the scripts of the Script Console, see [`script`](crate::script).
+    pub script_runtime: Option<Box<dyn ScriptRuntime>>,
+    /// Scripts saved as files, see [`scripts`](crate::scripts).
+    pub scripts: ScriptLibrary,
+    /// Languages of the user interface, see [`i18n`](crate::i18n).
+    pub locales: Locales,
+    /// User-facing messages, shown by the [Log panel](crate::subsystems::panels::LogPanel).
+    pub log: Log,
+}
+
+impl EngineContext {
+    /// Create a context using `input` to map raw input events.
+    ///
+    /// The style is the default one until replaced, eg. by one loaded from the
+    /// workspace.
+    pub fn new(input: InputMapper) -> Self {
+        let jobs = JobPool::new();
+        let mut force = ForceLayout::default();
+        force.set_jobs(jobs.clone());
+        Self {
+            documents: DocumentManager::new(),
+            graph: Graph::default(),
+            filters: FilterStack::new(),
+            groups: Groups::default(),
+            positions: Positions::default(),
+            timeline: Timeline::new(),
+            routes: EdgeRoutes::default(),
+            force,
+            layouts: vec![
+                Box::new(LayeredLayout::default()),
+                Box::new(CircularLayout::default()),
+                Box::new(GridLayout::default()),
+                Box::new(RadialLayout::default()),
+                Box::new(GroupLayout::default()),
+            ],
+            spatial: SpatialIndex::new(),
+            attribute_index: AttributeIndex::new(),
+            history: CommandStack::new(),
+            selection: Selection::new(),
+            clipboard: GraphClipboard::default(),
+            style: Style::default(),
+            jobs,
+            metrics: None,
+            loads: Vec::new(),
+            loaders: Vec::new(),
+            live: None,
+            #[cfg(feature = "http")]
+            remote: None,
+            memory: MemoryMonitor::new(),
+            #[cfg(feature = "sqlite")]
+            store: None,
+            exploration: None,
+            camera: CameraController::new(),
+            bookmarks: Bookmarks::default(),
+            hover: Hover::default(),
+            path_finder: PathFinder::new(),
+            animations: Animations::new(),
+            minimap: Minimap::new(),
+            matrix: MatrixState::new(),
+            view3d: View3d::default(),
+            config: ConfigManager::default(),
+            input,
+            palette: CommandPalette::new(),
+            plugins: PluginManager::new(),
+            script_runtime: None,
+            scripts: ScriptLibrary::new(),
+            locales: Locales::default(),
+            log: Log::new(),
+        }
+    }
+
+    /// Replace the graph, placing its nodes afresh and restarting the layout.
+    ///
+    /// The selection and history are cleared, as they refer to the previous
+    /// graph. See [`Command::ReplaceGraph`] for an undoable replacement.
+    pub fn set_graph(&mut self, graph: Graph) {
+        self.history.clear();
+        self.positions = Positions::new(&graph);
+        self.graph = graph;
+        self.graph_replaced();
+    }
+
+    /// Open `graph` as a new document, read from `path` if any, and make it
+    /// active. An untouched untitled document is replaced instead.
+    pub fn open_document(&mut self, graph: Graph, path: Option<PathBuf>) {
+        let active = self.documents.active_document();
+        let untouched = active.path().is_none()
+            && self.graph.is_empty()
+            && !self.history.can_undo()
+            && !self.is_document_dirty(self.documents.active());
+        if !untouched {
+            let stash = Stash::take(self);
+            self.documents.push(stash);
+        }
+        self.set_graph(graph);
+        let generation = self.graph.generation();
+        self.documents
+            .active_document_mut()
+            .set_clean(path, generation);
+    }
+
+    /// Open a new document and read the file at `path` as `format` into it in
+    /// the background. Its graph grows as [`Self::refresh()`] adds the parsed
+    /// nodes and edges, while the document is active.
+    pub fn load_document(&mut self, path: PathBuf, format: Format) {
+        memory::check_load(self, &path);
+        self.open_document(Graph::default(), Some(path.clone()));
+        let document = self.documents.active_document().id();
+        self.loads
+            .push(LoadJob::spawn(&self.jobs, path, format, document));
+    }
+
+    /// The first of [`Self::loaders`] accepting the file at `path`, if any.
+    pub fn loader_for(&self, path: &Path) -> Option<Arc<dyn GraphLoader>> {
+        self.loaders.iter().find(|l| l.accepts(path)).cloned()
+    }
+
+    /// Like [`Self::load_document()`], reading the file with `loader`.
+    pub fn load_document_with(&mut self, path: PathBuf, loader: Arc<dyn GraphLoader>) {
+        memory::check_load(self, &path);
+        self.open_document(Graph::default(), Some(path.clone()));
+        let document = self.documents.active_document().id();
+        self.loads
+            .push(LoadJob::spawn_with(&self.jobs, path, loader, document));
+    }
+
+    /// Open the graph store at `path` in a new document, which shows the part
+    /// of the store around the camera from then on. A store open before is
+    /// closed.
+    #[cfg(feature = "sqlite")]
+    pub fn open_store(&mut self, path: impl AsRef<Path>) -> Result<(), StoreError> {
+        let store = GraphStore::open(path)?;
+        let bounds = store.bounds()?;
+        let (nodes, edges) = (store.node_count()?, store.edge_count()?);
+        self.close_store();
+        self.open_document(Graph::new(store.directedness()), None);
+        // the store's positions are the layout
+        self.force.set_paused(true);
+        if let Some((min, max)) = bounds {
+            self.camera.fit_rect(min, max);
+        }
+        self.log.info(format!(
+            "Opened graph store '{}': {nodes} nodes, {edges} edges",
+            store.path().display()
+        ));
+        let document = self.documents.active_document().id();
+        self.store = Some(StoreCache::new(store, document));
+        Ok(())
+    }
+
+    /// Close the open graph store, writing back changes to its cached graph if
+    /// its document is active. Its document is left open.
+    #[cfg(feature = "sqlite")]
+    pub fn close_store(&mut self) {
+        let Some(mut cache) = self.store.take() else {
+            return;
+        };
+        if cache.document() == self.documents.active_document().id() {
+            self.force.sync_positions(&mut self.positions);
+            if let Err(e) = cache.flush(&self.graph, &self.positions) {
+                self.log.error(format!(
+                    "Failed to write to '{}': {e}",
+                    cache.store().path().display()
+                ));
+            }
+        }
+    }
+
+    /// Files being read in the background.
+    pub fn loads(&self) -> &[LoadJob] {
+        &self.loads
+    }
+
+    /// Make the document at `index` active. Does nothing if it already is.
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn switch_document(&mut self, index: usize) {
+        if index == self.documents.active() {
+            return;
+        }
+        anim::finish(self);
+        let stash = Stash::take(self);
+        self.documents.switch(index, stash).restore(self);
+    }
+
+    /// Close the document at `index`, discarding unsaved changes. Closing the
+    /// last document leaves an empty, untitled one.
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn close_document(&mut self, index: usize) {
+        #[cfg(feature = "sqlite")]
+        if self
+            .store
+            .as_ref()
+            .is_some_and(|cache| cache.document() == self.documents.documents()[index].id())
+        {
+            self.close_store();
+        }
+        if self
+            .exploration
+            .as_ref()
+            .is_some_and(|e| e.document() == self.documents.documents()[index].id())
+        {
+            self.exploration = None;
+        }
+        let count = self.documents.documents().len();
+        if count == 1 {
+            self.set_graph(Graph::default());
+            let generation = self.graph.generation();
+            self.documents
+                .active_document_mut()
+                .set_clean(None, generation);
+            return;
+        }
+        if index == self.documents.active() {
+            self.switch_document(if index + 1 < count {
+                index + 1
+            } else {
+                index - 1
+            });
+        }
+        self.documents.remove(index);
+    }
+
+    /// Returns `true` if the document at `index` changed since it was last
+    /// opened or saved.
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn is_document_dirty(&self, index: usize) -> bool {
+        self.documents.is_dirty(index, self.graph.generation())
+    }
+
+    /// Returns `true` while the scene changes on its own, ie. something moves
+    /// or work runs in the background, so it is worth redrawing without input.
+    pub fn is_busy(&self) -> bool {
+        self.force.is_running()
+            || self.camera.is_animating()
+            || self.animations.is_animating()
+            || self.timeline.is_playing()
+            || self.path_finder.is_animating()
+            || self.view3d.is_animating()
+            || !self.loads.is_empty()
+            || self.jobs.is_busy()
+            || self
+                .exploration
+                .as_ref()
+                .is_some_and(Exploration::is_running)
+    }
+
+    /// Save the active document's graph to `path`, or to the file it was
+    /// opened from, in the format matching the file's extension.
+    ///
+    /// Errors if the document has no file and no `path` is given, or the
+    /// file cannot be written.
+    pub fn save_document(&mut self, path: Option<PathBuf>) -> std::io::Result<PathBuf> {
+        let path = path
+            .or_else(|| self.documents.active_document().path().map(Path::to_owned))
+            .ok_or_else(|| {
+                std::io::Error::new(std::io::ErrorKind::NotFound, "The document has no file")
+            })?;
+        io::save(&self.graph, &path)?;
+        let generation = self.graph.generation();
+        self.documents
+            .active_document_mut()
+            .set_clean(Some(path.clone()), generation);
+        self.documents.recent.push(&path);
+        Ok(path)
+    }
+
+    /// Reset the state derived from the previous graph, once [`Self::graph`]
+    /// and [`Self::positions`] were replaced.
+    pub(crate) fn graph_replaced(&mut self) {
+        if let Some(job) = self.metrics.take() {
+            job.cancel();
+        }
+        self.selection.clear();
+        self.path_finder.clear();
+        if let Some(paused) = self.view3d.reset() {
+            self.force.set_paused(paused);
+        }
+        self.animations.clear();
+        self.spatial.clear();
+        self.attribute_index.clear();
+        self.style.resolve(&self.graph);
+        self.force.reset();
+        self.refresh();
+    }
+
+    /// Apply `command`, recording how to undo it in [`Self::history`]. Errors
+    /// are logged.
+    pub fn execute(&mut self, command: Command) {
+        let label = command.label();
+        let merge = command.merge_key();
+        match command.apply(self) {
+            Ok(undo) => self.history.push(label, merge, undo),
+            Err(e) => self.log.error(format!("{label} failed: {e}")),
+        }
+    }
+
+    /// Undo the last step of [`Self::history`]. Returns `false` if there was
+    /// none, or undoing it failed.
+    pub fn undo(&mut self) -> bool {
+        let Some((label, undo)) = self.history.pop_undo() else {
+            return false;
+        };
+        match undo.apply(self) {
+            Ok(redo) => {
+                self.history.push_undone(label, redo);
+                true
+            }
+            Err(e) => {
+                self.log.error(format!("Undo {label} failed: {e}"));
+                false
+            }
+        }
+    }
+
+    /// Redo the last undone step of [`Self::history`]. Returns `false` if
+    /// there was none, or redoing it failed.
+    pub fn redo(&mut self) -> bool {
+        let Some((label, redo)) = self.history.pop_redo() else {
+            return false;
+        };
+        match redo.apply(self) {
+            Ok(undo) => {
+                self.history.push_redone(label, undo);
+                true
+            }
+            Err(e) => {
+                self.log.error(format!("Redo {label} failed: {e}"));
+                false
+            }
+        }
+    }
+
+    /// The part of [`Self::graph`] left visible by [`Self::filters`], as of the
+    /// last [`Self::refresh()`].
+    pub fn visible_graph(&self) -> &Graph {
+        self.filters.graph(&self.graph)
+    }
+
+    /// Bring derived state up to date, once per frame:
+    /// - answer the requests of [`Self::remote`] it can
+    /// - merge neighborhoods fetched by [`Self::exploration`] into the graph
+    /// - write finished [`Self::metrics`] to the graph
+    /// - move [`Self::timeline`]'s window if playing
+    /// - apply changes to [`Self::filters`], the graph, or the selection to the
+    ///   visible graph, restarting the force layout if it changed
+    /// - re-resolve [`Self::style`] and [`Self::groups`] if the graph changed
+    /// - update [`Self::routes`] with the visible graph and positions
+    /// - find the paths of [`Self::path_finder`] again if the visible graph
+    ///   changed
+    /// - measure the memory held every [`memory::MEASURE_INTERVAL`]
+    pub fn refresh(&mut self) {
+        self.step_loads();
+        self.step_live();
+        #[cfg(feature = "sqlite")]
+        self.step_store();
+        #[cfg(feature = "http")]
+        remote::step(self);
+        explore::step(self);
+        // jobs report their results themselves, failures are logged here
+        for event in self.jobs.drain_events() {
+            if let JobOutcome::Panicked(message) = event.outcome {
+                self.log
+                    .error(format!("Job '{}' failed: {message}", event.name));
+            }
+        }
+        if let Some(job) = &mut self.metrics
+            && let Some(result) = job.try_finish()
+        {
+            self.metrics = None;
+            if !result.scores.is_empty() {
+                self.execute(result.command(&self.graph));
+                let names: Vec<&str> = result.scores.iter().map(|(m, _)| m.label()).collect();
+                self.log.info(format!(
+                    "Computed {} in {:.2?}",
+                    names.join(", "),
+                    result.elapsed
+                ));
+            }
+        }
+        self.style.refresh(&self.graph);
+        self.groups.update(&self.graph);
+        self.timeline.update(&self.graph, &mut self.filters);
+        if self.filters.update(
+            &self.graph,
+            &self.selection,
+            &self.groups,
+            &mut self.attribute_index,
+        ) {
+            self.force.graph_changed(&mut self.positions);
+        }
+        self.routes
+            .update(self.filters.graph(&self.graph), &self.positions);
+        self.path_finder.update(self.filters.graph(&self.graph));
+        memory::step(self);
+    }
+
+    /// Add what the loads of the active document parsed to its graph, and log
+    /// those which finished. Loads of closed documents are cancelled.
+    fn step_loads(&mut self) {
+        let active = self.documents.active_document().id();
+        let mut loads = std::mem::take(&mut self.loads);
+        loads.retain_mut(|job| {
+            if !self
+                .documents
+                .documents()
+                .iter()
+                .any(|d| d.id() == job.document())
+            {
+                job.cancel();
+                return false;
+            }
+            if job.document() != active {
+                return true;
+            }
+            let nodes = self.graph.node_count();
+            let step = job.step(&mut self.graph, LOAD_BUDGET);
+            if self.graph.node_count() != nodes {
+                self.positions.sync(&self.graph);
+                self.force.graph_changed(&mut self.positions);
+                // show the graph as soon as it appears
+                if nodes == 0 {
+                    self.camera.fit_to_graph(&self.graph, &self.positions);
+                }
+            }
+            let path = job.path().to_owned();
+            match step {
+                LoadStep::Loading => return true,
+                LoadStep::Done => {
+                    self.log.info(format!(
+                        "Opened '{}': {} nodes, {} edges in {:.2?}",
+                        path.display(),
+                        self.graph.node_count(),
+                        self.graph.edge_count(),
+                        job.elapsed()
+                    ));
+                    self.documents.recent.push(&path);
+                    self.apply_default_layout();
+                    self.camera.fit_to_graph(&self.graph, &self.positions);
+                }
+                LoadStep::Failed(_) if job.is_cancelled() => {
+                    self.log
+                        .info(format!("Cancelled opening '{}'", path.display()));
+                }
+                LoadStep::Failed(e) => {
+                    self.log
+                        .error(format!("Failed to open '{}': {e}", path.display()));
+                    self.documents.recent.remove(&path);
+                }
+            }
+            // the graph was only filled in, there is nothing to undo
+            self.history.clear();
+            let generation = self.graph.generation();
+            self.documents
+                .active_document_mut()
+                .set_clean(Some(path), generation);
+            false
+        });
+        loads.append(&mut self.loads);
+        self.loads = loads;
+    }
+
+    /// Apply the changes [`Self::live`] received, if its document is active.
+    /// The server stops once its document is closed.
+    fn step_live(&mut self) {
+        let Some(live) = &mut self.live else {
+            return;
+        };
+        let document = live.document();
+        if !self
+            .documents
+            .documents()
+            .iter()
+            .any(|d| d.id() == document)
+        {
+            self.log
+                .info(format!("Stopped listening on {}", live.address()));
+            self.live = None;
+            return;
+        }
+        if document == self.documents.active_document().id() && live.step(&mut self.graph) {
+            self.positions.sync(&self.graph);
+            self.force.graph_changed(&mut self.positions);
+        }
+    }
+
+    /// Load the part of [`Self::store`] around the camera once it moved away
+    /// from the cached one, and write back changes every few seconds, if its
+    /// document is active. The store closes once its document is closed.
+    #[cfg(feature = "sqlite")]
+    fn step_store(&mut self) {
+        let Some(cache) = &mut self.store else {
+            return;
+        };
+        let document = cache.document();
+        if !self
+            .documents
+            .documents()
+            .iter()
+            .any(|d| d.id() == document)
+        {
+            self.store = None;
+            return;
+        }
+        let Some(visible) = self.camera.visible_rect() else {
+            return;
+        };
+        if document != self.documents.active_document().id() || self.camera.is_animating() {
+            return;
+        }
+        let mut reload = cache.needs_reload(visible);
+        if !reload && !cache.flush_due() {
+            return;
+        }
+        self.force.sync_positions(&mut self.positions);
+        let mut result = if reload {
+            cache.reload(visible, &mut self.graph, &mut self.positions)
+        } else {
+            cache.flush(&self.graph, &self.positions)
+        };
+        if let Err(e @ StoreError::Replaced) = &result {
+            // show the store's own nodes again
+            self.log.error(format!("{e}, discarding them"));
+            cache.discard();
+            reload = true;
+            result = cache.reload(visible, &mut self.graph, &mut self.positions);
+        }
+        if let Err(e) = result {
+            self.log.error(format!(
+                "Failed to use graph store '{}': {e}",
+                cache.store().path().display()
+            ));
+            self.store = None;
+            return;
+        }
+        if reload {
+            // edits to the previous part cannot be undone
+            self.history.clear();
+            self.graph_replaced();
+        }
+        // the store is saved
+        let generation = self.graph.generation();
+        self.documents
+            .active_document_mut()
+            .set_clean(None, generation);
+    }
+
+    /// Bring [`Self::spatial`] up to date with the visible graph's current
+    /// positions, reading them back from the GPU layout if needed.
+    pub fn sync_spatial(&mut self) {
+        self.force.sync_positions(&mut self.positions);
+        self.spatial
+            .sync(self.filters.graph(&self.graph), &self.positions);
+    }
+
+    /// Lay the visible graph out with the layout at `index` in [`Self::layouts`],
+    /// pausing the force layout so it does not undo the result. The nodes are
+    /// at their new positions right away, and glide there from the old ones
+    /// as [`anim::update()`] runs.
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn apply_layout(&mut self, index: usize) {
+        self.force.set_paused(true);
+        self.force.sync_positions(&mut self.positions);
+        let from = self.positions.clone();
+        let graph = self.filters.graph(&self.graph);
+        self.layouts[index].compute(graph, &mut self.positions);
+        self.animations.transition(graph, &from, &self.positions);
+    }
+
+    /// Place a newly opened graph with the
+    /// [default layout](crate::subsystems::config::Config::default_layout), if
+    /// it is one of [`Self::layouts`]. Otherwise the force layout keeps
+    /// placing it.
+    pub fn apply_default_layout(&mut self) {
+        let name = &self.config.get().default_layout;
+        if let Some(index) = self.layouts.iter().position(|l| l.name() == name) {
+            self.apply_layout(index);
+        }
+    }
+}
@@ -0,0 +1,367 @@
+//! # Documents
+//!
+//! Several graphs can be open at once, each as a [`Document`] with its own
+//! selection, filters, positions, pinned nodes, camera view, bookmarks, and
+//! undo history.
+//! The active document's state lives in the [`EngineContext`] as usual, so
+//! panels and subsystems work on it unchanged; the others are stashed in the
+//! context's [`DocumentManager`] until
+//! [switched to](EngineContext::switch_document()).
+//!
+//! A document is dirty once its graph changed since it was opened or saved.
+//! The manager also keeps the [`RecentFiles`] list.
+//!
+//! ```ignore
+//! ctx.open_document(io::load(&path)?, Some(path.clone()));
+//! ctx.documents.recent.push(&path);
+//! ctx.switch_document(0);
+//! if !ctx.is_document_dirty(1) {
+//!     ctx.close_document(1);
+//! }
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use std::path::{Path, PathBuf};
+
+use crate::algo::metrics::MetricsJob;
+use crate::bookmarks::Bookmarks;
+use crate::context::EngineContext;
+use crate::filter::FilterStack;
+use crate::graph::{EdgeId, Graph, NodeId};
+use crate::history::CommandStack;
+use crate::layout::Positions;
+use crate::routing::EdgeRoutes;
+use crate::search::AttributeIndex;
+use crate::selection::SelectMode;
+use crate::spatial::SpatialIndex;
+use crate::timeline::Timeline;
+use crate::view3d::View3d;
+
+/// Name of the recent files list inside the config directory.
+pub const RECENT_FILE: &str = "recent.toml";
+
+/// Number of files kept by [`RecentFiles`].
+const RECENT_LIMIT: usize = 10;
+
+/// Title of documents without a file.
+const UNTITLED: &str = "Untitled";
+
+/// An open graph, see the [module documentation](self).
+pub struct Document {
+    /// Unique among the documents of a manager, eg. to tell tabs apart.
+    id: u64,
+    path: Option<PathBuf>,
+    /// Generation of the graph when it was last opened or saved, `None` if
+    /// it has changes which were never saved, eg. once recovered.
+    clean: Option<u64>,
+    /// The document's state while another one is active.
+    stash: Option<Box<Stash>>,
+}
+
+/// The per-document part of an [`EngineContext`], taken out of it while the
+/// document is not active.
+pub(crate) struct Stash {
+    pub(crate) graph: Graph,
+    pub(crate) filters: FilterStack,
+    pub(crate) positions: Positions,
+    pub(crate) routes: EdgeRoutes,
+    timeline: Timeline,
+    pub(crate) pinned: Vec<NodeId>,
+    pub(crate) paused: bool,
+    pub(crate) spatial: SpatialIndex,
+    attribute_index: AttributeIndex,
+    history: CommandStack,
+    selection: (Vec<NodeId>, Vec<EdgeId>),
+    metrics: Option<MetricsJob>,
+    pub(crate) view: Option<([f32; 2], f32)>,
+    pub(crate) bookmarks: Bookmarks,
+    view3d: View3d,
+}
+
+/// The open [`Document`]s, exactly one of which is active.
+pub struct DocumentManager {
+    documents: Vec<Document>,
+    active: usize,
+    next_id: u64,
+    /// Files opened lately, eg. for an "Open Recent" menu.
+    pub recent: RecentFiles,
+}
+
+/// Files opened lately, the most recent first, optionally persisted to a file.
+#[derive(Clone, Debug, Default)]
+pub struct RecentFiles {
+    files: Vec<PathBuf>,
+    /// Where the list is saved, if anywhere.
+    path: Option<PathBuf>,
+}
+
+/// Contents of the [`RECENT_FILE`].
+#[derive(Serialize, Deserialize)]
+struct RecentList {
+    files: Vec<PathBuf>,
+}
+
+impl Document {
+    /// Unique id of the document among the open ones.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// The file the document was opened from or saved to, if any.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// The name of the document's file, or `Untitled`.
+    pub fn title(&self) -> String {
+        self.path
+            .as_deref()
+            .and_then(Path::file_name)
+            .map_or(UNTITLED.into(), |name| name.to_string_lossy().into_owned())
+    }
+
+    /// Record that the document's graph was opened or saved at `generation`,
+    /// from or to `path`.
+    pub(crate) fn set_clean(&mut self, path: Option<PathBuf>, generation: u64) {
+        self.path = path;
+        self.clean = Some(generation);
+    }
+
+    /// Record that the document has unsaved changes, whatever its graph's
+    /// generation.
+    pub(crate) fn set_dirty(&mut self) {
+        self.clean = None;
+    }
+
+    /// Returns `true` if the document's graph changed since it was last opened
+    /// or saved, given the generation of its graph.
+    fn is_dirty(&self, generation: u64) -> bool {
+        self.clean != Some(generation)
+    }
+}
+
+impl Stash {
+    /// Take the active document's state out of `ctx`, leaving an empty graph.
+    pub(crate) fn take(ctx: &mut EngineContext) -> Self {
+        ctx.force.sync_positions(&mut ctx.positions);
+        let stash = Self {
+            graph: std::mem::take(&mut ctx.graph),
+            filters: std::mem::take(&mut ctx.filters),
+            positions: std::mem::take(&mut ctx.positions),
+            routes: std::mem::take(&mut ctx.routes),
+            timeline: std::mem::take(&mut ctx.timeline),
+            pinned: ctx.force.pinned().collect(),
+            paused: ctx.force.is_paused(),
+            spatial: std::mem::take(&mut ctx.spatial),
+            attribute_index: std::mem::take(&mut ctx.attribute_index),
+            history: std::mem::take(&mut ctx.history),
+            selection: (
+                ctx.selection.nodes().collect(),
+                ctx.selection.edges().collect(),
+            ),
+            metrics: ctx.metrics.take(),
+            view: ctx.camera.view(),
+            bookmarks: std::mem::take(&mut ctx.bookmarks),
+            view3d: std::mem::take(&mut ctx.view3d),
+        };
+        ctx.selection.clear();
+        ctx.path_finder.clear();
+        ctx.force.reset();
+        stash
+    }
+
+    /// Make this the active document's state in `ctx`, dropping the current one.
+    pub(crate) fn restore(self, ctx: &mut EngineContext) {
+        if let Some(job) = ctx.metrics.take() {
+            job.cancel();
+        }
+        ctx.graph = self.graph;
+        ctx.filters = self.filters;
+        ctx.positions = self.positions;
+        ctx.routes = self.routes;
+        ctx.timeline = self.timeline;
+        ctx.spatial = self.spatial;
+        ctx.attribute_index = self.attribute_index;
+        ctx.history = self.history;
+        ctx.metrics = self.metrics;
+        ctx.bookmarks = self.bookmarks;
+        ctx.view3d = self.view3d;
+        let (nodes, edges) = self.selection;
+        ctx.selection.select(nodes, edges, SelectMode::Replace);
+        ctx.force.reset();
+        for node in self.pinned {
+            ctx.force.set_pinned(node, true);
+        }
+        ctx.force.set_paused(self.paused);
+        if let Some((center, zoom)) = self.view {
+            ctx.camera.set_view(center, zoom);
+        }
+        ctx.style.resolve(&ctx.graph);
+        ctx.refresh();
+    }
+}
+
+impl DocumentManager {
+    /// Create a manager with a single empty, untitled document.
+    pub fn new() -> Self {
+        Self {
+            documents: vec![Document {
+                id: 0,
+                path: None,
+                clean: Some(Graph::default().generation()),
+                stash: None,
+            }],
+            active: 0,
+            next_id: 1,
+            recent: RecentFiles::default(),
+        }
+    }
+
+    /// The open documents, in tab order.
+    pub fn documents(&self) -> &[Document] {
+        &self.documents
+    }
+
+    /// Index of the active document.
+    pub fn active(&self) -> usize {
+        self.active
+    }
+
+    /// The active document.
+    pub fn active_document(&self) -> &Document {
+        &self.documents[self.active]
+    }
+
+    pub(crate) fn active_document_mut(&mut self) -> &mut Document {
+        &mut self.documents[self.active]
+    }
+
+    /// Returns `true` if the document at `index` is dirty, given the
+    /// generation of the active document's graph.
+    pub(crate) fn is_dirty(&self, index: usize, active_generation: u64) -> bool {
+        let document = &self.documents[index];
+        match &document.stash {
+            Some(stash) => document.is_dirty(stash.graph.generation()),
+            None => document.is_dirty(active_generation),
+        }
+    }
+
+    /// Add an untitled document, stashing the active document's state as
+    /// `stash`. The new document becomes active.
+    pub(crate) fn push(&mut self, stash: Stash) {
+        self.documents[self.active].stash = Some(Box::new(stash));
+        self.documents.push(Document {
+            id: self.next_id,
+            path: None,
+            clean: Some(0),
+            stash: None,
+        });
+        self.next_id += 1;
+        self.active = self.documents.len() - 1;
+    }
+
+    /// Make the document at `index` active, stashing the active document's
+    /// state as `stash`. Returns the state of the newly active document.
+    ///
+    /// Panics if `index` is out of bounds or already active.
+    pub(crate) fn switch(&mut self, index: usize, stash: Stash) -> Stash {
+        assert_ne!(index, self.active, "Tried to switch to the active document");
+        let restored = *self.documents[index].stash.take().unwrap();
+        self.documents[self.active].stash = Some(Box::new(stash));
+        self.active = index;
+        restored
+    }
+
+    /// The state of the document at `index`, or `None` for the active one.
+    pub(crate) fn stash(&self, index: usize) -> Option<&Stash> {
+        self.documents[index].stash.as_deref()
+    }
+
+    /// Forget every document but the active one.
+    pub(crate) fn remove_others(&mut self) {
+        for index in (0..self.documents.len()).rev() {
+            if index != self.active {
+                self.remove(index);
+            }
+        }
+    }
+
+    /// Forget the document at `index`, which is not the active one.
+    pub(crate) fn remove(&mut self, index: usize) {
+        assert_ne!(index, self.active, "Tried to remove the active document");
+        let document = self.documents.remove(index);
+        if let Some(job) = document.stash.and_then(|stash| stash.metrics) {
+            job.cancel();
+        }
+        if index < self.active {
+            self.active -= 1;
+        }
+    }
+}
+
+impl RecentFiles {
+    /// Load the list from `path`, starting empty if it cannot be read. The
+    /// list is saved back there whenever it changes.
+    pub fn load_or_default(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+        let files = std::fs::read_to_string(path)
+            .ok()
+            .and_then(|text| toml::from_str::<RecentList>(&text).ok())
+            .map(|list| list.files)
+            .unwrap_or_default();
+        Self {
+            files,
+            path: Some(path.to_owned()),
+        }
+    }
+
+    /// The files, the most recent first.
+    pub fn files(&self) -> &[PathBuf] {
+        &self.files
+    }
+
+    /// Move `path` to the top of the list.
+    pub fn push(&mut self, path: &Path) {
+        self.files.retain(|file| file != path);
+        self.files.insert(0, path.to_owned());
+        self.files.truncate(RECENT_LIMIT);
+        self.save();
+    }
+
+    /// Remove `path` from the list, eg. once it failed to open.
+    pub fn remove(&mut self, path: &Path) {
+        let len = self.files.len();
+        self.files.retain(|file| file != path);
+        if self.files.len() != len {
+            self.save();
+        }
+    }
+
+    /// Empty the list.
+    pub fn clear(&mut self) {
+        self.files.clear();
+        self.save();
+    }
+
+    fn save(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let list = RecentList {
+            files: self.files.clone(),
+        };
+        let result = toml::to_string(&list)
+            .map_err(std::io::Error::other)
+            .and_then(|text| std::fs::write(path, text));
+        if let Err(e) = result {
+            eprintln!("Failed to save recent files '{}': {e}", path.display());
+        }
+    }
+}
+
+impl std::default::Default for DocumentManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,663 @@
+//! # Neighborhood Exploration
+//!
+//! Big graphs are explored a neighborhood at a time rather than shown whole.
+//! An [`Exploration`] ties a document to a [`NeighborSource`] holding the full
+//! graph, and the document shows only the part explored so far:
+//! - [`explore()`] opens the selected nodes in a new document, exploring the
+//!   active graph, or the [graph store](crate::store) around the camera.
+//! - [`expand()`] adds the neighbors of nodes up to [`Exploration::depth`] hops
+//!   away, fetched from the source on a worker thread and placed around them.
+//! - [`collapse()`] hides what was reached only through a node behind a
+//!   meta-node counting the hidden nodes, which expands back into them.
+//!
+//! Sources key nodes and edges by their [`ID_ATTR`], which is how fetched
+//! neighborhoods are [merged](merge) into the document without duplicates.
+//! Query results of the [Neo4j connector](crate::io::neo4j) are explored in
+//! the database the same way.
+//!
+//! ```ignore
+//! explore::explore(&mut ctx, &[hub], 2, 50);
+//! // once the neighborhood arrived
+//! explore::collapse(&mut ctx, &[hub]);
+//! ```
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::thread::JoinHandle;
+
+use crate::context::EngineContext;
+use crate::graph::{Attributes, Graph, NodeId};
+use crate::history::Command;
+use crate::i18n::tr;
+use crate::imgui::Ui;
+#[cfg(feature = "neo4j")]
+use crate::io::neo4j::Neo4jError;
+use crate::io::{ID_ATTR, node_name};
+use crate::layout::Positions;
+use crate::selection::SelectMode;
+#[cfg(feature = "sqlite")]
+use crate::store::{StoreError, StoreSource};
+use crate::subsystems::panels::Panel;
+
+/// Attribute of meta-nodes holding the number of nodes they hide.
+pub const COLLAPSED_ATTR: &str = "collapsed";
+
+/// Hops expanded by default.
+pub const DEFAULT_DEPTH: usize = 1;
+/// Neighbors fetched per node by default.
+pub const DEFAULT_LIMIT: usize = 100;
+/// Deepest expansion offered by [`ExplorePanel`].
+const MAX_DEPTH: usize = 5;
+
+/// Distance between an expanded node and its new neighbors, in world units.
+const EXPAND_RADIUS: f32 = 60.0;
+/// Angle between consecutive neighbors placed around a node.
+const GOLDEN_ANGLE: f32 = 2.399_963;
+
+/// Where an [`Exploration`] fetches neighborhoods from.
+pub trait NeighborSource: Send {
+    /// Human readable description, eg. for a panel.
+    fn name(&self) -> String;
+
+    /// The nodes with `keys`, up to `limit` neighbors of each, and the edges
+    /// between each node and its neighbors, with their keys as [`ID_ATTR`].
+    fn neighbors(&mut self, keys: &[String], limit: usize) -> Result<Graph, ExploreError>;
+}
+
+/// A graph held in memory, eg. a copy of a document's graph.
+pub struct MemorySource {
+    graph: Graph,
+    /// Node of each key.
+    index: HashMap<String, NodeId>,
+}
+
+/// A document showing part of the graph of a [`NeighborSource`], see the
+/// [module documentation](self).
+pub struct Exploration {
+    /// Taken while [`Self::job`] runs.
+    source: Option<Box<dyn NeighborSource>>,
+    name: String,
+    /// Id of the document showing the exploration.
+    document: u64,
+    /// Keys of the nodes the exploration started from, never collapsed away.
+    seeds: HashSet<String>,
+    /// Hops expanded at once.
+    pub depth: usize,
+    /// Neighbors fetched per node.
+    pub limit: usize,
+    job: Option<ExpandJob>,
+}
+
+/// Neighborhoods being fetched on a worker thread.
+struct ExpandJob {
+    handle: JoinHandle<(Box<dyn NeighborSource>, Result<Graph, ExploreError>)>,
+    /// Keys of the nodes being expanded.
+    keys: Vec<String>,
+}
+
+/// Errors which can occur while fetching neighborhoods.
+#[derive(Debug)]
+pub enum ExploreError {
+    #[cfg(feature = "sqlite")]
+    Store(StoreError),
+    #[cfg(feature = "neo4j")]
+    Neo4j(Neo4jError),
+    /// The fetching thread panicked.
+    Panicked,
+}
+
+/// Starts explorations, and expands and collapses the selected nodes.
+pub struct ExplorePanel {
+    /// Settings of explorations started from the panel.
+    depth: usize,
+    limit: usize,
+}
+
+/// Start exploring from `nodes` of the active document, in a new document
+/// showing them and their neighbors up to `depth` hops away. The document's
+/// graph, or the [graph store](crate::store) it shows, is the source. An
+/// exploration started before ends.
+pub fn explore(ctx: &mut EngineContext, nodes: &[NodeId], depth: usize, limit: usize) {
+    if nodes.is_empty() {
+        return;
+    }
+    let source = source(ctx, nodes);
+    let (source, keys): (Box<dyn NeighborSource>, Vec<String>) = match source {
+        Ok(source) => source,
+        Err(e) => {
+            ctx.log.error(format!("Failed to start exploring: {e}"));
+            return;
+        }
+    };
+    ctx.force.sync_positions(&mut ctx.positions);
+    let mut graph = Graph::new(ctx.graph.directedness());
+    let mut points = Vec::new();
+    for (&node, key) in nodes.iter().zip(&keys) {
+        let mut attrs = ctx.graph.node(node).cloned().unwrap_or_default();
+        attrs.insert(ID_ATTR, key.as_str());
+        let seed = graph.add_node_with(attrs);
+        points.extend(ctx.positions.get(node).map(|point| (seed, point)));
+    }
+
+    let name = source.name();
+    ctx.open_document(graph, None);
+    for (node, point) in points {
+        ctx.positions.set(node, point);
+    }
+    ctx.force.graph_changed(&mut ctx.positions);
+    ctx.camera.fit_to_graph(&ctx.graph, &ctx.positions);
+    ctx.log
+        .info(format!("Exploring {name} from {} nodes", keys.len()));
+    let document = ctx.documents.active_document().id();
+    let mut exploration = Exploration::new(source, document, keys.iter().cloned());
+    exploration.depth = depth.max(1);
+    exploration.limit = limit.max(1);
+    exploration.expand(keys);
+    ctx.exploration = Some(exploration);
+}
+
+/// The source to explore from `nodes` of the active document, and their keys.
+fn source(
+    ctx: &EngineContext,
+    nodes: &[NodeId],
+) -> Result<(Box<dyn NeighborSource>, Vec<String>), ExploreError> {
+    #[cfg(feature = "sqlite")]
+    if let Some(cache) = &ctx.store
+        && cache.document() == ctx.documents.active_document().id()
+    {
+        let (source, keys) = StoreSource::from_cache(cache, &ctx.graph, nodes)?;
+        return Ok((Box::new(source), keys));
+    }
+    let keys = nodes.iter().map(|&n| node_name(&ctx.graph, n)).collect();
+    Ok((Box::new(MemorySource::new(ctx.graph.clone())), keys))
+}
+
+/// Fetch the neighborhoods of `nodes` of the active document, which must show
+/// [`EngineContext::exploration`]. Meta-nodes expand into what they hide.
+pub fn expand(ctx: &mut EngineContext, nodes: &[NodeId]) {
+    let mut keys: Vec<String> = nodes
+        .iter()
+        .flat_map(|&node| match is_meta(&ctx.graph, node) {
+            true => ctx.graph.neighbors(node).collect(),
+            false => vec![node],
+        })
+        .filter_map(|node| key(&ctx.graph, node))
+        .collect();
+    keys.sort_unstable();
+    keys.dedup();
+    if let Some(exploration) = active(ctx)
+        && !keys.is_empty()
+    {
+        exploration.expand(keys);
+    }
+}
+
+/// Replace what was reached only through `nodes` of the active document,
+/// which must show [`EngineContext::exploration`], by a meta-node attached to
+/// each, as an undoable step.
+pub fn collapse(ctx: &mut EngineContext, nodes: &[NodeId]) {
+    let Some(exploration) = active(ctx) else {
+        return;
+    };
+    let seeds = exploration.seeds.clone();
+    let seeds: Vec<NodeId> = ctx
+        .graph
+        .nodes()
+        .filter(|&n| key(&ctx.graph, n).is_some_and(|key| seeds.contains(&key)))
+        .collect();
+    ctx.force.sync_positions(&mut ctx.positions);
+    let mut graph = ctx.graph.clone();
+    let mut positions = ctx.positions.clone();
+    let mut hidden_total = 0;
+    for &node in nodes {
+        if !graph.contains_node(node) || is_meta(&graph, node) {
+            continue;
+        }
+        let hidden = hidden_behind(&graph, node, &seeds);
+        if hidden.is_empty() {
+            continue;
+        }
+        let count: usize = hidden
+            .iter()
+            .map(
+                |&n| match graph.node(n).and_then(|a| a.get(COLLAPSED_ATTR)) {
+                    Some(count) => count.as_f64().unwrap_or(0.0) as usize,
+                    None => 1,
+                },
+            )
+            .sum();
+        for &n in &hidden {
+            graph.remove_node(n);
+        }
+        let mut attrs = Attributes::default();
+        attrs.insert("label", format!("+{count}"));
+        attrs.insert(COLLAPSED_ATTR, count as i64);
+        let meta = graph.add_node_with(attrs);
+        let _ = graph.add_edge_with(node, meta, Attributes::default());
+        if let Some([x, y]) = positions.get(node) {
+            positions.set(meta, [x + EXPAND_RADIUS, y]);
+        }
+        hidden_total += count;
+    }
+    if hidden_total == 0 {
+        return;
+    }
+    ctx.execute(Command::Batch {
+        label: "Collapse Neighbors".into(),
+        commands: vec![Command::ReplaceGraph {
+            graph: Box::new(graph),
+            positions: Box::new(positions),
+        }],
+    });
+    ctx.log.info(format!("Collapsed {hidden_total} nodes"));
+}
+
+/// The nodes of `node`'s component which are not connected to any of `seeds`
+/// but through `node`, ignoring edge directions.
+fn hidden_behind(graph: &Graph, node: NodeId, seeds: &[NodeId]) -> Vec<NodeId> {
+    let reachable = |starts: &mut dyn Iterator<Item = NodeId>| {
+        let mut seen: HashSet<NodeId> = HashSet::from([node]);
+        let mut queue: VecDeque<NodeId> = starts.filter(|&n| seen.insert(n)).collect();
+        let mut order = Vec::new();
+        while let Some(n) = queue.pop_front() {
+            order.push(n);
+            for neighbor in graph.neighbors(n) {
+                if seen.insert(neighbor) {
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+        order
+    };
+    let kept: HashSet<NodeId> = reachable(&mut seeds.iter().copied()).into_iter().collect();
+    reachable(&mut graph.neighbors(node))
+        .into_iter()
+        .filter(|n| !kept.contains(n))
+        .collect()
+}
+
+/// Add the nodes and edges of `other` missing from `graph`, matching them by
+/// [`ID_ATTR`]. Returns the added nodes.
+pub fn merge(graph: &mut Graph, other: &Graph) -> Vec<NodeId> {
+    let id = |attrs: Option<&Attributes>| Some(attrs?.get(ID_ATTR)?.to_string());
+    let mut nodes: HashMap<String, NodeId> = graph
+        .nodes()
+        .filter_map(|node| Some((id(graph.node(node))?, node)))
+        .collect();
+    let mut edges: HashSet<String> = graph
+        .edges()
+        .filter_map(|edge| id(graph.edge(edge)))
+        .collect();
+
+    let mut added = Vec::new();
+    let mut mapped = HashMap::new();
+    for node in other.nodes() {
+        let attrs = other.node(node).unwrap();
+        let mapped_node = match id(Some(attrs)).and_then(|key| nodes.get(&key).copied()) {
+            Some(existing) => existing,
+            None => {
+                let new = graph.add_node_with(attrs.clone());
+                if let Some(key) = id(Some(attrs)) {
+                    nodes.insert(key, new);
+                }
+                added.push(new);
+                new
+            }
+        };
+        mapped.insert(node, mapped_node);
+    }
+    for edge in other.edges() {
+        let attrs = other.edge(edge).unwrap();
+        if let Some(key) = id(Some(attrs))
+            && !edges.insert(key)
+        {
+            continue;
+        }
+        let (source, target) = other.endpoints(edge).unwrap();
+        let _ = graph.add_edge_with(mapped[&source], mapped[&target], attrs.clone());
+    }
+    added
+}
+
+/// The key of `node`, its [`ID_ATTR`]. Meta-nodes have none.
+fn key(graph: &Graph, node: NodeId) -> Option<String> {
+    Some(graph.node(node)?.get(ID_ATTR)?.to_string())
+}
+
+fn is_meta(graph: &Graph, node: NodeId) -> bool {
+    graph.node(node).is_some_and(|a| a.contains(COLLAPSED_ATTR))
+}
+
+/// The exploration shown by the active document, if any.
+fn active(ctx: &mut EngineContext) -> Option<&mut Exploration> {
+    let document = ctx.documents.active_document().id();
+    ctx.exploration
+        .as_mut()
+        .filter(|exploration| exploration.document == document)
+}
+
+/// Merge finished neighborhoods into the active document if it shows
+/// [`EngineContext::exploration`], as an undoable step selecting the new nodes.
+pub(crate) fn step(ctx: &mut EngineContext) {
+    let Some(exploration) = active(ctx) else {
+        return;
+    };
+    let Some((keys, result)) = exploration.try_finish() else {
+        return;
+    };
+    let fetched = match result {
+        Ok(fetched) => fetched,
+        Err(e) => {
+            ctx.log.error(format!("Failed to expand: {e}"));
+            return;
+        }
+    };
+
+    ctx.force.sync_positions(&mut ctx.positions);
+    let mut graph = ctx.graph.clone();
+    let expanded: HashSet<NodeId> = graph
+        .nodes()
+        .filter(|&n| key(&graph, n).is_some_and(|key| keys.contains(&key)))
+        .collect();
+    // meta-nodes of the expanded nodes give way to what they hid
+    let metas: Vec<NodeId> = expanded
+        .iter()
+        .flat_map(|&n| graph.neighbors(n))
+        .filter(|&n| is_meta(&graph, n))
+        .collect();
+    for meta in metas {
+        graph.remove_node(meta);
+    }
+    let added = merge(&mut graph, &fetched);
+    let mut positions = ctx.positions.clone();
+    place(&graph, &added, &mut positions);
+    if graph.node_count() == ctx.graph.node_count() && graph.edge_count() == ctx.graph.edge_count()
+    {
+        ctx.log.info("No neighbors left to expand");
+        return;
+    }
+    ctx.execute(Command::Batch {
+        label: "Expand Neighbors".into(),
+        commands: vec![Command::ReplaceGraph {
+            graph: Box::new(graph),
+            positions: Box::new(positions),
+        }],
+    });
+    ctx.log.info(format!("Expanded {} nodes", added.len()));
+    ctx.selection.select(added, [], SelectMode::Replace);
+}
+
+/// Spread `added` nodes around a neighbor placed before them, by the golden
+/// angle.
+fn place(graph: &Graph, added: &[NodeId], positions: &mut Positions) {
+    positions.sync(graph);
+    let mut placed: HashSet<NodeId> = graph.nodes().collect();
+    for node in added {
+        placed.remove(node);
+    }
+    let mut around: HashMap<NodeId, usize> = HashMap::new();
+    // nodes further away were added after their neighbors, so they find one
+    for &node in added {
+        let Some(anchor) = graph.neighbors(node).find(|n| placed.contains(n)) else {
+            continue;
+        };
+        if let Some([x, y]) = positions.get(anchor) {
+            let i = around.entry(anchor).or_default();
+            let angle = *i as f32 * GOLDEN_ANGLE;
+            let r = EXPAND_RADIUS * (1.0 + *i as f32 / 16.0).sqrt();
+            positions.set(node, [x + r * angle.cos(), y + r * angle.sin()]);
+            *i += 1;
+        }
+        placed.insert(node);
+    }
+}
+
+impl MemorySource {
+    pub fn new(graph: Graph) -> Self {
+        let index = graph.nodes().map(|n| (node_name(&graph, n), n)).collect();
+        Self { graph, index }
+    }
+}
+
+impl NeighborSource for MemorySource {
+    fn name(&self) -> String {
+        format!(
+            "a graph of {} nodes and {} edges",
+            self.graph.node_count(),
+            self.graph.edge_count()
+        )
+    }
+
+    fn neighbors(&mut self, keys: &[String], limit: usize) -> Result<Graph, ExploreError> {
+        let graph = &self.graph;
+        let mut result = Graph::new(graph.directedness());
+        let mut mapped: HashMap<NodeId, NodeId> = HashMap::new();
+        let mut add = |result: &mut Graph, node: NodeId| {
+            *mapped.entry(node).or_insert_with(|| {
+                let mut attrs = graph.node(node).cloned().unwrap_or_default();
+                attrs.insert(ID_ATTR, node_name(graph, node));
+                result.add_node_with(attrs)
+            })
+        };
+        for key in keys {
+            let Some(&node) = self.index.get(key) else {
+                continue;
+            };
+            add(&mut result, node);
+            let mut neighbors = HashSet::new();
+            for edge in graph.incident_edges(node) {
+                let Some(neighbor) = graph.opposite(edge, node) else {
+                    continue;
+                };
+                if !neighbors.contains(&neighbor) && neighbors.len() == limit {
+                    continue;
+                }
+                neighbors.insert(neighbor);
+                let (source, target) = graph.endpoints(edge).unwrap();
+                let (source, target) = (add(&mut result, source), add(&mut result, target));
+                let mut attrs = graph.edge(edge).cloned().unwrap_or_default();
+                if !attrs.contains(ID_ATTR) {
+                    attrs.insert(ID_ATTR, edge.to_string());
+                }
+                let _ = result.add_edge_with(source, target, attrs);
+            }
+        }
+        Ok(result)
+    }
+}
+
+impl Exploration {
+    /// Explore `source` in the document with id `document`, starting from the
+    /// nodes with keys `seeds`.
+    pub fn new(
+        source: Box<dyn NeighborSource>,
+        document: u64,
+        seeds: impl IntoIterator<Item = String>,
+    ) -> Self {
+        Self {
+            name: source.name(),
+            source: Some(source),
+            document,
+            seeds: seeds.into_iter().collect(),
+            depth: DEFAULT_DEPTH,
+            limit: DEFAULT_LIMIT,
+            job: None,
+        }
+    }
+
+    /// Description of the source.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Id of the document showing the exploration.
+    pub fn document(&self) -> u64 {
+        self.document
+    }
+
+    /// Returns `true` while neighborhoods are being fetched.
+    pub fn is_running(&self) -> bool {
+        self.job.is_some()
+    }
+
+    /// Start fetching the nodes up to [`Self::depth`] hops away from the nodes
+    /// with `keys`, unless a fetch is running.
+    pub fn expand(&mut self, keys: Vec<String>) {
+        let Some(mut source) = self.source.take() else {
+            return;
+        };
+        let (depth, limit) = (self.depth, self.limit);
+        let frontier = keys.clone();
+        let handle = std::thread::spawn(move || {
+            let result = neighborhood(source.as_mut(), frontier, depth, limit);
+            (source, result)
+        });
+        self.job = Some(ExpandJob { handle, keys });
+    }
+
+    /// The keys of the expanded nodes and their neighborhoods, once fetched.
+    fn try_finish(&mut self) -> Option<(Vec<String>, Result<Graph, ExploreError>)> {
+        let job = self.job.take_if(|job| job.handle.is_finished())?;
+        match job.handle.join() {
+            Ok((source, result)) => {
+                self.source = Some(source);
+                Some((job.keys, result))
+            }
+            // the source went down with the thread
+            Err(_) => Some((job.keys, Err(ExploreError::Panicked))),
+        }
+    }
+}
+
+/// The nodes up to `depth` hops away from the nodes with `keys` in `source`,
+/// fetched one hop at a time.
+fn neighborhood(
+    source: &mut dyn NeighborSource,
+    keys: Vec<String>,
+    depth: usize,
+    limit: usize,
+) -> Result<Graph, ExploreError> {
+    let mut result = Graph::default();
+    let mut frontier = keys;
+    for hop in 0..depth {
+        let fetched = source.neighbors(&frontier, limit)?;
+        if hop == 0 {
+            result = Graph::new(fetched.directedness());
+        }
+        let added = merge(&mut result, &fetched);
+        frontier = added
+            .into_iter()
+            .filter_map(|node| key(&result, node))
+            .collect();
+        if frontier.is_empty() {
+            break;
+        }
+    }
+    Ok(result)
+}
+
+impl ExplorePanel {
+    pub fn new() -> Self {
+        Self {
+            depth: DEFAULT_DEPTH,
+            limit: DEFAULT_LIMIT,
+        }
+    }
+}
+
+impl Panel for ExplorePanel {
+    fn id(&self) -> &'static str {
+        "Explore"
+    }
+
+    fn draw(&mut self, ui: &Ui, ctx: &mut EngineContext) {
+        let nodes: Vec<NodeId> = ctx.selection.nodes().collect();
+        let exploring = active(ctx).is_some();
+        let (depth, limit) = match active(ctx) {
+            Some(exploration) => (&mut exploration.depth, &mut exploration.limit),
+            None => (&mut self.depth, &mut self.limit),
+        };
+        ui.slider(tr("Depth"), 1, MAX_DEPTH, depth);
+        let mut value = *limit as i32;
+        if ui.input_int(tr("Neighbors per Node"), &mut value).build() {
+            *limit = value.max(1) as usize;
+        }
+
+        if !exploring {
+            ui.enabled(!nodes.is_empty(), || {
+                if ui.button(tr("Explore Selection")) {
+                    explore(ctx, &nodes, self.depth, self.limit);
+                }
+            });
+            if ui.is_item_hovered() {
+                ui.tooltip_text(tr(
+                    "Open the selected nodes and their neighbors in a new document",
+                ));
+            }
+            return;
+        }
+
+        let Some(exploration) = active(ctx) else {
+            return;
+        };
+        ui.text_wrapped(tr("Exploring {name}").replace("{name}", exploration.name()));
+        let running = exploration.is_running();
+        ui.enabled(!running && !nodes.is_empty(), || {
+            if ui.button(tr("Expand")) {
+                expand(ctx, &nodes);
+            }
+            ui.same_line();
+            if ui.button(tr("Collapse")) {
+                collapse(ctx, &nodes);
+            }
+        });
+        if running {
+            ui.text_disabled(tr("Fetching neighbors..."));
+        } else if nodes.is_empty() {
+            ui.text_disabled(tr("Select nodes to expand or collapse"));
+        }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl From<StoreError> for ExploreError {
+    fn from(e: StoreError) -> Self {
+        Self::Store(e)
+    }
+}
+
+#[cfg(feature = "neo4j")]
+impl From<Neo4jError> for ExploreError {
+    fn from(e: Neo4jError) -> Self {
+        Self::Neo4j(e)
+    }
+}
+
+impl std::fmt::Display for ExploreError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            #[cfg(feature = "sqlite")]
+            Self::Store(e) => write!(f, "{e}"),
+            #[cfg(feature = "neo4j")]
+            Self::Neo4j(e) => write!(f, "{e}"),
+            Self::Panicked => write!(f, "The fetching thread panicked"),
+        }
+    }
+}
+
+impl std::error::Error for ExploreError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            #[cfg(feature = "sqlite")]
+            Self::Store(e) => Some(e),
+            #[cfg(feature = "neo4j")]
+            Self::Neo4j(e) => Some(e),
+            Self::Panicked => None,
+        }
+    }
+}
+
+impl std::default::Default for ExplorePanel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
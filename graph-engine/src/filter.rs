@@ -0,0 +1,437 @@
+//! # Graph Filters
+//!
+//! A [`FilterStack`] hides nodes and edges without touching the graph itself.
+//! Each [`Filter`] hides the elements matching its [`Predicate`], or keeps only
+//! those, and can be toggled on and off. Filters apply from top to bottom, each
+//! to what the filters above it left visible.
+//!
+//! The result is a view of the graph with the hidden elements removed, sharing
+//! the graph's [`NodeId`]s and [`EdgeId`]s, so [`Positions`](crate::layout::Positions),
+//! layouts, rendering, and picking work on it unchanged. Hiding a node hides
+//! its edges too. Members of collapsed [groups](crate::group), and elements out
+//! of the [timeline](crate::timeline)'s window, are hidden before any filter
+//! applies.
+//!
+//! ```ignore
+//! ctx.filters.push(Filter::new(Predicate::Degree { min: 0, max: 0 }));
+//! ctx.refresh();
+//! let visible = ctx.visible_graph();
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use crate::algo::connected_components;
+use crate::context::EngineContext;
+use crate::graph::{EdgeId, Graph, NodeId};
+use crate::group::Groups;
+use crate::i18n::tr;
+use crate::imgui::Ui;
+use crate::search::{AttributeIndex, Query, QueryError};
+use crate::selection::Selection;
+use crate::subsystems::panels::Panel;
+use crate::timeline::TimeWindow;
+
+/// Which elements a [`Filter`] matches.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Predicate {
+    /// Nodes, or edges, matching a search [`Query`] in its text form. Plain text
+    /// matches the `label` attribute.
+    Query { query: String, edges: bool },
+    /// Nodes whose visible degree is within `min..=max`.
+    Degree { min: usize, max: usize },
+    /// Selected nodes and edges.
+    Selected,
+    /// Nodes in visible connected components of fewer than `min_size` nodes.
+    SmallComponent { min_size: usize },
+}
+
+/// What a [`Filter`] does with the elements its predicate matches.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterMode {
+    /// Hide the matching elements.
+    #[default]
+    Hide,
+    /// Hide every element which does not match, of the kinds the predicate
+    /// applies to.
+    Keep,
+}
+
+/// One entry of a [`FilterStack`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Filter {
+    pub enabled: bool,
+    pub mode: FilterMode,
+    pub predicate: Predicate,
+}
+
+/// Filters hiding parts of a graph, see the [module docs](self).
+///
+/// Edit the filters with [`Self::filters_mut()`], then call [`Self::update()`]
+/// to bring the view up to date.
+#[derive(Clone, Debug, Default)]
+pub struct FilterStack {
+    filters: Vec<Filter>,
+    /// Why each filter's query failed to parse, if it did.
+    errors: Vec<Option<QueryError>>,
+    /// Hides the elements alive at no time of it, if set.
+    time: Option<TimeWindow>,
+    /// The graph with hidden elements removed, while any filter is enabled,
+    /// group collapsed, or time window set.
+    view: Option<Graph>,
+    /// Generations of the graph and selection, and revision of the filters,
+    /// the view was built from.
+    applied: Option<(u64, u64, u64)>,
+    /// Increased by every edit of the filters.
+    revision: u64,
+    hidden_nodes: usize,
+    hidden_edges: usize,
+}
+
+impl Filter {
+    /// Create an enabled filter hiding what `predicate` matches.
+    pub fn new(predicate: Predicate) -> Self {
+        Self {
+            enabled: true,
+            mode: FilterMode::Hide,
+            predicate,
+        }
+    }
+}
+
+impl Predicate {
+    /// Human-readable name of the predicate's kind.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Query { .. } => "Query",
+            Self::Degree { .. } => "Degree",
+            Self::Selected => "Selected",
+            Self::SmallComponent { .. } => "Small Component",
+        }
+    }
+}
+
+impl FilterStack {
+    /// Create an empty stack, hiding nothing.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The filters, in the order they apply.
+    pub fn filters(&self) -> &[Filter] {
+        &self.filters
+    }
+
+    /// Mutable access to the filters, marking the view stale.
+    pub fn filters_mut(&mut self) -> &mut Vec<Filter> {
+        self.revision += 1;
+        &mut self.filters
+    }
+
+    /// Add `filter` at the bottom of the stack.
+    pub fn push(&mut self, filter: Filter) {
+        self.filters_mut().push(filter);
+    }
+
+    /// Remove every filter.
+    pub fn clear(&mut self) {
+        self.filters_mut().clear();
+    }
+
+    /// The window of time out of which elements are hidden, if any.
+    pub fn time_window(&self) -> Option<&TimeWindow> {
+        self.time.as_ref()
+    }
+
+    /// Hide the elements alive at no time of `window`, or stop hiding them if
+    /// `None`, marking the view stale if it changed.
+    pub fn set_time_window(&mut self, window: Option<TimeWindow>) {
+        if self.time != window {
+            self.time = window;
+            self.revision += 1;
+        }
+    }
+
+    /// Why the query of the filter at `index` failed to parse, if it did, as
+    /// of the last [update](Self::update).
+    pub fn error(&self, index: usize) -> Option<&QueryError> {
+        self.errors.get(index)?.as_ref()
+    }
+
+    /// Returns `true` if any filter is enabled.
+    pub fn is_active(&self) -> bool {
+        self.filters.iter().any(|f| f.enabled)
+    }
+
+    /// Number of nodes and edges hidden, as of the last [update](Self::update).
+    pub fn hidden(&self) -> (usize, usize) {
+        (self.hidden_nodes, self.hidden_edges)
+    }
+
+    /// The visible part of `graph`, which must be the graph the stack was last
+    /// [updated](Self::update) with.
+    pub fn graph<'a>(&'a self, graph: &'a Graph) -> &'a Graph {
+        self.view.as_ref().unwrap_or(graph)
+    }
+
+    /// Returns `true` if `node` is visible, as of the last [update](Self::update).
+    pub fn is_node_visible(&self, node: NodeId) -> bool {
+        self.view
+            .as_ref()
+            .is_none_or(|view| view.contains_node(node))
+    }
+
+    /// Returns `true` if `edge` is visible, as of the last [update](Self::update).
+    pub fn is_edge_visible(&self, edge: EdgeId) -> bool {
+        self.view
+            .as_ref()
+            .is_none_or(|view| view.contains_edge(edge))
+    }
+
+    /// Rebuild the view of `graph` if it, the `selection`, or the filters changed
+    /// since the last update. Queries are answered by `index`, and `groups` must
+    /// index `graph`.
+    ///
+    /// Returns `true` if the view was rebuilt.
+    pub fn update(
+        &mut self,
+        graph: &Graph,
+        selection: &Selection,
+        groups: &Groups,
+        index: &mut AttributeIndex,
+    ) -> bool {
+        // selection changes only matter to filters on it
+        let on_selection = self
+            .filters
+            .iter()
+            .any(|f| f.enabled && f.predicate == Predicate::Selected);
+        let key = (
+            graph.generation(),
+            if on_selection {
+                selection.generation()
+            } else {
+                0
+            },
+            self.revision,
+        );
+        if self.applied == Some(key) {
+            return false;
+        }
+        self.applied = Some(key);
+        self.errors = vec![None; self.filters.len()];
+        if !self.is_active() && self.time.is_none() && groups.collapsed().is_empty() {
+            self.view = None;
+            (self.hidden_nodes, self.hidden_edges) = (0, 0);
+            return true;
+        }
+
+        let mut view = graph.clone();
+        for node in groups.hidden() {
+            view.remove_node(node);
+        }
+        if let Some(window) = &self.time {
+            let nodes: Vec<NodeId> = view
+                .nodes()
+                .filter(|&node| !graph.node(node).is_some_and(|a| window.contains(a)))
+                .collect();
+            for node in nodes {
+                view.remove_node(node);
+            }
+            let edges: Vec<EdgeId> = view
+                .edges()
+                .filter(|&edge| !graph.edge(edge).is_some_and(|a| window.contains(a)))
+                .collect();
+            for edge in edges {
+                view.remove_edge(edge);
+            }
+        }
+        for (filter, error) in self.filters.iter().zip(&mut self.errors) {
+            if !filter.enabled {
+                continue;
+            }
+            let keep = filter.mode == FilterMode::Keep;
+            let (nodes, edges) = match matches(&filter.predicate, graph, &view, selection, index) {
+                Ok(found) => found,
+                Err(e) => {
+                    *error = Some(e);
+                    continue;
+                }
+            };
+            if let Some(nodes) = nodes {
+                let hide: Vec<NodeId> = view
+                    .nodes()
+                    .filter(|node| nodes.binary_search(node).is_ok() != keep)
+                    .collect();
+                for node in hide {
+                    view.remove_node(node);
+                }
+            }
+            if let Some(edges) = edges {
+                let hide: Vec<EdgeId> = view
+                    .edges()
+                    .filter(|edge| edges.binary_search(edge).is_ok() != keep)
+                    .collect();
+                for edge in hide {
+                    view.remove_edge(edge);
+                }
+            }
+        }
+        self.hidden_nodes = graph.node_count() - view.node_count();
+        self.hidden_edges = graph.edge_count() - view.edge_count();
+        self.view = Some(view);
+        true
+    }
+}
+
+/// Sorted nodes and edges matched by a predicate, `None` for the kinds it does
+/// not apply to.
+type Matches = (Option<Vec<NodeId>>, Option<Vec<EdgeId>>);
+
+/// Elements matching `predicate`. Attributes are read from `graph`, structure
+/// from `view`.
+fn matches(
+    predicate: &Predicate,
+    graph: &Graph,
+    view: &Graph,
+    selection: &Selection,
+    index: &mut AttributeIndex,
+) -> Result<Matches, QueryError> {
+    Ok(match predicate {
+        Predicate::Query { query, edges } => {
+            let query = Query::parse(query)?;
+            match edges {
+                true => (None, Some(index.edges(graph, &query, "label"))),
+                false => (Some(index.nodes(graph, &query, "label")), None),
+            }
+        }
+        Predicate::Degree { min, max } => {
+            let nodes = view
+                .nodes()
+                .filter(|&node| (*min..=*max).contains(&view.degree(node)))
+                .collect();
+            (Some(nodes), None)
+        }
+        Predicate::Selected => (
+            Some(selection.nodes().collect()),
+            Some(selection.edges().collect()),
+        ),
+        Predicate::SmallComponent { min_size } => {
+            let components = connected_components(view);
+            let mut nodes: Vec<NodeId> = components
+                .iter()
+                .filter(|nodes| nodes.len() < *min_size)
+                .flatten()
+                .copied()
+                .collect();
+            nodes.sort_unstable();
+            (Some(nodes), None)
+        }
+    })
+}
+
+/// Edits the [`EngineContext::filters`].
+pub struct FilterPanel;
+
+impl Panel for FilterPanel {
+    fn id(&self) -> &'static str {
+        "Filters"
+    }
+
+    fn draw(&mut self, ui: &Ui, ctx: &mut EngineContext) {
+        const KINDS: [Predicate; 4] = [
+            Predicate::Query {
+                query: String::new(),
+                edges: false,
+            },
+            Predicate::Degree { min: 0, max: 0 },
+            Predicate::Selected,
+            Predicate::SmallComponent { min_size: 2 },
+        ];
+
+        let before = ctx.filters.filters().to_vec();
+        let mut filters = before.clone();
+        let mut remove = None;
+        for (i, filter) in filters.iter_mut().enumerate() {
+            let _id = ui.push_id_usize(i);
+            ui.checkbox("##enabled", &mut filter.enabled);
+            ui.same_line();
+            ui.set_next_item_width(70.0);
+            let mut mode = (filter.mode == FilterMode::Keep) as usize;
+            if ui.combo_simple_string("##mode", &mut mode, &[tr("Hide"), tr("Keep")]) {
+                filter.mode = match mode {
+                    1 => FilterMode::Keep,
+                    _ => FilterMode::Hide,
+                };
+            }
+            ui.same_line();
+            ui.set_next_item_width(140.0);
+            let mut kind = KINDS
+                .iter()
+                .position(|k| {
+                    std::mem::discriminant(k) == std::mem::discriminant(&filter.predicate)
+                })
+                .unwrap();
+            if ui.combo("##kind", &mut kind, &KINDS, |k| tr(k.label())) {
+                filter.predicate = KINDS[kind].clone();
+            }
+            ui.same_line();
+            if ui.small_button(tr("Remove")) {
+                remove = Some(i);
+            }
+
+            ui.indent();
+            match &mut filter.predicate {
+                Predicate::Query { query, edges } => {
+                    ui.set_next_item_width(-1.0);
+                    ui.input_text("##query", query)
+                        .hint(tr("eg. type == \"service\""))
+                        .build();
+                    ui.radio_button(tr("Nodes"), edges, false);
+                    ui.same_line();
+                    ui.radio_button(tr("Edges"), edges, true);
+                    if let Some(error) = ctx.filters.error(i) {
+                        ui.text_colored([1.0, 0.4, 0.4, 1.0], error.to_string());
+                    }
+                }
+                Predicate::Degree { min, max } => {
+                    let mut range = [*min as i32, *max as i32];
+                    if ui.input_int2(tr("Degree"), &mut range).build() {
+                        *min = range[0].max(0) as usize;
+                        *max = range[1].max(range[0]).max(0) as usize;
+                    }
+                }
+                Predicate::Selected => {}
+                Predicate::SmallComponent { min_size } => {
+                    let mut size = *min_size as i32;
+                    if ui.input_int(tr("Min Size"), &mut size).build() {
+                        *min_size = size.max(1) as usize;
+                    }
+                }
+            }
+            ui.unindent();
+            ui.separator();
+        }
+        if let Some(i) = remove {
+            filters.remove(i);
+        }
+
+        if ui.button(tr("Add Filter")) {
+            filters.push(Filter::new(Predicate::Selected));
+        }
+        ui.same_line();
+        if ui.button(tr("Clear")) {
+            filters.clear();
+        }
+        let (nodes, edges) = ctx.filters.hidden();
+        ui.text_disabled(
+            tr("{nodes} nodes and {edges} edges hidden")
+                .replace("{nodes}", &nodes.to_string())
+                .replace("{edges}", &edges.to_string()),
+        );
+
+        if filters != before {
+            *ctx.filters.filters_mut() = filters;
+        }
+    }
+}
@@ -0,0 +1,603 @@
+//! # Graph Generators
+//!
+//! Random and regular graphs for benchmarks and demos, each [`Generator`]
+//! giving the same graph for the same parameters and seed:
+//! - Erdős–Rényi: every pair of nodes linked with the same probability
+//! - Barabási–Albert: nodes added one at a time, linked to nodes picked in
+//!   proportion to their degree, which grows hubs
+//! - Watts–Strogatz: a ring of nodes linked to their nearest neighbors, with
+//!   some edges rewired at random, giving a small world
+//! - grid: rows and columns of nodes linked to their neighbors, with their
+//!   `row` and `column` as attributes
+//! - tree: a complete tree, directed from the root, with the `depth` of each
+//!   node as an attribute
+//!
+//! The [`GenerateDialog`] generates one into a new document, and
+//! `graph-browser --headless generate` writes one to a file.
+//!
+//! ```ignore
+//! let graph = Generator::BarabasiAlbert { nodes: 1000, edges: 2 }.generate(DEFAULT_SEED)?;
+//! ctx.open_document(graph, None);
+//! ```
+
+use std::collections::HashSet;
+
+use crate::graph::{Directedness, Graph, NodeId};
+use crate::i18n::tr;
+use crate::imgui::Ui;
+
+/// Seed the random generators use unless given another.
+pub const DEFAULT_SEED: u64 = 1;
+/// Most nodes a generated graph may have.
+pub const MAX_NODES: usize = 10_000_000;
+/// Most edges a generated graph may have, or be expected to have.
+pub const MAX_EDGES: usize = 100_000_000;
+/// Title and id of the dialog's modal popup.
+const TITLE: &str = "Generate Graph";
+
+/// A model of graph, with its parameters, see the
+/// [module documentation](self).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Generator {
+    ErdosRenyi {
+        nodes: usize,
+        /// Chance of each pair of nodes being linked, from `0.0` to `1.0`.
+        probability: f64,
+    },
+    BarabasiAlbert {
+        nodes: usize,
+        /// Edges from each node added to those before it.
+        edges: usize,
+    },
+    WattsStrogatz {
+        nodes: usize,
+        /// Nodes each is linked to on the ring before rewiring, even.
+        neighbors: usize,
+        /// Chance of each edge being rewired, from `0.0` to `1.0`.
+        rewiring: f64,
+    },
+    Grid {
+        rows: usize,
+        columns: usize,
+    },
+    Tree {
+        /// Children of each node but the leaves.
+        branching: usize,
+        /// Levels below the root.
+        depth: usize,
+    },
+}
+
+/// Errors which can occur while generating a graph.
+#[derive(Debug)]
+pub enum GenerateError {
+    /// A parameter is out of its range, with the range.
+    Invalid(&'static str),
+    /// The graph would have more than [`MAX_NODES`] nodes or [`MAX_EDGES`]
+    /// edges.
+    TooLarge,
+}
+
+/// Modal dialog picking a [`Generator`], its parameters and seed, and
+/// generating the graph once confirmed.
+///
+/// Typical usage, each frame while the dialog exists:
+/// ```ignore
+/// match dialog.draw(ui) {
+///     GenerateOutcome::Open => {}
+///     GenerateOutcome::Cancelled => self.dialog = None,
+///     GenerateOutcome::Generated(graph) => ctx.open_document(graph, None),
+/// }
+/// ```
+pub struct GenerateDialog {
+    generator: Generator,
+    seed: u64,
+    /// Error from the last attempt to generate.
+    error: Option<String>,
+    /// Whether the popup was opened yet.
+    opened: bool,
+}
+
+/// What happened in a [`GenerateDialog`] during a frame.
+pub enum GenerateOutcome {
+    /// The dialog is still open.
+    Open,
+    /// The user closed the dialog without generating.
+    Cancelled,
+    /// The user confirmed, and the graph was generated.
+    Generated(Graph),
+}
+
+/// SplitMix64, small and random enough to generate graphs, the same on every
+/// platform.
+struct Rng(u64);
+
+impl Generator {
+    /// Every model, with parameters giving graphs of a few hundred nodes.
+    pub const ALL: [Generator; 5] = [
+        Self::ErdosRenyi {
+            nodes: 500,
+            probability: 0.01,
+        },
+        Self::BarabasiAlbert {
+            nodes: 500,
+            edges: 2,
+        },
+        Self::WattsStrogatz {
+            nodes: 500,
+            neighbors: 4,
+            rewiring: 0.1,
+        },
+        Self::Grid {
+            rows: 20,
+            columns: 20,
+        },
+        Self::Tree {
+            branching: 3,
+            depth: 5,
+        },
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::ErdosRenyi { .. } => "Erdős–Rényi",
+            Self::BarabasiAlbert { .. } => "Barabási–Albert",
+            Self::WattsStrogatz { .. } => "Watts–Strogatz",
+            Self::Grid { .. } => "Grid",
+            Self::Tree { .. } => "Tree",
+        }
+    }
+
+    /// The model's name in ASCII, eg. on the command line.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::ErdosRenyi { .. } => "erdos-renyi",
+            Self::BarabasiAlbert { .. } => "barabasi-albert",
+            Self::WattsStrogatz { .. } => "watts-strogatz",
+            Self::Grid { .. } => "grid",
+            Self::Tree { .. } => "tree",
+        }
+    }
+
+    /// Whether the seed changes the graph.
+    pub fn is_random(self) -> bool {
+        !matches!(self, Self::Grid { .. } | Self::Tree { .. })
+    }
+
+    /// Number of nodes, and of edges or the expected number for random
+    /// models, or [None] if they overflow.
+    pub fn size(self) -> Option<(usize, usize)> {
+        match self {
+            Self::ErdosRenyi { nodes, probability } => {
+                let pairs = nodes as f64 * nodes.saturating_sub(1) as f64 / 2.0;
+                let edges = pairs * probability.clamp(0.0, 1.0);
+                (edges < usize::MAX as f64).then_some((nodes, edges as usize))
+            }
+            Self::BarabasiAlbert { nodes, edges } => {
+                Some((nodes, edges.checked_mul(nodes.saturating_sub(edges))?))
+            }
+            Self::WattsStrogatz {
+                nodes, neighbors, ..
+            } => Some((nodes, nodes.checked_mul(neighbors / 2)?)),
+            Self::Grid { rows, columns } => {
+                let nodes = rows.checked_mul(columns)?;
+                let edges = rows
+                    .saturating_sub(1)
+                    .checked_mul(columns)?
+                    .checked_add(columns.saturating_sub(1).checked_mul(rows)?)?;
+                Some((nodes, edges))
+            }
+            Self::Tree { branching, depth } => {
+                let (mut nodes, mut level) = (1usize, 1usize);
+                for _ in 0..depth {
+                    // past either, the levels no longer change the outcome
+                    if level == 0 || nodes > MAX_NODES {
+                        break;
+                    }
+                    level = level.checked_mul(branching)?;
+                    nodes = nodes.checked_add(level)?;
+                }
+                Some((nodes, nodes - 1))
+            }
+        }
+    }
+
+    /// Errors if a parameter is out of its range, or the graph would be too
+    /// large.
+    pub fn validate(self) -> Result<(), GenerateError> {
+        let probability = |p: f64| (0.0..=1.0).contains(&p);
+        match self {
+            Self::ErdosRenyi { probability: p, .. } if !probability(p) => {
+                return Err(GenerateError::Invalid(
+                    "The probability must be between 0 and 1",
+                ));
+            }
+            Self::BarabasiAlbert { nodes, edges } if edges == 0 || edges >= nodes => {
+                return Err(GenerateError::Invalid(
+                    "The edges per node must be at least 1, and fewer than the nodes",
+                ));
+            }
+            Self::WattsStrogatz {
+                nodes, neighbors, ..
+            } if neighbors % 2 == 1 || neighbors >= nodes => {
+                return Err(GenerateError::Invalid(
+                    "The neighbors must be even, and fewer than the nodes",
+                ));
+            }
+            Self::WattsStrogatz { rewiring, .. } if !probability(rewiring) => {
+                return Err(GenerateError::Invalid(
+                    "The rewiring probability must be between 0 and 1",
+                ));
+            }
+            _ => {}
+        }
+        match self.size() {
+            Some((nodes, edges)) if nodes <= MAX_NODES && edges <= MAX_EDGES => Ok(()),
+            _ => Err(GenerateError::TooLarge),
+        }
+    }
+
+    /// Generate the graph, drawing random numbers from `seed`.
+    ///
+    /// Errors if the parameters are [invalid](Self::validate()).
+    pub fn generate(self, seed: u64) -> Result<Graph, GenerateError> {
+        self.validate()?;
+        let mut rng = Rng(seed);
+        let directedness = match self {
+            Self::Tree { .. } => Directedness::Directed,
+            _ => Directedness::Undirected,
+        };
+        let mut graph = Graph::new(directedness);
+        match self {
+            Self::ErdosRenyi { nodes, probability } => {
+                let nodes = add_nodes(&mut graph, nodes);
+                erdos_renyi(&mut graph, &nodes, probability, &mut rng);
+            }
+            Self::BarabasiAlbert { nodes, edges } => {
+                let nodes = add_nodes(&mut graph, nodes);
+                barabasi_albert(&mut graph, &nodes, edges, &mut rng);
+            }
+            Self::WattsStrogatz {
+                nodes,
+                neighbors,
+                rewiring,
+            } => {
+                let nodes = add_nodes(&mut graph, nodes);
+                watts_strogatz(&mut graph, &nodes, neighbors, rewiring, &mut rng);
+            }
+            Self::Grid { rows, columns } => grid(&mut graph, rows, columns),
+            Self::Tree { branching, depth } => tree(&mut graph, branching, depth),
+        }
+        Ok(graph)
+    }
+}
+
+/// Add `count` nodes to `graph`, returning them in order.
+fn add_nodes(graph: &mut Graph, count: usize) -> Vec<NodeId> {
+    (0..count).map(|_| graph.add_node()).collect()
+}
+
+/// Link each pair of `nodes` with `probability`, skipping over the pairs left
+/// out rather than drawing for each, after Batagelj and Brandes.
+fn erdos_renyi(graph: &mut Graph, nodes: &[NodeId], probability: f64, rng: &mut Rng) {
+    if probability <= 0.0 {
+        return;
+    }
+    if probability >= 1.0 {
+        for (i, &source) in nodes.iter().enumerate() {
+            for &target in &nodes[..i] {
+                graph.add_edge(source, target).unwrap();
+            }
+        }
+        return;
+    }
+    let n = nodes.len();
+    let log = (1.0 - probability).ln();
+    let pairs = n as f64 * n.saturating_sub(1) as f64 / 2.0;
+    // pairs (v, w) with w < v, in order
+    let (mut v, mut w) = (1, -1i64);
+    while v < n {
+        let skip = ((1.0 - rng.float()).ln() / log).floor().min(pairs);
+        w += 1 + skip as i64;
+        while w >= v as i64 && v < n {
+            w -= v as i64;
+            v += 1;
+        }
+        if v < n {
+            graph.add_edge(nodes[v], nodes[w as usize]).unwrap();
+        }
+    }
+}
+
+/// Start from a star of `edges + 1` of `nodes`, then link each of the others
+/// to `edges` distinct nodes before it, picked in proportion to their degree.
+fn barabasi_albert(graph: &mut Graph, nodes: &[NodeId], edges: usize, rng: &mut Rng) {
+    // each node once per edge it has, to pick from in proportion to degree
+    let mut ends = Vec::with_capacity(2 * edges * nodes.len());
+    for target in 1..=edges {
+        graph.add_edge(nodes[0], nodes[target]).unwrap();
+        ends.extend([0, target]);
+    }
+    let mut targets = Vec::with_capacity(edges);
+    for source in edges + 1..nodes.len() {
+        targets.clear();
+        while targets.len() < edges {
+            let target = ends[rng.below(ends.len())];
+            if !targets.contains(&target) {
+                targets.push(target);
+            }
+        }
+        for &target in &targets {
+            graph.add_edge(nodes[source], nodes[target]).unwrap();
+            ends.extend([source, target]);
+        }
+    }
+}
+
+/// Link each of `nodes` to the `neighbors / 2` following it on a ring, then
+/// move the far end of each edge to a random node with `rewiring` probability,
+/// never linking a pair twice.
+fn watts_strogatz(
+    graph: &mut Graph,
+    nodes: &[NodeId],
+    neighbors: usize,
+    rewiring: f64,
+    rng: &mut Rng,
+) {
+    let n = nodes.len();
+    let pair = |a: usize, b: usize| (a.min(b), a.max(b));
+    let mut edges: Vec<(usize, usize)> = (0..n)
+        .flat_map(|i| (1..=neighbors / 2).map(move |j| (i, (i + j) % n)))
+        .collect();
+    let mut linked: HashSet<(usize, usize)> = edges.iter().map(|&(a, b)| pair(a, b)).collect();
+    let mut degree = vec![neighbors; n];
+    for edge in &mut edges {
+        let (source, target) = *edge;
+        if rng.float() >= rewiring || degree[source] >= n - 1 {
+            continue;
+        }
+        let other = loop {
+            let other = rng.below(n);
+            if other != source && !linked.contains(&pair(source, other)) {
+                break other;
+            }
+        };
+        linked.remove(&pair(source, target));
+        linked.insert(pair(source, other));
+        degree[target] -= 1;
+        degree[other] += 1;
+        *edge = (source, other);
+    }
+    for (source, target) in edges {
+        graph.add_edge(nodes[source], nodes[target]).unwrap();
+    }
+}
+
+/// Add `rows` by `columns` nodes, each linked to the next in its row and in
+/// its column.
+fn grid(graph: &mut Graph, rows: usize, columns: usize) {
+    let mut nodes = Vec::with_capacity(rows * columns);
+    for row in 0..rows {
+        for column in 0..columns {
+            let node = graph.add_node();
+            let attrs = graph.node_mut(node).unwrap();
+            attrs.insert("row", row as i64);
+            attrs.insert("column", column as i64);
+            nodes.push(node);
+        }
+    }
+    for row in 0..rows {
+        for column in 0..columns {
+            let node = nodes[row * columns + column];
+            if column + 1 < columns {
+                graph
+                    .add_edge(node, nodes[row * columns + column + 1])
+                    .unwrap();
+            }
+            if row + 1 < rows {
+                graph
+                    .add_edge(node, nodes[(row + 1) * columns + column])
+                    .unwrap();
+            }
+        }
+    }
+}
+
+/// Add a root, then `branching` children to each node, `depth` levels deep.
+fn tree(graph: &mut Graph, branching: usize, depth: usize) {
+    let root = graph.add_node();
+    graph.node_mut(root).unwrap().insert("depth", 0i64);
+    let mut level = vec![root];
+    for depth in 1..=depth {
+        // eg. without branching, the levels past the root are empty
+        if level.is_empty() {
+            break;
+        }
+        let mut next = Vec::with_capacity(level.len() * branching);
+        for &parent in &level {
+            for _ in 0..branching {
+                let child = graph.add_node();
+                graph.node_mut(child).unwrap().insert("depth", depth as i64);
+                graph.add_edge(parent, child).unwrap();
+                next.push(child);
+            }
+        }
+        level = next;
+    }
+}
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number from `0.0` up to `1.0`.
+    fn float(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// A number from `0` up to `n`.
+    fn below(&mut self, n: usize) -> usize {
+        ((self.next() as u128 * n as u128) >> 64) as usize
+    }
+}
+
+impl GenerateDialog {
+    /// Create a dialog offering the first of [`Generator::ALL`].
+    pub fn new() -> Self {
+        Self {
+            generator: Generator::ALL[0],
+            seed: DEFAULT_SEED,
+            error: None,
+            opened: false,
+        }
+    }
+
+    /// Draw the dialog, returning whether the user generated or cancelled.
+    pub fn draw(&mut self, ui: &Ui) -> GenerateOutcome {
+        if !self.opened {
+            ui.open_popup(TITLE);
+            self.opened = true;
+        }
+
+        let mut open = true;
+        let outcome = ui
+            .modal_popup_config(TITLE)
+            .opened(&mut open)
+            .always_auto_resize(true)
+            .build(|| self.draw_contents(ui));
+
+        match outcome {
+            Some(GenerateOutcome::Open) if !open => GenerateOutcome::Cancelled,
+            Some(outcome) => outcome,
+            None => GenerateOutcome::Cancelled,
+        }
+    }
+
+    fn draw_contents(&mut self, ui: &Ui) -> GenerateOutcome {
+        let labels = Generator::ALL.map(|g| tr(g.label()));
+        let mut model = Generator::ALL
+            .iter()
+            .position(|g| g.name() == self.generator.name())
+            .unwrap();
+        ui.set_next_item_width(200.0);
+        if ui.combo_simple_string(tr("Model"), &mut model, &labels) {
+            self.generator = Generator::ALL[model];
+            self.error = None;
+        }
+
+        match &mut self.generator {
+            Generator::ErdosRenyi { nodes, probability } => {
+                input_count(ui, "Nodes", nodes);
+                ui.slider(tr("Probability"), 0.0, 1.0, probability);
+            }
+            Generator::BarabasiAlbert { nodes, edges } => {
+                input_count(ui, "Nodes", nodes);
+                input_count(ui, "Edges per Node", edges);
+            }
+            Generator::WattsStrogatz {
+                nodes,
+                neighbors,
+                rewiring,
+            } => {
+                input_count(ui, "Nodes", nodes);
+                input_count(ui, "Neighbors", neighbors);
+                ui.slider(tr("Rewiring"), 0.0, 1.0, rewiring);
+            }
+            Generator::Grid { rows, columns } => {
+                input_count(ui, "Rows", rows);
+                input_count(ui, "Columns", columns);
+            }
+            Generator::Tree { branching, depth } => {
+                input_count(ui, "Branching", branching);
+                input_count(ui, "Depth", depth);
+            }
+        }
+        if self.generator.is_random() {
+            let mut seed = self.seed as i32;
+            ui.set_next_item_width(200.0);
+            if ui.input_int(tr("Seed"), &mut seed).build() {
+                self.seed = seed.max(0) as u64;
+            }
+        }
+
+        ui.separator();
+        let valid = match self.generator.validate() {
+            Ok(()) => {
+                let (nodes, edges) = self.generator.size().unwrap();
+                match self.generator.is_random() {
+                    true => ui.text(
+                        tr("{nodes} nodes, about {edges} edges")
+                            .replace("{nodes}", &nodes.to_string())
+                            .replace("{edges}", &edges.to_string()),
+                    ),
+                    false => ui.text(
+                        tr("{nodes} nodes, {edges} edges")
+                            .replace("{nodes}", &nodes.to_string())
+                            .replace("{edges}", &edges.to_string()),
+                    ),
+                }
+                true
+            }
+            Err(e) => {
+                ui.text_colored([1.0, 0.4, 0.4, 1.0], e.to_string());
+                false
+            }
+        };
+        if let Some(error) = &self.error {
+            ui.text_colored([1.0, 0.4, 0.4, 1.0], error);
+        }
+
+        let mut outcome = GenerateOutcome::Open;
+        ui.enabled(valid, || {
+            if ui.button(tr("Generate")) {
+                match self.generator.generate(self.seed) {
+                    Ok(graph) => {
+                        outcome = GenerateOutcome::Generated(graph);
+                        ui.close_current_popup();
+                    }
+                    Err(e) => self.error = Some(e.to_string()),
+                }
+            }
+        });
+        ui.same_line();
+        if ui.button(tr("Cancel")) {
+            outcome = GenerateOutcome::Cancelled;
+            ui.close_current_popup();
+        }
+        outcome
+    }
+}
+
+/// An input for a count, kept from `0` to [`MAX_NODES`].
+fn input_count(ui: &Ui, label: &str, value: &mut usize) {
+    let mut count = (*value).min(MAX_NODES) as i32;
+    ui.set_next_item_width(200.0);
+    if ui.input_int(label, &mut count).build() {
+        *value = count.clamp(0, MAX_NODES as i32) as usize;
+    }
+}
+
+impl std::fmt::Display for GenerateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Invalid(range) => write!(f, "{range}"),
+            Self::TooLarge => write!(
+                f,
+                "The graph would have more than {MAX_NODES} nodes or {MAX_EDGES} edges"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GenerateError {}
+
+impl std::default::Default for GenerateDialog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,584 @@
+//! # Graph's Data Model
+//!
+//! A [`Graph`] holds nodes and edges with typed [`Attributes`], and is what
+//! loaders produce and layouts, rendering, and analysis consume.
+//!
+//! - Graphs are either [directed or undirected](Directedness); parallel edges and
+//!   self-loops are allowed in both.
+//! - [`NodeId`]s and [`EdgeId`]s stay valid until their element is removed, and
+//!   are never reused by the same graph, so they can be stored by other
+//!   subsystems (eg. a selection) across edits.
+//! - Attribute keys are interned [`Symbol`]s, so repeating the same key on
+//!   millions of elements stays cheap.
+//! - Other subsystems can [subscribe](Graph::subscribe) to a graph's changes,
+//!   to update what they derived from it instead of rebuilding it.
+//!
+//! ```ignore
+//! use graph_engine::graph::Graph;
+//!
+//! let mut graph = Graph::undirected();
+//! let a = graph.add_node();
+//! let b = graph.add_node();
+//! let edge = graph.add_edge(a, b)?;
+//!
+//! graph.node_mut(a).unwrap().insert("label", "Alice");
+//! graph.edge_mut(edge).unwrap().insert("weight", 2.5);
+//!
+//! assert_eq!(graph.neighbors(b).collect::<Vec<_>>(), [a]);
+//! ```
+
+mod attributes;
+mod changes;
+
+pub use attributes::{AttrValue, Attributes, Symbol};
+#[doc(hidden)]
+pub use attributes::{share_symbols, symbol_table};
+pub use changes::{ChangeFilter, Element, GraphChange, Subscription};
+
+use changes::Subscribers;
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Last generation given to any graph, see [`Graph::generation()`].
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Identifies a node within a single [`Graph`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(u32);
+
+/// Identifies an edge within a single [`Graph`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct EdgeId(u32);
+
+/// Whether a graph's edges have a direction.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Directedness {
+    #[default]
+    Directed,
+    Undirected,
+}
+
+/// Errors which can occur while editing a [`Graph`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GraphError {
+    /// The node does not exist, or was removed.
+    MissingNode(NodeId),
+    /// The edge does not exist, or was removed.
+    MissingEdge(EdgeId),
+    /// The node cannot be restored, as it was never removed.
+    NodeExists(NodeId),
+    /// The edge cannot be restored, as it was never removed.
+    EdgeExists(EdgeId),
+}
+
+/// A directed or undirected multigraph with attributed nodes and edges.
+///
+/// Elements are stored in slots indexed by their id. Removed slots are left
+/// empty rather than reused, which keeps ids stable; [`Self::node_bound()`]
+/// and [`Self::edge_bound()`] give the slot counts, for dense per-element arrays.
+#[derive(Clone, Debug, Default)]
+pub struct Graph {
+    directedness: Directedness,
+    attrs: Attributes,
+    nodes: Vec<Option<Node>>,
+    edges: Vec<Option<Edge>>,
+    node_count: usize,
+    edge_count: usize,
+    /// Bumped on every change, see [`Self::generation()`].
+    generation: u64,
+    subscribers: Subscribers,
+}
+
+#[derive(Clone, Debug, Default)]
+struct Node {
+    attrs: Attributes,
+    /// Edges with this node as their source.
+    outgoing: Vec<EdgeId>,
+    /// Edges with this node as their target.
+    incoming: Vec<EdgeId>,
+}
+
+#[derive(Clone, Debug)]
+struct Edge {
+    source: NodeId,
+    target: NodeId,
+    attrs: Attributes,
+}
+
+impl NodeId {
+    /// The id's slot index, below [`Graph::node_bound()`].
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+impl EdgeId {
+    /// The id's slot index, below [`Graph::edge_bound()`].
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+impl Graph {
+    /// Create an empty graph.
+    pub fn new(directedness: Directedness) -> Self {
+        Self {
+            directedness,
+            ..Default::default()
+        }
+    }
+
+    /// Create an empty directed graph.
+    pub fn directed() -> Self {
+        Self::new(Directedness::Directed)
+    }
+
+    /// Create an empty undirected graph.
+    pub fn undirected() -> Self {
+        Self::new(Directedness::Undirected)
+    }
+
+    /// Whether the graph's edges have a direction.
+    pub fn directedness(&self) -> Directedness {
+        self.directedness
+    }
+
+    /// Returns `true` if the graph's edges have a direction.
+    pub fn is_directed(&self) -> bool {
+        self.directedness == Directedness::Directed
+    }
+
+    /// Attributes of the graph itself, eg. its name.
+    pub fn attrs(&self) -> &Attributes {
+        &self.attrs
+    }
+
+    /// Mutable access to the attributes of the graph itself.
+    pub fn attrs_mut(&mut self) -> &mut Attributes {
+        self.changed(GraphChange::AttrChanged(Element::Graph));
+        &mut self.attrs
+    }
+
+    /// Changes whenever the graph may have changed, including mutable access to
+    /// any attributes, so derived data can tell when it is stale.
+    ///
+    /// Generations are unique across graphs, except that clones share theirs
+    /// until changed, and every new graph starts at `0`.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Subscribe to the graph's changes matching `filter`, eg. to update an
+    /// index as nodes are added instead of building it again.
+    ///
+    /// Changes are queued until [polled](Subscription::poll), so subscriptions
+    /// should be polled regularly or dropped. Clones of the graph do not share
+    /// its subscribers.
+    pub fn subscribe(&self, filter: ChangeFilter) -> Subscription {
+        self.subscribers.subscribe(filter)
+    }
+
+    /// The number of nodes.
+    pub fn node_count(&self) -> usize {
+        self.node_count
+    }
+
+    /// The number of edges.
+    pub fn edge_count(&self) -> usize {
+        self.edge_count
+    }
+
+    /// Returns `true` if the graph has no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.node_count == 0
+    }
+
+    /// Bytes the graph's slots and adjacency lists hold on the heap, its
+    /// attributes aside, see [`Self::attributes_size()`].
+    pub fn structure_size(&self) -> usize {
+        let adjacency: usize = self
+            .nodes
+            .iter()
+            .flatten()
+            .map(|node| (node.outgoing.capacity() + node.incoming.capacity()) * size_of::<EdgeId>())
+            .sum();
+        self.nodes.capacity() * size_of::<Option<Node>>()
+            + self.edges.capacity() * size_of::<Option<Edge>>()
+            + adjacency
+    }
+
+    /// Bytes the attributes of the graph, its nodes, and its edges hold on the
+    /// heap.
+    pub fn attributes_size(&self) -> usize {
+        let nodes: usize = self
+            .nodes
+            .iter()
+            .flatten()
+            .map(|n| n.attrs.heap_size())
+            .sum();
+        let edges: usize = self
+            .edges
+            .iter()
+            .flatten()
+            .map(|e| e.attrs.heap_size())
+            .sum();
+        self.attrs.heap_size() + nodes + edges
+    }
+
+    /// One past the largest node index ever used, see [`NodeId::index()`].
+    pub fn node_bound(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// One past the largest edge index ever used, see [`EdgeId::index()`].
+    pub fn edge_bound(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// Add a node without attributes.
+    pub fn add_node(&mut self) -> NodeId {
+        self.add_node_with(Attributes::default())
+    }
+
+    /// Add a node with `attrs`.
+    pub fn add_node_with(&mut self, attrs: Attributes) -> NodeId {
+        let id = NodeId(self.nodes.len() as u32);
+        self.nodes.push(Some(Node {
+            attrs,
+            ..Default::default()
+        }));
+        self.node_count += 1;
+        self.changed(GraphChange::NodeAdded(id));
+        id
+    }
+
+    /// Remove a node and every edge attached to it, returning its attributes.
+    pub fn remove_node(&mut self, node: NodeId) -> Option<Attributes> {
+        let removed = self.nodes.get_mut(node.index())?.take()?;
+        self.node_count -= 1;
+        self.changed(GraphChange::NodeRemoved(node));
+        for edge in removed.outgoing.iter().chain(&removed.incoming) {
+            self.remove_edge(*edge);
+        }
+        Some(removed.attrs)
+    }
+
+    /// Put a removed node back under its old id, with `attrs` and no edges, eg.
+    /// to undo its removal.
+    ///
+    /// Errors if the id was never used, or its node exists.
+    pub fn restore_node(&mut self, node: NodeId, attrs: Attributes) -> Result<(), GraphError> {
+        let slot = self
+            .nodes
+            .get_mut(node.index())
+            .ok_or(GraphError::MissingNode(node))?;
+        if slot.is_some() {
+            return Err(GraphError::NodeExists(node));
+        }
+        *slot = Some(Node {
+            attrs,
+            ..Default::default()
+        });
+        self.node_count += 1;
+        self.changed(GraphChange::NodeAdded(node));
+        Ok(())
+    }
+
+    /// Add an edge from `source` to `target`, without attributes.
+    ///
+    /// Errors if either node does not exist.
+    pub fn add_edge(&mut self, source: NodeId, target: NodeId) -> Result<EdgeId, GraphError> {
+        self.add_edge_with(source, target, Attributes::default())
+    }
+
+    /// Add an edge from `source` to `target`, with `attrs`.
+    ///
+    /// Errors if either node does not exist.
+    pub fn add_edge_with(
+        &mut self,
+        source: NodeId,
+        target: NodeId,
+        attrs: Attributes,
+    ) -> Result<EdgeId, GraphError> {
+        for node in [source, target] {
+            if !self.contains_node(node) {
+                return Err(GraphError::MissingNode(node));
+            }
+        }
+
+        let id = EdgeId(self.edges.len() as u32);
+        self.edges.push(Some(Edge {
+            source,
+            target,
+            attrs,
+        }));
+        self.slot_mut(source).outgoing.push(id);
+        self.slot_mut(target).incoming.push(id);
+        self.edge_count += 1;
+        self.changed(GraphChange::EdgeAdded(id));
+        Ok(id)
+    }
+
+    /// Put a removed edge back under its old id, like [`Self::restore_node()`].
+    ///
+    /// Errors if the id was never used, its edge exists, or either node does
+    /// not exist.
+    pub fn restore_edge(
+        &mut self,
+        edge: EdgeId,
+        source: NodeId,
+        target: NodeId,
+        attrs: Attributes,
+    ) -> Result<(), GraphError> {
+        for node in [source, target] {
+            if !self.contains_node(node) {
+                return Err(GraphError::MissingNode(node));
+            }
+        }
+        let slot = self
+            .edges
+            .get_mut(edge.index())
+            .ok_or(GraphError::MissingEdge(edge))?;
+        if slot.is_some() {
+            return Err(GraphError::EdgeExists(edge));
+        }
+        *slot = Some(Edge {
+            source,
+            target,
+            attrs,
+        });
+        self.slot_mut(source).outgoing.push(edge);
+        self.slot_mut(target).incoming.push(edge);
+        self.edge_count += 1;
+        self.changed(GraphChange::EdgeAdded(edge));
+        Ok(())
+    }
+
+    /// Remove an edge, returning its attributes.
+    pub fn remove_edge(&mut self, edge: EdgeId) -> Option<Attributes> {
+        let removed = self.edges.get_mut(edge.index())?.take()?;
+        self.edge_count -= 1;
+        self.changed(GraphChange::EdgeRemoved(edge));
+        if let Some(Some(source)) = self.nodes.get_mut(removed.source.index()) {
+            source.outgoing.retain(|e| *e != edge);
+        }
+        if let Some(Some(target)) = self.nodes.get_mut(removed.target.index()) {
+            target.incoming.retain(|e| *e != edge);
+        }
+        Some(removed.attrs)
+    }
+
+    /// Remove every node and edge. Ids are not reused afterwards either.
+    pub fn clear(&mut self) {
+        self.nodes.iter_mut().for_each(|n| *n = None);
+        self.edges.iter_mut().for_each(|e| *e = None);
+        self.node_count = 0;
+        self.edge_count = 0;
+        self.changed(GraphChange::Cleared);
+    }
+
+    /// Returns `true` if `node` exists.
+    pub fn contains_node(&self, node: NodeId) -> bool {
+        self.slot(node).is_some()
+    }
+
+    /// Returns `true` if `edge` exists.
+    pub fn contains_edge(&self, edge: EdgeId) -> bool {
+        matches!(self.edges.get(edge.index()), Some(Some(_)))
+    }
+
+    /// The attributes of `node`, if it exists.
+    pub fn node(&self, node: NodeId) -> Option<&Attributes> {
+        self.slot(node).map(|n| &n.attrs)
+    }
+
+    /// Mutable access to the attributes of `node`, if it exists.
+    pub fn node_mut(&mut self, node: NodeId) -> Option<&mut Attributes> {
+        self.touch();
+        if self.contains_node(node) {
+            self.subscribers
+                .emit(GraphChange::AttrChanged(Element::Node(node)));
+        }
+        match self.nodes.get_mut(node.index()) {
+            Some(Some(n)) => Some(&mut n.attrs),
+            _ => None,
+        }
+    }
+
+    /// The attributes of `edge`, if it exists.
+    pub fn edge(&self, edge: EdgeId) -> Option<&Attributes> {
+        self.edge_slot(edge).map(|e| &e.attrs)
+    }
+
+    /// Mutable access to the attributes of `edge`, if it exists.
+    pub fn edge_mut(&mut self, edge: EdgeId) -> Option<&mut Attributes> {
+        self.touch();
+        if self.contains_edge(edge) {
+            self.subscribers
+                .emit(GraphChange::AttrChanged(Element::Edge(edge)));
+        }
+        match self.edges.get_mut(edge.index()) {
+            Some(Some(e)) => Some(&mut e.attrs),
+            _ => None,
+        }
+    }
+
+    /// The `(source, target)` nodes of `edge`, if it exists.
+    pub fn endpoints(&self, edge: EdgeId) -> Option<(NodeId, NodeId)> {
+        self.edge_slot(edge).map(|e| (e.source, e.target))
+    }
+
+    /// Iterate over all nodes, in id order.
+    pub fn nodes(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, n)| n.is_some())
+            .map(|(i, _)| NodeId(i as u32))
+    }
+
+    /// Iterate over all edges, in id order.
+    pub fn edges(&self) -> impl Iterator<Item = EdgeId> + '_ {
+        self.edges
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| e.is_some())
+            .map(|(i, _)| EdgeId(i as u32))
+    }
+
+    /// Edges with `node` as their source. Empty if the node does not exist.
+    pub fn out_edges(&self, node: NodeId) -> impl Iterator<Item = EdgeId> + '_ {
+        self.slot(node)
+            .into_iter()
+            .flat_map(|n| n.outgoing.iter().copied())
+    }
+
+    /// Edges with `node` as their target. Empty if the node does not exist.
+    pub fn in_edges(&self, node: NodeId) -> impl Iterator<Item = EdgeId> + '_ {
+        self.slot(node)
+            .into_iter()
+            .flat_map(|n| n.incoming.iter().copied())
+    }
+
+    /// Every edge attached to `node`, with self-loops listed once.
+    pub fn incident_edges(&self, node: NodeId) -> impl Iterator<Item = EdgeId> + '_ {
+        self.out_edges(node).chain(
+            self.in_edges(node)
+                .filter(move |e| self.endpoints(*e).is_some_and(|(s, _)| s != node)),
+        )
+    }
+
+    /// Nodes reachable from `node` over a single edge: its successors in a
+    /// directed graph, or every adjacent node in an undirected one.
+    ///
+    /// Nodes connected by parallel edges are listed once per edge.
+    pub fn neighbors(&self, node: NodeId) -> Box<dyn Iterator<Item = NodeId> + '_> {
+        match self.directedness {
+            Directedness::Directed => Box::new(self.successors(node)),
+            Directedness::Undirected => Box::new(
+                self.incident_edges(node)
+                    .filter_map(move |e| self.opposite(e, node)),
+            ),
+        }
+    }
+
+    /// Targets of the edges leaving `node`.
+    pub fn successors(&self, node: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        self.out_edges(node)
+            .filter_map(|e| self.endpoints(e).map(|(_, t)| t))
+    }
+
+    /// Sources of the edges entering `node`.
+    pub fn predecessors(&self, node: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        self.in_edges(node)
+            .filter_map(|e| self.endpoints(e).map(|(s, _)| s))
+    }
+
+    /// The other endpoint of `edge`, seen from `node`. Returns `node` itself for
+    /// self-loops, and [None] if `node` is not an endpoint of `edge`.
+    pub fn opposite(&self, edge: EdgeId, node: NodeId) -> Option<NodeId> {
+        match self.endpoints(edge)? {
+            (s, t) if s == node => Some(t),
+            (s, t) if t == node => Some(s),
+            _ => None,
+        }
+    }
+
+    /// The number of edges leaving `node`.
+    pub fn out_degree(&self, node: NodeId) -> usize {
+        self.slot(node).map_or(0, |n| n.outgoing.len())
+    }
+
+    /// The number of edges entering `node`.
+    pub fn in_degree(&self, node: NodeId) -> usize {
+        self.slot(node).map_or(0, |n| n.incoming.len())
+    }
+
+    /// The number of edges attached to `node`, counting self-loops twice.
+    pub fn degree(&self, node: NodeId) -> usize {
+        self.out_degree(node) + self.in_degree(node)
+    }
+
+    /// An edge from `source` to `target`, if any. In undirected graphs an edge
+    /// from `target` to `source` also matches.
+    pub fn find_edge(&self, source: NodeId, target: NodeId) -> Option<EdgeId> {
+        let directed = self.is_directed();
+        self.incident_edges(source)
+            .find(|e| match self.endpoints(*e) {
+                Some((s, t)) => {
+                    (s == source && t == target) || (!directed && t == source && s == target)
+                }
+                None => false,
+            })
+    }
+
+    fn slot(&self, node: NodeId) -> Option<&Node> {
+        self.nodes.get(node.index()).and_then(Option::as_ref)
+    }
+
+    /// Give the graph a fresh generation, after it may have changed.
+    fn touch(&mut self) {
+        self.generation = GENERATION.fetch_add(1, Ordering::Relaxed) + 1;
+    }
+
+    /// Touch the graph and tell its subscribers about `change`.
+    fn changed(&mut self, change: GraphChange) {
+        self.touch();
+        self.subscribers.emit(change);
+    }
+
+    fn slot_mut(&mut self, node: NodeId) -> &mut Node {
+        self.nodes[node.index()]
+            .as_mut()
+            .expect("Tried to access a removed node")
+    }
+
+    fn edge_slot(&self, edge: EdgeId) -> Option<&Edge> {
+        self.edges.get(edge.index()).and_then(Option::as_ref)
+    }
+}
+
+impl std::fmt::Display for NodeId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "n{}", self.0)
+    }
+}
+
+impl std::fmt::Display for EdgeId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "e{}", self.0)
+    }
+}
+
+impl std::fmt::Display for GraphError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingNode(n) => write!(f, "Node {n} does not exist"),
+            Self::MissingEdge(e) => write!(f, "Edge {e} does not exist"),
+            Self::NodeExists(n) => write!(f, "Node {n} already exists"),
+            Self::EdgeExists(e) => write!(f, "Edge {e} already exists"),
+        }
+    }
+}
+
+impl std::error::Error for GraphError {}
@@ -0,0 +1,252 @@
+use parking_lot::RwLock;
+
+use std::collections::HashMap;
+use std::ffi::c_void;
+use std::sync::LazyLock;
+use std::sync::atomic::{AtomicPtr, Ordering};
+
+/// Global table of interned attribute keys.
+static SYMBOLS: LazyLock<RwLock<SymbolTable>> = LazyLock::new(Default::default);
+/// The table used instead of [`SYMBOLS`] if set, see [`share_symbols()`].
+static SHARED_SYMBOLS: AtomicPtr<RwLock<SymbolTable>> = AtomicPtr::new(std::ptr::null_mut());
+
+#[derive(Default)]
+struct SymbolTable {
+    ids: HashMap<&'static str, Symbol>,
+    names: Vec<&'static str>,
+}
+
+/// An interned attribute key.
+///
+/// Interning makes keys cheap to copy, compare, and hash, since large graphs
+/// repeat the same few keys on every element. Interned strings live for the rest
+/// of the program.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Symbol(u32);
+
+/// A typed attribute value.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AttrValue {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+}
+
+/// Attributes of a single node or edge, keyed by [`Symbol`].
+///
+/// Stored as a small vector in insertion order, since elements rarely carry more
+/// than a handful of attributes.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Attributes(Vec<(Symbol, AttrValue)>);
+
+impl Symbol {
+    /// Intern `name`, returning the existing symbol if it was interned before.
+    pub fn new(name: &str) -> Self {
+        if let Some(symbol) = symbols().read().ids.get(name) {
+            return *symbol;
+        }
+
+        let mut table = symbols().write();
+        // another thread may have interned it between the locks
+        if let Some(symbol) = table.ids.get(name) {
+            return *symbol;
+        }
+        let name: &'static str = Box::leak(name.into());
+        let symbol = Symbol(table.names.len() as u32);
+        table.names.push(name);
+        table.ids.insert(name, symbol);
+        symbol
+    }
+
+    /// The symbol's name.
+    pub fn as_str(self) -> &'static str {
+        symbols().read().names[self.0 as usize]
+    }
+}
+
+/// The table symbols are interned in.
+fn symbols() -> &'static RwLock<SymbolTable> {
+    let shared = SHARED_SYMBOLS.load(Ordering::Acquire);
+    if shared.is_null() {
+        &SYMBOLS
+    } else {
+        // the application's table, which outlives the plugin library using it
+        unsafe { &*shared }
+    }
+}
+
+/// The table symbols are interned in, for [`share_symbols()`].
+#[doc(hidden)]
+pub fn symbol_table() -> *mut c_void {
+    std::ptr::from_ref(symbols()).cast_mut().cast()
+}
+
+/// Intern symbols in `table` from now on, so that a plugin library, which has
+/// its own copy of the engine's statics, gives keys the same symbols as the
+/// application.
+///
+/// # Safety
+///
+/// `table` must come from [`symbol_table()`] of a copy of the engine built
+/// from the same sources by the same compiler, and no symbol may have been
+/// interned in this copy yet.
+#[doc(hidden)]
+pub unsafe fn share_symbols(table: *mut c_void) {
+    SHARED_SYMBOLS.store(table.cast(), Ordering::Release);
+}
+
+impl AttrValue {
+    /// The value as a float, converting integers and booleans.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Bool(b) => Some(*b as u8 as f64),
+            Self::Int(i) => Some(*i as f64),
+            Self::Float(f) => Some(*f),
+            Self::String(_) => None,
+        }
+    }
+
+    /// The value as a string slice, if it is a string.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Bytes the value holds on the heap, besides its own.
+    pub fn heap_size(&self) -> usize {
+        match self {
+            Self::String(s) => s.capacity(),
+            _ => 0,
+        }
+    }
+}
+
+impl Attributes {
+    /// The value of `key`, if set.
+    pub fn get(&self, key: impl Into<Symbol>) -> Option<&AttrValue> {
+        let key = key.into();
+        self.0.iter().find(|(k, _)| *k == key).map(|(_, v)| v)
+    }
+
+    /// Mutable access to the value of `key`, if set.
+    pub fn get_mut(&mut self, key: impl Into<Symbol>) -> Option<&mut AttrValue> {
+        let key = key.into();
+        self.0.iter_mut().find(|(k, _)| *k == key).map(|(_, v)| v)
+    }
+
+    /// Set `key` to `value`, returning the previous value.
+    pub fn insert(
+        &mut self,
+        key: impl Into<Symbol>,
+        value: impl Into<AttrValue>,
+    ) -> Option<AttrValue> {
+        let key = key.into();
+        let value = value.into();
+        match self.get_mut(key) {
+            Some(old) => Some(std::mem::replace(old, value)),
+            None => {
+                self.0.push((key, value));
+                None
+            }
+        }
+    }
+
+    /// Unset `key`, returning its value.
+    pub fn remove(&mut self, key: impl Into<Symbol>) -> Option<AttrValue> {
+        let key = key.into();
+        let index = self.0.iter().position(|(k, _)| *k == key)?;
+        Some(self.0.remove(index).1)
+    }
+
+    /// Returns `true` if `key` is set.
+    pub fn contains(&self, key: impl Into<Symbol>) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// The number of attributes set.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if no attributes are set.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Iterate over keys and values, in insertion order.
+    pub fn iter(&self) -> impl Iterator<Item = (Symbol, &AttrValue)> {
+        self.0.iter().map(|(k, v)| (*k, v))
+    }
+
+    /// Bytes the attributes hold on the heap, besides their own.
+    pub fn heap_size(&self) -> usize {
+        let values: usize = self.0.iter().map(|(_, v)| v.heap_size()).sum();
+        self.0.capacity() * size_of::<(Symbol, AttrValue)>() + values
+    }
+}
+
+impl From<&str> for Symbol {
+    fn from(name: &str) -> Self {
+        Self::new(name)
+    }
+}
+
+impl std::fmt::Display for Symbol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::fmt::Display for AttrValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Bool(b) => write!(f, "{b}"),
+            Self::Int(i) => write!(f, "{i}"),
+            Self::Float(v) => write!(f, "{v}"),
+            Self::String(s) => f.write_str(s),
+        }
+    }
+}
+
+impl From<bool> for AttrValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<i64> for AttrValue {
+    fn from(value: i64) -> Self {
+        Self::Int(value)
+    }
+}
+
+impl From<f64> for AttrValue {
+    fn from(value: f64) -> Self {
+        Self::Float(value)
+    }
+}
+
+impl From<String> for AttrValue {
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+impl From<&str> for AttrValue {
+    fn from(value: &str) -> Self {
+        Self::String(value.to_owned())
+    }
+}
+
+impl<K: Into<Symbol>, V: Into<AttrValue>> FromIterator<(K, V)> for Attributes {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut attrs = Self::default();
+        for (k, v) in iter {
+            attrs.insert(k, v);
+        }
+        attrs
+    }
+}
@@ -0,0 +1,140 @@
+use parking_lot::Mutex;
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+
+use super::{EdgeId, Graph, NodeId};
+
+/// Last instance number given to any graph, see [`Subscription::poll()`].
+static INSTANCE: AtomicU64 = AtomicU64::new(0);
+
+/// A single change to a [`Graph`], sent to its [subscribers](Graph::subscribe).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum GraphChange {
+    /// A node was added, or restored under its old id.
+    NodeAdded(NodeId),
+    /// A node was removed. Each of its edges is reported removed after it.
+    NodeRemoved(NodeId),
+    /// An edge was added, or restored under its old id.
+    EdgeAdded(EdgeId),
+    EdgeRemoved(EdgeId),
+    /// The attributes of an element were mutably accessed, and may have changed.
+    AttrChanged(Element),
+    /// Every node and edge was removed at once, see [`Graph::clear()`].
+    Cleared,
+}
+
+/// The part of a graph whose attributes changed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Element {
+    Graph,
+    Node(NodeId),
+    Edge(EdgeId),
+}
+
+/// Which [`GraphChange`]s a subscriber is sent. [`GraphChange::Cleared`] is
+/// sent to every subscriber.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ChangeFilter {
+    /// Nodes added and removed.
+    pub nodes: bool,
+    /// Edges added and removed.
+    pub edges: bool,
+    /// Attributes of the graph, its nodes, and its edges changed.
+    pub attrs: bool,
+}
+
+/// Changes to a graph, see [`Graph::subscribe()`].
+#[derive(Debug)]
+pub struct Subscription {
+    /// Instance of the graph subscribed to.
+    instance: u64,
+    receiver: Receiver<GraphChange>,
+}
+
+/// Subscribers of a graph. A clone starts without any, as it is another graph.
+#[derive(Debug)]
+pub(super) struct Subscribers {
+    instance: u64,
+    senders: Mutex<Vec<(ChangeFilter, Sender<GraphChange>)>>,
+}
+
+impl ChangeFilter {
+    /// Every change.
+    pub const ALL: Self = Self {
+        nodes: true,
+        edges: true,
+        attrs: true,
+    };
+
+    /// Nodes and edges added and removed, but not attribute changes.
+    pub const STRUCTURE: Self = Self {
+        attrs: false,
+        ..Self::ALL
+    };
+
+    /// Returns `true` if subscribers with this filter are sent `change`.
+    pub fn matches(&self, change: &GraphChange) -> bool {
+        match change {
+            GraphChange::NodeAdded(_) | GraphChange::NodeRemoved(_) => self.nodes,
+            GraphChange::EdgeAdded(_) | GraphChange::EdgeRemoved(_) => self.edges,
+            GraphChange::AttrChanged(_) => self.attrs,
+            GraphChange::Cleared => true,
+        }
+    }
+}
+
+impl Subscription {
+    /// Changes made to `graph` since the last poll, oldest first.
+    ///
+    /// Returns [None] if `graph` is not the graph subscribed to, eg. because it
+    /// was replaced by another one or a clone, or the subscribed graph was
+    /// dropped. Derived data then has to be rebuilt from `graph`, and a new
+    /// subscription taken.
+    pub fn poll(&self, graph: &Graph) -> Option<Vec<GraphChange>> {
+        if graph.subscribers.instance != self.instance {
+            return None;
+        }
+        let mut changes = Vec::new();
+        loop {
+            match self.receiver.try_recv() {
+                Ok(change) => changes.push(change),
+                Err(TryRecvError::Empty) => return Some(changes),
+                Err(TryRecvError::Disconnected) => return None,
+            }
+        }
+    }
+}
+
+impl Subscribers {
+    pub(super) fn subscribe(&self, filter: ChangeFilter) -> Subscription {
+        let (sender, receiver) = mpsc::channel();
+        self.senders.lock().push((filter, sender));
+        Subscription {
+            instance: self.instance,
+            receiver,
+        }
+    }
+
+    /// Send `change` to the interested subscribers, forgetting those whose
+    /// subscription was dropped.
+    pub(super) fn emit(&self, change: GraphChange) {
+        let mut senders = self.senders.lock();
+        senders.retain(|(filter, sender)| !filter.matches(&change) || sender.send(change).is_ok());
+    }
+}
+
+impl Clone for Subscribers {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl std::default::Default for Subscribers {
+    fn default() -> Self {
+        Self {
+            instance: INSTANCE.fetch_add(1, Ordering::Relaxed) + 1,
+            senders: Mutex::default(),
+        }
+    }
+}
@@ -0,0 +1,526 @@
+//! # Groups
+//!
+//! Nodes can be nested inside group nodes, making compound graphs. A node's
+//! [`PARENT_ATTR`] holds the id of the group containing it, as read from
+//! nested GraphML graphs or GEXF hierarchies, where ids are
+//! [`ID_ATTR`]s or derived from [`NodeId`]s as on export. [`Groups`] indexes the
+//! hierarchy of a graph, ignoring parents which are missing or nested in their
+//! own children.
+//!
+//! - Collapsing a group sets its [`GROUP_COLLAPSED_ATTR`], which hides its
+//!   members from the [visible graph](crate::filter::FilterStack), and links
+//!   it to what they were linked to by aggregated edges, counting the edges
+//!   they stand for in [`AGGREGATE_ATTR`]. Expanding it reverses both.
+//! - [`group()`] and [`ungroup()`] nest nodes in a new group, and dissolve one.
+//! - The [`GroupLayout`](crate::layout::group::GroupLayout) packs the members
+//!   of each group together, inside their own box.
+//!
+//! Each returns a [`Command`], so the [`GroupPanel`] can undo them.
+//!
+//! ```ignore
+//! let groups = Groups::new(&graph);
+//! if let Some(command) = group::set_collapsed(&graph, &groups, &[team], true) {
+//!     ctx.execute(command);
+//! }
+//! ```
+
+use std::collections::{HashMap, HashSet};
+
+use crate::context::EngineContext;
+use crate::graph::{AttrValue, Attributes, EdgeId, Graph, NodeId};
+use crate::history::Command;
+use crate::i18n::tr;
+use crate::imgui::Ui;
+use crate::io::{ID_ATTR, PARENT_ATTR, node_name};
+use crate::layout::Positions;
+use crate::selection::SelectMode;
+use crate::spatial::Rect;
+use crate::subsystems::panels::Panel;
+
+/// Attribute set to `true` on collapsed groups.
+pub const GROUP_COLLAPSED_ATTR: &str = "group_collapsed";
+/// Attribute of aggregated edges holding the number of edges they stand for.
+pub const AGGREGATE_ATTR: &str = "aggregated";
+
+/// The group hierarchy of a graph, see the [module documentation](self).
+#[derive(Clone, Debug, Default)]
+pub struct Groups {
+    /// Group containing each node slot, by index.
+    parents: Vec<Option<NodeId>>,
+    /// Members of each group, in id order.
+    members: HashMap<NodeId, Vec<NodeId>>,
+    /// Collapsed groups, in id order.
+    collapsed: Vec<NodeId>,
+    /// Generation of the graph indexed, see [`Self::update()`].
+    generation: Option<u64>,
+}
+
+/// Lists the groups of the graph as a tree, and groups, ungroups, collapses and
+/// expands the selected nodes.
+pub struct GroupPanel;
+
+impl Groups {
+    /// Index the groups of `graph`.
+    pub fn new(graph: &Graph) -> Self {
+        let mut groups = Self::default();
+        groups.update(graph);
+        groups
+    }
+
+    /// Index `graph` again if it changed since the last update. Returns `true`
+    /// if it did.
+    pub fn update(&mut self, graph: &Graph) -> bool {
+        if self.generation == Some(graph.generation()) {
+            return false;
+        }
+        self.generation = Some(graph.generation());
+        let ids: HashMap<String, NodeId> =
+            graph.nodes().map(|n| (node_name(graph, n), n)).collect();
+        self.parents = vec![None; graph.node_bound()];
+        for node in graph.nodes() {
+            self.parents[node.index()] = graph
+                .node(node)
+                .and_then(|attrs| attrs.get(PARENT_ATTR))
+                .and_then(|parent| ids.get(&parent.to_string()).copied())
+                .filter(|&parent| parent != node);
+        }
+        // break cycles of groups nested in their own members
+        for node in graph.nodes() {
+            let mut seen = HashSet::from([node]);
+            let mut current = node;
+            while let Some(parent) = self.parents[current.index()] {
+                if !seen.insert(parent) {
+                    self.parents[current.index()] = None;
+                    break;
+                }
+                current = parent;
+            }
+        }
+
+        self.members.clear();
+        for node in graph.nodes() {
+            if let Some(parent) = self.parents[node.index()] {
+                self.members.entry(parent).or_default().push(node);
+            }
+        }
+        self.collapsed = self
+            .groups()
+            .filter(|&group| is_collapsed_attr(graph, group))
+            .collect();
+        true
+    }
+
+    /// Returns `true` if no node belongs to a group.
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    /// The group containing `node`, if any.
+    pub fn parent(&self, node: NodeId) -> Option<NodeId> {
+        self.parents.get(node.index()).copied().flatten()
+    }
+
+    /// Returns `true` if `node` contains other nodes.
+    pub fn is_group(&self, node: NodeId) -> bool {
+        self.members.contains_key(&node)
+    }
+
+    /// The nodes directly inside `group`, in id order.
+    pub fn members(&self, group: NodeId) -> &[NodeId] {
+        self.members.get(&group).map_or(&[], Vec::as_slice)
+    }
+
+    /// Every group, in id order.
+    pub fn groups(&self) -> impl Iterator<Item = NodeId> + '_ {
+        let mut groups: Vec<NodeId> = self.members.keys().copied().collect();
+        groups.sort_unstable();
+        groups.into_iter()
+    }
+
+    /// Returns `true` if `group` is collapsed.
+    pub fn is_collapsed(&self, group: NodeId) -> bool {
+        self.collapsed.binary_search(&group).is_ok()
+    }
+
+    /// The collapsed groups, in id order.
+    pub fn collapsed(&self) -> &[NodeId] {
+        &self.collapsed
+    }
+
+    /// The nodes of `graph` in no group, in id order.
+    pub fn roots<'a>(&'a self, graph: &'a Graph) -> impl Iterator<Item = NodeId> + 'a {
+        graph.nodes().filter(|&node| self.parent(node).is_none())
+    }
+
+    /// The groups containing `node`, innermost first.
+    pub fn ancestors(&self, node: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        std::iter::successors(self.parent(node), |&group| self.parent(group))
+    }
+
+    /// The nodes inside `group`, at any depth.
+    pub fn descendants(&self, group: NodeId) -> Vec<NodeId> {
+        let mut found = Vec::new();
+        let mut stack = vec![group];
+        while let Some(group) = stack.pop() {
+            for &member in self.members(group) {
+                found.push(member);
+                stack.push(member);
+            }
+        }
+        found
+    }
+
+    /// The nodes inside collapsed groups, in id order.
+    pub fn hidden(&self) -> Vec<NodeId> {
+        let mut hidden: Vec<NodeId> = self
+            .collapsed
+            .iter()
+            .flat_map(|&group| self.descendants(group))
+            .collect();
+        hidden.sort_unstable();
+        hidden.dedup();
+        hidden
+    }
+
+    /// The node standing for `node` when the groups in `collapsed` are: the
+    /// outermost of them containing it, or itself.
+    fn representative(&self, node: NodeId, collapsed: &HashSet<NodeId>) -> NodeId {
+        self.ancestors(node)
+            .filter(|group| collapsed.contains(group))
+            .last()
+            .unwrap_or(node)
+    }
+
+    /// The box around the visible members of each expanded group of `graph`,
+    /// and the group itself, at `positions`, outermost groups first.
+    pub fn bounds(&self, graph: &Graph, positions: &Positions) -> Vec<(NodeId, Rect)> {
+        let mut bounds: Vec<(usize, NodeId, Rect)> = Vec::new();
+        for group in self.groups() {
+            if self.is_collapsed(group) || !graph.contains_node(group) {
+                continue;
+            }
+            let mut points = std::iter::once(group)
+                .chain(self.descendants(group))
+                .filter(|&node| graph.contains_node(node))
+                .filter_map(|node| positions.get(node));
+            let Some(first) = points.next() else {
+                continue;
+            };
+            let (min, max) = points.fold((first, first), |(min, max), p| {
+                (
+                    [min[0].min(p[0]), min[1].min(p[1])],
+                    [max[0].max(p[0]), max[1].max(p[1])],
+                )
+            });
+            bounds.push((self.ancestors(group).count(), group, (min, max)));
+        }
+        bounds.sort_by_key(|&(depth, group, _)| (depth, group));
+        bounds
+            .into_iter()
+            .map(|(_, group, rect)| (group, rect))
+            .collect()
+    }
+}
+
+fn is_collapsed_attr(graph: &Graph, node: NodeId) -> bool {
+    graph
+        .node(node)
+        .and_then(|attrs| attrs.get(GROUP_COLLAPSED_ATTR))
+        .is_some_and(|value| *value == AttrValue::Bool(true))
+}
+
+fn is_aggregate(graph: &Graph, edge: EdgeId) -> bool {
+    graph
+        .edge(edge)
+        .is_some_and(|attrs| attrs.contains(AGGREGATE_ATTR))
+}
+
+/// A command collapsing, or expanding, the `targets` which are groups of
+/// `graph`, and replacing the aggregated edges by those of the collapsed
+/// groups then. `None` if nothing changes.
+pub fn set_collapsed(
+    graph: &Graph,
+    groups: &Groups,
+    targets: &[NodeId],
+    collapsed: bool,
+) -> Option<Command> {
+    let changed: Vec<NodeId> = targets
+        .iter()
+        .copied()
+        .filter(|&node| groups.is_group(node) && groups.is_collapsed(node) != collapsed)
+        .collect();
+    if changed.is_empty() {
+        return None;
+    }
+    let mut now: HashSet<NodeId> = groups.collapsed().iter().copied().collect();
+    for &group in &changed {
+        match collapsed {
+            true => now.insert(group),
+            false => now.remove(&group),
+        };
+    }
+
+    let mut commands = vec![Command::SetNodeAttribute {
+        key: GROUP_COLLAPSED_ATTR.into(),
+        values: changed
+            .iter()
+            .map(|&group| (group, collapsed.then_some(AttrValue::Bool(true))))
+            .collect(),
+    }];
+    commands.extend(
+        graph
+            .edges()
+            .filter(|&edge| is_aggregate(graph, edge))
+            .map(|edge| Command::RemoveEdge { edge }),
+    );
+    commands.extend(aggregates(graph, groups, &now).into_iter().map(
+        |((source, target), count)| {
+            let mut attrs = Attributes::default();
+            attrs.insert(AGGREGATE_ATTR, count as i64);
+            Command::AddEdge {
+                edge: None,
+                source,
+                target,
+                attrs,
+            }
+        },
+    ));
+    Some(Command::Batch {
+        label: match collapsed {
+            true => "Collapse Groups".into(),
+            false => "Expand Groups".into(),
+        },
+        commands,
+    })
+}
+
+/// The aggregated edges linking the groups in `collapsed` to what their
+/// members are linked to, with the number of edges each stands for, in a
+/// stable order.
+fn aggregates(
+    graph: &Graph,
+    groups: &Groups,
+    collapsed: &HashSet<NodeId>,
+) -> Vec<((NodeId, NodeId), usize)> {
+    let mut counts: HashMap<(NodeId, NodeId), usize> = HashMap::new();
+    for edge in graph.edges() {
+        if is_aggregate(graph, edge) {
+            continue;
+        }
+        let (source, target) = graph.endpoints(edge).unwrap();
+        let ends = (
+            groups.representative(source, collapsed),
+            groups.representative(target, collapsed),
+        );
+        // shown as is, or inside a single group
+        if ends == (source, target) || ends.0 == ends.1 {
+            continue;
+        }
+        let ends = match graph.is_directed() || ends.0 < ends.1 {
+            true => ends,
+            false => (ends.1, ends.0),
+        };
+        *counts.entry(ends).or_default() += 1;
+    }
+    let mut counts: Vec<_> = counts.into_iter().collect();
+    counts.sort_unstable();
+    counts
+}
+
+/// A command nesting `nodes` of `graph` in a new group, placed at their
+/// center, inside the group containing all of them if any. `None` if no node
+/// is given.
+pub fn group(
+    graph: &Graph,
+    groups: &Groups,
+    positions: &Positions,
+    nodes: &[NodeId],
+) -> Option<Command> {
+    if nodes.is_empty() {
+        return None;
+    }
+    let ids: HashSet<String> = graph.nodes().map(|n| node_name(graph, n)).collect();
+    let (number, id) = (1..)
+        .map(|i| (i, format!("group{i}")))
+        .find(|(_, id)| !ids.contains(id))
+        .unwrap();
+    let mut attrs = Attributes::default();
+    attrs.insert(ID_ATTR, id.as_str());
+    attrs.insert("label", format!("Group {number}"));
+    let parent = groups.parent(nodes[0]);
+    if let Some(parent) = parent
+        && nodes
+            .iter()
+            .all(|&node| groups.parent(node) == Some(parent))
+    {
+        attrs.insert(PARENT_ATTR, node_name(graph, parent));
+    }
+    let points: Vec<[f32; 2]> = nodes.iter().filter_map(|&n| positions.get(n)).collect();
+    let position = (!points.is_empty()).then(|| {
+        let sum = points
+            .iter()
+            .fold([0.0; 2], |s, p| [s[0] + p[0], s[1] + p[1]]);
+        let n = points.len() as f32;
+        [sum[0] / n, sum[1] / n]
+    });
+    Some(Command::Batch {
+        label: "Group".into(),
+        commands: vec![
+            Command::AddNode {
+                node: None,
+                attrs,
+                position,
+            },
+            Command::SetNodeAttribute {
+                key: PARENT_ATTR.into(),
+                values: nodes
+                    .iter()
+                    .map(|&node| (node, Some(AttrValue::from(id.as_str()))))
+                    .collect(),
+            },
+        ],
+    })
+}
+
+/// A command removing the expanded `group` of `graph`, moving its members to
+/// the group containing it, if any. `None` if it is not an expanded group.
+pub fn ungroup(graph: &Graph, groups: &Groups, group: NodeId) -> Option<Command> {
+    if !groups.is_group(group) || groups.is_collapsed(group) {
+        return None;
+    }
+    let parent = groups
+        .parent(group)
+        .map(|parent| AttrValue::from(node_name(graph, parent)));
+    Some(Command::Batch {
+        label: "Ungroup".into(),
+        commands: vec![
+            Command::SetNodeAttribute {
+                key: PARENT_ATTR.into(),
+                values: groups
+                    .members(group)
+                    .iter()
+                    .map(|&member| (member, parent.clone()))
+                    .collect(),
+            },
+            Command::RemoveNode { node: group },
+        ],
+    })
+}
+
+impl GroupPanel {
+    /// Every group of `graph` with its depth, each followed by those inside it.
+    fn tree(groups: &Groups, graph: &Graph) -> Vec<(usize, NodeId)> {
+        let mut rows = Vec::new();
+        let mut stack: Vec<(usize, NodeId)> = groups
+            .roots(graph)
+            .filter(|&node| groups.is_group(node))
+            .map(|node| (0, node))
+            .collect();
+        stack.reverse();
+        while let Some((depth, group)) = stack.pop() {
+            rows.push((depth, group));
+            let start = stack.len();
+            stack.extend(
+                groups
+                    .members(group)
+                    .iter()
+                    .filter(|&&member| groups.is_group(member))
+                    .map(|&member| (depth + 1, member)),
+            );
+            stack[start..].reverse();
+        }
+        rows
+    }
+}
+
+impl Panel for GroupPanel {
+    fn id(&self) -> &'static str {
+        "Groups"
+    }
+
+    fn draw(&mut self, ui: &Ui, ctx: &mut EngineContext) {
+        let selected: Vec<NodeId> = ctx.selection.nodes().collect();
+        let groups = &ctx.groups;
+        let selected_groups: Vec<NodeId> = selected
+            .iter()
+            .copied()
+            .filter(|&node| groups.is_group(node))
+            .collect();
+        let all: Vec<NodeId> = groups.groups().collect();
+
+        let mut command = None;
+        ui.enabled(!selected.is_empty(), || {
+            if ui.button(tr("Group Selection")) {
+                command = group(&ctx.graph, groups, &ctx.positions, &selected);
+            }
+        });
+        ui.same_line();
+        ui.enabled(!selected_groups.is_empty(), || {
+            if ui.button(tr("Ungroup")) {
+                let commands: Vec<Command> = selected_groups
+                    .iter()
+                    .filter_map(|&group| ungroup(&ctx.graph, groups, group))
+                    .collect();
+                command = (!commands.is_empty()).then(|| Command::Batch {
+                    label: "Ungroup".into(),
+                    commands,
+                });
+            }
+            ui.same_line();
+            if ui.button(tr("Collapse")) {
+                command = set_collapsed(&ctx.graph, groups, &selected_groups, true);
+            }
+            ui.same_line();
+            if ui.button(tr("Expand")) {
+                command = set_collapsed(&ctx.graph, groups, &selected_groups, false);
+            }
+        });
+        ui.enabled(!all.is_empty(), || {
+            if ui.button(tr("Collapse All")) {
+                command = set_collapsed(&ctx.graph, groups, &all, true);
+            }
+            ui.same_line();
+            if ui.button(tr("Expand All")) {
+                command = set_collapsed(&ctx.graph, groups, &all, false);
+            }
+        });
+        if let Some(command) = command {
+            ctx.execute(command);
+        }
+        ui.separator();
+
+        if ctx.groups.is_empty() {
+            ui.text_disabled(tr("No groups, select nodes to group them"));
+            return;
+        }
+        let rows = Self::tree(&ctx.groups, &ctx.graph);
+        let mut clicked = None;
+        ui.child_window("##groups").build(|| {
+            for (depth, group) in rows {
+                let name = ctx
+                    .style
+                    .node_label(&ctx.graph, group, true)
+                    .unwrap_or_else(|| node_name(&ctx.graph, group));
+                let members = ctx.groups.members(group).len();
+                let state = match ctx.groups.is_collapsed(group) {
+                    true => ", collapsed",
+                    false => "",
+                };
+                let _id = ui.push_id_usize(group.index());
+                if ui
+                    .selectable_config(format!(
+                        "{}{name}  ({members} members{state})",
+                        "    ".repeat(depth)
+                    ))
+                    .selected(ctx.selection.contains_node(group))
+                    .build()
+                {
+                    clicked = Some(group);
+                }
+            }
+        });
+        if let Some(group) = clicked {
+            ctx.selection.select_node(group, SelectMode::Replace);
+        }
+    }
+}
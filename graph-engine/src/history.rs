@@ -0,0 +1,519 @@
+//! # Undo and Redo
+//!
+//! Every change the user makes to the graph, its layout, or its style goes
+//! through [`EngineContext::execute()`] as a [`Command`]. Applying a command
+//! returns its inverse, which the context's [`CommandStack`] keeps so
+//! [`EngineContext::undo()`] can apply it, in turn returning the command to
+//! [redo](EngineContext::redo()).
+//!
+//! Commands repeating the previous one on the same target within
+//! [`MERGE_WINDOW`], eg. while dragging a slider or a node, are merged into a
+//! single step, undone all at once.
+//!
+//! ```ignore
+//! ctx.execute(Command::RemoveNode { node });
+//! ctx.undo(); // the node is back, with its edges and position
+//! ctx.redo();
+//! ```
+
+use std::time::{Duration, Instant};
+
+use crate::context::EngineContext;
+use crate::graph::{AttrValue, Attributes, EdgeId, Graph, GraphError, NodeId, Symbol};
+use crate::i18n::tr;
+use crate::imgui::{StyleColor, Ui};
+use crate::layout::Positions;
+use crate::style::StyleSheet;
+use crate::subsystems::panels::Panel;
+
+/// Maximum delay between two commands on the same target for them to merge.
+pub const MERGE_WINDOW: Duration = Duration::from_millis(1000);
+
+/// Text color of the steps which were undone in the [`HistoryPanel`].
+const UNDONE_COLOR: [f32; 4] = [0.5, 0.5, 0.5, 1.0];
+
+/// Number of steps kept by default, see [`CommandStack::with_limit()`].
+const DEFAULT_LIMIT: usize = 500;
+
+/// A reversible change, see [`EngineContext::execute()`].
+#[derive(Clone, Debug)]
+pub enum Command {
+    /// Add a node, or restore a removed one under its old `node` id.
+    AddNode {
+        node: Option<NodeId>,
+        attrs: Attributes,
+        position: Option<[f32; 2]>,
+    },
+    /// Remove a node and the edges attached to it.
+    RemoveNode { node: NodeId },
+    /// Add an edge, or restore a removed one under its old `edge` id.
+    AddEdge {
+        edge: Option<EdgeId>,
+        source: NodeId,
+        target: NodeId,
+        attrs: Attributes,
+    },
+    /// Remove an edge.
+    RemoveEdge { edge: EdgeId },
+    /// Set `key` on each node to its value, or unset it for `None`.
+    SetNodeAttribute {
+        key: Symbol,
+        values: Vec<(NodeId, Option<AttrValue>)>,
+    },
+    /// Set `key` on each edge to its value, or unset it for `None`.
+    SetEdgeAttribute {
+        key: Symbol,
+        values: Vec<(EdgeId, Option<AttrValue>)>,
+    },
+    /// Pin or release a node in the force layout, moving it to `position` if
+    /// given.
+    Pin {
+        node: NodeId,
+        pinned: bool,
+        position: Option<[f32; 2]>,
+    },
+    /// Apply a style sheet.
+    SetStyle(Box<StyleSheet>),
+    /// Replace the whole graph along with its positions, eg. by a collapsed
+    /// version of itself.
+    ReplaceGraph {
+        graph: Box<Graph>,
+        positions: Box<Positions>,
+    },
+    /// Several commands applied in order, and undone as one step.
+    Batch {
+        label: String,
+        commands: Vec<Command>,
+    },
+}
+
+/// What a command changes, for merging successive commands.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum MergeKey {
+    NodeAttribute(Symbol, Vec<NodeId>),
+    EdgeAttribute(Symbol, Vec<EdgeId>),
+    Pin(NodeId),
+    Style,
+}
+
+/// Undoable and redoable steps, see [`EngineContext::execute()`].
+pub struct CommandStack {
+    /// Steps which can be undone, the most recent last.
+    done: Vec<Step>,
+    /// Steps which can be redone, the most recently undone last.
+    undone: Vec<Step>,
+    limit: usize,
+    /// Prevents the next step from merging into the last one.
+    sealed: bool,
+}
+
+/// A step in a [`CommandStack`].
+#[derive(Debug)]
+struct Step {
+    label: String,
+    /// Undoes the step if done, or redoes it if undone.
+    command: Command,
+    merge: Option<MergeKey>,
+    time: Instant,
+}
+
+/// Lists the steps of [`EngineContext::history`], undoing or redoing up to
+/// the one clicked.
+pub struct HistoryPanel;
+
+impl Command {
+    /// Set `key` on nodes, see [`Self::SetNodeAttribute`].
+    pub fn set_node_attribute(
+        key: impl Into<Symbol>,
+        values: impl IntoIterator<Item = (NodeId, AttrValue)>,
+    ) -> Self {
+        Self::SetNodeAttribute {
+            key: key.into(),
+            values: values.into_iter().map(|(n, v)| (n, Some(v))).collect(),
+        }
+    }
+
+    /// Human-readable description of the command.
+    pub fn label(&self) -> String {
+        match self {
+            Self::AddNode { .. } => "Add Node".into(),
+            Self::RemoveNode { .. } => "Remove Node".into(),
+            Self::AddEdge { .. } => "Add Edge".into(),
+            Self::RemoveEdge { .. } => "Remove Edge".into(),
+            Self::SetNodeAttribute { key, values } => {
+                attribute_label(*key, values.iter().all(|(_, v)| v.is_none()))
+            }
+            Self::SetEdgeAttribute { key, values } => {
+                attribute_label(*key, values.iter().all(|(_, v)| v.is_none()))
+            }
+            Self::Pin {
+                pinned: true,
+                position: Some(_),
+                ..
+            } => "Move Node".into(),
+            Self::Pin { pinned: true, .. } => "Pin Node".into(),
+            Self::Pin { pinned: false, .. } => "Release Node".into(),
+            Self::SetStyle(_) => "Change Style".into(),
+            Self::ReplaceGraph { .. } => "Replace Graph".into(),
+            Self::Batch { label, .. } => label.clone(),
+        }
+    }
+
+    pub(crate) fn merge_key(&self) -> Option<MergeKey> {
+        match self {
+            Self::SetNodeAttribute { key, values } => Some(MergeKey::NodeAttribute(
+                *key,
+                values.iter().map(|(n, _)| *n).collect(),
+            )),
+            Self::SetEdgeAttribute { key, values } => Some(MergeKey::EdgeAttribute(
+                *key,
+                values.iter().map(|(e, _)| *e).collect(),
+            )),
+            Self::Pin { node, .. } => Some(MergeKey::Pin(*node)),
+            Self::SetStyle(_) => Some(MergeKey::Style),
+            _ => None,
+        }
+    }
+
+    /// Apply the command to `ctx`, returning the command undoing it.
+    ///
+    /// Errors if the command refers to elements which do not exist. A failed
+    /// [batch](Self::Batch) is left partly applied.
+    pub fn apply(self, ctx: &mut EngineContext) -> Result<Command, GraphError> {
+        Ok(match self {
+            Self::AddNode {
+                node,
+                attrs,
+                position,
+            } => {
+                let node = match node {
+                    Some(node) => ctx.graph.restore_node(node, attrs).map(|_| node)?,
+                    None => ctx.graph.add_node_with(attrs),
+                };
+                ctx.positions.sync(&ctx.graph);
+                if let Some(position) = position {
+                    ctx.positions.set(node, position);
+                }
+                Self::RemoveNode { node }
+            }
+            Self::RemoveNode { node } => {
+                ctx.force.sync_positions(&mut ctx.positions);
+                let position = ctx.positions.get(node);
+                let edges: Vec<EdgeId> = ctx.graph.incident_edges(node).collect();
+                let mut restore = Vec::with_capacity(edges.len() + 1);
+                restore.push(Self::AddNode {
+                    node: Some(node),
+                    attrs: Attributes::default(),
+                    position,
+                });
+                for edge in edges {
+                    restore.push(Self::RemoveEdge { edge }.apply(ctx)?);
+                }
+                let attrs = ctx
+                    .graph
+                    .remove_node(node)
+                    .ok_or(GraphError::MissingNode(node))?;
+                if let Self::AddNode { attrs: slot, .. } = &mut restore[0] {
+                    *slot = attrs;
+                }
+                ctx.selection.retain_in(&ctx.graph);
+                Self::Batch {
+                    label: "Remove Node".into(),
+                    commands: restore,
+                }
+            }
+            Self::AddEdge {
+                edge,
+                source,
+                target,
+                attrs,
+            } => {
+                let edge = match edge {
+                    Some(edge) => ctx
+                        .graph
+                        .restore_edge(edge, source, target, attrs)
+                        .map(|_| edge)?,
+                    None => ctx.graph.add_edge_with(source, target, attrs)?,
+                };
+                Self::RemoveEdge { edge }
+            }
+            Self::RemoveEdge { edge } => {
+                let (source, target) = ctx
+                    .graph
+                    .endpoints(edge)
+                    .ok_or(GraphError::MissingEdge(edge))?;
+                let attrs = ctx.graph.remove_edge(edge).unwrap();
+                ctx.selection.retain_in(&ctx.graph);
+                Self::AddEdge {
+                    edge: Some(edge),
+                    source,
+                    target,
+                    attrs,
+                }
+            }
+            Self::SetNodeAttribute { key, values } => {
+                let mut old = Vec::with_capacity(values.len());
+                for (node, value) in values {
+                    let attrs = ctx
+                        .graph
+                        .node_mut(node)
+                        .ok_or(GraphError::MissingNode(node))?;
+                    old.push((node, set(attrs, key, value)));
+                }
+                Self::SetNodeAttribute { key, values: old }
+            }
+            Self::SetEdgeAttribute { key, values } => {
+                let mut old = Vec::with_capacity(values.len());
+                for (edge, value) in values {
+                    let attrs = ctx
+                        .graph
+                        .edge_mut(edge)
+                        .ok_or(GraphError::MissingEdge(edge))?;
+                    old.push((edge, set(attrs, key, value)));
+                }
+                Self::SetEdgeAttribute { key, values: old }
+            }
+            Self::Pin {
+                node,
+                pinned,
+                position,
+            } => {
+                if !ctx.graph.contains_node(node) {
+                    return Err(GraphError::MissingNode(node));
+                }
+                ctx.force.sync_positions(&mut ctx.positions);
+                let undo = Self::Pin {
+                    node,
+                    pinned: ctx.force.is_pinned(node),
+                    position: position.and(ctx.positions.get(node)),
+                };
+                if let Some(position) = position {
+                    ctx.force.drag(&mut ctx.positions, node, position);
+                }
+                ctx.force.set_pinned(node, pinned);
+                undo
+            }
+            Self::SetStyle(sheet) => {
+                let old = ctx.style.sheet().clone();
+                ctx.style.set_sheet(*sheet, &ctx.graph);
+                Self::SetStyle(Box::new(old))
+            }
+            Self::ReplaceGraph { graph, positions } => {
+                ctx.force.sync_positions(&mut ctx.positions);
+                let graph = std::mem::replace(&mut ctx.graph, *graph);
+                let positions = std::mem::replace(&mut ctx.positions, *positions);
+                ctx.graph_replaced();
+                Self::ReplaceGraph {
+                    graph: Box::new(graph),
+                    positions: Box::new(positions),
+                }
+            }
+            Self::Batch { label, commands } => {
+                let mut undo = Vec::with_capacity(commands.len());
+                for command in commands {
+                    undo.push(command.apply(ctx)?);
+                }
+                undo.reverse();
+                Self::Batch {
+                    label,
+                    commands: undo,
+                }
+            }
+        })
+    }
+}
+
+fn attribute_label(key: Symbol, unset: bool) -> String {
+    match unset {
+        true => format!("Unset '{}'", key.as_str()),
+        false => format!("Set '{}'", key.as_str()),
+    }
+}
+
+/// Set or unset `key` in `attrs`, returning its previous value.
+fn set(attrs: &mut Attributes, key: Symbol, value: Option<AttrValue>) -> Option<AttrValue> {
+    match value {
+        Some(value) => attrs.insert(key, value),
+        None => attrs.remove(key),
+    }
+}
+
+impl CommandStack {
+    /// Create an empty stack keeping the last 500 steps.
+    pub fn new() -> Self {
+        Self::with_limit(DEFAULT_LIMIT)
+    }
+
+    /// Create an empty stack keeping the last `limit` steps.
+    pub fn with_limit(limit: usize) -> Self {
+        Self {
+            done: Vec::new(),
+            undone: Vec::new(),
+            limit: limit.max(1),
+            sealed: false,
+        }
+    }
+
+    /// Returns `true` if there is a step to undo.
+    pub fn can_undo(&self) -> bool {
+        !self.done.is_empty()
+    }
+
+    /// Returns `true` if there is a step to redo.
+    pub fn can_redo(&self) -> bool {
+        !self.undone.is_empty()
+    }
+
+    /// Label of the step [`EngineContext::undo()`] would undo.
+    pub fn undo_label(&self) -> Option<&str> {
+        self.done.last().map(|step| step.label.as_str())
+    }
+
+    /// Label of the step [`EngineContext::redo()`] would redo.
+    pub fn redo_label(&self) -> Option<&str> {
+        self.undone.last().map(|step| step.label.as_str())
+    }
+
+    /// Labels of the steps which can be undone, the oldest first.
+    pub fn done(&self) -> impl DoubleEndedIterator<Item = &str> + ExactSizeIterator {
+        self.done.iter().map(|step| step.label.as_str())
+    }
+
+    /// Labels of the steps which can be redone, the next one first.
+    pub fn undone(&self) -> impl DoubleEndedIterator<Item = &str> + ExactSizeIterator {
+        self.undone.iter().rev().map(|step| step.label.as_str())
+    }
+
+    /// Keep the next step from merging into the last one, eg. once the user
+    /// releases a slider.
+    pub fn seal(&mut self) {
+        self.sealed = true;
+    }
+
+    /// Forget every step, eg. once the commands no longer apply to the graph.
+    pub fn clear(&mut self) {
+        self.done.clear();
+        self.undone.clear();
+    }
+
+    /// Record a newly executed command, given its `undo` command. Redoing
+    /// undone steps is no longer possible.
+    pub(crate) fn push(&mut self, label: String, merge: Option<MergeKey>, undo: Command) {
+        self.undone.clear();
+        let sealed = std::mem::take(&mut self.sealed);
+        if !sealed
+            && let Some(last) = self.done.last_mut()
+            && merge.is_some()
+            && last.merge == merge
+            && last.time.elapsed() < MERGE_WINDOW
+        {
+            // the last step's command still undoes back to before both
+            last.time = Instant::now();
+            return;
+        }
+        self.done.push(Step {
+            label,
+            command: undo,
+            merge,
+            time: Instant::now(),
+        });
+        if self.done.len() > self.limit {
+            self.done.remove(0);
+        }
+    }
+
+    /// Take the command undoing the last step, with its label.
+    pub(crate) fn pop_undo(&mut self) -> Option<(String, Command)> {
+        self.done.pop().map(|step| (step.label, step.command))
+    }
+
+    /// Take the command redoing the last undone step, with its label.
+    pub(crate) fn pop_redo(&mut self) -> Option<(String, Command)> {
+        self.undone.pop().map(|step| (step.label, step.command))
+    }
+
+    /// Record an undone step, given the command redoing it.
+    pub(crate) fn push_undone(&mut self, label: String, redo: Command) {
+        self.sealed = true;
+        self.undone.push(Step {
+            label,
+            command: redo,
+            merge: None,
+            time: Instant::now(),
+        });
+    }
+
+    /// Record a redone step, given the command undoing it again. Unlike
+    /// [`Self::push()`], steps which can be redone are kept.
+    pub(crate) fn push_redone(&mut self, label: String, undo: Command) {
+        self.sealed = true;
+        self.done.push(Step {
+            label,
+            command: undo,
+            merge: None,
+            time: Instant::now(),
+        });
+    }
+}
+
+impl Panel for HistoryPanel {
+    fn id(&self) -> &'static str {
+        "History"
+    }
+
+    fn draw(&mut self, ui: &Ui, ctx: &mut EngineContext) {
+        ui.enabled(ctx.history.can_undo(), || {
+            if ui.button(tr("Undo")) {
+                ctx.undo();
+            }
+        });
+        ui.same_line();
+        ui.enabled(ctx.history.can_redo(), || {
+            if ui.button(tr("Redo")) {
+                ctx.redo();
+            }
+        });
+        ui.same_line();
+        if ui.button(tr("Clear")) {
+            ctx.history.clear();
+        }
+        ui.separator();
+
+        // clicking a step goes back or forward to just after it
+        let done = ctx.history.done().len();
+        let mut target = None;
+        ui.child_window("##steps").build(|| {
+            if ui
+                .selectable_config(tr("(start)"))
+                .selected(done == 0)
+                .build()
+            {
+                target = Some(0);
+            }
+            for (i, label) in ctx.history.done().enumerate() {
+                let _id = ui.push_id_usize(i);
+                if ui.selectable_config(label).selected(i + 1 == done).build() {
+                    target = Some(i + 1);
+                }
+            }
+            let _color = ui.push_style_color(StyleColor::Text, UNDONE_COLOR);
+            for (i, label) in ctx.history.undone().enumerate() {
+                let _id = ui.push_id_usize(done + i);
+                if ui.selectable(label) {
+                    target = Some(done + i + 1);
+                }
+            }
+        });
+
+        if let Some(target) = target {
+            while ctx.history.done().len() > target && ctx.undo() {}
+            while ctx.history.done().len() < target && ctx.redo() {}
+        }
+    }
+}
+
+impl std::default::Default for CommandStack {
+    fn default() -> Self {
+        Self::new()
+    }
+}
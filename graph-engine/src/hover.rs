@@ -0,0 +1,181 @@
+//! # Hover Cards
+//!
+//! Resting the cursor on a node or edge shows a card next to it with its label,
+//! degree or endpoints, community, and a few attributes, so they can be read
+//! without selecting the element and opening the Inspector.
+//!
+//! - The app [picks](crate::subsystems::scene::Picker) the element under the
+//!   cursor each frame and reports it with [`Hover::update()`].
+//! - [`draw()`] shows its card once it was hovered for the style sheet's
+//!   [delay](HoverCards::delay).
+//! - Whether cards show, their delay, and which attributes they list are part
+//!   of the [`StyleSheet`](crate::style::StyleSheet), edited in the Style panel.
+//!   Without a choice, cards list the first [`MAX_ATTRIBUTES`] attributes.
+//!
+//! ```ignore
+//! // in `GraphApp::update()`
+//! let hit = picker.pick(camera.screen_to_world(cursor), radius, tolerance);
+//! ctx.hover.update(hit);
+//! // in `GraphApp::draw()`
+//! hover::draw(ui, &ctx);
+//! ```
+
+use std::time::{Duration, Instant};
+
+use crate::algo::COMMUNITY_ATTR;
+use crate::context::EngineContext;
+use crate::graph::{AttrValue, Attributes, EdgeId, NodeId};
+use crate::i18n::tr;
+use crate::imgui::{TableFlags, Ui};
+use crate::style::{DEGREE_ATTR, HoverCards};
+use crate::subsystems::scene::Hit;
+
+/// Attributes listed on a card when the style sheet chooses none.
+pub const MAX_ATTRIBUTES: usize = 8;
+/// Longest value shown on a card, in characters. Longer ones are cut short.
+const MAX_VALUE_CHARS: usize = 60;
+
+/// The element under the cursor, see the [module documentation](self).
+#[derive(Clone, Debug, Default)]
+pub struct Hover {
+    /// The element under the cursor, and since when.
+    hit: Option<(Hit, Instant)>,
+}
+
+impl Hover {
+    /// Report the element under the cursor, if any, restarting the delay when
+    /// it differs from the last one.
+    pub fn update(&mut self, hit: Option<Hit>) {
+        if self.hovered() != hit {
+            self.hit = hit.map(|hit| (hit, Instant::now()));
+        }
+    }
+
+    /// The element under the cursor, however long it was hovered.
+    pub fn hovered(&self) -> Option<Hit> {
+        self.hit.map(|(hit, _)| hit)
+    }
+
+    /// The element whose card shows with `cards`, hovered for at least their
+    /// delay.
+    pub fn shown(&self, cards: &HoverCards) -> Option<Hit> {
+        let delay = Duration::from_secs_f32(cards.delay.max(0.0));
+        self.hit
+            .filter(|(_, since)| cards.enabled && since.elapsed() >= delay)
+            .map(|(hit, _)| hit)
+    }
+}
+
+/// Show the card of the hovered element of `ctx`, if it is due, as a tooltip
+/// at the cursor.
+pub fn draw(ui: &Ui, ctx: &EngineContext) {
+    let cards = &ctx.style.sheet().hover;
+    match ctx.hover.shown(cards) {
+        Some(Hit::Node(node)) if ctx.graph.contains_node(node) => {
+            ui.tooltip(|| node_card(ui, ctx, node));
+        }
+        Some(Hit::Edge(edge)) if ctx.graph.contains_edge(edge) => {
+            ui.tooltip(|| edge_card(ui, ctx, edge));
+        }
+        _ => {}
+    }
+}
+
+fn node_card(ui: &Ui, ctx: &EngineContext, node: NodeId) {
+    let graph = &ctx.graph;
+    let Some(attrs) = graph.node(node) else {
+        return;
+    };
+    ui.text(node_title(ctx, node));
+    ui.separator();
+    let mut rows = vec![(DEGREE_ATTR.to_string(), graph.degree(node).to_string())];
+    if let Some(community) = attrs.get(COMMUNITY_ATTR) {
+        rows.push((COMMUNITY_ATTR.to_string(), value_text(community)));
+    }
+    let label = &ctx.style.sheet().node_label.attribute;
+    let skip = [label.as_str(), DEGREE_ATTR, COMMUNITY_ATTR];
+    rows.extend(attribute_rows(
+        attrs,
+        &ctx.style.sheet().hover.node_attributes,
+        &skip,
+    ));
+    rows_ui(ui, rows);
+}
+
+fn edge_card(ui: &Ui, ctx: &EngineContext, edge: EdgeId) {
+    let graph = &ctx.graph;
+    let (Some(attrs), Some((source, target))) = (graph.edge(edge), graph.endpoints(edge)) else {
+        return;
+    };
+    let label = &ctx.style.sheet().edge_label.attribute;
+    match attrs.get(label.as_str()) {
+        Some(value) => ui.text(value_text(value)),
+        None => ui.text(tr("Edge {id}").replace("{id}", &edge.to_string())),
+    }
+    ui.text_disabled(format!(
+        "{} -> {}",
+        node_title(ctx, source),
+        node_title(ctx, target)
+    ));
+    ui.separator();
+    let rows = attribute_rows(
+        attrs,
+        &ctx.style.sheet().hover.edge_attributes,
+        &[label.as_str()],
+    );
+    rows_ui(ui, rows);
+}
+
+/// The label of `node`, or its id if it has none.
+fn node_title(ctx: &EngineContext, node: NodeId) -> String {
+    let label = &ctx.style.sheet().node_label.attribute;
+    match ctx
+        .graph
+        .node(node)
+        .and_then(|attrs| attrs.get(label.as_str()))
+    {
+        Some(value) => value_text(value),
+        None => format!("Node {node}"),
+    }
+}
+
+/// Names and values of the `chosen` attributes of `attrs` it has, or of its
+/// first [`MAX_ATTRIBUTES`] if none are chosen, but those in `skip`, shown
+/// elsewhere on the card.
+fn attribute_rows(attrs: &Attributes, chosen: &[String], skip: &[&str]) -> Vec<(String, String)> {
+    if !chosen.is_empty() {
+        return chosen
+            .iter()
+            .filter(|name| !skip.contains(&name.as_str()))
+            .filter_map(|name| Some((name.clone(), value_text(attrs.get(name.as_str())?))))
+            .collect();
+    }
+    attrs
+        .iter()
+        .filter(|(key, _)| !skip.contains(&key.as_str()))
+        .take(MAX_ATTRIBUTES)
+        .map(|(key, value)| (key.as_str().to_string(), value_text(value)))
+        .collect()
+}
+
+/// `value` as shown on a card, cut short if long.
+fn value_text(value: &AttrValue) -> String {
+    let text = value.to_string();
+    match text.char_indices().nth(MAX_VALUE_CHARS) {
+        Some((end, _)) => format!("{}...", &text[..end]),
+        None => text,
+    }
+}
+
+fn rows_ui(ui: &Ui, rows: Vec<(String, String)>) {
+    let Some(_table) = ui.begin_table_with_flags("##card", 2, TableFlags::SIZING_FIXED_FIT) else {
+        return;
+    };
+    for (name, value) in rows {
+        ui.table_next_row();
+        ui.table_next_column();
+        ui.text_disabled(name);
+        ui.table_next_column();
+        ui.text(value);
+    }
+}
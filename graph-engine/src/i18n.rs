@@ -0,0 +1,312 @@
+//! # Localization
+//!
+//! User-facing strings are written in English and passed through [`tr()`],
+//! which looks them up in the active [`Locale`]'s catalog and returns them
+//! unchanged if it has no translation, so English needs no catalog and partial
+//! catalogs still work.
+//!
+//! Catalogs are TOML files in [`LOCALES_DIR`], named after their language code,
+//! eg. `locales/ru.toml`:
+//! ```toml
+//! name = "Русский"
+//! glyphs = "cyrillic"
+//!
+//! [strings]
+//! "File" = "Файл"
+//! "Open Recent" = "Недавние файлы"
+//! ```
+//!
+//! Text with parts filled in at runtime is looked up with `{name}` in their
+//! place, and filled in once translated, so translations can move them:
+//! `tr("Save the changes to '{title}'?").replace("{title}", title)`.
+//!
+//! Menus, panels, dialogs and the errors of the HTTP, Neo4j, SPARQL and store
+//! connectors are translated. Messages written to the log, and the errors of
+//! the file format readers, are still English only; translating them is left
+//! for a follow-up.
+//!
+//! - [`discover()`] finds the catalogs, and activates the one chosen last, as
+//!   kept in [`LOCALE_FILE`].
+//! - [`Locales::select()`] switches the language at runtime; the View menu
+//!   lists the languages found.
+//! - imgui's default font only has Latin glyphs. A locale naming other
+//!   [`Glyphs`] has a font merged in by [`Locales::update_font()`], between
+//!   frames: its own `font` file, or the first common system font found.
+//!
+//! ```ignore
+//! i18n::discover(&mut ctx, ui_state.dir().join(LOCALES_DIR));
+//! if let Some(_menu) = ui.begin_menu(tr("File")) {}
+//! ```
+
+use parking_lot::RwLock;
+use serde::Deserialize;
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::context::EngineContext;
+use crate::imgui::FontGlyphRanges;
+use crate::subsystems::ui::GraphUi;
+
+/// Name of the directory catalogs are discovered in, in the application's
+/// state directory.
+pub const LOCALES_DIR: &str = "locales";
+/// Name of the file keeping the code of the chosen locale, in the locales
+/// directory.
+pub const LOCALE_FILE: &str = "locale.txt";
+/// Extension of the catalog files.
+pub const LOCALE_EXTENSION: &str = "toml";
+
+/// The locale [`tr()`] translates to, if not English.
+static ACTIVE: RwLock<Option<Arc<Locale>>> = RwLock::new(None);
+
+/// A language's catalog, see the [module documentation](self).
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Locale {
+    /// The file name without its extension, eg. `ru`.
+    #[serde(skip)]
+    pub code: String,
+    /// Name of the language, in that language.
+    pub name: String,
+    /// Glyphs the translations need beyond Latin ones.
+    pub glyphs: Glyphs,
+    /// A TTF or OTF file having the glyphs, instead of a system font.
+    pub font: Option<PathBuf>,
+    /// Translations, by their English text.
+    pub strings: HashMap<String, String>,
+}
+
+/// Ranges of characters a [`Locale`] needs a font for.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Glyphs {
+    /// Latin ones only, in imgui's default font.
+    #[default]
+    Latin,
+    Cyrillic,
+    Chinese,
+    Japanese,
+    Korean,
+    Thai,
+    Vietnamese,
+}
+
+/// The catalogs of a directory, and the one chosen.
+#[derive(Default)]
+pub struct Locales {
+    dir: Option<PathBuf>,
+    /// Sorted by code.
+    list: Vec<Locale>,
+    /// Code of the active locale, [None] for English.
+    active: Option<String>,
+    /// Set when the active locale changed, until its font is loaded.
+    font_changed: bool,
+}
+
+/// `text` in the active locale, or `text` itself if it has no translation.
+///
+/// An imgui id after `##` is kept as it is, and not part of the text looked
+/// up, eg. `tr("Name##endpoint")` translates `Name`.
+pub fn tr(text: &str) -> Cow<'_, str> {
+    let (label, id) = text.find("##").map_or((text, ""), |at| text.split_at(at));
+    match ACTIVE
+        .read()
+        .as_ref()
+        .and_then(|locale| locale.strings.get(label))
+    {
+        Some(translated) => Cow::Owned(format!("{translated}{id}")),
+        None => Cow::Borrowed(text),
+    }
+}
+
+/// Find the catalogs in `dir`, creating it if missing, and activate the locale
+/// chosen last.
+pub fn discover(ctx: &mut EngineContext, dir: impl Into<PathBuf>) {
+    let dir = dir.into();
+    if let Err(e) = std::fs::create_dir_all(&dir) {
+        ctx.log.warn(format!(
+            "Failed to create locales directory '{}': {e}",
+            dir.display()
+        ));
+    }
+    let chosen = std::fs::read_to_string(dir.join(LOCALE_FILE)).ok();
+    ctx.locales.dir = Some(dir);
+    for error in ctx.locales.reload() {
+        ctx.log.warn(error);
+    }
+    if let Some(code) = chosen.as_deref().map(str::trim)
+        && !code.is_empty()
+        && ctx.locales.activate(Some(code)).is_err()
+    {
+        ctx.log
+            .warn(format!("Language '{code}' is no longer available"));
+    }
+}
+
+impl Glyphs {
+    /// The ranges to load from a font for these glyphs.
+    pub fn ranges(self) -> FontGlyphRanges {
+        match self {
+            Self::Latin => FontGlyphRanges::default(),
+            Self::Cyrillic => FontGlyphRanges::cyrillic(),
+            Self::Chinese => FontGlyphRanges::chinese_simplified_common(),
+            Self::Japanese => FontGlyphRanges::japanese(),
+            Self::Korean => FontGlyphRanges::korean(),
+            Self::Thai => FontGlyphRanges::thai(),
+            Self::Vietnamese => FontGlyphRanges::vietnamese(),
+        }
+    }
+
+    /// Common system fonts having these glyphs, in order of preference.
+    pub fn system_fonts(self) -> &'static [&'static str] {
+        match self {
+            Self::Latin => &[],
+            Self::Cyrillic | Self::Vietnamese => &[
+                "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+                "/usr/share/fonts/TTF/DejaVuSans.ttf",
+                "/usr/share/fonts/dejavu-sans-fonts/DejaVuSans.ttf",
+                "/System/Library/Fonts/Supplemental/Arial.ttf",
+                "C:\\Windows\\Fonts\\arial.ttf",
+            ],
+            Self::Chinese | Self::Japanese | Self::Korean => &[
+                "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc",
+                "/usr/share/fonts/noto-cjk/NotoSansCJK-Regular.ttc",
+                "/usr/share/fonts/google-noto-cjk/NotoSansCJK-Regular.ttc",
+                "/System/Library/Fonts/Supplemental/Arial Unicode.ttf",
+                "C:\\Windows\\Fonts\\msyh.ttc",
+                "C:\\Windows\\Fonts\\msgothic.ttc",
+                "C:\\Windows\\Fonts\\malgun.ttf",
+            ],
+            Self::Thai => &[
+                "/usr/share/fonts/truetype/noto/NotoSansThai-Regular.ttf",
+                "/usr/share/fonts/noto/NotoSansThai-Regular.ttf",
+                "/System/Library/Fonts/Supplemental/Thonburi.ttc",
+                "C:\\Windows\\Fonts\\tahoma.ttf",
+            ],
+        }
+    }
+}
+
+impl Locales {
+    /// Every catalog found, by code.
+    pub fn list(&self) -> &[Locale] {
+        &self.list
+    }
+
+    /// Code of the active locale, [None] for English.
+    pub fn active(&self) -> Option<&str> {
+        self.active.as_deref()
+    }
+
+    /// Read the catalogs of the directory again, eg. after editing one, keeping
+    /// the active locale if it still exists and falling back to English if not.
+    ///
+    /// Returns why catalogs could not be read.
+    pub fn reload(&mut self) -> Vec<String> {
+        let Some(dir) = &self.dir else {
+            return Vec::new();
+        };
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) => return vec![format!("Failed to read '{}': {e}", dir.display())],
+        };
+        let mut errors = Vec::new();
+        self.list.clear();
+        for path in entries.filter_map(|entry| Some(entry.ok()?.path())) {
+            if path.extension().is_none_or(|e| e != LOCALE_EXTENSION) {
+                continue;
+            }
+            match load(&path) {
+                Ok(locale) => self.list.push(locale),
+                Err(e) => errors.push(e),
+            }
+        }
+        self.list.sort_by(|a, b| a.code.cmp(&b.code));
+        let active = self.active.take();
+        if self.activate(active.as_deref()).is_err() {
+            let _ = self.activate(None);
+        }
+        errors
+    }
+
+    /// Switch to the locale of `code`, or to English, remembering the choice
+    /// for the next run. Fails if there is no such locale.
+    pub fn select(&mut self, code: Option<&str>) -> Result<(), String> {
+        self.activate(code)?;
+        let Some(dir) = &self.dir else {
+            return Ok(());
+        };
+        let path = dir.join(LOCALE_FILE);
+        std::fs::write(&path, code.unwrap_or_default())
+            .map_err(|e| format!("Failed to write '{}': {e}", path.display()))
+    }
+
+    /// Merge the font the active locale needs into the ui's, if it changed
+    /// since the last call. Must be called between frames.
+    ///
+    /// Returns why no font could be loaded, if the locale needs one.
+    pub fn update_font(&mut self, ui: &mut GraphUi) -> Result<(), String> {
+        if !std::mem::take(&mut self.font_changed) {
+            return Ok(());
+        }
+        let Some(locale) = ACTIVE.read().clone() else {
+            return ui.set_font(None).map_err(|e| e.to_string());
+        };
+        if locale.glyphs == Glyphs::Latin {
+            return ui.set_font(None).map_err(|e| e.to_string());
+        }
+        let candidates = locale
+            .font
+            .iter()
+            .map(|path| path.as_path())
+            .chain(locale.glyphs.system_fonts().iter().map(Path::new));
+        for path in candidates {
+            if let Ok(data) = std::fs::read(path) {
+                return ui
+                    .set_font(Some((&data, locale.glyphs.ranges())))
+                    .map_err(|e| e.to_string());
+            }
+        }
+        ui.set_font(None).map_err(|e| e.to_string())?;
+        Err(format!(
+            "No font found for {}, set one as 'font' in its catalog",
+            locale.name
+        ))
+    }
+
+    /// Make the locale of `code` the one [`tr()`] translates to, or English.
+    fn activate(&mut self, code: Option<&str>) -> Result<(), String> {
+        let locale = match code {
+            Some(code) => Some(
+                self.list
+                    .iter()
+                    .find(|locale| locale.code == code)
+                    .ok_or_else(|| format!("No language '{code}'"))?,
+            ),
+            None => None,
+        };
+        *ACTIVE.write() = locale.cloned().map(Arc::new);
+        self.active = code.map(str::to_string);
+        self.font_changed = true;
+        Ok(())
+    }
+}
+
+/// Read the catalog at `path`.
+fn load(path: &Path) -> Result<Locale, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read language '{}': {e}", path.display()))?;
+    let mut locale: Locale = toml::from_str(&text)
+        .map_err(|e| format!("Failed to read language '{}': {e}", path.display()))?;
+    locale.code = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    if locale.name.is_empty() {
+        locale.name = locale.code.clone();
+    }
+    Ok(locale)
+}
@@ -0,0 +1,310 @@
+//! # Attribute Inspector
+//!
+//! The [`InspectorPanel`] shows the attributes of the selected nodes and edges,
+//! each with a widget matching its type: a checkbox for booleans, a drag for
+//! numbers, a color picker for `#rrggbb` strings, and a text field otherwise.
+//!
+//! With several elements selected, attributes are listed if any of them has
+//! them, and edits apply to all of them. Edits go through
+//! [`EngineContext::execute()`], so they can be undone.
+
+use crate::context::EngineContext;
+use crate::graph::{AttrValue, Attributes, EdgeId, Graph, NodeId, Symbol};
+use crate::history::Command;
+use crate::i18n::tr;
+use crate::imgui::{Drag, TableFlags, TreeNodeFlags, Ui};
+use crate::subsystems::panels::Panel;
+
+/// Edits the attributes of the selected elements.
+pub struct InspectorPanel {
+    /// Name of the attribute being added.
+    name: String,
+    /// Type of the attribute being added.
+    kind: ValueKind,
+}
+
+/// The type of a new attribute, see [`InspectorPanel`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum ValueKind {
+    Bool,
+    Int,
+    Float,
+    String,
+    Color,
+}
+
+/// Elements of one kind whose attributes are edited together.
+enum Elements {
+    Nodes(Vec<NodeId>),
+    Edges(Vec<EdgeId>),
+}
+
+/// An attribute of some of the edited elements.
+struct Row {
+    key: Symbol,
+    /// The value of the first element having it.
+    value: AttrValue,
+    /// Whether some elements lack the attribute or have another value.
+    mixed: bool,
+}
+
+impl InspectorPanel {
+    /// Create a panel adding string attributes by default.
+    pub fn new() -> Self {
+        Self {
+            name: String::new(),
+            kind: ValueKind::String,
+        }
+    }
+
+    /// Attribute table and add row for `elements`.
+    fn elements_ui(&mut self, ui: &Ui, ctx: &mut EngineContext, elements: &Elements) {
+        let rows = elements.rows(&ctx.graph);
+        if rows.is_empty() {
+            ui.text_disabled(tr("No attributes"));
+        } else if let Some(table) = ui.begin_table_with_flags(
+            "##attributes",
+            3,
+            TableFlags::ROW_BG | TableFlags::BORDERS_INNER_H | TableFlags::SIZING_STRETCH_PROP,
+        ) {
+            ui.table_setup_column(tr("Name"));
+            ui.table_setup_column(tr("Value"));
+            ui.table_setup_column("##remove");
+            ui.table_headers_row();
+            for row in rows {
+                let _id = ui.push_id(row.key.as_str());
+                ui.table_next_row();
+
+                ui.table_next_column();
+                ui.text(row.key.as_str());
+                if row.mixed {
+                    ui.same_line();
+                    ui.text_disabled(tr("(mixed)"));
+                    if ui.is_item_hovered() {
+                        ui.tooltip_text(tr("Values differ between the selected elements"));
+                    }
+                }
+
+                ui.table_next_column();
+                ui.set_next_item_width(-1.0);
+                if let Some(value) = value_ui(ui, &row.value) {
+                    ctx.execute(elements.command(row.key, Some(value)));
+                }
+
+                ui.table_next_column();
+                if ui.small_button(tr("Remove")) {
+                    ctx.execute(elements.command(row.key, None));
+                }
+            }
+            table.end();
+        }
+
+        ui.separator();
+        ui.set_next_item_width(ui.content_region_avail()[0] * 0.4);
+        ui.input_text("##name", &mut self.name)
+            .hint(tr("New attribute"))
+            .build();
+        ui.same_line();
+        ui.set_next_item_width(ui.content_region_avail()[0] * 0.5);
+        if let Some(_combo) = ui.begin_combo("##kind", tr(self.kind.label())) {
+            for kind in ValueKind::ALL {
+                if ui
+                    .selectable_config(tr(kind.label()))
+                    .selected(self.kind == kind)
+                    .build()
+                {
+                    self.kind = kind;
+                }
+            }
+        }
+        ui.same_line();
+        let name = self.name.trim().to_owned();
+        ui.enabled(!name.is_empty(), || {
+            if ui.button(tr("Add")) {
+                ctx.execute(elements.command(Symbol::new(&name), Some(self.kind.value())));
+                self.name.clear();
+            }
+        });
+    }
+}
+
+impl ValueKind {
+    const ALL: [ValueKind; 5] = [
+        Self::Bool,
+        Self::Int,
+        Self::Float,
+        Self::String,
+        Self::Color,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Bool => "Boolean",
+            Self::Int => "Integer",
+            Self::Float => "Number",
+            Self::String => "Text",
+            Self::Color => "Color",
+        }
+    }
+
+    /// Initial value of a new attribute of this type.
+    fn value(self) -> AttrValue {
+        match self {
+            Self::Bool => AttrValue::Bool(false),
+            Self::Int => AttrValue::Int(0),
+            Self::Float => AttrValue::Float(0.0),
+            Self::String => AttrValue::String(String::new()),
+            Self::Color => AttrValue::String("#ffffff".into()),
+        }
+    }
+}
+
+impl Elements {
+    /// The attributes of the elements which still exist in `graph`.
+    fn attrs<'a>(&self, graph: &'a Graph) -> Vec<&'a Attributes> {
+        match self {
+            Self::Nodes(nodes) => nodes.iter().filter_map(|&n| graph.node(n)).collect(),
+            Self::Edges(edges) => edges.iter().filter_map(|&e| graph.edge(e)).collect(),
+        }
+    }
+
+    /// Every attribute set on any of the elements, sorted by name.
+    fn rows(&self, graph: &Graph) -> Vec<Row> {
+        let attrs = self.attrs(graph);
+        let mut rows: Vec<Row> = Vec::new();
+        for element in &attrs {
+            for (key, value) in element.iter() {
+                if rows.iter().any(|row| row.key == key) {
+                    continue;
+                }
+                let mixed = attrs.iter().any(|other| other.get(key) != Some(value));
+                rows.push(Row {
+                    key,
+                    value: value.clone(),
+                    mixed,
+                });
+            }
+        }
+        rows.sort_by(|a, b| a.key.as_str().cmp(b.key.as_str()));
+        rows
+    }
+
+    /// A command setting `key` to `value` on every element, or unsetting it.
+    fn command(&self, key: Symbol, value: Option<AttrValue>) -> Command {
+        match self {
+            Self::Nodes(nodes) => Command::SetNodeAttribute {
+                key,
+                values: nodes.iter().map(|&n| (n, value.clone())).collect(),
+            },
+            Self::Edges(edges) => Command::SetEdgeAttribute {
+                key,
+                values: edges.iter().map(|&e| (e, value.clone())).collect(),
+            },
+        }
+    }
+}
+
+/// Widget editing `value`, returning the new value once changed.
+fn value_ui(ui: &Ui, value: &AttrValue) -> Option<AttrValue> {
+    match value {
+        AttrValue::Bool(b) => {
+            let mut b = *b;
+            ui.checkbox("##value", &mut b).then_some(AttrValue::Bool(b))
+        }
+        AttrValue::Int(i) => {
+            let mut i = *i;
+            Drag::new("##value")
+                .build(ui, &mut i)
+                .then_some(AttrValue::Int(i))
+        }
+        AttrValue::Float(f) => {
+            let mut f = *f;
+            let speed = (f.abs() * 0.01).max(0.001) as f32;
+            Drag::new("##value")
+                .speed(speed)
+                .build(ui, &mut f)
+                .then_some(AttrValue::Float(f))
+        }
+        AttrValue::String(s) => match parse_color(s) {
+            Some(mut color) => ui
+                .color_edit3("##value", &mut color)
+                .then(|| AttrValue::String(format_color(color))),
+            None => {
+                let mut s = s.clone();
+                ui.input_text("##value", &mut s)
+                    .build()
+                    .then_some(AttrValue::String(s))
+            }
+        },
+    }
+}
+
+/// Parse a `#rrggbb` color into normalized channels.
+fn parse_color(s: &str) -> Option<[f32; 3]> {
+    let hex = s.strip_prefix('#').filter(|hex| hex.len() == 6)?;
+    let channel = |i: usize| {
+        let value = u8::from_str_radix(hex.get(i..i + 2)?, 16).ok()?;
+        Some(value as f32 / 255.0)
+    };
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
+/// Format normalized channels as a `#rrggbb` color.
+fn format_color(color: [f32; 3]) -> String {
+    let [r, g, b] = color.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+impl Panel for InspectorPanel {
+    fn id(&self) -> &'static str {
+        "Inspector"
+    }
+
+    fn draw(&mut self, ui: &Ui, ctx: &mut EngineContext) {
+        let graph = &ctx.graph;
+        let nodes: Vec<NodeId> = ctx
+            .selection
+            .nodes()
+            .filter(|&n| graph.contains_node(n))
+            .collect();
+        let edges: Vec<EdgeId> = ctx
+            .selection
+            .edges()
+            .filter(|&e| graph.contains_edge(e))
+            .collect();
+        if nodes.is_empty() && edges.is_empty() {
+            ui.text_disabled(tr("Select nodes or edges to inspect them"));
+            return;
+        }
+
+        if !nodes.is_empty() {
+            let title = match nodes.as_slice() {
+                &[node] => format!("Node {node}, degree {}###nodes", graph.degree(node)),
+                nodes => format!("{} Nodes###nodes", nodes.len()),
+            };
+            if ui.collapsing_header(&title, TreeNodeFlags::DEFAULT_OPEN) {
+                let _id = ui.push_id("nodes");
+                self.elements_ui(ui, ctx, &Elements::Nodes(nodes));
+            }
+        }
+        if !edges.is_empty() {
+            let title = match edges.as_slice() {
+                &[edge] => match ctx.graph.endpoints(edge) {
+                    Some((s, t)) => format!("Edge {edge}, {s} to {t}###edges"),
+                    None => format!("Edge {edge}###edges"),
+                },
+                edges => format!("{} Edges###edges", edges.len()),
+            };
+            if ui.collapsing_header(&title, TreeNodeFlags::DEFAULT_OPEN) {
+                let _id = ui.push_id("edges");
+                self.elements_ui(ui, ctx, &Elements::Edges(edges));
+            }
+        }
+    }
+}
+
+impl std::default::Default for InspectorPanel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
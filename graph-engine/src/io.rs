@@ -0,0 +1,325 @@
+//! # Graph File Formats
+//!
+//! Readers and writers converting between files and the [`Graph`] model, one
+//! module per format:
+//! - [`graphml`], for GraphML (`.graphml`)
+//! - [`dot`], for Graphviz DOT (`.dot`, `.gv`)
+//! - [`json`], for node-link JSON as used by d3 and networkx (`.json`)
+//! - [`gexf`], for Gephi's GEXF (`.gexf`), read only
+//! - [`csv`], for CSV/TSV edge lists (`.csv`, `.tsv`), read only
+//! - [`rdf`], for Turtle and N-Triples (`.ttl`, `.nt`), read only
+//! - `columnar`, for Arrow and Parquet edge lists (`.arrow`, `.parquet`, ...),
+//!   read only, with the `arrow` feature, and only through a
+//!   `ColumnarLoader` since their columns need to be chosen
+//!
+//! [`load()`] opens any of them, picking the format by extension or, failing
+//! that, by looking at the file's first bytes. [`save()`] writes any writable
+//! one, picking the format by extension.
+//!
+//! [`loader`] reads a file on a worker thread instead, handing its graph over
+//! progressively, and [`live`] applies changes streamed over a socket.
+//!
+//! Readers keep each element's id from the file in its [`ID_ATTR`] attribute,
+//! and writers reuse it, so files survive a round trip with their ids intact.
+
+#[cfg(feature = "arrow")]
+pub mod columnar;
+pub mod csv;
+pub mod dot;
+pub mod gexf;
+pub mod graphml;
+#[cfg(any(feature = "neo4j", feature = "sparql"))]
+pub mod http;
+pub mod json;
+pub mod live;
+pub mod loader;
+#[cfg(feature = "neo4j")]
+pub mod neo4j;
+pub mod rdf;
+#[cfg(feature = "sparql")]
+pub mod sparql;
+pub mod xml;
+
+use std::io::Read;
+use std::path::Path;
+
+use crate::graph::{Graph, NodeId};
+
+/// Attribute holding the id a node or edge had in the file it was read from.
+pub const ID_ATTR: &str = "id";
+/// Attribute holding the id of the node a nested node belongs to, see [`crate::group`].
+pub const PARENT_ATTR: &str = "parent";
+/// Attribute recording an edge's direction when it differs from the graph's.
+pub const DIRECTED_ATTR: &str = "directed";
+
+/// Number of bytes looked at by [`Format::sniff()`].
+const SNIFF_LEN: usize = 1024;
+
+/// A supported graph file format.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Format {
+    GraphMl,
+    Dot,
+    /// Node-link JSON, see [`json`].
+    Json,
+    Gexf,
+    /// A delimiter-separated edge list, see [`csv::CsvOptions`].
+    Csv,
+    /// Turtle or N-Triples, see [`rdf`].
+    Rdf,
+}
+
+/// Errors which can occur while loading a graph file.
+#[derive(Debug)]
+pub enum LoadError {
+    Io(std::io::Error),
+    /// Neither the extension nor the contents matched a known format.
+    UnknownFormat,
+    GraphMl(graphml::GraphMlError),
+    Dot(dot::DotError),
+    Json(json::JsonError),
+    Gexf(gexf::GexfError),
+    Csv(csv::CsvError),
+    Rdf(rdf::RdfError),
+    /// A [`GraphLoader`](loader::GraphLoader) failed, with its message.
+    Loader(String),
+}
+
+/// Read a graph file, detecting its format with [`Format::detect()`].
+///
+/// CSV files are read with options [sniffed](csv::CsvOptions::sniff) from their
+/// first lines; use [`csv::load()`] to choose the columns explicitly.
+pub fn load(path: impl AsRef<Path>) -> Result<Graph, LoadError> {
+    let path = path.as_ref();
+    let format = Format::detect(path)?.ok_or(LoadError::UnknownFormat)?;
+    load_as(path, format)
+}
+
+/// Read a graph file in the given format.
+pub fn load_as(path: impl AsRef<Path>, format: Format) -> Result<Graph, LoadError> {
+    let path = path.as_ref();
+    Ok(match format {
+        Format::GraphMl => graphml::load(path)?,
+        Format::Dot => dot::load(path)?,
+        Format::Json => json::load(path)?,
+        Format::Gexf => gexf::load(path)?,
+        Format::Csv => csv::load(path, &csv::CsvOptions::sniff(path)?)?,
+        Format::Rdf => rdf::load(path)?,
+    })
+}
+
+/// Write a graph file in the format matching `path`'s extension.
+///
+/// Errors with [`std::io::ErrorKind::Unsupported`] if the extension is unknown
+/// or its format [cannot be written](Format::is_writable).
+pub fn save(graph: &Graph, path: impl AsRef<Path>) -> std::io::Result<()> {
+    let path = path.as_ref();
+    let format = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .and_then(Format::from_extension);
+    match format {
+        Some(Format::GraphMl) => graphml::save(graph, path),
+        Some(Format::Dot) => dot::save(graph, path),
+        Some(Format::Json) => json::save(graph, path),
+        Some(format) => Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            format!("{} files cannot be written", format.name()),
+        )),
+        None => Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "Unsupported file type",
+        )),
+    }
+}
+
+impl Format {
+    /// Every supported format.
+    pub const ALL: [Format; 6] = [
+        Self::GraphMl,
+        Self::Dot,
+        Self::Json,
+        Self::Gexf,
+        Self::Csv,
+        Self::Rdf,
+    ];
+
+    /// The format's name, eg. for a file dialog.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::GraphMl => "GraphML",
+            Self::Dot => "DOT",
+            Self::Json => "Node-link JSON",
+            Self::Gexf => "GEXF",
+            Self::Csv => "CSV edge list",
+            Self::Rdf => "RDF (Turtle, N-Triples)",
+        }
+    }
+
+    /// File extensions of the format, lowercase and without the dot.
+    pub fn extensions(self) -> &'static [&'static str] {
+        match self {
+            Self::GraphMl => &["graphml"],
+            Self::Dot => &["dot", "gv"],
+            Self::Json => &["json"],
+            Self::Gexf => &["gexf"],
+            Self::Csv => &["csv", "tsv"],
+            Self::Rdf => &["ttl", "nt"],
+        }
+    }
+
+    /// Returns `true` if [`save()`] can write the format.
+    pub fn is_writable(self) -> bool {
+        matches!(self, Self::GraphMl | Self::Dot | Self::Json)
+    }
+
+    /// The format with `extension`, compared case-insensitively.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        let extension = extension.to_ascii_lowercase();
+        Self::ALL
+            .into_iter()
+            .find(|f| f.extensions().contains(&extension.as_str()))
+    }
+
+    /// Guess the format from the first bytes of a file.
+    pub fn sniff(head: &[u8]) -> Option<Self> {
+        let head = String::from_utf8_lossy(head);
+        let text = head.trim_start_matches('\u{feff}').trim_start();
+        if text.starts_with('<') {
+            return if text.contains("<gexf") {
+                Some(Self::Gexf)
+            } else if text.contains("<graphml") {
+                Some(Self::GraphMl)
+            } else if is_iri_start(text) {
+                // an N-Triples subject
+                Some(Self::Rdf)
+            } else {
+                None
+            };
+        }
+
+        if text.starts_with('{') {
+            return Some(Self::Json);
+        }
+
+        // skip comments before the graph keyword
+        let first = text.lines().map(str::trim).find(|l| {
+            !l.is_empty() && !l.starts_with("//") && !l.starts_with('#') && !l.starts_with("/*")
+        })?;
+        let keyword = first
+            .split(|c: char| !c.is_ascii_alphabetic())
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        if matches!(keyword.as_str(), "graph" | "digraph" | "strict") {
+            return Some(Self::Dot);
+        }
+        if first.starts_with("@prefix")
+            || first.starts_with("@base")
+            || first.starts_with("_:")
+            || is_iri_start(first)
+            || matches!(keyword.as_str(), "prefix" | "base")
+                && first[keyword.len()..].starts_with(char::is_whitespace)
+        {
+            return Some(Self::Rdf);
+        }
+        first.contains([',', '\t', ';']).then_some(Self::Csv)
+    }
+
+    /// Detect the format of the file at `path`, by extension first, then by
+    /// [sniffing](Self::sniff) its contents.
+    ///
+    /// Errors if the file cannot be read.
+    pub fn detect(path: &Path) -> Result<Option<Self>, std::io::Error> {
+        if let Some(format) = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .and_then(Self::from_extension)
+        {
+            return Ok(Some(format));
+        }
+
+        let mut head = Vec::with_capacity(SNIFF_LEN);
+        std::fs::File::open(path)?
+            .take(SNIFF_LEN as u64)
+            .read_to_end(&mut head)?;
+        Ok(Self::sniff(&head))
+    }
+}
+
+/// Returns `true` if `text` starts with an absolute IRI between angle brackets.
+fn is_iri_start(text: &str) -> bool {
+    text.split_whitespace()
+        .next()
+        .and_then(|token| token.strip_prefix('<')?.strip_suffix('>'))
+        .is_some_and(|iri| iri.contains(':') && !iri.contains(['<', '"', '=']))
+}
+
+/// The id to write for `node`: its [`ID_ATTR`] if set, otherwise one derived
+/// from its [`NodeId`].
+pub(crate) fn node_name(graph: &Graph, node: NodeId) -> String {
+    match graph.node(node).and_then(|a| a.get(ID_ATTR)) {
+        Some(value) => value.to_string(),
+        None => node.to_string(),
+    }
+}
+
+impl std::fmt::Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "{e}"),
+            Self::UnknownFormat => write!(f, "Unsupported file type"),
+            Self::GraphMl(e) => write!(f, "{e}"),
+            Self::Dot(e) => write!(f, "{e}"),
+            Self::Json(e) => write!(f, "{e}"),
+            Self::Gexf(e) => write!(f, "{e}"),
+            Self::Csv(e) => write!(f, "{e}"),
+            Self::Rdf(e) => write!(f, "{e}"),
+            Self::Loader(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+impl From<std::io::Error> for LoadError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<graphml::GraphMlError> for LoadError {
+    fn from(e: graphml::GraphMlError) -> Self {
+        Self::GraphMl(e)
+    }
+}
+
+impl From<dot::DotError> for LoadError {
+    fn from(e: dot::DotError) -> Self {
+        Self::Dot(e)
+    }
+}
+
+impl From<json::JsonError> for LoadError {
+    fn from(e: json::JsonError) -> Self {
+        Self::Json(e)
+    }
+}
+
+impl From<gexf::GexfError> for LoadError {
+    fn from(e: gexf::GexfError) -> Self {
+        Self::Gexf(e)
+    }
+}
+
+impl From<csv::CsvError> for LoadError {
+    fn from(e: csv::CsvError) -> Self {
+        Self::Csv(e)
+    }
+}
+
+impl From<rdf::RdfError> for LoadError {
+    fn from(e: rdf::RdfError) -> Self {
+        Self::Rdf(e)
+    }
+}
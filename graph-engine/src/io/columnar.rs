@@ -0,0 +1,525 @@
+//! # Arrow and Parquet Tables
+//!
+//! Reads edge lists stored as columnar tables, as written by pandas, polars, or
+//! Arrow itself, keeping the type of each column instead of guessing it from
+//! text like [`csv`](super::csv) files:
+//! - Arrow IPC files and streams (`.arrow`, `.feather`, `.ipc`, `.arrows`)
+//! - Parquet files (`.parquet`, `.pq`)
+//!
+//! Which columns hold the source, target, and (optional) weight is set by
+//! [`ColumnarOptions`], which can be [guessed](ColumnarOptions::guess) from the
+//! column names like for CSV files. Every other column becomes an edge
+//! attribute. A second table may hold node attributes, one row per node, found
+//! by the value of its id column.
+//!
+//! Integer, floating point, and boolean columns keep their type, other columns
+//! become strings, and null values are left out. Nodes are identified by the
+//! value of their id, kept in their [`ID_ATTR`] attribute, so the integer `7`
+//! and the string `"7"` name the same node.
+//!
+//! Tables are read in batches of [`BATCH_ROWS`] rows, so only the file and a
+//! batch of decoded columns are in memory at once. The [`ColumnarLoader`] reads
+//! them as a [background load](super::loader).
+//!
+//! ```ignore
+//! let options = ColumnarOptions::guess(&columns(path)?);
+//! let graph = columnar::load(path, &options)?;
+//! ```
+
+use arrow_array::cast::AsArray;
+use arrow_array::types::{Float64Type, Int64Type};
+use arrow_array::{Array, ArrayRef, BooleanArray, Float64Array, Int64Array, RecordBatchReader};
+use arrow_array::{RecordBatch, StringArray};
+use arrow_cast::display::{ArrayFormatter, FormatOptions};
+use arrow_ipc::reader::{FileReader, StreamReader};
+use arrow_schema::{ArrowError, DataType};
+use bytes::Bytes;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::errors::ParquetError;
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Cursor, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use super::ID_ATTR;
+use super::csv::CsvOptions;
+use super::loader::GraphLoader;
+use crate::graph::{AttrValue, Attributes, Directedness, Graph, NodeId, Symbol};
+
+/// Number of rows decoded at once.
+pub const BATCH_ROWS: usize = 64 * 1024;
+
+/// Column names recognized as the node id of a node table, compared
+/// case-insensitively.
+const NODE_ID_NAMES: [&str; 4] = [ID_ATTR, "node", "name", "key"];
+/// The first bytes of an Arrow IPC file, as opposed to a stream.
+const IPC_FILE_MAGIC: &[u8] = b"ARROW1";
+
+/// A columnar file format.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ColumnarFormat {
+    /// Arrow IPC, as a file (also known as Feather) or a stream.
+    ArrowIpc,
+    Parquet,
+}
+
+/// A column of a table.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Column {
+    pub name: String,
+    /// Arrow data type, eg. `Int64` or `Utf8`.
+    pub data_type: DataType,
+}
+
+/// How to read an edge list table.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColumnarOptions {
+    /// Index of the column holding edge sources.
+    pub source: usize,
+    /// Index of the column holding edge targets.
+    pub target: usize,
+    /// Index of the column holding edge weights, if any.
+    pub weight: Option<usize>,
+    /// Whether edges are directed from source to target.
+    pub directed: bool,
+    /// Table of node attributes, if any.
+    pub nodes: Option<NodeTable>,
+}
+
+/// A table of node attributes, one row per node.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NodeTable {
+    pub path: PathBuf,
+    /// Index of the column holding node ids.
+    pub id: usize,
+}
+
+/// Errors which can occur while reading a table.
+#[derive(Debug)]
+pub enum ColumnarError {
+    Io(std::io::Error),
+    /// The file is not a table of the format.
+    UnknownFormat,
+    Arrow(ArrowError),
+    Parquet(ParquetError),
+    /// A row could not be read as an edge or node.
+    Invalid {
+        /// The table, the node or the edge one.
+        table: &'static str,
+        row: usize,
+        message: String,
+    },
+}
+
+/// Reads tables with the chosen [`ColumnarOptions`] as a
+/// [`LoadJob`](super::loader::LoadJob).
+pub struct ColumnarLoader {
+    options: ColumnarOptions,
+}
+
+/// A column of a batch, converted to the type of the attributes it becomes.
+enum Values {
+    Bool(BooleanArray),
+    Int(Int64Array),
+    Float(Float64Array),
+    String(StringArray),
+}
+
+/// Read an edge list table from disk, and its node table if any.
+pub fn load(path: impl AsRef<Path>, options: &ColumnarOptions) -> Result<Graph, ColumnarError> {
+    let path = path.as_ref();
+    let format = ColumnarFormat::from_path(path).ok_or(ColumnarError::UnknownFormat)?;
+    read(Bytes::from(std::fs::read(path)?), format, options)
+}
+
+/// Read an edge list table from the `contents` of a file in `format`, and its
+/// node table from disk if any.
+pub fn read(
+    contents: Bytes,
+    format: ColumnarFormat,
+    options: &ColumnarOptions,
+) -> Result<Graph, ColumnarError> {
+    let mut graph = Graph::new(match options.directed {
+        true => Directedness::Directed,
+        false => Directedness::Undirected,
+    });
+    let mut nodes: HashMap<String, NodeId> = HashMap::new();
+    let mut node = |graph: &mut Graph, id: AttrValue| {
+        *nodes
+            .entry(id.to_string())
+            .or_insert_with(|| graph.add_node_with(Attributes::from_iter([(ID_ATTR, id)])))
+    };
+
+    if let Some(table) = &options.nodes {
+        let format = ColumnarFormat::from_path(&table.path).ok_or(ColumnarError::UnknownFormat)?;
+        let contents = Bytes::from(std::fs::read(&table.path)?);
+        let mut row = 0;
+        for batch in batches(contents, format)? {
+            let (names, columns) = convert(&batch?);
+            let ids = columns.get(table.id).and_then(Option::as_ref);
+            for index in 0..columns_len(&columns) {
+                row += 1;
+                let id = ids.and_then(|ids| ids.get(index));
+                let id = id.ok_or_else(|| ColumnarError::Invalid {
+                    table: "node",
+                    row,
+                    message: "Missing node id".into(),
+                })?;
+                let node = node(&mut graph, id);
+                let attrs = graph.node_mut(node).expect("The node was just found");
+                for (column, values) in columns.iter().enumerate() {
+                    if column == table.id {
+                        continue;
+                    }
+                    if let Some(value) = values.as_ref().and_then(|v| v.get(index)) {
+                        attrs.insert(names[column], value);
+                    }
+                }
+            }
+        }
+    }
+
+    let weight = Symbol::new("weight");
+    let mut row = 0;
+    for batch in batches(contents, format)? {
+        let (names, columns) = convert(&batch?);
+        let column = |index: usize| {
+            columns
+                .get(index)
+                .and_then(Option::as_ref)
+                .ok_or_else(|| ColumnarError::Invalid {
+                    table: "edge",
+                    row: row + 1,
+                    message: format!("Column {} cannot be read", index + 1),
+                })
+        };
+        let (sources, targets) = (column(options.source)?, column(options.target)?);
+        for index in 0..columns_len(&columns) {
+            row += 1;
+            let invalid = |message: String| ColumnarError::Invalid {
+                table: "edge",
+                row,
+                message,
+            };
+            let source = sources
+                .get(index)
+                .ok_or_else(|| invalid("Missing source".into()))?;
+            let target = targets
+                .get(index)
+                .ok_or_else(|| invalid("Missing target".into()))?;
+
+            let mut attrs = Attributes::default();
+            for (column, values) in columns.iter().enumerate() {
+                if column == options.source || column == options.target {
+                    continue;
+                }
+                let Some(value) = values.as_ref().and_then(|v| v.get(index)) else {
+                    continue;
+                };
+                if Some(column) == options.weight {
+                    let number = value.as_f64().ok_or_else(|| {
+                        invalid(format!("Expected a numeric weight, found '{value}'"))
+                    })?;
+                    attrs.insert(weight, number);
+                    continue;
+                }
+                attrs.insert(names[column], value);
+            }
+
+            let (source, target) = (node(&mut graph, source), node(&mut graph, target));
+            graph
+                .add_edge_with(source, target, attrs)
+                .expect("Both endpoints were just looked up or created");
+        }
+    }
+    Ok(graph)
+}
+
+/// The columns of the table at `path`.
+pub fn columns(path: impl AsRef<Path>) -> Result<Vec<Column>, ColumnarError> {
+    Ok(open(path, 1)?
+        .schema()
+        .fields()
+        .iter()
+        .map(|field| Column {
+            name: field.name().clone(),
+            data_type: field.data_type().clone(),
+        })
+        .collect())
+}
+
+/// Read up to `rows` rows of the table at `path`, formatted as text, eg. to
+/// preview it before choosing its columns. Null values are empty.
+pub fn preview(path: impl AsRef<Path>, rows: usize) -> Result<Vec<Vec<String>>, ColumnarError> {
+    let Some(batch) = open(path, rows.max(1))?.next().transpose()? else {
+        return Ok(Vec::new());
+    };
+    let options = FormatOptions::default();
+    let formatters = batch
+        .columns()
+        .iter()
+        .map(|column| ArrayFormatter::try_new(column.as_ref(), &options))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok((0..batch.num_rows().min(rows))
+        .map(|row| {
+            formatters
+                .iter()
+                .map(|f| f.value(row).to_string())
+                .collect()
+        })
+        .collect())
+}
+
+/// Open the table at `path`, reading batches of up to `rows` rows, without
+/// reading the whole file first.
+fn open(path: impl AsRef<Path>, rows: usize) -> Result<Box<dyn RecordBatchReader>, ColumnarError> {
+    let path = path.as_ref();
+    let format = ColumnarFormat::from_path(path).ok_or(ColumnarError::UnknownFormat)?;
+    let mut file = File::open(path)?;
+    Ok(match format {
+        ColumnarFormat::Parquet => Box::new(
+            ParquetRecordBatchReaderBuilder::try_new(file)?
+                .with_batch_size(rows)
+                .build()?,
+        ),
+        ColumnarFormat::ArrowIpc => {
+            let mut magic = [0; IPC_FILE_MAGIC.len()];
+            let is_file = file.read_exact(&mut magic).is_ok() && magic == IPC_FILE_MAGIC;
+            file.seek(SeekFrom::Start(0))?;
+            match is_file {
+                true => Box::new(FileReader::try_new_buffered(file, None)?),
+                false => Box::new(StreamReader::try_new_buffered(file, None)?),
+            }
+        }
+    })
+}
+
+/// Batches of up to [`BATCH_ROWS`] rows of the table in `contents`.
+fn batches(
+    contents: Bytes,
+    format: ColumnarFormat,
+) -> Result<Box<dyn RecordBatchReader>, ColumnarError> {
+    Ok(match format {
+        ColumnarFormat::Parquet => Box::new(
+            ParquetRecordBatchReaderBuilder::try_new(contents)?
+                .with_batch_size(BATCH_ROWS)
+                .build()?,
+        ),
+        ColumnarFormat::ArrowIpc if contents.starts_with(IPC_FILE_MAGIC) => {
+            Box::new(FileReader::try_new(Cursor::new(contents), None)?)
+        }
+        ColumnarFormat::ArrowIpc => Box::new(StreamReader::try_new(Cursor::new(contents), None)?),
+    })
+}
+
+/// The names of the columns of `batch` and their values, `None` for columns
+/// which cannot be converted to attributes, eg. lists.
+fn convert(batch: &RecordBatch) -> (Vec<Symbol>, Vec<Option<Values>>) {
+    let names = batch
+        .schema()
+        .fields()
+        .iter()
+        .map(|field| Symbol::new(field.name()))
+        .collect();
+    let columns = batch.columns().iter().map(Values::new).collect();
+    (names, columns)
+}
+
+/// Number of rows of the converted `columns` of a batch.
+fn columns_len(columns: &[Option<Values>]) -> usize {
+    columns.iter().flatten().map(Values::len).max().unwrap_or(0)
+}
+
+impl ColumnarFormat {
+    /// Every supported format.
+    pub const ALL: [ColumnarFormat; 2] = [Self::ArrowIpc, Self::Parquet];
+
+    /// The format's name, eg. for a file dialog.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::ArrowIpc => "Arrow IPC",
+            Self::Parquet => "Parquet",
+        }
+    }
+
+    /// File extensions of the format, lowercase and without the dot.
+    pub fn extensions(self) -> &'static [&'static str] {
+        match self {
+            Self::ArrowIpc => &["arrow", "feather", "ipc", "arrows"],
+            Self::Parquet => &["parquet", "pq"],
+        }
+    }
+
+    /// The format of the file at `path`, by extension.
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        let extension = path.as_ref().extension()?.to_str()?.to_ascii_lowercase();
+        Self::ALL
+            .into_iter()
+            .find(|f| f.extensions().contains(&extension.as_str()))
+    }
+}
+
+impl Column {
+    /// Returns `true` if the column's values become numbers.
+    pub fn is_numeric(&self) -> bool {
+        self.data_type.is_numeric()
+    }
+}
+
+impl ColumnarOptions {
+    /// Guess the column mapping from the names of the `columns`, like
+    /// [`CsvOptions::from_rows()`] does from a header. Without a source or
+    /// target column by name, the first two columns are the source and target,
+    /// and a numeric third column is the weight.
+    ///
+    /// Edges are assumed to be directed.
+    pub fn guess(columns: &[Column]) -> Self {
+        let names: Vec<String> = columns.iter().map(|c| c.name.clone()).collect();
+        let csv = CsvOptions::from_rows(',', &[names]);
+        let (source, target, weight) = match csv.has_header {
+            true => (csv.source, csv.target, csv.weight),
+            false => (0, 1, columns.get(2).filter(|c| c.is_numeric()).map(|_| 2)),
+        };
+        Self {
+            source,
+            target,
+            weight,
+            directed: true,
+            nodes: None,
+        }
+    }
+}
+
+impl NodeTable {
+    /// A node table at `path` with the given `columns`, its id column guessed
+    /// from their names.
+    pub fn guess(path: impl Into<PathBuf>, columns: &[Column]) -> Self {
+        let id = columns
+            .iter()
+            .position(|c| {
+                NODE_ID_NAMES
+                    .iter()
+                    .any(|n| n.eq_ignore_ascii_case(&c.name))
+            })
+            .unwrap_or(0);
+        Self {
+            path: path.into(),
+            id,
+        }
+    }
+}
+
+impl ColumnarLoader {
+    pub fn new(options: ColumnarOptions) -> Self {
+        Self { options }
+    }
+}
+
+impl GraphLoader for ColumnarLoader {
+    fn name(&self) -> &str {
+        "Arrow/Parquet"
+    }
+
+    fn accepts(&self, path: &Path) -> bool {
+        ColumnarFormat::from_path(path).is_some()
+    }
+
+    fn read(&self, path: &Path, reader: &mut dyn Read) -> Result<Graph, String> {
+        let format = ColumnarFormat::from_path(path).ok_or("Unsupported file type")?;
+        let mut contents = Vec::new();
+        reader
+            .read_to_end(&mut contents)
+            .map_err(|e| e.to_string())?;
+        read(Bytes::from(contents), format, &self.options).map_err(|e| e.to_string())
+    }
+}
+
+impl Values {
+    /// Convert `array` to the type of the attributes its values become, if
+    /// they can be.
+    fn new(array: &ArrayRef) -> Option<Self> {
+        let data_type = array.data_type();
+        let cast = |to: DataType| arrow_cast::cast(array, &to).ok();
+        Some(if *data_type == DataType::Boolean {
+            Self::Bool(array.as_boolean().clone())
+        } else if data_type.is_integer() {
+            Self::Int(cast(DataType::Int64)?.as_primitive::<Int64Type>().clone())
+        } else if data_type.is_numeric() {
+            Self::Float(
+                cast(DataType::Float64)?
+                    .as_primitive::<Float64Type>()
+                    .clone(),
+            )
+        } else {
+            Self::String(cast(DataType::Utf8)?.as_string::<i32>().clone())
+        })
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Self::Bool(array) => array.len(),
+            Self::Int(array) => array.len(),
+            Self::Float(array) => array.len(),
+            Self::String(array) => array.len(),
+        }
+    }
+
+    /// The value at `index`, unless it is null.
+    fn get(&self, index: usize) -> Option<AttrValue> {
+        let valid = match self {
+            Self::Bool(array) => array.is_valid(index),
+            Self::Int(array) => array.is_valid(index),
+            Self::Float(array) => array.is_valid(index),
+            Self::String(array) => array.is_valid(index),
+        };
+        valid.then(|| match self {
+            Self::Bool(array) => AttrValue::Bool(array.value(index)),
+            Self::Int(array) => AttrValue::Int(array.value(index)),
+            Self::Float(array) => AttrValue::Float(array.value(index)),
+            Self::String(array) => AttrValue::String(array.value(index).to_owned()),
+        })
+    }
+}
+
+impl std::fmt::Display for ColumnarFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl std::fmt::Display for ColumnarError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "Failed to read table: {e}"),
+            Self::UnknownFormat => write!(f, "Not an Arrow or Parquet file"),
+            Self::Arrow(e) => write!(f, "Invalid Arrow table: {e}"),
+            Self::Parquet(e) => write!(f, "Invalid Parquet table: {e}"),
+            Self::Invalid {
+                table,
+                row,
+                message,
+            } => write!(f, "Invalid {table} table at row {row}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for ColumnarError {}
+
+impl From<std::io::Error> for ColumnarError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<ArrowError> for ColumnarError {
+    fn from(e: ArrowError) -> Self {
+        Self::Arrow(e)
+    }
+}
+
+impl From<ParquetError> for ColumnarError {
+    fn from(e: ParquetError) -> Self {
+        Self::Parquet(e)
+    }
+}
@@ -0,0 +1,356 @@
+//! # CSV/TSV Edge Lists
+//!
+//! Reads delimiter-separated edge lists, one edge per row:
+//! ```text
+//! source,target,weight,kind
+//! alice,bob,2.5,friend
+//! bob,carol,1,colleague
+//! ```
+//!
+//! Which columns hold the source, target, and (optional) weight is set by
+//! [`CsvOptions`], which can be [sniffed](CsvOptions::sniff) from the file. Every
+//! other column becomes an edge attribute named after its header. Nodes are
+//! identified by name, kept in their [`ID_ATTR`] attribute; rows with an empty
+//! source or target are rejected. Attributes of numeric literals become
+//! numbers, so that eg. `nan` or `inf` stay strings.
+//!
+//! Fields may be quoted with `"`, with `""` for a literal quote; quoted fields
+//! may span lines.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use super::ID_ATTR;
+use crate::graph::{AttrValue, Attributes, Directedness, Graph, NodeId, Symbol};
+
+/// Delimiters recognized by [`CsvOptions::sniff()`], most likely first.
+pub const DELIMITERS: [char; 4] = [',', '\t', ';', '|'];
+
+/// Header names recognized as the source column, compared case-insensitively.
+const SOURCE_NAMES: [&str; 4] = ["source", "from", "src", "u"];
+/// Header names recognized as the target column, compared case-insensitively.
+const TARGET_NAMES: [&str; 4] = ["target", "to", "dst", "v"];
+/// Header names recognized as the weight column, compared case-insensitively.
+const WEIGHT_NAMES: [&str; 3] = ["weight", "value", "w"];
+
+/// Number of rows looked at by [`CsvOptions::sniff()`].
+const SNIFF_ROWS: usize = 5;
+
+/// How to read an edge list.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CsvOptions {
+    /// Field delimiter.
+    pub delimiter: char,
+    /// Whether the first row names the columns instead of holding an edge.
+    pub has_header: bool,
+    /// Index of the column holding edge sources.
+    pub source: usize,
+    /// Index of the column holding edge targets.
+    pub target: usize,
+    /// Index of the column holding edge weights, if any.
+    pub weight: Option<usize>,
+    /// Whether edges are directed from source to target.
+    pub directed: bool,
+}
+
+/// Errors which can occur while reading an edge list.
+#[derive(Debug)]
+pub enum CsvError {
+    Io(std::io::Error),
+    /// A row could not be read as an edge.
+    Invalid {
+        line: usize,
+        message: String,
+    },
+}
+
+/// Reads records from a delimiter-separated file, one at a time.
+pub(crate) struct RecordReader<R: BufRead> {
+    inner: R,
+    delimiter: char,
+    /// Line the last record started on, 1-based.
+    line: usize,
+    /// Line the next record starts on, 1-based.
+    next_line: usize,
+    buf: String,
+}
+
+/// Read an edge list from disk.
+pub fn load(path: impl AsRef<Path>, options: &CsvOptions) -> Result<Graph, CsvError> {
+    read(BufReader::new(File::open(path)?), options)
+}
+
+/// Read an edge list.
+pub fn read(reader: impl BufRead, options: &CsvOptions) -> Result<Graph, CsvError> {
+    let mut graph = Graph::new(if options.directed {
+        Directedness::Directed
+    } else {
+        Directedness::Undirected
+    });
+    let mut nodes: HashMap<String, NodeId> = HashMap::new();
+    let mut node = |graph: &mut Graph, name: &str| {
+        *nodes
+            .entry(name.to_owned())
+            .or_insert_with(|| graph.add_node_with(Attributes::from_iter([(ID_ATTR, name)])))
+    };
+    read_edges(reader, options, |source, target, attrs| {
+        let (source, target) = (node(&mut graph, source), node(&mut graph, target));
+        graph
+            .add_edge_with(source, target, attrs)
+            .expect("Both endpoints were just looked up or created");
+        Ok::<_, CsvError>(())
+    })?;
+    Ok(graph)
+}
+
+/// Read an edge list one row at a time, passing the names of each edge's
+/// endpoints and its attributes to `edge`, which can stop the read by
+/// returning an error.
+pub(crate) fn read_edges<E: From<CsvError>>(
+    reader: impl BufRead,
+    options: &CsvOptions,
+    mut edge: impl FnMut(&str, &str, Attributes) -> Result<(), E>,
+) -> Result<(), E> {
+    let mut records = RecordReader::new(reader, options.delimiter);
+    let mut fields = Vec::new();
+    let mut names: Vec<Symbol> = Vec::new();
+    if options.has_header && records.next_record(&mut fields).map_err(CsvError::from)? {
+        names = fields.iter().map(|f| Symbol::new(f.trim())).collect();
+    }
+    let weight = Symbol::new("weight");
+
+    while records.next_record(&mut fields).map_err(CsvError::from)? {
+        let line = records.line();
+        let column = |index: usize| {
+            fields
+                .get(index)
+                .map(|f| f.trim())
+                .ok_or_else(|| CsvError::Invalid {
+                    line,
+                    message: format!("Expected at least {} columns", index + 1),
+                })
+        };
+        let (source, target) = (column(options.source)?, column(options.target)?);
+        if source.is_empty() || target.is_empty() {
+            return Err(CsvError::Invalid {
+                line,
+                message: "Expected a source and a target, found an empty node name".to_owned(),
+            }
+            .into());
+        }
+
+        let mut attrs = Attributes::default();
+        for (index, field) in fields.iter().enumerate() {
+            if index == options.source || index == options.target || field.trim().is_empty() {
+                continue;
+            }
+            if Some(index) == options.weight {
+                let value = number(field).ok_or_else(|| CsvError::Invalid {
+                    line,
+                    message: format!("Expected a numeric weight, found '{}'", field.trim()),
+                })?;
+                attrs.insert(weight, value);
+                continue;
+            }
+            let name = match names.get(index) {
+                Some(name) => *name,
+                None => Symbol::new(&format!("column{}", index + 1)),
+            };
+            attrs.insert(name, parse_value(field));
+        }
+        edge(source, target, attrs)?;
+    }
+    Ok(())
+}
+
+/// Read up to `rows` records, eg. to preview a file before choosing its columns.
+pub fn preview(
+    path: impl AsRef<Path>,
+    delimiter: char,
+    rows: usize,
+) -> Result<Vec<Vec<String>>, CsvError> {
+    let mut records = RecordReader::new(BufReader::new(File::open(path)?), delimiter);
+    let mut preview = Vec::new();
+    let mut fields = Vec::new();
+    while preview.len() < rows && records.next_record(&mut fields)? {
+        preview.push(std::mem::take(&mut fields));
+    }
+    Ok(preview)
+}
+
+/// An integer, float, or string, whichever `field` parses as first.
+fn parse_value(field: &str) -> AttrValue {
+    if let Ok(i) = field.trim().parse() {
+        AttrValue::Int(i)
+    } else if let Some(f) = number(field) {
+        AttrValue::Float(f)
+    } else {
+        AttrValue::String(field.to_owned())
+    }
+}
+
+/// The value of `field` if it is a decimal numeral, eg. `-1.5e3`, but not
+/// `nan` or `inf`, which Rust parses too.
+fn number(field: &str) -> Option<f64> {
+    let trimmed = field.trim();
+    let literal = trimmed.contains(|c: char| c.is_ascii_digit())
+        && trimmed
+            .chars()
+            .all(|c| c.is_ascii_digit() || "+-.eE".contains(c));
+    literal.then(|| trimmed.parse().ok()).flatten()
+}
+
+impl CsvOptions {
+    /// Guess the options of the file at `path` from its first rows:
+    /// - the delimiter is the most common of [`DELIMITERS`] in the first line,
+    ///   or a tab for `.tsv` files
+    /// - a header is assumed if the first row names a source or target column
+    ///   (eg. `source`, `from`), and the columns are mapped by name
+    /// - otherwise the first two columns are the source and target, and a
+    ///   numeric third column is the weight
+    ///
+    /// Edges are assumed to be directed.
+    pub fn sniff(path: impl AsRef<Path>) -> Result<Self, CsvError> {
+        let path = path.as_ref();
+        let tsv = path
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("tsv"));
+
+        let mut first_line = String::new();
+        BufReader::new(File::open(path)?).read_line(&mut first_line)?;
+        let delimiter = if tsv {
+            '\t'
+        } else {
+            // `max_by_key()` picks the last of equals, so prefer earlier delimiters
+            DELIMITERS
+                .into_iter()
+                .rev()
+                .max_by_key(|d| first_line.matches(*d).count())
+                .unwrap()
+        };
+
+        let rows = preview(path, delimiter, SNIFF_ROWS)?;
+        Ok(Self::from_rows(delimiter, &rows))
+    }
+
+    /// Guess the column mapping from the first `rows` of a file, see [`Self::sniff()`].
+    pub fn from_rows(delimiter: char, rows: &[Vec<String>]) -> Self {
+        let find = |row: &[String], names: &[&str]| {
+            row.iter()
+                .position(|field| names.iter().any(|n| n.eq_ignore_ascii_case(field.trim())))
+        };
+
+        let mut options = Self {
+            delimiter,
+            ..Default::default()
+        };
+        let Some(first) = rows.first() else {
+            return options;
+        };
+
+        let (source, target) = (find(first, &SOURCE_NAMES), find(first, &TARGET_NAMES));
+        if source.is_some() || target.is_some() {
+            options.has_header = true;
+            options.source = source.unwrap_or(0);
+            options.target = target.unwrap_or(if options.source == 0 { 1 } else { 0 });
+            options.weight = find(first, &WEIGHT_NAMES);
+        } else if first.get(2).is_some_and(|f| number(f).is_some()) {
+            options.weight = Some(2);
+        }
+        options
+    }
+}
+
+impl<R: BufRead> RecordReader<R> {
+    pub(crate) fn new(inner: R, delimiter: char) -> Self {
+        Self {
+            inner,
+            delimiter,
+            line: 0,
+            next_line: 1,
+            buf: String::new(),
+        }
+    }
+
+    /// The line the last record started on, 1-based.
+    pub(crate) fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Read the next non-empty record into `fields`, returning `false` at the end
+    /// of the file.
+    pub(crate) fn next_record(&mut self, fields: &mut Vec<String>) -> std::io::Result<bool> {
+        fields.clear();
+        loop {
+            self.buf.clear();
+            self.line = self.next_line;
+            if self.inner.read_line(&mut self.buf)? == 0 {
+                return Ok(false);
+            }
+            self.next_line += 1;
+            // quoted fields may continue on the next lines
+            while self.buf.matches('"').count() % 2 == 1 {
+                if self.inner.read_line(&mut self.buf)? == 0 {
+                    break;
+                }
+                self.next_line += 1;
+            }
+            let record = self.buf.trim_end_matches(['\r', '\n']);
+            if record.trim().is_empty() {
+                continue;
+            }
+            split_record(record, self.delimiter, fields);
+            return Ok(true);
+        }
+    }
+}
+
+/// Split a single record into its fields, unquoting quoted fields.
+fn split_record(record: &str, delimiter: char, fields: &mut Vec<String>) {
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = record.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.next_if_eq(&'"').is_some() => field.push('"'),
+            '"' => quoted = !quoted,
+            c if c == delimiter && !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+}
+
+impl std::default::Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            has_header: false,
+            source: 0,
+            target: 1,
+            weight: None,
+            directed: true,
+        }
+    }
+}
+
+impl std::fmt::Display for CsvError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "Failed to read edge list: {e}"),
+            Self::Invalid { line, message } => {
+                write!(f, "Invalid edge list at line {line}: {message}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CsvError {}
+
+impl From<std::io::Error> for CsvError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
@@ -0,0 +1,698 @@
+//! # Graphviz DOT
+//!
+//! Reads and writes [DOT](https://graphviz.org/doc/info/lang.html) files.
+//!
+//! - Node names are kept in the [`ID_ATTR`] attribute and reused on export.
+//! - Attributes are kept under their DOT names (eg. `label`, `color`, `shape`).
+//!   Unquoted numerals become numbers, everything else stays a string.
+//! - Default attributes (`node [color=red]`) apply to elements created after
+//!   them, within the same subgraph.
+//! - Subgraphs are flattened. Nodes inside a subgraph whose name starts with
+//!   `cluster` get a [`CLUSTER_ATTR`] attribute naming the innermost cluster,
+//!   and are grouped back into clusters on export.
+//! - Edges between subgraphs (`a -> { b c }`) connect every pair of nodes.
+//! - `strict` graphs ignore repeated edges. Ports are ignored.
+//! - In quoted strings, `\"`, `\\`, and `\n` stand for a quote, a backslash,
+//!   and a line break, and other backslashes for themselves, so that strings
+//!   are read back as written.
+//! - Subgraphs nest at most [`MAX_DEPTH`] deep.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use super::{ID_ATTR, node_name};
+use crate::graph::{AttrValue, Attributes, Directedness, Graph, NodeId, Symbol};
+
+/// Attribute naming the cluster subgraph a node was declared in.
+pub const CLUSTER_ATTR: &str = "cluster";
+/// Graph attribute holding the graph's name.
+pub const NAME_ATTR: &str = "name";
+/// How deep subgraphs may nest, so that the parser's recursion is bounded.
+pub const MAX_DEPTH: usize = 64;
+
+/// Errors which can occur while reading a DOT file.
+#[derive(Debug)]
+pub enum DotError {
+    Io(std::io::Error),
+    /// The file is not valid DOT.
+    Syntax {
+        line: usize,
+        message: String,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    /// An identifier, numeral, or string.
+    Id {
+        text: String,
+        kind: IdKind,
+    },
+    LBrace,
+    RBrace,
+    LBracket,
+    RBracket,
+    Equals,
+    Semicolon,
+    Comma,
+    Colon,
+    /// `->` if directed, `--` otherwise.
+    EdgeOp {
+        directed: bool,
+    },
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum IdKind {
+    Name,
+    Numeral,
+    /// A quoted or HTML string.
+    String,
+}
+
+/// Default attributes in effect within a subgraph.
+#[derive(Clone, Default)]
+struct Scope {
+    node: Attributes,
+    edge: Attributes,
+    /// Innermost enclosing cluster, if any.
+    cluster: Option<String>,
+}
+
+/// Recursive descent parser over a tokenized file.
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+    graph: Graph,
+    strict: bool,
+    /// DOT node names to graph nodes.
+    nodes: HashMap<String, NodeId>,
+    scopes: Vec<Scope>,
+    /// Nodes mentioned in each open subgraph, for edges to subgraphs.
+    members: Vec<Vec<NodeId>>,
+}
+
+/// Read a DOT file from disk.
+pub fn load(path: impl AsRef<Path>) -> Result<Graph, DotError> {
+    read(&fs::read_to_string(path)?)
+}
+
+/// Parse the first graph in a DOT document.
+pub fn read(source: &str) -> Result<Graph, DotError> {
+    let tokens = tokenize(source)?;
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        graph: Graph::default(),
+        strict: false,
+        nodes: HashMap::new(),
+        scopes: vec![Scope::default()],
+        members: vec![Vec::new()],
+    };
+    parser.parse_graph()?;
+    Ok(parser.graph)
+}
+
+/// Write `graph` to a DOT file on disk.
+pub fn save(graph: &Graph, path: impl AsRef<Path>) -> std::io::Result<()> {
+    let mut writer = BufWriter::new(fs::File::create(path)?);
+    write(graph, &mut writer)?;
+    writer.flush()
+}
+
+/// Write `graph` as a DOT document.
+pub fn write(graph: &Graph, mut out: impl Write) -> std::io::Result<()> {
+    let (keyword, edge_op) = match graph.directedness() {
+        Directedness::Directed => ("digraph", "->"),
+        Directedness::Undirected => ("graph", "--"),
+    };
+    match graph.attrs().get(NAME_ATTR) {
+        Some(name) => writeln!(out, "{keyword} {} {{", quote(&name.to_string()))?,
+        None => writeln!(out, "{keyword} {{")?,
+    }
+
+    let name = Symbol::new(NAME_ATTR);
+    for (key, value) in graph.attrs().iter().filter(|(k, _)| *k != name) {
+        writeln!(out, "  {}={};", quote(key.as_str()), format_value(value))?;
+    }
+
+    let cluster = Symbol::new(CLUSTER_ATTR);
+    let mut clusters: Vec<(String, Vec<NodeId>)> = Vec::new();
+    for node in graph.nodes() {
+        match graph.node(node).and_then(|a| a.get(cluster)) {
+            Some(c) => {
+                let c = c.to_string();
+                match clusters.iter_mut().find(|(name, _)| *name == c) {
+                    Some((_, nodes)) => nodes.push(node),
+                    None => clusters.push((c, vec![node])),
+                }
+            }
+            None => write_node(&mut out, graph, node, "  ")?,
+        }
+    }
+    for (name, nodes) in clusters {
+        writeln!(out, "  subgraph {} {{", quote(&name))?;
+        for node in nodes {
+            write_node(&mut out, graph, node, "    ")?;
+        }
+        writeln!(out, "  }}")?;
+    }
+
+    for edge in graph.edges() {
+        let (source, target) = graph.endpoints(edge).unwrap();
+        write!(
+            out,
+            "  {} {edge_op} {}",
+            quote(&node_name(graph, source)),
+            quote(&node_name(graph, target))
+        )?;
+        write_attrs(&mut out, graph.edge(edge).unwrap(), &[ID_ATTR])?;
+        writeln!(out, ";")?;
+    }
+
+    writeln!(out, "}}")
+}
+
+fn write_node(
+    out: &mut impl Write,
+    graph: &Graph,
+    node: NodeId,
+    indent: &str,
+) -> std::io::Result<()> {
+    write!(out, "{indent}{}", quote(&node_name(graph, node)))?;
+    write_attrs(out, graph.node(node).unwrap(), &[ID_ATTR, CLUSTER_ATTR])?;
+    writeln!(out, ";")
+}
+
+/// Write an attribute list, leaving out the `skip` keys.
+fn write_attrs(out: &mut impl Write, attrs: &Attributes, skip: &[&str]) -> std::io::Result<()> {
+    let mut first = true;
+    for (key, value) in attrs.iter() {
+        if skip.contains(&key.as_str()) {
+            continue;
+        }
+        write!(out, "{}", if first { " [" } else { ", " })?;
+        write!(out, "{}={}", quote(key.as_str()), format_value(value))?;
+        first = false;
+    }
+    if !first {
+        write!(out, "]")?;
+    }
+    Ok(())
+}
+
+fn format_value(value: &AttrValue) -> String {
+    match value {
+        AttrValue::Int(_) | AttrValue::Float(_) => value.to_string(),
+        _ => quote(&value.to_string()),
+    }
+}
+
+/// Quote `text` as a DOT string, unless it is a plain name.
+fn quote(text: &str) -> String {
+    let plain = text
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && text.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !is_keyword(text);
+    if plain {
+        text.to_owned()
+    } else {
+        let mut quoted = String::from('"');
+        for c in text.chars() {
+            match c {
+                '"' => quoted.push_str("\\\""),
+                '\\' => quoted.push_str("\\\\"),
+                '\n' => quoted.push_str("\\n"),
+                c => quoted.push(c),
+            }
+        }
+        quoted.push('"');
+        quoted
+    }
+}
+
+fn is_keyword(text: &str) -> bool {
+    ["strict", "graph", "digraph", "subgraph", "node", "edge"]
+        .iter()
+        .any(|k| k.eq_ignore_ascii_case(text))
+}
+
+impl Parser {
+    fn parse_graph(&mut self) -> Result<(), DotError> {
+        self.strict = self.eat_keyword("strict");
+        let directedness = if self.eat_keyword("digraph") {
+            Directedness::Directed
+        } else if self.eat_keyword("graph") {
+            Directedness::Undirected
+        } else {
+            return Err(self.error("Expected 'graph' or 'digraph'"));
+        };
+        self.graph = Graph::new(directedness);
+
+        if let Some(Token::Id { .. }) = self.peek() {
+            let name = self.id()?;
+            self.graph.attrs_mut().insert(NAME_ATTR, name);
+        }
+        self.expect(Token::LBrace)?;
+        self.statements()
+    }
+
+    /// Parse statements up to and including the closing brace.
+    fn statements(&mut self) -> Result<(), DotError> {
+        loop {
+            match self.peek() {
+                Some(Token::RBrace) => {
+                    self.pos += 1;
+                    return Ok(());
+                }
+                Some(Token::Semicolon) => self.pos += 1,
+                Some(_) => self.statement()?,
+                None => return Err(self.error("Expected '}'")),
+            }
+        }
+    }
+
+    fn statement(&mut self) -> Result<(), DotError> {
+        // attribute statements: `graph [...]`, `node [...]`, `edge [...]`
+        if self.peek_at(1) == Some(&Token::LBracket)
+            && let Some(keyword) = ["graph", "node", "edge"]
+                .into_iter()
+                .find(|k| self.peek_keyword(k))
+        {
+            self.pos += 1;
+            let attrs = self.attr_lists()?;
+            let top_level = self.scopes.len() == 1;
+            let scope = self.scopes.last_mut().unwrap();
+            match keyword {
+                "graph" if top_level => extend(self.graph.attrs_mut(), attrs),
+                "node" => extend(&mut scope.node, attrs),
+                "edge" => extend(&mut scope.edge, attrs),
+                _ => {}
+            }
+            return Ok(());
+        }
+
+        if matches!(self.peek(), Some(Token::Id { .. })) && self.peek_at(1) == Some(&Token::Equals)
+        {
+            let key = self.id()?;
+            self.pos += 1;
+            let value = self.value()?;
+            // attributes of subgraphs themselves are not kept
+            if self.scopes.len() == 1 {
+                self.graph.attrs_mut().insert(key.as_str(), value);
+            }
+            return Ok(());
+        }
+
+        let first = self.operand()?;
+        if matches!(self.peek(), Some(Token::EdgeOp { .. })) {
+            return self.edges(first);
+        }
+
+        let attrs = self.attr_lists()?;
+        for node in first {
+            extend(self.graph.node_mut(node).unwrap(), attrs.clone());
+        }
+        Ok(())
+    }
+
+    /// Parse the rest of an edge statement, starting at its first edge operator.
+    fn edges(&mut self, first: Vec<NodeId>) -> Result<(), DotError> {
+        let mut operands = vec![first];
+        while let Some(Token::EdgeOp { directed }) = self.peek() {
+            if *directed != self.graph.is_directed() {
+                return Err(self.error(match self.graph.directedness() {
+                    Directedness::Directed => "Expected '->' in a digraph",
+                    Directedness::Undirected => "Expected '--' in an undirected graph",
+                }));
+            }
+            self.pos += 1;
+            operands.push(self.operand()?);
+        }
+
+        let mut attrs = self.scopes.last().unwrap().edge.clone();
+        extend(&mut attrs, self.attr_lists()?);
+
+        for pair in operands.windows(2) {
+            for &source in &pair[0] {
+                for &target in &pair[1] {
+                    if self.strict && self.graph.find_edge(source, target).is_some() {
+                        continue;
+                    }
+                    self.graph
+                        .add_edge_with(source, target, attrs.clone())
+                        .expect("Both endpoints were created by the parser");
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// A node, or the nodes of a subgraph.
+    fn operand(&mut self) -> Result<Vec<NodeId>, DotError> {
+        if self.peek_keyword("subgraph") || self.peek() == Some(&Token::LBrace) {
+            return self.subgraph();
+        }
+
+        let name = self.id()?;
+        // ports are ignored
+        while self.peek() == Some(&Token::Colon) {
+            self.pos += 1;
+            self.id()?;
+        }
+        Ok(vec![self.node(name)])
+    }
+
+    fn subgraph(&mut self) -> Result<Vec<NodeId>, DotError> {
+        let mut name = None;
+        if self.eat_keyword("subgraph") && matches!(self.peek(), Some(Token::Id { .. })) {
+            name = Some(self.id()?);
+        }
+        self.expect(Token::LBrace)?;
+        if self.scopes.len() > MAX_DEPTH {
+            return Err(self.error(format!("Subgraphs nested more than {MAX_DEPTH} deep")));
+        }
+
+        let mut scope = self.scopes.last().unwrap().clone();
+        if let Some(name) = name.filter(|n| n.starts_with("cluster")) {
+            scope.cluster = Some(name);
+        }
+        self.scopes.push(scope);
+        self.members.push(Vec::new());
+
+        self.statements()?;
+
+        self.scopes.pop();
+        let members = self.members.pop().unwrap();
+        self.members.last_mut().unwrap().extend(&members);
+        Ok(members)
+    }
+
+    /// The node named `name`, created with the current default attributes if it
+    /// was not seen yet.
+    fn node(&mut self, name: String) -> NodeId {
+        let scope = self.scopes.last().unwrap();
+        let node = match self.nodes.get(&name) {
+            Some(node) => *node,
+            None => {
+                let mut attrs = Attributes::from_iter([(ID_ATTR, name.as_str())]);
+                extend(&mut attrs, scope.node.iter().map(|(k, v)| (k, v.clone())));
+                let node = self.graph.add_node_with(attrs);
+                self.nodes.insert(name, node);
+                node
+            }
+        };
+        if let Some(cluster) = &scope.cluster {
+            self.graph
+                .node_mut(node)
+                .unwrap()
+                .insert(CLUSTER_ATTR, cluster.as_str());
+        }
+        self.members.last_mut().unwrap().push(node);
+        node
+    }
+
+    /// Parse any number of consecutive `[a=b, ...]` lists.
+    fn attr_lists(&mut self) -> Result<Vec<(Symbol, AttrValue)>, DotError> {
+        let mut attrs = Vec::new();
+        while self.peek() == Some(&Token::LBracket) {
+            self.pos += 1;
+            loop {
+                match self.peek() {
+                    Some(Token::RBracket) => {
+                        self.pos += 1;
+                        break;
+                    }
+                    Some(Token::Comma | Token::Semicolon) => self.pos += 1,
+                    _ => {
+                        let key = Symbol::new(&self.id()?);
+                        self.expect(Token::Equals)?;
+                        attrs.push((key, self.value()?));
+                    }
+                }
+            }
+        }
+        Ok(attrs)
+    }
+
+    /// An attribute value: numerals become numbers, everything else a string.
+    fn value(&mut self) -> Result<AttrValue, DotError> {
+        match self.next() {
+            Some(Token::Id {
+                text,
+                kind: IdKind::Numeral,
+            }) => Ok(match text.parse() {
+                Ok(i) => AttrValue::Int(i),
+                Err(_) => AttrValue::Float(text.parse().unwrap_or_default()),
+            }),
+            Some(Token::Id { text, .. }) => Ok(AttrValue::String(text)),
+            _ => Err(self.error("Expected a value")),
+        }
+    }
+
+    fn id(&mut self) -> Result<String, DotError> {
+        match self.next() {
+            Some(Token::Id { text, .. }) => Ok(text),
+            _ => Err(self.error("Expected an identifier")),
+        }
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), DotError> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            _ => Err(self.error(format!("Expected {}", expected.describe()))),
+        }
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let found = self.peek_keyword(keyword);
+        self.pos += found as usize;
+        found
+    }
+
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(
+            self.peek(),
+            Some(Token::Id { text, kind: IdKind::Name }) if text.eq_ignore_ascii_case(keyword)
+        )
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.peek_at(0)
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<&Token> {
+        self.tokens.get(self.pos + offset).map(|(t, _)| t)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).map(|(t, _)| t.clone());
+        self.pos += 1;
+        token
+    }
+
+    fn error(&self, message: impl Into<String>) -> DotError {
+        let line = self
+            .tokens
+            .get(self.pos.min(self.tokens.len().saturating_sub(1)))
+            .map_or(1, |(_, line)| *line);
+        DotError::syntax(line, message)
+    }
+}
+
+/// Set every attribute in `values` on `attrs`.
+fn extend(attrs: &mut Attributes, values: impl IntoIterator<Item = (Symbol, AttrValue)>) {
+    for (key, value) in values {
+        attrs.insert(key, value);
+    }
+}
+
+/// Split a DOT document into tokens, each with its line number.
+fn tokenize(source: &str) -> Result<Vec<(Token, usize)>, DotError> {
+    let mut tokens: Vec<(Token, usize)> = Vec::new();
+    let mut chars = source.chars().peekable();
+    let mut line = 1;
+    let mut line_start = true;
+    // set by `+`, which appends the next string to the previous one
+    let mut concat = false;
+
+    while let Some(c) = chars.next() {
+        let token = match c {
+            '\n' => {
+                line += 1;
+                line_start = true;
+                continue;
+            }
+            c if c.is_whitespace() => continue,
+            // preprocessor output lines
+            '#' if line_start => {
+                while chars.next_if(|c| *c != '\n').is_some() {}
+                continue;
+            }
+            '/' if chars.next_if_eq(&'/').is_some() => {
+                while chars.next_if(|c| *c != '\n').is_some() {}
+                continue;
+            }
+            '/' if chars.next_if_eq(&'*').is_some() => {
+                let mut previous = ' ';
+                loop {
+                    match chars.next() {
+                        Some('/') if previous == '*' => break,
+                        Some(c) => {
+                            line += (c == '\n') as usize;
+                            previous = c;
+                        }
+                        None => return Err(DotError::syntax(line, "Unterminated comment")),
+                    }
+                }
+                continue;
+            }
+            '{' => Token::LBrace,
+            '}' => Token::RBrace,
+            '[' => Token::LBracket,
+            ']' => Token::RBracket,
+            '=' => Token::Equals,
+            ';' => Token::Semicolon,
+            ',' => Token::Comma,
+            ':' => Token::Colon,
+            '-' if chars.next_if_eq(&'>').is_some() => Token::EdgeOp { directed: true },
+            '-' if chars.next_if_eq(&'-').is_some() => Token::EdgeOp { directed: false },
+            '+' if is_string(tokens.last()) => {
+                concat = true;
+                continue;
+            }
+            '"' => {
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') if chars.next_if_eq(&'"').is_some() => text.push('"'),
+                        Some('\\') if chars.next_if_eq(&'\\').is_some() => text.push('\\'),
+                        Some('\\') if chars.next_if_eq(&'n').is_some() => text.push('\n'),
+                        // escaped line continuation
+                        Some('\\') if chars.next_if_eq(&'\n').is_some() => line += 1,
+                        Some(c) => {
+                            line += (c == '\n') as usize;
+                            text.push(c);
+                        }
+                        None => return Err(DotError::syntax(line, "Unterminated string")),
+                    }
+                }
+                if std::mem::take(&mut concat)
+                    && let Some((Token::Id { text: previous, .. }, _)) = tokens.last_mut()
+                {
+                    previous.push_str(&text);
+                    continue;
+                }
+                Token::Id {
+                    text,
+                    kind: IdKind::String,
+                }
+            }
+            '<' => {
+                let mut text = String::new();
+                let mut depth = 1;
+                loop {
+                    match chars.next() {
+                        Some('>') if depth == 1 => break,
+                        Some(c) => {
+                            depth += (c == '<') as usize;
+                            depth -= (c == '>') as usize;
+                            line += (c == '\n') as usize;
+                            text.push(c);
+                        }
+                        None => return Err(DotError::syntax(line, "Unterminated HTML string")),
+                    }
+                }
+                Token::Id {
+                    text,
+                    kind: IdKind::String,
+                }
+            }
+            c if c.is_alphanumeric() || matches!(c, '_' | '.' | '-') => {
+                let mut text = String::from(c);
+                while let Some(c) = chars.next_if(|c| c.is_alphanumeric() || matches!(c, '_' | '.'))
+                {
+                    text.push(c);
+                }
+                let kind = match text.parse::<f64>() {
+                    Ok(_) if !text.contains(char::is_alphabetic) => IdKind::Numeral,
+                    _ => IdKind::Name,
+                };
+                Token::Id { text, kind }
+            }
+            c => {
+                return Err(DotError::syntax(
+                    line,
+                    format!("Unexpected character '{c}'"),
+                ));
+            }
+        };
+        if concat {
+            return Err(DotError::syntax(line, "Expected a string after '+'"));
+        }
+        line_start = false;
+        tokens.push((token, line));
+    }
+    Ok(tokens)
+}
+
+fn is_string(token: Option<&(Token, usize)>) -> bool {
+    matches!(
+        token,
+        Some((
+            Token::Id {
+                kind: IdKind::String,
+                ..
+            },
+            _
+        ))
+    )
+}
+
+impl Token {
+    fn describe(&self) -> &'static str {
+        match self {
+            Self::Id { .. } => "an identifier",
+            Self::LBrace => "'{'",
+            Self::RBrace => "'}'",
+            Self::LBracket => "'['",
+            Self::RBracket => "']'",
+            Self::Equals => "'='",
+            Self::Semicolon => "';'",
+            Self::Comma => "','",
+            Self::Colon => "':'",
+            Self::EdgeOp { .. } => "an edge operator",
+        }
+    }
+}
+
+impl DotError {
+    fn syntax(line: usize, message: impl Into<String>) -> Self {
+        Self::Syntax {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for DotError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "Failed to read DOT file: {e}"),
+            Self::Syntax { line, message } => write!(f, "Invalid DOT at line {line}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for DotError {}
+
+impl From<std::io::Error> for DotError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
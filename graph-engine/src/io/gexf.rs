@@ -0,0 +1,416 @@
+//! # GEXF
+//!
+//! Reads [GEXF](https://gexf.net/) files, as written by Gephi.
+//!
+//! Like [GraphML](super::graphml), reading is streaming.
+//!
+//! - Declared `<attributes>` become typed [`Attributes`], with their defaults
+//!   applied to elements which do not set them.
+//! - Node and edge ids are kept in the [`ID_ATTR`] attribute; labels in `label`,
+//!   and edge weights in `weight`.
+//! - Visualization data is kept as `x`, `y`, (`z`), `size`, and `color` (as
+//!   `#rrggbb`) attributes.
+//! - Nested nodes and `pid` references set the [`PARENT_ATTR`] attribute.
+//! - Edges whose `type` differs from the graph's `defaultedgetype` keep it as a
+//!   boolean [`DIRECTED_ATTR`] attribute.
+//! - Dynamic data (spells, time intervals) is ignored.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use super::xml::{self, XmlError, XmlEvent, XmlReader, local_name};
+use super::{DIRECTED_ATTR, ID_ATTR, PARENT_ATTR};
+use crate::graph::{AttrValue, Attributes, Directedness, EdgeId, Graph, NodeId, Symbol};
+
+/// Errors which can occur while reading a GEXF file.
+#[derive(Debug)]
+pub enum GexfError {
+    /// The file is not well-formed XML, or could not be read.
+    Xml(XmlError),
+    /// The file is well-formed XML but not valid GEXF.
+    Invalid { line: usize, message: String },
+}
+
+/// Which elements an attribute declaration applies to.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+enum Class {
+    Node,
+    Edge,
+}
+
+/// An `<attribute>` declaration.
+struct Key {
+    name: Symbol,
+    ty: KeyType,
+    default: Option<AttrValue>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum KeyType {
+    Boolean,
+    Int,
+    Float,
+    String,
+}
+
+/// An open element.
+enum Element {
+    Node(NodeId),
+    Edge(EdgeId),
+    /// An `<attribute>` declaration, whose `<default>` may follow.
+    Attribute(Class, String),
+    /// A `<default>`, collecting its text.
+    Default(String),
+    Other,
+}
+
+/// Parser state while reading a file.
+struct Reader<R: BufRead> {
+    xml: XmlReader<R>,
+    keys: HashMap<(Class, String), Key>,
+    /// Class of the `<attributes>` block being read.
+    class: Class,
+    graph: Option<Graph>,
+    /// GEXF node ids to graph nodes.
+    nodes: HashMap<String, NodeId>,
+    stack: Vec<Element>,
+}
+
+/// Read a GEXF file from disk.
+pub fn load(path: impl AsRef<Path>) -> Result<Graph, GexfError> {
+    let file = File::open(path).map_err(XmlError::Io)?;
+    read(BufReader::new(file))
+}
+
+/// Read a GEXF document.
+///
+/// The graph's directedness is taken from its `defaultedgetype`, with `mutual`
+/// treated as undirected.
+pub fn read(reader: impl BufRead) -> Result<Graph, GexfError> {
+    Reader {
+        xml: XmlReader::new(reader),
+        keys: HashMap::new(),
+        class: Class::Node,
+        graph: None,
+        nodes: HashMap::new(),
+        stack: Vec::new(),
+    }
+    .read()
+}
+
+impl<R: BufRead> Reader<R> {
+    fn read(mut self) -> Result<Graph, GexfError> {
+        loop {
+            match self.xml.next_event()? {
+                XmlEvent::Start { name, attrs } => {
+                    let element = self.start(local_name(&name), &attrs)?;
+                    self.stack.push(element);
+                }
+                XmlEvent::End { .. } => self.end()?,
+                XmlEvent::Text(text) => {
+                    if let Some(Element::Default(value)) = self.stack.last_mut() {
+                        value.push_str(&text);
+                    }
+                }
+                XmlEvent::Eof => break,
+            }
+        }
+        self.graph
+            .ok_or_else(|| GexfError::invalid(self.xml.line(), "The file contains no graph"))
+    }
+
+    fn start(&mut self, name: &str, attrs: &[(String, String)]) -> Result<Element, GexfError> {
+        Ok(match name {
+            "graph" if self.graph.is_none() => {
+                let directedness = match xml::attr(attrs, "defaultedgetype") {
+                    Some("directed") => Directedness::Directed,
+                    _ => Directedness::Undirected,
+                };
+                self.graph = Some(Graph::new(directedness));
+                Element::Other
+            }
+            "attributes" => {
+                self.class = match xml::attr(attrs, "class") {
+                    Some("edge") => Class::Edge,
+                    _ => Class::Node,
+                };
+                Element::Other
+            }
+            "attribute" => {
+                let id = self.required(attrs, "id")?.to_owned();
+                let ty = match xml::attr(attrs, "type").unwrap_or("string") {
+                    "boolean" => KeyType::Boolean,
+                    "integer" | "long" | "short" | "byte" => KeyType::Int,
+                    "float" | "double" | "bigdecimal" => KeyType::Float,
+                    _ => KeyType::String,
+                };
+                let name = Symbol::new(xml::attr(attrs, "title").unwrap_or(&id));
+                self.keys.insert(
+                    (self.class, id.clone()),
+                    Key {
+                        name,
+                        ty,
+                        default: None,
+                    },
+                );
+                Element::Attribute(self.class, id)
+            }
+            "default" if matches!(self.stack.last(), Some(Element::Attribute(..))) => {
+                Element::Default(String::new())
+            }
+            "node" => {
+                let id = self.required(attrs, "id")?.to_owned();
+                let parent = match xml::attr(attrs, "pid") {
+                    Some(pid) => Some(pid.to_owned()),
+                    None => self.parent_id(),
+                };
+                let node = self.node(&id)?;
+                let graph = self.graph.as_mut().unwrap();
+                let node_attrs = graph.node_mut(node).unwrap();
+                if let Some(label) = xml::attr(attrs, "label") {
+                    node_attrs.insert("label", label);
+                }
+                if let Some(parent) = parent {
+                    node_attrs.insert(PARENT_ATTR, parent);
+                }
+                Element::Node(node)
+            }
+            "edge" => {
+                let source = self.required(attrs, "source")?.to_owned();
+                let target = self.required(attrs, "target")?.to_owned();
+                let (source, target) = (self.node(&source)?, self.node(&target)?);
+                let weight = match xml::attr(attrs, "weight") {
+                    Some(w) => Some(self.parse(KeyType::Float, w)?),
+                    None => None,
+                };
+
+                let graph = self.graph.as_mut().unwrap();
+                let directed = graph.is_directed();
+                let edge = graph
+                    .add_edge(source, target)
+                    .expect("Both endpoints were just looked up or created");
+                let edge_attrs = graph.edge_mut(edge).unwrap();
+                if let Some(id) = xml::attr(attrs, "id") {
+                    edge_attrs.insert(ID_ATTR, id);
+                }
+                if let Some(label) = xml::attr(attrs, "label") {
+                    edge_attrs.insert("label", label);
+                }
+                if let Some(weight) = weight {
+                    edge_attrs.insert("weight", weight);
+                }
+                match xml::attr(attrs, "type") {
+                    Some("directed") if !directed => {
+                        edge_attrs.insert(DIRECTED_ATTR, true);
+                    }
+                    Some("undirected" | "mutual") if directed => {
+                        edge_attrs.insert(DIRECTED_ATTR, false);
+                    }
+                    _ => {}
+                }
+                Element::Edge(edge)
+            }
+            "attvalue" => {
+                let id = match xml::attr(attrs, "for").or_else(|| xml::attr(attrs, "id")) {
+                    Some(id) => id.to_owned(),
+                    None => return Err(self.invalid("Missing attribute 'for'")),
+                };
+                let value = self.required(attrs, "value")?.to_owned();
+                self.set_attvalue(&id, &value)?;
+                Element::Other
+            }
+            "color" => {
+                let channel = |c| {
+                    xml::attr(attrs, c)
+                        .and_then(|v| v.trim().parse::<u8>().ok())
+                        .unwrap_or(0)
+                };
+                let color = format!(
+                    "#{:02x}{:02x}{:02x}",
+                    channel("r"),
+                    channel("g"),
+                    channel("b")
+                );
+                self.set_viz([("color", AttrValue::String(color))]);
+                Element::Other
+            }
+            "position" => {
+                let mut values = Vec::new();
+                for axis in ["x", "y", "z"] {
+                    if let Some(v) = xml::attr(attrs, axis) {
+                        values.push((axis, self.parse(KeyType::Float, v)?));
+                    }
+                }
+                self.set_viz(values);
+                Element::Other
+            }
+            "size" => {
+                if let Some(v) = xml::attr(attrs, "value") {
+                    let size = self.parse(KeyType::Float, v)?;
+                    self.set_viz([("size", size)]);
+                }
+                Element::Other
+            }
+            _ => Element::Other,
+        })
+    }
+
+    fn end(&mut self) -> Result<(), GexfError> {
+        match self.stack.pop() {
+            Some(Element::Default(text)) => {
+                if let Some(Element::Attribute(class, id)) = self.stack.last() {
+                    let key = (*class, id.clone());
+                    let value = self.parse(self.keys[&key].ty, &text)?;
+                    self.keys.get_mut(&key).unwrap().default = Some(value);
+                }
+            }
+            Some(Element::Node(node)) => self.apply_defaults(Class::Node, |g| g.node_mut(node)),
+            Some(Element::Edge(edge)) => self.apply_defaults(Class::Edge, |g| g.edge_mut(edge)),
+            Some(_) => {}
+            None => return Err(self.invalid("Unbalanced closing tag")),
+        }
+        Ok(())
+    }
+
+    /// Set an `<attvalue>` on the innermost open node or edge.
+    fn set_attvalue(&mut self, id: &str, value: &str) -> Result<(), GexfError> {
+        let Some(target) = self
+            .stack
+            .iter()
+            .rposition(|e| matches!(e, Element::Node(_) | Element::Edge(_)))
+        else {
+            return Ok(());
+        };
+        let class = match self.stack[target] {
+            Element::Node(_) => Class::Node,
+            _ => Class::Edge,
+        };
+        let (name, ty) = match self.keys.get(&(class, id.to_owned())) {
+            Some(key) => (key.name, key.ty),
+            None => (Symbol::new(id), KeyType::String),
+        };
+        let value = self.parse(ty, value)?;
+
+        let graph = self.graph.as_mut().unwrap();
+        let attrs = match self.stack[target] {
+            Element::Node(node) => graph.node_mut(node),
+            Element::Edge(edge) => graph.edge_mut(edge),
+            _ => None,
+        };
+        if let Some(attrs) = attrs {
+            attrs.insert(name, value);
+        }
+        Ok(())
+    }
+
+    /// Set visualization attributes on the innermost open node or edge.
+    fn set_viz(&mut self, values: impl IntoIterator<Item = (&'static str, AttrValue)>) {
+        let Some(graph) = self.graph.as_mut() else {
+            return;
+        };
+        let attrs = match self.stack.last() {
+            Some(Element::Node(n)) => graph.node_mut(*n),
+            Some(Element::Edge(e)) => graph.edge_mut(*e),
+            _ => None,
+        };
+        if let Some(attrs) = attrs {
+            for (key, value) in values {
+                attrs.insert(key, value);
+            }
+        }
+    }
+
+    /// Set the declared default of every `class` attribute the element does not set.
+    fn apply_defaults(
+        &mut self,
+        class: Class,
+        attrs: impl FnOnce(&mut Graph) -> Option<&mut Attributes>,
+    ) {
+        let Some(attrs) = self.graph.as_mut().and_then(attrs) else {
+            return;
+        };
+        for ((key_class, _), key) in &self.keys {
+            if let Some(default) = &key.default
+                && *key_class == class
+                && !attrs.contains(key.name)
+            {
+                attrs.insert(key.name, default.clone());
+            }
+        }
+    }
+
+    /// The GEXF id of the innermost open node, for nested nodes.
+    fn parent_id(&self) -> Option<String> {
+        let graph = self.graph.as_ref()?;
+        self.stack.iter().rev().find_map(|e| match e {
+            Element::Node(n) => graph.node(*n)?.get(ID_ATTR).map(|v| v.to_string()),
+            _ => None,
+        })
+    }
+
+    /// The node with GEXF id `id`, created if it was not seen yet.
+    fn node(&mut self, id: &str) -> Result<NodeId, GexfError> {
+        if let Some(node) = self.nodes.get(id) {
+            return Ok(*node);
+        }
+        let line = self.xml.line();
+        let graph = self
+            .graph
+            .as_mut()
+            .ok_or_else(|| GexfError::invalid(line, "Element found outside of a <graph>"))?;
+        let node = graph.add_node_with(Attributes::from_iter([(ID_ATTR, id)]));
+        self.nodes.insert(id.to_owned(), node);
+        Ok(node)
+    }
+
+    fn parse(&self, ty: KeyType, text: &str) -> Result<AttrValue, GexfError> {
+        let trimmed = text.trim();
+        let value = match ty {
+            KeyType::Boolean => match trimmed.to_ascii_lowercase().as_str() {
+                "true" | "1" => Some(AttrValue::Bool(true)),
+                "false" | "0" => Some(AttrValue::Bool(false)),
+                _ => None,
+            },
+            KeyType::Int => trimmed.parse().ok().map(AttrValue::Int),
+            KeyType::Float => trimmed.parse().ok().map(AttrValue::Float),
+            KeyType::String => Some(AttrValue::String(text.to_owned())),
+        };
+        value.ok_or_else(|| self.invalid(format!("Invalid {ty:?} value '{trimmed}'")))
+    }
+
+    fn required<'a>(&self, attrs: &'a [(String, String)], key: &str) -> Result<&'a str, GexfError> {
+        xml::attr(attrs, key).ok_or_else(|| self.invalid(format!("Missing attribute '{key}'")))
+    }
+
+    fn invalid(&self, message: impl Into<String>) -> GexfError {
+        GexfError::invalid(self.xml.line(), message)
+    }
+}
+
+impl GexfError {
+    fn invalid(line: usize, message: impl Into<String>) -> Self {
+        Self::Invalid {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for GexfError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Xml(e) => write!(f, "Failed to read GEXF: {e}"),
+            Self::Invalid { line, message } => write!(f, "Invalid GEXF at line {line}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for GexfError {}
+
+impl From<XmlError> for GexfError {
+    fn from(e: XmlError) -> Self {
+        Self::Xml(e)
+    }
+}
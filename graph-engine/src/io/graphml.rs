@@ -0,0 +1,581 @@
+//! # GraphML
+//!
+//! Reads and writes [GraphML](http://graphml.graphdrawing.org/) files.
+//!
+//! Reading is streaming: the file is parsed element by element, so only the
+//! resulting [`Graph`] is held in memory.
+//!
+//! - `<key>` declarations become typed [`Attributes`] on nodes, edges, or the
+//!   graph, with declared defaults applied to elements which do not set them.
+//! - GraphML ids are kept in the [`ID_ATTR`] attribute and reused on export.
+//! - Nested graphs are flattened; nodes inside a nested graph get a [`PARENT_ATTR`]
+//!   attribute holding the id of the node containing them, which makes it a
+//!   [group](crate::group).
+//! - Edges whose `directed` attribute differs from the graph's default keep it as a
+//!   boolean [`DIRECTED_ATTR`] attribute.
+//! - Hyperedges, ports, and `<data>` with element content (eg. yEd graphics) are
+//!   skipped.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+use super::xml::{self, XmlError, XmlEvent, XmlReader, local_name};
+use super::{DIRECTED_ATTR, ID_ATTR, PARENT_ATTR, node_name};
+use crate::graph::{AttrValue, Attributes, Directedness, EdgeId, Graph, NodeId, Symbol};
+
+/// Errors which can occur while reading a GraphML file.
+#[derive(Debug)]
+pub enum GraphMlError {
+    /// The file is not well-formed XML, or could not be read.
+    Xml(XmlError),
+    /// The file is well-formed XML but not valid GraphML.
+    Invalid { line: usize, message: String },
+}
+
+/// Which elements a `<key>` applies to.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Domain {
+    Graph,
+    Node,
+    Edge,
+    All,
+}
+
+/// The declared type of a `<key>`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum KeyType {
+    Boolean,
+    Int,
+    Float,
+    String,
+}
+
+/// A `<key>` declaration.
+struct Key {
+    name: Symbol,
+    domain: Domain,
+    ty: KeyType,
+    default: Option<AttrValue>,
+}
+
+/// An open element, which `<data>` is attached to.
+enum Element {
+    /// The outermost `<graph>`.
+    Graph,
+    /// A nested `<graph>`, whose data is dropped.
+    NestedGraph,
+    Node(NodeId),
+    Edge(EdgeId),
+    Key(String),
+    /// `<data>` or `<default>`, collecting text for the element below it.
+    Value {
+        key: String,
+        text: String,
+    },
+    Other,
+}
+
+/// Parser state while reading a file.
+struct Reader<R: BufRead> {
+    xml: XmlReader<R>,
+    keys: HashMap<String, Key>,
+    graph: Option<Graph>,
+    /// GraphML node ids to graph nodes.
+    nodes: HashMap<String, NodeId>,
+    stack: Vec<Element>,
+}
+
+/// Read a GraphML file from disk.
+pub fn load(path: impl AsRef<Path>) -> Result<Graph, GraphMlError> {
+    let file = File::open(path).map_err(XmlError::Io)?;
+    read(BufReader::new(file))
+}
+
+/// Read a GraphML document.
+///
+/// The graph's directedness is taken from the outermost `<graph>`'s `edgedefault`.
+pub fn read(reader: impl BufRead) -> Result<Graph, GraphMlError> {
+    Reader {
+        xml: XmlReader::new(reader),
+        keys: HashMap::new(),
+        graph: None,
+        nodes: HashMap::new(),
+        stack: Vec::new(),
+    }
+    .read()
+}
+
+/// Write `graph` to a GraphML file on disk.
+pub fn save(graph: &Graph, path: impl AsRef<Path>) -> std::io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write(graph, &mut writer)?;
+    writer.flush()
+}
+
+/// Write `graph` as a GraphML document.
+///
+/// Attribute types are declared from the values found: keys holding both
+/// integers and floats are written as `double`, and any other mix as `string`.
+pub fn write(graph: &Graph, mut out: impl Write) -> std::io::Result<()> {
+    let id = Symbol::new(ID_ATTR);
+    let mut keys = KeyTable::default();
+    for (key, value) in graph.attrs().iter() {
+        keys.add(Domain::Graph, key, value);
+    }
+    for node in graph.nodes() {
+        for (key, value) in graph.node(node).unwrap().iter() {
+            if is_markup(Domain::Node, key, value) {
+                continue;
+            }
+            keys.add(Domain::Node, key, value);
+        }
+    }
+    for edge in graph.edges() {
+        for (key, value) in graph.edge(edge).unwrap().iter() {
+            if is_markup(Domain::Edge, key, value) {
+                continue;
+            }
+            keys.add(Domain::Edge, key, value);
+        }
+    }
+
+    writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        out,
+        r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#
+    )?;
+    for (i, ((domain, name), ty)) in keys.keys.iter().enumerate() {
+        writeln!(
+            out,
+            r#"  <key id="d{i}" for="{}" attr.name="{}" attr.type="{}"/>"#,
+            domain.as_str(),
+            xml::escape(name.as_str()),
+            ty.as_str(),
+        )?;
+    }
+
+    let edgedefault = match graph.directedness() {
+        Directedness::Directed => "directed",
+        Directedness::Undirected => "undirected",
+    };
+    writeln!(out, r#"  <graph id="G" edgedefault="{edgedefault}">"#)?;
+    write_data(&mut out, &keys, Domain::Graph, graph.attrs(), "    ")?;
+
+    let node_id = |node: NodeId| node_name(graph, node);
+    for node in graph.nodes() {
+        let attrs = graph.node(node).unwrap();
+        write!(out, r#"    <node id="{}""#, xml::escape(&node_id(node)))?;
+        write_element_body(&mut out, &keys, Domain::Node, attrs, "node")?;
+    }
+    for edge in graph.edges() {
+        let attrs = graph.edge(edge).unwrap();
+        let (source, target) = graph.endpoints(edge).unwrap();
+        write!(out, "    <edge")?;
+        if let Some(value) = attrs.get(id) {
+            write!(out, r#" id="{}""#, xml::escape(&value.to_string()))?;
+        }
+        if let Some(AttrValue::Bool(directed)) = attrs.get(DIRECTED_ATTR) {
+            write!(out, r#" directed="{directed}""#)?;
+        }
+        write!(
+            out,
+            r#" source="{}" target="{}""#,
+            xml::escape(&node_id(source)),
+            xml::escape(&node_id(target)),
+        )?;
+        write_element_body(&mut out, &keys, Domain::Edge, attrs, "edge")?;
+    }
+
+    writeln!(out, "  </graph>")?;
+    writeln!(out, "</graphml>")
+}
+
+/// Finish a `<node>` or `<edge>` start tag, writing its data and closing it.
+fn write_element_body(
+    out: &mut impl Write,
+    keys: &KeyTable,
+    domain: Domain,
+    attrs: &Attributes,
+    tag: &str,
+) -> std::io::Result<()> {
+    if attrs.iter().all(|(k, v)| is_markup(domain, k, v)) {
+        return writeln!(out, "/>");
+    }
+    writeln!(out, ">")?;
+    write_data(out, keys, domain, attrs, "      ")?;
+    writeln!(out, "    </{tag}>")
+}
+
+fn write_data(
+    out: &mut impl Write,
+    keys: &KeyTable,
+    domain: Domain,
+    attrs: &Attributes,
+    indent: &str,
+) -> std::io::Result<()> {
+    for (key, value) in attrs.iter() {
+        if is_markup(domain, key, value) {
+            continue;
+        }
+        let index = keys.index(domain, key);
+        writeln!(
+            out,
+            r#"{indent}<data key="d{index}">{}</data>"#,
+            xml::escape(&value.to_string())
+        )?;
+    }
+    Ok(())
+}
+
+/// Returns `true` for attributes written as XML attributes of the element
+/// itself rather than as `<data>`.
+fn is_markup(domain: Domain, key: Symbol, value: &AttrValue) -> bool {
+    match domain {
+        Domain::Node => key == Symbol::new(ID_ATTR),
+        Domain::Edge => {
+            key == Symbol::new(ID_ATTR)
+                || (key == Symbol::new(DIRECTED_ATTR) && matches!(value, AttrValue::Bool(_)))
+        }
+        Domain::Graph | Domain::All => false,
+    }
+}
+
+/// Keys found while exporting, in first-seen order.
+#[derive(Default)]
+struct KeyTable {
+    keys: Vec<((Domain, Symbol), KeyType)>,
+}
+
+impl KeyTable {
+    fn add(&mut self, domain: Domain, name: Symbol, value: &AttrValue) {
+        let ty = KeyType::of(value);
+        match self.keys.iter_mut().find(|(k, _)| *k == (domain, name)) {
+            Some((_, existing)) => *existing = existing.merge(ty),
+            None => self.keys.push(((domain, name), ty)),
+        }
+    }
+
+    fn index(&self, domain: Domain, name: Symbol) -> usize {
+        self.keys
+            .iter()
+            .position(|(k, _)| *k == (domain, name))
+            .expect("Every written attribute was added to the key table")
+    }
+}
+
+impl<R: BufRead> Reader<R> {
+    fn read(mut self) -> Result<Graph, GraphMlError> {
+        loop {
+            match self.xml.next_event()? {
+                XmlEvent::Start { name, attrs } => self.start(local_name(&name), &attrs)?,
+                XmlEvent::End { .. } => self.end()?,
+                XmlEvent::Text(text) => {
+                    if let Some(Element::Value { text: value, .. }) = self.stack.last_mut() {
+                        value.push_str(&text);
+                    }
+                }
+                XmlEvent::Eof => break,
+            }
+        }
+        self.graph
+            .ok_or_else(|| GraphMlError::invalid(self.xml.line(), "The file contains no graph"))
+    }
+
+    fn start(&mut self, name: &str, attrs: &[(String, String)]) -> Result<(), GraphMlError> {
+        // Anything inside <data> is application-specific markup.
+        if self
+            .stack
+            .iter()
+            .any(|e| matches!(e, Element::Value { .. }))
+        {
+            self.stack.push(Element::Other);
+            return Ok(());
+        }
+
+        let element = match name {
+            "key" => {
+                let id = self.required(attrs, "id")?.to_owned();
+                let domain = match xml::attr(attrs, "for").unwrap_or("all") {
+                    "graph" => Domain::Graph,
+                    "node" => Domain::Node,
+                    "edge" => Domain::Edge,
+                    _ => Domain::All,
+                };
+                let ty = match xml::attr(attrs, "attr.type").unwrap_or("string") {
+                    "boolean" => KeyType::Boolean,
+                    "int" | "long" => KeyType::Int,
+                    "float" | "double" => KeyType::Float,
+                    _ => KeyType::String,
+                };
+                let name = Symbol::new(xml::attr(attrs, "attr.name").unwrap_or(&id));
+                self.keys.insert(
+                    id.clone(),
+                    Key {
+                        name,
+                        domain,
+                        ty,
+                        default: None,
+                    },
+                );
+                Element::Key(id)
+            }
+            "default" => match self.stack.last() {
+                Some(Element::Key(key)) => Element::Value {
+                    key: key.clone(),
+                    text: String::new(),
+                },
+                _ => Element::Other,
+            },
+            "data" => Element::Value {
+                key: self.required(attrs, "key")?.to_owned(),
+                text: String::new(),
+            },
+            "graph" => match self.graph {
+                None => {
+                    let directedness = match xml::attr(attrs, "edgedefault") {
+                        Some("undirected") => Directedness::Undirected,
+                        _ => Directedness::Directed,
+                    };
+                    self.graph = Some(Graph::new(directedness));
+                    Element::Graph
+                }
+                Some(_) => Element::NestedGraph,
+            },
+            "node" => {
+                let id = self.required(attrs, "id")?.to_owned();
+                let parent = self.stack.iter().rev().find_map(|e| match e {
+                    Element::Node(parent) => Some(*parent),
+                    _ => None,
+                });
+                let parent_id = parent
+                    .and_then(|p| self.graph()?.node(p)?.get(ID_ATTR))
+                    .cloned();
+                let node = self.node(&id)?;
+                if let Some(parent_id) = parent_id {
+                    self.graph_mut()?
+                        .node_mut(node)
+                        .unwrap()
+                        .insert(PARENT_ATTR, parent_id);
+                }
+                Element::Node(node)
+            }
+            "edge" => {
+                let source = self.required(attrs, "source")?.to_owned();
+                let target = self.required(attrs, "target")?.to_owned();
+                let (source, target) = (self.node(&source)?, self.node(&target)?);
+
+                let graph = self.graph_mut()?;
+                let edge = graph
+                    .add_edge(source, target)
+                    .expect("Both endpoints were just looked up or created");
+                let directed = graph.is_directed();
+                let attrs_out = graph.edge_mut(edge).unwrap();
+                if let Some(id) = xml::attr(attrs, "id") {
+                    attrs_out.insert(ID_ATTR, id);
+                }
+                match xml::attr(attrs, "directed") {
+                    Some("true") if !directed => {
+                        attrs_out.insert(DIRECTED_ATTR, true);
+                    }
+                    Some("false") if directed => {
+                        attrs_out.insert(DIRECTED_ATTR, false);
+                    }
+                    _ => {}
+                }
+                Element::Edge(edge)
+            }
+            _ => Element::Other,
+        };
+        self.stack.push(element);
+        Ok(())
+    }
+
+    fn end(&mut self) -> Result<(), GraphMlError> {
+        let line = self.xml.line();
+        match self.stack.pop() {
+            Some(Element::Value { key, text }) => match self.stack.last() {
+                Some(Element::Key(id)) => {
+                    let id = id.clone();
+                    let ty = self.keys[&id].ty;
+                    let value = parse_value(ty, &text)
+                        .map_err(|message| GraphMlError::invalid(line, message))?;
+                    self.keys.get_mut(&id).unwrap().default = Some(value);
+                }
+                Some(Element::Graph | Element::Node(_) | Element::Edge(_)) => {
+                    // <data> holding only markup (eg. yEd graphics) carries no value.
+                    if text.trim().is_empty() {
+                        return Ok(());
+                    }
+                    let (name, ty) = match self.keys.get(&key) {
+                        Some(k) => (k.name, k.ty),
+                        None => (Symbol::new(&key), KeyType::String),
+                    };
+                    let value = parse_value(ty, &text)
+                        .map_err(|message| GraphMlError::invalid(line, message))?;
+                    let target = self.stack.last().unwrap();
+                    let graph = self.graph.as_mut().unwrap();
+                    let attrs = match target {
+                        Element::Graph => Some(graph.attrs_mut()),
+                        Element::Node(n) => graph.node_mut(*n),
+                        Element::Edge(e) => graph.edge_mut(*e),
+                        _ => None,
+                    };
+                    if let Some(attrs) = attrs {
+                        attrs.insert(name, value);
+                    }
+                }
+                _ => {}
+            },
+            Some(Element::Node(node)) => self.apply_defaults(Domain::Node, |g| g.node_mut(node)),
+            Some(Element::Edge(edge)) => self.apply_defaults(Domain::Edge, |g| g.edge_mut(edge)),
+            Some(Element::Graph) => self.apply_defaults(Domain::Graph, |g| Some(g.attrs_mut())),
+            Some(_) => {}
+            None => return Err(GraphMlError::invalid(line, "Unbalanced closing tag")),
+        }
+        Ok(())
+    }
+
+    /// Set the declared default of every `domain` key the element does not set.
+    fn apply_defaults(
+        &mut self,
+        domain: Domain,
+        attrs: impl FnOnce(&mut Graph) -> Option<&mut Attributes>,
+    ) {
+        let Some(attrs) = self.graph.as_mut().and_then(attrs) else {
+            return;
+        };
+        for key in self.keys.values() {
+            if let Some(default) = &key.default
+                && (key.domain == domain || key.domain == Domain::All)
+                && !attrs.contains(key.name)
+            {
+                attrs.insert(key.name, default.clone());
+            }
+        }
+    }
+
+    /// The node with GraphML id `id`, created if it was not seen yet, since edges
+    /// may reference nodes declared after them.
+    fn node(&mut self, id: &str) -> Result<NodeId, GraphMlError> {
+        if let Some(node) = self.nodes.get(id) {
+            return Ok(*node);
+        }
+        let node = self
+            .graph_mut()?
+            .add_node_with(Attributes::from_iter([(ID_ATTR, id)]));
+        self.nodes.insert(id.to_owned(), node);
+        Ok(node)
+    }
+
+    fn graph(&self) -> Option<&Graph> {
+        self.graph.as_ref()
+    }
+
+    fn graph_mut(&mut self) -> Result<&mut Graph, GraphMlError> {
+        let line = self.xml.line();
+        self.graph
+            .as_mut()
+            .ok_or_else(|| GraphMlError::invalid(line, "Element found outside of a <graph>"))
+    }
+
+    fn required<'a>(
+        &self,
+        attrs: &'a [(String, String)],
+        key: &str,
+    ) -> Result<&'a str, GraphMlError> {
+        xml::attr(attrs, key).ok_or_else(|| {
+            GraphMlError::invalid(self.xml.line(), format!("Missing attribute '{key}'"))
+        })
+    }
+}
+
+/// Parse the text of a `<data>` or `<default>` element as `ty`.
+fn parse_value(ty: KeyType, text: &str) -> Result<AttrValue, String> {
+    let trimmed = text.trim();
+    match ty {
+        KeyType::Boolean => match trimmed.to_ascii_lowercase().as_str() {
+            "true" | "1" => Ok(AttrValue::Bool(true)),
+            "false" | "0" => Ok(AttrValue::Bool(false)),
+            _ => Err(format!("Expected a boolean, found '{trimmed}'")),
+        },
+        KeyType::Int => trimmed
+            .parse()
+            .map(AttrValue::Int)
+            .map_err(|_| format!("Expected an integer, found '{trimmed}'")),
+        KeyType::Float => trimmed
+            .parse()
+            .map(AttrValue::Float)
+            .map_err(|_| format!("Expected a number, found '{trimmed}'")),
+        KeyType::String => Ok(AttrValue::String(text.to_owned())),
+    }
+}
+
+impl Domain {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Graph => "graph",
+            Self::Node => "node",
+            Self::Edge => "edge",
+            Self::All => "all",
+        }
+    }
+}
+
+impl KeyType {
+    fn of(value: &AttrValue) -> Self {
+        match value {
+            AttrValue::Bool(_) => Self::Boolean,
+            AttrValue::Int(_) => Self::Int,
+            AttrValue::Float(_) => Self::Float,
+            AttrValue::String(_) => Self::String,
+        }
+    }
+
+    /// The narrowest type able to hold values of both `self` and `other`.
+    fn merge(self, other: Self) -> Self {
+        match (self, other) {
+            (a, b) if a == b => a,
+            (Self::Int, Self::Float) | (Self::Float, Self::Int) => Self::Float,
+            _ => Self::String,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Boolean => "boolean",
+            Self::Int => "long",
+            Self::Float => "double",
+            Self::String => "string",
+        }
+    }
+}
+
+impl GraphMlError {
+    fn invalid(line: usize, message: impl Into<String>) -> Self {
+        Self::Invalid {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for GraphMlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Xml(e) => write!(f, "Failed to read GraphML: {e}"),
+            Self::Invalid { line, message } => {
+                write!(f, "Invalid GraphML at line {line}: {message}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for GraphMlError {}
+
+impl From<XmlError> for GraphMlError {
+    fn from(e: XmlError) -> Self {
+        Self::Xml(e)
+    }
+}
@@ -0,0 +1,365 @@
+//! # HTTP Client
+//!
+//! A minimal HTTP/1.1 client for the database connectors, sending a single
+//! request per connection to `http://` URLs, or `https://` ones over TLS
+//! checked against the Mozilla root certificates. Chunked responses are
+//! decoded, and responses with an error status become [`HttpError::Status`].
+//! Hosts may be IPv6 addresses in brackets, eg. `http://[::1]:7474`.
+//!
+//! Credentials sent to a plain `http://` URL can be read by anyone on the
+//! way, which [`is_cleartext()`] tells the connectors to warn about.
+//!
+//! ```ignore
+//! let auth = basic_auth("neo4j", "secret");
+//! let response = post(
+//!     "http://localhost:7474/db/neo4j/tx/commit",
+//!     &[("Content-Type", "application/json"), ("Authorization", &auth)],
+//!     body,
+//! )?;
+//! ```
+
+use std::io::{ErrorKind, Read, Write};
+use std::net::{IpAddr, TcpStream};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use rustls::pki_types::ServerName;
+use rustls::{ClientConfig, ClientConnection, RootCertStore, StreamOwned};
+
+use crate::i18n::tr;
+
+/// Port used when an `http://` URL has none.
+const DEFAULT_PORT: u16 = 80;
+/// Port used when an `https://` URL has none.
+const DEFAULT_TLS_PORT: u16 = 443;
+
+/// How long to wait for the server before giving up.
+const TIMEOUT: Duration = Duration::from_secs(30);
+/// Largest response read, in bytes.
+const MAX_RESPONSE: u64 = 256 << 20;
+
+/// The parts of a URL requests are sent to.
+#[derive(Debug, PartialEq)]
+struct Url {
+    tls: bool,
+    /// The host name or address, without the brackets of IPv6 addresses.
+    host: String,
+    port: u16,
+    path: String,
+}
+
+/// A successful response.
+pub(crate) struct Response {
+    /// Header names and values, in the order received.
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+/// Errors which can occur while sending a request.
+#[derive(Debug)]
+pub enum HttpError {
+    Io(std::io::Error),
+    /// The URL is not an `http://` or `https://` one.
+    UnsupportedUrl(String),
+    /// The response is larger than [`MAX_RESPONSE`].
+    TooLarge,
+    /// The response is not valid HTTP.
+    Malformed(&'static str),
+    /// The server answered with an error status, and this body.
+    Status(u16, String),
+}
+
+/// Send a POST request with `headers` and `body` to `url`.
+pub(crate) fn post(
+    url: &str,
+    headers: &[(&str, &str)],
+    body: &[u8],
+) -> Result<Response, HttpError> {
+    let url = parse_url(url)?;
+    let stream = TcpStream::connect((url.host.as_str(), url.port))?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+
+    let host = match url.host.parse::<IpAddr>() {
+        Ok(IpAddr::V6(_)) => format!("[{}]", url.host),
+        _ => url.host.clone(),
+    };
+    let mut request = format!(
+        "POST {} HTTP/1.1\r\n\
+         Host: {host}:{}\r\n\
+         Content-Length: {}\r\n\
+         Connection: close\r\n",
+        url.path,
+        url.port,
+        body.len()
+    );
+    for (name, value) in headers {
+        request.push_str(&format!("{name}: {value}\r\n"));
+    }
+    request.push_str("\r\n");
+
+    let response = match url.tls {
+        true => {
+            let name = ServerName::try_from(url.host)
+                .map_err(|_| HttpError::UnsupportedUrl(host.clone()))?;
+            let connection = ClientConnection::new(tls_config(), name)
+                .map_err(|e| std::io::Error::other(e.to_string()))?;
+            exchange(StreamOwned::new(connection, stream), &request, body)?
+        }
+        false => exchange(stream, &request, body)?,
+    };
+    parse_response(&response)
+}
+
+/// Returns `true` if what is sent to `url`, eg. credentials, can be read on
+/// the way: it is a plain `http://` URL to another host than this one.
+pub(crate) fn is_cleartext(url: &str) -> bool {
+    let Ok(url) = parse_url(url) else {
+        return false;
+    };
+    let local = match url.host.parse::<IpAddr>() {
+        Ok(ip) => ip.is_loopback(),
+        Err(_) => url.host.eq_ignore_ascii_case("localhost"),
+    };
+    !url.tls && !local
+}
+
+/// Send `request` and `body` over `stream`, and read the response until the
+/// server closes the connection.
+fn exchange(
+    mut stream: impl Read + Write,
+    request: &str,
+    body: &[u8],
+) -> Result<Vec<u8>, HttpError> {
+    stream.write_all(request.as_bytes())?;
+    stream.write_all(body)?;
+    stream.flush()?;
+
+    let mut response = Vec::new();
+    match stream.take(MAX_RESPONSE + 1).read_to_end(&mut response) {
+        Ok(_) => {}
+        // servers may close TLS connections without notifying it first, the
+        // response then ends as declared by its headers
+        Err(e) if e.kind() == ErrorKind::UnexpectedEof && !response.is_empty() => {}
+        Err(e) => return Err(e.into()),
+    }
+    if response.len() as u64 > MAX_RESPONSE {
+        return Err(HttpError::TooLarge);
+    }
+    Ok(response)
+}
+
+/// The TLS configuration of every connection, trusting the Mozilla root
+/// certificates.
+fn tls_config() -> Arc<ClientConfig> {
+    static CONFIG: OnceLock<Arc<ClientConfig>> = OnceLock::new();
+    CONFIG
+        .get_or_init(|| {
+            let roots = RootCertStore {
+                roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+            };
+            let config = ClientConfig::builder()
+                .with_root_certificates(roots)
+                .with_no_client_auth();
+            Arc::new(config)
+        })
+        .clone()
+}
+
+/// The value of an `Authorization` header for `user` and `password`.
+pub(crate) fn basic_auth(user: &str, password: &str) -> String {
+    format!("Basic {}", base64(format!("{user}:{password}").as_bytes()))
+}
+
+/// Percent-encode `text` for a URL query or form body.
+#[cfg(feature = "sparql")]
+pub(crate) fn url_encode(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+impl Response {
+    /// The value of the header `name`, compared case-insensitively.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Split an `http://host:port/path` or `https://` URL into its parts.
+fn parse_url(url: &str) -> Result<Url, HttpError> {
+    let unsupported = || HttpError::UnsupportedUrl(url.into());
+    let url = url.trim();
+    let (tls, rest) = match (url.strip_prefix("https://"), url.strip_prefix("http://")) {
+        (Some(rest), _) => (true, rest),
+        (None, Some(rest)) => (false, rest),
+        (None, None) => return Err(unsupported()),
+    };
+    let (authority, path) = match rest.find('/') {
+        Some(index) => rest.split_at(index),
+        None => (rest, "/"),
+    };
+    // IPv6 addresses are in brackets, as their colons would be taken for the
+    // port's
+    let (host, port) = match authority.strip_prefix('[') {
+        Some(bracketed) => {
+            let (host, rest) = bracketed.split_once(']').ok_or_else(unsupported)?;
+            host.parse::<std::net::Ipv6Addr>()
+                .map_err(|_| unsupported())?;
+            (host, rest.strip_prefix(':'))
+        }
+        None => match authority.split_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (authority, None),
+        },
+    };
+    let port = match port {
+        Some(port) => port.parse().map_err(|_| unsupported())?,
+        None if tls => DEFAULT_TLS_PORT,
+        None => DEFAULT_PORT,
+    };
+    if host.is_empty() {
+        return Err(unsupported());
+    }
+    Ok(Url {
+        tls,
+        host: host.into(),
+        port,
+        path: path.into(),
+    })
+}
+
+/// Split an HTTP/1.1 response into its headers and body, decoding chunked
+/// transfer encoding.
+fn parse_response(response: &[u8]) -> Result<Response, HttpError> {
+    let split = response
+        .windows(4)
+        .position(|w| w == b"\r\n\r\n")
+        .ok_or(HttpError::Malformed("incomplete response"))?;
+    let head = String::from_utf8_lossy(&response[..split]);
+    let mut body = response[split + 4..].to_vec();
+
+    let mut lines = head.lines();
+    let status: u16 = lines
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .and_then(|code| code.parse().ok())
+        .ok_or(HttpError::Malformed("invalid status line"))?;
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+        .collect();
+    let response = Response {
+        headers,
+        body: Vec::new(),
+    };
+    if response
+        .header("transfer-encoding")
+        .is_some_and(|value| value.eq_ignore_ascii_case("chunked"))
+    {
+        body = dechunk(&body)?;
+    }
+    if !(200..300).contains(&status) {
+        let message = String::from_utf8_lossy(&body).trim().to_string();
+        return Err(HttpError::Status(status, message));
+    }
+    Ok(Response { body, ..response })
+}
+
+/// Decode a body sent with chunked transfer encoding.
+fn dechunk(mut body: &[u8]) -> Result<Vec<u8>, HttpError> {
+    let invalid = HttpError::Malformed("invalid chunked body");
+    let mut decoded = Vec::with_capacity(body.len());
+    loop {
+        let Some(end) = body.windows(2).position(|w| w == b"\r\n") else {
+            return Err(invalid);
+        };
+        let size = String::from_utf8_lossy(&body[..end]);
+        // chunk extensions follow a `;`
+        let size = size.split(';').next().unwrap_or_default().trim();
+        let Ok(size) = usize::from_str_radix(size, 16) else {
+            return Err(invalid);
+        };
+        if size == 0 {
+            return Ok(decoded);
+        }
+        // sizes near usize::MAX must not wrap around to a valid range
+        let Some(chunk_end) = (end + 2).checked_add(size) else {
+            return Err(invalid);
+        };
+        let (Some(chunk), Some(rest)) = (
+            body.get(end + 2..chunk_end),
+            chunk_end.checked_add(2).and_then(|start| body.get(start..)),
+        ) else {
+            return Err(invalid);
+        };
+        decoded.extend_from_slice(chunk);
+        body = rest;
+    }
+}
+
+/// Standard base64 encoding of `bytes`, with padding.
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+impl std::fmt::Display for HttpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            Self::Io(e) => {
+                tr("Failed to reach the server: {error}").replace("{error}", &e.to_string())
+            }
+            Self::UnsupportedUrl(url) => {
+                tr("Unsupported URL '{url}', expected http://host:port/path or https://")
+                    .replace("{url}", url)
+            }
+            Self::TooLarge => tr("The response is larger than {size} bytes")
+                .replace("{size}", &MAX_RESPONSE.to_string()),
+            Self::Malformed(e) => tr("Invalid HTTP response: {error}").replace("{error}", e),
+            Self::Status(status, message) => return write!(f, "HTTP {status}: {message}"),
+        };
+        f.write_str(&text)
+    }
+}
+
+impl std::error::Error for HttpError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for HttpError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
@@ -0,0 +1,345 @@
+//! # Node-Link JSON
+//!
+//! Reads and writes the node-link JSON convention used by d3 and networkx:
+//! ```json
+//! {
+//!   "directed": true,
+//!   "graph": { "name": "example" },
+//!   "nodes": [{ "id": "a" }, { "id": "b", "size": 2 }],
+//!   "links": [{ "source": "a", "target": "b", "weight": 1.5 }]
+//! }
+//! ```
+//!
+//! - Node ids are kept in the [`ID_ATTR`] attribute, with their JSON type, and
+//!   reused on export.
+//! - Links may name their endpoints by id, by index into `nodes` (as d3 allows),
+//!   or by a node object with an `id` or `index`. `edges` is accepted in place
+//!   of `links`.
+//! - Nested objects and arrays are flattened into dotted attribute names, eg.
+//!   `{"pos": {"x": 1}}` becomes `pos.x` and `{"tags": ["a"]}` becomes `tags.0`,
+//!   and are nested again on export. `null`s and empty containers are dropped.
+//! - Graphs without a `directed` key are read as directed.
+
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use serde_json::{Map, Number, Value};
+
+use super::ID_ATTR;
+use crate::graph::{AttrValue, Attributes, Directedness, Graph, NodeId};
+
+/// Separator between the parts of a flattened attribute name.
+pub const PATH_SEPARATOR: char = '.';
+
+/// Errors which can occur while reading a node-link JSON file.
+#[derive(Debug)]
+pub enum JsonError {
+    Io(std::io::Error),
+    /// The file is not valid JSON.
+    Parse(serde_json::Error),
+    /// The file is valid JSON but not a node-link graph.
+    Invalid(String),
+}
+
+/// Read a node-link JSON file from disk.
+pub fn load(path: impl AsRef<Path>) -> Result<Graph, JsonError> {
+    read(BufReader::new(File::open(path)?))
+}
+
+/// Read a node-link JSON document.
+pub fn read(reader: impl Read) -> Result<Graph, JsonError> {
+    from_value(serde_json::from_reader(reader)?)
+}
+
+/// Build a graph from a parsed node-link JSON document.
+pub fn from_value(doc: Value) -> Result<Graph, JsonError> {
+    let Value::Object(doc) = doc else {
+        return Err(JsonError::invalid("expected an object at the top level"));
+    };
+
+    let mut graph = match doc.get("directed") {
+        None => Graph::directed(),
+        Some(Value::Bool(true)) => Graph::directed(),
+        Some(Value::Bool(false)) => Graph::undirected(),
+        Some(_) => return Err(JsonError::invalid("'directed' must be a boolean")),
+    };
+    match doc.get("graph") {
+        None | Some(Value::Null) => {}
+        Some(Value::Object(attrs)) => flatten_into(attrs, graph.attrs_mut()),
+        Some(_) => return Err(JsonError::invalid("'graph' must be an object")),
+    }
+
+    let nodes = match doc.get("nodes") {
+        Some(Value::Array(nodes)) => nodes.as_slice(),
+        None => &[],
+        Some(_) => return Err(JsonError::invalid("'nodes' must be an array")),
+    };
+    // node ids keyed by their JSON text, so `1` and `"1"` stay distinct
+    let mut ids = HashMap::new();
+    let mut by_index = Vec::with_capacity(nodes.len());
+    for (index, node) in nodes.iter().enumerate() {
+        let Value::Object(fields) = node else {
+            return Err(JsonError::invalid(format!("node {index} is not an object")));
+        };
+        let mut attrs = Attributes::default();
+        flatten_into(fields, &mut attrs);
+        let id = graph.add_node_with(attrs);
+        if let Some(key) = fields.get(ID_ATTR).and_then(id_key)
+            && ids.insert(key, id).is_some()
+        {
+            return Err(JsonError::invalid(format!(
+                "duplicate node id {}",
+                fields[ID_ATTR]
+            )));
+        }
+        by_index.push(id);
+    }
+
+    let links = match doc.get("links").or_else(|| doc.get("edges")) {
+        Some(Value::Array(links)) => links.as_slice(),
+        None => &[],
+        Some(_) => return Err(JsonError::invalid("'links' must be an array")),
+    };
+    for (index, link) in links.iter().enumerate() {
+        let Value::Object(fields) = link else {
+            return Err(JsonError::invalid(format!("link {index} is not an object")));
+        };
+        let endpoint = |name: &str| {
+            let value = fields
+                .get(name)
+                .ok_or_else(|| JsonError::invalid(format!("link {index} has no '{name}'")))?;
+            resolve(value, &ids, &by_index).ok_or_else(|| {
+                JsonError::invalid(format!("link {index} has an unknown {name} {value}"))
+            })
+        };
+        let source = endpoint("source")?;
+        let target = endpoint("target")?;
+
+        let mut attrs = Attributes::default();
+        flatten_into(fields, &mut attrs);
+        attrs.remove("source");
+        attrs.remove("target");
+        graph
+            .add_edge_with(source, target, attrs)
+            .expect("Tried to add a link between nodes which were just added");
+    }
+
+    Ok(graph)
+}
+
+/// Write `graph` to a node-link JSON file on disk.
+pub fn save(graph: &Graph, path: impl AsRef<Path>) -> std::io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write(graph, &mut writer)?;
+    writer.flush()
+}
+
+/// Write `graph` as a pretty-printed node-link JSON document.
+pub fn write(graph: &Graph, out: impl Write) -> std::io::Result<()> {
+    serde_json::to_writer_pretty(out, &to_value(graph))?;
+    Ok(())
+}
+
+/// Convert `graph` to a node-link JSON document.
+///
+/// `multigraph` is set if the graph has parallel edges.
+pub fn to_value(graph: &Graph) -> Value {
+    let node_id = |node: NodeId| match graph.node(node).and_then(|a| a.get(ID_ATTR)) {
+        Some(value) => to_json(value),
+        None => Value::String(node.to_string()),
+    };
+
+    let nodes = graph
+        .nodes()
+        .map(|node| {
+            let mut fields = unflatten(graph.node(node).unwrap());
+            fields.insert(ID_ATTR.to_owned(), node_id(node));
+            Value::Object(fields)
+        })
+        .collect();
+
+    let mut pairs = HashSet::new();
+    let mut multigraph = false;
+    let links = graph
+        .edges()
+        .map(|edge| {
+            let (source, target) = graph.endpoints(edge).unwrap();
+            let pair = match graph.directedness() {
+                Directedness::Undirected if target < source => (target, source),
+                _ => (source, target),
+            };
+            multigraph |= !pairs.insert(pair);
+
+            let mut fields = unflatten(graph.edge(edge).unwrap());
+            fields.insert("source".to_owned(), node_id(source));
+            fields.insert("target".to_owned(), node_id(target));
+            Value::Object(fields)
+        })
+        .collect();
+
+    let mut doc = Map::new();
+    doc.insert("directed".to_owned(), Value::Bool(graph.is_directed()));
+    doc.insert("multigraph".to_owned(), Value::Bool(multigraph));
+    doc.insert("graph".to_owned(), Value::Object(unflatten(graph.attrs())));
+    doc.insert("nodes".to_owned(), Value::Array(nodes));
+    doc.insert("links".to_owned(), Value::Array(links));
+    Value::Object(doc)
+}
+
+/// The key a node id is looked up by, or `None` if it cannot be an id.
+fn id_key(id: &Value) -> Option<String> {
+    match id {
+        Value::String(_) | Value::Number(_) | Value::Bool(_) => Some(id.to_string()),
+        _ => None,
+    }
+}
+
+/// Find the node a link endpoint refers to: by id, then by index into `nodes`.
+fn resolve(value: &Value, ids: &HashMap<String, NodeId>, by_index: &[NodeId]) -> Option<NodeId> {
+    if let Value::Object(node) = value {
+        // d3 replaces endpoints with the node objects themselves
+        return node
+            .get(ID_ATTR)
+            .and_then(|id| resolve(id, ids, by_index))
+            .or_else(|| by_index.get(node.get("index")?.as_u64()? as usize).copied());
+    }
+    if let Some(node) = id_key(value).and_then(|key| ids.get(&key)) {
+        return Some(*node);
+    }
+    by_index.get(value.as_u64()? as usize).copied()
+}
+
+/// Add `fields` to `attrs`, flattening nested values into dotted names.
+fn flatten_into(fields: &Map<String, Value>, attrs: &mut Attributes) {
+    for (key, value) in fields {
+        flatten(key, value, attrs);
+    }
+}
+
+/// Add `value` to `attrs` as `name`, flattening nested values into dotted names.
+pub(crate) fn flatten(name: &str, value: &Value, attrs: &mut Attributes) {
+    match value {
+        Value::Null => {}
+        Value::Bool(b) => {
+            attrs.insert(name, *b);
+        }
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => {
+                attrs.insert(name, i);
+            }
+            None => {
+                attrs.insert(name, n.as_f64().unwrap_or(f64::NAN));
+            }
+        },
+        Value::String(s) => {
+            attrs.insert(name, s.as_str());
+        }
+        Value::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                flatten(&format!("{name}{PATH_SEPARATOR}{i}"), item, attrs);
+            }
+        }
+        Value::Object(fields) => {
+            for (key, item) in fields {
+                flatten(&format!("{name}{PATH_SEPARATOR}{key}"), item, attrs);
+            }
+        }
+    }
+}
+
+/// Nest dotted attribute names back into objects, turning objects whose keys
+/// are exactly `0..n` into arrays.
+///
+/// A name whose prefix is already a plain value, eg. `a.b` next to `a`, is kept
+/// as is.
+pub(crate) fn unflatten(attrs: &Attributes) -> Map<String, Value> {
+    let mut root = Map::new();
+    for (key, value) in attrs.iter() {
+        let name = key.as_str();
+        let mut parts = name.split(PATH_SEPARATOR).peekable();
+        let mut fields = &mut root;
+        let mut nested = true;
+        while let Some(part) = parts.next() {
+            if parts.peek().is_none() {
+                fields.insert(part.to_owned(), to_json(value));
+                break;
+            }
+            let entry = fields
+                .entry(part.to_owned())
+                .or_insert_with(|| Value::Object(Map::new()));
+            match entry {
+                Value::Object(inner) => fields = inner,
+                _ => {
+                    nested = false;
+                    break;
+                }
+            }
+        }
+        if !nested {
+            root.insert(name.to_owned(), to_json(value));
+        }
+    }
+
+    for value in root.values_mut() {
+        restore_arrays(value);
+    }
+    root
+}
+
+fn restore_arrays(value: &mut Value) {
+    let Value::Object(fields) = value else {
+        return;
+    };
+    for item in fields.values_mut() {
+        restore_arrays(item);
+    }
+    let is_array = (0..fields.len()).all(|i| fields.contains_key(&i.to_string()));
+    if is_array && !fields.is_empty() {
+        let items = (0..fields.len())
+            .map(|i| fields.remove(&i.to_string()).unwrap())
+            .collect();
+        *value = Value::Array(items);
+    }
+}
+
+pub(crate) fn to_json(value: &AttrValue) -> Value {
+    match value {
+        AttrValue::Bool(b) => Value::Bool(*b),
+        AttrValue::Int(i) => Value::from(*i),
+        // JSON has no NaN or infinity
+        AttrValue::Float(f) => Number::from_f64(*f).map_or(Value::Null, Value::Number),
+        AttrValue::String(s) => Value::String(s.clone()),
+    }
+}
+
+impl JsonError {
+    fn invalid(message: impl Into<String>) -> Self {
+        Self::Invalid(message.into())
+    }
+}
+
+impl std::fmt::Display for JsonError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "{e}"),
+            Self::Parse(e) => write!(f, "Failed to parse JSON: {e}"),
+            Self::Invalid(message) => write!(f, "Invalid node-link JSON: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for JsonError {}
+
+impl From<std::io::Error> for JsonError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for JsonError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Parse(e)
+    }
+}
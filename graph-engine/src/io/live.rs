@@ -0,0 +1,495 @@
+//! # Live Graph Updates
+//!
+//! A [`LiveServer`] listens on a TCP port for graph mutations, and applies them
+//! to a document as they arrive, eg. to watch a service topology evolve. Each
+//! connection sends [`LiveEvent`]s as JSON objects, one per line:
+//! ```json
+//! {"op": "add_node", "id": "api", "attrs": {"kind": "service"}}
+//! {"op": "add_edge", "id": "api-db", "source": "api", "target": "db"}
+//! {"op": "update_node", "id": "api", "attrs": {"load": 0.8, "kind": null}}
+//! {"op": "remove_edge", "id": "api-db"}
+//! {"op": "remove_node", "id": "db"}
+//! {"op": "clear"}
+//! ```
+//!
+//! - Elements are named by their [`ID_ATTR`], a JSON string or number, which
+//!   nodes and edges already in the document are found by too.
+//! - Attributes are flattened like in [`json`](super::json) files. Updates
+//!   merge them into the element's, removing those set to `null`.
+//! - Adding an element which exists updates it instead, and adding an edge
+//!   adds its missing endpoints.
+//! - Lines which are not valid events are answered with
+//!   `{"error": "<message>"}`, and the connection stays open.
+//!
+//! Connections are read on worker threads. The server is stepped from
+//! [`EngineContext::refresh()`] while its document is active, see
+//! [`EngineContext::live`], and controlled from the [`LivePanel`].
+//!
+//! ```ignore
+//! let document = ctx.documents.active_document().id();
+//! ctx.live = Some(LiveServer::bind(LIVE_ADDRESS, document)?);
+//! ```
+
+use serde::{Deserialize, Deserializer};
+use serde_json::{Map, Value};
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::time::{Duration, Instant};
+
+use super::ID_ATTR;
+use super::json::flatten;
+use crate::context::EngineContext;
+use crate::graph::{Attributes, EdgeId, Graph, NodeId};
+use crate::i18n::tr;
+use crate::imgui::Ui;
+use crate::subsystems::panels::Panel;
+
+/// Address the [`LivePanel`] offers to listen on by default.
+pub const LIVE_ADDRESS: &str = "127.0.0.1:9750";
+
+/// How long [`LiveServer::step()`] applies events for each frame.
+const LIVE_BUDGET: Duration = Duration::from_millis(4);
+
+/// How often worker threads check whether the server stopped.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A graph mutation, see the [module documentation](self) for the protocol.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case", deny_unknown_fields)]
+pub enum LiveEvent {
+    AddNode {
+        #[serde(deserialize_with = "element_id")]
+        id: String,
+        #[serde(default)]
+        attrs: Map<String, Value>,
+    },
+    UpdateNode {
+        #[serde(deserialize_with = "element_id")]
+        id: String,
+        attrs: Map<String, Value>,
+    },
+    RemoveNode {
+        #[serde(deserialize_with = "element_id")]
+        id: String,
+    },
+    AddEdge {
+        /// Edges without an id cannot be updated or removed later.
+        #[serde(default, deserialize_with = "optional_element_id")]
+        id: Option<String>,
+        #[serde(deserialize_with = "element_id")]
+        source: String,
+        #[serde(deserialize_with = "element_id")]
+        target: String,
+        #[serde(default)]
+        attrs: Map<String, Value>,
+    },
+    UpdateEdge {
+        #[serde(deserialize_with = "element_id")]
+        id: String,
+        attrs: Map<String, Value>,
+    },
+    RemoveEdge {
+        #[serde(deserialize_with = "element_id")]
+        id: String,
+    },
+    /// Remove every node and edge.
+    Clear,
+}
+
+/// Listens for [`LiveEvent`]s and applies them to a document, see the
+/// [module documentation](self).
+pub struct LiveServer {
+    address: SocketAddr,
+    /// Id of the document events are applied to.
+    document: u64,
+    events: Receiver<LiveEvent>,
+    /// Shared with the worker threads, which stop once it is set.
+    stopped: Arc<AtomicBool>,
+    connections: Arc<AtomicUsize>,
+    /// The document's elements by [`ID_ATTR`], as of the last step.
+    index: Option<ElementIndex>,
+    applied: usize,
+    /// Number of events which could not be applied, and why the last one
+    /// could not.
+    failed: usize,
+    error: Option<String>,
+}
+
+/// Elements of a graph by [`ID_ATTR`].
+struct ElementIndex {
+    /// Generation of the graph the index matches.
+    generation: u64,
+    nodes: HashMap<String, NodeId>,
+    edges: HashMap<String, EdgeId>,
+}
+
+/// Starts, stops, and shows a [`LiveServer`] for the active document.
+pub struct LivePanel {
+    address: String,
+    /// Why the server could not be started, if it could not.
+    error: Option<String>,
+}
+
+impl LiveServer {
+    /// Listen on `address` for events applied to the document with id
+    /// `document`.
+    ///
+    /// Errors if the address cannot be bound.
+    pub fn bind(address: impl ToSocketAddrs, document: u64) -> std::io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+        let address = listener.local_addr()?;
+        let (sender, events) = mpsc::channel();
+        let stopped = Arc::new(AtomicBool::new(false));
+        let connections = Arc::new(AtomicUsize::new(0));
+        {
+            let stopped = stopped.clone();
+            let connections = connections.clone();
+            std::thread::spawn(move || accept(&listener, &sender, &stopped, &connections));
+        }
+        Ok(Self {
+            address,
+            document,
+            events,
+            stopped,
+            connections,
+            index: None,
+            applied: 0,
+            failed: 0,
+            error: None,
+        })
+    }
+
+    /// The address the server listens on.
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Id of the document events are applied to.
+    pub fn document(&self) -> u64 {
+        self.document
+    }
+
+    /// Number of open connections.
+    pub fn connections(&self) -> usize {
+        self.connections.load(Ordering::Relaxed)
+    }
+
+    /// Number of events applied so far.
+    pub fn applied(&self) -> usize {
+        self.applied
+    }
+
+    /// Number of events which could not be applied so far.
+    pub fn failed(&self) -> usize {
+        self.failed
+    }
+
+    /// Why the last event which could not be applied failed.
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Stop listening and close every connection. Also done on drop.
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+
+    /// Apply the events received so far to `graph`, for at most a few
+    /// milliseconds. Returns `true` if the graph changed.
+    pub fn step(&mut self, graph: &mut Graph) -> bool {
+        let start = Instant::now();
+        let mut changed = false;
+        while start.elapsed() < LIVE_BUDGET {
+            let event = match self.events.try_recv() {
+                Ok(event) => event,
+                Err(TryRecvError::Empty | TryRecvError::Disconnected) => break,
+            };
+            let index = match self.index.take() {
+                Some(index) if index.generation == graph.generation() => index,
+                _ => ElementIndex::new(graph),
+            };
+            let index = self.index.insert(index);
+            match index.apply(graph, event) {
+                Ok(()) => self.applied += 1,
+                Err(e) => {
+                    self.failed += 1;
+                    self.error = Some(e);
+                }
+            }
+            index.generation = graph.generation();
+            changed = true;
+        }
+        changed
+    }
+}
+
+impl ElementIndex {
+    /// Index the elements of `graph` having an [`ID_ATTR`].
+    fn new(graph: &Graph) -> Self {
+        let id = |attrs: Option<&Attributes>| Some(attrs?.get(ID_ATTR)?.to_string());
+        Self {
+            generation: graph.generation(),
+            nodes: graph
+                .nodes()
+                .filter_map(|node| Some((id(graph.node(node))?, node)))
+                .collect(),
+            edges: graph
+                .edges()
+                .filter_map(|edge| Some((id(graph.edge(edge))?, edge)))
+                .collect(),
+        }
+    }
+
+    /// The node named `id`, added if missing.
+    fn node(&mut self, graph: &mut Graph, id: &str) -> NodeId {
+        if let Some(&node) = self.nodes.get(id) {
+            return node;
+        }
+        let mut attrs = Attributes::default();
+        attrs.insert(ID_ATTR, id);
+        let node = graph.add_node_with(attrs);
+        self.nodes.insert(id.to_owned(), node);
+        node
+    }
+
+    fn apply(&mut self, graph: &mut Graph, event: LiveEvent) -> Result<(), String> {
+        match event {
+            LiveEvent::AddNode { id, attrs } => {
+                let node = self.node(graph, &id);
+                merge(graph.node_mut(node).unwrap(), &attrs);
+            }
+            LiveEvent::UpdateNode { id, attrs } => {
+                let node = self.nodes.get(&id).ok_or(format!("No node \"{id}\""))?;
+                merge(graph.node_mut(*node).unwrap(), &attrs);
+            }
+            LiveEvent::RemoveNode { id } => {
+                let node = self.nodes.remove(&id).ok_or(format!("No node \"{id}\""))?;
+                graph.remove_node(node);
+                self.edges.retain(|_, edge| graph.contains_edge(*edge));
+            }
+            LiveEvent::AddEdge {
+                id,
+                source,
+                target,
+                attrs,
+            } => {
+                let existing = id.as_ref().and_then(|id| self.edges.get(id));
+                let edge = match existing {
+                    Some(&edge) => edge,
+                    None => {
+                        let source = self.node(graph, &source);
+                        let target = self.node(graph, &target);
+                        let edge = graph.add_edge(source, target).map_err(|e| e.to_string())?;
+                        if let Some(id) = id {
+                            graph.edge_mut(edge).unwrap().insert(ID_ATTR, id.as_str());
+                            self.edges.insert(id, edge);
+                        }
+                        edge
+                    }
+                };
+                merge(graph.edge_mut(edge).unwrap(), &attrs);
+            }
+            LiveEvent::UpdateEdge { id, attrs } => {
+                let edge = self.edges.get(&id).ok_or(format!("No edge \"{id}\""))?;
+                merge(graph.edge_mut(*edge).unwrap(), &attrs);
+            }
+            LiveEvent::RemoveEdge { id } => {
+                let edge = self.edges.remove(&id).ok_or(format!("No edge \"{id}\""))?;
+                graph.remove_edge(edge);
+            }
+            LiveEvent::Clear => {
+                graph.clear();
+                self.nodes.clear();
+                self.edges.clear();
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Merge `fields` into `attrs`, removing those set to `null`. The element's
+/// [`ID_ATTR`] is kept.
+fn merge(attrs: &mut Attributes, fields: &Map<String, Value>) {
+    for (key, value) in fields {
+        if key == ID_ATTR {
+            continue;
+        }
+        match value {
+            Value::Null => {
+                attrs.remove(key.as_str());
+            }
+            value => flatten(key, value, attrs),
+        }
+    }
+}
+
+/// Accept connections until `stopped` is set, reading each on its own thread.
+fn accept(
+    listener: &TcpListener,
+    sender: &Sender<LiveEvent>,
+    stopped: &Arc<AtomicBool>,
+    connections: &Arc<AtomicUsize>,
+) {
+    while !stopped.load(Ordering::Relaxed) {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                let sender = sender.clone();
+                let stopped = stopped.clone();
+                let connections = connections.clone();
+                connections.fetch_add(1, Ordering::Relaxed);
+                std::thread::spawn(move || {
+                    // the connection ends with an error or once closed either way
+                    let _ = read(stream, &sender, &stopped);
+                    connections.fetch_sub(1, Ordering::Relaxed);
+                });
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => std::thread::sleep(POLL_INTERVAL),
+            Err(_) => return,
+        }
+    }
+}
+
+/// Read events from `stream`, one per line, until it closes or `stopped` is set.
+fn read(
+    stream: TcpStream,
+    sender: &Sender<LiveEvent>,
+    stopped: &AtomicBool,
+) -> std::io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(POLL_INTERVAL))?;
+    let mut writer = stream.try_clone()?;
+    let mut reader = BufReader::new(stream);
+    let mut line = Vec::new();
+    while !stopped.load(Ordering::Relaxed) {
+        // a timed out read keeps what it read in `line`
+        match reader.read_until(b'\n', &mut line) {
+            Ok(0) => return Ok(()),
+            Ok(_) => {}
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => continue,
+            Err(e) => return Err(e),
+        }
+        if !line.trim_ascii().is_empty() {
+            match serde_json::from_slice::<LiveEvent>(&line) {
+                Ok(event) => {
+                    if sender.send(event).is_err() {
+                        return Ok(());
+                    }
+                }
+                Err(e) => {
+                    let reply = serde_json::json!({ "error": e.to_string() });
+                    writeln!(writer, "{reply}")?;
+                }
+            }
+        }
+        line.clear();
+    }
+    Ok(())
+}
+
+/// Deserialize an element id from a JSON string or number.
+fn element_id<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    match Value::deserialize(deserializer)? {
+        Value::String(s) => Ok(s),
+        Value::Number(n) => Ok(n.to_string()),
+        other => Err(serde::de::Error::custom(format!(
+            "expected a string or number id, got {other}"
+        ))),
+    }
+}
+
+fn optional_element_id<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<String>, D::Error> {
+    element_id(deserializer).map(Some)
+}
+
+impl Drop for LiveServer {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+impl LivePanel {
+    /// Create a panel offering to listen on [`LIVE_ADDRESS`].
+    pub fn new() -> Self {
+        Self {
+            address: LIVE_ADDRESS.into(),
+            error: None,
+        }
+    }
+}
+
+impl Panel for LivePanel {
+    fn id(&self) -> &'static str {
+        "Live"
+    }
+
+    fn draw(&mut self, ui: &Ui, ctx: &mut EngineContext) {
+        match &ctx.live {
+            Some(server) => {
+                let document = ctx
+                    .documents
+                    .documents()
+                    .iter()
+                    .find(|d| d.id() == server.document())
+                    .map_or(tr("a closed document").into_owned(), |d| d.title());
+                ui.text(
+                    tr("Listening on {address} for {document}")
+                        .replace("{address}", &server.address().to_string())
+                        .replace("{document}", &document),
+                );
+                ui.text(
+                    tr("{connections} connections, {events} events applied")
+                        .replace("{connections}", &server.connections().to_string())
+                        .replace("{events}", &server.applied().to_string()),
+                );
+                if let Some(error) = server.error() {
+                    ui.text_colored(
+                        [1.0, 0.4, 0.4, 1.0],
+                        tr("{count} failed, last: {error}")
+                            .replace("{count}", &server.failed().to_string())
+                            .replace("{error}", error),
+                    );
+                }
+                if server.document() != ctx.documents.active_document().id() {
+                    ui.text_disabled(tr("Events wait until the document is active"));
+                }
+                if ui.button(tr("Stop")) {
+                    ctx.live = None;
+                }
+            }
+            None => {
+                ui.input_text(tr("Address"), &mut self.address).build();
+                if ui.button(tr("Listen")) {
+                    let document = ctx.documents.active_document().id();
+                    match LiveServer::bind(self.address.trim(), document) {
+                        Ok(server) => {
+                            ctx.log.info(format!(
+                                "Listening for live updates on {}",
+                                server.address()
+                            ));
+                            ctx.live = Some(server);
+                            self.error = None;
+                        }
+                        Err(e) => self.error = Some(format!("Failed to listen: {e}")),
+                    }
+                }
+                if let Some(error) = &self.error {
+                    ui.text_colored([1.0, 0.4, 0.4, 1.0], error);
+                }
+            }
+        }
+    }
+}
+
+impl std::default::Default for LivePanel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,425 @@
+//! # Background Loading
+//!
+//! A [`LoadJob`] reads a graph file as a [background job](crate::jobs), so
+//! large files do not block the ui. The worker reports how much of the file it
+//! read through a shared [`Progress`], then hands the parsed graph over as
+//! chunks of nodes and edges on a channel. [`LoadJob::step()`] adds chunks to a document's graph
+//! within a time budget each frame, so the graph appears progressively.
+//!
+//! Jobs are usually started with
+//! [`EngineContext::load_document()`](crate::context::EngineContext::load_document),
+//! which steps them from [`EngineContext::refresh()`], and shown with
+//! [`draw_progress()`].
+//!
+//! Files in formats the engine does not know are read by a [`GraphLoader`],
+//! eg. one registered by a [plugin](crate::plugin), with [`LoadJob::spawn_with()`].
+//!
+//! ```ignore
+//! let mut job = LoadJob::spawn(&jobs, path, Format::GraphMl, document);
+//! loop {
+//!     match job.step(&mut graph, LOAD_BUDGET) {
+//!         LoadStep::Loading => {}
+//!         LoadStep::Done => break,
+//!         LoadStep::Failed(e) => return Err(e),
+//!     }
+//! }
+//! ```
+
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::time::{Duration, Instant};
+
+use super::{Format, LoadError, csv, dot, gexf, graphml, json, rdf};
+use crate::context::EngineContext;
+use crate::graph::{Attributes, Directedness, Graph, NodeId};
+use crate::i18n::tr;
+use crate::imgui::{Condition, ProgressBar, Ui, WindowFlags};
+use crate::jobs::{JobPool, Priority, Progress};
+use crate::status;
+
+/// How long [`LoadJob::step()`] adds chunks for each frame, by default.
+pub const LOAD_BUDGET: Duration = Duration::from_millis(4);
+
+/// Number of nodes or edges handed over at once.
+const CHUNK_LEN: usize = 4096;
+
+/// Reads graph files of a format the engine does not know, eg. a proprietary
+/// data source, for a [`LoadJob`].
+pub trait GraphLoader: Send + Sync {
+    /// Human readable name, eg. for the log.
+    fn name(&self) -> &str;
+
+    /// Whether the loader reads the file at `path`, eg. judging by its
+    /// extension.
+    fn accepts(&self, path: &Path) -> bool;
+
+    /// Read the whole graph from `reader`, the contents of the file at `path`.
+    ///
+    /// Called on the job's worker thread. `reader` fails once the job is
+    /// cancelled.
+    fn read(&self, path: &Path, reader: &mut dyn Read) -> Result<Graph, String>;
+}
+
+/// Reads a graph file as a background job, see the [module documentation](self).
+pub struct LoadJob {
+    path: PathBuf,
+    /// Id of the document the graph is loaded into.
+    document: u64,
+    progress: Arc<Progress>,
+    events: Receiver<LoadEvent>,
+    /// Ids given to the worker's nodes, by the worker's index.
+    nodes: Vec<Option<NodeId>>,
+    /// Number of nodes and edges added so far, and in total once parsed.
+    added: (usize, usize),
+    total: Option<(usize, usize)>,
+    started: Instant,
+}
+
+/// What a [`LoadJob`] did during a [step](LoadJob::step).
+#[derive(Debug)]
+pub enum LoadStep {
+    /// The file is still being read, or its graph handed over.
+    Loading,
+    /// The whole graph was added.
+    Done,
+    /// The file could not be read, or the job was cancelled.
+    Failed(LoadError),
+}
+
+/// How the worker of a [`LoadJob`] reads its file.
+enum Source {
+    Format(Format),
+    Loader(Arc<dyn GraphLoader>),
+}
+
+/// Sent by the worker thread of a [`LoadJob`].
+enum LoadEvent {
+    /// The file was parsed. Its nodes and edges follow.
+    Parsed {
+        directedness: Directedness,
+        attrs: Attributes,
+        nodes: usize,
+        edges: usize,
+    },
+    /// Nodes, with their index in the parsed graph.
+    Nodes(Vec<(usize, Attributes)>),
+    /// Edges, with the index of their source and target in the parsed graph.
+    Edges(Vec<(usize, usize, Attributes)>),
+    Failed(LoadError),
+}
+
+/// A reader recording how much of a file was read, and failing once the load
+/// is cancelled.
+struct Tracked<R> {
+    inner: R,
+    read: u64,
+    len: u64,
+    progress: Arc<Progress>,
+}
+
+impl LoadJob {
+    /// Start reading the file at `path` as `format`, for the document with id
+    /// `document`. CSV files are read with [sniffed](csv::CsvOptions::sniff)
+    /// options.
+    pub fn spawn(jobs: &JobPool, path: PathBuf, format: Format, document: u64) -> Self {
+        Self::start(jobs, path, Source::Format(format), document)
+    }
+
+    /// Start reading the file at `path` with `loader`, for the document with
+    /// id `document`.
+    pub fn spawn_with(
+        jobs: &JobPool,
+        path: PathBuf,
+        loader: Arc<dyn GraphLoader>,
+        document: u64,
+    ) -> Self {
+        Self::start(jobs, path, Source::Loader(loader), document)
+    }
+
+    fn start(jobs: &JobPool, path: PathBuf, source: Source, document: u64) -> Self {
+        let progress = Arc::new(Progress::new());
+        let (sender, events) = mpsc::channel();
+        let shared = progress.clone();
+        let file = path.clone();
+        let name = match path.file_name() {
+            Some(name) => format!("Load {}", name.to_string_lossy()),
+            None => format!("Load {}", path.display()),
+        };
+        jobs.spawn(name, Priority::Normal, &progress, move || {
+            run(&file, &source, &shared, &sender)
+        });
+        Self {
+            path,
+            document,
+            progress,
+            events,
+            nodes: Vec::new(),
+            added: (0, 0),
+            total: None,
+            started: Instant::now(),
+        }
+    }
+
+    /// The file being read.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Id of the document the graph is loaded into.
+    pub fn document(&self) -> u64 {
+        self.document
+    }
+
+    /// Fraction done: reading the file counts for the first half, adding its
+    /// nodes and edges for the second.
+    pub fn progress(&self) -> f32 {
+        match self.total {
+            None => self.progress.fraction() * 0.5,
+            Some((nodes, edges)) => {
+                let added = (self.added.0 + self.added.1) as f32;
+                0.5 + 0.5 * added / (nodes + edges).max(1) as f32
+            }
+        }
+    }
+
+    /// What the job is doing, eg. to label a progress bar.
+    pub fn status(&self) -> String {
+        match self.total {
+            None => format!("Reading {:.0}%", self.progress.fraction() * 100.0),
+            Some((nodes, edges)) => format!(
+                "Adding {}/{nodes} nodes, {}/{edges} edges",
+                self.added.0, self.added.1
+            ),
+        }
+    }
+
+    /// Time since the job started.
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// Ask the job to stop. The next [step](Self::step) fails.
+    pub fn cancel(&self) {
+        self.progress.cancel();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.progress.is_cancelled()
+    }
+
+    /// Add the chunks handed over so far to `graph`, for at most `budget`.
+    ///
+    /// `graph` is replaced by an empty one with the file's directedness and
+    /// graph attributes once parsed, so it should not be edited meanwhile.
+    pub fn step(&mut self, graph: &mut Graph, budget: Duration) -> LoadStep {
+        if self.is_cancelled() {
+            return LoadStep::Failed(LoadError::Io(cancelled()));
+        }
+        let start = Instant::now();
+        while start.elapsed() < budget {
+            let event = match self.events.try_recv() {
+                Ok(event) => event,
+                Err(TryRecvError::Empty) => return LoadStep::Loading,
+                Err(TryRecvError::Disconnected) => {
+                    return match self.total {
+                        Some(total) if total == self.added => LoadStep::Done,
+                        // the worker panicked
+                        _ => LoadStep::Failed(LoadError::Io(std::io::Error::other(
+                            "The loader stopped unexpectedly",
+                        ))),
+                    };
+                }
+            };
+            match event {
+                LoadEvent::Parsed {
+                    directedness,
+                    attrs,
+                    nodes,
+                    edges,
+                } => {
+                    *graph = Graph::new(directedness);
+                    *graph.attrs_mut() = attrs;
+                    self.nodes = vec![None; nodes];
+                    self.total = Some((nodes, edges));
+                }
+                LoadEvent::Nodes(chunk) => {
+                    self.added.0 += chunk.len();
+                    for (index, attrs) in chunk {
+                        self.nodes[index] = Some(graph.add_node_with(attrs));
+                    }
+                }
+                LoadEvent::Edges(chunk) => {
+                    self.added.1 += chunk.len();
+                    for (source, target, attrs) in chunk {
+                        if let (Some(source), Some(target)) =
+                            (self.nodes[source], self.nodes[target])
+                            && let Err(e) = graph.add_edge_with(source, target, attrs)
+                        {
+                            return LoadStep::Failed(LoadError::Io(std::io::Error::other(e)));
+                        }
+                    }
+                }
+                LoadEvent::Failed(e) => return LoadStep::Failed(e),
+            }
+        }
+        LoadStep::Loading
+    }
+}
+
+/// Parse the file, then hand its graph over to `sender` in chunks.
+fn run(path: &Path, source: &Source, progress: &Arc<Progress>, sender: &Sender<LoadEvent>) {
+    let mut graph = match parse(path, source, progress) {
+        Ok(graph) => graph,
+        Err(e) => {
+            let _ = sender.send(LoadEvent::Failed(e));
+            return;
+        }
+    };
+
+    let nodes: Vec<NodeId> = graph.nodes().collect();
+    let edges: Vec<_> = graph.edges().collect();
+    let parsed = LoadEvent::Parsed {
+        directedness: graph.directedness(),
+        attrs: std::mem::take(graph.attrs_mut()),
+        nodes: nodes.len(),
+        edges: edges.len(),
+    };
+    if sender.send(parsed).is_err() {
+        return;
+    }
+    // nodes are sent by their index in `nodes`, which edges refer to
+    let mut index = vec![0; graph.node_bound()];
+    for (i, node) in nodes.iter().enumerate() {
+        index[node.index()] = i;
+    }
+    for (start, chunk) in nodes.chunks(CHUNK_LEN).enumerate() {
+        let chunk = chunk
+            .iter()
+            .enumerate()
+            .map(|(i, &node)| {
+                let attrs = std::mem::take(graph.node_mut(node).unwrap());
+                (start * CHUNK_LEN + i, attrs)
+            })
+            .collect();
+        if progress.is_cancelled() || sender.send(LoadEvent::Nodes(chunk)).is_err() {
+            return;
+        }
+    }
+    for chunk in edges.chunks(CHUNK_LEN) {
+        let chunk = chunk
+            .iter()
+            .map(|&edge| {
+                let (source, target) = graph.endpoints(edge).unwrap();
+                let attrs = std::mem::take(graph.edge_mut(edge).unwrap());
+                (index[source.index()], index[target.index()], attrs)
+            })
+            .collect();
+        if progress.is_cancelled() || sender.send(LoadEvent::Edges(chunk)).is_err() {
+            return;
+        }
+    }
+}
+
+/// Read the file at `path` from `source`, recording progress in `progress`.
+fn parse(path: &Path, source: &Source, progress: &Arc<Progress>) -> Result<Graph, LoadError> {
+    let file = std::fs::File::open(path)?;
+    let len = file.metadata()?.len();
+    let mut reader = BufReader::new(Tracked {
+        inner: file,
+        read: 0,
+        len,
+        progress: progress.clone(),
+    });
+    let format = match source {
+        Source::Format(format) => *format,
+        Source::Loader(loader) => {
+            return loader.read(path, &mut reader).map_err(|e| {
+                if progress.is_cancelled() {
+                    LoadError::Io(cancelled())
+                } else {
+                    LoadError::Loader(format!("{}: {e}", loader.name()))
+                }
+            });
+        }
+    };
+    Ok(match format {
+        Format::GraphMl => graphml::read(reader)?,
+        Format::Gexf => gexf::read(reader)?,
+        Format::Json => json::read(reader)?,
+        Format::Dot => {
+            let mut source = String::new();
+            reader.read_to_string(&mut source)?;
+            dot::read(&source)?
+        }
+        Format::Csv => {
+            let options = csv::CsvOptions::sniff(path)?;
+            csv::read(reader, &options)?
+        }
+        Format::Rdf => {
+            let mut source = String::new();
+            reader.read_to_string(&mut source)?;
+            rdf::read(&source)?
+        }
+    })
+}
+
+/// The error of a cancelled load.
+fn cancelled() -> std::io::Error {
+    std::io::Error::other("Cancelled")
+}
+
+impl<R: Read> Read for Tracked<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.progress.is_cancelled() {
+            return Err(cancelled());
+        }
+        let read = self.inner.read(buf)?;
+        self.read += read as u64;
+        self.progress.set(self.read as f32 / self.len.max(1) as f32);
+        Ok(read)
+    }
+}
+
+/// Draw a small window in the bottom-right corner of the main viewport with
+/// the progress of each of `ctx`'s loads, and a button cancelling it.
+pub fn draw_progress(ui: &Ui, ctx: &EngineContext) {
+    let loads = ctx.loads();
+    if loads.is_empty() {
+        return;
+    }
+    let [width, height] = ui.io().display_size;
+    let bottom = height - status::height(ui);
+    ui.window("##loading")
+        .position([width - 10.0, bottom - 10.0], Condition::Always)
+        .position_pivot([1.0, 1.0])
+        .flags(
+            WindowFlags::NO_DECORATION
+                | WindowFlags::ALWAYS_AUTO_RESIZE
+                | WindowFlags::NO_SAVED_SETTINGS
+                | WindowFlags::NO_FOCUS_ON_APPEARING
+                | WindowFlags::NO_NAV
+                | WindowFlags::NO_DOCKING,
+        )
+        .build(|| {
+            for (index, job) in loads.iter().enumerate() {
+                let _id = ui.push_id_usize(index);
+                let name = job.path().file_name().map_or_else(
+                    || job.path().display().to_string(),
+                    |name| name.to_string_lossy().into_owned(),
+                );
+                ui.text(tr("Loading '{name}'").replace("{name}", &name));
+                ProgressBar::new(job.progress())
+                    .size([260.0, 0.0])
+                    .overlay_text(job.status())
+                    .build(ui);
+                ui.same_line();
+                ui.enabled(!job.is_cancelled(), || {
+                    if ui.button(tr("Cancel")) {
+                        job.cancel();
+                    }
+                });
+            }
+        });
+}
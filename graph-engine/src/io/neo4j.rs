@@ -0,0 +1,501 @@
+//! # Neo4j Connector
+//!
+//! Runs Cypher queries against a Neo4j database through its HTTP API, and turns
+//! the nodes and relationships they return into a [`Graph`]:
+//! - Each node's and relationship's database id is kept in its [`ID_ATTR`], so
+//!   results can be [merged](crate::explore::merge) into a graph without
+//!   duplicates.
+//! - Properties become attributes, flattened like in [`json`](super::json)
+//!   files. Node labels are joined into a [`LABELS_ATTR`], and relationship
+//!   types kept in a [`TYPE_ATTR`].
+//! - Nodes get a `label` attribute from their `name` or `title` property, or
+//!   their first label.
+//!
+//! Endpoints are reached over `http://` or `https://`, the panel warning that
+//! passwords sent over `http://` to another host are readable on the way.
+//! Saved [`Endpoints`] keep everything but passwords, which are asked for
+//! again in each session.
+//!
+//! The [`Neo4jPanel`] manages endpoints and opens query results as documents,
+//! [explored](crate::explore) further in the database: an [`Endpoint`] is a
+//! [`NeighborSource`] fetching the relationships of nodes.
+//!
+//! ```ignore
+//! let endpoint = Endpoint::new("local", "http://localhost:7474");
+//! let graph = endpoint.query("MATCH (n)-[r]->(m) RETURN n, r, m LIMIT 100", &Map::new())?;
+//! ctx.open_document(graph, None);
+//! ```
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::thread::JoinHandle;
+
+use super::ID_ATTR;
+use super::http::{self, HttpError, basic_auth};
+use super::json::flatten;
+use crate::context::EngineContext;
+use crate::explore::{Exploration, ExploreError, NeighborSource};
+use crate::graph::{Attributes, Graph, NodeId};
+use crate::i18n::tr;
+use crate::imgui::{InputTextFlags, Ui};
+use crate::subsystems::panels::Panel;
+
+/// Name of the saved endpoints inside the config directory.
+pub const ENDPOINTS_FILE: &str = "neo4j.toml";
+
+/// Attribute holding a node's labels, joined by `:`.
+pub const LABELS_ATTR: &str = "labels";
+
+/// Attribute holding a relationship's type.
+pub const TYPE_ATTR: &str = "type";
+
+/// Color of the warning about passwords sent unencrypted.
+const WARNING_COLOR: [f32; 4] = [1.0, 0.8, 0.3, 1.0];
+
+/// Cypher query fetching the neighbors of the nodes with ids `$ids`.
+const EXPAND_QUERY: &str = "MATCH (n)-[r]-(m) WHERE id(n) IN $ids \
+    WITH n, r, m LIMIT $limit RETURN n, r, m";
+
+/// A Neo4j database reachable over HTTP.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Endpoint {
+    /// Shown in the endpoint list.
+    pub name: String,
+    /// Base URL of the HTTP API, eg. `http://localhost:7474`.
+    pub url: String,
+    pub database: String,
+    /// User to authenticate as, none if empty.
+    pub user: String,
+    /// Never saved.
+    #[serde(skip)]
+    pub password: String,
+}
+
+/// Saved [`Endpoint`]s, optionally persisted to a file.
+#[derive(Clone, Debug, Default)]
+pub struct Endpoints {
+    pub endpoints: Vec<Endpoint>,
+    /// Where the list is saved, if anywhere.
+    path: Option<PathBuf>,
+}
+
+/// Contents of the [`ENDPOINTS_FILE`].
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+struct EndpointList {
+    endpoints: Vec<Endpoint>,
+}
+
+/// Errors which can occur while querying a Neo4j database.
+#[derive(Debug)]
+pub enum Neo4jError {
+    Http(HttpError),
+    /// The response is not what the HTTP API returns.
+    Parse(String),
+    /// The database rejected the query.
+    Cypher {
+        code: String,
+        message: String,
+    },
+}
+
+/// Manages [`Endpoints`] and runs queries, exploring their results in the
+/// database.
+pub struct Neo4jPanel {
+    endpoints: Endpoints,
+    /// Index of the endpoint queries run against.
+    current: usize,
+    query: String,
+    job: Option<QueryJob>,
+    /// Outcome of the last query, and whether it failed.
+    status: Option<(String, bool)>,
+}
+
+/// A query running on a worker thread.
+struct QueryJob {
+    handle: JoinHandle<Result<Graph, Neo4jError>>,
+    /// The endpoint the query runs against, explored from its result.
+    endpoint: Endpoint,
+}
+
+impl Endpoint {
+    /// Create an endpoint for the default database at `url`, without
+    /// authentication.
+    pub fn new(name: &str, url: &str) -> Self {
+        Self {
+            name: name.into(),
+            url: url.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Run `query` with `parameters`, returning the nodes and relationships
+    /// of its result as a directed graph.
+    pub fn query(&self, query: &str, parameters: &Map<String, Value>) -> Result<Graph, Neo4jError> {
+        let body = json!({
+            "statements": [{
+                "statement": query,
+                "parameters": parameters,
+                "resultDataContents": ["graph"],
+            }]
+        });
+        let url = format!(
+            "{}/db/{}/tx/commit",
+            self.url.trim_end_matches('/'),
+            self.database
+        );
+        let auth = basic_auth(&self.user, &self.password);
+        let mut headers = vec![
+            ("Accept", "application/json"),
+            ("Content-Type", "application/json"),
+        ];
+        if !self.user.is_empty() {
+            headers.push(("Authorization", &auth));
+        }
+        let response = http::post(&url, &headers, &serde_json::to_vec(&body).unwrap())?;
+        let response: Value =
+            serde_json::from_slice(&response.body).map_err(|e| Neo4jError::Parse(e.to_string()))?;
+        if let Some(error) = response["errors"].as_array().and_then(|e| e.first()) {
+            return Err(Neo4jError::Cypher {
+                code: error["code"].as_str().unwrap_or_default().into(),
+                message: error["message"].as_str().unwrap_or_default().into(),
+            });
+        }
+        to_graph(&response)
+    }
+}
+
+impl std::default::Default for Endpoint {
+    fn default() -> Self {
+        Self {
+            name: "Local".into(),
+            url: "http://localhost:7474".into(),
+            database: "neo4j".into(),
+            user: "neo4j".into(),
+            password: String::new(),
+        }
+    }
+}
+
+impl Endpoints {
+    /// Load the endpoints from `path`, starting with a local one if it cannot
+    /// be read. The list is saved back there by [`Self::save()`].
+    pub fn load_or_default(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+        let endpoints = std::fs::read_to_string(path)
+            .ok()
+            .and_then(|text| toml::from_str::<EndpointList>(&text).ok())
+            .map(|list| list.endpoints)
+            .unwrap_or_else(|| vec![Endpoint::default()]);
+        Self {
+            endpoints,
+            path: Some(path.to_owned()),
+        }
+    }
+
+    /// Write the endpoints, without their passwords, where they were loaded
+    /// from.
+    pub fn save(&self) -> std::io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let list = EndpointList {
+            endpoints: self.endpoints.clone(),
+        };
+        let text = toml::to_string(&list).map_err(std::io::Error::other)?;
+        std::fs::write(path, text)
+    }
+}
+
+/// Build a graph from the `graph` result data of a transaction response.
+fn to_graph(response: &Value) -> Result<Graph, Neo4jError> {
+    let rows = response["results"]
+        .get(0)
+        .and_then(|result| result["data"].as_array())
+        .ok_or_else(|| Neo4jError::Parse("missing result data".into()))?;
+
+    let mut graph = Graph::directed();
+    let mut nodes: HashMap<String, NodeId> = HashMap::new();
+    let mut edges = HashSet::new();
+    for row in rows {
+        for node in row["graph"]["nodes"].as_array().into_iter().flatten() {
+            let Some(id) = element_id(node) else {
+                continue;
+            };
+            if nodes.contains_key(&id) {
+                continue;
+            }
+            let mut attrs = properties(node);
+            let labels: Vec<&str> = node["labels"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .collect();
+            if !labels.is_empty() {
+                attrs.insert(LABELS_ATTR, labels.join(":"));
+            }
+            if !attrs.contains("label") {
+                let name = ["name", "title"]
+                    .iter()
+                    .find_map(|key| attrs.get(*key).map(|v| v.to_string()))
+                    .or_else(|| labels.first().map(|label| label.to_string()));
+                if let Some(name) = name {
+                    attrs.insert("label", name);
+                }
+            }
+            attrs.insert(ID_ATTR, id.as_str());
+            nodes.insert(id, graph.add_node_with(attrs));
+        }
+        for relationship in row["graph"]["relationships"]
+            .as_array()
+            .into_iter()
+            .flatten()
+        {
+            let Some(id) = element_id(relationship) else {
+                continue;
+            };
+            let endpoint = |key: &str| {
+                let value = &relationship[key];
+                let key = value
+                    .as_str()
+                    .map_or_else(|| value.to_string(), str::to_owned);
+                nodes.get(&key).copied()
+            };
+            let (Some(source), Some(target)) = (endpoint("startNode"), endpoint("endNode")) else {
+                continue;
+            };
+            if !edges.insert(id.clone()) {
+                continue;
+            }
+            let mut attrs = properties(relationship);
+            if let Some(kind) = relationship["type"].as_str() {
+                attrs.insert(TYPE_ATTR, kind);
+            }
+            attrs.insert(ID_ATTR, id.as_str());
+            graph.add_edge_with(source, target, attrs).unwrap();
+        }
+    }
+    Ok(graph)
+}
+
+/// The id of a node or relationship of the result data.
+fn element_id(element: &Value) -> Option<String> {
+    match &element["id"] {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// The properties of a node or relationship of the result data.
+fn properties(element: &Value) -> Attributes {
+    let mut attrs = Attributes::default();
+    for (key, value) in element["properties"].as_object().into_iter().flatten() {
+        flatten(key, value, &mut attrs);
+    }
+    attrs
+}
+
+impl Neo4jPanel {
+    /// Create a panel with the endpoints saved at `path`.
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            endpoints: Endpoints::load_or_default(path),
+            current: 0,
+            query: "MATCH (n)-[r]->(m) RETURN n, r, m LIMIT 100".into(),
+            job: None,
+            status: None,
+        }
+    }
+
+    fn endpoints_ui(&mut self, ui: &Ui) {
+        let preview = self
+            .endpoints
+            .endpoints
+            .get(self.current)
+            .map_or("(none)", |e| e.name.as_str());
+        if let Some(_combo) = ui.begin_combo(tr("Endpoint"), preview) {
+            for (i, endpoint) in self.endpoints.endpoints.iter().enumerate() {
+                let _id = ui.push_id_usize(i);
+                if ui
+                    .selectable_config(&endpoint.name)
+                    .selected(i == self.current)
+                    .build()
+                {
+                    self.current = i;
+                }
+            }
+        }
+        ui.same_line();
+        if ui.button(tr("Add")) {
+            self.endpoints.endpoints.push(Endpoint {
+                name: format!("Endpoint {}", self.endpoints.endpoints.len() + 1),
+                ..Endpoint::default()
+            });
+            self.current = self.endpoints.endpoints.len() - 1;
+        }
+        ui.same_line();
+        ui.enabled(self.current < self.endpoints.endpoints.len(), || {
+            if ui.button(tr("Remove")) {
+                self.endpoints.endpoints.remove(self.current);
+                self.current = self.current.saturating_sub(1);
+            }
+        });
+
+        if let Some(endpoint) = self.endpoints.endpoints.get_mut(self.current) {
+            ui.input_text(tr("Name"), &mut endpoint.name).build();
+            ui.input_text(tr("URL"), &mut endpoint.url)
+                .hint("http://localhost:7474")
+                .build();
+            ui.input_text(tr("Database"), &mut endpoint.database)
+                .build();
+            ui.input_text(tr("User"), &mut endpoint.user).build();
+            ui.input_text(tr("Password"), &mut endpoint.password)
+                .flags(InputTextFlags::PASSWORD)
+                .build();
+            if !endpoint.user.is_empty() && http::is_cleartext(&endpoint.url) {
+                ui.text_colored(
+                    WARNING_COLOR,
+                    tr("The password is sent unencrypted, use an https:// URL"),
+                );
+            }
+        }
+        if ui.button(tr("Save Endpoints")) {
+            self.status = Some(match self.endpoints.save() {
+                Ok(()) => ("Saved endpoints, without passwords".into(), false),
+                Err(e) => (format!("Failed to save endpoints: {e}"), true),
+            });
+        }
+    }
+
+    /// Run `query` on the current endpoint, in the background.
+    fn spawn(&mut self, query: String) {
+        let Some(endpoint) = self.endpoints.endpoints.get(self.current).cloned() else {
+            return;
+        };
+        self.status = None;
+        let queried = endpoint.clone();
+        self.job = Some(QueryJob {
+            handle: std::thread::spawn(move || queried.query(&query, &Map::new())),
+            endpoint,
+        });
+    }
+
+    /// Open the result of the finished query as a new document, explored
+    /// further in the database.
+    fn finish(&mut self, ctx: &mut EngineContext) {
+        let Some(job) = self.job.take_if(|job| job.handle.is_finished()) else {
+            return;
+        };
+        let result = job
+            .handle
+            .join()
+            .unwrap_or_else(|_| Err(Neo4jError::Parse("the query thread panicked".into())));
+        let graph = match result {
+            Ok(graph) => graph,
+            Err(e) => {
+                self.status = Some((e.to_string(), true));
+                return;
+            }
+        };
+        self.status = Some((
+            format!("{} nodes, {} edges", graph.node_count(), graph.edge_count()),
+            false,
+        ));
+        let seeds: Vec<String> = graph
+            .nodes()
+            .filter_map(|node| Some(graph.node(node)?.get(ID_ATTR)?.to_string()))
+            .collect();
+        ctx.open_document(graph, None);
+        ctx.camera.fit_to_graph(&ctx.graph, &ctx.positions);
+        let document = ctx.documents.active_document().id();
+        ctx.exploration = Some(Exploration::new(Box::new(job.endpoint), document, seeds));
+    }
+}
+
+impl Panel for Neo4jPanel {
+    fn id(&self) -> &'static str {
+        "Neo4j"
+    }
+
+    fn draw(&mut self, ui: &Ui, ctx: &mut EngineContext) {
+        self.finish(ctx);
+        self.endpoints_ui(ui);
+        ui.separator();
+
+        ui.input_text_multiline("##query", &mut self.query, [-1.0, 80.0])
+            .build();
+        let running = self.job.is_some();
+        let ready = !running && self.current < self.endpoints.endpoints.len();
+        ui.enabled(ready, || {
+            if ui.button(tr("Run as New Document")) {
+                self.spawn(self.query.clone());
+            }
+        });
+        if ui.is_item_hovered() {
+            ui.tooltip_text(tr(
+                "Expand its nodes from the database in the Explore panel",
+            ));
+        }
+
+        if running {
+            ui.text_disabled(tr("Querying..."));
+        }
+        match &self.status {
+            Some((error, true)) => ui.text_colored([1.0, 0.4, 0.4, 1.0], error),
+            Some((status, false)) => ui.text_wrapped(status),
+            None => {}
+        }
+    }
+}
+
+impl From<HttpError> for Neo4jError {
+    fn from(e: HttpError) -> Self {
+        Self::Http(e)
+    }
+}
+
+impl NeighborSource for Endpoint {
+    fn name(&self) -> String {
+        format!("Neo4j database '{}'", self.name)
+    }
+
+    fn neighbors(&mut self, keys: &[String], limit: usize) -> Result<Graph, ExploreError> {
+        // nodes from the database carry its ids
+        let ids: Vec<i64> = keys.iter().filter_map(|key| key.parse().ok()).collect();
+        if ids.is_empty() {
+            return Ok(Graph::directed());
+        }
+        let mut parameters = Map::new();
+        parameters.insert("ids".into(), ids.iter().copied().collect());
+        parameters.insert("limit".into(), (limit * ids.len()).into());
+        Ok(self.query(EXPAND_QUERY, &parameters)?)
+    }
+}
+
+impl std::fmt::Display for Neo4jError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Http(e) => write!(f, "{e}"),
+            Self::Parse(e) => {
+                let text = tr("Unexpected response: {error}");
+                f.write_str(&text.replace("{error}", &e.to_string()))
+            }
+            Self::Cypher { code, message } => write!(f, "{code}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for Neo4jError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Http(e) => Some(e),
+            _ => None,
+        }
+    }
+}
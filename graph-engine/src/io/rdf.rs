@@ -0,0 +1,720 @@
+//! # RDF
+//!
+//! Reads [Turtle](https://www.w3.org/TR/turtle/) files, and so
+//! [N-Triples](https://www.w3.org/TR/n-triples/) ones, which are a subset.
+//!
+//! Resources become nodes and literals become their attributes:
+//! - IRIs and blank nodes are kept in the [`ID_ATTR`] attribute, blank nodes
+//!   as `_:name`. Nodes get a `label` attribute from their `rdfs:label`,
+//!   `skos:prefLabel` or `foaf:name`, or else their IRI shortened with the
+//!   file's prefixes.
+//! - Triples with a literal object set an attribute named after the local
+//!   part of their predicate, keeping the first value. Typed numbers and
+//!   booleans keep their type, everything else becomes a string.
+//! - Triples with a resource object become edges, with their predicate's IRI
+//!   in a [`PREDICATE_ATTR`] and its short form (eg. `rdf:type`) in a
+//!   [`TYPE_ATTR`], which edges can be styled by. Repeated triples are ignored.
+//! - Collections become `rdf:first`/`rdf:rest` chains of blank nodes.
+//!
+//! ```ignore
+//! let graph = rdf::load("ontology.ttl")?;
+//! let (triples, prefixes) = rdf::parse(&text)?;
+//! let graph = rdf::to_graph(&triples, &prefixes);
+//! ```
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use super::ID_ATTR;
+use crate::graph::{AttrValue, Attributes, Graph, NodeId, Symbol};
+
+/// Edge attribute holding the IRI of the edge's predicate.
+pub const PREDICATE_ATTR: &str = "predicate";
+/// Edge attribute holding the predicate's short form, eg. `rdfs:subClassOf`.
+pub const TYPE_ATTR: &str = "type";
+
+const RDF: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+const XSD: &str = "http://www.w3.org/2001/XMLSchema#";
+
+/// Namespaces used to shorten IRIs even when a file does not declare them.
+const WELL_KNOWN: [(&str, &str); 6] = [
+    ("rdf", RDF),
+    ("rdfs", "http://www.w3.org/2000/01/rdf-schema#"),
+    ("owl", "http://www.w3.org/2002/07/owl#"),
+    ("xsd", XSD),
+    ("skos", "http://www.w3.org/2004/02/skos/core#"),
+    ("foaf", "http://xmlns.com/foaf/0.1/"),
+];
+
+/// Predicates whose value becomes a node's `label`, by priority.
+const LABEL_PREDICATES: [&str; 3] = [
+    "http://www.w3.org/2000/01/rdf-schema#label",
+    "http://www.w3.org/2004/02/skos/core#prefLabel",
+    "http://xmlns.com/foaf/0.1/name",
+];
+
+/// Prefixes declared in a file, mapping each prefix to its namespace IRI.
+pub type Prefixes = HashMap<String, String>;
+
+/// A subject or object of a triple.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Term {
+    Iri(String),
+    /// A blank node, by its label without the `_:`.
+    Blank(String),
+    Literal(Literal),
+}
+
+/// A literal value.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Literal {
+    /// The lexical form, with escapes resolved.
+    pub value: String,
+    /// IRI of the datatype, none for plain strings.
+    pub datatype: Option<String>,
+    pub language: Option<String>,
+}
+
+/// A statement: `subject predicate object`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Triple {
+    pub subject: Term,
+    pub predicate: String,
+    pub object: Term,
+}
+
+/// Errors which can occur while reading an RDF file.
+#[derive(Debug)]
+pub enum RdfError {
+    Io(std::io::Error),
+    /// The file is not valid Turtle.
+    Syntax {
+        line: usize,
+        message: String,
+    },
+}
+
+/// Recursive descent parser over a Turtle document.
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+    base: String,
+    prefixes: Prefixes,
+    /// Number of blank nodes created for `[]` and collections.
+    anonymous: usize,
+    triples: Vec<Triple>,
+}
+
+/// Read a Turtle or N-Triples file from disk.
+pub fn load(path: impl AsRef<Path>) -> Result<Graph, RdfError> {
+    read(&fs::read_to_string(path)?)
+}
+
+/// Parse a Turtle or N-Triples document into a graph.
+pub fn read(source: &str) -> Result<Graph, RdfError> {
+    let (triples, prefixes) = parse(source)?;
+    Ok(to_graph(&triples, &prefixes))
+}
+
+/// Parse a Turtle or N-Triples document into its triples, and the prefixes it
+/// declares.
+pub fn parse(source: &str) -> Result<(Vec<Triple>, Prefixes), RdfError> {
+    let mut parser = Parser {
+        chars: source.trim_start_matches('\u{feff}').chars().collect(),
+        pos: 0,
+        line: 1,
+        base: String::new(),
+        prefixes: Prefixes::new(),
+        anonymous: 0,
+        triples: Vec::new(),
+    };
+    parser.parse_document()?;
+    Ok((parser.triples, parser.prefixes))
+}
+
+/// Build a directed graph from `triples`, as described in the
+/// [module documentation](self). IRIs are shortened with `prefixes`.
+pub fn to_graph(triples: &[Triple], prefixes: &Prefixes) -> Graph {
+    let mut graph = Graph::directed();
+    let mut nodes: HashMap<String, NodeId> = HashMap::new();
+    // the label from the highest priority predicate seen so far
+    let mut labels: HashMap<NodeId, (usize, &str)> = HashMap::new();
+    let mut edges = HashSet::new();
+
+    let mut node = |graph: &mut Graph, term: &Term| -> Option<NodeId> {
+        let (id, label) = match term {
+            Term::Iri(iri) => (iri.clone(), compact(iri, prefixes)),
+            Term::Blank(name) => (format!("_:{name}"), format!("_:{name}")),
+            Term::Literal(_) => return None,
+        };
+        if let Some(&node) = nodes.get(&id) {
+            return Some(node);
+        }
+        let mut attrs = Attributes::default();
+        attrs.insert(ID_ATTR, id.as_str());
+        attrs.insert("label", label);
+        let node = graph.add_node_with(attrs);
+        nodes.insert(id, node);
+        Some(node)
+    };
+
+    for triple in triples {
+        let Some(subject) = node(&mut graph, &triple.subject) else {
+            continue;
+        };
+        match &triple.object {
+            Term::Literal(literal) => {
+                if let Some(priority) = LABEL_PREDICATES.iter().position(|p| *p == triple.predicate)
+                {
+                    if labels.get(&subject).is_none_or(|(p, _)| priority < *p) {
+                        labels.insert(subject, (priority, &literal.value));
+                    }
+                    continue;
+                }
+                let key = Symbol::from(local_name(&triple.predicate));
+                let attrs = graph.node_mut(subject).unwrap();
+                if !attrs.contains(key) {
+                    attrs.insert(key, literal_value(literal));
+                }
+            }
+            object => {
+                let object = node(&mut graph, object).unwrap();
+                if !edges.insert((subject, &triple.predicate, object)) {
+                    continue;
+                }
+                let mut attrs = Attributes::default();
+                attrs.insert(PREDICATE_ATTR, triple.predicate.as_str());
+                attrs.insert(TYPE_ATTR, compact(&triple.predicate, prefixes));
+                graph.add_edge_with(subject, object, attrs).unwrap();
+            }
+        }
+    }
+    for (node, (_, label)) in labels {
+        graph.node_mut(node).unwrap().insert("label", label);
+    }
+    graph
+}
+
+/// Shorten `iri` to `prefix:local` with the longest matching namespace in
+/// `prefixes` or the [well-known](WELL_KNOWN) ones, or else to its local name.
+pub fn compact(iri: &str, prefixes: &Prefixes) -> String {
+    let declared = prefixes.iter().map(|(p, ns)| (p.as_str(), ns.as_str()));
+    declared
+        .chain(WELL_KNOWN)
+        .filter(|(_, namespace)| !namespace.is_empty())
+        .filter_map(|(prefix, namespace)| {
+            let local = iri.strip_prefix(namespace)?;
+            let valid = !local.is_empty() && !local.contains(['/', '#', '?']);
+            valid.then_some((namespace.len(), prefix, local))
+        })
+        .max_by_key(|(len, _, _)| *len)
+        .map_or_else(
+            || local_name(iri).to_string(),
+            |(_, prefix, local)| format!("{prefix}:{local}"),
+        )
+}
+
+/// The part of `iri` after its last `#`, `/` or `:`, or the whole of it if
+/// that is empty.
+pub fn local_name(iri: &str) -> &str {
+    match iri
+        .trim_end_matches(['/', '#'])
+        .rsplit_once(['#', '/', ':'])
+    {
+        Some((_, local)) if !local.is_empty() => local,
+        _ => iri,
+    }
+}
+
+/// Convert a literal to an attribute value, keeping numbers and booleans.
+fn literal_value(literal: &Literal) -> AttrValue {
+    let value = &literal.value;
+    let datatype = literal
+        .datatype
+        .as_deref()
+        .and_then(|d| d.strip_prefix(XSD));
+    let converted = match datatype {
+        Some(
+            "integer" | "int" | "long" | "short" | "byte" | "nonNegativeInteger"
+            | "positiveInteger" | "negativeInteger" | "nonPositiveInteger" | "unsignedInt"
+            | "unsignedLong" | "unsignedShort" | "unsignedByte",
+        ) => value.trim().parse().ok().map(AttrValue::Int),
+        Some("decimal" | "double" | "float") => value.trim().parse().ok().map(AttrValue::Float),
+        Some("boolean") => match value.trim() {
+            "true" | "1" => Some(AttrValue::Bool(true)),
+            "false" | "0" => Some(AttrValue::Bool(false)),
+            _ => None,
+        },
+        _ => None,
+    };
+    converted.unwrap_or_else(|| AttrValue::String(value.clone()))
+}
+
+/// Returns `true` if `c` may appear in a prefix, local name, or blank node
+/// label.
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | '\u{b7}')
+}
+
+impl Parser {
+    fn parse_document(&mut self) -> Result<(), RdfError> {
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                None => return Ok(()),
+                Some('@') => {
+                    self.bump();
+                    let directive: String = self.take_while(|c| c.is_ascii_alphabetic());
+                    match directive.as_str() {
+                        "prefix" => self.parse_prefix()?,
+                        "base" => self.parse_base()?,
+                        _ => return Err(self.error(format!("unknown directive '@{directive}'"))),
+                    }
+                    self.expect('.')?;
+                }
+                // SPARQL-style directives end without a dot
+                _ if self.keyword("PREFIX") => self.parse_prefix()?,
+                _ if self.keyword("BASE") => self.parse_base()?,
+                _ => {
+                    self.parse_triples()?;
+                    self.expect('.')?;
+                }
+            }
+        }
+    }
+
+    fn parse_prefix(&mut self) -> Result<(), RdfError> {
+        self.skip_whitespace();
+        let prefix = self.take_while(is_name_char);
+        if self.bump() != Some(':') {
+            return Err(self.error("expected ':' after the prefix"));
+        }
+        let namespace = self.parse_iriref()?;
+        self.prefixes.insert(prefix, namespace);
+        Ok(())
+    }
+
+    fn parse_base(&mut self) -> Result<(), RdfError> {
+        self.base = self.parse_iriref()?;
+        Ok(())
+    }
+
+    fn parse_triples(&mut self) -> Result<(), RdfError> {
+        self.skip_whitespace();
+        if self.peek() == Some('[') {
+            let subject = self.parse_blank_property_list()?;
+            self.skip_whitespace();
+            if self.peek() != Some('.') {
+                self.parse_predicate_objects(&subject)?;
+            }
+            return Ok(());
+        }
+        let subject = match self.peek() {
+            Some('(') => self.parse_collection()?,
+            Some('_') if self.peek_at(1) == Some(':') => self.parse_blank()?,
+            _ => Term::Iri(self.parse_iri()?),
+        };
+        self.parse_predicate_objects(&subject)
+    }
+
+    fn parse_predicate_objects(&mut self, subject: &Term) -> Result<(), RdfError> {
+        loop {
+            let predicate = self.parse_verb()?;
+            loop {
+                let object = self.parse_object()?;
+                self.triples.push(Triple {
+                    subject: subject.clone(),
+                    predicate: predicate.clone(),
+                    object,
+                });
+                if !self.eat(',') {
+                    break;
+                }
+            }
+            if !self.eat(';') {
+                return Ok(());
+            }
+            while self.eat(';') {}
+            self.skip_whitespace();
+            if matches!(self.peek(), None | Some('.') | Some(']')) {
+                return Ok(());
+            }
+        }
+    }
+
+    fn parse_verb(&mut self) -> Result<String, RdfError> {
+        self.skip_whitespace();
+        if self.peek() == Some('a') && self.peek_at(1).is_none_or(|c| !is_name_char(c) && c != ':')
+        {
+            self.bump();
+            return Ok(format!("{RDF}type"));
+        }
+        self.parse_iri()
+    }
+
+    fn parse_object(&mut self) -> Result<Term, RdfError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('[') => self.parse_blank_property_list(),
+            Some('(') => self.parse_collection(),
+            Some('_') if self.peek_at(1) == Some(':') => self.parse_blank(),
+            Some('"' | '\'') => self.parse_string_literal(),
+            Some(c) if c.is_ascii_digit() || matches!(c, '+' | '-' | '.') => self.parse_number(),
+            _ if self.keyword_followed_by_delimiter("true") => Ok(boolean(true)),
+            _ if self.keyword_followed_by_delimiter("false") => Ok(boolean(false)),
+            _ => Ok(Term::Iri(self.parse_iri()?)),
+        }
+    }
+
+    /// A blank node with the properties between brackets.
+    fn parse_blank_property_list(&mut self) -> Result<Term, RdfError> {
+        self.expect('[')?;
+        let node = self.anonymous_node();
+        self.skip_whitespace();
+        if self.peek() != Some(']') {
+            self.parse_predicate_objects(&node)?;
+        }
+        self.expect(']')?;
+        Ok(node)
+    }
+
+    /// A list of objects between parentheses, as an `rdf:first`/`rdf:rest`
+    /// chain.
+    fn parse_collection(&mut self) -> Result<Term, RdfError> {
+        self.expect('(')?;
+        let mut items = Vec::new();
+        while !self.eat(')') {
+            if self.peek().is_none() {
+                return Err(self.error("unterminated collection"));
+            }
+            items.push(self.parse_object()?);
+        }
+        let mut list = Term::Iri(format!("{RDF}nil"));
+        for item in items.into_iter().rev() {
+            let node = self.anonymous_node();
+            for (predicate, object) in [("first", item), ("rest", list)] {
+                self.triples.push(Triple {
+                    subject: node.clone(),
+                    predicate: format!("{RDF}{predicate}"),
+                    object,
+                });
+            }
+            list = node;
+        }
+        Ok(list)
+    }
+
+    fn parse_blank(&mut self) -> Result<Term, RdfError> {
+        self.pos += 2;
+        let label = self.take_name();
+        if label.is_empty() {
+            return Err(self.error("expected a blank node label"));
+        }
+        Ok(Term::Blank(label))
+    }
+
+    /// An IRI, either between angle brackets or as a prefixed name.
+    fn parse_iri(&mut self) -> Result<String, RdfError> {
+        self.skip_whitespace();
+        if self.peek() == Some('<') {
+            return self.parse_iriref();
+        }
+        let prefix = self.take_while(is_name_char);
+        if self.peek() != Some(':') {
+            return Err(match self.peek() {
+                Some(c) => self.error(format!("unexpected '{prefix}{c}'")),
+                None => self.error("unexpected end of file"),
+            });
+        }
+        self.bump();
+        let Some(namespace) = self.prefixes.get(&prefix).cloned() else {
+            return Err(self.error(format!("undefined prefix '{prefix}:'")));
+        };
+        Ok(namespace + &self.take_name())
+    }
+
+    /// An IRI between angle brackets, resolved against the base IRI.
+    fn parse_iriref(&mut self) -> Result<String, RdfError> {
+        self.expect('<')?;
+        let mut iri = String::new();
+        loop {
+            match self.bump() {
+                Some('>') => break,
+                Some('\\') => iri.push(self.parse_unicode_escape()?),
+                Some('\n') | None => return Err(self.error("unterminated IRI")),
+                Some(c) => iri.push(c),
+            }
+        }
+        Ok(self.resolve(iri))
+    }
+
+    fn parse_string_literal(&mut self) -> Result<Term, RdfError> {
+        let quote = self.bump().unwrap();
+        let long = self.peek() == Some(quote) && self.peek_at(1) == Some(quote);
+        if long {
+            self.pos += 2;
+        }
+        let mut value = String::new();
+        loop {
+            match self.bump() {
+                Some(c) if c == quote => {
+                    if !long {
+                        break;
+                    }
+                    if self.peek() == Some(quote) && self.peek_at(1) == Some(quote) {
+                        self.pos += 2;
+                        break;
+                    }
+                    value.push(c);
+                }
+                Some('\\') => {
+                    let escaped = match self.peek() {
+                        Some('t') => '\t',
+                        Some('b') => '\u{8}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('f') => '\u{c}',
+                        Some(c @ ('"' | '\'' | '\\')) => c,
+                        _ => {
+                            value.push(self.parse_unicode_escape()?);
+                            continue;
+                        }
+                    };
+                    self.bump();
+                    value.push(escaped);
+                }
+                Some('\n') if !long => return Err(self.error("unterminated string")),
+                Some(c) => value.push(c),
+                None => return Err(self.error("unterminated string")),
+            }
+        }
+
+        let mut literal = Literal {
+            value,
+            datatype: None,
+            language: None,
+        };
+        if self.peek() == Some('@') {
+            self.bump();
+            literal.language = Some(self.take_while(|c| c.is_ascii_alphanumeric() || c == '-'));
+        } else if self.peek() == Some('^') && self.peek_at(1) == Some('^') {
+            self.pos += 2;
+            literal.datatype = Some(self.parse_iri()?);
+        }
+        Ok(Term::Literal(literal))
+    }
+
+    fn parse_number(&mut self) -> Result<Term, RdfError> {
+        let mut value = String::new();
+        if let Some(sign @ ('+' | '-')) = self.peek() {
+            value.push(sign);
+            self.bump();
+        }
+        value += &self.take_while(|c| c.is_ascii_digit());
+        let mut datatype = "integer";
+        if self.peek() == Some('.') && self.peek_at(1).is_some_and(|c| c.is_ascii_digit()) {
+            self.bump();
+            value.push('.');
+            value += &self.take_while(|c| c.is_ascii_digit());
+            datatype = "decimal";
+        }
+        if let Some(e @ ('e' | 'E')) = self.peek() {
+            self.bump();
+            value.push(e);
+            if let Some(sign @ ('+' | '-')) = self.peek() {
+                value.push(sign);
+                self.bump();
+            }
+            value += &self.take_while(|c| c.is_ascii_digit());
+            datatype = "double";
+        }
+        if !value.ends_with(|c: char| c.is_ascii_digit()) {
+            return Err(self.error(format!("invalid number '{value}'")));
+        }
+        Ok(Term::Literal(Literal {
+            value,
+            datatype: Some(format!("{XSD}{datatype}")),
+            language: None,
+        }))
+    }
+
+    /// The character of a `\uXXXX` or `\UXXXXXXXX` escape, after the `\`.
+    fn parse_unicode_escape(&mut self) -> Result<char, RdfError> {
+        let len = match self.bump() {
+            Some('u') => 4,
+            Some('U') => 8,
+            _ => return Err(self.error("invalid escape sequence")),
+        };
+        let digits: String = (0..len).filter_map(|_| self.bump()).collect();
+        u32::from_str_radix(&digits, 16)
+            .ok()
+            .and_then(char::from_u32)
+            .ok_or_else(|| self.error(format!("invalid unicode escape '{digits}'")))
+    }
+
+    /// A local name or blank node label. Escaped characters are kept, and a
+    /// trailing `.` is left for the end of the statement.
+    fn take_name(&mut self) -> String {
+        let mut name = String::new();
+        while let Some(c) = self.peek() {
+            if c == '\\' && self.peek_at(1).is_some() {
+                self.bump();
+                name.push(self.bump().unwrap());
+            } else if is_name_char(c) || c == ':' || c == '%' {
+                self.bump();
+                name.push(c);
+            } else {
+                break;
+            }
+        }
+        while name.ends_with('.') {
+            name.pop();
+            self.pos -= 1;
+        }
+        name
+    }
+
+    /// Resolve a relative `iri` against the base IRI.
+    fn resolve(&self, iri: String) -> String {
+        let is_absolute = iri
+            .split_once(':')
+            .is_some_and(|(scheme, _)| !scheme.is_empty() && !scheme.contains(['/', '#', '?']));
+        if is_absolute || self.base.is_empty() {
+            return iri;
+        }
+        if iri.starts_with('#') {
+            let base = self.base.split('#').next().unwrap_or_default();
+            return format!("{base}{iri}");
+        }
+        let base = match self.base.rfind('/') {
+            Some(index) => &self.base[..=index],
+            None => &self.base,
+        };
+        format!("{base}{}", iri.trim_start_matches('/'))
+    }
+
+    fn anonymous_node(&mut self) -> Term {
+        self.anonymous += 1;
+        Term::Blank(format!("anon{}", self.anonymous))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += 1;
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    fn take_while(&mut self, mut predicate: impl FnMut(char) -> bool) -> String {
+        let mut taken = String::new();
+        while let Some(c) = self.peek().filter(|c| predicate(*c)) {
+            self.bump();
+            taken.push(c);
+        }
+        taken
+    }
+
+    /// Skip whitespace and comments.
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek() {
+            if c == '#' {
+                while self.bump().is_some_and(|c| c != '\n') {}
+            } else if c.is_whitespace() {
+                self.bump();
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Skip whitespace, then `c` if it is next.
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        let found = self.peek() == Some(c);
+        if found {
+            self.bump();
+        }
+        found
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), RdfError> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected '{c}'")))
+        }
+    }
+
+    /// Skip `keyword`, compared case-insensitively, if it is next and followed
+    /// by whitespace.
+    fn keyword(&mut self, keyword: &str) -> bool {
+        let len = keyword.len();
+        let matches = self.chars.len() > self.pos + len
+            && self.chars[self.pos..self.pos + len]
+                .iter()
+                .zip(keyword.chars())
+                .all(|(a, b)| a.eq_ignore_ascii_case(&b))
+            && self.chars[self.pos + len].is_whitespace();
+        if matches {
+            self.pos += len;
+        }
+        matches
+    }
+
+    /// Skip `keyword` if it is next and not part of a longer name.
+    fn keyword_followed_by_delimiter(&mut self, keyword: &str) -> bool {
+        let len = keyword.len();
+        let matches = self.chars[self.pos..].starts_with(&keyword.chars().collect::<Vec<_>>())
+            && self
+                .peek_at(len)
+                .is_none_or(|c| !is_name_char(c) && c != ':');
+        if matches {
+            self.pos += len;
+        }
+        matches
+    }
+
+    fn error(&self, message: impl Into<String>) -> RdfError {
+        RdfError::Syntax {
+            line: self.line,
+            message: message.into(),
+        }
+    }
+}
+
+fn boolean(value: bool) -> Term {
+    Term::Literal(Literal {
+        value: value.to_string(),
+        datatype: Some(format!("{XSD}boolean")),
+        language: None,
+    })
+}
+
+impl std::fmt::Display for RdfError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "Failed to read RDF file: {e}"),
+            Self::Syntax { line, message } => write!(f, "Invalid Turtle at line {line}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for RdfError {}
+
+impl From<std::io::Error> for RdfError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
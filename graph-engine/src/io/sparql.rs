@@ -0,0 +1,276 @@
+//! # SPARQL Connector
+//!
+//! Runs SPARQL queries against an endpoint over HTTP, and turns the triples
+//! they return into a [`Graph`] the same way [`rdf`] files are read:
+//! - `CONSTRUCT` and `DESCRIBE` queries are asked for N-Triples or Turtle.
+//! - `SELECT` queries must bind three variables, used as the subject,
+//!   predicate and object of each row: `?s ?p ?o`, `?subject ?predicate
+//!   ?object`, or else the first three.
+//!
+//! Endpoints are reached over `http://` or `https://`, the panel warning that
+//! passwords sent over `http://` to another host are readable on the way.
+//!
+//! The [`SparqlPanel`] opens query results as documents.
+//!
+//! ```ignore
+//! let graph = sparql::query(
+//!     "http://localhost:3030/ds/sparql",
+//!     None,
+//!     "CONSTRUCT { ?s ?p ?o } WHERE { ?s ?p ?o } LIMIT 100",
+//! )?;
+//! ctx.open_document(graph, None);
+//! ```
+
+use serde_json::Value;
+
+use std::thread::JoinHandle;
+
+use super::http::{self, HttpError, basic_auth, url_encode};
+use super::rdf::{self, Literal, Prefixes, RdfError, Term, Triple};
+use crate::context::EngineContext;
+use crate::graph::Graph;
+use crate::i18n::tr;
+use crate::imgui::{InputTextFlags, Ui};
+use crate::subsystems::panels::Panel;
+
+/// Endpoint shown by [`SparqlPanel`] at first, a local Fuseki dataset.
+const DEFAULT_ENDPOINT: &str = "http://localhost:3030/ds/sparql";
+
+/// Color of the warning about passwords sent unencrypted.
+const WARNING_COLOR: [f32; 4] = [1.0, 0.8, 0.3, 1.0];
+
+/// Formats asked for, by preference. Graph results come as triples, and
+/// `SELECT` results as JSON.
+const ACCEPT: &str = "application/n-triples, text/turtle;q=0.9, \
+    application/sparql-results+json;q=0.8";
+
+/// Errors which can occur while querying a SPARQL endpoint.
+#[derive(Debug)]
+pub enum SparqlError {
+    Http(HttpError),
+    /// The returned triples are not valid Turtle.
+    Rdf(RdfError),
+    /// The response is not triples, or bindings of three variables.
+    Parse(String),
+}
+
+/// Runs queries against an endpoint, opening their results as documents.
+pub struct SparqlPanel {
+    url: String,
+    /// User to authenticate as, none if empty.
+    user: String,
+    password: String,
+    query: String,
+    job: Option<JoinHandle<Result<Graph, SparqlError>>>,
+    /// Outcome of the last query, and whether it failed.
+    status: Option<(String, bool)>,
+}
+
+/// Run `query` against the endpoint at `url`, authenticating with a user and
+/// password if given, and return the triples of its result as a directed graph.
+pub fn query(
+    url: &str,
+    credentials: Option<(&str, &str)>,
+    query: &str,
+) -> Result<Graph, SparqlError> {
+    let body = format!("query={}", url_encode(query));
+    let auth = credentials.map(|(user, password)| basic_auth(user, password));
+    let mut headers = vec![
+        ("Accept", ACCEPT),
+        ("Content-Type", "application/x-www-form-urlencoded"),
+    ];
+    if let Some(auth) = &auth {
+        headers.push(("Authorization", auth));
+    }
+    let response = http::post(url, &headers, body.as_bytes())?;
+    let text = String::from_utf8_lossy(&response.body);
+    let content_type = response.header("content-type").unwrap_or_default();
+    if content_type.contains("json") {
+        let results: Value =
+            serde_json::from_str(&text).map_err(|e| SparqlError::Parse(e.to_string()))?;
+        let triples = bindings_to_triples(&results)?;
+        return Ok(rdf::to_graph(&triples, &Prefixes::new()));
+    }
+    if content_type.contains("xml") {
+        return Err(SparqlError::Parse(format!(
+            "unsupported result format '{content_type}'"
+        )));
+    }
+    Ok(rdf::read(&text)?)
+}
+
+/// The triples of `SELECT` query results, see the
+/// [module documentation](self).
+fn bindings_to_triples(results: &Value) -> Result<Vec<Triple>, SparqlError> {
+    let vars: Vec<&str> = results["head"]["vars"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .collect();
+    let names = [["s", "p", "o"], ["subject", "predicate", "object"]]
+        .into_iter()
+        .find(|names| names.iter().all(|n| vars.contains(n)))
+        .or_else(|| vars.get(..3).map(|v| [v[0], v[1], v[2]]))
+        .ok_or_else(|| {
+            SparqlError::Parse("expected triples, or bindings of three variables".into())
+        })?;
+
+    let rows = results["results"]["bindings"]
+        .as_array()
+        .into_iter()
+        .flatten();
+    let triples = rows
+        .filter_map(|row| {
+            let [subject, predicate, object] = names.map(|name| to_term(&row[name]));
+            let Some(Term::Iri(predicate)) = predicate else {
+                return None;
+            };
+            Some(Triple {
+                subject: subject.filter(|s| !matches!(s, Term::Literal(_)))?,
+                predicate,
+                object: object?,
+            })
+        })
+        .collect();
+    Ok(triples)
+}
+
+/// Convert a value of the SPARQL JSON results format to a term.
+fn to_term(value: &Value) -> Option<Term> {
+    let text = value["value"].as_str()?.to_string();
+    Some(match value["type"].as_str()? {
+        "uri" => Term::Iri(text),
+        "bnode" => Term::Blank(text),
+        "literal" | "typed-literal" => Term::Literal(Literal {
+            value: text,
+            datatype: value["datatype"].as_str().map(str::to_owned),
+            language: value["xml:lang"].as_str().map(str::to_owned),
+        }),
+        _ => return None,
+    })
+}
+
+impl SparqlPanel {
+    pub fn new() -> Self {
+        Self {
+            url: DEFAULT_ENDPOINT.into(),
+            user: String::new(),
+            password: String::new(),
+            query: "CONSTRUCT { ?s ?p ?o } WHERE { ?s ?p ?o } LIMIT 500".into(),
+            job: None,
+            status: None,
+        }
+    }
+
+    /// Open the result of the finished query as a new document.
+    fn finish(&mut self, ctx: &mut EngineContext) {
+        let Some(job) = self.job.take_if(|job| job.is_finished()) else {
+            return;
+        };
+        let result = job
+            .join()
+            .unwrap_or_else(|_| Err(SparqlError::Parse("the query thread panicked".into())));
+        match result {
+            Ok(graph) => {
+                self.status = Some((
+                    format!("{} nodes, {} edges", graph.node_count(), graph.edge_count()),
+                    false,
+                ));
+                ctx.open_document(graph, None);
+                ctx.camera.fit_to_graph(&ctx.graph, &ctx.positions);
+            }
+            Err(e) => self.status = Some((e.to_string(), true)),
+        }
+    }
+}
+
+impl Panel for SparqlPanel {
+    fn id(&self) -> &'static str {
+        "SPARQL"
+    }
+
+    fn draw(&mut self, ui: &Ui, ctx: &mut EngineContext) {
+        self.finish(ctx);
+        ui.input_text(tr("Endpoint"), &mut self.url)
+            .hint(DEFAULT_ENDPOINT)
+            .build();
+        ui.input_text(tr("User"), &mut self.user)
+            .hint(tr("(none)"))
+            .build();
+        ui.input_text(tr("Password"), &mut self.password)
+            .flags(InputTextFlags::PASSWORD)
+            .build();
+        if !self.user.is_empty() && http::is_cleartext(self.url.trim()) {
+            ui.text_colored(
+                WARNING_COLOR,
+                tr("The password is sent unencrypted, use an https:// URL"),
+            );
+        }
+        ui.input_text_multiline("##query", &mut self.query, [-1.0, 120.0])
+            .build();
+
+        let running = self.job.is_some();
+        ui.enabled(!running && !self.url.trim().is_empty(), || {
+            if ui.button(tr("Run as New Document")) {
+                let url = self.url.trim().to_string();
+                let text = self.query.clone();
+                let credentials =
+                    (!self.user.is_empty()).then(|| (self.user.clone(), self.password.clone()));
+                self.status = None;
+                self.job = Some(std::thread::spawn(move || {
+                    let credentials = credentials.as_ref().map(|(u, p)| (u.as_str(), p.as_str()));
+                    query(&url, credentials, &text)
+                }));
+            }
+        });
+        if running {
+            ui.text_disabled(tr("Querying..."));
+        }
+        match &self.status {
+            Some((error, true)) => ui.text_colored([1.0, 0.4, 0.4, 1.0], error),
+            Some((status, false)) => ui.text_wrapped(status),
+            None => {}
+        }
+    }
+}
+
+impl From<HttpError> for SparqlError {
+    fn from(e: HttpError) -> Self {
+        Self::Http(e)
+    }
+}
+
+impl From<RdfError> for SparqlError {
+    fn from(e: RdfError) -> Self {
+        Self::Rdf(e)
+    }
+}
+
+impl std::fmt::Display for SparqlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Http(e) => write!(f, "{e}"),
+            Self::Rdf(e) => write!(f, "{e}"),
+            Self::Parse(e) => {
+                let text = tr("Unexpected response: {error}");
+                f.write_str(&text.replace("{error}", &e.to_string()))
+            }
+        }
+    }
+}
+
+impl std::error::Error for SparqlError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Http(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl std::default::Default for SparqlPanel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,361 @@
+//! A minimal streaming XML reader and escaping helpers, shared by the XML-based
+//! graph formats.
+//!
+//! Only what graph files need is supported: elements, attributes, text, CDATA,
+//! and the predefined and numeric entities. Comments, processing instructions,
+//! and DOCTYPE declarations are skipped. Namespaces are not resolved; compare
+//! names with [`local_name()`].
+
+use std::io::BufRead;
+
+/// A single item read from an XML document.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum XmlEvent {
+    /// An opening tag. Self-closing tags are followed by a matching [`Self::End`].
+    Start {
+        name: String,
+        attrs: Vec<(String, String)>,
+    },
+    /// A closing tag.
+    End { name: String },
+    /// Character data, with entities decoded. Whitespace-only text is skipped.
+    Text(String),
+    /// The end of the document.
+    Eof,
+}
+
+/// Errors which can occur while reading XML.
+#[derive(Debug)]
+pub enum XmlError {
+    Io(std::io::Error),
+    /// The document is malformed.
+    Syntax {
+        line: usize,
+        message: String,
+    },
+}
+
+/// Pull parser reading [`XmlEvent`]s from a buffered reader, one at a time, so
+/// that memory use does not grow with the document's size.
+pub(crate) struct XmlReader<R: BufRead> {
+    inner: R,
+    /// Current line, 1-based, for error messages.
+    line: usize,
+    /// Closing tag to report next, after a self-closing tag.
+    pending_end: Option<String>,
+    /// Scratch buffer for text and names.
+    buf: Vec<u8>,
+}
+
+/// The part of a qualified name after its namespace prefix, eg. `node` for `g:node`.
+pub(crate) fn local_name(name: &str) -> &str {
+    name.rsplit_once(':').map_or(name, |(_, local)| local)
+}
+
+/// Look up `key` in a start tag's attributes.
+pub(crate) fn attr<'a>(attrs: &'a [(String, String)], key: &str) -> Option<&'a str> {
+    attrs
+        .iter()
+        .find(|(k, _)| local_name(k) == key)
+        .map(|(_, v)| v.as_str())
+}
+
+/// Escape `text` for use in element content or attribute values.
+pub(crate) fn escape(text: &str) -> std::borrow::Cow<'_, str> {
+    if !text.contains(['&', '<', '>', '"', '\'']) {
+        return text.into();
+    }
+    let mut escaped = String::with_capacity(text.len() + 8);
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped.into()
+}
+
+impl<R: BufRead> XmlReader<R> {
+    pub(crate) fn new(inner: R) -> Self {
+        Self {
+            inner,
+            line: 1,
+            pending_end: None,
+            buf: Vec::new(),
+        }
+    }
+
+    /// The line currently being read, 1-based.
+    pub(crate) fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Read the next event.
+    pub(crate) fn next_event(&mut self) -> Result<XmlEvent, XmlError> {
+        if let Some(name) = self.pending_end.take() {
+            return Ok(XmlEvent::End { name });
+        }
+
+        loop {
+            let Some(c) = self.peek()? else {
+                return Ok(XmlEvent::Eof);
+            };
+            if c != b'<' {
+                let text = self.read_text()?;
+                if !text.trim().is_empty() {
+                    return Ok(XmlEvent::Text(text));
+                }
+                continue;
+            }
+
+            self.bump();
+            match self.peek()? {
+                Some(b'?') => self.skip_until(b"?>")?,
+                Some(b'!') => {
+                    self.bump();
+                    if self.eat(b"--")? {
+                        self.skip_until(b"-->")?;
+                    } else if self.eat(b"[CDATA[")? {
+                        let text = self.read_until(b"]]>")?;
+                        return Ok(XmlEvent::Text(text));
+                    } else {
+                        self.skip_declaration()?;
+                    }
+                }
+                Some(b'/') => {
+                    self.bump();
+                    let name = self.read_name()?;
+                    self.skip_whitespace()?;
+                    self.expect(b'>')?;
+                    return Ok(XmlEvent::End { name });
+                }
+                Some(_) => return self.read_start_tag(),
+                None => return Err(self.error("Unexpected end of document in tag")),
+            }
+        }
+    }
+
+    fn read_start_tag(&mut self) -> Result<XmlEvent, XmlError> {
+        let name = self.read_name()?;
+        let mut attrs = Vec::new();
+        loop {
+            self.skip_whitespace()?;
+            match self.peek()? {
+                Some(b'>') => {
+                    self.bump();
+                    break;
+                }
+                Some(b'/') => {
+                    self.bump();
+                    self.expect(b'>')?;
+                    self.pending_end = Some(name.clone());
+                    break;
+                }
+                Some(_) => {
+                    let key = self.read_name()?;
+                    self.skip_whitespace()?;
+                    self.expect(b'=')?;
+                    self.skip_whitespace()?;
+                    let quote = match self.peek()? {
+                        Some(q @ (b'"' | b'\'')) => q,
+                        _ => return Err(self.error("Expected a quoted attribute value")),
+                    };
+                    self.bump();
+                    self.buf.clear();
+                    loop {
+                        match self.next_byte()? {
+                            Some(c) if c == quote => break,
+                            Some(c) => self.buf.push(c),
+                            None => return Err(self.error("Unterminated attribute value")),
+                        }
+                    }
+                    let value = self.take_string()?;
+                    attrs.push((key, self.unescape(&value)?));
+                }
+                None => return Err(self.error("Unexpected end of document in tag")),
+            }
+        }
+        Ok(XmlEvent::Start { name, attrs })
+    }
+
+    /// Read character data up to the next `<`.
+    fn read_text(&mut self) -> Result<String, XmlError> {
+        self.buf.clear();
+        while let Some(c) = self.peek()? {
+            if c == b'<' {
+                break;
+            }
+            self.bump();
+            self.buf.push(c);
+        }
+        let text = self.take_string()?;
+        self.unescape(&text)
+    }
+
+    fn read_name(&mut self) -> Result<String, XmlError> {
+        self.buf.clear();
+        while let Some(c) = self.peek()? {
+            if c.is_ascii_whitespace() || matches!(c, b'>' | b'/' | b'=') {
+                break;
+            }
+            self.bump();
+            self.buf.push(c);
+        }
+        if self.buf.is_empty() {
+            return Err(self.error("Expected a name"));
+        }
+        self.take_string()
+    }
+
+    /// Read raw text up to and excluding `end`, consuming `end`.
+    fn read_until(&mut self, end: &[u8]) -> Result<String, XmlError> {
+        self.buf.clear();
+        while !self.buf.ends_with(end) {
+            match self.next_byte()? {
+                Some(c) => self.buf.push(c),
+                None => return Err(self.error("Unexpected end of document")),
+            }
+        }
+        self.buf.truncate(self.buf.len() - end.len());
+        self.take_string()
+    }
+
+    fn skip_until(&mut self, end: &[u8]) -> Result<(), XmlError> {
+        self.read_until(end).map(drop)
+    }
+
+    /// Skip a `<!...>` declaration, including an internal DOCTYPE subset.
+    fn skip_declaration(&mut self) -> Result<(), XmlError> {
+        let mut depth = 0usize;
+        loop {
+            match self.next_byte()? {
+                Some(b'[') => depth += 1,
+                Some(b']') => depth = depth.saturating_sub(1),
+                Some(b'>') if depth == 0 => return Ok(()),
+                Some(_) => {}
+                None => return Err(self.error("Unterminated declaration")),
+            }
+        }
+    }
+
+    fn skip_whitespace(&mut self) -> Result<(), XmlError> {
+        while let Some(c) = self.peek()? {
+            if !c.is_ascii_whitespace() {
+                break;
+            }
+            self.bump();
+        }
+        Ok(())
+    }
+
+    /// Consume `expected` if the input starts with it. Only the first byte is
+    /// looked ahead, so a partial match is an error.
+    fn eat(&mut self, expected: &[u8]) -> Result<bool, XmlError> {
+        if self.peek()? != expected.first().copied() {
+            return Ok(false);
+        }
+        for c in expected {
+            self.expect(*c)?;
+        }
+        Ok(true)
+    }
+
+    fn expect(&mut self, expected: u8) -> Result<(), XmlError> {
+        match self.next_byte()? {
+            Some(c) if c == expected => Ok(()),
+            _ => Err(self.error(format!("Expected '{}'", expected as char))),
+        }
+    }
+
+    fn peek(&mut self) -> Result<Option<u8>, XmlError> {
+        Ok(self.inner.fill_buf()?.first().copied())
+    }
+
+    /// Consume the byte returned by the last [`Self::peek()`].
+    fn bump(&mut self) {
+        if self
+            .inner
+            .fill_buf()
+            .is_ok_and(|b| b.first() == Some(&b'\n'))
+        {
+            self.line += 1;
+        }
+        self.inner.consume(1);
+    }
+
+    fn next_byte(&mut self) -> Result<Option<u8>, XmlError> {
+        let c = self.peek()?;
+        if c.is_some() {
+            self.bump();
+        }
+        Ok(c)
+    }
+
+    fn take_string(&mut self) -> Result<String, XmlError> {
+        String::from_utf8(std::mem::take(&mut self.buf)).map_err(|_| self.error("Invalid UTF-8"))
+    }
+
+    /// Decode the predefined and numeric entities in `text`.
+    fn unescape(&self, text: &str) -> Result<String, XmlError> {
+        if !text.contains('&') {
+            return Ok(text.to_owned());
+        }
+        let mut decoded = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find('&') {
+            decoded.push_str(&rest[..start]);
+            let Some(end) = rest[start..].find(';') else {
+                return Err(self.error("Unterminated entity"));
+            };
+            let entity = &rest[start + 1..start + end];
+            let c = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                _ => entity
+                    .strip_prefix("#x")
+                    .map(|hex| u32::from_str_radix(hex, 16))
+                    .or_else(|| entity.strip_prefix('#').map(str::parse))
+                    .and_then(Result::ok)
+                    .and_then(char::from_u32),
+            };
+            match c {
+                Some(c) => decoded.push(c),
+                None => return Err(self.error(format!("Unknown entity '&{entity};'"))),
+            }
+            rest = &rest[start + end + 1..];
+        }
+        decoded.push_str(rest);
+        Ok(decoded)
+    }
+
+    fn error(&self, message: impl Into<String>) -> XmlError {
+        XmlError::Syntax {
+            line: self.line,
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for XmlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "{e}"),
+            Self::Syntax { line, message } => write!(f, "Line {line}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for XmlError {}
+
+impl From<std::io::Error> for XmlError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
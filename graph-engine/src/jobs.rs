@@ -0,0 +1,624 @@
+//! # Background Jobs
+//!
+//! A [`JobPool`] runs work on a few worker threads so the ui stays responsive:
+//! [metrics](crate::algo::metrics), [file loads](crate::io::loader), and the
+//! iterations of the [force layout](crate::layout::force) on large graphs.
+//!
+//! Jobs are [spawned](JobPool::spawn) with a name and a [`Priority`]. The
+//! queued job of highest priority runs first, in the order they were spawned
+//! for equal priorities. Each shares a [`Progress`] with its spawner, through
+//! which it reports how far along it is and learns that it was cancelled, eg.
+//! from the [`JobsPanel`]. Its result is handed back through its
+//! [`JobHandle`], and a [`JobEvent`] is posted once it ends, which
+//! [`EngineContext::refresh()`] drains to the log.
+//!
+//! [`JobPool::map()`] instead splits work across the workers and waits for it,
+//! eg. to compute the forces on all nodes at once.
+//!
+//! ```ignore
+//! let jobs = JobPool::new();
+//! let progress = Arc::new(Progress::new());
+//! let shared = progress.clone();
+//! let job = jobs.spawn("Count", Priority::Normal, &progress, move || {
+//!     (0..100).inspect(|i| shared.set(*i as f32 / 100.0)).sum::<u32>()
+//! });
+//! // each frame
+//! if job.is_finished() {
+//!     let sum = job.join();
+//! }
+//!
+//! let squares = jobs.map(8, |i| i * i);
+//! ```
+
+use std::collections::{BinaryHeap, VecDeque};
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use parking_lot::{Condvar, Mutex};
+
+use crate::context::EngineContext;
+use crate::i18n::tr;
+use crate::imgui::{ProgressBar, TableFlags, Ui};
+use crate::subsystems::crash;
+use crate::subsystems::panels::Panel;
+
+/// Fewest worker threads a pool runs, so that a long job leaves room for others.
+const MIN_WORKERS: usize = 2;
+/// Number of ended jobs listed by [`JobPool::recent()`].
+const RECENT_LEN: usize = 16;
+
+const ERROR_COLOR: [f32; 4] = [1.0, 0.4, 0.4, 1.0];
+
+/// Which queued job runs first.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Priority {
+    /// Eg. metrics, which the user may wait a while for.
+    Low,
+    #[default]
+    Normal,
+    /// Eg. work the current frame waits for.
+    High,
+}
+
+/// Progress of a job, shared with the thread running it, which also tells it
+/// to stop.
+#[derive(Debug, Default)]
+pub struct Progress {
+    /// Fraction done, as the bits of an `f32`.
+    done: AtomicU32,
+    cancelled: AtomicBool,
+}
+
+/// Worker threads running jobs, see the [module documentation](self).
+///
+/// Clones share the same workers, which stop once every clone is dropped,
+/// after cancelling the jobs still queued.
+#[derive(Clone)]
+pub struct JobPool {
+    owner: Arc<Owner>,
+}
+
+/// The result of a job spawned on a [`JobPool`], like a
+/// [`JoinHandle`](std::thread::JoinHandle). Dropping it lets the job run on.
+pub struct JobHandle<T> {
+    id: u64,
+    result: Arc<Mutex<Option<std::thread::Result<T>>>>,
+    shared: Arc<Shared>,
+}
+
+/// A job queued or running on a [`JobPool`], see [`JobPool::jobs()`].
+#[derive(Clone, Debug)]
+pub struct JobInfo {
+    id: u64,
+    name: String,
+    priority: Priority,
+    progress: Arc<Progress>,
+    /// When the job started running, if it did.
+    started: Option<Instant>,
+}
+
+/// Posted once a job ended, see [`JobPool::drain_events()`].
+#[derive(Clone, Debug)]
+pub struct JobEvent {
+    pub id: u64,
+    pub name: String,
+    pub outcome: JobOutcome,
+    /// How long the job ran.
+    pub elapsed: Duration,
+}
+
+/// How a job ended.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum JobOutcome {
+    Finished,
+    /// Returned after being [cancelled](Progress::cancel), its result likely
+    /// incomplete.
+    Cancelled,
+    /// Panicked, with the panic's message.
+    Panicked(String),
+}
+
+/// Lists the jobs of the [`EngineContext::jobs`] pool, with buttons cancelling
+/// them, and the jobs which ended last.
+#[derive(Default)]
+pub struct JobsPanel;
+
+/// Stops the workers once the last [`JobPool`] clone is dropped.
+struct Owner {
+    shared: Arc<Shared>,
+    workers: usize,
+}
+
+/// State shared by a pool's workers and handles.
+#[derive(Default)]
+struct Shared {
+    state: Mutex<State>,
+    /// Signalled when a job is queued, or the pool stops.
+    queued: Condvar,
+    /// Signalled when a job or a task of [`JobPool::map()`] ends.
+    ended: Condvar,
+}
+
+#[derive(Default)]
+struct State {
+    queue: BinaryHeap<Queued>,
+    /// The named jobs queued or running, in the order they were spawned.
+    jobs: Vec<JobInfo>,
+    events: Vec<JobEvent>,
+    recent: VecDeque<JobEvent>,
+    next_id: u64,
+    stopped: bool,
+}
+
+/// Work waiting for a worker.
+struct Queued {
+    priority: Priority,
+    id: u64,
+    run: Box<dyn FnOnce() + Send>,
+}
+
+/// The tasks of a [`JobPool::map()`] call, taken one by one by the calling
+/// thread and the workers which get to them.
+struct Batch {
+    next: AtomicUsize,
+    tasks: usize,
+    /// Number of workers taking tasks.
+    running: AtomicUsize,
+    panicked: AtomicBool,
+    /// The caller's task function, with its lifetime erased. It outlives every
+    /// call through it, as the caller waits for the tasks taken to end, and no
+    /// task is taken once all were.
+    task: *const (dyn Fn(usize) + Sync),
+}
+
+// SAFETY: `task` is only called while the caller's function is alive, see
+// `Batch::task`, and is `Sync`.
+unsafe impl Send for Batch {}
+unsafe impl Sync for Batch {}
+
+impl Priority {
+    pub const ALL: [Priority; 3] = [Priority::Low, Priority::Normal, Priority::High];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Priority::Low => "Low",
+            Priority::Normal => "Normal",
+            Priority::High => "High",
+        }
+    }
+}
+
+impl Progress {
+    /// Create progress at zero.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fraction of the work done, from `0.0` to `1.0`.
+    pub fn fraction(&self) -> f32 {
+        f32::from_bits(self.done.load(Ordering::Relaxed))
+    }
+
+    /// Report the fraction of the work done.
+    pub fn set(&self, fraction: f32) {
+        self.done
+            .store(fraction.clamp(0.0, 1.0).to_bits(), Ordering::Relaxed);
+    }
+
+    /// Ask the computation to stop early. Its results are then incomplete.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns `true` once [`Self::cancel()`] was called.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+impl JobPool {
+    /// Start a pool with a worker per core but one, left to the ui.
+    pub fn new() -> Self {
+        let cores = std::thread::available_parallelism().map_or(MIN_WORKERS, |n| n.get());
+        Self::with_workers(cores.saturating_sub(1))
+    }
+
+    /// Start a pool with `workers` worker threads, at least two.
+    pub fn with_workers(workers: usize) -> Self {
+        let workers = workers.max(MIN_WORKERS);
+        let shared = Arc::new(Shared::default());
+        for i in 0..workers {
+            let shared = shared.clone();
+            std::thread::Builder::new()
+                .name(format!("job worker {i}"))
+                .spawn(move || work(&shared))
+                .expect("Failed to spawn a job worker");
+        }
+        Self {
+            owner: Arc::new(Owner { shared, workers }),
+        }
+    }
+
+    /// Number of worker threads.
+    pub fn workers(&self) -> usize {
+        self.owner.workers
+    }
+
+    /// Queue `job`, named `name` in the [`JobsPanel`], sharing `progress` with
+    /// it. The job should stop early once `progress` is cancelled.
+    pub fn spawn<T: Send + 'static>(
+        &self,
+        name: impl Into<String>,
+        priority: Priority,
+        progress: &Arc<Progress>,
+        job: impl FnOnce() -> T + Send + 'static,
+    ) -> JobHandle<T> {
+        let shared = &self.owner.shared;
+        let result = Arc::new(Mutex::new(None));
+        let mut state = shared.state.lock();
+        let id = state.next_id;
+        state.next_id += 1;
+        state.jobs.push(JobInfo {
+            id,
+            name: name.into(),
+            priority,
+            progress: progress.clone(),
+            started: None,
+        });
+
+        let slot = result.clone();
+        let owner = shared.clone();
+        let run = move || {
+            let start = Instant::now();
+            if let Some(info) = owner.state.lock().jobs.iter_mut().find(|j| j.id == id) {
+                info.started = Some(start);
+            }
+            // a failing job is reported in the log, not as a crash
+            let outcome = crash::catch_unwind(AssertUnwindSafe(job));
+            let panicked = outcome.as_ref().err().map(|e| panic_message(e.as_ref()));
+            *slot.lock() = Some(outcome);
+
+            let mut state = owner.state.lock();
+            let Some(index) = state.jobs.iter().position(|j| j.id == id) else {
+                return;
+            };
+            let info = state.jobs.remove(index);
+            let outcome = match panicked {
+                Some(message) => JobOutcome::Panicked(message),
+                None if info.progress.is_cancelled() => JobOutcome::Cancelled,
+                None => JobOutcome::Finished,
+            };
+            let event = JobEvent {
+                id,
+                name: info.name,
+                outcome,
+                elapsed: start.elapsed(),
+            };
+            if state.recent.len() == RECENT_LEN {
+                state.recent.pop_back();
+            }
+            state.recent.push_front(event.clone());
+            state.events.push(event);
+            owner.ended.notify_all();
+        };
+        state.queue.push(Queued {
+            priority,
+            id,
+            run: Box::new(run),
+        });
+        shared.queued.notify_one();
+        JobHandle {
+            id,
+            result,
+            shared: shared.clone(),
+        }
+    }
+
+    /// Call `task` with each index in `0..tasks`, spread over the workers and
+    /// the calling thread, and return the results in order once all are done.
+    ///
+    /// Panics if a task panicked.
+    pub fn map<R: Send>(&self, tasks: usize, task: impl Fn(usize) -> R + Sync) -> Vec<R> {
+        let results: Vec<Mutex<Option<R>>> = (0..tasks).map(|_| Mutex::new(None)).collect();
+        self.run_batch(tasks, &|i| *results[i].lock() = Some(task(i)));
+        results
+            .into_iter()
+            .map(|result| result.into_inner().expect("every task ran"))
+            .collect()
+    }
+
+    fn run_batch(&self, tasks: usize, task: &(dyn Fn(usize) + Sync)) {
+        let shared = &self.owner.shared;
+        // SAFETY: only the lifetime is erased, see `Batch::task`
+        let task: *const (dyn Fn(usize) + Sync + 'static) = unsafe { std::mem::transmute(task) };
+        let batch = Arc::new(Batch {
+            next: AtomicUsize::new(0),
+            tasks,
+            running: AtomicUsize::new(0),
+            panicked: AtomicBool::new(false),
+            task,
+        });
+        let helpers = tasks.saturating_sub(1).min(self.workers());
+        if helpers > 0 {
+            let mut state = shared.state.lock();
+            for _ in 0..helpers {
+                let id = state.next_id;
+                state.next_id += 1;
+                let (batch, owner) = (batch.clone(), shared.clone());
+                let run = move || {
+                    batch.running.fetch_add(1, Ordering::SeqCst);
+                    batch.take_tasks();
+                    batch.running.fetch_sub(1, Ordering::SeqCst);
+                    let _state = owner.state.lock();
+                    owner.ended.notify_all();
+                };
+                state.queue.push(Queued {
+                    priority: Priority::High,
+                    id,
+                    run: Box::new(run),
+                });
+            }
+            shared.queued.notify_all();
+        }
+
+        batch.take_tasks();
+        // helpers which did not start yet find no task left
+        let mut state = shared.state.lock();
+        while batch.running.load(Ordering::SeqCst) > 0 {
+            shared.ended.wait(&mut state);
+        }
+        drop(state);
+        if batch.panicked.load(Ordering::SeqCst) {
+            panic!("A task of JobPool::map() panicked");
+        }
+    }
+
+    /// The named jobs queued or running, in the order they were spawned.
+    pub fn jobs(&self) -> Vec<JobInfo> {
+        self.owner.shared.state.lock().jobs.clone()
+    }
+
+    /// Returns `true` while named jobs are queued or running.
+    pub fn is_busy(&self) -> bool {
+        !self.owner.shared.state.lock().jobs.is_empty()
+    }
+
+    /// Ask the job with `id` to stop, if still queued or running.
+    pub fn cancel(&self, id: u64) {
+        let state = self.owner.shared.state.lock();
+        if let Some(job) = state.jobs.iter().find(|job| job.id == id) {
+            job.progress.cancel();
+        }
+    }
+
+    /// The events posted since the last call, oldest first.
+    pub fn drain_events(&self) -> Vec<JobEvent> {
+        std::mem::take(&mut self.owner.shared.state.lock().events)
+    }
+
+    /// The jobs which ended last, latest first.
+    pub fn recent(&self) -> Vec<JobEvent> {
+        let state = self.owner.shared.state.lock();
+        state.recent.iter().cloned().collect()
+    }
+}
+
+impl<T> JobHandle<T> {
+    /// Id of the job, eg. to [cancel](JobPool::cancel) it.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Returns `true` once the job returned or panicked.
+    pub fn is_finished(&self) -> bool {
+        self.result.lock().is_some()
+    }
+
+    /// Wait for the job to end, returning its result, or the payload of its
+    /// panic.
+    pub fn join(self) -> std::thread::Result<T> {
+        let mut state = self.shared.state.lock();
+        loop {
+            if let Some(result) = self.result.lock().take() {
+                return result;
+            }
+            self.shared.ended.wait(&mut state);
+        }
+    }
+}
+
+impl JobInfo {
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn priority(&self) -> Priority {
+        self.priority
+    }
+
+    pub fn progress(&self) -> &Progress {
+        &self.progress
+    }
+
+    /// How long the job has been running, or `None` while queued.
+    pub fn elapsed(&self) -> Option<Duration> {
+        self.started.map(|start| start.elapsed())
+    }
+}
+
+impl JobOutcome {
+    pub fn label(&self) -> &'static str {
+        match self {
+            JobOutcome::Finished => "Finished",
+            JobOutcome::Cancelled => "Cancelled",
+            JobOutcome::Panicked(_) => "Failed",
+        }
+    }
+}
+
+impl Batch {
+    /// Run tasks until none is left.
+    fn take_tasks(&self) {
+        loop {
+            let i = self.next.fetch_add(1, Ordering::SeqCst);
+            if i >= self.tasks {
+                return;
+            }
+            // SAFETY: a task was taken, so the caller still waits, see `Batch::task`
+            let task = unsafe { &*self.task };
+            if crash::catch_unwind(AssertUnwindSafe(|| task(i))).is_err() {
+                self.panicked.store(true, Ordering::SeqCst);
+            }
+        }
+    }
+}
+
+impl Drop for Owner {
+    fn drop(&mut self) {
+        let mut state = self.shared.state.lock();
+        state.stopped = true;
+        for job in &state.jobs {
+            job.progress.cancel();
+        }
+        self.shared.queued.notify_all();
+    }
+}
+
+impl Ord for Queued {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        // the oldest job first among those of the highest priority
+        (self.priority, other.id).cmp(&(other.priority, self.id))
+    }
+}
+
+impl PartialOrd for Queued {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Queued {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for Queued {}
+
+/// Run queued work until the pool stops and its queue is empty.
+fn work(shared: &Shared) {
+    loop {
+        let mut state = shared.state.lock();
+        let queued = loop {
+            if let Some(queued) = state.queue.pop() {
+                break queued;
+            }
+            if state.stopped {
+                return;
+            }
+            shared.queued.wait(&mut state);
+        };
+        drop(state);
+        (queued.run)();
+    }
+}
+
+/// The message of a panic, if it has one.
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+impl Panel for JobsPanel {
+    fn id(&self) -> &'static str {
+        "Jobs"
+    }
+
+    fn draw(&mut self, ui: &Ui, ctx: &mut EngineContext) {
+        let jobs = ctx.jobs.jobs();
+        ui.text_disabled(tr("{count} workers").replace("{count}", &ctx.jobs.workers().to_string()));
+        if jobs.is_empty() {
+            ui.text_disabled(tr("No jobs running"));
+        } else if let Some(table) = ui.begin_table_with_flags(
+            "##jobs",
+            3,
+            TableFlags::ROW_BG | TableFlags::BORDERS_INNER_H | TableFlags::SIZING_STRETCH_PROP,
+        ) {
+            ui.table_setup_column(tr("Job"));
+            ui.table_setup_column(tr("Progress"));
+            ui.table_setup_column("##cancel");
+            ui.table_headers_row();
+            for job in &jobs {
+                let _id = ui.push_id(job.id().to_string());
+                ui.table_next_row();
+
+                ui.table_next_column();
+                ui.text(job.name());
+                if ui.is_item_hovered() {
+                    ui.tooltip_text(
+                        tr("{priority} priority")
+                            .replace("{priority}", &tr(job.priority().label())),
+                    );
+                }
+
+                ui.table_next_column();
+                let progress = job.progress();
+                let status = match job.elapsed() {
+                    _ if progress.is_cancelled() => "Cancelling...".to_string(),
+                    None => "Queued".to_string(),
+                    Some(elapsed) => format!("{:.1}s", elapsed.as_secs_f32()),
+                };
+                ProgressBar::new(progress.fraction())
+                    .overlay_text(status)
+                    .build(ui);
+
+                ui.table_next_column();
+                ui.enabled(!progress.is_cancelled(), || {
+                    if ui.small_button(tr("Cancel")) {
+                        ctx.jobs.cancel(job.id());
+                    }
+                });
+            }
+            table.end();
+        }
+
+        let recent = ctx.jobs.recent();
+        if recent.is_empty() {
+            return;
+        }
+        ui.separator();
+        ui.text(tr("Recently ended"));
+        for event in &recent {
+            let text = format!(
+                "{}: {} after {:.1}s",
+                event.name,
+                event.outcome.label(),
+                event.elapsed.as_secs_f32()
+            );
+            match &event.outcome {
+                JobOutcome::Panicked(message) => {
+                    ui.text_colored(ERROR_COLOR, text);
+                    if ui.is_item_hovered() {
+                        ui.tooltip_text(message);
+                    }
+                }
+                _ => ui.text_disabled(text),
+            }
+        }
+    }
+}
+
+impl std::default::Default for JobPool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,240 @@
+//! # Graph Layout
+//!
+//! Layouts place the nodes of a [`Graph`] in the plane, writing to a
+//! [`Positions`] table kept alongside the graph:
+//! - [`force`], a live force-directed layout
+//! - [`layered`], a hierarchical layout for DAG-like graphs, which also routes
+//!   edges through bend points
+//! - [`circular`], [`grid`], and [`radial`], simple deterministic layouts
+//! - [`group`], packing the members of each group inside its box
+//!
+//! [`arrange`] places the selected nodes by hand instead, eg. aligning them.
+//!
+//! [`bundling`] bundles the edges of a placed graph, see
+//! [`routing`](crate::routing).
+//!
+//! Each implements [`Layout`], so they are interchangeable, eg. in the
+//! [`LayoutPanel`].
+//!
+//! Positions are in world units, as drawn by the [`Scene`](crate::subsystems::scene::Scene).
+
+pub mod arrange;
+pub mod bundling;
+pub mod circular;
+pub mod force;
+pub mod gpu;
+pub mod grid;
+pub mod group;
+pub mod layered;
+pub mod radial;
+
+use std::collections::HashMap;
+
+use crate::context::EngineContext;
+use crate::graph::{EdgeId, Graph, NodeId};
+use crate::i18n::tr;
+use crate::imgui::Ui;
+use crate::subsystems::panels::Panel;
+
+/// Node attribute read as the initial x coordinate, eg. from GEXF or JSON files.
+pub const X_ATTR: &str = "x";
+/// Node attribute read as the initial y coordinate.
+pub const Y_ATTR: &str = "y";
+
+/// Distance between nodes seeded by [`Positions::sync()`], in world units.
+const SEED_SPACING: f32 = 30.0;
+/// Angle between consecutive seeded nodes, spreading them evenly on a spiral.
+const GOLDEN_ANGLE: f32 = 2.399_963;
+
+/// An algorithm placing the nodes of a graph.
+pub trait Layout {
+    /// Human readable name, eg. for a menu.
+    fn name(&self) -> &'static str;
+
+    /// Place every node of `graph`, replacing their positions and bends.
+    fn compute(&mut self, graph: &Graph, positions: &mut Positions);
+
+    /// Issue widgets editing the layout's settings.
+    ///
+    /// Returns `true` if a setting changed. Defaults to no settings.
+    fn settings_ui(&mut self, _ui: &Ui) -> bool {
+        false
+    }
+}
+
+/// Position of every node of a graph, indexed by [`NodeId`], and the bend points
+/// of edges routed by a layout.
+#[derive(Clone, Debug, Default)]
+pub struct Positions {
+    points: Vec<Option<[f32; 2]>>,
+    /// Points an edge passes through between its endpoints, from source to target.
+    /// Edges without an entry are straight.
+    bends: HashMap<EdgeId, Vec<[f32; 2]>>,
+    /// Increased by every change, see [`Self::generation()`].
+    generation: u64,
+}
+
+impl Positions {
+    /// Create positions for every node of `graph`, see [`Self::sync()`].
+    pub fn new(graph: &Graph) -> Self {
+        let mut positions = Self::default();
+        positions.sync(graph);
+        positions
+    }
+
+    /// Give a position to every node of `graph` which does not have one yet.
+    ///
+    /// Nodes with numeric [`X_ATTR`] and [`Y_ATTR`] attributes are placed there,
+    /// others on a spiral around the origin.
+    pub fn sync(&mut self, graph: &Graph) {
+        if self.points.len() < graph.node_bound() {
+            self.points.resize(graph.node_bound(), None);
+        }
+        for node in graph.nodes() {
+            if self.points[node.index()].is_some() {
+                continue;
+            }
+            let attrs = graph.node(node).unwrap();
+            let coord = |name| attrs.get(name).and_then(|v| v.as_f64()).map(|v| v as f32);
+            let point = match (coord(X_ATTR), coord(Y_ATTR)) {
+                (Some(x), Some(y)) => [x, y],
+                _ => seed(node.index()),
+            };
+            self.points[node.index()] = Some(point);
+            self.generation += 1;
+        }
+    }
+
+    /// Counter increased by every change, so users of the positions, eg. a
+    /// [`SpatialIndex`](crate::spatial::SpatialIndex), can tell whether they moved.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Bytes the positions and bends hold on the heap.
+    pub fn heap_size(&self) -> usize {
+        let bends: usize = self
+            .bends
+            .values()
+            .map(|points| points.capacity() * size_of::<[f32; 2]>())
+            .sum();
+        self.points.capacity() * size_of::<Option<[f32; 2]>>()
+            + self.bends.capacity() * size_of::<(EdgeId, Vec<[f32; 2]>)>()
+            + bends
+    }
+
+    /// Position of `node`, or `None` if it has none.
+    pub fn get(&self, node: NodeId) -> Option<[f32; 2]> {
+        self.points.get(node.index()).copied().flatten()
+    }
+
+    /// Move `node` to `point`.
+    pub fn set(&mut self, node: NodeId, point: [f32; 2]) {
+        if self.points.len() <= node.index() {
+            self.points.resize(node.index() + 1, None);
+        }
+        self.points[node.index()] = Some(point);
+        self.generation += 1;
+    }
+
+    /// Bend points of `edge`, from its source to its target. Empty if the edge
+    /// is drawn straight.
+    pub fn bends(&self, edge: EdgeId) -> &[[f32; 2]] {
+        self.bends.get(&edge).map_or(&[], Vec::as_slice)
+    }
+
+    /// Route `edge` through `bends`, from its source to its target.
+    pub fn set_bends(&mut self, edge: EdgeId, bends: Vec<[f32; 2]>) {
+        if bends.is_empty() {
+            self.bends.remove(&edge);
+        } else {
+            self.bends.insert(edge, bends);
+        }
+        self.generation += 1;
+    }
+
+    /// Straighten every edge, eg. once nodes moved away from their routes.
+    pub fn clear_bends(&mut self) {
+        self.bends.clear();
+        self.generation += 1;
+    }
+
+    /// Forget every position and bend.
+    pub fn clear(&mut self) {
+        self.points.clear();
+        self.bends.clear();
+        self.generation += 1;
+    }
+
+    /// Smallest rectangle containing the positions of `graph`'s nodes, as
+    /// `(min, max)`, or `None` if no node has a position.
+    pub fn bounds(&self, graph: &Graph) -> Option<([f32; 2], [f32; 2])> {
+        graph
+            .nodes()
+            .filter_map(|node| self.get(node))
+            .fold(None, |bounds, [x, y]| match bounds {
+                None => Some(([x, y], [x, y])),
+                Some((min, max)) => Some((
+                    [f32::min(min[0], x), f32::min(min[1], y)],
+                    [f32::max(max[0], x), f32::max(max[1], y)],
+                )),
+            })
+    }
+}
+
+/// Initial position of the node with index `index`, on a spiral around the origin.
+pub(crate) fn seed(index: usize) -> [f32; 2] {
+    let radius = SEED_SPACING * (index as f32 + 0.5).sqrt();
+    let angle = index as f32 * GOLDEN_ANGLE;
+    [radius * angle.cos(), radius * angle.sin()]
+}
+
+/// Picks one of the [`EngineContext::layouts`], edits its settings, and applies it.
+pub struct LayoutPanel {
+    /// Index of the layout shown.
+    selected: usize,
+    /// Apply the layout again whenever a setting changes.
+    live: bool,
+}
+
+impl LayoutPanel {
+    /// Create a panel showing the first layout.
+    pub fn new() -> Self {
+        Self {
+            selected: 0,
+            live: false,
+        }
+    }
+}
+
+impl Panel for LayoutPanel {
+    fn id(&self) -> &'static str {
+        "Layouts"
+    }
+
+    fn draw(&mut self, ui: &Ui, ctx: &mut EngineContext) {
+        if ctx.layouts.is_empty() {
+            ui.text_disabled(tr("No layouts"));
+            return;
+        }
+        self.selected = self.selected.min(ctx.layouts.len() - 1);
+
+        let names: Vec<_> = ctx.layouts.iter().map(|l| tr(l.name())).collect();
+        ui.combo_simple_string(tr("Layout"), &mut self.selected, &names);
+        ui.separator();
+        let changed = ctx.layouts[self.selected].settings_ui(ui);
+        ui.separator();
+
+        ui.checkbox(tr("Live"), &mut self.live);
+        ui.same_line();
+        if ui.button(tr("Apply")) || (changed && self.live) {
+            ctx.apply_layout(self.selected);
+        }
+    }
+}
+
+impl std::default::Default for LayoutPanel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
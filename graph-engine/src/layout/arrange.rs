@@ -0,0 +1,150 @@
+//! # Manual Layout
+//!
+//! [`Arrange`] commands place the selected nodes by hand, eg. from the Layout
+//! menu or the [command palette](crate::palette): lining them up on a side of
+//! their bounds, spreading them evenly, or snapping them to a grid of
+//! [`GRID_SPACING`]. Arranged nodes are pinned, like a dragged node, so the
+//! force layout leaves them where they were put until they are released.
+//!
+//! Each command is a single [undoable](crate::history) step.
+//!
+//! ```ignore
+//! ctx.selection.select(nodes, [], SelectMode::Replace);
+//! let status = Arrange::DistributeHorizontally.run(&mut ctx);
+//! ```
+
+use crate::context::EngineContext;
+use crate::graph::NodeId;
+use crate::history::Command;
+
+/// Distance between the lines of the grid [`Arrange::SnapToGrid`] snaps to,
+/// in world units.
+pub const GRID_SPACING: f32 = 50.0;
+
+/// A way of placing the selected nodes, see the [module documentation](self).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Arrange {
+    /// Line the nodes up with the leftmost one.
+    AlignLeft,
+    AlignRight,
+    AlignTop,
+    AlignBottom,
+    /// Space the nodes evenly between the leftmost and rightmost ones, keeping
+    /// their order.
+    DistributeHorizontally,
+    DistributeVertically,
+    /// Move each node to the closest grid point.
+    SnapToGrid,
+    /// Pin the nodes where they are.
+    Pin,
+    /// Let the force layout move the nodes again.
+    Release,
+}
+
+impl Arrange {
+    pub const ALL: [Arrange; 9] = [
+        Self::AlignLeft,
+        Self::AlignRight,
+        Self::AlignTop,
+        Self::AlignBottom,
+        Self::DistributeHorizontally,
+        Self::DistributeVertically,
+        Self::SnapToGrid,
+        Self::Pin,
+        Self::Release,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::AlignLeft => "Align Left",
+            Self::AlignRight => "Align Right",
+            Self::AlignTop => "Align Top",
+            Self::AlignBottom => "Align Bottom",
+            Self::DistributeHorizontally => "Distribute Horizontally",
+            Self::DistributeVertically => "Distribute Vertically",
+            Self::SnapToGrid => "Snap to Grid",
+            Self::Pin => "Pin Selection",
+            Self::Release => "Release Selection",
+        }
+    }
+
+    /// Number of selected nodes the command needs to do anything.
+    pub fn min_nodes(self) -> usize {
+        match self {
+            Self::AlignLeft | Self::AlignRight | Self::AlignTop | Self::AlignBottom => 2,
+            Self::DistributeHorizontally | Self::DistributeVertically => 3,
+            Self::SnapToGrid | Self::Pin | Self::Release => 1,
+        }
+    }
+
+    /// Place the selected nodes of `ctx` as one undoable step. Returns its
+    /// outcome, for the user.
+    pub fn run(self, ctx: &mut EngineContext) -> String {
+        ctx.force.sync_positions(&mut ctx.positions);
+        let mut nodes: Vec<(NodeId, [f32; 2])> = ctx
+            .selection
+            .nodes()
+            .filter_map(|node| Some((node, ctx.positions.get(node)?)))
+            .collect();
+        if nodes.len() < self.min_nodes() {
+            return format!(
+                "{}: select at least {} nodes",
+                self.label(),
+                self.min_nodes()
+            );
+        }
+
+        match self {
+            Self::AlignLeft => align(&mut nodes, 0, f32::min),
+            Self::AlignRight => align(&mut nodes, 0, f32::max),
+            Self::AlignTop => align(&mut nodes, 1, f32::min),
+            Self::AlignBottom => align(&mut nodes, 1, f32::max),
+            Self::DistributeHorizontally => distribute(&mut nodes, 0),
+            Self::DistributeVertically => distribute(&mut nodes, 1),
+            Self::SnapToGrid => {
+                for (_, point) in &mut nodes {
+                    *point = point.map(|v| (v / GRID_SPACING).round() * GRID_SPACING);
+                }
+            }
+            Self::Pin | Self::Release => {}
+        }
+        let pinned = self != Self::Release;
+        let moved = !matches!(self, Self::Pin | Self::Release);
+        let count = nodes.len();
+        let commands = nodes
+            .into_iter()
+            .map(|(node, point)| Command::Pin {
+                node,
+                pinned,
+                position: moved.then_some(point),
+            })
+            .collect();
+        ctx.execute(Command::Batch {
+            label: self.label().to_string(),
+            commands,
+        });
+        format!("{}: {count} nodes", self.label())
+    }
+}
+
+/// Move every point's `axis` coordinate to the one `pick` keeps of them all.
+fn align(nodes: &mut [(NodeId, [f32; 2])], axis: usize, pick: fn(f32, f32) -> f32) {
+    let target = nodes
+        .iter()
+        .map(|(_, point)| point[axis])
+        .reduce(pick)
+        .unwrap_or_default();
+    for (_, point) in nodes {
+        point[axis] = target;
+    }
+}
+
+/// Space the points evenly along `axis`, between the two outermost ones.
+fn distribute(nodes: &mut [(NodeId, [f32; 2])], axis: usize) {
+    nodes.sort_by(|(_, a), (_, b)| a[axis].total_cmp(&b[axis]));
+    let (first, last) = (nodes[0].1[axis], nodes[nodes.len() - 1].1[axis]);
+    let step = (last - first) / (nodes.len() - 1) as f32;
+    for (i, (_, point)) in nodes.iter_mut().enumerate() {
+        point[axis] = first + step * i as f32;
+    }
+}
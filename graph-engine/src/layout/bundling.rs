@@ -0,0 +1,226 @@
+//! # Edge Bundling
+//!
+//! Force-directed edge bundling, after Holten and van Wijk: each edge is cut
+//! into points which attract the matching points of compatible edges, while
+//! springs keep every edge smooth. Edges are compatible when they are about
+//! parallel, as long as each other, close, and facing each other. Every cycle
+//! halves the step, and doubles the points of each edge.
+//!
+//! Unlike the original, points of edges running in opposite directions are
+//! matched from opposite ends, so those are bundled too.
+//!
+//! ```ignore
+//! let paths = bundle(&[(a, b), (c, d)], &BundleSettings::default());
+//! scene.graph().edge_path(&paths[0], style);
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+/// Mean length edges are scaled to while bundling, so the settings do not
+/// depend on the size of the layout.
+const SCALE: f32 = 100.0;
+/// Distance under which points are considered at the same place.
+const EPSILON: f32 = 1e-4;
+
+/// Tuning of [`bundle()`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BundleSettings {
+    /// Number of cycles, each doubling the points of every edge.
+    pub cycles: u32,
+    /// Number of iterations of the first cycle, two thirds of it in each next
+    /// one.
+    pub iterations: u32,
+    /// Distance points move by per unit of force in the first cycle, halved
+    /// in each next one, for edges a hundred units long.
+    pub step: f32,
+    /// Strength of the springs keeping edges straight.
+    pub stiffness: f32,
+    /// Lowest compatibility, from `0.0` to `1.0`, of edges bundled together.
+    pub compatibility: f32,
+}
+
+/// The path of each of `edges`, given as their endpoints, from the first to
+/// the second, once bundled. See the [module documentation](self).
+pub fn bundle(edges: &[([f32; 2], [f32; 2])], settings: &BundleSettings) -> Vec<Vec<[f32; 2]>> {
+    let lengths: Vec<f32> = edges.iter().map(|&(a, b)| distance(a, b)).collect();
+    let mean = lengths.iter().sum::<f32>() / edges.len().max(1) as f32;
+    if mean <= EPSILON {
+        return edges.iter().map(|&(a, b)| vec![a, b]).collect();
+    }
+    let scale = SCALE / mean;
+    let segments: Vec<([f32; 2], [f32; 2])> = edges
+        .iter()
+        .map(|&(a, b)| (mul(a, scale), mul(b, scale)))
+        .collect();
+
+    // compatible edges, and whether they run the other way
+    let mut compatible: Vec<Vec<(usize, bool)>> = vec![Vec::new(); edges.len()];
+    for i in 0..segments.len() {
+        for j in i + 1..segments.len() {
+            if lengths[i] <= EPSILON || lengths[j] <= EPSILON {
+                continue;
+            }
+            let (score, reversed) = compatibility(segments[i], segments[j]);
+            if score >= settings.compatibility {
+                compatible[i].push((j, reversed));
+                compatible[j].push((i, reversed));
+            }
+        }
+    }
+
+    let mut paths: Vec<Vec<[f32; 2]>> = segments.iter().map(|&(a, b)| vec![a, b]).collect();
+    let mut divisions = 2;
+    let mut step = settings.step;
+    let mut iterations = settings.iterations as f32;
+    for _ in 0..settings.cycles {
+        for path in &mut paths {
+            *path = subdivide(path, divisions);
+        }
+        for _ in 0..iterations.round() as u32 {
+            let forces: Vec<Vec<[f32; 2]>> = (0..paths.len())
+                .map(|i| {
+                    let spring =
+                        settings.stiffness / (lengths[i] * scale).max(EPSILON) / divisions as f32;
+                    forces(&paths, i, &compatible[i], spring, step)
+                })
+                .collect();
+            for (path, forces) in paths.iter_mut().zip(forces) {
+                for (point, force) in path[1..].iter_mut().zip(forces) {
+                    *point = add(*point, force);
+                }
+            }
+        }
+        divisions *= 2;
+        step /= 2.0;
+        iterations *= 2.0 / 3.0;
+    }
+
+    paths
+        .into_iter()
+        .map(|path| path.into_iter().map(|p| mul(p, 1.0 / scale)).collect())
+        .collect()
+}
+
+/// How far each inner point of the path at `index` moves, in order.
+fn forces(
+    paths: &[Vec<[f32; 2]>],
+    index: usize,
+    compatible: &[(usize, bool)],
+    spring: f32,
+    step: f32,
+) -> Vec<[f32; 2]> {
+    let path = &paths[index];
+    let last = path.len() - 1;
+    (1..last)
+        .map(|i| {
+            let point = path[i];
+            let mut force = mul(
+                add(sub(path[i - 1], point), sub(path[i + 1], point)),
+                spring,
+            );
+            for &(other, reversed) in compatible {
+                let other = &paths[other][if reversed { last - i } else { i }];
+                let toward = sub(*other, point);
+                let length = distance(*other, point);
+                if length > EPSILON {
+                    force = add(force, mul(toward, 1.0 / length));
+                }
+            }
+            mul(force, step)
+        })
+        .collect()
+}
+
+/// `path` resampled into `divisions` pieces of equal length.
+fn subdivide(path: &[[f32; 2]], divisions: usize) -> Vec<[f32; 2]> {
+    let total: f32 = path.windows(2).map(|w| distance(w[0], w[1])).sum();
+    let (first, last) = (path[0], path[path.len() - 1]);
+    if total <= EPSILON {
+        return vec![first; divisions + 1];
+    }
+    let piece = total / divisions as f32;
+    let mut points = vec![first];
+    let mut walked = 0.0;
+    let mut next = piece;
+    for w in path.windows(2) {
+        let length = distance(w[0], w[1]);
+        while points.len() < divisions && next <= walked + length {
+            let t = (next - walked) / length.max(EPSILON);
+            points.push(add(w[0], mul(sub(w[1], w[0]), t)));
+            next += piece;
+        }
+        walked += length;
+    }
+    while points.len() < divisions {
+        points.push(last);
+    }
+    points.push(last);
+    points
+}
+
+/// How well the edges `p` and `q` bundle together, from `0.0` to `1.0`, and
+/// whether they run in opposite directions.
+fn compatibility(p: ([f32; 2], [f32; 2]), q: ([f32; 2], [f32; 2])) -> (f32, bool) {
+    let (vp, vq) = (sub(p.1, p.0), sub(q.1, q.0));
+    let (lp, lq) = (distance(p.0, p.1), distance(q.0, q.1));
+    let dot = vp[0] * vq[0] + vp[1] * vq[1];
+    let angle = (dot / (lp * lq)).abs();
+    let average = (lp + lq) / 2.0;
+    let scale = 2.0 / (average / lp.min(lq) + lp.max(lq) / average);
+    let position = average / (average + distance(midpoint(p), midpoint(q)));
+    let visibility = visibility(p, q).min(visibility(q, p));
+    (angle * scale * position * visibility, dot < 0.0)
+}
+
+/// How much of `p` is seen from `q`: `1.0` if their middles face each other,
+/// down to `0.0` once `p`'s middle is out of `q` projected on `p`.
+fn visibility(p: ([f32; 2], [f32; 2]), q: ([f32; 2], [f32; 2])) -> f32 {
+    let (i0, i1) = (project(q.0, p), project(q.1, p));
+    let span = distance(i0, i1);
+    if span <= EPSILON {
+        return 0.0;
+    }
+    let middle = [(i0[0] + i1[0]) / 2.0, (i0[1] + i1[1]) / 2.0];
+    (1.0 - 2.0 * distance(midpoint(p), middle) / span).max(0.0)
+}
+
+/// `point` projected on the line through `line`.
+fn project(point: [f32; 2], line: ([f32; 2], [f32; 2])) -> [f32; 2] {
+    let v = sub(line.1, line.0);
+    let length = v[0] * v[0] + v[1] * v[1];
+    let w = sub(point, line.0);
+    add(line.0, mul(v, (w[0] * v[0] + w[1] * v[1]) / length))
+}
+
+fn midpoint(segment: ([f32; 2], [f32; 2])) -> [f32; 2] {
+    mul(add(segment.0, segment.1), 0.5)
+}
+
+fn add(a: [f32; 2], b: [f32; 2]) -> [f32; 2] {
+    [a[0] + b[0], a[1] + b[1]]
+}
+
+fn sub(a: [f32; 2], b: [f32; 2]) -> [f32; 2] {
+    [a[0] - b[0], a[1] - b[1]]
+}
+
+fn mul(a: [f32; 2], k: f32) -> [f32; 2] {
+    [a[0] * k, a[1] * k]
+}
+
+fn distance(a: [f32; 2], b: [f32; 2]) -> f32 {
+    (a[0] - b[0]).hypot(a[1] - b[1])
+}
+
+impl std::default::Default for BundleSettings {
+    fn default() -> Self {
+        Self {
+            cycles: 6,
+            iterations: 60,
+            step: 0.1,
+            stiffness: 0.1,
+            compatibility: 0.6,
+        }
+    }
+}
@@ -0,0 +1,93 @@
+//! # Circular Layout
+//!
+//! Places nodes evenly on a circle, in breadth-first order so neighbors sit
+//! next to each other.
+
+use std::collections::VecDeque;
+
+use crate::graph::{Graph, NodeId};
+use crate::i18n::tr;
+use crate::imgui::Ui;
+
+use super::{Layout, Positions};
+
+/// Circular layout, see the [module documentation](self).
+#[derive(Clone, Debug, PartialEq)]
+pub struct CircularLayout {
+    /// Distance between consecutive nodes along the circle, in world units.
+    pub spacing: f32,
+    /// Order nodes breadth-first from the highest-degree node, rather than by id.
+    pub breadth_first: bool,
+}
+
+impl Layout for CircularLayout {
+    fn name(&self) -> &'static str {
+        "Circular"
+    }
+
+    fn compute(&mut self, graph: &Graph, positions: &mut Positions) {
+        positions.clear_bends();
+        let order = match self.breadth_first {
+            true => breadth_first_order(graph),
+            false => graph.nodes().collect(),
+        };
+        let count = order.len();
+        if count == 1 {
+            positions.set(order[0], [0.0; 2]);
+            return;
+        }
+
+        let radius = self.spacing * count as f32 / std::f32::consts::TAU;
+        for (i, node) in order.into_iter().enumerate() {
+            // start at the top, going clockwise on screen
+            let angle =
+                i as f32 / count as f32 * std::f32::consts::TAU - std::f32::consts::FRAC_PI_2;
+            positions.set(node, [radius * angle.cos(), radius * angle.sin()]);
+        }
+    }
+
+    fn settings_ui(&mut self, ui: &Ui) -> bool {
+        let before = self.clone();
+        ui.slider(tr("Node Spacing"), 5.0, 300.0, &mut self.spacing);
+        ui.checkbox(tr("Keep Neighbors Together"), &mut self.breadth_first);
+        *self != before
+    }
+}
+
+/// Every node of `graph`, component by component, each visited breadth-first
+/// from its highest-degree node.
+fn breadth_first_order(graph: &Graph) -> Vec<NodeId> {
+    let mut roots: Vec<NodeId> = graph.nodes().collect();
+    // stable, so ties keep id order
+    roots.sort_by_key(|node| std::cmp::Reverse(graph.degree(*node)));
+
+    let mut visited = vec![false; graph.node_bound()];
+    let mut order = Vec::with_capacity(roots.len());
+    let mut queue = VecDeque::new();
+    for root in roots {
+        if visited[root.index()] {
+            continue;
+        }
+        visited[root.index()] = true;
+        queue.push_back(root);
+        while let Some(node) = queue.pop_front() {
+            order.push(node);
+            for neighbor in graph.neighbors(node) {
+                if !visited[neighbor.index()] {
+                    visited[neighbor.index()] = true;
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+    }
+    order
+}
+
+impl std::default::Default for CircularLayout {
+    fn default() -> Self {
+        Self {
+            spacing: 30.0,
+            breadth_first: true,
+        }
+    }
+}
@@ -0,0 +1,712 @@
+//! # Force-Directed Layout
+//!
+//! [`ForceLayout`] moves nodes under simulated forces until they settle,
+//! combining Fruchterman–Reingold's cooling schedule with ForceAtlas2's
+//! degree-weighted repulsion and gravity:
+//! - every pair of nodes repels, with a force falling off with distance,
+//!   approximated with a Barnes–Hut quadtree;
+//! - every edge pulls its endpoints together, balancing repulsion at about
+//!   [`ForceSettings::edge_length`];
+//! - gravity pulls every node towards the origin, keeping components together.
+//!
+//! The layout is stepped once per frame, so it animates live. A node being
+//! [dragged](ForceLayout::drag) is pinned in place while the others keep moving.
+//!
+//! Once [given a job pool](ForceLayout::set_jobs), the repulsion on graphs of
+//! at least [`PARALLEL_MIN_NODES`] nodes is computed across its workers.
+//!
+//! Once [given a GL context](ForceLayout::enable_gpu), graphs of at least
+//! [`GPU_MIN_NODES`] nodes are laid out by the [GPU layout](super::gpu) instead,
+//! falling back to the CPU when compute shaders are unavailable.
+
+use std::collections::HashSet;
+use std::rc::Rc;
+
+use crate::context::EngineContext;
+use crate::graph::{Graph, NodeId};
+use crate::i18n::tr;
+use crate::imgui::renderers::glow::inner::Context;
+use crate::imgui::{ItemHoveredFlags, SliderFlags, Ui};
+use crate::jobs::JobPool;
+use crate::subsystems::panels::Panel;
+use crate::subsystems::scene::GraphBuffers;
+
+use super::gpu::{GpuForceLayout, GpuLayoutError};
+use super::{Layout, Positions};
+
+/// Number of nodes from which the layout runs on the GPU, if enabled.
+pub const GPU_MIN_NODES: usize = 10_000;
+/// Number of nodes from which repulsion is computed by the layout's job pool,
+/// if any.
+pub const PARALLEL_MIN_NODES: usize = 2_000;
+
+/// Movement per iteration, relative to the edge length, below which the layout
+/// counts as settled.
+const SETTLED_MOVEMENT: f32 = 0.005;
+/// Lowest temperature, relative to the edge length. The layout counts as settled
+/// once cooled down to it.
+const MIN_TEMPERATURE: f32 = 0.01;
+/// Depth after which coincident nodes share a quadtree cell instead of splitting it.
+const MAX_DEPTH: usize = 24;
+/// Iterations after which [`Layout::compute()`] gives up waiting for the layout to settle.
+const MAX_ITERATIONS: usize = 2000;
+
+/// Parameters of a [`ForceLayout`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ForceSettings {
+    /// Strength of the repulsion between nodes.
+    pub repulsion: f32,
+    /// Rest length of an edge between two otherwise unconnected nodes, in world
+    /// units. Edges of denser graphs settle somewhat longer.
+    pub edge_length: f32,
+    /// Strength of the pull towards the origin.
+    pub gravity: f32,
+    /// Barnes–Hut accuracy: cells whose size relative to their distance is below
+    /// this are approximated as a single body. `0` computes every pair exactly.
+    pub theta: f32,
+    /// Weigh nodes by their degree, pushing hubs apart (as in ForceAtlas2).
+    pub degree_mass: bool,
+    /// Factor the temperature (the largest move per iteration) is multiplied by
+    /// each iteration.
+    pub cooling: f32,
+    /// Iterations run by each [`ForceLayout::step()`].
+    pub iterations_per_step: u32,
+    /// Run on the GPU for graphs of at least [`GPU_MIN_NODES`] nodes, when
+    /// [available](ForceLayout::enable_gpu).
+    pub gpu: bool,
+}
+
+/// A live force-directed layout, see the [module documentation](self).
+///
+/// Typical usage, each frame:
+/// ```ignore
+/// positions.sync(&graph);
+/// layout.step(&graph, &mut positions);
+/// ```
+pub struct ForceLayout {
+    pub settings: ForceSettings,
+    /// Largest distance a node may move in one iteration.
+    temperature: f32,
+    paused: bool,
+    settled: bool,
+    pinned: HashSet<NodeId>,
+    /// Scratch buffers, kept to avoid reallocating every iteration.
+    tree: QuadTree,
+    displacement: Vec<[f32; 2]>,
+    jobs: Option<JobPool>,
+    gpu: Option<GpuState>,
+}
+
+/// The GPU layout, and where the newest positions are.
+struct GpuState {
+    layout: GpuForceLayout,
+    /// Whether the GPU holds newer positions than the CPU's [`Positions`].
+    ahead: bool,
+    /// [`ForceSettings::degree_mass`] when masses were last uploaded.
+    degree_mass: bool,
+}
+
+/// Barnes–Hut quadtree of node masses.
+#[derive(Default)]
+struct QuadTree {
+    cells: Vec<Cell>,
+}
+
+#[derive(Clone)]
+struct Cell {
+    /// Lower corner and side length of the cell.
+    min: [f32; 2],
+    size: f32,
+    mass: f32,
+    /// Center of mass, once [`QuadTree::finish()`]ed; the mass-weighted sum of
+    /// positions while building.
+    center: [f32; 2],
+    /// Index of the first of four children, or `None` for a leaf.
+    children: Option<usize>,
+    /// The node in a leaf, if any.
+    body: Option<usize>,
+}
+
+impl ForceLayout {
+    /// Create a layout with `settings`, ready to run.
+    pub fn new(settings: ForceSettings) -> Self {
+        let mut layout = Self {
+            settings,
+            temperature: 0.0,
+            paused: false,
+            settled: false,
+            pinned: HashSet::new(),
+            tree: QuadTree::default(),
+            displacement: Vec::new(),
+            jobs: None,
+            gpu: None,
+        };
+        layout.reheat();
+        layout
+    }
+
+    /// Compute the repulsion on large graphs across the workers of `jobs`.
+    pub fn set_jobs(&mut self, jobs: JobPool) {
+        self.jobs = Some(jobs);
+    }
+
+    /// Allow laying out large graphs on the GPU through `gl`, which must be the
+    /// context the [`Scene`](crate::subsystems::scene::Scene) draws with.
+    ///
+    /// Errors if compute shaders are unsupported, in which case the layout keeps
+    /// running on the CPU.
+    pub fn enable_gpu(&mut self, gl: Rc<Context>) -> Result<(), GpuLayoutError> {
+        self.gpu = Some(GpuState {
+            layout: GpuForceLayout::new(gl)?,
+            ahead: false,
+            degree_mass: self.settings.degree_mass,
+        });
+        Ok(())
+    }
+
+    /// Returns `true` if the GPU layout is available.
+    pub fn has_gpu(&self) -> bool {
+        self.gpu.is_some()
+    }
+
+    /// The GPU layout's buffers with the given style, if it holds newer positions
+    /// than the CPU, in which case they should be drawn instead of [`Positions`].
+    pub fn gpu_buffers(
+        &self,
+        node_size: f32,
+        node_color: [f32; 4],
+        edge_color: [f32; 4],
+    ) -> Option<GraphBuffers> {
+        self.gpu
+            .as_ref()
+            .filter(|gpu| gpu.ahead)
+            .map(|gpu| gpu.layout.buffers(node_size, node_color, edge_color))
+    }
+
+    /// Read positions back from the GPU layout if it is ahead of `positions`.
+    ///
+    /// Done by [`Self::step()`] once the layout stops; needed before changing
+    /// `positions` any other way while it runs.
+    pub fn sync_positions(&mut self, positions: &mut Positions) {
+        if let Some(gpu) = self.gpu.as_mut().filter(|gpu| gpu.ahead) {
+            gpu.layout.download(positions);
+            gpu.ahead = false;
+        }
+    }
+
+    /// Forget pins and GPU state and restart, eg. for a new graph.
+    pub fn reset(&mut self) {
+        self.pinned.clear();
+        if let Some(gpu) = &mut self.gpu {
+            gpu.ahead = false;
+        }
+        self.reheat();
+    }
+
+    /// Pick up changes to the graph being laid out, eg. nodes hidden by a
+    /// filter, keeping pins. Positions are read back from the GPU layout first.
+    pub fn graph_changed(&mut self, positions: &mut Positions) {
+        self.sync_positions(positions);
+        self.reheat();
+    }
+
+    /// Returns `true` if the layout is neither paused nor settled.
+    pub fn is_running(&self) -> bool {
+        !self.paused && !self.settled
+    }
+
+    /// Returns `true` if the layout was paused by the user.
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Pause or resume the layout.
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    /// Returns `true` if nodes stopped moving.
+    pub fn is_settled(&self) -> bool {
+        self.settled
+    }
+
+    /// Restart the cooling schedule, eg. after the graph or the settings changed.
+    pub fn reheat(&mut self) {
+        self.temperature = self.settings.edge_length * 2.0;
+        self.settled = false;
+    }
+
+    /// Returns `true` if `node` is pinned in place.
+    pub fn is_pinned(&self, node: NodeId) -> bool {
+        self.pinned.contains(&node)
+    }
+
+    /// Pin `node` in place, or release it.
+    pub fn set_pinned(&mut self, node: NodeId, pinned: bool) {
+        if pinned {
+            self.pinned.insert(node);
+        } else if self.pinned.remove(&node) {
+            self.reheat();
+        }
+        self.upload_pins();
+    }
+
+    /// Release every pinned node.
+    pub fn clear_pinned(&mut self) {
+        if !self.pinned.is_empty() {
+            self.pinned.clear();
+            self.reheat();
+            self.upload_pins();
+        }
+    }
+
+    fn upload_pins(&mut self) {
+        if let Some(gpu) = self.gpu.as_mut().filter(|gpu| gpu.ahead) {
+            gpu.layout.set_pinned(|node| self.pinned.contains(&node));
+        }
+    }
+
+    /// Pinned nodes, in no particular order.
+    pub fn pinned(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.pinned.iter().copied()
+    }
+
+    /// Move `node` to `point`, as the user drags it, pinning it there so the
+    /// rest of the layout adjusts around it.
+    pub fn drag(&mut self, positions: &mut Positions, node: NodeId, point: [f32; 2]) {
+        positions.set(node, point);
+        self.pinned.insert(node);
+        if let Some(gpu) = self.gpu.as_mut().filter(|gpu| gpu.ahead) {
+            gpu.layout.set_position(node, point, true);
+        }
+        // keep neighbors following the drag without restarting from scratch
+        self.temperature = self.temperature.max(self.settings.edge_length * 0.5);
+        self.settled = false;
+    }
+
+    /// Advance the layout by [`ForceSettings::iterations_per_step`] iterations,
+    /// if it is running.
+    ///
+    /// Returns `true` if any node moved.
+    pub fn step(&mut self, graph: &Graph, positions: &mut Positions) -> bool {
+        if !self.is_running() || graph.is_empty() {
+            self.sync_positions(positions);
+            return false;
+        }
+        let use_gpu = self.settings.gpu && graph.node_count() >= GPU_MIN_NODES;
+        if use_gpu && let Some(gpu) = &mut self.gpu {
+            if !gpu.ahead {
+                positions.sync(graph);
+                positions.clear_bends();
+                gpu.layout.upload(
+                    graph,
+                    positions,
+                    |node| self.pinned.contains(&node),
+                    &self.settings,
+                );
+                gpu.ahead = true;
+                gpu.degree_mass = self.settings.degree_mass;
+            } else if gpu.degree_mass != self.settings.degree_mass {
+                gpu.layout.upload_masses(graph, &self.settings);
+                gpu.degree_mass = self.settings.degree_mass;
+            }
+            for _ in 0..self.settings.iterations_per_step {
+                if let Some(gpu) = &mut self.gpu {
+                    gpu.layout.step(&self.settings, self.temperature);
+                }
+                self.cool(None);
+                if self.settled {
+                    break;
+                }
+            }
+            return true;
+        }
+
+        self.sync_positions(positions);
+        for _ in 0..self.settings.iterations_per_step {
+            self.iterate(graph, positions);
+            if self.settled {
+                break;
+            }
+        }
+        true
+    }
+
+    fn iterate(&mut self, graph: &Graph, positions: &mut Positions) {
+        let settings = &self.settings;
+        let k = settings.edge_length.max(f32::EPSILON);
+        let nodes: Vec<NodeId> = graph
+            .nodes()
+            .filter(|node| positions.get(*node).is_some())
+            .collect();
+        // normalized so the layout's scale does not grow with the average degree
+        let mean_mass = match settings.degree_mass {
+            true => 1.0 + 2.0 * graph.edge_count() as f32 / nodes.len().max(1) as f32,
+            false => 1.0,
+        };
+        let mass = |node: NodeId| match settings.degree_mass {
+            true => (graph.degree(node) as f32 + 1.0) / mean_mass,
+            false => 1.0,
+        };
+        let bodies: Vec<([f32; 2], f32)> = nodes
+            .iter()
+            .map(|node| (positions.get(*node).unwrap(), mass(*node)))
+            .collect();
+        self.tree.build(&bodies);
+
+        self.displacement.clear();
+        self.displacement.resize(graph.node_bound(), [0.0; 2]);
+
+        // repulsion and gravity
+        let strength = settings.repulsion * k * k * k;
+        let tree = &self.tree;
+        let push = |i: usize| {
+            let (pos, m) = bodies[i];
+            let mut d = tree.repulsion(i, pos, m, settings.theta);
+            d[0] *= strength;
+            d[1] *= strength;
+
+            let distance = length(pos);
+            if distance > f32::EPSILON {
+                let pull = settings.gravity * m * k / distance;
+                d[0] -= pos[0] * pull;
+                d[1] -= pos[1] * pull;
+            }
+            d
+        };
+        match &self.jobs {
+            Some(jobs) if nodes.len() >= PARALLEL_MIN_NODES => {
+                let chunk = nodes.len().div_ceil(jobs.workers() + 1);
+                let chunks = jobs.map(nodes.len().div_ceil(chunk), |c| {
+                    let range = c * chunk..((c + 1) * chunk).min(nodes.len());
+                    range.map(push).collect::<Vec<_>>()
+                });
+                for (node, d) in nodes.iter().zip(chunks.into_iter().flatten()) {
+                    self.displacement[node.index()] = d;
+                }
+            }
+            _ => {
+                for (i, node) in nodes.iter().enumerate() {
+                    self.displacement[node.index()] = push(i);
+                }
+            }
+        }
+
+        // attraction along edges
+        for edge in graph.edges() {
+            let (source, target) = graph.endpoints(edge).unwrap();
+            let (Some(a), Some(b)) = (positions.get(source), positions.get(target)) else {
+                continue;
+            };
+            let delta = [b[0] - a[0], b[1] - a[1]];
+            // d² / k along the edge, ie. d / k per unit of delta
+            let pull = length(delta) / k;
+            let force = [delta[0] * pull, delta[1] * pull];
+            let d = &mut self.displacement[source.index()];
+            d[0] += force[0];
+            d[1] += force[1];
+            let d = &mut self.displacement[target.index()];
+            d[0] -= force[0];
+            d[1] -= force[1];
+        }
+
+        // move, limited by the temperature; routes no longer fit once nodes move
+        positions.clear_bends();
+        let mut largest = 0.0_f32;
+        for (i, node) in nodes.iter().enumerate() {
+            if self.pinned.contains(node) {
+                continue;
+            }
+            let d = self.displacement[node.index()];
+            let distance = length(d);
+            if distance <= f32::EPSILON || !distance.is_finite() {
+                continue;
+            }
+            let moved = distance.min(self.temperature);
+            let (pos, _) = bodies[i];
+            positions.set(
+                *node,
+                [
+                    pos[0] + d[0] / distance * moved,
+                    pos[1] + d[1] / distance * moved,
+                ],
+            );
+            largest = largest.max(moved);
+        }
+
+        self.cool(Some(largest));
+    }
+
+    /// Lower the temperature after an iteration whose largest movement was
+    /// `largest`, if known, and check whether the layout settled.
+    fn cool(&mut self, largest: Option<f32>) {
+        let k = self.settings.edge_length.max(f32::EPSILON);
+        self.temperature = (self.temperature * self.settings.cooling).max(k * MIN_TEMPERATURE);
+        if largest.is_some_and(|l| l < k * SETTLED_MOVEMENT)
+            || self.temperature <= k * MIN_TEMPERATURE
+        {
+            self.settled = true;
+        }
+    }
+}
+
+impl QuadTree {
+    /// Rebuild the tree from `bodies`' positions and masses.
+    fn build(&mut self, bodies: &[([f32; 2], f32)]) {
+        self.cells.clear();
+        let Some(((first, _), rest)) = bodies.split_first() else {
+            return;
+        };
+        let (min, max) = rest.iter().fold((*first, *first), |(min, max), (p, _)| {
+            (
+                [min[0].min(p[0]), min[1].min(p[1])],
+                [max[0].max(p[0]), max[1].max(p[1])],
+            )
+        });
+        let size = (max[0] - min[0]).max(max[1] - min[1]).max(1.0);
+        self.cells.push(Cell::new(min, size));
+
+        for (body, (pos, mass)) in bodies.iter().enumerate() {
+            self.insert(body, *pos, *mass, bodies);
+        }
+        for cell in &mut self.cells {
+            if cell.mass > 0.0 {
+                cell.center = [cell.center[0] / cell.mass, cell.center[1] / cell.mass];
+            }
+        }
+    }
+
+    fn insert(&mut self, body: usize, pos: [f32; 2], mass: f32, bodies: &[([f32; 2], f32)]) {
+        let mut index = 0;
+        let mut depth = 0;
+        loop {
+            let cell = &mut self.cells[index];
+            let empty = cell.mass == 0.0;
+            cell.mass += mass;
+            cell.center[0] += pos[0] * mass;
+            cell.center[1] += pos[1] * mass;
+
+            if let Some(children) = cell.children {
+                index = children + cell.quadrant(pos);
+                depth += 1;
+                continue;
+            }
+            if empty {
+                cell.body = Some(body);
+                return;
+            }
+            if depth >= MAX_DEPTH {
+                // coincident nodes share the leaf
+                return;
+            }
+
+            // split the leaf, moving its body down
+            let (min, half) = (cell.min, cell.size / 2.0);
+            let previous = cell.body.take();
+            let children = self.cells.len();
+            self.cells[index].children = Some(children);
+            for quadrant in 0..4 {
+                let x = min[0] + half * (quadrant & 1) as f32;
+                let y = min[1] + half * (quadrant >> 1) as f32;
+                self.cells.push(Cell::new([x, y], half));
+            }
+            if let Some(previous) = previous {
+                let (p, m) = bodies[previous];
+                let quadrant = self.cells[index].quadrant(p);
+                let child = &mut self.cells[children + quadrant];
+                child.mass = m;
+                child.center = [p[0] * m, p[1] * m];
+                child.body = Some(previous);
+            }
+            index = children + self.cells[index].quadrant(pos);
+            depth += 1;
+        }
+    }
+
+    /// Sum of the repulsion from every other body on `body`, without the
+    /// strength factor.
+    fn repulsion(&self, body: usize, pos: [f32; 2], mass: f32, theta: f32) -> [f32; 2] {
+        let mut force = [0.0; 2];
+        if self.cells.is_empty() {
+            return force;
+        }
+        let mut stack = vec![0];
+        while let Some(index) = stack.pop() {
+            let cell = &self.cells[index];
+            if cell.mass == 0.0 {
+                continue;
+            }
+            let delta = [pos[0] - cell.center[0], pos[1] - cell.center[1]];
+            let distance = length(delta);
+
+            let other = match cell.children {
+                Some(children) if cell.size >= theta * distance => {
+                    stack.extend(children..children + 4);
+                    continue;
+                }
+                Some(_) => cell.mass,
+                None if cell.body == Some(body) => cell.mass - mass,
+                None => cell.mass,
+            };
+            if other <= 0.0 {
+                continue;
+            }
+
+            // F = k³ m₁ m₂ / d², pushing apart
+            let (direction, distance) = if distance > f32::EPSILON {
+                ([delta[0] / distance, delta[1] / distance], distance)
+            } else {
+                // separate coincident nodes in a direction unique to each
+                let angle = body as f32 * 2.399_963;
+                ([angle.cos(), angle.sin()], 1.0)
+            };
+            let magnitude = mass * other / (distance * distance);
+            force[0] += direction[0] * magnitude;
+            force[1] += direction[1] * magnitude;
+        }
+        force
+    }
+}
+
+impl Cell {
+    fn new(min: [f32; 2], size: f32) -> Self {
+        Self {
+            min,
+            size,
+            mass: 0.0,
+            center: [0.0; 2],
+            children: None,
+            body: None,
+        }
+    }
+
+    /// Index of the child containing `pos`, from 0 to 3.
+    fn quadrant(&self, pos: [f32; 2]) -> usize {
+        let half = self.size / 2.0;
+        let right = pos[0] >= self.min[0] + half;
+        let below = pos[1] >= self.min[1] + half;
+        right as usize | (below as usize) << 1
+    }
+}
+
+fn length(v: [f32; 2]) -> f32 {
+    (v[0] * v[0] + v[1] * v[1]).sqrt()
+}
+
+/// Edits the [`EngineContext::force`] layout's settings.
+pub struct ForceLayoutPanel;
+
+impl Panel for ForceLayoutPanel {
+    fn id(&self) -> &'static str {
+        "Force Layout"
+    }
+
+    fn draw(&mut self, ui: &Ui, ctx: &mut EngineContext) {
+        let layout = &mut ctx.force;
+
+        let mut paused = layout.is_paused();
+        if ui.checkbox(tr("Paused"), &mut paused) {
+            layout.set_paused(paused);
+        }
+        ui.same_line();
+        if ui.button(tr("Reheat")) {
+            layout.reheat();
+        }
+        ui.same_line();
+        let pinned = layout.pinned.len();
+        ui.enabled(pinned > 0, || {
+            if ui.button(tr("Unpin All ({count})").replace("{count}", &pinned.to_string())) {
+                layout.clear_pinned();
+            }
+        });
+        ui.text_disabled(match (layout.is_paused(), layout.is_settled()) {
+            (true, _) => "Paused",
+            (false, true) => "Settled",
+            (false, false) => "Running",
+        });
+        ui.separator();
+
+        if layout.settings_ui(ui) {
+            layout.reheat();
+        }
+        if ui.button(tr("Defaults")) {
+            layout.settings = ForceSettings::default();
+            layout.reheat();
+        }
+    }
+}
+
+impl Layout for ForceLayout {
+    fn name(&self) -> &'static str {
+        "Force-Directed"
+    }
+
+    /// Run the layout until it settles, even if paused, eg. for scripts. Pinned
+    /// nodes stay in place.
+    fn compute(&mut self, graph: &Graph, positions: &mut Positions) {
+        self.sync_positions(positions);
+        positions.sync(graph);
+        self.reheat();
+        for _ in 0..MAX_ITERATIONS {
+            self.iterate(graph, positions);
+            if self.settled {
+                break;
+            }
+        }
+    }
+
+    fn settings_ui(&mut self, ui: &Ui) -> bool {
+        let before = self.settings.clone();
+        let settings = &mut self.settings;
+        ui.slider_config(tr("Repulsion"), 0.01, 10.0)
+            .flags(SliderFlags::LOGARITHMIC)
+            .build(&mut settings.repulsion);
+        ui.slider_config(tr("Edge Length"), 5.0, 500.0)
+            .flags(SliderFlags::LOGARITHMIC)
+            .build(&mut settings.edge_length);
+        ui.slider(tr("Gravity"), 0.0, 1.0, &mut settings.gravity);
+        ui.checkbox(tr("Weigh by Degree"), &mut settings.degree_mass);
+        ui.separator();
+        ui.slider(tr("Accuracy (theta)"), 0.0, 2.0, &mut settings.theta);
+        ui.slider(tr("Cooling"), 0.8, 0.999, &mut settings.cooling);
+        ui.slider(
+            tr("Iterations per Frame"),
+            1,
+            20,
+            &mut settings.iterations_per_step,
+        );
+        ui.enabled(self.gpu.is_some(), || {
+            ui.checkbox(
+                tr("Use the GPU from {count} Nodes").replace("{count}", &GPU_MIN_NODES.to_string()),
+                &mut settings.gpu,
+            );
+        });
+        if self.gpu.is_none()
+            && ui.is_item_hovered_with_flags(ItemHoveredFlags::ALLOW_WHEN_DISABLED)
+        {
+            ui.tooltip_text(tr("Compute shaders are unavailable"));
+        }
+        self.settings != before
+    }
+}
+
+impl std::default::Default for ForceSettings {
+    fn default() -> Self {
+        Self {
+            repulsion: 1.0,
+            edge_length: 60.0,
+            gravity: 0.05,
+            theta: 0.9,
+            degree_mass: true,
+            cooling: 0.97,
+            iterations_per_step: 1,
+            gpu: true,
+        }
+    }
+}
+
+impl std::default::Default for ForceLayout {
+    fn default() -> Self {
+        Self::new(ForceSettings::default())
+    }
+}
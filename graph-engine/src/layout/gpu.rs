@@ -0,0 +1,525 @@
+//! # GPU Force Layout
+//!
+//! [`GpuForceLayout`] runs the [force layout](super::force)'s iterations in GL
+//! compute shaders, for graphs too large to lay out interactively on the CPU.
+//!
+//! Repulsion is computed exactly between every pair of nodes, tiled through
+//! shared memory, rather than with the CPU's Barnes–Hut approximation; edge
+//! attraction, gravity, cooling, and pinning behave the same.
+//!
+//! Positions stay on the GPU while the layout runs: the
+//! [`Scene`](crate::subsystems::scene::Scene) draws them directly from the
+//! layout's buffers (see [`GpuForceLayout::buffers()`]), and they are only read
+//! back with [`GpuForceLayout::download()`], eg. once the layout settles.
+//!
+//! Compute shaders need OpenGL 4.3 (or `GL_ARB_compute_shader`); use
+//! [`GpuForceLayout::is_supported()`] to fall back to the CPU layout.
+
+use std::rc::Rc;
+
+use crate::graph::{Graph, NodeId};
+use crate::imgui::renderers::glow::inner::{self as glow, Context, HasContext};
+use crate::memory::GpuAllocation;
+use crate::subsystems::scene::GraphBuffers;
+use crate::subsystems::scene::renderer::link_program;
+
+use super::Positions;
+use super::force::ForceSettings;
+
+/// Invocations per work group, and nodes per shared memory tile.
+const GROUP_SIZE: u32 = 256;
+
+const FORCE_SHADER: &str = r#"#version 430
+layout(local_size_x = 256) in;
+layout(std430, binding = 0) readonly buffer Positions { vec2 pos[]; };
+layout(std430, binding = 1) readonly buffer Masses { float mass[]; };
+layout(std430, binding = 2) readonly buffer Offsets { uint offsets[]; };
+layout(std430, binding = 3) readonly buffer Neighbors { uint neighbors[]; };
+layout(std430, binding = 4) writeonly buffer Displacement { vec2 disp[]; };
+uniform uint u_count;
+uniform float u_strength;
+uniform float u_edge_length;
+uniform float u_gravity;
+shared vec3 tile[256];
+
+void main() {
+    uint i = gl_GlobalInvocationID.x;
+    bool active = i < u_count;
+    vec2 p = active ? pos[i] : vec2(0.0);
+    float m = active ? mass[i] : 0.0;
+
+    // repulsion, F = k³ m₁ m₂ / d²
+    vec2 d = vec2(0.0);
+    for (uint base = 0u; base < u_count; base += 256u) {
+        uint j = base + gl_LocalInvocationID.x;
+        tile[gl_LocalInvocationID.x] = j < u_count ? vec3(pos[j], mass[j]) : vec3(0.0);
+        barrier();
+        uint n = min(256u, u_count - base);
+        for (uint t = 0u; t < n; t++) {
+            if (base + t == i) {
+                continue;
+            }
+            vec2 delta = p - tile[t].xy;
+            float dist2 = dot(delta, delta);
+            if (dist2 < 1e-6) {
+                // separate coincident nodes in a direction unique to each
+                float angle = float(i) * 2.399963;
+                delta = vec2(cos(angle), sin(angle));
+                dist2 = 1.0;
+            }
+            d += delta * (m * tile[t].z / (dist2 * sqrt(dist2)));
+        }
+        barrier();
+    }
+    if (!active) {
+        return;
+    }
+    d *= u_strength;
+
+    float dist = length(p);
+    if (dist > 1e-6) {
+        d -= p * (u_gravity * m * u_edge_length / dist);
+    }
+
+    // attraction, F = d² / k towards each neighbor
+    for (uint e = offsets[i]; e < offsets[i + 1u]; e++) {
+        vec2 delta = pos[neighbors[e]] - p;
+        d += delta * (length(delta) / u_edge_length);
+    }
+    disp[i] = d;
+}
+"#;
+
+const MOVE_SHADER: &str = r#"#version 430
+layout(local_size_x = 256) in;
+layout(std430, binding = 0) buffer Positions { vec2 pos[]; };
+layout(std430, binding = 4) readonly buffer Displacement { vec2 disp[]; };
+layout(std430, binding = 5) readonly buffer Pinned { uint pinned[]; };
+uniform uint u_count;
+uniform float u_temperature;
+
+void main() {
+    uint i = gl_GlobalInvocationID.x;
+    if (i >= u_count || pinned[i] != 0u) {
+        return;
+    }
+    vec2 d = disp[i];
+    float dist = length(d);
+    if (dist <= 1e-6 || isinf(dist) || isnan(dist)) {
+        return;
+    }
+    pos[i] += d / dist * min(dist, u_temperature);
+}
+"#;
+
+/// Force layout running on the GPU, see the [module documentation](self).
+///
+/// Typical usage:
+/// ```ignore
+/// let mut gpu = GpuForceLayout::new(gl)?;
+/// gpu.upload(&graph, &positions, |node| false, &settings);
+/// // each frame
+/// gpu.step(&settings, temperature);
+/// scene.draw_buffers(gpu.buffers(node_size, node_color, edge_color));
+/// // once done
+/// gpu.download(&mut positions);
+/// ```
+pub struct GpuForceLayout {
+    gl: Rc<Context>,
+    force: Program,
+    movement: Program,
+    /// Storage buffers, bound at the shaders' binding points.
+    positions: glow::Buffer,
+    masses: glow::Buffer,
+    offsets: glow::Buffer,
+    neighbors: glow::Buffer,
+    displacement: glow::Buffer,
+    pinned: glow::Buffer,
+    /// Index pairs of each edge's endpoints, for drawing.
+    edges: glow::Buffer,
+    /// Node at each index of the buffers.
+    nodes: Vec<NodeId>,
+    /// Index of each node in the buffers, by [`NodeId::index()`].
+    indices: Vec<u32>,
+    edge_count: u32,
+    /// Size of all buffers, as last uploaded.
+    allocated: GpuAllocation,
+}
+
+/// A compute program and its uniforms.
+struct Program {
+    program: glow::Program,
+    count: Option<glow::UniformLocation>,
+    strength: Option<glow::UniformLocation>,
+    edge_length: Option<glow::UniformLocation>,
+    gravity: Option<glow::UniformLocation>,
+    temperature: Option<glow::UniformLocation>,
+}
+
+/// Errors which can occur while creating a [`GpuForceLayout`].
+#[derive(Debug)]
+pub enum GpuLayoutError {
+    /// The GL context cannot run compute shaders.
+    Unsupported,
+    /// A shader failed to compile or link, or a buffer could not be created.
+    Gl(String),
+}
+
+impl GpuForceLayout {
+    /// Returns `true` if `gl` can run compute shaders.
+    pub fn is_supported(gl: &Context) -> bool {
+        let version = gl.version();
+        let extensions = gl.supported_extensions();
+        (!version.is_embedded && (version.major, version.minor) >= (4, 3))
+            || (extensions.contains("GL_ARB_compute_shader")
+                && extensions.contains("GL_ARB_shader_storage_buffer_object"))
+    }
+
+    /// Create a layout computing through `gl`, which must be current, and shared
+    /// with the [`Scene`](crate::subsystems::scene::Scene) drawing its buffers.
+    ///
+    /// Errors if compute shaders are unsupported, or its GL objects cannot be created.
+    pub fn new(gl: Rc<Context>) -> Result<Self, GpuLayoutError> {
+        if !Self::is_supported(&gl) {
+            return Err(GpuLayoutError::Unsupported);
+        }
+        unsafe {
+            let force = Program::new(&gl, FORCE_SHADER)?;
+            let movement = Program::new(&gl, MOVE_SHADER)?;
+            let buffer = || gl.create_buffer().map_err(GpuLayoutError::Gl);
+            Ok(Self {
+                positions: buffer()?,
+                masses: buffer()?,
+                offsets: buffer()?,
+                neighbors: buffer()?,
+                displacement: buffer()?,
+                pinned: buffer()?,
+                edges: buffer()?,
+                force,
+                movement,
+                gl,
+                nodes: Vec::new(),
+                indices: Vec::new(),
+                edge_count: 0,
+                allocated: GpuAllocation::default(),
+            })
+        }
+    }
+
+    /// Number of nodes uploaded.
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Upload `graph`, starting from `positions`, replacing what was uploaded before.
+    ///
+    /// Nodes without a position are left out.
+    pub fn upload(
+        &mut self,
+        graph: &Graph,
+        positions: &Positions,
+        pinned: impl Fn(NodeId) -> bool,
+        settings: &ForceSettings,
+    ) {
+        self.nodes = graph
+            .nodes()
+            .filter(|node| positions.get(*node).is_some())
+            .collect();
+        self.indices = vec![u32::MAX; graph.node_bound()];
+        for (i, node) in self.nodes.iter().enumerate() {
+            self.indices[node.index()] = i as u32;
+        }
+
+        // adjacency in both directions, as offsets into a flat neighbor list
+        let mut offsets = Vec::with_capacity(self.nodes.len() + 1);
+        let mut neighbors = Vec::new();
+        let mut edges = Vec::new();
+        for node in &self.nodes {
+            offsets.push(neighbors.len() as u32);
+            for edge in graph.incident_edges(*node) {
+                let other = graph.opposite(edge, *node).unwrap();
+                let index = self.indices[other.index()];
+                if other != *node && index != u32::MAX {
+                    neighbors.push(index);
+                }
+            }
+        }
+        offsets.push(neighbors.len() as u32);
+        for edge in graph.edges() {
+            let (source, target) = graph.endpoints(edge).unwrap();
+            let (source, target) = (self.indices[source.index()], self.indices[target.index()]);
+            if source != u32::MAX && target != u32::MAX {
+                edges.extend([source, target]);
+            }
+        }
+        self.edge_count = (edges.len() / 2) as u32;
+
+        let points: Vec<f32> = self
+            .nodes
+            .iter()
+            .flat_map(|node| positions.get(*node).unwrap())
+            .collect();
+        let pins: Vec<u32> = self.nodes.iter().map(|node| pinned(*node) as u32).collect();
+
+        let gl = &self.gl;
+        unsafe {
+            upload(
+                gl,
+                glow::SHADER_STORAGE_BUFFER,
+                self.positions,
+                &f32_bytes(&points),
+            );
+            upload(
+                gl,
+                glow::SHADER_STORAGE_BUFFER,
+                self.offsets,
+                &u32_bytes(&offsets),
+            );
+            // GL rejects binding empty buffers, so keep at least one element
+            neighbors.push(0);
+            upload(
+                gl,
+                glow::SHADER_STORAGE_BUFFER,
+                self.neighbors,
+                &u32_bytes(&neighbors),
+            );
+            upload(
+                gl,
+                glow::SHADER_STORAGE_BUFFER,
+                self.pinned,
+                &u32_bytes(&pins),
+            );
+            upload(gl, glow::ARRAY_BUFFER, self.edges, &u32_bytes(&edges));
+            let displacement = vec![0.0f32; self.nodes.len() * 2];
+            upload(
+                gl,
+                glow::SHADER_STORAGE_BUFFER,
+                self.displacement,
+                &f32_bytes(&displacement),
+            );
+        }
+        // every element is 4 bytes, masses are one per node
+        let elements = points.len()
+            + offsets.len()
+            + neighbors.len()
+            + pins.len()
+            + edges.len()
+            + self.nodes.len() * 2
+            + self.nodes.len();
+        self.allocated.set(elements * 4);
+        self.upload_masses(graph, settings);
+    }
+
+    /// Upload node masses for `settings`, eg. after [`ForceSettings::degree_mass`]
+    /// changed.
+    pub fn upload_masses(&mut self, graph: &Graph, settings: &ForceSettings) {
+        let mean = 1.0 + 2.0 * graph.edge_count() as f32 / self.nodes.len().max(1) as f32;
+        let masses: Vec<f32> = self
+            .nodes
+            .iter()
+            .map(|node| match settings.degree_mass {
+                true => (graph.degree(*node) as f32 + 1.0) / mean,
+                false => 1.0,
+            })
+            .collect();
+        unsafe {
+            upload(
+                &self.gl,
+                glow::SHADER_STORAGE_BUFFER,
+                self.masses,
+                &f32_bytes(&masses),
+            );
+        }
+    }
+
+    /// Run one iteration, moving each node at most `temperature` world units.
+    pub fn step(&mut self, settings: &ForceSettings, temperature: f32) {
+        let count = self.nodes.len() as u32;
+        if count == 0 {
+            return;
+        }
+        let k = settings.edge_length.max(f32::EPSILON);
+        let gl = &self.gl;
+        unsafe {
+            for (binding, buffer) in [
+                self.positions,
+                self.masses,
+                self.offsets,
+                self.neighbors,
+                self.displacement,
+                self.pinned,
+            ]
+            .into_iter()
+            .enumerate()
+            {
+                gl.bind_buffer_base(glow::SHADER_STORAGE_BUFFER, binding as u32, Some(buffer));
+            }
+            let groups = count.div_ceil(GROUP_SIZE);
+
+            let force = &self.force;
+            gl.use_program(Some(force.program));
+            gl.uniform_1_u32(force.count.as_ref(), count);
+            gl.uniform_1_f32(force.strength.as_ref(), settings.repulsion * k * k * k);
+            gl.uniform_1_f32(force.edge_length.as_ref(), k);
+            gl.uniform_1_f32(force.gravity.as_ref(), settings.gravity);
+            gl.dispatch_compute(groups, 1, 1);
+            gl.memory_barrier(glow::SHADER_STORAGE_BARRIER_BIT);
+
+            let movement = &self.movement;
+            gl.use_program(Some(movement.program));
+            gl.uniform_1_u32(movement.count.as_ref(), count);
+            gl.uniform_1_f32(movement.temperature.as_ref(), temperature);
+            gl.dispatch_compute(groups, 1, 1);
+            // the positions are next read as vertices, or by the next iteration
+            gl.memory_barrier(
+                glow::SHADER_STORAGE_BARRIER_BIT
+                    | glow::VERTEX_ATTRIB_ARRAY_BARRIER_BIT
+                    | glow::BUFFER_UPDATE_BARRIER_BIT,
+            );
+            gl.use_program(None);
+        }
+    }
+
+    /// Move `node` to `point` and pin or release it, eg. while the user drags it.
+    pub fn set_position(&mut self, node: NodeId, point: [f32; 2], pinned: bool) {
+        let Some(&index) = self.indices.get(node.index()).filter(|i| **i != u32::MAX) else {
+            return;
+        };
+        let gl = &self.gl;
+        unsafe {
+            gl.bind_buffer(glow::SHADER_STORAGE_BUFFER, Some(self.positions));
+            gl.buffer_sub_data_u8_slice(
+                glow::SHADER_STORAGE_BUFFER,
+                (index as usize * size_of::<[f32; 2]>()) as i32,
+                &f32_bytes(&point),
+            );
+            gl.bind_buffer(glow::SHADER_STORAGE_BUFFER, Some(self.pinned));
+            gl.buffer_sub_data_u8_slice(
+                glow::SHADER_STORAGE_BUFFER,
+                (index as usize * size_of::<u32>()) as i32,
+                &(pinned as u32).to_ne_bytes(),
+            );
+            gl.bind_buffer(glow::SHADER_STORAGE_BUFFER, None);
+        }
+    }
+
+    /// Release or pin every node, matching `pinned`.
+    pub fn set_pinned(&mut self, pinned: impl Fn(NodeId) -> bool) {
+        let pins: Vec<u32> = self.nodes.iter().map(|node| pinned(*node) as u32).collect();
+        unsafe {
+            upload(
+                &self.gl,
+                glow::SHADER_STORAGE_BUFFER,
+                self.pinned,
+                &u32_bytes(&pins),
+            );
+        }
+    }
+
+    /// Read the positions back into `positions`. This stalls until the GPU is done,
+    /// so should not be done every frame.
+    pub fn download(&self, positions: &mut Positions) {
+        let mut data = vec![0u8; self.nodes.len() * size_of::<[f32; 2]>()];
+        if data.is_empty() {
+            return;
+        }
+        let gl = &self.gl;
+        unsafe {
+            gl.bind_buffer(glow::SHADER_STORAGE_BUFFER, Some(self.positions));
+            gl.get_buffer_sub_data(glow::SHADER_STORAGE_BUFFER, 0, &mut data);
+            gl.bind_buffer(glow::SHADER_STORAGE_BUFFER, None);
+        }
+        for (node, chunk) in self.nodes.iter().zip(data.chunks_exact(8)) {
+            let x = f32::from_ne_bytes(chunk[..4].try_into().unwrap());
+            let y = f32::from_ne_bytes(chunk[4..].try_into().unwrap());
+            positions.set(*node, [x, y]);
+        }
+    }
+
+    /// The layout's buffers, to be drawn by a [`Scene`](crate::subsystems::scene::Scene)
+    /// with the given style.
+    pub fn buffers(
+        &self,
+        node_size: f32,
+        node_color: [f32; 4],
+        edge_color: [f32; 4],
+    ) -> GraphBuffers {
+        GraphBuffers {
+            positions: self.positions,
+            node_count: self.nodes.len() as u32,
+            edges: self.edges,
+            edge_count: self.edge_count,
+            node_size,
+            node_color,
+            edge_color,
+        }
+    }
+}
+
+impl Program {
+    unsafe fn new(gl: &Context, source: &str) -> Result<Self, GpuLayoutError> {
+        unsafe {
+            let program =
+                link_program(gl, &[(glow::COMPUTE_SHADER, source)]).map_err(GpuLayoutError::Gl)?;
+            let uniform = |name| gl.get_uniform_location(program, name);
+            Ok(Self {
+                program,
+                count: uniform("u_count"),
+                strength: uniform("u_strength"),
+                edge_length: uniform("u_edge_length"),
+                gravity: uniform("u_gravity"),
+                temperature: uniform("u_temperature"),
+            })
+        }
+    }
+}
+
+/// Replace the contents of `buffer`, bound to `target`.
+unsafe fn upload(gl: &Context, target: u32, buffer: glow::Buffer, data: &[u8]) {
+    unsafe {
+        gl.bind_buffer(target, Some(buffer));
+        gl.buffer_data_u8_slice(target, data, glow::DYNAMIC_DRAW);
+        gl.bind_buffer(target, None);
+    }
+}
+
+/// Native-endian bytes of `values`, as laid out in std430 buffers.
+fn f32_bytes(values: &[f32]) -> Vec<u8> {
+    values.iter().flat_map(|v| v.to_ne_bytes()).collect()
+}
+
+/// Native-endian bytes of `values`, as laid out in std430 buffers.
+fn u32_bytes(values: &[u32]) -> Vec<u8> {
+    values.iter().flat_map(|v| v.to_ne_bytes()).collect()
+}
+
+impl Drop for GpuForceLayout {
+    fn drop(&mut self) {
+        unsafe {
+            for buffer in [
+                self.positions,
+                self.masses,
+                self.offsets,
+                self.neighbors,
+                self.displacement,
+                self.pinned,
+                self.edges,
+            ] {
+                self.gl.delete_buffer(buffer);
+            }
+            self.gl.delete_program(self.force.program);
+            self.gl.delete_program(self.movement.program);
+        }
+    }
+}
+
+impl std::fmt::Display for GpuLayoutError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unsupported => write!(f, "Compute shaders are not supported"),
+            Self::Gl(e) => write!(f, "Failed to create GPU layout GL objects: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for GpuLayoutError {}
@@ -0,0 +1,67 @@
+//! # Grid Layout
+//!
+//! Places nodes on a square-ish grid, row by row in id order.
+
+use crate::graph::Graph;
+use crate::i18n::tr;
+use crate::imgui::Ui;
+
+use super::{Layout, Positions};
+
+/// Grid layout, see the [module documentation](self).
+#[derive(Clone, Debug, PartialEq)]
+pub struct GridLayout {
+    /// Distance between neighboring cells, in world units.
+    pub spacing: f32,
+    /// Number of columns, or `0` to fit a square.
+    pub columns: u32,
+}
+
+impl Layout for GridLayout {
+    fn name(&self) -> &'static str {
+        "Grid"
+    }
+
+    fn compute(&mut self, graph: &Graph, positions: &mut Positions) {
+        positions.clear_bends();
+        let count = graph.node_count();
+        let columns = match self.columns {
+            0 => (count as f32).sqrt().ceil() as usize,
+            columns => columns as usize,
+        }
+        .max(1);
+        let rows = count.div_ceil(columns);
+
+        // centered on the origin
+        let offset = [
+            (columns.min(count).max(1) - 1) as f32 * self.spacing / 2.0,
+            (rows.max(1) - 1) as f32 * self.spacing / 2.0,
+        ];
+        for (i, node) in graph.nodes().enumerate() {
+            let (row, column) = (i / columns, i % columns);
+            positions.set(
+                node,
+                [
+                    column as f32 * self.spacing - offset[0],
+                    row as f32 * self.spacing - offset[1],
+                ],
+            );
+        }
+    }
+
+    fn settings_ui(&mut self, ui: &Ui) -> bool {
+        let before = self.clone();
+        ui.slider(tr("Spacing"), 5.0, 300.0, &mut self.spacing);
+        ui.slider(tr("Columns (0 = square)"), 0, 200, &mut self.columns);
+        *self != before
+    }
+}
+
+impl std::default::Default for GridLayout {
+    fn default() -> Self {
+        Self {
+            spacing: 40.0,
+            columns: 0,
+        }
+    }
+}
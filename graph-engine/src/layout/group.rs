@@ -0,0 +1,111 @@
+//! # Group Layout
+//!
+//! Packs the members of each [group](crate::group) together, inside a box
+//! with the group node centered above them, nesting boxes like the groups are.
+//! Boxes fill rows of a square-ish width, in id order, as do the nodes in no
+//! group.
+
+use crate::graph::{Graph, NodeId};
+use crate::group::Groups;
+use crate::i18n::tr;
+use crate::imgui::Ui;
+
+use super::{Layout, Positions};
+
+/// Group layout, see the [module documentation](self).
+#[derive(Clone, Debug, PartialEq)]
+pub struct GroupLayout {
+    /// Size of the cell of a node in no group or in an expanded one, in world
+    /// units.
+    pub spacing: f32,
+    /// Margin between the box of a group and its members.
+    pub padding: f32,
+}
+
+/// Size of a packed box, and the position of each node inside it relative to
+/// its top left corner.
+type Packed = ([f32; 2], Vec<(NodeId, [f32; 2])>);
+
+impl GroupLayout {
+    /// The box of `node`, and of its members if it is a group.
+    fn pack_node(&self, groups: &Groups, node: NodeId) -> Packed {
+        let members = groups.members(node);
+        if members.is_empty() {
+            let half = self.spacing / 2.0;
+            return ([self.spacing, self.spacing], vec![(node, [half, half])]);
+        }
+        let boxes = members.iter().map(|&m| self.pack_node(groups, m)).collect();
+        let (size, mut placed) = self.pack_rows(boxes);
+        for (_, point) in &mut placed {
+            point[0] += self.padding;
+            point[1] += self.spacing;
+        }
+        let size = [
+            size[0] + 2.0 * self.padding,
+            size[1] + self.spacing + self.padding,
+        ];
+        placed.push((node, [size[0] / 2.0, self.spacing / 2.0]));
+        (size, placed)
+    }
+
+    /// Boxes laid out in rows about as wide as the whole is tall.
+    fn pack_rows(&self, boxes: Vec<Packed>) -> Packed {
+        let area: f32 = boxes.iter().map(|(size, _)| size[0] * size[1]).sum();
+        let widest = boxes.iter().map(|(size, _)| size[0]).fold(0.0, f32::max);
+        let width = area.sqrt().max(widest);
+
+        let mut placed = Vec::new();
+        let (mut x, mut y, mut row_height, mut right) = (0.0f32, 0.0f32, 0.0f32, 0.0f32);
+        for (size, nodes) in boxes {
+            if x > 0.0 && x + size[0] > width + f32::EPSILON {
+                (x, y, row_height) = (0.0, y + row_height, 0.0);
+            }
+            placed.extend(
+                nodes
+                    .into_iter()
+                    .map(|(node, point)| (node, [point[0] + x, point[1] + y])),
+            );
+            x += size[0];
+            right = right.max(x);
+            row_height = row_height.max(size[1]);
+        }
+        ([right, y + row_height], placed)
+    }
+}
+
+impl Layout for GroupLayout {
+    fn name(&self) -> &'static str {
+        "Groups"
+    }
+
+    fn compute(&mut self, graph: &Graph, positions: &mut Positions) {
+        positions.clear_bends();
+        let groups = Groups::new(graph);
+        let boxes = groups
+            .roots(graph)
+            .map(|node| self.pack_node(&groups, node))
+            .collect();
+        let (size, placed) = self.pack_rows(boxes);
+
+        // centered on the origin
+        for (node, point) in placed {
+            positions.set(node, [point[0] - size[0] / 2.0, point[1] - size[1] / 2.0]);
+        }
+    }
+
+    fn settings_ui(&mut self, ui: &Ui) -> bool {
+        let before = self.clone();
+        ui.slider(tr("Spacing"), 5.0, 300.0, &mut self.spacing);
+        ui.slider(tr("Padding"), 0.0, 200.0, &mut self.padding);
+        *self != before
+    }
+}
+
+impl std::default::Default for GroupLayout {
+    fn default() -> Self {
+        Self {
+            spacing: 40.0,
+            padding: 20.0,
+        }
+    }
+}
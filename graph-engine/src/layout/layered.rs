@@ -0,0 +1,485 @@
+//! # Layered Layout
+//!
+//! [`LayeredLayout`] draws DAG-like graphs, eg. dependency trees, in layers with
+//! edges flowing in one [`Direction`], following Sugiyama's four phases:
+//! 1. Cycle removal: edges closing a cycle, found by depth-first search, are
+//!    temporarily reversed.
+//! 2. Layering: every node goes one layer below its lowest predecessor, and
+//!    sources are pulled down next to their successors.
+//! 3. Crossing minimization: edges spanning several layers are split by a dummy
+//!    vertex per layer crossed, then layers are reordered by the barycenter of
+//!    their neighbors, sweeping down and up, keeping the order with the fewest
+//!    crossings.
+//! 4. Coordinate assignment: vertices move towards the average of their
+//!    neighbors, keeping their order and spacing.
+//!
+//! Edges spanning several layers are routed through their dummy vertices, so
+//! they pass between the nodes of the layers they cross.
+
+use crate::graph::{EdgeId, Graph, NodeId};
+use crate::i18n::tr;
+use crate::imgui::Ui;
+
+use super::{Layout, Positions};
+
+/// Coordinate assignment passes, each moving vertices down then up.
+const COORDINATE_PASSES: usize = 8;
+
+/// Which way edges point in a [`LayeredLayout`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Direction {
+    #[default]
+    TopToBottom,
+    BottomToTop,
+    LeftToRight,
+    RightToLeft,
+}
+
+/// Hierarchical layout, see the [module documentation](self).
+#[derive(Clone, Debug, PartialEq)]
+pub struct LayeredLayout {
+    pub direction: Direction,
+    /// Distance between consecutive layers, in world units.
+    pub layer_spacing: f32,
+    /// Distance between neighboring nodes within a layer.
+    pub node_spacing: f32,
+    /// Width reserved for each edge passing through a layer.
+    pub edge_spacing: f32,
+    /// Down-and-up sweeps of crossing minimization.
+    pub sweeps: u32,
+    /// Route edges spanning several layers around the nodes in between, rather
+    /// than drawing them straight.
+    pub route_edges: bool,
+}
+
+/// A node, or a dummy vertex splitting a long edge, in the layered graph.
+struct Vertex {
+    node: Option<NodeId>,
+    layer: usize,
+    /// Neighbors in the previous and next layers.
+    up: Vec<usize>,
+    down: Vec<usize>,
+}
+
+/// An edge of the graph, oriented to point down the layers.
+struct Link {
+    edge: EdgeId,
+    source: usize,
+    target: usize,
+    /// Whether the link points against the edge, to break a cycle.
+    reversed: bool,
+    /// Dummy vertices the link passes through, from source to target.
+    dummies: Vec<usize>,
+}
+
+impl Layout for LayeredLayout {
+    fn name(&self) -> &'static str {
+        "Hierarchical"
+    }
+
+    /// Place the nodes of `graph` in layers, routing long edges between them.
+    ///
+    /// Self-loops are ignored.
+    fn compute(&mut self, graph: &Graph, positions: &mut Positions) {
+        layout(graph, positions, self);
+    }
+
+    fn settings_ui(&mut self, ui: &Ui) -> bool {
+        const DIRECTIONS: [Direction; 4] = [
+            Direction::TopToBottom,
+            Direction::BottomToTop,
+            Direction::LeftToRight,
+            Direction::RightToLeft,
+        ];
+
+        let before = self.clone();
+        let mut direction = DIRECTIONS
+            .iter()
+            .position(|d| *d == self.direction)
+            .unwrap();
+        if ui.combo(tr("Direction"), &mut direction, &DIRECTIONS, |d| {
+            tr(d.label())
+        }) {
+            self.direction = DIRECTIONS[direction];
+        }
+        ui.slider(tr("Layer Spacing"), 10.0, 500.0, &mut self.layer_spacing);
+        ui.slider(tr("Node Spacing"), 5.0, 300.0, &mut self.node_spacing);
+        ui.slider(tr("Edge Spacing"), 1.0, 100.0, &mut self.edge_spacing);
+        ui.slider(tr("Crossing Sweeps"), 0, 50, &mut self.sweeps);
+        ui.checkbox(tr("Route Edges Around Layers"), &mut self.route_edges);
+        *self != before
+    }
+}
+
+fn layout(graph: &Graph, positions: &mut Positions, settings: &LayeredLayout) {
+    positions.clear_bends();
+    let nodes: Vec<NodeId> = graph.nodes().collect();
+    if nodes.is_empty() {
+        return;
+    }
+    let mut local = vec![usize::MAX; graph.node_bound()];
+    for (i, node) in nodes.iter().enumerate() {
+        local[node.index()] = i;
+    }
+
+    let mut links: Vec<Link> = graph
+        .edges()
+        .filter_map(|edge| {
+            let (source, target) = graph.endpoints(edge).unwrap();
+            (source != target).then(|| Link {
+                edge,
+                source: local[source.index()],
+                target: local[target.index()],
+                reversed: false,
+                dummies: Vec::new(),
+            })
+        })
+        .collect();
+
+    remove_cycles(nodes.len(), &mut links);
+    let layers_of = assign_layers(nodes.len(), &links);
+
+    let mut vertices: Vec<Vertex> = nodes
+        .iter()
+        .zip(&layers_of)
+        .map(|(node, layer)| Vertex {
+            node: Some(*node),
+            layer: *layer,
+            up: Vec::new(),
+            down: Vec::new(),
+        })
+        .collect();
+    for link in &mut links {
+        let mut previous = link.source;
+        for layer in layers_of[link.source] + 1..layers_of[link.target] {
+            let dummy = vertices.len();
+            vertices.push(Vertex {
+                node: None,
+                layer,
+                up: vec![previous],
+                down: Vec::new(),
+            });
+            vertices[previous].down.push(dummy);
+            link.dummies.push(dummy);
+            previous = dummy;
+        }
+        vertices[previous].down.push(link.target);
+        vertices[link.target].up.push(previous);
+    }
+
+    let layer_count = layers_of.iter().max().unwrap() + 1;
+    let mut layers = vec![Vec::new(); layer_count];
+    for (v, vertex) in vertices.iter().enumerate() {
+        layers[vertex.layer].push(v);
+    }
+    order_layers(&vertices, &mut layers, settings.sweeps);
+    let x = assign_coordinates(&vertices, &layers, settings);
+
+    let place = |v: usize| {
+        let x = x[v];
+        let y = vertices[v].layer as f32 * settings.layer_spacing;
+        let y = y - (layer_count - 1) as f32 * settings.layer_spacing / 2.0;
+        match settings.direction {
+            Direction::TopToBottom => [x, y],
+            Direction::BottomToTop => [x, -y],
+            Direction::LeftToRight => [y, x],
+            Direction::RightToLeft => [-y, x],
+        }
+    };
+    for (v, vertex) in vertices.iter().enumerate() {
+        if let Some(node) = vertex.node {
+            positions.set(node, place(v));
+        }
+    }
+    if settings.route_edges {
+        for link in &links {
+            let mut bends: Vec<[f32; 2]> = link.dummies.iter().map(|v| place(*v)).collect();
+            if link.reversed {
+                bends.reverse();
+            }
+            positions.set_bends(link.edge, bends);
+        }
+    }
+}
+
+/// Reverse the links closing cycles, found by depth-first search.
+fn remove_cycles(count: usize, links: &mut [Link]) {
+    let mut out = vec![Vec::new(); count];
+    for (i, link) in links.iter().enumerate() {
+        out[link.source].push(i);
+    }
+
+    #[derive(Copy, Clone, PartialEq)]
+    enum State {
+        New,
+        OnStack,
+        Done,
+    }
+    let mut state = vec![State::New; count];
+    for root in 0..count {
+        if state[root] != State::New {
+            continue;
+        }
+        state[root] = State::OnStack;
+        // (vertex, next outgoing link to visit)
+        let mut stack = vec![(root, 0)];
+        while let Some((v, next)) = stack.last_mut() {
+            let v = *v;
+            let Some(&link) = out[v].get(*next) else {
+                state[v] = State::Done;
+                stack.pop();
+                continue;
+            };
+            *next += 1;
+            let target = links[link].target;
+            match state[target] {
+                State::New => {
+                    state[target] = State::OnStack;
+                    stack.push((target, 0));
+                }
+                State::OnStack => links[link].reversed = true,
+                State::Done => {}
+            }
+        }
+    }
+    for link in links.iter_mut().filter(|l| l.reversed) {
+        std::mem::swap(&mut link.source, &mut link.target);
+    }
+}
+
+/// Layer of each vertex by longest path from the sources, with sources moved
+/// down to just above their highest successor.
+fn assign_layers(count: usize, links: &[Link]) -> Vec<usize> {
+    let mut out = vec![Vec::new(); count];
+    let mut incoming = vec![0; count];
+    for link in links {
+        out[link.source].push(link.target);
+        incoming[link.target] += 1;
+    }
+
+    // Kahn's topological sort, tracking the longest path to each vertex
+    let mut order = Vec::with_capacity(count);
+    let mut remaining = incoming.clone();
+    let mut ready: Vec<usize> = (0..count).filter(|v| remaining[*v] == 0).collect();
+    let mut layers = vec![0; count];
+    while let Some(v) = ready.pop() {
+        order.push(v);
+        for &w in &out[v] {
+            layers[w] = layers[w].max(layers[v] + 1);
+            remaining[w] -= 1;
+            if remaining[w] == 0 {
+                ready.push(w);
+            }
+        }
+    }
+
+    for &v in order.iter().rev() {
+        if incoming[v] == 0
+            && let Some(lowest) = out[v].iter().map(|w| layers[*w]).min()
+        {
+            layers[v] = lowest - 1;
+        }
+    }
+    layers
+}
+
+/// Reorder each layer to reduce crossings, with the barycenter heuristic.
+fn order_layers(vertices: &[Vertex], layers: &mut [Vec<usize>], sweeps: u32) {
+    let mut position = vec![0; vertices.len()];
+    let update = |layer: &[usize], position: &mut [usize]| {
+        for (i, v) in layer.iter().enumerate() {
+            position[*v] = i;
+        }
+    };
+    for layer in layers.iter() {
+        update(layer, &mut position);
+    }
+
+    let mut best = layers.to_vec();
+    let mut best_crossings = crossings(vertices, layers, &position);
+    for _ in 0..sweeps {
+        if best_crossings == 0 {
+            break;
+        }
+        for layer in layers.iter_mut().skip(1) {
+            reorder(layer, &position, |v| &vertices[v].up);
+            update(layer, &mut position);
+        }
+        for layer in layers.iter_mut().rev().skip(1) {
+            reorder(layer, &position, |v| &vertices[v].down);
+            update(layer, &mut position);
+        }
+
+        let count = crossings(vertices, layers, &position);
+        if count < best_crossings {
+            best_crossings = count;
+            best.clone_from_slice(layers);
+        }
+    }
+    layers.clone_from_slice(&best);
+}
+
+/// Sort `layer` by the mean position of each vertex's `neighbors`; vertices
+/// without any keep their place.
+fn reorder<'a>(
+    layer: &mut [usize],
+    position: &[usize],
+    neighbors: impl Fn(usize) -> &'a Vec<usize>,
+) {
+    let mut keyed: Vec<(f32, usize)> = layer
+        .iter()
+        .map(|&v| {
+            let neighbors = neighbors(v);
+            let key = match neighbors.len() {
+                0 => position[v] as f32,
+                n => neighbors.iter().map(|w| position[*w] as f32).sum::<f32>() / n as f32,
+            };
+            (key, v)
+        })
+        .collect();
+    keyed.sort_by(|a, b| a.0.total_cmp(&b.0));
+    for (slot, (_, v)) in layer.iter_mut().zip(keyed) {
+        *slot = v;
+    }
+}
+
+/// Number of edge crossings between consecutive layers.
+fn crossings(vertices: &[Vertex], layers: &[Vec<usize>], position: &[usize]) -> usize {
+    let mut total = 0;
+    for layer in layers {
+        // targets' positions, ordered by source then target; each inversion is a crossing
+        let mut targets: Vec<(usize, usize)> = layer
+            .iter()
+            .flat_map(|&v| {
+                vertices[v]
+                    .down
+                    .iter()
+                    .map(move |w| (position[v], position[*w]))
+            })
+            .collect();
+        targets.sort_unstable();
+        let mut targets: Vec<usize> = targets.into_iter().map(|(_, t)| t).collect();
+        total += inversions(&mut targets);
+    }
+    total
+}
+
+/// Number of pairs `i < j` with `values[i] > values[j]`, sorting `values`.
+fn inversions(values: &mut [usize]) -> usize {
+    if values.len() < 2 {
+        return 0;
+    }
+    let mid = values.len() / 2;
+    let mut count = inversions(&mut values[..mid]) + inversions(&mut values[mid..]);
+
+    let mut merged = Vec::with_capacity(values.len());
+    let (mut i, mut j) = (0, mid);
+    while i < mid && j < values.len() {
+        if values[j] < values[i] {
+            // every remaining value on the left is greater
+            count += mid - i;
+            merged.push(values[j]);
+            j += 1;
+        } else {
+            merged.push(values[i]);
+            i += 1;
+        }
+    }
+    merged.extend_from_slice(&values[i..mid]);
+    merged.extend_from_slice(&values[j..]);
+    values.copy_from_slice(&merged);
+    count
+}
+
+/// Position of each vertex within its layer, centered on 0.
+fn assign_coordinates(
+    vertices: &[Vertex],
+    layers: &[Vec<usize>],
+    settings: &LayeredLayout,
+) -> Vec<f32> {
+    let size = |v: usize| match vertices[v].node {
+        Some(_) => settings.node_spacing,
+        None => settings.edge_spacing,
+    };
+    let gap = |a: usize, b: usize| (size(a) + size(b)) / 2.0;
+
+    let mut x = vec![0.0; vertices.len()];
+    for layer in layers {
+        let mut next = 0.0;
+        for (i, &v) in layer.iter().enumerate() {
+            if i > 0 {
+                next += gap(layer[i - 1], v);
+            }
+            x[v] = next;
+        }
+    }
+
+    for pass in 0..COORDINATE_PASSES * 2 {
+        let down = pass % 2 == 0;
+        let order: Box<dyn Iterator<Item = &Vec<usize>>> = match down {
+            true => Box::new(layers.iter().skip(1)),
+            false => Box::new(layers.iter().rev().skip(1)),
+        };
+        for layer in order {
+            let targets: Vec<f32> = layer
+                .iter()
+                .map(|&v| {
+                    let neighbors = match down {
+                        true => &vertices[v].up,
+                        false => &vertices[v].down,
+                    };
+                    match neighbors.len() {
+                        0 => x[v],
+                        n => neighbors.iter().map(|w| x[*w]).sum::<f32>() / n as f32,
+                    }
+                })
+                .collect();
+
+            // closest positions to the targets keeping the gaps, packed from
+            // either side, then averaged so neither side is favored
+            let mut left = targets.clone();
+            for i in 1..layer.len() {
+                left[i] = left[i].max(left[i - 1] + gap(layer[i - 1], layer[i]));
+            }
+            let mut right = targets;
+            for i in (0..layer.len().saturating_sub(1)).rev() {
+                right[i] = right[i].min(right[i + 1] - gap(layer[i], layer[i + 1]));
+            }
+            for (i, &v) in layer.iter().enumerate() {
+                x[v] = (left[i] + right[i]) / 2.0;
+            }
+        }
+    }
+
+    let (min, max) = x.iter().fold((f32::MAX, f32::MIN), |(min, max), x| {
+        (min.min(*x), max.max(*x))
+    });
+    let center = (min + max) / 2.0;
+    x.iter_mut().for_each(|x| *x -= center);
+    x
+}
+
+impl Direction {
+    /// Human readable name, eg. for a menu.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::TopToBottom => "Top to Bottom",
+            Self::BottomToTop => "Bottom to Top",
+            Self::LeftToRight => "Left to Right",
+            Self::RightToLeft => "Right to Left",
+        }
+    }
+}
+
+impl std::default::Default for LayeredLayout {
+    fn default() -> Self {
+        Self {
+            direction: Direction::default(),
+            layer_spacing: 80.0,
+            node_spacing: 40.0,
+            edge_spacing: 10.0,
+            sweeps: 12,
+            route_edges: true,
+        }
+    }
+}
@@ -0,0 +1,122 @@
+//! # Radial Tree Layout
+//!
+//! Places a root node at the center and every other node on a concentric ring
+//! according to its distance from the root. Each node gets a wedge of its
+//! parent's wedge, sized by how many leaves hang below it, so subtrees do not
+//! overlap.
+//!
+//! Nodes not connected to the root go on an extra, outermost ring.
+
+use std::collections::VecDeque;
+use std::f32::consts::TAU;
+
+use crate::graph::{Graph, NodeId};
+use crate::i18n::tr;
+use crate::imgui::Ui;
+
+use super::{Layout, Positions};
+
+/// Radial tree layout, see the [module documentation](self).
+#[derive(Clone, Debug, PartialEq)]
+pub struct RadialLayout {
+    /// Node at the center, or `None` for the highest-degree node.
+    pub root: Option<NodeId>,
+    /// Distance between consecutive rings, in world units.
+    pub ring_spacing: f32,
+}
+
+impl Layout for RadialLayout {
+    fn name(&self) -> &'static str {
+        "Radial Tree"
+    }
+
+    fn compute(&mut self, graph: &Graph, positions: &mut Positions) {
+        positions.clear_bends();
+        let root = self
+            .root
+            .filter(|root| graph.contains_node(*root))
+            .or_else(|| graph.nodes().max_by_key(|node| graph.degree(*node)));
+        let Some(root) = root else {
+            return;
+        };
+
+        // breadth-first spanning tree
+        let mut depth = vec![usize::MAX; graph.node_bound()];
+        let mut children: Vec<Vec<NodeId>> = vec![Vec::new(); graph.node_bound()];
+        let mut order = vec![root];
+        depth[root.index()] = 0;
+        let mut queue = VecDeque::from([root]);
+        while let Some(node) = queue.pop_front() {
+            for neighbor in graph.neighbors(node) {
+                if depth[neighbor.index()] == usize::MAX {
+                    depth[neighbor.index()] = depth[node.index()] + 1;
+                    children[node.index()].push(neighbor);
+                    order.push(neighbor);
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+
+        let mut leaves = vec![0usize; graph.node_bound()];
+        for node in order.iter().rev() {
+            let below: usize = children[node.index()]
+                .iter()
+                .map(|c| leaves[c.index()])
+                .sum();
+            leaves[node.index()] = below.max(1);
+        }
+
+        // each node's wedge, as (start, width) in radians, split among its children
+        let mut wedge = vec![(0.0, 0.0); graph.node_bound()];
+        wedge[root.index()] = (0.0, TAU);
+        positions.set(root, [0.0; 2]);
+        for node in &order {
+            let (mut start, width) = wedge[node.index()];
+            let total = leaves[node.index()] as f32;
+            for child in &children[node.index()] {
+                let share = width * leaves[child.index()] as f32 / total;
+                wedge[child.index()] = (start, share);
+                let angle = start + share / 2.0;
+                let radius = depth[child.index()] as f32 * self.ring_spacing;
+                positions.set(*child, [radius * angle.cos(), radius * angle.sin()]);
+                start += share;
+            }
+        }
+
+        let unreached: Vec<NodeId> = graph
+            .nodes()
+            .filter(|node| depth[node.index()] == usize::MAX)
+            .collect();
+        let outer = order.iter().map(|n| depth[n.index()]).max().unwrap_or(0) + 1;
+        let radius = outer as f32 * self.ring_spacing;
+        for (i, node) in unreached.iter().enumerate() {
+            let angle = i as f32 / unreached.len() as f32 * TAU;
+            positions.set(*node, [radius * angle.cos(), radius * angle.sin()]);
+        }
+    }
+
+    fn settings_ui(&mut self, ui: &Ui) -> bool {
+        let before = self.clone();
+        ui.slider(tr("Ring Spacing"), 10.0, 500.0, &mut self.ring_spacing);
+        match self.root {
+            Some(root) => {
+                ui.text(tr("Root: {root}").replace("{root}", &root.to_string()));
+                ui.same_line();
+                if ui.small_button(tr("Reset")) {
+                    self.root = None;
+                }
+            }
+            None => ui.text_disabled(tr("Root: highest-degree node")),
+        }
+        *self != before
+    }
+}
+
+impl std::default::Default for RadialLayout {
+    fn default() -> Self {
+        Self {
+            root: None,
+            ring_spacing: 80.0,
+        }
+    }
+}
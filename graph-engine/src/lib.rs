@@ -1,6 +1,47 @@
 use common::renderer::imgui;
 use common::renderer::sdl3;
 
+pub mod algo;
+pub mod anim;
+pub mod bookmarks;
+pub mod clipboard;
+pub mod context;
+pub mod document;
+pub mod explore;
+pub mod filter;
+pub mod generate;
+pub mod graph;
+pub mod group;
+pub mod history;
+pub mod hover;
+pub mod i18n;
+pub mod inspector;
+pub mod io;
+pub mod jobs;
+pub mod layout;
+pub mod matrix;
+pub mod memory;
+pub mod minimap;
+pub mod palette;
+pub mod pathfind;
+pub mod plugin;
+#[cfg(feature = "http")]
+pub mod remote;
+pub mod routing;
+pub mod script;
+pub mod scripts;
+pub mod search;
+pub mod selection;
+pub mod session;
+pub mod spatial;
+pub mod stats;
+pub mod status;
+#[cfg(feature = "sqlite")]
+pub mod store;
+pub mod style;
 pub mod subsystems;
+pub mod table;
+pub mod timeline;
+pub mod view3d;
 
 pub use common::renderer;
@@ -0,0 +1,687 @@
+//! # Matrix View
+//!
+//! Shows the visible graph as its adjacency matrix, with a row and a column per
+//! node and a cell filled for every edge from the row's node to the column's.
+//! Dense graphs, which are a hairball as nodes and links, show their structure
+//! as blocks once rows are put in a [`MatrixOrder`]:
+//! - by degree, hubs first
+//! - by [community](algo::leiden), communities showing as blocks along the
+//!   diagonal
+//! - spectral, along the Fiedler vector of the graph's Laplacian, so connected
+//!   nodes end up close
+//!
+//! The [`MatrixPanel`] zooms with the wheel and pans by dragging. Hovering a
+//! cell flashes its two nodes in the scene, and clicking it selects its edge,
+//! or both nodes if there is none, and moves the camera to them.
+//!
+//! Cells are drawn from textures, a pixel per cell, so large graphs draw as a
+//! few quads:
+//! - zoomed out, a single overview of at most [`OVERVIEW_SIZE`] pixels, each
+//!   covering a square of cells
+//! - zoomed in, [`TILE_SIZE`] cells square tiles, made as they come into view
+//!   and dropped once more than [`MAX_TILES`] are kept
+//!
+//! Making textures needs the window's GL context, so the app calls [`render()`]
+//! once per frame, as for the [minimap](crate::minimap).
+//!
+//! ```ignore
+//! // in `GraphApp::update()`, after `EngineContext::refresh()`
+//! matrix::render(&mut self.ctx, ctx.window());
+//! ```
+
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+
+use common::renderer::texture::image::RgbaImage;
+use common::renderer::texture::{Texture2d, TextureFilter};
+
+use crate::algo;
+use crate::context::EngineContext;
+use crate::graph::{EdgeId, Graph, NodeId};
+use crate::i18n::tr;
+use crate::imgui::{MouseButton, TextureId, Ui};
+use crate::selection::SelectMode;
+use crate::subsystems::panels::Panel;
+use crate::subsystems::window::GraphWindow;
+
+/// Side of a tile, in cells.
+pub const TILE_SIZE: usize = 256;
+/// Most tiles kept at once, the least recently shown ones being dropped.
+pub const MAX_TILES: usize = 128;
+/// Largest side of the overview texture, in pixels.
+pub const OVERVIEW_SIZE: usize = 1024;
+/// Most tiles made per frame, so zooming into a large graph stays smooth.
+const TILES_PER_FRAME: usize = 16;
+/// Zoom, in points per cell, from which tiles are drawn rather than the
+/// overview.
+const TILE_ZOOM: f32 = 1.0;
+/// Largest zoom, in points per cell.
+const MAX_ZOOM: f32 = 64.0;
+/// Zoom factor of a wheel notch.
+const ZOOM_STEP: f32 = 1.2;
+/// Power iterations approximating the Fiedler vector for [`MatrixOrder::Spectral`].
+const SPECTRAL_ITERATIONS: usize = 100;
+/// Smallest side of the matrix in its panel, in points.
+const MIN_SIDE: f32 = 64.0;
+
+/// How the rows and columns of the matrix are ordered.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum MatrixOrder {
+    /// By node id, ie. the order the nodes were added in.
+    Id,
+    /// By decreasing degree.
+    #[default]
+    Degree,
+    /// By community, then by decreasing degree.
+    Community,
+    /// Along the Fiedler vector, placing connected nodes close.
+    Spectral,
+}
+
+/// The visible graph as an adjacency matrix, in some [`MatrixOrder`].
+#[derive(Clone, Debug, Default)]
+pub struct AdjacencyMatrix {
+    /// The node of each row and column.
+    nodes: Vec<NodeId>,
+    /// Row of each node slot, `u32::MAX` for removed nodes.
+    rows: Vec<u32>,
+    /// Columns of the filled cells of each row, sorted, with the edge of each.
+    cells: Vec<Vec<(u32, EdgeId)>>,
+    /// Tiles with at least a filled cell, by row and column.
+    filled: HashSet<(usize, usize)>,
+}
+
+/// The part of the matrix shown by the [`MatrixPanel`], in cells.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct MatrixView {
+    /// Column and row at the top left corner.
+    pub offset: [f32; 2],
+    /// Points per cell.
+    pub zoom: f32,
+    /// Size of the matrix in its panel, in points.
+    pub size: [f32; 2],
+}
+
+/// The [`AdjacencyMatrix`] of the active document's visible graph, and the
+/// textures drawing it, see the [module documentation](self).
+#[derive(Default)]
+pub struct MatrixState {
+    order: MatrixOrder,
+    matrix: AdjacencyMatrix,
+    /// Generation of the graph, order and cell color the matrix and textures
+    /// were made for.
+    built: Option<(u64, MatrixOrder, [u8; 4])>,
+    /// The view of the panel as of the last frame, `None` until fitted.
+    view: Option<MatrixView>,
+    /// Cells per pixel of the overview, and the overview itself.
+    overview: Option<(usize, Texture2d)>,
+    /// Tiles by row and column, with the render they were last shown at.
+    tiles: HashMap<(usize, usize), (Texture2d, u64)>,
+    /// Number of renders done, to find the tiles shown least recently.
+    renders: u64,
+    /// Set by the panel each frame it is drawn, read by the next render.
+    shown: bool,
+    /// Why the textures could not be made, if they could not.
+    error: Option<String>,
+}
+
+/// Draws the [`MatrixState`], see the [module documentation](self).
+#[derive(Default)]
+pub struct MatrixPanel {
+    /// Set while the pressed cursor moved, so releasing it does not click.
+    dragged: bool,
+}
+
+impl MatrixOrder {
+    pub const ALL: [MatrixOrder; 4] = [Self::Id, Self::Degree, Self::Community, Self::Spectral];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Id => "Node Id",
+            Self::Degree => "Degree",
+            Self::Community => "Community",
+            Self::Spectral => "Spectral",
+        }
+    }
+
+    /// The nodes of `graph` in this order.
+    pub fn sort(self, graph: &Graph) -> Vec<NodeId> {
+        let mut nodes: Vec<NodeId> = graph.nodes().collect();
+        match self {
+            Self::Id => {}
+            Self::Degree => nodes.sort_by_key(|&node| Reverse(graph.degree(node))),
+            Self::Community => {
+                let communities = algo::leiden(graph, None);
+                nodes.sort_by_key(|&node| {
+                    (
+                        communities.community(node).unwrap_or(usize::MAX),
+                        Reverse(graph.degree(node)),
+                    )
+                });
+            }
+            Self::Spectral => {
+                let fiedler = fiedler_vector(graph);
+                nodes.sort_by(|a, b| fiedler[a.index()].total_cmp(&fiedler[b.index()]));
+            }
+        }
+        nodes
+    }
+}
+
+impl AdjacencyMatrix {
+    /// The matrix of `graph`, with rows and columns in `order`. Undirected edges
+    /// fill both of their cells.
+    pub fn new(graph: &Graph, order: MatrixOrder) -> Self {
+        let nodes = order.sort(graph);
+        let mut rows = vec![u32::MAX; graph.node_bound()];
+        for (row, node) in nodes.iter().enumerate() {
+            rows[node.index()] = row as u32;
+        }
+        let mut cells = vec![Vec::new(); nodes.len()];
+        let mut filled = HashSet::new();
+        for edge in graph.edges() {
+            let Some((source, target)) = graph.endpoints(edge) else {
+                continue;
+            };
+            let (row, column) = (rows[source.index()], rows[target.index()]);
+            let mut fill = |row: u32, column: u32| {
+                cells[row as usize].push((column, edge));
+                filled.insert((row as usize / TILE_SIZE, column as usize / TILE_SIZE));
+            };
+            fill(row, column);
+            if !graph.is_directed() && row != column {
+                fill(column, row);
+            }
+        }
+        for row in &mut cells {
+            row.sort_unstable();
+        }
+        Self {
+            nodes,
+            rows,
+            cells,
+            filled,
+        }
+    }
+
+    /// Number of rows, and of columns.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// The node of `row`, which is also that of the column of the same index.
+    pub fn node(&self, row: usize) -> Option<NodeId> {
+        self.nodes.get(row).copied()
+    }
+
+    /// The row, and column, of `node`.
+    pub fn row(&self, node: NodeId) -> Option<usize> {
+        self.rows
+            .get(node.index())
+            .filter(|row| **row != u32::MAX)
+            .map(|row| *row as usize)
+    }
+
+    /// The edge filling the cell at `row` and `column`, if any; the first one
+    /// for parallel edges.
+    pub fn edge(&self, row: usize, column: usize) -> Option<EdgeId> {
+        let cells = self.cells.get(row)?;
+        let at = cells.partition_point(|&(c, _)| (c as usize) < column);
+        cells
+            .get(at)
+            .filter(|(c, _)| *c as usize == column)
+            .map(|(_, edge)| *edge)
+    }
+
+    /// Pixels of the square of `side` cells from `row` and `column`, a pixel
+    /// per `cells` square of cells, filled in `color` if any of them is.
+    fn pixels(
+        &self,
+        row: usize,
+        column: usize,
+        side: usize,
+        cells: usize,
+        color: [u8; 4],
+    ) -> RgbaImage {
+        let pixels = side.div_ceil(cells) as u32;
+        let mut image = RgbaImage::new(pixels, pixels);
+        let end = (row + side).min(self.len());
+        for (r, cells_of_row) in self.cells.iter().enumerate().take(end).skip(row) {
+            let from = cells_of_row.partition_point(|&(c, _)| (c as usize) < column);
+            for &(c, _) in &cells_of_row[from..] {
+                let c = c as usize;
+                if c >= column + side {
+                    break;
+                }
+                let (x, y) = ((c - column) / cells, (r - row) / cells);
+                image.put_pixel(x as u32, y as u32, color.into());
+            }
+        }
+        image
+    }
+}
+
+impl MatrixView {
+    /// A view showing the whole matrix of `len` rows in `size` points.
+    pub fn fit(len: usize, size: [f32; 2]) -> Self {
+        let zoom = (size[0].min(size[1]) / len.max(1) as f32).min(MAX_ZOOM);
+        Self {
+            offset: [0.0; 2],
+            zoom,
+            size,
+        }
+    }
+
+    /// Zoom by `factor` about `point`, in points from the top left corner.
+    pub fn zoom_at(&mut self, point: [f32; 2], factor: f32, len: usize) {
+        let min = (self.size[0].min(self.size[1]) / len.max(1) as f32).min(1.0);
+        let zoom = (self.zoom * factor).clamp(min, MAX_ZOOM);
+        for (offset, point) in self.offset.iter_mut().zip(point) {
+            *offset += point / self.zoom - point / zoom;
+        }
+        self.zoom = zoom;
+    }
+
+    /// Rows, and columns, from the first to the last one at least partly
+    /// visible, out of `len`.
+    pub fn visible(&self, len: usize) -> [std::ops::Range<usize>; 2] {
+        [0, 1].map(|axis| {
+            let first = self.offset[axis].max(0.0) as usize;
+            let last = (self.offset[axis] + self.size[axis] / self.zoom)
+                .ceil()
+                .max(0.0) as usize;
+            first.min(len)..last.min(len)
+        })
+    }
+}
+
+impl MatrixState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn order(&self) -> MatrixOrder {
+        self.order
+    }
+
+    /// Order the rows and columns by `order`, from the next [`render()`].
+    pub fn set_order(&mut self, order: MatrixOrder) {
+        self.order = order;
+    }
+
+    /// The matrix as of the last [`render()`].
+    pub fn matrix(&self) -> &AdjacencyMatrix {
+        &self.matrix
+    }
+
+    /// Why the matrix could not be drawn, if it could not.
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Ask for the matrix to be kept up to date by the next [`render()`].
+    pub fn show(&mut self) {
+        self.shown = true;
+    }
+
+    /// The textures to draw for `view`, in order, with the column and row each
+    /// starts at and the number of cells on its side.
+    fn textures(&self, view: &MatrixView) -> Vec<(TextureId, [usize; 2], usize)> {
+        let overview = self
+            .overview
+            .as_ref()
+            .map(|(cells, texture)| (texture.id(), [0, 0], texture.width() as usize * cells));
+        if view.zoom < TILE_ZOOM {
+            return overview.into_iter().collect();
+        }
+        let [rows, columns] = view.visible(self.matrix.len());
+        let mut missing = false;
+        let tiles: Vec<_> = tiles_of(rows, columns)
+            .filter(|tile| self.matrix.filled.contains(tile))
+            .filter_map(|tile| {
+                let texture = self.tiles.get(&tile).map(|(texture, _)| texture.id());
+                missing |= texture.is_none();
+                Some((
+                    texture?,
+                    [tile.1 * TILE_SIZE, tile.0 * TILE_SIZE],
+                    TILE_SIZE,
+                ))
+            })
+            .collect();
+        // the overview stands in for the tiles not made yet
+        match missing {
+            true => overview.into_iter().chain(tiles).collect(),
+            false => tiles,
+        }
+    }
+}
+
+/// Build the adjacency matrix of the visible graph of `ctx` if it changed, and
+/// make the textures it is drawn with through the GL context of `window`, if
+/// the panel was shown since the last call. See the
+/// [module documentation](self).
+pub fn render(ctx: &mut EngineContext, window: &GraphWindow) {
+    let state = &mut ctx.matrix;
+    if !std::mem::take(&mut state.shown) || state.error.is_some() {
+        return;
+    }
+    state.renders += 1;
+    let graph = ctx.filters.graph(&ctx.graph);
+    let color = ctx
+        .style
+        .sheet()
+        .theme
+        .colors()
+        .label
+        .map(|c| (c * 255.0).round() as u8);
+    let key = (graph.generation(), state.order, color);
+    if state.built != Some(key) {
+        let resized = state.matrix.len() != graph.node_count();
+        state.matrix = AdjacencyMatrix::new(graph, state.order);
+        state.built = Some(key);
+        state.overview = None;
+        state.tiles.clear();
+        if resized {
+            state.view = None;
+        }
+    }
+    let Some(mut ui) = window.get_ui() else {
+        return;
+    };
+    let matrix = &state.matrix;
+    if matrix.is_empty() {
+        return;
+    }
+
+    if state.overview.is_none() {
+        let cells = matrix.len().div_ceil(OVERVIEW_SIZE);
+        let image = matrix.pixels(0, 0, matrix.len(), cells, color);
+        match ui.create_texture(&image, TextureFilter::Nearest) {
+            Ok(texture) => state.overview = Some((cells, texture)),
+            Err(e) => {
+                ctx.log.error(format!("Failed to draw the matrix: {e}"));
+                state.error = Some(e.to_string());
+                return;
+            }
+        }
+    }
+
+    // tiles are only needed once the overview is too coarse
+    let Some(view) = state.view.filter(|view| view.zoom >= TILE_ZOOM) else {
+        return;
+    };
+    let [rows, columns] = view.visible(matrix.len());
+    let mut made = 0;
+    for tile in tiles_of(rows, columns) {
+        if let Some((_, shown)) = state.tiles.get_mut(&tile) {
+            *shown = state.renders;
+            continue;
+        }
+        if !matrix.filled.contains(&tile) || made == TILES_PER_FRAME {
+            continue;
+        }
+        made += 1;
+        let image = matrix.pixels(tile.0 * TILE_SIZE, tile.1 * TILE_SIZE, TILE_SIZE, 1, color);
+        match ui.create_texture(&image, TextureFilter::Nearest) {
+            Ok(texture) => {
+                state.tiles.insert(tile, (texture, state.renders));
+            }
+            Err(e) => {
+                ctx.log.error(format!("Failed to draw the matrix: {e}"));
+                state.error = Some(e.to_string());
+                return;
+            }
+        }
+    }
+    while state.tiles.len() > MAX_TILES {
+        let oldest = state
+            .tiles
+            .iter()
+            .min_by_key(|(_, (_, shown))| *shown)
+            .map(|(tile, _)| *tile);
+        if let Some(tile) = oldest {
+            state.tiles.remove(&tile);
+        }
+    }
+}
+
+/// The tiles covering `rows` and `columns`, by row and column.
+fn tiles_of(
+    rows: std::ops::Range<usize>,
+    columns: std::ops::Range<usize>,
+) -> impl Iterator<Item = (usize, usize)> {
+    let tiles =
+        |range: std::ops::Range<usize>| range.start / TILE_SIZE..range.end.div_ceil(TILE_SIZE);
+    let columns = tiles(columns);
+    tiles(rows).flat_map(move |row| columns.clone().map(move |column| (row, column)))
+}
+
+/// The Fiedler vector of `graph`, ignoring edge directions, by node slot:
+/// the eigenvector of the second smallest eigenvalue of its normalized
+/// Laplacian, approximated by power iteration. Isolated nodes get `0.0`.
+fn fiedler_vector(graph: &Graph) -> Vec<f64> {
+    let bound = graph.node_bound();
+    let neighbors = |node| {
+        graph
+            .incident_edges(node)
+            .filter_map(move |edge| graph.opposite(edge, node))
+    };
+    let mut scale = vec![0.0; bound];
+    for node in graph.nodes() {
+        scale[node.index()] = (neighbors(node).count() as f64).sqrt();
+    }
+    // the eigenvector of the largest eigenvalue, projected out at each step
+    let norm = scale.iter().map(|s| s * s).sum::<f64>().sqrt();
+    let top: Vec<f64> = scale.iter().map(|s| s / norm.max(f64::EPSILON)).collect();
+
+    // a deterministic start, unlikely to be orthogonal to the Fiedler vector
+    let mut x: Vec<f64> = (0..bound)
+        .map(|i| (i as f64 * 0.618_033_988_7).fract() - 0.5)
+        .collect();
+    let mut next = vec![0.0; bound];
+    for _ in 0..SPECTRAL_ITERATIONS {
+        let dot: f64 = x.iter().zip(&top).map(|(x, t)| x * t).sum();
+        for (x, t) in x.iter_mut().zip(&top) {
+            *x -= dot * t;
+        }
+        // (I + D^-1/2 A D^-1/2) / 2, shifted so its eigenvalues are positive
+        for node in graph.nodes() {
+            let i = node.index();
+            if scale[i] == 0.0 {
+                next[i] = 0.0;
+                continue;
+            }
+            let sum: f64 = neighbors(node)
+                .map(|n| x[n.index()] / scale[n.index()])
+                .sum();
+            next[i] = 0.5 * x[i] + 0.5 * sum / scale[i];
+        }
+        let norm = next.iter().map(|v| v * v).sum::<f64>().sqrt();
+        if norm <= f64::EPSILON {
+            break;
+        }
+        for (x, next) in x.iter_mut().zip(&next) {
+            *x = next / norm;
+        }
+    }
+    x.iter()
+        .zip(&scale)
+        .map(|(x, s)| if *s > 0.0 { x / s } else { 0.0 })
+        .collect()
+}
+
+impl MatrixPanel {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Name of `node` for the tooltip of a cell.
+    fn node_name(ctx: &EngineContext, node: NodeId) -> String {
+        ctx.style
+            .node_label(ctx.visible_graph(), node, true)
+            .unwrap_or_else(|| format!("#{}", node.index()))
+    }
+
+    /// Flash the nodes of the cell at `row` and `column` in the scene, and
+    /// select them, or their edge, once clicked.
+    fn cell_ui(ui: &Ui, ctx: &mut EngineContext, row: usize, column: usize, clicked: bool) {
+        let matrix = ctx.matrix.matrix();
+        let (Some(source), Some(target)) = (matrix.node(row), matrix.node(column)) else {
+            return;
+        };
+        let edge = matrix.edge(row, column);
+        let text = match edge {
+            Some(_) => tr("{source} to {target}"),
+            None => tr("{source} to {target}, no edge"),
+        }
+        .replace("{source}", &Self::node_name(ctx, source))
+        .replace("{target}", &Self::node_name(ctx, target));
+        ui.tooltip_text(text);
+
+        let colors = ctx.style.sheet().theme.colors();
+        ctx.animations.flash_node(source, colors.selected);
+        ctx.animations.flash_node(target, colors.selected);
+        if clicked {
+            match edge {
+                Some(edge) => ctx
+                    .selection
+                    .select([source, target], [edge], SelectMode::Replace),
+                None => ctx
+                    .selection
+                    .select([source, target], [], SelectMode::Replace),
+            }
+            ctx.camera.center_on_node(source, &ctx.positions);
+        }
+    }
+}
+
+impl Panel for MatrixPanel {
+    fn id(&self) -> &'static str {
+        "Matrix"
+    }
+
+    fn draw(&mut self, ui: &Ui, ctx: &mut EngineContext) {
+        ctx.matrix.show();
+        let mut order = ctx.matrix.order();
+        ui.set_next_item_width(140.0);
+        if let Some(_combo) = ui.begin_combo(tr("Order"), tr(order.label())) {
+            for option in MatrixOrder::ALL {
+                if ui
+                    .selectable_config(tr(option.label()))
+                    .selected(option == order)
+                    .build()
+                {
+                    order = option;
+                }
+            }
+        }
+        ctx.matrix.set_order(order);
+        ui.same_line();
+        let fit = ui.button(tr("Fit"));
+        if let Some(error) = ctx.matrix.error() {
+            ui.text_colored([1.0, 0.4, 0.4, 1.0], error);
+            return;
+        }
+        let len = ctx.matrix.matrix().len();
+        if len == 0 {
+            ui.text_disabled(tr("Nothing to show"));
+            return;
+        }
+
+        let available = ui.content_region_avail();
+        let size = [available[0].max(MIN_SIDE), available[1].max(MIN_SIDE)];
+        let mut view = match ctx.matrix.view {
+            Some(view) if !fit => MatrixView { size, ..view },
+            _ => MatrixView::fit(len, size),
+        };
+        let origin = ui.cursor_screen_pos();
+        ui.invisible_button("##matrix", size);
+        let mouse = ui.io().mouse_pos;
+        let local = [mouse[0] - origin[0], mouse[1] - origin[1]];
+        let hovered = ui.is_item_hovered();
+        if hovered && ui.io().mouse_wheel != 0.0 {
+            view.zoom_at(local, ZOOM_STEP.powf(ui.io().mouse_wheel), len);
+        }
+        if ui.is_item_active() {
+            let delta = ui.io().mouse_delta;
+            if ui.is_mouse_dragging(MouseButton::Left) {
+                self.dragged = true;
+                view.offset[0] -= delta[0] / view.zoom;
+                view.offset[1] -= delta[1] / view.zoom;
+            }
+        }
+        let clicked = ui.is_item_deactivated() && !std::mem::take(&mut self.dragged);
+        ctx.matrix.view = Some(view);
+
+        let colors = ctx.style.sheet().theme.colors();
+        let draw_list = ui.get_window_draw_list();
+        let end = [origin[0] + size[0], origin[1] + size[1]];
+        let to_screen = |[column, row]: [f32; 2]| {
+            [
+                origin[0] + (column - view.offset[0]) * view.zoom,
+                origin[1] + (row - view.offset[1]) * view.zoom,
+            ]
+        };
+        let cell = [
+            (view.offset[0] + local[0] / view.zoom).floor(),
+            (view.offset[1] + local[1] / view.zoom).floor(),
+        ];
+        let hovered_cell = (hovered && cell[0] >= 0.0 && cell[1] >= 0.0)
+            .then(|| [cell[0] as usize, cell[1] as usize])
+            .filter(|[column, row]| *column < len && *row < len);
+        draw_list.with_clip_rect_intersect(origin, end, || {
+            let full = to_screen([len as f32; 2]);
+            draw_list
+                .add_rect(to_screen([0.0; 2]), full, colors.background)
+                .filled(true)
+                .build();
+            for (texture, [column, row], side) in ctx.matrix.textures(&view) {
+                let (column, row) = (column as f32, row as f32);
+                draw_list
+                    .add_image(
+                        texture,
+                        to_screen([column, row]),
+                        to_screen([column + side as f32, row + side as f32]),
+                    )
+                    .build();
+            }
+            draw_list
+                .add_rect(to_screen([0.0; 2]), full, colors.grid)
+                .build();
+            if let Some([column, row]) = hovered_cell {
+                let (column, row) = (column as f32, row as f32);
+                // the row and column of the cell, and the cell itself
+                draw_list
+                    .add_rect(
+                        to_screen([0.0, row]),
+                        to_screen([len as f32, row + 1.0]),
+                        colors.grid,
+                    )
+                    .filled(true)
+                    .build();
+                draw_list
+                    .add_rect(
+                        to_screen([column, 0.0]),
+                        to_screen([column + 1.0, len as f32]),
+                        colors.grid,
+                    )
+                    .filled(true)
+                    .build();
+                draw_list
+                    .add_rect(
+                        to_screen([column, row]),
+                        to_screen([column + 1.0, row + 1.0]),
+                        colors.selected,
+                    )
+                    .build();
+            }
+        });
+        if let Some([column, row]) = hovered_cell {
+            Self::cell_ui(ui, ctx, row, column, clicked);
+        }
+    }
+}
@@ -0,0 +1,351 @@
+//! # Memory Accounting
+//!
+//! Estimates how much memory the browser holds, by [`MemoryCategory`]:
+//! - the graph model: the slots and adjacency lists of every open document's
+//!   graph, and the positions of its nodes
+//! - the attribute store: the attributes of the graphs, their nodes and edges
+//! - the spatial index of every document
+//! - GPU buffers and textures, counted as they are allocated, see
+//!   [`GpuAllocation`]
+//!
+//! The estimates count what the data structures hold, without the allocator's
+//! overhead, so the process's resident size is measured too where the platform
+//! reports it.
+//!
+//! The [`MemoryMonitor`] measures them every [`MEASURE_INTERVAL`], from
+//! [`EngineContext::refresh()`], and warns once the resident size (or the
+//! estimate, where it is unknown) goes over the soft
+//! [budget](crate::subsystems::config::Config::memory_budget). Opening a file
+//! warns ahead if it is unlikely to fit. The warnings suggest a graph store,
+//! which keeps large graphs on disk, before the system runs out of memory.
+//!
+//! ```ignore
+//! let usage = MemoryUsage::measure(&ctx);
+//! println!("attributes: {}", format_bytes(usage.get(MemoryCategory::Attributes)));
+//! ```
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::context::EngineContext;
+use crate::i18n::tr;
+use crate::imgui::{ProgressBar, StyleColor, Ui};
+use crate::renderer::texture;
+use crate::subsystems::panels::Panel;
+
+/// How often the [`MemoryMonitor`] measures.
+pub const MEASURE_INTERVAL: Duration = Duration::from_secs(2);
+/// How many times its size a file is assumed to take once opened.
+pub const LOAD_EXPANSION: usize = 4;
+/// Share of the budget usage must fall below before warning again.
+const REARM_RATIO: f64 = 0.9;
+
+/// Bytes of GPU memory held by the live [`GpuAllocation`]s.
+static GPU_BUFFERS: AtomicUsize = AtomicUsize::new(0);
+
+/// What memory is held for, see the [module documentation](self).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum MemoryCategory {
+    GraphModel,
+    Attributes,
+    SpatialIndex,
+    GpuBuffers,
+    Textures,
+}
+
+/// Bytes held by each [`MemoryCategory`], as measured at one point.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryUsage {
+    bytes: [usize; MemoryCategory::ALL.len()],
+    /// Resident size of the process, where the platform reports it.
+    pub resident: Option<usize>,
+}
+
+/// Measures the [`MemoryUsage`] regularly, and warns when it goes over budget.
+#[derive(Debug, Default)]
+pub struct MemoryMonitor {
+    usage: Option<MemoryUsage>,
+    measured: Option<Instant>,
+    /// Graph model and attribute sizes of each document's graph, by document
+    /// id, with the generation of the graph they were measured at, as walking
+    /// all attributes is slow for large graphs.
+    graphs: HashMap<u64, (u64, usize, usize)>,
+    /// Whether usage went over budget since it last fell back below
+    /// [`REARM_RATIO`] of it.
+    warned: bool,
+}
+
+/// The size of a GPU buffer, counted towards [`MemoryCategory::GpuBuffers`]
+/// until dropped.
+#[derive(Debug, Default)]
+pub struct GpuAllocation(usize);
+
+/// Shows the [`MemoryUsage`] measured by [`EngineContext::memory`].
+pub struct MemoryPanel;
+
+impl MemoryCategory {
+    pub const ALL: [MemoryCategory; 5] = [
+        Self::GraphModel,
+        Self::Attributes,
+        Self::SpatialIndex,
+        Self::GpuBuffers,
+        Self::Textures,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::GraphModel => "Graph Model",
+            Self::Attributes => "Attribute Store",
+            Self::SpatialIndex => "Spatial Index",
+            Self::GpuBuffers => "GPU Buffers",
+            Self::Textures => "Textures",
+        }
+    }
+
+    fn index(self) -> usize {
+        Self::ALL.iter().position(|c| *c == self).unwrap()
+    }
+}
+
+impl MemoryUsage {
+    /// Measure the memory `ctx` holds, walking every document's graph.
+    pub fn measure(ctx: &EngineContext) -> Self {
+        Self::measure_with(ctx, &mut HashMap::new())
+    }
+
+    /// Like [`Self::measure()`], reusing the sizes of graphs in `graphs`
+    /// which did not change since, and recording the others there.
+    fn measure_with(ctx: &EngineContext, graphs: &mut HashMap<u64, (u64, usize, usize)>) -> Self {
+        let mut usage = Self {
+            resident: resident_size(),
+            ..Self::default()
+        };
+        let documents = ctx.documents.documents();
+        graphs.retain(|id, _| documents.iter().any(|d| d.id() == *id));
+        for (index, document) in documents.iter().enumerate() {
+            let (graph, positions, spatial) = match ctx.documents.stash(index) {
+                Some(stash) => (&stash.graph, &stash.positions, &stash.spatial),
+                None => (&ctx.graph, &ctx.positions, &ctx.spatial),
+            };
+            let generation = graph.generation();
+            let (structure, attributes) = match graphs.get(&document.id()) {
+                Some(&(measured, structure, attributes)) if measured == generation => {
+                    (structure, attributes)
+                }
+                _ => {
+                    let sizes = (graph.structure_size(), graph.attributes_size());
+                    graphs.insert(document.id(), (generation, sizes.0, sizes.1));
+                    sizes
+                }
+            };
+            usage.bytes[MemoryCategory::GraphModel.index()] += structure + positions.heap_size();
+            usage.bytes[MemoryCategory::Attributes.index()] += attributes;
+            usage.bytes[MemoryCategory::SpatialIndex.index()] += spatial.heap_size();
+        }
+        usage.bytes[MemoryCategory::GpuBuffers.index()] = GPU_BUFFERS.load(Ordering::Relaxed);
+        usage.bytes[MemoryCategory::Textures.index()] = texture::allocated_bytes();
+        usage
+    }
+
+    /// Bytes held for `category`.
+    pub fn get(&self, category: MemoryCategory) -> usize {
+        self.bytes[category.index()]
+    }
+
+    /// Bytes held for all categories.
+    pub fn total(&self) -> usize {
+        self.bytes.iter().sum()
+    }
+
+    /// The size compared to the budget: the resident size, or the total where
+    /// it is unknown.
+    pub fn used(&self) -> usize {
+        self.resident.unwrap_or_else(|| self.total())
+    }
+}
+
+impl MemoryMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The usage as of the last measure, if any.
+    pub fn usage(&self) -> Option<&MemoryUsage> {
+        self.usage.as_ref()
+    }
+
+    /// Measure on the next [`step()`], rather than once [`MEASURE_INTERVAL`]
+    /// passed.
+    pub fn invalidate(&mut self) {
+        self.measured = None;
+    }
+}
+
+impl GpuAllocation {
+    /// Record that the buffer now holds `bytes`, eg. after uploading to it.
+    pub fn set(&mut self, bytes: usize) {
+        GPU_BUFFERS.fetch_add(bytes, Ordering::Relaxed);
+        GPU_BUFFERS.fetch_sub(self.0, Ordering::Relaxed);
+        self.0 = bytes;
+    }
+}
+
+/// Measure the memory of `ctx` if [`MEASURE_INTERVAL`] passed since the last
+/// time, warning if it went over budget.
+pub(crate) fn step(ctx: &mut EngineContext) {
+    let monitor = &mut ctx.memory;
+    if monitor
+        .measured
+        .is_some_and(|at| at.elapsed() < MEASURE_INTERVAL)
+    {
+        return;
+    }
+    let mut graphs = std::mem::take(&mut monitor.graphs);
+    let usage = MemoryUsage::measure_with(ctx, &mut graphs);
+    let used = usage.used();
+    let monitor = &mut ctx.memory;
+    monitor.graphs = graphs;
+    monitor.usage = Some(usage);
+    monitor.measured = Some(Instant::now());
+
+    let Some(budget) = ctx.config.get().memory_budget() else {
+        return;
+    };
+    if used > budget && !monitor.warned {
+        monitor.warned = true;
+        ctx.log.warn(format!(
+            "Memory use of {} is over the budget of {}. {}",
+            format_bytes(used),
+            format_bytes(budget),
+            advice()
+        ));
+    } else if (used as f64) < budget as f64 * REARM_RATIO {
+        monitor.warned = false;
+    }
+}
+
+/// Warn if opening the file at `path` likely takes the memory use over budget,
+/// assuming it takes [`LOAD_EXPANSION`] times its size.
+pub(crate) fn check_load(ctx: &mut EngineContext, path: &Path) {
+    let Some(budget) = ctx.config.get().memory_budget() else {
+        return;
+    };
+    let Ok(size) = std::fs::metadata(path).map(|m| m.len() as usize) else {
+        return;
+    };
+    let used = ctx.memory.usage().map_or(0, MemoryUsage::used);
+    let expected = used + size.saturating_mul(LOAD_EXPANSION);
+    if expected > budget {
+        ctx.log.warn(format!(
+            "Opening '{}' may take memory use to {}, over the budget of {}. {}",
+            path.display(),
+            format_bytes(expected),
+            format_bytes(budget),
+            advice()
+        ));
+    }
+}
+
+/// What to do about memory use over budget.
+fn advice() -> &'static str {
+    match cfg!(feature = "sqlite") {
+        true => {
+            "Close other documents, or keep large graphs on disk: write them to a graph \
+             store from the Graph Store panel, and open that instead."
+        }
+        false => "Close other documents, or open smaller graphs.",
+    }
+}
+
+/// `bytes` in the largest binary unit keeping them at least one, eg. `1.5 GiB`.
+pub fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    match unit {
+        0 => format!("{bytes} B"),
+        _ => format!("{value:.1} {}", UNITS[unit]),
+    }
+}
+
+/// Resident size of the process, from `/proc/self/statm`.
+#[cfg(target_os = "linux")]
+fn resident_size() -> Option<usize> {
+    let statm = std::fs::read_to_string("/proc/self/statm").ok()?;
+    let pages: usize = statm.split_whitespace().nth(1)?.parse().ok()?;
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    Some(pages * usize::try_from(page_size).ok()?)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn resident_size() -> Option<usize> {
+    None
+}
+
+impl Panel for MemoryPanel {
+    fn id(&self) -> &'static str {
+        "Memory"
+    }
+
+    fn draw(&mut self, ui: &Ui, ctx: &mut EngineContext) {
+        let budget = ctx.config.get().memory_budget();
+        let Some(usage) = ctx.memory.usage() else {
+            ui.text_disabled(tr("Not measured yet"));
+            return;
+        };
+        for category in MemoryCategory::ALL {
+            ui.text(tr(category.label()));
+            ui.same_line_with_pos(160.0);
+            ui.text(format_bytes(usage.get(category)));
+        }
+        ui.separator();
+        ui.text(tr("Estimated"));
+        ui.same_line_with_pos(160.0);
+        ui.text(format_bytes(usage.total()));
+        if let Some(resident) = usage.resident {
+            ui.text(tr("Resident"));
+            ui.same_line_with_pos(160.0);
+            ui.text(format_bytes(resident));
+            if ui.is_item_hovered() {
+                ui.tooltip_text(tr(
+                    "Memory the process holds, including what the estimates leave out",
+                ));
+            }
+        }
+
+        ui.separator();
+        let used = usage.used();
+        match budget {
+            Some(budget) => {
+                let fraction = used as f32 / budget as f32;
+                let color = match used > budget {
+                    true => [1.0, 0.4, 0.4, 1.0],
+                    false => ui.style_color(StyleColor::PlotHistogram),
+                };
+                let _color = ui.push_style_color(StyleColor::PlotHistogram, color);
+                ProgressBar::new(fraction.min(1.0))
+                    .overlay_text(format!("{} / {}", format_bytes(used), format_bytes(budget)))
+                    .build(ui);
+                if used > budget {
+                    ui.text_wrapped(advice());
+                }
+            }
+            None => ui.text_disabled(tr("No memory budget, see Preferences")),
+        }
+        if ui.button(tr("Measure Now")) {
+            ctx.memory.invalidate();
+        }
+    }
+}
+
+impl Drop for GpuAllocation {
+    fn drop(&mut self) {
+        GPU_BUFFERS.fetch_sub(self.0, Ordering::Relaxed);
+    }
+}
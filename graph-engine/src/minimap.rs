@@ -0,0 +1,231 @@
+//! # Minimap
+//!
+//! A [`Minimap`] renders the whole visible graph at low detail into an
+//! offscreen [`RenderTarget`], which the [`MinimapPanel`] shows with the part
+//! of the world in view outlined. Pressing or dragging in it moves the camera
+//! there, keeping the zoom.
+//!
+//! - Rendering needs the window's GL context, so the app calls [`render()`]
+//!   once per frame, eg. from [`GraphApp::update()`](crate::subsystems::app::GraphApp::update).
+//! - The minimap is only rendered while its panel is shown, when the graph or
+//!   the positions changed, at most every [`REDRAW_INTERVAL`], and otherwise
+//!   every [`STALE_AFTER`], eg. to pick style edits up.
+//!
+//! ```ignore
+//! // in `GraphApp::update()`, after `EngineContext::refresh()`
+//! minimap::render(&mut self.ctx, ctx.window());
+//! ```
+
+use std::time::{Duration, Instant};
+
+use common::renderer::target::RenderTarget;
+
+use crate::context::EngineContext;
+use crate::i18n::tr;
+use crate::imgui::{Image, TextureId, Ui};
+use crate::spatial::Rect;
+use crate::subsystems::panels::Panel;
+use crate::subsystems::scene::Scene;
+use crate::subsystems::window::GraphWindow;
+
+/// Width and height of the offscreen target, in pixels.
+const SIZE: u32 = 256;
+/// Margin around the graph, as a fraction of its size.
+const MARGIN: f32 = 0.05;
+/// Smallest side of the minimap in its panel, in points.
+const MIN_SIDE: f32 = 64.0;
+
+/// Shortest time between two renders, while the graph or positions change.
+pub const REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+/// Longest time between two renders, while the panel is shown.
+pub const STALE_AFTER: Duration = Duration::from_secs(1);
+
+/// The whole graph rendered offscreen, see the [module documentation](self).
+#[derive(Default)]
+pub struct Minimap {
+    /// Created by the first render.
+    target: Option<(RenderTarget, Scene)>,
+    /// The square of the world shown, as of the last render, `None` if the
+    /// graph had no positions.
+    bounds: Option<Rect>,
+    /// Generations of the visible graph and positions last rendered, and when.
+    rendered: Option<((u64, u64), Instant)>,
+    /// Set by the panel each frame it is drawn, read by the next render.
+    shown: bool,
+    /// Why the target could not be created, if it could not.
+    error: Option<String>,
+}
+
+/// Shows the [`Minimap`], and moves the camera where it is pressed.
+pub struct MinimapPanel;
+
+impl Minimap {
+    /// Create a minimap, rendered by the first [`render()`] while shown.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The imgui texture holding the minimap, once rendered. Its rows are
+    /// bottom up, as rendered by GL.
+    pub fn texture(&self) -> Option<TextureId> {
+        self.rendered?;
+        self.target.as_ref().map(|(target, _)| target.id())
+    }
+
+    /// The square of the world shown, as of the last [`render()`].
+    pub fn bounds(&self) -> Option<Rect> {
+        self.bounds
+    }
+
+    /// Why the minimap could not be rendered, if it could not.
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Ask for the minimap to be kept up to date by the next [`render()`].
+    pub fn show(&mut self) {
+        self.shown = true;
+    }
+}
+
+/// Render the visible graph of `ctx` into its minimap, through the GL context
+/// of `window`, if it was shown since the last call and is out of date. See the
+/// [module documentation](self).
+pub fn render(ctx: &mut EngineContext, window: &GraphWindow) {
+    let minimap = &mut ctx.minimap;
+    if !std::mem::take(&mut minimap.shown) || minimap.error.is_some() {
+        return;
+    }
+    let graph = ctx.filters.graph(&ctx.graph);
+    let key = (graph.generation(), ctx.positions.generation());
+    if let Some((rendered, at)) = minimap.rendered
+        && (at.elapsed() < REDRAW_INTERVAL || (rendered == key && at.elapsed() < STALE_AFTER))
+    {
+        return;
+    }
+    if minimap.target.is_none() {
+        match create(window) {
+            Ok(target) => minimap.target = Some(target),
+            Err(e) => {
+                ctx.log.error(format!("Failed to create the minimap: {e}"));
+                minimap.error = Some(e);
+                return;
+            }
+        }
+    }
+    let Some((target, scene)) = &mut minimap.target else {
+        return;
+    };
+    minimap.rendered = Some((key, Instant::now()));
+
+    let side = SIZE as f32;
+    scene.set_viewport([side, side]);
+    minimap.bounds = ctx.positions.bounds(graph).map(|(min, max)| {
+        let extent = (max[0] - min[0]).max(max[1] - min[1]) * (1.0 + 2.0 * MARGIN);
+        let camera = scene.camera_mut();
+        camera.set_center([(min[0] + max[0]) * 0.5, (min[1] + max[1]) * 0.5]);
+        camera.set_zoom(match extent > 0.0 {
+            true => side / extent,
+            false => 1.0,
+        });
+        camera.visible_rect()
+    });
+    if minimap.bounds.is_some() {
+        ctx.spatial.sync(graph, &ctx.positions);
+        let (camera, style) = (*scene.camera(), &ctx.style);
+        scene.graph().add_graph(
+            graph,
+            &ctx.positions,
+            &ctx.spatial,
+            &ctx.routes,
+            &camera,
+            |node| style.node_style(graph, node),
+            |edge| style.edge_style(graph, edge),
+        );
+    }
+    let bound = target.bind();
+    bound.clear(ctx.style.sheet().theme.colors().background);
+    scene.render();
+}
+
+/// The offscreen target and the scene drawing into it.
+fn create(window: &GraphWindow) -> Result<(RenderTarget, Scene), String> {
+    let target = window
+        .create_render_target(SIZE, SIZE)
+        .map_err(|e| e.to_string())?;
+    let gl = window
+        .get_ui()
+        .and_then(|mut ui| ui.renderer().gl())
+        .ok_or("The ui renderer cannot draw a scene")?;
+    let scene = Scene::new(gl).map_err(|e| e.to_string())?;
+    Ok((target, scene))
+}
+
+impl Panel for MinimapPanel {
+    fn id(&self) -> &'static str {
+        "Minimap"
+    }
+
+    fn draw(&mut self, ui: &Ui, ctx: &mut EngineContext) {
+        ctx.minimap.show();
+        if let Some(error) = ctx.minimap.error() {
+            ui.text_colored([1.0, 0.4, 0.4, 1.0], error);
+            return;
+        }
+        let Some(texture) = ctx.minimap.texture() else {
+            return;
+        };
+        let Some((min, max)) = ctx.minimap.bounds() else {
+            ui.text_disabled(tr("Nothing to show"));
+            return;
+        };
+
+        let available = ui.content_region_avail();
+        let side = available[0].min(available[1]).max(MIN_SIDE);
+        let origin = ui.cursor_screen_pos();
+        // GL renders rows bottom up
+        Image::new(texture, [side, side])
+            .uv0([0.0, 1.0])
+            .uv1([1.0, 0.0])
+            .build(ui);
+        ui.set_cursor_screen_pos(origin);
+        ui.invisible_button("##minimap", [side, side]);
+
+        let scale = [side / (max[0] - min[0]), side / (max[1] - min[1])];
+        if ui.is_item_active() {
+            let mouse = ui.io().mouse_pos;
+            let point = [
+                min[0] + (mouse[0] - origin[0]) / scale[0],
+                min[1] + (mouse[1] - origin[1]) / scale[1],
+            ];
+            let zoom = ctx.camera.view().map_or(1.0, |(_, zoom)| zoom);
+            ctx.camera.set_view(point, zoom);
+        }
+        if let Some((view_min, view_max)) = ctx.camera.visible_rect() {
+            let to_panel = |p: [f32; 2]| {
+                [
+                    origin[0] + (p[0] - min[0]) * scale[0],
+                    origin[1] + (p[1] - min[1]) * scale[1],
+                ]
+            };
+            let (view_min, view_max) = (to_panel(view_min), to_panel(view_max));
+            // the part of the world in view, filled like the grid and outlined
+            // like the selection
+            let colors = ctx.style.sheet().theme.colors();
+            let draw_list = ui.get_window_draw_list();
+            draw_list.with_clip_rect_intersect(
+                origin,
+                [origin[0] + side, origin[1] + side],
+                || {
+                    draw_list
+                        .add_rect(view_min, view_max, colors.grid)
+                        .filled(true)
+                        .build();
+                    draw_list
+                        .add_rect(view_min, view_max, colors.selected)
+                        .build();
+                },
+            );
+        }
+    }
+}
@@ -0,0 +1,424 @@
+//! # Command Palette
+//!
+//! A list of everything the application can do, opened from the keyboard with
+//! [`Action::CommandPalette`] (`Ctrl+P` by default). Typing narrows it down by
+//! [`fuzzy_score()`], Up and Down move through the matches, Enter runs the
+//! current one and Escape closes the palette. With nothing typed, the commands
+//! run last come first.
+//!
+//! Commands are registered in [`EngineContext::palette`] by the application,
+//! and by plugins or scripts alike, as one of:
+//! - a closure run on the [`EngineContext`], see [`PaletteCommand::new()`]
+//! - an input [`Action`], see [`PaletteCommand::action()`], handed back to
+//!   the application by [`draw()`] as if its binding was pressed
+//! - a command of the application, see [`PaletteCommand::app()`], handed back
+//!   by its id
+//!
+//! Commands can be bound to keys in the keymap by their id, as
+//! [`InputMap::bind_command()`](crate::subsystems::input::InputMap::bind_command)
+//! does, and run with [`run()`] once pressed.
+//!
+//! ```ignore
+//! ctx.palette.register(PaletteCommand::new("layout.grid", "Layout: Grid", |ctx| {
+//!     ctx.apply_layout(2);
+//! }));
+//! match palette::draw(ui, &mut ctx) {
+//!     Some(Picked::Action(action)) => run_action(action),
+//!     Some(Picked::Command(id)) => run_command(&id),
+//!     None => {}
+//! }
+//! ```
+
+use crate::context::EngineContext;
+use crate::i18n::tr;
+use crate::imgui::{Condition, Key, Ui, WindowFlags, WindowFocusedFlags};
+use crate::subsystems::input::{Action, InputMapper};
+
+/// Width of the palette's window.
+const WIDTH: f32 = 480.0;
+/// Distance from the top of the main window to the palette.
+const TOP: f32 = 60.0;
+/// Matches listed at once, more are scrolled to.
+const MAX_ROWS: usize = 12;
+/// Commands remembered as run last.
+const MAX_RECENT: usize = 8;
+
+/// Score of a character of the pattern matched in the text.
+const MATCH_SCORE: i32 = 1;
+/// Bonus for a character matched at the start of a word.
+const WORD_START_BONUS: i32 = 8;
+/// Bonus for a character matched right after the previous one.
+const CONSECUTIVE_BONUS: i32 = 5;
+/// Most a gap between matched characters costs.
+const MAX_GAP_PENALTY: i32 = 3;
+
+/// Closure run by a [`PaletteCommand`].
+type RunFn = Box<dyn FnMut(&mut EngineContext)>;
+
+/// Something the user can do from the [`CommandPalette`].
+pub struct PaletteCommand {
+    /// Stable identifier, unique within a palette.
+    pub id: String,
+    /// What the palette lists and matches, eg. `Layout: Circular`, in English:
+    /// it is [translated](crate::i18n::tr) when shown.
+    pub label: String,
+    /// Key binding shown next to the label, if any. Commands of an [`Action`]
+    /// show its current binding instead, and others without one the binding
+    /// of their id in the keymap.
+    pub shortcut: Option<String>,
+    target: Target,
+}
+
+/// What running a [`PaletteCommand`] does.
+enum Target {
+    /// Run the closure, which is taken out while it runs.
+    Run(Option<RunFn>),
+    Action(Action),
+    App,
+}
+
+/// A command picked in the palette for the application to run, see
+/// [`draw()`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Picked {
+    /// Do what the binding of the action does.
+    Action(Action),
+    /// Run the application's command with this id.
+    Command(String),
+}
+
+/// Searchable commands, see the [module documentation](self).
+pub struct CommandPalette {
+    /// Registered commands, in listing order.
+    commands: Vec<PaletteCommand>,
+    open: bool,
+    /// Set when opened, to focus the query on the next draw.
+    focus: bool,
+    query: String,
+    /// Position of the current command among the matches.
+    cursor: usize,
+    /// Scroll the current command into view on the next draw.
+    scroll_to_cursor: bool,
+    /// Ids of the commands run last, the most recent first.
+    recent: Vec<String>,
+}
+
+impl PaletteCommand {
+    /// A command running `run` on the context.
+    pub fn new(
+        id: impl Into<String>,
+        label: impl Into<String>,
+        run: impl FnMut(&mut EngineContext) + 'static,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            label: label.into(),
+            shortcut: None,
+            target: Target::Run(Some(Box::new(run))),
+        }
+    }
+
+    /// A command doing what the binding of `action` does, handed back to the
+    /// application as [`Picked::Action`].
+    pub fn action(action: Action) -> Self {
+        Self {
+            id: format!("action.{}", action.label()),
+            label: action.label().into(),
+            shortcut: None,
+            target: Target::Action(action),
+        }
+    }
+
+    /// A command run by the application, handed back as [`Picked::Command`].
+    pub fn app(id: impl Into<String>, label: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            label: label.into(),
+            shortcut: None,
+            target: Target::App,
+        }
+    }
+
+    /// Show `shortcut` next to the command.
+    pub fn shortcut(mut self, shortcut: impl Into<String>) -> Self {
+        self.shortcut = Some(shortcut.into());
+        self
+    }
+
+    /// The shortcut to show, looking up the binding of actions, and of other
+    /// commands unless given, in `input`.
+    fn shortcut_text(&self, input: &InputMapper) -> Option<String> {
+        let bindings = match self.target {
+            Target::Action(action) => input.map().bindings(action),
+            _ if self.shortcut.is_some() => return self.shortcut.clone(),
+            _ => input.map().command_bindings(&self.id),
+        };
+        bindings.first().map(|binding| binding.to_string())
+    }
+}
+
+impl CommandPalette {
+    /// Create a closed palette without commands.
+    pub fn new() -> Self {
+        Self {
+            commands: Vec::new(),
+            open: false,
+            focus: false,
+            query: String::new(),
+            cursor: 0,
+            scroll_to_cursor: false,
+            recent: Vec::new(),
+        }
+    }
+
+    /// Add `command` to the palette.
+    ///
+    /// Panics if a command with the same id is already registered.
+    pub fn register(&mut self, command: PaletteCommand) {
+        assert!(
+            !self.contains(&command.id),
+            "Tried to register command '{}' twice",
+            command.id
+        );
+        self.commands.push(command);
+    }
+
+    /// Remove the command `id`. Returns `false` if there was none.
+    pub fn unregister(&mut self, id: &str) -> bool {
+        let count = self.commands.len();
+        self.commands.retain(|c| c.id != id);
+        self.commands.len() != count
+    }
+
+    /// Returns `true` if a command `id` is registered.
+    pub fn contains(&self, id: &str) -> bool {
+        self.commands.iter().any(|c| c.id == id)
+    }
+
+    /// Registered commands, in listing order.
+    pub fn commands(&self) -> impl Iterator<Item = &PaletteCommand> {
+        self.commands.iter()
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Open the palette with an empty query.
+    pub fn open(&mut self) {
+        self.open = true;
+        self.focus = true;
+        self.query.clear();
+        self.cursor = 0;
+    }
+
+    pub fn close(&mut self) {
+        self.open = false;
+    }
+
+    /// Open the palette if it is closed, or close it if it is open.
+    pub fn toggle(&mut self) {
+        match self.open {
+            true => self.close(),
+            false => self.open(),
+        }
+    }
+
+    /// Indices of the commands matching the query, best first.
+    fn matches(&self) -> Vec<usize> {
+        let recency = |index: usize| {
+            let id = &self.commands[index].id;
+            self.recent
+                .iter()
+                .position(|r| r == id)
+                .unwrap_or(usize::MAX)
+        };
+        let mut matches: Vec<(i32, usize)> = self
+            .commands
+            .iter()
+            .enumerate()
+            .filter_map(|(i, c)| Some((fuzzy_score(&self.query, &tr(&c.label))?, i)))
+            .collect();
+        // stable, so equal matches stay in listing order
+        matches.sort_by_key(|&(score, i)| (-score, recency(i)));
+        matches.into_iter().map(|(_, i)| i).collect()
+    }
+
+    /// Draw the palette if open. Returns the index of the command picked.
+    fn draw_window(&mut self, ui: &Ui, input: &InputMapper) -> Option<usize> {
+        if !self.open {
+            return None;
+        }
+        let [width, _] = ui.io().display_size;
+        let mut picked = None;
+        ui.window("##palette")
+            .position([width / 2.0, TOP], Condition::Always)
+            .position_pivot([0.5, 0.0])
+            .size([WIDTH, 0.0], Condition::Always)
+            .flags(
+                WindowFlags::NO_DECORATION
+                    | WindowFlags::NO_MOVE
+                    | WindowFlags::NO_SAVED_SETTINGS
+                    | WindowFlags::NO_DOCKING,
+            )
+            .build(|| {
+                let focusing = std::mem::take(&mut self.focus);
+                if focusing {
+                    ui.set_keyboard_focus_here();
+                }
+                let query = self.query.clone();
+                ui.set_next_item_width(-1.0);
+                let entered = ui
+                    .input_text("##command", &mut self.query)
+                    .hint(tr("Type a command"))
+                    .enter_returns_true(true)
+                    .build();
+                if self.query != query {
+                    self.cursor = 0;
+                }
+                let matches = self.matches();
+
+                if !matches.is_empty() {
+                    if ui.is_key_pressed(Key::DownArrow) {
+                        self.cursor = (self.cursor + 1) % matches.len();
+                        self.scroll_to_cursor = true;
+                    }
+                    if ui.is_key_pressed(Key::UpArrow) {
+                        self.cursor = (self.cursor + matches.len() - 1) % matches.len();
+                        self.scroll_to_cursor = true;
+                    }
+                    self.cursor = self.cursor.min(matches.len() - 1);
+                }
+                if entered {
+                    picked = matches.get(self.cursor).copied();
+                }
+                if ui.is_key_pressed(Key::Escape) {
+                    self.close();
+                }
+
+                if matches.is_empty() {
+                    ui.text_disabled(tr("No matching command"));
+                    return;
+                }
+                let rows = matches.len().min(MAX_ROWS) as f32;
+                let height = rows * ui.text_line_height_with_spacing();
+                ui.child_window("##commands").size([0.0, height]).build(|| {
+                    let scroll = std::mem::take(&mut self.scroll_to_cursor);
+                    for (row, &index) in matches.iter().enumerate() {
+                        let command = &self.commands[index];
+                        let _id = ui.push_id_usize(index);
+                        if ui
+                            .selectable_config(tr(&command.label))
+                            .selected(row == self.cursor)
+                            .build()
+                        {
+                            picked = Some(index);
+                        }
+                        if row == self.cursor && scroll {
+                            ui.set_scroll_here_y();
+                        }
+                        if let Some(shortcut) = command.shortcut_text(input) {
+                            let size = ui.calc_text_size(&shortcut)[0];
+                            ui.same_line_with_pos(ui.window_content_region_max()[0] - size);
+                            ui.text_disabled(shortcut);
+                        }
+                    }
+                });
+
+                // clicking elsewhere closes the palette
+                if !focusing
+                    && !ui.is_window_focused_with_flags(WindowFocusedFlags::ROOT_AND_CHILD_WINDOWS)
+                {
+                    self.close();
+                }
+            });
+        if picked.is_some() {
+            self.close();
+        }
+        picked
+    }
+
+    /// Move `id` to the front of the commands run last.
+    fn remember(&mut self, id: &str) {
+        self.recent.retain(|r| r != id);
+        self.recent.insert(0, id.to_owned());
+        self.recent.truncate(MAX_RECENT);
+    }
+}
+
+/// Draw the [palette](EngineContext::palette) of `ctx` if open, running the
+/// command picked by the user. Returns the command if it is one for the
+/// application to run.
+pub fn draw(ui: &Ui, ctx: &mut EngineContext) -> Option<Picked> {
+    let index = ctx.palette.draw_window(ui, &ctx.input)?;
+    run_index(ctx, index)
+}
+
+/// Run the command `id` of the [palette](EngineContext::palette) of `ctx` as if
+/// picked, eg. when its binding is pressed, see
+/// [`InputMapper::drain_commands()`]. Returns the command if it is one for the
+/// application to run, or `None` if there is no such command.
+pub fn run(ctx: &mut EngineContext, id: &str) -> Option<Picked> {
+    let index = ctx.palette.commands.iter().position(|c| c.id == id)?;
+    run_index(ctx, index)
+}
+
+/// Run the command at `index`, as [`run()`] does.
+fn run_index(ctx: &mut EngineContext, index: usize) -> Option<Picked> {
+    let id = ctx.palette.commands[index].id.clone();
+    ctx.palette.remember(&id);
+    let run = match &mut ctx.palette.commands[index].target {
+        Target::Run(run) => run.take(),
+        Target::Action(action) => return Some(Picked::Action(*action)),
+        Target::App => return Some(Picked::Command(id)),
+    };
+    // a command running itself again, eg. through a script, does nothing
+    let mut run = run?;
+    run(ctx);
+    // the command may have been unregistered while it ran
+    if let Some(command) = ctx.palette.commands.iter_mut().find(|c| c.id == id)
+        && let Target::Run(slot) = &mut command.target
+        && slot.is_none()
+    {
+        *slot = Some(run);
+    }
+    None
+}
+
+/// How well `pattern` matches `text`, ignoring case and spaces in `pattern`,
+/// or `None` unless its characters appear in `text` in order. Higher is
+/// better: characters starting words or following the previous match score
+/// more, and gaps between them less.
+pub fn fuzzy_score(pattern: &str, text: &str) -> Option<i32> {
+    let text: Vec<char> = text.chars().collect();
+    let mut score = 0;
+    let mut next = 0;
+    let mut previous = None;
+    for p in pattern.chars().filter(|c| !c.is_whitespace()) {
+        let offset = text[next..]
+            .iter()
+            .position(|&t| t.to_lowercase().eq(p.to_lowercase()))?;
+        let i = next + offset;
+        score += MATCH_SCORE;
+        let word_start = i == 0
+            || !text[i - 1].is_alphanumeric()
+            || (text[i - 1].is_lowercase() && text[i].is_uppercase());
+        if word_start {
+            score += WORD_START_BONUS;
+        }
+        if previous.is_some_and(|p| p + 1 == i) {
+            score += CONSECUTIVE_BONUS;
+        } else if previous.is_some() {
+            score -= (offset as i32).min(MAX_GAP_PENALTY);
+        }
+        previous = Some(i);
+        next = i + 1;
+    }
+    Some(score)
+}
+
+impl std::default::Default for CommandPalette {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,314 @@
+//! # Path Finding
+//!
+//! The "find path" tool highlights the cheapest paths between two nodes
+//! clicked in the scene:
+//! - while [active](PathFinder::set_active), a click on a node
+//!   [picks](PathFinder::pick) it as the source, and the next one as the
+//!   target; a third click starts over from a new source
+//! - the paths are found with [`algo::k_shortest_paths()`] on the
+//!   [visible graph](EngineContext::visible_graph), so hidden elements are
+//!   avoided, weighted by the edge attribute chosen, and found again whenever
+//!   the visible graph changes
+//! - once found, the paths are traversed from the source, one hop every
+//!   [`HOP_DURATION`], with a marker moving along the cheapest one
+//! - the [`PathFinderPanel`] toggles the tool, chooses the weight and number of
+//!   paths, and selects a path found
+//!
+//! ```ignore
+//! ctx.path_finder.set_active(true);
+//! ctx.path_finder.pick(a);
+//! ctx.path_finder.pick(b);
+//! ctx.refresh();
+//! let hops = ctx.path_finder.paths()[0].edges.len();
+//! ```
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::algo::{self, Path};
+use crate::context::EngineContext;
+use crate::graph::{EdgeId, Graph, NodeId};
+use crate::i18n::tr;
+use crate::imgui::Ui;
+use crate::selection::SelectMode;
+use crate::subsystems::panels::Panel;
+
+/// Time the traversal takes to cross an edge.
+pub const HOP_DURATION: Duration = Duration::from_millis(120);
+/// Most paths found at once.
+pub const MAX_PATHS: usize = 10;
+
+/// State of the "find path" tool, see the [module documentation](self).
+///
+/// Call [`Self::update()`] once per frame, after the filters are updated.
+#[derive(Clone, Debug)]
+pub struct PathFinder {
+    /// Number of paths to find, from `1` to [`MAX_PATHS`].
+    pub k: usize,
+    /// Edge attribute weighting paths, `None` counting hops.
+    pub weight: Option<String>,
+    active: bool,
+    source: Option<NodeId>,
+    target: Option<NodeId>,
+    /// Cheapest first.
+    paths: Vec<Path>,
+    /// Index of the cheapest path each node and edge is on, and the step of
+    /// that path it is reached at.
+    nodes: HashMap<NodeId, (usize, usize)>,
+    edges: HashMap<EdgeId, (usize, usize)>,
+    /// Generation of the graph, endpoints, number of paths and weight the
+    /// paths were found with.
+    found: Option<(u64, NodeId, NodeId, usize, Option<String>)>,
+    /// When the traversal of the paths started.
+    traversal: Option<Instant>,
+}
+
+/// Toggles the [`EngineContext::path_finder`] tool and lists the paths found.
+pub struct PathFinderPanel;
+
+impl PathFinder {
+    /// Create an inactive tool finding a single path, counting hops.
+    pub fn new() -> Self {
+        Self {
+            k: 1,
+            weight: None,
+            active: false,
+            source: None,
+            target: None,
+            paths: Vec::new(),
+            nodes: HashMap::new(),
+            edges: HashMap::new(),
+            found: None,
+            traversal: None,
+        }
+    }
+
+    /// Whether clicks on nodes pick the endpoints rather than select them.
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Start or stop picking endpoints. Stopping clears the paths.
+    pub fn set_active(&mut self, active: bool) {
+        self.active = active;
+        if !active {
+            self.clear();
+        }
+    }
+
+    /// Pick `node` as the source, or as the target once a source is picked.
+    pub fn pick(&mut self, node: NodeId) {
+        match (self.source, self.target) {
+            (Some(source), None) if source != node => self.target = Some(node),
+            _ => {
+                self.clear();
+                self.source = Some(node);
+            }
+        }
+    }
+
+    /// Forget the endpoints and the paths.
+    pub fn clear(&mut self) {
+        self.source = None;
+        self.target = None;
+        self.set_paths(Vec::new());
+        self.found = None;
+    }
+
+    pub fn source(&self) -> Option<NodeId> {
+        self.source
+    }
+
+    pub fn target(&self) -> Option<NodeId> {
+        self.target
+    }
+
+    /// The paths from the source to the target, cheapest first, as of the last
+    /// [`Self::update()`].
+    pub fn paths(&self) -> &[Path] {
+        &self.paths
+    }
+
+    /// Find the paths between the endpoints in `graph` if it or the settings
+    /// changed, dropping endpoints no longer in it.
+    pub fn update(&mut self, graph: &Graph) {
+        if self.source.is_some_and(|node| !graph.contains_node(node)) {
+            self.clear();
+        }
+        if self.target.is_some_and(|node| !graph.contains_node(node)) {
+            self.target = None;
+        }
+        let (Some(source), Some(target)) = (self.source, self.target) else {
+            self.set_paths(Vec::new());
+            self.found = None;
+            return;
+        };
+        self.k = self.k.clamp(1, MAX_PATHS);
+        let key = (
+            graph.generation(),
+            source,
+            target,
+            self.k,
+            self.weight.clone(),
+        );
+        if self.found.as_ref() == Some(&key) {
+            return;
+        }
+        self.found = Some(key);
+        let paths = algo::k_shortest_paths(graph, source, target, self.k, self.weight.as_deref());
+        self.set_paths(paths);
+    }
+
+    /// Returns `true` while the traversal is moving along the paths.
+    pub fn is_animating(&self) -> bool {
+        let longest = self.paths.iter().map(|path| path.edges.len()).max();
+        self.traversal
+            .zip(longest)
+            .is_some_and(|(start, hops)| start.elapsed() < HOP_DURATION * hops as u32)
+    }
+
+    /// Index of the cheapest path `node` is on, once the traversal reached it.
+    pub fn node_path(&self, node: NodeId) -> Option<usize> {
+        let &(path, step) = self.nodes.get(&node)?;
+        (self.hops() >= step as f32).then_some(path)
+    }
+
+    /// Index of the cheapest path `edge` is on, once the traversal crossed it.
+    pub fn edge_path(&self, edge: EdgeId) -> Option<usize> {
+        let &(path, step) = self.edges.get(&edge)?;
+        (self.hops() >= (step + 1) as f32).then_some(path)
+    }
+
+    /// The edge of the cheapest path the traversal is crossing, from the node
+    /// it left to the next one, with how far along it is from `0.0` to `1.0`.
+    pub fn front(&self) -> Option<(NodeId, NodeId, f32)> {
+        let path = self.paths.first()?;
+        let hops = self.hops();
+        let step = hops as usize;
+        let (&from, &to) = (path.nodes.get(step)?, path.nodes.get(step + 1)?);
+        Some((from, to, hops.fract()))
+    }
+
+    /// Number of hops the traversal went through.
+    fn hops(&self) -> f32 {
+        self.traversal.map_or(f32::INFINITY, |start| {
+            start.elapsed().as_secs_f32() / HOP_DURATION.as_secs_f32()
+        })
+    }
+
+    /// Replace the paths, indexing their elements and restarting the
+    /// traversal.
+    fn set_paths(&mut self, paths: Vec<Path>) {
+        self.nodes.clear();
+        self.edges.clear();
+        // cheapest last, so they overwrite the rest
+        for (index, path) in paths.iter().enumerate().rev() {
+            for (step, &node) in path.nodes.iter().enumerate() {
+                self.nodes.insert(node, (index, step));
+            }
+            for (step, &edge) in path.edges.iter().enumerate() {
+                self.edges.insert(edge, (index, step));
+            }
+        }
+        self.traversal = (!paths.is_empty()).then(Instant::now);
+        self.paths = paths;
+    }
+}
+
+impl Panel for PathFinderPanel {
+    fn id(&self) -> &'static str {
+        "Find Path"
+    }
+
+    fn draw(&mut self, ui: &Ui, ctx: &mut EngineContext) {
+        let finder = &mut ctx.path_finder;
+        let mut active = finder.is_active();
+        if ui.checkbox(tr("Pick nodes in the scene"), &mut active) {
+            finder.set_active(active);
+        }
+        let preview = finder.weight.as_deref().unwrap_or("(hops)").to_string();
+        if let Some(_combo) = ui.begin_combo(tr("Weight"), preview) {
+            if ui
+                .selectable_config(tr("(hops)"))
+                .selected(finder.weight.is_none())
+                .build()
+            {
+                finder.weight = None;
+            }
+            for name in ctx.style.edge_attributes() {
+                if ui
+                    .selectable_config(name)
+                    .selected(finder.weight.as_ref() == Some(name))
+                    .build()
+                {
+                    finder.weight = Some(name.clone());
+                }
+            }
+        }
+        let mut k = finder.k as i32;
+        if ui.slider(tr("Paths"), 1, MAX_PATHS as i32, &mut k) {
+            finder.k = k as usize;
+        }
+
+        ui.separator();
+        let (source, target) = (finder.source(), finder.target());
+        let graph = ctx.filters.graph(&ctx.graph);
+        let name = |node: Option<NodeId>| match node {
+            Some(node) => ctx
+                .style
+                .node_label(graph, node, true)
+                .unwrap_or_else(|| format!("#{}", node.index())),
+            None => "-".to_string(),
+        };
+        ui.text(
+            tr("From {source} to {target}")
+                .replace("{source}", &name(source))
+                .replace("{target}", &name(target)),
+        );
+        if source.is_some() {
+            ui.same_line();
+            if ui.small_button(tr("Clear")) {
+                finder.clear();
+                return;
+            }
+        }
+        if source.is_none() && finder.is_active() {
+            ui.text_disabled(tr("Click the node to start from"));
+            return;
+        }
+        if target.is_none() {
+            if finder.is_active() {
+                ui.text_disabled(tr("Click the node to go to"));
+            }
+            return;
+        }
+        if finder.paths().is_empty() {
+            ui.text_disabled(tr("No path between the nodes"));
+        }
+        let mut picked = None;
+        for (index, path) in finder.paths().iter().enumerate() {
+            let _id = ui.push_id_usize(index);
+            let label = match finder.weight {
+                Some(_) => tr("{hops} hops, cost {cost}").replace("{cost}", &path.cost.to_string()),
+                None => tr("{hops} hops").into_owned(),
+            }
+            .replace("{hops}", &path.edges.len().to_string());
+            if ui.small_button(tr("Select")) {
+                picked = Some(index);
+            }
+            ui.same_line();
+            ui.text(label);
+        }
+        if let Some(index) = picked {
+            let path = ctx.path_finder.paths()[index].clone();
+            ctx.selection
+                .select(path.nodes, path.edges, SelectMode::Replace);
+        }
+    }
+}
+
+impl std::default::Default for PathFinder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
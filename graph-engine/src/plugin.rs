@@ -0,0 +1,656 @@
+//! # Plugins
+//!
+//! A [`GraphPlugin`] extends the engine without forking it. Once enabled, it
+//! hands a [`PluginRegistrar`] its [panels](Panel), palette
+//! [commands](PaletteCommand), [loaders](GraphLoader) for data sources the
+//! engine does not know, and [layouts](Layout), which are removed again when
+//! it is disabled.
+//!
+//! Plugins are `cdylib` crates depending on `graph-engine`, exporting their
+//! plugin with [`export_plugin!`]. The [`PluginManager`] loads the libraries
+//! found in [`PLUGINS_DIR`] with [`discover()`], refusing those built for
+//! another [`ABI_VERSION`], engine version or compiler, as Rust has no stable
+//! ABI:
+//! - a library has its own copy of the engine's statics, so only imgui's
+//!   context and the table of attribute [`Symbol`](crate::graph::Symbol)s are
+//!   shared with it, and plugins should not touch SDL
+//! - libraries stay loaded until the application exits, as the panels and
+//!   closures they registered point into them
+//!
+//! Which plugins the user disabled is kept in [`DISABLED_FILE`] in the plugins
+//! directory.
+//!
+//! ```ignore
+//! // the plugin, built with `crate-type = ["cdylib"]`
+//! struct Acme;
+//!
+//! impl GraphPlugin for Acme {
+//!     fn name(&self) -> &str {
+//!         "Acme"
+//!     }
+//!
+//!     fn register(&mut self, registrar: &mut PluginRegistrar) {
+//!         registrar.loader(AcmeLoader);
+//!         registrar.panel(AcmePanel::new());
+//!     }
+//! }
+//!
+//! graph_engine::export_plugin!(|| Acme);
+//!
+//! // the application
+//! plugin::discover(&mut ctx, ui_state.dir().join(PLUGINS_DIR));
+//! for change in ctx.plugins.take_panel_changes() {
+//!     match change {
+//!         PanelChange::Register(panel) => panels.register_boxed(panel),
+//!         PanelChange::Unregister(id) => _ = panels.unregister(id),
+//!     }
+//! }
+//! ```
+
+use std::collections::HashSet;
+use std::ffi::{CStr, c_char, c_void};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::context::EngineContext;
+use crate::i18n::tr;
+use crate::imgui::{TableFlags, Ui};
+use crate::io::loader::GraphLoader;
+use crate::layout::Layout;
+use crate::palette::PaletteCommand;
+use crate::subsystems::panels::Panel;
+
+/// Name of the directory plugins are discovered in, in the application's
+/// state directory.
+pub const PLUGINS_DIR: &str = "plugins";
+/// Name of the file listing the disabled plugins, one library file name per
+/// line, in the plugins directory.
+pub const DISABLED_FILE: &str = "disabled.txt";
+/// Version of [`PluginDeclaration`], increased whenever its layout changes.
+pub const ABI_VERSION: u32 = 2;
+/// Version of the engine, which plugins must be built against.
+pub const ENGINE_VERSION: &str = env!("CARGO_PKG_VERSION");
+/// Version of the compiler the engine was built with, which plugins must be
+/// built with too.
+pub const RUSTC_VERSION: &str = env!("GRAPH_ENGINE_RUSTC");
+
+/// [`ENGINE_VERSION`] and [`RUSTC_VERSION`] for a [`PluginDeclaration`].
+#[doc(hidden)]
+pub const ENGINE_VERSION_C: &CStr = c_str(concat!(env!("CARGO_PKG_VERSION"), "\0"));
+#[doc(hidden)]
+pub const RUSTC_VERSION_C: &CStr = c_str(concat!(env!("GRAPH_ENGINE_RUSTC"), "\0"));
+
+/// Name of the [`PluginDeclaration`] exported by [`export_plugin!`].
+const DECLARATION_SYMBOL: &CStr = c"GRAPH_PLUGIN_DECLARATION";
+
+/// An extension of the engine, loaded from a dynamic library, see the
+/// [module documentation](self).
+pub trait GraphPlugin {
+    /// Human readable name, eg. for the Plugin Manager panel.
+    fn name(&self) -> &str;
+
+    /// Version of the plugin. Defaults to none.
+    fn version(&self) -> &str {
+        ""
+    }
+
+    /// What the plugin adds, eg. for the Plugin Manager panel. Defaults to
+    /// nothing.
+    fn description(&self) -> &str {
+        ""
+    }
+
+    /// Called when the plugin is enabled, before [`Self::register()`].
+    /// Defaults to nothing.
+    fn init(&mut self, _ctx: &mut EngineContext) {}
+
+    /// Hand what the plugin adds to the engine to `registrar`, each time it is
+    /// enabled.
+    fn register(&mut self, registrar: &mut PluginRegistrar);
+
+    /// Called when the plugin is disabled, or the application exits, after what
+    /// it registered was removed. Defaults to nothing.
+    fn shutdown(&mut self, _ctx: &mut EngineContext) {}
+}
+
+/// Collects what a [`GraphPlugin`] adds to the engine.
+#[derive(Default)]
+pub struct PluginRegistrar {
+    panels: Vec<Box<dyn Panel>>,
+    commands: Vec<PaletteCommand>,
+    loaders: Vec<Arc<dyn GraphLoader>>,
+    layouts: Vec<Box<dyn Layout>>,
+}
+
+/// Exported by a plugin library as `GRAPH_PLUGIN_DECLARATION`, see
+/// [`export_plugin!`].
+///
+/// The fields up to [`Self::rustc_version`] keep their layout across
+/// [`ABI_VERSION`]s, so mismatched libraries are refused safely.
+#[repr(C)]
+pub struct PluginDeclaration {
+    pub abi_version: u32,
+    /// Nul-terminated [`ENGINE_VERSION`] the library was built against.
+    pub engine_version: *const c_char,
+    /// Nul-terminated [`RUSTC_VERSION`] the library was built with.
+    pub rustc_version: *const c_char,
+    /// Make the library's copy of imgui use the application's context.
+    pub set_imgui_context: unsafe fn(*mut c_void),
+    /// Make the library's copy of the engine intern attribute keys in the
+    /// application's table, see [`share_symbols()`](crate::graph::share_symbols).
+    pub share_symbols: unsafe fn(*mut c_void),
+    pub create: fn() -> Box<dyn GraphPlugin>,
+}
+
+// the pointers are to string literals
+unsafe impl Sync for PluginDeclaration {}
+
+/// Panels added or removed by enabling or disabling plugins, which the
+/// application applies to its [`PanelManager`](crate::subsystems::panels::PanelManager).
+pub enum PanelChange {
+    Register(Box<dyn Panel>),
+    Unregister(&'static str),
+}
+
+/// Errors which can occur while loading a plugin library.
+#[derive(Debug)]
+pub enum PluginError {
+    /// The library could not be opened, or exports no declaration.
+    Load(String),
+    /// The library was built for another [`ABI_VERSION`].
+    Abi(u32),
+    /// The library was built against another [`ENGINE_VERSION`].
+    EngineVersion(String),
+    /// The library was built with another [`RUSTC_VERSION`].
+    Compiler(String),
+}
+
+/// Loads plugin libraries and enables or disables their plugins, see the
+/// [module documentation](self).
+#[derive(Default)]
+pub struct PluginManager {
+    /// The directory plugins were last discovered in.
+    dir: Option<PathBuf>,
+    /// File names of the disabled plugin libraries.
+    disabled: HashSet<String>,
+    plugins: Vec<PluginEntry>,
+    panel_changes: Vec<PanelChange>,
+}
+
+/// A library found in the plugins directory.
+struct PluginEntry {
+    path: PathBuf,
+    plugin: Result<LoadedPlugin, PluginError>,
+}
+
+struct LoadedPlugin {
+    plugin: Box<dyn GraphPlugin>,
+    enabled: bool,
+    /// What the plugin registered while enabled.
+    panels: Vec<&'static str>,
+    commands: Vec<String>,
+    loaders: Vec<Arc<dyn GraphLoader>>,
+    layouts: Vec<&'static str>,
+}
+
+/// Lists the [`EngineContext::plugins`], enabling and disabling them.
+pub struct PluginManagerPanel;
+
+/// Export `$create`, a function or closure returning a [`GraphPlugin`], from a
+/// plugin library, see the [module documentation](crate::plugin).
+#[macro_export]
+macro_rules! export_plugin {
+    ($create:expr) => {
+        #[unsafe(no_mangle)]
+        pub static GRAPH_PLUGIN_DECLARATION: $crate::plugin::PluginDeclaration =
+            $crate::plugin::PluginDeclaration {
+                abi_version: $crate::plugin::ABI_VERSION,
+                engine_version: $crate::plugin::ENGINE_VERSION_C.as_ptr(),
+                rustc_version: $crate::plugin::RUSTC_VERSION_C.as_ptr(),
+                set_imgui_context: {
+                    unsafe fn set_imgui_context(context: *mut ::std::ffi::c_void) {
+                        unsafe {
+                            $crate::renderer::imgui::sys::igSetCurrentContext(context.cast())
+                        };
+                    }
+                    set_imgui_context
+                },
+                share_symbols: $crate::graph::share_symbols,
+                create: {
+                    fn create() -> ::std::boxed::Box<dyn $crate::plugin::GraphPlugin> {
+                        ::std::boxed::Box::new(($create)())
+                    }
+                    create
+                },
+            };
+    };
+}
+
+impl PluginRegistrar {
+    /// Add `panel` to the application's panels.
+    pub fn panel(&mut self, panel: impl Panel + 'static) {
+        self.panels.push(Box::new(panel));
+    }
+
+    /// Add `command` to the [command palette](crate::palette).
+    pub fn command(&mut self, command: PaletteCommand) {
+        self.commands.push(command);
+    }
+
+    /// Add `loader` to the [`EngineContext::loaders`], reading the files it
+    /// accepts.
+    pub fn loader(&mut self, loader: impl GraphLoader + 'static) {
+        self.loaders.push(Arc::new(loader));
+    }
+
+    /// Add `layout` to the [`EngineContext::layouts`].
+    pub fn layout(&mut self, layout: impl Layout + 'static) {
+        self.layouts.push(Box::new(layout));
+    }
+}
+
+impl PluginManager {
+    /// Create a manager without plugins.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The directory plugins were last discovered in, if any.
+    pub fn dir(&self) -> Option<&Path> {
+        self.dir.as_deref()
+    }
+
+    /// Number of libraries found, loaded or not.
+    pub fn len(&self) -> usize {
+        self.plugins.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.plugins.is_empty()
+    }
+
+    /// Returns `true` if the plugin at `index` is loaded and enabled.
+    pub fn is_enabled(&self, index: usize) -> bool {
+        matches!(&self.plugins[index].plugin, Ok(loaded) if loaded.enabled)
+    }
+
+    /// Take the panels to add to or remove from the application's panels since
+    /// the last call, in order.
+    pub fn take_panel_changes(&mut self) -> Vec<PanelChange> {
+        std::mem::take(&mut self.panel_changes)
+    }
+
+    /// Load the library at `path`, unless already loaded, returning its index.
+    fn load(&mut self, path: &Path) -> usize {
+        if let Some(index) = self.plugins.iter().position(|p| p.path == path) {
+            return index;
+        }
+        let plugin = unsafe { load_library(path) }.map(|plugin| LoadedPlugin {
+            plugin,
+            enabled: false,
+            panels: Vec::new(),
+            commands: Vec::new(),
+            loaders: Vec::new(),
+            layouts: Vec::new(),
+        });
+        self.plugins.push(PluginEntry {
+            path: path.to_owned(),
+            plugin,
+        });
+        self.plugins.len() - 1
+    }
+
+    /// Enable or disable the plugin at `index`, if loaded, registering or
+    /// removing what it adds to `ctx`.
+    fn set_enabled(&mut self, ctx: &mut EngineContext, index: usize, enabled: bool) {
+        let entry = &mut self.plugins[index];
+        let Ok(loaded) = &mut entry.plugin else {
+            return;
+        };
+        if loaded.enabled == enabled {
+            return;
+        }
+        loaded.enabled = enabled;
+        if enabled {
+            self.disabled.remove(&file_name(&entry.path));
+            loaded.plugin.init(ctx);
+            let mut registrar = PluginRegistrar::default();
+            loaded.plugin.register(&mut registrar);
+            let name = loaded.plugin.name().to_owned();
+            for command in registrar.commands {
+                if ctx.palette.contains(&command.id) {
+                    ctx.log.warn(format!(
+                        "Plugin '{name}': command '{}' is already registered",
+                        command.id
+                    ));
+                    continue;
+                }
+                loaded.commands.push(command.id.clone());
+                ctx.palette.register(command);
+            }
+            loaded.loaders.extend(registrar.loaders.iter().cloned());
+            ctx.loaders.extend(registrar.loaders);
+            loaded
+                .layouts
+                .extend(registrar.layouts.iter().map(|l| l.name()));
+            ctx.layouts.extend(registrar.layouts);
+            for panel in registrar.panels {
+                loaded.panels.push(panel.id());
+                self.panel_changes.push(PanelChange::Register(panel));
+            }
+            ctx.log.info(format!("Enabled plugin '{name}'"));
+        } else {
+            self.disabled.insert(file_name(&entry.path));
+            Self::unregister(ctx, loaded, &mut self.panel_changes);
+            ctx.log
+                .info(format!("Disabled plugin '{}'", loaded.plugin.name()));
+        }
+    }
+
+    /// Remove what `loaded` registered from `ctx`, and shut it down.
+    fn unregister(
+        ctx: &mut EngineContext,
+        loaded: &mut LoadedPlugin,
+        panel_changes: &mut Vec<PanelChange>,
+    ) {
+        for id in loaded.commands.drain(..) {
+            ctx.palette.unregister(&id);
+        }
+        let loaders = std::mem::take(&mut loaded.loaders);
+        ctx.loaders
+            .retain(|l| !loaders.iter().any(|ours| Arc::ptr_eq(l, ours)));
+        // plugin layouts follow the built-in ones, whose indices stay valid
+        for name in loaded.layouts.drain(..) {
+            if let Some(index) = ctx.layouts.iter().rposition(|l| l.name() == name) {
+                ctx.layouts.remove(index);
+            }
+        }
+        panel_changes.extend(loaded.panels.drain(..).map(PanelChange::Unregister));
+        loaded.plugin.shutdown(ctx);
+    }
+
+    /// Read [`DISABLED_FILE`] from the plugins directory.
+    fn load_disabled(&mut self) {
+        let Some(dir) = &self.dir else {
+            return;
+        };
+        self.disabled = match std::fs::read_to_string(dir.join(DISABLED_FILE)) {
+            Ok(text) => text
+                .lines()
+                .map(str::trim)
+                .filter(|l| !l.is_empty())
+                .map(str::to_owned)
+                .collect(),
+            Err(_) => HashSet::new(),
+        };
+    }
+
+    /// Write [`DISABLED_FILE`] to the plugins directory.
+    fn save_disabled(&self) -> std::io::Result<()> {
+        let Some(dir) = &self.dir else {
+            return Ok(());
+        };
+        let mut names: Vec<&str> = self.disabled.iter().map(String::as_str).collect();
+        names.sort_unstable();
+        std::fs::create_dir_all(dir)?;
+        std::fs::write(dir.join(DISABLED_FILE), names.join("\n"))
+    }
+}
+
+/// Load the plugin libraries in `dir`, creating it if missing, and enable those
+/// not listed in its [`DISABLED_FILE`]. Libraries already loaded are kept.
+pub fn discover(ctx: &mut EngineContext, dir: impl Into<PathBuf>) {
+    let dir = dir.into();
+    if let Err(e) = std::fs::create_dir_all(&dir) {
+        ctx.log.error(format!(
+            "Failed to create the plugins directory '{}': {e}",
+            dir.display()
+        ));
+        return;
+    }
+    let mut paths: Vec<PathBuf> = match std::fs::read_dir(&dir) {
+        Ok(entries) => entries
+            .filter_map(|e| Some(e.ok()?.path()))
+            .filter(|p| {
+                p.extension()
+                    .is_some_and(|e| e == std::env::consts::DLL_EXTENSION)
+            })
+            .collect(),
+        Err(e) => {
+            ctx.log.error(format!(
+                "Failed to list the plugins in '{}': {e}",
+                dir.display()
+            ));
+            return;
+        }
+    };
+    paths.sort();
+
+    let mut plugins = std::mem::take(&mut ctx.plugins);
+    plugins.dir = Some(dir);
+    plugins.load_disabled();
+    for path in paths {
+        let known = plugins.plugins.iter().any(|p| p.path == path);
+        let index = plugins.load(&path);
+        if known {
+            continue;
+        }
+        match &plugins.plugins[index].plugin {
+            Ok(_) if plugins.disabled.contains(&file_name(&path)) => {}
+            Ok(_) => plugins.set_enabled(ctx, index, true),
+            Err(e) => ctx
+                .log
+                .error(format!("Failed to load plugin '{}': {e}", path.display())),
+        }
+    }
+    ctx.plugins = plugins;
+}
+
+/// Enable or disable the plugin at `index` of the [`EngineContext::plugins`],
+/// remembering the choice in [`DISABLED_FILE`].
+///
+/// Panics if `index` is out of bounds.
+pub fn set_enabled(ctx: &mut EngineContext, index: usize, enabled: bool) {
+    let mut plugins = std::mem::take(&mut ctx.plugins);
+    plugins.set_enabled(ctx, index, enabled);
+    if let Err(e) = plugins.save_disabled() {
+        ctx.log
+            .error(format!("Failed to save the disabled plugins: {e}"));
+    }
+    ctx.plugins = plugins;
+}
+
+/// Shut down every enabled plugin, eg. when the application exits. They are
+/// not marked disabled.
+pub fn shutdown(ctx: &mut EngineContext) {
+    let mut plugins = std::mem::take(&mut ctx.plugins);
+    for entry in &mut plugins.plugins {
+        if let Ok(loaded) = &mut entry.plugin
+            && loaded.enabled
+        {
+            loaded.enabled = false;
+            PluginManager::unregister(ctx, loaded, &mut plugins.panel_changes);
+        }
+    }
+    ctx.plugins = plugins;
+}
+
+/// Open the library at `path` and create its plugin, if built for this engine.
+///
+/// # Safety
+/// Opening a library runs its initializers, and its declaration is trusted to
+/// be one exported by [`export_plugin!`].
+unsafe fn load_library(path: &Path) -> Result<Box<dyn GraphPlugin>, PluginError> {
+    let symbol = unsafe { native::open(path, DECLARATION_SYMBOL) }.map_err(PluginError::Load)?;
+    let declaration = unsafe { &*symbol.cast::<PluginDeclaration>() };
+    if declaration.abi_version != ABI_VERSION {
+        return Err(PluginError::Abi(declaration.abi_version));
+    }
+    let engine = unsafe { CStr::from_ptr(declaration.engine_version) }.to_string_lossy();
+    if engine != ENGINE_VERSION {
+        return Err(PluginError::EngineVersion(engine.into_owned()));
+    }
+    let rustc = unsafe { CStr::from_ptr(declaration.rustc_version) }.to_string_lossy();
+    if rustc != RUSTC_VERSION {
+        return Err(PluginError::Compiler(rustc.into_owned()));
+    }
+    unsafe { (declaration.set_imgui_context)(crate::imgui::sys::igGetCurrentContext().cast()) };
+    unsafe { (declaration.share_symbols)(crate::graph::symbol_table()) };
+    Ok((declaration.create)())
+}
+
+/// Name of the library file at `path`, as listed in [`DISABLED_FILE`].
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// `s`, which must end with its only nul, as a C string.
+const fn c_str(s: &'static str) -> &'static CStr {
+    match CStr::from_bytes_with_nul(s.as_bytes()) {
+        Ok(s) => s,
+        Err(_) => panic!("Expected a nul-terminated string"),
+    }
+}
+
+#[cfg(unix)]
+mod native {
+    use std::ffi::{CStr, CString, c_void};
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::Path;
+
+    /// Open the library at `path`, which is never closed, and look up `symbol`
+    /// in it.
+    pub unsafe fn open(path: &Path, symbol: &CStr) -> Result<*const c_void, String> {
+        let path = CString::new(path.as_os_str().as_bytes()).map_err(|e| e.to_string())?;
+        let handle = unsafe { libc::dlopen(path.as_ptr(), libc::RTLD_NOW | libc::RTLD_LOCAL) };
+        if handle.is_null() {
+            return Err(last_error());
+        }
+        let address = unsafe { libc::dlsym(handle, symbol.as_ptr()) };
+        if address.is_null() {
+            return Err(last_error());
+        }
+        Ok(address.cast_const())
+    }
+
+    fn last_error() -> String {
+        let error = unsafe { libc::dlerror() };
+        if error.is_null() {
+            return "Unknown error".into();
+        }
+        unsafe { CStr::from_ptr(error) }
+            .to_string_lossy()
+            .into_owned()
+    }
+}
+
+#[cfg(not(unix))]
+mod native {
+    use std::ffi::{CStr, c_void};
+    use std::path::Path;
+
+    pub unsafe fn open(_path: &Path, _symbol: &CStr) -> Result<*const c_void, String> {
+        Err("Plugins are not supported on this platform".into())
+    }
+}
+
+impl Panel for PluginManagerPanel {
+    fn id(&self) -> &'static str {
+        "Plugins"
+    }
+
+    fn draw(&mut self, ui: &Ui, ctx: &mut EngineContext) {
+        let dir = ctx.plugins.dir().map(Path::to_path_buf);
+        match &dir {
+            Some(dir) => ui.text_wrapped(
+                tr("Directory: {path}").replace("{path}", &dir.display().to_string()),
+            ),
+            None => ui.text_disabled(tr("No plugins directory")),
+        }
+        if let Some(dir) = &dir {
+            if ui.button(tr("Rescan")) {
+                discover(ctx, dir.clone());
+            }
+            if ui.is_item_hovered() {
+                ui.tooltip_text(tr("Load libraries added to the directory"));
+            }
+        }
+        ui.text_disabled(format!(
+            "Engine {ENGINE_VERSION}, ABI {ABI_VERSION}, {RUSTC_VERSION}"
+        ));
+        ui.separator();
+
+        if ctx.plugins.is_empty() {
+            ui.text_disabled(tr("No plugins found"));
+            return;
+        }
+        let mut toggled = None;
+        if let Some(table) = ui.begin_table_with_flags(
+            "##plugins",
+            3,
+            TableFlags::ROW_BG | TableFlags::BORDERS_INNER_H | TableFlags::SIZING_STRETCH_PROP,
+        ) {
+            ui.table_setup_column("##enabled");
+            ui.table_setup_column(tr("Plugin"));
+            ui.table_setup_column(tr("Library"));
+            ui.table_headers_row();
+
+            for (index, entry) in ctx.plugins.plugins.iter().enumerate() {
+                let _id = ui.push_id_usize(index);
+                ui.table_next_row();
+
+                ui.table_next_column();
+                match &entry.plugin {
+                    Ok(loaded) => {
+                        let mut enabled = loaded.enabled;
+                        if ui.checkbox("##enabled", &mut enabled) {
+                            toggled = Some((index, enabled));
+                        }
+                    }
+                    Err(_) => ui.text_disabled("-"),
+                }
+
+                ui.table_next_column();
+                match &entry.plugin {
+                    Ok(loaded) => {
+                        let plugin = &loaded.plugin;
+                        ui.text(format!("{} {}", plugin.name(), plugin.version()));
+                        if !plugin.description().is_empty() {
+                            ui.text_wrapped(plugin.description());
+                        }
+                    }
+                    Err(e) => ui.text_colored([1.0, 0.4, 0.4, 1.0], e.to_string()),
+                }
+
+                ui.table_next_column();
+                ui.text(file_name(&entry.path));
+            }
+            table.end();
+        }
+        if let Some((index, enabled)) = toggled {
+            set_enabled(ctx, index, enabled);
+        }
+    }
+}
+
+impl std::fmt::Display for PluginError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Load(e) => write!(f, "{e}"),
+            Self::Abi(version) => {
+                write!(f, "Built for plugin ABI {version}, expected {ABI_VERSION}")
+            }
+            Self::EngineVersion(version) => write!(
+                f,
+                "Built against engine {version}, expected {ENGINE_VERSION}"
+            ),
+            Self::Compiler(version) => {
+                write!(f, "Built with '{version}', expected '{RUSTC_VERSION}'")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PluginError {}
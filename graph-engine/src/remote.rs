@@ -0,0 +1,733 @@
+//! # Remote Control
+//!
+//! A [`RemoteServer`] answers HTTP requests on a TCP port, so scripts,
+//! notebooks, and other tools can drive a running browser. Bodies and answers
+//! are JSON, errors are answered as `{"error": "<message>"}`:
+//! - `GET /status`: the open documents and the size of the active one's graph,
+//!   visible graph, and selection
+//! - `POST /open` with `{"path": "<file>"}`: open a file in a new document
+//! - `POST /layout` with `{"name": "<layout>"}`: run a layout, named as in the
+//!   Layout menu, ignoring case, spaces, and dashes
+//! - `POST /metrics` with `{"metrics": ["pagerank", ...]}`: compute metrics in
+//!   the background, named by the attribute they are written to, all if none
+//! - `GET /selection`: the selected nodes and edges, with their attributes
+//! - `GET /render?format=png|svg`: the window as drawn, or the view as an SVG
+//!   image
+//!
+//! Connections are read on worker threads, which wait for the answer, up to
+//! [`MAX_CONNECTIONS`] at once: those beyond are closed unanswered. The
+//! server is stepped from [`EngineContext::refresh()`] and answers all but
+//! opening files and rendering, which need the application and are left in
+//! [`RemoteServer::take_app_calls()`]. It is controlled from the
+//! [`RemotePanel`].
+//!
+//! Anyone who can reach the port controls the browser, so the server listens
+//! on the loopback interface by default, and refuses requests web pages could
+//! make:
+//! - the `Host` header must name the address listened on, so that pages cannot
+//!   reach the server through a domain rebound to it
+//! - requests with an `Origin` header, which browsers send from pages, are
+//!   refused
+//! - `POST` bodies must be sent as `Content-Type: application/json`, which
+//!   pages cannot send to another site without asking it first
+//! - requests must carry the [token](RemoteServer::token) of the server, shown
+//!   in the panel, as `Authorization: Bearer <token>`
+//!
+//! ```sh
+//! curl -H "Authorization: Bearer $TOKEN" -H 'Content-Type: application/json' \
+//!     -d '{"name": "circular"}' http://127.0.0.1:9751/layout
+//! curl -H "Authorization: Bearer $TOKEN" -o view.png 'http://127.0.0.1:9751/render?format=png'
+//! ```
+
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde_json::{Value, json};
+
+use std::hash::{BuildHasher, RandomState};
+use std::io::{BufRead, BufReader, ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::Duration;
+
+use crate::algo::metrics::{Metric, MetricsJob};
+use crate::context::EngineContext;
+use crate::graph::Graph;
+use crate::i18n::tr;
+use crate::imgui::Ui;
+use crate::io::json::unflatten;
+use crate::layout::Layout;
+use crate::subsystems::panels::Panel;
+
+/// Address the [`RemotePanel`] offers to listen on by default.
+pub const REMOTE_ADDRESS: &str = "127.0.0.1:9751";
+
+/// Most connections answered at once.
+pub const MAX_CONNECTIONS: usize = 16;
+
+/// Largest request line and headers read, in bytes.
+const MAX_HEAD: usize = 16 << 10;
+/// Largest request body read, in bytes.
+const MAX_BODY: usize = 1 << 20;
+/// How long a connection waits for its answer.
+const REPLY_TIMEOUT: Duration = Duration::from_secs(30);
+/// How long a connection waits for its request to arrive.
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+/// How often the listening thread checks whether the server stopped.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// What a request asks for, see the [module documentation](self).
+#[derive(Clone, Debug, PartialEq)]
+pub enum RemoteCommand {
+    Status,
+    Open { path: PathBuf },
+    Layout { name: String },
+    Metrics { metrics: Vec<Metric> },
+    Selection,
+    Render { format: RenderFormat },
+}
+
+/// The image format of [`RemoteCommand::Render`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum RenderFormat {
+    /// The window's pixels, as drawn.
+    Png,
+    /// The camera's view, as an [exported](crate::subsystems::export) image.
+    Svg,
+}
+
+/// A request waiting for its answer, which the connection writes back.
+pub struct RemoteCall {
+    pub command: RemoteCommand,
+    reply: Sender<RemoteReply>,
+}
+
+/// An HTTP answer.
+#[derive(Clone, Debug)]
+pub struct RemoteReply {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: Vec<u8>,
+}
+
+/// Answers remote requests, see the [module documentation](self).
+pub struct RemoteServer {
+    /// Shared with the connections, which check requests against it.
+    access: Arc<Access>,
+    calls: Receiver<RemoteCall>,
+    /// Calls the application answers, see [`Self::take_app_calls()`].
+    app_calls: Vec<RemoteCall>,
+    /// Shared with the listening thread, which stops once it is set.
+    stopped: Arc<AtomicBool>,
+    answered: usize,
+}
+
+/// What requests must match to be answered, see the
+/// [module documentation](self).
+struct Access {
+    address: SocketAddr,
+    token: String,
+}
+
+/// Starts, stops, and shows the [`EngineContext::remote`] server.
+pub struct RemotePanel {
+    address: String,
+    /// Why the server could not be started, if it could not.
+    error: Option<String>,
+}
+
+/// Body of `POST /open`.
+#[derive(Deserialize)]
+struct OpenBody {
+    path: PathBuf,
+}
+
+/// Body of `POST /layout`.
+#[derive(Deserialize)]
+struct LayoutBody {
+    name: String,
+}
+
+/// Body of `POST /metrics`.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct MetricsBody {
+    metrics: Vec<String>,
+}
+
+impl RenderFormat {
+    /// The format named `name`, as in a `format` query parameter.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "png" => Some(Self::Png),
+            "svg" => Some(Self::Svg),
+            _ => None,
+        }
+    }
+
+    /// The MIME type of images in the format.
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Png => "image/png",
+            Self::Svg => "image/svg+xml",
+        }
+    }
+}
+
+impl RemoteCall {
+    /// Answer the call. The connection may have given up waiting already.
+    pub fn reply(self, reply: RemoteReply) {
+        let _ = self.reply.send(reply);
+    }
+}
+
+impl RemoteReply {
+    /// A `200 OK` answer with `value` as its body.
+    pub fn json(value: Value) -> Self {
+        Self::with_status(200, value)
+    }
+
+    /// An answer with `status` and `value` as its body.
+    pub fn with_status(status: u16, value: Value) -> Self {
+        Self {
+            status,
+            content_type: "application/json",
+            body: value.to_string().into_bytes(),
+        }
+    }
+
+    /// An error answer with `status`, explained by `message`.
+    pub fn error(status: u16, message: impl Into<String>) -> Self {
+        Self::with_status(status, json!({ "error": message.into() }))
+    }
+
+    /// A `202 Accepted` answer to [`RemoteCommand::Open`], once the file at
+    /// `path` started loading.
+    pub fn opening(path: &Path) -> Self {
+        Self::with_status(202, json!({ "opening": path.display().to_string() }))
+    }
+
+    /// A `200 OK` answer with `body` of `content_type`.
+    pub fn bytes(content_type: &'static str, body: Vec<u8>) -> Self {
+        Self {
+            status: 200,
+            content_type,
+            body,
+        }
+    }
+}
+
+impl RemoteServer {
+    /// Listen on `address`.
+    ///
+    /// Errors if the address cannot be bound.
+    pub fn bind(address: impl ToSocketAddrs) -> std::io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+        let access = Arc::new(Access {
+            address: listener.local_addr()?,
+            token: session_token(),
+        });
+        let (sender, calls) = mpsc::channel();
+        let stopped = Arc::new(AtomicBool::new(false));
+        {
+            let (access, stopped) = (access.clone(), stopped.clone());
+            std::thread::spawn(move || accept(&listener, &sender, &stopped, &access));
+        }
+        Ok(Self {
+            access,
+            calls,
+            app_calls: Vec::new(),
+            stopped,
+            answered: 0,
+        })
+    }
+
+    /// The address the server listens on.
+    pub fn address(&self) -> SocketAddr {
+        self.access.address
+    }
+
+    /// The token requests must carry, new each time the server starts.
+    pub fn token(&self) -> &str {
+        &self.access.token
+    }
+
+    /// Number of requests answered so far, or handed to the application.
+    pub fn answered(&self) -> usize {
+        self.answered
+    }
+
+    /// Stop listening. Also done on drop.
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+
+    /// The calls received since the last time which the application answers:
+    /// [`RemoteCommand::Open`] and [`RemoteCommand::Render`].
+    pub fn take_app_calls(&mut self) -> Vec<RemoteCall> {
+        std::mem::take(&mut self.app_calls)
+    }
+
+    /// Returns `true` if calls wait for the application.
+    pub fn has_app_calls(&self) -> bool {
+        !self.app_calls.is_empty()
+    }
+}
+
+/// Answer the calls [`EngineContext::remote`] received, keeping those the
+/// application answers.
+pub(crate) fn step(ctx: &mut EngineContext) {
+    let Some(server) = &mut ctx.remote else {
+        return;
+    };
+    let calls: Vec<RemoteCall> = server.calls.try_iter().collect();
+    server.answered += calls.len();
+    for call in calls {
+        let reply = match &call.command {
+            RemoteCommand::Open { .. } | RemoteCommand::Render { .. } => {
+                if let Some(server) = &mut ctx.remote {
+                    server.app_calls.push(call);
+                }
+                continue;
+            }
+            RemoteCommand::Status => RemoteReply::json(status(ctx)),
+            RemoteCommand::Layout { name } => run_layout(ctx, name),
+            RemoteCommand::Metrics { metrics } => run_metrics(ctx, metrics),
+            RemoteCommand::Selection => RemoteReply::json(selection(ctx)),
+        };
+        call.reply(reply);
+    }
+}
+
+fn status(ctx: &EngineContext) -> Value {
+    let documents: Vec<Value> = ctx
+        .documents
+        .documents()
+        .iter()
+        .enumerate()
+        .map(|(index, document)| {
+            json!({
+                "title": document.title(),
+                "path": document.path(),
+                "active": index == ctx.documents.active(),
+            })
+        })
+        .collect();
+    let visible = ctx.visible_graph();
+    json!({
+        "documents": documents,
+        "nodes": ctx.graph.node_count(),
+        "edges": ctx.graph.edge_count(),
+        "visible_nodes": visible.node_count(),
+        "visible_edges": visible.edge_count(),
+        "selected_nodes": ctx.selection.node_count(),
+        "selected_edges": ctx.selection.edge_count(),
+        "busy": ctx.is_busy(),
+    })
+}
+
+/// Run the layout named `name`, the force-directed one live.
+fn run_layout(ctx: &mut EngineContext, name: &str) -> RemoteReply {
+    if matches(Layout::name(&ctx.force), name) {
+        ctx.force.set_paused(false);
+        ctx.force.reheat();
+        return RemoteReply::json(json!({ "layout": Layout::name(&ctx.force) }));
+    }
+    let Some(index) = ctx.layouts.iter().position(|l| matches(l.name(), name)) else {
+        return RemoteReply::error(404, format!("Unknown layout '{name}'"));
+    };
+    ctx.apply_layout(index);
+    RemoteReply::json(json!({ "layout": ctx.layouts[index].name() }))
+}
+
+/// Start computing `metrics` on the visible graph, unless metrics are being
+/// computed already.
+fn run_metrics(ctx: &mut EngineContext, metrics: &[Metric]) -> RemoteReply {
+    if ctx.metrics.is_some() {
+        return RemoteReply::error(409, "Metrics are being computed already");
+    }
+    let metrics = match metrics.is_empty() {
+        true => Metric::ALL.to_vec(),
+        false => metrics.to_vec(),
+    };
+    ctx.metrics = Some(MetricsJob::spawn(&ctx.jobs, ctx.visible_graph(), &metrics));
+    let names: Vec<&str> = metrics.iter().map(|m| m.attribute()).collect();
+    RemoteReply::with_status(202, json!({ "started": names }))
+}
+
+/// The selected nodes and edges of the active document, by index, with their
+/// attributes.
+fn selection(ctx: &EngineContext) -> Value {
+    let graph: &Graph = &ctx.graph;
+    let nodes: Vec<Value> = ctx
+        .selection
+        .nodes()
+        .filter_map(|node| {
+            let attrs = graph.node(node)?;
+            Some(json!({ "index": node.index(), "attrs": unflatten(attrs) }))
+        })
+        .collect();
+    let edges: Vec<Value> = ctx
+        .selection
+        .edges()
+        .filter_map(|edge| {
+            let (source, target) = graph.endpoints(edge)?;
+            Some(json!({
+                "index": edge.index(),
+                "source": source.index(),
+                "target": target.index(),
+                "attrs": unflatten(graph.edge(edge)?),
+            }))
+        })
+        .collect();
+    json!({ "nodes": nodes, "edges": edges })
+}
+
+/// Returns `true` if `name` names `label`, ignoring case, spaces, and dashes.
+fn matches(label: &str, name: &str) -> bool {
+    let normalize = |s: &str| {
+        s.chars()
+            .filter(|c| c.is_alphanumeric())
+            .flat_map(char::to_lowercase)
+            .collect::<String>()
+    };
+    normalize(label) == normalize(name)
+}
+
+/// Accept connections until `stopped` is set, answering each on its own
+/// thread, up to [`MAX_CONNECTIONS`] at once.
+fn accept(
+    listener: &TcpListener,
+    sender: &Sender<RemoteCall>,
+    stopped: &AtomicBool,
+    access: &Arc<Access>,
+) {
+    let open = Arc::new(AtomicUsize::new(0));
+    while !stopped.load(Ordering::Relaxed) {
+        match listener.accept() {
+            // dropped, closing it
+            Ok(_) if open.load(Ordering::SeqCst) >= MAX_CONNECTIONS => {}
+            Ok((stream, _)) => {
+                let (sender, access, open) = (sender.clone(), access.clone(), open.clone());
+                open.fetch_add(1, Ordering::SeqCst);
+                std::thread::spawn(move || {
+                    // the connection ends with an error or once answered
+                    let _ = serve(stream, &sender, &access);
+                    open.fetch_sub(1, Ordering::SeqCst);
+                });
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => std::thread::sleep(POLL_INTERVAL),
+            Err(_) => return,
+        }
+    }
+}
+
+/// Read a request from `stream`, have it answered, and write the answer back.
+fn serve(stream: TcpStream, sender: &Sender<RemoteCall>, access: &Access) -> std::io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut writer = stream.try_clone()?;
+    let reply = match read_request(BufReader::new(stream), access) {
+        Ok(command) => {
+            let (reply, answer) = mpsc::channel();
+            match sender.send(RemoteCall { command, reply }) {
+                Ok(()) => answer
+                    .recv_timeout(REPLY_TIMEOUT)
+                    .unwrap_or_else(|_| RemoteReply::error(504, "No answer from the browser")),
+                Err(_) => RemoteReply::error(503, "The server stopped"),
+            }
+        }
+        Err(reply) => reply,
+    };
+    write!(
+        writer,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        reply.status,
+        reason(reply.status),
+        reply.content_type,
+        reply.body.len()
+    )?;
+    writer.write_all(&reply.body)?;
+    writer.flush()
+}
+
+/// Read and route an HTTP request, or the error answering it if it does not
+/// match `access`.
+fn read_request(mut reader: impl BufRead, access: &Access) -> Result<RemoteCommand, RemoteReply> {
+    let bad = |message: &str| RemoteReply::error(400, message);
+    let mut line = String::new();
+    let mut left = MAX_HEAD;
+    read_head_line(&mut reader, &mut line, &mut left)?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err(bad("Malformed request line"));
+    };
+    let (method, target) = (method.to_owned(), target.to_owned());
+
+    let mut length = 0;
+    let (mut host, mut origin, mut content_type, mut authorization) = (None, false, None, None);
+    loop {
+        read_head_line(&mut reader, &mut line, &mut left)?;
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        let Some((name, value)) = header.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        match name.to_ascii_lowercase().as_str() {
+            "content-length" => {
+                length = value.parse().map_err(|_| bad("Invalid Content-Length"))?;
+            }
+            "host" => host = Some(value.to_owned()),
+            "origin" => origin = true,
+            "content-type" => content_type = Some(value.to_ascii_lowercase()),
+            "authorization" => authorization = Some(value.to_owned()),
+            _ => {}
+        }
+    }
+    if !host.is_some_and(|host| access.allows_host(&host)) {
+        return Err(RemoteReply::error(
+            403,
+            "The Host is not the server's address",
+        ));
+    }
+    if origin {
+        return Err(RemoteReply::error(
+            403,
+            "Requests from web pages are refused",
+        ));
+    }
+    let token = authorization
+        .as_deref()
+        .and_then(|a| a.strip_prefix("Bearer "));
+    if !token.is_some_and(|token| same_token(token.trim(), &access.token)) {
+        return Err(RemoteReply::error(401, "Missing or wrong token"));
+    }
+    let json = content_type
+        .as_deref()
+        .and_then(|ty| ty.split(';').next())
+        .is_some_and(|ty| ty.trim() == "application/json");
+    if method == "POST" && !json {
+        return Err(RemoteReply::error(415, "Bodies must be application/json"));
+    }
+    if length > MAX_BODY {
+        return Err(RemoteReply::error(413, "The body is too large"));
+    }
+    let mut body = vec![0; length];
+    reader
+        .read_exact(&mut body)
+        .map_err(|_| bad("Could not read the body"))?;
+
+    let (path, query) = target.split_once('?').unwrap_or((&target, ""));
+    let param = |name: &str| {
+        query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| value)
+    };
+    match (method.as_str(), path) {
+        ("GET", "/status") => Ok(RemoteCommand::Status),
+        ("GET", "/selection") => Ok(RemoteCommand::Selection),
+        ("GET", "/render") => {
+            let name = param("format").unwrap_or("png");
+            let format = RenderFormat::from_name(name)
+                .ok_or_else(|| bad(&format!("Unknown image format '{name}'")))?;
+            Ok(RemoteCommand::Render { format })
+        }
+        ("POST", "/open") => {
+            let body: OpenBody = decode(&body)?;
+            Ok(RemoteCommand::Open { path: body.path })
+        }
+        ("POST", "/layout") => {
+            let body: LayoutBody = decode(&body)?;
+            Ok(RemoteCommand::Layout { name: body.name })
+        }
+        ("POST", "/metrics") => {
+            let body: MetricsBody = decode(&body)?;
+            let metrics = body
+                .metrics
+                .iter()
+                .map(|name| {
+                    Metric::ALL
+                        .into_iter()
+                        .find(|m| matches(m.attribute(), name))
+                        .ok_or_else(|| bad(&format!("Unknown metric '{name}'")))
+                })
+                .collect::<Result<_, _>>()?;
+            Ok(RemoteCommand::Metrics { metrics })
+        }
+        (_, "/status" | "/selection" | "/render" | "/open" | "/layout" | "/metrics") => Err(
+            RemoteReply::error(405, format!("{method} is not allowed on {path}")),
+        ),
+        _ => Err(RemoteReply::error(404, format!("No endpoint {path}"))),
+    }
+}
+
+/// Read the next line of the request line and headers into `line`, out of
+/// the `left` bytes they may still take.
+fn read_head_line(
+    reader: &mut impl BufRead,
+    line: &mut String,
+    left: &mut usize,
+) -> Result<(), RemoteReply> {
+    line.clear();
+    let read = Read::take(reader, *left as u64)
+        .read_line(line)
+        .map_err(|_| RemoteReply::error(400, "Could not read the request"))?;
+    *left -= read;
+    if *left == 0 && !line.ends_with('\n') {
+        return Err(RemoteReply::error(431, "The headers are too large"));
+    }
+    Ok(())
+}
+
+/// Returns `true` if `token` is the server's `expected` one, in a time which
+/// does not tell how much of it is right.
+fn same_token(token: &str, expected: &str) -> bool {
+    let (token, expected) = (token.as_bytes(), expected.as_bytes());
+    token.len() == expected.len()
+        && token
+            .iter()
+            .zip(expected)
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+impl Access {
+    /// Returns `true` if `host`, the `Host` header of a request, names the
+    /// address listened on. Only addresses and `localhost` are, as any other
+    /// name could be rebound to the address by someone else.
+    fn allows_host(&self, host: &str) -> bool {
+        let ip = self.address.ip();
+        match host.parse::<SocketAddr>() {
+            Ok(address) if ip.is_unspecified() => address.port() == self.address.port(),
+            Ok(address) => address == self.address,
+            Err(_) => {
+                (ip.is_loopback() || ip.is_unspecified())
+                    && host.eq_ignore_ascii_case(&format!("localhost:{}", self.address.port()))
+            }
+        }
+    }
+}
+
+/// A token unguessable by other programs, from the random keys of the
+/// standard library's hasher.
+fn session_token() -> String {
+    let state = RandomState::new();
+    format!("{:016x}{:016x}", state.hash_one(0u8), state.hash_one(1u8))
+}
+
+/// Decode a JSON request `body`, an empty one as an empty object.
+fn decode<T: DeserializeOwned>(body: &[u8]) -> Result<T, RemoteReply> {
+    let body = match body.trim_ascii().is_empty() {
+        true => b"{}".as_slice(),
+        false => body,
+    };
+    serde_json::from_slice(body).map_err(|e| RemoteReply::error(400, e.to_string()))
+}
+
+/// The reason phrase of the HTTP `status` codes the server answers with.
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        202 => "Accepted",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        413 => "Payload Too Large",
+        415 => "Unsupported Media Type",
+        431 => "Request Header Fields Too Large",
+        503 => "Service Unavailable",
+        504 => "Gateway Timeout",
+        _ => "Internal Server Error",
+    }
+}
+
+impl Drop for RemoteServer {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+impl RemotePanel {
+    /// Create a panel offering to listen on [`REMOTE_ADDRESS`].
+    pub fn new() -> Self {
+        Self {
+            address: REMOTE_ADDRESS.into(),
+            error: None,
+        }
+    }
+}
+
+impl Panel for RemotePanel {
+    fn id(&self) -> &'static str {
+        "Remote Control"
+    }
+
+    fn draw(&mut self, ui: &Ui, ctx: &mut EngineContext) {
+        match &ctx.remote {
+            Some(server) => {
+                ui.text(
+                    tr("Listening on {address}")
+                        .replace("{address}", &format!("http://{}", server.address())),
+                );
+                ui.text(
+                    tr("{count} requests answered")
+                        .replace("{count}", &server.answered().to_string()),
+                );
+                ui.text(tr("Token: {token}").replace("{token}", server.token()));
+                ui.same_line();
+                if ui.small_button(tr("Copy")) {
+                    ui.set_clipboard_text(server.token());
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip_text(tr(
+                        "Requests must carry it as 'Authorization: Bearer <token>'",
+                    ));
+                }
+                if ui.button(tr("Stop")) {
+                    ctx.log
+                        .info(format!("Stopped listening on {}", server.address()));
+                    ctx.remote = None;
+                }
+            }
+            None => {
+                ui.input_text(tr("Address"), &mut self.address).build();
+                if ui.is_item_hovered() {
+                    ui.tooltip_text(tr("Anyone who can reach the address controls the browser"));
+                }
+                if ui.button(tr("Listen")) {
+                    match RemoteServer::bind(self.address.trim()) {
+                        Ok(server) => {
+                            ctx.log.info(format!(
+                                "Listening for remote control on http://{}",
+                                server.address()
+                            ));
+                            ctx.remote = Some(server);
+                            self.error = None;
+                        }
+                        Err(e) => self.error = Some(format!("Failed to listen: {e}")),
+                    }
+                }
+                if let Some(error) = &self.error {
+                    ui.text_colored([1.0, 0.4, 0.4, 1.0], error);
+                }
+            }
+        }
+    }
+}
+
+impl std::default::Default for RemotePanel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,305 @@
+//! # Edge Routing
+//!
+//! How edges run between their nodes, set per document by an [`EdgeRouting`]
+//! and edited in the [Style panel](crate::style::StylePanel):
+//! - [straight](EdgeShape::Straight), or [curved](EdgeShape::Curved) to
+//!   one side, so edges in both directions between two nodes are told apart
+//! - [bundled](EdgeShape::Bundled) with their neighbors, see
+//!   [`bundling`](crate::layout::bundling), which untangles dense graphs
+//! - edges linking the same nodes are fanned out as arcs, and self-loops
+//!   on the same node nested, [`EdgeRouting::parallel_spacing`] apart
+//!
+//! [`EdgeRoutes`] applies the settings to the visible graph, bundling its edges
+//! on a worker thread whenever it or its positions change. Until new bundles
+//! are ready, the previous ones are bent to follow their moved endpoints.
+//!
+//! ```ignore
+//! ctx.routes.settings.shape = EdgeShape::Bundled;
+//! ctx.refresh();
+//! scene.graph().add_graph(graph, &ctx.positions, &ctx.spatial, &ctx.routes, &camera, ..);
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+use std::thread::JoinHandle;
+
+use crate::graph::{ChangeFilter, EdgeId, Graph, NodeId, Subscription};
+use crate::layout::Positions;
+use crate::layout::bundling::{self, BundleSettings};
+
+/// Graphs with more edges than this are not bundled, as bundling takes time
+/// quadratic in the number of edges.
+pub const MAX_BUNDLED_EDGES: usize = 5000;
+
+/// Pieces curves are drawn with, and with when zoomed far out.
+const CURVE_SEGMENTS: usize = 16;
+const CURVE_SEGMENTS_LOW: usize = 4;
+
+/// The line edges follow between distinct nodes.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EdgeShape {
+    #[default]
+    Straight,
+    /// An arc bending to the left of the edge's direction.
+    Curved,
+    /// Bundled with compatible edges.
+    Bundled,
+}
+
+/// How the edges of a document are drawn, see the [module documentation](self).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EdgeRouting {
+    pub shape: EdgeShape,
+    /// How far curved edges bend, as a fraction of their length.
+    pub curvature: f32,
+    /// Distance between edges linking the same nodes, in world units. Zero
+    /// draws them on top of each other.
+    pub parallel_spacing: f32,
+    pub bundling: BundleSettings,
+}
+
+/// An [`EdgeRouting`] applied to a graph, see the [module documentation](self).
+///
+/// Call [`Self::update()`] whenever the graph or its positions may have
+/// changed.
+#[derive(Debug, Default)]
+pub struct EdgeRoutes {
+    pub settings: EdgeRouting,
+    /// Rank of each edge among those linking the same nodes, and their number,
+    /// by edge index.
+    parallel: Vec<(u32, u32)>,
+    /// Edges added to and removed from the graph [`Self::parallel`] was
+    /// counted in.
+    counted: Option<Subscription>,
+    bundles: Option<Bundles>,
+    job: Option<JoinHandle<Bundles>>,
+}
+
+/// Edges bundled from a snapshot of the graph and its positions.
+#[derive(Debug, Default)]
+struct Bundles {
+    /// Generations of the graph and positions bundled.
+    key: (u64, u64),
+    settings: BundleSettings,
+    /// The route of each edge, by edge index.
+    routes: Vec<Option<Route>>,
+}
+
+/// The bundled path of an edge.
+#[derive(Debug)]
+struct Route {
+    ends: (NodeId, NodeId),
+    /// From the source to the target, as they were placed when bundled.
+    points: Vec<[f32; 2]>,
+}
+
+impl EdgeShape {
+    pub const ALL: [EdgeShape; 3] = [Self::Straight, Self::Curved, Self::Bundled];
+
+    /// Human-readable name of the shape.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Straight => "Straight",
+            Self::Curved => "Curved",
+            Self::Bundled => "Bundled",
+        }
+    }
+}
+
+impl EdgeRoutes {
+    /// Apply `settings`. Routes are computed by the first [`Self::update()`].
+    pub fn new(settings: EdgeRouting) -> Self {
+        Self {
+            settings,
+            ..Self::default()
+        }
+    }
+
+    /// Returns `true` while edges are being bundled.
+    pub fn is_bundling(&self) -> bool {
+        self.job.is_some()
+    }
+
+    /// Bring the routes up to date with `graph` and `positions`: count parallel
+    /// edges again if edges were added or removed, pick up finished bundles,
+    /// and start bundling again if they are out of date.
+    pub fn update(&mut self, graph: &Graph, positions: &Positions) {
+        match self.counted.as_ref().and_then(|c| c.poll(graph)) {
+            Some(changes) if changes.is_empty() => {}
+            Some(_) => self.parallel = count_parallel(graph),
+            None => {
+                let filter = ChangeFilter {
+                    nodes: false,
+                    ..ChangeFilter::STRUCTURE
+                };
+                self.counted = Some(graph.subscribe(filter));
+                self.parallel = count_parallel(graph);
+            }
+        }
+
+        if let Some(job) = self.job.take_if(|job| job.is_finished())
+            && let Ok(bundles) = job.join()
+        {
+            self.bundles = Some(bundles);
+        }
+        if self.settings.shape != EdgeShape::Bundled || graph.edge_count() > MAX_BUNDLED_EDGES {
+            self.bundles = None;
+            return;
+        }
+        let key = (graph.generation(), positions.generation());
+        let current = self
+            .bundles
+            .as_ref()
+            .is_some_and(|b| b.key == key && b.settings == self.settings.bundling);
+        if current || self.job.is_some() {
+            return;
+        }
+
+        let (edges, segments): (Vec<_>, Vec<_>) = graph
+            .edges()
+            .filter_map(|edge| {
+                let (source, target) = graph.endpoints(edge)?;
+                let a = positions.get(source)?;
+                let b = positions.get(target)?;
+                (source != target).then_some(((edge, (source, target)), (a, b)))
+            })
+            .unzip();
+        let (bound, settings) = (graph.edge_bound(), self.settings.bundling.clone());
+        self.job = Some(std::thread::spawn(move || {
+            let paths = bundling::bundle(&segments, &settings);
+            let mut routes: Vec<Option<Route>> = (0..bound).map(|_| None).collect();
+            for ((edge, ends), points) in edges.into_iter().zip(paths) {
+                routes[edge.index()] = Some(Route { ends, points });
+            }
+            Bundles {
+                key,
+                settings,
+                routes,
+            }
+        }));
+    }
+
+    /// Rank of `edge` among the edges linking the same nodes, in id order, and
+    /// their number, as of the last [`Self::update()`].
+    pub fn parallel(&self, edge: EdgeId) -> (usize, usize) {
+        self.parallel
+            .get(edge.index())
+            .map_or((0, 1), |&(rank, count)| (rank as usize, count as usize))
+    }
+
+    /// Replace `points` by the path of `edge`, from `ends.0` at `a` to `ends.1`
+    /// at `b`, with fewer points unless `detail` is set. Returns `false`, with
+    /// `points` left empty, if the edge is a straight line.
+    pub fn path(
+        &self,
+        edge: EdgeId,
+        ends: (NodeId, NodeId),
+        a: [f32; 2],
+        b: [f32; 2],
+        detail: bool,
+        points: &mut Vec<[f32; 2]>,
+    ) -> bool {
+        points.clear();
+        if let Some(route) = self.route(edge, ends) {
+            follow(route, a, b, points);
+            return true;
+        }
+        let (rank, count) = self.parallel(edge);
+        let offset = (rank as f32 - (count - 1) as f32 / 2.0) * self.settings.parallel_spacing;
+        let curvature = match self.settings.shape {
+            EdgeShape::Curved => self.settings.curvature,
+            _ => 0.0,
+        };
+        let d = [b[0] - a[0], b[1] - a[1]];
+        let length = d[0].hypot(d[1]);
+        if (offset == 0.0 && curvature == 0.0) || length <= 0.0 {
+            return false;
+        }
+        // parallel edges fan out the same way whichever their direction
+        let normal = [d[1] / length, -d[0] / length];
+        let side = if ends.0 <= ends.1 { 1.0 } else { -1.0 };
+        // a quadratic curve peaks halfway to its control point
+        let bend = 2.0 * (curvature * length + side * offset);
+        let control = [
+            (a[0] + b[0]) / 2.0 + normal[0] * bend,
+            (a[1] + b[1]) / 2.0 + normal[1] * bend,
+        ];
+        let segments = if detail {
+            CURVE_SEGMENTS
+        } else {
+            CURVE_SEGMENTS_LOW
+        };
+        points.extend((0..=segments).map(|i| {
+            let t = i as f32 / segments as f32;
+            let (u, v, w) = ((1.0 - t) * (1.0 - t), 2.0 * (1.0 - t) * t, t * t);
+            [
+                u * a[0] + v * control[0] + w * b[0],
+                u * a[1] + v * control[1] + w * b[1],
+            ]
+        }));
+        true
+    }
+
+    /// Returns `true` if edges are drawn along bundles, which may stray far
+    /// from the straight line between their nodes.
+    pub fn is_bundled(&self) -> bool {
+        self.bundles.is_some()
+    }
+
+    /// The bundled route of `edge` from `ends.0` to `ends.1`, if any.
+    fn route(&self, edge: EdgeId, ends: (NodeId, NodeId)) -> Option<&Route> {
+        let route = self.bundles.as_ref()?.routes.get(edge.index())?.as_ref()?;
+        (route.ends == ends).then_some(route)
+    }
+}
+
+/// Rank and number of the edges linking the same nodes, by edge index.
+fn count_parallel(graph: &Graph) -> Vec<(u32, u32)> {
+    let mut links: HashMap<(NodeId, NodeId), Vec<EdgeId>> = HashMap::new();
+    for edge in graph.edges() {
+        let (source, target) = graph.endpoints(edge).unwrap();
+        links
+            .entry((source.min(target), source.max(target)))
+            .or_default()
+            .push(edge);
+    }
+    let mut parallel = vec![(0, 1); graph.edge_bound()];
+    for edges in links.values() {
+        for (rank, edge) in edges.iter().enumerate() {
+            parallel[edge.index()] = (rank as u32, edges.len() as u32);
+        }
+    }
+    parallel
+}
+
+/// Replace `points` by `route`, bent so its ends move to `a` and `b`, each
+/// point following the closer end the most.
+fn follow(route: &Route, a: [f32; 2], b: [f32; 2], points: &mut Vec<[f32; 2]>) {
+    let [first, last] = [route.points[0], route.points[route.points.len() - 1]];
+    let moved = [
+        [a[0] - first[0], a[1] - first[1]],
+        [b[0] - last[0], b[1] - last[1]],
+    ];
+    let pieces = (route.points.len() - 1).max(1) as f32;
+    points.extend(route.points.iter().enumerate().map(|(i, p)| {
+        let t = i as f32 / pieces;
+        [
+            p[0] + moved[0][0] * (1.0 - t) + moved[1][0] * t,
+            p[1] + moved[0][1] * (1.0 - t) + moved[1][1] * t,
+        ]
+    }));
+}
+
+impl std::default::Default for EdgeRouting {
+    fn default() -> Self {
+        Self {
+            shape: EdgeShape::Straight,
+            curvature: 0.15,
+            parallel_spacing: 10.0,
+            bundling: BundleSettings::default(),
+        }
+    }
+}
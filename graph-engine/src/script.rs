@@ -0,0 +1,369 @@
+//! # Scripting
+//!
+//! The Script Console panel runs snippets typed by the user through a
+//! [`ScriptRuntime`], eg. the [`TealRuntime`](teal::TealRuntime), set as
+//! [`EngineContext::script_runtime`]. Scripts reach the engine only through a
+//! [`ScriptHost`], the stable host API runtimes bind to, versioned by
+//! [`HOST_API_VERSION`]:
+//! - nodes are named by their [`ID_ATTR`](crate::io::ID_ATTR), or their id if
+//!   unset, as ids are not stable across edits
+//! - queries read the active document, and changes to it are undoable, as if
+//!   made in the ui
+//! - [`ScriptHost::print()`] writes to the console
+//!
+//! ```ignore
+//! ctx.script_runtime = Some(Box::new(TealRuntime::new()));
+//! let (printed, result) = script::run(&mut ctx, "#graph.neighbors(\"a\")");
+//! ```
+
+#[cfg(feature = "teal")]
+pub mod teal;
+
+use std::collections::HashMap;
+
+use crate::context::EngineContext;
+use crate::graph::{AttrValue, Attributes, NodeId, Symbol};
+use crate::history::Command;
+use crate::i18n::tr;
+use crate::imgui::{Key, Ui};
+use crate::io::node_name;
+use crate::selection::SelectMode;
+use crate::subsystems::panels::Panel;
+
+/// Version of the [`ScriptHost`] API, increased whenever it changes
+/// incompatibly.
+pub const HOST_API_VERSION: u32 = 1;
+/// Number of lines kept by the Script Console panel.
+const OUTPUT_LEN: usize = 1000;
+const ERROR_COLOR: [f32; 4] = [1.0, 0.4, 0.4, 1.0];
+
+/// Runs scripts against a [`ScriptHost`], see the [module documentation](self).
+pub trait ScriptRuntime {
+    /// Human readable name of the language, eg. for the console's hint.
+    fn name(&self) -> &str;
+
+    /// Run `source`, keeping what it defines for later calls. Returns the value
+    /// of its last expression as text, if any, or the error it failed with.
+    fn eval(&mut self, source: &str, host: &mut ScriptHost) -> Result<Option<String>, String>;
+
+    /// `source` in the language's canonical layout, or the error it fails to
+    /// parse with. `None` if the runtime has no formatter, as by default.
+    fn format(&self, source: &str) -> Option<Result<String, String>> {
+        let _ = source;
+        None
+    }
+}
+
+/// What scripts can do to the engine, see the [module documentation](self).
+pub struct ScriptHost<'a> {
+    ctx: &'a mut EngineContext,
+    /// Nodes by name, built on first lookup and dropped by changes.
+    names: Option<HashMap<String, NodeId>>,
+    output: Vec<String>,
+}
+
+/// Runs snippets through the [`EngineContext::script_runtime`], showing what
+/// they print.
+pub struct ScriptConsolePanel {
+    input: String,
+    /// Lines printed, and whether each is an error.
+    output: Vec<(String, bool)>,
+    /// Snippets run, oldest first, and the one recalled with the arrow keys.
+    history: Vec<String>,
+    recalled: Option<usize>,
+    scroll_to_bottom: bool,
+}
+
+impl<'a> ScriptHost<'a> {
+    /// A host for scripts reading and changing `ctx`.
+    pub fn new(ctx: &'a mut EngineContext) -> Self {
+        Self {
+            ctx,
+            names: None,
+            output: Vec::new(),
+        }
+    }
+
+    /// Lines printed so far.
+    pub fn output(&self) -> &[String] {
+        &self.output
+    }
+
+    /// Write `text` to the console.
+    pub fn print(&mut self, text: impl Into<String>) {
+        self.output.push(text.into());
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.ctx.graph.node_count()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.ctx.graph.edge_count()
+    }
+
+    /// Names of every node.
+    pub fn nodes(&self) -> Vec<String> {
+        let graph = &self.ctx.graph;
+        graph.nodes().map(|n| node_name(graph, n)).collect()
+    }
+
+    /// Returns `true` if a node is named `node`.
+    pub fn has_node(&mut self, node: &str) -> bool {
+        self.find(node).is_some()
+    }
+
+    /// Names of the nodes adjacent to `node`, or `None` if there is no such
+    /// node.
+    pub fn neighbors(&mut self, node: &str) -> Option<Vec<String>> {
+        let node = self.find(node)?;
+        let graph = &self.ctx.graph;
+        Some(graph.neighbors(node).map(|n| node_name(graph, n)).collect())
+    }
+
+    /// The attribute `key` of `node`, if set.
+    pub fn attribute(&mut self, node: &str, key: &str) -> Option<AttrValue> {
+        let node = self.find(node)?;
+        self.ctx.graph.node(node)?.get(key).cloned()
+    }
+
+    /// Set the attribute `key` of `node` to `value`, or unset it for `None`.
+    /// Returns `false` if there is no such node.
+    pub fn set_attribute(&mut self, node: &str, key: &str, value: Option<AttrValue>) -> bool {
+        let Some(node) = self.find(node) else {
+            return false;
+        };
+        self.ctx.execute(Command::SetNodeAttribute {
+            key: Symbol::from(key),
+            values: vec![(node, value)],
+        });
+        // renaming a node invalidates the lookup
+        self.names = None;
+        true
+    }
+
+    /// Add an edge from `source` to `target`. Returns `false` if either node
+    /// does not exist.
+    pub fn add_edge(&mut self, source: &str, target: &str) -> bool {
+        let (Some(source), Some(target)) = (self.find(source), self.find(target)) else {
+            return false;
+        };
+        self.ctx.execute(Command::AddEdge {
+            edge: None,
+            source,
+            target,
+            attrs: Attributes::default(),
+        });
+        true
+    }
+
+    /// Names of the selected nodes.
+    pub fn selected(&self) -> Vec<String> {
+        let graph = &self.ctx.graph;
+        self.ctx
+            .selection
+            .nodes()
+            .map(|n| node_name(graph, n))
+            .collect()
+    }
+
+    /// Select the named `nodes` as `mode` does, ignoring unknown names. Returns
+    /// the number of nodes found.
+    pub fn select(&mut self, nodes: &[&str], mode: SelectMode) -> usize {
+        let found: Vec<NodeId> = nodes.iter().filter_map(|n| self.find(n)).collect();
+        let count = found.len();
+        self.ctx.selection.select(found, [], mode);
+        count
+    }
+
+    pub fn clear_selection(&mut self) {
+        self.ctx.selection.clear();
+    }
+
+    /// Names of the layouts [`Self::run_layout()`] accepts.
+    pub fn layouts(&self) -> Vec<String> {
+        let mut names = vec!["Force-Directed".to_owned()];
+        names.extend(self.ctx.layouts.iter().map(|l| l.name().to_owned()));
+        names
+    }
+
+    /// Run the layout `name`, one of [`Self::layouts()`]. Returns `false` if
+    /// there is no such layout.
+    pub fn run_layout(&mut self, name: &str) -> bool {
+        if name.eq_ignore_ascii_case("Force-Directed") {
+            self.ctx.force.set_paused(false);
+            self.ctx.force.reheat();
+            return true;
+        }
+        match self
+            .ctx
+            .layouts
+            .iter()
+            .position(|l| l.name().eq_ignore_ascii_case(name))
+        {
+            Some(index) => {
+                self.ctx.apply_layout(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The node named `name`.
+    fn find(&mut self, name: &str) -> Option<NodeId> {
+        let graph = &self.ctx.graph;
+        let names = self
+            .names
+            .get_or_insert_with(|| graph.nodes().map(|n| (node_name(graph, n), n)).collect());
+        names.get(name).copied()
+    }
+}
+
+/// Run `source` through the [`EngineContext::script_runtime`]. Returns what it
+/// printed, and its value or the error it failed with.
+pub fn run(ctx: &mut EngineContext, source: &str) -> (Vec<String>, Result<Option<String>, String>) {
+    let Some(mut runtime) = ctx.script_runtime.take() else {
+        return (Vec::new(), Err("No script runtime is available".into()));
+    };
+    let mut host = ScriptHost::new(ctx);
+    let result = runtime.eval(source, &mut host);
+    let output = std::mem::take(&mut host.output);
+    ctx.script_runtime = Some(runtime);
+    (output, result)
+}
+
+impl ScriptConsolePanel {
+    /// Create a console with nothing run yet.
+    pub fn new() -> Self {
+        Self {
+            input: String::new(),
+            output: Vec::new(),
+            history: Vec::new(),
+            recalled: None,
+            scroll_to_bottom: false,
+        }
+    }
+
+    /// Run the input, echoing it and what it printed.
+    fn submit(&mut self, ctx: &mut EngineContext) {
+        let source = std::mem::take(&mut self.input);
+        if source.trim().is_empty() {
+            return;
+        }
+        self.push(format!("> {source}"), false);
+        let (lines, result) = run(ctx, &source);
+        for line in lines {
+            self.push(line, false);
+        }
+        match result {
+            Ok(Some(value)) => self.push(value, false),
+            Ok(None) => {}
+            Err(e) => self.push(e, true),
+        }
+        if self.history.last() != Some(&source) {
+            self.history.push(source);
+        }
+        self.recalled = None;
+        self.scroll_to_bottom = true;
+    }
+
+    /// Replace the input by its formatted source, or print why it cannot be.
+    fn format_input(&mut self, ctx: &EngineContext) {
+        let Some(runtime) = &ctx.script_runtime else {
+            return;
+        };
+        match runtime.format(&self.input) {
+            Some(Ok(formatted)) => self.input = formatted.trim_end().to_owned(),
+            Some(Err(e)) => self.push(e, true),
+            None => self.push(format!("{} has no formatter", runtime.name()), true),
+        }
+        self.scroll_to_bottom = true;
+    }
+
+    fn push(&mut self, line: String, error: bool) {
+        if self.output.len() == OUTPUT_LEN {
+            self.output.remove(0);
+        }
+        self.output.push((line, error));
+    }
+
+    /// Replace the input by an older snippet, or a newer one if `back` is
+    /// `false`.
+    fn recall(&mut self, back: bool) {
+        if self.history.is_empty() {
+            return;
+        }
+        let last = self.history.len() - 1;
+        self.recalled = match (self.recalled, back) {
+            (None, true) => Some(last),
+            (None, false) => None,
+            (Some(i), true) => Some(i.saturating_sub(1)),
+            (Some(i), false) if i < last => Some(i + 1),
+            (Some(_), false) => None,
+        };
+        self.input = self
+            .recalled
+            .map_or_else(String::new, |i| self.history[i].clone());
+    }
+}
+
+impl Panel for ScriptConsolePanel {
+    fn id(&self) -> &'static str {
+        "Script Console"
+    }
+
+    fn draw(&mut self, ui: &Ui, ctx: &mut EngineContext) {
+        let height = ui.frame_height_with_spacing();
+        ui.child_window("##output").size([0.0, -height]).build(|| {
+            for (line, error) in &self.output {
+                if *error {
+                    ui.text_colored(ERROR_COLOR, line);
+                } else {
+                    ui.text_wrapped(line);
+                }
+            }
+            if self.scroll_to_bottom {
+                ui.set_scroll_here_y_with_ratio(1.0);
+                self.scroll_to_bottom = false;
+            }
+        });
+
+        let hint = match &ctx.script_runtime {
+            Some(runtime) => format!("{} (Enter runs, Up/Down recall)", runtime.name()),
+            None => "No script runtime is available".to_owned(),
+        };
+        ui.set_next_item_width(-120.0);
+        let entered = ui
+            .input_text("##input", &mut self.input)
+            .hint(hint)
+            .enter_returns_true(true)
+            .build();
+        if ui.is_item_active() {
+            if ui.is_key_pressed(Key::UpArrow) {
+                self.recall(true);
+            }
+            if ui.is_key_pressed(Key::DownArrow) {
+                self.recall(false);
+            }
+        }
+        if entered {
+            self.submit(ctx);
+            // keep typing after running
+            ui.set_keyboard_focus_here_with_offset(crate::imgui::FocusedWidget::Previous);
+        }
+        ui.same_line();
+        if ui.button(tr("Format")) {
+            self.format_input(ctx);
+        }
+        ui.same_line();
+        if ui.button(tr("Clear")) {
+            self.output.clear();
+        }
+    }
+}
+
+impl std::default::Default for ScriptConsolePanel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
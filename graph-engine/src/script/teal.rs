@@ -0,0 +1,131 @@
+//! # Teal runtime
+//!
+//! A [`ScriptRuntime`] compiling snippets of [Teal](https://github.com/teal-language/tl)
+//! to WASM with [tl2wasm], and running them in a [`Repl`] so that what a
+//! snippet declares is kept for the next ones. Snippets reach the
+//! [`ScriptHost`] through the modules of [`host::graph_api()`], and:
+//! - `layout.names()`, the names of the layouts `layout.run()` accepts
+//! - `layout.run(name)`, returning `false` if there is no such layout
+//!
+//! ```ignore
+//! ctx.script_runtime = Some(Box::new(TealRuntime::new()));
+//! script::run(&mut ctx, "selection.select(graph.neighbors(\"a\"))");
+//! ```
+
+use std::panic::AssertUnwindSafe;
+
+use tl2wasm::host::{self, GraphApi, HostRegistry, HostType, HostValue};
+use tl2wasm::repl::Repl;
+use tl2wasm::teal::format;
+
+use super::{ScriptHost, ScriptRuntime};
+use crate::graph::AttrValue;
+use crate::selection::SelectMode;
+use crate::subsystems::crash;
+
+/// Runs Teal snippets, see the [module documentation](self).
+pub struct TealRuntime {
+    repl: Repl,
+}
+
+/// The [`ScriptHost`] of the snippet being run, borrowed for the time it runs.
+struct Host<'h, 'a>(&'h mut ScriptHost<'a>);
+
+impl TealRuntime {
+    /// A runtime with nothing declared yet.
+    pub fn new() -> Self {
+        Self {
+            repl: Repl::new(&registry()),
+        }
+    }
+}
+
+/// The functions snippets can call on a [`Host`].
+fn registry<'h, 'a>() -> HostRegistry<Host<'h, 'a>> {
+    use HostType::*;
+
+    let mut registry = host::graph_api::<Host>();
+    registry
+        .register("layout", "names", &[], Some(Strings), |h, _| {
+            Ok(HostValue::Strings(h.0.layouts()))
+        })
+        .register("layout", "run", &[String], Some(Boolean), |h, args| {
+            Ok(HostValue::Boolean(h.0.run_layout(args[0].as_str())))
+        });
+    registry
+}
+
+impl ScriptRuntime for TealRuntime {
+    fn name(&self) -> &str {
+        "Teal"
+    }
+
+    fn eval(&mut self, source: &str, host: &mut ScriptHost) -> Result<Option<String>, String> {
+        // a panic compiling the snippet fails it, as one in a host function does
+        let eval = || self.repl.eval(source, &registry(), &mut Host(host));
+        let (printed, result) = crash::catch_unwind(AssertUnwindSafe(eval))
+            .map_err(|_| "The script runtime panicked".to_owned())?;
+        for line in printed {
+            host.print(line);
+        }
+        result.map_err(|e| e.to_string())
+    }
+
+    fn format(&self, source: &str) -> Option<Result<String, String>> {
+        Some(format::format(source).map_err(|e| e.to_string()))
+    }
+}
+
+impl GraphApi for Host<'_, '_> {
+    fn node_count(&self) -> usize {
+        self.0.node_count()
+    }
+
+    fn edge_count(&self) -> usize {
+        self.0.edge_count()
+    }
+
+    fn nodes(&self) -> Vec<String> {
+        self.0.nodes()
+    }
+
+    fn has_node(&mut self, node: &str) -> bool {
+        self.0.has_node(node)
+    }
+
+    fn neighbors(&mut self, node: &str) -> Option<Vec<String>> {
+        self.0.neighbors(node)
+    }
+
+    fn add_edge(&mut self, source: &str, target: &str) -> bool {
+        self.0.add_edge(source, target)
+    }
+
+    fn attribute(&mut self, node: &str, key: &str) -> Option<String> {
+        let value = self.0.attribute(node, key)?;
+        Some(value.to_string())
+    }
+
+    fn set_attribute(&mut self, node: &str, key: &str, value: &str) -> bool {
+        let value = AttrValue::String(value.to_owned());
+        self.0.set_attribute(node, key, Some(value))
+    }
+
+    fn selected(&self) -> Vec<String> {
+        self.0.selected()
+    }
+
+    fn select(&mut self, nodes: &[&str]) -> usize {
+        self.0.select(nodes, SelectMode::Replace)
+    }
+
+    fn clear_selection(&mut self) {
+        self.0.clear_selection()
+    }
+}
+
+impl std::default::Default for TealRuntime {
+    fn default() -> Self {
+        Self::new()
+    }
+}
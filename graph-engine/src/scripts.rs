@@ -0,0 +1,466 @@
+//! # Saved Scripts
+//!
+//! Scripts kept as `<name>.tl` files in [`SCRIPTS_DIR`], found with
+//! [`discover()`], for what is done to graphs over and over. They run through
+//! the [`EngineContext::script_runtime`] against the active document, as
+//! snippets of the [Script Console](crate::script) do:
+//! - the Scripts panel lists them, runs them, and shows what the last one run
+//!   printed
+//! - each is a [palette](crate::palette) command, `Script: <name>`, which can be
+//!   bound to a hotkey in the keymap
+//! - scripts pinned to the toolbar, kept in [`TOOLBAR_FILE`], get a button
+//!   drawn by [`draw_toolbar()`]
+//!
+//! ```ignore
+//! scripts::discover(&mut ctx, ui_state.dir().join(SCRIPTS_DIR));
+//! if let Some(_bar) = ui.begin_main_menu_bar() {
+//!     scripts::draw_toolbar(ui, &mut ctx);
+//! }
+//! for id in ctx.input.drain_commands() {
+//!     palette::run(&mut ctx, &id);
+//! }
+//! ```
+
+use std::path::{Path, PathBuf};
+
+use crate::context::EngineContext;
+use crate::i18n::tr;
+use crate::imgui::{TableFlags, Ui};
+use crate::palette::PaletteCommand;
+use crate::script;
+use crate::subsystems::input::Binding;
+use crate::subsystems::panels::Panel;
+
+/// Name of the directory scripts are discovered in, in the application's state
+/// directory.
+pub const SCRIPTS_DIR: &str = "scripts";
+/// Name of the file listing the scripts pinned to the toolbar, one name per
+/// line in order, in the scripts directory.
+pub const TOOLBAR_FILE: &str = "toolbar.txt";
+/// Extension of the script files.
+pub const SCRIPT_EXTENSION: &str = "tl";
+/// Prefix of the ids of palette commands running a script, followed by its
+/// name.
+pub const RUN_COMMAND: &str = "script.run:";
+const ERROR_COLOR: [f32; 4] = [1.0, 0.4, 0.4, 1.0];
+
+/// The scripts of a directory, see the [module documentation](self).
+#[derive(Default)]
+pub struct ScriptLibrary {
+    dir: Option<PathBuf>,
+    /// Sorted by name.
+    scripts: Vec<SavedScript>,
+    /// Names of the scripts pinned to the toolbar, in order.
+    pinned: Vec<String>,
+    last_run: Option<ScriptRun>,
+}
+
+/// A script file of a [`ScriptLibrary`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SavedScript {
+    /// The file name without its extension.
+    pub name: String,
+    pub path: PathBuf,
+}
+
+/// What a script printed when run.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScriptRun {
+    pub name: String,
+    /// Lines printed, then its value or error, and whether each is an error.
+    pub output: Vec<(String, bool)>,
+}
+
+/// Lists the scripts of the [`EngineContext::scripts`], running them and
+/// binding them to hotkeys or the toolbar.
+pub struct ScriptsPanel {
+    /// The script whose hotkey is edited, if any.
+    editing: Option<String>,
+    hotkey: String,
+    /// Why the last hotkey could not be bound, shown until the next try.
+    error: Option<String>,
+}
+
+/// What the Scripts panel does once drawn, as it borrows the library.
+enum PanelAction {
+    Run(String),
+    Pin(String, bool),
+    EditHotkey(String),
+}
+
+impl ScriptLibrary {
+    /// Create a library without scripts.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The directory scripts were last discovered in, if any.
+    pub fn dir(&self) -> Option<&Path> {
+        self.dir.as_deref()
+    }
+
+    /// The scripts found, sorted by name.
+    pub fn scripts(&self) -> &[SavedScript] {
+        &self.scripts
+    }
+
+    /// The script `name`, if any.
+    pub fn get(&self, name: &str) -> Option<&SavedScript> {
+        self.scripts.iter().find(|s| s.name == name)
+    }
+
+    /// Names of the scripts pinned to the toolbar, in order.
+    pub fn pinned(&self) -> &[String] {
+        &self.pinned
+    }
+
+    pub fn is_pinned(&self, name: &str) -> bool {
+        self.pinned.iter().any(|p| p == name)
+    }
+
+    /// The script run last, and what it printed.
+    pub fn last_run(&self) -> Option<&ScriptRun> {
+        self.last_run.as_ref()
+    }
+
+    /// Read [`TOOLBAR_FILE`] from the scripts directory.
+    fn load_pinned(&mut self) {
+        let Some(dir) = &self.dir else {
+            return;
+        };
+        self.pinned = match std::fs::read_to_string(dir.join(TOOLBAR_FILE)) {
+            Ok(text) => text
+                .lines()
+                .map(str::trim)
+                .filter(|l| !l.is_empty())
+                .map(str::to_owned)
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+    }
+
+    /// Write [`TOOLBAR_FILE`] to the scripts directory.
+    fn save_pinned(&self) -> std::io::Result<()> {
+        let Some(dir) = &self.dir else {
+            return Ok(());
+        };
+        std::fs::create_dir_all(dir)?;
+        std::fs::write(dir.join(TOOLBAR_FILE), self.pinned.join("\n"))
+    }
+}
+
+impl SavedScript {
+    /// Id of the palette command running the script.
+    pub fn command(&self) -> String {
+        format!("{RUN_COMMAND}{}", self.name)
+    }
+}
+
+/// List the scripts in `dir`, creating it if missing, as the
+/// [`EngineContext::scripts`], with a palette command running each. Commands
+/// of scripts since removed are unregistered.
+pub fn discover(ctx: &mut EngineContext, dir: impl Into<PathBuf>) {
+    let dir = dir.into();
+    if let Err(e) = std::fs::create_dir_all(&dir) {
+        ctx.log.error(format!(
+            "Failed to create the scripts directory '{}': {e}",
+            dir.display()
+        ));
+        return;
+    }
+    let mut scripts: Vec<SavedScript> = match std::fs::read_dir(&dir) {
+        Ok(entries) => entries
+            .filter_map(|e| Some(e.ok()?.path()))
+            .filter(|p| p.extension().is_some_and(|e| e == SCRIPT_EXTENSION))
+            .filter_map(|path| {
+                let name = path.file_stem()?.to_str()?.to_owned();
+                Some(SavedScript { name, path })
+            })
+            .collect(),
+        Err(e) => {
+            ctx.log.error(format!(
+                "Failed to list the scripts in '{}': {e}",
+                dir.display()
+            ));
+            return;
+        }
+    };
+    scripts.sort_by(|a, b| a.name.cmp(&b.name));
+
+    for old in &ctx.scripts.scripts {
+        if !scripts.contains(old) {
+            ctx.palette.unregister(&old.command());
+        }
+    }
+    for script in &scripts {
+        let id = script.command();
+        if ctx.palette.contains(&id) {
+            continue;
+        }
+        let name = script.name.clone();
+        ctx.palette.register(PaletteCommand::new(
+            id,
+            format!("Script: {name}"),
+            move |ctx| run(ctx, &name),
+        ));
+    }
+    let library = &mut ctx.scripts;
+    library.dir = Some(dir);
+    library.scripts = scripts;
+    library.load_pinned();
+}
+
+/// Run the script `name` of the [`EngineContext::scripts`], keeping what it
+/// printed as the [last run](ScriptLibrary::last_run). Errors are also logged,
+/// as scripts run from hotkeys show nothing else.
+pub fn run(ctx: &mut EngineContext, name: &str) {
+    let Some(path) = ctx.scripts.get(name).map(|s| s.path.clone()) else {
+        ctx.log.warn(format!("No script '{name}'"));
+        return;
+    };
+    let mut output = Vec::new();
+    let result = match std::fs::read_to_string(&path) {
+        Ok(source) => {
+            let (printed, result) = script::run(ctx, &source);
+            output.extend(printed.into_iter().map(|line| (line, false)));
+            result
+        }
+        Err(e) => Err(format!("Failed to read '{}': {e}", path.display())),
+    };
+    match result {
+        Ok(Some(value)) => output.push((value, false)),
+        Ok(None) => {}
+        Err(e) => {
+            ctx.log.error(format!("Script '{name}': {e}"));
+            output.push((e, true));
+        }
+    }
+    ctx.scripts.last_run = Some(ScriptRun {
+        name: name.to_owned(),
+        output,
+    });
+}
+
+/// Pin the script `name` to the toolbar, or unpin it, remembering the choice
+/// in [`TOOLBAR_FILE`].
+pub fn set_pinned(ctx: &mut EngineContext, name: &str, pinned: bool) {
+    let library = &mut ctx.scripts;
+    library.pinned.retain(|p| p != name);
+    if pinned {
+        library.pinned.push(name.to_owned());
+    }
+    if let Err(e) = library.save_pinned() {
+        ctx.log
+            .error(format!("Failed to save the pinned scripts: {e}"));
+    }
+}
+
+/// Draw a button running each script pinned to the toolbar, eg. in the main
+/// menu bar.
+pub fn draw_toolbar(ui: &Ui, ctx: &mut EngineContext) {
+    let mut clicked = None;
+    for name in ctx.scripts.pinned() {
+        // pinned scripts may have been removed from the directory since
+        let Some(script) = ctx.scripts.get(name) else {
+            continue;
+        };
+        let _id = ui.push_id(name);
+        if ui.small_button(name) {
+            clicked = Some(name.clone());
+        }
+        if ui.is_item_hovered() {
+            match ctx.input.map().command_bindings(&script.command()).first() {
+                Some(binding) => ui.tooltip_text(
+                    tr("Run script '{name}' ({binding})")
+                        .replace("{name}", name)
+                        .replace("{binding}", &binding.to_string()),
+                ),
+                None => ui.tooltip_text(tr("Run script '{name}'").replace("{name}", name)),
+            }
+        }
+    }
+    if let Some(name) = clicked {
+        run(ctx, &name);
+    }
+}
+
+impl ScriptsPanel {
+    pub fn new() -> Self {
+        Self {
+            editing: None,
+            hotkey: String::new(),
+            error: None,
+        }
+    }
+
+    /// Draw the editor of the hotkey of the script [`Self::editing`].
+    fn draw_hotkey_editor(&mut self, ui: &Ui, ctx: &mut EngineContext) {
+        let Some(name) = self.editing.clone() else {
+            return;
+        };
+        let Some(id) = ctx.scripts.get(&name).map(SavedScript::command) else {
+            self.editing = None;
+            return;
+        };
+        ui.separator();
+        ui.text(tr("Hotkey of '{name}'").replace("{name}", &name));
+        ui.set_next_item_width(-1.0);
+        let entered = ui
+            .input_text("##hotkey", &mut self.hotkey)
+            .hint(tr("eg. Ctrl+Shift+T, or Ctrl+K T"))
+            .enter_returns_true(true)
+            .build();
+        if ui.button(tr("Bind")) || entered {
+            match self.hotkey.parse::<Binding>() {
+                Ok(binding) => {
+                    let map = ctx.input.map_mut();
+                    map.clear_command(&id);
+                    map.bind_command(&id, binding);
+                    self.error = ctx.input.save().err().map(|e| e.to_string());
+                    if self.error.is_none() {
+                        self.editing = None;
+                    }
+                }
+                Err(e) => self.error = Some(e),
+            }
+        }
+        ui.same_line();
+        if ui.button(tr("Clear")) {
+            ctx.input.map_mut().clear_command(&id);
+            self.error = ctx.input.save().err().map(|e| e.to_string());
+            self.hotkey.clear();
+        }
+        ui.same_line();
+        if ui.button(tr("Cancel")) {
+            self.editing = None;
+            self.error = None;
+        }
+        if let Some(error) = &self.error {
+            ui.text_colored(ERROR_COLOR, error);
+        }
+    }
+}
+
+impl Panel for ScriptsPanel {
+    fn id(&self) -> &'static str {
+        "Scripts"
+    }
+
+    fn draw(&mut self, ui: &Ui, ctx: &mut EngineContext) {
+        let dir = ctx.scripts.dir().map(Path::to_path_buf);
+        match &dir {
+            Some(dir) => ui.text_wrapped(
+                tr("Directory: {path}").replace("{path}", &dir.display().to_string()),
+            ),
+            None => ui.text_disabled(tr("No scripts directory")),
+        }
+        if let Some(dir) = &dir {
+            if ui.button(tr("Rescan")) {
+                discover(ctx, dir.clone());
+            }
+            if ui.is_item_hovered() {
+                ui.tooltip_text(tr("List scripts added to or removed from the directory"));
+            }
+        }
+        if ctx.script_runtime.is_none() {
+            ui.text_colored(ERROR_COLOR, tr("No script runtime is available"));
+        }
+        ui.separator();
+
+        if ctx.scripts.scripts().is_empty() {
+            ui.text_disabled(
+                tr("No scripts found, add <name>.{extension} files to the directory")
+                    .replace("{extension}", SCRIPT_EXTENSION),
+            );
+            return;
+        }
+        let mut action = None;
+        if let Some(table) = ui.begin_table_with_flags(
+            "##scripts",
+            3,
+            TableFlags::ROW_BG | TableFlags::BORDERS_INNER_H | TableFlags::SIZING_STRETCH_PROP,
+        ) {
+            ui.table_setup_column(tr("Script"));
+            ui.table_setup_column(tr("Hotkey"));
+            ui.table_setup_column("##buttons");
+            ui.table_headers_row();
+
+            for script in ctx.scripts.scripts() {
+                let _id = ui.push_id(&script.name);
+                ui.table_next_row();
+
+                ui.table_next_column();
+                ui.text(&script.name);
+
+                ui.table_next_column();
+                let bindings = ctx.input.map().command_bindings(&script.command());
+                if bindings.is_empty() {
+                    ui.text_disabled(tr("None"));
+                } else {
+                    let text: Vec<_> = bindings.iter().map(Binding::to_string).collect();
+                    ui.text(text.join(", "));
+                }
+
+                ui.table_next_column();
+                if ui.small_button(tr("Run")) {
+                    action = Some(PanelAction::Run(script.name.clone()));
+                }
+                ui.same_line();
+                let pinned = ctx.scripts.is_pinned(&script.name);
+                if ui.small_button(tr(if pinned { "Unpin" } else { "Pin" })) {
+                    action = Some(PanelAction::Pin(script.name.clone(), !pinned));
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip_text(tr("Show a button running the script in the toolbar"));
+                }
+                ui.same_line();
+                if ui.small_button(tr("Hotkey")) {
+                    action = Some(PanelAction::EditHotkey(script.name.clone()));
+                }
+            }
+            table.end();
+        }
+        match action {
+            Some(PanelAction::Run(name)) => run(ctx, &name),
+            Some(PanelAction::Pin(name, pinned)) => set_pinned(ctx, &name, pinned),
+            Some(PanelAction::EditHotkey(name)) => {
+                let id = format!("{RUN_COMMAND}{name}");
+                self.hotkey = ctx
+                    .input
+                    .map()
+                    .command_bindings(&id)
+                    .first()
+                    .map(Binding::to_string)
+                    .unwrap_or_default();
+                self.editing = Some(name);
+                self.error = None;
+            }
+            None => {}
+        }
+        self.draw_hotkey_editor(ui, ctx);
+
+        let Some(last_run) = ctx.scripts.last_run() else {
+            return;
+        };
+        ui.separator();
+        ui.text(tr("Output of '{name}'").replace("{name}", &last_run.name));
+        ui.child_window("##output").build(|| {
+            if last_run.output.is_empty() {
+                ui.text_disabled(tr("Nothing printed"));
+            }
+            for (line, error) in &last_run.output {
+                if *error {
+                    ui.text_colored(ERROR_COLOR, line);
+                } else {
+                    ui.text_wrapped(line);
+                }
+            }
+        });
+    }
+}
+
+impl std::default::Default for ScriptsPanel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
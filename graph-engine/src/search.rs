@@ -0,0 +1,278 @@
+//! # Graph Search
+//!
+//! Finds nodes or edges by a [`Query`]: either plain text looked up in labels,
+//! or an expression over attributes like `degree > 10 && type == "service"`.
+//! Queries are answered by an [`AttributeIndex`], which keeps per-attribute
+//! lookup tables until the graph changes, so refining a query while typing
+//! stays fast on large graphs.
+//!
+//! The [`SearchPanel`] searches as the user types, and moves the camera to a
+//! result picked with the mouse or the arrow keys.
+//!
+//! ```ignore
+//! let query = Query::parse("degree > 10 && type == \"service\"")?;
+//! let hubs = ctx.attribute_index.nodes(&ctx.graph, &query, "label");
+//! ```
+
+mod index;
+mod query;
+
+pub use index::AttributeIndex;
+pub use query::{CompareOp, Literal, Query, QueryError};
+
+use crate::context::EngineContext;
+use crate::graph::{EdgeId, Graph, NodeId};
+use crate::i18n::tr;
+use crate::imgui::{Key, ListClipper, Ui};
+use crate::selection::SelectMode;
+use crate::spatial::edge_bounds;
+use crate::subsystems::panels::Panel;
+
+/// Finds nodes or edges as the user types a [`Query`], see the [module docs](self).
+///
+/// Up and Down move through the results, Enter shows the current one. Shown
+/// results are also selected.
+pub struct SearchPanel {
+    text: String,
+    /// Search edges rather than nodes.
+    edges: bool,
+    /// Why the current text is not a valid query, if it is not.
+    error: Option<QueryError>,
+    found_nodes: Vec<NodeId>,
+    found_edges: Vec<EdgeId>,
+    /// What the results were found for, to search again once any of it changes.
+    searched: Option<(u64, String, bool, String)>,
+    /// Index of the highlighted result.
+    cursor: usize,
+    /// Scroll the result list to the cursor on the next draw.
+    scroll_to_cursor: bool,
+}
+
+impl SearchPanel {
+    /// Create a search panel with an empty query.
+    pub fn new() -> Self {
+        Self {
+            text: String::new(),
+            edges: false,
+            error: None,
+            found_nodes: Vec::new(),
+            found_edges: Vec::new(),
+            searched: None,
+            cursor: 0,
+            scroll_to_cursor: false,
+        }
+    }
+
+    /// Number of results of the last search.
+    pub fn result_count(&self) -> usize {
+        match self.edges {
+            true => self.found_edges.len(),
+            false => self.found_nodes.len(),
+        }
+    }
+
+    /// Search again if the query, scope, label attribute, or graph changed.
+    fn refresh(&mut self, ctx: &mut EngineContext) {
+        let label = match self.edges {
+            true => &ctx.style.sheet().edge_label.attribute,
+            false => &ctx.style.sheet().node_label.attribute,
+        };
+        let key = (
+            ctx.graph.generation(),
+            self.text.clone(),
+            self.edges,
+            label.clone(),
+        );
+        if self.searched.as_ref() == Some(&key) {
+            return;
+        }
+        self.searched = Some(key);
+        self.found_nodes.clear();
+        self.found_edges.clear();
+        self.error = None;
+        self.cursor = 0;
+
+        if self.text.trim().is_empty() {
+            return;
+        }
+        match Query::parse(&self.text) {
+            Ok(query) if self.edges => {
+                self.found_edges = ctx.attribute_index.edges(&ctx.graph, &query, label);
+            }
+            Ok(query) => {
+                self.found_nodes = ctx.attribute_index.nodes(&ctx.graph, &query, label);
+            }
+            Err(e) => self.error = Some(e),
+        }
+    }
+
+    /// Select the result at `index` and glide the camera to it.
+    fn show(&self, index: usize, ctx: &mut EngineContext) {
+        ctx.force.sync_positions(&mut ctx.positions);
+        if self.edges {
+            let Some(&edge) = self.found_edges.get(index) else {
+                return;
+            };
+            ctx.selection.select_edge(edge, SelectMode::Replace);
+            if let Some((min, max)) = edge_bounds(&ctx.graph, &ctx.positions, edge) {
+                ctx.camera.fit_rect(min, max);
+            }
+        } else {
+            let Some(&node) = self.found_nodes.get(index) else {
+                return;
+            };
+            ctx.selection.select_node(node, SelectMode::Replace);
+            if let Some(point) = ctx.positions.get(node) {
+                ctx.camera.fit_rect(point, point);
+            }
+        }
+    }
+
+    /// Select every result and glide the camera to show them all.
+    fn show_all(&self, ctx: &mut EngineContext) {
+        ctx.force.sync_positions(&mut ctx.positions);
+        let mut bounds: Option<([f32; 2], [f32; 2])> = None;
+        let mut grow = |(min, max): ([f32; 2], [f32; 2])| {
+            let (lo, hi) = bounds.get_or_insert((min, max));
+            *lo = [lo[0].min(min[0]), lo[1].min(min[1])];
+            *hi = [hi[0].max(max[0]), hi[1].max(max[1])];
+        };
+        if self.edges {
+            for &edge in &self.found_edges {
+                if let Some(rect) = edge_bounds(&ctx.graph, &ctx.positions, edge) {
+                    grow(rect);
+                }
+            }
+            ctx.selection
+                .select([], self.found_edges.iter().copied(), SelectMode::Replace);
+        } else {
+            for &node in &self.found_nodes {
+                if let Some(point) = ctx.positions.get(node) {
+                    grow((point, point));
+                }
+            }
+            ctx.selection
+                .select(self.found_nodes.iter().copied(), [], SelectMode::Replace);
+        }
+        if let Some((min, max)) = bounds {
+            ctx.camera.fit_rect(min, max);
+        }
+    }
+
+    /// Text shown for the result at `index`.
+    fn describe(&self, index: usize, ctx: &EngineContext) -> String {
+        let sheet = ctx.style.sheet();
+        let node_text = |node| node_name(&ctx.graph, node, &sheet.node_label.attribute);
+        match self.edges {
+            true => {
+                let edge = self.found_edges[index];
+                let ends = ctx.graph.endpoints(edge).map(|(s, t)| {
+                    let arrow = if ctx.graph.is_directed() { "->" } else { "--" };
+                    format!("{} {arrow} {}", node_text(s), node_text(t))
+                });
+                match ctx
+                    .graph
+                    .edge(edge)
+                    .and_then(|a| a.get(sheet.edge_label.attribute.as_str()))
+                {
+                    Some(label) => format!("{label}  ({})", ends.unwrap_or_default()),
+                    None => ends.unwrap_or_else(|| edge.to_string()),
+                }
+            }
+            false => node_text(self.found_nodes[index]),
+        }
+    }
+}
+
+impl Panel for SearchPanel {
+    fn id(&self) -> &'static str {
+        "Search"
+    }
+
+    fn draw(&mut self, ui: &Ui, ctx: &mut EngineContext) {
+        ui.set_next_item_width(-1.0);
+        let entered = ui
+            .input_text("##query", &mut self.text)
+            .hint(tr("label, or eg. degree > 10 && type == \"service\""))
+            .enter_returns_true(true)
+            .build();
+        let typing = ui.is_item_active();
+        ui.radio_button(tr("Nodes"), &mut self.edges, false);
+        ui.same_line();
+        ui.radio_button(tr("Edges"), &mut self.edges, true);
+        self.refresh(ctx);
+
+        let count = self.result_count();
+        if let Some(error) = &self.error {
+            ui.text_colored([1.0, 0.4, 0.4, 1.0], error.to_string());
+        } else {
+            ui.text(tr("{count} results").replace("{count}", &count.to_string()));
+        }
+        ui.same_line();
+        if ui.button(tr("Show All")) && count > 0 {
+            self.show_all(ctx);
+        }
+
+        // keys move through the results while typing, or while the panel is focused
+        if count > 0 && (typing || ui.is_window_focused()) {
+            let previous = self.cursor;
+            if ui.is_key_pressed(Key::DownArrow) {
+                self.cursor = (self.cursor + 1).min(count - 1);
+            }
+            if ui.is_key_pressed(Key::UpArrow) {
+                self.cursor = self.cursor.saturating_sub(1);
+            }
+            if self.cursor != previous {
+                self.scroll_to_cursor = true;
+                self.show(self.cursor, ctx);
+            }
+        }
+        if entered && count > 0 {
+            self.show(self.cursor, ctx);
+        }
+        ui.separator();
+
+        let mut clicked = None;
+        ui.child_window("##results").build(|| {
+            let row = ui.text_line_height_with_spacing();
+            if std::mem::take(&mut self.scroll_to_cursor) {
+                let (top, height) = (self.cursor as f32 * row, ui.window_size()[1]);
+                if top < ui.scroll_y() {
+                    ui.set_scroll_y(top);
+                } else if top + row > ui.scroll_y() + height {
+                    ui.set_scroll_y(top + row - height);
+                }
+            }
+            let clipper = ListClipper::new(count as i32).items_height(row);
+            for i in clipper.begin(ui).iter() {
+                let i = i as usize;
+                let _id = ui.push_id_usize(i);
+                if ui
+                    .selectable_config(self.describe(i, ctx))
+                    .selected(i == self.cursor)
+                    .build()
+                {
+                    clicked = Some(i);
+                }
+            }
+        });
+        if let Some(i) = clicked {
+            self.cursor = i;
+            self.show(i, ctx);
+        }
+    }
+}
+
+/// Label of `node` from its `label` attribute, or its id if it has none.
+fn node_name(graph: &Graph, node: NodeId, label: &str) -> String {
+    match graph.node(node).and_then(|attrs| attrs.get(label)) {
+        Some(value) => value.to_string(),
+        None => node.to_string(),
+    }
+}
+
+impl std::default::Default for SearchPanel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
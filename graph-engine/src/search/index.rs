@@ -0,0 +1,339 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use super::query::{CompareOp, Literal, Query};
+use crate::graph::{
+    AttrValue, ChangeFilter, EdgeId, Element, Graph, GraphChange, NodeId, Subscription,
+};
+use crate::style::DEGREE_ATTR;
+
+/// Per-attribute lookup tables over a graph's nodes and edges, answering
+/// [`Query`]s without scanning every element.
+///
+/// An attribute's table is built the first time a query names it, and kept
+/// until the graph's [changes](Graph::subscribe) touch its kind of element:
+/// editing an edge keeps the node tables, and the other way around.
+#[derive(Debug, Default)]
+pub struct AttributeIndex {
+    /// Changes to the graph the tables were built from.
+    changes: Option<Subscription>,
+    nodes: Columns<NodeId>,
+    edges: Columns<EdgeId>,
+}
+
+/// Tables of one kind of element.
+#[derive(Debug)]
+struct Columns<K> {
+    /// Every element, sorted.
+    all: Option<Vec<K>>,
+    columns: HashMap<String, Column<K>>,
+}
+
+/// Lookup tables of a single attribute.
+#[derive(Clone, Debug)]
+struct Column<K> {
+    /// Elements holding the attribute, sorted.
+    present: Vec<K>,
+    /// Elements with a numeric value, sorted by value.
+    numbers: Vec<(f64, K)>,
+    /// Distinct text forms of the values, with the elements holding them.
+    values: Vec<ColumnValue<K>>,
+    /// Index into `values` of each text form.
+    by_text: HashMap<String, usize>,
+}
+
+#[derive(Clone, Debug)]
+struct ColumnValue<K> {
+    text: String,
+    /// `text` in lowercase, for case-insensitive matching.
+    lower: String,
+    /// Sorted.
+    keys: Vec<K>,
+}
+
+impl AttributeIndex {
+    /// Create an empty index.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Drop every table, eg. when switching graphs.
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Nodes of `graph` matching `query`, sorted. [`Query::Text`] matches the
+    /// `label` attribute.
+    ///
+    /// Nodes without a `degree` attribute match [`DEGREE_ATTR`] by their degree.
+    pub fn nodes(&mut self, graph: &Graph, query: &Query, label: &str) -> Vec<NodeId> {
+        self.refresh(graph);
+        let value = |node, name: &str| match graph.node(node)?.get(name) {
+            Some(value) => Some(value.clone()),
+            None if name == DEGREE_ATTR => Some(AttrValue::Int(graph.degree(node) as i64)),
+            None => None,
+        };
+        self.nodes.eval(query, label, &|| graph.nodes(), &value)
+    }
+
+    /// Edges of `graph` matching `query`, sorted. [`Query::Text`] matches the
+    /// `label` attribute.
+    pub fn edges(&mut self, graph: &Graph, query: &Query, label: &str) -> Vec<EdgeId> {
+        self.refresh(graph);
+        let value = |edge, name: &str| graph.edge(edge)?.get(name).cloned();
+        self.edges.eval(query, label, &|| graph.edges(), &value)
+    }
+
+    fn refresh(&mut self, graph: &Graph) {
+        let Some(changes) = self.changes.as_ref().and_then(|c| c.poll(graph)) else {
+            self.clear();
+            self.changes = Some(graph.subscribe(ChangeFilter::ALL));
+            return;
+        };
+        for change in changes {
+            match change {
+                GraphChange::NodeAdded(_) | GraphChange::NodeRemoved(_) => self.nodes.clear(),
+                // degrees stand in for missing `degree` attributes
+                GraphChange::EdgeAdded(_) | GraphChange::EdgeRemoved(_) => {
+                    self.edges.clear();
+                    self.nodes.columns.remove(DEGREE_ATTR);
+                }
+                GraphChange::AttrChanged(Element::Node(_)) => self.nodes.columns.clear(),
+                GraphChange::AttrChanged(Element::Edge(_)) => self.edges.columns.clear(),
+                GraphChange::AttrChanged(Element::Graph) => {}
+                GraphChange::Cleared => {
+                    self.nodes.clear();
+                    self.edges.clear();
+                }
+            }
+        }
+    }
+}
+
+impl<K> Columns<K> {
+    fn clear(&mut self) {
+        self.all = None;
+        self.columns.clear();
+    }
+}
+
+impl<K: Copy + Ord + Hash> Columns<K> {
+    fn eval<I: Iterator<Item = K>>(
+        &mut self,
+        query: &Query,
+        label: &str,
+        elements: &impl Fn() -> I,
+        value: &impl Fn(K, &str) -> Option<AttrValue>,
+    ) -> Vec<K> {
+        match query {
+            Query::Text(text) => {
+                let needle = text.to_lowercase();
+                self.column(label, elements, value).contains(&needle)
+            }
+            Query::Compare {
+                attribute,
+                op,
+                value: literal,
+            } => self
+                .column(attribute, elements, value)
+                .compare(*op, literal),
+            Query::Not(inner) => {
+                let matched = self.eval(inner, label, elements, value);
+                let all = self.all.get_or_insert_with(|| elements().collect());
+                difference(all, &matched)
+            }
+            Query::And(a, b) => {
+                let a = self.eval(a, label, elements, value);
+                let b = self.eval(b, label, elements, value);
+                intersection(&a, &b)
+            }
+            Query::Or(a, b) => {
+                let a = self.eval(a, label, elements, value);
+                let b = self.eval(b, label, elements, value);
+                union(&a, &b)
+            }
+        }
+    }
+
+    fn column<I: Iterator<Item = K>>(
+        &mut self,
+        name: &str,
+        elements: &impl Fn() -> I,
+        value: &impl Fn(K, &str) -> Option<AttrValue>,
+    ) -> &Column<K> {
+        if !self.columns.contains_key(name) {
+            let column = Column::build(elements().filter_map(|k| Some((k, value(k, name)?))));
+            self.columns.insert(name.to_owned(), column);
+        }
+        &self.columns[name]
+    }
+}
+
+impl<K: Copy + Ord + Hash> Column<K> {
+    /// Build the tables from `(element, value)` pairs in element order.
+    fn build(values: impl Iterator<Item = (K, AttrValue)>) -> Self {
+        let mut column = Self {
+            present: Vec::new(),
+            numbers: Vec::new(),
+            values: Vec::new(),
+            by_text: HashMap::new(),
+        };
+        for (key, value) in values {
+            column.present.push(key);
+            if let Some(n) = value.as_f64().filter(|n| !n.is_nan()) {
+                column.numbers.push((n, key));
+            }
+            let text = value.to_string();
+            let index = match column.by_text.get(&text) {
+                Some(&index) => index,
+                None => {
+                    column.by_text.insert(text.clone(), column.values.len());
+                    column.values.push(ColumnValue {
+                        lower: text.to_lowercase(),
+                        text,
+                        keys: Vec::new(),
+                    });
+                    column.values.len() - 1
+                }
+            };
+            column.values[index].keys.push(key);
+        }
+        column.present.sort_unstable();
+        column.numbers.sort_by(|a, b| a.0.total_cmp(&b.0));
+        for value in &mut column.values {
+            value.keys.sort_unstable();
+        }
+        column
+    }
+
+    fn compare(&self, op: CompareOp, literal: &Literal) -> Vec<K> {
+        match (op, literal) {
+            (CompareOp::Eq, Literal::Number(n)) => {
+                let mut keys = self.number_range(|v| v < *n, |v| v <= *n);
+                keys.extend(self.text_equal(&n.to_string()));
+                sorted(keys)
+            }
+            (CompareOp::Eq, Literal::Text(text)) => self.text_equal(text).to_vec(),
+            (CompareOp::Ne, _) => difference(&self.present, &self.compare(CompareOp::Eq, literal)),
+            (CompareOp::Lt, Literal::Number(n)) => sorted(self.number_range(|_| false, |v| v < *n)),
+            (CompareOp::Le, Literal::Number(n)) => {
+                sorted(self.number_range(|_| false, |v| v <= *n))
+            }
+            (CompareOp::Gt, Literal::Number(n)) => sorted(self.number_range(|v| v <= *n, |_| true)),
+            (CompareOp::Ge, Literal::Number(n)) => sorted(self.number_range(|v| v < *n, |_| true)),
+            (
+                CompareOp::Lt | CompareOp::Le | CompareOp::Gt | CompareOp::Ge,
+                Literal::Text(text),
+            ) => {
+                let keep = |value: &str| match op {
+                    CompareOp::Lt => value < text.as_str(),
+                    CompareOp::Le => value <= text.as_str(),
+                    CompareOp::Gt => value > text.as_str(),
+                    _ => value >= text.as_str(),
+                };
+                self.matching(|value| keep(&value.text))
+            }
+            (CompareOp::Contains, Literal::Number(n)) => self.contains(&n.to_string()),
+            (CompareOp::Contains, Literal::Text(text)) => self.contains(&text.to_lowercase()),
+        }
+    }
+
+    /// Elements whose value contains `needle`, which must be lowercase.
+    fn contains(&self, needle: &str) -> Vec<K> {
+        self.matching(|value| value.lower.contains(needle))
+    }
+
+    fn matching(&self, mut f: impl FnMut(&ColumnValue<K>) -> bool) -> Vec<K> {
+        let keys = self
+            .values
+            .iter()
+            .filter(|value| f(value))
+            .flat_map(|value| value.keys.iter().copied())
+            .collect();
+        sorted(keys)
+    }
+
+    fn text_equal(&self, text: &str) -> &[K] {
+        self.by_text
+            .get(text)
+            .map_or(&[], |&index| &self.values[index].keys)
+    }
+
+    /// Elements with a number from the first not `before` up to the last `within`.
+    fn number_range(&self, before: impl Fn(f64) -> bool, within: impl Fn(f64) -> bool) -> Vec<K> {
+        let start = self.numbers.partition_point(|(v, _)| before(*v));
+        let end = self.numbers.partition_point(|(v, _)| within(*v));
+        self.numbers[start..end.max(start)]
+            .iter()
+            .map(|(_, k)| *k)
+            .collect()
+    }
+}
+
+fn sorted<K: Ord>(mut keys: Vec<K>) -> Vec<K> {
+    keys.sort_unstable();
+    keys.dedup();
+    keys
+}
+
+fn intersection<K: Copy + Ord>(a: &[K], b: &[K]) -> Vec<K> {
+    let (mut i, mut j, mut out) = (0, 0, Vec::new());
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            std::cmp::Ordering::Less => i += 1,
+            std::cmp::Ordering::Greater => j += 1,
+            std::cmp::Ordering::Equal => {
+                out.push(a[i]);
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    out
+}
+
+fn union<K: Copy + Ord>(a: &[K], b: &[K]) -> Vec<K> {
+    let (mut i, mut j, mut out) = (0, 0, Vec::with_capacity(a.len().max(b.len())));
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            std::cmp::Ordering::Less => {
+                out.push(a[i]);
+                i += 1;
+            }
+            std::cmp::Ordering::Greater => {
+                out.push(b[j]);
+                j += 1;
+            }
+            std::cmp::Ordering::Equal => {
+                out.push(a[i]);
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    out.extend_from_slice(&a[i..]);
+    out.extend_from_slice(&b[j..]);
+    out
+}
+
+fn difference<K: Copy + Ord>(a: &[K], b: &[K]) -> Vec<K> {
+    let mut j = 0;
+    a.iter()
+        .copied()
+        .filter(|k| {
+            while j < b.len() && b[j] < *k {
+                j += 1;
+            }
+            b.get(j) != Some(k)
+        })
+        .collect()
+}
+
+impl<K> std::default::Default for Columns<K> {
+    fn default() -> Self {
+        Self {
+            all: None,
+            columns: HashMap::new(),
+        }
+    }
+}
@@ -0,0 +1,311 @@
+/// A parsed search query, see [`Query::parse()`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum Query {
+    /// Elements whose label contains the text, ignoring case.
+    Text(String),
+    /// Elements whose `attribute` compares to `value` by `op`. Elements without
+    /// the attribute never match.
+    Compare {
+        attribute: String,
+        op: CompareOp,
+        value: Literal,
+    },
+    Not(Box<Query>),
+    And(Box<Query>, Box<Query>),
+    Or(Box<Query>, Box<Query>),
+}
+
+/// How an attribute is compared to a [`Literal`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum CompareOp {
+    /// `==`
+    Eq,
+    /// `!=`
+    Ne,
+    /// `<`
+    Lt,
+    /// `<=`
+    Le,
+    /// `>`
+    Gt,
+    /// `>=`
+    Ge,
+    /// `~`, the value contains the text, ignoring case.
+    Contains,
+}
+
+/// A value compared against in a [`Query`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum Literal {
+    Number(f64),
+    Text(String),
+}
+
+/// Errors which can occur while parsing a [`Query`]. Offsets are in bytes.
+#[derive(Clone, Debug, PartialEq)]
+pub enum QueryError {
+    /// The query ended where more was expected, eg. after `&&`.
+    UnexpectedEnd,
+    /// A token which does not fit where it is, eg. a second operator.
+    Unexpected { offset: usize, found: String },
+    /// A character which starts no token.
+    InvalidChar { offset: usize, found: char },
+    /// A quoted string missing its closing quote.
+    UnterminatedString { offset: usize },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Word(String),
+    Number(f64),
+    Str(String),
+    Op(CompareOp),
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+/// Recursive descent over the tokens of a query.
+struct Parser<'a> {
+    source: &'a str,
+    tokens: Vec<(usize, Token)>,
+    next: usize,
+}
+
+impl Query {
+    /// Parse a query.
+    ///
+    /// Text without any operator, quotes, or parentheses is searched for in
+    /// labels as a whole, spaces included. Otherwise the query is an expression:
+    /// - `attribute op value` compares an attribute, with `op` one of
+    ///   `== != < <= > >= ~`
+    /// - values are numbers, `"quoted strings"`, or bare words
+    /// - attribute names with spaces can be quoted, `"first name" == Ada`
+    /// - a lone word or quoted string searches labels, like plain text
+    /// - `!`, `&&`, `||`, and parentheses combine them, `&&` binding tighter
+    ///
+    /// ```text
+    /// degree > 10 && type == "service"
+    /// !(weight < 0.5) || label ~ cache
+    /// ```
+    pub fn parse(source: &str) -> Result<Self, QueryError> {
+        let text = source.trim();
+        if text.is_empty() {
+            return Err(QueryError::UnexpectedEnd);
+        }
+        if !text.contains(['=', '!', '<', '>', '~', '&', '|', '(', ')', '"']) {
+            return Ok(Self::Text(text.to_owned()));
+        }
+
+        let mut parser = Parser {
+            source,
+            tokens: tokenize(source)?,
+            next: 0,
+        };
+        let query = parser.or()?;
+        match parser.tokens.get(parser.next) {
+            None => Ok(query),
+            Some(&(offset, _)) => Err(parser.unexpected(offset)),
+        }
+    }
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.next).map(|(_, token)| token)
+    }
+
+    fn advance(&mut self) -> Result<(usize, Token), QueryError> {
+        let token = self
+            .tokens
+            .get(self.next)
+            .cloned()
+            .ok_or(QueryError::UnexpectedEnd)?;
+        self.next += 1;
+        Ok(token)
+    }
+
+    fn or(&mut self) -> Result<Query, QueryError> {
+        let mut query = self.and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.next += 1;
+            query = Query::Or(Box::new(query), Box::new(self.and()?));
+        }
+        Ok(query)
+    }
+
+    fn and(&mut self) -> Result<Query, QueryError> {
+        let mut query = self.unary()?;
+        while self.peek() == Some(&Token::And) {
+            self.next += 1;
+            query = Query::And(Box::new(query), Box::new(self.unary()?));
+        }
+        Ok(query)
+    }
+
+    fn unary(&mut self) -> Result<Query, QueryError> {
+        let (offset, token) = self.advance()?;
+        match token {
+            Token::Not => Ok(Query::Not(Box::new(self.unary()?))),
+            Token::Open => {
+                let query = self.or()?;
+                match self.advance()? {
+                    (_, Token::Close) => Ok(query),
+                    (offset, _) => Err(self.unexpected(offset)),
+                }
+            }
+            Token::Word(name) | Token::Str(name) => match self.peek() {
+                Some(&Token::Op(op)) => {
+                    self.next += 1;
+                    let value = self.literal()?;
+                    Ok(Query::Compare {
+                        attribute: name,
+                        op,
+                        value,
+                    })
+                }
+                _ => Ok(Query::Text(name)),
+            },
+            Token::Number(_) => Ok(Query::Text(self.source_of(self.next - 1).to_owned())),
+            _ => Err(self.unexpected(offset)),
+        }
+    }
+
+    fn literal(&mut self) -> Result<Literal, QueryError> {
+        match self.advance()? {
+            (_, Token::Number(n)) => Ok(Literal::Number(n)),
+            (_, Token::Word(text) | Token::Str(text)) => Ok(Literal::Text(text)),
+            (offset, _) => Err(self.unexpected(offset)),
+        }
+    }
+
+    /// Source text of the token at `index`.
+    fn source_of(&self, index: usize) -> &str {
+        let start = self.tokens[index].0;
+        let end = self
+            .tokens
+            .get(index + 1)
+            .map_or(self.source.len(), |(offset, _)| *offset);
+        self.source[start..end].trim_end()
+    }
+
+    fn unexpected(&self, offset: usize) -> QueryError {
+        let index = self.tokens.iter().position(|(o, _)| *o == offset).unwrap();
+        QueryError::Unexpected {
+            offset,
+            found: self.source_of(index).to_owned(),
+        }
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<(usize, Token)>, QueryError> {
+    let mut tokens = Vec::new();
+    let mut chars = source.char_indices().peekable();
+    while let Some(&(offset, c)) = chars.peek() {
+        chars.next();
+        let next = chars.peek().map(|(_, c)| *c);
+        let token = match (c, next) {
+            _ if c.is_whitespace() => continue,
+            ('=', Some('=')) => Token::Op(CompareOp::Eq),
+            ('!', Some('=')) => Token::Op(CompareOp::Ne),
+            ('<', Some('=')) => Token::Op(CompareOp::Le),
+            ('>', Some('=')) => Token::Op(CompareOp::Ge),
+            ('&', Some('&')) => Token::And,
+            ('|', Some('|')) => Token::Or,
+            ('<', _) => Token::Op(CompareOp::Lt),
+            ('>', _) => Token::Op(CompareOp::Gt),
+            ('~', _) => Token::Op(CompareOp::Contains),
+            ('!', _) => Token::Not,
+            ('(', _) => Token::Open,
+            (')', _) => Token::Close,
+            ('"', _) => {
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '"')) => break,
+                        Some((_, '\\')) => match chars.next() {
+                            Some((_, c)) => text.push(c),
+                            None => return Err(QueryError::UnterminatedString { offset }),
+                        },
+                        Some((_, c)) => text.push(c),
+                        None => return Err(QueryError::UnterminatedString { offset }),
+                    }
+                }
+                tokens.push((offset, Token::Str(text)));
+                continue;
+            }
+            _ if c.is_ascii_digit() || (c == '-' && next.is_some_and(|n| n.is_ascii_digit())) => {
+                let mut end = offset + c.len_utf8();
+                let mut prev = c;
+                while let Some(&(i, n)) = chars.peek() {
+                    let exponent_sign = matches!(n, '+' | '-') && matches!(prev, 'e' | 'E');
+                    if !(n.is_ascii_alphanumeric() || n == '.' || exponent_sign) {
+                        break;
+                    }
+                    chars.next();
+                    end = i + n.len_utf8();
+                    prev = n;
+                }
+                // eg. `3rd` is a word rather than a malformed number
+                let token = match source[offset..end].parse() {
+                    Ok(n) => Token::Number(n),
+                    Err(_) => Token::Word(source[offset..end].to_owned()),
+                };
+                tokens.push((offset, token));
+                continue;
+            }
+            _ if is_word_char(c) => {
+                let mut end = offset + c.len_utf8();
+                while let Some(&(i, n)) = chars.peek()
+                    && is_word_char(n)
+                {
+                    chars.next();
+                    end = i + n.len_utf8();
+                }
+                tokens.push((offset, Token::Word(source[offset..end].to_owned())));
+                continue;
+            }
+            _ => return Err(QueryError::InvalidChar { offset, found: c }),
+        };
+        // two-character operators consume their second character here
+        if matches!(
+            token,
+            Token::And
+                | Token::Or
+                | Token::Op(CompareOp::Eq | CompareOp::Ne | CompareOp::Le | CompareOp::Ge)
+        ) {
+            chars.next();
+        }
+        tokens.push((offset, token));
+    }
+    Ok(tokens)
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | ':' | '/' | '@' | '#')
+}
+
+impl std::fmt::Display for QueryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnexpectedEnd => write!(f, "Unexpected end of query"),
+            Self::Unexpected { offset, found } => {
+                write!(f, "Unexpected '{found}' at position {}", offset + 1)
+            }
+            Self::InvalidChar { offset, found } => {
+                write!(f, "Invalid character '{found}' at position {}", offset + 1)
+            }
+            Self::UnterminatedString { offset } => {
+                write!(
+                    f,
+                    "Missing closing quote for the string at position {}",
+                    offset + 1
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for QueryError {}
@@ -0,0 +1,230 @@
+//! # Graph Selection
+//!
+//! A [`Selection`] is the set of nodes and edges the user picked, eg. by clicking
+//! them or dragging a rectangle around them in the scene (see
+//! [`Picker`](crate::subsystems::scene::Picker)). Subsystems reacting to the
+//! selection either:
+//! - [`subscribe`](Selection::subscribe) to receive every [`SelectionChange`], or
+//! - poll [`Selection::generation()`], which increases with each change.
+//!
+//! ```ignore
+//! let mut selection = Selection::new();
+//! selection.subscribe(|change| println!("{} nodes added", change.added_nodes.len()));
+//!
+//! selection.select_node(node, SelectMode::Replace);
+//! selection.select_node(other, SelectMode::Toggle);
+//! assert!(selection.contains_node(other));
+//! ```
+
+use std::collections::BTreeSet;
+
+use crate::graph::{EdgeId, Graph, NodeId};
+
+/// How picked elements are combined with the current selection.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SelectMode {
+    /// Select only the picked elements.
+    #[default]
+    Replace,
+    /// Add the picked elements to the selection, eg. while holding Shift.
+    Add,
+    /// Flip whether each picked element is selected, eg. while holding Ctrl.
+    Toggle,
+}
+
+/// Identifies a listener registered with [`Selection::subscribe()`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ListenerId(u64);
+
+/// A callback registered with [`Selection::subscribe()`].
+type Listener = Box<dyn FnMut(&SelectionChange)>;
+
+/// The elements added to and removed from a [`Selection`] by a single change.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SelectionChange {
+    pub added_nodes: Vec<NodeId>,
+    pub removed_nodes: Vec<NodeId>,
+    pub added_edges: Vec<EdgeId>,
+    pub removed_edges: Vec<EdgeId>,
+}
+
+/// The selected nodes and edges of a graph, broadcasting each change.
+#[derive(Default)]
+pub struct Selection {
+    nodes: BTreeSet<NodeId>,
+    edges: BTreeSet<EdgeId>,
+    /// Increased by every change, see [`Self::generation()`].
+    generation: u64,
+    listeners: Vec<(ListenerId, Listener)>,
+    next_listener: u64,
+}
+
+impl Selection {
+    /// Create an empty selection.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Selected nodes, in id order.
+    pub fn nodes(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.nodes.iter().copied()
+    }
+
+    /// Selected edges, in id order.
+    pub fn edges(&self) -> impl Iterator<Item = EdgeId> + '_ {
+        self.edges.iter().copied()
+    }
+
+    /// Number of selected nodes.
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Number of selected edges.
+    pub fn edge_count(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// Returns `true` if nothing is selected.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty() && self.edges.is_empty()
+    }
+
+    pub fn contains_node(&self, node: NodeId) -> bool {
+        self.nodes.contains(&node)
+    }
+
+    pub fn contains_edge(&self, edge: EdgeId) -> bool {
+        self.edges.contains(&edge)
+    }
+
+    /// Counter increased by every change, so pollers can tell whether the
+    /// selection changed since they last looked.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Call `listener` with every future change, until [`Self::unsubscribe()`]d.
+    pub fn subscribe(&mut self, listener: impl FnMut(&SelectionChange) + 'static) -> ListenerId {
+        let id = ListenerId(self.next_listener);
+        self.next_listener += 1;
+        self.listeners.push((id, Box::new(listener)));
+        id
+    }
+
+    /// Stop calling the listener `id`. Returns `false` if it was not subscribed.
+    pub fn unsubscribe(&mut self, id: ListenerId) -> bool {
+        let len = self.listeners.len();
+        self.listeners.retain(|(listener, _)| *listener != id);
+        self.listeners.len() != len
+    }
+
+    /// Combine `nodes` and `edges` with the selection according to `mode`.
+    pub fn select(
+        &mut self,
+        nodes: impl IntoIterator<Item = NodeId>,
+        edges: impl IntoIterator<Item = EdgeId>,
+        mode: SelectMode,
+    ) {
+        let nodes: BTreeSet<_> = nodes.into_iter().collect();
+        let edges: BTreeSet<_> = edges.into_iter().collect();
+        let mut change = SelectionChange::default();
+        combine(
+            &mut self.nodes,
+            nodes,
+            mode,
+            &mut change.added_nodes,
+            &mut change.removed_nodes,
+        );
+        combine(
+            &mut self.edges,
+            edges,
+            mode,
+            &mut change.added_edges,
+            &mut change.removed_edges,
+        );
+        self.broadcast(change);
+    }
+
+    /// Combine a single node with the selection according to `mode`.
+    pub fn select_node(&mut self, node: NodeId, mode: SelectMode) {
+        self.select([node], [], mode);
+    }
+
+    /// Combine a single edge with the selection according to `mode`.
+    pub fn select_edge(&mut self, edge: EdgeId, mode: SelectMode) {
+        self.select([], [edge], mode);
+    }
+
+    /// Deselect everything.
+    pub fn clear(&mut self) {
+        self.select([], [], SelectMode::Replace);
+    }
+
+    /// Deselect the elements which are no longer part of `graph`, eg. after an edit.
+    pub fn retain_in(&mut self, graph: &Graph) {
+        let change = SelectionChange {
+            removed_nodes: take_if(&mut self.nodes, |&node| !graph.contains_node(node)),
+            removed_edges: take_if(&mut self.edges, |&edge| !graph.contains_edge(edge)),
+            ..Default::default()
+        };
+        self.broadcast(change);
+    }
+
+    /// Record `change` and hand it to every listener, unless nothing changed.
+    fn broadcast(&mut self, change: SelectionChange) {
+        if change.is_empty() {
+            return;
+        }
+        self.generation += 1;
+        for (_, listener) in &mut self.listeners {
+            listener(&change);
+        }
+    }
+}
+
+impl SelectionChange {
+    /// Returns `true` if nothing was added or removed.
+    pub fn is_empty(&self) -> bool {
+        self.added_nodes.is_empty()
+            && self.removed_nodes.is_empty()
+            && self.added_edges.is_empty()
+            && self.removed_edges.is_empty()
+    }
+}
+
+/// Combine `picked` into `set` according to `mode`, recording the differences.
+fn combine<T: Copy + Ord>(
+    set: &mut BTreeSet<T>,
+    picked: BTreeSet<T>,
+    mode: SelectMode,
+    added: &mut Vec<T>,
+    removed: &mut Vec<T>,
+) {
+    match mode {
+        SelectMode::Replace => {
+            removed.extend(take_if(set, |item| !picked.contains(item)));
+            added.extend(picked.into_iter().filter(|&item| set.insert(item)));
+        }
+        SelectMode::Add => added.extend(picked.into_iter().filter(|&item| set.insert(item))),
+        SelectMode::Toggle => {
+            for item in picked {
+                if set.remove(&item) {
+                    removed.push(item);
+                } else {
+                    set.insert(item);
+                    added.push(item);
+                }
+            }
+        }
+    }
+}
+
+/// Remove and return the items of `set` matching `predicate`.
+fn take_if<T: Copy + Ord>(set: &mut BTreeSet<T>, predicate: impl Fn(&T) -> bool) -> Vec<T> {
+    let taken: Vec<T> = set.iter().filter(|item| predicate(item)).copied().collect();
+    for item in &taken {
+        set.remove(item);
+    }
+    taken
+}
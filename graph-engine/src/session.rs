@@ -0,0 +1,473 @@
+//! # Workspace Sessions
+//!
+//! A [`Session`] captures the whole workspace: every open document with its
+//! positions, pinned nodes, filters, edge routing, camera view, and bookmarks,
+//! the style,
+//! which panels are open where, and the settings of the last image export. It is saved as a versioned JSON document,
+//! either on request through a [`WorkspaceDialog`] or periodically to the
+//! [`AUTOSAVE_FILE`], which survives a crash so the workspace can be recovered.
+//!
+//! Documents saved to a file are read from it again on restore. Untitled
+//! documents and those with unsaved changes carry their graph in the session,
+//! in the [`json`](crate::io::json) format.
+//!
+//! ```ignore
+//! let mut session = Session::capture(&mut ctx);
+//! session.layout = ui.save_layout();
+//! session.save(&path)?;
+//!
+//! Session::load(&path)?.restore(&mut ctx);
+//! ```
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::bookmarks::{Bookmark, Bookmarks};
+use crate::context::EngineContext;
+use crate::filter::Filter;
+use crate::graph::{EdgeId, Graph, NodeId};
+use crate::i18n::tr;
+use crate::imgui::Ui;
+use crate::io::{self, json};
+use crate::layout::Positions;
+use crate::routing::EdgeRouting;
+use crate::style::{Style, StyleSheet};
+use crate::subsystems::export::ExportOptions;
+
+/// Version of the [`Session`] document written by this build.
+pub const SESSION_VERSION: u32 = 1;
+
+/// Name of the autosaved session inside the config directory.
+pub const AUTOSAVE_FILE: &str = "autosave.json";
+
+/// How often the workspace is autosaved.
+pub const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Name of the session offered by a [`WorkspaceDialog`] by default.
+pub const WORKSPACE_FILE: &str = "workspace.json";
+
+/// The saved workspace, see the [module documentation](self).
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Session {
+    /// Document version, see [`SESSION_VERSION`].
+    pub version: u32,
+    /// The open documents, in tab order.
+    pub documents: Vec<DocumentSession>,
+    /// Index of the active document.
+    pub active: usize,
+    pub style: StyleSheet,
+    /// Open/closed state of each named panel.
+    pub panels: BTreeMap<String, bool>,
+    /// imgui's settings (window positions, sizes, dock layout) in ini format.
+    pub layout: String,
+    /// The settings of the last image export, for the application to offer
+    /// again.
+    pub export: Option<ExportOptions>,
+}
+
+/// One open document of a [`Session`].
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DocumentSession {
+    /// The file the document was opened from or saved to, if any.
+    pub path: Option<PathBuf>,
+    /// The graph in the JSON format, if it has no file or unsaved changes.
+    pub graph: Option<Value>,
+    /// Position of each node, in the graph's node order.
+    pub positions: Vec<Option<[f32; 2]>>,
+    /// Pinned nodes, as indices into the graph's node order.
+    pub pinned: Vec<usize>,
+    /// Whether the force-directed layout was paused.
+    pub paused: bool,
+    pub filters: Vec<Filter>,
+    /// How the edges are drawn.
+    pub edges: EdgeRouting,
+    /// The camera's center and zoom.
+    pub view: Option<([f32; 2], f32)>,
+    pub bookmarks: Vec<BookmarkSession>,
+}
+
+/// A [`Bookmark`] of a [`DocumentSession`], with its selection as indices into
+/// the graph's node and edge orders.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BookmarkSession {
+    pub name: String,
+    /// The camera's center and zoom.
+    pub view: ([f32; 2], f32),
+    pub filters: Vec<Filter>,
+    pub nodes: Vec<usize>,
+    pub edges: Vec<usize>,
+}
+
+/// Errors which can occur while loading or saving a [`Session`].
+#[derive(Debug)]
+pub enum SessionError {
+    /// The session could not be read or written.
+    Io(std::io::Error),
+    /// The session is not valid.
+    Parse(serde_json::Error),
+    /// The session was written by a newer build.
+    UnsupportedVersion(u32),
+}
+
+/// Whether a [`WorkspaceDialog`] saves or opens a workspace.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum WorkspaceAction {
+    Save,
+    Open,
+}
+
+/// Modal dialog asking for the file to save a workspace to, or open one from.
+///
+/// Typical usage, each frame while the dialog exists:
+/// ```ignore
+/// match dialog.draw(ui) {
+///     WorkspaceOutcome::Open => {}
+///     WorkspaceOutcome::Cancelled => self.dialog = None,
+///     WorkspaceOutcome::Confirmed(path) => { /* save or open the session */ }
+/// }
+/// ```
+pub struct WorkspaceDialog {
+    action: WorkspaceAction,
+    path: String,
+    /// Whether the popup was opened yet.
+    opened: bool,
+}
+
+/// What happened in a [`WorkspaceDialog`] during a frame.
+pub enum WorkspaceOutcome {
+    /// The dialog is still open.
+    Open,
+    /// The user closed the dialog.
+    Cancelled,
+    /// The user confirmed the file.
+    Confirmed(PathBuf),
+}
+
+impl Session {
+    /// Capture the documents and style of `ctx`. The panels, layout, and
+    /// export settings are left empty, for the application to fill in.
+    pub fn capture(ctx: &mut EngineContext) -> Self {
+        ctx.force.sync_positions(&mut ctx.positions);
+        let documents = (0..ctx.documents.documents().len())
+            .map(|index| {
+                let dirty = ctx.is_document_dirty(index);
+                let path = ctx.documents.documents()[index].path().map(Path::to_owned);
+                match ctx.documents.stash(index) {
+                    Some(stash) => DocumentSession {
+                        paused: stash.paused,
+                        filters: stash.filters.filters().to_vec(),
+                        view: stash.view,
+                        edges: stash.routes.settings.clone(),
+                        ..DocumentSession::capture(
+                            path,
+                            dirty,
+                            &stash.graph,
+                            &stash.positions,
+                            &stash.pinned,
+                            &stash.bookmarks,
+                        )
+                    },
+                    None => DocumentSession {
+                        paused: ctx.force.is_paused(),
+                        filters: ctx.filters.filters().to_vec(),
+                        view: ctx.camera.view(),
+                        edges: ctx.routes.settings.clone(),
+                        ..DocumentSession::capture(
+                            path,
+                            dirty,
+                            &ctx.graph,
+                            &ctx.positions,
+                            &ctx.force.pinned().collect::<Vec<_>>(),
+                            &ctx.bookmarks,
+                        )
+                    },
+                }
+            })
+            .collect();
+        Self {
+            version: SESSION_VERSION,
+            documents,
+            active: ctx.documents.active(),
+            style: ctx.style.sheet().clone(),
+            panels: BTreeMap::new(),
+            layout: String::new(),
+            export: None,
+        }
+    }
+
+    /// Read a session from `path`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SessionError> {
+        let text = std::fs::read_to_string(path)?;
+        let session: Self = serde_json::from_str(&text)?;
+        if session.version > SESSION_VERSION {
+            return Err(SessionError::UnsupportedVersion(session.version));
+        }
+        Ok(session)
+    }
+
+    /// Write the session to `path`.
+    ///
+    /// The document is written to a temporary file first and then renamed over
+    /// the previous one, so a crash mid-write never leaves a truncated session.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), SessionError> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent()
+            && !dir.as_os_str().is_empty()
+        {
+            std::fs::create_dir_all(dir)?;
+        }
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_string(self)?)?;
+        std::fs::rename(tmp, path)?;
+        Ok(())
+    }
+
+    /// Replace the documents and style of `ctx` with the session's, discarding
+    /// unsaved changes. Documents which cannot be read are skipped, with a
+    /// warning in the log.
+    pub fn restore(self, ctx: &mut EngineContext) {
+        ctx.documents.remove_others();
+        ctx.close_document(0);
+        ctx.style = Style::new(self.style);
+
+        let mut active = 0;
+        let mut restored = 0;
+        for (index, document) in self.documents.into_iter().enumerate() {
+            let name = document
+                .path
+                .as_deref()
+                .map_or("an untitled document".into(), |path| {
+                    format!("'{}'", path.display())
+                });
+            match document.restore(ctx) {
+                Ok(()) => {
+                    if index == self.active {
+                        active = restored;
+                    }
+                    restored += 1;
+                }
+                Err(e) => ctx.log.warn(format!("Failed to restore {name}: {e}")),
+            }
+        }
+        if active < restored {
+            ctx.switch_document(active);
+        }
+        ctx.style.resolve(&ctx.graph);
+    }
+}
+
+impl DocumentSession {
+    /// Capture a document's graph, if it needs to be, where its nodes are,
+    /// and its bookmarks. The rest of its state is left to the caller.
+    fn capture(
+        path: Option<PathBuf>,
+        dirty: bool,
+        graph: &Graph,
+        positions: &Positions,
+        pinned: &[NodeId],
+        bookmarks: &Bookmarks,
+    ) -> Self {
+        let nodes: Vec<NodeId> = graph.nodes().collect();
+        let edges: Vec<EdgeId> = graph.edges().collect();
+        let bookmarks = bookmarks
+            .list()
+            .iter()
+            .map(|bookmark| BookmarkSession {
+                name: bookmark.name.clone(),
+                view: bookmark.view,
+                filters: bookmark.filters.clone(),
+                nodes: indices(&nodes, &bookmark.nodes),
+                edges: indices(&edges, &bookmark.edges),
+            })
+            .collect();
+        Self {
+            bookmarks,
+            graph: (dirty || path.is_none()).then(|| json::to_value(graph)),
+            path,
+            positions: nodes.iter().map(|&node| positions.get(node)).collect(),
+            pinned: pinned
+                .iter()
+                .filter_map(|node| nodes.iter().position(|n| n == node))
+                .collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Open the document in `ctx`, as its active one.
+    fn restore(self, ctx: &mut EngineContext) -> Result<(), String> {
+        let dirty = self.graph.is_some() && self.path.is_some();
+        let graph = match (self.graph, &self.path) {
+            (Some(doc), _) => json::from_value(doc).map_err(|e| e.to_string())?,
+            (None, Some(path)) => io::load(path).map_err(|e| e.to_string())?,
+            (None, None) => Graph::default(),
+        };
+        ctx.open_document(graph, self.path);
+        if dirty {
+            ctx.documents.active_document_mut().set_dirty();
+        }
+
+        let nodes: Vec<NodeId> = ctx.graph.nodes().collect();
+        for (&node, point) in nodes.iter().zip(self.positions) {
+            if let Some(point) = point {
+                ctx.positions.set(node, point);
+            }
+        }
+        for index in self.pinned {
+            if let Some(&node) = nodes.get(index) {
+                ctx.force.set_pinned(node, true);
+            }
+        }
+        ctx.force.set_paused(self.paused);
+        *ctx.filters.filters_mut() = self.filters;
+        ctx.routes.settings = self.edges;
+        if let Some((center, zoom)) = self.view {
+            ctx.camera.set_view(center, zoom);
+        }
+        let edges: Vec<EdgeId> = ctx.graph.edges().collect();
+        ctx.bookmarks = Bookmarks::new(
+            self.bookmarks
+                .into_iter()
+                .map(|bookmark| Bookmark {
+                    name: bookmark.name,
+                    view: bookmark.view,
+                    filters: bookmark.filters,
+                    nodes: elements(&nodes, &bookmark.nodes),
+                    edges: elements(&edges, &bookmark.edges),
+                })
+                .collect(),
+        );
+        ctx.refresh();
+        Ok(())
+    }
+}
+
+/// Indices of `ids` in `all`, both in id order. Ids not in `all` are left out.
+fn indices<T: Ord>(all: &[T], ids: &[T]) -> Vec<usize> {
+    ids.iter()
+        .filter_map(|id| all.binary_search(id).ok())
+        .collect()
+}
+
+/// The ids at `indices` of `all`, the reverse of [`indices()`].
+fn elements<T: Copy>(all: &[T], indices: &[usize]) -> Vec<T> {
+    indices
+        .iter()
+        .filter_map(|&i| all.get(i).copied())
+        .collect()
+}
+
+impl WorkspaceDialog {
+    /// Create a dialog for `action`, suggesting `path`.
+    pub fn new(action: WorkspaceAction, path: &Path) -> Self {
+        Self {
+            action,
+            path: path.display().to_string(),
+            opened: false,
+        }
+    }
+
+    /// Whether the dialog saves or opens a workspace.
+    pub fn action(&self) -> WorkspaceAction {
+        self.action
+    }
+
+    /// Draw the dialog, returning whether the user confirmed or cancelled.
+    pub fn draw(&mut self, ui: &Ui) -> WorkspaceOutcome {
+        let title = match self.action {
+            WorkspaceAction::Save => "Save Workspace",
+            WorkspaceAction::Open => "Open Workspace",
+        };
+        if !self.opened {
+            ui.open_popup(title);
+            self.opened = true;
+        }
+
+        let mut open = true;
+        let outcome = ui
+            .modal_popup_config(title)
+            .opened(&mut open)
+            .always_auto_resize(true)
+            .build(|| {
+                ui.set_next_item_width(400.0);
+                let entered = ui
+                    .input_text(tr("File"), &mut self.path)
+                    .enter_returns_true(true)
+                    .build();
+                let path = PathBuf::from(self.path.trim());
+                let exists = path.is_file();
+                if self.action == WorkspaceAction::Save && exists {
+                    ui.text_disabled(tr("The file will be replaced"));
+                }
+                let valid = match self.action {
+                    WorkspaceAction::Save => !self.path.trim().is_empty(),
+                    WorkspaceAction::Open => exists,
+                };
+                let mut outcome = WorkspaceOutcome::Open;
+                ui.enabled(valid, || {
+                    let label = match self.action {
+                        WorkspaceAction::Save => "Save",
+                        WorkspaceAction::Open => "Open",
+                    };
+                    if ui.button(label) || (entered && valid) {
+                        outcome = WorkspaceOutcome::Confirmed(path);
+                    }
+                });
+                ui.same_line();
+                if ui.button(tr("Cancel")) {
+                    outcome = WorkspaceOutcome::Cancelled;
+                }
+                if !matches!(outcome, WorkspaceOutcome::Open) {
+                    ui.close_current_popup();
+                }
+                outcome
+            });
+
+        match outcome {
+            Some(WorkspaceOutcome::Open) if !open => WorkspaceOutcome::Cancelled,
+            Some(outcome) => outcome,
+            None => WorkspaceOutcome::Cancelled,
+        }
+    }
+}
+
+impl From<std::io::Error> for SessionError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl From<serde_json::Error> for SessionError {
+    fn from(value: serde_json::Error) -> Self {
+        Self::Parse(value)
+    }
+}
+
+impl std::fmt::Display for SessionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "Failed to access session: {e}"),
+            Self::Parse(e) => write!(f, "Failed to parse session: {e}"),
+            Self::UnsupportedVersion(v) => write!(
+                f,
+                "Session version {v} is newer than supported version {SESSION_VERSION}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SessionError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Parse(e) => Some(e),
+            Self::UnsupportedVersion(_) => None,
+        }
+    }
+}
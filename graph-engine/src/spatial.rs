@@ -0,0 +1,567 @@
+//! # Spatial Index
+//!
+//! A [`SpatialIndex`] keeps the nodes and edges of a graph in [`QuadTree`]s by
+//! where they are drawn, so that finding what lies in a region takes time
+//! proportional to the answer rather than to the size of the graph:
+//! - picking what is under the cursor, see [`Picker`](crate::subsystems::scene::Picker)
+//! - culling what lies outside the viewport before drawing
+//! - nearest-neighbor queries, eg. "find the node near the cursor"
+//!
+//! The index follows a [`Positions`] table incrementally: [`SpatialIndex::sync()`]
+//! only moves the elements whose position changed, and returns immediately if
+//! none did, so it can be called every frame while a layout runs. Elements
+//! added to or removed from the graph are picked up from its
+//! [changes](Graph::subscribe), without scanning it.
+//!
+//! ```ignore
+//! let mut index = SpatialIndex::new();
+//! index.sync(&graph, &positions);
+//!
+//! let (min, max) = camera.visible_rect();
+//! index.nodes_in(min, max, |node, _| draw(node));
+//! let hovered = index.nearest_node(cursor, 10.0);
+//! ```
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use crate::graph::{ChangeFilter, EdgeId, Graph, GraphChange, NodeId, Subscription};
+use crate::layout::Positions;
+
+/// Number of items a leaf holds before it is split.
+const LEAF_CAPACITY: usize = 8;
+/// Cells are not split below the root's size divided by `2^MAX_DEPTH`, which
+/// bounds the tree's depth when many items share the same position.
+const MAX_DEPTH: i32 = 20;
+/// No cell index, eg. for the root's parent.
+const NONE: usize = usize::MAX;
+
+/// An axis-aligned rectangle, as `(min, max)` corners.
+pub type Rect = ([f32; 2], [f32; 2]);
+
+/// A key stored in a [`QuadTree`], with a dense index to track where it is.
+pub trait SpatialKey: Copy + Eq {
+    fn index(self) -> usize;
+}
+
+/// A loose quadtree of keyed rectangles, supporting incremental updates.
+///
+/// Each item is stored in the smallest cell fully containing its rectangle,
+/// so points always reach a leaf while long edges stay near the root. The tree
+/// grows to fit items inserted outside of it, and collapses cells emptied by
+/// removals.
+#[derive(Clone, Debug)]
+pub struct QuadTree<K> {
+    cells: Vec<Cell<K>>,
+    /// Indices of unused slots in `cells`.
+    free: Vec<usize>,
+    root: usize,
+    /// Cell holding each key and its slot in the cell's items, indexed by
+    /// [`SpatialKey::index()`].
+    locations: Vec<(usize, usize)>,
+    len: usize,
+}
+
+/// The nodes and edges of a graph, indexed by where they are drawn.
+#[derive(Debug, Default)]
+pub struct SpatialIndex {
+    nodes: QuadTree<NodeId>,
+    /// Edges by the bounding box of their endpoints and bends.
+    edges: QuadTree<EdgeId>,
+    /// [`Graph::generation()`] and [`Positions::generation()`] the index was
+    /// last synced with.
+    synced: Option<(u64, u64)>,
+    /// Nodes and edges added to and removed from the graph indexed.
+    changes: Option<Subscription>,
+}
+
+#[derive(Clone, Debug)]
+struct Cell<K> {
+    min: [f32; 2],
+    size: f32,
+    parent: usize,
+    /// Quadrants in `[top-left, top-right, bottom-left, bottom-right]` order.
+    children: Option<[usize; 4]>,
+    items: Vec<(K, Rect)>,
+}
+
+/// A cell waiting to be searched by [`QuadTree::nearest()`], closest first.
+struct Candidate {
+    distance: f32,
+    cell: usize,
+}
+
+impl<K: SpatialKey> QuadTree<K> {
+    /// Create an empty tree.
+    pub fn new() -> Self {
+        Self {
+            cells: vec![Cell::new([0.0, 0.0], 1.0, NONE)],
+            free: Vec::new(),
+            root: 0,
+            locations: Vec::new(),
+            len: 0,
+        }
+    }
+
+    /// Number of items in the tree.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Bytes the tree holds on the heap.
+    pub fn heap_size(&self) -> usize {
+        let items: usize = self
+            .cells
+            .iter()
+            .map(|cell| cell.items.capacity() * size_of::<(K, Rect)>())
+            .sum();
+        self.cells.capacity() * size_of::<Cell<K>>()
+            + self.free.capacity() * size_of::<usize>()
+            + self.locations.capacity() * size_of::<(usize, usize)>()
+            + items
+    }
+
+    /// Returns `true` if the tree holds no items.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns `true` if `key` is in the tree.
+    pub fn contains(&self, key: K) -> bool {
+        self.locations
+            .get(key.index())
+            .is_some_and(|&(cell, _)| cell != NONE)
+    }
+
+    /// Remove every item.
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Store `key` covering `rect`, replacing its previous rectangle if any.
+    ///
+    /// Cheap if the item stays within its cell, eg. a node moving a little.
+    pub fn insert(&mut self, key: K, rect: Rect) {
+        if let Some(&(cell, slot)) = self.locations.get(key.index())
+            && cell != NONE
+        {
+            if self.cells[cell].contains(rect) && self.child_for(cell, rect).is_none() {
+                self.cells[cell].items[slot].1 = rect;
+                return;
+            }
+            self.remove(key);
+        }
+
+        if self.len == 0 {
+            // restart around the first item rather than growing towards it
+            let size = (rect.1[0] - rect.0[0]).max(rect.1[1] - rect.0[1]).max(1.0);
+            self.clear();
+            self.cells[self.root].min = rect.0;
+            self.cells[self.root].size = size * 2.0;
+        }
+        while !self.cells[self.root].contains(rect) {
+            self.grow(rect);
+        }
+
+        let mut cell = self.root;
+        while let Some(child) = self.child_for(cell, rect) {
+            cell = child;
+        }
+        self.push(cell, key, rect);
+        self.len += 1;
+
+        if self.cells[cell].children.is_none() && self.cells[cell].items.len() > LEAF_CAPACITY {
+            self.split(cell);
+        }
+    }
+
+    /// Remove `key`, returning its rectangle if it was in the tree.
+    pub fn remove(&mut self, key: K) -> Option<Rect> {
+        let (cell, slot) = *self.locations.get(key.index())?;
+        if cell == NONE {
+            return None;
+        }
+        self.locations[key.index()] = (NONE, 0);
+        let items = &mut self.cells[cell].items;
+        let (_, rect) = items.swap_remove(slot);
+        if let Some(&(moved, _)) = items.get(slot) {
+            self.locations[moved.index()].1 = slot;
+        }
+        self.len -= 1;
+        self.collapse(cell);
+        Some(rect)
+    }
+
+    /// Rectangle `key` was stored with, if it is in the tree.
+    pub fn get(&self, key: K) -> Option<Rect> {
+        let (cell, slot) = *self.locations.get(key.index())?;
+        if cell == NONE {
+            return None;
+        }
+        Some(self.cells[cell].items[slot].1)
+    }
+
+    /// Call `f` with every item whose rectangle overlaps `rect`.
+    pub fn query(&self, rect: Rect, mut f: impl FnMut(K, Rect)) {
+        let mut stack = vec![self.root];
+        while let Some(index) = stack.pop() {
+            let cell = &self.cells[index];
+            if !overlaps(cell.bounds(), rect) {
+                continue;
+            }
+            for &(key, item) in &cell.items {
+                if overlaps(item, rect) {
+                    f(key, item);
+                }
+            }
+            if let Some(children) = cell.children {
+                stack.extend(children);
+            }
+        }
+    }
+
+    /// The item closest to `point` within `max_distance`, with its distance.
+    ///
+    /// Distance is measured to the closest point of each item's rectangle.
+    pub fn nearest(&self, point: [f32; 2], max_distance: f32) -> Option<(K, f32)> {
+        let mut best: Option<(K, f32)> = None;
+        let mut queue = BinaryHeap::from([Candidate {
+            distance: rect_distance(self.cells[self.root].bounds(), point),
+            cell: self.root,
+        }]);
+        while let Some(Candidate { distance, cell }) = queue.pop() {
+            let limit = best.map_or(max_distance, |(_, d)| d);
+            // cells contain their items, so nothing closer is left
+            if distance > limit {
+                break;
+            }
+            let cell = &self.cells[cell];
+            for &(key, rect) in &cell.items {
+                let distance = rect_distance(rect, point);
+                if distance <= best.map_or(max_distance, |(_, d)| d) {
+                    best = Some((key, distance));
+                }
+            }
+            for child in cell.children.into_iter().flatten() {
+                queue.push(Candidate {
+                    distance: rect_distance(self.cells[child].bounds(), point),
+                    cell: child,
+                });
+            }
+        }
+        best
+    }
+
+    /// Child of `cell` fully containing `rect`, if `cell` is split and one does.
+    fn child_for(&self, cell: usize, rect: Rect) -> Option<usize> {
+        self.cells[cell]
+            .children?
+            .into_iter()
+            .find(|&child| self.cells[child].contains(rect))
+    }
+
+    /// Add `key` to the items of `cell`, recording where it is.
+    fn push(&mut self, cell: usize, key: K, rect: Rect) {
+        let items = &mut self.cells[cell].items;
+        items.push((key, rect));
+        let location = (cell, items.len() - 1);
+        if self.locations.len() <= key.index() {
+            self.locations.resize(key.index() + 1, (NONE, 0));
+        }
+        self.locations[key.index()] = location;
+    }
+
+    fn alloc(&mut self, cell: Cell<K>) -> usize {
+        match self.free.pop() {
+            Some(index) => {
+                self.cells[index] = cell;
+                index
+            }
+            None => {
+                self.cells.push(cell);
+                self.cells.len() - 1
+            }
+        }
+    }
+
+    /// Double the root's size towards `rect`, making the old root a quadrant.
+    fn grow(&mut self, rect: Rect) {
+        let old = self.root;
+        let Cell { min, size, .. } = self.cells[old];
+        let left = rect.0[0] < min[0];
+        let up = rect.0[1] < min[1];
+        let new_min = [
+            if left { min[0] - size } else { min[0] },
+            if up { min[1] - size } else { min[1] },
+        ];
+
+        let root = self.alloc(Cell::new(new_min, size * 2.0, NONE));
+        let mut children = [NONE; 4];
+        for (quadrant, child) in children.iter_mut().enumerate() {
+            let (right, down) = (quadrant % 2 == 1, quadrant / 2 == 1);
+            if right == left && down == up {
+                *child = old;
+            } else {
+                let child_min = [
+                    new_min[0] + if right { size } else { 0.0 },
+                    new_min[1] + if down { size } else { 0.0 },
+                ];
+                *child = self.alloc(Cell::new(child_min, size, root));
+            }
+        }
+        self.cells[old].parent = root;
+        self.cells[root].children = Some(children);
+        self.root = root;
+    }
+
+    /// Give the leaf `cell` four children and move down the items fitting in them.
+    fn split(&mut self, cell: usize) {
+        let Cell { min, size, .. } = self.cells[cell];
+        if size <= self.cells[self.root].size / 2f32.powi(MAX_DEPTH) {
+            return;
+        }
+        let half = size * 0.5;
+        let mut children = [NONE; 4];
+        for (quadrant, child) in children.iter_mut().enumerate() {
+            let child_min = [
+                min[0] + half * (quadrant % 2) as f32,
+                min[1] + half * (quadrant / 2) as f32,
+            ];
+            *child = self.alloc(Cell::new(child_min, half, cell));
+        }
+        self.cells[cell].children = Some(children);
+
+        let items = std::mem::take(&mut self.cells[cell].items);
+        for (key, rect) in items {
+            let target = self.child_for(cell, rect).unwrap_or(cell);
+            self.push(target, key, rect);
+        }
+    }
+
+    /// Free the children of `cell` and its ancestors while they are all empty leaves.
+    fn collapse(&mut self, mut cell: usize) {
+        if self.cells[cell].children.is_none() {
+            cell = self.cells[cell].parent;
+        }
+        while cell != NONE {
+            let Some(children) = self.cells[cell].children else {
+                return;
+            };
+            let empty = children.iter().all(|&child| {
+                let child = &self.cells[child];
+                child.children.is_none() && child.items.is_empty()
+            });
+            if !empty {
+                return;
+            }
+            self.cells[cell].children = None;
+            self.free.extend(children);
+            cell = self.cells[cell].parent;
+        }
+    }
+}
+
+impl SpatialIndex {
+    /// Create an empty index.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bytes the index holds on the heap.
+    pub fn heap_size(&self) -> usize {
+        self.nodes.heap_size() + self.edges.heap_size()
+    }
+
+    /// Remove every node and edge, eg. when the graph is replaced.
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.edges.clear();
+        self.synced = None;
+        self.changes = None;
+    }
+
+    /// Bring the index up to date with `graph`'s `positions`.
+    ///
+    /// Returns immediately if neither `graph` nor `positions` changed since the
+    /// last sync. Nodes and edges added or removed since are indexed or dropped
+    /// one by one, and positions are only scanned if they changed. The index is
+    /// rebuilt from scratch if `graph` is another graph, eg. when a filter hid
+    /// some of it.
+    pub fn sync(&mut self, graph: &Graph, positions: &Positions) {
+        let key = (graph.generation(), positions.generation());
+        if self.synced == Some(key) {
+            return;
+        }
+        match self.changes.as_ref().and_then(|c| c.poll(graph)) {
+            Some(changes) => self.apply(graph, positions, &changes),
+            None => {
+                self.clear();
+                self.changes = Some(graph.subscribe(ChangeFilter::STRUCTURE));
+            }
+        }
+        let moved = self.synced.is_none_or(|(_, synced)| synced != key.1);
+        self.synced = Some(key);
+        if !moved {
+            return;
+        }
+
+        for node in graph.nodes() {
+            match positions.get(node) {
+                Some(point) if self.nodes.get(node) != Some((point, point)) => {
+                    self.nodes.insert(node, (point, point))
+                }
+                Some(_) => {}
+                None => _ = self.nodes.remove(node),
+            }
+        }
+        for edge in graph.edges() {
+            match edge_bounds(graph, positions, edge) {
+                Some(rect) if self.edges.get(edge) != Some(rect) => self.edges.insert(edge, rect),
+                Some(_) => {}
+                None => _ = self.edges.remove(edge),
+            }
+        }
+    }
+
+    /// Index or drop the elements added to or removed from `graph`.
+    fn apply(&mut self, graph: &Graph, positions: &Positions, changes: &[GraphChange]) {
+        for change in changes {
+            match *change {
+                GraphChange::NodeAdded(node) if graph.contains_node(node) => {
+                    if let Some(point) = positions.get(node) {
+                        self.nodes.insert(node, (point, point));
+                    }
+                }
+                GraphChange::NodeRemoved(node) => _ = self.nodes.remove(node),
+                GraphChange::EdgeAdded(edge) if graph.contains_edge(edge) => {
+                    if let Some(rect) = edge_bounds(graph, positions, edge) {
+                        self.edges.insert(edge, rect);
+                    }
+                }
+                GraphChange::EdgeRemoved(edge) => _ = self.edges.remove(edge),
+                GraphChange::Cleared => {
+                    self.nodes.clear();
+                    self.edges.clear();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// The index of nodes, by their position.
+    pub fn nodes(&self) -> &QuadTree<NodeId> {
+        &self.nodes
+    }
+
+    /// The index of edges, by the bounding box of their endpoints and bends.
+    pub fn edges(&self) -> &QuadTree<EdgeId> {
+        &self.edges
+    }
+
+    /// Call `f` with every node positioned inside the rectangle from `min` to
+    /// `max`, and its position.
+    pub fn nodes_in(&self, min: [f32; 2], max: [f32; 2], mut f: impl FnMut(NodeId, [f32; 2])) {
+        self.nodes
+            .query((min, max), |node, (point, _)| f(node, point));
+    }
+
+    /// Call `f` with every edge whose bounding box overlaps the rectangle from
+    /// `min` to `max`. The edge itself may still pass outside of the rectangle.
+    pub fn edges_in(&self, min: [f32; 2], max: [f32; 2], mut f: impl FnMut(EdgeId)) {
+        self.edges.query((min, max), |edge, _| f(edge));
+    }
+
+    /// The node closest to `point` within `max_distance`, with its distance.
+    pub fn nearest_node(&self, point: [f32; 2], max_distance: f32) -> Option<(NodeId, f32)> {
+        self.nodes.nearest(point, max_distance)
+    }
+}
+
+/// Bounding box of `edge`'s endpoints and bends, if both endpoints have a position.
+pub fn edge_bounds(graph: &Graph, positions: &Positions, edge: EdgeId) -> Option<Rect> {
+    let (source, target) = graph.endpoints(edge)?;
+    let (a, b) = (positions.get(source)?, positions.get(target)?);
+    let mut rect = (
+        [a[0].min(b[0]), a[1].min(b[1])],
+        [a[0].max(b[0]), a[1].max(b[1])],
+    );
+    for p in positions.bends(edge) {
+        rect = (
+            [rect.0[0].min(p[0]), rect.0[1].min(p[1])],
+            [rect.1[0].max(p[0]), rect.1[1].max(p[1])],
+        );
+    }
+    Some(rect)
+}
+
+impl<K> Cell<K> {
+    fn new(min: [f32; 2], size: f32, parent: usize) -> Self {
+        Self {
+            min,
+            size,
+            parent,
+            children: None,
+            items: Vec::new(),
+        }
+    }
+
+    fn bounds(&self) -> Rect {
+        (self.min, [self.min[0] + self.size, self.min[1] + self.size])
+    }
+
+    /// Returns `true` if `rect` lies entirely within the cell.
+    fn contains(&self, rect: Rect) -> bool {
+        let (min, max) = self.bounds();
+        rect.0[0] >= min[0] && rect.0[1] >= min[1] && rect.1[0] < max[0] && rect.1[1] < max[1]
+    }
+}
+
+fn overlaps(a: Rect, b: Rect) -> bool {
+    a.0[0] <= b.1[0] && b.0[0] <= a.1[0] && a.0[1] <= b.1[1] && b.0[1] <= a.1[1]
+}
+
+/// Distance from `point` to the closest point of `rect`, zero if inside.
+fn rect_distance(rect: Rect, point: [f32; 2]) -> f32 {
+    let dx = (rect.0[0] - point[0]).max(point[0] - rect.1[0]).max(0.0);
+    let dy = (rect.0[1] - point[1]).max(point[1] - rect.1[1]).max(0.0);
+    dx.hypot(dy)
+}
+
+impl SpatialKey for NodeId {
+    fn index(self) -> usize {
+        NodeId::index(self)
+    }
+}
+
+impl SpatialKey for EdgeId {
+    fn index(self) -> usize {
+        EdgeId::index(self)
+    }
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.distance == other.distance
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    /// Reversed, so the max-heap pops the closest cell first.
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.distance.total_cmp(&self.distance)
+    }
+}
+
+impl<K: SpatialKey> std::default::Default for QuadTree<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,311 @@
+//! # Graph Statistics
+//!
+//! A summary of the shape of a graph:
+//! - its density, the share of possible edges it has
+//! - an estimate of its diameter, the longest shortest path in hops
+//! - the distribution of node degrees and of connected component sizes, in
+//!   bins doubling in width so they plot on log-log scales
+//! - the distribution of each numeric node attribute
+//!
+//! Computing them takes a pass over the whole graph and a few traversals, so
+//! the [`StatisticsPanel`] only does it while open, and again once
+//! [`Graph::generation()`] changes.
+//!
+//! ```ignore
+//! let stats = GraphStats::compute(ctx.visible_graph());
+//! println!("density {:.3}, diameter >= {}", stats.density, stats.diameter);
+//! ```
+
+use std::collections::BTreeMap;
+
+use crate::algo::{self, Bfs, Direction};
+use crate::context::EngineContext;
+use crate::graph::{Graph, NodeId};
+use crate::i18n::tr;
+use crate::imgui::{TreeNodeFlags, Ui};
+use crate::subsystems::panels::Panel;
+
+/// Number of bins of the attribute histograms.
+pub const ATTRIBUTE_BINS: usize = 20;
+/// Number of double sweeps [`diameter()`] runs from the farthest node found.
+const DIAMETER_SWEEPS: usize = 4;
+/// Height of the plots, in points.
+const PLOT_HEIGHT: f32 = 100.0;
+
+/// Statistics of a graph, see the [module documentation](self).
+#[derive(Clone, Debug, Default)]
+pub struct GraphStats {
+    pub nodes: usize,
+    pub edges: usize,
+    /// Edges over the number of possible ones, without loops or parallel edges.
+    pub density: f64,
+    /// Lower bound of the diameter of the largest component, in hops.
+    pub diameter: usize,
+    /// Nodes without any edge.
+    pub isolated: usize,
+    /// Nodes by degree, the bin `i` holding degrees from `2^i` to `2^(i+1)-1`.
+    pub degrees: Vec<usize>,
+    /// Components by size, binned as [`Self::degrees`].
+    pub components: Vec<usize>,
+    pub component_count: usize,
+    /// Distribution of the numeric node attributes, by name.
+    pub attributes: BTreeMap<String, Histogram>,
+}
+
+/// Numbers of values in [`ATTRIBUTE_BINS`] bins of equal width.
+#[derive(Clone, Debug, Default)]
+pub struct Histogram {
+    /// Smallest and largest value.
+    pub min: f64,
+    pub max: f64,
+    pub counts: Vec<usize>,
+}
+
+/// Plots the [`GraphStats`] of the [visible graph](EngineContext::visible_graph).
+#[derive(Default)]
+pub struct StatisticsPanel {
+    /// Generation of the graph the stats were computed from.
+    computed: Option<(u64, GraphStats)>,
+    /// Attribute whose histogram shows.
+    attribute: String,
+}
+
+impl GraphStats {
+    /// Compute the statistics of `graph`, ignoring edge directions for the
+    /// components and diameter.
+    pub fn compute(graph: &Graph) -> Self {
+        let (nodes, edges) = (graph.node_count(), graph.edge_count());
+        let pairs = nodes.saturating_sub(1) as f64 * nodes as f64;
+        let density = match (pairs > 0.0, graph.is_directed()) {
+            (false, _) => 0.0,
+            (true, true) => edges as f64 / pairs,
+            (true, false) => 2.0 * edges as f64 / pairs,
+        };
+
+        let mut isolated = 0;
+        let mut degrees = Vec::new();
+        for node in graph.nodes() {
+            match graph.degree(node) {
+                0 => isolated += 1,
+                degree => add_to_log_bin(&mut degrees, degree),
+            }
+        }
+
+        let found = algo::connected_components(graph);
+        let mut components = Vec::new();
+        for members in found.iter() {
+            add_to_log_bin(&mut components, members.len());
+        }
+        let diameter = found
+            .iter()
+            .next()
+            .map_or(0, |largest| diameter(graph, largest[0]));
+
+        Self {
+            nodes,
+            edges,
+            density,
+            diameter,
+            isolated,
+            degrees,
+            components,
+            component_count: found.count(),
+            attributes: attribute_histograms(graph),
+        }
+    }
+}
+
+impl Histogram {
+    /// Bin `values`, returning `None` if there are none.
+    pub fn new(values: &[f64]) -> Option<Self> {
+        let min = values.iter().copied().reduce(f64::min)?;
+        let max = values.iter().copied().reduce(f64::max)?;
+        let mut counts = vec![0; ATTRIBUTE_BINS];
+        for value in values {
+            let bin = match max > min {
+                true => ((value - min) / (max - min) * ATTRIBUTE_BINS as f64) as usize,
+                false => 0,
+            };
+            counts[bin.min(ATTRIBUTE_BINS - 1)] += 1;
+        }
+        Some(Self { min, max, counts })
+    }
+
+    /// Width of each bin.
+    pub fn bin_width(&self) -> f64 {
+        (self.max - self.min) / ATTRIBUTE_BINS as f64
+    }
+}
+
+/// Estimate the diameter of the component of `start` with double sweeps: the
+/// farthest node from a node is likely at an end of a longest path, so the
+/// farthest node from it gives a distance close to the diameter, and never
+/// above it.
+pub fn diameter(graph: &Graph, start: NodeId) -> usize {
+    let farthest = |from| {
+        Bfs::new(graph, from, Direction::Both)
+            .last()
+            .map_or((from, 0), |visit| (visit.node, visit.depth))
+    };
+    let (mut node, mut best) = (start, 0);
+    for _ in 0..DIAMETER_SWEEPS {
+        let (next, depth) = farthest(node);
+        if depth <= best {
+            break;
+        }
+        (node, best) = (next, depth);
+    }
+    best
+}
+
+/// Count `value`, at least `1`, in the bin `floor(log2(value))` of `bins`.
+fn add_to_log_bin(bins: &mut Vec<usize>, value: usize) {
+    let bin = value.max(1).ilog2() as usize;
+    if bins.len() <= bin {
+        bins.resize(bin + 1, 0);
+    }
+    bins[bin] += 1;
+}
+
+/// Histograms of the node attributes of `graph` with a number for every value,
+/// by name.
+fn attribute_histograms(graph: &Graph) -> BTreeMap<String, Histogram> {
+    let mut values: BTreeMap<&str, Option<Vec<f64>>> = BTreeMap::new();
+    for attributes in graph.nodes().filter_map(|node| graph.node(node)) {
+        for (key, value) in attributes.iter() {
+            let numbers = values
+                .entry(key.as_str())
+                .or_insert_with(|| Some(Vec::new()));
+            match value.as_f64().filter(|v| v.is_finite()) {
+                Some(number) => {
+                    if let Some(numbers) = numbers {
+                        numbers.push(number);
+                    }
+                }
+                None => *numbers = None,
+            }
+        }
+    }
+    values
+        .into_iter()
+        .filter_map(|(key, numbers)| Some((key.to_string(), Histogram::new(&numbers?)?)))
+        .collect()
+}
+
+impl StatisticsPanel {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Plot `bins` of counts as the log of each count, with a line naming the
+    /// range of values of each bin below.
+    fn log_log_ui(ui: &Ui, id: &str, bins: &[usize], unit: &str) {
+        if bins.is_empty() {
+            ui.text_disabled(tr("None"));
+            return;
+        }
+        let heights: Vec<f32> = bins.iter().map(|&n| (n as f32 + 1.0).log10()).collect();
+        ui.plot_histogram(id, &heights)
+            .graph_size([ui.content_region_avail()[0], PLOT_HEIGHT])
+            .scale_min(0.0)
+            .overlay_text(tr("log10(count + 1)"))
+            .build();
+        ui.text_disabled(
+            tr("{unit} from 1 to {max}, doubling per bar")
+                .replace("{unit}", unit)
+                .replace("{max}", &((1usize << bins.len()) - 1).to_string()),
+        );
+        if ui.is_item_hovered() {
+            let lines: Vec<String> = bins
+                .iter()
+                .enumerate()
+                .map(|(i, n)| format!("{}-{}: {n}", 1usize << i, (1usize << (i + 1)) - 1))
+                .collect();
+            ui.tooltip_text(lines.join("\n"));
+        }
+    }
+
+    fn attributes_ui(&mut self, ui: &Ui, stats: &GraphStats) {
+        let Some(first) = stats.attributes.keys().next() else {
+            ui.text_disabled(tr("No numeric node attribute"));
+            return;
+        };
+        if !stats.attributes.contains_key(&self.attribute) {
+            self.attribute = first.clone();
+        }
+        if let Some(_combo) = ui.begin_combo(tr("Attribute"), &self.attribute) {
+            for name in stats.attributes.keys() {
+                if ui
+                    .selectable_config(name)
+                    .selected(*name == self.attribute)
+                    .build()
+                {
+                    self.attribute = name.clone();
+                }
+            }
+        }
+        let histogram = &stats.attributes[&self.attribute];
+        let heights: Vec<f32> = histogram.counts.iter().map(|&n| n as f32).collect();
+        ui.plot_histogram("##attribute", &heights)
+            .graph_size([ui.content_region_avail()[0], PLOT_HEIGHT])
+            .scale_min(0.0)
+            .build();
+        ui.text_disabled(format!(
+            "{} .. {}, {}",
+            histogram.min,
+            histogram.max,
+            tr("{width} per bar").replace("{width}", &format!("{:.3}", histogram.bin_width())),
+        ));
+    }
+}
+
+impl Panel for StatisticsPanel {
+    fn id(&self) -> &'static str {
+        "Statistics"
+    }
+
+    fn draw(&mut self, ui: &Ui, ctx: &mut EngineContext) {
+        let graph = ctx.visible_graph();
+        let generation = graph.generation();
+        if self.computed.as_ref().is_none_or(|(g, _)| *g != generation) {
+            self.computed = Some((generation, GraphStats::compute(graph)));
+        }
+        let Some((_, stats)) = self.computed.take() else {
+            return;
+        };
+
+        ui.text(
+            tr("{nodes} nodes, {edges} edges")
+                .replace("{nodes}", &stats.nodes.to_string())
+                .replace("{edges}", &stats.edges.to_string()),
+        );
+        ui.text(tr("Density: {density}").replace("{density}", &format!("{:.4}", stats.density)));
+        ui.text(
+            tr("Diameter: at least {hops} hops").replace("{hops}", &stats.diameter.to_string()),
+        );
+        if ui.is_item_hovered() {
+            ui.tooltip_text(tr(
+                "Estimated on the largest component, ignoring edge directions",
+            ));
+        }
+
+        if ui.collapsing_header(tr("Degree Distribution"), TreeNodeFlags::DEFAULT_OPEN) {
+            if stats.isolated > 0 {
+                ui.text_disabled(
+                    tr("{count} isolated nodes").replace("{count}", &stats.isolated.to_string()),
+                );
+            }
+            Self::log_log_ui(ui, "##degrees", &stats.degrees, &tr("Degrees"));
+        }
+        if ui.collapsing_header(tr("Component Sizes"), TreeNodeFlags::DEFAULT_OPEN) {
+            ui.text_disabled(
+                tr("{count} components").replace("{count}", &stats.component_count.to_string()),
+            );
+            Self::log_log_ui(ui, "##components", &stats.components, &tr("Sizes"));
+        }
+        if ui.collapsing_header(tr("Attributes"), TreeNodeFlags::DEFAULT_OPEN) {
+            self.attributes_ui(ui, &stats);
+        }
+        self.computed = Some((generation, stats));
+    }
+}
@@ -0,0 +1,176 @@
+//! # Status Bar
+//!
+//! A strip along the bottom of the main window, showing at a glance:
+//! - the number of nodes and edges, and how many are visible through filters
+//! - how many are selected
+//! - the camera's zoom level
+//! - what the [find path tool](crate::pathfind) waits for, while active
+//! - the background [jobs](crate::jobs) running, with the progress of the
+//!   first one
+//!
+//! Above it, messages recorded in the [`Log`] pop up as toasts for
+//! [`TOAST_DURATION`]. The log is where every subsystem reports to the user,
+//! so anything logged shows without a subsystem knowing about the status bar;
+//! debug messages only go to the Log panel.
+//!
+//! ```ignore
+//! ctx.log.info("Saved 'graph.gml'"); // shows as a toast
+//! status::draw(ui, &ctx);
+//! ```
+
+use std::time::Duration;
+
+use crate::context::EngineContext;
+use crate::i18n::tr;
+use crate::imgui::{Condition, ProgressBar, StyleVar, Ui, WindowFlags};
+use crate::subsystems::log::{Log, LogEntry, LogLevel};
+
+/// How long a message shows as a toast.
+pub const TOAST_DURATION: Duration = Duration::from_secs(4);
+/// Toasts shown at once, the most recent ones.
+const MAX_TOASTS: usize = 3;
+/// Width of the progress bar of the running job.
+const PROGRESS_WIDTH: f32 = 120.0;
+/// Space around the contents of the status bar, in points.
+const PADDING: [f32; 2] = [8.0, 3.0];
+
+/// Height of the status bar, which windows along the bottom of the main
+/// window are placed above.
+pub fn height(ui: &Ui) -> f32 {
+    ui.text_line_height() + 2.0 * PADDING[1]
+}
+
+/// The messages of `log` showing as toasts, the most recent last.
+pub fn toasts(log: &Log) -> impl Iterator<Item = &LogEntry> {
+    let mut toasts: Vec<_> = log
+        .entries()
+        .rev()
+        .take_while(|entry| entry.time.elapsed() < TOAST_DURATION)
+        .filter(|entry| entry.level > LogLevel::Debug)
+        .take(MAX_TOASTS)
+        .collect();
+    toasts.reverse();
+    toasts.into_iter()
+}
+
+/// Draw the status bar of `ctx` along the bottom of the main window, and the
+/// toasts above it.
+pub fn draw(ui: &Ui, ctx: &EngineContext) {
+    let [width, height] = ui.io().display_size;
+    let bar = self::height(ui);
+    let padding = ui.push_style_var(StyleVar::WindowPadding(PADDING));
+    ui.window("##status")
+        .position([0.0, height - bar], Condition::Always)
+        .size([width, bar], Condition::Always)
+        .flags(
+            WindowFlags::NO_DECORATION
+                | WindowFlags::NO_MOVE
+                | WindowFlags::NO_SAVED_SETTINGS
+                | WindowFlags::NO_FOCUS_ON_APPEARING
+                | WindowFlags::NO_NAV
+                | WindowFlags::NO_DOCKING,
+        )
+        .build(|| bar_ui(ui, ctx));
+    padding.pop();
+    draw_toasts(ui, ctx, height - bar);
+}
+
+fn bar_ui(ui: &Ui, ctx: &EngineContext) {
+    let graph = &ctx.graph;
+    let counts = tr("{nodes} nodes, {edges} edges")
+        .replace("{nodes}", &graph.node_count().to_string())
+        .replace("{edges}", &graph.edge_count().to_string());
+    ui.text(counts);
+    let visible = ctx.visible_graph();
+    if visible.node_count() != graph.node_count() || visible.edge_count() != graph.edge_count() {
+        ui.same_line();
+        ui.text_disabled(
+            tr("({nodes} and {edges} visible)")
+                .replace("{nodes}", &visible.node_count().to_string())
+                .replace("{edges}", &visible.edge_count().to_string()),
+        );
+    }
+
+    let selected = ctx.selection.node_count() + ctx.selection.edge_count();
+    if selected > 0 {
+        divider(ui);
+        ui.text(tr("{count} selected").replace("{count}", &selected.to_string()));
+    }
+
+    if let Some((_, zoom)) = ctx.camera.view() {
+        divider(ui);
+        ui.text(tr("Zoom {percent}%").replace("{percent}", &format!("{:.0}", zoom * 100.0)));
+    }
+
+    let finder = &ctx.path_finder;
+    if finder.is_active() {
+        divider(ui);
+        let hint = match (finder.source(), finder.target()) {
+            (None, _) => tr("Find Path: click the node to start from"),
+            (Some(_), None) => tr("Find Path: click the node to go to"),
+            (Some(_), Some(_)) => tr("Find Path: click a node to start over"),
+        };
+        ui.text_colored(ctx.style.sheet().theme.colors().path, hint);
+    }
+
+    let running: Vec<_> = ctx
+        .jobs
+        .jobs()
+        .into_iter()
+        .filter(|job| job.elapsed().is_some())
+        .collect();
+    if let Some(job) = running.first() {
+        divider(ui);
+        ui.text(job.name());
+        ui.same_line();
+        ProgressBar::new(job.progress().fraction())
+            .size([PROGRESS_WIDTH, ui.text_line_height()])
+            .build(ui);
+        if running.len() > 1 {
+            ui.same_line();
+            ui.text_disabled(
+                tr("+{count} more").replace("{count}", &(running.len() - 1).to_string()),
+            );
+        }
+    }
+}
+
+/// A vertical bar between two parts of the status bar.
+fn divider(ui: &Ui) {
+    ui.same_line();
+    ui.text_disabled("|");
+    ui.same_line();
+}
+
+/// The toasts of `ctx`'s log, stacked up from `bottom` at the left of the
+/// main window.
+fn draw_toasts(ui: &Ui, ctx: &EngineContext, bottom: f32) {
+    let toasts: Vec<_> = toasts(&ctx.log).collect();
+    if toasts.is_empty() {
+        return;
+    }
+    ui.window("##toasts")
+        .position([10.0, bottom - 10.0], Condition::Always)
+        .position_pivot([0.0, 1.0])
+        .bg_alpha(0.85)
+        .flags(
+            WindowFlags::NO_DECORATION
+                | WindowFlags::ALWAYS_AUTO_RESIZE
+                | WindowFlags::NO_SAVED_SETTINGS
+                | WindowFlags::NO_FOCUS_ON_APPEARING
+                | WindowFlags::NO_NAV
+                | WindowFlags::NO_INPUTS
+                | WindowFlags::NO_DOCKING,
+        )
+        .build(|| {
+            for (index, entry) in toasts.iter().enumerate() {
+                if index > 0 {
+                    ui.separator();
+                }
+                match entry.level {
+                    LogLevel::Debug | LogLevel::Info => ui.text(&entry.message),
+                    _ => ui.text_colored(entry.level.color(), &entry.message),
+                }
+            }
+        });
+}
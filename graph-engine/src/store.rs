@@ -0,0 +1,1238 @@
+//! # On-Disk Graph Store
+//!
+//! Keeps graphs too large for memory in an SQLite database. A [`GraphStore`]
+//! holds every node with its position and attributes, and every edge, while a
+//! [`StoreCache`] keeps only the nodes around the camera in memory, as the
+//! document's [`Graph`]. Drawing, picking, layouts and panels work on that
+//! graph as on any other, and the cache swaps it for another part of the store
+//! as the camera moves:
+//! - A window somewhat larger than the visible area is loaded, with the edges
+//!   between its nodes. Windows holding more than [`StoreCache::limit()`]
+//!   nodes keep those with the highest degree, so hubs show first when zoomed
+//!   out, and zooming in loads the rest.
+//! - Moved nodes, edited attributes, and nodes and edges added or removed are
+//!   written back to the store every few seconds, and before another window is
+//!   loaded. Only the rows which changed are written, and removing a node also
+//!   removes its edges to nodes outside the window.
+//! - Changes cannot be told apart once the cached graph is replaced by another
+//!   one, eg. by a paste, so they fail with [`StoreError::Replaced`] and are
+//!   discarded.
+//!
+//! Stores are created from a loaded graph with [`GraphStore::create()`], from
+//! an edge list too large to load with [`GraphStore::import_csv()`], one node
+//! and edge at a time with a [`StoreWriter`], or by any tool writing the same
+//! tables:
+//!
+//! ```sql
+//! CREATE TABLE meta (key TEXT PRIMARY KEY, value TEXT NOT NULL);
+//! CREATE TABLE nodes (id INTEGER PRIMARY KEY, degree INTEGER NOT NULL, attrs TEXT NOT NULL);
+//! CREATE VIRTUAL TABLE node_positions USING rtree(id, min_x, max_x, min_y, max_y);
+//! CREATE TABLE edges (id INTEGER PRIMARY KEY, source INTEGER NOT NULL,
+//!     target INTEGER NOT NULL, attrs TEXT NOT NULL);
+//! ```
+//!
+//! A [`StoreSource`] fetches neighborhoods from a store for an
+//! [`Exploration`](crate::explore::Exploration), which shows nodes connected
+//! to each other wherever they are placed.
+//!
+//! Attributes are flat JSON objects, `meta` holds the `version` (`1`), the
+//! `directedness` (`directed` or `undirected`) and the graph's `attrs`, and
+//! each node's position is a point in `node_positions`.
+//!
+//! ```ignore
+//! GraphStore::create("huge.graphdb", &ctx.graph, &ctx.positions)?;
+//! ctx.open_store("huge.graphdb")?;
+//! ```
+
+use rusqlite::{Connection, OptionalExtension, params};
+use serde_json::{Map, Value};
+
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use crate::context::EngineContext;
+use crate::explore::{ExploreError, NeighborSource};
+use crate::graph::{
+    Attributes, ChangeFilter, Directedness, Element, Graph, GraphChange, NodeId, Subscription,
+};
+use crate::i18n::tr;
+use crate::imgui::Ui;
+use crate::io::csv::{self, CsvError, CsvOptions};
+use crate::io::json::{flatten, to_json};
+use crate::io::{ID_ATTR, node_name};
+use crate::layout::{self, Positions};
+use crate::spatial::Rect;
+use crate::subsystems::panels::Panel;
+
+/// Extension of graph store files, without the dot.
+pub const STORE_EXTENSION: &str = "graphdb";
+
+/// Version of the tables written by [`GraphStore::create()`].
+const VERSION: &str = "1";
+
+/// Number of nodes kept in memory by default.
+const DEFAULT_LIMIT: usize = 50_000;
+
+/// How far the loaded window extends past each side of the visible area, as a
+/// fraction of its size.
+const MARGIN: f32 = 0.5;
+
+/// How often changes to the cached graph are written back.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Number of nodes listed per page by [`StorePanel`].
+const PAGE_LEN: usize = 100;
+
+const SCHEMA: &str = "
+    CREATE TABLE meta (key TEXT PRIMARY KEY, value TEXT NOT NULL);
+    CREATE TABLE nodes (id INTEGER PRIMARY KEY, degree INTEGER NOT NULL, attrs TEXT NOT NULL);
+    CREATE VIRTUAL TABLE node_positions USING rtree(id, min_x, max_x, min_y, max_y);
+    CREATE TABLE edges (
+        id INTEGER PRIMARY KEY,
+        source INTEGER NOT NULL,
+        target INTEGER NOT NULL,
+        attrs TEXT NOT NULL
+    );
+    CREATE INDEX edges_source ON edges (source);
+    CREATE INDEX edges_target ON edges (target);
+";
+
+/// A graph kept in an SQLite database, see the [module documentation](self).
+pub struct GraphStore {
+    connection: Connection,
+    path: PathBuf,
+    directedness: Directedness,
+}
+
+/// Writes a new [`GraphStore`] one node and edge at a time, so that graphs
+/// too large for memory never have to be loaded whole. Nothing is visible in
+/// the store before [`Self::finish()`].
+pub struct StoreWriter {
+    connection: Connection,
+    path: PathBuf,
+    directedness: Directedness,
+}
+
+/// A node read from a [`GraphStore`].
+#[derive(Clone, Debug)]
+pub struct StoredNode {
+    /// The node's id in the store.
+    pub id: i64,
+    pub position: [f32; 2],
+    pub attrs: Attributes,
+}
+
+/// An edge read from a [`GraphStore`], as its store id, the store ids of its
+/// source and target, and its attributes.
+pub type StoredEdge = (i64, i64, i64, Attributes);
+
+/// Keeps the part of a [`GraphStore`] around the camera in memory, see the
+/// [module documentation](self).
+pub struct StoreCache {
+    store: GraphStore,
+    /// Id of the document showing the store.
+    document: u64,
+    limit: usize,
+    /// The loaded window, as `(min, max)` corners, none before the first load.
+    window: Option<Rect>,
+    /// Whether the window held more than [`Self::limit`] nodes.
+    truncated: bool,
+    /// Store ids of the cached nodes and edges, by index in the cached graph,
+    /// `-1` for those not in the store.
+    nodes: Vec<i64>,
+    edges: Vec<i64>,
+    /// Positions of the cached nodes in the store, by index in the cached graph.
+    points: Vec<[f32; 2]>,
+    /// Changes to the cached graph since the last write, none before the
+    /// first load.
+    changes: Option<Subscription>,
+    /// Generations of the cached graph and positions when last written back.
+    flushed: (u64, u64),
+    last_flush: Instant,
+}
+
+/// Fetches neighborhoods from a [`GraphStore`] for an
+/// [`Exploration`](crate::explore::Exploration).
+///
+/// Nodes are keyed by their [`ID_ATTR`], or their store id as `n<id>` if they
+/// have none, like the nodes of the graph the store was created from.
+pub struct StoreSource {
+    store: GraphStore,
+    /// Store id of each node key handed out.
+    ids: HashMap<String, i64>,
+}
+
+/// Errors which can occur while using a [`GraphStore`].
+#[derive(Debug)]
+pub enum StoreError {
+    Sqlite(rusqlite::Error),
+    Io(std::io::Error),
+    /// The database is not a graph store, or a newer version of one.
+    Invalid(String),
+    /// An edge list could not be imported.
+    Csv(CsvError),
+    /// The cached graph was replaced by another one, whose changes cannot be
+    /// written back.
+    Replaced,
+}
+
+/// Lists the nodes of the open store page by page, and creates stores from the
+/// current graph.
+pub struct StorePanel {
+    /// Where [`GraphStore::create()`] writes.
+    path: String,
+    page: usize,
+    /// The nodes of [`Self::page`], read when the page or store changes.
+    rows: Option<Vec<StoredNode>>,
+    /// The edge list [`GraphStore::import_csv()`] reads.
+    csv: String,
+    /// The import running in the background, if any.
+    import: Option<JoinHandle<Result<GraphStore, StoreError>>>,
+    status: Option<(String, bool)>,
+}
+
+impl GraphStore {
+    /// Write `graph`, with its nodes at `positions`, to a new store at `path`,
+    /// replacing any file there.
+    pub fn create(
+        path: impl AsRef<Path>,
+        graph: &Graph,
+        positions: &Positions,
+    ) -> Result<Self, StoreError> {
+        let mut writer = StoreWriter::new(path, graph.directedness(), graph.attrs())?;
+        for id in graph.nodes() {
+            let position = positions.get(id).unwrap_or_default();
+            writer.add_node(id.index() as i64, position, graph.node(id).unwrap())?;
+        }
+        for id in graph.edges() {
+            let (source, target) = graph.endpoints(id).unwrap();
+            writer.add_edge(
+                id.index() as i64,
+                source.index() as i64,
+                target.index() as i64,
+                graph.edge(id).unwrap(),
+            )?;
+        }
+        writer.finish()
+    }
+
+    /// Write the edge list at `csv`, read with `options`, to a new store at
+    /// `path`, replacing any file there. The list is read one row at a time,
+    /// keeping only node names in memory, and nodes are placed on a spiral in
+    /// the order they first appear, until laid out.
+    pub fn import_csv(
+        path: impl AsRef<Path>,
+        csv: impl AsRef<Path>,
+        options: &CsvOptions,
+    ) -> Result<Self, StoreError> {
+        let reader = BufReader::new(File::open(csv)?);
+        let directedness = if options.directed {
+            Directedness::Directed
+        } else {
+            Directedness::Undirected
+        };
+        let mut writer = StoreWriter::new(path, directedness, &Attributes::default())?;
+        let mut ids: HashMap<String, i64> = HashMap::new();
+        let mut node = |writer: &mut StoreWriter, name: &str| -> Result<i64, StoreError> {
+            if let Some(&id) = ids.get(name) {
+                return Ok(id);
+            }
+            let id = ids.len() as i64;
+            let attrs = Attributes::from_iter([(ID_ATTR, name)]);
+            writer.add_node(id, layout::seed(id as usize), &attrs)?;
+            ids.insert(name.to_owned(), id);
+            Ok(id)
+        };
+        let mut edges = 0;
+        csv::read_edges(reader, options, |source, target, attrs| {
+            let (source, target) = (node(&mut writer, source)?, node(&mut writer, target)?);
+            writer.add_edge(edges, source, target, &attrs)?;
+            edges += 1;
+            Ok::<_, StoreError>(())
+        })?;
+        writer.finish()
+    }
+
+    /// Open the store at `path`.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, StoreError> {
+        let path = path.as_ref();
+        if !path.is_file() {
+            return Err(std::io::Error::from(std::io::ErrorKind::NotFound).into());
+        }
+        let connection = Connection::open(path)?;
+        let meta = |key: &str| -> Result<Option<String>, StoreError> {
+            let value = connection
+                .query_row("SELECT value FROM meta WHERE key = ?1", [key], |row| {
+                    row.get(0)
+                })
+                .optional();
+            value.map_err(|_| StoreError::Invalid("not a graph store".into()))
+        };
+        let version = meta("version")?;
+        if version.as_deref() != Some(VERSION) {
+            return Err(StoreError::Invalid(format!(
+                "unsupported version {}",
+                version.as_deref().unwrap_or("(none)")
+            )));
+        }
+        let directedness = match meta("directedness")?.as_deref() {
+            Some("undirected") => Directedness::Undirected,
+            _ => Directedness::Directed,
+        };
+        Ok(Self {
+            connection,
+            path: path.to_owned(),
+            directedness,
+        })
+    }
+
+    /// The database file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn directedness(&self) -> Directedness {
+        self.directedness
+    }
+
+    /// The graph's own attributes.
+    pub fn attrs(&self) -> Result<Attributes, StoreError> {
+        let text: Option<String> = self
+            .connection
+            .query_row("SELECT value FROM meta WHERE key = 'attrs'", [], |row| {
+                row.get(0)
+            })
+            .optional()?;
+        Ok(text.map(|text| from_text(&text)).unwrap_or_default())
+    }
+
+    pub fn node_count(&self) -> Result<usize, StoreError> {
+        self.count("SELECT COUNT(*) FROM nodes")
+    }
+
+    pub fn edge_count(&self) -> Result<usize, StoreError> {
+        self.count("SELECT COUNT(*) FROM edges")
+    }
+
+    /// The corners of the rectangle holding every node, none if there are no
+    /// nodes.
+    pub fn bounds(&self) -> Result<Option<Rect>, StoreError> {
+        let bounds = self.connection.query_row(
+            "SELECT MIN(min_x), MIN(min_y), MAX(max_x), MAX(max_y) FROM node_positions",
+            [],
+            |row| {
+                Ok(match (row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?) {
+                    (Some(x0), Some(y0), Some(x1), Some(y1)) => Some(([x0, y0], [x1, y1])),
+                    _ => None,
+                })
+            },
+        )?;
+        Ok(bounds)
+    }
+
+    /// The node with store id `id`, if any.
+    pub fn node(&self, id: i64) -> Result<Option<StoredNode>, StoreError> {
+        let node = self
+            .connection
+            .query_row(
+                "SELECT n.id, p.min_x, p.min_y, n.attrs FROM nodes n
+                 JOIN node_positions p ON p.id = n.id WHERE n.id = ?1",
+                [id],
+                stored_node,
+            )
+            .optional()?;
+        Ok(node)
+    }
+
+    /// Up to `limit` nodes in order of their store id, skipping the first
+    /// `offset`.
+    pub fn nodes_page(&self, offset: usize, limit: usize) -> Result<Vec<StoredNode>, StoreError> {
+        let mut statement = self.connection.prepare_cached(
+            "SELECT n.id, p.min_x, p.min_y, n.attrs FROM nodes n
+             JOIN node_positions p ON p.id = n.id ORDER BY n.id LIMIT ?1 OFFSET ?2",
+        )?;
+        let nodes = statement
+            .query_map(params![limit as i64, offset as i64], stored_node)?
+            .collect::<Result<_, _>>()?;
+        Ok(nodes)
+    }
+
+    /// Up to `limit` nodes between the corners `min` and `max`, those with the
+    /// highest degree first.
+    pub fn nodes_in(
+        &self,
+        min: [f32; 2],
+        max: [f32; 2],
+        limit: usize,
+    ) -> Result<Vec<StoredNode>, StoreError> {
+        let mut statement = self.connection.prepare_cached(
+            "SELECT n.id, p.min_x, p.min_y, n.attrs FROM node_positions p
+             JOIN nodes n ON n.id = p.id
+             WHERE p.max_x >= ?1 AND p.min_x <= ?2 AND p.max_y >= ?3 AND p.min_y <= ?4
+             ORDER BY n.degree DESC LIMIT ?5",
+        )?;
+        let nodes = statement
+            .query_map(
+                params![min[0], max[0], min[1], max[1], limit as i64],
+                stored_node,
+            )?
+            .collect::<Result<_, _>>()?;
+        Ok(nodes)
+    }
+
+    /// Every edge between two of the nodes with store ids `nodes`, as its id,
+    /// source, target and attributes.
+    pub fn edges_between(&self, nodes: &[i64]) -> Result<Vec<StoredEdge>, StoreError> {
+        self.connection.execute_batch(
+            "CREATE TEMP TABLE IF NOT EXISTS cached (id INTEGER PRIMARY KEY);
+             DELETE FROM cached;",
+        )?;
+        {
+            let mut insert = self
+                .connection
+                .prepare_cached("INSERT OR IGNORE INTO cached (id) VALUES (?1)")?;
+            for id in nodes {
+                insert.execute([id])?;
+            }
+        }
+        let mut statement = self.connection.prepare_cached(
+            "SELECT e.id, e.source, e.target, e.attrs FROM edges e
+             JOIN cached s ON s.id = e.source JOIN cached t ON t.id = e.target",
+        )?;
+        let edges = statement
+            .query_map([], stored_edge)?
+            .collect::<Result<_, _>>()?;
+        Ok(edges)
+    }
+
+    /// Up to `limit` nodes linked to the node with store id `id`, those with
+    /// the highest degree first, and the edges linking them to it, as their id,
+    /// source, target and attributes.
+    pub fn neighbors(
+        &self,
+        id: i64,
+        limit: usize,
+    ) -> Result<(Vec<StoredNode>, Vec<StoredEdge>), StoreError> {
+        let mut statement = self.connection.prepare_cached(
+            "SELECT n.id, p.min_x, p.min_y, n.attrs FROM nodes n
+             JOIN node_positions p ON p.id = n.id
+             WHERE n.id IN (SELECT target FROM edges WHERE source = ?1
+                            UNION SELECT source FROM edges WHERE target = ?1)
+             ORDER BY n.degree DESC LIMIT ?2",
+        )?;
+        let nodes: Vec<StoredNode> = statement
+            .query_map(params![id, limit as i64], stored_node)?
+            .collect::<Result<_, _>>()?;
+        let mut statement = self.connection.prepare_cached(
+            "SELECT id, source, target, attrs FROM edges WHERE source = ?1 OR target = ?1",
+        )?;
+        let mut edges: Vec<StoredEdge> = statement
+            .query_map([id], stored_edge)?
+            .collect::<Result<_, _>>()?;
+        // edges to neighbors past the limit are left out
+        edges.retain(|&(_, source, target, _)| {
+            let other = if source == id { target } else { source };
+            nodes.iter().any(|n| n.id == other)
+        });
+        Ok((nodes, edges))
+    }
+
+    fn count(&self, query: &str) -> Result<usize, StoreError> {
+        let count: i64 = self.connection.query_row(query, [], |row| row.get(0))?;
+        Ok(count as usize)
+    }
+}
+
+impl StoreWriter {
+    /// Start a new store of a graph with `directedness` and `attrs` at `path`,
+    /// replacing any file there.
+    pub fn new(
+        path: impl AsRef<Path>,
+        directedness: Directedness,
+        attrs: &Attributes,
+    ) -> Result<Self, StoreError> {
+        let path = path.as_ref();
+        match std::fs::remove_file(path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        }
+        let connection = Connection::open(path)?;
+        connection.execute_batch(SCHEMA)?;
+        // committed by `finish()`, and rolled back if the writer is dropped
+        connection.execute_batch("BEGIN")?;
+        let mut meta = connection.prepare("INSERT INTO meta (key, value) VALUES (?1, ?2)")?;
+        let name = match directedness {
+            Directedness::Directed => "directed",
+            Directedness::Undirected => "undirected",
+        };
+        meta.execute(params!["version", VERSION])?;
+        meta.execute(params!["directedness", name])?;
+        meta.execute(params!["attrs", to_text(attrs)])?;
+        drop(meta);
+        Ok(Self {
+            connection,
+            path: path.to_owned(),
+            directedness,
+        })
+    }
+
+    /// Add a node with store id `id` at `position`.
+    pub fn add_node(
+        &mut self,
+        id: i64,
+        position: [f32; 2],
+        attrs: &Attributes,
+    ) -> Result<(), StoreError> {
+        insert_node(&self.connection, id, position, attrs)
+    }
+
+    /// Add an edge with store id `id` between the nodes with store ids
+    /// `source` and `target`, which must be added before [`Self::finish()`].
+    pub fn add_edge(
+        &mut self,
+        id: i64,
+        source: i64,
+        target: i64,
+        attrs: &Attributes,
+    ) -> Result<(), StoreError> {
+        insert_edge(&self.connection, id, source, target, attrs)
+    }
+
+    /// Count the degree of each node, and write everything added to the store.
+    pub fn finish(self) -> Result<GraphStore, StoreError> {
+        let dangling: i64 = self.connection.query_row(
+            "SELECT COUNT(*) FROM edges
+             WHERE source NOT IN (SELECT id FROM nodes) OR target NOT IN (SELECT id FROM nodes)",
+            [],
+            |row| row.get(0),
+        )?;
+        if dangling > 0 {
+            return Err(StoreError::Invalid(format!(
+                "{dangling} edges link missing nodes"
+            )));
+        }
+        self.connection.execute_batch(
+            "UPDATE nodes SET degree = (SELECT COUNT(*) FROM edges WHERE source = nodes.id)
+                + (SELECT COUNT(*) FROM edges WHERE target = nodes.id);
+             COMMIT;",
+        )?;
+        Ok(GraphStore {
+            connection: self.connection,
+            path: self.path,
+            directedness: self.directedness,
+        })
+    }
+}
+
+impl StoreCache {
+    /// Cache `store` for the document with id `document`. Nothing is loaded
+    /// before the first [`Self::reload()`].
+    pub fn new(store: GraphStore, document: u64) -> Self {
+        Self {
+            store,
+            document,
+            limit: DEFAULT_LIMIT,
+            window: None,
+            truncated: false,
+            nodes: Vec::new(),
+            edges: Vec::new(),
+            points: Vec::new(),
+            changes: None,
+            flushed: (0, 0),
+            last_flush: Instant::now(),
+        }
+    }
+
+    pub fn store(&self) -> &GraphStore {
+        &self.store
+    }
+
+    /// Id of the document showing the store.
+    pub fn document(&self) -> u64 {
+        self.document
+    }
+
+    /// Most nodes kept in memory.
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Set the most nodes kept in memory, taking effect on the next reload.
+    pub fn set_limit(&mut self, limit: usize) {
+        self.limit = limit.max(1);
+        // reload with the new limit
+        self.window = None;
+    }
+
+    /// Returns `true` if the loaded window held more nodes than the
+    /// [limit](Self::limit).
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Returns `true` if the camera showing `visible` left the loaded window,
+    /// or zoomed far into a truncated one.
+    pub fn needs_reload(&self, visible: Rect) -> bool {
+        let Some((min, max)) = self.window else {
+            return true;
+        };
+        let (vmin, vmax) = visible;
+        let inside =
+            vmin[0] >= min[0] && vmin[1] >= min[1] && vmax[0] <= max[0] && vmax[1] <= max[1];
+        let (gmin, gmax) = grow(visible);
+        let zoomed_in = area(min, max) > 4.0 * area(gmin, gmax);
+        !inside || (self.truncated && zoomed_in)
+    }
+
+    /// Returns `true` if changes should be written back.
+    pub fn flush_due(&self) -> bool {
+        self.last_flush.elapsed() >= FLUSH_INTERVAL
+    }
+
+    /// Write the nodes and edges of the cached graph which changed since the
+    /// last write back to the store: their positions, their attributes, and
+    /// those added and removed.
+    ///
+    /// Fails with [`StoreError::Replaced`] if `graph` is not the cached graph.
+    pub fn flush(&mut self, graph: &Graph, positions: &Positions) -> Result<(), StoreError> {
+        self.last_flush = Instant::now();
+        let generations = (graph.generation(), positions.generation());
+        if self.window.is_none() || generations == self.flushed {
+            return Ok(());
+        }
+        let changes = self
+            .changes
+            .as_ref()
+            .and_then(|changes| changes.poll(graph));
+        let changes = changes.ok_or(StoreError::Replaced)?;
+        let Self {
+            store,
+            nodes,
+            edges,
+            points,
+            ..
+        } = self;
+        let tx = store.connection.transaction()?;
+
+        let mut dirty_nodes = HashSet::new();
+        let mut dirty_edges = HashSet::new();
+        let mut dirty_attrs = false;
+        for change in changes {
+            match change {
+                GraphChange::NodeAdded(node) => {
+                    if !graph.contains_node(node) || stored(nodes, node.index()).is_some() {
+                        continue;
+                    }
+                    let id = next_id(&tx, "nodes")?;
+                    let position = positions.get(node).unwrap_or_default();
+                    insert_node(&tx, id, position, graph.node(node).unwrap())?;
+                    set_stored(nodes, node.index(), id);
+                    if node.index() >= points.len() {
+                        points.resize(node.index() + 1, [0.0; 2]);
+                    }
+                    points[node.index()] = position;
+                }
+                GraphChange::NodeRemoved(node) => {
+                    if let Some(id) = stored(nodes, node.index()) {
+                        remove_node(&tx, id)?;
+                        set_stored(nodes, node.index(), -1);
+                    }
+                }
+                GraphChange::EdgeAdded(edge) => {
+                    if !graph.contains_edge(edge) || stored(edges, edge.index()).is_some() {
+                        continue;
+                    }
+                    let (source, target) = graph.endpoints(edge).unwrap();
+                    let (Some(source), Some(target)) =
+                        (stored(nodes, source.index()), stored(nodes, target.index()))
+                    else {
+                        continue;
+                    };
+                    let id = next_id(&tx, "edges")?;
+                    insert_edge(&tx, id, source, target, graph.edge(edge).unwrap())?;
+                    add_degree(&tx, source, target, 1)?;
+                    set_stored(edges, edge.index(), id);
+                }
+                GraphChange::EdgeRemoved(edge) => {
+                    if let Some(id) = stored(edges, edge.index()) {
+                        remove_edge(&tx, id)?;
+                        set_stored(edges, edge.index(), -1);
+                    }
+                }
+                GraphChange::AttrChanged(Element::Node(node)) => {
+                    dirty_nodes.insert(node);
+                }
+                GraphChange::AttrChanged(Element::Edge(edge)) => {
+                    dirty_edges.insert(edge);
+                }
+                GraphChange::AttrChanged(Element::Graph) => dirty_attrs = true,
+                GraphChange::Cleared => {
+                    for id in nodes.iter_mut().filter(|id| **id >= 0) {
+                        remove_node(&tx, *id)?;
+                        *id = -1;
+                    }
+                    edges.fill(-1);
+                }
+            }
+        }
+
+        if dirty_attrs {
+            tx.execute(
+                "UPDATE meta SET value = ?1 WHERE key = 'attrs'",
+                [to_text(graph.attrs())],
+            )?;
+        }
+        {
+            let mut update = tx.prepare_cached("UPDATE nodes SET attrs = ?2 WHERE id = ?1")?;
+            for node in dirty_nodes {
+                if let (Some(id), Some(attrs)) = (stored(nodes, node.index()), graph.node(node)) {
+                    update.execute(params![id, to_text(attrs)])?;
+                }
+            }
+            let mut update = tx.prepare_cached("UPDATE edges SET attrs = ?2 WHERE id = ?1")?;
+            for edge in dirty_edges {
+                if let (Some(id), Some(attrs)) = (stored(edges, edge.index()), graph.edge(edge)) {
+                    update.execute(params![id, to_text(attrs)])?;
+                }
+            }
+            let mut update = tx.prepare_cached(
+                "UPDATE node_positions SET min_x = ?2, max_x = ?2, min_y = ?3, max_y = ?3
+                 WHERE id = ?1",
+            )?;
+            for node in graph.nodes() {
+                let (Some(id), Some(point)) = (stored(nodes, node.index()), positions.get(node))
+                else {
+                    continue;
+                };
+                if points[node.index()] != point {
+                    update.execute(params![id, point[0], point[1]])?;
+                    points[node.index()] = point;
+                }
+            }
+        }
+        tx.commit()?;
+        self.flushed = generations;
+        Ok(())
+    }
+
+    /// Forget the changes to the cached graph, which the next
+    /// [`Self::reload()`] replaces without writing them back.
+    pub fn discard(&mut self) {
+        self.window = None;
+        self.changes = None;
+    }
+
+    /// Write back changes to the cached graph, then replace it and `positions`
+    /// with the nodes around `visible` and the edges between them.
+    pub fn reload(
+        &mut self,
+        visible: Rect,
+        graph: &mut Graph,
+        positions: &mut Positions,
+    ) -> Result<(), StoreError> {
+        self.flush(graph, positions)?;
+        let (min, max) = grow(visible);
+        let mut nodes = self.store.nodes_in(min, max, self.limit + 1)?;
+        self.truncated = nodes.len() > self.limit;
+        nodes.truncate(self.limit);
+
+        let mut cached = Graph::new(self.store.directedness());
+        *cached.attrs_mut() = self.store.attrs()?;
+        let mut index = std::collections::HashMap::with_capacity(nodes.len());
+        self.nodes.clear();
+        self.points.clear();
+        for node in nodes {
+            let id = cached.add_node_with(node.attrs);
+            index.insert(node.id, id);
+            self.nodes.push(node.id);
+            self.points.push(node.position);
+        }
+        self.edges.clear();
+        for (id, source, target, attrs) in self.store.edges_between(&self.nodes)? {
+            let edge = cached
+                .add_edge_with(index[&source], index[&target], attrs)
+                .map_err(|e| StoreError::Invalid(e.to_string()))?;
+            if edge.index() >= self.edges.len() {
+                self.edges.resize(edge.index() + 1, -1);
+            }
+            self.edges[edge.index()] = id;
+        }
+
+        let mut placed = Positions::new(&cached);
+        for (id, &point) in cached.nodes().zip(&self.points) {
+            placed.set(id, point);
+        }
+        self.changes = Some(cached.subscribe(ChangeFilter::ALL));
+        *graph = cached;
+        *positions = placed;
+        self.window = Some((min, max));
+        self.flushed = (graph.generation(), positions.generation());
+        self.last_flush = Instant::now();
+        Ok(())
+    }
+}
+
+impl StoreSource {
+    /// Explore the store of `cache` from `nodes` of its cached `graph`, in a
+    /// connection of its own. Returns the source and the keys of `nodes`.
+    pub fn from_cache(
+        cache: &StoreCache,
+        graph: &Graph,
+        nodes: &[NodeId],
+    ) -> Result<(Self, Vec<String>), StoreError> {
+        let store = GraphStore::open(cache.store().path())?;
+        let mut ids = HashMap::new();
+        let keys = nodes
+            .iter()
+            .map(|&node| match stored(&cache.nodes, node.index()) {
+                Some(id) => {
+                    let key = stored_key(id, graph.node(node).unwrap_or(&Attributes::default()));
+                    ids.insert(key.clone(), id);
+                    key
+                }
+                // added since the load, so not in the store
+                None => node_name(graph, node),
+            })
+            .collect();
+        Ok((Self { store, ids }, keys))
+    }
+}
+
+impl NeighborSource for StoreSource {
+    fn name(&self) -> String {
+        format!("graph store '{}'", self.store.path().display())
+    }
+
+    fn neighbors(&mut self, keys: &[String], limit: usize) -> Result<Graph, ExploreError> {
+        let mut result = Graph::new(self.store.directedness());
+        let mut mapped: HashMap<i64, NodeId> = HashMap::new();
+        for key in keys {
+            let Some(&id) = self.ids.get(key) else {
+                continue;
+            };
+            let Some(node) = self.store.node(id)? else {
+                continue;
+            };
+            let (neighbors, edges) = self.store.neighbors(id, limit)?;
+            for node in std::iter::once(node).chain(neighbors) {
+                if mapped.contains_key(&node.id) {
+                    continue;
+                }
+                let key = stored_key(node.id, &node.attrs);
+                let mut attrs = node.attrs;
+                attrs.insert(ID_ATTR, key.as_str());
+                self.ids.insert(key, node.id);
+                mapped.insert(node.id, result.add_node_with(attrs));
+            }
+            for (id, source, target, mut attrs) in edges {
+                if !attrs.contains(ID_ATTR) {
+                    attrs.insert(ID_ATTR, format!("e{id}"));
+                }
+                let _ = result.add_edge_with(mapped[&source], mapped[&target], attrs);
+            }
+        }
+        Ok(result)
+    }
+}
+
+/// The key of the stored node with store id `id` and `attrs`, see
+/// [`StoreSource`].
+fn stored_key(id: i64, attrs: &Attributes) -> String {
+    match attrs.get(ID_ATTR) {
+        Some(value) => value.to_string(),
+        None => format!("n{id}"),
+    }
+}
+
+/// The store id at `index` of `ids`, if it is in the store.
+fn stored(ids: &[i64], index: usize) -> Option<i64> {
+    ids.get(index).copied().filter(|&id| id >= 0)
+}
+
+/// Set the store id at `index` of `ids`, growing them as needed.
+fn set_stored(ids: &mut Vec<i64>, index: usize, id: i64) {
+    if index >= ids.len() {
+        ids.resize(index + 1, -1);
+    }
+    ids[index] = id;
+}
+
+/// The store id following the highest one in `table`.
+fn next_id(connection: &Connection, table: &str) -> Result<i64, StoreError> {
+    let query = format!("SELECT COALESCE(MAX(id), -1) + 1 FROM {table}");
+    Ok(connection.query_row(&query, [], |row| row.get(0))?)
+}
+
+/// Add a node of degree 0 to the store.
+fn insert_node(
+    connection: &Connection,
+    id: i64,
+    [x, y]: [f32; 2],
+    attrs: &Attributes,
+) -> Result<(), StoreError> {
+    connection
+        .prepare_cached("INSERT INTO nodes (id, degree, attrs) VALUES (?1, 0, ?2)")?
+        .execute(params![id, to_text(attrs)])?;
+    connection
+        .prepare_cached(
+            "INSERT INTO node_positions (id, min_x, max_x, min_y, max_y)
+             VALUES (?1, ?2, ?2, ?3, ?3)",
+        )?
+        .execute(params![id, x, y])?;
+    Ok(())
+}
+
+/// Add an edge to the store, without counting it in the degrees of its nodes.
+fn insert_edge(
+    connection: &Connection,
+    id: i64,
+    source: i64,
+    target: i64,
+    attrs: &Attributes,
+) -> Result<(), StoreError> {
+    connection
+        .prepare_cached("INSERT INTO edges (id, source, target, attrs) VALUES (?1, ?2, ?3, ?4)")?
+        .execute(params![id, source, target, to_text(attrs)])?;
+    Ok(())
+}
+
+/// Add `delta` to the degrees of `source` and `target`, the nodes of an edge.
+fn add_degree(
+    connection: &Connection,
+    source: i64,
+    target: i64,
+    delta: i64,
+) -> Result<(), StoreError> {
+    let mut update =
+        connection.prepare_cached("UPDATE nodes SET degree = degree + ?2 WHERE id = ?1")?;
+    update.execute(params![source, delta])?;
+    update.execute(params![target, delta])?;
+    Ok(())
+}
+
+/// Remove the edge with store id `id` from the store, if it is still there.
+fn remove_edge(connection: &Connection, id: i64) -> Result<(), StoreError> {
+    let endpoints: Option<(i64, i64)> = connection
+        .query_row(
+            "DELETE FROM edges WHERE id = ?1 RETURNING source, target",
+            [id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+    // edges of removed nodes are gone already
+    if let Some((source, target)) = endpoints {
+        add_degree(connection, source, target, -1)?;
+    }
+    Ok(())
+}
+
+/// Remove the node with store id `id` from the store, with all its edges.
+fn remove_node(connection: &Connection, id: i64) -> Result<(), StoreError> {
+    let edges: Vec<i64> = connection
+        .prepare_cached("SELECT id FROM edges WHERE source = ?1 OR target = ?1")?
+        .query_map([id], |row| row.get(0))?
+        .collect::<Result<_, _>>()?;
+    for edge in edges {
+        remove_edge(connection, edge)?;
+    }
+    connection.execute("DELETE FROM nodes WHERE id = ?1", [id])?;
+    connection.execute("DELETE FROM node_positions WHERE id = ?1", [id])?;
+    Ok(())
+}
+
+/// `visible` grown by [`MARGIN`] on each side.
+fn grow((min, max): Rect) -> Rect {
+    let dx = (max[0] - min[0]) * MARGIN;
+    let dy = (max[1] - min[1]) * MARGIN;
+    ([min[0] - dx, min[1] - dy], [max[0] + dx, max[1] + dy])
+}
+
+fn area(min: [f32; 2], max: [f32; 2]) -> f32 {
+    (max[0] - min[0]).max(0.0) * (max[1] - min[1]).max(0.0)
+}
+
+/// Read a node from a row of id, x, y and attributes.
+fn stored_node(row: &rusqlite::Row) -> rusqlite::Result<StoredNode> {
+    let attrs: String = row.get(3)?;
+    Ok(StoredNode {
+        id: row.get(0)?,
+        position: [row.get(1)?, row.get(2)?],
+        attrs: from_text(&attrs),
+    })
+}
+
+/// Read an edge from a row of id, source, target and attributes.
+fn stored_edge(row: &rusqlite::Row) -> rusqlite::Result<StoredEdge> {
+    let attrs: String = row.get(3)?;
+    Ok((row.get(0)?, row.get(1)?, row.get(2)?, from_text(&attrs)))
+}
+
+/// Encode attributes as a flat JSON object.
+fn to_text(attrs: &Attributes) -> String {
+    let object: Map<String, Value> = attrs
+        .iter()
+        .map(|(key, value)| (key.as_str().to_owned(), to_json(value)))
+        .collect();
+    Value::Object(object).to_string()
+}
+
+/// Decode attributes written by [`to_text()`], ignoring invalid ones.
+fn from_text(text: &str) -> Attributes {
+    let mut attrs = Attributes::default();
+    if let Ok(Value::Object(object)) = serde_json::from_str(text) {
+        for (key, value) in &object {
+            flatten(key, value, &mut attrs);
+        }
+    }
+    attrs
+}
+
+impl StorePanel {
+    pub fn new() -> Self {
+        Self {
+            path: format!("graph.{STORE_EXTENSION}"),
+            page: 0,
+            rows: None,
+            csv: String::new(),
+            import: None,
+            status: None,
+        }
+    }
+
+    /// Open the store the import wrote, once it is done.
+    fn finish_import(&mut self, ctx: &mut EngineContext) {
+        let Some(job) = self.import.take_if(|job| job.is_finished()) else {
+            return;
+        };
+        let result = job
+            .join()
+            .unwrap_or_else(|_| Err(StoreError::Invalid("the import thread panicked".into())));
+        let result = result.and_then(|store| {
+            let path = store.path().to_owned();
+            drop(store);
+            ctx.open_store(&path)?;
+            Ok(path)
+        });
+        self.status = Some(match result {
+            Ok(path) => (format!("Imported '{}'", path.display()), false),
+            Err(e) => (format!("Failed to import '{}': {e}", self.csv.trim()), true),
+        });
+        self.page = 0;
+        self.rows = None;
+    }
+
+    fn create_ui(&mut self, ui: &Ui, ctx: &mut EngineContext) {
+        ui.text_wrapped(
+            tr(
+                "No graph store is open. Drop a .{extension} file onto the window, \
+                or write the current graph to a new store:",
+            )
+            .replace("{extension}", STORE_EXTENSION),
+        );
+        ui.input_text(tr("Path"), &mut self.path).build();
+        ui.enabled(
+            !self.path.trim().is_empty() && !ctx.graph.is_empty(),
+            || {
+                if ui.button(tr("Create Store")) {
+                    let path = PathBuf::from(self.path.trim());
+                    ctx.force.sync_positions(&mut ctx.positions);
+                    let result = GraphStore::create(&path, &ctx.graph, &ctx.positions)
+                        .and_then(|_| ctx.open_store(&path));
+                    self.status = Some(match result {
+                        Ok(()) => (format!("Created '{}'", path.display()), false),
+                        Err(e) => (format!("Failed to create '{}': {e}", path.display()), true),
+                    });
+                    self.page = 0;
+                    self.rows = None;
+                }
+            },
+        );
+
+        ui.separator();
+        ui.text_wrapped(tr(
+            "Or import a CSV edge list to it, without loading the list first:",
+        ));
+        ui.input_text(tr("Edge List"), &mut self.csv).build();
+        let importing = self.import.is_some();
+        ui.enabled(
+            !importing && !self.path.trim().is_empty() && !self.csv.trim().is_empty(),
+            || {
+                if ui.button(tr("Import Edge List")) {
+                    let path = PathBuf::from(self.path.trim());
+                    let csv = PathBuf::from(self.csv.trim());
+                    self.status = None;
+                    self.import = Some(std::thread::spawn(move || {
+                        let options = CsvOptions::sniff(&csv)?;
+                        GraphStore::import_csv(&path, &csv, &options)
+                    }));
+                }
+            },
+        );
+        if importing {
+            ui.text_disabled(tr("Importing..."));
+        }
+    }
+
+    fn store_ui(&mut self, ui: &Ui, ctx: &mut EngineContext) {
+        let Some(cache) = &mut ctx.store else {
+            return;
+        };
+        let store = cache.store();
+        ui.text(tr("Store: {path}").replace("{path}", &store.path().display().to_string()));
+        let nodes = store.node_count().unwrap_or_default();
+        let edges = store.edge_count().unwrap_or_default();
+        ui.text(
+            tr("{nodes} nodes, {edges} edges")
+                .replace("{nodes}", &nodes.to_string())
+                .replace("{edges}", &edges.to_string()),
+        );
+        ui.text(
+            tr("In memory: {nodes} nodes, {edges} edges")
+                .replace("{nodes}", &ctx.graph.node_count().to_string())
+                .replace("{edges}", &ctx.graph.edge_count().to_string()),
+        );
+        if cache.is_truncated() {
+            ui.text_disabled(tr("Showing the highest degree nodes, zoom in for more"));
+        }
+        let mut limit = cache.limit() as i32;
+        ui.set_next_item_width(120.0);
+        if ui
+            .input_int(tr("Node Limit"), &mut limit)
+            .step(10_000)
+            .enter_returns_true(true)
+            .build()
+        {
+            cache.set_limit(limit.max(1000) as usize);
+        }
+
+        ui.separator();
+        let pages = nodes.div_ceil(PAGE_LEN).max(1);
+        self.page = self.page.min(pages - 1);
+        ui.enabled(self.page > 0, || {
+            if ui.arrow_button("##previous", crate::imgui::Direction::Left) {
+                self.page -= 1;
+                self.rows = None;
+            }
+        });
+        ui.same_line();
+        ui.enabled(self.page + 1 < pages, || {
+            if ui.arrow_button("##next", crate::imgui::Direction::Right) {
+                self.page += 1;
+                self.rows = None;
+            }
+        });
+        ui.same_line();
+        ui.text(
+            tr("Page {page} of {pages}")
+                .replace("{page}", &(self.page + 1).to_string())
+                .replace("{pages}", &pages.to_string()),
+        );
+
+        if self.rows.is_none() {
+            self.rows = match cache.store().nodes_page(self.page * PAGE_LEN, PAGE_LEN) {
+                Ok(rows) => Some(rows),
+                Err(e) => {
+                    self.status = Some((format!("Failed to read nodes: {e}"), true));
+                    Some(Vec::new())
+                }
+            };
+        }
+        let mut target = None;
+        ui.child_window("##nodes").build(|| {
+            for node in self.rows.iter().flatten() {
+                let label = node
+                    .attrs
+                    .get("label")
+                    .map_or_else(String::new, |label| label.to_string());
+                let _id = ui.push_id_usize(node.id as usize);
+                if ui.selectable(format!("{}  {label}", node.id)) {
+                    target = Some(node.position);
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip_text(tr("Show this node"));
+                }
+            }
+        });
+        if let Some([x, y]) = target {
+            const SPAN: f32 = 200.0;
+            ctx.camera
+                .fit_rect([x - SPAN, y - SPAN], [x + SPAN, y + SPAN]);
+        }
+    }
+}
+
+impl Panel for StorePanel {
+    fn id(&self) -> &'static str {
+        "Graph Store"
+    }
+
+    fn draw(&mut self, ui: &Ui, ctx: &mut EngineContext) {
+        self.finish_import(ctx);
+        if ctx.store.is_some() {
+            self.store_ui(ui, ctx);
+        } else {
+            self.rows = None;
+            self.create_ui(ui, ctx);
+        }
+        match &self.status {
+            Some((error, true)) => ui.text_colored([1.0, 0.4, 0.4, 1.0], error),
+            Some((status, false)) => ui.text_wrapped(status),
+            None => {}
+        }
+    }
+}
+
+impl std::fmt::Display for StoreError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Sqlite(e) => write!(f, "{e}"),
+            Self::Io(e) => write!(f, "{e}"),
+            Self::Invalid(e) => {
+                f.write_str(&tr("Invalid graph store: {error}").replace("{error}", e))
+            }
+            Self::Csv(e) => write!(f, "{e}"),
+            Self::Replaced => f.write_str(&tr(
+                "The graph was replaced, so its changes cannot be written to the store",
+            )),
+        }
+    }
+}
+
+impl std::error::Error for StoreError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Sqlite(e) => Some(e),
+            Self::Io(e) => Some(e),
+            Self::Csv(e) => Some(e),
+            Self::Invalid(_) | Self::Replaced => None,
+        }
+    }
+}
+
+impl From<rusqlite::Error> for StoreError {
+    fn from(e: rusqlite::Error) -> Self {
+        Self::Sqlite(e)
+    }
+}
+
+impl From<CsvError> for StoreError {
+    fn from(e: CsvError) -> Self {
+        Self::Csv(e)
+    }
+}
+
+impl From<std::io::Error> for StoreError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl std::default::Default for StorePanel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
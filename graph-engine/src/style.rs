@@ -0,0 +1,1429 @@
+//! # Visual Style
+//!
+//! A [`StyleSheet`] maps the attributes of nodes and edges to how they look:
+//! - color, fixed, along a continuous [`ColorRamp`] for numeric attributes, or
+//!   from a [`CategoryPalette`] for categories, with colorblind-safe choices of
+//!   both
+//! - node size and edge width, fixed or scaled by a numeric attribute
+//! - node shape, fixed or per category
+//! - which attribute labels show, and when
+//! - whether [hover cards](crate::hover) show, after how long, and with which
+//!   attributes
+//! - the [`Theme`] of the scene around them: its background, grid, labels, and
+//!   selection highlights, eg. high contrast
+//!
+//! Mappings only name attributes, so a sheet is independent of any graph and is
+//! saved with the workspace in [`STYLE_FILE`]. A [`Style`] applies a sheet to a
+//! graph, gathering the value ranges and categories its mappings need, and
+//! describes them as a [legend](Style::legend()), eg. for exported images.
+//!
+//! Nodes without an attribute named by a mapping fall back to the
+//! [`DEGREE_ATTR`] pseudo-attribute if that is the name, and to a neutral
+//! appearance otherwise.
+//!
+//! ```ignore
+//! let mut sheet = StyleSheet::default();
+//! sheet.node_color = ColorMapping::Ramp {
+//!     attribute: "score".into(),
+//!     ramp: ColorRamp::Viridis,
+//!     range: None,
+//! };
+//! let mut style = Style::new(sheet);
+//! style.resolve(&graph);
+//! let look = style.node_style(&graph, node);
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+
+use crate::context::EngineContext;
+use crate::graph::{Attributes, EdgeId, Graph, NodeId};
+use crate::history::Command;
+use crate::i18n::tr;
+use crate::imgui::{TreeNodeFlags, Ui};
+use crate::routing::{EdgeRoutes, EdgeShape, MAX_BUNDLED_EDGES};
+use crate::subsystems::panels::Panel;
+use crate::subsystems::scene::{EdgeStyle, LegendEntry, LegendGroup, NodeShape, NodeStyle};
+
+/// Name of the style file inside the config directory.
+pub const STYLE_FILE: &str = "style.toml";
+
+/// Pseudo-attribute holding each node's degree, for nodes without a real
+/// attribute of that name.
+pub const DEGREE_ATTR: &str = "degree";
+
+/// Color of elements missing the attribute their color is mapped from.
+const MISSING_COLOR: [f32; 4] = [0.5, 0.5, 0.5, 0.6];
+
+/// Color of the swatches of shape categories in a legend.
+const LEGEND_SHAPE_COLOR: [f32; 4] = [0.5, 0.5, 0.5, 1.0];
+/// Most entries of a legend group, the rest summed up in a last entry.
+const MAX_LEGEND_ENTRIES: usize = 12;
+/// Values of a color ramp shown in a legend, from the lowest to the highest.
+const LEGEND_RAMP_STEPS: usize = 5;
+
+/// Shapes given to categories, in order of their first value.
+const CATEGORY_SHAPES: [NodeShape; 4] = [
+    NodeShape::Circle,
+    NodeShape::Square,
+    NodeShape::Diamond,
+    NodeShape::Triangle,
+];
+
+/// How elements are colored.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ColorMapping {
+    /// The same color for every element.
+    Fixed { color: [f32; 4] },
+    /// A color along `ramp` for the numeric value of `attribute`.
+    Ramp {
+        attribute: String,
+        ramp: ColorRamp,
+        /// Values mapped to either end of the ramp. `None` uses the smallest
+        /// and largest values in the graph.
+        #[serde(default)]
+        range: Option<[f64; 2]>,
+    },
+    /// A distinct color for each value of `attribute`.
+    Categories { attribute: String },
+}
+
+/// A continuous color scale.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorRamp {
+    /// Perceptually uniform, from dark purple to yellow.
+    #[default]
+    Viridis,
+    /// Like [`Self::Viridis`], from dark blue to yellow, looking alike to
+    /// viewers with a red-green color vision deficiency.
+    Cividis,
+    /// From dark red through orange to pale yellow.
+    Heat,
+    /// Diverging, from blue through gray to red.
+    BlueRed,
+    Grayscale,
+}
+
+/// The colors given to categories, in order of their first value, repeating
+/// once they run out.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CategoryPalette {
+    #[default]
+    Tableau,
+    /// Okabe and Ito's palette, told apart with any color vision deficiency.
+    OkabeIto,
+    /// Paul Tol's bright palette, colorblind-safe.
+    TolBright,
+    /// Paul Tol's muted palette, colorblind-safe, with more colors.
+    TolMuted,
+}
+
+/// The look of the scene around the styled nodes and edges.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Theme {
+    #[default]
+    Dark,
+    Light,
+    /// A black background, with white labels and bold highlights.
+    HighContrast,
+}
+
+/// The colors of a [`Theme`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ThemeColors {
+    pub background: [f32; 4],
+    pub grid: [f32; 4],
+    /// Outline of selected nodes, and color of selected edges.
+    pub selected: [f32; 4],
+    /// Outline of pinned nodes.
+    pub pinned: [f32; 4],
+    /// Nodes and edges on a path found by the [find path tool](crate::pathfind),
+    /// and the marker traversing it.
+    pub path: [f32; 4],
+    pub label: [f32; 4],
+    /// Outline around labels, keeping them readable over edges.
+    pub label_halo: [f32; 4],
+    /// Fill and outline of the box around the members of an expanded group.
+    pub group_fill: [f32; 4],
+    pub group_outline: [f32; 4],
+    /// Fill and outline of the selection rectangle.
+    pub marquee_fill: [f32; 4],
+    pub marquee_outline: [f32; 4],
+}
+
+/// How large elements are: node radius in world units, or edge width in points.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SizeMapping {
+    /// The same size for every element.
+    Fixed { size: f32 },
+    /// A size between `min` and `max` for the numeric value of `attribute`.
+    Scaled {
+        attribute: String,
+        min: f32,
+        max: f32,
+        /// Values mapped to `min` and `max`. `None` uses the smallest and
+        /// largest values in the graph.
+        #[serde(default)]
+        range: Option<[f64; 2]>,
+    },
+}
+
+/// How nodes are shaped.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ShapeMapping {
+    /// The same shape for every node.
+    Fixed { shape: NodeShape },
+    /// A distinct shape for each value of `attribute`, repeating once shapes run out.
+    Categories { attribute: String },
+}
+
+/// Which labels are shown.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LabelMapping {
+    /// Attribute shown as the label. Elements without it have no label.
+    pub attribute: String,
+    pub visibility: LabelVisibility,
+}
+
+/// When an element's label is shown.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LabelVisibility {
+    Never,
+    /// Only for selected elements.
+    Selected,
+    #[default]
+    Always,
+}
+
+/// What [hover cards](crate::hover) show, and when.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HoverCards {
+    pub enabled: bool,
+    /// How long the cursor rests on an element before its card shows, in seconds.
+    pub delay: f32,
+    /// Attributes listed on the cards of nodes, in order. Empty for the first
+    /// few attributes of each node.
+    pub node_attributes: Vec<String>,
+    pub edge_attributes: Vec<String>,
+}
+
+/// Mappings from attributes to the appearance of nodes and edges.
+///
+/// Saved to and loaded from [`STYLE_FILE`] in TOML format:
+/// ```toml
+/// [node_color]
+/// kind = "ramp"
+/// attribute = "score"
+/// ramp = "viridis"
+///
+/// [node_size]
+/// kind = "scaled"
+/// attribute = "degree"
+/// min = 4.0
+/// max = 20.0
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StyleSheet {
+    pub theme: Theme,
+    pub palette: CategoryPalette,
+    pub node_color: ColorMapping,
+    pub node_size: SizeMapping,
+    pub node_shape: ShapeMapping,
+    pub node_label: LabelMapping,
+    pub edge_color: ColorMapping,
+    pub edge_width: SizeMapping,
+    pub edge_label: LabelMapping,
+    pub hover: HoverCards,
+}
+
+/// Errors which can occur while loading or saving a [`StyleSheet`].
+#[derive(Debug)]
+pub enum StyleError {
+    Io(std::io::Error),
+    Parse(toml::de::Error),
+    Serialize(toml::ser::Error),
+}
+
+/// A [`StyleSheet`] applied to a graph.
+///
+/// Call [`Self::refresh()`] whenever the sheet or the graph's attributes may
+/// have changed, so value ranges and categories are up to date.
+#[derive(Clone, Debug, Default)]
+pub struct Style {
+    sheet: StyleSheet,
+    nodes: Stats,
+    edges: Stats,
+    /// Generation of the graph last resolved against.
+    resolved: Option<u64>,
+}
+
+/// What the mappings of a sheet need to know about one kind of element.
+#[derive(Clone, Debug, Default)]
+struct Stats {
+    /// Smallest and largest value of each numeric attribute mapped by a sheet.
+    ranges: HashMap<String, [f64; 2]>,
+    /// Index of each value of each categorical attribute mapped by a sheet,
+    /// in sorted order of the values.
+    categories: HashMap<String, HashMap<String, usize>>,
+    /// Every attribute name the elements carry, eg. to offer in the ui.
+    attributes: Vec<String>,
+}
+
+impl ColorRamp {
+    pub const ALL: [ColorRamp; 5] = [
+        Self::Viridis,
+        Self::Cividis,
+        Self::Heat,
+        Self::BlueRed,
+        Self::Grayscale,
+    ];
+
+    /// Human-readable name of the ramp.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Viridis => "Viridis",
+            Self::Cividis => "Cividis",
+            Self::Heat => "Heat",
+            Self::BlueRed => "Blue-Red",
+            Self::Grayscale => "Grayscale",
+        }
+    }
+
+    /// Returns `true` if the ramp reads the same with any color vision
+    /// deficiency, its lightness changing steadily along it.
+    pub fn is_colorblind_safe(self) -> bool {
+        !matches!(self, Self::Heat)
+    }
+
+    /// The color at `t`, from `0.0` to `1.0`.
+    pub fn sample(self, t: f32) -> [f32; 4] {
+        let stops: &[[f32; 3]] = match self {
+            Self::Viridis => &[
+                [0.267, 0.005, 0.329],
+                [0.283, 0.141, 0.458],
+                [0.254, 0.265, 0.530],
+                [0.207, 0.372, 0.553],
+                [0.164, 0.471, 0.558],
+                [0.128, 0.567, 0.551],
+                [0.135, 0.659, 0.518],
+                [0.267, 0.749, 0.441],
+                [0.478, 0.821, 0.319],
+                [0.741, 0.873, 0.150],
+                [0.993, 0.906, 0.144],
+            ],
+            Self::Cividis => &[
+                [0.000, 0.135, 0.304],
+                [0.071, 0.208, 0.439],
+                [0.231, 0.286, 0.424],
+                [0.341, 0.365, 0.427],
+                [0.439, 0.443, 0.451],
+                [0.541, 0.529, 0.475],
+                [0.651, 0.616, 0.459],
+                [0.769, 0.710, 0.424],
+                [0.894, 0.812, 0.357],
+                [0.996, 0.910, 0.220],
+            ],
+            Self::Heat => &[
+                [0.25, 0.0, 0.0],
+                [0.8, 0.1, 0.0],
+                [1.0, 0.6, 0.0],
+                [1.0, 1.0, 0.6],
+            ],
+            Self::BlueRed => &[[0.23, 0.30, 0.75], [0.85, 0.85, 0.85], [0.71, 0.02, 0.15]],
+            Self::Grayscale => &[[0.2, 0.2, 0.2], [0.95, 0.95, 0.95]],
+        };
+        let scaled = t.clamp(0.0, 1.0) * (stops.len() - 1) as f32;
+        let i = (scaled as usize).min(stops.len() - 2);
+        let f = scaled - i as f32;
+        let [a, b] = [stops[i], stops[i + 1]];
+        [
+            a[0] + (b[0] - a[0]) * f,
+            a[1] + (b[1] - a[1]) * f,
+            a[2] + (b[2] - a[2]) * f,
+            1.0,
+        ]
+    }
+}
+
+impl CategoryPalette {
+    pub const ALL: [CategoryPalette; 4] = [
+        Self::Tableau,
+        Self::OkabeIto,
+        Self::TolBright,
+        Self::TolMuted,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Tableau => "Tableau",
+            Self::OkabeIto => "Okabe-Ito",
+            Self::TolBright => "Tol Bright",
+            Self::TolMuted => "Tol Muted",
+        }
+    }
+
+    /// Returns `true` if the colors can be told apart with any color vision
+    /// deficiency.
+    pub fn is_colorblind_safe(self) -> bool {
+        self != Self::Tableau
+    }
+
+    /// The colors, in the order categories are given them.
+    pub fn colors(self) -> &'static [[f32; 4]] {
+        match self {
+            Self::Tableau => &[
+                [0.31, 0.47, 0.65, 1.0],
+                [0.95, 0.56, 0.17, 1.0],
+                [0.88, 0.34, 0.35, 1.0],
+                [0.46, 0.72, 0.70, 1.0],
+                [0.35, 0.63, 0.31, 1.0],
+                [0.93, 0.79, 0.28, 1.0],
+                [0.69, 0.48, 0.63, 1.0],
+                [1.00, 0.62, 0.66, 1.0],
+                [0.61, 0.46, 0.37, 1.0],
+                [0.73, 0.69, 0.67, 1.0],
+            ],
+            // gray in place of black, which vanishes on dark backgrounds
+            Self::OkabeIto => &[
+                [0.902, 0.624, 0.000, 1.0],
+                [0.337, 0.706, 0.914, 1.0],
+                [0.000, 0.620, 0.451, 1.0],
+                [0.941, 0.894, 0.259, 1.0],
+                [0.000, 0.447, 0.698, 1.0],
+                [0.835, 0.369, 0.000, 1.0],
+                [0.800, 0.475, 0.655, 1.0],
+                [0.600, 0.600, 0.600, 1.0],
+            ],
+            Self::TolBright => &[
+                [0.267, 0.467, 0.667, 1.0],
+                [0.933, 0.400, 0.467, 1.0],
+                [0.133, 0.533, 0.200, 1.0],
+                [0.800, 0.733, 0.267, 1.0],
+                [0.400, 0.800, 0.933, 1.0],
+                [0.667, 0.200, 0.467, 1.0],
+                [0.733, 0.733, 0.733, 1.0],
+            ],
+            Self::TolMuted => &[
+                [0.800, 0.400, 0.467, 1.0],
+                [0.200, 0.133, 0.533, 1.0],
+                [0.867, 0.800, 0.467, 1.0],
+                [0.067, 0.467, 0.200, 1.0],
+                [0.533, 0.800, 0.933, 1.0],
+                [0.533, 0.133, 0.333, 1.0],
+                [0.267, 0.667, 0.600, 1.0],
+                [0.600, 0.600, 0.200, 1.0],
+                [0.667, 0.267, 0.600, 1.0],
+            ],
+        }
+    }
+}
+
+impl Theme {
+    pub const ALL: [Theme; 3] = [Self::Dark, Self::Light, Self::HighContrast];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Dark => "Dark",
+            Self::Light => "Light",
+            Self::HighContrast => "High Contrast",
+        }
+    }
+
+    pub fn colors(self) -> ThemeColors {
+        match self {
+            Self::Dark => ThemeColors {
+                background: [0.08, 0.08, 0.1, 1.0],
+                grid: [1.0, 1.0, 1.0, 0.08],
+                selected: [1.0, 0.85, 0.2, 1.0],
+                pinned: [1.0, 0.7, 0.3, 1.0],
+                path: [0.3, 0.9, 0.6, 1.0],
+                label: [0.95, 0.95, 0.95, 1.0],
+                label_halo: [0.08, 0.08, 0.1, 0.85],
+                group_fill: [0.6, 0.7, 1.0, 0.06],
+                group_outline: [0.6, 0.7, 1.0, 0.35],
+                marquee_fill: [0.35, 0.65, 1.0, 0.15],
+                marquee_outline: [0.35, 0.65, 1.0, 0.8],
+            },
+            Self::Light => ThemeColors {
+                background: [0.97, 0.97, 0.96, 1.0],
+                grid: [0.0, 0.0, 0.0, 0.08],
+                selected: [0.84, 0.37, 0.0, 1.0],
+                pinned: [0.0, 0.45, 0.7, 1.0],
+                path: [0.0, 0.6, 0.35, 1.0],
+                label: [0.1, 0.1, 0.12, 1.0],
+                label_halo: [0.97, 0.97, 0.96, 0.85],
+                group_fill: [0.2, 0.3, 0.6, 0.06],
+                group_outline: [0.2, 0.3, 0.6, 0.4],
+                marquee_fill: [0.0, 0.45, 0.7, 0.12],
+                marquee_outline: [0.0, 0.45, 0.7, 0.8],
+            },
+            Self::HighContrast => ThemeColors {
+                background: [0.0, 0.0, 0.0, 1.0],
+                grid: [1.0, 1.0, 1.0, 0.15],
+                selected: [1.0, 1.0, 0.0, 1.0],
+                pinned: [1.0, 0.0, 1.0, 1.0],
+                path: [0.0, 1.0, 1.0, 1.0],
+                label: [1.0, 1.0, 1.0, 1.0],
+                label_halo: [0.0, 0.0, 0.0, 1.0],
+                group_fill: [1.0, 1.0, 1.0, 0.08],
+                group_outline: [1.0, 1.0, 1.0, 0.7],
+                marquee_fill: [1.0, 1.0, 0.0, 0.15],
+                marquee_outline: [1.0, 1.0, 0.0, 1.0],
+            },
+        }
+    }
+}
+
+impl StyleSheet {
+    /// Load a style sheet from a TOML file.
+    ///
+    /// Mappings missing from the file keep their defaults.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, StyleError> {
+        let text = std::fs::read_to_string(path)?;
+        Ok(toml::from_str(&text)?)
+    }
+
+    /// Load a style sheet from a TOML file, falling back to the defaults if the
+    /// file does not exist or is invalid.
+    pub fn load_or_default(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Self::default();
+        }
+        Self::load(path).unwrap_or_else(|e| {
+            eprintln!(
+                "Failed to load style '{}', using defaults: {e}",
+                path.display()
+            );
+            Self::default()
+        })
+    }
+
+    /// Save the style sheet to a TOML file.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), StyleError> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, toml::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+impl Style {
+    /// Create a style applying `sheet`. It must be [resolved](Self::resolve)
+    /// against a graph before use.
+    pub fn new(sheet: StyleSheet) -> Self {
+        Self {
+            sheet,
+            ..Default::default()
+        }
+    }
+
+    /// The applied sheet.
+    pub fn sheet(&self) -> &StyleSheet {
+        &self.sheet
+    }
+
+    /// Replace the applied sheet, then [resolve](Self::resolve) it against `graph`.
+    pub fn set_sheet(&mut self, sheet: StyleSheet, graph: &Graph) {
+        self.sheet = sheet;
+        self.resolve(graph);
+    }
+
+    /// Gather the value ranges and categories the sheet's mappings need from `graph`.
+    pub fn resolve(&mut self, graph: &Graph) {
+        self.resolved = Some(graph.generation());
+        let sheet = &self.sheet;
+        self.nodes = Stats::gather(
+            graph.nodes().map(|node| NodeValues(graph, node)),
+            [&sheet.node_color],
+            [&sheet.node_size],
+            [&sheet.node_shape],
+            true,
+        );
+        self.edges = Stats::gather(
+            graph.edges().map(|edge| graph.edge(edge).unwrap()),
+            [&sheet.edge_color],
+            [&sheet.edge_width],
+            [],
+            false,
+        );
+    }
+
+    /// [Resolve](Self::resolve) against `graph` if it changed since last time.
+    pub fn refresh(&mut self, graph: &Graph) {
+        if self.resolved != Some(graph.generation()) {
+            self.resolve(graph);
+        }
+    }
+
+    /// Names of the attributes nodes carry, including [`DEGREE_ATTR`], sorted.
+    pub fn node_attributes(&self) -> &[String] {
+        &self.nodes.attributes
+    }
+
+    /// Names of the attributes edges carry, sorted.
+    pub fn edge_attributes(&self) -> &[String] {
+        &self.edges.attributes
+    }
+
+    /// Radius of the largest node the sheet can draw, in world units.
+    pub fn max_node_radius(&self) -> f32 {
+        match self.sheet.node_size {
+            SizeMapping::Fixed { size } => size,
+            SizeMapping::Scaled { min, max, .. } => min.max(max),
+        }
+    }
+
+    /// How `node` looks, without an outline.
+    pub fn node_style(&self, graph: &Graph, node: NodeId) -> NodeStyle {
+        let values = NodeValues(graph, node);
+        let sheet = &self.sheet;
+        NodeStyle {
+            shape: match &sheet.node_shape {
+                ShapeMapping::Fixed { shape } => *shape,
+                ShapeMapping::Categories { attribute } => self
+                    .nodes
+                    .category(&values, attribute)
+                    .map_or(NodeShape::Circle, |i| {
+                        CATEGORY_SHAPES[i % CATEGORY_SHAPES.len()]
+                    }),
+            },
+            radius: self.nodes.size(&values, &sheet.node_size),
+            color: self.nodes.color(&values, &sheet.node_color, sheet.palette),
+            ..NodeStyle::default()
+        }
+    }
+
+    /// How `edge` looks.
+    pub fn edge_style(&self, graph: &Graph, edge: EdgeId) -> EdgeStyle {
+        let Some(attrs) = graph.edge(edge) else {
+            return EdgeStyle::default();
+        };
+        EdgeStyle {
+            width: self.edges.size(attrs, &self.sheet.edge_width),
+            color: self
+                .edges
+                .color(attrs, &self.sheet.edge_color, self.sheet.palette),
+        }
+    }
+
+    /// Groups of swatches explaining the color and shape mappings of the sheet
+    /// which depend on attributes, eg. the color of each category. Fixed
+    /// mappings need no explaining and are left out.
+    pub fn legend(&self) -> Vec<LegendGroup> {
+        let sheet = &self.sheet;
+        let node_shape = match &sheet.node_shape {
+            ShapeMapping::Fixed { shape } => *shape,
+            ShapeMapping::Categories { .. } => NodeShape::Circle,
+        };
+        let mut groups = Vec::new();
+        let colors = [
+            ("Nodes", &sheet.node_color, &self.nodes, Some(node_shape)),
+            ("Edges", &sheet.edge_color, &self.edges, None),
+        ];
+        for (kind, mapping, stats, shape) in colors {
+            let (attribute, entries) = match mapping {
+                ColorMapping::Fixed { .. } => continue,
+                ColorMapping::Categories { attribute } => {
+                    let colors = sheet.palette.colors();
+                    let entries = stats
+                        .sorted_categories(attribute)
+                        .map(|(value, i)| LegendEntry {
+                            text: value.to_owned(),
+                            color: colors[i % colors.len()],
+                            shape,
+                        })
+                        .collect();
+                    (attribute, entries)
+                }
+                ColorMapping::Ramp {
+                    attribute,
+                    ramp,
+                    range,
+                } => {
+                    let Some([lo, hi]) = range.or_else(|| stats.ranges.get(attribute).copied())
+                    else {
+                        continue;
+                    };
+                    let entries = (0..LEGEND_RAMP_STEPS)
+                        .map(|step| {
+                            let t = step as f32 / (LEGEND_RAMP_STEPS - 1) as f32;
+                            LegendEntry {
+                                text: format_value(lo + (hi - lo) * t as f64),
+                                color: ramp.sample(t),
+                                shape,
+                            }
+                        })
+                        .collect();
+                    (attribute, entries)
+                }
+            };
+            groups.push(legend_group(format!("{kind}: {attribute}"), entries));
+        }
+        if let ShapeMapping::Categories { attribute } = &sheet.node_shape {
+            let entries = self
+                .nodes
+                .sorted_categories(attribute)
+                .map(|(value, i)| LegendEntry {
+                    text: value.to_owned(),
+                    color: LEGEND_SHAPE_COLOR,
+                    shape: Some(CATEGORY_SHAPES[i % CATEGORY_SHAPES.len()]),
+                })
+                .collect();
+            groups.push(legend_group(format!("Node shapes: {attribute}"), entries));
+        }
+        groups
+    }
+
+    /// The label shown for `node`, if any, given whether it is `selected`.
+    pub fn node_label(&self, graph: &Graph, node: NodeId, selected: bool) -> Option<String> {
+        label(&NodeValues(graph, node), &self.sheet.node_label, selected)
+    }
+
+    /// The label shown for `edge`, if any, given whether it is `selected`.
+    pub fn edge_label(&self, graph: &Graph, edge: EdgeId, selected: bool) -> Option<String> {
+        label(graph.edge(edge)?, &self.sheet.edge_label, selected)
+    }
+}
+
+/// Attribute values of an element, as read by mappings.
+trait Values {
+    fn number(&self, attribute: &str) -> Option<f64>;
+    fn text(&self, attribute: &str) -> Option<String>;
+    fn names(&self) -> Vec<&'static str>;
+}
+
+/// The attributes of a node, plus its [`DEGREE_ATTR`].
+#[derive(Copy, Clone)]
+struct NodeValues<'a>(&'a Graph, NodeId);
+
+impl Values for Attributes {
+    fn number(&self, attribute: &str) -> Option<f64> {
+        self.get(attribute)?.as_f64()
+    }
+
+    fn text(&self, attribute: &str) -> Option<String> {
+        self.get(attribute).map(|value| value.to_string())
+    }
+
+    fn names(&self) -> Vec<&'static str> {
+        self.iter().map(|(key, _)| key.as_str()).collect()
+    }
+}
+
+impl Values for NodeValues<'_> {
+    fn number(&self, attribute: &str) -> Option<f64> {
+        let attrs = self.0.node(self.1)?;
+        match attrs.get(attribute) {
+            Some(value) => value.as_f64(),
+            None if attribute == DEGREE_ATTR => Some(self.0.degree(self.1) as f64),
+            None => None,
+        }
+    }
+
+    fn text(&self, attribute: &str) -> Option<String> {
+        let attrs = self.0.node(self.1)?;
+        match attrs.get(attribute) {
+            Some(value) => Some(value.to_string()),
+            None if attribute == DEGREE_ATTR => Some(self.0.degree(self.1).to_string()),
+            None => None,
+        }
+    }
+
+    fn names(&self) -> Vec<&'static str> {
+        self.0.node(self.1).map_or_else(Vec::new, Values::names)
+    }
+}
+
+impl<T: Values + ?Sized> Values for &T {
+    fn number(&self, attribute: &str) -> Option<f64> {
+        (**self).number(attribute)
+    }
+
+    fn text(&self, attribute: &str) -> Option<String> {
+        (**self).text(attribute)
+    }
+
+    fn names(&self) -> Vec<&'static str> {
+        (**self).names()
+    }
+}
+
+impl Stats {
+    /// Gather what `colors`, `sizes`, and `shapes` need from `elements`.
+    fn gather<'a, V: Values>(
+        elements: impl Iterator<Item = V>,
+        colors: impl IntoIterator<Item = &'a ColorMapping>,
+        sizes: impl IntoIterator<Item = &'a SizeMapping>,
+        shapes: impl IntoIterator<Item = &'a ShapeMapping>,
+        degree: bool,
+    ) -> Self {
+        let mut numeric = BTreeSet::new();
+        let mut categorical = BTreeSet::new();
+        for mapping in colors {
+            match mapping {
+                ColorMapping::Ramp {
+                    attribute,
+                    range: None,
+                    ..
+                } => _ = numeric.insert(attribute.as_str()),
+                ColorMapping::Categories { attribute } => {
+                    _ = categorical.insert(attribute.as_str())
+                }
+                _ => {}
+            }
+        }
+        for mapping in sizes {
+            if let SizeMapping::Scaled {
+                attribute,
+                range: None,
+                ..
+            } = mapping
+            {
+                numeric.insert(attribute.as_str());
+            }
+        }
+        for mapping in shapes {
+            if let ShapeMapping::Categories { attribute } = mapping {
+                categorical.insert(attribute.as_str());
+            }
+        }
+
+        let mut names = BTreeSet::new();
+        let mut ranges: HashMap<String, [f64; 2]> = HashMap::new();
+        let mut values: HashMap<&str, BTreeSet<String>> = HashMap::new();
+        for element in elements {
+            names.extend(element.names());
+            for &attribute in &numeric {
+                if let Some(v) = element.number(attribute).filter(|v| v.is_finite()) {
+                    let range = ranges.entry(attribute.to_owned()).or_insert([v, v]);
+                    *range = [range[0].min(v), range[1].max(v)];
+                }
+            }
+            for &attribute in &categorical {
+                if let Some(text) = element.text(attribute) {
+                    values.entry(attribute).or_default().insert(text);
+                }
+            }
+        }
+        if degree {
+            names.insert(DEGREE_ATTR);
+        }
+
+        Self {
+            ranges,
+            categories: values
+                .into_iter()
+                .map(|(attribute, values)| {
+                    let indices = values
+                        .into_iter()
+                        .enumerate()
+                        .map(|(i, v)| (v, i))
+                        .collect();
+                    (attribute.to_owned(), indices)
+                })
+                .collect(),
+            attributes: names.into_iter().map(str::to_owned).collect(),
+        }
+    }
+
+    /// Position of `values`' `attribute` within `range`, or the gathered range,
+    /// from `0.0` to `1.0`.
+    fn fraction(
+        &self,
+        values: &impl Values,
+        attribute: &str,
+        range: Option<[f64; 2]>,
+    ) -> Option<f32> {
+        let value = values.number(attribute)?;
+        let [lo, hi] = range.or_else(|| self.ranges.get(attribute).copied())?;
+        if hi <= lo {
+            return Some(0.5);
+        }
+        Some(((value - lo) / (hi - lo)).clamp(0.0, 1.0) as f32)
+    }
+
+    /// The values of the categorical `attribute` with their index, in order.
+    fn sorted_categories(&self, attribute: &str) -> impl Iterator<Item = (&str, usize)> {
+        let mut values: Vec<(&str, usize)> = self
+            .categories
+            .get(attribute)
+            .into_iter()
+            .flatten()
+            .map(|(value, i)| (value.as_str(), *i))
+            .collect();
+        values.sort_by_key(|(_, i)| *i);
+        values.into_iter()
+    }
+
+    fn category(&self, values: &impl Values, attribute: &str) -> Option<usize> {
+        let text = values.text(attribute)?;
+        self.categories.get(attribute)?.get(&text).copied()
+    }
+
+    fn color(
+        &self,
+        values: &impl Values,
+        mapping: &ColorMapping,
+        palette: CategoryPalette,
+    ) -> [f32; 4] {
+        match mapping {
+            ColorMapping::Fixed { color } => *color,
+            ColorMapping::Ramp {
+                attribute,
+                ramp,
+                range,
+            } => self
+                .fraction(values, attribute, *range)
+                .map_or(MISSING_COLOR, |t| ramp.sample(t)),
+            ColorMapping::Categories { attribute } => {
+                let colors = palette.colors();
+                self.category(values, attribute)
+                    .map_or(MISSING_COLOR, |i| colors[i % colors.len()])
+            }
+        }
+    }
+
+    fn size(&self, values: &impl Values, mapping: &SizeMapping) -> f32 {
+        match mapping {
+            SizeMapping::Fixed { size } => *size,
+            SizeMapping::Scaled {
+                attribute,
+                min,
+                max,
+                range,
+            } => self
+                .fraction(values, attribute, *range)
+                .map_or(*min, |t| min + (max - min) * t),
+        }
+    }
+}
+
+/// A legend group titled `title`, its entries beyond [`MAX_LEGEND_ENTRIES`]
+/// summed up in a last one without a swatch.
+fn legend_group(title: String, mut entries: Vec<LegendEntry>) -> LegendGroup {
+    if entries.len() > MAX_LEGEND_ENTRIES {
+        let more = entries.len() - (MAX_LEGEND_ENTRIES - 1);
+        entries.truncate(MAX_LEGEND_ENTRIES - 1);
+        entries.push(LegendEntry {
+            text: format!("and {more} more"),
+            color: [0.0; 4],
+            shape: None,
+        });
+    }
+    LegendGroup { title, entries }
+}
+
+/// `value` rounded for a legend, without trailing zeros.
+fn format_value(value: f64) -> String {
+    let text = format!("{value:.3}");
+    match text.contains('.') {
+        true => text.trim_end_matches('0').trim_end_matches('.').to_owned(),
+        false => text,
+    }
+}
+
+fn label(values: &impl Values, mapping: &LabelMapping, selected: bool) -> Option<String> {
+    match mapping.visibility {
+        LabelVisibility::Never => None,
+        LabelVisibility::Selected if !selected => None,
+        _ => values.text(&mapping.attribute),
+    }
+}
+
+/// Edits the [`EngineContext::style`]'s sheet.
+pub struct StylePanel;
+
+impl Panel for StylePanel {
+    fn id(&self) -> &'static str {
+        "Style"
+    }
+
+    fn draw(&mut self, ui: &Ui, ctx: &mut EngineContext) {
+        let mut sheet = ctx.style.sheet().clone();
+        let node_attributes = ctx.style.node_attributes().to_vec();
+        let edge_attributes = ctx.style.edge_attributes().to_vec();
+
+        if ui.collapsing_header(tr("Colors"), TreeNodeFlags::DEFAULT_OPEN) {
+            let _id = ui.push_id("colors");
+            colors_ui(ui, &mut sheet);
+        }
+        if ui.collapsing_header(tr("Nodes"), TreeNodeFlags::DEFAULT_OPEN) {
+            let _id = ui.push_id("nodes");
+            color_ui(ui, &mut sheet.node_color, &node_attributes);
+            size_ui(
+                ui,
+                "Size",
+                &mut sheet.node_size,
+                &node_attributes,
+                1.0..=50.0,
+            );
+            shape_ui(ui, &mut sheet.node_shape, &node_attributes);
+            label_ui(ui, &mut sheet.node_label, &node_attributes);
+        }
+        if ui.collapsing_header(tr("Edges"), TreeNodeFlags::DEFAULT_OPEN) {
+            let _id = ui.push_id("edges");
+            color_ui(ui, &mut sheet.edge_color, &edge_attributes);
+            size_ui(
+                ui,
+                "Width",
+                &mut sheet.edge_width,
+                &edge_attributes,
+                0.5..=10.0,
+            );
+            label_ui(ui, &mut sheet.edge_label, &edge_attributes);
+        }
+        if ui.collapsing_header(tr("Hover Cards"), TreeNodeFlags::empty()) {
+            let _id = ui.push_id("hover");
+            hover_ui(ui, &mut sheet.hover, &node_attributes, &edge_attributes);
+        }
+        if ui.collapsing_header(tr("Edge Rendering"), TreeNodeFlags::empty()) {
+            let _id = ui.push_id("routing");
+            let edges = ctx.visible_graph().edge_count();
+            routing_ui(ui, &mut ctx.routes, edges);
+        }
+        ui.separator();
+        if ui.button(tr("Defaults")) {
+            sheet = StyleSheet::default();
+        }
+
+        if sheet != *ctx.style.sheet() {
+            ctx.execute(Command::SetStyle(Box::new(sheet)));
+        }
+    }
+}
+
+/// Combo choosing one of `attributes`, keeping `attribute` if it is not among them.
+fn attribute_ui(ui: &Ui, attribute: &mut String, attributes: &[String]) {
+    if let Some(_combo) = ui.begin_combo(tr("Attribute"), attribute.as_str()) {
+        for name in attributes {
+            if ui
+                .selectable_config(name)
+                .selected(name == attribute)
+                .build()
+            {
+                *attribute = name.clone();
+            }
+        }
+    }
+}
+
+/// An attribute to map by default, preferring `current`.
+fn default_attribute(current: Option<&String>, attributes: &[String]) -> String {
+    current.or(attributes.first()).cloned().unwrap_or_default()
+}
+
+fn color_ui(ui: &Ui, mapping: &mut ColorMapping, attributes: &[String]) {
+    let _id = ui.push_id("color");
+    let attribute = match mapping {
+        ColorMapping::Fixed { .. } => None,
+        ColorMapping::Ramp { attribute, .. } | ColorMapping::Categories { attribute } => {
+            Some(attribute.clone())
+        }
+    };
+    let mut kind = match mapping {
+        ColorMapping::Fixed { .. } => 0,
+        ColorMapping::Ramp { .. } => 1,
+        ColorMapping::Categories { .. } => 2,
+    };
+    if ui.combo_simple_string(
+        tr("Color"),
+        &mut kind,
+        &[tr("Fixed"), tr("Ramp"), tr("Categories")],
+    ) {
+        let attribute = default_attribute(attribute.as_ref(), attributes);
+        *mapping = match kind {
+            0 => ColorMapping::Fixed {
+                color: NodeStyle::default().color,
+            },
+            1 => ColorMapping::Ramp {
+                attribute,
+                ramp: ColorRamp::default(),
+                range: None,
+            },
+            _ => ColorMapping::Categories { attribute },
+        };
+    }
+
+    ui.indent();
+    match mapping {
+        ColorMapping::Fixed { color } => {
+            ui.color_edit4(tr("Value"), color);
+        }
+        ColorMapping::Ramp {
+            attribute,
+            ramp,
+            range,
+        } => {
+            attribute_ui(ui, attribute, attributes);
+            let mut index = ColorRamp::ALL.iter().position(|r| r == ramp).unwrap();
+            if ui.combo(tr("Ramp"), &mut index, &ColorRamp::ALL, |r| tr(r.label())) {
+                *ramp = ColorRamp::ALL[index];
+            }
+            ramp_preview(ui, *ramp);
+            range_ui(ui, range);
+        }
+        ColorMapping::Categories { attribute } => attribute_ui(ui, attribute, attributes),
+    }
+    ui.unindent();
+}
+
+fn size_ui(
+    ui: &Ui,
+    label: &str,
+    mapping: &mut SizeMapping,
+    attributes: &[String],
+    limits: std::ops::RangeInclusive<f32>,
+) {
+    let _id = ui.push_id(label);
+    let (lo, hi) = (*limits.start(), *limits.end());
+    let mut kind = matches!(mapping, SizeMapping::Scaled { .. }) as usize;
+    if ui.combo_simple_string(label, &mut kind, &[tr("Fixed"), tr("Scaled")]) {
+        *mapping = match (kind == 1, &*mapping) {
+            (false, SizeMapping::Scaled { min, max, .. }) => SizeMapping::Fixed {
+                size: (min + max) / 2.0,
+            },
+            (true, SizeMapping::Fixed { size }) => SizeMapping::Scaled {
+                attribute: default_attribute(None, attributes),
+                min: (size / 2.0).max(lo),
+                max: (size * 2.0).min(hi),
+                range: None,
+            },
+            (_, mapping) => mapping.clone(),
+        };
+    }
+
+    ui.indent();
+    match mapping {
+        SizeMapping::Fixed { size } => {
+            ui.slider(tr("Value"), lo, hi, size);
+        }
+        SizeMapping::Scaled {
+            attribute,
+            min,
+            max,
+            range,
+        } => {
+            attribute_ui(ui, attribute, attributes);
+            ui.slider(tr("Min"), lo, hi, min);
+            ui.slider(tr("Max"), lo, hi, max);
+            range_ui(ui, range);
+        }
+    }
+    ui.unindent();
+}
+
+fn shape_ui(ui: &Ui, mapping: &mut ShapeMapping, attributes: &[String]) {
+    let _id = ui.push_id("shape");
+    let mut kind = matches!(mapping, ShapeMapping::Categories { .. }) as usize;
+    if ui.combo_simple_string(tr("Shape"), &mut kind, &[tr("Fixed"), tr("Categories")]) {
+        *mapping = match kind {
+            0 => ShapeMapping::Fixed {
+                shape: NodeShape::Circle,
+            },
+            _ => ShapeMapping::Categories {
+                attribute: default_attribute(None, attributes),
+            },
+        };
+    }
+
+    ui.indent();
+    match mapping {
+        ShapeMapping::Fixed { shape } => {
+            let mut index = CATEGORY_SHAPES.iter().position(|s| s == shape).unwrap();
+            if ui.combo(tr("Value"), &mut index, &CATEGORY_SHAPES, |s| {
+                format!("{s:?}").into()
+            }) {
+                *shape = CATEGORY_SHAPES[index];
+            }
+        }
+        ShapeMapping::Categories { attribute } => attribute_ui(ui, attribute, attributes),
+    }
+    ui.unindent();
+}
+
+fn label_ui(ui: &Ui, mapping: &mut LabelMapping, attributes: &[String]) {
+    const VISIBILITIES: [LabelVisibility; 3] = [
+        LabelVisibility::Never,
+        LabelVisibility::Selected,
+        LabelVisibility::Always,
+    ];
+
+    let _id = ui.push_id("label");
+    let mut index = VISIBILITIES
+        .iter()
+        .position(|v| *v == mapping.visibility)
+        .unwrap();
+    if ui.combo(tr("Labels"), &mut index, &VISIBILITIES, |v| {
+        format!("{v:?}").into()
+    }) {
+        mapping.visibility = VISIBILITIES[index];
+    }
+    if mapping.visibility != LabelVisibility::Never {
+        ui.indent();
+        attribute_ui(ui, &mut mapping.attribute, attributes);
+        ui.unindent();
+    }
+}
+
+/// Edit the theme and the category palette, previewing the palette.
+fn colors_ui(ui: &Ui, sheet: &mut StyleSheet) {
+    let mut index = Theme::ALL.iter().position(|t| *t == sheet.theme).unwrap();
+    if ui.combo(tr("Theme"), &mut index, &Theme::ALL, |t| tr(t.label())) {
+        sheet.theme = Theme::ALL[index];
+    }
+    let mut index = CategoryPalette::ALL
+        .iter()
+        .position(|p| *p == sheet.palette)
+        .unwrap();
+    if ui.combo(tr("Categories"), &mut index, &CategoryPalette::ALL, |p| {
+        tr(p.label())
+    }) {
+        sheet.palette = CategoryPalette::ALL[index];
+    }
+    swatches(ui, sheet.palette.colors());
+    if !sheet.palette.is_colorblind_safe() {
+        ui.same_line();
+        ui.text_disabled(tr("Not colorblind-safe"));
+    }
+}
+
+/// A row of squares of `colors`.
+fn swatches(ui: &Ui, colors: &[[f32; 4]]) {
+    let side = ui.frame_height();
+    let draw_list = ui.get_window_draw_list();
+    let [x, y] = ui.cursor_screen_pos();
+    for (i, color) in colors.iter().enumerate() {
+        let left = x + i as f32 * side;
+        draw_list
+            .add_rect(
+                [left + 1.0, y + 1.0],
+                [left + side - 1.0, y + side - 1.0],
+                *color,
+            )
+            .filled(true)
+            .build();
+    }
+    ui.dummy([side * colors.len() as f32, side]);
+}
+
+/// A bar of `ramp` from its start to its end, as wide as an item.
+fn ramp_preview(ui: &Ui, ramp: ColorRamp) {
+    const STEPS: usize = 32;
+
+    let width = ui.calc_item_width();
+    let height = ui.text_line_height();
+    let draw_list = ui.get_window_draw_list();
+    let [x, y] = ui.cursor_screen_pos();
+    for i in 0..STEPS {
+        let (a, b) = (i as f32 / STEPS as f32, (i + 1) as f32 / STEPS as f32);
+        let (left, right) = (ramp.sample(a), ramp.sample(b));
+        draw_list.add_rect_filled_multicolor(
+            [x + a * width, y],
+            [x + b * width, y + height],
+            left,
+            right,
+            right,
+            left,
+        );
+    }
+    ui.dummy([width, height]);
+    if !ramp.is_colorblind_safe() {
+        ui.same_line();
+        ui.text_disabled(tr("Not colorblind-safe"));
+    }
+}
+
+fn hover_ui(
+    ui: &Ui,
+    cards: &mut HoverCards,
+    node_attributes: &[String],
+    edge_attributes: &[String],
+) {
+    ui.checkbox(tr("Show on Hover"), &mut cards.enabled);
+    ui.enabled(cards.enabled, || {
+        ui.slider_config(tr("Delay"), 0.0, 2.0)
+            .display_format("%.2f s")
+            .build(&mut cards.delay);
+        attributes_ui(
+            ui,
+            "Node Attributes",
+            &mut cards.node_attributes,
+            node_attributes,
+        );
+        attributes_ui(
+            ui,
+            "Edge Attributes",
+            &mut cards.edge_attributes,
+            edge_attributes,
+        );
+    });
+}
+
+/// Combo checking which of `attributes` are `chosen`, keeping those chosen
+/// which are not among them.
+fn attributes_ui(ui: &Ui, label: &str, chosen: &mut Vec<String>, attributes: &[String]) {
+    let preview = match chosen.is_empty() {
+        true => "Automatic".to_string(),
+        false => chosen.join(", "),
+    };
+    if let Some(_combo) = ui.begin_combo(label, preview) {
+        for name in attributes {
+            let mut checked = chosen.contains(name);
+            if ui.checkbox(name, &mut checked) {
+                match checked {
+                    true => chosen.push(name.clone()),
+                    false => chosen.retain(|n| n != name),
+                }
+            }
+        }
+        if attributes.is_empty() {
+            ui.text_disabled(tr("No attributes"));
+        }
+    }
+}
+
+/// Edit the active document's [edge routing](crate::routing), given the number
+/// of visible edges.
+fn routing_ui(ui: &Ui, routes: &mut EdgeRoutes, edges: usize) {
+    let settings = &mut routes.settings;
+    let mut index = EdgeShape::ALL
+        .iter()
+        .position(|s| *s == settings.shape)
+        .unwrap();
+    if ui.combo(tr("Shape"), &mut index, &EdgeShape::ALL, |s| tr(s.label())) {
+        settings.shape = EdgeShape::ALL[index];
+    }
+    ui.indent();
+    match settings.shape {
+        EdgeShape::Straight => {}
+        EdgeShape::Curved => {
+            ui.slider(tr("Curvature"), -0.5, 0.5, &mut settings.curvature);
+        }
+        EdgeShape::Bundled => {
+            let bundling = &mut settings.bundling;
+            ui.slider(tr("Cycles"), 1, 8, &mut bundling.cycles);
+            ui.slider(tr("Iterations"), 1, 200, &mut bundling.iterations);
+            ui.slider(tr("Step"), 0.01, 1.0, &mut bundling.step);
+            ui.slider(tr("Stiffness"), 0.0, 1.0, &mut bundling.stiffness);
+            ui.slider(tr("Compatibility"), 0.0, 1.0, &mut bundling.compatibility);
+            if edges > MAX_BUNDLED_EDGES {
+                ui.text_disabled(
+                    tr("Too many edges to bundle, at most {count}")
+                        .replace("{count}", &MAX_BUNDLED_EDGES.to_string()),
+                );
+            } else if routes.is_bundling() {
+                ui.text_disabled(tr("Bundling..."));
+            }
+        }
+    }
+    ui.unindent();
+    ui.slider(
+        tr("Parallel spacing"),
+        0.0,
+        50.0,
+        &mut routes.settings.parallel_spacing,
+    );
+}
+
+/// Edit a value range, `None` meaning the graph's own range.
+fn range_ui(ui: &Ui, range: &mut Option<[f64; 2]>) {
+    let mut auto = range.is_none();
+    if ui.checkbox(tr("Automatic Range"), &mut auto) {
+        *range = match auto {
+            true => None,
+            false => Some([0.0, 1.0]),
+        };
+    }
+    if let Some([lo, hi]) = range {
+        ui.input_scalar(tr("From"), lo).build();
+        ui.input_scalar(tr("To"), hi).build();
+    }
+}
+
+impl std::fmt::Display for StyleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "{e}"),
+            Self::Parse(e) => write!(f, "Failed to parse style: {e}"),
+            Self::Serialize(e) => write!(f, "Failed to serialize style: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for StyleError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Parse(e) => Some(e),
+            Self::Serialize(e) => Some(e),
+        }
+    }
+}
+
+impl From<std::io::Error> for StyleError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<toml::de::Error> for StyleError {
+    fn from(e: toml::de::Error) -> Self {
+        Self::Parse(e)
+    }
+}
+
+impl From<toml::ser::Error> for StyleError {
+    fn from(e: toml::ser::Error) -> Self {
+        Self::Serialize(e)
+    }
+}
+
+impl std::default::Default for StyleSheet {
+    fn default() -> Self {
+        let node = NodeStyle::default();
+        let edge = EdgeStyle::default();
+        Self {
+            theme: Theme::default(),
+            palette: CategoryPalette::default(),
+            node_color: ColorMapping::Fixed { color: node.color },
+            node_size: SizeMapping::Fixed { size: node.radius },
+            node_shape: ShapeMapping::Fixed { shape: node.shape },
+            node_label: LabelMapping {
+                attribute: "label".into(),
+                visibility: LabelVisibility::Always,
+            },
+            edge_color: ColorMapping::Fixed { color: edge.color },
+            edge_width: SizeMapping::Fixed { size: edge.width },
+            edge_label: LabelMapping {
+                attribute: "label".into(),
+                visibility: LabelVisibility::Never,
+            },
+            hover: HoverCards::default(),
+        }
+    }
+}
+
+impl std::default::Default for HoverCards {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            delay: 0.4,
+            node_attributes: Vec::new(),
+            edge_attributes: Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,266 @@
+use common::time::FrameClock;
+
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use crate::imgui::Ui;
+use crate::sdl3::event::{Event, WindowEvent};
+
+use super::profiler;
+use super::scene::Scene;
+use super::window::GraphWindow;
+
+/// Time slept per frame while the window is not [drawable](GraphWindow::is_drawable).
+const UNDRAWABLE_FRAME_INTERVAL: Duration = Duration::from_millis(100);
+/// Frames still drawn after the last event or redraw, letting the ui settle,
+/// eg. hover highlights catching up with the cursor.
+const SETTLE_FRAMES: u32 = 3;
+/// Longest time waited for events per idle frame, so that updates keep
+/// polling background work.
+const IDLE_FRAME_INTERVAL: Duration = Duration::from_millis(50);
+/// Longest time between frames drawn while idle, catching changes an app does
+/// not report through [`GraphApp::needs_redraw()`].
+const IDLE_REDRAW_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Application hooks driven by [`GraphWindow::run()`].
+///
+/// Each frame, the run loop:
+/// 1. ticks the [`FrameClock`], pacing the frame rate if a limit is set
+/// 2. calls [`Self::on_event()`] for every pending SDL event
+/// 3. calls [`Self::update()`] with the frame's delta time
+/// 4. calls [`Self::draw_scene()`], if the window has a [`Scene`]
+/// 5. begins a ui frame (rendering the scene), calls [`Self::draw()`], renders,
+///    calls [`Self::post_render()`], then swaps buffers
+///
+/// Each step is timed by the [profiler](super::profiler).
+///
+/// While the window is minimized or occluded, steps 4 and 5 are skipped and frames are
+/// thinned to a few per second. They are also skipped while idle, ie. after a
+/// few frames without events in which [`Self::needs_redraw()`] returned `false`,
+/// the loop then waiting for events instead of spinning. Resizes and display
+/// scale changes are handled by the window itself.
+///
+/// Quitting (through [`Event::Quit`], closing the window, or
+/// [`AppContext::quit()`]) ends the loop after the current frame, followed by
+/// [`Self::on_exit()`].
+pub trait GraphApp {
+    /// Handle a single SDL event, after imgui has seen it.
+    fn on_event(&mut self, ctx: &mut AppContext, event: &Event) {
+        let _ = (ctx, event);
+    }
+
+    /// Handle a file dropped onto the window, eg. to open it.
+    ///
+    /// Called after [`Self::on_event()`] has seen the drop event.
+    fn on_file_dropped(&mut self, ctx: &mut AppContext, path: PathBuf) {
+        let _ = (ctx, path);
+    }
+
+    /// Advance application state by `dt`.
+    fn update(&mut self, ctx: &mut AppContext, dt: Duration) {
+        let _ = (ctx, dt);
+    }
+
+    /// Returns `true` if the frame must be drawn although no event arrived, eg.
+    /// while something animates or changed in the background. Called after
+    /// [`Self::update()`].
+    ///
+    /// Apps draw every frame by default. Those returning `false` while nothing
+    /// changes let the run loop skip frames, saving power.
+    fn needs_redraw(&mut self) -> bool {
+        true
+    }
+
+    /// Record the frame's shapes into the window's [`Scene`].
+    ///
+    /// Only called for windows with a scene, see [`GraphWindow::new_scene()`].
+    fn draw_scene(&mut self, scene: &mut Scene) {
+        let _ = scene;
+    }
+
+    /// Issue the frame's imgui widgets.
+    fn draw(&mut self, ui: &mut Ui);
+
+    /// Called after the frame is rendered and before buffers are swapped, eg. to
+    /// capture it with [`GraphWindow::capture_frame()`].
+    fn post_render(&mut self, ctx: &mut AppContext) {
+        let _ = ctx;
+    }
+
+    /// Called once after the loop ends, before the window's ui state is saved.
+    fn on_exit(&mut self, window: &GraphWindow) {
+        let _ = window;
+    }
+}
+
+/// Per-frame access to the run loop, given to [`GraphApp`] hooks.
+pub struct AppContext<'a> {
+    window: &'a GraphWindow,
+    clock: &'a mut FrameClock,
+    quit: &'a mut bool,
+}
+
+impl<'a> AppContext<'a> {
+    pub(crate) fn new(
+        window: &'a GraphWindow,
+        clock: &'a mut FrameClock,
+        quit: &'a mut bool,
+    ) -> Self {
+        Self {
+            window,
+            clock,
+            quit,
+        }
+    }
+
+    /// The window being run.
+    pub fn window(&self) -> &GraphWindow {
+        self.window
+    }
+
+    /// The clock timing the run loop.
+    pub fn clock(&self) -> &FrameClock {
+        self.clock
+    }
+
+    /// Mutable access to the clock, eg. to change the frame limit.
+    pub fn clock_mut(&mut self) -> &mut FrameClock {
+        self.clock
+    }
+
+    /// End the run loop after the current frame.
+    pub fn quit(&mut self) {
+        *self.quit = true;
+    }
+
+    /// Returns `true` if the run loop will end after the current frame.
+    pub fn is_quitting(&self) -> bool {
+        *self.quit
+    }
+}
+
+impl GraphWindow {
+    /// Run `app` until it quits, with a default [`FrameClock`].
+    ///
+    /// See [`GraphApp`] for the order hooks are called in.
+    ///
+    /// Panics if the window has no ui.
+    pub fn run(&mut self, app: impl GraphApp) {
+        self.run_with_clock(app, FrameClock::new());
+    }
+
+    /// Run `app` until it quits, timing and pacing frames with `clock`.
+    ///
+    /// Use [`FrameClock::builder()`] to set a frame limit or fixed timestep.
+    /// Vsync is controlled by the window's swap interval, see
+    /// [`GraphWindowBuilder::swap_interval()`](super::window::GraphWindowBuilder::swap_interval).
+    ///
+    /// Panics if the window has no ui.
+    pub fn run_with_clock(&mut self, mut app: impl GraphApp, mut clock: FrameClock) {
+        assert!(self.get_ui().is_some(), "Tried to run a window with no ui");
+
+        if let Some(timer) = self.get_ui().unwrap().renderer().create_gpu_timer() {
+            profiler::set_gpu_timer(timer);
+        }
+
+        let mut quit = false;
+        let mut settle = SETTLE_FRAMES;
+        let mut last_drawn = Instant::now();
+        while !quit {
+            let dt = clock.tick();
+            profiler::begin_frame();
+
+            let events = profiler::scope("events");
+            self.pump_events();
+            if self.event_queue().len() > 0 {
+                settle = SETTLE_FRAMES;
+            }
+            for event in self.events() {
+                if let Event::Quit { .. }
+                | Event::Window {
+                    win_event: WindowEvent::CloseRequested,
+                    ..
+                } = event
+                {
+                    quit = true;
+                }
+
+                app.on_event(&mut AppContext::new(self, &mut clock, &mut quit), &event);
+
+                if let Event::DropFile {
+                    window_id,
+                    filename,
+                    ..
+                } = event
+                    && window_id == self.id()
+                {
+                    app.on_file_dropped(
+                        &mut AppContext::new(self, &mut clock, &mut quit),
+                        PathBuf::from(filename),
+                    );
+                }
+            }
+            drop(events);
+
+            {
+                let _scope = profiler::scope("update");
+                app.update(&mut AppContext::new(self, &mut clock, &mut quit), dt);
+            }
+
+            if !self.is_drawable() {
+                // Nothing drawn now would be seen, so only keep events and
+                // updates flowing, at a reduced rate.
+                profiler::end_frame();
+                std::thread::sleep(UNDRAWABLE_FRAME_INTERVAL);
+                continue;
+            }
+
+            if app.needs_redraw() {
+                settle = SETTLE_FRAMES;
+            }
+            if settle == 0 && last_drawn.elapsed() < IDLE_REDRAW_INTERVAL {
+                profiler::end_frame();
+                self.wait_events(IDLE_FRAME_INTERVAL);
+                continue;
+            }
+            settle = settle.saturating_sub(1);
+            last_drawn = Instant::now();
+
+            if let Some(mut scene) = self.scene() {
+                let _scope = profiler::scope("scene");
+                scene.set_viewport(self.viewport_size());
+                app.draw_scene(&mut scene);
+            }
+
+            let mut ui_frame = self.ui_frame_begin();
+            {
+                let _scope = profiler::scope("draw");
+                let mut gui = ui_frame.get();
+                app.draw(&mut gui);
+            }
+            {
+                let _scope = profiler::scope("render");
+                let _gpu = profiler::gpu_scope("ui");
+                ui_frame.end();
+            }
+
+            {
+                let _scope = profiler::scope("post_render");
+                app.post_render(&mut AppContext::new(self, &mut clock, &mut quit));
+            }
+
+            if self.gl_context().is_some() {
+                let _scope = profiler::scope("swap");
+                self.gl_swap_window();
+            }
+
+            profiler::end_frame();
+        }
+
+        app.on_exit(self);
+
+        if let Err(e) = self.save_ui_state() {
+            eprintln!("Failed to save ui state: {e}");
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use common::renderer::texture::TextureError;
+use common::renderer::texture::image::RgbaImage;
+
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::window::GraphWindow;
+
+/// Name of the directory screenshots and recordings are saved to, inside the
+/// config directory.
+pub const CAPTURE_DIR: &str = "captures";
+
+/// Records a sequence of frames from a [`GraphWindow`] as numbered PNG files.
+///
+/// Each recording gets its own directory, with frames named `frame-00000.png`,
+/// `frame-00001.png`, etc., ready to be assembled with eg. `ffmpeg`:
+/// ```sh
+/// ffmpeg -framerate 60 -i frame-%05d.png clip.mp4
+/// ```
+///
+/// Frames must be captured after the ui is rendered and before buffers are
+/// swapped, ie. from [`GraphApp::post_render()`](super::app::GraphApp::post_render).
+pub struct FrameRecorder {
+    dir: PathBuf,
+    /// Index of the next frame to be written.
+    frame: u32,
+    /// Capture only every n-th recorded frame.
+    every: u32,
+    /// Number of frames offered to the recorder, captured or not.
+    offered: u32,
+    /// Maximum number of frames to write before stopping automatically.
+    max_frames: Option<u32>,
+}
+
+/// An idiomatic builder for [`FrameRecorder`].
+///
+/// Allows optional configuration of:
+/// - frame interval (capture every n-th frame)
+/// - maximum number of frames
+pub struct FrameRecorderBuilder {
+    dir: PathBuf,
+    every: u32,
+    max_frames: Option<u32>,
+}
+
+/// Save `image` to `path` as a PNG file, creating parent directories as needed.
+pub fn save_png(image: &RgbaImage, path: impl AsRef<Path>) -> Result<(), TextureError> {
+    let path = path.as_ref();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| TextureError::Decode(e.into()))?;
+    }
+    image
+        .save_with_format(path, common::renderer::texture::image::ImageFormat::Png)
+        .map_err(TextureError::Decode)
+}
+
+/// Encode `image` as a PNG file in memory.
+pub fn encode_png(image: &RgbaImage) -> Result<Vec<u8>, TextureError> {
+    let mut bytes = std::io::Cursor::new(Vec::new());
+    image
+        .write_to(
+            &mut bytes,
+            common::renderer::texture::image::ImageFormat::Png,
+        )
+        .map_err(TextureError::Decode)?;
+    Ok(bytes.into_inner())
+}
+
+/// A unique, timestamped path for a new capture in `dir`, eg.
+/// `dir/screenshot-1700000000000.png`.
+pub fn timestamped_path(dir: impl AsRef<Path>, prefix: &str, extension: &str) -> PathBuf {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis());
+    let name = if extension.is_empty() {
+        format!("{prefix}-{millis}")
+    } else {
+        format!("{prefix}-{millis}.{extension}")
+    };
+    dir.as_ref().join(name)
+}
+
+impl GraphWindow {
+    /// Read back the frame currently in the window's back buffer.
+    ///
+    /// Call after the ui is rendered and before buffers are swapped, eg. from
+    /// [`GraphApp::post_render()`](super::app::GraphApp::post_render).
+    ///
+    /// Errors if the ui's renderer does not support reading pixels back.
+    /// Panics if the window has no ui.
+    pub fn capture_frame(&self) -> Result<RgbaImage, TextureError> {
+        let (width, height) = self.size_in_pixels();
+        self.get_ui()
+            .expect("Tried to capture a frame of a window with no ui")
+            .renderer()
+            .read_pixels(width, height)
+    }
+
+    /// Capture the current frame and save it as a timestamped PNG in `dir`,
+    /// returning the path it was saved to.
+    ///
+    /// See [`Self::capture_frame()`].
+    pub fn save_screenshot(&self, dir: impl AsRef<Path>) -> Result<PathBuf, TextureError> {
+        let path = timestamped_path(dir, "screenshot", "png");
+        save_png(&self.capture_frame()?, &path)?;
+        Ok(path)
+    }
+}
+
+impl FrameRecorder {
+    /// Begin building a recorder writing to a new timestamped directory in `dir`.
+    pub fn builder(dir: impl AsRef<Path>) -> FrameRecorderBuilder {
+        FrameRecorderBuilder::new(dir)
+    }
+
+    /// Offer the current frame of `window` to the recorder, capturing it if it
+    /// falls on the recorder's interval.
+    ///
+    /// Returns `Ok(false)` once the recorder has written its maximum number of
+    /// frames, after which it should be dropped.
+    pub fn record(&mut self, window: &GraphWindow) -> Result<bool, TextureError> {
+        if self.is_finished() {
+            return Ok(false);
+        }
+
+        let offered = self.offered;
+        self.offered += 1;
+        if !offered.is_multiple_of(self.every) {
+            return Ok(true);
+        }
+
+        let path = self.dir.join(format!("frame-{:05}.png", self.frame));
+        save_png(&window.capture_frame()?, path)?;
+        self.frame += 1;
+
+        Ok(!self.is_finished())
+    }
+
+    /// Returns `true` once the maximum number of frames has been written.
+    pub fn is_finished(&self) -> bool {
+        self.max_frames.is_some_and(|max| self.frame >= max)
+    }
+
+    /// Number of frames written so far.
+    pub fn frames(&self) -> u32 {
+        self.frame
+    }
+
+    /// The directory frames are written to.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+}
+
+impl FrameRecorderBuilder {
+    /// Create a new [`FrameRecorderBuilder`] recording into a new timestamped
+    /// directory in `dir`.
+    pub fn new(dir: impl AsRef<Path>) -> Self {
+        Self {
+            dir: timestamped_path(dir, "recording", ""),
+            every: 1,
+            max_frames: None,
+        }
+    }
+
+    /// Build and return the configured [`FrameRecorder`].
+    pub fn build(self) -> FrameRecorder {
+        FrameRecorder {
+            dir: self.dir,
+            frame: 0,
+            every: self.every,
+            offered: 0,
+            max_frames: self.max_frames,
+        }
+    }
+
+    /// Capture only every `n`-th frame, eg. `2` to halve the frame rate of the clip.
+    ///
+    /// Defaults to every frame. Panics if `n` is zero.
+    pub fn every(mut self, n: u32) -> Self {
+        assert!(n > 0, "Tried to record every 0th frame");
+        self.every = n;
+        self
+    }
+
+    /// Stop recording automatically after `frames` frames have been written.
+    ///
+    /// Unlimited by default.
+    pub fn max_frames(mut self, frames: u32) -> Self {
+        self.max_frames = Some(frames);
+        self
+    }
+}
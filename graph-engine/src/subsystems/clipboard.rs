@@ -0,0 +1,37 @@
+use crate::imgui::ClipboardBackend;
+
+use common::renderer::SDL;
+
+/// The system clipboard, through SDL.
+///
+/// Used by [`GraphUi`](super::ui::GraphUi) unless another backend is set with
+/// [`GraphUiBuilder::clipboard_backend()`](super::ui::GraphUiBuilder::clipboard_backend),
+/// so imgui text fields share the clipboard with other applications.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct SdlClipboard;
+
+/// The text on the system clipboard, if any.
+pub fn text() -> Option<String> {
+    let clipboard = SDL.video().borrow().clipboard();
+    if !clipboard.has_clipboard_text() {
+        return None;
+    }
+    clipboard.clipboard_text().ok()
+}
+
+/// Put `text` on the system clipboard.
+pub fn set_text(text: &str) -> Result<(), crate::sdl3::Error> {
+    SDL.video().borrow().clipboard().set_clipboard_text(text)
+}
+
+impl ClipboardBackend for SdlClipboard {
+    fn get(&mut self) -> Option<String> {
+        text()
+    }
+
+    fn set(&mut self, value: &str) {
+        if let Err(e) = set_text(value) {
+            eprintln!("Failed to set the clipboard text: {e}");
+        }
+    }
+}
@@ -0,0 +1,539 @@
+//! # Configuration
+//!
+//! Settings of the application itself, as opposed to those of a document or of
+//! the [style sheet](crate::style): the rendering backend, vsync, the ui scale,
+//! how often the workspace is autosaved, the layout new graphs are placed
+//! with, and the memory budget. A [`Config`] is kept in [`CONFIG_FILE`].
+//!
+//! The [`PreferencesPanel`] edits a copy of the configuration, along with the
+//! key bindings and the language, which keep their own files. Its changes only
+//! take effect once applied, and can be reverted until then. Applying them:
+//! - writes the configuration through to its file, and the key bindings and
+//!   language to theirs
+//! - queues a [`ConfigChange`] for each setting changed, which the application
+//!   takes with [`ConfigManager::take_changes()`] and hands to the subsystem
+//!   it concerns, eg. vsync to the window
+//!
+//! ```ignore
+//! ctx.config = ConfigManager::load(ui_state.dir().join(CONFIG_FILE));
+//! for change in ctx.config.take_changes() {
+//!     match change {
+//!         ConfigChange::Vsync(_) => window.set_swap_interval(ctx.config.get().swap_interval())?,
+//!         ConfigChange::UiScale(scale) => ui.set_ui_scale(scale),
+//!         _ => {}
+//!     }
+//! }
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::context::EngineContext;
+use crate::i18n::tr;
+use crate::imgui::{SliderFlags, TreeNodeFlags, Ui};
+use crate::layout::Layout;
+use crate::sdl3::video::SwapInterval;
+use crate::session::AUTOSAVE_INTERVAL;
+use crate::subsystems::input::InputMap;
+use crate::subsystems::panels::Panel;
+
+/// Name of the file the configuration is kept in, in the application's state
+/// directory.
+pub const CONFIG_FILE: &str = "config.toml";
+/// Smallest [`Config::ui_scale`].
+pub const MIN_UI_SCALE: f32 = 0.5;
+/// Largest [`Config::ui_scale`].
+pub const MAX_UI_SCALE: f32 = 3.0;
+/// Longest [`Config::autosave_interval`] the panel offers, in seconds.
+const MAX_AUTOSAVE_INTERVAL: u64 = 3600;
+/// Largest [`Config::memory_budget`] the panel offers, in MiB.
+const MAX_MEMORY_BUDGET: u64 = 256 * 1024;
+
+/// Settings of the application, see the [module documentation](self).
+///
+/// Saved to and loaded from [`CONFIG_FILE`] in TOML format:
+/// ```toml
+/// backend = "open_gl"
+/// vsync = true
+/// ui_scale = 1.25
+/// autosave_interval = 60
+/// default_layout = "Circular"
+/// memory_budget = 8192
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Takes effect on the next start.
+    pub backend: Backend,
+    /// Whether frames are presented in sync with the display's refresh.
+    pub vsync: bool,
+    /// Scale of the ui on top of the display's, see
+    /// [`GraphUi::set_ui_scale()`](super::ui::GraphUi::set_ui_scale).
+    pub ui_scale: f32,
+    /// Seconds between autosaves of the workspace, `0` to never autosave.
+    pub autosave_interval: u64,
+    /// Name of the layout new graphs are placed with, or the force layout's.
+    pub default_layout: String,
+    /// Memory in MiB [`crate::memory`] warns about going over, `0` for none.
+    pub memory_budget: u64,
+}
+
+/// A rendering backend of the window, see [`Config::backend`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Backend {
+    #[default]
+    OpenGl,
+    Wgpu,
+}
+
+/// A setting changed by applying the [`PreferencesPanel`], with its new value.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConfigChange {
+    /// Takes effect on the next start.
+    Backend(Backend),
+    Vsync(bool),
+    UiScale(f32),
+    /// [None] to never autosave.
+    AutosaveInterval(Option<Duration>),
+    DefaultLayout(String),
+    /// In bytes, [None] for no budget.
+    MemoryBudget(Option<usize>),
+}
+
+/// Errors which can occur while loading or saving a [`Config`].
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(std::io::Error),
+    Parse(toml::de::Error),
+    Serialize(toml::ser::Error),
+}
+
+/// The configuration in use, the file it is kept in, and the changes not yet
+/// taken by the application.
+#[derive(Default)]
+pub struct ConfigManager {
+    config: Config,
+    path: Option<PathBuf>,
+    changes: Vec<ConfigChange>,
+}
+
+/// Edits the [`Config`], the key bindings, and the language, applying or
+/// reverting the changes together.
+pub struct PreferencesPanel {
+    /// The settings being edited, from when the panel was last applied or
+    /// reverted.
+    draft: Option<Draft>,
+    /// Outcome of the last apply, and whether it failed.
+    status: Option<(String, bool)>,
+}
+
+/// Settings edited in the [`PreferencesPanel`] and not yet applied.
+struct Draft {
+    config: Config,
+    /// Code of the language, [None] for English.
+    language: Option<String>,
+    /// The key bindings to revert to. They are edited in place, so that
+    /// rebinding can record them.
+    keymap: InputMap,
+}
+
+impl Backend {
+    pub const ALL: [Backend; 2] = [Self::OpenGl, Self::Wgpu];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::OpenGl => "OpenGL",
+            Self::Wgpu => "wgpu",
+        }
+    }
+
+    /// Returns `false` for backends whose renderer is not written yet.
+    pub fn is_available(self) -> bool {
+        match self {
+            Self::OpenGl => true,
+            Self::Wgpu => false,
+        }
+    }
+}
+
+impl Config {
+    /// Load a configuration from a TOML file.
+    ///
+    /// Settings missing from the file keep their defaults.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let text = std::fs::read_to_string(path)?;
+        Ok(toml::from_str(&text)?)
+    }
+
+    /// Load a configuration from a TOML file, falling back to the defaults if
+    /// the file does not exist or is invalid.
+    pub fn load_or_default(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Self::default();
+        }
+        Self::load(path).unwrap_or_else(|e| {
+            eprintln!(
+                "Failed to load configuration '{}', using defaults: {e}",
+                path.display()
+            );
+            Self::default()
+        })
+    }
+
+    /// Save the configuration to a TOML file.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ConfigError> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, toml::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// The swap interval of the window for [`Self::vsync`].
+    pub fn swap_interval(&self) -> SwapInterval {
+        match self.vsync {
+            true => SwapInterval::VSync,
+            false => SwapInterval::Immediate,
+        }
+    }
+
+    /// Time between autosaves of the workspace, [None] to never autosave.
+    pub fn autosave_interval(&self) -> Option<Duration> {
+        (self.autosave_interval > 0).then(|| Duration::from_secs(self.autosave_interval))
+    }
+
+    /// Bytes of memory [`crate::memory`] warns about going over, [None] for
+    /// no budget.
+    pub fn memory_budget(&self) -> Option<usize> {
+        (self.memory_budget > 0).then(|| self.memory_budget as usize * 1024 * 1024)
+    }
+
+    /// The settings of `other` which differ from these.
+    pub fn changes(&self, other: &Config) -> Vec<ConfigChange> {
+        let mut changes = Vec::new();
+        if self.backend != other.backend {
+            changes.push(ConfigChange::Backend(other.backend));
+        }
+        if self.vsync != other.vsync {
+            changes.push(ConfigChange::Vsync(other.vsync));
+        }
+        if self.ui_scale != other.ui_scale {
+            changes.push(ConfigChange::UiScale(other.ui_scale));
+        }
+        if self.autosave_interval != other.autosave_interval {
+            changes.push(ConfigChange::AutosaveInterval(other.autosave_interval()));
+        }
+        if self.default_layout != other.default_layout {
+            changes.push(ConfigChange::DefaultLayout(other.default_layout.clone()));
+        }
+        if self.memory_budget != other.memory_budget {
+            changes.push(ConfigChange::MemoryBudget(other.memory_budget()));
+        }
+        changes
+    }
+}
+
+impl ConfigManager {
+    /// Use the configuration kept at `path`, or the defaults if there is none.
+    /// Applied changes are saved there.
+    pub fn load(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        Self {
+            config: Config::load_or_default(&path),
+            path: Some(path),
+            changes: Vec::new(),
+        }
+    }
+
+    /// The configuration in use.
+    pub fn get(&self) -> &Config {
+        &self.config
+    }
+
+    /// Use `config`, saving it to the file if any, and queue a change for each
+    /// setting it changes.
+    ///
+    /// The configuration is used even if saving it fails.
+    pub fn apply(&mut self, config: Config) -> Result<(), ConfigError> {
+        self.changes.extend(self.config.changes(&config));
+        self.config = config;
+        match &self.path {
+            Some(path) => self.config.save(path),
+            None => Ok(()),
+        }
+    }
+
+    /// Take the changes applied since the last call, in order.
+    pub fn take_changes(&mut self) -> Vec<ConfigChange> {
+        std::mem::take(&mut self.changes)
+    }
+}
+
+impl PreferencesPanel {
+    /// Create a panel editing the settings in use once first drawn.
+    pub fn new() -> Self {
+        Self {
+            draft: None,
+            status: None,
+        }
+    }
+
+    fn rendering_ui(ui: &Ui, config: &mut Config) {
+        let preview = config.backend.label();
+        if let Some(_combo) = ui.begin_combo(tr("Backend"), preview) {
+            for backend in Backend::ALL {
+                let label = match backend.is_available() {
+                    true => backend.label().to_string(),
+                    false => format!("{} ({})", backend.label(), tr("not available yet")),
+                };
+                if ui
+                    .selectable_config(label)
+                    .selected(config.backend == backend)
+                    .disabled(!backend.is_available())
+                    .build()
+                {
+                    config.backend = backend;
+                }
+            }
+        }
+        ui.same_line();
+        ui.text_disabled(tr("(on the next start)"));
+        ui.checkbox(tr("Vsync"), &mut config.vsync);
+    }
+
+    fn interface_ui(ui: &Ui, ctx: &EngineContext, draft: &mut Draft) {
+        ui.slider_config(tr("UI Scale"), MIN_UI_SCALE, MAX_UI_SCALE)
+            .display_format("%.2fx")
+            .build(&mut draft.config.ui_scale);
+        let active = ctx
+            .locales
+            .list()
+            .iter()
+            .find(|locale| Some(&locale.code) == draft.language.as_ref());
+        let preview = active.map_or("English", |locale| locale.name.as_str());
+        if let Some(_combo) = ui.begin_combo(tr("Language"), preview) {
+            if ui
+                .selectable_config("English")
+                .selected(draft.language.is_none())
+                .build()
+            {
+                draft.language = None;
+            }
+            for locale in ctx.locales.list() {
+                if ui
+                    .selectable_config(&locale.name)
+                    .selected(draft.language.as_ref() == Some(&locale.code))
+                    .build()
+                {
+                    draft.language = Some(locale.code.clone());
+                }
+            }
+        }
+    }
+
+    fn workspace_ui(ui: &Ui, ctx: &EngineContext, config: &mut Config) {
+        let mut seconds = config.autosave_interval as i32;
+        let format = match seconds {
+            0 => tr("Never").into_owned(),
+            _ => "%d s".to_string(),
+        };
+        if ui
+            .slider_config(tr("Autosave Every"), 0, MAX_AUTOSAVE_INTERVAL as i32)
+            .display_format(format)
+            .build(&mut seconds)
+        {
+            config.autosave_interval = seconds.max(0) as u64;
+        }
+        let force = Layout::name(&ctx.force);
+        let names = std::iter::once(force).chain(ctx.layouts.iter().map(|layout| layout.name()));
+        let current = match config.default_layout.as_str() {
+            "" => force.to_string(),
+            name => name.to_string(),
+        };
+        if let Some(_combo) = ui.begin_combo(tr("Default Layout"), tr(&current)) {
+            for name in names {
+                if ui
+                    .selectable_config(tr(name))
+                    .selected(current == name)
+                    .build()
+                {
+                    config.default_layout = match name == force {
+                        true => String::new(),
+                        false => name.to_string(),
+                    };
+                }
+            }
+        }
+        let mut budget = config.memory_budget as i32;
+        let format = match budget {
+            0 => tr("None").into_owned(),
+            _ => "%d MiB".to_string(),
+        };
+        if ui
+            .slider_config(tr("Memory Budget"), 0, MAX_MEMORY_BUDGET as i32)
+            .display_format(format)
+            .flags(SliderFlags::LOGARITHMIC)
+            .build(&mut budget)
+        {
+            config.memory_budget = budget.max(0) as u64;
+        }
+        if ui.is_item_hovered() {
+            ui.tooltip_text(tr(
+                "Warn when the browser uses more memory than this, see the Memory panel",
+            ));
+        }
+    }
+
+    /// Apply the draft's changes to `ctx`, writing them to their files.
+    fn apply(ctx: &mut EngineContext, draft: &mut Draft) -> Result<(), String> {
+        let mut errors = Vec::new();
+        if let Err(e) = ctx.config.apply(draft.config.clone()) {
+            errors.push(format!("Failed to save the configuration: {e}"));
+        }
+        if ctx.locales.active() != draft.language.as_deref()
+            && let Err(e) = ctx.locales.select(draft.language.as_deref())
+        {
+            errors.push(e);
+        }
+        if *ctx.input.map() != draft.keymap {
+            match ctx.input.save() {
+                Ok(()) => draft.keymap = ctx.input.map().clone(),
+                Err(e) => errors.push(format!("Failed to save the key bindings: {e}")),
+            }
+        }
+        match errors.is_empty() {
+            true => Ok(()),
+            false => Err(errors.join("\n")),
+        }
+    }
+}
+
+impl Draft {
+    /// The settings in use in `ctx`.
+    fn new(ctx: &EngineContext) -> Self {
+        Self {
+            config: ctx.config.get().clone(),
+            language: ctx.locales.active().map(str::to_string),
+            keymap: ctx.input.map().clone(),
+        }
+    }
+
+    /// Returns `true` if anything differs from the settings in use in `ctx`.
+    fn is_changed(&self, ctx: &EngineContext) -> bool {
+        self.config != *ctx.config.get()
+            || self.language.as_deref() != ctx.locales.active()
+            || self.keymap != *ctx.input.map()
+    }
+}
+
+impl Panel for PreferencesPanel {
+    fn id(&self) -> &'static str {
+        "Preferences"
+    }
+
+    fn draw(&mut self, ui: &Ui, ctx: &mut EngineContext) {
+        let draft = self.draft.get_or_insert_with(|| Draft::new(ctx));
+        if ui.collapsing_header(tr("Rendering"), TreeNodeFlags::DEFAULT_OPEN) {
+            Self::rendering_ui(ui, &mut draft.config);
+        }
+        if ui.collapsing_header(tr("Interface"), TreeNodeFlags::DEFAULT_OPEN) {
+            Self::interface_ui(ui, ctx, draft);
+        }
+        if ui.collapsing_header(tr("Workspace"), TreeNodeFlags::DEFAULT_OPEN) {
+            Self::workspace_ui(ui, ctx, &mut draft.config);
+        }
+        if ui.collapsing_header(tr("Key Bindings"), TreeNodeFlags::empty()) {
+            ctx.input.draw_editor(ui);
+        }
+
+        ui.separator();
+        let changed = draft.is_changed(ctx);
+        let (mut apply, mut revert) = (false, false);
+        ui.enabled(changed, || {
+            apply = ui.button(tr("Apply"));
+            ui.same_line();
+            revert = ui.button(tr("Revert"));
+        });
+        if changed {
+            ui.same_line();
+            ui.text_disabled(tr("Not applied"));
+        }
+        if apply {
+            self.status = Some(match Self::apply(ctx, draft) {
+                Ok(()) => (tr("Preferences applied").into_owned(), false),
+                Err(e) => (e, true),
+            });
+        } else if revert {
+            *ctx.input.map_mut() = draft.keymap.clone();
+            self.draft = None;
+            self.status = None;
+        }
+        match &self.status {
+            Some((status, true)) => ui.text_colored([1.0, 0.4, 0.4, 1.0], status),
+            Some((status, false)) => ui.text_disabled(status),
+            None => {}
+        }
+    }
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "{e}"),
+            Self::Parse(e) => write!(f, "Failed to parse configuration: {e}"),
+            Self::Serialize(e) => write!(f, "Failed to serialize configuration: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Parse(e) => Some(e),
+            Self::Serialize(e) => Some(e),
+        }
+    }
+}
+
+impl From<std::io::Error> for ConfigError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<toml::de::Error> for ConfigError {
+    fn from(e: toml::de::Error) -> Self {
+        Self::Parse(e)
+    }
+}
+
+impl From<toml::ser::Error> for ConfigError {
+    fn from(e: toml::ser::Error) -> Self {
+        Self::Serialize(e)
+    }
+}
+
+impl std::default::Default for Config {
+    fn default() -> Self {
+        Self {
+            backend: Backend::default(),
+            vsync: true,
+            ui_scale: 1.0,
+            autosave_interval: AUTOSAVE_INTERVAL.as_secs(),
+            default_layout: String::new(),
+            memory_budget: 4096,
+        }
+    }
+}
+
+impl std::default::Default for PreferencesPanel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
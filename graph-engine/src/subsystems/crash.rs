@@ -0,0 +1,327 @@
+use parking_lot::Mutex;
+
+use std::backtrace::Backtrace;
+use std::cell::Cell;
+use std::fmt::Write as _;
+use std::panic::{PanicHookInfo, UnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::capture::timestamped_path;
+use super::panels::Panel;
+use crate::context::EngineContext;
+use crate::i18n::tr;
+use crate::imgui::{InputTextFlags, Ui};
+
+/// Name of the directory crash reports are written to, inside the config
+/// directory.
+pub const CRASH_DIR: &str = "crashes";
+/// Name of the file naming the report of a crash the next launch has not seen
+/// yet, in the crash directory.
+const PENDING_FILE: &str = "pending";
+const REPORT_EXTENSION: &str = "txt";
+/// Number of the latest undoable actions listed in a report.
+const ACTION_COUNT: usize = 20;
+
+thread_local! {
+    /// How many calls of [`catch_unwind()`] the current thread is in.
+    static CATCHING: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Writes a report to a directory when the application panics, with the
+/// backtrace, the open documents, and the last undoable actions.
+///
+/// The panic hook cannot reach the [`EngineContext`], so what it reports is a
+/// snapshot taken by [`Self::update()`] each frame. The report of the last
+/// crash is returned once by [`take_pending()`] on the next launch, eg. to
+/// offer starting in safe mode, if the panic was on the main thread: the
+/// application may outlive a panic on another one. Panics caught by
+/// [`catch_unwind()`], eg. in a failing job, are not reported.
+pub struct CrashReporter {
+    dir: PathBuf,
+    snapshot: Arc<Mutex<Snapshot>>,
+    /// The state [`Self::snapshot`] was taken in: the graph's generation, and
+    /// the number of documents and index of the active one.
+    taken: Option<(u64, usize, usize)>,
+}
+
+/// What a crash report lists about the application's state.
+#[derive(Default)]
+struct Snapshot {
+    documents: Vec<String>,
+    actions: Vec<String>,
+}
+
+/// Lists the crash reports in a directory, and shows the selected one.
+pub struct CrashReportsPanel {
+    dir: PathBuf,
+    /// Reports, newest first, as of the last refresh.
+    reports: Option<Vec<PathBuf>>,
+    selected: usize,
+    /// The contents of the selected report, read once selected.
+    text: Option<(usize, String)>,
+}
+
+impl CrashReporter {
+    /// Install a panic hook writing reports to `dir`, created as needed. The
+    /// previous hook still runs after it, eg. to print the panic.
+    pub fn install(dir: impl Into<PathBuf>) -> Self {
+        let dir = dir.into();
+        let snapshot = Arc::new(Mutex::new(Snapshot::default()));
+        let previous = std::panic::take_hook();
+        let (hook_dir, hook_snapshot) = (dir.clone(), snapshot.clone());
+        std::panic::set_hook(Box::new(move |info| {
+            if CATCHING.with(Cell::get) > 0 {
+                previous(info);
+                return;
+            }
+            match write_report(&hook_dir, info, &hook_snapshot) {
+                Ok(path) => eprintln!("Wrote a crash report to '{}'", path.display()),
+                Err(e) => eprintln!("Failed to write a crash report: {e}"),
+            }
+            previous(info);
+        }));
+        Self {
+            dir,
+            snapshot,
+            taken: None,
+        }
+    }
+
+    /// The directory reports are written to.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Record the open documents and last actions of `ctx` for a report, if
+    /// they changed since the last call.
+    pub fn update(&mut self, ctx: &EngineContext) {
+        let state = (
+            ctx.graph.generation(),
+            ctx.documents.documents().len(),
+            ctx.documents.active(),
+        );
+        if self.taken == Some(state) {
+            return;
+        }
+        self.taken = Some(state);
+
+        let active = ctx.documents.active();
+        let documents = ctx
+            .documents
+            .documents()
+            .iter()
+            .enumerate()
+            .map(|(index, document)| {
+                let mut line = match document.path() {
+                    Some(path) => format!("{} ({})", document.title(), path.display()),
+                    None => document.title(),
+                };
+                if index == active {
+                    let _ = write!(
+                        line,
+                        " [active, {} nodes, {} edges]",
+                        ctx.graph.node_count(),
+                        ctx.graph.edge_count()
+                    );
+                }
+                line
+            })
+            .collect();
+        let done = ctx.history.done();
+        let skip = done.len().saturating_sub(ACTION_COUNT);
+        let actions = done.skip(skip).map(str::to_owned).collect();
+        *self.snapshot.lock() = Snapshot { documents, actions };
+    }
+}
+
+/// Run `f`, catching its panics as [`std::panic::catch_unwind()`] does, for
+/// the [`CrashReporter`] not to report them.
+pub fn catch_unwind<R>(f: impl FnOnce() -> R + UnwindSafe) -> std::thread::Result<R> {
+    CATCHING.with(|catching| catching.set(catching.get() + 1));
+    let result = std::panic::catch_unwind(f);
+    CATCHING.with(|catching| catching.set(catching.get() - 1));
+    result
+}
+
+/// The report of a crash of the previous run, if it has not been returned
+/// since. Reports are written to `dir` by a [`CrashReporter`].
+pub fn take_pending(dir: impl AsRef<Path>) -> Option<PathBuf> {
+    let marker = dir.as_ref().join(PENDING_FILE);
+    let name = std::fs::read_to_string(&marker).ok()?;
+    let _ = std::fs::remove_file(&marker);
+    Some(dir.as_ref().join(name.trim()))
+}
+
+/// The crash reports in `dir`, newest first.
+pub fn reports(dir: impl AsRef<Path>) -> std::io::Result<Vec<PathBuf>> {
+    let mut reports: Vec<PathBuf> = match std::fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|e| Some(e.ok()?.path()))
+            .filter(|p| p.extension().is_some_and(|e| e == REPORT_EXTENSION))
+            .collect(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => return Err(e),
+    };
+    // names are timestamped, so they sort by age
+    reports.sort_unstable_by(|a, b| b.cmp(a));
+    Ok(reports)
+}
+
+/// Write the report of the panic `info` to `dir`, and mark it pending if it
+/// ends the application.
+fn write_report(
+    dir: &Path,
+    info: &PanicHookInfo,
+    snapshot: &Mutex<Snapshot>,
+) -> std::io::Result<PathBuf> {
+    let message = match info.payload().downcast_ref::<&str>() {
+        Some(message) => (*message).to_owned(),
+        None => match info.payload().downcast_ref::<String>() {
+            Some(message) => message.clone(),
+            None => "Box<dyn Any>".to_owned(),
+        },
+    };
+    let location = info
+        .location()
+        .map_or_else(String::new, |l| format!(" at {l}"));
+    let thread = std::thread::current();
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+
+    let mut report = String::new();
+    let _ = writeln!(report, "Crash report");
+    let _ = writeln!(report, "Version: {}", env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(report, "Time: {secs} (seconds since the Unix epoch)");
+    let _ = writeln!(report, "Thread: {}", thread.name().unwrap_or("<unnamed>"));
+    let _ = writeln!(report, "Panic: {message}{location}");
+    // the panic may have happened while the snapshot was being taken
+    match snapshot.try_lock() {
+        Some(snapshot) => {
+            let _ = writeln!(report, "\nOpen documents:");
+            for document in &snapshot.documents {
+                let _ = writeln!(report, "  {document}");
+            }
+            let _ = writeln!(report, "\nLast actions, oldest first:");
+            if snapshot.actions.is_empty() {
+                let _ = writeln!(report, "  (none)");
+            }
+            for action in &snapshot.actions {
+                let _ = writeln!(report, "  {action}");
+            }
+        }
+        None => {
+            let _ = writeln!(report, "\nThe application's state was unavailable.");
+        }
+    }
+    let _ = writeln!(report, "\nBacktrace:\n{}", Backtrace::force_capture());
+
+    std::fs::create_dir_all(dir)?;
+    let path = timestamped_path(dir, "crash", REPORT_EXTENSION);
+    std::fs::write(&path, report)?;
+    if thread.name() != Some("main") {
+        return Ok(path);
+    }
+    if let Some(name) = path.file_name() {
+        std::fs::write(dir.join(PENDING_FILE), name.to_string_lossy().as_bytes())?;
+    }
+    Ok(path)
+}
+
+impl CrashReportsPanel {
+    /// Create a panel listing the reports in `dir`.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            reports: None,
+            selected: 0,
+            text: None,
+        }
+    }
+
+    /// List the reports again, eg. after one was deleted.
+    fn refresh(&mut self, ctx: &mut EngineContext) {
+        let reports = match reports(&self.dir) {
+            Ok(reports) => reports,
+            Err(e) => {
+                ctx.log.error(format!(
+                    "Failed to list the crash reports in '{}': {e}",
+                    self.dir.display()
+                ));
+                Vec::new()
+            }
+        };
+        self.selected = self.selected.min(reports.len().saturating_sub(1));
+        self.reports = Some(reports);
+        self.text = None;
+    }
+}
+
+impl Panel for CrashReportsPanel {
+    fn id(&self) -> &'static str {
+        "Crash Reports"
+    }
+
+    fn draw(&mut self, ui: &Ui, ctx: &mut EngineContext) {
+        if self.reports.is_none() {
+            self.refresh(ctx);
+        }
+        if ui.button(tr("Refresh")) {
+            self.refresh(ctx);
+        }
+        let reports = self.reports.clone().unwrap_or_default();
+        if let Some(path) = reports.get(self.selected) {
+            ui.same_line();
+            if ui.button(tr("Delete")) {
+                match std::fs::remove_file(path) {
+                    Ok(()) => ctx
+                        .log
+                        .info(format!("Deleted crash report '{}'", path.display())),
+                    Err(e) => ctx.log.error(format!(
+                        "Failed to delete crash report '{}': {e}",
+                        path.display()
+                    )),
+                }
+                self.refresh(ctx);
+                return;
+            }
+        }
+        ui.text_disabled(self.dir.display().to_string());
+        ui.separator();
+
+        if reports.is_empty() {
+            ui.text_disabled(tr("No crash reports"));
+            return;
+        }
+        let names: Vec<String> = reports
+            .iter()
+            .map(|p| {
+                p.file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect();
+        ui.set_next_item_width(-1.0);
+        ui.combo_simple_string("##report", &mut self.selected, &names);
+
+        let selected = self.selected;
+        if self
+            .text
+            .as_ref()
+            .is_none_or(|(index, _)| *index != selected)
+        {
+            let text = std::fs::read_to_string(&reports[selected])
+                .unwrap_or_else(|e| format!("Failed to read the report: {e}"));
+            self.text = Some((selected, text));
+        }
+        if let Some((_, text)) = &mut self.text {
+            // read-only, but selectable to copy it eg. into a bug report
+            ui.input_text_multiline("##text", text, [-1.0, -1.0])
+                .flags(InputTextFlags::READ_ONLY)
+                .build();
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use common::renderer::texture::image::RgbaImage;
+use common::renderer::texture::{TextureError, load_image};
+
+use std::path::Path;
+use std::rc::Rc;
+
+use crate::sdl3::mouse::{Cursor, SystemCursor};
+use crate::sdl3::pixels::PixelFormat;
+use crate::sdl3::surface::Surface;
+use crate::sdl3::sys::pixels::SDL_PixelFormat;
+
+/// A standard cursor shape provided by the operating system.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum CursorShape {
+    #[default]
+    Arrow,
+    /// Pointing hand, eg. over something clickable.
+    Hand,
+    Crosshair,
+    /// Text insertion bar.
+    IBeam,
+    /// Busy, input is blocked.
+    Wait,
+    /// Busy in the background, input is still accepted.
+    Progress,
+    /// Four-way arrow, eg. while panning.
+    Move,
+    ResizeNS,
+    ResizeEW,
+    ResizeNWSE,
+    ResizeNESW,
+    NotAllowed,
+}
+
+/// A cursor created from an image.
+///
+/// Cheap to clone: clones share the same underlying SDL cursor.
+#[derive(Clone)]
+pub struct CustomCursor {
+    inner: Rc<Cursor>,
+}
+
+/// The cursor requested by the application for a [`GraphWindow`](super::window::GraphWindow).
+///
+/// See [`GraphWindow::set_cursor()`](super::window::GraphWindow::set_cursor).
+#[derive(Clone)]
+pub enum WindowCursor {
+    Shape(CursorShape),
+    Custom(CustomCursor),
+}
+
+/// Errors which can occur while creating a cursor.
+#[derive(Debug)]
+pub enum CursorError {
+    /// The cursor image could not be loaded.
+    Image(TextureError),
+    /// SDL failed to create the cursor.
+    Sdl(crate::sdl3::Error),
+}
+
+impl CursorShape {
+    fn to_sdl(self) -> SystemCursor {
+        match self {
+            CursorShape::Arrow => SystemCursor::Arrow,
+            CursorShape::Hand => SystemCursor::Hand,
+            CursorShape::Crosshair => SystemCursor::Crosshair,
+            CursorShape::IBeam => SystemCursor::IBeam,
+            CursorShape::Wait => SystemCursor::Wait,
+            CursorShape::Progress => SystemCursor::WaitArrow,
+            CursorShape::Move => SystemCursor::SizeAll,
+            CursorShape::ResizeNS => SystemCursor::SizeNS,
+            CursorShape::ResizeEW => SystemCursor::SizeWE,
+            CursorShape::ResizeNWSE => SystemCursor::SizeNWSE,
+            CursorShape::ResizeNESW => SystemCursor::SizeNESW,
+            CursorShape::NotAllowed => SystemCursor::No,
+        }
+    }
+}
+
+impl CustomCursor {
+    /// Create a cursor from an RGBA image, with its click point at `hotspot`
+    /// pixels from the image's top-left corner.
+    pub fn from_image(image: &RgbaImage, hotspot: (i32, i32)) -> Result<Self, CursorError> {
+        let (width, height) = image.dimensions();
+        let mut pixels = image.as_raw().clone();
+        let format = PixelFormat::try_from(SDL_PixelFormat::RGBA32).map_err(CursorError::Sdl)?;
+        let surface = Surface::from_data(&mut pixels, width, height, width * 4, format)
+            .map_err(CursorError::Sdl)?;
+        // SDL copies the surface's pixels, so neither needs to outlive the cursor.
+        let cursor =
+            Cursor::from_surface(surface, hotspot.0, hotspot.1).map_err(CursorError::Sdl)?;
+        Ok(Self {
+            inner: Rc::new(cursor),
+        })
+    }
+
+    /// Load a PNG or JPEG cursor image, with its click point at `hotspot`.
+    pub fn load(path: impl AsRef<Path>, hotspot: (i32, i32)) -> Result<Self, CursorError> {
+        let image = load_image(path).map_err(CursorError::Image)?;
+        Self::from_image(&image, hotspot)
+    }
+}
+
+impl WindowCursor {
+    /// Create the SDL cursor for this request.
+    pub(crate) fn create(&self) -> Result<Rc<Cursor>, CursorError> {
+        match self {
+            WindowCursor::Shape(shape) => Ok(Rc::new(
+                Cursor::from_system(shape.to_sdl()).map_err(CursorError::Sdl)?,
+            )),
+            WindowCursor::Custom(custom) => Ok(custom.inner.clone()),
+        }
+    }
+}
+
+impl From<CursorShape> for WindowCursor {
+    fn from(value: CursorShape) -> Self {
+        Self::Shape(value)
+    }
+}
+
+impl From<CustomCursor> for WindowCursor {
+    fn from(value: CustomCursor) -> Self {
+        Self::Custom(value)
+    }
+}
+
+impl std::fmt::Display for CursorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Image(e) => write!(f, "Failed to load cursor image: {e}"),
+            Self::Sdl(e) => write!(f, "Failed to create cursor: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for CursorError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Image(e) => Some(e),
+            Self::Sdl(e) => Some(e),
+        }
+    }
+}
@@ -1,63 +1,100 @@
-use parking_lot::RwLock;
-
 use common::renderer::SDL;
-use common::renderer::sdl3::EventPump;
 use common::renderer::sdl3::event::Event;
+use common::renderer::sdl3::sys::events::SDL_WaitEventTimeout;
 
-use std::sync::Arc;
+use std::cell::RefCell;
+use std::time::Duration;
 
 use super::window::GraphWindow;
 
-/// Iterator over SDL events for a [`GraphWindow`], handling GUI integration per event.
+/// The SDL events received by a [`GraphWindow`] during the current frame.
+///
+/// Filled once per frame by [`GraphWindow::pump_events()`], which drains the
+/// global SDL event pump under a single lock. Events stay buffered until the
+/// next pump, so they can be iterated any number of times in between.
+#[derive(Default)]
+pub(crate) struct EventQueue {
+    events: RefCell<Vec<Event>>,
+}
+
+/// Iterator over the SDL events buffered by a [`GraphWindow`] for the current frame.
 ///
-/// The iterator is lazy: it only polls events when consumed. On each event:
-/// - If a GUI is attached to the window, the event is given to the GUI first so it
-///   can update automatically.
-/// - The event is returned.
+/// Created via [`GraphWindow::events()`] or [`GraphWindow::poll_events()`].
+/// The iterator yields clones of the buffered events, so:
+/// - It never panics, and keeps returning [None] once exhausted.
+/// - It can be rewound with [`Self::restart()`], or cloned to iterate the same
+///   events from several places in a frame.
 ///
-/// When the iterator is exhausted of events:
-/// - Performs start-of-frame UI preparation via `ui.prepare(window)` if a GUI exists.
-/// - Subsequent calls to `next()` will not repeat preparation (window reference is taken).
-///     - Taking the window reference is done to provide a limit on the use of a single
-///       [`GraphEventIterator`]. Reuse is discouraged and disallowed.
+/// Events have already been given to the window and its ui by the time they are
+/// buffered (see [`GraphWindow::pump_events()`]).
+#[derive(Clone)]
 #[must_use = "Iterators are lazy and do nothing unless consumed"]
 pub struct GraphEventIterator<'a> {
-    window: Option<&'a GraphWindow>,
-    pump: Arc<RwLock<EventPump>>,
+    window: &'a GraphWindow,
+    /// Index of the next event to yield.
+    next: usize,
 }
 
-impl<'a> GraphEventIterator<'a> {
-    /// Create a new event iterator for the given [`GraphWindow`].
+impl EventQueue {
+    /// Drain every pending event from the global SDL event pump, replacing the
+    /// previous frame's events.
     ///
-    /// Grabs the global SDL event pump and holds a reference to the window for UI handling.
-    /// <br />
-    /// The window reference is dropped upon the first instance of the event pump returning
-    /// [None], causing a panic if next() is called afterwards.
-    pub fn new(window: &'a GraphWindow) -> Self {
-        let pump = SDL.event_pump();
-        Self {
-            window: Some(window),
-            pump,
+    /// `handle` is called for each event as it is buffered.
+    pub(crate) fn pump(&self, mut handle: impl FnMut(&Event)) {
+        let mut events = self.events.borrow_mut();
+        events.clear();
+        events.extend(SDL.event_pump().write().poll_iter());
+        for event in events.iter() {
+            handle(event);
         }
     }
+
+    /// Block until an event is pending in the global SDL event pump, or until
+    /// `timeout` passed, leaving the event for the next [`Self::pump()`].
+    pub(crate) fn wait(&self, timeout: Duration) {
+        let timeout = timeout.as_millis().min(i32::MAX as u128) as i32;
+        // SAFETY: a null event only peeks at the queue.
+        unsafe {
+            SDL_WaitEventTimeout(std::ptr::null_mut(), timeout);
+        }
+    }
+
+    /// The buffered event at `index`, if any.
+    fn get(&self, index: usize) -> Option<Event> {
+        self.events.borrow().get(index).cloned()
+    }
+
+    /// Number of buffered events.
+    pub(crate) fn len(&self) -> usize {
+        self.events.borrow().len()
+    }
+}
+
+impl<'a> GraphEventIterator<'a> {
+    /// Create an iterator over the events `window` buffered for the current frame.
+    pub(crate) fn new(window: &'a GraphWindow) -> Self {
+        Self { window, next: 0 }
+    }
+
+    /// Rewind the iterator to the frame's first event.
+    pub fn restart(&mut self) {
+        self.next = 0;
+    }
 }
 
 impl Iterator for GraphEventIterator<'_> {
     type Item = Event;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if let Some(event) = self.pump.write().poll_event() {
-            if let Some(mut ui) = self.window.unwrap().get_ui() {
-                ui.handle_event(&event);
-            }
-            Some(event)
-        } else {
-            if let Some(window) = self.window.take() {
-                if let Some(mut ui) = window.get_ui() {
-                    ui.prepare(window);
-                }
-            }
-            None
-        }
+        let event = self.window.event_queue().get(self.next)?;
+        self.next += 1;
+        Some(event)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.window.event_queue().len().saturating_sub(self.next);
+        (remaining, Some(remaining))
     }
 }
+
+impl ExactSizeIterator for GraphEventIterator<'_> {}
@@ -0,0 +1,300 @@
+use serde::{Deserialize, Serialize};
+
+use std::path::{Path, PathBuf};
+
+use super::scene::{Camera, VectorFormat, VectorScene};
+use crate::i18n::tr;
+use crate::imgui::{SliderFlags, Ui};
+use crate::spatial::Rect;
+
+/// Title and id of the dialog's modal popup.
+const TITLE: &str = "Export Image";
+/// Margin around the graph or selection, as a fraction of its size.
+const MARGIN: f32 = 0.05;
+/// Resolution offered once the page is given a printed size, in page units per
+/// inch.
+const DEFAULT_DPI: f32 = 300.0;
+
+/// The part of the world a vector export shows.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportArea {
+    /// What the camera shows.
+    #[default]
+    View,
+    /// Every visible node, at the view's aspect ratio.
+    Graph,
+    /// The selected nodes, at the view's aspect ratio.
+    Selection,
+}
+
+/// How to export the scene to a vector file, chosen in an [`ExportDialog`].
+///
+/// Saved with the workspace, so an export can be made again the same way.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExportOptions {
+    pub path: PathBuf,
+    pub format: VectorFormat,
+    pub area: ExportArea,
+    /// Color filling the page, transparent if `None`.
+    pub background: Option<[f32; 4]>,
+    /// Page units per point of the view, scaling the whole drawing.
+    pub scale: f32,
+    /// Page units per inch, giving the page a printed size, or `None` to keep
+    /// page units as points.
+    pub dpi: Option<f32>,
+    /// Written above the drawing, if not empty.
+    pub title: String,
+    /// Written below the drawing, if not empty.
+    pub caption: String,
+    /// Explain the style's colors and shapes in a corner of the drawing, see
+    /// [`Style::legend()`](crate::style::Style::legend).
+    pub legend: bool,
+}
+
+/// Modal dialog choosing the file, format, area, and look of a vector export.
+///
+/// Typical usage, each frame while the dialog exists:
+/// ```ignore
+/// match dialog.draw(ui) {
+///     ExportOutcome::Open => {}
+///     ExportOutcome::Cancelled => self.dialog = None,
+///     ExportOutcome::Confirmed(options) => { /* draw into a `VectorScene` */ }
+/// }
+/// ```
+pub struct ExportDialog {
+    path: String,
+    options: ExportOptions,
+    /// Kept while the background is transparent, so toggling it back restores it.
+    background: [f32; 4],
+    /// Whether the popup was opened yet.
+    opened: bool,
+}
+
+/// What happened in an [`ExportDialog`] during a frame.
+pub enum ExportOutcome {
+    /// The dialog is still open.
+    Open,
+    /// The user closed the dialog.
+    Cancelled,
+    /// The user confirmed the export.
+    Confirmed(ExportOptions),
+}
+
+impl ExportArea {
+    pub const ALL: [ExportArea; 3] = [Self::View, Self::Graph, Self::Selection];
+
+    /// Human-readable name of the area.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::View => "Current View",
+            Self::Graph => "Whole Graph",
+            Self::Selection => "Selection",
+        }
+    }
+}
+
+impl ExportOptions {
+    /// The camera showing the area to export, given the camera of the view and
+    /// the bounds of the graph or selection, whichever the area is. The view is
+    /// kept if there are no bounds.
+    pub fn camera(&self, view: &Camera, bounds: Option<Rect>) -> Camera {
+        let Some((min, max)) = bounds.filter(|_| self.area != ExportArea::View) else {
+            return *view;
+        };
+        let [width, height] = view.viewport();
+        let size = [
+            (max[0] - min[0]) * (1.0 + 2.0 * MARGIN),
+            (max[1] - min[1]) * (1.0 + 2.0 * MARGIN),
+        ];
+        let zoom = match size[0] > 0.0 || size[1] > 0.0 {
+            true => (width / size[0]).min(height / size[1]),
+            false => 1.0,
+        };
+        let center = [(min[0] + max[0]) * 0.5, (min[1] + max[1]) * 0.5];
+        let mut camera = Camera::new(center, zoom);
+        camera.set_viewport(view.viewport());
+        camera
+    }
+
+    /// An empty page showing the area of the options, with their background,
+    /// resolution, and title. The legend is left to the caller, which knows
+    /// the style.
+    pub fn page(&self, view: &Camera, bounds: Option<Rect>) -> VectorScene {
+        let mut page = VectorScene::new(self.camera(view, bounds), self.scale);
+        page.set_background(self.background);
+        page.set_dpi(self.dpi);
+        // dark text on light backgrounds, and the other way around
+        let light = self
+            .background
+            .is_none_or(|[r, g, b, _]| 0.299 * r + 0.587 * g + 0.114 * b > 0.5);
+        let ink = match light {
+            true => [0.0, 0.0, 0.0, 1.0],
+            false => [1.0; 4],
+        };
+        page.set_title(Some(self.title.clone()), Some(self.caption.clone()), ink);
+        page
+    }
+}
+
+impl ExportDialog {
+    /// Create a dialog suggesting to export the view to `path`, in the format
+    /// of its extension, on `background`, eg. the scene's.
+    pub fn new(path: &Path, background: [f32; 4]) -> Self {
+        Self {
+            path: path.display().to_string(),
+            options: ExportOptions {
+                path: path.to_owned(),
+                format: VectorFormat::from_path(path).unwrap_or_default(),
+                background: Some(background),
+                ..ExportOptions::default()
+            },
+            background,
+            opened: false,
+        }
+    }
+
+    /// Create a dialog suggesting to export to `path` with the settings of a
+    /// previous export, in its format.
+    pub fn with_settings(path: &Path, settings: &ExportOptions) -> Self {
+        let path = path.with_extension(settings.format.extension());
+        let background = settings.background.unwrap_or([1.0; 4]);
+        Self {
+            path: path.display().to_string(),
+            options: ExportOptions {
+                path,
+                ..settings.clone()
+            },
+            background,
+            opened: false,
+        }
+    }
+
+    /// Draw the dialog, returning whether the user confirmed or cancelled.
+    pub fn draw(&mut self, ui: &Ui) -> ExportOutcome {
+        if !self.opened {
+            ui.open_popup(TITLE);
+            self.opened = true;
+        }
+
+        let mut open = true;
+        let outcome = ui
+            .modal_popup_config(TITLE)
+            .opened(&mut open)
+            .always_auto_resize(true)
+            .build(|| self.draw_contents(ui));
+
+        match outcome {
+            Some(ExportOutcome::Open) if !open => ExportOutcome::Cancelled,
+            Some(outcome) => outcome,
+            None => ExportOutcome::Cancelled,
+        }
+    }
+
+    fn draw_contents(&mut self, ui: &Ui) -> ExportOutcome {
+        let options = &mut self.options;
+        ui.set_next_item_width(400.0);
+        ui.input_text(tr("File"), &mut self.path).build();
+        let mut index = VectorFormat::ALL
+            .iter()
+            .position(|f| *f == options.format)
+            .unwrap();
+        ui.set_next_item_width(120.0);
+        if ui.combo(tr("Format"), &mut index, &VectorFormat::ALL, |f| {
+            tr(f.label())
+        }) {
+            options.format = VectorFormat::ALL[index];
+            let path = PathBuf::from(self.path.trim()).with_extension(options.format.extension());
+            self.path = path.display().to_string();
+        }
+
+        let mut index = ExportArea::ALL
+            .iter()
+            .position(|a| *a == options.area)
+            .unwrap();
+        ui.set_next_item_width(120.0);
+        if ui.combo(tr("Area"), &mut index, &ExportArea::ALL, |a| tr(a.label())) {
+            options.area = ExportArea::ALL[index];
+        }
+        ui.set_next_item_width(120.0);
+        ui.slider_config(tr("Scale"), 0.25, 8.0)
+            .flags(SliderFlags::LOGARITHMIC)
+            .display_format("%.2fx")
+            .build(&mut options.scale);
+        let mut filled = options.background.is_some();
+        if ui.checkbox(tr("Background"), &mut filled) {
+            options.background = filled.then_some(self.background);
+        }
+        if let Some(color) = &mut options.background {
+            ui.same_line();
+            if ui.color_edit4("##background", color) {
+                self.background = *color;
+            }
+        }
+        let mut printed = options.dpi.is_some();
+        if ui.checkbox(tr("Printed Size"), &mut printed) {
+            options.dpi = printed.then_some(DEFAULT_DPI);
+        }
+        if ui.is_item_hovered() {
+            ui.tooltip_text(tr(
+                "Give the page a size in inches, for printing at a resolution",
+            ));
+        }
+        if let Some(dpi) = &mut options.dpi {
+            ui.same_line();
+            ui.set_next_item_width(120.0);
+            ui.slider_config(tr("DPI"), 72.0, 1200.0)
+                .flags(SliderFlags::LOGARITHMIC)
+                .display_format("%.0f")
+                .build(dpi);
+        }
+
+        ui.separator();
+        ui.set_next_item_width(400.0);
+        ui.input_text(tr("Title"), &mut options.title).build();
+        ui.set_next_item_width(400.0);
+        ui.input_text(tr("Caption"), &mut options.caption).build();
+        ui.checkbox(tr("Legend"), &mut options.legend);
+        if ui.is_item_hovered() {
+            ui.tooltip_text(tr("Explain the colors and shapes mapped from attributes"));
+        }
+
+        let path = PathBuf::from(self.path.trim());
+        if path.is_file() {
+            ui.text_disabled(tr("The file will be replaced"));
+        }
+        ui.separator();
+        let mut outcome = ExportOutcome::Open;
+        ui.enabled(!self.path.trim().is_empty(), || {
+            if ui.button(tr("Export")) {
+                options.path = path;
+                outcome = ExportOutcome::Confirmed(options.clone());
+            }
+        });
+        ui.same_line();
+        if ui.button(tr("Cancel")) {
+            outcome = ExportOutcome::Cancelled;
+        }
+        if !matches!(outcome, ExportOutcome::Open) {
+            ui.close_current_popup();
+        }
+        outcome
+    }
+}
+
+impl std::default::Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            path: PathBuf::new(),
+            format: VectorFormat::default(),
+            area: ExportArea::View,
+            background: None,
+            scale: 1.0,
+            dpi: None,
+            title: String::new(),
+            caption: String::new(),
+            legend: false,
+        }
+    }
+}
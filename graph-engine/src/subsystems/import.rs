@@ -0,0 +1,440 @@
+use std::path::{Path, PathBuf};
+
+use crate::graph::Graph;
+use crate::i18n::tr;
+use crate::imgui::{TableFlags, Ui};
+#[cfg(feature = "arrow")]
+use crate::io::columnar::{self, Column, ColumnarError, ColumnarOptions, NodeTable};
+use crate::io::csv::{self, CsvError, CsvOptions, DELIMITERS};
+
+/// Title and id of the dialog's modal popup.
+const TITLE: &str = "Import Edge List";
+/// Title and id of the [`ColumnarImportDialog`]'s modal popup.
+#[cfg(feature = "arrow")]
+const COLUMNAR_TITLE: &str = "Import Table";
+/// Number of rows shown in the preview table.
+const PREVIEW_ROWS: usize = 8;
+/// Labels of [`DELIMITERS`], in the same order.
+const DELIMITER_LABELS: [&str; 4] = ["Comma", "Tab", "Semicolon", "Pipe"];
+
+/// Modal dialog choosing which columns of a CSV/TSV edge list hold the source,
+/// target, and weight, with a preview of the file's first rows.
+///
+/// Typical usage, each frame while the dialog exists:
+/// ```ignore
+/// match dialog.draw(ui) {
+///     ImportOutcome::Open => {}
+///     ImportOutcome::Cancelled => self.dialog = None,
+///     ImportOutcome::Imported(result) => { /* use the graph */ }
+/// }
+/// ```
+pub struct CsvImportDialog {
+    path: PathBuf,
+    options: CsvOptions,
+    preview: Vec<Vec<String>>,
+    /// Error from the last preview or import attempt.
+    error: Option<String>,
+    /// Whether the popup was opened yet.
+    opened: bool,
+}
+
+/// Modal dialog choosing which columns of an Arrow or Parquet edge list hold
+/// the source, target, and weight, and which table holds node attributes, with
+/// a preview of the table's first rows. Used like a [`CsvImportDialog`], the
+/// table being read by a [`ColumnarLoader`](columnar::ColumnarLoader) once
+/// confirmed.
+#[cfg(feature = "arrow")]
+pub struct ColumnarImportDialog {
+    path: PathBuf,
+    options: ColumnarOptions,
+    columns: Vec<Column>,
+    preview: Vec<Vec<String>>,
+    /// Path of the node table as typed, and its columns once read.
+    node_path: String,
+    node_columns: Vec<Column>,
+    /// Error from the last attempt to read the node table.
+    error: Option<String>,
+    opened: bool,
+}
+
+/// What happened in a [`ColumnarImportDialog`] during a frame.
+#[cfg(feature = "arrow")]
+pub enum ColumnarImportOutcome {
+    /// The dialog is still open.
+    Open,
+    /// The user closed the dialog without importing.
+    Cancelled,
+    /// The user confirmed the options to read the table with.
+    Confirmed(ColumnarOptions),
+}
+
+/// What happened in a [`CsvImportDialog`] during a frame.
+pub enum ImportOutcome {
+    /// The dialog is still open.
+    Open,
+    /// The user closed the dialog without importing.
+    Cancelled,
+    /// The user confirmed, and the file was read with the chosen options.
+    Imported(Result<Graph, CsvError>),
+}
+
+impl CsvImportDialog {
+    /// Create a dialog for the edge list at `path`, with options
+    /// [sniffed](CsvOptions::sniff) from its first rows.
+    ///
+    /// Errors if the file cannot be read.
+    pub fn new(path: impl Into<PathBuf>) -> Result<Self, CsvError> {
+        let path = path.into();
+        let options = CsvOptions::sniff(&path)?;
+        let preview = csv::preview(&path, options.delimiter, PREVIEW_ROWS + 1)?;
+        Ok(Self {
+            path,
+            options,
+            preview,
+            error: None,
+            opened: false,
+        })
+    }
+
+    /// The file being imported.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The options the file will be read with.
+    pub fn options(&self) -> &CsvOptions {
+        &self.options
+    }
+
+    /// Draw the dialog, returning whether the user imported or cancelled.
+    pub fn draw(&mut self, ui: &Ui) -> ImportOutcome {
+        if !self.opened {
+            ui.open_popup(TITLE);
+            self.opened = true;
+        }
+
+        let mut open = true;
+        let outcome = ui
+            .modal_popup_config(TITLE)
+            .opened(&mut open)
+            .always_auto_resize(true)
+            .build(|| self.draw_contents(ui));
+
+        match outcome {
+            Some(ImportOutcome::Open) if !open => ImportOutcome::Cancelled,
+            Some(outcome) => outcome,
+            None => ImportOutcome::Cancelled,
+        }
+    }
+
+    fn draw_contents(&mut self, ui: &Ui) -> ImportOutcome {
+        ui.text(self.path.display().to_string());
+        ui.separator();
+
+        let mut delimiter = DELIMITERS
+            .iter()
+            .position(|d| *d == self.options.delimiter)
+            .unwrap_or(0);
+        ui.set_next_item_width(120.0);
+        if ui.combo_simple_string(tr("Delimiter"), &mut delimiter, &DELIMITER_LABELS) {
+            self.options.delimiter = DELIMITERS[delimiter];
+            self.refresh_preview();
+        }
+        ui.checkbox(tr("First row is a header"), &mut self.options.has_header);
+        ui.checkbox(tr("Directed edges"), &mut self.options.directed);
+
+        let columns = self.column_names();
+        if !columns.is_empty() {
+            ui.set_next_item_width(160.0);
+            ui.combo_simple_string(tr("Source"), &mut self.options.source, &columns);
+            ui.set_next_item_width(160.0);
+            ui.combo_simple_string(tr("Target"), &mut self.options.target, &columns);
+
+            let mut weight_columns = vec!["None".to_owned()];
+            weight_columns.extend(columns.iter().cloned());
+            let mut weight = self.options.weight.map_or(0, |w| w + 1);
+            ui.set_next_item_width(160.0);
+            if ui.combo_simple_string(tr("Weight"), &mut weight, &weight_columns) {
+                self.options.weight = weight.checked_sub(1);
+            }
+
+            self.draw_preview(ui, &columns);
+        }
+
+        if let Some(error) = &self.error {
+            ui.text_colored([1.0, 0.4, 0.4, 1.0], error);
+        }
+
+        ui.separator();
+        let valid = !columns.is_empty() && self.options.source != self.options.target;
+        let mut outcome = ImportOutcome::Open;
+        ui.enabled(valid, || {
+            if ui.button(tr("Import")) {
+                outcome = ImportOutcome::Imported(csv::load(&self.path, &self.options));
+                ui.close_current_popup();
+            }
+        });
+        ui.same_line();
+        if ui.button(tr("Cancel")) {
+            outcome = ImportOutcome::Cancelled;
+            ui.close_current_popup();
+        }
+        outcome
+    }
+
+    fn draw_preview(&self, ui: &Ui, columns: &[String]) {
+        let Some(table) = ui.begin_table_with_flags(
+            "##preview",
+            columns.len(),
+            TableFlags::ROW_BG | TableFlags::BORDERS | TableFlags::SIZING_FIXED_FIT,
+        ) else {
+            return;
+        };
+        for (index, name) in columns.iter().enumerate() {
+            let role = if index == self.options.source {
+                " (source)"
+            } else if index == self.options.target {
+                " (target)"
+            } else if Some(index) == self.options.weight {
+                " (weight)"
+            } else {
+                ""
+            };
+            ui.table_setup_column(format!("{name}{role}"));
+        }
+        ui.table_headers_row();
+
+        let skip = self.options.has_header as usize;
+        for row in self.preview.iter().skip(skip).take(PREVIEW_ROWS) {
+            ui.table_next_row();
+            for index in 0..columns.len() {
+                ui.table_next_column();
+                ui.text(row.get(index).map_or("", String::as_str));
+            }
+        }
+        table.end();
+    }
+
+    /// Names of the preview's columns: the header's fields if it has one,
+    /// otherwise `Column 1`, `Column 2`, etc.
+    fn column_names(&self) -> Vec<String> {
+        let count = self.preview.iter().map(Vec::len).max().unwrap_or(0);
+        (0..count)
+            .map(|i| match self.preview.first() {
+                Some(header) if self.options.has_header => header
+                    .get(i)
+                    .map(|f| f.trim().to_owned())
+                    .filter(|f| !f.is_empty())
+                    .unwrap_or_else(|| column_name(i)),
+                _ => column_name(i),
+            })
+            .collect()
+    }
+
+    /// Re-read the preview after the delimiter changed, keeping the column mapping
+    /// in range.
+    fn refresh_preview(&mut self) {
+        match csv::preview(&self.path, self.options.delimiter, PREVIEW_ROWS + 1) {
+            Ok(preview) => {
+                let columns = preview.iter().map(Vec::len).max().unwrap_or(0);
+                self.options.source = self.options.source.min(columns.saturating_sub(1));
+                self.options.target = self.options.target.min(columns.saturating_sub(1));
+                self.options.weight = self.options.weight.filter(|w| *w < columns);
+                self.preview = preview;
+                self.error = None;
+            }
+            Err(e) => self.error = Some(e.to_string()),
+        }
+    }
+}
+
+/// Name of the column at `index` of a file without a header.
+fn column_name(index: usize) -> String {
+    tr("Column {index}").replace("{index}", &(index + 1).to_string())
+}
+
+#[cfg(feature = "arrow")]
+impl ColumnarImportDialog {
+    /// Create a dialog for the edge list table at `path`, with options
+    /// [guessed](ColumnarOptions::guess) from its column names.
+    ///
+    /// Errors if the file cannot be read.
+    pub fn new(path: impl Into<PathBuf>) -> Result<Self, ColumnarError> {
+        let path = path.into();
+        let columns = columnar::columns(&path)?;
+        let preview = columnar::preview(&path, PREVIEW_ROWS)?;
+        Ok(Self {
+            options: ColumnarOptions::guess(&columns),
+            path,
+            columns,
+            preview,
+            node_path: String::new(),
+            node_columns: Vec::new(),
+            error: None,
+            opened: false,
+        })
+    }
+
+    /// The file being imported.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Draw the dialog, returning whether the user confirmed or cancelled.
+    pub fn draw(&mut self, ui: &Ui) -> ColumnarImportOutcome {
+        if !self.opened {
+            ui.open_popup(COLUMNAR_TITLE);
+            self.opened = true;
+        }
+
+        let mut open = true;
+        let outcome = ui
+            .modal_popup_config(COLUMNAR_TITLE)
+            .opened(&mut open)
+            .always_auto_resize(true)
+            .build(|| self.draw_contents(ui));
+
+        match outcome {
+            Some(ColumnarImportOutcome::Open) if !open => ColumnarImportOutcome::Cancelled,
+            Some(outcome) => outcome,
+            None => ColumnarImportOutcome::Cancelled,
+        }
+    }
+
+    fn draw_contents(&mut self, ui: &Ui) -> ColumnarImportOutcome {
+        ui.text(self.path.display().to_string());
+        ui.separator();
+
+        ui.checkbox(tr("Directed edges"), &mut self.options.directed);
+        let columns: Vec<String> = self
+            .columns
+            .iter()
+            .map(|c| format!("{} ({})", c.name, c.data_type))
+            .collect();
+        if !columns.is_empty() {
+            ui.set_next_item_width(200.0);
+            ui.combo_simple_string(tr("Source"), &mut self.options.source, &columns);
+            ui.set_next_item_width(200.0);
+            ui.combo_simple_string(tr("Target"), &mut self.options.target, &columns);
+
+            let mut weight_columns = vec!["None".to_owned()];
+            weight_columns.extend(columns.iter().cloned());
+            let mut weight = self.options.weight.map_or(0, |w| w + 1);
+            ui.set_next_item_width(200.0);
+            if ui.combo_simple_string(tr("Weight"), &mut weight, &weight_columns) {
+                self.options.weight = weight.checked_sub(1);
+            }
+            if self
+                .options
+                .weight
+                .is_some_and(|w| !self.columns[w].is_numeric())
+            {
+                ui.text_colored([1.0, 0.4, 0.4, 1.0], tr("The weight column is not numeric"));
+            }
+
+            self.draw_preview(ui);
+        }
+
+        ui.separator();
+        self.draw_node_table(ui);
+
+        ui.separator();
+        let valid = !columns.is_empty() && self.options.source != self.options.target;
+        let mut outcome = ColumnarImportOutcome::Open;
+        ui.enabled(valid, || {
+            if ui.button(tr("Import")) {
+                outcome = ColumnarImportOutcome::Confirmed(self.options.clone());
+                ui.close_current_popup();
+            }
+        });
+        ui.same_line();
+        if ui.button(tr("Cancel")) {
+            outcome = ColumnarImportOutcome::Cancelled;
+            ui.close_current_popup();
+        }
+        outcome
+    }
+
+    fn draw_preview(&self, ui: &Ui) {
+        let Some(table) = ui.begin_table_with_flags(
+            "##preview",
+            self.columns.len(),
+            TableFlags::ROW_BG | TableFlags::BORDERS | TableFlags::SIZING_FIXED_FIT,
+        ) else {
+            return;
+        };
+        for (index, column) in self.columns.iter().enumerate() {
+            let role = if index == self.options.source {
+                " (source)"
+            } else if index == self.options.target {
+                " (target)"
+            } else if Some(index) == self.options.weight {
+                " (weight)"
+            } else {
+                ""
+            };
+            ui.table_setup_column(format!("{}{role}", column.name));
+        }
+        ui.table_headers_row();
+
+        for row in &self.preview {
+            ui.table_next_row();
+            for index in 0..self.columns.len() {
+                ui.table_next_column();
+                ui.text(row.get(index).map_or("", String::as_str));
+            }
+        }
+        table.end();
+    }
+
+    /// Choose the table of node attributes, if any, and its id column.
+    fn draw_node_table(&mut self, ui: &Ui) {
+        ui.text(tr("Node attributes (optional)"));
+        ui.set_next_item_width(300.0);
+        ui.input_text("##nodes", &mut self.node_path)
+            .hint(tr("Path of an Arrow or Parquet table"))
+            .build();
+        ui.same_line();
+        if ui.button(tr("Read")) {
+            self.read_node_table();
+        }
+        if let Some(error) = &self.error {
+            ui.text_colored([1.0, 0.4, 0.4, 1.0], error);
+        }
+        let Some(nodes) = &mut self.options.nodes else {
+            return;
+        };
+        let columns: Vec<String> = self
+            .node_columns
+            .iter()
+            .map(|c| format!("{} ({})", c.name, c.data_type))
+            .collect();
+        ui.set_next_item_width(200.0);
+        ui.combo_simple_string(tr("Node id"), &mut nodes.id, &columns);
+        ui.same_line();
+        if ui.small_button(tr("Remove")) {
+            self.options.nodes = None;
+            self.node_columns.clear();
+        }
+    }
+
+    /// Read the columns of the node table at the typed path, guessing its id
+    /// column, or clear it if no path is typed.
+    fn read_node_table(&mut self) {
+        let path = self.node_path.trim();
+        if path.is_empty() {
+            self.options.nodes = None;
+            self.node_columns.clear();
+            self.error = None;
+            return;
+        }
+        match columnar::columns(path) {
+            Ok(columns) => {
+                self.options.nodes = Some(NodeTable::guess(path, &columns));
+                self.node_columns = columns;
+                self.error = None;
+            }
+            Err(e) => self.error = Some(e.to_string()),
+        }
+    }
+}
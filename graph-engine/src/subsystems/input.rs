@@ -0,0 +1,1066 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use std::collections::{BTreeMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::bookmarks;
+use crate::i18n::tr;
+use crate::imgui::{TableFlags, Ui};
+use crate::sdl3::event::Event;
+use crate::sdl3::keyboard::{Keycode, Mod};
+use crate::sdl3::mouse::MouseButton;
+
+/// Name of the keymap file inside the config directory.
+pub const KEYMAP_FILE: &str = "keymap.toml";
+
+/// Maximum delay between the chords of a multi-chord [`Binding`].
+const CHORD_TIMEOUT: Duration = Duration::from_millis(1000);
+
+/// Maximum number of chords in a single [`Binding`].
+const MAX_CHORDS: usize = 2;
+
+/// A semantic action the user can trigger, independent of the input producing it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    /// Pan the view while held.
+    Pan,
+    /// Zoom the view in one step.
+    ZoomIn,
+    /// Zoom the view out one step.
+    ZoomOut,
+    /// Select the node or edge under the cursor, or drag a selection rectangle.
+    SelectNode,
+    /// Like [`Action::SelectNode`], adding to the selection.
+    AddToSelection,
+    /// Like [`Action::SelectNode`], toggling whether elements are selected.
+    ToggleSelection,
+    /// Zoom and pan to show the whole graph.
+    FitToGraph,
+    /// Focus the node search field.
+    OpenSearch,
+    /// Open the [command palette](crate::palette).
+    CommandPalette,
+    /// Save a screenshot of the window.
+    Screenshot,
+    /// Start or stop recording frames.
+    ToggleRecording,
+    /// Undo the last change to the graph.
+    Undo,
+    /// Redo the last undone change to the graph.
+    Redo,
+    /// Remove the selected nodes and edges from the graph.
+    DeleteSelection,
+    /// Copy the selected nodes and the edges between them, see
+    /// [`clipboard`](crate::clipboard).
+    Copy,
+    /// Paste the copied nodes at the cursor.
+    Paste,
+    /// Fetch the neighbors of the selected nodes, see [`explore`](crate::explore).
+    ExpandSelection,
+    /// Hide what was reached only through the selected nodes.
+    CollapseSelection,
+    /// Go to the next [bookmark](crate::bookmarks) of the document.
+    NextBookmark,
+    /// Go to the previous bookmark of the document.
+    PreviousBookmark,
+    /// Start or stop picking the endpoints of a path, see
+    /// [`pathfind`](crate::pathfind).
+    FindPath,
+}
+
+/// Whether an [`Action`] started or stopped.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ActionState {
+    Pressed,
+    Released,
+}
+
+/// An [`Action`] produced by an [`InputMapper`] from raw SDL events.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ActionEvent {
+    pub action: Action,
+    pub state: ActionState,
+    /// Cursor position in window coordinates, for actions triggered by the mouse.
+    pub position: Option<[f32; 2]>,
+}
+
+/// Modifier keys held as part of a [`Chord`].
+///
+/// Left and right variants of each modifier are treated as the same key.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Modifiers {
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+    pub gui: bool,
+}
+
+/// The non-modifier part of a [`Chord`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Trigger {
+    Key(Keycode),
+    Mouse(MouseButton),
+    WheelUp,
+    WheelDown,
+}
+
+/// A single trigger combined with modifier keys, eg. `Ctrl+Shift+F`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Chord {
+    pub modifiers: Modifiers,
+    pub trigger: Trigger,
+}
+
+/// One or more [`Chord`]s pressed in sequence, eg. `Ctrl+K Ctrl+S`.
+///
+/// Bindings are written in config files as strings: chords are separated by
+/// spaces, and modifiers are prefixed to the trigger with `+`. Triggers are SDL
+/// key names (`F`, `=`, `Escape`), `MouseLeft`/`MouseMiddle`/`MouseRight`/
+/// `MouseX1`/`MouseX2`, or `WheelUp`/`WheelDown`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Binding(Vec<Chord>);
+
+/// Which input devices imgui currently wants for itself.
+///
+/// See also: [`GraphUi::input_capture()`](super::ui::GraphUi::input_capture).
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct InputCapture {
+    pub keyboard: bool,
+    pub mouse: bool,
+    /// A text field is focused, so typed characters belong to imgui.
+    pub text: bool,
+}
+
+/// A rebindable mapping from [`Action`]s to their [`Binding`]s, and from the
+/// ids of [palette commands](crate::palette::PaletteCommand) to theirs.
+///
+/// Loaded from and saved to [`KEYMAP_FILE`] in TOML format:
+/// ```toml
+/// [bindings]
+/// pan = ["MouseMiddle"]
+/// zoom_in = ["WheelUp", "Ctrl+="]
+/// open_search = ["Ctrl+F"]
+///
+/// [commands]
+/// "script.run:tidy" = ["Ctrl+Shift+T"]
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct InputMap {
+    #[serde(default)]
+    bindings: BTreeMap<Action, Vec<Binding>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    commands: BTreeMap<String, Vec<Binding>>,
+}
+
+/// Errors which can occur while loading or saving an [`InputMap`].
+#[derive(Debug)]
+pub enum InputMapError {
+    Io(std::io::Error),
+    Parse(toml::de::Error),
+    Serialize(toml::ser::Error),
+}
+
+/// Translates raw SDL events into [`ActionEvent`]s through an [`InputMap`],
+/// and into the ids of the palette commands whose bindings were pressed.
+///
+/// Typical usage:
+/// ```ignore
+/// for event in window.poll_events() {
+///     input.handle(&event, window.get_ui().map(|ui| ui.input_capture()).unwrap_or_default());
+/// }
+/// for action in input.drain() {
+///     // react to action
+/// }
+/// for id in input.drain_commands() {
+///     palette::run(&mut ctx, &id);
+/// }
+/// ```
+pub struct InputMapper {
+    map: InputMap,
+    /// Where [`Self::map`] was loaded from, and is saved to by the rebinding ui.
+    path: Option<PathBuf>,
+
+    /// Modifiers currently held down.
+    modifiers: Modifiers,
+    /// Last known cursor position.
+    cursor: [f32; 2],
+    /// Chords pressed so far towards a multi-chord binding.
+    pending: Vec<Chord>,
+    /// Time at which the last pending chord was pressed.
+    pending_at: Instant,
+    /// Actions currently held, with the trigger that will release them.
+    held: Vec<(Action, Trigger)>,
+    /// Actions produced but not yet drained.
+    queue: VecDeque<ActionEvent>,
+    /// Ids of the commands pressed but not yet drained.
+    commands: VecDeque<String>,
+
+    /// In-progress rebinding started from the rebinding ui.
+    rebinding: Option<Rebinding>,
+    /// Last error from the rebinding ui, shown until the next save.
+    editor_error: Option<String>,
+}
+
+/// State of an in-progress rebinding.
+struct Rebinding {
+    action: Action,
+    chords: Vec<Chord>,
+    last: Instant,
+}
+
+impl Action {
+    /// Every action, in display order.
+    pub const ALL: &'static [Action] = &[
+        Action::Pan,
+        Action::ZoomIn,
+        Action::ZoomOut,
+        Action::SelectNode,
+        Action::AddToSelection,
+        Action::ToggleSelection,
+        Action::FitToGraph,
+        Action::OpenSearch,
+        Action::CommandPalette,
+        Action::Screenshot,
+        Action::ToggleRecording,
+        Action::Undo,
+        Action::Redo,
+        Action::DeleteSelection,
+        Action::Copy,
+        Action::Paste,
+        Action::ExpandSelection,
+        Action::CollapseSelection,
+        Action::NextBookmark,
+        Action::PreviousBookmark,
+        Action::FindPath,
+    ];
+
+    /// Human-readable name of the action.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Action::Pan => "Pan",
+            Action::ZoomIn => "Zoom In",
+            Action::ZoomOut => "Zoom Out",
+            Action::SelectNode => "Select Node",
+            Action::AddToSelection => "Add to Selection",
+            Action::ToggleSelection => "Toggle Selection",
+            Action::FitToGraph => "Fit to Graph",
+            Action::OpenSearch => "Open Search",
+            Action::CommandPalette => "Command Palette",
+            Action::Screenshot => "Screenshot",
+            Action::ToggleRecording => "Toggle Recording",
+            Action::Undo => "Undo",
+            Action::Redo => "Redo",
+            Action::DeleteSelection => "Delete Selection",
+            Action::Copy => "Copy",
+            Action::Paste => "Paste",
+            Action::ExpandSelection => "Expand Selection",
+            Action::CollapseSelection => "Collapse Selection",
+            Action::NextBookmark => "Next Bookmark",
+            Action::PreviousBookmark => "Previous Bookmark",
+            Action::FindPath => "Find Path",
+        }
+    }
+}
+
+impl Modifiers {
+    /// Extract the modifiers from SDL's modifier state.
+    pub fn from_sdl(keymod: Mod) -> Self {
+        Self {
+            ctrl: keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD),
+            shift: keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD),
+            alt: keymod.intersects(Mod::LALTMOD | Mod::RALTMOD),
+            gui: keymod.intersects(Mod::LGUIMOD | Mod::RGUIMOD),
+        }
+    }
+}
+
+impl Trigger {
+    /// Returns `true` if the trigger has a matching release (ie. is not a wheel step).
+    fn is_holdable(&self) -> bool {
+        matches!(self, Trigger::Key(_) | Trigger::Mouse(_))
+    }
+}
+
+impl Chord {
+    /// Create a chord with no modifiers.
+    pub fn new(trigger: Trigger) -> Self {
+        Self {
+            modifiers: Modifiers::default(),
+            trigger,
+        }
+    }
+
+    /// Add the ctrl modifier to the chord.
+    pub fn ctrl(mut self) -> Self {
+        self.modifiers.ctrl = true;
+        self
+    }
+
+    /// Add the shift modifier to the chord.
+    pub fn shift(mut self) -> Self {
+        self.modifiers.shift = true;
+        self
+    }
+
+    /// Add the alt modifier to the chord.
+    pub fn alt(mut self) -> Self {
+        self.modifiers.alt = true;
+        self
+    }
+}
+
+impl Binding {
+    /// A binding consisting of a single chord.
+    pub fn single(chord: Chord) -> Self {
+        Self(vec![chord])
+    }
+
+    /// A binding consisting of a sequence of chords.
+    ///
+    /// Panics if `chords` is empty.
+    pub fn sequence(chords: impl Into<Vec<Chord>>) -> Self {
+        let chords = chords.into();
+        assert!(
+            !chords.is_empty(),
+            "Tried to create a binding with no chords"
+        );
+        Self(chords)
+    }
+
+    /// The chords making up this binding, in order.
+    pub fn chords(&self) -> &[Chord] {
+        &self.0
+    }
+}
+
+impl InputMap {
+    /// Create an input map with no bindings.
+    pub fn empty() -> Self {
+        Self {
+            bindings: BTreeMap::new(),
+            commands: BTreeMap::new(),
+        }
+    }
+
+    /// Load an input map from a TOML file.
+    ///
+    /// Actions missing from the file keep their default bindings.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, InputMapError> {
+        let text = std::fs::read_to_string(path)?;
+        let loaded: InputMap = toml::from_str(&text)?;
+
+        let mut map = Self::default();
+        map.bindings.extend(loaded.bindings);
+        map.commands = loaded.commands;
+        Ok(map)
+    }
+
+    /// Load an input map from a TOML file, falling back to the defaults if the file
+    /// does not exist or is invalid.
+    pub fn load_or_default(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Self::default();
+        }
+        Self::load(path).unwrap_or_else(|e| {
+            eprintln!(
+                "Failed to load keymap '{}', using defaults: {e}",
+                path.display()
+            );
+            Self::default()
+        })
+    }
+
+    /// Save the input map to a TOML file.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), InputMapError> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, toml::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// The bindings of `action`.
+    pub fn bindings(&self, action: Action) -> &[Binding] {
+        self.bindings.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// Add `binding` to `action`, removing it from any other action first.
+    pub fn bind(&mut self, action: Action, binding: Binding) {
+        self.unbind_all(&binding);
+        self.bindings.entry(action).or_default().push(binding);
+    }
+
+    /// Remove every binding of `action`.
+    pub fn clear(&mut self, action: Action) {
+        self.bindings.remove(&action);
+    }
+
+    /// The bindings of the palette command `id`.
+    pub fn command_bindings(&self, id: &str) -> &[Binding] {
+        self.commands.get(id).map_or(&[], Vec::as_slice)
+    }
+
+    /// Add `binding` to the palette command `id`, removing it from any action
+    /// or other command first.
+    pub fn bind_command(&mut self, id: impl Into<String>, binding: Binding) {
+        self.unbind_all(&binding);
+        self.commands.entry(id.into()).or_default().push(binding);
+    }
+
+    /// Remove every binding of the palette command `id`.
+    pub fn clear_command(&mut self, id: &str) {
+        self.commands.remove(id);
+    }
+
+    /// Remove `binding` from every action and command it is bound to.
+    pub fn unbind_all(&mut self, binding: &Binding) {
+        for bindings in self.bindings.values_mut().chain(self.commands.values_mut()) {
+            bindings.retain(|b| b != binding);
+        }
+        self.commands.retain(|_, bindings| !bindings.is_empty());
+    }
+
+    /// The action bound to exactly `chords`, if any.
+    fn action_for(&self, chords: &[Chord]) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|(_, bindings)| bindings.iter().any(|b| b.0 == chords))
+            .map(|(action, _)| *action)
+    }
+
+    /// The id of the command bound to exactly `chords`, if any.
+    fn command_for(&self, chords: &[Chord]) -> Option<&str> {
+        self.commands
+            .iter()
+            .find(|(_, bindings)| bindings.iter().any(|b| b.0 == chords))
+            .map(|(id, _)| id.as_str())
+    }
+
+    /// Returns `true` if `chords` is a strict prefix of any binding.
+    fn is_prefix(&self, chords: &[Chord]) -> bool {
+        self.bindings
+            .values()
+            .chain(self.commands.values())
+            .flatten()
+            .any(|b| b.0.len() > chords.len() && b.0.starts_with(chords))
+    }
+}
+
+impl InputMapper {
+    /// Create a mapper using `map`.
+    pub fn new(map: InputMap) -> Self {
+        Self {
+            map,
+            path: None,
+
+            modifiers: Modifiers::default(),
+            cursor: [0.0, 0.0],
+            pending: Vec::new(),
+            pending_at: Instant::now(),
+            held: Vec::new(),
+            queue: VecDeque::new(),
+            commands: VecDeque::new(),
+
+            rebinding: None,
+            editor_error: None,
+        }
+    }
+
+    /// Create a mapper using the keymap at `path`, which the rebinding ui saves to.
+    ///
+    /// See [`InputMap::load_or_default()`].
+    pub fn from_file(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let mut mapper = Self::new(InputMap::load_or_default(&path));
+        mapper.path = Some(path);
+        mapper
+    }
+
+    /// The input map in use.
+    pub fn map(&self) -> &InputMap {
+        &self.map
+    }
+
+    /// Mutable access to the input map in use.
+    pub fn map_mut(&mut self) -> &mut InputMap {
+        &mut self.map
+    }
+
+    /// Returns `true` while a holdable binding of `action` is held down.
+    pub fn is_active(&self, action: Action) -> bool {
+        self.held.iter().any(|(a, _)| *a == action)
+    }
+
+    /// Last known cursor position, in window coordinates.
+    pub fn cursor(&self) -> [f32; 2] {
+        self.cursor
+    }
+
+    /// Take every [`ActionEvent`] produced since the last call.
+    pub fn drain(&mut self) -> impl Iterator<Item = ActionEvent> + '_ {
+        self.queue.drain(..)
+    }
+
+    /// Take the ids of the palette commands whose bindings were pressed since
+    /// the last call, in order.
+    pub fn drain_commands(&mut self) -> impl Iterator<Item = String> + '_ {
+        self.commands.drain(..)
+    }
+
+    /// Save the input map to the file it was loaded from, if any, as the
+    /// rebinding ui does.
+    pub fn save(&self) -> Result<(), InputMapError> {
+        match &self.path {
+            Some(path) => self.map.save(path),
+            None => Ok(()),
+        }
+    }
+
+    /// Feed a raw SDL event to the mapper.
+    ///
+    /// Presses are ignored for devices imgui has `capture`d (eg. while typing into
+    /// a text field), but releases are always processed so that held actions end.
+    pub fn handle(&mut self, event: &Event, capture: InputCapture) {
+        match *event {
+            Event::KeyDown {
+                keycode: Some(keycode),
+                keymod,
+                repeat,
+                ..
+            } => {
+                self.modifiers = Modifiers::from_sdl(keymod);
+                if repeat || is_modifier(keycode) {
+                    return;
+                }
+                if self.rebinding.is_some() {
+                    self.record(Trigger::Key(keycode));
+                } else if !capture.keyboard {
+                    self.press(Trigger::Key(keycode), None);
+                }
+            }
+            Event::KeyUp {
+                keycode: Some(keycode),
+                keymod,
+                ..
+            } => {
+                self.modifiers = Modifiers::from_sdl(keymod);
+                self.release(Trigger::Key(keycode));
+            }
+            Event::MouseMotion { x, y, .. } => {
+                self.cursor = [x, y];
+            }
+            Event::MouseButtonDown {
+                mouse_btn, x, y, ..
+            } => {
+                self.cursor = [x, y];
+                if self.rebinding.is_some() {
+                    self.record(Trigger::Mouse(mouse_btn));
+                } else if !capture.mouse {
+                    self.press(Trigger::Mouse(mouse_btn), Some([x, y]));
+                }
+            }
+            Event::MouseButtonUp {
+                mouse_btn, x, y, ..
+            } => {
+                self.cursor = [x, y];
+                self.release(Trigger::Mouse(mouse_btn));
+            }
+            Event::MouseWheel {
+                y,
+                mouse_x,
+                mouse_y,
+                ..
+            } if y != 0.0 => {
+                self.cursor = [mouse_x, mouse_y];
+                let trigger = if y > 0.0 {
+                    Trigger::WheelUp
+                } else {
+                    Trigger::WheelDown
+                };
+                if self.rebinding.is_some() {
+                    self.record(trigger);
+                } else if !capture.mouse {
+                    self.press(trigger, Some([mouse_x, mouse_y]));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Match a newly pressed trigger against the map, continuing any pending sequence.
+    fn press(&mut self, trigger: Trigger, position: Option<[f32; 2]>) {
+        let chord = Chord {
+            modifiers: self.modifiers,
+            trigger,
+        };
+
+        if self.pending_at.elapsed() > CHORD_TIMEOUT {
+            self.pending.clear();
+        }
+
+        let mut sequence = std::mem::take(&mut self.pending);
+        sequence.push(chord);
+
+        let bound = |chords: &[Chord]| {
+            self.map.action_for(chords).is_some() || self.map.command_for(chords).is_some()
+        };
+        let sequence = if bound(&sequence) {
+            sequence
+        } else if self.map.is_prefix(&sequence) {
+            self.pending = sequence;
+            self.pending_at = Instant::now();
+            return;
+        } else {
+            // A failed sequence falls back to the last chord on its own.
+            vec![chord]
+        };
+
+        if let Some(id) = self.map.command_for(&sequence) {
+            self.commands.push_back(id.to_owned());
+            return;
+        }
+        let Some(action) = self.map.action_for(&sequence) else {
+            return;
+        };
+
+        self.queue.push_back(ActionEvent {
+            action,
+            state: ActionState::Pressed,
+            position,
+        });
+
+        if trigger.is_holdable() {
+            self.held.push((action, trigger));
+        } else {
+            self.queue.push_back(ActionEvent {
+                action,
+                state: ActionState::Released,
+                position,
+            });
+        }
+    }
+
+    /// End every held action released by `trigger`.
+    fn release(&mut self, trigger: Trigger) {
+        let cursor = self.cursor;
+        let queue = &mut self.queue;
+        self.held.retain(|(action, held)| {
+            if *held != trigger {
+                return true;
+            }
+            queue.push_back(ActionEvent {
+                action: *action,
+                state: ActionState::Released,
+                position: matches!(trigger, Trigger::Mouse(_)).then_some(cursor),
+            });
+            false
+        });
+    }
+
+    /// Record a chord into the in-progress rebinding.
+    fn record(&mut self, trigger: Trigger) {
+        let Some(rebinding) = self.rebinding.as_mut() else {
+            return;
+        };
+
+        if trigger == Trigger::Key(Keycode::Escape) && self.modifiers == Modifiers::default() {
+            self.rebinding = None;
+            return;
+        }
+
+        rebinding.chords.push(Chord {
+            modifiers: self.modifiers,
+            trigger,
+        });
+        rebinding.last = Instant::now();
+
+        if rebinding.chords.len() >= MAX_CHORDS {
+            self.finish_rebinding();
+        }
+    }
+
+    /// Commit the in-progress rebinding, if it recorded any chords.
+    fn finish_rebinding(&mut self) {
+        let Some(rebinding) = self.rebinding.take() else {
+            return;
+        };
+        if !rebinding.chords.is_empty() {
+            self.map
+                .bind(rebinding.action, Binding::sequence(rebinding.chords));
+        }
+    }
+
+    /// Draw the rebinding ui: a table of every action with its bindings.
+    ///
+    /// Clicking "Bind" records the next one or two chords pressed as a new binding
+    /// for that action; Escape cancels. Bindings are saved to the file the map was
+    /// loaded from, if any.
+    pub fn draw_editor(&mut self, ui: &Ui) {
+        if let Some(rebinding) = self.rebinding.as_ref()
+            && !rebinding.chords.is_empty()
+            && rebinding.last.elapsed() > CHORD_TIMEOUT
+        {
+            self.finish_rebinding();
+        }
+
+        if let Some(table) = ui.begin_table_with_flags(
+            "##keymap",
+            3,
+            TableFlags::ROW_BG | TableFlags::BORDERS_INNER_H | TableFlags::SIZING_STRETCH_PROP,
+        ) {
+            ui.table_setup_column(tr("Action"));
+            ui.table_setup_column(tr("Bindings"));
+            ui.table_setup_column("##buttons");
+            ui.table_headers_row();
+
+            for &action in Action::ALL {
+                let _id = ui.push_id(action.label());
+                ui.table_next_row();
+
+                ui.table_next_column();
+                ui.text(tr(action.label()));
+
+                ui.table_next_column();
+                match self.rebinding.as_ref().filter(|r| r.action == action) {
+                    Some(rebinding) if rebinding.chords.is_empty() => {
+                        ui.text_disabled(tr("Press a key or button... (Esc to cancel)"));
+                    }
+                    Some(rebinding) => {
+                        ui.text(Binding(rebinding.chords.clone()).to_string() + " ...");
+                    }
+                    None => {
+                        let bindings = self.map.bindings(action);
+                        if bindings.is_empty() {
+                            ui.text_disabled(tr("Unbound"));
+                        } else {
+                            let text: Vec<_> = bindings.iter().map(Binding::to_string).collect();
+                            ui.text(text.join(", "));
+                        }
+                    }
+                }
+
+                ui.table_next_column();
+                if ui.small_button(tr("Bind")) {
+                    self.rebinding = Some(Rebinding {
+                        action,
+                        chords: Vec::new(),
+                        last: Instant::now(),
+                    });
+                }
+                ui.same_line();
+                if ui.small_button(tr("Clear")) {
+                    self.map.clear(action);
+                }
+            }
+
+            table.end();
+        }
+
+        ui.separator();
+        if ui.button(tr("Reset to Defaults")) {
+            self.map = InputMap::default();
+        }
+        if let Some(path) = self.path.clone() {
+            ui.same_line();
+            if ui.button(tr("Save")) {
+                self.editor_error = self.map.save(&path).err().map(|e| e.to_string());
+            }
+        }
+        if let Some(error) = self.editor_error.as_ref() {
+            ui.text_colored([1.0, 0.4, 0.4, 1.0], error);
+        }
+    }
+}
+
+/// Returns `true` for keys which only act as [`Modifiers`].
+fn is_modifier(keycode: Keycode) -> bool {
+    matches!(
+        keycode,
+        Keycode::LCtrl
+            | Keycode::RCtrl
+            | Keycode::LShift
+            | Keycode::RShift
+            | Keycode::LAlt
+            | Keycode::RAlt
+            | Keycode::LGui
+            | Keycode::RGui
+    )
+}
+
+impl std::default::Default for InputMap {
+    /// The built-in bindings.
+    fn default() -> Self {
+        let mut map = Self::empty();
+        map.bind(
+            Action::Pan,
+            Binding::single(Chord::new(Trigger::Mouse(MouseButton::Middle))),
+        );
+        map.bind(
+            Action::ZoomIn,
+            Binding::single(Chord::new(Trigger::WheelUp)),
+        );
+        map.bind(
+            Action::ZoomIn,
+            Binding::single(Chord::new(Trigger::Key(Keycode::Equals)).ctrl()),
+        );
+        map.bind(
+            Action::ZoomOut,
+            Binding::single(Chord::new(Trigger::WheelDown)),
+        );
+        map.bind(
+            Action::ZoomOut,
+            Binding::single(Chord::new(Trigger::Key(Keycode::Minus)).ctrl()),
+        );
+        map.bind(
+            Action::SelectNode,
+            Binding::single(Chord::new(Trigger::Mouse(MouseButton::Left))),
+        );
+        map.bind(
+            Action::AddToSelection,
+            Binding::single(Chord::new(Trigger::Mouse(MouseButton::Left)).shift()),
+        );
+        map.bind(
+            Action::ToggleSelection,
+            Binding::single(Chord::new(Trigger::Mouse(MouseButton::Left)).ctrl()),
+        );
+        map.bind(
+            Action::FitToGraph,
+            Binding::single(Chord::new(Trigger::Key(Keycode::Home))),
+        );
+        map.bind(
+            Action::OpenSearch,
+            Binding::single(Chord::new(Trigger::Key(Keycode::F)).ctrl()),
+        );
+        map.bind(
+            Action::CommandPalette,
+            Binding::single(Chord::new(Trigger::Key(Keycode::P)).ctrl()),
+        );
+        map.bind(
+            Action::Screenshot,
+            Binding::single(Chord::new(Trigger::Key(Keycode::F12))),
+        );
+        map.bind(
+            Action::ToggleRecording,
+            Binding::single(Chord::new(Trigger::Key(Keycode::F12)).shift()),
+        );
+        map.bind(
+            Action::Undo,
+            Binding::single(Chord::new(Trigger::Key(Keycode::Z)).ctrl()),
+        );
+        map.bind(
+            Action::Redo,
+            Binding::single(Chord::new(Trigger::Key(Keycode::Z)).ctrl().shift()),
+        );
+        map.bind(
+            Action::DeleteSelection,
+            Binding::single(Chord::new(Trigger::Key(Keycode::Delete))),
+        );
+        map.bind(
+            Action::Copy,
+            Binding::single(Chord::new(Trigger::Key(Keycode::C)).ctrl()),
+        );
+        map.bind(
+            Action::Paste,
+            Binding::single(Chord::new(Trigger::Key(Keycode::V)).ctrl()),
+        );
+        map.bind(
+            Action::ExpandSelection,
+            Binding::single(Chord::new(Trigger::Key(Keycode::E))),
+        );
+        map.bind(
+            Action::CollapseSelection,
+            Binding::single(Chord::new(Trigger::Key(Keycode::E)).shift()),
+        );
+        map.bind(
+            Action::NextBookmark,
+            Binding::single(Chord::new(Trigger::Key(Keycode::PageDown))),
+        );
+        map.bind(
+            Action::PreviousBookmark,
+            Binding::single(Chord::new(Trigger::Key(Keycode::PageUp))),
+        );
+        map.bind(
+            Action::FindPath,
+            Binding::single(Chord::new(Trigger::Key(Keycode::P))),
+        );
+        let digits = [
+            Keycode::_1,
+            Keycode::_2,
+            Keycode::_3,
+            Keycode::_4,
+            Keycode::_5,
+            Keycode::_6,
+            Keycode::_7,
+            Keycode::_8,
+            Keycode::_9,
+        ];
+        for (number, key) in (1..=bookmarks::GOTO_COMMANDS).zip(digits) {
+            map.bind_command(
+                format!("{}{number}", bookmarks::GOTO_COMMAND),
+                Binding::single(Chord::new(Trigger::Key(key)).alt()),
+            );
+        }
+        map
+    }
+}
+
+impl std::fmt::Display for Trigger {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Trigger::Key(keycode) => write!(f, "{}", keycode.name()),
+            Trigger::Mouse(MouseButton::Left) => write!(f, "MouseLeft"),
+            Trigger::Mouse(MouseButton::Middle) => write!(f, "MouseMiddle"),
+            Trigger::Mouse(MouseButton::Right) => write!(f, "MouseRight"),
+            Trigger::Mouse(MouseButton::X1) => write!(f, "MouseX1"),
+            Trigger::Mouse(MouseButton::X2) => write!(f, "MouseX2"),
+            Trigger::Mouse(MouseButton::Unknown) => write!(f, "MouseUnknown"),
+            Trigger::WheelUp => write!(f, "WheelUp"),
+            Trigger::WheelDown => write!(f, "WheelDown"),
+        }
+    }
+}
+
+impl std::str::FromStr for Trigger {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "MouseLeft" => Trigger::Mouse(MouseButton::Left),
+            "MouseMiddle" => Trigger::Mouse(MouseButton::Middle),
+            "MouseRight" => Trigger::Mouse(MouseButton::Right),
+            "MouseX1" => Trigger::Mouse(MouseButton::X1),
+            "MouseX2" => Trigger::Mouse(MouseButton::X2),
+            "WheelUp" => Trigger::WheelUp,
+            "WheelDown" => Trigger::WheelDown,
+            key => {
+                Trigger::Key(Keycode::from_name(key).ok_or_else(|| format!("Unknown key '{key}'"))?)
+            }
+        })
+    }
+}
+
+impl std::fmt::Display for Chord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let m = self.modifiers;
+        for (held, name) in [
+            (m.ctrl, "Ctrl"),
+            (m.shift, "Shift"),
+            (m.alt, "Alt"),
+            (m.gui, "Gui"),
+        ] {
+            if held {
+                write!(f, "{name}+")?;
+            }
+        }
+        write!(f, "{}", self.trigger)
+    }
+}
+
+impl std::str::FromStr for Chord {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut modifiers = Modifiers::default();
+        let mut rest = s;
+
+        // The trigger is whatever remains once no modifier prefix matches, so
+        // that the `+` key itself can be bound as eg. `Ctrl++`.
+        while let Some((prefix, tail)) = rest.split_once('+') {
+            let flag = match prefix.to_ascii_lowercase().as_str() {
+                "ctrl" => &mut modifiers.ctrl,
+                "shift" => &mut modifiers.shift,
+                "alt" => &mut modifiers.alt,
+                "gui" => &mut modifiers.gui,
+                _ => break,
+            };
+            *flag = true;
+            rest = tail;
+        }
+
+        Ok(Chord {
+            modifiers,
+            trigger: rest.parse()?,
+        })
+    }
+}
+
+impl std::fmt::Display for Binding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let chords: Vec<_> = self.0.iter().map(Chord::to_string).collect();
+        write!(f, "{}", chords.join(" "))
+    }
+}
+
+impl std::str::FromStr for Binding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let chords = s
+            .split_whitespace()
+            .map(str::parse)
+            .collect::<Result<Vec<Chord>, _>>()?;
+        if chords.is_empty() {
+            return Err("Binding has no chords".to_owned());
+        }
+        if chords.len() > MAX_CHORDS {
+            return Err(format!("Binding '{s}' has more than {MAX_CHORDS} chords"));
+        }
+        Ok(Binding(chords))
+    }
+}
+
+impl Serialize for Binding {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Binding {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+impl From<std::io::Error> for InputMapError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl From<toml::de::Error> for InputMapError {
+    fn from(value: toml::de::Error) -> Self {
+        Self::Parse(value)
+    }
+}
+
+impl From<toml::ser::Error> for InputMapError {
+    fn from(value: toml::ser::Error) -> Self {
+        Self::Serialize(value)
+    }
+}
+
+impl std::fmt::Display for InputMapError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "Failed to access keymap: {e}"),
+            Self::Parse(e) => write!(f, "Failed to parse keymap: {e}"),
+            Self::Serialize(e) => write!(f, "Failed to serialize keymap: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for InputMapError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Parse(e) => Some(e),
+            Self::Serialize(e) => Some(e),
+        }
+    }
+}
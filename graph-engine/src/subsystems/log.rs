@@ -0,0 +1,138 @@
+use std::collections::VecDeque;
+use std::time::Instant;
+
+/// Default maximum number of entries kept by a [`Log`].
+const DEFAULT_CAPACITY: usize = 1000;
+
+/// Severity of a [`LogEntry`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+/// A single message recorded in a [`Log`].
+#[derive(Clone, Debug)]
+pub struct LogEntry {
+    pub level: LogLevel,
+    pub message: String,
+    /// Time at which the entry was recorded.
+    pub time: Instant,
+}
+
+/// A bounded, in-memory log of user-facing messages, shown by the
+/// [Log panel](super::panels::LogPanel).
+///
+/// Once full, the oldest entries are discarded first.
+pub struct Log {
+    entries: VecDeque<LogEntry>,
+    capacity: usize,
+    /// Time the log was created, used to display relative timestamps.
+    start: Instant,
+}
+
+impl Log {
+    /// Create an empty log keeping up to 1000 entries.
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY)
+    }
+
+    /// Create an empty log keeping up to `capacity` entries.
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn with_capacity(capacity: usize) -> Self {
+        assert!(capacity > 0, "Tried to create a log with no capacity");
+        Self {
+            entries: VecDeque::with_capacity(capacity.min(DEFAULT_CAPACITY)),
+            capacity,
+            start: Instant::now(),
+        }
+    }
+
+    /// Record a message at `level`.
+    pub fn push(&mut self, level: LogLevel, message: impl Into<String>) {
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(LogEntry {
+            level,
+            message: message.into(),
+            time: Instant::now(),
+        });
+    }
+
+    /// Record a debug message.
+    pub fn debug(&mut self, message: impl Into<String>) {
+        self.push(LogLevel::Debug, message);
+    }
+
+    /// Record an informational message.
+    pub fn info(&mut self, message: impl Into<String>) {
+        self.push(LogLevel::Info, message);
+    }
+
+    /// Record a warning.
+    pub fn warn(&mut self, message: impl Into<String>) {
+        self.push(LogLevel::Warn, message);
+    }
+
+    /// Record an error.
+    pub fn error(&mut self, message: impl Into<String>) {
+        self.push(LogLevel::Error, message);
+    }
+
+    /// Every entry currently held, oldest first.
+    pub fn entries(&self) -> impl DoubleEndedIterator<Item = &LogEntry> + ExactSizeIterator {
+        self.entries.iter()
+    }
+
+    /// Time the log was created.
+    pub fn start(&self) -> Instant {
+        self.start
+    }
+
+    /// Remove every entry.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Number of entries currently held.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the log holds no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl LogLevel {
+    /// Short, fixed-width name of the level.
+    pub fn label(&self) -> &'static str {
+        match self {
+            LogLevel::Debug => "DEBUG",
+            LogLevel::Info => "INFO ",
+            LogLevel::Warn => "WARN ",
+            LogLevel::Error => "ERROR",
+        }
+    }
+
+    /// Text color used for the level in the ui.
+    pub fn color(&self) -> [f32; 4] {
+        match self {
+            LogLevel::Debug => [0.6, 0.6, 0.6, 1.0],
+            LogLevel::Info => [0.9, 0.9, 0.9, 1.0],
+            LogLevel::Warn => [1.0, 0.8, 0.3, 1.0],
+            LogLevel::Error => [1.0, 0.4, 0.4, 1.0],
+        }
+    }
+}
+
+impl std::default::Default for Log {
+    fn default() -> Self {
+        Self::new()
+    }
+}
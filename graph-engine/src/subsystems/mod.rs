@@ -1,3 +1,19 @@
+pub mod app;
+pub mod capture;
+pub mod clipboard;
+pub mod config;
+pub mod crash;
+pub mod cursor;
 pub mod event;
+pub mod export;
+pub mod import;
+pub mod input;
+pub mod log;
+pub mod panels;
+pub mod profiler;
+pub mod scene;
+pub mod text_input;
 pub mod ui;
+pub mod ui_renderer;
+pub mod ui_state;
 pub mod window;
@@ -0,0 +1,260 @@
+use crate::context::EngineContext;
+use crate::i18n::tr;
+use crate::imgui::Ui;
+
+use super::log::LogLevel;
+use super::ui_state::UiStateManager;
+
+/// A named imgui panel owned by a [`PanelManager`].
+///
+/// Features ship their own panels by implementing this trait and registering an
+/// instance with [`PanelManager::register()`]. The manager draws the panel's
+/// window, so [`Self::draw()`] only issues the window's contents.
+pub trait Panel {
+    /// Stable identifier of the panel, used to persist its visibility.
+    ///
+    /// Must be unique among the panels registered with a [`PanelManager`].
+    fn id(&self) -> &'static str;
+
+    /// Title of the panel's window and menu entry. Defaults to [`Self::id()`].
+    fn title(&self) -> &str {
+        self.id()
+    }
+
+    /// Whether the panel is open the first time the application runs.
+    fn default_open(&self) -> bool {
+        false
+    }
+
+    /// Issue the panel's widgets, inside its already-begun window.
+    fn draw(&mut self, ui: &Ui, ctx: &mut EngineContext);
+}
+
+/// Owns named [`Panel`]s, tracking which are open and drawing them.
+///
+/// Typical usage, each frame:
+/// ```ignore
+/// if let Some(_bar) = ui.begin_main_menu_bar()
+///     && let Some(_menu) = ui.begin_menu(tr("View"))
+/// {
+///     panels.menu_items(ui);
+/// }
+/// panels.draw(ui, &mut ctx);
+/// ```
+///
+/// Visibility is persisted through a [`UiStateManager`] with [`Self::restore()`]
+/// and [`Self::store()`].
+pub struct PanelManager {
+    /// Registered panels, in menu order.
+    panels: Vec<PanelEntry>,
+}
+
+/// A registered panel and its visibility.
+struct PanelEntry {
+    panel: Box<dyn Panel>,
+    open: bool,
+}
+
+impl PanelManager {
+    /// Create a manager with no panels.
+    pub fn new() -> Self {
+        Self { panels: Vec::new() }
+    }
+
+    /// Register `panel`, opened according to [`Panel::default_open()`].
+    ///
+    /// Panics if a panel with the same [`Panel::id()`] is already registered.
+    pub fn register(&mut self, panel: impl Panel + 'static) {
+        self.register_boxed(Box::new(panel));
+    }
+
+    /// Like [`Self::register()`], for a panel already boxed, eg. one added by a
+    /// [plugin](crate::plugin).
+    pub fn register_boxed(&mut self, panel: Box<dyn Panel>) {
+        assert!(
+            self.entry(panel.id()).is_none(),
+            "Tried to register panel '{}' twice",
+            panel.id()
+        );
+        let open = panel.default_open();
+        self.panels.push(PanelEntry { panel, open });
+    }
+
+    /// Remove the panel `id`. Returns `false` if there was none.
+    pub fn unregister(&mut self, id: &str) -> bool {
+        let count = self.panels.len();
+        self.panels.retain(|e| e.panel.id() != id);
+        self.panels.len() != count
+    }
+
+    /// Returns `true` if the panel `id` is registered and open.
+    pub fn is_open(&self, id: &str) -> bool {
+        self.entry(id).is_some_and(|e| e.open)
+    }
+
+    /// Open or close the panel `id`.
+    ///
+    /// Panics if no panel `id` is registered.
+    pub fn set_open(&mut self, id: &str, open: bool) {
+        self.entry_mut(id)
+            .unwrap_or_else(|| panic!("Tried to open unregistered panel '{id}'"))
+            .open = open;
+    }
+
+    /// Open the panel `id` if it is closed, or close it if it is open.
+    ///
+    /// Panics if no panel `id` is registered.
+    pub fn toggle(&mut self, id: &str) {
+        let open = self.is_open(id);
+        self.set_open(id, !open);
+    }
+
+    /// Open or close every panel according to [`Panel::default_open()`].
+    pub fn reset(&mut self) {
+        for entry in &mut self.panels {
+            entry.open = entry.panel.default_open();
+        }
+    }
+
+    /// Ids of every registered panel, in menu order.
+    pub fn ids(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.panels.iter().map(|e| e.panel.id())
+    }
+
+    /// Apply the visibility recorded in `state`, for panels it knows about.
+    pub fn restore(&mut self, state: &UiStateManager) {
+        for entry in &mut self.panels {
+            if let Some(open) = state.panel_open(entry.panel.id()) {
+                entry.open = open;
+            }
+        }
+    }
+
+    /// Record the visibility of every panel in `state`.
+    pub fn store(&self, state: &mut UiStateManager) {
+        for entry in &self.panels {
+            state.set_panel_open(entry.panel.id(), entry.open);
+        }
+    }
+
+    /// Issue a toggleable menu item for each panel, inside an already-begun menu
+    /// (eg. "View").
+    pub fn menu_items(&mut self, ui: &Ui) {
+        for entry in &mut self.panels {
+            ui.menu_item_config(tr(entry.panel.title()))
+                .build_with_ref(&mut entry.open);
+        }
+    }
+
+    /// Draw the window of every open panel.
+    pub fn draw(&mut self, ui: &Ui, ctx: &mut EngineContext) {
+        for entry in &mut self.panels {
+            if !entry.open {
+                continue;
+            }
+            let PanelEntry { panel, open } = entry;
+            // `###id` keeps the window's layout stable if a panel's title changes.
+            let name = format!("{}###{}", tr(panel.title()), panel.id());
+            ui.window(name)
+                .size([400.0, 300.0], crate::imgui::Condition::FirstUseEver)
+                .opened(open)
+                .build(|| panel.draw(ui, ctx));
+        }
+    }
+
+    fn entry(&self, id: &str) -> Option<&PanelEntry> {
+        self.panels.iter().find(|e| e.panel.id() == id)
+    }
+
+    fn entry_mut(&mut self, id: &str) -> Option<&mut PanelEntry> {
+        self.panels.iter_mut().find(|e| e.panel.id() == id)
+    }
+}
+
+/// Shows the [`EngineContext::log`], filterable by level.
+pub struct LogPanel {
+    /// Lowest level shown.
+    min_level: LogLevel,
+    /// Keep the view scrolled to the newest entry.
+    follow: bool,
+}
+
+/// Shows the [`EngineContext::input`] rebinding editor.
+pub struct KeyBindingsPanel;
+
+impl LogPanel {
+    /// Create a log panel showing info messages and above.
+    pub fn new() -> Self {
+        Self {
+            min_level: LogLevel::Info,
+            follow: true,
+        }
+    }
+}
+
+impl Panel for LogPanel {
+    fn id(&self) -> &'static str {
+        "Log"
+    }
+
+    fn draw(&mut self, ui: &Ui, ctx: &mut EngineContext) {
+        const LEVELS: [LogLevel; 4] = [
+            LogLevel::Debug,
+            LogLevel::Info,
+            LogLevel::Warn,
+            LogLevel::Error,
+        ];
+
+        let mut level = LEVELS.iter().position(|l| *l == self.min_level).unwrap();
+        ui.set_next_item_width(100.0);
+        if ui.combo(tr("Level"), &mut level, &LEVELS, |l| {
+            l.label().trim_end().into()
+        }) {
+            self.min_level = LEVELS[level];
+        }
+        ui.same_line();
+        ui.checkbox(tr("Follow"), &mut self.follow);
+        ui.same_line();
+        if ui.button(tr("Clear")) {
+            ctx.log.clear();
+        }
+        ui.separator();
+
+        ui.child_window("##entries").build(|| {
+            let start = ctx.log.start();
+            for entry in ctx.log.entries().filter(|e| e.level >= self.min_level) {
+                let secs = entry.time.duration_since(start).as_secs_f32();
+                ui.text_disabled(format!("{secs:>9.3}"));
+                ui.same_line();
+                ui.text_colored(entry.level.color(), entry.level.label());
+                ui.same_line();
+                ui.text_wrapped(&entry.message);
+            }
+            if self.follow && ui.scroll_y() >= ui.scroll_max_y() {
+                ui.set_scroll_here_y_with_ratio(1.0);
+            }
+        });
+    }
+}
+
+impl Panel for KeyBindingsPanel {
+    fn id(&self) -> &'static str {
+        "Key Bindings"
+    }
+
+    fn draw(&mut self, ui: &Ui, ctx: &mut EngineContext) {
+        ctx.input.draw_editor(ui);
+    }
+}
+
+impl std::default::Default for PanelManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::default::Default for LogPanel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,640 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use crate::context::EngineContext;
+use crate::i18n::tr;
+use crate::imgui::renderers::glow::inner::{self as glow, Context, HasContext};
+use crate::imgui::{Condition, TreeNodeFlags, Ui, WindowFlags};
+
+use super::panels::Panel;
+
+/// Default number of frames kept in the profiler's history.
+const DEFAULT_HISTORY: usize = 300;
+
+/// Number of GPU timer queries that may be in flight at once.
+const GPU_QUERY_POOL: usize = 64;
+
+thread_local! {
+    /// The main thread's profiler. Scopes opened on other threads are ignored.
+    static PROFILER: RefCell<Profiler> = RefCell::new(Profiler::new());
+}
+
+/// Open a CPU timing scope lasting until the end of the enclosing block.
+///
+/// ```ignore
+/// fn layout_step() {
+///     graph_engine::profile_scope!("layout");
+///     // ...
+/// }
+/// ```
+#[macro_export]
+macro_rules! profile_scope {
+    ($name:expr) => {
+        let _profile_scope = $crate::subsystems::profiler::scope($name);
+    };
+}
+
+/// Per-frame CPU and GPU timings, aggregated into a rolling history.
+///
+/// The profiler is a per-thread singleton driven through free functions:
+/// - [`begin_frame()`] and [`end_frame()`] delimit frames (done by
+///   [`GraphWindow::run()`](super::window::GraphWindow::run)).
+/// - [`scope()`] (or [`profile_scope!`](crate::profile_scope)) times a nested
+///   CPU scope.
+/// - [`gpu_scope()`] times GPU work with timer queries, once a [`GpuTimer`] is
+///   installed with [`set_gpu_timer()`]. GPU results arrive a few frames late
+///   and are attached to the frame that issued them.
+///
+/// Inspect the collected data with [`with()`], or show it with [`ProfilerPanel`]
+/// and [`draw_overlay()`].
+pub struct Profiler {
+    enabled: bool,
+    /// Stop recording new frames, keeping the history as-is for inspection.
+    paused: bool,
+
+    /// Completed frames, oldest first.
+    history: VecDeque<FrameProfile>,
+    capacity: usize,
+
+    /// Index of the frame being recorded.
+    frame_index: u64,
+    /// Start of the frame being recorded, if one was begun.
+    frame_start: Option<Instant>,
+    /// Scopes recorded so far in the current frame.
+    scopes: Vec<Scope>,
+    /// Indices into [`Self::scopes`] of the scopes currently open, innermost last.
+    open: Vec<usize>,
+
+    gpu: Option<GpuTimer>,
+}
+
+/// The timings of a single frame.
+#[derive(Clone, Debug)]
+pub struct FrameProfile {
+    /// Sequential index of the frame.
+    pub index: u64,
+    /// Time from [`begin_frame()`] to [`end_frame()`].
+    pub duration: Duration,
+    /// CPU scopes in the order they were opened. A scope's children directly
+    /// follow it, with a [`Scope::depth`] one greater.
+    pub scopes: Vec<Scope>,
+    /// GPU scopes, in the order they were issued.
+    pub gpu: Vec<GpuScope>,
+}
+
+/// A timed CPU scope.
+#[derive(Clone, Debug)]
+pub struct Scope {
+    pub name: &'static str,
+    /// Nesting depth, `0` for top-level scopes.
+    pub depth: u16,
+    /// Time from the start of the frame to the start of the scope.
+    pub start: Duration,
+    pub duration: Duration,
+}
+
+/// A timed GPU scope.
+#[derive(Clone, Debug)]
+pub struct GpuScope {
+    pub name: &'static str,
+    pub duration: Duration,
+}
+
+/// Summary statistics over the profiler's history.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct FrameStats {
+    pub average: Duration,
+    pub min: Duration,
+    pub max: Duration,
+    /// 99th percentile frame time, ie. the threshold of the worst 1% of frames.
+    pub p99: Duration,
+}
+
+/// GPU timer queries (`GL_TIME_ELAPSED`) for the GL backend.
+///
+/// Created with [`UiRenderer::create_gpu_timer()`](super::ui_renderer::UiRenderer::create_gpu_timer).
+/// GL does not allow elapsed-time queries to nest, so a GPU scope opened while
+/// another is active is ignored.
+pub struct GpuTimer {
+    gl: Rc<Context>,
+    /// Queries ready for reuse.
+    free: Vec<glow::Query>,
+    /// Issued queries whose results have not been read yet, oldest first.
+    pending: VecDeque<PendingQuery>,
+    /// The query currently recording, if any.
+    active: Option<PendingQuery>,
+}
+
+/// An issued GPU timer query.
+struct PendingQuery {
+    frame: u64,
+    name: &'static str,
+    query: glow::Query,
+}
+
+/// Closes a CPU scope when dropped. See [`scope()`].
+#[must_use = "The scope ends as soon as the guard is dropped"]
+pub struct ScopeGuard {
+    /// Index of the scope in the current frame, if it was recorded.
+    index: Option<usize>,
+}
+
+/// Closes a GPU scope when dropped. See [`gpu_scope()`].
+#[must_use = "The scope ends as soon as the guard is dropped"]
+pub struct GpuScopeGuard {
+    started: bool,
+}
+
+/// Shows the profiler's frame-time graph, statistics, and the scope breakdown of
+/// the latest frame.
+pub struct ProfilerPanel {
+    /// Show top-level scopes expanded.
+    expand: bool,
+}
+
+/// Begin recording a new frame, ending the previous one if it was not ended.
+pub fn begin_frame() {
+    PROFILER.with_borrow_mut(Profiler::begin_frame);
+}
+
+/// Finish recording the current frame and add it to the history.
+pub fn end_frame() {
+    PROFILER.with_borrow_mut(Profiler::end_frame);
+}
+
+/// Open a CPU scope named `name`, closed when the returned guard is dropped.
+///
+/// Does nothing if the profiler is disabled or no frame was begun.
+pub fn scope(name: &'static str) -> ScopeGuard {
+    ScopeGuard {
+        index: PROFILER.with_borrow_mut(|p| p.begin_scope(name)),
+    }
+}
+
+/// Open a GPU scope named `name`, closed when the returned guard is dropped.
+///
+/// Does nothing if the profiler is disabled, no [`GpuTimer`] is installed, or
+/// another GPU scope is already open.
+pub fn gpu_scope(name: &'static str) -> GpuScopeGuard {
+    GpuScopeGuard {
+        started: PROFILER.with_borrow_mut(|p| p.begin_gpu_scope(name)),
+    }
+}
+
+/// Install the [`GpuTimer`] used by [`gpu_scope()`].
+pub fn set_gpu_timer(timer: GpuTimer) {
+    PROFILER.with_borrow_mut(|p| p.gpu = Some(timer));
+}
+
+/// Returns `true` if the profiler is recording.
+pub fn enabled() -> bool {
+    PROFILER.with_borrow(|p| p.enabled)
+}
+
+/// Enable or disable recording. Disabled scopes cost a single thread-local lookup.
+pub fn set_enabled(enabled: bool) {
+    PROFILER.with_borrow_mut(|p| {
+        p.enabled = enabled;
+        p.frame_start = None;
+        p.scopes.clear();
+        p.open.clear();
+    });
+}
+
+/// Access the profiler, eg. to read its history.
+///
+/// Panics if called from inside another call to [`with()`] or [`with_mut()`].
+pub fn with<R>(f: impl FnOnce(&Profiler) -> R) -> R {
+    PROFILER.with_borrow(f)
+}
+
+/// Mutable access to the profiler, eg. to pause it or change its history size.
+///
+/// Panics if called from inside another call to [`with()`] or [`with_mut()`].
+pub fn with_mut<R>(f: impl FnOnce(&mut Profiler) -> R) -> R {
+    PROFILER.with_borrow_mut(f)
+}
+
+impl Profiler {
+    fn new() -> Self {
+        Self {
+            enabled: true,
+            paused: false,
+
+            history: VecDeque::with_capacity(DEFAULT_HISTORY),
+            capacity: DEFAULT_HISTORY,
+
+            frame_index: 0,
+            frame_start: None,
+            scopes: Vec::new(),
+            open: Vec::new(),
+
+            gpu: None,
+        }
+    }
+
+    fn begin_frame(&mut self) {
+        if self.frame_start.is_some() {
+            self.end_frame();
+        }
+        if self.enabled {
+            self.frame_start = Some(Instant::now());
+        }
+    }
+
+    fn end_frame(&mut self) {
+        let Some(start) = self.frame_start.take() else {
+            return;
+        };
+
+        // Close any scopes left open, eg. by a guard leaked with `mem::forget`.
+        while let Some(index) = self.open.pop() {
+            let scope = &mut self.scopes[index];
+            scope.duration = start.elapsed() - scope.start;
+        }
+
+        let frame = FrameProfile {
+            index: self.frame_index,
+            duration: start.elapsed(),
+            scopes: std::mem::take(&mut self.scopes),
+            gpu: Vec::new(),
+        };
+        self.frame_index += 1;
+
+        if !self.paused {
+            if self.history.len() == self.capacity {
+                self.history.pop_front();
+            }
+            self.history.push_back(frame);
+        }
+
+        if let Some(gpu) = self.gpu.as_mut() {
+            for (frame, scope) in gpu.poll() {
+                if let Some(profile) = self.history.iter_mut().rev().find(|p| p.index == frame) {
+                    profile.gpu.push(scope);
+                }
+            }
+        }
+    }
+
+    fn begin_scope(&mut self, name: &'static str) -> Option<usize> {
+        let start = self.frame_start?.elapsed();
+        let index = self.scopes.len();
+        self.scopes.push(Scope {
+            name,
+            depth: self.open.len() as u16,
+            start,
+            duration: Duration::ZERO,
+        });
+        self.open.push(index);
+        Some(index)
+    }
+
+    fn end_scope(&mut self, index: usize) {
+        let Some(frame_start) = self.frame_start else {
+            return;
+        };
+        // Scopes are closed innermost-first, so anything opened after `index`
+        // and still open was leaked; close it too.
+        while let Some(open) = self.open.pop() {
+            let scope = &mut self.scopes[open];
+            scope.duration = frame_start.elapsed() - scope.start;
+            if open == index {
+                break;
+            }
+        }
+    }
+
+    fn begin_gpu_scope(&mut self, name: &'static str) -> bool {
+        if self.frame_start.is_none() {
+            return false;
+        }
+        let frame = self.frame_index;
+        self.gpu.as_mut().is_some_and(|gpu| gpu.begin(frame, name))
+    }
+
+    /// Completed frames, oldest first.
+    pub fn frames(&self) -> impl DoubleEndedIterator<Item = &FrameProfile> + ExactSizeIterator {
+        self.history.iter()
+    }
+
+    /// The most recently completed frame.
+    pub fn latest(&self) -> Option<&FrameProfile> {
+        self.history.back()
+    }
+
+    /// Statistics over every frame in the history.
+    pub fn stats(&self) -> FrameStats {
+        if self.history.is_empty() {
+            return FrameStats::default();
+        }
+
+        let mut durations: Vec<_> = self.history.iter().map(|f| f.duration).collect();
+        durations.sort_unstable();
+        let total: Duration = durations.iter().sum();
+        let p99 = ((durations.len() - 1) as f64 * 0.99).round() as usize;
+
+        FrameStats {
+            average: total / durations.len() as u32,
+            min: durations[0],
+            max: durations[durations.len() - 1],
+            p99: durations[p99],
+        }
+    }
+
+    /// Returns `true` if new frames are being kept out of the history.
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Pause or resume adding new frames to the history.
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    /// Sets the number of frames kept in the history. Defaults to 300.
+    ///
+    /// Panics if `frames` is zero.
+    pub fn set_history(&mut self, frames: usize) {
+        assert!(frames > 0, "Tried to keep a profiler history of 0 frames");
+        self.capacity = frames;
+        while self.history.len() > frames {
+            self.history.pop_front();
+        }
+    }
+
+    /// Returns `true` if GPU scopes can be recorded.
+    pub fn has_gpu_timer(&self) -> bool {
+        self.gpu.is_some()
+    }
+}
+
+impl FrameProfile {
+    /// Total time of the frame's top-level CPU scopes.
+    pub fn scoped_time(&self) -> Duration {
+        self.scopes
+            .iter()
+            .filter(|s| s.depth == 0)
+            .map(|s| s.duration)
+            .sum()
+    }
+}
+
+impl GpuTimer {
+    /// Create a GPU timer issuing queries on `gl`.
+    pub fn new(gl: Rc<Context>) -> Self {
+        Self {
+            gl,
+            free: Vec::new(),
+            pending: VecDeque::new(),
+            active: None,
+        }
+    }
+
+    /// Begin timing GPU work for `name` in frame `frame`.
+    ///
+    /// Returns `false` if a query is already active or no query is available.
+    fn begin(&mut self, frame: u64, name: &'static str) -> bool {
+        if self.active.is_some() || self.pending.len() >= GPU_QUERY_POOL {
+            return false;
+        }
+        let query = match self.free.pop() {
+            Some(query) => query,
+            None => match unsafe { self.gl.create_query() } {
+                Ok(query) => query,
+                Err(_) => return false,
+            },
+        };
+        unsafe { self.gl.begin_query(glow::TIME_ELAPSED, query) };
+        self.active = Some(PendingQuery { frame, name, query });
+        true
+    }
+
+    /// Stop timing the active query.
+    fn end(&mut self) {
+        if let Some(active) = self.active.take() {
+            unsafe { self.gl.end_query(glow::TIME_ELAPSED) };
+            self.pending.push_back(active);
+        }
+    }
+
+    /// Collect the results of every finished query, in the order they were issued.
+    fn poll(&mut self) -> Vec<(u64, GpuScope)> {
+        let mut results = Vec::new();
+        while let Some(pending) = self.pending.front() {
+            let available = unsafe {
+                self.gl
+                    .get_query_parameter_u32(pending.query, glow::QUERY_RESULT_AVAILABLE)
+            };
+            if available == 0 {
+                break;
+            }
+            let nanos = unsafe {
+                self.gl
+                    .get_query_parameter_u32(pending.query, glow::QUERY_RESULT)
+            };
+
+            let pending = self.pending.pop_front().unwrap();
+            results.push((
+                pending.frame,
+                GpuScope {
+                    name: pending.name,
+                    duration: Duration::from_nanos(nanos as u64),
+                },
+            ));
+            self.free.push(pending.query);
+        }
+        results
+    }
+}
+
+impl ProfilerPanel {
+    /// Create a profiler panel.
+    pub fn new() -> Self {
+        Self { expand: true }
+    }
+}
+
+impl Panel for ProfilerPanel {
+    fn id(&self) -> &'static str {
+        "Profiler"
+    }
+
+    fn draw(&mut self, ui: &Ui, _ctx: &mut EngineContext) {
+        let mut recording = enabled();
+        if ui.checkbox(tr("Enabled"), &mut recording) {
+            set_enabled(recording);
+        }
+
+        with_mut(|profiler| {
+            ui.same_line();
+            let mut paused = profiler.is_paused();
+            if ui.checkbox(tr("Paused"), &mut paused) {
+                profiler.set_paused(paused);
+            }
+            ui.same_line();
+            ui.checkbox(tr("Expand"), &mut self.expand);
+
+            let stats = profiler.stats();
+            ui.text(format!(
+                "avg {:.2}ms ({:.0} fps)   min {:.2}ms   max {:.2}ms   p99 {:.2}ms",
+                millis(stats.average),
+                1000.0 / millis(stats.average).max(f32::EPSILON),
+                millis(stats.min),
+                millis(stats.max),
+                millis(stats.p99),
+            ));
+
+            let times: Vec<f32> = profiler.frames().map(|f| millis(f.duration)).collect();
+            ui.plot_lines("##frame_times", &times)
+                .graph_size([ui.content_region_avail()[0], 80.0])
+                .scale_min(0.0)
+                .scale_max(millis(stats.max).max(1000.0 / 30.0))
+                .overlay_text(tr("frame time (ms)"))
+                .build();
+
+            let Some(frame) = profiler.latest() else {
+                ui.text_disabled(tr("No frames recorded"));
+                return;
+            };
+
+            ui.separator();
+            ui.text(
+                tr("Frame {index}: {time}ms")
+                    .replace("{index}", &frame.index.to_string())
+                    .replace("{time}", &format!("{:.3}", millis(frame.duration))),
+            );
+
+            let mut index = 0;
+            draw_scopes(ui, &frame.scopes, &mut index, 0, self.expand);
+
+            if !profiler.has_gpu_timer() {
+                return;
+            }
+            // GPU results arrive late, so show the newest frame that has any.
+            if let Some(frame) = profiler.frames().rev().find(|f| !f.gpu.is_empty()) {
+                ui.separator();
+                ui.text(tr("GPU (frame {index})").replace("{index}", &frame.index.to_string()));
+                for scope in &frame.gpu {
+                    ui.bullet_text(format!("{}: {:.3}ms", scope.name, millis(scope.duration)));
+                }
+            }
+        });
+    }
+}
+
+/// Draw the scopes at `depth` starting at `scopes[*index]` as a tree, advancing
+/// `index` past them and their children.
+fn draw_scopes(ui: &Ui, scopes: &[Scope], index: &mut usize, depth: u16, expand: bool) {
+    while let Some(scope) = scopes.get(*index) {
+        if scope.depth < depth {
+            return;
+        }
+        *index += 1;
+
+        let has_children = scopes.get(*index).is_some_and(|s| s.depth > depth);
+        let label = format!("{}: {:.3}ms", scope.name, millis(scope.duration));
+        if !has_children {
+            ui.bullet_text(label);
+            continue;
+        }
+
+        let mut flags = TreeNodeFlags::SPAN_AVAIL_WIDTH;
+        if expand && depth == 0 {
+            flags |= TreeNodeFlags::DEFAULT_OPEN;
+        }
+        // Use the position as the id so that equally named siblings stay distinct.
+        let id = format!("{label}###{}", *index);
+        match ui.tree_node_config(id).flags(flags).push() {
+            Some(_node) => draw_scopes(ui, scopes, index, depth + 1, expand),
+            None => {
+                // Skip the collapsed node's children.
+                while scopes.get(*index).is_some_and(|s| s.depth > depth) {
+                    *index += 1;
+                }
+            }
+        }
+    }
+}
+
+/// Draw a small, undecorated frame statistics overlay in the top-right corner
+/// of the main viewport.
+pub fn draw_overlay(ui: &Ui) {
+    let [width, _] = ui.io().display_size;
+    with(|profiler| {
+        let stats = profiler.stats();
+        let times: Vec<f32> = profiler.frames().map(|f| millis(f.duration)).collect();
+
+        ui.window("##profiler_overlay")
+            .position([width - 10.0, 30.0], Condition::Always)
+            .position_pivot([1.0, 0.0])
+            .bg_alpha(0.6)
+            .flags(
+                WindowFlags::NO_DECORATION
+                    | WindowFlags::ALWAYS_AUTO_RESIZE
+                    | WindowFlags::NO_SAVED_SETTINGS
+                    | WindowFlags::NO_FOCUS_ON_APPEARING
+                    | WindowFlags::NO_NAV
+                    | WindowFlags::NO_DOCKING,
+            )
+            .build(|| {
+                ui.text(format!(
+                    "{:.0} fps  {:.2}ms (max {:.2}ms)",
+                    1000.0 / millis(stats.average).max(f32::EPSILON),
+                    millis(stats.average),
+                    millis(stats.max),
+                ));
+                ui.plot_lines("##overlay_times", &times)
+                    .graph_size([200.0, 40.0])
+                    .scale_min(0.0)
+                    .build();
+            });
+    });
+}
+
+/// A duration in fractional milliseconds.
+fn millis(duration: Duration) -> f32 {
+    duration.as_secs_f32() * 1000.0
+}
+
+impl std::ops::Drop for ScopeGuard {
+    fn drop(&mut self) {
+        if let Some(index) = self.index {
+            PROFILER.with_borrow_mut(|p| p.end_scope(index));
+        }
+    }
+}
+
+impl std::ops::Drop for GpuScopeGuard {
+    fn drop(&mut self) {
+        if self.started {
+            PROFILER.with_borrow_mut(|p| {
+                if let Some(gpu) = p.gpu.as_mut() {
+                    gpu.end();
+                }
+            });
+        }
+    }
+}
+
+impl std::ops::Drop for GpuTimer {
+    fn drop(&mut self) {
+        self.end();
+        unsafe {
+            for pending in self.pending.drain(..) {
+                self.gl.delete_query(pending.query);
+            }
+            for query in self.free.drain(..) {
+                self.gl.delete_query(query);
+            }
+        }
+    }
+}
+
+impl std::default::Default for ProfilerPanel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,265 @@
+//! # Graph's Scene Layer
+//!
+//! The [`Scene`] is a 2D canvas drawn underneath imgui every frame. Shapes are
+//! recorded into two [`DrawList`]s each frame, then batched into a single GL
+//! vertex buffer and drawn before the ui:
+//! - [`Scene::world()`] is in world coordinates, transformed by the [`Camera`]
+//! - [`Scene::graph()`] holds nodes and edges in world coordinates, drawn as
+//!   instanced shapes in a handful of draw calls, with nodes reduced to points
+//!   when zoomed far out. [`GraphBatch::add_graph()`] fills it with the visible
+//!   part of a graph, styled per element.
+//! - [`Scene::screen()`] is in screen coordinates (points, origin top-left),
+//!   eg. for a selection rectangle
+//!
+//! Graphs whose positions already live in GL buffers, eg. moved by the
+//! [GPU layout](crate::layout::gpu), are drawn with [`Scene::draw_buffers()`]
+//! under the screen list, without reading them back.
+//!
+//! A [`CameraController`] moves the camera from user input, and exposes
+//! animated moves like fitting the whole graph into view. What is worth drawing
+//! at the current zoom is given by [`Camera::level_of_detail()`].
+//!
+//! A [`Picker`] finds the nodes and edges under the cursor, or inside a
+//! selection rectangle, from a [`SpatialIndex`](crate::spatial::SpatialIndex)
+//! of their world positions.
+//!
+//! Text is drawn through imgui's background draw list, so it shares the ui's
+//! fonts and stays underneath every panel. Labels recorded in
+//! [`Scene::labels()`] are decluttered as they are drawn: each is placed beside
+//! its anchor where it fits, in order of priority, and hidden where it would
+//! overlap a label placed before it.
+//!
+//! Shapes recorded in a [`VectorScene`] rather than a [`Scene`] are written to an
+//! SVG or PDF file instead of drawn. Code drawing into either takes a
+//! [`Canvas`].
+//!
+//! ```ignore
+//! window.new_scene()?;
+//!
+//! // in `GraphApp::draw_scene()`
+//! scene.world().circle([0.0, 0.0], 10.0, [1.0, 0.5, 0.0, 1.0]);
+//! scene.world().text([12.0, -6.0], [1.0; 4], "origin");
+//!
+//! let under_cursor = scene.camera().screen_to_world(mouse_pos);
+//! ```
+
+mod camera;
+mod controller;
+mod draw_list;
+mod graph_renderer;
+mod labels;
+mod picking;
+pub(crate) mod renderer;
+mod vector;
+
+pub use camera::{Camera, LevelOfDetail};
+pub use controller::CameraController;
+pub use draw_list::{DrawList, SceneText};
+pub use graph_renderer::{EdgeStyle, GraphBatch, NodeShape, NodeStyle};
+pub use labels::{Label, LabelBatch};
+pub use picking::{Hit, Picker};
+pub use vector::{LegendEntry, LegendGroup, VectorFormat, VectorScene};
+
+use std::rc::Rc;
+
+use crate::imgui::Ui;
+use crate::imgui::renderers::glow::inner::{self as glow, Context};
+
+use graph_renderer::GraphRenderer;
+use renderer::SceneRenderer;
+
+/// A 2D canvas with a pan/zoom [`Camera`], rendered underneath the ui.
+///
+/// Draw lists are cleared after each render, so shapes must be recorded again
+/// every frame.
+pub struct Scene {
+    camera: Camera,
+    world: DrawList,
+    graph: GraphBatch,
+    labels: LabelBatch,
+    screen: DrawList,
+    /// Drawn between the world and screen lists, cleared after each render.
+    buffers: Vec<GraphBuffers>,
+    renderer: SceneRenderer,
+    graph_renderer: GraphRenderer,
+}
+
+/// Layers shapes are recorded into, in drawing order, by a [`Scene`] drawn on
+/// screen or a [`VectorScene`] written to a file.
+pub trait Canvas {
+    /// The camera the shapes are seen through.
+    fn camera(&self) -> &Camera;
+
+    /// Shapes in world coordinates, under everything else.
+    fn world(&mut self) -> &mut DrawList;
+
+    /// Nodes and edges, over the world shapes.
+    fn graph(&mut self) -> &mut GraphBatch;
+
+    /// Labels, over every shape.
+    fn labels(&mut self) -> &mut LabelBatch;
+}
+
+/// Nodes and edges whose data lives in GL buffers, drawn by a [`Scene`]
+/// without reading them back.
+#[derive(Copy, Clone, Debug)]
+pub struct GraphBuffers {
+    /// Tightly packed `vec2` world positions, one per node.
+    pub positions: glow::Buffer,
+    pub node_count: u32,
+    /// Tightly packed `u32` pairs of indices into `positions`, one per edge.
+    pub edges: glow::Buffer,
+    pub edge_count: u32,
+    /// Diameter of nodes, in world units.
+    pub node_size: f32,
+    pub node_color: [f32; 4],
+    pub edge_color: [f32; 4],
+}
+
+/// Text and labels left to draw through imgui once a [`Scene`] is rendered,
+/// in screen coordinates.
+#[derive(Clone, Debug, Default)]
+pub struct SceneOverlay {
+    pub text: Vec<SceneText>,
+    pub labels: Vec<Label>,
+}
+
+/// Errors which can occur while creating a [`Scene`].
+#[derive(Debug)]
+pub enum SceneError {
+    /// The window's ui renderer has no GL context to draw the scene with.
+    Unsupported,
+    /// A shader failed to compile or link, or a GL object could not be created.
+    Gl(String),
+}
+
+impl Scene {
+    /// Create a scene drawing through `gl`, which must be the window's current context.
+    ///
+    /// Errors if the scene's shaders or buffers cannot be created.
+    pub fn new(gl: Rc<Context>) -> Result<Self, SceneError> {
+        Ok(Self {
+            camera: Camera::default(),
+            world: DrawList::default(),
+            graph: GraphBatch::default(),
+            labels: LabelBatch::default(),
+            screen: DrawList::default(),
+            buffers: Vec::new(),
+            renderer: SceneRenderer::new(gl.clone())?,
+            graph_renderer: GraphRenderer::new(gl).map_err(SceneError::Gl)?,
+        })
+    }
+
+    /// The camera transforming world coordinates to the screen.
+    pub fn camera(&self) -> &Camera {
+        &self.camera
+    }
+
+    /// Mutable access to the camera, eg. to pan or zoom.
+    pub fn camera_mut(&mut self) -> &mut Camera {
+        &mut self.camera
+    }
+
+    /// The draw list for shapes in world coordinates.
+    pub fn world(&mut self) -> &mut DrawList {
+        &mut self.world
+    }
+
+    /// The nodes and edges to draw this frame, over the world draw list.
+    pub fn graph(&mut self) -> &mut GraphBatch {
+        &mut self.graph
+    }
+
+    /// The labels to draw this frame, over every shape.
+    pub fn labels(&mut self) -> &mut LabelBatch {
+        &mut self.labels
+    }
+
+    /// The draw list for shapes in screen coordinates, drawn over the world.
+    pub fn screen(&mut self) -> &mut DrawList {
+        &mut self.screen
+    }
+
+    /// Draw `buffers` this frame, over the world draw list and graph batch.
+    ///
+    /// The buffers must stay alive until the scene is rendered.
+    pub fn draw_buffers(&mut self, buffers: GraphBuffers) {
+        self.buffers.push(buffers);
+    }
+
+    /// Sets the size of the area the scene is drawn into, in points.
+    ///
+    /// Done by the window before each frame; only needed when rendering elsewhere.
+    pub fn set_viewport(&mut self, size: [f32; 2]) {
+        self.camera.set_viewport(size);
+    }
+
+    /// Draw the draw lists and graph batch into the currently bound framebuffer,
+    /// then clear them.
+    ///
+    /// Returns the recorded text and labels, transformed to screen coordinates,
+    /// to be drawn with [`Self::draw_overlay()`] once the ui frame has started.
+    pub fn render(&mut self) -> SceneOverlay {
+        let world = self.camera.world_to_ndc();
+        let screen = self.camera.screen_to_ndc();
+        self.renderer.draw(&[(&self.world, world)]);
+        self.graph_renderer
+            .draw(&self.graph, &world, self.camera.zoom());
+        for buffers in self.buffers.drain(..) {
+            self.renderer
+                .draw_buffers(&buffers, &world, self.camera.zoom());
+        }
+        self.renderer.draw(&[(&self.screen, screen)]);
+
+        let mut text = self.world.take_text();
+        for t in &mut text {
+            t.pos = self.camera.world_to_screen(t.pos);
+        }
+        text.append(&mut self.screen.take_text());
+        let labels = self.labels.take(&self.camera);
+
+        self.world.clear();
+        self.graph.clear();
+        self.screen.clear();
+        SceneOverlay { text, labels }
+    }
+
+    /// Draw text and labels returned by [`Self::render()`] on imgui's background
+    /// draw list, placing labels so they do not overlap.
+    pub fn draw_overlay(ui: &Ui, overlay: &mut SceneOverlay) {
+        labels::draw_labels(ui, &mut overlay.labels);
+        let draw_list = ui.get_background_draw_list();
+        for t in &overlay.text {
+            draw_list.add_text(t.pos, t.color, &t.text);
+        }
+    }
+}
+
+impl Canvas for Scene {
+    fn camera(&self) -> &Camera {
+        &self.camera
+    }
+
+    fn world(&mut self) -> &mut DrawList {
+        &mut self.world
+    }
+
+    fn graph(&mut self) -> &mut GraphBatch {
+        &mut self.graph
+    }
+
+    fn labels(&mut self) -> &mut LabelBatch {
+        &mut self.labels
+    }
+}
+
+impl std::fmt::Display for SceneError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unsupported => write!(f, "The ui renderer cannot draw a scene"),
+            Self::Gl(e) => write!(f, "Failed to create scene GL objects: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for SceneError {}
@@ -0,0 +1,181 @@
+/// Smallest zoom the camera allows, in points per world unit.
+const MIN_ZOOM: f32 = 1e-4;
+/// Largest zoom the camera allows, in points per world unit.
+const MAX_ZOOM: f32 = 1e4;
+
+/// Zoom below which labels start fading out.
+const LABEL_FADE_START: f32 = 0.8;
+/// Zoom below which labels are hidden.
+const LABEL_FADE_END: f32 = 0.4;
+/// Zoom below which edges are drawn as plain straight lines.
+const EDGE_DETAIL_ZOOM: f32 = 0.25;
+/// Zoom below which nodes are clustered into tiles, see [`LevelOfDetail::tile`].
+const AGGREGATE_ZOOM: f32 = 0.1;
+/// Size of the tiles nodes are clustered into, in points, about.
+const TILE_SIZE: f32 = 24.0;
+
+/// What is worth drawing at the camera's zoom, see [`Camera::level_of_detail()`].
+///
+/// Zoomed far out, details are too small to read and only slow drawing down.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LevelOfDetail {
+    /// Opacity to draw labels with, fading from `1.0` to `0.0` as the camera zooms out.
+    pub label_alpha: f32,
+    /// Whether labels are visible at all, ie. [`Self::label_alpha`] is not zero.
+    pub labels: bool,
+    /// Whether edges are drawn with their bends and decorations, rather than as
+    /// straight lines.
+    pub edge_detail: bool,
+    /// Size, in world units, of the tiles nodes are clustered into once too
+    /// small to tell apart, if zoomed out that far. A power of two, so tiles
+    /// stay put while panning and split in four when zooming in.
+    pub tile: Option<f32>,
+}
+
+/// A 2D pan/zoom camera, mapping world coordinates to screen coordinates.
+///
+/// Screen coordinates are in points with the origin at the top-left of the
+/// viewport, like imgui and SDL mouse events. World coordinates share the same
+/// orientation (y grows downwards), so a zoom of `1.0` with the camera centered
+/// on `[0.0, 0.0]` puts the world origin at the middle of the viewport.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Camera {
+    /// World position shown at the middle of the viewport.
+    center: [f32; 2],
+    /// Points per world unit.
+    zoom: f32,
+    /// Size of the viewport, in points.
+    viewport: [f32; 2],
+}
+
+impl Camera {
+    /// Create a camera centered on `center`, at `zoom` points per world unit.
+    pub fn new(center: [f32; 2], zoom: f32) -> Self {
+        Self {
+            center,
+            zoom: zoom.clamp(MIN_ZOOM, MAX_ZOOM),
+            ..Default::default()
+        }
+    }
+
+    /// World position shown at the middle of the viewport.
+    pub fn center(&self) -> [f32; 2] {
+        self.center
+    }
+
+    /// Points per world unit.
+    pub fn zoom(&self) -> f32 {
+        self.zoom
+    }
+
+    /// Size of the viewport, in points.
+    pub fn viewport(&self) -> [f32; 2] {
+        self.viewport
+    }
+
+    /// Move the camera to show `center` at the middle of the viewport.
+    pub fn set_center(&mut self, center: [f32; 2]) {
+        self.center = center;
+    }
+
+    /// Sets the zoom, in points per world unit. Clamped to a sane range.
+    pub fn set_zoom(&mut self, zoom: f32) {
+        self.zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+    }
+
+    /// Set the size of the viewport, in points, eg. of a page drawn without a
+    /// window. The scene sets its camera's each frame.
+    pub fn set_viewport(&mut self, size: [f32; 2]) {
+        self.viewport = size;
+    }
+
+    /// Move the view by `delta` points, eg. the mouse movement while dragging.
+    pub fn pan(&mut self, delta: [f32; 2]) {
+        self.center[0] -= delta[0] / self.zoom;
+        self.center[1] -= delta[1] / self.zoom;
+    }
+
+    /// Multiply the zoom by `factor`, keeping the world position under the
+    /// screen position `anchor` in place, eg. zooming about the cursor.
+    pub fn zoom_about(&mut self, anchor: [f32; 2], factor: f32) {
+        let before = self.screen_to_world(anchor);
+        self.set_zoom(self.zoom * factor);
+        let after = self.screen_to_world(anchor);
+        self.center[0] += before[0] - after[0];
+        self.center[1] += before[1] - after[1];
+    }
+
+    /// Convert a world position to screen coordinates.
+    pub fn world_to_screen(&self, world: [f32; 2]) -> [f32; 2] {
+        [
+            (world[0] - self.center[0]) * self.zoom + self.viewport[0] * 0.5,
+            (world[1] - self.center[1]) * self.zoom + self.viewport[1] * 0.5,
+        ]
+    }
+
+    /// Convert a screen position to world coordinates, eg. for picking under the cursor.
+    pub fn screen_to_world(&self, screen: [f32; 2]) -> [f32; 2] {
+        [
+            (screen[0] - self.viewport[0] * 0.5) / self.zoom + self.center[0],
+            (screen[1] - self.viewport[1] * 0.5) / self.zoom + self.center[1],
+        ]
+    }
+
+    /// The world-space rectangle currently visible, as `(min, max)` corners.
+    pub fn visible_rect(&self) -> ([f32; 2], [f32; 2]) {
+        (
+            self.screen_to_world([0.0, 0.0]),
+            self.screen_to_world(self.viewport),
+        )
+    }
+
+    /// What is worth drawing at the current zoom.
+    pub fn level_of_detail(&self) -> LevelOfDetail {
+        let label_alpha =
+            ((self.zoom - LABEL_FADE_END) / (LABEL_FADE_START - LABEL_FADE_END)).clamp(0.0, 1.0);
+        LevelOfDetail {
+            label_alpha,
+            labels: label_alpha > 0.0,
+            edge_detail: self.zoom >= EDGE_DETAIL_ZOOM,
+            tile: (self.zoom < AGGREGATE_ZOOM)
+                .then(|| (TILE_SIZE / self.zoom).log2().round().exp2()),
+        }
+    }
+
+    /// Column-major matrix from world coordinates to GL normalized device coordinates.
+    pub(crate) fn world_to_ndc(&self) -> [f32; 16] {
+        let [width, height] = self.viewport.map(|v| v.max(1.0));
+        let scale = [2.0 * self.zoom / width, -2.0 * self.zoom / height];
+        ortho(
+            scale,
+            [-self.center[0] * scale[0], -self.center[1] * scale[1]],
+        )
+    }
+
+    /// Column-major matrix from screen coordinates to GL normalized device coordinates.
+    pub(crate) fn screen_to_ndc(&self) -> [f32; 16] {
+        let [width, height] = self.viewport.map(|v| v.max(1.0));
+        ortho([2.0 / width, -2.0 / height], [-1.0, 1.0])
+    }
+}
+
+/// Column-major 2D scale + translation matrix.
+#[rustfmt::skip]
+fn ortho(scale: [f32; 2], translate: [f32; 2]) -> [f32; 16] {
+    [
+        scale[0],     0.0,          0.0, 0.0,
+        0.0,          scale[1],     0.0, 0.0,
+        0.0,          0.0,          1.0, 0.0,
+        translate[0], translate[1], 0.0, 1.0,
+    ]
+}
+
+impl std::default::Default for Camera {
+    fn default() -> Self {
+        Self {
+            center: [0.0, 0.0],
+            zoom: 1.0,
+            viewport: [0.0, 0.0],
+        }
+    }
+}
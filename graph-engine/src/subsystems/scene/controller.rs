@@ -0,0 +1,284 @@
+use std::time::{Duration, Instant};
+
+use super::Camera;
+use crate::anim::{Easing, Tween};
+use crate::graph::{Graph, NodeId};
+use crate::layout::Positions;
+use crate::sdl3::event::Event;
+use crate::subsystems::input::InputCapture;
+
+/// Zoom factor of a single wheel notch or zoom key press.
+const ZOOM_STEP: f32 = 1.2;
+/// How long animated moves, eg. [`CameraController::fit_to_graph()`], take.
+const FLIGHT_DURATION: Duration = Duration::from_millis(400);
+/// Empty space left around a graph fitted to the viewport, as a fraction of its size.
+const FIT_MARGIN: f32 = 0.1;
+/// Zoom used to fit a graph with a single node, or all nodes at the same place.
+const FIT_DEFAULT_ZOOM: f32 = 1.0;
+
+/// Drives a [`Camera`] from user input and animated moves.
+///
+/// Input is collected as it arrives, eg. while draining actions in
+/// [`GraphApp::update()`](crate::subsystems::app::GraphApp::update), and applied
+/// once per frame by [`Self::apply()`] from
+/// [`GraphApp::draw_scene()`](crate::subsystems::app::GraphApp::draw_scene),
+/// once the camera knows the size of the viewport:
+/// - [`Self::zoom_at()`] zooms about a point, eg. the cursor for wheel zoom
+/// - [`Self::begin_pan()`] / [`Self::end_pan()`] drag the view with the cursor
+/// - two-finger pinches on a touch device pan and zoom, see [`Self::handle()`]
+/// - [`Self::fit_to_graph()`], [`Self::fit_rect()`], [`Self::center_on_node()`],
+///   and [`Self::glide_to()`] glide to a target
+///
+/// Any direct pan or zoom cancels an animated move.
+#[derive(Clone, Debug, Default)]
+pub struct CameraController {
+    /// Zooms requested since the last apply, as `(anchor, factor)`. Anchors are
+    /// screen positions; `None` zooms about the middle of the viewport.
+    zooms: Vec<(Option<[f32; 2]>, f32)>,
+    /// Last cursor position applied while panning.
+    panning: Option<[f32; 2]>,
+    /// Touches currently down, as `(finger id, normalized position)`.
+    fingers: Vec<(u64, [f32; 2])>,
+    /// Pinch movement since the last apply.
+    pinch: Option<Pinch>,
+    /// Where a requested move should go, resolved by the next apply.
+    target: Option<Target>,
+    flight: Option<Flight>,
+    /// The camera's center and zoom after the last apply.
+    view: Option<([f32; 2], f32)>,
+    /// The world-space rectangle visible as of the last [`Self::apply()`].
+    visible: Option<([f32; 2], [f32; 2])>,
+}
+
+/// Two-finger movement accumulated between frames, in normalized coordinates.
+#[derive(Copy, Clone, Debug)]
+struct Pinch {
+    factor: f32,
+    pan: [f32; 2],
+    /// Midpoint of the fingers, which the zoom is about.
+    anchor: [f32; 2],
+}
+
+#[derive(Copy, Clone, Debug)]
+enum Target {
+    /// Show the whole world-space rectangle between two corners.
+    Rect([f32; 2], [f32; 2]),
+    /// Center the view on a world position, keeping the zoom.
+    Point([f32; 2]),
+    /// Glide to a center and zoom.
+    Glide([f32; 2], f32),
+    /// Jump to a center and zoom, without animating.
+    View([f32; 2], f32),
+}
+
+/// An animated move of the camera, from the view when it started.
+#[derive(Copy, Clone, Debug)]
+struct Flight {
+    view: Tween<([f32; 2], f32)>,
+    start: Instant,
+}
+
+impl CameraController {
+    /// Create a controller with no pending input.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed a raw SDL event to the controller, to track touch gestures.
+    ///
+    /// Touches starting while imgui has `capture`d the mouse are ignored.
+    pub fn handle(&mut self, event: &Event, capture: InputCapture) {
+        match *event {
+            Event::FingerDown {
+                finger_id, x, y, ..
+            } if !capture.mouse => {
+                self.fingers.push((finger_id, [x, y]));
+            }
+            Event::FingerUp { finger_id, .. } => {
+                self.fingers.retain(|(id, _)| *id != finger_id);
+            }
+            Event::FingerMotion {
+                finger_id, x, y, ..
+            } => {
+                let before = self.pinch_span();
+                if let Some(finger) = self.fingers.iter_mut().find(|(id, _)| *id == finger_id) {
+                    finger.1 = [x, y];
+                }
+                if let (Some((from_mid, from_dist)), Some((to_mid, to_dist))) =
+                    (before, self.pinch_span())
+                {
+                    let pinch = self.pinch.get_or_insert(Pinch {
+                        factor: 1.0,
+                        pan: [0.0, 0.0],
+                        anchor: to_mid,
+                    });
+                    if from_dist > 0.0 {
+                        pinch.factor *= to_dist / from_dist;
+                    }
+                    pinch.pan[0] += to_mid[0] - from_mid[0];
+                    pinch.pan[1] += to_mid[1] - from_mid[1];
+                    pinch.anchor = to_mid;
+                    self.flight = None;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Zoom in by `steps` notches, or out for negative steps, about the screen
+    /// position `anchor`, or the middle of the viewport.
+    pub fn zoom_at(&mut self, anchor: Option<[f32; 2]>, steps: f32) {
+        self.zooms.push((anchor, ZOOM_STEP.powf(steps)));
+        self.flight = None;
+    }
+
+    /// Start dragging the view with the cursor, which is at `cursor`.
+    pub fn begin_pan(&mut self, cursor: [f32; 2]) {
+        self.panning = Some(cursor);
+        self.flight = None;
+    }
+
+    /// Stop dragging the view.
+    pub fn end_pan(&mut self) {
+        self.panning = None;
+    }
+
+    /// Returns `true` while the view is being dragged.
+    pub fn is_panning(&self) -> bool {
+        self.panning.is_some()
+    }
+
+    /// Glide to show every node of `graph` at its `positions`.
+    ///
+    /// Does nothing if no node has a position.
+    pub fn fit_to_graph(&mut self, graph: &Graph, positions: &Positions) {
+        if let Some((min, max)) = positions.bounds(graph) {
+            self.target = Some(Target::Rect(min, max));
+        }
+    }
+
+    /// Glide to show the world-space rectangle between `min` and `max`.
+    ///
+    /// A rectangle without area, eg. a single point, is shown at a zoom of `1.0`.
+    pub fn fit_rect(&mut self, min: [f32; 2], max: [f32; 2]) {
+        self.target = Some(Target::Rect(min, max));
+    }
+
+    /// Glide to center the view on `node`, keeping the zoom.
+    ///
+    /// Does nothing if the node has no position.
+    pub fn center_on_node(&mut self, node: NodeId, positions: &Positions) {
+        if let Some(point) = positions.get(node) {
+            self.target = Some(Target::Point(point));
+        }
+    }
+
+    /// Glide to `center` at `zoom`, eg. to show a view saved earlier.
+    pub fn glide_to(&mut self, center: [f32; 2], zoom: f32) {
+        self.target = Some(Target::Glide(center, zoom));
+    }
+
+    /// Returns `true` while the camera is gliding towards a target.
+    pub fn is_animating(&self) -> bool {
+        self.flight.is_some() || self.target.is_some()
+    }
+
+    /// Move `camera` by the input collected since the last call and advance any
+    /// animated move. `cursor` is the current cursor position, to drag the view.
+    ///
+    /// Must be called once per frame, after the camera's viewport is set.
+    pub fn apply(&mut self, camera: &mut Camera, cursor: [f32; 2]) {
+        let viewport = camera.viewport();
+        let middle = [viewport[0] * 0.5, viewport[1] * 0.5];
+
+        if let Some(last) = self.panning.replace(cursor) {
+            camera.pan([cursor[0] - last[0], cursor[1] - last[1]]);
+        }
+        for (anchor, factor) in self.zooms.drain(..) {
+            camera.zoom_about(anchor.unwrap_or(middle), factor);
+        }
+        if let Some(pinch) = self.pinch.take() {
+            camera.pan([pinch.pan[0] * viewport[0], pinch.pan[1] * viewport[1]]);
+            let anchor = [pinch.anchor[0] * viewport[0], pinch.anchor[1] * viewport[1]];
+            camera.zoom_about(anchor, pinch.factor);
+        }
+
+        // targets wait for a viewport to be fitted into, eg. while minimized
+        if viewport[0] > 0.0
+            && viewport[1] > 0.0
+            && let Some(target) = self.target.take()
+        {
+            let to = match target {
+                Target::Rect(min, max) => Some(fit(min, max, viewport)),
+                Target::Point(point) => Some((point, camera.zoom())),
+                Target::Glide(center, zoom) => Some((center, zoom)),
+                Target::View(center, zoom) => {
+                    camera.set_center(center);
+                    camera.set_zoom(zoom);
+                    None
+                }
+            };
+            self.flight = to.map(|to| Flight {
+                view: Tween::new((camera.center(), camera.zoom()), to, FLIGHT_DURATION)
+                    .easing(Easing::EaseOut),
+                start: Instant::now(),
+            });
+        }
+        if let Some(flight) = &mut self.flight {
+            flight.view.seek(flight.start.elapsed());
+            let (center, zoom) = flight.view.value();
+            camera.set_center(center);
+            camera.set_zoom(zoom);
+            if flight.view.is_finished() {
+                self.flight = None;
+            }
+        }
+        self.view = Some((camera.center(), camera.zoom()));
+        self.visible = Some(camera.visible_rect());
+    }
+
+    /// The camera's center and zoom as of the last [`Self::apply()`] or
+    /// [`Self::set_view()`], if any, eg. to come back to it later.
+    pub fn view(&self) -> Option<([f32; 2], f32)> {
+        self.view
+    }
+
+    /// The world-space rectangle visible as of the last [`Self::apply()`], as
+    /// `(min, max)` corners.
+    pub fn visible_rect(&self) -> Option<([f32; 2], [f32; 2])> {
+        self.visible
+    }
+
+    /// Jump to `center` at `zoom` on the next [`Self::apply()`], eg. to show a
+    /// document as it was last seen.
+    pub fn set_view(&mut self, center: [f32; 2], zoom: f32) {
+        self.target = Some(Target::View(center, zoom));
+        self.flight = None;
+        self.view = Some((center, zoom));
+    }
+
+    /// Midpoint and distance between the fingers of a two-finger touch, if any.
+    fn pinch_span(&self) -> Option<([f32; 2], f32)> {
+        let [(_, a), (_, b)] = self.fingers.as_slice() else {
+            return None;
+        };
+        let mid = [(a[0] + b[0]) * 0.5, (a[1] + b[1]) * 0.5];
+        Some((mid, (b[0] - a[0]).hypot(b[1] - a[1])))
+    }
+}
+
+/// Center and zoom showing the rectangle between `min` and `max` in `viewport`.
+fn fit(min: [f32; 2], max: [f32; 2], viewport: [f32; 2]) -> ([f32; 2], f32) {
+    let center = [(min[0] + max[0]) * 0.5, (min[1] + max[1]) * 0.5];
+    let size = [
+        (max[0] - min[0]) * (1.0 + 2.0 * FIT_MARGIN),
+        (max[1] - min[1]) * (1.0 + 2.0 * FIT_MARGIN),
+    ];
+    let zoom = match (size[0] > 0.0, size[1] > 0.0) {
+        (false, false) => FIT_DEFAULT_ZOOM,
+        (true, false) => viewport[0] / size[0],
+        (false, true) => viewport[1] / size[1],
+        (true, true) => f32::min(viewport[0] / size[0], viewport[1] / size[1]),
+    };
+    (center, zoom)
+}
@@ -0,0 +1,141 @@
+use std::f32::consts::TAU;
+
+/// Number of segments circles are approximated with.
+const CIRCLE_SEGMENTS: usize = 32;
+
+/// A single vertex, as uploaded to the GPU.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct Vertex {
+    pub(crate) pos: [f32; 2],
+    /// RGBA8, normalized by the vertex shader.
+    pub(crate) color: [u8; 4],
+}
+
+/// Text recorded in a [`DrawList`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct SceneText {
+    /// Top-left corner of the text.
+    pub pos: [f32; 2],
+    /// RGBA, `0.0..=1.0`.
+    pub color: [f32; 4],
+    pub text: String,
+}
+
+/// Shapes recorded for a single frame, tessellated into triangles as they are added.
+///
+/// Positions and sizes are in the list's coordinate space, see
+/// [`Scene::world()`](super::Scene::world) and [`Scene::screen()`](super::Scene::screen).
+/// Colors are RGBA, `0.0..=1.0`.
+#[derive(Clone, Debug, Default)]
+pub struct DrawList {
+    vertices: Vec<Vertex>,
+    text: Vec<SceneText>,
+}
+
+impl DrawList {
+    /// A line from `a` to `b`, `width` units wide.
+    pub fn line(&mut self, a: [f32; 2], b: [f32; 2], width: f32, color: [f32; 4]) {
+        let (dx, dy) = (b[0] - a[0], b[1] - a[1]);
+        let len = (dx * dx + dy * dy).sqrt();
+        if len <= f32::EPSILON {
+            return;
+        }
+        let (nx, ny) = (-dy / len * width * 0.5, dx / len * width * 0.5);
+        self.quad(
+            [a[0] + nx, a[1] + ny],
+            [b[0] + nx, b[1] + ny],
+            [b[0] - nx, b[1] - ny],
+            [a[0] - nx, a[1] - ny],
+            color,
+        );
+    }
+
+    /// Connected lines through `points`, joining the last point back to the
+    /// first if `closed` is set.
+    pub fn polyline(&mut self, points: &[[f32; 2]], width: f32, color: [f32; 4], closed: bool) {
+        for pair in points.windows(2) {
+            self.line(pair[0], pair[1], width, color);
+        }
+        if closed && let [first, .., last] = points {
+            self.line(*last, *first, width, color);
+        }
+    }
+
+    /// A filled circle.
+    pub fn circle(&mut self, center: [f32; 2], radius: f32, color: [f32; 4]) {
+        let points = circle_points(center, radius);
+        for pair in points.windows(2) {
+            self.triangle(center, pair[0], pair[1], color);
+        }
+    }
+
+    /// A circle outline, `width` units wide.
+    pub fn circle_outline(&mut self, center: [f32; 2], radius: f32, width: f32, color: [f32; 4]) {
+        let points = circle_points(center, radius);
+        self.polyline(&points, width, color, false);
+    }
+
+    /// A filled axis-aligned rectangle between the corners `min` and `max`.
+    pub fn rect(&mut self, min: [f32; 2], max: [f32; 2], color: [f32; 4]) {
+        self.quad(min, [max[0], min[1]], max, [min[0], max[1]], color);
+    }
+
+    /// An axis-aligned rectangle outline, `width` units wide.
+    pub fn rect_outline(&mut self, min: [f32; 2], max: [f32; 2], width: f32, color: [f32; 4]) {
+        let corners = [min, [max[0], min[1]], max, [min[0], max[1]]];
+        self.polyline(&corners, width, color, true);
+    }
+
+    /// Text with its top-left corner at `pos`.
+    ///
+    /// Text keeps the ui's font size regardless of the camera's zoom.
+    pub fn text(&mut self, pos: [f32; 2], color: [f32; 4], text: impl Into<String>) {
+        self.text.push(SceneText {
+            pos,
+            color,
+            text: text.into(),
+        });
+    }
+
+    /// Returns `true` if nothing was recorded.
+    pub fn is_empty(&self) -> bool {
+        self.vertices.is_empty() && self.text.is_empty()
+    }
+
+    /// Remove everything recorded.
+    pub fn clear(&mut self) {
+        self.vertices.clear();
+        self.text.clear();
+    }
+
+    pub(crate) fn vertices(&self) -> &[Vertex] {
+        &self.vertices
+    }
+
+    pub(crate) fn take_text(&mut self) -> Vec<SceneText> {
+        std::mem::take(&mut self.text)
+    }
+
+    fn triangle(&mut self, a: [f32; 2], b: [f32; 2], c: [f32; 2], color: [f32; 4]) {
+        let color = color.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+        self.vertices
+            .extend([a, b, c].map(|pos| Vertex { pos, color }));
+    }
+
+    fn quad(&mut self, a: [f32; 2], b: [f32; 2], c: [f32; 2], d: [f32; 2], color: [f32; 4]) {
+        self.triangle(a, b, c, color);
+        self.triangle(a, c, d, color);
+    }
+}
+
+/// Points around a circle, with the first point repeated at the end.
+fn circle_points(center: [f32; 2], radius: f32) -> [[f32; 2]; CIRCLE_SEGMENTS + 1] {
+    std::array::from_fn(|i| {
+        let angle = i as f32 / CIRCLE_SEGMENTS as f32 * TAU;
+        [
+            center[0] + angle.cos() * radius,
+            center[1] + angle.sin() * radius,
+        ]
+    })
+}
@@ -0,0 +1,853 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+use std::f32::consts::TAU;
+use std::rc::Rc;
+
+use super::Camera;
+use super::renderer::link_program;
+use crate::graph::{EdgeId, Graph, NodeId};
+use crate::imgui::renderers::glow::inner::{self as glow, Context, HasContext};
+use crate::layout::Positions;
+use crate::memory::GpuAllocation;
+use crate::routing::EdgeRoutes;
+use crate::spatial::SpatialIndex;
+
+/// Nodes smaller than this on screen, in points, are drawn as single points.
+const POINT_RADIUS: f32 = 1.5;
+/// Extra space around the viewport in which elements are still drawn, in
+/// points, covering outlines and antialiasing.
+const CULL_MARGIN: f32 = 16.0;
+/// Number of segments self-loops are approximated with.
+const LOOP_SEGMENTS: usize = 16;
+/// Graphs with fewer nodes are never clustered into tiles, however far out
+/// the camera is.
+const AGGREGATE_MIN_NODES: usize = 2_000;
+/// Widest an edge bundle between two tiles is drawn, in points.
+const MAX_BUNDLE_WIDTH: f32 = 8.0;
+
+/// Nodes are drawn as instanced quads, shaded as antialiased circles.
+const NODE_VERTEX_SHADER: &str = r#"#version 330 core
+uniform mat4 u_transform;
+uniform float u_zoom;
+layout(location = 0) in vec2 a_corner;
+layout(location = 1) in vec2 i_center;
+layout(location = 2) in float i_radius;
+layout(location = 3) in float i_outline;
+layout(location = 4) in vec4 i_color;
+layout(location = 5) in vec4 i_outline_color;
+layout(location = 6) in float i_shape;
+out vec2 v_local;
+out float v_radius;
+out float v_outline;
+out vec4 v_color;
+out vec4 v_outline_color;
+flat out int v_shape;
+void main() {
+    v_radius = max(i_radius * u_zoom, 1.0);
+    v_outline = i_outline;
+    v_color = i_color;
+    v_outline_color = i_outline_color;
+    v_shape = int(i_shape);
+    // shapes poke out of the circle of their radius by up to a quarter
+    v_local = (a_corner * 2.0 - 1.0) * (v_radius * 1.25 + i_outline + 1.0);
+    gl_Position = u_transform * vec4(i_center + v_local / u_zoom, 0.0, 1.0);
+}
+"#;
+
+const NODE_FRAGMENT_SHADER: &str = r#"#version 330 core
+in vec2 v_local;
+in float v_radius;
+in float v_outline;
+in vec4 v_color;
+in vec4 v_outline_color;
+flat in int v_shape;
+out vec4 o_color;
+
+// signed distance from the shape's border, negative inside
+float shape_distance(vec2 p, float r) {
+    if (v_shape == 1) {
+        vec2 q = abs(p) - vec2(r * 0.85);
+        return length(max(q, 0.0)) + min(max(q.x, q.y), 0.0);
+    }
+    if (v_shape == 2) {
+        return (abs(p.x) + abs(p.y) - r * 1.2) * 0.70710678;
+    }
+    if (v_shape == 3) {
+        const float k = 1.7320508;
+        p = vec2(abs(p.x) - r, -p.y + r / k);
+        if (p.x + k * p.y > 0.0) {
+            p = vec2(p.x - k * p.y, -k * p.x - p.y) * 0.5;
+        }
+        p.x -= clamp(p.x, -2.0 * r, 0.0);
+        return -length(p) * sign(p.y);
+    }
+    return length(p) - r;
+}
+
+void main() {
+    float d = shape_distance(v_local, v_radius);
+    float outside_fill = smoothstep(-0.5, 0.5, d);
+    float outside_outline = smoothstep(v_outline - 0.5, v_outline + 0.5, d);
+    vec4 color = v_outline > 0.0 ? mix(v_color, v_outline_color, outside_fill) : v_color;
+    float coverage = v_outline > 0.0 ? 1.0 - outside_outline : 1.0 - outside_fill;
+    if (coverage <= 0.0) {
+        discard;
+    }
+    o_color = vec4(color.rgb, color.a * coverage);
+}
+"#;
+
+/// Edge segments are drawn as instanced quads, shaded as antialiased capsules
+/// so consecutive segments of a path join smoothly.
+const EDGE_VERTEX_SHADER: &str = r#"#version 330 core
+uniform mat4 u_transform;
+uniform float u_zoom;
+layout(location = 0) in vec2 a_corner;
+layout(location = 1) in vec2 i_a;
+layout(location = 2) in vec2 i_b;
+layout(location = 3) in float i_width;
+layout(location = 4) in vec4 i_color;
+out vec2 v_local;
+out float v_length;
+out float v_half_width;
+out vec4 v_color;
+void main() {
+    vec2 d = (i_b - i_a) * u_zoom;
+    v_length = length(d);
+    vec2 dir = v_length > 0.0 ? d / v_length : vec2(1.0, 0.0);
+    vec2 normal = vec2(-dir.y, dir.x);
+    v_half_width = i_width * 0.5;
+    v_color = i_color;
+
+    float extent = v_half_width + 1.0;
+    v_local = vec2(mix(-extent, v_length + extent, a_corner.x), (a_corner.y * 2.0 - 1.0) * extent);
+    vec2 offset = dir * v_local.x + normal * v_local.y;
+    gl_Position = u_transform * vec4(i_a + offset / u_zoom, 0.0, 1.0);
+}
+"#;
+
+const EDGE_FRAGMENT_SHADER: &str = r#"#version 330 core
+in vec2 v_local;
+in float v_length;
+in float v_half_width;
+in vec4 v_color;
+out vec4 o_color;
+void main() {
+    float along = clamp(v_local.x, 0.0, v_length);
+    float d = length(vec2(v_local.x - along, v_local.y));
+    float coverage = 1.0 - smoothstep(v_half_width - 0.5, v_half_width + 0.5, d);
+    if (coverage <= 0.0) {
+        discard;
+    }
+    o_color = vec4(v_color.rgb, v_color.a * coverage);
+}
+"#;
+
+/// Far zoomed out, nodes are reduced to points sized in pixels.
+const POINT_VERTEX_SHADER: &str = r#"#version 330 core
+uniform mat4 u_transform;
+uniform float u_point_size;
+layout(location = 0) in vec2 i_center;
+layout(location = 1) in vec4 i_color;
+out vec4 v_color;
+void main() {
+    v_color = i_color;
+    gl_Position = u_transform * vec4(i_center, 0.0, 1.0);
+    gl_PointSize = u_point_size;
+}
+"#;
+
+const POINT_FRAGMENT_SHADER: &str = r#"#version 330 core
+in vec4 v_color;
+out vec4 o_color;
+void main() {
+    o_color = v_color;
+}
+"#;
+
+/// The outline of a node.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeShape {
+    #[default]
+    Circle,
+    Square,
+    Diamond,
+    Triangle,
+}
+
+/// How a node is drawn by a [`GraphBatch`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct NodeStyle {
+    pub shape: NodeShape,
+    /// Radius, in world units. Shapes other than circles are sized to look
+    /// about as large as a circle of this radius.
+    pub radius: f32,
+    /// RGBA, `0.0..=1.0`.
+    pub color: [f32; 4],
+    /// Width of the outline drawn around the node, in points. Zero for none.
+    pub outline_width: f32,
+    pub outline_color: [f32; 4],
+}
+
+/// How an edge is drawn by a [`GraphBatch`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct EdgeStyle {
+    /// Width, in points, so edges stay visible at any zoom.
+    pub width: f32,
+    /// RGBA, `0.0..=1.0`.
+    pub color: [f32; 4],
+}
+
+/// A node, as uploaded to the GPU.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct NodeInstance {
+    center: [f32; 2],
+    radius: f32,
+    outline_width: f32,
+    /// [`NodeShape`] discriminant, as a float for the vertex shader.
+    shape: f32,
+    /// RGBA8, normalized by the vertex shader.
+    color: [u8; 4],
+    outline_color: [u8; 4],
+}
+
+/// A straight piece of an edge, as uploaded to the GPU.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct EdgeInstance {
+    a: [f32; 2],
+    b: [f32; 2],
+    width: f32,
+    color: [u8; 4],
+}
+
+/// Nodes and edges recorded for a single frame, drawn by the scene in a few
+/// instanced draw calls, see [`Scene::graph()`](super::Scene::graph).
+///
+/// Positions are in world coordinates, like [`Scene::world()`](super::Scene::world).
+/// Edges are drawn under nodes.
+#[derive(Clone, Debug, Default)]
+pub struct GraphBatch {
+    nodes: Vec<NodeInstance>,
+    edges: Vec<EdgeInstance>,
+    /// Largest node radius recorded this frame, in world units.
+    max_radius: f32,
+    /// Largest node radius recorded last frame, to cull nodes before their
+    /// style is known.
+    cull_radius: f32,
+}
+
+/// GL objects drawing [`GraphBatch`]es, deleted when dropped.
+pub(super) struct GraphRenderer {
+    gl: Rc<Context>,
+    /// Unit square corners shared by node and edge quads.
+    corners: glow::Buffer,
+    nodes: glow::Buffer,
+    edges: glow::Buffer,
+    node_program: InstancedProgram,
+    edge_program: InstancedProgram,
+    point_program: InstancedProgram,
+    /// Scratch buffer instances are packed into before uploading.
+    upload: Vec<u8>,
+    /// The instances in [`Self::nodes`] and [`Self::edges`], which are only
+    /// uploaded again once they change.
+    uploaded_nodes: Vec<NodeInstance>,
+    uploaded_edges: Vec<EdgeInstance>,
+    /// Sizes of [`Self::nodes`] and [`Self::edges`], as last uploaded.
+    node_bytes: GpuAllocation,
+    edge_bytes: GpuAllocation,
+}
+
+/// A shader program with the vertex array describing its inputs.
+struct InstancedProgram {
+    program: glow::Program,
+    transform: Option<glow::UniformLocation>,
+    /// `u_zoom`, or `u_point_size` for points.
+    scale: Option<glow::UniformLocation>,
+    vao: glow::VertexArray,
+}
+
+impl GraphBatch {
+    /// Draw a node at `center`.
+    pub fn node(&mut self, center: [f32; 2], style: NodeStyle) {
+        self.max_radius = self.max_radius.max(style.radius);
+        self.nodes.push(NodeInstance {
+            center,
+            radius: style.radius,
+            outline_width: style.outline_width,
+            shape: style.shape as u32 as f32,
+            color: rgba8(style.color),
+            outline_color: rgba8(style.outline_color),
+        });
+    }
+
+    /// Draw a straight edge from `a` to `b`.
+    pub fn edge(&mut self, a: [f32; 2], b: [f32; 2], style: EdgeStyle) {
+        self.edges.push(EdgeInstance {
+            a,
+            b,
+            width: style.width,
+            color: rgba8(style.color),
+        });
+    }
+
+    /// Draw an edge through `points`, eg. its endpoints and bends.
+    pub fn edge_path(&mut self, points: &[[f32; 2]], style: EdgeStyle) {
+        for pair in points.windows(2) {
+            self.edge(pair[0], pair[1], style);
+        }
+    }
+
+    /// Draw an edge from a node at `center` with `radius` back to itself, as a
+    /// loop above the node.
+    pub fn edge_loop(&mut self, center: [f32; 2], radius: f32, style: EdgeStyle) {
+        let loop_radius = radius * 1.2;
+        let loop_center = [center[0], center[1] - radius - loop_radius * 0.5];
+        let points: Vec<_> = (0..=LOOP_SEGMENTS)
+            .map(|i| {
+                let angle = i as f32 / LOOP_SEGMENTS as f32 * TAU;
+                [
+                    loop_center[0] + loop_radius * angle.cos(),
+                    loop_center[1] + loop_radius * angle.sin(),
+                ]
+            })
+            .collect();
+        self.edge_path(&points, style);
+    }
+
+    /// Draw the elements of `graph` visible through `camera`, styled per element.
+    ///
+    /// Elements are culled through `spatial`, which must be synced with
+    /// `positions`, and edges drawn along `routes`, which must be up to date
+    /// with `graph`. Bends, and self-loops, are only drawn when the camera's
+    /// [level of detail](Camera::level_of_detail) asks for edge detail; edges
+    /// with bends ignore their route.
+    ///
+    /// Zoomed out far enough on large graphs, nodes are clustered into
+    /// [tiles](super::LevelOfDetail::tile) instead, each drawn as a blob
+    /// growing and darkening with the number of its nodes, and edges into
+    /// bundles between tiles, widening with the number of their edges.
+    #[allow(clippy::too_many_arguments)]
+    pub fn add_graph(
+        &mut self,
+        graph: &Graph,
+        positions: &Positions,
+        spatial: &SpatialIndex,
+        routes: &EdgeRoutes,
+        camera: &Camera,
+        mut node_style: impl FnMut(NodeId) -> NodeStyle,
+        mut edge_style: impl FnMut(EdgeId) -> EdgeStyle,
+    ) {
+        let lod = camera.level_of_detail();
+        let (min, max) = camera.visible_rect();
+        let margin = CULL_MARGIN / camera.zoom() + self.cull_radius.max(self.max_radius);
+        let min = [min[0] - margin, min[1] - margin];
+        let max = [max[0] + margin, max[1] + margin];
+        if let Some(tile) = lod.tile
+            && graph.node_count() >= AGGREGATE_MIN_NODES
+        {
+            let aggregate = Aggregate::new(graph, positions, spatial, (min, max), tile);
+            aggregate.draw(self, node_style, edge_style);
+            return;
+        }
+
+        let mut points = Vec::new();
+        let mut add_edge = |batch: &mut Self, edge| {
+            let Some((source, target)) = graph.endpoints(edge) else {
+                return;
+            };
+            let (Some(a), Some(b)) = (positions.get(source), positions.get(target)) else {
+                return;
+            };
+            let style = edge_style(edge);
+            if source == target {
+                if lod.edge_detail {
+                    // loops on the same node are nested
+                    let (rank, _) = routes.parallel(edge);
+                    let spacing = routes.settings.parallel_spacing;
+                    let radius = node_style(source).radius + rank as f32 * spacing;
+                    batch.edge_loop(a, radius, style);
+                }
+                return;
+            }
+            let bends = positions.bends(edge);
+            if !bends.is_empty() && lod.edge_detail {
+                points.clear();
+                points.push(a);
+                points.extend_from_slice(bends);
+                points.push(b);
+            } else if !routes.path(edge, (source, target), a, b, lod.edge_detail, &mut points) {
+                points.clear();
+                points.extend([a, b]);
+            }
+            // only the pieces of long paths crossing the view are submitted
+            for pair in points.windows(2) {
+                if overlaps(pair[0], pair[1], min, max) {
+                    batch.edge(pair[0], pair[1], style);
+                }
+            }
+        };
+        if routes.is_bundled() {
+            // bundled edges stray far from their bounding box
+            graph.edges().for_each(|edge| add_edge(self, edge));
+        } else {
+            spatial.edges_in(min, max, |edge| add_edge(self, edge));
+        }
+        spatial.nodes_in(min, max, |node, center| self.node(center, node_style(node)));
+    }
+
+    /// Number of nodes recorded, tiles counting as one.
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Number of straight edge pieces recorded.
+    pub fn segment_count(&self) -> usize {
+        self.edges.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty() && self.edges.is_empty()
+    }
+
+    /// The recorded nodes, in drawing order, with their center.
+    pub(super) fn recorded_nodes(&self) -> impl Iterator<Item = ([f32; 2], NodeStyle)> + '_ {
+        self.nodes.iter().map(|node| {
+            let shape = match node.shape as u32 {
+                1 => NodeShape::Square,
+                2 => NodeShape::Diamond,
+                3 => NodeShape::Triangle,
+                _ => NodeShape::Circle,
+            };
+            let style = NodeStyle {
+                shape,
+                radius: node.radius,
+                color: unit_rgba(node.color),
+                outline_width: node.outline_width,
+                outline_color: unit_rgba(node.outline_color),
+            };
+            (node.center, style)
+        })
+    }
+
+    /// The recorded straight edge pieces, in drawing order, with their ends.
+    pub(super) fn recorded_edges(
+        &self,
+    ) -> impl Iterator<Item = ([f32; 2], [f32; 2], EdgeStyle)> + '_ {
+        self.edges.iter().map(|edge| {
+            let style = EdgeStyle {
+                width: edge.width,
+                color: unit_rgba(edge.color),
+            };
+            (edge.a, edge.b, style)
+        })
+    }
+
+    /// Forget every recorded element.
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.edges.clear();
+        self.cull_radius = std::mem::take(&mut self.max_radius);
+    }
+}
+
+/// The nodes of a graph clustered into square tiles, and its edges into
+/// bundles between them, see [`GraphBatch::add_graph()`].
+struct Aggregate {
+    /// The visible nodes of each tile, by tile coordinates.
+    tiles: HashMap<[i32; 2], Vec<(NodeId, [f32; 2])>>,
+    /// The visible edges between each pair of tiles, smallest first.
+    bundles: HashMap<([i32; 2], [i32; 2]), Vec<EdgeId>>,
+    /// Size of the tiles, in world units.
+    size: f32,
+}
+
+impl Aggregate {
+    /// Cluster the nodes of `graph` in `visible` into tiles of `size`.
+    fn new(
+        graph: &Graph,
+        positions: &Positions,
+        spatial: &SpatialIndex,
+        visible: ([f32; 2], [f32; 2]),
+        size: f32,
+    ) -> Self {
+        let tile_of = |point: [f32; 2]| point.map(|v| (v / size).floor() as i32);
+        let (min, max) = visible;
+        let mut tiles: HashMap<_, Vec<_>> = HashMap::new();
+        spatial.nodes_in(min, max, |node, center| {
+            tiles
+                .entry(tile_of(center))
+                .or_default()
+                .push((node, center));
+        });
+        let mut bundles: HashMap<_, Vec<_>> = HashMap::new();
+        spatial.edges_in(min, max, |edge| {
+            let Some((source, target)) = graph.endpoints(edge) else {
+                return;
+            };
+            let (Some(a), Some(b)) = (positions.get(source), positions.get(target)) else {
+                return;
+            };
+            let (a, b) = (tile_of(a), tile_of(b));
+            // edges within a tile disappear into its blob
+            if a != b {
+                bundles.entry((a.min(b), a.max(b))).or_default().push(edge);
+            }
+        });
+        Self {
+            tiles,
+            bundles,
+            size,
+        }
+    }
+
+    /// Record the tiles and bundles into `batch`, styled as the average of
+    /// their elements.
+    fn draw(
+        &self,
+        batch: &mut GraphBatch,
+        mut node_style: impl FnMut(NodeId) -> NodeStyle,
+        mut edge_style: impl FnMut(EdgeId) -> EdgeStyle,
+    ) {
+        // tiles are centered on their nodes, those off screen at their middle
+        let mut centers: HashMap<[i32; 2], [f32; 2]> = HashMap::new();
+        let densest = self.tiles.values().map(Vec::len).max().unwrap_or(1);
+        let mut blobs = Vec::with_capacity(self.tiles.len());
+        for (&tile, nodes) in &self.tiles {
+            let mut center = [0.0; 2];
+            let mut color = [0.0; 4];
+            for &(node, position) in nodes {
+                let style = node_style(node);
+                add(&mut center, position);
+                add(&mut color, style.color);
+            }
+            let count = nodes.len() as f32;
+            let center = center.map(|v| v / count);
+            let mut color = color.map(|v| v / count);
+            let density = density(nodes.len(), densest);
+            color[3] *= 0.4 + 0.6 * density;
+            let style = NodeStyle {
+                radius: self.size * 0.5 * (0.3 + 0.7 * density),
+                color,
+                ..NodeStyle::default()
+            };
+            centers.insert(tile, center);
+            blobs.push((center, style));
+        }
+
+        let middle = |tile: [i32; 2]| tile.map(|v| (v as f32 + 0.5) * self.size);
+        let busiest = self.bundles.values().map(Vec::len).max().unwrap_or(1);
+        for (&(a, b), edges) in &self.bundles {
+            let mut color = [0.0; 4];
+            for &edge in edges {
+                let style = edge_style(edge);
+                add(&mut color, style.color);
+            }
+            let mut color = color.map(|v| v / edges.len() as f32);
+            let density = density(edges.len(), busiest);
+            color[3] = (color[3] * (1.0 + 2.0 * density)).min(1.0);
+            let style = EdgeStyle {
+                width: 1.0 + (MAX_BUNDLE_WIDTH - 1.0) * density,
+                color,
+            };
+            let a = centers.get(&a).copied().unwrap_or_else(|| middle(a));
+            let b = centers.get(&b).copied().unwrap_or_else(|| middle(b));
+            batch.edge(a, b, style);
+        }
+        for (center, style) in blobs {
+            batch.node(center, style);
+        }
+    }
+}
+
+/// Returns `true` if the bounding box of the segment from `a` to `b` overlaps
+/// the rectangle from `min` to `max`.
+fn overlaps(a: [f32; 2], b: [f32; 2], min: [f32; 2], max: [f32; 2]) -> bool {
+    (0..2).all(|i| a[i].max(b[i]) >= min[i] && a[i].min(b[i]) <= max[i])
+}
+
+fn add<const N: usize>(sum: &mut [f32; N], value: [f32; N]) {
+    for (sum, value) in sum.iter_mut().zip(value) {
+        *sum += value;
+    }
+}
+
+/// How full something holding `count` elements is, from `0.0` to `1.0` for
+/// the fullest holding `most`, on a log scale so sparse tiles still show.
+fn density(count: usize, most: usize) -> f32 {
+    match most {
+        0 | 1 => 1.0,
+        _ => (count as f32).ln() / (most as f32).ln(),
+    }
+}
+
+impl GraphRenderer {
+    pub(super) fn new(gl: Rc<Context>) -> Result<Self, String> {
+        unsafe {
+            let corners = gl.create_buffer()?;
+            gl.bind_buffer(glow::ARRAY_BUFFER, Some(corners));
+            let square: [f32; 8] = [0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 1.0];
+            let bytes: Vec<u8> = square.iter().flat_map(|v| v.to_ne_bytes()).collect();
+            gl.buffer_data_u8_slice(glow::ARRAY_BUFFER, &bytes, glow::STATIC_DRAW);
+            let nodes = gl.create_buffer()?;
+            let edges = gl.create_buffer()?;
+
+            let node_program =
+                InstancedProgram::new(&gl, NODE_VERTEX_SHADER, NODE_FRAGMENT_SHADER, "u_zoom")?;
+            gl.bind_vertex_array(Some(node_program.vao));
+            bind_corners(&gl, corners);
+            gl.bind_buffer(glow::ARRAY_BUFFER, Some(nodes));
+            let stride = size_of::<NodeInstance>() as i32;
+            instance_attrib(&gl, 1, 2, glow::FLOAT, stride, 0, 1);
+            instance_attrib(&gl, 2, 1, glow::FLOAT, stride, 8, 1);
+            instance_attrib(&gl, 3, 1, glow::FLOAT, stride, 12, 1);
+            instance_attrib(&gl, 6, 1, glow::FLOAT, stride, 16, 1);
+            instance_attrib(&gl, 4, 4, glow::UNSIGNED_BYTE, stride, 20, 1);
+            instance_attrib(&gl, 5, 4, glow::UNSIGNED_BYTE, stride, 24, 1);
+
+            let edge_program =
+                InstancedProgram::new(&gl, EDGE_VERTEX_SHADER, EDGE_FRAGMENT_SHADER, "u_zoom")?;
+            gl.bind_vertex_array(Some(edge_program.vao));
+            bind_corners(&gl, corners);
+            gl.bind_buffer(glow::ARRAY_BUFFER, Some(edges));
+            let stride = size_of::<EdgeInstance>() as i32;
+            instance_attrib(&gl, 1, 2, glow::FLOAT, stride, 0, 1);
+            instance_attrib(&gl, 2, 2, glow::FLOAT, stride, 8, 1);
+            instance_attrib(&gl, 3, 1, glow::FLOAT, stride, 16, 1);
+            instance_attrib(&gl, 4, 4, glow::UNSIGNED_BYTE, stride, 20, 1);
+
+            // reads the node buffer one vertex per node
+            let point_program = InstancedProgram::new(
+                &gl,
+                POINT_VERTEX_SHADER,
+                POINT_FRAGMENT_SHADER,
+                "u_point_size",
+            )?;
+            gl.bind_vertex_array(Some(point_program.vao));
+            gl.bind_buffer(glow::ARRAY_BUFFER, Some(nodes));
+            let stride = size_of::<NodeInstance>() as i32;
+            instance_attrib(&gl, 0, 2, glow::FLOAT, stride, 0, 0);
+            instance_attrib(&gl, 1, 4, glow::UNSIGNED_BYTE, stride, 20, 0);
+
+            gl.bind_vertex_array(None);
+            gl.bind_buffer(glow::ARRAY_BUFFER, None);
+
+            Ok(Self {
+                gl,
+                corners,
+                nodes,
+                edges,
+                node_program,
+                edge_program,
+                point_program,
+                upload: Vec::new(),
+                uploaded_nodes: Vec::new(),
+                uploaded_edges: Vec::new(),
+                node_bytes: GpuAllocation::default(),
+                edge_bytes: GpuAllocation::default(),
+            })
+        }
+    }
+
+    /// Draw the edges then the nodes of `batch`, with `transform` from world
+    /// coordinates and `zoom` points per world unit. Instances are only
+    /// uploaded if they changed since the last call.
+    pub(super) fn draw(&mut self, batch: &GraphBatch, transform: &[f32; 16], zoom: f32) {
+        if batch.is_empty() {
+            return;
+        }
+        let gl = &self.gl;
+        unsafe {
+            gl.enable(glow::BLEND);
+            gl.blend_func_separate(
+                glow::SRC_ALPHA,
+                glow::ONE_MINUS_SRC_ALPHA,
+                glow::ONE,
+                glow::ONE_MINUS_SRC_ALPHA,
+            );
+
+            if !batch.edges.is_empty() && batch.edges != self.uploaded_edges {
+                self.upload.clear();
+                for edge in &batch.edges {
+                    push_f32s(
+                        &mut self.upload,
+                        &[edge.a[0], edge.a[1], edge.b[0], edge.b[1]],
+                    );
+                    push_f32s(&mut self.upload, &[edge.width]);
+                    self.upload.extend(edge.color);
+                }
+                gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.edges));
+                gl.buffer_data_u8_slice(glow::ARRAY_BUFFER, &self.upload, glow::DYNAMIC_DRAW);
+                self.edge_bytes.set(self.upload.len());
+                self.uploaded_edges.clone_from(&batch.edges);
+            }
+            if !batch.edges.is_empty() {
+                let program = &self.edge_program;
+                gl.use_program(Some(program.program));
+                gl.uniform_matrix_4_f32_slice(program.transform.as_ref(), false, transform);
+                gl.uniform_1_f32(program.scale.as_ref(), zoom);
+                gl.bind_vertex_array(Some(program.vao));
+                gl.draw_arrays_instanced(glow::TRIANGLE_STRIP, 0, 4, batch.edges.len() as i32);
+            }
+
+            if !batch.nodes.is_empty() && batch.nodes != self.uploaded_nodes {
+                self.upload.clear();
+                for node in &batch.nodes {
+                    push_f32s(
+                        &mut self.upload,
+                        &[
+                            node.center[0],
+                            node.center[1],
+                            node.radius,
+                            node.outline_width,
+                            node.shape,
+                        ],
+                    );
+                    self.upload.extend(node.color);
+                    self.upload.extend(node.outline_color);
+                }
+                gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.nodes));
+                gl.buffer_data_u8_slice(glow::ARRAY_BUFFER, &self.upload, glow::DYNAMIC_DRAW);
+                self.node_bytes.set(self.upload.len());
+                self.uploaded_nodes.clone_from(&batch.nodes);
+            }
+            if !batch.nodes.is_empty() {
+                if batch.max_radius * zoom < POINT_RADIUS {
+                    let program = &self.point_program;
+                    gl.enable(glow::PROGRAM_POINT_SIZE);
+                    gl.use_program(Some(program.program));
+                    gl.uniform_matrix_4_f32_slice(program.transform.as_ref(), false, transform);
+                    gl.uniform_1_f32(program.scale.as_ref(), POINT_RADIUS * 2.0);
+                    gl.bind_vertex_array(Some(program.vao));
+                    gl.draw_arrays(glow::POINTS, 0, batch.nodes.len() as i32);
+                    gl.disable(glow::PROGRAM_POINT_SIZE);
+                } else {
+                    let program = &self.node_program;
+                    gl.use_program(Some(program.program));
+                    gl.uniform_matrix_4_f32_slice(program.transform.as_ref(), false, transform);
+                    gl.uniform_1_f32(program.scale.as_ref(), zoom);
+                    gl.bind_vertex_array(Some(program.vao));
+                    gl.draw_arrays_instanced(glow::TRIANGLE_STRIP, 0, 4, batch.nodes.len() as i32);
+                }
+            }
+
+            gl.bind_vertex_array(None);
+            gl.bind_buffer(glow::ARRAY_BUFFER, None);
+            gl.use_program(None);
+        }
+    }
+}
+
+impl InstancedProgram {
+    /// Link a program and create its (empty) vertex array.
+    unsafe fn new(gl: &Context, vertex: &str, fragment: &str, scale: &str) -> Result<Self, String> {
+        unsafe {
+            let program = link_program(
+                gl,
+                &[
+                    (glow::VERTEX_SHADER, vertex),
+                    (glow::FRAGMENT_SHADER, fragment),
+                ],
+            )?;
+            Ok(Self {
+                program,
+                transform: gl.get_uniform_location(program, "u_transform"),
+                scale: gl.get_uniform_location(program, scale),
+                vao: gl.create_vertex_array()?,
+            })
+        }
+    }
+
+    unsafe fn delete(&self, gl: &Context) {
+        unsafe {
+            gl.delete_vertex_array(self.vao);
+            gl.delete_program(self.program);
+        }
+    }
+}
+
+impl Drop for GraphRenderer {
+    fn drop(&mut self) {
+        unsafe {
+            self.gl.delete_buffer(self.corners);
+            self.gl.delete_buffer(self.nodes);
+            self.gl.delete_buffer(self.edges);
+            self.node_program.delete(&self.gl);
+            self.edge_program.delete(&self.gl);
+            self.point_program.delete(&self.gl);
+        }
+    }
+}
+
+/// Feed the unit square `corners` to attribute 0 of the bound vertex array.
+unsafe fn bind_corners(gl: &Context, corners: glow::Buffer) {
+    unsafe {
+        gl.bind_buffer(glow::ARRAY_BUFFER, Some(corners));
+        gl.enable_vertex_attrib_array(0);
+        gl.vertex_attrib_pointer_f32(0, 2, glow::FLOAT, false, 0, 0);
+    }
+}
+
+/// Describe attribute `index` of the bound vertex array, read from the bound
+/// buffer once per `divisor` instances, or per vertex if zero. Bytes are
+/// normalized to `0.0..=1.0`.
+unsafe fn instance_attrib(
+    gl: &Context,
+    index: u32,
+    size: i32,
+    kind: u32,
+    stride: i32,
+    offset: i32,
+    divisor: u32,
+) {
+    unsafe {
+        gl.enable_vertex_attrib_array(index);
+        gl.vertex_attrib_pointer_f32(
+            index,
+            size,
+            kind,
+            kind == glow::UNSIGNED_BYTE,
+            stride,
+            offset,
+        );
+        gl.vertex_attrib_divisor(index, divisor);
+    }
+}
+
+fn push_f32s(bytes: &mut Vec<u8>, values: &[f32]) {
+    for value in values {
+        bytes.extend_from_slice(&value.to_ne_bytes());
+    }
+}
+
+fn rgba8(color: [f32; 4]) -> [u8; 4] {
+    color.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8)
+}
+
+pub(super) fn unit_rgba(color: [u8; 4]) -> [f32; 4] {
+    color.map(|c| c as f32 / 255.0)
+}
+
+impl std::default::Default for NodeStyle {
+    fn default() -> Self {
+        Self {
+            shape: NodeShape::Circle,
+            radius: 6.0,
+            color: [0.35, 0.65, 1.0, 1.0],
+            outline_width: 0.0,
+            outline_color: [0.0; 4],
+        }
+    }
+}
+
+impl std::default::Default for EdgeStyle {
+    fn default() -> Self {
+        Self {
+            width: 1.0,
+            color: [0.8, 0.8, 0.8, 0.4],
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+
+use super::Camera;
+use crate::imgui::Ui;
+
+/// Gap between a label and the area around its anchor it keeps clear, in points.
+const LABEL_GAP: f32 = 3.0;
+/// Most labels placed each frame, keeping the highest priorities.
+const MAX_LABELS: usize = 2000;
+/// Size of the grid cells overlapping labels are looked up in, in points.
+const GRID_CELL: f32 = 64.0;
+/// Offsets the halo is drawn at around each label, in points.
+const HALO_OFFSETS: [[f32; 2]; 8] = [
+    [-1.0, -1.0],
+    [0.0, -1.0],
+    [1.0, -1.0],
+    [-1.0, 0.0],
+    [1.0, 0.0],
+    [-1.0, 1.0],
+    [0.0, 1.0],
+    [1.0, 1.0],
+];
+
+/// Text placed next to a point, eg. a node, unless it would overlap a label of
+/// higher priority.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Label {
+    /// Point the label is placed around.
+    pub anchor: [f32; 2],
+    /// Radius around the anchor the label keeps clear, eg. the node's radius.
+    pub clearance: f32,
+    /// Labels are placed in order of decreasing priority, so higher ones win
+    /// where labels would overlap.
+    pub priority: f32,
+    /// RGBA, `0.0..=1.0`.
+    pub color: [f32; 4],
+    /// Color of an outline around the glyphs, keeping the label readable over
+    /// edges and other shapes.
+    pub halo: Option<[f32; 4]>,
+    pub text: String,
+}
+
+/// Labels recorded for a single frame, in world coordinates, see
+/// [`Scene::labels()`](super::Scene::labels).
+///
+/// Each label is tried to the right of its anchor, then to the left, below, and
+/// above, and hidden if every spot overlaps a label placed before it.
+#[derive(Clone, Debug, Default)]
+pub struct LabelBatch {
+    labels: Vec<Label>,
+}
+
+impl LabelBatch {
+    /// Add `label`, with its anchor and clearance in world units.
+    pub fn push(&mut self, label: Label) {
+        self.labels.push(label);
+    }
+
+    /// Number of labels recorded.
+    pub fn len(&self) -> usize {
+        self.labels.len()
+    }
+
+    /// Returns `true` if no labels were recorded.
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+
+    /// Remove every recorded label.
+    pub fn clear(&mut self) {
+        self.labels.clear();
+    }
+
+    /// Take the recorded labels, transformed to screen coordinates by `camera`.
+    pub(super) fn take(&mut self, camera: &Camera) -> Vec<Label> {
+        let mut labels = std::mem::take(&mut self.labels);
+        for label in &mut labels {
+            label.anchor = camera.world_to_screen(label.anchor);
+            label.clearance *= camera.zoom();
+        }
+        labels
+    }
+}
+
+/// Place `labels`, in screen coordinates, and draw those which fit on imgui's
+/// background draw list.
+pub(super) fn draw_labels(ui: &Ui, labels: &mut [Label]) {
+    let viewport = ui.io().display_size;
+    let draw_list = ui.get_background_draw_list();
+    for (label, min) in place_labels(labels, viewport, |text| ui.calc_text_size(text)) {
+        if let Some(halo) = label.halo {
+            for [dx, dy] in HALO_OFFSETS {
+                draw_list.add_text([min[0] + dx, min[1] + dy], halo, &label.text);
+            }
+        }
+        draw_list.add_text(min, label.color, &label.text);
+    }
+}
+
+/// The labels, in screen coordinates, which fit inside `viewport` without
+/// overlapping, with the top-left corner of each, given the size of a text.
+pub(super) fn place_labels(
+    labels: &mut [Label],
+    viewport: [f32; 2],
+    mut measure: impl FnMut(&str) -> [f32; 2],
+) -> Vec<(&Label, [f32; 2])> {
+    labels.sort_by(|a, b| b.priority.total_cmp(&a.priority));
+
+    let mut shown = Vec::new();
+    let mut placed: Vec<([f32; 2], [f32; 2])> = Vec::new();
+    let mut grid: HashMap<(i32, i32), Vec<usize>> = HashMap::new();
+    for label in labels.iter() {
+        if placed.len() >= MAX_LABELS {
+            break;
+        }
+        let size = measure(&label.text);
+        let Some(min) = candidates(label, size).into_iter().find(|&min| {
+            let max = [min[0] + size[0], min[1] + size[1]];
+            on_screen(min, max, viewport) && !overlaps(&placed, &grid, min, max)
+        }) else {
+            continue;
+        };
+        let max = [min[0] + size[0], min[1] + size[1]];
+        for cell in cells(min, max) {
+            grid.entry(cell).or_default().push(placed.len());
+        }
+        placed.push((min, max));
+        shown.push((label, min));
+    }
+    shown
+}
+
+/// Top-left corners `label` can be drawn at, in order of preference.
+fn candidates(label: &Label, [w, h]: [f32; 2]) -> [[f32; 2]; 4] {
+    let [x, y] = label.anchor;
+    let d = label.clearance + LABEL_GAP;
+    [
+        [x + d, y - h * 0.5],
+        [x - d - w, y - h * 0.5],
+        [x - w * 0.5, y + d],
+        [x - w * 0.5, y - d - h],
+    ]
+}
+
+fn on_screen(min: [f32; 2], max: [f32; 2], viewport: [f32; 2]) -> bool {
+    max[0] > 0.0 && max[1] > 0.0 && min[0] < viewport[0] && min[1] < viewport[1]
+}
+
+fn overlaps(
+    placed: &[([f32; 2], [f32; 2])],
+    grid: &HashMap<(i32, i32), Vec<usize>>,
+    min: [f32; 2],
+    max: [f32; 2],
+) -> bool {
+    cells(min, max).any(|cell| {
+        grid.get(&cell).is_some_and(|indices| {
+            indices.iter().any(|&i| {
+                let (a, b) = placed[i];
+                a[0] < max[0] && min[0] < b[0] && a[1] < max[1] && min[1] < b[1]
+            })
+        })
+    })
+}
+
+/// Grid cells the rectangle from `min` to `max` touches.
+fn cells(min: [f32; 2], max: [f32; 2]) -> impl Iterator<Item = (i32, i32)> {
+    let cell = |v: f32| (v / GRID_CELL).floor() as i32;
+    let (x0, x1) = (cell(min[0]), cell(max[0]));
+    let (y0, y1) = (cell(min[1]), cell(max[1]));
+    (x0..=x1).flat_map(move |x| (y0..=y1).map(move |y| (x, y)))
+}
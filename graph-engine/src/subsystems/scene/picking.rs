@@ -0,0 +1,121 @@
+use crate::graph::{EdgeId, Graph, NodeId};
+use crate::layout::Positions;
+use crate::spatial::SpatialIndex;
+
+/// The element found under a point by [`Picker::pick()`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Hit {
+    Node(NodeId),
+    Edge(EdgeId),
+}
+
+/// Finds the nodes and edges of a graph under a point or inside a rectangle,
+/// in world coordinates.
+///
+/// Candidates come from a [`SpatialIndex`], which must be
+/// [synced](SpatialIndex::sync) with `positions`; they are then tested against
+/// the exact shapes drawn.
+#[derive(Copy, Clone)]
+pub struct Picker<'a> {
+    graph: &'a Graph,
+    positions: &'a Positions,
+    index: &'a SpatialIndex,
+}
+
+impl<'a> Picker<'a> {
+    /// Create a picker over `graph` drawn at `positions`, indexed by `index`.
+    pub fn new(graph: &'a Graph, positions: &'a Positions, index: &'a SpatialIndex) -> Self {
+        Self {
+            graph,
+            positions,
+            index,
+        }
+    }
+
+    /// The element drawn under `point`, preferring nodes over edges.
+    ///
+    /// Nodes are hit within `node_radius` of their center and edges within
+    /// `edge_tolerance` of their line, both in world units. The closest element
+    /// wins when several are hit.
+    pub fn pick(&self, point: [f32; 2], node_radius: f32, edge_tolerance: f32) -> Option<Hit> {
+        if let Some((node, _)) = self.index.nearest_node(point, node_radius) {
+            return Some(Hit::Node(node));
+        }
+
+        let mut best: Option<(f32, EdgeId)> = None;
+        let reach = [edge_tolerance, edge_tolerance];
+        self.index
+            .edges_in(sub(point, reach), add(point, reach), |edge| {
+                let Some(points) = self.edge_points(edge) else {
+                    return;
+                };
+                for pair in points.windows(2) {
+                    let dist = segment_distance_sq(point, pair[0], pair[1]);
+                    if dist <= edge_tolerance * edge_tolerance && best.is_none_or(|(d, _)| dist < d)
+                    {
+                        best = Some((dist, edge));
+                    }
+                }
+            });
+        best.map(|(_, edge)| Hit::Edge(edge))
+    }
+
+    /// Nodes whose center lies inside the rectangle between corners `a` and `b`,
+    /// and edges lying entirely inside it, in world units. Both are in id order.
+    pub fn pick_rect(&self, a: [f32; 2], b: [f32; 2]) -> (Vec<NodeId>, Vec<EdgeId>) {
+        let min = [a[0].min(b[0]), a[1].min(b[1])];
+        let max = [a[0].max(b[0]), a[1].max(b[1])];
+
+        let mut nodes = Vec::new();
+        self.index.nodes_in(min, max, |node, _| nodes.push(node));
+        nodes.sort_unstable();
+
+        // an edge's bounding box lies inside the rectangle exactly when the edge does
+        let mut edges = Vec::new();
+        self.index.edges().query((min, max), |edge, (lo, hi)| {
+            if lo[0] >= min[0] && lo[1] >= min[1] && hi[0] <= max[0] && hi[1] <= max[1] {
+                edges.push(edge);
+            }
+        });
+        edges.sort_unstable();
+
+        (nodes, edges)
+    }
+
+    /// Points `edge` is drawn through, endpoints included.
+    fn edge_points(&self, edge: EdgeId) -> Option<Vec<[f32; 2]>> {
+        let (source, target) = self.graph.endpoints(edge)?;
+        let (a, b) = (self.positions.get(source)?, self.positions.get(target)?);
+        let bends = self.positions.bends(edge);
+        let mut points = Vec::with_capacity(bends.len() + 2);
+        points.push(a);
+        points.extend_from_slice(bends);
+        points.push(b);
+        Some(points)
+    }
+}
+
+fn add(a: [f32; 2], b: [f32; 2]) -> [f32; 2] {
+    [a[0] + b[0], a[1] + b[1]]
+}
+
+fn sub(a: [f32; 2], b: [f32; 2]) -> [f32; 2] {
+    [a[0] - b[0], a[1] - b[1]]
+}
+
+fn distance_sq(a: [f32; 2], b: [f32; 2]) -> f32 {
+    let d = sub(a, b);
+    d[0] * d[0] + d[1] * d[1]
+}
+
+/// Squared distance from `p` to the segment between `a` and `b`.
+fn segment_distance_sq(p: [f32; 2], a: [f32; 2], b: [f32; 2]) -> f32 {
+    let ab = sub(b, a);
+    let len_sq = ab[0] * ab[0] + ab[1] * ab[1];
+    if len_sq == 0.0 {
+        return distance_sq(p, a);
+    }
+    let ap = sub(p, a);
+    let t = ((ap[0] * ab[0] + ap[1] * ab[1]) / len_sq).clamp(0.0, 1.0);
+    distance_sq(p, [a[0] + ab[0] * t, a[1] + ab[1] * t])
+}
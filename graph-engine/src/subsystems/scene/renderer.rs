@@ -0,0 +1,301 @@
+use std::rc::Rc;
+
+use crate::imgui::renderers::glow::inner::{self as glow, Context, HasContext};
+use crate::memory::GpuAllocation;
+
+use super::draw_list::{DrawList, Vertex};
+use super::{GraphBuffers, SceneError};
+
+const VERTEX_SHADER: &str = r#"#version 330 core
+uniform mat4 u_transform;
+layout(location = 0) in vec2 a_pos;
+layout(location = 1) in vec4 a_color;
+out vec4 v_color;
+void main() {
+    v_color = a_color;
+    gl_Position = u_transform * vec4(a_pos, 0.0, 1.0);
+}
+"#;
+
+const FRAGMENT_SHADER: &str = r#"#version 330 core
+in vec4 v_color;
+out vec4 o_color;
+void main() {
+    o_color = v_color;
+}
+"#;
+
+/// Positions come straight from a [`GraphBuffers`] position buffer.
+const BUFFER_VERTEX_SHADER: &str = r#"#version 330 core
+uniform mat4 u_transform;
+uniform float u_point_size;
+layout(location = 0) in vec2 a_pos;
+void main() {
+    gl_Position = u_transform * vec4(a_pos, 0.0, 1.0);
+    gl_PointSize = u_point_size;
+}
+"#;
+
+const BUFFER_FRAGMENT_SHADER: &str = r#"#version 330 core
+uniform vec4 u_color;
+uniform bool u_round;
+out vec4 o_color;
+void main() {
+    if (u_round) {
+        vec2 c = gl_PointCoord * 2.0 - 1.0;
+        if (dot(c, c) > 1.0) {
+            discard;
+        }
+    }
+    o_color = u_color;
+}
+"#;
+
+/// GL objects drawing [`DrawList`]s, deleted when dropped.
+pub(super) struct SceneRenderer {
+    gl: Rc<Context>,
+    program: glow::Program,
+    transform: Option<glow::UniformLocation>,
+    vao: glow::VertexArray,
+    vbo: glow::Buffer,
+    /// Size of [`Self::vbo`], as last uploaded.
+    vbo_bytes: GpuAllocation,
+    /// Scratch buffer all draw lists are packed into before uploading.
+    upload: Vec<u8>,
+    buffers: BufferProgram,
+}
+
+/// Draws [`GraphBuffers`] as round points and lines.
+struct BufferProgram {
+    program: glow::Program,
+    transform: Option<glow::UniformLocation>,
+    point_size: Option<glow::UniformLocation>,
+    color: Option<glow::UniformLocation>,
+    round: Option<glow::UniformLocation>,
+    /// Rebound to each [`GraphBuffers`]' buffers before drawing it.
+    vao: glow::VertexArray,
+}
+
+impl SceneRenderer {
+    pub(super) fn new(gl: Rc<Context>) -> Result<Self, SceneError> {
+        unsafe {
+            let program = link_program(
+                &gl,
+                &[
+                    (glow::VERTEX_SHADER, VERTEX_SHADER),
+                    (glow::FRAGMENT_SHADER, FRAGMENT_SHADER),
+                ],
+            )
+            .map_err(SceneError::Gl)?;
+            let transform = gl.get_uniform_location(program, "u_transform");
+
+            let vao = gl.create_vertex_array().map_err(SceneError::Gl)?;
+            let vbo = gl.create_buffer().map_err(SceneError::Gl)?;
+            gl.bind_vertex_array(Some(vao));
+            gl.bind_buffer(glow::ARRAY_BUFFER, Some(vbo));
+            let stride = size_of::<Vertex>() as i32;
+            gl.enable_vertex_attrib_array(0);
+            gl.vertex_attrib_pointer_f32(0, 2, glow::FLOAT, false, stride, 0);
+            gl.enable_vertex_attrib_array(1);
+            gl.vertex_attrib_pointer_f32(
+                1,
+                4,
+                glow::UNSIGNED_BYTE,
+                true,
+                stride,
+                size_of::<[f32; 2]>() as i32,
+            );
+            gl.bind_vertex_array(None);
+            gl.bind_buffer(glow::ARRAY_BUFFER, None);
+
+            let buffer_program = link_program(
+                &gl,
+                &[
+                    (glow::VERTEX_SHADER, BUFFER_VERTEX_SHADER),
+                    (glow::FRAGMENT_SHADER, BUFFER_FRAGMENT_SHADER),
+                ],
+            )
+            .map_err(SceneError::Gl)?;
+            let buffers = BufferProgram {
+                program: buffer_program,
+                transform: gl.get_uniform_location(buffer_program, "u_transform"),
+                point_size: gl.get_uniform_location(buffer_program, "u_point_size"),
+                color: gl.get_uniform_location(buffer_program, "u_color"),
+                round: gl.get_uniform_location(buffer_program, "u_round"),
+                vao: gl.create_vertex_array().map_err(SceneError::Gl)?,
+            };
+
+            Ok(Self {
+                gl,
+                program,
+                transform,
+                vao,
+                vbo,
+                vbo_bytes: GpuAllocation::default(),
+                upload: Vec::new(),
+                buffers,
+            })
+        }
+    }
+
+    /// Upload all `lists` in one buffer, then draw each with its transform matrix.
+    pub(super) fn draw(&mut self, lists: &[(&DrawList, [f32; 16])]) {
+        self.upload.clear();
+        for (list, _) in lists {
+            self.upload
+                .extend(list.vertices().iter().flat_map(vertex_bytes));
+        }
+        if self.upload.is_empty() {
+            return;
+        }
+
+        let gl = &self.gl;
+        unsafe {
+            gl.enable(glow::BLEND);
+            gl.blend_func_separate(
+                glow::SRC_ALPHA,
+                glow::ONE_MINUS_SRC_ALPHA,
+                glow::ONE,
+                glow::ONE_MINUS_SRC_ALPHA,
+            );
+            gl.disable(glow::DEPTH_TEST);
+            gl.disable(glow::CULL_FACE);
+            gl.disable(glow::SCISSOR_TEST);
+
+            gl.use_program(Some(self.program));
+            gl.bind_vertex_array(Some(self.vao));
+            gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.vbo));
+            gl.buffer_data_u8_slice(glow::ARRAY_BUFFER, &self.upload, glow::STREAM_DRAW);
+            self.vbo_bytes.set(self.upload.len());
+
+            let mut first = 0;
+            for (list, transform) in lists {
+                let count = list.vertices().len() as i32;
+                if count > 0 {
+                    gl.uniform_matrix_4_f32_slice(self.transform.as_ref(), false, transform);
+                    gl.draw_arrays(glow::TRIANGLES, first, count);
+                }
+                first += count;
+            }
+
+            gl.bind_vertex_array(None);
+            gl.bind_buffer(glow::ARRAY_BUFFER, None);
+            gl.use_program(None);
+        }
+    }
+
+    /// Draw the edges then the nodes of `buffers`, with `transform` from world
+    /// coordinates and `zoom` points per world unit.
+    pub(super) fn draw_buffers(
+        &mut self,
+        buffers: &GraphBuffers,
+        transform: &[f32; 16],
+        zoom: f32,
+    ) {
+        let gl = &self.gl;
+        let program = &self.buffers;
+        unsafe {
+            gl.enable(glow::BLEND);
+            gl.blend_func_separate(
+                glow::SRC_ALPHA,
+                glow::ONE_MINUS_SRC_ALPHA,
+                glow::ONE,
+                glow::ONE_MINUS_SRC_ALPHA,
+            );
+            gl.enable(glow::PROGRAM_POINT_SIZE);
+
+            gl.use_program(Some(program.program));
+            gl.uniform_matrix_4_f32_slice(program.transform.as_ref(), false, transform);
+            gl.bind_vertex_array(Some(program.vao));
+            gl.bind_buffer(glow::ARRAY_BUFFER, Some(buffers.positions));
+            gl.enable_vertex_attrib_array(0);
+            gl.vertex_attrib_pointer_f32(0, 2, glow::FLOAT, false, 0, 0);
+
+            if buffers.edge_count > 0 {
+                gl.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, Some(buffers.edges));
+                gl.uniform_4_f32_slice(program.color.as_ref(), &buffers.edge_color);
+                gl.uniform_1_i32(program.round.as_ref(), 0);
+                gl.draw_elements(
+                    glow::LINES,
+                    buffers.edge_count as i32 * 2,
+                    glow::UNSIGNED_INT,
+                    0,
+                );
+            }
+            if buffers.node_count > 0 {
+                gl.uniform_4_f32_slice(program.color.as_ref(), &buffers.node_color);
+                gl.uniform_1_i32(program.round.as_ref(), 1);
+                gl.uniform_1_f32(
+                    program.point_size.as_ref(),
+                    (buffers.node_size * zoom).max(1.0),
+                );
+                gl.draw_arrays(glow::POINTS, 0, buffers.node_count as i32);
+            }
+
+            gl.bind_vertex_array(None);
+            gl.bind_buffer(glow::ARRAY_BUFFER, None);
+            gl.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, None);
+            gl.disable(glow::PROGRAM_POINT_SIZE);
+            gl.use_program(None);
+        }
+    }
+}
+
+impl Drop for SceneRenderer {
+    fn drop(&mut self) {
+        unsafe {
+            self.gl.delete_buffer(self.vbo);
+            self.gl.delete_vertex_array(self.vao);
+            self.gl.delete_program(self.program);
+            self.gl.delete_vertex_array(self.buffers.vao);
+            self.gl.delete_program(self.buffers.program);
+        }
+    }
+}
+
+/// The bytes of `vertex`, laid out as described to GL in [`SceneRenderer::new()`].
+fn vertex_bytes(vertex: &Vertex) -> impl Iterator<Item = u8> {
+    let [x, y] = vertex.pos.map(f32::to_ne_bytes);
+    x.into_iter().chain(y).chain(vertex.color)
+}
+
+/// Compile and link a shader program from `(kind, source)` pairs, eg.
+/// `(glow::VERTEX_SHADER, source)`.
+///
+/// Errors with the compile or link log.
+pub(crate) unsafe fn link_program(
+    gl: &Context,
+    sources: &[(u32, &str)],
+) -> Result<glow::Program, String> {
+    unsafe {
+        let program = gl.create_program()?;
+        let mut shaders = Vec::new();
+        for &(kind, source) in sources {
+            let shader = gl.create_shader(kind)?;
+            gl.shader_source(shader, source);
+            gl.compile_shader(shader);
+            if !gl.get_shader_compile_status(shader) {
+                let log = gl.get_shader_info_log(shader);
+                for shader in shaders.into_iter().chain([shader]) {
+                    gl.delete_shader(shader);
+                }
+                gl.delete_program(program);
+                return Err(log);
+            }
+            gl.attach_shader(program, shader);
+            shaders.push(shader);
+        }
+
+        gl.link_program(program);
+        for shader in shaders {
+            gl.detach_shader(program, shader);
+            gl.delete_shader(shader);
+        }
+        if !gl.get_program_link_status(program) {
+            let log = gl.get_program_info_log(program);
+            gl.delete_program(program);
+            return Err(log);
+        }
+        Ok(program)
+    }
+}
@@ -0,0 +1,788 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use super::graph_renderer::unit_rgba;
+use super::labels::{self, Label};
+use super::{Camera, Canvas, DrawList, GraphBatch, LabelBatch, NodeShape, NodeStyle};
+
+/// Size text is written at, in points, matching the ui's default font.
+const FONT_SIZE: f32 = 13.0;
+/// Average advance of a glyph, as a fraction of the font size, to place labels
+/// without the font at hand.
+const CHAR_WIDTH: f32 = 0.55;
+/// Distance from the top of a line of text to its baseline, as a fraction of
+/// the font size.
+const ASCENT: f32 = 0.8;
+/// Width of the outline around the glyphs of labels with a halo, in points.
+const HALO_WIDTH: f32 = 2.0;
+/// Size the title of a page is written at, in points.
+const TITLE_SIZE: f32 = 20.0;
+/// Empty space around the title, the caption, and the legend's entries, in
+/// points.
+const PADDING: f32 = 8.0;
+/// Height of a line of the legend, as a fraction of the font size.
+const LINE_HEIGHT: f32 = 1.5;
+/// Radius of the swatches of the legend, in points.
+const SWATCH_RADIUS: f32 = 5.0;
+/// Opacity of the legend's box over the graph.
+const LEGEND_OPACITY: f32 = 0.85;
+/// Points per inch, the unit of PDF pages.
+const PDF_DPI: f32 = 72.0;
+/// Control point distance of the cubic curves approximating a quarter circle,
+/// as a fraction of the radius.
+const KAPPA: f32 = 0.552_284_8;
+
+/// A vector file format a [`VectorScene`] can be written in.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VectorFormat {
+    #[default]
+    Svg,
+    Pdf,
+}
+
+/// Shapes recorded like in a [`Scene`](super::Scene), written to a vector file
+/// rather than drawn, eg. for publications.
+///
+/// Layers keep the scene's order: the world draw list, then edges, nodes, and
+/// labels on top. Text and labels are written in a generic sans-serif font,
+/// labels placed as if each glyph was [`CHAR_WIDTH`] ems wide. Nodes are always
+/// drawn as shapes, whatever the zoom.
+///
+/// ```ignore
+/// let mut vector = VectorScene::new(*scene.camera(), 2.0);
+/// vector.set_background(Some([1.0; 4]));
+/// vector.graph().node([0.0, 0.0], NodeStyle::default());
+/// vector.save("graph.pdf", VectorFormat::Pdf)?;
+/// ```
+#[derive(Clone, Debug)]
+pub struct VectorScene {
+    camera: Camera,
+    /// Page units per point of the camera's viewport.
+    scale: f32,
+    /// Page units per inch, if the page has a physical size.
+    dpi: Option<f32>,
+    background: Option<[f32; 4]>,
+    /// Written above and below the drawing, each in a band of its own.
+    title: Option<String>,
+    caption: Option<String>,
+    legend: Vec<LegendGroup>,
+    /// Color of the title, caption, and legend.
+    text_color: [f32; 4],
+    world: DrawList,
+    graph: GraphBatch,
+    labels: LabelBatch,
+}
+
+/// A titled group of entries of the legend of a [`VectorScene`], eg. the colors
+/// of the values of a node attribute.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LegendGroup {
+    pub title: String,
+    pub entries: Vec<LegendEntry>,
+}
+
+/// A swatch of the legend and what it stands for.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LegendEntry {
+    pub text: String,
+    pub color: [f32; 4],
+    /// Shape of a node swatch, or `None` for the line of an edge.
+    pub shape: Option<NodeShape>,
+}
+
+/// A shape on the page, in page units with the origin top-left.
+enum Shape {
+    /// Polygons filled in one go, so those sharing edges join without seams.
+    Fill {
+        polygons: Vec<Vec<[f32; 2]>>,
+        color: [f32; 4],
+    },
+    /// An open line with round caps and joins.
+    Line {
+        points: Vec<[f32; 2]>,
+        width: f32,
+        color: [f32; 4],
+    },
+    /// A node, with its outline drawn around it.
+    Node {
+        outline: Outline,
+        color: [f32; 4],
+        stroke: Option<(f32, [f32; 4])>,
+    },
+    /// Text with the left end of its baseline at `pos`.
+    Text {
+        pos: [f32; 2],
+        size: f32,
+        color: [f32; 4],
+        halo: Option<(f32, [f32; 4])>,
+        text: String,
+    },
+}
+
+/// The outline of a node on the page.
+enum Outline {
+    Circle { center: [f32; 2], radius: f32 },
+    Polygon(Vec<[f32; 2]>),
+}
+
+impl VectorFormat {
+    pub const ALL: [VectorFormat; 2] = [Self::Svg, Self::Pdf];
+
+    /// Human-readable name of the format.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Svg => "SVG",
+            Self::Pdf => "PDF",
+        }
+    }
+
+    /// Extension of files in the format, without the dot.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Svg => "svg",
+            Self::Pdf => "pdf",
+        }
+    }
+
+    /// The format of `path`, guessed from its extension.
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        let extension = path.as_ref().extension()?.to_str()?.to_ascii_lowercase();
+        Self::ALL
+            .into_iter()
+            .find(|format| format.extension() == extension)
+    }
+}
+
+impl VectorScene {
+    /// Create an empty page showing what `camera` does, its viewport scaled by
+    /// `scale`. Strokes, text, and node sizes scale along.
+    pub fn new(camera: Camera, scale: f32) -> Self {
+        Self {
+            camera,
+            scale,
+            dpi: None,
+            background: None,
+            title: None,
+            caption: None,
+            legend: Vec::new(),
+            text_color: [0.0, 0.0, 0.0, 1.0],
+            world: DrawList::default(),
+            graph: GraphBatch::default(),
+            labels: LabelBatch::default(),
+        }
+    }
+
+    /// Fill the page with `color` under every shape, or leave it transparent.
+    pub fn set_background(&mut self, background: Option<[f32; 4]>) {
+        self.background = background;
+    }
+
+    /// Give the page a physical size of `dpi` page units per inch, or leave its
+    /// units as points.
+    pub fn set_dpi(&mut self, dpi: Option<f32>) {
+        self.dpi = dpi.filter(|dpi| *dpi > 0.0);
+    }
+
+    /// Write `title` above the drawing and `caption` below it, in `color`,
+    /// which the legend's text is written in too.
+    pub fn set_title(&mut self, title: Option<String>, caption: Option<String>, color: [f32; 4]) {
+        self.title = title.filter(|title| !title.trim().is_empty());
+        self.caption = caption.filter(|caption| !caption.trim().is_empty());
+        self.text_color = color;
+    }
+
+    /// Draw `legend` in the top right corner of the drawing, if not empty.
+    pub fn set_legend(&mut self, legend: Vec<LegendGroup>) {
+        self.legend = legend;
+        self.legend.retain(|group| !group.entries.is_empty());
+    }
+
+    /// Size of the page, in page units, including the title and caption.
+    pub fn size(&self) -> [f32; 2] {
+        let [width, height] = self.camera.viewport().map(|v| v * self.scale);
+        let (top, bottom) = self.bands();
+        [width, top + height + bottom]
+    }
+
+    /// Heights of the bands of the title and caption, in page units.
+    fn bands(&self) -> (f32, f32) {
+        let band = |text: &Option<String>, size: f32| match text {
+            Some(_) => (size + 2.0 * PADDING) * self.scale,
+            None => 0.0,
+        };
+        (
+            band(&self.title, TITLE_SIZE),
+            band(&self.caption, FONT_SIZE),
+        )
+    }
+
+    /// Write the page in `format` to a file on disk.
+    pub fn save(self, path: impl AsRef<Path>, format: VectorFormat) -> std::io::Result<()> {
+        let mut writer = BufWriter::new(fs::File::create(path)?);
+        self.write(format, &mut writer)?;
+        writer.flush()
+    }
+
+    /// Write the page as a document in `format`.
+    pub fn write(self, format: VectorFormat, mut out: impl Write) -> std::io::Result<()> {
+        let (size, dpi) = (self.size(), self.dpi);
+        let shapes = self.shapes();
+        match format {
+            VectorFormat::Svg => write_svg(size, dpi, &shapes, &mut out),
+            VectorFormat::Pdf => write_pdf(size, dpi, &shapes, &mut out),
+        }
+    }
+
+    /// Everything recorded, in drawing order, on the page.
+    fn shapes(mut self) -> Vec<Shape> {
+        let (camera, scale) = (self.camera, self.scale);
+        let (top, _) = self.bands();
+        let to_page = |p: [f32; 2]| {
+            let [x, y] = camera.world_to_screen(p);
+            [x * scale, y * scale + top]
+        };
+        let mut shapes = Vec::new();
+        if let Some(color) = self.background {
+            let [w, h] = self.size();
+            shapes.push(Shape::Fill {
+                polygons: vec![vec![[0.0, 0.0], [w, 0.0], [w, h], [0.0, h]]],
+                color,
+            });
+        }
+
+        // consecutive triangles of a color form a single fill
+        for triangle in self.world.vertices().chunks_exact(3) {
+            let color = unit_rgba(triangle[0].color);
+            let polygon = triangle.iter().map(|v| to_page(v.pos)).collect();
+            match shapes.last_mut() {
+                Some(Shape::Fill {
+                    polygons,
+                    color: last,
+                }) if *last == color => polygons.push(polygon),
+                _ => shapes.push(Shape::Fill {
+                    polygons: vec![polygon],
+                    color,
+                }),
+            }
+        }
+        for text in self.world.take_text() {
+            let [x, y] = to_page(text.pos);
+            shapes.push(Shape::Text {
+                pos: [x, y + ASCENT * FONT_SIZE * scale],
+                size: FONT_SIZE * scale,
+                color: text.color,
+                halo: None,
+                text: text.text,
+            });
+        }
+
+        // consecutive pieces of an edge form a single line
+        for (a, b, style) in self.graph.recorded_edges() {
+            let (a, b, width) = (to_page(a), to_page(b), style.width * scale);
+            match shapes.last_mut() {
+                Some(Shape::Line {
+                    points,
+                    width: w,
+                    color,
+                }) if points.last() == Some(&a) && *w == width && *color == style.color => {
+                    points.push(b)
+                }
+                _ => shapes.push(Shape::Line {
+                    points: vec![a, b],
+                    width,
+                    color: style.color,
+                }),
+            }
+        }
+        for (center, style) in self.graph.recorded_nodes() {
+            let radius = (style.radius * camera.zoom()).max(1.0) * scale;
+            shapes.push(Shape::Node {
+                outline: outline(to_page(center), radius, &style),
+                color: style.color,
+                stroke: (style.outline_width > 0.0)
+                    .then_some((style.outline_width * scale, style.outline_color)),
+            });
+        }
+
+        let mut labels: Vec<Label> = self.labels.take(&camera);
+        let measure = |text: &str| {
+            let width = text.chars().count() as f32 * CHAR_WIDTH * FONT_SIZE;
+            [width, FONT_SIZE]
+        };
+        for (label, min) in labels::place_labels(&mut labels, camera.viewport(), measure) {
+            shapes.push(Shape::Text {
+                pos: [min[0] * scale, (min[1] + ASCENT * FONT_SIZE) * scale + top],
+                size: FONT_SIZE * scale,
+                color: label.color,
+                halo: label.halo.map(|halo| (HALO_WIDTH * scale, halo)),
+                text: label.text.clone(),
+            });
+        }
+        self.title_shapes(&mut shapes);
+        self.legend_shapes(&mut shapes);
+        shapes
+    }
+
+    /// The title and caption, in their bands above and below the drawing.
+    fn title_shapes(&self, shapes: &mut Vec<Shape>) {
+        let scale = self.scale;
+        let (top, _) = self.bands();
+        if let Some(title) = &self.title {
+            shapes.push(Shape::Text {
+                pos: [PADDING * scale, (PADDING + ASCENT * TITLE_SIZE) * scale],
+                size: TITLE_SIZE * scale,
+                color: self.text_color,
+                halo: None,
+                text: title.clone(),
+            });
+        }
+        if let Some(caption) = &self.caption {
+            let bottom = top + self.camera.viewport()[1] * scale;
+            shapes.push(Shape::Text {
+                pos: [
+                    PADDING * scale,
+                    bottom + (PADDING + ASCENT * FONT_SIZE) * scale,
+                ],
+                size: FONT_SIZE * scale,
+                color: self.text_color,
+                halo: None,
+                text: caption.clone(),
+            });
+        }
+    }
+
+    /// The legend, as a box of swatches over the top right corner of the
+    /// drawing, on the page's background.
+    fn legend_shapes(&self, shapes: &mut Vec<Shape>) {
+        if self.legend.is_empty() {
+            return;
+        }
+        let scale = self.scale;
+        let line = FONT_SIZE * LINE_HEIGHT;
+        let swatch = 2.0 * SWATCH_RADIUS + PADDING;
+        let text_width = |text: &str| text.chars().count() as f32 * CHAR_WIDTH * FONT_SIZE;
+        let mut width: f32 = 0.0;
+        let mut lines = 0;
+        for group in &self.legend {
+            width = width.max(text_width(&group.title));
+            for entry in &group.entries {
+                width = width.max(swatch + text_width(&entry.text));
+            }
+            lines += 1 + group.entries.len();
+        }
+        let size = [width + 2.0 * PADDING, lines as f32 * line + 2.0 * PADDING];
+
+        // in points from the box's top left corner, then on the page
+        let (top, _) = self.bands();
+        let corner = [
+            self.camera.viewport()[0] - size[0] - PADDING,
+            top / scale + PADDING,
+        ];
+        let at = |[x, y]: [f32; 2]| [(corner[0] + x) * scale, (corner[1] + y) * scale];
+        let [r, g, b, _] = self.background.unwrap_or([1.0; 4]);
+        shapes.push(Shape::Fill {
+            polygons: vec![vec![
+                at([0.0, 0.0]),
+                at([size[0], 0.0]),
+                at(size),
+                at([0.0, size[1]]),
+            ]],
+            color: [r, g, b, LEGEND_OPACITY],
+        });
+        let mut y = PADDING;
+        let text = |shapes: &mut Vec<Shape>, x: f32, y: f32, text: &str| {
+            shapes.push(Shape::Text {
+                pos: at([x, y + (line - FONT_SIZE) * 0.5 + ASCENT * FONT_SIZE]),
+                size: FONT_SIZE * scale,
+                color: self.text_color,
+                halo: None,
+                text: text.to_owned(),
+            })
+        };
+        for group in &self.legend {
+            text(shapes, PADDING, y, &group.title);
+            y += line;
+            for entry in &group.entries {
+                let center = at([PADDING + SWATCH_RADIUS, y + line * 0.5]);
+                shapes.push(match entry.shape {
+                    Some(shape) => Shape::Node {
+                        outline: outline(
+                            center,
+                            SWATCH_RADIUS * scale,
+                            &NodeStyle {
+                                shape,
+                                ..NodeStyle::default()
+                            },
+                        ),
+                        color: entry.color,
+                        stroke: None,
+                    },
+                    None => Shape::Line {
+                        points: vec![
+                            at([PADDING, y + line * 0.5]),
+                            at([PADDING + 2.0 * SWATCH_RADIUS, y + line * 0.5]),
+                        ],
+                        width: 2.0 * scale,
+                        color: entry.color,
+                    },
+                });
+                text(shapes, PADDING + swatch, y, &entry.text);
+                y += line;
+            }
+        }
+    }
+}
+
+impl Canvas for VectorScene {
+    fn camera(&self) -> &Camera {
+        &self.camera
+    }
+
+    fn world(&mut self) -> &mut DrawList {
+        &mut self.world
+    }
+
+    fn graph(&mut self) -> &mut GraphBatch {
+        &mut self.graph
+    }
+
+    fn labels(&mut self) -> &mut LabelBatch {
+        &mut self.labels
+    }
+}
+
+/// The outline of a node at `center` with `radius`, both on the page, matching
+/// the shapes drawn by the scene's shaders.
+fn outline(center: [f32; 2], radius: f32, style: &NodeStyle) -> Outline {
+    let [x, y] = center;
+    let polygon = |corners: &[[f32; 2]]| {
+        Outline::Polygon(
+            corners
+                .iter()
+                .map(|[dx, dy]| [x + dx * radius, y + dy * radius])
+                .collect(),
+        )
+    };
+    let k = 3f32.sqrt();
+    match style.shape {
+        NodeShape::Circle => Outline::Circle { center, radius },
+        NodeShape::Square => polygon(&[[-0.85, -0.85], [0.85, -0.85], [0.85, 0.85], [-0.85, 0.85]]),
+        NodeShape::Diamond => polygon(&[[0.0, -1.2], [1.2, 0.0], [0.0, 1.2], [-1.2, 0.0]]),
+        NodeShape::Triangle => polygon(&[[0.0, -2.0 / k], [1.0, 1.0 / k], [-1.0, 1.0 / k]]),
+    }
+}
+
+/// Write `shapes` as an SVG document of `size`, `dpi` page units per inch if
+/// given.
+fn write_svg(
+    [width, height]: [f32; 2],
+    dpi: Option<f32>,
+    shapes: &[Shape],
+    out: &mut impl Write,
+) -> std::io::Result<()> {
+    let (printed_width, printed_height) = match dpi {
+        Some(dpi) => (
+            format!("{:.3}in", width / dpi),
+            format!("{:.3}in", height / dpi),
+        ),
+        None => (format!("{width:.2}"), format!("{height:.2}")),
+    };
+    writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{printed_width}" height="{printed_height}" viewBox="0 0 {width:.2} {height:.2}">"#
+    )?;
+    for shape in shapes {
+        match shape {
+            Shape::Fill { polygons, color } => {
+                write!(out, r#"<path d=""#)?;
+                for polygon in polygons {
+                    for (i, [x, y]) in polygon.iter().enumerate() {
+                        let op = if i == 0 { 'M' } else { 'L' };
+                        write!(out, "{op}{x:.2} {y:.2}")?;
+                    }
+                    write!(out, "Z")?;
+                }
+                writeln!(out, r#""{}/>"#, svg_paint("fill", *color))?;
+            }
+            Shape::Line {
+                points,
+                width,
+                color,
+            } => writeln!(
+                out,
+                r#"<polyline points="{}" fill="none" stroke-width="{width:.2}" stroke-linecap="round" stroke-linejoin="round"{}/>"#,
+                svg_points(points),
+                svg_paint("stroke", *color)
+            )?,
+            Shape::Node {
+                outline,
+                color,
+                stroke,
+            } => {
+                // the stroke is drawn under the fill, so only its outer half shows
+                let stroke = stroke.map_or(String::new(), |(width, color)| {
+                    format!(
+                        r#" stroke-width="{:.2}" paint-order="stroke"{}"#,
+                        width * 2.0,
+                        svg_paint("stroke", color)
+                    )
+                });
+                let fill = svg_paint("fill", *color);
+                match outline {
+                    Outline::Circle { center, radius } => writeln!(
+                        out,
+                        r#"<circle cx="{:.2}" cy="{:.2}" r="{radius:.2}"{fill}{stroke}/>"#,
+                        center[0], center[1]
+                    )?,
+                    Outline::Polygon(points) => writeln!(
+                        out,
+                        r#"<polygon points="{}"{fill}{stroke}/>"#,
+                        svg_points(points)
+                    )?,
+                }
+            }
+            Shape::Text {
+                pos,
+                size,
+                color,
+                halo,
+                text,
+            } => {
+                let halo = halo.map_or(String::new(), |(width, color)| {
+                    format!(
+                        r#" stroke-width="{width:.2}" stroke-linejoin="round" paint-order="stroke"{}"#,
+                        svg_paint("stroke", color)
+                    )
+                });
+                writeln!(
+                    out,
+                    r#"<text x="{:.2}" y="{:.2}" font-family="sans-serif" font-size="{size:.2}"{}{halo}>{}</text>"#,
+                    pos[0],
+                    pos[1],
+                    svg_paint("fill", *color),
+                    escape_xml(text)
+                )?;
+            }
+        }
+    }
+    writeln!(out, "</svg>")
+}
+
+/// Attributes painting `property`, `fill` or `stroke`, with `color`.
+fn svg_paint(property: &str, [r, g, b, a]: [f32; 4]) -> String {
+    let [r, g, b] = [r, g, b].map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+    let paint = format!(r##" {property}="#{r:02x}{g:02x}{b:02x}""##);
+    match a < 1.0 {
+        true => format!(r#"{paint} {property}-opacity="{:.3}""#, a.max(0.0)),
+        false => paint,
+    }
+}
+
+fn svg_points(points: &[[f32; 2]]) -> String {
+    let points: Vec<String> = points
+        .iter()
+        .map(|[x, y]| format!("{x:.2},{y:.2}"))
+        .collect();
+    points.join(" ")
+}
+
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Write `shapes` as a single page PDF document of `size`, in points unless
+/// `dpi` page units make an inch.
+///
+/// Text is set in the standard Helvetica font, so characters outside of Latin-1
+/// are replaced by `?`.
+fn write_pdf(
+    [width, height]: [f32; 2],
+    dpi: Option<f32>,
+    shapes: &[Shape],
+    out: &mut impl Write,
+) -> std::io::Result<()> {
+    // PDF pages have their origin bottom-left
+    let flip = |[x, y]: [f32; 2]| [x, height - y];
+    let mut content = Vec::new();
+    // graphics states by opacity, in 1/255 steps
+    let mut alphas: BTreeMap<u8, usize> = BTreeMap::new();
+    let mut color = |content: &mut Vec<u8>, operator: &str, [r, g, b, a]: [f32; 4]| {
+        let alpha = (a.clamp(0.0, 1.0) * 255.0).round() as u8;
+        let count = alphas.len();
+        let state = *alphas.entry(alpha).or_insert(count);
+        writeln!(content, "/A{state} gs {r:.3} {g:.3} {b:.3} {operator}")
+    };
+    let path = |content: &mut Vec<u8>, points: &[[f32; 2]], close: bool| {
+        for (i, &point) in points.iter().enumerate() {
+            let [x, y] = flip(point);
+            let op = if i == 0 { 'm' } else { 'l' };
+            writeln!(content, "{x:.2} {y:.2} {op}")?;
+        }
+        match close {
+            true => writeln!(content, "h"),
+            false => Ok(()),
+        }
+    };
+
+    let points = dpi.map_or(1.0, |dpi| PDF_DPI / dpi);
+    writeln!(content, "{points:.5} 0 0 {points:.5} 0 0 cm 1 J 1 j")?;
+    for shape in shapes {
+        match shape {
+            Shape::Fill {
+                polygons,
+                color: fill,
+            } => {
+                color(&mut content, "rg", *fill)?;
+                for polygon in polygons {
+                    path(&mut content, polygon, true)?;
+                }
+                writeln!(content, "f")?;
+            }
+            Shape::Line {
+                points,
+                width,
+                color: stroke,
+            } => {
+                color(&mut content, "RG", *stroke)?;
+                writeln!(content, "{width:.2} w")?;
+                path(&mut content, points, false)?;
+                writeln!(content, "S")?;
+            }
+            Shape::Node {
+                outline,
+                color: fill,
+                stroke,
+            } => {
+                let outline_path = |content: &mut Vec<u8>| match outline {
+                    Outline::Circle { center, radius } => circle(content, flip(*center), *radius),
+                    Outline::Polygon(points) => path(content, points, true),
+                };
+                // the stroke is drawn under the fill, so only its outer half shows
+                if let Some((width, stroke)) = stroke {
+                    color(&mut content, "RG", *stroke)?;
+                    writeln!(content, "{:.2} w 0 j", width * 2.0)?;
+                    outline_path(&mut content)?;
+                    writeln!(content, "S 1 j")?;
+                }
+                color(&mut content, "rg", *fill)?;
+                outline_path(&mut content)?;
+                writeln!(content, "f")?;
+            }
+            Shape::Text {
+                pos,
+                size,
+                color: fill,
+                halo,
+                text,
+            } => {
+                let [x, y] = flip(*pos);
+                let mut string = Vec::new();
+                for c in text.chars() {
+                    match c {
+                        '(' | ')' | '\\' => string.extend_from_slice(&[b'\\', c as u8]),
+                        // WinAnsi matches Latin-1 on printable characters
+                        ' '..='~' | '\u{a0}'..='\u{ff}' => string.push(c as u32 as u8),
+                        _ => string.push(b'?'),
+                    }
+                }
+                let show = |content: &mut Vec<u8>, mode: u8| {
+                    write!(content, "BT /F1 {size:.2} Tf {mode} Tr {x:.2} {y:.2} Td (")?;
+                    content.extend_from_slice(&string);
+                    writeln!(content, ") Tj ET")
+                };
+                if let Some((width, stroke)) = halo {
+                    color(&mut content, "RG", *stroke)?;
+                    writeln!(content, "{width:.2} w")?;
+                    show(&mut content, 1)?;
+                }
+                color(&mut content, "rg", *fill)?;
+                show(&mut content, 0)?;
+            }
+        }
+    }
+
+    let states: String = alphas
+        .iter()
+        .map(|(alpha, state)| {
+            let alpha = *alpha as f32 / 255.0;
+            format!("/A{state} << /ca {alpha:.3} /CA {alpha:.3} >> ")
+        })
+        .collect();
+    let mut stream = format!("<< /Length {} >>\nstream\n", content.len()).into_bytes();
+    stream.extend_from_slice(&content);
+    stream.extend_from_slice(b"endstream");
+    let objects: [Vec<u8>; 5] = [
+        b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+        b"<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_vec(),
+        format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {:.2} {:.2}] \
+             /Resources << /Font << /F1 4 0 R >> /ExtGState << {states}>> >> /Contents 5 0 R >>",
+            width * points,
+            height * points,
+        )
+        .into_bytes(),
+        b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>"
+            .to_vec(),
+        stream,
+    ];
+
+    // the cross-reference table needs the offset of every object
+    let mut document = b"%PDF-1.4\n".to_vec();
+    let mut offsets = Vec::new();
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(document.len());
+        writeln!(document, "{} 0 obj", i + 1)?;
+        document.extend_from_slice(object);
+        writeln!(document, "\nendobj")?;
+    }
+    let xref = document.len();
+    writeln!(
+        document,
+        "xref\n0 {}\n0000000000 65535 f ",
+        objects.len() + 1
+    )?;
+    for offset in offsets {
+        writeln!(document, "{offset:010} 00000 n ")?;
+    }
+    writeln!(
+        document,
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref}\n%%EOF",
+        objects.len() + 1
+    )?;
+    out.write_all(&document)
+}
+
+/// A closed circle path made of four cubic curves, in PDF coordinates.
+fn circle(content: &mut Vec<u8>, [x, y]: [f32; 2], r: f32) -> std::io::Result<()> {
+    let k = r * KAPPA;
+    writeln!(content, "{:.2} {y:.2} m", x + r)?;
+    let quarters = [
+        [[x + r, y + k], [x + k, y + r], [x, y + r]],
+        [[x - k, y + r], [x - r, y + k], [x - r, y]],
+        [[x - r, y - k], [x - k, y - r], [x, y - r]],
+        [[x + k, y - r], [x + r, y - k], [x + r, y]],
+    ];
+    for [a, b, c] in quarters {
+        writeln!(
+            content,
+            "{:.2} {:.2} {:.2} {:.2} {:.2} {:.2} c",
+            a[0], a[1], b[0], b[1], c[0], c[1]
+        )?;
+    }
+    writeln!(content, "h")
+}
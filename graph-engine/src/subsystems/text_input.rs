@@ -0,0 +1,116 @@
+use std::cell::Cell;
+
+use crate::imgui::sys::{ImGuiPlatformImeData, ImGuiViewport};
+use crate::sdl3::sys::keyboard::SDL_SetTextInputArea;
+use crate::sdl3::sys::rect::SDL_Rect;
+use crate::sdl3::video::Window;
+
+use common::renderer::SDL;
+
+thread_local! {
+    /// IME placement last reported by imgui, not yet applied to a window.
+    static PENDING_IME: Cell<Option<ImeArea>> = const { Cell::new(None) };
+}
+
+/// Where the IME candidate window should appear, in window coordinates.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ImeArea {
+    /// Top-left corner of the text cursor.
+    pub pos: [f32; 2],
+    /// Height of the line being edited, so candidates open just below it.
+    pub line_height: f32,
+}
+
+/// Tracks SDL text input for a window, starting it while imgui wants text
+/// (eg. a search field is focused) or while the application requested it.
+///
+/// SDL only delivers text and IME composition events while text input is
+/// active, so without this imgui text fields receive no CJK input at all.
+#[derive(Debug, Default)]
+pub(crate) struct TextInputState {
+    /// Whether the application asked for text input with [`GraphWindow::start_text_input()`](super::window::GraphWindow::start_text_input).
+    requested: Cell<bool>,
+    /// Whether imgui wanted text input at the last [`Self::sync()`].
+    ui_wants_text: Cell<bool>,
+    /// Whether text input is currently started on the window.
+    active: Cell<bool>,
+    /// IME area last passed to SDL.
+    area: Cell<Option<ImeArea>>,
+}
+
+impl TextInputState {
+    pub(crate) fn request(&self, window: &Window, enabled: bool) {
+        self.requested.set(enabled);
+        self.set_active(window, enabled || self.ui_wants_text.get());
+    }
+
+    pub(crate) fn requested(&self) -> bool {
+        self.requested.get()
+    }
+
+    pub(crate) fn is_active(&self) -> bool {
+        self.active.get()
+    }
+
+    /// Start or stop text input to match whether imgui wants it, then move the
+    /// IME candidate window to the latest position reported by imgui.
+    pub(crate) fn sync(&self, window: &Window, ui_wants_text: bool) {
+        self.ui_wants_text.set(ui_wants_text);
+        self.set_active(window, ui_wants_text || self.requested.get());
+
+        if let Some(area) = PENDING_IME.take()
+            && self.active.get()
+            && self.area.get() != Some(area)
+        {
+            set_area(window, area);
+            self.area.set(Some(area));
+        }
+    }
+
+    fn set_active(&self, window: &Window, active: bool) {
+        if self.active.get() == active {
+            return;
+        }
+        let text_input = SDL.video().borrow().text_input();
+        if active {
+            text_input.start(window);
+        } else {
+            text_input.stop(window);
+            self.area.set(None);
+        }
+        self.active.set(active);
+    }
+}
+
+/// Pass the IME area to SDL, spanning from the text cursor to the window's right edge.
+fn set_area(window: &Window, area: ImeArea) {
+    let (width, _) = window.size();
+    let [x, y] = area.pos.map(|v| v.round() as i32);
+    let rect = SDL_Rect {
+        x,
+        y,
+        w: (width as i32 - x).max(1),
+        h: area.line_height.ceil().max(1.0) as i32,
+    };
+    // SAFETY: the window outlives the call and SDL copies the rect.
+    unsafe {
+        SDL_SetTextInputArea(window.raw(), &rect, 0);
+    }
+}
+
+/// imgui's `SetPlatformImeDataFn`, called during rendering whenever the
+/// focused text field's cursor moves.
+pub(crate) unsafe extern "C" fn set_platform_ime_data(
+    _viewport: *mut ImGuiViewport,
+    data: *mut ImGuiPlatformImeData,
+) {
+    // SAFETY: imgui passes a valid pointer for the duration of the call.
+    let Some(data) = (unsafe { data.as_ref() }) else {
+        return;
+    };
+    let area = data.WantVisible.then_some(ImeArea {
+        pos: [data.InputPos.x, data.InputPos.y],
+        line_height: data.InputLineHeight,
+    });
+    PENDING_IME.set(area);
+}
@@ -1,44 +1,81 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use crate::imgui::sdl3_support::SdlPlatform;
 use crate::imgui::{
-    ClipboardBackend, Context as ImguiContext, DummyClipboardContext, SharedFontAtlas as FontAtlas,
+    ClipboardBackend, ConfigFlags, Context as ImguiContext, DummyClipboardContext, FontConfig,
+    FontGlyphRanges, FontSource, SharedFontAtlas as FontAtlas,
 };
 
-use crate::imgui::renderers::glow::AutoRenderer;
-use crate::imgui::renderers::glow::inner::{Context, HasContext};
-
 use crate::sdl3::event::Event;
 use crate::sdl3::video::Window;
 
+use super::clipboard::SdlClipboard;
+use super::input::InputCapture;
+use super::scene::{Scene, SceneOverlay};
+use super::text_input::set_platform_ime_data;
+use super::ui_renderer::{GlowUiRenderer, UiRenderError, UiRenderer};
+use super::ui_state::UiStateManager;
 use super::window::GraphWindow;
 
 use common::renderer::SDL;
+use common::renderer::texture::image::RgbaImage;
+use common::renderer::texture::{Texture2d, TextureError, TextureFilter};
+
+/// Whether the [`SdlPlatform`] layer can create os windows for imgui viewports.
+const PLATFORM_SUPPORTS_VIEWPORTS: bool = false;
+
+/// Default color the framebuffer is cleared to at the start of each frame.
+const DEFAULT_CLEAR_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
+
+/// Size of imgui's default font, in pixels, which fonts merged into it by
+/// [`GraphUi::set_font()`] match.
+const DEFAULT_FONT_SIZE: f32 = 13.0;
 
 /// Central UI container tying together [ImGui Context](ImguiContext),
 /// [SDL platform integration for ImGui](SdlPlatform), and
-/// [the renderer backend](AutoRenderer).
+/// [the renderer backend](UiRenderer).
 pub struct GraphUi {
     imgui: ImguiContext,
     platform: SdlPlatform,
-    renderer: AutoRenderer,
+    renderer: Box<dyn UiRenderer>,
+    state: Option<UiStateManager>,
+    clear_color: [f32; 4],
+    /// Content scale of the window's display.
+    display_scale: f32,
+    /// Scale chosen by the user on top of the display's, see [`Self::set_ui_scale()`].
+    ui_scale: f32,
+    /// Whether a dockspace is submitted over the main viewport each frame.
+    dockspace: bool,
 }
 
+/// Constructs the [`UiRenderer`] of a [`GraphUi`] once its imgui context exists.
+type BackendFactory<'a> =
+    Box<dyn FnOnce(&Window, &mut ImguiContext) -> Result<Box<dyn UiRenderer>, UiRenderError> + 'a>;
+
 /// Builder for [`GraphUi`], parameterized by clipboard backend.
 ///
 /// Wraps [`common::util::ImguiBuilder`] and a mutable reference to `GraphWindow`.
 /// <br />
 /// Use [`GraphWindow::ui_frame_begin()`] to start.
-pub struct GraphUiBuilder<'a, C: ClipboardBackend = DummyClipboardContext>(
-    common::util::ImguiBuilder<C>,
-    &'a mut super::window::GraphWindow,
-);
+pub struct GraphUiBuilder<'a, C: ClipboardBackend = DummyClipboardContext> {
+    imgui: common::util::ImguiBuilder<C>,
+    window: &'a mut GraphWindow,
+    state: Option<UiStateManager>,
+    backend: Option<BackendFactory<'a>>,
+    /// Whether a clipboard backend was set, replacing the [`SdlClipboard`].
+    clipboard: bool,
+    docking: bool,
+    dockspace: bool,
+    viewports: bool,
+}
 
 /// RAII guard for the duration of an ImGui frame.
 ///  
 /// On [`UiFrameGuard::end()`] the UI draw commands are submitted.
 pub struct UiFrameGuard<'a> {
     pub(crate) gui: &'a mut GraphUi,
+    /// Scene text and labels to draw once the imgui frame starts.
+    overlay: SceneOverlay,
 }
 
 /// Temporary borrow of the [`imgui::Ui`](crate::imgui::Ui) for issuing widgets.
@@ -49,28 +86,19 @@ pub struct UiDropGuard<'a> {
     pub(crate) ui: &'a mut crate::imgui::Ui,
 }
 
-/// Create a GL function loader for the given window's GL context.
-/// 
-/// See also:
-/// https://github.com/imgui-rs/imgui-sdl2-support/blob/main/examples/sdl2_01_basic.rs#L13
-///  
-/// # Safety
-/// Must be called after the window's GL context has been created and made current.
-fn glow_context(window: &Window) -> Context {
-    unsafe {
-        Context::from_loader_function(|s| {
-            window
-                .subsystem()
-                .gl_get_proc_address(s)
-                .unwrap_or_else(|| panic!("Expected function '{s}' but did not")) as _
-        })
-    }
-}
-
 impl GraphUi {
     /// Begin building a [`GraphUi`] for the [`window`](GraphWindow).
-    pub(crate) fn builder(window: &mut super::window::GraphWindow) -> GraphUiBuilder {
-        GraphUiBuilder(common::util::ImguiBuilder::new(), window)
+    pub(crate) fn builder(window: &mut GraphWindow) -> GraphUiBuilder<'_> {
+        GraphUiBuilder {
+            imgui: common::util::ImguiBuilder::new(),
+            window,
+            state: None,
+            backend: None,
+            clipboard: false,
+            docking: true,
+            dockspace: true,
+            viewports: false,
+        }
     }
 
     /// Forward an [Event] to ImGui's platform layer.
@@ -79,20 +107,179 @@ impl GraphUi {
     }
 
     /// Prepare a new UI frame:
+    /// - Records imgui's layout in the [`UiStateManager`], if imgui asked for it to be saved.
     /// - Updates the ImGui ui state (see also: [`SdlPlatform::prepare_frame()`])
-    /// - Clears the GL color buffer for rendering.
-    pub(crate) fn prepare(&mut self, window: &GraphWindow) {
+    /// - Clears the framebuffer to the [clear color](Self::set_clear_color).
+    pub(crate) fn prepare(&mut self, window: &Window) {
+        if let Some(state) = self.state.as_mut()
+            && self.imgui.io().want_save_ini_settings
+        {
+            state.capture_imgui(&mut self.imgui);
+        }
+
         self.platform.prepare_frame(
             &mut SDL.core().borrow_mut(),
             &mut self.imgui,
             window,
             &SDL.event_pump().read(),
         );
-        unsafe {
-            self.renderer
-                .gl_context()
-                .clear(crate::imgui::renderers::glow::inner::COLOR_BUFFER_BIT)
+        self.renderer.clear(self.clear_color);
+    }
+
+    /// Which input devices imgui currently wants for itself, eg. while a text
+    /// field is focused or the cursor hovers a panel.
+    pub fn input_capture(&self) -> InputCapture {
+        let io = self.imgui.io();
+        InputCapture {
+            keyboard: io.want_capture_keyboard,
+            mouse: io.want_capture_mouse,
+            text: io.want_text_input,
+        }
+    }
+
+    /// Sets whether imgui is kept from changing the cursor, so the window's own
+    /// cursor (see [`GraphWindow::set_cursor()`]) can be shown.
+    pub(crate) fn set_cursor_override(&mut self, enabled: bool) {
+        self.imgui
+            .io_mut()
+            .config_flags
+            .set(ConfigFlags::NO_MOUSE_CURSOR_CHANGE, enabled);
+    }
+
+    /// The renderer backend drawing this ui.
+    pub fn renderer(&mut self) -> &mut dyn UiRenderer {
+        self.renderer.as_mut()
+    }
+
+    /// Sets the color the framebuffer is cleared to at the start of each frame.
+    pub fn set_clear_color(&mut self, color: [f32; 4]) {
+        self.clear_color = color;
+    }
+
+    /// Scale imgui's fonts and style sizes for a display with content scale `scale`.
+    ///
+    /// Called by the window when it moves to a display with a different scale.
+    pub(crate) fn set_display_scale(&mut self, scale: f32) {
+        if scale <= 0.0 || scale == self.display_scale {
+            return;
+        }
+        self.rescale(scale, self.ui_scale);
+    }
+
+    /// Scale imgui's fonts and style sizes by `scale` on top of the display's
+    /// content scale, eg. `1.25` for a larger ui.
+    pub fn set_ui_scale(&mut self, scale: f32) {
+        if scale <= 0.0 || scale == self.ui_scale {
+            return;
+        }
+        self.rescale(self.display_scale, scale);
+    }
+
+    /// The scale chosen with [`Self::set_ui_scale()`].
+    pub fn ui_scale(&self) -> f32 {
+        self.ui_scale
+    }
+
+    /// Scale the style and fonts for `display_scale` and `ui_scale`, from the
+    /// scales they were last scaled for.
+    fn rescale(&mut self, display_scale: f32, ui_scale: f32) {
+        let scale = display_scale * ui_scale;
+        self.imgui
+            .style_mut()
+            .scale_all_sizes(scale / (self.display_scale * self.ui_scale));
+        self.imgui.io_mut().font_global_scale = scale;
+        self.display_scale = display_scale;
+        self.ui_scale = ui_scale;
+    }
+
+    /// Replace imgui's fonts by its default one, with the glyphs of `ranges`
+    /// from the TTF or OTF `data` merged in if given, eg. for Cyrillic or CJK
+    /// text. Must be called between frames.
+    pub fn set_font(&mut self, font: Option<(&[u8], FontGlyphRanges)>) -> Result<(), TextureError> {
+        let fonts = self.imgui.fonts();
+        fonts.clear();
+        let mut sources = vec![FontSource::DefaultFontData { config: None }];
+        if let Some((data, glyph_ranges)) = font {
+            sources.push(FontSource::TtfData {
+                data,
+                size_pixels: DEFAULT_FONT_SIZE,
+                config: Some(FontConfig {
+                    glyph_ranges,
+                    ..FontConfig::default()
+                }),
+            });
+        }
+        fonts.add_font(&sources);
+        self.renderer.reload_fonts(&mut self.imgui)
+    }
+
+    /// Returns `true` if panels can be docked into each other.
+    pub fn docking_enabled(&self) -> bool {
+        self.imgui
+            .io()
+            .config_flags
+            .contains(ConfigFlags::DOCKING_ENABLE)
+    }
+
+    /// Returns `true` if panels can be dragged outside the main window.
+    pub fn viewports_enabled(&self) -> bool {
+        self.imgui
+            .io()
+            .config_flags
+            .contains(ConfigFlags::VIEWPORTS_ENABLE)
+    }
+
+    /// Sets whether a dockspace is submitted over the main viewport each frame.
+    ///
+    /// Has no effect if docking is disabled.
+    pub fn set_dockspace(&mut self, enabled: bool) {
+        self.dockspace = enabled && self.docking_enabled();
+    }
+
+    /// The [`UiStateManager`] persisting this ui's layout, if one was attached.
+    pub fn state(&self) -> Option<&UiStateManager> {
+        self.state.as_ref()
+    }
+
+    /// Mutable access to the [`UiStateManager`], if one was attached.
+    pub fn state_mut(&mut self) -> Option<&mut UiStateManager> {
+        self.state.as_mut()
+    }
+
+    /// Record the current imgui layout and geometry of `window`, then write the
+    /// state to disk.
+    ///
+    /// Does nothing if no [`UiStateManager`] was attached.
+    pub(crate) fn save_state(
+        &mut self,
+        window: &Window,
+    ) -> Result<(), super::ui_state::UiStateError> {
+        let Some(state) = self.state.as_mut() else {
+            return Ok(());
         };
+        state.capture_imgui(&mut self.imgui);
+        state.capture_window(window);
+        state.save()
+    }
+
+    /// imgui's current layout (window positions, sizes, dock layout) in ini
+    /// format, eg. to save it in a [`Session`](crate::session::Session).
+    pub fn save_layout(&mut self) -> String {
+        let mut ini = String::new();
+        self.imgui.save_ini_settings(&mut ini);
+        ini
+    }
+
+    /// Replace imgui's layout by one from [`Self::save_layout()`]. Must be
+    /// called between frames.
+    pub fn load_layout(&mut self, ini: &str) {
+        self.imgui.load_ini_settings(ini);
+    }
+
+    /// Forget imgui's layout, so windows open at their default place and size,
+    /// eg. in safe mode. Must be called between frames.
+    pub fn reset_layout(&mut self) {
+        unsafe { crate::imgui::sys::igClearIniSettings() };
     }
 
     /// Access the underlying ImGui context for custom integrations.
@@ -100,49 +287,177 @@ impl GraphUi {
         &mut self.imgui
     }
 
-    /// Render the current frame's ImGui draw data via the [`Self::renderer`](AutoRenderer).
+    /// Read and decode a PNG or JPEG file, uploading it as a [`Texture2d`] usable
+    /// in both imgui panels and scene rendering.
+    pub fn load_texture(&mut self, path: impl AsRef<Path>) -> Result<Texture2d, TextureError> {
+        let image = common::renderer::texture::load_image(path)?;
+        self.create_texture(&image, TextureFilter::default())
+    }
+
+    /// Upload a decoded image as a [`Texture2d`] usable in both imgui panels and
+    /// scene rendering.
+    pub fn create_texture(
+        &mut self,
+        image: &RgbaImage,
+        filter: TextureFilter,
+    ) -> Result<Texture2d, TextureError> {
+        self.renderer
+            .create_texture(image.width(), image.height(), image.as_raw(), filter)
+    }
+
+    /// Render the current frame's ImGui draw data via the [`Self::renderer`](UiRenderer).
+    ///
+    /// With multi-viewport enabled, this also updates and renders the os windows of
+    /// panels dragged outside the main window.
     pub(crate) fn frame_render(&mut self) {
         let draw_data = self.imgui.render();
         self.renderer.render(draw_data).unwrap();
+
+        if self.viewports_enabled() {
+            self.imgui.update_platform_windows();
+            self.imgui.render_platform_windows_default();
+        }
     }
 }
 
-impl<C: ClipboardBackend> GraphUiBuilder<'_, C> {
+impl<'a, C: ClipboardBackend> GraphUiBuilder<'a, C> {
     /// Finalize building and attach the [`GraphUi`] to the window.
-    pub fn build(self) {
-        let mut imgui = self.0.build();
+    ///
+    /// Uses the [OpenGL renderer](GlowUiRenderer) unless another backend was set
+    /// with [`Self::backend()`].
+    ///
+    /// Panics if the renderer backend fails to initialize.
+    pub fn build(mut self) {
+        let mut imgui = self.imgui.build();
+        if !self.clipboard {
+            imgui.set_clipboard_backend(SdlClipboard);
+        }
+
+        if let Some(state) = self.state.as_mut() {
+            // The state manager owns persistence, so imgui must not write its own ini file.
+            imgui.set_ini_filename(None);
+            if let Err(e) = state.load() {
+                eprintln!("Failed to load ui state, using defaults: {e}");
+            }
+            state.apply_imgui(&mut imgui);
+            state.apply_window(self.window);
+        }
+
+        if self.docking {
+            imgui.io_mut().config_flags |= ConfigFlags::DOCKING_ENABLE;
+        }
 
         let platform = SdlPlatform::new(&mut imgui);
+        // Installed after the platform layer so the window can position the IME.
+        imgui.io_mut().set_platform_ime_data_fn = Some(set_platform_ime_data);
+
+        let backend = self.backend.take().unwrap_or_else(|| {
+            Box::new(|window, imgui| {
+                Ok(Box::new(GlowUiRenderer::new(window, imgui)?) as Box<dyn UiRenderer>)
+            })
+        });
+        let renderer = backend(self.window, &mut imgui).unwrap();
+        if imgui.renderer_name().is_none() {
+            imgui.set_renderer_name(Some(renderer.name().to_owned()));
+        }
 
-        let gl = glow_context(self.1);
-        let renderer = AutoRenderer::new(gl, &mut imgui).unwrap();
+        if self.viewports {
+            // The SDL3 platform layer cannot create os windows for imgui yet, so
+            // viewports also depend on it, not just on the renderer.
+            if renderer.supports_viewports() && PLATFORM_SUPPORTS_VIEWPORTS {
+                imgui.io_mut().config_flags |= ConfigFlags::VIEWPORTS_ENABLE;
+            } else {
+                eprintln!(
+                    "Multi-viewport was requested but the '{}' backend does not support it",
+                    renderer.name()
+                );
+            }
+        }
 
         let result = GraphUi {
             imgui,
             platform,
             renderer,
+            state: self.state,
+            clear_color: DEFAULT_CLEAR_COLOR,
+            display_scale: 1.0,
+            ui_scale: 1.0,
+            dockspace: self.docking && self.dockspace,
         };
 
-        self.1.set_ui(result);
+        self.window.set_ui(result);
+    }
+
+    /// Sets the [`UiRenderer`] backend, constructed by `factory` once the imgui
+    /// context exists.
+    ///
+    /// Defaults to [`GlowUiRenderer`].
+    pub fn backend(
+        mut self,
+        factory: impl FnOnce(&Window, &mut ImguiContext) -> Result<Box<dyn UiRenderer>, UiRenderError>
+        + 'a,
+    ) -> Self {
+        self.backend = Some(Box::new(factory));
+        self
+    }
+
+    /// Sets whether panels can be docked into each other. Defaults to `true`.
+    ///
+    /// Dock layouts are saved with the rest of imgui's layout, see [`Self::state()`].
+    pub fn docking(mut self, enabled: bool) -> Self {
+        self.docking = enabled;
+        self
+    }
+
+    /// Sets whether a dockspace covering the main viewport is submitted at the start
+    /// of each frame, so panels can be docked to the window's edges. Defaults to `true`.
+    ///
+    /// The dockspace is transparent where no panel is docked, so scene rendering
+    /// stays visible underneath. Has no effect if docking is disabled.
+    pub fn dockspace(mut self, enabled: bool) -> Self {
+        self.dockspace = enabled;
+        self
+    }
+
+    /// Sets whether panels can be dragged outside the main window into their own
+    /// os windows. Defaults to `false`.
+    ///
+    /// Only takes effect if both the platform layer and [`UiRenderer`] support it
+    /// (see [`UiRenderer::supports_viewports()`]); otherwise a warning is printed
+    /// and panels stay inside the main window.
+    pub fn viewports(mut self, enabled: bool) -> Self {
+        self.viewports = enabled;
+        self
     }
 
     /// Add a shared font atlas to the imfui-rs context when building.
     pub fn font_atlas(mut self, atlas: FontAtlas) -> Self {
-        self.0 = self.0.font_atlas(atlas);
+        self.imgui = self.imgui.font_atlas(atlas);
         self
     }
 
-    /// Sets the clipboard backend used for clipboard operations.
+    /// Sets the clipboard backend used for clipboard operations, instead of the
+    /// system clipboard.
     pub fn clipboard_backend(mut self, backend: C) -> Self {
-        self.0 = self.0.clipboard_backend(backend);
+        self.imgui = self.imgui.clipboard_backend(backend);
+        self.clipboard = true;
+        self
+    }
+
+    /// Attach a [`UiStateManager`] to persist the imgui layout, window geometry,
+    /// and open panels together.
+    ///
+    /// Preferred over [`Self::ini()`], which is ignored when a manager is attached.
+    pub fn state(mut self, manager: UiStateManager) -> Self {
+        self.state = Some(manager);
         self
     }
 
     /// Sets the path to the imgui ini file.
     ///
-    /// imgui ini files are disabled by default.
+    /// imgui ini files are disabled by default. See also: [`Self::state()`].
     pub fn ini(mut self, path: impl Into<PathBuf>) -> Self {
-        self.0 = self.0.ini(path);
+        self.imgui = self.imgui.ini(path);
         self
     }
 
@@ -150,30 +465,32 @@ impl<C: ClipboardBackend> GraphUiBuilder<'_, C> {
     ///
     /// imgui log files are disabled by default.
     pub fn log(mut self, path: impl Into<PathBuf>) -> Self {
-        self.0 = self.0.log(path);
+        self.imgui = self.imgui.log(path);
         self
     }
 
     /// Sets the backend platform name.
     pub fn platform(mut self, path: impl Into<String>) -> Self {
-        self.0 = self.0.platform(path);
+        self.imgui = self.imgui.platform(path);
         self
     }
 
     /// Sets the backend renderer name.
+    ///
+    /// Defaults to the [`UiRenderer::name()`] of the selected backend.
     pub fn renderer(mut self, path: impl Into<String>) -> Self {
-        self.0 = self.0.renderer(path);
+        self.imgui = self.imgui.renderer(path);
         self
     }
 }
 
 impl<'a> UiFrameGuard<'a> {
-    pub(crate) fn new(gui: &'a mut GraphUi) -> Self {
-        Self { gui }
+    pub(crate) fn new(gui: &'a mut GraphUi, overlay: SceneOverlay) -> Self {
+        Self { gui, overlay }
     }
 
     /// Begin ImGui frame and return a UI guard for widget calls.
-    pub fn get(&mut self) -> UiDropGuard {
+    pub fn get(&mut self) -> UiDropGuard<'_> {
         UiDropGuard::new(self)
     }
 
@@ -185,10 +502,18 @@ impl<'a> UiFrameGuard<'a> {
 
 impl<'a> UiDropGuard<'a> {
     /// Create a UI drop guard from the frame guard.
+    ///
+    /// Draws the scene's text and labels, and submits the main viewport's dockspace first, if
+    /// enabled.
     pub(crate) fn new(guard: &'a mut UiFrameGuard) -> Self {
-        Self {
-            ui: guard.gui.context().new_frame(),
+        let dockspace = guard.gui.dockspace;
+        let mut overlay = std::mem::take(&mut guard.overlay);
+        let ui = guard.gui.context().new_frame();
+        Scene::draw_overlay(ui, &mut overlay);
+        if dockspace {
+            ui.dockspace_over_main_viewport();
         }
+        Self { ui }
     }
 }
 
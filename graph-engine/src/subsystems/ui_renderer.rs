@@ -0,0 +1,269 @@
+use std::rc::Rc;
+
+use crate::imgui::renderers::glow::AutoRenderer;
+use crate::imgui::renderers::glow::inner::{self as glow, Context, HasContext};
+use crate::imgui::{Context as ImguiContext, DrawData};
+
+use crate::sdl3::video::Window;
+
+use super::profiler::GpuTimer;
+
+use common::renderer::target::{self, RenderTarget};
+use common::renderer::texture::image::RgbaImage;
+use common::renderer::texture::{Texture2d, TextureError, TextureFilter};
+
+/// A rendering backend for [`GraphUi`](super::ui::GraphUi).
+///
+/// [`GraphUi`](super::ui::GraphUi) only ever talks to its renderer through this
+/// trait, so new backends (Vulkan, wgpu, software) can be added by implementing
+/// it, without changes to the ui itself.
+///
+/// Implementations:
+/// - [`GlowUiRenderer`]: OpenGL through [glow](https://github.com/grovesNL/glow).
+/// - [`NullUiRenderer`]: discards all draw data, for headless use and tests.
+pub trait UiRenderer {
+    /// Submit a finished frame's imgui draw data.
+    fn render(&mut self, draw_data: &DrawData) -> Result<(), UiRenderError>;
+
+    /// Clear the framebuffer to `color` (RGBA, `0.0..=1.0`) before drawing a frame.
+    fn clear(&mut self, color: [f32; 4]);
+
+    /// Notify the renderer that the drawable size changed to `width` x `height` pixels.
+    fn resize(&mut self, width: u32, height: u32);
+
+    /// Upload tightly packed RGBA8 `pixels` as a texture usable by imgui widgets.
+    ///
+    /// Backends without texture support return [`TextureError::Unsupported`].
+    fn create_texture(
+        &mut self,
+        width: u32,
+        height: u32,
+        pixels: &[u8],
+        filter: TextureFilter,
+    ) -> Result<Texture2d, TextureError> {
+        let _ = (width, height, pixels, filter);
+        Err(TextureError::Unsupported)
+    }
+
+    /// Create an offscreen [`RenderTarget`] whose color texture is usable by imgui widgets.
+    ///
+    /// Backends without render target support return [`TextureError::Unsupported`].
+    fn create_render_target(
+        &mut self,
+        width: u32,
+        height: u32,
+    ) -> Result<RenderTarget, TextureError> {
+        let _ = (width, height);
+        Err(TextureError::Unsupported)
+    }
+
+    /// Read back `width` x `height` pixels of the window's current frame.
+    ///
+    /// Backends which cannot read pixels back return [`TextureError::Unsupported`].
+    fn read_pixels(&mut self, width: u32, height: u32) -> Result<RgbaImage, TextureError> {
+        let _ = (width, height);
+        Err(TextureError::Unsupported)
+    }
+
+    /// Build `imgui`'s font atlas again after its fonts changed, and upload it
+    /// in place of the previous one.
+    ///
+    /// Backends without texture support only build the atlas, so frames can
+    /// still be started.
+    fn reload_fonts(&mut self, imgui: &mut ImguiContext) -> Result<(), TextureError> {
+        imgui.fonts().build_rgba32_texture();
+        Ok(())
+    }
+
+    /// Create a [`GpuTimer`] for the profiler's GPU scopes.
+    ///
+    /// Backends without GPU timer queries return [None].
+    fn create_gpu_timer(&self) -> Option<GpuTimer> {
+        None
+    }
+
+    /// The backend's GL context, shared with the [`Scene`](super::scene::Scene).
+    ///
+    /// Backends which do not render through OpenGL return [None].
+    fn gl(&self) -> Option<Rc<Context>> {
+        None
+    }
+
+    /// Returns `true` if the backend can render imgui viewports into their own os
+    /// windows (see [`GraphUiBuilder::viewports()`](super::ui::GraphUiBuilder::viewports)).
+    fn supports_viewports(&self) -> bool {
+        false
+    }
+
+    /// The backend's name, as reported to imgui (eg. `"imgui_impl_opengl3"`).
+    fn name(&self) -> &str;
+}
+
+/// Errors which can occur while creating or using a [`UiRenderer`].
+#[derive(Debug)]
+pub enum UiRenderError {
+    /// The backend failed to initialize its device objects.
+    Init(String),
+    /// The backend failed to render a frame.
+    Render(String),
+}
+
+/// An OpenGL [`UiRenderer`], wrapping [`AutoRenderer`].
+pub struct GlowUiRenderer {
+    inner: AutoRenderer,
+    /// The font atlas uploaded by [`UiRenderer::reload_fonts()`], replacing the
+    /// one [`AutoRenderer`] uploaded on creation.
+    fonts: Option<Texture2d>,
+}
+
+/// A [`UiRenderer`] which draws nothing.
+///
+/// Builds imgui's font atlas on creation so that frames can be started, then
+/// discards every frame. Useful for headless operation and tests.
+pub struct NullUiRenderer;
+
+/// Create a GL function loader for the given window's GL context.
+///
+/// See also:
+/// https://github.com/imgui-rs/imgui-sdl2-support/blob/main/examples/sdl2_01_basic.rs#L13
+///
+/// # Safety
+/// Must be called after the window's GL context has been created and made current.
+fn glow_context(window: &Window) -> Context {
+    unsafe {
+        Context::from_loader_function(|s| {
+            window
+                .subsystem()
+                .gl_get_proc_address(s)
+                .unwrap_or_else(|| panic!("Expected function '{s}' but did not")) as _
+        })
+    }
+}
+
+impl GlowUiRenderer {
+    /// Create an OpenGL renderer for `window`, uploading `imgui`'s font atlas.
+    ///
+    /// The window's GL context must already be created and current.
+    pub fn new(window: &Window, imgui: &mut ImguiContext) -> Result<Self, UiRenderError> {
+        let inner = AutoRenderer::new(glow_context(window), imgui)
+            .map_err(|e| UiRenderError::Init(e.to_string()))?;
+        Ok(Self { inner, fonts: None })
+    }
+
+    /// The GL context used by this renderer, for scene rendering.
+    pub fn gl_context(&self) -> &Rc<Context> {
+        self.inner.gl_context()
+    }
+}
+
+impl UiRenderer for GlowUiRenderer {
+    fn render(&mut self, draw_data: &DrawData) -> Result<(), UiRenderError> {
+        self.inner.render(draw_data).map_err(UiRenderError::Render)
+    }
+
+    fn clear(&mut self, color: [f32; 4]) {
+        let gl = self.inner.gl_context();
+        unsafe {
+            gl.clear_color(color[0], color[1], color[2], color[3]);
+            gl.clear(glow::COLOR_BUFFER_BIT);
+        }
+    }
+
+    fn resize(&mut self, width: u32, height: u32) {
+        unsafe {
+            self.inner
+                .gl_context()
+                .viewport(0, 0, width as _, height as _)
+        };
+    }
+
+    fn create_texture(
+        &mut self,
+        width: u32,
+        height: u32,
+        pixels: &[u8],
+        filter: TextureFilter,
+    ) -> Result<Texture2d, TextureError> {
+        let gl = self.inner.gl_context().clone();
+        Texture2d::from_rgba(
+            &gl,
+            self.inner.texture_map_mut(),
+            width,
+            height,
+            pixels,
+            filter,
+        )
+    }
+
+    fn create_render_target(
+        &mut self,
+        width: u32,
+        height: u32,
+    ) -> Result<RenderTarget, TextureError> {
+        let gl = self.inner.gl_context().clone();
+        RenderTarget::new(&gl, self.inner.texture_map_mut(), width, height)
+    }
+
+    fn read_pixels(&mut self, width: u32, height: u32) -> Result<RgbaImage, TextureError> {
+        let gl = self.inner.gl_context();
+        unsafe {
+            gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+            gl.read_buffer(glow::BACK);
+        }
+        Ok(target::read_framebuffer(gl, width, height))
+    }
+
+    fn reload_fonts(&mut self, imgui: &mut ImguiContext) -> Result<(), TextureError> {
+        let atlas = imgui.fonts().build_rgba32_texture();
+        let texture =
+            self.create_texture(atlas.width, atlas.height, atlas.data, TextureFilter::Linear)?;
+        imgui.fonts().tex_id = texture.id();
+        self.fonts = Some(texture);
+        Ok(())
+    }
+
+    fn create_gpu_timer(&self) -> Option<GpuTimer> {
+        Some(GpuTimer::new(self.inner.gl_context().clone()))
+    }
+
+    fn gl(&self) -> Option<Rc<Context>> {
+        Some(self.inner.gl_context().clone())
+    }
+
+    fn name(&self) -> &str {
+        "imgui_impl_opengl3"
+    }
+}
+
+impl NullUiRenderer {
+    /// Create a null renderer, building `imgui`'s font atlas.
+    pub fn new(imgui: &mut ImguiContext) -> Self {
+        imgui.fonts().build_rgba32_texture();
+        Self
+    }
+}
+
+impl UiRenderer for NullUiRenderer {
+    fn render(&mut self, _draw_data: &DrawData) -> Result<(), UiRenderError> {
+        Ok(())
+    }
+
+    fn clear(&mut self, _color: [f32; 4]) {}
+
+    fn resize(&mut self, _width: u32, _height: u32) {}
+
+    fn name(&self) -> &str {
+        "null"
+    }
+}
+
+impl std::fmt::Display for UiRenderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Init(e) => write!(f, "Failed to initialize ui renderer: {e}"),
+            Self::Render(e) => write!(f, "Failed to render ui: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for UiRenderError {}
@@ -0,0 +1,299 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::imgui::Context as ImguiContext;
+use crate::sdl3::video::{Window, WindowPos};
+
+/// Organization name used when resolving per-platform directories.
+pub const ORGANIZATION: &str = "bitgrid";
+
+/// Version of the [`UiState`] document written by this build.
+///
+/// Bump this whenever the layout of [`UiState`] changes, and add a step to
+/// [`migrate()`] upgrading documents of the previous version.
+pub const UI_STATE_VERSION: u32 = 1;
+
+/// Name of the state document inside the config directory.
+const STATE_FILE: &str = "ui_state.json";
+
+/// Name of the raw imgui ini file written by builds predating [`UiStateManager`].
+const LEGACY_INI_FILE: &str = "imgui.ini";
+
+/// Persisted UI state: imgui's layout, the window's geometry, and which panels are open.
+///
+/// Saved and loaded as a single versioned document by [`UiStateManager`].
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UiState {
+    /// Document version, see [`UI_STATE_VERSION`].
+    pub version: u32,
+    /// imgui's own settings (window positions, sizes, dock layout) in ini format.
+    pub imgui_ini: String,
+    /// Geometry of the main window when the state was last captured.
+    pub window: Option<WindowGeometry>,
+    /// Open/closed state of each named panel.
+    pub panels: BTreeMap<String, bool>,
+}
+
+/// Position, size, and maximized state of a window.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowGeometry {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub maximized: bool,
+}
+
+/// Loads, tracks, and saves [`UiState`] in a per-platform config directory.
+///
+/// The config directory is resolved through SDL (see
+/// [`sdl3::filesystem::get_pref_path()`](crate::sdl3::filesystem::get_pref_path)),
+/// eg. `~/.local/share/bitgrid/<app>/` on Linux or `%APPDATA%\bitgrid\<app>\` on Windows.
+///
+/// Attach a manager to a window's ui with [`GraphUiBuilder::state()`](super::ui::GraphUiBuilder::state)
+/// to have the layout restored on startup, and persist it again with
+/// [`GraphWindow::save_ui_state()`](super::window::GraphWindow::save_ui_state).
+pub struct UiStateManager {
+    dir: PathBuf,
+    state: UiState,
+    dirty: bool,
+}
+
+/// Errors which can occur while loading or saving [`UiState`].
+#[derive(Debug)]
+pub enum UiStateError {
+    /// SDL could not resolve a config directory for the application.
+    NoConfigDir(String),
+    /// The state document could not be read or written.
+    Io(std::io::Error),
+    /// The state document is not valid.
+    Parse(serde_json::Error),
+    /// The state document was written by a newer build.
+    UnsupportedVersion(u32),
+}
+
+impl UiStateManager {
+    /// Create a manager storing state in the per-platform config directory for `app`.
+    ///
+    /// The directory is created if it does not exist yet. No state is loaded
+    /// until [`Self::load()`] is called.
+    pub fn new(app: &str) -> Result<Self, UiStateError> {
+        let dir = crate::sdl3::filesystem::get_pref_path(ORGANIZATION, app)
+            .map_err(|e| UiStateError::NoConfigDir(e.to_string()))?;
+        Ok(Self::with_dir(dir))
+    }
+
+    /// Create a manager storing state in `dir`, bypassing platform resolution.
+    pub fn with_dir(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            state: UiState {
+                version: UI_STATE_VERSION,
+                ..Default::default()
+            },
+            dirty: false,
+        }
+    }
+
+    /// The directory state is stored in.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Path of the state document.
+    pub fn path(&self) -> PathBuf {
+        self.dir.join(STATE_FILE)
+    }
+
+    /// Load state from disk, migrating older documents to [`UI_STATE_VERSION`].
+    ///
+    /// If no state document exists yet, but an `imgui.ini` from an older build
+    /// does, its layout is imported instead. Missing state is not an error.
+    pub fn load(&mut self) -> Result<(), UiStateError> {
+        let path = self.path();
+
+        if path.exists() {
+            let text = std::fs::read_to_string(&path)?;
+            let doc = migrate(serde_json::from_str(&text)?)?;
+            self.state = serde_json::from_value(doc)?;
+            self.dirty = false;
+        } else {
+            let legacy = self.dir.join(LEGACY_INI_FILE);
+            if legacy.exists() {
+                self.state.imgui_ini = std::fs::read_to_string(legacy)?;
+                self.dirty = true;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Write state to disk if it changed since the last load or save.
+    ///
+    /// The document is written to a temporary file first and then renamed over
+    /// the previous one, so a crash mid-write never leaves a truncated layout.
+    pub fn save(&mut self) -> Result<(), UiStateError> {
+        if !self.dirty {
+            return Ok(());
+        }
+
+        std::fs::create_dir_all(&self.dir)?;
+
+        let path = self.path();
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_string_pretty(&self.state)?)?;
+        std::fs::rename(tmp, path)?;
+
+        self.dirty = false;
+        Ok(())
+    }
+
+    /// Returns `true` if the state changed since the last load or save.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// The currently tracked state.
+    pub fn state(&self) -> &UiState {
+        &self.state
+    }
+
+    /// Whether the panel `name` was open, or [None] if its state was never recorded.
+    pub fn panel_open(&self, name: &str) -> Option<bool> {
+        self.state.panels.get(name).copied()
+    }
+
+    /// Record whether the panel `name` is open.
+    pub fn set_panel_open(&mut self, name: &str, open: bool) {
+        if self.state.panels.insert(name.to_owned(), open) != Some(open) {
+            self.dirty = true;
+        }
+    }
+
+    /// The recorded main window geometry, if any.
+    pub fn window_geometry(&self) -> Option<WindowGeometry> {
+        self.state.window
+    }
+
+    /// Record the current geometry of `window`.
+    ///
+    /// Position and size are left untouched while the window is maximized, so
+    /// that un-maximizing after a restart returns to the previous geometry.
+    pub fn capture_window(&mut self, window: &Window) {
+        let maximized = window.is_maximized();
+        let geometry = match (self.state.window, maximized) {
+            (Some(previous), true) => WindowGeometry {
+                maximized,
+                ..previous
+            },
+            _ => {
+                let (x, y) = window.position();
+                let (width, height) = window.size();
+                WindowGeometry {
+                    x,
+                    y,
+                    width,
+                    height,
+                    maximized,
+                }
+            }
+        };
+
+        if self.state.window != Some(geometry) {
+            self.state.window = Some(geometry);
+            self.dirty = true;
+        }
+    }
+
+    /// Apply the recorded geometry, if any, to `window`.
+    pub fn apply_window(&self, window: &mut Window) {
+        let Some(geometry) = self.state.window else {
+            return;
+        };
+
+        // A failed resize leaves the builder's default size, which is acceptable.
+        let _ = window.set_size(geometry.width, geometry.height);
+        window.set_position(
+            WindowPos::Positioned(geometry.x),
+            WindowPos::Positioned(geometry.y),
+        );
+        if geometry.maximized {
+            window.maximize();
+        }
+    }
+
+    /// Load the recorded imgui layout into `imgui`.
+    pub(crate) fn apply_imgui(&self, imgui: &mut ImguiContext) {
+        if !self.state.imgui_ini.is_empty() {
+            imgui.load_ini_settings(&self.state.imgui_ini);
+        }
+    }
+
+    /// Record imgui's current layout from `imgui`.
+    pub(crate) fn capture_imgui(&mut self, imgui: &mut ImguiContext) {
+        let mut ini = String::new();
+        imgui.save_ini_settings(&mut ini);
+        if self.state.imgui_ini != ini {
+            self.state.imgui_ini = ini;
+            self.dirty = true;
+        }
+        imgui.io_mut().want_save_ini_settings = false;
+    }
+}
+
+/// Upgrade a raw state document one version at a time until it matches
+/// [`UI_STATE_VERSION`].
+fn migrate(mut doc: Value) -> Result<Value, UiStateError> {
+    loop {
+        let version = doc.get("version").and_then(Value::as_u64).unwrap_or(0) as u32;
+        match version {
+            UI_STATE_VERSION => return Ok(doc),
+            // Unversioned documents only held the imgui layout under "ini".
+            0 => {
+                let ini = doc.get("ini").cloned().unwrap_or_default();
+                doc = serde_json::json!({ "version": 1, "imgui_ini": ini });
+            }
+            v => return Err(UiStateError::UnsupportedVersion(v)),
+        }
+    }
+}
+
+impl From<std::io::Error> for UiStateError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl From<serde_json::Error> for UiStateError {
+    fn from(value: serde_json::Error) -> Self {
+        Self::Parse(value)
+    }
+}
+
+impl std::fmt::Display for UiStateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoConfigDir(e) => write!(f, "Failed to resolve config directory: {e}"),
+            Self::Io(e) => write!(f, "Failed to access ui state: {e}"),
+            Self::Parse(e) => write!(f, "Failed to parse ui state: {e}"),
+            Self::UnsupportedVersion(v) => write!(
+                f,
+                "Ui state version {v} is newer than supported version {UI_STATE_VERSION}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for UiStateError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Parse(e) => Some(e),
+            _ => None,
+        }
+    }
+}
@@ -1,9 +1,24 @@
 use common::renderer::SDL;
-
-use std::cell::{RefCell, RefMut};
-
+use common::renderer::target::RenderTarget;
+use common::renderer::texture::TextureError;
+
+use std::cell::{Cell, RefCell, RefMut};
+use std::rc::Rc;
+use std::time::Duration;
+
+use super::cursor::{CursorError, WindowCursor};
+use super::event::{EventQueue, GraphEventIterator};
+use super::profiler;
+use super::scene::{Scene, SceneError, SceneOverlay};
+use super::text_input::TextInputState;
 use super::ui::{GraphUi, GraphUiBuilder, UiFrameGuard};
-use crate::sdl3::video::{Window, WindowBuilder};
+use super::ui_state::UiStateError;
+use crate::sdl3::event::{Event, WindowEvent};
+use crate::sdl3::mouse::Cursor;
+use crate::sdl3::sys::video::{SDL_WINDOW_HIDDEN, SDL_WINDOW_MINIMIZED, SDL_WINDOW_OCCLUDED};
+use crate::sdl3::video::{
+    GLContext, GLProfile, SwapInterval, Window, WindowBuildError, WindowBuilder,
+};
 
 /// Possible rendering backends for a window.
 #[allow(dead_code)]
@@ -16,12 +31,40 @@ enum RenderBackend {
     Cpu,
 }
 
+/// OpenGL context configuration applied by [`GraphWindowBuilder::build()`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct GlConfig {
+    /// Requested context version, as `(major, minor)`.
+    version: (u8, u8),
+    /// Requested context profile.
+    profile: GLProfile,
+    /// Swap interval applied once the context is current.
+    swap_interval: SwapInterval,
+}
+
 /// A window with optional [`GraphUi`] state attached.
+///
+/// Fields are dropped in declaration order: the scene and ui (and their GPU
+/// objects) first, then the GL context, then the window itself.
 pub struct GraphWindow {
-    /// Underlying SDL window.
-    inner: Window,
+    /// Optional [`Scene`] drawn underneath the ui, with interior mutability.
+    scene: RefCell<Option<Scene>>,
     /// Optional [`GraphUi`] instance for this window, with interior mutability.
     gui: RefCell<Option<GraphUi>>,
+    /// The window's GL context, if it was built with [`GraphWindowBuilder::opengl()`].
+    ///
+    /// Owned by the window so that it lives exactly as long as the window does.
+    gl_context: Option<GLContext>,
+    /// Events received during the current frame, see [`GraphWindow::pump_events()`].
+    events: EventQueue,
+    /// Cursor requested by the application, with the SDL cursor created for it.
+    cursor: RefCell<Option<(WindowCursor, Rc<Cursor>)>>,
+    /// Display scale last applied to the ui, see [`GraphWindow::display_scale()`].
+    display_scale: Cell<f32>,
+    /// SDL text input and IME state, see [`GraphWindow::start_text_input()`].
+    text_input: TextInputState,
+    /// Underlying SDL window.
+    inner: Window,
 }
 
 /// Builder for `GraphWindow`, allowing configuration of SDL window flags and render backend.
@@ -38,44 +81,101 @@ pub struct GraphWindow {
 /// - resizeable status
 /// - minimized/maximized status
 /// - focused status
-pub struct GraphWindowBuilder(WindowBuilder, RenderBackend);
+/// - OpenGL context version, profile, and swap interval
+pub struct GraphWindowBuilder(WindowBuilder, RenderBackend, GlConfig);
 
 impl GraphWindowBuilder {
     fn new(title: &str, width: u32, height: u32) -> Self {
         Self(
             WindowBuilder::new(&SDL.video().borrow(), title, width, height),
             RenderBackend::None,
+            GlConfig {
+                version: (4, 0),
+                profile: GLProfile::Core,
+                swap_interval: SwapInterval::VSync,
+            },
         )
     }
 
-    /// Finalize building the window and return a `GraphWindow`.
+    /// Finalize building the window and return a render-ready `GraphWindow`.
     ///
-    /// Errors if SDL fails to build the window, or if no rendering backend was selected.
-    pub fn build(self) -> Result<GraphWindow, crate::sdl3::video::WindowBuildError> {
+    /// For [OpenGL](Self::opengl) windows, this also creates the GL context, makes it
+    /// current, and applies the swap interval. The context is owned by the returned
+    /// window.
+    ///
+    /// Errors if SDL fails to build the window or create its rendering context.
+    /// Panics if no rendering backend was selected, or if the backend is unsupported.
+    pub fn build(self) -> Result<GraphWindow, WindowBuildError> {
         matches!(self.1, RenderBackend::None)
             .then(|| panic!("No render backend was selected before building the graph window"));
 
-        let inner = self.0.build()?;
+        if self.1 == RenderBackend::OpenGL {
+            // Context attributes are read by SDL when the window is created,
+            // so they must be set before building it.
+            let video = SDL.video();
+            let video = video.borrow();
+            let gl_attr = video.gl_attr();
+            gl_attr.set_context_version(self.2.version.0, self.2.version.1);
+            gl_attr.set_context_profile(self.2.profile);
+        }
 
-        // TODO: Why is this not working?
+        let inner = self.0.build()?;
 
-        // match self.1 {
-        //     RenderBackend::OpenGL => {
-        //         let gl_context = inner.gl_create_context().unwrap();
-        //         inner.gl_make_current(&gl_context).unwrap();
-        //         inner.subsystem().gl_set_swap_interval(1).unwrap();
-        //         println!("Initializing OpenGL");
-        //     },
-        //     RenderBackend::None => {}, // already checked
-        //     _ => panic!("Backend '{:?}' is not supported", self.1)
-        // }
+        let gl_context = match self.1 {
+            RenderBackend::OpenGL => {
+                let gl_context = inner
+                    .gl_create_context()
+                    .map_err(WindowBuildError::SdlError)?;
+                inner
+                    .gl_make_current(&gl_context)
+                    .map_err(WindowBuildError::SdlError)?;
+                inner
+                    .subsystem()
+                    .gl_set_swap_interval(self.2.swap_interval)
+                    .map_err(WindowBuildError::SdlError)?;
+                Some(gl_context)
+            }
+            RenderBackend::None => unreachable!(), // already checked
+            _ => panic!("Backend '{:?}' is not supported", self.1),
+        };
 
         Ok(GraphWindow {
-            inner,
+            scene: RefCell::new(None),
             gui: RefCell::new(None),
+            gl_context,
+            events: EventQueue::default(),
+            cursor: RefCell::new(None),
+            display_scale: Cell::new(inner.display_scale()),
+            text_input: TextInputState::default(),
+            inner,
         })
     }
 
+    /// Sets the requested OpenGL context version. Defaults to 4.0.
+    ///
+    /// Only used by windows built with [`Self::opengl()`].
+    pub fn gl_version(mut self, major: u8, minor: u8) -> GraphWindowBuilder {
+        self.2.version = (major, minor);
+        self
+    }
+
+    /// Sets the requested OpenGL context profile. Defaults to [`GLProfile::Core`].
+    ///
+    /// Only used by windows built with [`Self::opengl()`].
+    pub fn gl_profile(mut self, profile: GLProfile) -> GraphWindowBuilder {
+        self.2.profile = profile;
+        self
+    }
+
+    /// Sets the swap interval applied after the context is created.
+    /// Defaults to [`SwapInterval::VSync`].
+    ///
+    /// Only used by windows built with [`Self::opengl()`].
+    pub fn swap_interval(mut self, interval: SwapInterval) -> GraphWindowBuilder {
+        self.2.swap_interval = interval;
+        self
+    }
+
     /// Sets the underlying window flags. <br />
     /// This will effectively undo any previous build operations, excluding window size and position.
     pub fn set_window_flags(mut self, flags: u32) -> GraphWindowBuilder {
@@ -176,14 +276,15 @@ impl GraphWindow {
     ///
     /// Returns a builder for [`GraphUi`].
     /// Calling [`GraphUiBuilder::build()`] automatically sets it as this window's gui.
-    pub fn new_ui(&mut self) -> GraphUiBuilder {
+    pub fn new_ui(&mut self) -> GraphUiBuilder<'_> {
         GraphUi::builder(self)
     }
 
     /// Set the GUI instance for this window.
     ///
     /// Called internally after building via `new_ui().build(...)`.
-    pub(crate) fn set_ui(&mut self, ui: GraphUi) {
+    pub(crate) fn set_ui(&mut self, mut ui: GraphUi) {
+        ui.set_display_scale(self.display_scale.get());
         self.gui.replace(Some(ui));
     }
 
@@ -192,33 +293,278 @@ impl GraphWindow {
     /// Returns `Some(RefMut<GraphUi>)` if a GUI was set, otherwise `None`.
     ///
     /// See also: [`RefMut`].
-    pub fn get_ui(&self) -> Option<RefMut<GraphUi>> {
+    pub fn get_ui(&self) -> Option<RefMut<'_, GraphUi>> {
         RefMut::filter_map(self.gui.borrow_mut(), |o| o.as_mut()).ok()
     }
 
-    /// Internal helper to get a mutable reference to the GUI.
-    fn ui_mut(&mut self) -> Option<&mut GraphUi> {
-        self.gui.get_mut().as_mut()
+    /// Attach a [`Scene`] to this window, drawn underneath the ui each frame
+    /// through the ui renderer's GL context.
+    ///
+    /// Errors if the ui's renderer has no GL context, or the scene's GL objects
+    /// cannot be created. Panics if the window has no ui.
+    pub fn new_scene(&self) -> Result<(), SceneError> {
+        let gl = self
+            .get_ui()
+            .expect("Tried to create a scene on a window with no ui")
+            .renderer()
+            .gl()
+            .ok_or(SceneError::Unsupported)?;
+        let mut scene = Scene::new(gl)?;
+        scene.set_viewport(self.viewport_size());
+        self.scene.replace(Some(scene));
+        Ok(())
     }
 
-    /// Poll SDL events, returning an iterator over unprocessed [`crate::sdl3::event::Event`]s.
+    /// Get a mutable reference to the scene if it exists.
+    ///
+    /// Returns `Some(RefMut<Scene>)` if a scene was attached with
+    /// [`Self::new_scene()`], otherwise `None`.
+    pub fn scene(&self) -> Option<RefMut<'_, Scene>> {
+        RefMut::filter_map(self.scene.borrow_mut(), |o| o.as_mut()).ok()
+    }
+
+    /// The window's size in points, as `[width, height]`.
+    pub(crate) fn viewport_size(&self) -> [f32; 2] {
+        let (width, height) = self.inner.size();
+        [width as f32, height as f32]
+    }
+
+    /// Persist the ui's layout and this window's geometry through the ui's
+    /// [`UiStateManager`](super::ui_state::UiStateManager).
+    ///
+    /// Does nothing if the window has no ui, or its ui has no state manager.
+    pub fn save_ui_state(&self) -> Result<(), UiStateError> {
+        match self.get_ui() {
+            Some(mut ui) => ui.save_state(&self.inner),
+            None => Ok(()),
+        }
+    }
+
+    /// The window's GL context, if it was built with [`GraphWindowBuilder::opengl()`].
+    pub fn gl_context(&self) -> Option<&GLContext> {
+        self.gl_context.as_ref()
+    }
+
+    /// Change the swap interval of the window's GL context, eg. to toggle vsync.
+    ///
+    /// Errors if SDL rejects the interval. Panics if the window has no GL context.
+    pub fn set_swap_interval(&self, interval: SwapInterval) -> Result<(), crate::sdl3::Error> {
+        let gl_context = self
+            .gl_context
+            .as_ref()
+            .expect("Tried to set the swap interval of a window with no GL context");
+        self.inner.gl_make_current(gl_context)?;
+        self.inner.subsystem().gl_set_swap_interval(interval)
+    }
+
+    /// Create an offscreen [`RenderTarget`] of `width` x `height` pixels, sharing
+    /// this window's GL context.
+    ///
+    /// Its texture can be shown in imgui panels or read back and saved to disk.
     ///
-    /// The iterator does some automatic state-handling, including:
-    /// - Sending each event to the current [`imgui`](crate::imgui) ui, if the window has a [`GraphUi`].
-    ///     - See also: [`GraphUi::handle_event()`]
-    /// - When the iterator is exhausted, before returning [None]
-    pub fn poll_events(&self) -> super::event::GraphEventIterator {
-        super::event::GraphEventIterator::new(self)
+    /// Errors if the ui's renderer does not support render targets, or fails to
+    /// create one. Panics if the window has no ui.
+    pub fn create_render_target(
+        &self,
+        width: u32,
+        height: u32,
+    ) -> Result<RenderTarget, TextureError> {
+        self.get_ui()
+            .expect("Tried to create a render target on a window with no ui")
+            .renderer()
+            .create_render_target(width, height)
+    }
+
+    /// Returns `false` while the window is minimized, hidden, or fully occluded,
+    /// ie. while anything drawn to it would never be seen.
+    ///
+    /// [`GraphWindow::run()`] skips drawing while this is `false`; hand-written
+    /// loops should do the same.
+    pub fn is_drawable(&self) -> bool {
+        let hidden = SDL_WINDOW_MINIMIZED | SDL_WINDOW_HIDDEN | SDL_WINDOW_OCCLUDED;
+        self.inner.window_flags() & hidden == 0
+    }
+
+    /// The content scale of the display the window is on (eg. `1.5` for 150%).
+    ///
+    /// The ui's fonts and style are scaled to match whenever this changes.
+    pub fn display_scale(&self) -> f32 {
+        self.display_scale.get()
+    }
+
+    /// Keep the renderer and ui in sync with the window's size and display.
+    ///
+    /// Called by [`Self::pump_events()`] for every window event addressed to this window:
+    /// - On a drawable size change, resizes the renderer's viewport.
+    /// - On a drawable size or display change, rescales the ui if the display
+    ///   scale changed.
+    pub(crate) fn handle_window_event(&self, event: &WindowEvent) {
+        let Some(mut ui) = self.get_ui() else {
+            return;
+        };
+
+        if let WindowEvent::PixelSizeChanged(width, height) = *event {
+            ui.renderer()
+                .resize(width.max(0) as u32, height.max(0) as u32);
+        }
+
+        if matches!(
+            event,
+            WindowEvent::PixelSizeChanged(..) | WindowEvent::DisplayChanged(..)
+        ) {
+            let scale = self.inner.display_scale();
+            if scale > 0.0 && scale != self.display_scale.get() {
+                self.display_scale.set(scale);
+                ui.set_display_scale(scale);
+            }
+        }
+    }
+
+    /// Set the cursor shown over the window, eg. a hand over a hovered node.
+    ///
+    /// imgui keeps control of the cursor while the mouse is over one of its
+    /// windows; the requested cursor is shown everywhere else.
+    ///
+    /// Errors if SDL fails to create the cursor.
+    pub fn set_cursor(&self, cursor: impl Into<WindowCursor>) -> Result<(), CursorError> {
+        let cursor = cursor.into();
+        let sdl_cursor = cursor.create()?;
+        if self.get_ui().is_none() {
+            sdl_cursor.set();
+        }
+        self.cursor.replace(Some((cursor, sdl_cursor)));
+        Ok(())
+    }
+
+    /// Return control of the cursor to imgui, undoing [`Self::set_cursor()`].
+    pub fn reset_cursor(&self) {
+        self.cursor.replace(None);
+    }
+
+    /// The cursor requested with [`Self::set_cursor()`], if any.
+    pub fn cursor(&self) -> Option<WindowCursor> {
+        self.cursor
+            .borrow()
+            .as_ref()
+            .map(|(cursor, _)| cursor.clone())
+    }
+
+    /// Start receiving text and IME composition events, regardless of whether an
+    /// imgui text field is focused.
+    ///
+    /// With a ui attached, text input is already started and stopped automatically
+    /// whenever imgui wants text, and the IME candidate window follows the focused
+    /// text field. This is only needed for text typed outside of imgui widgets.
+    pub fn start_text_input(&self) {
+        self.text_input.request(&self.inner, true);
+    }
+
+    /// Undo [`Self::start_text_input()`]. Text input stays active while an imgui
+    /// text field is focused.
+    pub fn stop_text_input(&self) {
+        self.text_input.request(&self.inner, false);
+    }
+
+    /// Whether text input was requested with [`Self::start_text_input()`].
+    pub fn text_input_requested(&self) -> bool {
+        self.text_input.requested()
+    }
+
+    /// Whether text and IME events are currently being received.
+    pub fn is_text_input_active(&self) -> bool {
+        self.text_input.is_active()
+    }
+
+    /// Drain all pending SDL events into this window's buffer for the current
+    /// frame, replacing the previous frame's events.
+    ///
+    /// Each event is processed as it is buffered:
+    /// - Window events addressed to this window keep the viewport and ui scale up
+    ///   to date (see [`Self::is_drawable()`] and [`Self::display_scale()`]).
+    /// - Every event is sent to the current [`imgui`](crate::imgui) ui, if the
+    ///   window has a [`GraphUi`].
+    ///
+    /// Call once per frame, then read the events with [`Self::events()`].
+    pub fn pump_events(&self) {
+        self.events.pump(|event| {
+            if let Event::Window {
+                window_id,
+                win_event,
+                ..
+            } = event
+                && *window_id == self.id()
+            {
+                self.handle_window_event(win_event);
+            }
+            if let Some(mut ui) = self.get_ui() {
+                ui.handle_event(event);
+            }
+        });
+    }
+
+    /// Block until an SDL event arrives, or until `timeout` passed, eg. to idle
+    /// between frames. The event is left for the next [`Self::pump_events()`].
+    pub fn wait_events(&self, timeout: Duration) {
+        self.events.wait(timeout);
+    }
+
+    /// Iterate over the events buffered by the last [`Self::pump_events()`].
+    ///
+    /// May be called any number of times per frame.
+    pub fn events(&self) -> GraphEventIterator<'_> {
+        GraphEventIterator::new(self)
+    }
+
+    /// [Pump](Self::pump_events) SDL events, returning an iterator over them.
+    ///
+    /// Shorthand for calling [`Self::pump_events()`] followed by [`Self::events()`].
+    pub fn poll_events(&self) -> GraphEventIterator<'_> {
+        self.pump_events();
+        self.events()
+    }
+
+    /// The events buffered for the current frame.
+    pub(crate) fn event_queue(&self) -> &EventQueue {
+        &self.events
     }
 
     /// Begin a new UI frame, returning a guard for frame lifetime.
     ///
+    /// Prepares the ui for the frame first (see [`GraphUi::prepare()`]), so events
+    /// should be [pumped](Self::pump_events) before calling this. The
+    /// [scene](Self::scene), if any, is then rendered underneath the ui.
+    ///
     /// Panics if no GUI has been set.
     pub fn ui_frame_begin(&mut self) -> UiFrameGuard<'_> {
+        let viewport = self.viewport_size();
         let ui = self
-            .ui_mut()
+            .gui
+            .get_mut()
+            .as_mut()
             .expect("Tried to begin a ui frame on a window with no ui");
-        UiFrameGuard::new(ui)
+
+        let capture = ui.input_capture();
+        self.text_input.sync(&self.inner, capture.text);
+
+        let cursor = self.cursor.get_mut();
+        let override_cursor = cursor.is_some() && !capture.mouse;
+        ui.set_cursor_override(override_cursor);
+        ui.prepare(&self.inner);
+        if override_cursor && let Some((_, cursor)) = cursor {
+            cursor.set();
+            SDL.core().borrow().mouse().show_cursor(true);
+        }
+
+        let overlay = match self.scene.get_mut() {
+            Some(scene) => {
+                let _gpu = profiler::gpu_scope("scene");
+                scene.set_viewport(viewport);
+                scene.render()
+            }
+            None => SceneOverlay::default(),
+        };
+
+        UiFrameGuard::new(ui, overlay)
     }
 }
 
@@ -0,0 +1,428 @@
+//! # Attribute Table
+//!
+//! The [`TablePanel`] lists the nodes or edges of the visible graph as the rows
+//! of a spreadsheet, with a column per attribute:
+//! - clicking a header sorts by its column, numbers by value and anything else
+//!   as text, with empty cells last
+//! - the filter keeps the rows with a cell containing its text, ignoring case
+//! - clicking a row selects its element, Ctrl+click toggles it, and Shift+click
+//!   selects every row from the one clicked before; rows are highlighted while
+//!   their element is selected, however it was selected
+//! - Export CSV writes the rows shown, in their order, see [`Table::write_csv()`]
+//!
+//! ```ignore
+//! let table = Table::nodes(&graph, ctx.style.node_attributes());
+//! let rows = table.filter("alice");
+//! table.write_csv(&rows, File::create("nodes.csv")?)?;
+//! ```
+
+use std::cmp::Ordering;
+use std::io::Write;
+
+use crate::context::EngineContext;
+use crate::graph::{Attributes, EdgeId, Element, Graph, NodeId};
+use crate::i18n::tr;
+use crate::imgui::{ListClipper, TableFlags, TableSortDirection, Ui};
+use crate::selection::SelectMode;
+use crate::subsystems::panels::Panel;
+
+/// Which elements a [`Table`] lists.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum TableKind {
+    #[default]
+    Nodes,
+    Edges,
+}
+
+/// The attributes of a graph's nodes or edges, as text and numbers.
+#[derive(Clone, Debug, Default)]
+pub struct Table {
+    columns: Vec<String>,
+    rows: Vec<TableRow>,
+}
+
+/// A node or edge with its cells, in the order of [`Table::columns()`].
+#[derive(Clone, Debug)]
+pub struct TableRow {
+    pub element: Element,
+    pub cells: Vec<Cell>,
+}
+
+/// A value in a [`Table`]. Cells of missing attributes are empty.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Cell {
+    pub text: String,
+    /// The value as a number, for sorting, if it is one.
+    pub number: Option<f64>,
+}
+
+/// Lists the nodes or edges of the visible graph with their attributes, see
+/// the [module documentation](self).
+pub struct TablePanel {
+    kind: TableKind,
+    filter: String,
+    export_path: String,
+    /// Result of the last export, and whether it failed.
+    status: Option<(String, bool)>,
+    table: Table,
+    /// Indices of the rows shown, filtered and sorted.
+    shown: Vec<usize>,
+    /// Generation of the graph and kind of elements `table` lists.
+    built: Option<(u64, TableKind)>,
+    /// Filter and sort `shown` was picked with.
+    picked: Option<(String, Option<(usize, bool)>)>,
+    /// Column sorted by, and whether descending.
+    sort: Option<(usize, bool)>,
+    /// Shown row clicked last, where Shift+click ranges start.
+    anchor: Option<usize>,
+}
+
+impl TableKind {
+    pub const ALL: [TableKind; 2] = [Self::Nodes, Self::Edges];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Nodes => "Nodes",
+            Self::Edges => "Edges",
+        }
+    }
+}
+
+impl Table {
+    /// List the nodes of `graph` with an id column, then a column per name in
+    /// `attributes`.
+    pub fn nodes(graph: &Graph, attributes: &[String]) -> Self {
+        let mut columns = vec!["Id".to_string()];
+        columns.extend(attributes.iter().cloned());
+        let rows = graph
+            .nodes()
+            .map(|node| {
+                let mut cells = vec![id_cell(node.to_string(), node.index())];
+                cells.extend(attribute_cells(graph.node(node), attributes));
+                TableRow {
+                    element: Element::Node(node),
+                    cells,
+                }
+            })
+            .collect();
+        Self { columns, rows }
+    }
+
+    /// List the edges of `graph` with id, source, and target columns, then a
+    /// column per name in `attributes`.
+    pub fn edges(graph: &Graph, attributes: &[String]) -> Self {
+        let mut columns = ["Id", "Source", "Target"].map(String::from).to_vec();
+        columns.extend(attributes.iter().cloned());
+        let rows = graph
+            .edges()
+            .filter_map(|edge| {
+                let (source, target) = graph.endpoints(edge)?;
+                let mut cells = vec![
+                    id_cell(edge.to_string(), edge.index()),
+                    id_cell(source.to_string(), source.index()),
+                    id_cell(target.to_string(), target.index()),
+                ];
+                cells.extend(attribute_cells(graph.edge(edge), attributes));
+                Some(TableRow {
+                    element: Element::Edge(edge),
+                    cells,
+                })
+            })
+            .collect();
+        Self { columns, rows }
+    }
+
+    /// Names of the columns.
+    pub fn columns(&self) -> &[String] {
+        &self.columns
+    }
+
+    /// Every row, in id order.
+    pub fn rows(&self) -> &[TableRow] {
+        &self.rows
+    }
+
+    /// Indices of the rows with a cell containing `text`, ignoring case. Every
+    /// row matches an empty `text`.
+    pub fn filter(&self, text: &str) -> Vec<usize> {
+        let needle = text.trim().to_lowercase();
+        (0..self.rows.len())
+            .filter(|&i| {
+                needle.is_empty()
+                    || self.rows[i]
+                        .cells
+                        .iter()
+                        .any(|cell| cell.text.to_lowercase().contains(&needle))
+            })
+            .collect()
+    }
+
+    /// Sort the row indices `rows` by `column`. Numbers are compared by value
+    /// and come before text; empty cells come last either way. The sort is
+    /// stable, so equal rows stay in id order.
+    pub fn sort(&self, rows: &mut [usize], column: usize, descending: bool) {
+        rows.sort_by(|&a, &b| {
+            let (a, b) = (&self.rows[a].cells[column], &self.rows[b].cells[column]);
+            match (a.text.is_empty(), b.text.is_empty()) {
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => {
+                    let order = match (a.number, b.number) {
+                        (Some(a), Some(b)) => a.total_cmp(&b),
+                        (Some(_), None) => Ordering::Less,
+                        (None, Some(_)) => Ordering::Greater,
+                        (None, None) => a.text.cmp(&b.text),
+                    };
+                    if descending { order.reverse() } else { order }
+                }
+            }
+        });
+    }
+
+    /// Write the header and the row indices `rows` to `out` as comma separated
+    /// values, quoting fields which need it.
+    pub fn write_csv(&self, rows: &[usize], mut out: impl Write) -> std::io::Result<()> {
+        let line = |out: &mut dyn Write, fields: &mut dyn Iterator<Item = &str>| {
+            let fields: Vec<String> = fields.map(csv_field).collect();
+            writeln!(out, "{}", fields.join(","))
+        };
+        line(&mut out, &mut self.columns.iter().map(String::as_str))?;
+        for &row in rows {
+            line(
+                &mut out,
+                &mut self.rows[row].cells.iter().map(|c| c.text.as_str()),
+            )?;
+        }
+        out.flush()
+    }
+}
+
+impl TablePanel {
+    /// Create a panel listing nodes.
+    pub fn new() -> Self {
+        Self {
+            kind: TableKind::Nodes,
+            filter: String::new(),
+            export_path: "table.csv".into(),
+            status: None,
+            table: Table::default(),
+            shown: Vec::new(),
+            built: None,
+            picked: None,
+            sort: None,
+            anchor: None,
+        }
+    }
+
+    /// Build the table again if the graph or the kind of elements changed, and
+    /// pick the rows shown again if the filter or the sort changed too.
+    fn refresh(&mut self, ctx: &EngineContext) {
+        let graph = ctx.visible_graph();
+        let built = Some((graph.generation(), self.kind));
+        if self.built != built {
+            self.built = built;
+            self.picked = None;
+            self.table = match self.kind {
+                TableKind::Nodes => Table::nodes(graph, ctx.style.node_attributes()),
+                TableKind::Edges => Table::edges(graph, ctx.style.edge_attributes()),
+            };
+            self.sort = self.sort.filter(|(c, _)| *c < self.table.columns.len());
+        }
+        let picked = Some((self.filter.clone(), self.sort));
+        if self.picked != picked {
+            self.picked = picked;
+            self.shown = self.table.filter(&self.filter);
+            if let Some((column, descending)) = self.sort {
+                self.table.sort(&mut self.shown, column, descending);
+            }
+            self.anchor = None;
+        }
+    }
+
+    /// Select the element of the shown row `index`, according to the modifier
+    /// keys held.
+    fn click(&mut self, ui: &Ui, ctx: &mut EngineContext, index: usize) {
+        let io = ui.io();
+        let (rows, mode) = match self.anchor {
+            Some(anchor) if io.key_shift => {
+                let range = anchor.min(index)..=anchor.max(index);
+                (&self.shown[range], SelectMode::Replace)
+            }
+            _ if io.key_ctrl => (&self.shown[index..=index], SelectMode::Toggle),
+            _ => (&self.shown[index..=index], SelectMode::Replace),
+        };
+        let (mut nodes, mut edges) = (Vec::<NodeId>::new(), Vec::<EdgeId>::new());
+        for &row in rows {
+            match self.table.rows[row].element {
+                Element::Node(node) => nodes.push(node),
+                Element::Edge(edge) => edges.push(edge),
+                Element::Graph => {}
+            }
+        }
+        ctx.selection.select(nodes, edges, mode);
+        if !io.key_shift {
+            self.anchor = Some(index);
+        }
+    }
+
+    /// Write the rows shown to the export path.
+    fn export(&mut self) {
+        let result = std::fs::File::create(&self.export_path)
+            .map(std::io::BufWriter::new)
+            .and_then(|out| self.table.write_csv(&self.shown, out));
+        self.status = Some(match result {
+            Ok(()) => (
+                format!(
+                    "Exported {} rows to '{}'",
+                    self.shown.len(),
+                    self.export_path
+                ),
+                false,
+            ),
+            Err(e) => (format!("Export failed: {e}"), true),
+        });
+    }
+}
+
+impl Panel for TablePanel {
+    fn id(&self) -> &'static str {
+        "Table"
+    }
+
+    fn draw(&mut self, ui: &Ui, ctx: &mut EngineContext) {
+        if let Some(_tabs) = ui.tab_bar("##kind") {
+            for kind in TableKind::ALL {
+                if let Some(_tab) = ui.tab_item(kind.label()) {
+                    self.kind = kind;
+                }
+            }
+        }
+        ui.set_next_item_width(-1.0);
+        ui.input_text("##filter", &mut self.filter)
+            .hint(tr("Filter rows"))
+            .build();
+        self.refresh(ctx);
+
+        ui.text(
+            tr("{shown} of {count} rows")
+                .replace("{shown}", &self.shown.len().to_string())
+                .replace("{count}", &self.table.rows.len().to_string()),
+        );
+        ui.same_line();
+        ui.set_next_item_width(160.0);
+        ui.input_text("##export", &mut self.export_path).build();
+        ui.same_line();
+        ui.enabled(!self.export_path.trim().is_empty(), || {
+            if ui.button(tr("Export CSV")) {
+                self.export();
+            }
+        });
+        match &self.status {
+            Some((error, true)) => ui.text_colored([1.0, 0.4, 0.4, 1.0], error),
+            Some((status, false)) => ui.text_disabled(status),
+            None => {}
+        }
+
+        let columns = self.table.columns.len();
+        let Some(_table) = ui.begin_table_with_flags(
+            format!("##{}", self.kind.label()),
+            columns,
+            TableFlags::ROW_BG
+                | TableFlags::BORDERS_INNER_V
+                | TableFlags::RESIZABLE
+                | TableFlags::SORTABLE
+                | TableFlags::SORT_TRISTATE
+                | TableFlags::SCROLL_X
+                | TableFlags::SCROLL_Y
+                | TableFlags::SIZING_FIXED_FIT,
+        ) else {
+            return;
+        };
+        ui.table_setup_scroll_freeze(1, 1);
+        for column in &self.table.columns {
+            ui.table_setup_column(column);
+        }
+        ui.table_headers_row();
+        if let Some(mut specs) = ui.table_sort_specs_mut()
+            && specs.should_sort()
+        {
+            self.sort = specs.specs().iter().next().and_then(|spec| {
+                let descending = spec.sort_direction()? == TableSortDirection::Descending;
+                Some((spec.column_idx(), descending))
+            });
+            specs.set_sorted();
+        }
+
+        let mut clicked = None;
+        let clipper = ListClipper::new(self.shown.len() as i32);
+        for i in clipper.begin(ui).iter() {
+            let i = i as usize;
+            let row = &self.table.rows[self.shown[i]];
+            let selected = match row.element {
+                Element::Node(node) => ctx.selection.contains_node(node),
+                Element::Edge(edge) => ctx.selection.contains_edge(edge),
+                Element::Graph => false,
+            };
+            let _id = ui.push_id_usize(i);
+            ui.table_next_row();
+            for (column, cell) in row.cells.iter().enumerate() {
+                ui.table_next_column();
+                if column == 0 {
+                    if ui
+                        .selectable_config(&cell.text)
+                        .selected(selected)
+                        .span_all_columns(true)
+                        .build()
+                    {
+                        clicked = Some(i);
+                    }
+                } else {
+                    ui.text(&cell.text);
+                }
+            }
+        }
+        if let Some(i) = clicked {
+            self.click(ui, ctx, i);
+        }
+    }
+}
+
+/// An id cell, sorted by its slot `index`.
+fn id_cell(text: String, index: usize) -> Cell {
+    Cell {
+        text,
+        number: Some(index as f64),
+    }
+}
+
+/// Cells of the `attributes` in `attrs`, empty where missing.
+fn attribute_cells<'a>(
+    attrs: Option<&'a Attributes>,
+    attributes: &'a [String],
+) -> impl Iterator<Item = Cell> + 'a {
+    attributes.iter().map(
+        move |name| match attrs.and_then(|attrs| attrs.get(name.as_str())) {
+            Some(value) => Cell {
+                text: value.to_string(),
+                number: value.as_f64(),
+            },
+            None => Cell::default(),
+        },
+    )
+}
+
+/// `text` as a CSV field, quoted if it holds a comma, a quote, or a line break.
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+impl std::default::Default for TablePanel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,403 @@
+//! # Timeline
+//!
+//! Replays temporal graphs, whose nodes and edges carry the time they appear
+//! and disappear in two numeric attributes, `start` and `end` by default:
+//! - a [`Timeline`] keeps a window of time, ending at its cursor, and hides
+//!   through a [`TimeWindow`] the elements alive at no time of it
+//! - playing moves the cursor through the whole time range in
+//!   [`PLAYBACK_DURATION`], times the speed
+//! - elements fade in as they appear and out as they disappear, see
+//!   [`Timeline::node_presence()`]
+//! - the [`TimelinePanel`] scrubs, plays, and tunes the timeline
+//!
+//! Elements without a start were always there, and those without an end never
+//! leave. Events at a single instant, eg. messages, can name the same
+//! attribute as their start and end.
+//!
+//! ```ignore
+//! ctx.timeline.set_enabled(true);
+//! ctx.timeline.play();
+//! ctx.refresh(); // each frame
+//! let alpha = ctx.timeline.node_presence(node);
+//! ```
+
+use std::time::Instant;
+
+use crate::context::EngineContext;
+use crate::filter::FilterStack;
+use crate::graph::{Attributes, EdgeId, Graph, NodeId, Symbol};
+use crate::i18n::tr;
+use crate::imgui::{SliderFlags, Ui};
+use crate::subsystems::panels::Panel;
+
+/// Time playing through the whole range takes at normal speed, in seconds.
+pub const PLAYBACK_DURATION: f64 = 30.0;
+
+/// Which elements are alive during a window of time.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TimeWindow {
+    /// Attributes holding when elements appear and disappear.
+    pub start: Symbol,
+    pub end: Symbol,
+    /// First and last time of the window, both included.
+    pub from: f64,
+    pub to: f64,
+}
+
+/// Playback of a temporal graph, see the [module documentation](self).
+///
+/// Call [`Self::update()`] once per frame, before the filters are updated.
+#[derive(Clone, Debug)]
+pub struct Timeline {
+    /// Attributes holding when elements appear and disappear.
+    pub start_attribute: String,
+    pub end_attribute: String,
+    /// Length of the window, as a fraction of the time range.
+    pub window: f64,
+    /// Show everything which appeared until the cursor, whatever the window.
+    pub cumulative: bool,
+    /// Time elements take to fade in and out, as a fraction of the time range.
+    pub fade: f64,
+    /// Multiplies the playback rate.
+    pub speed: f64,
+    /// Start over once the end of the range is reached, rather than stopping.
+    pub looping: bool,
+    enabled: bool,
+    /// End of the window.
+    cursor: f64,
+    /// When the cursor last moved while playing.
+    playing: Option<Instant>,
+    /// Lifetime of each node and edge, by index.
+    nodes: Vec<[f64; 2]>,
+    edges: Vec<[f64; 2]>,
+    /// Finite starts and ends of every element, sorted.
+    starts: Vec<f64>,
+    ends: Vec<f64>,
+    /// First and last time in the graph, if any.
+    range: Option<[f64; 2]>,
+    /// Generation of the graph, and the attributes, the lifetimes were read
+    /// with.
+    indexed: Option<(u64, Symbol, Symbol)>,
+    /// Which elements the window applied to the filters shows, as numbers of
+    /// starts and ends passed.
+    applied: Option<(usize, usize)>,
+}
+
+/// Scrubs, plays, and tunes the [`EngineContext::timeline`].
+pub struct TimelinePanel;
+
+impl TimeWindow {
+    /// When the element with `attributes` appears and disappears, infinite if
+    /// it has no such attribute.
+    pub fn lifetime(start: Symbol, end: Symbol, attributes: &Attributes) -> [f64; 2] {
+        let time = |key, missing| {
+            attributes
+                .get(key)
+                .and_then(|value| {
+                    value
+                        .as_f64()
+                        .or_else(|| value.as_str()?.trim().parse().ok())
+                })
+                .filter(|time: &f64| !time.is_nan())
+                .unwrap_or(missing)
+        };
+        [time(start, f64::NEG_INFINITY), time(end, f64::INFINITY)]
+    }
+
+    /// Returns `true` if the element with `attributes` is alive at some time
+    /// of the window.
+    pub fn contains(&self, attributes: &Attributes) -> bool {
+        let [start, end] = Self::lifetime(self.start, self.end, attributes);
+        start <= self.to && end >= self.from
+    }
+}
+
+impl Timeline {
+    /// Create a disabled timeline.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Enable or disable the timeline. Disabling it shows every element again.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.playing = None;
+        }
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing.is_some()
+    }
+
+    /// Start playing, from the start of the range if the cursor is at its end.
+    pub fn play(&mut self) {
+        if let Some([min, max]) = self.range
+            && self.cursor >= max
+        {
+            self.cursor = min;
+        }
+        self.enabled = true;
+        self.playing = Some(Instant::now());
+    }
+
+    pub fn pause(&mut self) {
+        self.playing = None;
+    }
+
+    /// First and last time in the graph, as of the last [`Self::update()`],
+    /// `None` if it has no timestamps.
+    pub fn range(&self) -> Option<[f64; 2]> {
+        self.range
+    }
+
+    /// End of the window.
+    pub fn cursor(&self) -> f64 {
+        self.cursor
+    }
+
+    /// Move the end of the window to `time`, within the range.
+    pub fn seek(&mut self, time: f64) {
+        self.cursor = match self.range {
+            Some([min, max]) => time.clamp(min, max),
+            None => time,
+        };
+    }
+
+    /// First and last time of the window, `None` while the timeline is disabled
+    /// or the graph has no timestamps.
+    pub fn window(&self) -> Option<[f64; 2]> {
+        let [min, max] = self.range.filter(|_| self.enabled)?;
+        let from = match self.cumulative {
+            true => f64::NEG_INFINITY,
+            false => self.cursor - self.window * (max - min),
+        };
+        Some([from, self.cursor])
+    }
+
+    /// Read the lifetimes of the elements of `graph` if it changed, move the
+    /// cursor if playing, and hide from `filters` the elements out of the window
+    /// if they changed.
+    pub fn update(&mut self, graph: &Graph, filters: &mut FilterStack) {
+        let (start, end) = (
+            Symbol::new(&self.start_attribute),
+            Symbol::new(&self.end_attribute),
+        );
+        if self.indexed != Some((graph.generation(), start, end)) {
+            self.indexed = Some((graph.generation(), start, end));
+            self.index(graph, start, end);
+        }
+
+        if let (Some(last), Some([min, max])) = (self.playing, self.range) {
+            let now = Instant::now();
+            let elapsed = now.duration_since(last).as_secs_f64();
+            self.playing = Some(now);
+            self.cursor += elapsed * self.speed * (max - min) / PLAYBACK_DURATION;
+            if self.cursor >= max {
+                match self.looping {
+                    true => self.cursor = min,
+                    false => self.playing = None,
+                }
+            }
+        }
+        if let Some([min, max]) = self.range {
+            self.cursor = self.cursor.clamp(min, max);
+        }
+
+        let Some([from, to]) = self.window() else {
+            self.applied = None;
+            filters.set_time_window(None);
+            return;
+        };
+        // the visible elements only change when the window passes a time
+        let passed = (
+            self.starts.partition_point(|&t| t <= to),
+            self.ends.partition_point(|&t| t < from),
+        );
+        if self.applied != Some(passed) || filters.time_window().is_none() {
+            self.applied = Some(passed);
+            filters.set_time_window(Some(TimeWindow {
+                start,
+                end,
+                from,
+                to,
+            }));
+        }
+    }
+
+    /// How much `node` is there, from `0.0` as it appears or disappears to
+    /// `1.0` once it faded in, eg. to scale its size and opacity.
+    pub fn node_presence(&self, node: NodeId) -> f32 {
+        self.presence(self.nodes.get(node.index()))
+    }
+
+    /// How much `edge` is there, see [`Self::node_presence()`].
+    pub fn edge_presence(&self, edge: EdgeId) -> f32 {
+        self.presence(self.edges.get(edge.index()))
+    }
+
+    fn presence(&self, lifetime: Option<&[f64; 2]>) -> f32 {
+        let (Some(&[start, end]), Some([from, to]), Some([min, max])) =
+            (lifetime, self.window(), self.range)
+        else {
+            return 1.0;
+        };
+        let fade = self.fade * (max - min);
+        if fade <= 0.0 {
+            return 1.0;
+        }
+        let appeared = ((to - start) / fade).clamp(0.0, 1.0);
+        let leaving = ((end - from) / fade).clamp(0.0, 1.0);
+        appeared.min(leaving) as f32
+    }
+
+    /// Read the lifetime of every element of `graph`.
+    fn index(&mut self, graph: &Graph, start: Symbol, end: Symbol) {
+        let lifetime = |attributes: Option<&Attributes>| {
+            attributes.map_or([f64::NEG_INFINITY, f64::INFINITY], |a| {
+                TimeWindow::lifetime(start, end, a)
+            })
+        };
+        self.nodes = vec![[f64::NEG_INFINITY, f64::INFINITY]; graph.node_bound()];
+        for node in graph.nodes() {
+            self.nodes[node.index()] = lifetime(graph.node(node));
+        }
+        self.edges = vec![[f64::NEG_INFINITY, f64::INFINITY]; graph.edge_bound()];
+        for edge in graph.edges() {
+            self.edges[edge.index()] = lifetime(graph.edge(edge));
+        }
+
+        let lifetimes = || {
+            let nodes = graph.nodes().map(|node| self.nodes[node.index()]);
+            nodes.chain(graph.edges().map(|edge| self.edges[edge.index()]))
+        };
+        self.starts = lifetimes()
+            .map(|[s, _]| s)
+            .filter(|t| t.is_finite())
+            .collect();
+        self.ends = lifetimes()
+            .map(|[_, e]| e)
+            .filter(|t| t.is_finite())
+            .collect();
+        self.starts.sort_unstable_by(f64::total_cmp);
+        self.ends.sort_unstable_by(f64::total_cmp);
+
+        let first = self.starts.first().into_iter().chain(self.ends.first());
+        let last = self.starts.last().into_iter().chain(self.ends.last());
+        self.range = first
+            .copied()
+            .reduce(f64::min)
+            .zip(last.copied().reduce(f64::max))
+            .map(|(min, max)| [min, max]);
+        if self.range.is_none() {
+            self.playing = None;
+        }
+        self.applied = None;
+    }
+}
+
+impl Panel for TimelinePanel {
+    fn id(&self) -> &'static str {
+        "Timeline"
+    }
+
+    fn draw(&mut self, ui: &Ui, ctx: &mut EngineContext) {
+        let timeline = &mut ctx.timeline;
+        ui.set_next_item_width(120.0);
+        ui.input_text(tr("Start"), &mut timeline.start_attribute)
+            .hint(tr("attribute"))
+            .build();
+        ui.same_line();
+        ui.set_next_item_width(120.0);
+        ui.input_text(tr("End"), &mut timeline.end_attribute)
+            .hint(tr("attribute"))
+            .build();
+        let Some([min, max]) = timeline.range() else {
+            ui.text_disabled(tr("No element has a numeric start or end"));
+            return;
+        };
+
+        let mut enabled = timeline.is_enabled();
+        if ui.checkbox(tr("Enabled"), &mut enabled) {
+            timeline.set_enabled(enabled);
+        }
+        ui.same_line();
+        let playing = timeline.is_playing();
+        if ui.button(tr(if playing { "Pause" } else { "Play" })) {
+            match playing {
+                true => timeline.pause(),
+                false => timeline.play(),
+            }
+        }
+        ui.same_line();
+        if ui.button(tr("Rewind")) {
+            timeline.seek(min);
+        }
+        ui.same_line();
+        ui.checkbox(tr("Loop"), &mut timeline.looping);
+
+        ui.enabled(timeline.is_enabled(), || {
+            let mut cursor = timeline.cursor();
+            ui.set_next_item_width(-1.0);
+            if ui.slider("##cursor", min, max, &mut cursor) {
+                timeline.seek(cursor);
+            }
+            ui.slider_config(tr("Speed"), 0.1, 10.0)
+                .flags(SliderFlags::LOGARITHMIC)
+                .display_format("%.2fx")
+                .build(&mut timeline.speed);
+            ui.checkbox(tr("Cumulative"), &mut timeline.cumulative);
+            if !timeline.cumulative {
+                ui.slider_config(tr("Window"), 0.001, 1.0)
+                    .flags(SliderFlags::LOGARITHMIC)
+                    .build(&mut timeline.window);
+            }
+            ui.slider(tr("Fade"), 0.0, 0.2, &mut timeline.fade);
+        });
+
+        if let Some([from, to]) = timeline.window() {
+            match from.is_finite() {
+                true => ui.text(
+                    tr("Showing {from} to {to}")
+                        .replace("{from}", &format!("{from:.3}"))
+                        .replace("{to}", &format!("{to:.3}")),
+                ),
+                false => ui.text(tr("Showing until {to}").replace("{to}", &format!("{to:.3}"))),
+            }
+            let (nodes, edges) = ctx.filters.hidden();
+            ui.text_disabled(
+                tr("{nodes} nodes and {edges} edges hidden")
+                    .replace("{nodes}", &nodes.to_string())
+                    .replace("{edges}", &edges.to_string()),
+            );
+        }
+    }
+}
+
+impl std::default::Default for Timeline {
+    fn default() -> Self {
+        Self {
+            start_attribute: "start".into(),
+            end_attribute: "end".into(),
+            window: 0.1,
+            cumulative: false,
+            fade: 0.02,
+            speed: 1.0,
+            looping: false,
+            enabled: false,
+            cursor: 0.0,
+            playing: None,
+            nodes: Vec::new(),
+            edges: Vec::new(),
+            starts: Vec::new(),
+            ends: Vec::new(),
+            range: None,
+            indexed: None,
+            applied: None,
+        }
+    }
+}
@@ -0,0 +1,420 @@
+//! # 3D View
+//!
+//! An optional mode drawing the active document's graph in three dimensions,
+//! toggled per document with [`set_enabled()`]:
+//! - a force-directed layout in 3D, seeded from the 2D positions, runs instead
+//!   of the [2D one](crate::layout::force::ForceLayout), which is paused
+//!   meanwhile; every pair of nodes repels exactly up to [`EXACT_MAX_NODES`]
+//!   nodes, and a sample of [`REPULSION_SAMPLES`] nodes stands for the rest
+//!   beyond
+//! - an [`OrbitCamera`] turns about the middle of the layout as the cursor is
+//!   dragged, see [`View3d::begin_orbit()`]; zooming and panning keep moving
+//!   the 2D camera, which looks at the projected scene
+//! - every frame, [`update()`] projects the layout with perspective into
+//!   [`EngineContext::positions`], so picking, hovering, labels and the minimap
+//!   work on the projected scene as they do in 2D
+//! - nodes are billboards facing the viewer, their radius scaled by
+//!   [`View3d::node_scale()`]; nodes and edges fade with depth by
+//!   [`View3d::node_fade()`] and [`View3d::edge_fade()`]
+//!
+//! Turning the mode off puts the nodes back where they were in 2D.
+//!
+//! ```ignore
+//! view3d::set_enabled(&mut ctx, true);
+//! ctx.view3d.begin_orbit(cursor);
+//! view3d::update(&mut ctx);
+//! let radius = look.radius * ctx.view3d.node_scale(node);
+//! ```
+
+use crate::context::EngineContext;
+use crate::graph::{Graph, NodeId};
+use crate::layout::Positions;
+use crate::layout::force::ForceSettings;
+
+/// Number of visible nodes up to which repulsion is computed between every
+/// pair of nodes.
+pub const EXACT_MAX_NODES: usize = 1_500;
+/// Nodes each node is repelled by beyond [`EXACT_MAX_NODES`].
+pub const REPULSION_SAMPLES: usize = 64;
+/// Rotation of the [`OrbitCamera`] per point the cursor moves, in radians.
+pub const ORBIT_SPEED: f32 = 0.01;
+/// Share of their opacity the farthest nodes and edges lose.
+pub const DEPTH_FADE: f32 = 0.75;
+/// Largest pitch of the [`OrbitCamera`], just short of looking straight down
+/// or up.
+const MAX_PITCH: f32 = 89.0 * std::f32::consts::PI / 180.0;
+/// Distance of the camera from the middle of the layout, relative to the
+/// layout's radius.
+const CAMERA_DISTANCE: f32 = 2.5;
+/// Nearest depth points are projected from, relative to the camera distance,
+/// so points behind the camera do not blow up.
+const NEAR: f32 = 0.1;
+/// Lowest temperature, relative to the edge length. The layout counts as
+/// settled once cooled down to it.
+const MIN_TEMPERATURE: f32 = 0.01;
+
+/// The 3D state of a document, see the [module documentation](self).
+#[derive(Clone, Debug, Default)]
+pub struct View3d {
+    enabled: bool,
+    /// The 2D positions and whether the 2D layout was paused, as they were
+    /// when the mode was turned on.
+    flat: Option<(Positions, bool)>,
+    pub camera: OrbitCamera,
+    /// Position of each node in 3D, indexed by [`NodeId`].
+    points: Vec<Option<[f32; 3]>>,
+    /// Largest distance a node may move in one iteration.
+    temperature: f32,
+    settled: bool,
+    /// Generation of the visible graph the layout was last stepped with.
+    generation: u64,
+    /// Last cursor position applied while orbiting.
+    orbiting: Option<[f32; 2]>,
+    /// Fit the 2D camera to the projected scene once projected.
+    fit: bool,
+    /// Perspective scale and depth fade of each node as of the last
+    /// projection, indexed by [`NodeId`].
+    projected: Vec<(f32, f32)>,
+    /// Scratch buffer, kept to avoid reallocating every iteration.
+    displacement: Vec<[f32; 3]>,
+}
+
+/// A camera looking at a target from a distance, turned about it by a yaw
+/// around the vertical axis and a pitch above or below the horizon.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct OrbitCamera {
+    pub yaw: f32,
+    /// Clamped to ±89°.
+    pub pitch: f32,
+    pub distance: f32,
+    pub target: [f32; 3],
+}
+
+impl OrbitCamera {
+    /// Turn by the cursor moving `delta` points.
+    pub fn orbit(&mut self, delta: [f32; 2]) {
+        self.yaw += delta[0] * ORBIT_SPEED;
+        self.pitch = (self.pitch + delta[1] * ORBIT_SPEED).clamp(-MAX_PITCH, MAX_PITCH);
+    }
+
+    /// Project `point` onto the plane through the target facing the camera,
+    /// returning the projected point, its perspective scale, and its depth
+    /// from the camera.
+    pub fn project(&self, point: [f32; 3]) -> ([f32; 2], f32, f32) {
+        let v = [
+            point[0] - self.target[0],
+            point[1] - self.target[1],
+            point[2] - self.target[2],
+        ];
+        // yaw about the vertical axis, then pitch about the horizontal one
+        let (sin, cos) = self.yaw.sin_cos();
+        let (x, z) = (v[0] * cos - v[2] * sin, v[0] * sin + v[2] * cos);
+        let (sin, cos) = self.pitch.sin_cos();
+        let (y, z) = (v[1] * cos - z * sin, v[1] * sin + z * cos);
+        let depth = (self.distance + z).max(self.distance * NEAR);
+        let scale = self.distance / depth;
+        ([x * scale, y * scale], scale, depth)
+    }
+}
+
+impl View3d {
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Start turning the camera with the cursor, which is at `cursor`.
+    pub fn begin_orbit(&mut self, cursor: [f32; 2]) {
+        self.orbiting = Some(cursor);
+    }
+
+    /// Stop turning the camera.
+    pub fn end_orbit(&mut self) {
+        self.orbiting = None;
+    }
+
+    /// Returns `true` while the layout moves or the camera is being turned.
+    pub fn is_animating(&self) -> bool {
+        self.enabled && (!self.settled || self.orbiting.is_some())
+    }
+
+    /// Turn the mode off without putting the nodes back, eg. once the graph was
+    /// replaced, returning whether the 2D layout was paused before it was on.
+    pub(crate) fn reset(&mut self) -> Option<bool> {
+        let flat = std::mem::take(self).flat;
+        flat.map(|(_, paused)| paused)
+    }
+
+    /// Restart the cooling schedule of the 3D layout, eg. after the graph or the
+    /// settings changed.
+    pub fn reheat(&mut self, edge_length: f32) {
+        self.temperature = edge_length * 2.0;
+        self.settled = false;
+    }
+
+    /// Factor the radius of `node` is scaled by for its depth, `1.0` at the
+    /// middle of the layout or outside the mode.
+    pub fn node_scale(&self, node: NodeId) -> f32 {
+        self.projection(node).0
+    }
+
+    /// Opacity `node` keeps for its depth, from `1.0` for the nearest nodes
+    /// down to `1.0 - DEPTH_FADE` for the farthest.
+    pub fn node_fade(&self, node: NodeId) -> f32 {
+        self.projection(node).1
+    }
+
+    /// Opacity an edge between `source` and `target` keeps for its depth, see
+    /// [`Self::node_fade()`].
+    pub fn edge_fade(&self, source: NodeId, target: NodeId) -> f32 {
+        (self.node_fade(source) + self.node_fade(target)) * 0.5
+    }
+
+    fn projection(&self, node: NodeId) -> (f32, f32) {
+        match self.enabled {
+            true => self
+                .projected
+                .get(node.index())
+                .copied()
+                .unwrap_or((1.0, 1.0)),
+            false => (1.0, 1.0),
+        }
+    }
+
+    /// Give a 3D position to every node of `graph` which does not have one yet,
+    /// from its 2D position and a depth derived from its id, and forget those
+    /// of nodes no longer in it.
+    fn sync(&mut self, graph: &Graph, positions: &Positions, edge_length: f32) {
+        let mut points = vec![None; graph.node_bound()];
+        for node in graph.nodes() {
+            let kept = self.points.get(node.index()).copied().flatten();
+            points[node.index()] = kept.or_else(|| {
+                let [x, y] = positions.get(node).unwrap_or_default();
+                Some([x, y, seed_depth(node) * edge_length])
+            });
+        }
+        self.points = points;
+    }
+
+    /// Run one iteration of the 3D layout over `graph`, see the
+    /// [`ForceLayout`](crate::layout::force::ForceLayout) for the forces.
+    fn iterate(&mut self, graph: &Graph, settings: &ForceSettings) {
+        let k = settings.edge_length.max(f32::EPSILON);
+        let nodes: Vec<(NodeId, [f32; 3])> = graph
+            .nodes()
+            .filter_map(|node| Some((node, self.points[node.index()]?)))
+            .collect();
+        let n = nodes.len();
+        self.displacement.clear();
+        self.displacement.resize(graph.node_bound(), [0.0; 3]);
+
+        // repulsion, k³/d² so it balances the d²/k attraction at d = k
+        let strength = settings.repulsion * k * k * k;
+        let exact = n <= EXACT_MAX_NODES;
+        let (samples, weight) = match exact {
+            true => (n, 1.0),
+            false => (REPULSION_SAMPLES, n as f32 / REPULSION_SAMPLES as f32),
+        };
+        for (i, &(node, a)) in nodes.iter().enumerate() {
+            let mut d = [0.0; 3];
+            for s in 0..samples {
+                // a stride coprime with most sizes walks the nodes evenly
+                let j = match exact {
+                    true => s,
+                    false => (i + 1 + s * 7919) % n,
+                };
+                if j == i {
+                    continue;
+                }
+                let b = nodes[j].1;
+                let delta = [a[0] - b[0], a[1] - b[1], a[2] - b[2]];
+                let distance2 = dot(delta, delta).max(k * k * 1e-4);
+                let push = strength * weight / (distance2 * distance2.sqrt());
+                add_scaled(&mut d, delta, push);
+            }
+            let distance = dot(a, a).sqrt();
+            if distance > f32::EPSILON {
+                let pull = settings.gravity * k / distance;
+                add_scaled(&mut d, a, -pull);
+            }
+            self.displacement[node.index()] = d;
+        }
+
+        // attraction along edges
+        for edge in graph.edges() {
+            let (source, target) = graph.endpoints(edge).unwrap();
+            let (Some(a), Some(b)) = (self.points[source.index()], self.points[target.index()])
+            else {
+                continue;
+            };
+            let delta = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+            let pull = dot(delta, delta).sqrt() / k;
+            add_scaled(&mut self.displacement[source.index()], delta, pull);
+            add_scaled(&mut self.displacement[target.index()], delta, -pull);
+        }
+
+        // move, limited by the temperature
+        for (node, _) in &nodes {
+            let d = self.displacement[node.index()];
+            let distance = dot(d, d).sqrt();
+            if distance <= f32::EPSILON {
+                continue;
+            }
+            let step = distance.min(self.temperature) / distance;
+            if let Some(point) = &mut self.points[node.index()] {
+                add_scaled(point, d, step);
+            }
+        }
+        self.temperature *= settings.cooling;
+        if self.temperature < MIN_TEMPERATURE * k {
+            self.settled = true;
+        }
+    }
+
+    /// Project the 3D positions of the nodes of `graph` into `positions`,
+    /// looking at the middle of the layout.
+    fn project(&mut self, graph: &Graph, positions: &mut Positions) {
+        let mut sum = [0.0; 3];
+        let mut count = 0;
+        for point in self.points.iter().flatten() {
+            add_scaled(&mut sum, *point, 1.0);
+            count += 1;
+        }
+        if count == 0 {
+            return;
+        }
+        self.camera.target = sum.map(|s| s / count as f32);
+        let radius = self
+            .points
+            .iter()
+            .flatten()
+            .map(|p| {
+                let t = self.camera.target;
+                let v = [p[0] - t[0], p[1] - t[1], p[2] - t[2]];
+                dot(v, v)
+            })
+            .fold(0.0_f32, f32::max)
+            .sqrt();
+        self.camera.distance = radius.max(f32::EPSILON) * CAMERA_DISTANCE;
+
+        self.projected.clear();
+        self.projected.resize(self.points.len(), (1.0, 1.0));
+        let mut depths = Vec::with_capacity(count);
+        for node in graph.nodes() {
+            let Some(point) = self.points[node.index()] else {
+                continue;
+            };
+            let (at, scale, depth) = self.camera.project(point);
+            positions.set(node, at);
+            self.projected[node.index()] = (scale, depth);
+            depths.push(depth);
+        }
+        let near = depths.iter().copied().fold(f32::INFINITY, f32::min);
+        let far = depths.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+        for projected in &mut self.projected {
+            let t = match far > near {
+                true => ((projected.1 - near) / (far - near)).clamp(0.0, 1.0),
+                false => 0.0,
+            };
+            projected.1 = 1.0 - DEPTH_FADE * t;
+        }
+    }
+}
+
+/// Turn the 3D view of the active document on or off.
+///
+/// Turning it on pauses the 2D layout and seeds the 3D one from the 2D
+/// positions; turning it off puts the nodes back and resumes the 2D layout if
+/// it was running.
+pub fn set_enabled(ctx: &mut EngineContext, enabled: bool) {
+    if ctx.view3d.enabled == enabled {
+        return;
+    }
+    ctx.force.sync_positions(&mut ctx.positions);
+    let view = &mut ctx.view3d;
+    if enabled {
+        view.flat = Some((ctx.positions.clone(), ctx.force.is_paused()));
+        ctx.force.set_paused(true);
+        ctx.positions.clear_bends();
+        view.points.clear();
+        view.generation = 0;
+        view.fit = true;
+        view.enabled = true;
+        view.reheat(ctx.force.settings.edge_length);
+        return;
+    }
+    view.enabled = false;
+    view.orbiting = None;
+    view.points.clear();
+    view.projected.clear();
+    if let Some((flat, paused)) = view.flat.take() {
+        for node in ctx.graph.nodes() {
+            if let Some(point) = flat.get(node) {
+                ctx.positions.set(node, point);
+            }
+        }
+        for edge in ctx.graph.edges() {
+            let bends = flat.bends(edge);
+            if !bends.is_empty() {
+                ctx.positions.set_bends(edge, bends.to_vec());
+            }
+        }
+        ctx.force.set_paused(paused);
+    }
+    ctx.camera
+        .fit_to_graph(ctx.filters.graph(&ctx.graph), &ctx.positions);
+}
+
+/// Step the 3D layout, turn the camera with the cursor, and project the nodes
+/// into [`EngineContext::positions`], once per frame while the mode is on.
+pub fn update(ctx: &mut EngineContext) {
+    let view = &mut ctx.view3d;
+    if !view.enabled {
+        return;
+    }
+    let graph = ctx.filters.graph(&ctx.graph);
+    let edge_length = ctx.force.settings.edge_length;
+    if view.generation != graph.generation() {
+        view.generation = graph.generation();
+        view.sync(graph, &ctx.positions, edge_length);
+        view.reheat(edge_length);
+    }
+    let mut moved = false;
+    if graph.is_empty() {
+        view.settled = true;
+    } else if !view.settled {
+        view.iterate(graph, &ctx.force.settings);
+        moved = true;
+    }
+    if let Some(last) = view.orbiting {
+        let cursor = ctx.input.cursor();
+        view.camera
+            .orbit([cursor[0] - last[0], cursor[1] - last[1]]);
+        view.orbiting = Some(cursor);
+        moved |= cursor != last;
+    }
+    if moved || view.fit {
+        view.project(graph, &mut ctx.positions);
+    }
+    if std::mem::take(&mut view.fit) {
+        ctx.camera.fit_to_graph(graph, &ctx.positions);
+    }
+}
+
+/// Depth from `-1.0` to `1.0` given to `node` when placed in 3D, the same
+/// every time.
+fn seed_depth(node: NodeId) -> f32 {
+    // a multiplicative hash spreads consecutive ids apart
+    let hash = (node.index() as u32).wrapping_mul(2_654_435_761);
+    hash as f32 / u32::MAX as f32 * 2.0 - 1.0
+}
+
+/// Add `v` times `factor` to `to`.
+fn add_scaled(to: &mut [f32; 3], v: [f32; 3], factor: f32) {
+    for (to, v) in to.iter_mut().zip(v) {
+        *to += v * factor;
+    }
+}
+
+fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
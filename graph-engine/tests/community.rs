@@ -0,0 +1,161 @@
+//! Community detection finds the known communities of small graphs.
+
+use graph_engine::algo::{Communities, leiden};
+use graph_engine::graph::{Directedness, Graph, NodeId};
+
+/// Zachary's karate club, by the 1-based members of each friendship.
+const KARATE_CLUB: [(usize, usize); 78] = [
+    (1, 2),
+    (1, 3),
+    (1, 4),
+    (1, 5),
+    (1, 6),
+    (1, 7),
+    (1, 8),
+    (1, 9),
+    (1, 11),
+    (1, 12),
+    (1, 13),
+    (1, 14),
+    (1, 18),
+    (1, 20),
+    (1, 22),
+    (1, 32),
+    (2, 3),
+    (2, 4),
+    (2, 8),
+    (2, 14),
+    (2, 18),
+    (2, 20),
+    (2, 22),
+    (2, 31),
+    (3, 4),
+    (3, 8),
+    (3, 9),
+    (3, 10),
+    (3, 14),
+    (3, 28),
+    (3, 29),
+    (3, 33),
+    (4, 8),
+    (4, 13),
+    (4, 14),
+    (5, 7),
+    (5, 11),
+    (6, 7),
+    (6, 11),
+    (6, 17),
+    (7, 17),
+    (9, 31),
+    (9, 33),
+    (9, 34),
+    (10, 34),
+    (14, 34),
+    (15, 33),
+    (15, 34),
+    (16, 33),
+    (16, 34),
+    (19, 33),
+    (19, 34),
+    (20, 34),
+    (21, 33),
+    (21, 34),
+    (23, 33),
+    (23, 34),
+    (24, 26),
+    (24, 28),
+    (24, 30),
+    (24, 33),
+    (24, 34),
+    (25, 26),
+    (25, 28),
+    (25, 32),
+    (26, 32),
+    (27, 30),
+    (27, 34),
+    (28, 34),
+    (29, 32),
+    (29, 34),
+    (30, 33),
+    (30, 34),
+    (31, 33),
+    (31, 34),
+    (32, 33),
+    (32, 34),
+    (33, 34),
+];
+
+/// An undirected graph of `nodes` nodes and the 1-based `edges`.
+fn graph(nodes: usize, edges: &[(usize, usize)]) -> (Graph, Vec<NodeId>) {
+    let mut graph = Graph::new(Directedness::Undirected);
+    let ids: Vec<NodeId> = (0..nodes).map(|_| graph.add_node()).collect();
+    for &(s, t) in edges {
+        graph.add_edge(ids[s - 1], ids[t - 1]).unwrap();
+    }
+    (graph, ids)
+}
+
+/// Panics unless every community is connected by its own edges.
+fn assert_connected(graph: &Graph, communities: &Communities) {
+    for (c, members) in communities.iter().enumerate() {
+        let mut reached = vec![members[0]];
+        let mut next = 0;
+        while let Some(&node) = reached.get(next) {
+            next += 1;
+            for edge in graph.edges() {
+                let (s, t) = graph.endpoints(edge).unwrap();
+                let other = match node {
+                    _ if s == node => t,
+                    _ if t == node => s,
+                    _ => continue,
+                };
+                if communities.community(other) == Some(c) && !reached.contains(&other) {
+                    reached.push(other);
+                }
+            }
+        }
+        assert_eq!(
+            reached.len(),
+            members.len(),
+            "community {c} is disconnected"
+        );
+    }
+}
+
+#[test]
+fn two_triangles_split_at_their_bridge() {
+    let (graph, ids) = graph(6, &[(1, 2), (2, 3), (3, 1), (4, 5), (5, 6), (6, 4), (3, 4)]);
+    let communities = leiden(&graph, None);
+    assert_eq!(communities.count(), 2);
+    assert_eq!(communities.nodes(0), &ids[..3]);
+    assert_eq!(communities.nodes(1), &ids[3..]);
+    // 2 * (3/7 - (7/14)^2)
+    assert!((communities.modularity() - 5.0 / 14.0).abs() < 1e-9);
+}
+
+#[test]
+fn karate_club_is_near_its_best_partition() {
+    let (graph, ids) = graph(34, &KARATE_CLUB);
+    let communities = leiden(&graph, None);
+    // the best partition has 4 communities and a modularity of 0.4198
+    assert!(
+        communities.modularity() > 0.415,
+        "{}",
+        communities.modularity()
+    );
+    assert!((3..=4).contains(&communities.count()));
+    assert_connected(&graph, &communities);
+    // the instructor and the administrator end up apart
+    assert_ne!(
+        communities.community(ids[0]),
+        communities.community(ids[33])
+    );
+}
+
+#[test]
+fn edgeless_graph_is_singletons() {
+    let (graph, _) = graph(3, &[]);
+    let communities = leiden(&graph, None);
+    assert_eq!(communities.count(), 3);
+    assert_eq!(communities.modularity(), 0.0);
+}
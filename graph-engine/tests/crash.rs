@@ -0,0 +1,58 @@
+//! Panics the application outlives are not reported as crashes to recover
+//! from on the next launch.
+
+use std::panic::AssertUnwindSafe;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+
+use graph_engine::jobs::{JobPool, Priority, Progress};
+use graph_engine::subsystems::crash::{self, CrashReporter};
+
+/// The directory reports are written to, locked for the test panicking.
+fn reports_dir() -> MutexGuard<'static, PathBuf> {
+    static DIR: OnceLock<Mutex<PathBuf>> = OnceLock::new();
+    let dir = DIR.get_or_init(|| {
+        let dir = std::env::temp_dir().join(format!("graph-engine-crash-{}", std::process::id()));
+        CrashReporter::install(&dir);
+        Mutex::new(dir)
+    });
+    dir.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn report_count(dir: &Path) -> usize {
+    crash::reports(dir).unwrap().len()
+}
+
+#[test]
+fn caught_panic_is_not_reported() {
+    let dir = reports_dir();
+    let before = report_count(&dir);
+    assert!(crash::catch_unwind(|| panic!("caught")).is_err());
+    assert_eq!(report_count(&dir), before);
+    assert_eq!(crash::take_pending(&*dir), None);
+}
+
+#[test]
+fn panic_on_another_thread_is_not_pending() {
+    let dir = reports_dir();
+    let before = report_count(&dir);
+    let thread = std::thread::spawn(|| panic!("on a thread"));
+    assert!(thread.join().is_err());
+    assert_eq!(report_count(&dir), before + 1);
+    assert_eq!(crash::take_pending(&*dir), None);
+}
+
+#[test]
+fn panicking_job_is_not_reported() {
+    let dir = reports_dir();
+    let before = report_count(&dir);
+    let jobs = JobPool::new();
+    let progress = Arc::new(Progress::new());
+    let job = jobs.spawn("Panic", Priority::Normal, &progress, || panic!("in a job"));
+    assert!(job.join().is_err());
+    // the panic of the map itself is for its caller to catch
+    let map = AssertUnwindSafe(|| jobs.map(4, |i| assert!(i < 2)));
+    assert!(crash::catch_unwind(map).is_err());
+    assert_eq!(report_count(&dir), before);
+    assert_eq!(crash::take_pending(&*dir), None);
+}
@@ -0,0 +1,26 @@
+//! Generated graphs have the size their parameters describe.
+
+use graph_engine::generate::Generator;
+
+#[test]
+fn tree_without_branching_is_a_root() {
+    let generator = Generator::Tree {
+        branching: 0,
+        depth: usize::MAX,
+    };
+    assert_eq!(generator.size(), Some((1, 0)));
+    let graph = generator.generate(0).unwrap();
+    assert_eq!((graph.node_count(), graph.edge_count()), (1, 0));
+}
+
+#[test]
+fn tree_has_its_size() {
+    let generator = Generator::Tree {
+        branching: 3,
+        depth: 4,
+    };
+    let graph = generator.generate(0).unwrap();
+    let size = (graph.node_count(), graph.edge_count());
+    assert_eq!(Some(size), generator.size());
+    assert_eq!(size, (1 + 3 + 9 + 27 + 81, 120));
+}
@@ -0,0 +1,108 @@
+//! Graph files read back what was written, and reject malformed input.
+
+use graph_engine::graph::{AttrValue, Attributes, Directedness, Graph};
+use graph_engine::io::ID_ATTR;
+use graph_engine::io::csv::{self, CsvOptions};
+use graph_engine::io::dot;
+
+#[test]
+fn dot_round_trips_escapes() {
+    let names = [
+        "plain",
+        "with \"quotes\"",
+        "back\\slash",
+        "two\nlines",
+        "\\n",
+    ];
+    let mut graph = Graph::new(Directedness::Directed);
+    let nodes: Vec<_> = names
+        .iter()
+        .map(|name| {
+            let attrs = Attributes::from_iter([(ID_ATTR, *name), ("label", *name)]);
+            graph.add_node_with(attrs)
+        })
+        .collect();
+    for pair in nodes.windows(2) {
+        graph.add_edge(pair[0], pair[1]).unwrap();
+    }
+
+    let mut written = Vec::new();
+    dot::write(&graph, &mut written).unwrap();
+    let text = String::from_utf8(written).unwrap();
+    assert_eq!(text.lines().count(), 2 + names.len() + nodes.len() - 1);
+
+    let read = dot::read(&text).unwrap();
+    assert_eq!(read.node_count(), names.len());
+    assert_eq!(read.edge_count(), names.len() - 1);
+    let labels: Vec<_> = read
+        .nodes()
+        .map(|node| read.node(node).unwrap().get("label").cloned())
+        .collect();
+    let expected: Vec<_> = names
+        .iter()
+        .map(|name| Some(AttrValue::String(name.to_string())))
+        .collect();
+    assert_eq!(labels, expected);
+}
+
+#[test]
+fn dot_limits_subgraph_depth() {
+    let nested = |depth: usize| {
+        format!(
+            "graph {{ {} a {} }}",
+            "{ ".repeat(depth),
+            "} ".repeat(depth)
+        )
+    };
+    assert!(dot::read(&nested(dot::MAX_DEPTH)).is_ok());
+    let error = dot::read(&nested(100_000)).unwrap_err();
+    assert!(error.to_string().contains("nested"), "{error}");
+}
+
+#[test]
+fn csv_rejects_empty_node_names() {
+    let options = CsvOptions {
+        delimiter: ',',
+        has_header: false,
+        source: 0,
+        target: 1,
+        weight: None,
+        directed: true,
+    };
+    let graph = csv::read("a,b\nb,c\n".as_bytes(), &options).unwrap();
+    assert_eq!(graph.node_count(), 3);
+    for rows in ["a,b\n,c\n", "a,b\nb, \n"] {
+        let error = csv::read(rows.as_bytes(), &options).unwrap_err();
+        assert!(error.to_string().contains("line 2"), "{error}");
+    }
+}
+
+#[test]
+fn csv_types_only_numeric_literals() {
+    let options = CsvOptions {
+        delimiter: ',',
+        has_header: true,
+        source: 0,
+        target: 1,
+        weight: Some(2),
+        directed: true,
+    };
+    let rows = "source,target,weight,value\na,b,1.5,nan\nb,c,2,inf\nc,a,-1e3,-2.5e-1\n";
+    let graph = csv::read(rows.as_bytes(), &options).unwrap();
+    let values: Vec<_> = graph
+        .edges()
+        .map(|edge| graph.edge(edge).unwrap().get("value").cloned().unwrap())
+        .collect();
+    assert_eq!(
+        values,
+        [
+            AttrValue::String("nan".to_owned()),
+            AttrValue::String("inf".to_owned()),
+            AttrValue::Float(-0.25),
+        ]
+    );
+    for weight in ["nan", "inf", "-infinity"] {
+        let rows = format!("source,target,weight\na,b,{weight}\n");
+        assert!(csv::read(rows.as_bytes(), &options).is_err(), "{weight}");
+    }
+}
@@ -0,0 +1,65 @@
+//! The Neo4j connector reaches endpoints through the HTTP client.
+
+#![cfg(feature = "neo4j")]
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::thread::JoinHandle;
+
+use graph_engine::io::neo4j::Endpoint;
+use serde_json::Map;
+
+/// A server answering one request with a node, returning the request's head.
+fn serve_once(listener: TcpListener) -> JoinHandle<String> {
+    std::thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut head = String::new();
+        let mut length = 0;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if let Some(value) = line.strip_prefix("Content-Length:") {
+                length = value.trim().parse().unwrap();
+            }
+            if line.trim().is_empty() {
+                break;
+            }
+            head.push_str(&line);
+        }
+        reader.read_exact(&mut vec![0; length]).unwrap();
+        let body = r#"{"results": [{"data": [{"graph": {"nodes": [{"id": "1",
+            "labels": ["Person"], "properties": {"name": "Ada"}}],
+            "relationships": []}}]}], "errors": []}"#;
+        write!(
+            &stream,
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        )
+        .unwrap();
+        head
+    })
+}
+
+#[test]
+fn queries_ipv6_endpoint() {
+    // without an IPv6 loopback interface, there is nothing to test
+    let Ok(listener) = TcpListener::bind("[::1]:0") else {
+        return;
+    };
+    let port = listener.local_addr().unwrap().port();
+    let server = serve_once(listener);
+
+    let endpoint = Endpoint::new("local", &format!("http://[::1]:{port}"));
+    let graph = endpoint.query("MATCH (n) RETURN n", &Map::new()).unwrap();
+    assert_eq!(graph.node_count(), 1);
+    let head = server.join().unwrap();
+    assert!(head.contains(&format!("Host: [::1]:{port}\r\n")), "{head}");
+}
+
+#[test]
+fn rejects_other_schemes() {
+    let endpoint = Endpoint::new("local", "ftp://localhost:7474");
+    let error = endpoint.query("MATCH (n) RETURN n", &Map::new());
+    assert!(error.unwrap_err().to_string().contains("Unsupported URL"));
+}
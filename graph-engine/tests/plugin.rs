@@ -0,0 +1,63 @@
+//! Plugin libraries share the application's attribute keys.
+
+use std::path::PathBuf;
+use std::process::Command;
+
+use graph_engine::context::EngineContext;
+use graph_engine::graph::Symbol;
+use graph_engine::plugin;
+use graph_engine::subsystems::input::{InputMap, InputMapper};
+
+/// Build the plugin of `tests/plugin`, returning the path of its library.
+fn build_plugin() -> PathBuf {
+    let status = Command::new(env!("CARGO"))
+        .args(["build", "--quiet", "--package", "graph-test-plugin"])
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .status()
+        .unwrap();
+    assert!(status.success(), "Failed to build the test plugin");
+    // this test runs from `<target>/debug/deps`
+    let exe = std::env::current_exe().unwrap();
+    let name = format!(
+        "{}graph_test_plugin{}",
+        std::env::consts::DLL_PREFIX,
+        std::env::consts::DLL_SUFFIX
+    );
+    exe.parent().unwrap().parent().unwrap().join(name)
+}
+
+#[test]
+fn plugin_graph_uses_application_keys() {
+    let library = build_plugin();
+    let dir = std::env::temp_dir().join(format!("graph-engine-plugins-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::copy(&library, dir.join(library.file_name().unwrap())).unwrap();
+    let file = dir.join("nodes.test-graph");
+    std::fs::write(&file, "Ada\nGrace\n").unwrap();
+
+    // interned by the application before the plugin is loaded
+    let label = Symbol::new("label");
+    let mut ctx = EngineContext::new(InputMapper::new(InputMap::default()));
+    plugin::discover(&mut ctx, &dir);
+    let loader = ctx
+        .loader_for(&file)
+        .expect("the plugin's loader is registered");
+    let graph = loader
+        .read(&file, &mut std::fs::File::open(&file).unwrap())
+        .unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let labels: Vec<_> = graph
+        .nodes()
+        .map(|n| graph.node(n).unwrap().get(label).unwrap().to_string())
+        .collect();
+    assert_eq!(labels, ["Ada", "Grace"]);
+    let (key, _) = graph
+        .node(graph.nodes().next().unwrap())
+        .unwrap()
+        .iter()
+        .next()
+        .unwrap();
+    assert_eq!(key, Symbol::new("plugin_only_key"));
+    assert_eq!(key.as_str(), "plugin_only_key");
+}
@@ -0,0 +1,49 @@
+//! A plugin loading `.test-graph` files, one node per line with its label,
+//! for the plugin tests of `graph-engine`.
+
+use std::io::Read;
+use std::path::Path;
+
+use graph_engine::graph::{Attributes, Graph};
+use graph_engine::io::loader::GraphLoader;
+use graph_engine::plugin::{GraphPlugin, PluginRegistrar};
+
+struct TestPlugin;
+
+struct LineLoader;
+
+impl GraphPlugin for TestPlugin {
+    fn name(&self) -> &str {
+        "Test"
+    }
+
+    fn register(&mut self, registrar: &mut PluginRegistrar) {
+        registrar.loader(LineLoader);
+    }
+}
+
+impl GraphLoader for LineLoader {
+    fn name(&self) -> &str {
+        "Test graph"
+    }
+
+    fn accepts(&self, path: &Path) -> bool {
+        path.extension().is_some_and(|e| e == "test-graph")
+    }
+
+    fn read(&self, _path: &Path, reader: &mut dyn Read) -> Result<Graph, String> {
+        let mut text = String::new();
+        reader
+            .read_to_string(&mut text)
+            .map_err(|e| e.to_string())?;
+        let mut graph = Graph::undirected();
+        for line in text.lines() {
+            // a key the application has not interned yet, and one it has
+            let attrs = Attributes::from_iter([("plugin_only_key", line), ("label", line)]);
+            graph.add_node_with(attrs);
+        }
+        Ok(graph)
+    }
+}
+
+graph_engine::export_plugin!(|| TestPlugin);
@@ -0,0 +1,44 @@
+//! The remote control server refuses requests before handing them to the
+//! browser.
+
+#![cfg(feature = "http")]
+
+use std::io::{Read, Write};
+use std::net::TcpStream;
+
+use graph_engine::remote::RemoteServer;
+
+/// The status line the server answers `request` with.
+fn status(server: &RemoteServer, request: &[u8]) -> String {
+    let mut stream = TcpStream::connect(server.address()).unwrap();
+    // the server may answer before reading all of the request
+    let _ = stream.write_all(request);
+    let mut answer = String::new();
+    let _ = stream.read_to_string(&mut answer);
+    answer.lines().next().unwrap_or_default().to_owned()
+}
+
+#[test]
+fn wrong_token_is_refused() {
+    let server = RemoteServer::bind("127.0.0.1:0").unwrap();
+    let request = format!(
+        "GET /status HTTP/1.1\r\nHost: {}\r\nAuthorization: Bearer {}0\r\n\r\n",
+        server.address(),
+        server.token()
+    );
+    assert_eq!(
+        status(&server, request.as_bytes()),
+        "HTTP/1.1 401 Unauthorized"
+    );
+}
+
+#[test]
+fn oversized_headers_are_refused() {
+    let server = RemoteServer::bind("127.0.0.1:0").unwrap();
+    let header = "x".repeat(64 << 10);
+    let request = format!("GET /status HTTP/1.1\r\nX-Padding: {header}\r\n\r\n");
+    assert_eq!(
+        status(&server, request.as_bytes()),
+        "HTTP/1.1 431 Request Header Fields Too Large"
+    );
+}
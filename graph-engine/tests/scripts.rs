@@ -0,0 +1,80 @@
+//! Saved scripts run end-to-end through the Teal runtime.
+
+#![cfg(feature = "teal")]
+
+use graph_engine::context::EngineContext;
+use graph_engine::graph::{Attributes, Directedness, Graph};
+use graph_engine::io::ID_ATTR;
+use graph_engine::script::teal::TealRuntime;
+use graph_engine::subsystems::input::{InputMap, InputMapper};
+use graph_engine::{script, scripts};
+
+/// A context showing the path `a - b - c`, with the Teal runtime installed.
+fn context() -> EngineContext {
+    let mut graph = Graph::new(Directedness::Undirected);
+    let nodes: Vec<_> = ["a", "b", "c"]
+        .into_iter()
+        .map(|id| {
+            let mut attrs = Attributes::default();
+            attrs.insert(ID_ATTR, id.to_owned());
+            graph.add_node_with(attrs)
+        })
+        .collect();
+    graph.add_edge(nodes[0], nodes[1]).unwrap();
+    graph.add_edge(nodes[1], nodes[2]).unwrap();
+
+    let mut ctx = EngineContext::new(InputMapper::new(InputMap::default()));
+    ctx.open_document(graph, None);
+    ctx.script_runtime = Some(Box::new(TealRuntime::new()));
+    ctx
+}
+
+#[test]
+fn saved_script_runs() {
+    let dir = std::env::temp_dir().join(format!("graph-engine-scripts-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join("select_neighbors.tl"),
+        "local found = selection.select(graph.neighbors(\"b\"))\n\
+         print(\"selected\", found)\n\
+         graph.set_attribute(\"b\", \"visited\", \"yes\")\n",
+    )
+    .unwrap();
+
+    let mut ctx = context();
+    scripts::discover(&mut ctx, &dir);
+    assert!(ctx.palette.contains("script.run:select_neighbors"));
+    scripts::run(&mut ctx, "select_neighbors");
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let run = ctx.scripts.last_run().unwrap();
+    assert_eq!(run.output, vec![("selected\t2".to_owned(), false)]);
+    assert_eq!(ctx.selection.nodes().count(), 2);
+    let b = ctx.graph.nodes().nth(1).unwrap();
+    let visited = ctx.graph.node(b).unwrap().get("visited");
+    assert_eq!(visited.and_then(|v| v.as_str()), Some("yes"));
+}
+
+#[test]
+fn failing_script_reports_its_error() {
+    let dir = std::env::temp_dir().join(format!("graph-engine-failing-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("broken.tl"), "graph.no_such_function()\n").unwrap();
+
+    let mut ctx = context();
+    scripts::discover(&mut ctx, &dir);
+    scripts::run(&mut ctx, "broken");
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let run = ctx.scripts.last_run().unwrap();
+    assert!(matches!(run.output.as_slice(), [(_, true)]));
+}
+
+#[test]
+fn endless_script_is_stopped() {
+    let mut ctx = context();
+    let (_, result) = script::run(&mut ctx, "while true do end");
+    assert_eq!(result, Err("The entry ran for too long".to_owned()));
+    let (_, result) = script::run(&mut ctx, "graph.node_count()");
+    assert_eq!(result, Ok(Some("3".to_owned())));
+}
@@ -0,0 +1,187 @@
+//! Edits to the cached part of a graph store are written back to it.
+
+#![cfg(feature = "sqlite")]
+
+use std::path::PathBuf;
+
+use graph_engine::graph::{Attributes, Directedness, Graph, NodeId};
+use graph_engine::io::ID_ATTR;
+use graph_engine::io::csv::CsvOptions;
+use graph_engine::layout::Positions;
+use graph_engine::store::{GraphStore, StoreCache, StoreError};
+
+/// A fresh path for a store in the temporary directory.
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!(
+        "graph-engine-{name}-{}.graphdb",
+        std::process::id()
+    ))
+}
+
+/// A store of the path `a - b - c`, with its nodes 10 units apart on the x
+/// axis, and a cache of all of it.
+fn cached_path(name: &str) -> (PathBuf, StoreCache, Graph, Positions) {
+    let mut graph = Graph::new(Directedness::Undirected);
+    let mut positions = Positions::new(&graph);
+    let nodes: Vec<_> = ["a", "b", "c"]
+        .into_iter()
+        .map(|id| graph.add_node_with(Attributes::from_iter([(ID_ATTR, id)])))
+        .collect();
+    positions.sync(&graph);
+    for (i, &node) in nodes.iter().enumerate() {
+        positions.set(node, [i as f32 * 10.0, 0.0]);
+    }
+    graph.add_edge(nodes[0], nodes[1]).unwrap();
+    graph.add_edge(nodes[1], nodes[2]).unwrap();
+
+    let path = temp_path(name);
+    let store = GraphStore::create(&path, &graph, &positions).unwrap();
+    let mut cache = StoreCache::new(store, 0);
+    let mut graph = Graph::undirected();
+    let mut positions = Positions::new(&graph);
+    cache
+        .reload(([-5.0, -5.0], [25.0, 5.0]), &mut graph, &mut positions)
+        .unwrap();
+    assert_eq!(graph.node_count(), 3);
+    (path, cache, graph, positions)
+}
+
+/// The node of the cached `graph` with id `id`.
+fn node(graph: &Graph, id: &str) -> NodeId {
+    graph
+        .nodes()
+        .find(|&n| graph.node(n).unwrap().get(ID_ATTR).unwrap().to_string() == id)
+        .unwrap()
+}
+
+/// The ids of the nodes in `store` with their numbers of neighbors, in store
+/// order.
+fn neighbor_counts(store: &GraphStore) -> Vec<(String, i64)> {
+    let nodes = store.nodes_page(0, 100).unwrap();
+    nodes
+        .into_iter()
+        .map(|node| {
+            let id = node.attrs.get(ID_ATTR).unwrap().to_string();
+            let (neighbors, _) = store.neighbors(node.id, 100).unwrap();
+            (id, neighbors.len() as i64)
+        })
+        .collect()
+}
+
+#[test]
+fn edits_are_written_back() {
+    let (path, mut cache, mut graph, mut positions) = cached_path("edits");
+    let (a, b, c) = (node(&graph, "a"), node(&graph, "b"), node(&graph, "c"));
+    positions.set(a, [1.0, 2.0]);
+    graph.node_mut(b).unwrap().insert("label", "Bob");
+    graph.remove_node(c);
+    let d = graph.add_node_with(Attributes::from_iter([(ID_ATTR, "d")]));
+    positions.sync(&graph);
+    positions.set(d, [3.0, 4.0]);
+    graph.add_edge(a, d).unwrap();
+    cache.flush(&graph, &positions).unwrap();
+    drop(cache);
+
+    let store = GraphStore::open(&path).unwrap();
+    assert_eq!(
+        (store.node_count().unwrap(), store.edge_count().unwrap()),
+        (3, 2)
+    );
+    let nodes = store.nodes_page(0, 100).unwrap();
+    let ids: Vec<String> = nodes
+        .iter()
+        .map(|n| n.attrs.get(ID_ATTR).unwrap().to_string())
+        .collect();
+    assert_eq!(ids, ["a", "b", "d"]);
+    assert_eq!(nodes[0].position, [1.0, 2.0]);
+    assert_eq!(nodes[2].position, [3.0, 4.0]);
+    let label = nodes[1].attrs.get("label").map(|l| l.to_string());
+    assert_eq!(label.as_deref(), Some("Bob"));
+    // the degree orders nodes when windows are truncated
+    let hubs = store.nodes_in([-100.0; 2], [100.0; 2], 1).unwrap();
+    assert_eq!(hubs[0].attrs.get(ID_ATTR).unwrap().to_string(), "a");
+    assert_eq!(
+        neighbor_counts(&store),
+        [("a".into(), 2), ("b".into(), 1), ("d".into(), 1)]
+    );
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn undone_edits_are_written_back() {
+    let (path, mut cache, mut graph, positions) = cached_path("undone");
+    let edge = graph.edges().next().unwrap();
+    let (source, target) = graph.endpoints(edge).unwrap();
+    let attrs = graph.remove_edge(edge).unwrap();
+    cache.flush(&graph, &positions).unwrap();
+    assert_eq!(cache.store().edge_count().unwrap(), 1);
+
+    graph.restore_edge(edge, source, target, attrs).unwrap();
+    cache.flush(&graph, &positions).unwrap();
+    assert_eq!(cache.store().edge_count().unwrap(), 2);
+    drop(cache);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn unchanged_nodes_are_not_written() {
+    let (path, mut cache, mut graph, positions) = cached_path("unchanged");
+    // another writer changes the store behind the cache's back
+    let connection = rusqlite::Connection::open(&path).unwrap();
+    connection
+        .execute(
+            "UPDATE nodes SET attrs = '{\"id\":\"a\",\"note\":\"kept\"}' WHERE id = 0",
+            [],
+        )
+        .unwrap();
+
+    let b = node(&graph, "b");
+    graph.node_mut(b).unwrap().insert("label", "Bob");
+    cache.flush(&graph, &positions).unwrap();
+    let a = cache.store().node(0).unwrap().unwrap();
+    assert_eq!(
+        a.attrs.get("note").map(|n| n.to_string()).as_deref(),
+        Some("kept")
+    );
+    drop(cache);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn replaced_graph_is_refused() {
+    let (path, mut cache, graph, positions) = cached_path("replaced");
+    let mut pasted = graph.clone();
+    pasted.add_node();
+    let error = cache.flush(&pasted, &positions).unwrap_err();
+    assert!(matches!(error, StoreError::Replaced));
+    drop(cache);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn edge_list_is_imported() {
+    let csv = std::env::temp_dir().join(format!("graph-engine-import-{}.csv", std::process::id()));
+    std::fs::write(&csv, "source,target,weight\na,b,1\nb,c,2\nc,a,3\na,d,4\n").unwrap();
+    let path = temp_path("import");
+    let options = CsvOptions::sniff(&csv).unwrap();
+    let store = GraphStore::import_csv(&path, &csv, &options).unwrap();
+    std::fs::remove_file(&csv).unwrap();
+
+    assert_eq!(
+        (store.node_count().unwrap(), store.edge_count().unwrap()),
+        (4, 4)
+    );
+    assert_eq!(
+        neighbor_counts(&store),
+        [
+            ("a".into(), 3),
+            ("b".into(), 2),
+            ("c".into(), 2),
+            ("d".into(), 1)
+        ]
+    );
+    let hubs = store.nodes_in([-1000.0; 2], [1000.0; 2], 1).unwrap();
+    assert_eq!(hubs[0].attrs.get(ID_ATTR).unwrap().to_string(), "a");
+    drop(store);
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,131 @@
+(module
+  (type (;0;) (func))
+  (type (;1;) (func (param f64 i64 i64 i64 f64 f64 f64)))
+  (type (;2;) (func (param i64 i64) (result i64)))
+  (type (;3;) (func (param f64 f64) (result f64)))
+  (type (;4;) (func (param i64 i64 i64 i64)))
+  (import "env" "print(number,integer,integer,integer,number,number,number)" (func $"print(number,integer,integer,integer,number,number,number)" (;0;) (type 1)))
+  (import "env" "pow" (func $pow (;1;) (type 3)))
+  (import "env" "print(integer,integer,integer,integer)" (func $"print(integer,integer,integer,integer)" (;2;) (type 4)))
+  (memory (;0;) 1)
+  (global $x (;0;) (mut f64) f64.const 0x0p+0 (;=0;))
+  (global $n (;1;) (mut i64) i64.const 0)
+  (global $small (;2;) (mut i32) i32.const 0)
+  (export "main" (func $main))
+  (export "memory" (memory 0))
+  (func $main (;3;) (type 0)
+    f64.const 0x1.ep+2 (;=7.5;)
+    global.set $x
+    i64.const 7
+    global.set $n
+    global.get $x
+    i64.const 2
+    f64.convert_i64_s
+    f64.mul
+    global.get $n
+    i64.const 2
+    call $int_div
+    global.get $n
+    i64.const -1
+    i64.mul
+    i64.const 2
+    call $int_div
+    global.get $n
+    i64.const -1
+    i64.mul
+    i64.const 3
+    call $int_mod
+    global.get $x
+    i64.const 2
+    f64.convert_i64_s
+    call $float_mod
+    i64.const 2
+    f64.convert_i64_s
+    i64.const 10
+    f64.convert_i64_s
+    call $pow
+    global.get $n
+    f64.convert_i64_s
+    i64.const 2
+    f64.convert_i64_s
+    f64.div
+    call $"print(number,integer,integer,integer,number,number,number)"
+    i64.const 5
+    i32.wrap_i64
+    global.set $small
+    global.get $small
+    i64.extend_i32_s
+    i64.const 1
+    i64.add
+    i64.const 5
+    i64.const 3
+    i64.and
+    i64.const 1
+    i64.const 4
+    i64.shl
+    i64.const 0
+    i64.const -1
+    i64.xor
+    call $"print(integer,integer,integer,integer)"
+  )
+  (func $int_div (;4;) (type 2) (param i64 i64) (result i64)
+    (local i64)
+    local.get 0
+    local.get 1
+    i64.div_s
+    local.set 2
+    local.get 0
+    local.get 1
+    i64.rem_s
+    i64.const 0
+    i64.ne
+    local.get 0
+    local.get 1
+    i64.xor
+    i64.const 0
+    i64.lt_s
+    i32.and
+    if (result i64) ;; label = @1
+      local.get 2
+      i64.const 1
+      i64.sub
+    else
+      local.get 2
+    end
+  )
+  (func $int_mod (;5;) (type 2) (param i64 i64) (result i64)
+    (local i64)
+    local.get 0
+    local.get 1
+    i64.rem_s
+    local.set 2
+    local.get 0
+    local.get 1
+    i64.rem_s
+    i64.const 0
+    i64.ne
+    local.get 2
+    local.get 1
+    i64.xor
+    i64.const 0
+    i64.lt_s
+    i32.and
+    if (result i64) ;; label = @1
+      local.get 2
+      local.get 1
+      i64.add
+    else
+      local.get 2
+    end
+  )
+  (func $float_mod (;6;) (type 3) (param f64 f64) (result f64)
+    local.get 0
+    local.get 0
+    local.get 1
+    f64.div
+    f64.floor
+    local.get 1
+    f64.mul
+    f64.sub
+  )
+)
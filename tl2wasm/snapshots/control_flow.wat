@@ -0,0 +1,154 @@
+(module
+  (type (;0;) (func))
+  (type (;1;) (func (param i64 i64) (result i64)))
+  (type (;2;) (func (param i64)))
+  (type (;3;) (func (param i64 i64 i32 i32)))
+  (import "env" "print(integer)" (func $"print(integer)" (;0;) (type 2)))
+  (import "env" "print(integer,integer,boolean,boolean)" (func $"print(integer,integer,boolean,boolean)" (;1;) (type 3)))
+  (memory (;0;) 1)
+  (global $total (;0;) (mut i64) i64.const 0)
+  (global $n (;1;) (mut i64) i64.const 0)
+  (export "main" (func $main))
+  (export "memory" (memory 0))
+  (func $main (;2;) (type 0)
+    (local i64 i64 i64) (local $i i64) (local i64 i64 i64) (local $"#local7 i" (@name "i") i64)
+    i64.const 0
+    global.set $total
+    i64.const 1
+    local.set 0
+    i64.const 10
+    local.set 1
+    i64.const 1
+    local.set 2
+    block ;; label = @1
+      loop ;; label = @2
+        local.get 0
+        local.get 1
+        i64.le_s
+        i32.eqz
+        br_if 1 (;@1;)
+        local.get 0
+        local.set $i
+        local.get $i
+        i64.const 5
+        i64.eq
+        if ;; label = @3
+          br 2 (;@1;)
+        else
+          local.get $i
+          i64.const 2
+          call $int_mod
+          i64.const 0
+          i64.eq
+          if ;; label = @4
+            global.get $total
+            local.get $i
+            i64.add
+            global.set $total
+          else
+            global.get $total
+            i64.const 1
+            i64.sub
+            global.set $total
+          end
+        end
+        local.get 0
+        local.get 2
+        i64.add
+        local.set 0
+        br 0 (;@2;)
+      end
+    end
+    i64.const 10
+    local.set 4
+    i64.const 1
+    local.set 5
+    i64.const 3
+    i64.const -1
+    i64.mul
+    local.set 6
+    block ;; label = @1
+      loop ;; label = @2
+        local.get 4
+        local.get 5
+        i64.ge_s
+        i32.eqz
+        br_if 1 (;@1;)
+        local.get 4
+        local.set $"#local7 i"
+        local.get $"#local7 i"
+        call $"print(integer)"
+        local.get 4
+        local.get 6
+        i64.add
+        local.set 4
+        br 0 (;@2;)
+      end
+    end
+    i64.const 0
+    global.set $n
+    block ;; label = @1
+      loop ;; label = @2
+        global.get $n
+        i64.const 3
+        i64.lt_s
+        i32.eqz
+        br_if 1 (;@1;)
+        global.get $n
+        i64.const 1
+        i64.add
+        global.set $n
+        br 0 (;@2;)
+      end
+    end
+    block ;; label = @1
+      loop ;; label = @2
+        global.get $n
+        i64.const 1
+        i64.sub
+        global.set $n
+        global.get $n
+        i64.const 0
+        i64.eq
+        i32.eqz
+        br_if 0 (;@2;)
+      end
+    end
+    global.get $total
+    global.get $n
+    i32.const 1
+    if (result i32) ;; label = @1
+      i32.const 0
+    else
+      i32.const 0
+    end
+    i32.const 1
+    i32.eqz
+    call $"print(integer,integer,boolean,boolean)"
+  )
+  (func $int_mod (;3;) (type 1) (param i64 i64) (result i64)
+    (local i64)
+    local.get 0
+    local.get 1
+    i64.rem_s
+    local.set 2
+    local.get 0
+    local.get 1
+    i64.rem_s
+    i64.const 0
+    i64.ne
+    local.get 2
+    local.get 1
+    i64.xor
+    i64.const 0
+    i64.lt_s
+    i32.and
+    if (result i64) ;; label = @1
+      local.get 2
+      local.get 1
+      i64.add
+    else
+      local.get 2
+    end
+  )
+)
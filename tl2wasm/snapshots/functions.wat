@@ -0,0 +1,78 @@
+(module
+  (type (;0;) (func))
+  (type (;1;) (func (param f64) (result f64)))
+  (type (;2;) (func (param i64) (result i64)))
+  (type (;3;) (func (result i64 i32)))
+  (type (;4;) (func (param i64 f64 i64 i32)))
+  (import "env" "print(integer,number,integer,string)" (func $"print(integer,number,integer,string)" (;0;) (type 4)))
+  (memory (;0;) 1)
+  (global $a (;0;) (mut i64) i64.const 0)
+  (global $b (;1;) (mut i32) i32.const 0)
+  (export "main" (func $main))
+  (export "twice" (func $twice))
+  (export "later" (func $later))
+  (export "memory" (memory 0))
+  (func $main (;1;) (type 0)
+    (local i32 i64)
+    call $pair
+    local.set 0
+    local.set 1
+    local.get 1
+    local.get 0
+    global.set $b
+    global.set $a
+    i64.const 20
+    call $fib
+    i64.const 3
+    f64.convert_i64_s
+    call $twice
+    global.get $a
+    global.get $b
+    call $"print(integer,number,integer,string)"
+  )
+  (func $twice (;2;) (type 1) (param $a f64) (result f64)
+    local.get $a
+    call $later
+    i64.const 2
+    f64.convert_i64_s
+    f64.mul
+    return
+    unreachable
+  )
+  (func $later (;3;) (type 1) (param $a f64) (result f64)
+    local.get $a
+    i64.const 1
+    f64.convert_i64_s
+    f64.add
+    return
+    unreachable
+  )
+  (func $fib (;4;) (type 2) (param $n i64) (result i64)
+    local.get $n
+    i64.const 2
+    i64.lt_s
+    if ;; label = @1
+      local.get $n
+      return
+    else
+    end
+    local.get $n
+    i64.const 1
+    i64.sub
+    call $fib
+    local.get $n
+    i64.const 2
+    i64.sub
+    call $fib
+    i64.add
+    return
+    unreachable
+  )
+  (func $pair (;5;) (type 3) (result i64 i32)
+    i64.const 4
+    i32.const 8
+    return
+    unreachable
+  )
+  (data (;0;) (i32.const 8) "\04\00\00\00four")
+)
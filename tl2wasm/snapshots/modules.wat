@@ -0,0 +1,47 @@
+(module
+  (type (;0;) (func))
+  (type (;1;) (func (param f64) (result f64)))
+  (type (;2;) (func (param f64 i64)))
+  (import "env" "print(number,integer)" (func $"print(number,integer)" (;0;) (type 2)))
+  (memory (;0;) 1)
+  (global $lib.shapes.loaded (;0;) (mut i32) i32.const 0)
+  (global $shapes_loaded (;1;) (mut i64) i64.const 0)
+  (export "main" (func $main))
+  (export "square" (func $square))
+  (export "memory" (memory 0))
+  (func $main (;1;) (type 0)
+    global.get $lib.shapes.loaded
+    i32.eqz
+    if ;; label = @1
+      i32.const 1
+      global.set $lib.shapes.loaded
+      call $"require(lib.shapes)"
+    end
+    global.get $lib.shapes.loaded
+    i32.eqz
+    if ;; label = @1
+      i32.const 1
+      global.set $lib.shapes.loaded
+      call $"require(lib.shapes)"
+    end
+    f64.const 0x1.8p+1 (;=3;)
+    call $square
+    global.get $shapes_loaded
+    call $"print(number,integer)"
+  )
+  (func $"require(lib.shapes)" (;2;) (type 0)
+    i64.const 0
+    global.set $shapes_loaded
+    global.get $shapes_loaded
+    i64.const 1
+    i64.add
+    global.set $shapes_loaded
+  )
+  (func $square (;3;) (type 1) (param $side f64) (result f64)
+    local.get $side
+    local.get $side
+    f64.mul
+    return
+    unreachable
+  )
+)
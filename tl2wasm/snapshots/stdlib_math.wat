@@ -0,0 +1,267 @@
+(module
+  (type (;0;) (func))
+  (type (;1;) (func (param i64 i64 i64 i64)))
+  (type (;2;) (func (param i64 i64 f64)))
+  (type (;3;) (func (param i64 i64 f64 f64)))
+  (type (;4;) (func (param f64 f64 f64)))
+  (type (;5;) (func (param f64) (result f64)))
+  (type (;6;) (func (param i32) (result i32)))
+  (type (;7;) (func (param i64 i64)))
+  (type (;8;) (func (param i64)))
+  (type (;9;) (func (param i64) (result i64)))
+  (type (;10;) (func (param i64 i64) (result i64)))
+  (type (;11;) (func (result f64)))
+  (type (;12;) (func (param i32 i32 i32)))
+  (import "env" "print(integer,integer,integer,integer)" (func $"print(integer,integer,integer,integer)" (;0;) (type 1)))
+  (import "env" "print(integer,integer,number)" (func $"print(integer,integer,number)" (;1;) (type 2)))
+  (import "env" "print(integer,integer,number,number)" (func $"print(integer,integer,number,number)" (;2;) (type 3)))
+  (import "env" "print(number,number,number)" (func $"print(number,number,number)" (;3;) (type 4)))
+  (import "math" "exp" (func $math.exp (;4;) (type 5)))
+  (import "math" "log" (func $math.log (;5;) (type 5)))
+  (import "math" "sin" (func $math.sin (;6;) (type 5)))
+  (import "math" "cos" (func $math.cos (;7;) (type 5)))
+  (import "math" "atan" (func $math.atan (;8;) (type 5)))
+  (import "env" "print(integer,integer)" (func $"print(integer,integer)" (;9;) (type 7)))
+  (import "math" "randomseed" (func $math.randomseed (;10;) (type 8)))
+  (import "math" "random(integer)" (func $"math.random(integer)" (;11;) (type 9)))
+  (import "math" "random(integer,integer)" (func $"math.random(integer,integer)" (;12;) (type 10)))
+  (import "math" "random()" (func $"math.random()" (;13;) (type 11)))
+  (import "env" "print(boolean,boolean,boolean)" (func $"print(boolean,boolean,boolean)" (;14;) (type 12)))
+  (memory (;0;) 1)
+  (global $heap (;0;) (mut i32) i32.const 8)
+  (global $first (;1;) (mut i64) i64.const 0)
+  (global $second (;2;) (mut i64) i64.const 0)
+  (global $fraction (;3;) (mut f64) f64.const 0x0p+0 (;=0;))
+  (export "main" (func $main))
+  (export "alloc" (func $alloc))
+  (export "memory" (memory 0))
+  (func $main (;15;) (type 0)
+    (local i64 i64 i64 i64 i64 i64 f64 f64 f64 f64)
+    f64.const 0x1.d99999999999ap+1 (;=3.7;)
+    f64.floor
+    i64.trunc_sat_f64_s
+    f64.const 0x1.999999999999ap+1 (;=3.2;)
+    f64.ceil
+    i64.trunc_sat_f64_s
+    f64.const 0x1.cp+1 (;=3.5;)
+    f64.neg
+    f64.floor
+    i64.trunc_sat_f64_s
+    f64.const 0x1.cp+1 (;=3.5;)
+    f64.neg
+    f64.ceil
+    i64.trunc_sat_f64_s
+    call $"print(integer,integer,integer,integer)"
+    i64.const 5
+    i64.const -1
+    i64.mul
+    local.set 0
+    i64.const 0
+    local.get 0
+    i64.sub
+    local.get 0
+    local.get 0
+    i64.const 0
+    i64.lt_s
+    select
+    i64.const 7
+    local.set 1
+    i64.const 0
+    local.get 1
+    i64.sub
+    local.get 1
+    local.get 1
+    i64.const 0
+    i64.lt_s
+    select
+    f64.const 0x1.4p+1 (;=2.5;)
+    f64.neg
+    f64.abs
+    call $"print(integer,integer,number)"
+    i64.const 3
+    local.set 2
+    i64.const 1
+    local.set 3
+    local.get 2
+    local.get 3
+    local.get 2
+    local.get 3
+    i64.lt_s
+    select
+    local.set 2
+    i64.const 2
+    local.set 3
+    local.get 2
+    local.get 3
+    local.get 2
+    local.get 3
+    i64.lt_s
+    select
+    i64.const 3
+    local.set 4
+    i64.const 7
+    local.set 5
+    local.get 4
+    local.get 5
+    local.get 4
+    local.get 5
+    i64.gt_s
+    select
+    local.set 4
+    i64.const 2
+    local.set 5
+    local.get 4
+    local.get 5
+    local.get 4
+    local.get 5
+    i64.gt_s
+    select
+    f64.const 0x1.8p+0 (;=1.5;)
+    local.set 6
+    f64.const 0x1p-1 (;=0.5;)
+    local.set 7
+    local.get 6
+    local.get 7
+    local.get 6
+    local.get 7
+    f64.lt
+    select
+    f64.const 0x1p+0 (;=1;)
+    f64.neg
+    local.set 8
+    f64.const 0x1p+1 (;=2;)
+    f64.neg
+    local.set 9
+    local.get 8
+    local.get 9
+    local.get 8
+    local.get 9
+    f64.gt
+    select
+    call $"print(integer,integer,number,number)"
+    f64.const 0x1p+4 (;=16;)
+    f64.sqrt
+    f64.const 0x0p+0 (;=0;)
+    call $math.exp
+    f64.const 0x1p+0 (;=1;)
+    call $math.log
+    call $"print(number,number,number)"
+    f64.const 0x0p+0 (;=0;)
+    call $math.sin
+    f64.const 0x0p+0 (;=0;)
+    call $math.cos
+    f64.const 0x0p+0 (;=0;)
+    call $math.atan
+    call $"print(number,number,number)"
+    f64.const 0x1.921fb54442d18p+1 (;=3.141592653589793;)
+    f64.const inf (;=inf;)
+    f64.const inf (;=inf;)
+    f64.neg
+    call $"print(number,number,number)"
+    i64.const 9223372036854775807
+    i64.const -9223372036854775808
+    call $"print(integer,integer)"
+    i64.const 42
+    call $math.randomseed
+    i64.const 6
+    call $"math.random(integer)"
+    i64.const 10
+    i64.const 20
+    call $"math.random(integer,integer)"
+    global.set $second
+    global.set $first
+    call $"math.random()"
+    global.set $fraction
+    i64.const 42
+    call $math.randomseed
+    i64.const 6
+    call $"math.random(integer)"
+    global.get $first
+    i64.eq
+    i64.const 10
+    i64.const 20
+    call $"math.random(integer,integer)"
+    global.get $second
+    i64.eq
+    call $"math.random()"
+    global.get $fraction
+    f64.eq
+    call $"print(boolean,boolean,boolean)"
+    global.get $first
+    i64.const 1
+    i64.ge_s
+    if (result i32) ;; label = @1
+      global.get $first
+      i64.const 6
+      i64.le_s
+    else
+      i32.const 0
+    end
+    global.get $second
+    i64.const 10
+    i64.ge_s
+    if (result i32) ;; label = @1
+      global.get $second
+      i64.const 20
+      i64.le_s
+    else
+      i32.const 0
+    end
+    global.get $fraction
+    f64.const 0x0p+0 (;=0;)
+    f64.ge
+    if (result i32) ;; label = @1
+      global.get $fraction
+      f64.const 0x1p+0 (;=1;)
+      f64.lt
+    else
+      i32.const 0
+    end
+    call $"print(boolean,boolean,boolean)"
+    i64.const 3
+    i64.const 3
+    call $"math.random(integer,integer)"
+    i64.const 1
+    i64.const -1
+    i64.mul
+    i64.const 1
+    i64.const -1
+    i64.mul
+    call $"math.random(integer,integer)"
+    call $"print(integer,integer)"
+  )
+  (func $alloc (;16;) (type 6) (param i32) (result i32)
+    (local i32)
+    global.get $heap
+    local.set 1
+    local.get 1
+    local.get 0
+    i32.add
+    memory.size
+    i32.const 16
+    i32.shl
+    i32.gt_u
+    if ;; label = @1
+      local.get 1
+      local.get 0
+      i32.add
+      i32.const 65535
+      i32.add
+      i32.const 16
+      i32.shr_u
+      memory.size
+      i32.sub
+      memory.grow
+      drop
+    end
+    local.get 1
+    local.get 0
+    i32.add
+    i32.const 3
+    i32.add
+    i32.const -4
+    i32.and
+    global.set $heap
+    local.get 1
+  )
+)
@@ -0,0 +1,336 @@
+(module
+  (type (;0;) (func))
+  (type (;1;) (func (param i64 i64 i64)))
+  (type (;2;) (func (param i32 i32)))
+  (type (;3;) (func (param i32) (result i32)))
+  (type (;4;) (func (param i32 i32 i32 i32 i32)))
+  (type (;5;) (func (param i32 i64 i64) (result i32)))
+  (type (;6;) (func (param i32 i32 i32)))
+  (type (;7;) (func (param i32 i64) (result i32)))
+  (type (;8;) (func (param i32)))
+  (type (;9;) (func (param i32 i64 f64) (result i32)))
+  (type (;10;) (func (param i32 i64 i64 i64 i64 i64 i64 i64) (result i32)))
+  (type (;11;) (func (param i32 f64 f64 f64 f64 f64) (result i32)))
+  (type (;12;) (func (param i32 i32 i64 f64 i32) (result i32)))
+  (type (;13;) (func (param i32 i32 i32 i64 i64) (result i32)))
+  (type (;14;) (func (param i32 i32 i64 i32) (result i64 i64)))
+  (type (;15;) (func (param i64 i64 i64 i64)))
+  (type (;16;) (func (param i32 i64) (result i64)))
+  (type (;17;) (func (param i64 i64) (result i32)))
+  (type (;18;) (func (result i32)))
+  (type (;19;) (func (param i64) (result i32)))
+  (type (;20;) (func (param f64) (result i32)))
+  (type (;21;) (func (param i32 i32) (result i32)))
+  (import "env" "print(integer,integer,integer)" (func $"print(integer,integer,integer)" (;0;) (type 1)))
+  (import "env" "print(string,string)" (func $"print(string,string)" (;1;) (type 2)))
+  (import "string" "upper" (func $string.upper (;2;) (type 3)))
+  (import "string" "lower" (func $string.lower (;3;) (type 3)))
+  (import "env" "print(string,string,string,string,string)" (func $"print(string,string,string,string,string)" (;4;) (type 4)))
+  (import "string" "sub" (func $string.sub (;5;) (type 5)))
+  (import "env" "print(string,string,string)" (func $"print(string,string,string)" (;6;) (type 6)))
+  (import "string" "rep" (func $string.rep (;7;) (type 7)))
+  (import "string" "reverse" (func $string.reverse (;8;) (type 3)))
+  (import "env" "print(string)" (func $"print(string)" (;9;) (type 8)))
+  (import "string" "format(string,integer,number)" (func $"string.format(string,integer,number)" (;10;) (type 9)))
+  (import "string" "format(string,integer,integer,integer,integer,integer,integer,integer)" (func $"string.format(string,integer,integer,integer,integer,integer,integer,integer)" (;11;) (type 10)))
+  (import "string" "format(string,number,number,number,number,number)" (func $"string.format(string,number,number,number,number,number)" (;12;) (type 11)))
+  (import "string" "format(string,string,integer,number,boolean)" (func $"string.format(string,string,integer,number,boolean)" (;13;) (type 12)))
+  (import "string" "format(string,string,string,integer,integer)" (func $"string.format(string,string,string,integer,integer)" (;14;) (type 13)))
+  (import "string" "find" (func $string.find (;15;) (type 14)))
+  (import "env" "print(integer,integer,integer,integer)" (func $"print(integer,integer,integer,integer)" (;16;) (type 15)))
+  (import "string" "byte" (func $string.byte (;17;) (type 16)))
+  (import "string" "char(integer,integer)" (func $"string.char(integer,integer)" (;18;) (type 17)))
+  (import "string" "char()" (func $"string.char()" (;19;) (type 18)))
+  (import "env" "tostring(integer)" (func $"tostring(integer)" (;20;) (type 19)))
+  (import "env" "tostring(number)" (func $"tostring(number)" (;21;) (type 20)))
+  (import "env" "tostring(boolean)" (func $"tostring(boolean)" (;22;) (type 3)))
+  (import "env" "tostring(string)" (func $"tostring(string)" (;23;) (type 3)))
+  (memory (;0;) 1)
+  (global $s (;0;) (mut i32) i32.const 0)
+  (global $heap (;1;) (mut i32) i32.const 256)
+  (global $first (;2;) (mut i64) i64.const 0)
+  (global $last (;3;) (mut i64) i64.const 0)
+  (global $missing (;4;) (mut i64) i64.const 0)
+  (global $missing_last (;5;) (mut i64) i64.const 0)
+  (export "main" (func $main))
+  (export "alloc" (func $alloc))
+  (export "memory" (memory 0))
+  (func $main (;24;) (type 0)
+    (local i64 i64 i64 i64)
+    i32.const 8
+    global.set $s
+    global.get $s
+    i32.load
+    i64.extend_i32_u
+    global.get $s
+    i32.load
+    i64.extend_i32_u
+    global.get $s
+    i32.load
+    i64.extend_i32_u
+    call $"print(integer,integer,integer)"
+    global.get $s
+    call $string.upper
+    global.get $s
+    call $string.lower
+    call $"print(string,string)"
+    global.get $s
+    i64.const 1
+    i64.const 5
+    call $string.sub
+    global.get $s
+    i64.const 8
+    i64.const 0
+    call $string.sub
+    global.get $s
+    i64.const 4
+    i64.const -1
+    i64.mul
+    i64.const 0
+    call $string.sub
+    global.get $s
+    i64.const 4
+    i64.const -1
+    i64.mul
+    i64.const 2
+    i64.const -1
+    i64.mul
+    call $string.sub
+    global.get $s
+    i64.const 20
+    i64.const 0
+    call $string.sub
+    call $"print(string,string,string,string,string)"
+    i32.const 24
+    i64.const 3
+    call $string.rep
+    i32.const 32
+    i64.const 0
+    call $string.rep
+    global.get $s
+    call $string.reverse
+    call $"print(string,string,string)"
+    i32.const 40
+    i64.const 3
+    f64.const 0x1.8p+0 (;=1.5;)
+    call $"string.format(string,integer,number)"
+    call $"print(string)"
+    i32.const 68
+    i64.const 42
+    i64.const 42
+    i64.const 42
+    i64.const -1
+    i64.mul
+    i64.const 7
+    i64.const 255
+    i64.const 255
+    i64.const 8
+    call $"string.format(string,integer,integer,integer,integer,integer,integer,integer)"
+    call $"print(string)"
+    i32.const 116
+    f64.const 0x1.81cd6c8b43958p+13 (;=12345.678;)
+    f64.const 0x1.a36e2eb1c432dp-14 (;=0.0001;)
+    f64.const 0x1.86ap+16 (;=100000;)
+    f64.const 0x1.5af1d78b58c4p+66 (;=100000000000000000000;)
+    f64.const 0x1.b7cdfd9d7bdbbp-34 (;=0.0000000001;)
+    call $"string.format(string,number,number,number,number,number)"
+    call $"print(string)"
+    i32.const 136
+    i32.const 160
+    i64.const 10
+    f64.const 0x1.4p+1 (;=2.5;)
+    i32.const 1
+    call $"string.format(string,string,integer,number,boolean)"
+    call $"print(string)"
+    i32.const 172
+    i32.const 196
+    i32.const 216
+    i64.const 72
+    i64.const 105
+    call $"string.format(string,string,string,integer,integer)"
+    call $"print(string)"
+    global.get $s
+    i32.const 224
+    i64.const 0
+    i32.const 0
+    call $string.find
+    local.set 0
+    local.set 1
+    local.get 1
+    local.get 0
+    global.set $last
+    global.set $first
+    global.get $s
+    i32.const 232
+    i64.const 0
+    i32.const 0
+    call $string.find
+    local.set 2
+    local.set 3
+    local.get 3
+    local.get 2
+    global.set $missing_last
+    global.set $missing
+    global.get $first
+    global.get $last
+    global.get $missing
+    global.get $missing_last
+    call $"print(integer,integer,integer,integer)"
+    global.get $s
+    i32.const 240
+    i64.const 0
+    i32.const 0
+    call $string.find
+    drop
+    global.get $s
+    i32.const 240
+    i64.const 5
+    i32.const 0
+    call $string.find
+    drop
+    global.get $s
+    i32.const 240
+    i64.const 2
+    i64.const -1
+    i64.mul
+    i32.const 0
+    call $string.find
+    drop
+    global.get $s
+    i32.const 240
+    i64.const 20
+    i32.const 0
+    call $string.find
+    drop
+    call $"print(integer,integer,integer,integer)"
+    global.get $s
+    i32.const 248
+    i64.const 1
+    i32.const 1
+    call $string.find
+    drop
+    global.get $s
+    i32.const 0
+    i64.const 0
+    i32.const 0
+    call $string.find
+    drop
+    global.get $s
+    i32.const 0
+    i64.const 20
+    i32.const 0
+    call $string.find
+    drop
+    call $"print(integer,integer,integer)"
+    global.get $s
+    i64.const 0
+    call $string.byte
+    global.get $s
+    i64.const 2
+    call $string.byte
+    global.get $s
+    i64.const 1
+    i64.const -1
+    i64.mul
+    call $string.byte
+    global.get $s
+    i64.const 20
+    call $string.byte
+    call $"print(integer,integer,integer,integer)"
+    i64.const 72
+    i64.const 105
+    call $"string.char(integer,integer)"
+    call $"string.char()"
+    i64.const 195
+    i64.const 169
+    call $"string.char(integer,integer)"
+    call $"print(string,string,string)"
+    i64.const 42
+    call $"tostring(integer)"
+    f64.const 0x1.4p+1 (;=2.5;)
+    call $"tostring(number)"
+    i32.const 1
+    call $"tostring(boolean)"
+    global.get $s
+    call $"tostring(string)"
+    call $concat
+    call $concat
+    call $concat
+    call $"print(string)"
+  )
+  (func $alloc (;25;) (type 3) (param i32) (result i32)
+    (local i32)
+    global.get $heap
+    local.set 1
+    local.get 1
+    local.get 0
+    i32.add
+    memory.size
+    i32.const 16
+    i32.shl
+    i32.gt_u
+    if ;; label = @1
+      local.get 1
+      local.get 0
+      i32.add
+      i32.const 65535
+      i32.add
+      i32.const 16
+      i32.shr_u
+      memory.size
+      i32.sub
+      memory.grow
+      drop
+    end
+    local.get 1
+    local.get 0
+    i32.add
+    i32.const 3
+    i32.add
+    i32.const -4
+    i32.and
+    global.set $heap
+    local.get 1
+  )
+  (func $concat (;26;) (type 21) (param i32 i32) (result i32)
+    (local i32 i32 i32)
+    local.get 0
+    i32.load
+    local.set 2
+    local.get 1
+    i32.load
+    local.set 3
+    i32.const 4
+    local.get 2
+    i32.add
+    local.get 3
+    i32.add
+    call $alloc
+    local.set 4
+    local.get 4
+    local.get 2
+    local.get 3
+    i32.add
+    i32.store
+    local.get 4
+    i32.const 4
+    i32.add
+    local.get 0
+    i32.const 4
+    i32.add
+    local.get 2
+    memory.copy
+    local.get 4
+    i32.const 4
+    i32.add
+    local.get 2
+    i32.add
+    local.get 1
+    i32.const 4
+    i32.add
+    local.get 3
+    memory.copy
+    local.get 4
+  )
+  (data (;0;) (i32.const 8) "\0b\00\00\00Hello, Teal\00\02\00\00\00ab\00\00\01\00\00\00x\00\00\00\15\00\00\00%d items at %.2f each\00\00\00)\00\00\00[%5d] [%-5d] [%05d] [%+d] [%x] [%X] [%#o]\00\00\00\0e\00\00\00%e %g %g %g %G\00\00\11\00\00\00%s and %s, %s, %s\00\00\00\07\00\00\00strings\00\11\00\00\00%q %5.2s| %c%c %%\00\00\00\0f\00\00\00a \22quoted\22\0aline\00\03\00\00\00abc\00\04\00\00\00Teal\03\00\00\00xyz\00\01\00\00\00l\00\00\00\02\00\00\00, \00\00")
+)
@@ -0,0 +1,1005 @@
+(module
+  (type (;0;) (func))
+  (type (;1;) (func (param i32) (result i32)))
+  (type (;2;) (func (param i64 i64)))
+  (type (;3;) (func (param i32 i32) (result i32)))
+  (type (;4;) (func (param f64)))
+  (type (;5;) (func (param i32 i32)))
+  (type (;6;) (func (param i32)))
+  (type (;7;) (func (param i64)))
+  (type (;8;) (func (param i64 i64 i64 i64 i64)))
+  (type (;9;) (func (param f64 f64 f64)))
+  (import "env" "print(integer,integer)" (func $"print(integer,integer)" (;0;) (type 2)))
+  (import "env" "print(number)" (func $"print(number)" (;1;) (type 4)))
+  (import "env" "print(string,string)" (func $"print(string,string)" (;2;) (type 5)))
+  (import "table" "concat" (func $table.concat (;3;) (type 3)))
+  (import "env" "print(string)" (func $"print(string)" (;4;) (type 6)))
+  (import "env" "print(integer)" (func $"print(integer)" (;5;) (type 7)))
+  (import "table" "sort({integer})" (func $"table.sort({integer})" (;6;) (type 1)))
+  (import "env" "print(integer,integer,integer,integer,integer)" (func $"print(integer,integer,integer,integer,integer)" (;7;) (type 8)))
+  (import "table" "sort({number})" (func $"table.sort({number})" (;8;) (type 1)))
+  (import "env" "print(number,number,number)" (func $"print(number,number,number)" (;9;) (type 9)))
+  (import "table" "sort({string})" (func $"table.sort({string})" (;10;) (type 1)))
+  (memory (;0;) 1)
+  (global $heap (;0;) (mut i32) i32.const 104)
+  (global $numbers (;1;) (mut i32) i32.const 0)
+  (global $scales (;2;) (mut i32) i32.const 0)
+  (global $total (;3;) (mut f64) f64.const 0x0p+0 (;=0;))
+  (global $words (;4;) (mut i32) i32.const 0)
+  (global $letters (;5;) (mut i32) i32.const 0)
+  (global $unsorted (;6;) (mut i32) i32.const 0)
+  (global $reals (;7;) (mut i32) i32.const 0)
+  (global $names (;8;) (mut i32) i32.const 0)
+  (global $none (;9;) (mut i32) i32.const 0)
+  (export "main" (func $main))
+  (export "alloc" (func $alloc))
+  (export "memory" (memory 0))
+  (func $main (;11;) (type 0)
+    (local i32 i32 i32 i32 i32 i32 i64 i32 i64 i32 i64 i32 i64 i32 i32) (local $i i64) (local $n i64) (local i32 i32 i32 i32 i32 i32 i32) (local $_ i64) (local $scale f64) (local i32 i32 i32 i32 i32 i32 i32 i32 i32 i32 i32 i32 i64 i64 i64) (local $"#local41 i" (@name "i") i64) (local i32 i64 i32 i32 i32 i32 i32 i64 i32 i32 i32 i32 i64 i32 i32 i32 i32 i64 i32 i32 i64 i32 i64 i32 i64 i32 i64 i32 i64 i32 i32 i64 i32 i64 i32 i64 i32)
+    i32.const 28
+    call $alloc
+    local.tee 0
+    i32.const 3
+    i32.store
+    local.get 0
+    i64.const 10
+    i64.store offset=4 align=1
+    local.get 0
+    i64.const 20
+    i64.store offset=12 align=1
+    local.get 0
+    i64.const 30
+    i64.store offset=20 align=1
+    local.get 0
+    global.set $numbers
+    global.get $numbers
+    local.set 1
+    local.get 1
+    i32.load
+    local.set 2
+    local.get 1
+    call $array_append_8
+    local.tee 3
+    global.set $numbers
+    local.get 3
+    local.get 2
+    i32.const 8
+    i32.mul
+    i32.add
+    local.set 4
+    local.get 4
+    i64.const 40
+    i64.store offset=4 align=1
+    global.get $numbers
+    i32.load
+    i64.extend_i32_u
+    global.get $numbers
+    local.set 5
+    i64.const 4
+    i64.const 1
+    i64.sub
+    local.tee 6
+    local.get 5
+    i32.load
+    i64.extend_i32_u
+    i64.lt_u
+    i32.eqz
+    if ;; label = @1
+      unreachable
+    end
+    local.get 5
+    local.get 6
+    i32.wrap_i64
+    i32.const 8
+    i32.mul
+    i32.add
+    i64.load offset=4 align=1
+    call $"print(integer,integer)"
+    global.get $numbers
+    local.set 7
+    i64.const 2
+    i64.const 1
+    i64.sub
+    local.tee 8
+    local.get 7
+    i32.load
+    i64.extend_i32_u
+    i64.lt_u
+    if (result i64) ;; label = @1
+      local.get 7
+      local.get 8
+      i32.wrap_i64
+      i32.const 8
+      i32.mul
+      i32.add
+      i64.load offset=4 align=1
+      local.get 7
+      local.get 8
+      i32.wrap_i64
+      call $array_remove_8
+      global.set $numbers
+    else
+      i64.const 0
+    end
+    global.get $numbers
+    i32.load
+    i64.extend_i32_u
+    call $"print(integer,integer)"
+    global.get $numbers
+    local.set 9
+    local.get 9
+    i32.load
+    i64.extend_i32_u
+    i64.const 1
+    i64.sub
+    local.tee 10
+    local.get 9
+    i32.load
+    i64.extend_i32_u
+    i64.lt_u
+    if (result i64) ;; label = @1
+      local.get 9
+      local.get 10
+      i32.wrap_i64
+      i32.const 8
+      i32.mul
+      i32.add
+      i64.load offset=4 align=1
+      local.get 9
+      local.get 10
+      i32.wrap_i64
+      call $array_remove_8
+      global.set $numbers
+    else
+      i64.const 0
+    end
+    global.get $numbers
+    i32.load
+    i64.extend_i32_u
+    call $"print(integer,integer)"
+    global.get $numbers
+    local.set 11
+    i64.const 9
+    i64.const 1
+    i64.sub
+    local.tee 12
+    local.get 11
+    i32.load
+    i64.extend_i32_u
+    i64.lt_u
+    if (result i64) ;; label = @1
+      local.get 11
+      local.get 12
+      i32.wrap_i64
+      i32.const 8
+      i32.mul
+      i32.add
+      i64.load offset=4 align=1
+      local.get 11
+      local.get 12
+      i32.wrap_i64
+      call $array_remove_8
+      global.set $numbers
+    else
+      i64.const 0
+    end
+    global.get $numbers
+    i32.load
+    i64.extend_i32_u
+    call $"print(integer,integer)"
+    global.get $numbers
+    local.set 13
+    i32.const 0
+    local.set 14
+    block ;; label = @1
+      loop ;; label = @2
+        local.get 14
+        local.get 13
+        i32.load
+        i32.ge_u
+        br_if 1 (;@1;)
+        local.get 14
+        i32.const 1
+        i32.add
+        i64.extend_i32_u
+        local.set $i
+        local.get 13
+        local.get 14
+        i32.const 8
+        i32.mul
+        i32.add
+        i64.load offset=4 align=1
+        local.set $n
+        local.get $i
+        local.get $n
+        call $"print(integer,integer)"
+        local.get 14
+        i32.const 1
+        i32.add
+        local.set 14
+        br 0 (;@2;)
+      end
+    end
+    i32.const 20
+    call $alloc
+    local.tee 17
+    i32.const 2
+    i32.store
+    local.get 17
+    f64.const 0x1.8p+0 (;=1.5;)
+    f64.store offset=4 align=1
+    local.get 17
+    f64.const 0x1.4p+1 (;=2.5;)
+    f64.store offset=12 align=1
+    local.get 17
+    global.set $scales
+    global.get $scales
+    local.set 18
+    local.get 18
+    i32.load
+    local.set 19
+    local.get 18
+    call $array_append_8
+    local.tee 20
+    global.set $scales
+    local.get 20
+    local.get 19
+    i32.const 8
+    i32.mul
+    i32.add
+    local.set 21
+    local.get 21
+    f64.const 0x1.cp+1 (;=3.5;)
+    f64.store offset=4 align=1
+    f64.const 0x0p+0 (;=0;)
+    global.set $total
+    global.get $scales
+    local.set 22
+    i32.const 0
+    local.set 23
+    block ;; label = @1
+      loop ;; label = @2
+        local.get 23
+        local.get 22
+        i32.load
+        i32.ge_u
+        br_if 1 (;@1;)
+        local.get 23
+        i32.const 1
+        i32.add
+        i64.extend_i32_u
+        local.set $_
+        local.get 22
+        local.get 23
+        i32.const 8
+        i32.mul
+        i32.add
+        f64.load offset=4 align=1
+        local.set $scale
+        global.get $total
+        local.get $scale
+        f64.add
+        global.set $total
+        local.get 23
+        i32.const 1
+        i32.add
+        local.set 23
+        br 0 (;@2;)
+      end
+    end
+    global.get $total
+    call $"print(number)"
+    i32.const 0
+    global.set $words
+    global.get $words
+    local.set 26
+    local.get 26
+    i32.load
+    local.set 27
+    local.get 26
+    call $array_append_4
+    local.tee 28
+    global.set $words
+    local.get 28
+    local.get 27
+    i32.const 4
+    i32.mul
+    i32.add
+    local.set 29
+    local.get 29
+    i32.const 8
+    i32.store offset=4 align=1
+    global.get $words
+    local.set 30
+    local.get 30
+    i32.load
+    local.set 31
+    local.get 30
+    call $array_append_4
+    local.tee 32
+    global.set $words
+    local.get 32
+    local.get 31
+    i32.const 4
+    i32.mul
+    i32.add
+    local.set 33
+    local.get 33
+    i32.const 16
+    i32.store offset=4 align=1
+    global.get $words
+    local.set 34
+    local.get 34
+    i32.load
+    local.set 35
+    local.get 34
+    call $array_append_4
+    local.tee 36
+    global.set $words
+    local.get 36
+    local.get 35
+    i32.const 4
+    i32.mul
+    i32.add
+    local.set 37
+    local.get 37
+    i32.const 24
+    i32.store offset=4 align=1
+    global.get $words
+    i32.const 32
+    call $table.concat
+    global.get $words
+    i32.const 0
+    call $table.concat
+    call $"print(string,string)"
+    global.get $words
+    i32.load
+    i64.extend_i32_u
+    local.set 38
+    i64.const 1
+    local.set 39
+    i64.const 1
+    i64.const -1
+    i64.mul
+    local.set 40
+    block ;; label = @1
+      loop ;; label = @2
+        local.get 38
+        local.get 39
+        i64.ge_s
+        i32.eqz
+        br_if 1 (;@1;)
+        local.get 38
+        local.set $"#local41 i"
+        global.get $words
+        local.set 42
+        local.get 42
+        i32.load
+        i64.extend_i32_u
+        i64.const 1
+        i64.sub
+        local.tee 43
+        local.get 42
+        i32.load
+        i64.extend_i32_u
+        i64.lt_u
+        if (result i32) ;; label = @3
+          local.get 42
+          local.get 43
+          i32.wrap_i64
+          i32.const 4
+          i32.mul
+          i32.add
+          i32.load offset=4 align=1
+          local.get 42
+          local.get 43
+          i32.wrap_i64
+          call $array_remove_4
+          global.set $words
+        else
+          i32.const 0
+        end
+        call $"print(string)"
+        local.get 38
+        local.get 40
+        i64.add
+        local.set 38
+        br 0 (;@2;)
+      end
+    end
+    global.get $words
+    i32.load
+    i64.extend_i32_u
+    call $"print(integer)"
+    i32.const 12
+    call $alloc
+    local.tee 44
+    i32.const 2
+    i32.store
+    local.get 44
+    i32.const 16
+    i32.store offset=4 align=1
+    local.get 44
+    i32.const 40
+    i32.store offset=8 align=1
+    local.get 44
+    global.set $letters
+    global.get $letters
+    local.set 45
+    i64.const 1
+    i64.const 1
+    i64.sub
+    local.tee 49
+    local.get 45
+    i32.load
+    i64.extend_i32_u
+    i64.gt_u
+    if ;; label = @1
+      unreachable
+    end
+    local.get 49
+    i32.wrap_i64
+    local.set 46
+    local.get 45
+    call $array_append_4
+    local.tee 47
+    global.set $letters
+    local.get 47
+    local.get 46
+    i32.const 4
+    i32.mul
+    i32.add
+    local.set 48
+    local.get 48
+    i32.const 8
+    i32.add
+    local.get 48
+    i32.const 4
+    i32.add
+    local.get 45
+    i32.load
+    local.get 46
+    i32.sub
+    i32.const 4
+    i32.mul
+    memory.copy
+    local.get 48
+    i32.const 8
+    i32.store offset=4 align=1
+    global.get $letters
+    local.set 50
+    i64.const 3
+    i64.const 1
+    i64.sub
+    local.tee 54
+    local.get 50
+    i32.load
+    i64.extend_i32_u
+    i64.gt_u
+    if ;; label = @1
+      unreachable
+    end
+    local.get 54
+    i32.wrap_i64
+    local.set 51
+    local.get 50
+    call $array_append_4
+    local.tee 52
+    global.set $letters
+    local.get 52
+    local.get 51
+    i32.const 4
+    i32.mul
+    i32.add
+    local.set 53
+    local.get 53
+    i32.const 8
+    i32.add
+    local.get 53
+    i32.const 4
+    i32.add
+    local.get 50
+    i32.load
+    local.get 51
+    i32.sub
+    i32.const 4
+    i32.mul
+    memory.copy
+    local.get 53
+    i32.const 24
+    i32.store offset=4 align=1
+    global.get $letters
+    local.set 55
+    i64.const 5
+    i64.const 1
+    i64.sub
+    local.tee 59
+    local.get 55
+    i32.load
+    i64.extend_i32_u
+    i64.gt_u
+    if ;; label = @1
+      unreachable
+    end
+    local.get 59
+    i32.wrap_i64
+    local.set 56
+    local.get 55
+    call $array_append_4
+    local.tee 57
+    global.set $letters
+    local.get 57
+    local.get 56
+    i32.const 4
+    i32.mul
+    i32.add
+    local.set 58
+    local.get 58
+    i32.const 8
+    i32.add
+    local.get 58
+    i32.const 4
+    i32.add
+    local.get 55
+    i32.load
+    local.get 56
+    i32.sub
+    i32.const 4
+    i32.mul
+    memory.copy
+    local.get 58
+    i32.const 48
+    i32.store offset=4 align=1
+    global.get $letters
+    i32.const 56
+    call $table.concat
+    call $"print(string)"
+    i32.const 44
+    call $alloc
+    local.tee 60
+    i32.const 5
+    i32.store
+    local.get 60
+    i64.const 3
+    i64.store offset=4 align=1
+    local.get 60
+    i64.const 1
+    i64.const -1
+    i64.mul
+    i64.store offset=12 align=1
+    local.get 60
+    i64.const 20
+    i64.store offset=20 align=1
+    local.get 60
+    i64.const 7
+    i64.store offset=28 align=1
+    local.get 60
+    i64.const 0
+    i64.store offset=36 align=1
+    local.get 60
+    global.set $unsorted
+    global.get $unsorted
+    call $"table.sort({integer})"
+    global.set $unsorted
+    global.get $unsorted
+    local.set 61
+    i64.const 1
+    i64.const 1
+    i64.sub
+    local.tee 62
+    local.get 61
+    i32.load
+    i64.extend_i32_u
+    i64.lt_u
+    i32.eqz
+    if ;; label = @1
+      unreachable
+    end
+    local.get 61
+    local.get 62
+    i32.wrap_i64
+    i32.const 8
+    i32.mul
+    i32.add
+    i64.load offset=4 align=1
+    global.get $unsorted
+    local.set 63
+    i64.const 2
+    i64.const 1
+    i64.sub
+    local.tee 64
+    local.get 63
+    i32.load
+    i64.extend_i32_u
+    i64.lt_u
+    i32.eqz
+    if ;; label = @1
+      unreachable
+    end
+    local.get 63
+    local.get 64
+    i32.wrap_i64
+    i32.const 8
+    i32.mul
+    i32.add
+    i64.load offset=4 align=1
+    global.get $unsorted
+    local.set 65
+    i64.const 3
+    i64.const 1
+    i64.sub
+    local.tee 66
+    local.get 65
+    i32.load
+    i64.extend_i32_u
+    i64.lt_u
+    i32.eqz
+    if ;; label = @1
+      unreachable
+    end
+    local.get 65
+    local.get 66
+    i32.wrap_i64
+    i32.const 8
+    i32.mul
+    i32.add
+    i64.load offset=4 align=1
+    global.get $unsorted
+    local.set 67
+    i64.const 4
+    i64.const 1
+    i64.sub
+    local.tee 68
+    local.get 67
+    i32.load
+    i64.extend_i32_u
+    i64.lt_u
+    i32.eqz
+    if ;; label = @1
+      unreachable
+    end
+    local.get 67
+    local.get 68
+    i32.wrap_i64
+    i32.const 8
+    i32.mul
+    i32.add
+    i64.load offset=4 align=1
+    global.get $unsorted
+    local.set 69
+    i64.const 5
+    i64.const 1
+    i64.sub
+    local.tee 70
+    local.get 69
+    i32.load
+    i64.extend_i32_u
+    i64.lt_u
+    i32.eqz
+    if ;; label = @1
+      unreachable
+    end
+    local.get 69
+    local.get 70
+    i32.wrap_i64
+    i32.const 8
+    i32.mul
+    i32.add
+    i64.load offset=4 align=1
+    call $"print(integer,integer,integer,integer,integer)"
+    i32.const 28
+    call $alloc
+    local.tee 71
+    i32.const 3
+    i32.store
+    local.get 71
+    f64.const 0x1.4p+1 (;=2.5;)
+    f64.store offset=4 align=1
+    local.get 71
+    f64.const 0x1p-1 (;=0.5;)
+    f64.neg
+    f64.store offset=12 align=1
+    local.get 71
+    f64.const 0x1p+0 (;=1;)
+    f64.store offset=20 align=1
+    local.get 71
+    global.set $reals
+    global.get $reals
+    call $"table.sort({number})"
+    global.set $reals
+    global.get $reals
+    local.set 72
+    i64.const 1
+    i64.const 1
+    i64.sub
+    local.tee 73
+    local.get 72
+    i32.load
+    i64.extend_i32_u
+    i64.lt_u
+    i32.eqz
+    if ;; label = @1
+      unreachable
+    end
+    local.get 72
+    local.get 73
+    i32.wrap_i64
+    i32.const 8
+    i32.mul
+    i32.add
+    f64.load offset=4 align=1
+    global.get $reals
+    local.set 74
+    i64.const 2
+    i64.const 1
+    i64.sub
+    local.tee 75
+    local.get 74
+    i32.load
+    i64.extend_i32_u
+    i64.lt_u
+    i32.eqz
+    if ;; label = @1
+      unreachable
+    end
+    local.get 74
+    local.get 75
+    i32.wrap_i64
+    i32.const 8
+    i32.mul
+    i32.add
+    f64.load offset=4 align=1
+    global.get $reals
+    local.set 76
+    i64.const 3
+    i64.const 1
+    i64.sub
+    local.tee 77
+    local.get 76
+    i32.load
+    i64.extend_i32_u
+    i64.lt_u
+    i32.eqz
+    if ;; label = @1
+      unreachable
+    end
+    local.get 76
+    local.get 77
+    i32.wrap_i64
+    i32.const 8
+    i32.mul
+    i32.add
+    f64.load offset=4 align=1
+    call $"print(number,number,number)"
+    i32.const 20
+    call $alloc
+    local.tee 78
+    i32.const 4
+    i32.store
+    local.get 78
+    i32.const 64
+    i32.store offset=4 align=1
+    local.get 78
+    i32.const 72
+    i32.store offset=8 align=1
+    local.get 78
+    i32.const 84
+    i32.store offset=12 align=1
+    local.get 78
+    i32.const 92
+    i32.store offset=16 align=1
+    local.get 78
+    global.set $names
+    global.get $names
+    call $"table.sort({string})"
+    global.set $names
+    global.get $names
+    i32.const 56
+    call $table.concat
+    call $"print(string)"
+    i32.const 0
+    global.set $none
+    global.get $none
+    call $"table.sort({integer})"
+    global.set $none
+    global.get $none
+    i32.load
+    i64.extend_i32_u
+    call $"print(integer)"
+  )
+  (func $alloc (;12;) (type 1) (param i32) (result i32)
+    (local i32)
+    global.get $heap
+    local.set 1
+    local.get 1
+    local.get 0
+    i32.add
+    memory.size
+    i32.const 16
+    i32.shl
+    i32.gt_u
+    if ;; label = @1
+      local.get 1
+      local.get 0
+      i32.add
+      i32.const 65535
+      i32.add
+      i32.const 16
+      i32.shr_u
+      memory.size
+      i32.sub
+      memory.grow
+      drop
+    end
+    local.get 1
+    local.get 0
+    i32.add
+    i32.const 3
+    i32.add
+    i32.const -4
+    i32.and
+    global.set $heap
+    local.get 1
+  )
+  (func $array_append_8 (;13;) (type 1) (param i32) (result i32)
+    (local i32 i32)
+    local.get 0
+    i32.load
+    local.set 1
+    i32.const 4
+    local.get 1
+    i32.const 1
+    i32.add
+    i32.const 8
+    i32.mul
+    i32.add
+    call $alloc
+    local.set 2
+    local.get 2
+    local.get 1
+    i32.const 1
+    i32.add
+    i32.store
+    local.get 2
+    i32.const 4
+    i32.add
+    local.get 0
+    i32.const 4
+    i32.add
+    local.get 1
+    i32.const 8
+    i32.mul
+    memory.copy
+    local.get 2
+  )
+  (func $array_remove_8 (;14;) (type 3) (param i32 i32) (result i32)
+    (local i32 i32)
+    local.get 0
+    i32.load
+    i32.const 1
+    i32.sub
+    local.set 2
+    i32.const 4
+    local.get 2
+    i32.const 8
+    i32.mul
+    i32.add
+    call $alloc
+    local.set 3
+    local.get 3
+    local.get 2
+    i32.store
+    local.get 3
+    i32.const 4
+    i32.add
+    local.get 0
+    i32.const 4
+    i32.add
+    local.get 1
+    i32.const 8
+    i32.mul
+    memory.copy
+    local.get 3
+    i32.const 4
+    i32.add
+    local.get 1
+    i32.const 8
+    i32.mul
+    i32.add
+    local.get 0
+    i32.const 12
+    i32.add
+    local.get 1
+    i32.const 8
+    i32.mul
+    i32.add
+    local.get 2
+    local.get 1
+    i32.sub
+    i32.const 8
+    i32.mul
+    memory.copy
+    local.get 3
+  )
+  (func $array_append_4 (;15;) (type 1) (param i32) (result i32)
+    (local i32 i32)
+    local.get 0
+    i32.load
+    local.set 1
+    i32.const 4
+    local.get 1
+    i32.const 1
+    i32.add
+    i32.const 4
+    i32.mul
+    i32.add
+    call $alloc
+    local.set 2
+    local.get 2
+    local.get 1
+    i32.const 1
+    i32.add
+    i32.store
+    local.get 2
+    i32.const 4
+    i32.add
+    local.get 0
+    i32.const 4
+    i32.add
+    local.get 1
+    i32.const 4
+    i32.mul
+    memory.copy
+    local.get 2
+  )
+  (func $array_remove_4 (;16;) (type 3) (param i32 i32) (result i32)
+    (local i32 i32)
+    local.get 0
+    i32.load
+    i32.const 1
+    i32.sub
+    local.set 2
+    i32.const 4
+    local.get 2
+    i32.const 4
+    i32.mul
+    i32.add
+    call $alloc
+    local.set 3
+    local.get 3
+    local.get 2
+    i32.store
+    local.get 3
+    i32.const 4
+    i32.add
+    local.get 0
+    i32.const 4
+    i32.add
+    local.get 1
+    i32.const 4
+    i32.mul
+    memory.copy
+    local.get 3
+    i32.const 4
+    i32.add
+    local.get 1
+    i32.const 4
+    i32.mul
+    i32.add
+    local.get 0
+    i32.const 8
+    i32.add
+    local.get 1
+    i32.const 4
+    i32.mul
+    i32.add
+    local.get 2
+    local.get 1
+    i32.sub
+    i32.const 4
+    i32.mul
+    memory.copy
+    local.get 3
+  )
+  (data (;0;) (i32.const 8) "\01\00\00\00a\00\00\00\01\00\00\00b\00\00\00\01\00\00\00c\00\00\00\02\00\00\00, \00\00\01\00\00\00d\00\00\00\01\00\00\00e\00\00\00\01\00\00\00 \00\00\00\04\00\00\00pear\05\00\00\00apple\00\00\00\03\00\00\00fig\00\06\00\00\00Banana\00\00")
+)
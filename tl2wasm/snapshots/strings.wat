@@ -0,0 +1,163 @@
+(module
+  (type (;0;) (func))
+  (type (;1;) (func (param i32) (result i32)))
+  (type (;2;) (func (param i32 i32) (result i32)))
+  (type (;3;) (func (param i32 i64 i32 i32 i32)))
+  (import "env" "print(string,integer,boolean,boolean,boolean)" (func $"print(string,integer,boolean,boolean,boolean)" (;0;) (type 3)))
+  (memory (;0;) 1)
+  (global $s (;0;) (mut i32) i32.const 0)
+  (global $heap (;1;) (mut i32) i32.const 72)
+  (global $t (;2;) (mut i32) i32.const 0)
+  (global $d (;3;) (mut i32) i32.const 0)
+  (export "main" (func $main))
+  (export "memory" (memory 0))
+  (func $main (;1;) (type 0)
+    i32.const 8
+    global.set $s
+    global.get $s
+    i32.const 20
+    i32.const 28
+    call $concat
+    call $concat
+    global.set $t
+    i32.const 40
+    global.set $d
+    global.get $t
+    global.get $t
+    i32.load
+    i64.extend_i32_u
+    global.get $t
+    i32.const 48
+    call $str_eq
+    global.get $s
+    i32.const 8
+    call $str_eq
+    i32.eqz
+    global.get $d
+    i32.const 64
+    call $str_eq
+    call $"print(string,integer,boolean,boolean,boolean)"
+  )
+  (func $alloc (;2;) (type 1) (param i32) (result i32)
+    (local i32)
+    global.get $heap
+    local.set 1
+    local.get 1
+    local.get 0
+    i32.add
+    memory.size
+    i32.const 16
+    i32.shl
+    i32.gt_u
+    if ;; label = @1
+      local.get 1
+      local.get 0
+      i32.add
+      i32.const 65535
+      i32.add
+      i32.const 16
+      i32.shr_u
+      memory.size
+      i32.sub
+      memory.grow
+      drop
+    end
+    local.get 1
+    local.get 0
+    i32.add
+    i32.const 3
+    i32.add
+    i32.const -4
+    i32.and
+    global.set $heap
+    local.get 1
+  )
+  (func $concat (;3;) (type 2) (param i32 i32) (result i32)
+    (local i32 i32 i32)
+    local.get 0
+    i32.load
+    local.set 2
+    local.get 1
+    i32.load
+    local.set 3
+    i32.const 4
+    local.get 2
+    i32.add
+    local.get 3
+    i32.add
+    call $alloc
+    local.set 4
+    local.get 4
+    local.get 2
+    local.get 3
+    i32.add
+    i32.store
+    local.get 4
+    i32.const 4
+    i32.add
+    local.get 0
+    i32.const 4
+    i32.add
+    local.get 2
+    memory.copy
+    local.get 4
+    i32.const 4
+    i32.add
+    local.get 2
+    i32.add
+    local.get 1
+    i32.const 4
+    i32.add
+    local.get 3
+    memory.copy
+    local.get 4
+  )
+  (func $str_eq (;4;) (type 2) (param i32 i32) (result i32)
+    (local i32 i32)
+    local.get 0
+    local.get 1
+    i32.eq
+    if ;; label = @1
+      i32.const 1
+      return
+    end
+    local.get 0
+    i32.load
+    local.tee 2
+    local.get 1
+    i32.load
+    i32.ne
+    if ;; label = @1
+      i32.const 0
+      return
+    end
+    block ;; label = @1
+      loop ;; label = @2
+        local.get 3
+        local.get 2
+        i32.ge_u
+        br_if 1 (;@1;)
+        local.get 0
+        local.get 3
+        i32.add
+        i32.load8_u offset=4
+        local.get 1
+        local.get 3
+        i32.add
+        i32.load8_u offset=4
+        i32.ne
+        if ;; label = @3
+          i32.const 0
+          return
+        end
+        local.get 3
+        i32.const 1
+        i32.add
+        local.set 3
+        br 0 (;@2;)
+      end
+    end
+    i32.const 1
+  )
+  (data (;0;) (i32.const 8) "\05\00\00\00hello\00\00\00\02\00\00\00, \00\00\05\00\00\00world\00\00\00\02\00\00\00up\00\00\0c\00\00\00hello, world\04\00\00\00down")
+)
@@ -0,0 +1,4 @@
+//! Code generation from a [checked](crate::teal::check) syntax tree.
+
+pub mod source_map;
+pub mod wasm;
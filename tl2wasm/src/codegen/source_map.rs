@@ -0,0 +1,149 @@
+//! Mapping of the instructions of an emitted module back to the Teal source
+//! they were lowered from, eg. to report where a script trapped.
+//!
+//! A [`SourceMap`] maps the offset of each instruction from the start of the
+//! module, as runtimes report in traps, to the [`Span`] of the innermost
+//! statement or expression it was lowered from. It is written as a [source map
+//! v3](https://tc39.es/ecma426/) with every offset a column of the first line,
+//! as browsers read those of WASM modules, and linked from the module by a
+//! [`URL_SECTION`] custom section:
+//!
+//! ```ignore
+//! let (mut bytes, map) = wasm::emit_with_source_map(&chunk, &types)?;
+//! source_map::append_url(&mut bytes, "script.wasm.map");
+//! std::fs::write("script.wasm.map", map.to_json(&SourceFile::new("script.tl", &source)))?;
+//!
+//! // when a trap is reported at `offset`
+//! let location = map.lookup(offset).map(|span| source.location(span.start));
+//! ```
+
+use std::fmt::Write as _;
+
+use wasm_encoder::{CustomSection, Encode};
+
+use crate::diagnostics::SourceFile;
+use crate::teal::ast::Span;
+
+/// Name of the custom section holding the URL of a module's source map.
+pub const URL_SECTION: &str = "sourceMappingURL";
+
+/// The source spans of the instructions of a module, see the
+/// [module documentation](self).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SourceMap {
+    /// Offsets from the start of the module, increasing, and the span each
+    /// instruction from there on was lowered from.
+    entries: Vec<(u32, Span)>,
+}
+
+impl SourceMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Map the instructions from `offset` on to `span`. Offsets must be added
+    /// in increasing order.
+    pub fn push(&mut self, offset: u32, span: Span) {
+        if self.entries.last().is_some_and(|&(_, last)| last == span) {
+            return;
+        }
+        self.entries.push((offset, span));
+    }
+
+    /// The span the instruction at `offset` was lowered from, if mapped.
+    pub fn lookup(&self, offset: u32) -> Option<Span> {
+        let index = self.entries.partition_point(|&(start, _)| start <= offset);
+        index.checked_sub(1).map(|i| self.entries[i].1)
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, (u32, Span)> {
+        self.entries.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The map as source map v3 JSON, with `source` as its only source.
+    pub fn to_json(&self, source: &SourceFile) -> String {
+        let mut mappings = String::new();
+        let (mut offset, mut line, mut column) = (0, 0, 0);
+        for (i, &(start, span)) in self.entries.iter().enumerate() {
+            if i > 0 {
+                mappings.push(',');
+            }
+            let location = source.location(span.start);
+            let (to_line, to_column) = (location.line as i64 - 1, location.column as i64 - 1);
+            // generated column, source index, original line and column
+            for delta in [start as i64 - offset, 0, to_line - line, to_column - column] {
+                vlq(delta, &mut mappings);
+            }
+            (offset, line, column) = (start as i64, to_line, to_column);
+        }
+
+        let mut json = String::from("{\"version\":3,\"sources\":[");
+        quote(source.name(), &mut json);
+        json.push_str("],\"sourcesContent\":[");
+        quote(source.text(), &mut json);
+        json.push_str("],\"names\":[],\"mappings\":");
+        quote(&mappings, &mut json);
+        json.push('}');
+        json
+    }
+}
+
+/// Append to the module `bytes` a [`URL_SECTION`] linking to its source map
+/// at `url`.
+pub fn append_url(bytes: &mut Vec<u8>, url: &str) {
+    let mut data = Vec::new();
+    url.encode(&mut data);
+    let section = CustomSection {
+        name: URL_SECTION.into(),
+        data: data.into(),
+    };
+    // the id of custom sections
+    bytes.push(0);
+    section.encode(bytes);
+}
+
+/// Append `value` as a base64 VLQ.
+fn vlq(value: i64, out: &mut String) {
+    const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    // the sign is the lowest bit
+    let mut rest = (value.unsigned_abs() << 1) | (value < 0) as u64;
+    loop {
+        let mut digit = (rest & 0b11111) as usize;
+        rest >>= 5;
+        if rest > 0 {
+            digit |= 0b100000;
+        }
+        out.push(BASE64[digit] as char);
+        if rest == 0 {
+            break;
+        }
+    }
+}
+
+/// Append `value` as a JSON string.
+fn quote(value: &str, out: &mut String) {
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
@@ -0,0 +1,2772 @@
+//! Lowering of a checked Teal chunk to a WebAssembly module.
+//!
+//! Values are lowered by their [checked type](crate::teal::check::Type):
+//! - `integer`, `i64`, and `u64` to `i64`, the other sized integers to `i32`
+//! - `number` and `f64` to `f64`, the other sized floats to `f32`
+//! - `boolean` to `i32`
+//! - `string` and enums to `i32`, the address of the string in linear memory:
+//!   its length in bytes as a little-endian `u32`, then its bytes
+//!
+//! - arrays of the above to `i32`, the address of the array in linear memory:
+//!   its length as a little-endian `u32`, then its elements, of 8 bytes if
+//!   lowered to `i64` or `f64` and 4 bytes otherwise. Arrays are read-only,
+//!   and indexing out of bounds traps: `table.insert`, `table.remove`, and
+//!   `table.sort` assign the variable they are passed an updated copy
+//!   instead, in time linear in its length.
+//!
+//! `nil` is lowered to the zero value of the type it is used as, so that eg. a
+//! `nil` string is the empty string at address 0.
+//!
+//! The statements of the chunk become the exported function [`ENTRY_POINT`],
+//! its top-level variables WASM globals, and its functions WASM functions,
+//! exported by name if global. Functions the chunk calls but does not declare,
+//! such as `print`, are imported from [`HOST_MODULE`]. Parameters of type
+//! `any` take the types of the arguments, which are appended to the name of
+//! the import, eg. `print(integer,string)`. Calls to functions of other
+//! modules, eg. `graph.node_count()`, are imported from those modules, as the
+//! [host bindings](crate::host) provide them.
+//!
+//! Of the [standard library](crate::stdlib), `string.len`, `table.insert`,
+//! `table.remove`, and the `math` functions and constants with a WASM
+//! instruction are lowered to instructions, and `for ... in` loops over
+//! `ipairs()` or `pairs()` of arrays to loops over their indices. The other
+//! functions are imported like those of the host, from a module named after
+//! their library, those with several signatures or varargs named after the
+//! types of their arguments too, eg. `random(integer,integer)`. Methods are
+//! only supported on strings, calling the function of `string` of their name.
+//!
+//! Table constructors other than of arrays, and so maps and `pairs()` over
+//! them, closures, varargs, and `goto` fail with
+//! [`CodegenError::Unsupported`] for now.
+//!
+//! Modules emitted [in a session](emit_in_session), eg. the entries of a REPL,
+//! share one instance's worth of state: each imports from [`SESSION_MODULE`]
+//! the memory, the heap global, and the globals and global functions of the
+//! modules before it, and exports its own under their names for the next.
+//!
+//! The module has a name section naming its functions, globals, and the
+//! locals declared in the source after them, so that its WAT is readable.
+//!
+//! ```ignore
+//! let chunk = TealParser::parse_chunk(source)?;
+//! let types = Checker::new().check(&chunk)?;
+//! let bytes = wasm::emit(&chunk, &types)?;
+//! wasm::emit_to_file(&chunk, &types, "script.wasm")?;
+//! ```
+
+use wasm_encoder::{
+    BlockType, CodeSection, ConstExpr, DataSection, EntityType, ExportKind, ExportSection,
+    Function, FunctionSection, GlobalSection, GlobalType, ImportSection, IndirectNameMap,
+    Instruction, MemArg, MemorySection, MemoryType, Module, NameMap, NameSection, TypeSection,
+    ValType,
+};
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use std::rc::Rc;
+
+use super::source_map::SourceMap;
+use crate::teal::ast::*;
+use crate::teal::check::{FunctionSig, Type, TypeInfo};
+use crate::teal::visit::{self, Visitor};
+
+/// Name of the exported function running the statements of the chunk.
+pub const ENTRY_POINT: &str = "main";
+/// Name of the exported linear memory.
+pub const MEMORY_EXPORT: &str = "memory";
+/// Module the functions provided by the host are imported from.
+pub const HOST_MODULE: &str = "env";
+/// Name of the exported function allocating memory, see [`crate::host`].
+pub const ALLOC_EXPORT: &str = "alloc";
+/// Module the modules of a [`Session`] import their shared state from.
+pub const SESSION_MODULE: &str = "session";
+/// Name of the mutable `i32` global holding the address [`ALLOC_EXPORT`]
+/// allocates at next, as shared by the modules of a [`Session`].
+pub const HEAP_EXPORT: &str = "heap";
+
+/// Address of the first string constant, after the empty string at 0.
+const DATA_START: u32 = 8;
+pub(crate) const PAGE_SIZE: u32 = 65536;
+/// The length of a string or array, at its address.
+const WORD: MemArg = MemArg {
+    offset: 0,
+    align: 2,
+    memory_index: 0,
+};
+/// The first element of an array, after its length.
+const ELEMENTS: MemArg = MemArg {
+    offset: 4,
+    align: 0,
+    memory_index: 0,
+};
+
+/// Errors which can occur while emitting a module.
+#[derive(Debug)]
+pub enum CodegenError {
+    Io(std::io::Error),
+    /// The chunk uses a feature the backend does not support yet.
+    Unsupported {
+        message: String,
+        span: Span,
+    },
+}
+
+/// Lower `chunk`, checked into `info`, to the bytes of a WASM module.
+pub fn emit(chunk: &Block, info: &TypeInfo) -> Result<Vec<u8>, CodegenError> {
+    emit_with_source_map(chunk, info).map(|(bytes, _)| bytes)
+}
+
+/// Lower `chunk`, checked into `info`, to the bytes of a WASM module and the
+/// [`SourceMap`] of its instructions.
+pub fn emit_with_source_map(
+    chunk: &Block,
+    info: &TypeInfo,
+) -> Result<(Vec<u8>, SourceMap), CodegenError> {
+    let mut emitter = Emitter::new(info);
+    emitter.chunk(chunk)?;
+    Ok(emitter.finish())
+}
+
+/// What the modules emitted one after the other by [`emit_in_session()`]
+/// share. The host defines in [`SESSION_MODULE`] a memory as
+/// [`MEMORY_EXPORT`] and a mutable `i32` global as [`HEAP_EXPORT`], then after
+/// instantiating each module every global and function it exports, under
+/// their names.
+#[derive(Clone, Debug)]
+pub struct Session {
+    /// The address the constants of the next module are placed at, which
+    /// must be kept in sync with the heap: set it from the heap before
+    /// emitting, and set the heap to it after instantiating. The memory must
+    /// be grown to fit it before instantiating.
+    pub heap: u32,
+    /// The globals exported so far, by name.
+    globals: Vec<(String, Type)>,
+    /// The global functions exported so far, by name.
+    functions: Vec<(String, Rc<FunctionSig>)>,
+}
+
+/// Lower `chunk`, checked into `info`, to a WASM module linked to the modules
+/// emitted before it in `session`, which is updated with what it declares.
+pub fn emit_in_session(
+    chunk: &Block,
+    info: &TypeInfo,
+    session: &mut Session,
+) -> Result<Vec<u8>, CodegenError> {
+    let mut emitter = Emitter::new(info);
+    emitter.session = Some(session);
+    emitter.chunk(chunk)?;
+    Ok(emitter.finish().0)
+}
+
+/// A chunk of a program, see [`emit_program()`].
+pub struct ProgramUnit<'a> {
+    /// The name the chunk is `require`d by.
+    pub name: &'a str,
+    pub chunk: &'a Block,
+    /// The types of the chunk, as checked after those it requires.
+    pub info: &'a TypeInfo,
+}
+
+/// Lower the chunks of a program to one WASM module. Each of `units` comes
+/// after the modules it requires, and the last is run by [`ENTRY_POINT`],
+/// the others when first `require`d. Fails with the index of the unit at
+/// fault.
+pub fn emit_program(units: &[ProgramUnit]) -> Result<Vec<u8>, (usize, CodegenError)> {
+    let Some((entry, modules)) = units.split_last() else {
+        let empty = Block {
+            statements: Vec::new(),
+            ret: None,
+            span: Span { start: 0, end: 0 },
+        };
+        return emit(&empty, &TypeInfo::default()).map_err(|e| (0, e));
+    };
+    let mut emitter = Emitter::new(entry.info);
+    let main = emitter.entry_point();
+    let mut slots: Vec<usize> = modules
+        .iter()
+        .map(|unit| emitter.declare_module(unit.name))
+        .collect();
+    slots.push(main);
+    for (i, (unit, slot)) in units.iter().zip(slots).enumerate() {
+        emitter.info = unit.info;
+        emitter.unit(unit.chunk, slot).map_err(|e| (i, e))?;
+    }
+    Ok(emitter.finish().0)
+}
+
+/// Lower `chunk`, checked into `info`, to a WASM module written to `path`.
+pub fn emit_to_file(
+    chunk: &Block,
+    info: &TypeInfo,
+    path: impl AsRef<Path>,
+) -> Result<(), CodegenError> {
+    let bytes = emit(chunk, info)?;
+    std::fs::write(path, bytes)?;
+    Ok(())
+}
+
+/// Builds a module, see the [module documentation](self).
+struct Emitter<'a> {
+    info: &'a TypeInfo,
+    /// Function types, by index.
+    types: Vec<(Vec<ValType>, Vec<ValType>)>,
+    /// Functions in the order they were declared, imported or not. Calls
+    /// refer to them by position until [`Self::finish()`] sorts the imports
+    /// first, as WASM requires.
+    functions: Vec<Slot>,
+    /// Imported functions by module, name, and type.
+    imports: HashMap<(String, String, u32), usize>,
+    helpers: HashMap<Helper, usize>,
+    globals: Vec<ValType>,
+    /// The names of the globals declared in the source, by index.
+    global_names: Vec<(u32, String)>,
+    /// The names of the first globals, imported from the session.
+    global_imports: Vec<String>,
+    /// The global holding the address [`Helper::Alloc`] allocates at next.
+    heap: Option<u32>,
+    /// String constants, by their address.
+    strings: HashMap<String, u32>,
+    data: Vec<u8>,
+    /// The address of the first string constant.
+    data_start: u32,
+    /// The session the module is linked in, if any.
+    session: Option<&'a mut Session>,
+    /// The modules of the program, by name: the function running each, and
+    /// the global recording whether it ran.
+    modules: HashMap<String, (usize, u32)>,
+    /// Variables by block, the first holding the globals.
+    scopes: Vec<HashMap<String, Var>>,
+    /// The functions being emitted, innermost last.
+    builders: Vec<Builder>,
+    /// The span of the innermost node being lowered, which instructions are
+    /// mapped to.
+    span: Span,
+}
+
+struct Slot {
+    ty: u32,
+    /// The name of the function in the name section.
+    name: String,
+    kind: SlotKind,
+}
+
+enum SlotKind {
+    /// A function of the host, by module and name.
+    Import { module: String, name: String },
+    /// A function of the module, exported if named, with its body once
+    /// emitted.
+    Defined {
+        export: Option<String>,
+        body: Option<Builder>,
+    },
+}
+
+/// The body of a function being emitted.
+#[derive(Default)]
+struct Builder {
+    params: usize,
+    /// The types of the parameters, then of the locals.
+    locals: Vec<ValType>,
+    /// The names of the parameters and locals declared in the source, by
+    /// index.
+    names: Vec<(u32, String)>,
+    body: Vec<Instruction<'static>>,
+    /// The span each instruction of `body` was lowered from, if any.
+    spans: Vec<Span>,
+    /// The blocks the current instruction is in, innermost last, `true` for
+    /// those `break` exits.
+    labels: Vec<bool>,
+    returns: Vec<Type>,
+}
+
+#[derive(Clone)]
+enum Var {
+    /// A local of the function at this depth of [`Emitter::builders`].
+    Local {
+        function: usize,
+        index: u32,
+        ty: Type,
+    },
+    Global {
+        index: u32,
+        ty: Type,
+    },
+    Function {
+        slot: usize,
+        sig: Rc<FunctionSig>,
+    },
+}
+
+/// Functions of the runtime, emitted once used.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+enum Helper {
+    /// `//` on integers, rounding towards minus infinity.
+    IntDiv,
+    /// `%` on integers, with the sign of the divisor.
+    IntMod,
+    /// `%` on numbers, with the sign of the divisor.
+    FloatMod,
+    /// `==` on strings.
+    StrEq,
+    /// Allocates a number of bytes, growing the memory if needed. Exported
+    /// as [`ALLOC_EXPORT`] for the host to return strings and arrays.
+    Alloc,
+    /// `..` on strings, allocating the result.
+    Concat,
+    /// Copies an array with room for one more element at its end, of 8 bytes
+    /// if `wide` and 4 otherwise.
+    Append { wide: bool },
+    /// Copies an array without the element at a 0-based index, of 8 bytes if
+    /// `wide` and 4 otherwise.
+    Remove { wide: bool },
+}
+
+/// Collects the names of the global functions of a chunk, which may be
+/// called before they are declared.
+struct GlobalFunctions(Vec<Name>);
+
+impl Visitor for GlobalFunctions {
+    fn visit_statement(&mut self, statement: &Statement) {
+        if let StatementKind::Function(decl) = &statement.kind {
+            let global = match decl.scope {
+                Some(scope) => scope == Scope::Global,
+                None => decl.method.is_none(),
+            };
+            if global && decl.path.len() == 1 {
+                self.0.push(decl.path[0].clone());
+            }
+        }
+        visit::walk_statement(self, statement);
+    }
+}
+
+impl Session {
+    /// A session no module was emitted in yet.
+    pub fn new() -> Self {
+        Self {
+            heap: DATA_START,
+            globals: Vec::new(),
+            functions: Vec::new(),
+        }
+    }
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> Emitter<'a> {
+    fn new(info: &'a TypeInfo) -> Self {
+        Self {
+            info,
+            types: Vec::new(),
+            functions: Vec::new(),
+            imports: HashMap::new(),
+            helpers: HashMap::new(),
+            globals: Vec::new(),
+            global_names: Vec::new(),
+            global_imports: Vec::new(),
+            heap: None,
+            strings: HashMap::new(),
+            data: Vec::new(),
+            data_start: DATA_START,
+            session: None,
+            modules: HashMap::new(),
+            scopes: vec![HashMap::new()],
+            builders: Vec::new(),
+            span: Span::default(),
+        }
+    }
+
+    fn chunk(&mut self, chunk: &Block) -> Result<(), CodegenError> {
+        let main = self.entry_point();
+        self.unit(chunk, main)
+    }
+
+    /// Declare the exported function [`ENTRY_POINT`], running the entry
+    /// chunk.
+    fn entry_point(&mut self) -> usize {
+        let ty = self.type_index(&[], &[]);
+        self.functions.push(Slot {
+            ty,
+            name: ENTRY_POINT.to_owned(),
+            kind: SlotKind::Defined {
+                export: Some(ENTRY_POINT.to_owned()),
+                body: None,
+            },
+        });
+        self.functions.len() - 1
+    }
+
+    /// Declare the function running the module `name`, and the global
+    /// recording whether it ran.
+    fn declare_module(&mut self, name: &str) -> usize {
+        let ty = self.type_index(&[], &[]);
+        self.functions.push(Slot {
+            ty,
+            name: format!("require({name})"),
+            kind: SlotKind::Defined {
+                export: None,
+                body: None,
+            },
+        });
+        let slot = self.functions.len() - 1;
+        self.globals.push(ValType::I32);
+        let loaded = self.globals.len() as u32 - 1;
+        self.global_names.push((loaded, format!("{name}.loaded")));
+        self.modules.insert(name.to_owned(), (slot, loaded));
+        slot
+    }
+
+    /// Emit `chunk` as the body of the function at `slot`, after declaring
+    /// its global functions.
+    fn unit(&mut self, chunk: &Block, slot: usize) -> Result<(), CodegenError> {
+        let mut globals = GlobalFunctions(Vec::new());
+        globals.visit_block(chunk);
+        if let Some(session) = self.session.take() {
+            let redefined = |name: &str| globals.0.iter().any(|n| n.name == name);
+            let result = self.link(session, redefined);
+            self.session = Some(session);
+            result?;
+        }
+        for name in &globals.0 {
+            if self.scopes[0].contains_key(&name.name) {
+                return Err(unsupported(
+                    name.span,
+                    format!("redefining the global function '{}' is", name.name),
+                ));
+            }
+            let sig = self.signature(name)?;
+            let export = match name.name.as_str() {
+                ENTRY_POINT | MEMORY_EXPORT => None,
+                name => Some(name.to_owned()),
+            };
+            let slot = self.define(&sig, export, name)?;
+            self.scopes[0].insert(name.name.clone(), Var::Function { slot, sig });
+        }
+
+        self.builders.push(Builder::default());
+        self.scopes.push(HashMap::new());
+        self.statements(chunk)?;
+        self.scopes.pop();
+        let builder = self.builders.pop().unwrap();
+        if let SlotKind::Defined { body, .. } = &mut self.functions[slot].kind {
+            *body = Some(builder);
+        }
+        Ok(())
+    }
+
+    /// Import the state shared by the modules before in `session`, but the
+    /// functions the chunk `redefined`.
+    fn link(
+        &mut self,
+        session: &Session,
+        redefined: impl Fn(&str) -> bool,
+    ) -> Result<(), CodegenError> {
+        self.data_start = session.heap;
+        self.heap = Some(self.import_global(HEAP_EXPORT, ValType::I32));
+        for (name, ty) in &session.globals {
+            if redefined(name) {
+                continue;
+            }
+            let index = self.import_global(name, self.lower(ty, Span::default())?);
+            let var = Var::Global {
+                index,
+                ty: ty.clone(),
+            };
+            self.scopes[0].insert(name.clone(), var);
+        }
+        for (name, sig) in &session.functions {
+            if redefined(name) {
+                continue;
+            }
+            let params = self.lower_all(&sig.params, Span::default())?;
+            let results = self.lower_all(&sig.returns, Span::default())?;
+            let slot = self.import(SESSION_MODULE, name, &params, &results);
+            let var = Var::Function {
+                slot,
+                sig: sig.clone(),
+            };
+            self.scopes[0].insert(name.clone(), var);
+        }
+        Ok(())
+    }
+
+    /// Import the global `name` of the session, before any other is declared.
+    fn import_global(&mut self, name: &str, val_type: ValType) -> u32 {
+        self.globals.push(val_type);
+        self.global_imports.push(name.to_owned());
+        let index = self.globals.len() as u32 - 1;
+        self.global_names.push((index, name.to_owned()));
+        index
+    }
+
+    /// Record in the session what the module exports for the next ones.
+    fn export_to_session(&mut self, exports: &mut ExportSection, end: u32) {
+        let Some(session) = &mut self.session else {
+            return;
+        };
+        session.heap = align(end);
+        let reserved = [ENTRY_POINT, MEMORY_EXPORT, ALLOC_EXPORT, HEAP_EXPORT];
+        let mut declared: Vec<_> = self.scopes[0].iter().collect();
+        declared.sort_by_key(|&(name, _)| name);
+        for (name, var) in declared {
+            match var {
+                Var::Global { index, ty } if *index as usize >= self.global_imports.len() => {
+                    if reserved.contains(&name.as_str()) {
+                        continue;
+                    }
+                    exports.export(name, ExportKind::Global, *index);
+                    session.globals.retain(|(n, _)| n != name);
+                    session.globals.push((name.clone(), ty.clone()));
+                }
+                Var::Function { slot, sig } => {
+                    let SlotKind::Defined {
+                        export: Some(export),
+                        ..
+                    } = &self.functions[*slot].kind
+                    else {
+                        continue;
+                    };
+                    session.functions.retain(|(n, _)| n != export);
+                    session.functions.push((export.clone(), sig.clone()));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Assemble the module, and map its instructions to their source.
+    fn finish(mut self) -> (Vec<u8>, SourceMap) {
+        // imports come first in the function index space
+        let mut indices = vec![0; self.functions.len()];
+        let mut next = 0;
+        for imported in [true, false] {
+            for (slot, function) in self.functions.iter().enumerate() {
+                if matches!(function.kind, SlotKind::Import { .. }) == imported {
+                    indices[slot] = next;
+                    next += 1;
+                }
+            }
+        }
+
+        let mut types = TypeSection::new();
+        for (params, results) in &self.types {
+            types
+                .ty()
+                .function(params.iter().copied(), results.iter().copied());
+        }
+        let mut imports = ImportSection::new();
+        let mut functions = FunctionSection::new();
+        let mut exports = ExportSection::new();
+        let mut code = CodeSection::new();
+        let mut function_names = Vec::new();
+        let mut local_names = Vec::new();
+        // offsets of instructions from the start of the code section's
+        // entries, and of each entry's body from its length
+        let mut offsets = Vec::new();
+        for (slot, function) in self.functions.iter_mut().enumerate() {
+            function_names.push((indices[slot], std::mem::take(&mut function.name)));
+            match &mut function.kind {
+                SlotKind::Import { module, name } => {
+                    imports.import(module, name, EntityType::Function(function.ty));
+                }
+                SlotKind::Defined { export, body } => {
+                    functions.function(function.ty);
+                    if let Some(name) = export {
+                        exports.export(name, ExportKind::Func, indices[slot]);
+                    }
+                    let mut builder = body.take().unwrap_or_default();
+                    local_names.push((indices[slot], std::mem::take(&mut builder.names)));
+                    let (function, spans) = builder.into_function(&indices);
+                    let start = code.byte_len() + leb128_len(function.byte_len());
+                    offsets.extend(
+                        spans
+                            .into_iter()
+                            .map(|(at, span)| (start + at as usize, span)),
+                    );
+                    code.function(&function);
+                }
+            }
+        }
+
+        let end = self.data_start + self.data.len() as u32;
+        let memory = MemoryType {
+            minimum: end.div_ceil(PAGE_SIZE).max(1) as u64,
+            maximum: None,
+            memory64: false,
+            shared: false,
+            page_size_log2: None,
+        };
+        let mut memories = MemorySection::new();
+        match self.session {
+            Some(_) => {
+                imports.import(SESSION_MODULE, MEMORY_EXPORT, memory);
+            }
+            None => {
+                memories.memory(memory);
+                exports.export(MEMORY_EXPORT, ExportKind::Memory, 0);
+            }
+        }
+        self.export_to_session(&mut exports, end);
+
+        let mut globals = GlobalSection::new();
+        for (name, &val_type) in self.global_imports.iter().zip(&self.globals) {
+            let ty = GlobalType {
+                val_type,
+                mutable: true,
+                shared: false,
+            };
+            imports.import(SESSION_MODULE, name, ty);
+        }
+        let imported = self.global_imports.len();
+        for (index, &val_type) in self.globals.iter().enumerate().skip(imported) {
+            let init = match val_type {
+                ValType::I64 => ConstExpr::i64_const(0),
+                ValType::F32 => ConstExpr::f32_const(0.0.into()),
+                ValType::F64 => ConstExpr::f64_const(0.0.into()),
+                _ if self.heap == Some(index as u32) => ConstExpr::i32_const(align(end) as i32),
+                _ => ConstExpr::i32_const(0),
+            };
+            let ty = GlobalType {
+                val_type,
+                mutable: true,
+                shared: false,
+            };
+            globals.global(ty, &init);
+        }
+
+        let mut data = DataSection::new();
+        if !self.data.is_empty() {
+            let offset = ConstExpr::i32_const(self.data_start as i32);
+            data.active(0, &offset, self.data.iter().copied());
+        }
+
+        let mut module = Module::new();
+        module.section(&types);
+        if !imports.is_empty() {
+            module.section(&imports);
+        }
+        module.section(&functions);
+        if !memories.is_empty() {
+            module.section(&memories);
+        }
+        if !globals.is_empty() {
+            module.section(&globals);
+        }
+        module.section(&exports);
+        let entries = leb128_len(code.len() as usize) + code.byte_len();
+        let start = module.len() + 1 + leb128_len(entries) + leb128_len(code.len() as usize);
+        let mut map = SourceMap::new();
+        for (offset, span) in offsets {
+            map.push((start + offset) as u32, span);
+        }
+        module.section(&code);
+        if !data.is_empty() {
+            module.section(&data);
+        }
+        module.section(&names(function_names, local_names, self.global_names));
+        (module.finish(), map)
+    }
+
+    /// The index of the function type taking `params` and returning `results`.
+    fn type_index(&mut self, params: &[ValType], results: &[ValType]) -> u32 {
+        let ty = (params.to_vec(), results.to_vec());
+        let index = match self.types.iter().position(|t| *t == ty) {
+            Some(index) => index,
+            None => {
+                self.types.push(ty);
+                self.types.len() - 1
+            }
+        };
+        index as u32
+    }
+
+    /// The checked type of the function declared as `name`.
+    fn signature(&self, name: &Name) -> Result<Rc<FunctionSig>, CodegenError> {
+        match self.info.name(name) {
+            Type::Function(sig) => Ok(sig),
+            _ => Err(unsupported(name.span, "functions without a type are")),
+        }
+    }
+
+    /// Declare a function of the module of type `sig`, to be emitted later.
+    fn define(
+        &mut self,
+        sig: &FunctionSig,
+        export: Option<String>,
+        name: &Name,
+    ) -> Result<usize, CodegenError> {
+        let span = name.span;
+        if !sig.type_params.is_empty() {
+            return Err(unsupported(span, "generic functions are"));
+        }
+        if sig.varargs.is_some() || sig.variadic_return {
+            return Err(unsupported(span, "variadic functions are"));
+        }
+        let params = self.lower_all(&sig.params, span)?;
+        let results = self.lower_all(&sig.returns, span)?;
+        let ty = self.type_index(&params, &results);
+        self.functions.push(Slot {
+            ty,
+            name: name.name.clone(),
+            kind: SlotKind::Defined { export, body: None },
+        });
+        Ok(self.functions.len() - 1)
+    }
+
+    /// The function of the host's `module` named `name` taking `params` and
+    /// returning `results`. Importing from a module other than
+    /// [`HOST_MODULE`] exports [`Helper::Alloc`], for the host to return
+    /// strings and arrays.
+    fn import(
+        &mut self,
+        module: &str,
+        name: &str,
+        params: &[ValType],
+        results: &[ValType],
+    ) -> usize {
+        let ty = self.type_index(params, results);
+        let key = (module.to_owned(), name.to_owned(), ty);
+        if let Some(&slot) = self.imports.get(&key) {
+            return slot;
+        }
+        if module != HOST_MODULE && module != SESSION_MODULE {
+            let alloc = self.helper_slot(Helper::Alloc);
+            if let SlotKind::Defined { export, .. } = &mut self.functions[alloc].kind {
+                *export = Some(ALLOC_EXPORT.to_owned());
+            }
+        }
+        let qualified = match module {
+            HOST_MODULE => name.to_owned(),
+            module => format!("{module}.{name}"),
+        };
+        self.functions.push(Slot {
+            ty,
+            name: qualified,
+            kind: SlotKind::Import {
+                module: module.to_owned(),
+                name: name.to_owned(),
+            },
+        });
+        let slot = self.functions.len() - 1;
+        self.imports.insert(key, slot);
+        slot
+    }
+
+    /// The address of the string constant `value`.
+    fn string(&mut self, value: &str) -> u32 {
+        if value.is_empty() {
+            return 0;
+        }
+        if let Some(&address) = self.strings.get(value) {
+            return address;
+        }
+        let address = self.data_start + self.data.len() as u32;
+        self.data.extend((value.len() as u32).to_le_bytes());
+        self.data.extend(value.as_bytes());
+        self.data.resize(align(self.data.len() as u32) as usize, 0);
+        self.strings.insert(value.to_owned(), address);
+        address
+    }
+
+    fn builder(&mut self) -> &mut Builder {
+        self.builders.last_mut().unwrap()
+    }
+
+    fn push(&mut self, instruction: Instruction<'static>) {
+        let span = self.span;
+        let builder = self.builder();
+        builder.body.push(instruction);
+        builder.spans.push(span);
+    }
+
+    /// Allocate a local of the current function.
+    fn local(&mut self, ty: ValType) -> u32 {
+        let builder = self.builder();
+        builder.locals.push(ty);
+        builder.locals.len() as u32 - 1
+    }
+
+    /// Enter a block, exited by `break` if `breakable`.
+    fn enter(&mut self, instruction: Instruction<'static>, breakable: bool) {
+        self.push(instruction);
+        self.builder().labels.push(breakable);
+    }
+
+    fn end(&mut self) {
+        self.push(Instruction::End);
+        self.builder().labels.pop();
+    }
+
+    fn lookup(&self, name: &str) -> Option<&Var> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name))
+    }
+
+    fn lower(&self, ty: &Type, span: Span) -> Result<ValType, CodegenError> {
+        val_type(ty).ok_or_else(|| unsupported(span, format!("values of type {ty} are")))
+    }
+
+    fn lower_all(&self, types: &[Type], span: Span) -> Result<Vec<ValType>, CodegenError> {
+        types.iter().map(|ty| self.lower(ty, span)).collect()
+    }
+
+    /// Declare the variable `name` of type `ty` in `scope`.
+    fn declare(&mut self, scope: Scope, name: &Name, ty: &Type) -> Result<Var, CodegenError> {
+        let val_type = self.lower(ty, name.span)?;
+        // the chunk's own locals are visible to its functions
+        let top_level = self.builders.len() == 1 && self.scopes.len() == 2;
+        let var = match scope == Scope::Global || top_level {
+            true => {
+                self.globals.push(val_type);
+                let index = self.globals.len() as u32 - 1;
+                self.global_names.push((index, name.name.clone()));
+                Var::Global {
+                    index,
+                    ty: ty.clone(),
+                }
+            }
+            false => {
+                let index = self.local(val_type);
+                self.builder().names.push((index, name.name.clone()));
+                Var::Local {
+                    function: self.builders.len() - 1,
+                    index,
+                    ty: ty.clone(),
+                }
+            }
+        };
+        let scope = match scope {
+            Scope::Global => &mut self.scopes[0],
+            Scope::Local => self.scopes.last_mut().unwrap(),
+        };
+        scope.insert(name.name.clone(), var.clone());
+        Ok(var)
+    }
+
+    /// Store the value on the stack in `var`.
+    fn set(&mut self, var: &Var) {
+        match var {
+            Var::Local { index, .. } => self.push(Instruction::LocalSet(*index)),
+            Var::Global { index, .. } => self.push(Instruction::GlobalSet(*index)),
+            Var::Function { .. } => unreachable!("functions are not assignable"),
+        }
+    }
+
+    fn block(&mut self, block: &Block) -> Result<(), CodegenError> {
+        self.scopes.push(HashMap::new());
+        let result = self.statements(block);
+        self.scopes.pop();
+        result
+    }
+
+    /// Emit the statements of `block` in the current scope.
+    fn statements(&mut self, block: &Block) -> Result<(), CodegenError> {
+        for statement in &block.statements {
+            let outer = std::mem::replace(&mut self.span, statement.span);
+            let result = self.statement(statement);
+            self.span = outer;
+            result?;
+        }
+        if let Some(ret) = &block.ret {
+            self.span = ret.span;
+            let returns = self.builder().returns.clone();
+            if self.builders.len() == 1 && !ret.values.is_empty() {
+                return Err(unsupported(ret.span, "returning values from the chunk is"));
+            }
+            self.values(&ret.values, &returns)?;
+            self.push(Instruction::Return);
+        }
+        Ok(())
+    }
+
+    fn statement(&mut self, statement: &Statement) -> Result<(), CodegenError> {
+        match &statement.kind {
+            StatementKind::Assign { targets, values } => {
+                let mut vars = Vec::with_capacity(targets.len());
+                for target in targets {
+                    vars.push(self.variable(target)?);
+                }
+                let types: Vec<Type> = vars.iter().map(|var| var_type(var).clone()).collect();
+                self.values(values, &types)?;
+                for var in vars.iter().rev() {
+                    self.set(var);
+                }
+            }
+            StatementKind::Call(call) => {
+                let results = self.call(call, true)?;
+                for _ in results {
+                    self.push(Instruction::Drop);
+                }
+            }
+            StatementKind::Label(_) | StatementKind::Goto(_) => {
+                return Err(unsupported(statement.span, "'goto' is"));
+            }
+            StatementKind::Break => {
+                let labels = &self.builder().labels;
+                // the checker rejects `break` outside loops
+                let target = labels.iter().rposition(|&breakable| breakable).unwrap();
+                let depth = labels.len() - 1 - target;
+                self.push(Instruction::Br(depth as u32));
+            }
+            StatementKind::Do(body) => self.block(body)?,
+            StatementKind::While { condition, body } => {
+                self.enter(Instruction::Block(BlockType::Empty), true);
+                self.enter(Instruction::Loop(BlockType::Empty), false);
+                self.condition(condition)?;
+                self.push(Instruction::I32Eqz);
+                self.push(Instruction::BrIf(1));
+                self.block(body)?;
+                self.push(Instruction::Br(0));
+                self.end();
+                self.end();
+            }
+            StatementKind::Repeat { body, condition } => {
+                self.enter(Instruction::Block(BlockType::Empty), true);
+                self.enter(Instruction::Loop(BlockType::Empty), false);
+                // the condition sees the body's locals
+                self.scopes.push(HashMap::new());
+                self.statements(body)?;
+                self.condition(condition)?;
+                self.scopes.pop();
+                self.push(Instruction::I32Eqz);
+                self.push(Instruction::BrIf(0));
+                self.end();
+                self.end();
+            }
+            StatementKind::If {
+                branches,
+                otherwise,
+            } => {
+                for branch in branches {
+                    self.condition(&branch.condition)?;
+                    self.enter(Instruction::If(BlockType::Empty), false);
+                    self.block(&branch.body)?;
+                    self.push(Instruction::Else);
+                }
+                if let Some(body) = otherwise {
+                    self.block(body)?;
+                }
+                for _ in branches {
+                    self.end();
+                }
+            }
+            StatementKind::NumericFor {
+                var,
+                start,
+                limit,
+                step,
+                body,
+            } => self.numeric_for(var, start, limit, step.as_ref(), body)?,
+            StatementKind::GenericFor {
+                names,
+                values,
+                body,
+            } => self.generic_for(names, values, body, statement.span)?,
+            StatementKind::Variables {
+                scope,
+                names,
+                values,
+                ..
+            } => {
+                let mut types = Vec::with_capacity(names.len());
+                for name in names {
+                    let ty = self.info.name(&name.name);
+                    // forward declarations of global functions
+                    if let (Type::Function(_), Some(Var::Function { .. }), true) =
+                        (&ty, self.scopes[0].get(&name.name.name), values.is_empty())
+                    {
+                        return Ok(());
+                    }
+                    types.push(ty);
+                }
+                self.values(values, &types)?;
+                let mut vars = Vec::with_capacity(names.len());
+                for (name, ty) in names.iter().zip(&types) {
+                    vars.push(self.declare(*scope, &name.name, ty)?);
+                }
+                for var in vars.iter().rev() {
+                    self.set(var);
+                }
+            }
+            StatementKind::Function(decl) => self.function_decl(decl)?,
+            // types are only checked
+            StatementKind::Type { .. } => {}
+        }
+        Ok(())
+    }
+
+    /// The variable assigned to by `target`.
+    fn variable(&mut self, target: &Expr) -> Result<Var, CodegenError> {
+        let ExprKind::Name(name) = &target.kind else {
+            return Err(unsupported(target.span, "assigning to fields is"));
+        };
+        match self.resolve(name)? {
+            Var::Function { .. } => Err(unsupported(target.span, "assigning to functions is")),
+            var => Ok(var),
+        }
+    }
+
+    /// The variable `name` refers to, visible from the current function.
+    fn resolve(&self, name: &Name) -> Result<Var, CodegenError> {
+        match self.lookup(&name.name) {
+            Some(Var::Local { function, .. }) if *function != self.builders.len() - 1 => Err(
+                unsupported(name.span, format!("closures over '{}' are", name.name)),
+            ),
+            Some(var) => Ok(var.clone()),
+            None => Err(unsupported(
+                name.span,
+                format!("values of the host such as '{}' are", name.name),
+            )),
+        }
+    }
+
+    fn numeric_for(
+        &mut self,
+        var: &Name,
+        start: &Expr,
+        limit: &Expr,
+        step: Option<&Expr>,
+        body: &Block,
+    ) -> Result<(), CodegenError> {
+        let ty = self.info.name(var);
+        let val_type = self.lower(&ty, var.span)?;
+        let counter = self.local(val_type);
+        let end = self.local(val_type);
+        let increment = self.local(val_type);
+        self.expr_as(start, &ty)?;
+        self.push(Instruction::LocalSet(counter));
+        self.expr_as(limit, &ty)?;
+        self.push(Instruction::LocalSet(end));
+        match step {
+            Some(step) => self.expr_as(step, &ty)?,
+            None => self.constant(&ty, 1.0),
+        }
+        self.push(Instruction::LocalSet(increment));
+
+        self.enter(Instruction::Block(BlockType::Empty), true);
+        self.enter(Instruction::Loop(BlockType::Empty), false);
+        let [le, ge, gt] = comparisons(val_type);
+        let compare = |emitter: &mut Self, op: Instruction<'static>| {
+            emitter.push(Instruction::LocalGet(counter));
+            emitter.push(Instruction::LocalGet(end));
+            emitter.push(op);
+        };
+        match step.map_or(Some(false), constant_sign) {
+            Some(negative) => compare(self, if negative { ge } else { le }),
+            None => {
+                self.push(Instruction::LocalGet(increment));
+                self.constant(&ty, 0.0);
+                self.push(gt);
+                self.enter(Instruction::If(BlockType::Result(ValType::I32)), false);
+                compare(self, le);
+                self.push(Instruction::Else);
+                compare(self, ge);
+                self.end();
+            }
+        }
+        self.push(Instruction::I32Eqz);
+        self.push(Instruction::BrIf(1));
+
+        // the body gets a copy of the counter it may assign to
+        self.scopes.push(HashMap::new());
+        let copy = self.declare(Scope::Local, var, &ty)?;
+        self.push(Instruction::LocalGet(counter));
+        self.set(&copy);
+        self.statements(body)?;
+        self.scopes.pop();
+
+        self.push(Instruction::LocalGet(counter));
+        self.push(Instruction::LocalGet(increment));
+        self.push(arithmetic(BinOp::Add, val_type).unwrap());
+        self.push(Instruction::LocalSet(counter));
+        self.push(Instruction::Br(0));
+        self.end();
+        self.end();
+        Ok(())
+    }
+
+    /// `for i, v in ipairs(array)`, or `pairs(array)`, the only iterators
+    /// supported.
+    fn generic_for(
+        &mut self,
+        names: &[Name],
+        values: &[Expr],
+        body: &Block,
+        span: Span,
+    ) -> Result<(), CodegenError> {
+        let iterated = match values {
+            [
+                Expr {
+                    kind: ExprKind::Call { callee, args },
+                    ..
+                },
+            ] => match (&callee.kind, args.as_slice()) {
+                (ExprKind::Name(iterator), [array])
+                    if matches!(iterator.name.as_str(), "ipairs" | "pairs")
+                        && self.lookup(&iterator.name).is_none() =>
+                {
+                    match self.info.expr(array) {
+                        Type::Array(element) => Some((array, element)),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            },
+            _ => None,
+        };
+        let Some((array, element)) = iterated else {
+            return Err(unsupported(
+                span,
+                "'for ... in' loops other than over ipairs() or pairs() of arrays are",
+            ));
+        };
+        let val_type = self.lower(&element, array.span)?;
+        let (address, at) = (self.local(ValType::I32), self.local(ValType::I32));
+        self.expr(array)?;
+        self.push(Instruction::LocalSet(address));
+        self.push(Instruction::I32Const(0));
+        self.push(Instruction::LocalSet(at));
+
+        self.enter(Instruction::Block(BlockType::Empty), true);
+        self.enter(Instruction::Loop(BlockType::Empty), false);
+        self.push(Instruction::LocalGet(at));
+        self.push(Instruction::LocalGet(address));
+        self.push(Instruction::I32Load(WORD));
+        self.push(Instruction::I32GeU);
+        self.push(Instruction::BrIf(1));
+
+        self.scopes.push(HashMap::new());
+        for (i, name) in names.iter().enumerate() {
+            let ty = self.info.name(name);
+            let var = self.declare(Scope::Local, name, &ty)?;
+            match i {
+                0 => {
+                    self.push(Instruction::LocalGet(at));
+                    self.push(Instruction::I32Const(1));
+                    self.push(Instruction::I32Add);
+                    self.push(Instruction::I64ExtendI32U);
+                    self.convert(&Type::INTEGER, &ty, false, name.span)?;
+                }
+                1 => {
+                    self.push(Instruction::LocalGet(address));
+                    self.push(Instruction::LocalGet(at));
+                    self.push(Instruction::I32Const(width(val_type)));
+                    self.push(Instruction::I32Mul);
+                    self.push(Instruction::I32Add);
+                    self.push(load(val_type, ELEMENTS));
+                    self.convert(&element, &ty, false, name.span)?;
+                }
+                _ => self.constant(&ty, 0.0),
+            }
+            self.set(&var);
+        }
+        self.statements(body)?;
+        self.scopes.pop();
+
+        self.push(Instruction::LocalGet(at));
+        self.push(Instruction::I32Const(1));
+        self.push(Instruction::I32Add);
+        self.push(Instruction::LocalSet(at));
+        self.push(Instruction::Br(0));
+        self.end();
+        self.end();
+        Ok(())
+    }
+
+    fn function_decl(&mut self, decl: &FunctionDecl) -> Result<(), CodegenError> {
+        if decl.method.is_some() || decl.path.len() > 1 {
+            return Err(unsupported(decl.span, "functions in tables are"));
+        }
+        let name = &decl.path[0];
+        let (slot, sig) = match decl.scope {
+            Some(Scope::Local) => {
+                let sig = self.signature(name)?;
+                let slot = self.define(&sig, None, name)?;
+                // declared before its body, which may call it
+                let var = Var::Function {
+                    slot,
+                    sig: sig.clone(),
+                };
+                self.scopes
+                    .last_mut()
+                    .unwrap()
+                    .insert(name.name.clone(), var);
+                (slot, sig)
+            }
+            _ => match self.scopes[0].get(&name.name) {
+                Some(Var::Function { slot, sig }) => (*slot, sig.clone()),
+                _ => return Err(unsupported(name.span, "functions in variables are")),
+            },
+        };
+        self.function(slot, &sig, &decl.body)
+    }
+
+    /// Emit the body of the function at `slot`, of type `sig`.
+    fn function(
+        &mut self,
+        slot: usize,
+        sig: &FunctionSig,
+        body: &FunctionBody,
+    ) -> Result<(), CodegenError> {
+        let params = self.lower_all(&sig.params, body.span)?;
+        let names = body.params.iter().enumerate();
+        self.builders.push(Builder {
+            params: params.len(),
+            locals: params,
+            names: names
+                .map(|(i, p)| (i as u32, p.name.name.clone()))
+                .collect(),
+            returns: sig.returns.clone(),
+            ..Builder::default()
+        });
+        self.scopes.push(HashMap::new());
+        let function = self.builders.len() - 1;
+        for (index, (param, ty)) in body.params.iter().zip(&sig.params).enumerate() {
+            let var = Var::Local {
+                function,
+                index: index as u32,
+                ty: ty.clone(),
+            };
+            self.scopes
+                .last_mut()
+                .unwrap()
+                .insert(param.name.name.clone(), var);
+        }
+        let result = self.statements(&body.block);
+        self.scopes.pop();
+        let mut builder = self.builders.pop().unwrap();
+        result?;
+        if !sig.returns.is_empty() {
+            // falling off the end of a function returning values traps
+            builder.body.push(Instruction::Unreachable);
+            builder.spans.push(body.span);
+        }
+        if let SlotKind::Defined { body, .. } = &mut self.functions[slot].kind {
+            *body = Some(builder);
+        }
+        Ok(())
+    }
+
+    /// Push one value of each of `types` from `exprs`, converted, dropping
+    /// those in excess and pushing `nil` for those missing.
+    fn values(&mut self, exprs: &[Expr], types: &[Type]) -> Result<(), CodegenError> {
+        for (i, expr) in exprs.iter().enumerate() {
+            let is_call = matches!(
+                expr.kind,
+                ExprKind::Call { .. } | ExprKind::MethodCall { .. }
+            );
+            if i + 1 == exprs.len() && types.len() > i + 1 && is_call {
+                // the values of the last call expand to the missing ones
+                let results = self.call(expr, false)?;
+                let mut temporaries = Vec::with_capacity(results.len());
+                for result in results.iter().rev() {
+                    let local = self.local(self.lower(result, expr.span)?);
+                    self.push(Instruction::LocalSet(local));
+                    temporaries.push(local);
+                }
+                temporaries.reverse();
+                for (j, ty) in types[i..].iter().enumerate() {
+                    match temporaries.get(j) {
+                        Some(&local) => {
+                            self.push(Instruction::LocalGet(local));
+                            self.convert(&results[j], ty, false, expr.span)?;
+                        }
+                        None => self.convert(&Type::NIL, ty, false, expr.span)?,
+                    }
+                }
+                return Ok(());
+            }
+            match types.get(i) {
+                Some(ty) => self.expr_as(expr, ty)?,
+                None => {
+                    let ty = self.expr(expr)?;
+                    if val_type(&ty).is_some() {
+                        self.push(Instruction::Drop);
+                    }
+                }
+            }
+        }
+        for ty in types.iter().skip(exprs.len()) {
+            self.convert(&Type::NIL, ty, false, Span::default())?;
+        }
+        Ok(())
+    }
+
+    /// Push the value of `condition` as an `i32`, 0 if false or `nil`.
+    fn condition(&mut self, condition: &Expr) -> Result<(), CodegenError> {
+        let ty = self.expr(condition)?;
+        match &ty {
+            Type::Primitive(Primitive::Boolean) => {}
+            Type::Primitive(Primitive::Nil) => self.push(Instruction::I32Const(0)),
+            Type::Primitive(Primitive::String) | Type::Enum(_) => {
+                self.push(Instruction::I32Const(0));
+                self.push(Instruction::I32Ne);
+            }
+            // numbers are always true
+            _ => {
+                self.push(Instruction::Drop);
+                self.push(Instruction::I32Const(1));
+            }
+        }
+        Ok(())
+    }
+
+    /// Push the value of `expr` converted to `ty`.
+    fn expr_as(&mut self, expr: &Expr, ty: &Type) -> Result<(), CodegenError> {
+        let from = self.expr(expr)?;
+        self.convert(&from, ty, false, expr.span)
+    }
+
+    /// Push the first value of `expr`, nothing if `nil`, returning its type.
+    fn expr(&mut self, expr: &Expr) -> Result<Type, CodegenError> {
+        let outer = std::mem::replace(&mut self.span, expr.span);
+        let result = self.expr_value(expr);
+        self.span = outer;
+        result
+    }
+
+    /// [`Self::expr()`], with the instructions mapped to the span of `expr`.
+    fn expr_value(&mut self, expr: &Expr) -> Result<Type, CodegenError> {
+        let ty = self.info.expr(expr);
+        match &expr.kind {
+            ExprKind::Nil => return Ok(Type::NIL),
+            ExprKind::Boolean(value) => self.push(Instruction::I32Const(*value as i32)),
+            ExprKind::Integer(value) => {
+                self.push(Instruction::I64Const(*value));
+                return Ok(Type::INTEGER);
+            }
+            ExprKind::Number(value) => {
+                self.push(Instruction::F64Const((*value).into()));
+                return Ok(Type::NUMBER);
+            }
+            ExprKind::String(value) => {
+                let address = self.string(value);
+                self.push(Instruction::I32Const(address as i32));
+                if !matches!(ty, Type::Enum(_)) {
+                    return Ok(Type::STRING);
+                }
+            }
+            ExprKind::Name(name) => match self.resolve(name)? {
+                Var::Local { index, ty, .. } => {
+                    self.push(Instruction::LocalGet(index));
+                    return Ok(ty);
+                }
+                Var::Global { index, ty } => {
+                    self.push(Instruction::GlobalGet(index));
+                    return Ok(ty);
+                }
+                Var::Function { .. } => {
+                    return Err(unsupported(expr.span, "functions as values are"));
+                }
+            },
+            ExprKind::Paren(inner) => return self.expr(inner),
+            ExprKind::Unary { op, operand } => return self.unary(*op, operand, ty, expr.span),
+            ExprKind::Binary { op, lhs, rhs } => {
+                return self.binary(*op, lhs, rhs, ty, expr.span);
+            }
+            ExprKind::Call { .. } | ExprKind::MethodCall { .. } => {
+                let results = self.call(expr, false)?;
+                for _ in results.iter().skip(1) {
+                    self.push(Instruction::Drop);
+                }
+                return Ok(results.into_iter().next().unwrap_or(Type::NIL));
+            }
+            ExprKind::Cast { expr: inner, types } => {
+                let from = self.expr(inner)?;
+                let to = self.info.expr(expr);
+                if types.len() > 1 {
+                    return Err(unsupported(expr.span, "casts to several types are"));
+                }
+                self.convert(&from, &to, true, expr.span)?;
+                return Ok(to);
+            }
+            ExprKind::Varargs => return Err(unsupported(expr.span, "varargs are")),
+            ExprKind::Function(_) => {
+                return Err(unsupported(expr.span, "anonymous functions are"));
+            }
+            ExprKind::Index { target, index } => {
+                if let Type::Array(element) = self.info.expr(target) {
+                    self.element(target, index, &element, expr.span)?;
+                    return Ok((*element).clone());
+                }
+                return Err(unsupported(expr.span, "tables are"));
+            }
+            ExprKind::Table(fields) => {
+                let Type::Array(element) = &ty else {
+                    return Err(unsupported(expr.span, "tables other than arrays are"));
+                };
+                self.array(fields, element, expr.span)?;
+            }
+            ExprKind::Field { target, name } => {
+                return match self.library_constant(target, name) {
+                    Some(ty) => Ok(ty),
+                    None => Err(unsupported(expr.span, "tables are")),
+                };
+            }
+            ExprKind::Is { .. } => return Err(unsupported(expr.span, "'is' is")),
+        }
+        Ok(ty)
+    }
+
+    /// Push the element `index` of the array `target`, of type `element`,
+    /// trapping if out of bounds.
+    fn element(
+        &mut self,
+        target: &Expr,
+        index: &Expr,
+        element: &Type,
+        span: Span,
+    ) -> Result<(), CodegenError> {
+        let ty = self.lower(element, span)?;
+        let (array, offset) = (self.local(ValType::I32), self.local(ValType::I64));
+        self.expr(target)?;
+        self.push(Instruction::LocalSet(array));
+        self.expr_as(index, &Type::INTEGER)?;
+        self.push(Instruction::I64Const(1));
+        self.push(Instruction::I64Sub);
+        self.push(Instruction::LocalTee(offset));
+        // indices below 1 wrap around to above the length
+        self.push(Instruction::LocalGet(array));
+        self.push(Instruction::I32Load(WORD));
+        self.push(Instruction::I64ExtendI32U);
+        self.push(Instruction::I64LtU);
+        self.push(Instruction::I32Eqz);
+        self.enter(Instruction::If(BlockType::Empty), false);
+        self.push(Instruction::Unreachable);
+        self.end();
+        self.push(Instruction::LocalGet(array));
+        self.push(Instruction::LocalGet(offset));
+        self.push(Instruction::I32WrapI64);
+        self.push(Instruction::I32Const(width(ty)));
+        self.push(Instruction::I32Mul);
+        self.push(Instruction::I32Add);
+        self.push(load(ty, ELEMENTS));
+        Ok(())
+    }
+
+    /// Push a new array of the values of `fields`, of type `element`.
+    fn array(
+        &mut self,
+        fields: &[TableField],
+        element: &Type,
+        span: Span,
+    ) -> Result<(), CodegenError> {
+        if fields.is_empty() {
+            // the empty array, also that of `nil`
+            self.push(Instruction::I32Const(0));
+            return Ok(());
+        }
+        let ty = self.lower(element, span)?;
+        let array = self.local(ValType::I32);
+        self.push(Instruction::I32Const(4 + fields.len() as i32 * width(ty)));
+        self.helper(Helper::Alloc);
+        self.push(Instruction::LocalTee(array));
+        self.push(Instruction::I32Const(fields.len() as i32));
+        self.push(Instruction::I32Store(WORD));
+        for (i, field) in fields.iter().enumerate() {
+            if !matches!(field.key, FieldKey::Positional) {
+                return Err(unsupported(field.span, "keyed fields in arrays are"));
+            }
+            self.push(Instruction::LocalGet(array));
+            self.expr_as(&field.value, element)?;
+            let at = MemArg {
+                offset: ELEMENTS.offset + (i as i32 * width(ty)) as u64,
+                ..ELEMENTS
+            };
+            self.push(store(ty, at));
+        }
+        self.push(Instruction::LocalGet(array));
+        Ok(())
+    }
+
+    /// Push the constant `name` of the library `target`, eg. `math.pi`,
+    /// returning its type, if `target` is the library.
+    fn library_constant(&mut self, target: &Expr, name: &Name) -> Option<Type> {
+        let ExprKind::Name(library) = &target.kind else {
+            return None;
+        };
+        if library.name != "math" || self.lookup(&library.name).is_some() {
+            return None;
+        }
+        let (instruction, ty) = match name.name.as_str() {
+            "pi" => (
+                Instruction::F64Const(std::f64::consts::PI.into()),
+                Type::NUMBER,
+            ),
+            "huge" => (Instruction::F64Const(f64::INFINITY.into()), Type::NUMBER),
+            "maxinteger" => (Instruction::I64Const(i64::MAX), Type::INTEGER),
+            "mininteger" => (Instruction::I64Const(i64::MIN), Type::INTEGER),
+            _ => return None,
+        };
+        self.push(instruction);
+        Some(ty)
+    }
+
+    /// Push a `ty` of value `value`.
+    fn constant(&mut self, ty: &Type, value: f64) {
+        let instruction = match val_type(ty) {
+            Some(ValType::I64) => Instruction::I64Const(value as i64),
+            Some(ValType::F32) => Instruction::F32Const((value as f32).into()),
+            Some(ValType::F64) => Instruction::F64Const(value.into()),
+            _ => Instruction::I32Const(value as i32),
+        };
+        self.push(instruction);
+    }
+
+    /// Convert the value of type `from` on the stack to a `to`, truncating
+    /// numbers to integers if `cast`.
+    fn convert(
+        &mut self,
+        from: &Type,
+        to: &Type,
+        cast: bool,
+        span: Span,
+    ) -> Result<(), CodegenError> {
+        let target = self.lower(to, span)?;
+        if matches!(from, Type::Primitive(Primitive::Nil)) {
+            self.constant(to, 0.0);
+            return Ok(());
+        }
+        let source = self.lower(from, span)?;
+        let unsigned = is_unsigned(from);
+        let instruction = match (source, target) {
+            (a, b) if a == b => return Ok(()),
+            (ValType::I32, ValType::I64) if unsigned => Instruction::I64ExtendI32U,
+            (ValType::I32, ValType::I64) => Instruction::I64ExtendI32S,
+            (ValType::I64, ValType::I32) => Instruction::I32WrapI64,
+            (ValType::I32, ValType::F64) if unsigned => Instruction::F64ConvertI32U,
+            (ValType::I32, ValType::F64) => Instruction::F64ConvertI32S,
+            (ValType::I64, ValType::F64) if unsigned => Instruction::F64ConvertI64U,
+            (ValType::I64, ValType::F64) => Instruction::F64ConvertI64S,
+            (ValType::I32, ValType::F32) if unsigned => Instruction::F32ConvertI32U,
+            (ValType::I32, ValType::F32) => Instruction::F32ConvertI32S,
+            (ValType::I64, ValType::F32) if unsigned => Instruction::F32ConvertI64U,
+            (ValType::I64, ValType::F32) => Instruction::F32ConvertI64S,
+            (ValType::F32, ValType::F64) => Instruction::F64PromoteF32,
+            (ValType::F64, ValType::F32) => Instruction::F32DemoteF64,
+            (ValType::F64, ValType::I64) if cast => Instruction::I64TruncSatF64S,
+            (ValType::F64, ValType::I32) if cast => Instruction::I32TruncSatF64S,
+            (ValType::F32, ValType::I64) if cast => Instruction::I64TruncSatF32S,
+            (ValType::F32, ValType::I32) if cast => Instruction::I32TruncSatF32S,
+            _ => {
+                return Err(unsupported(
+                    span,
+                    format!("converting values of type {from} to {to} is"),
+                ));
+            }
+        };
+        self.push(instruction);
+        Ok(())
+    }
+
+    fn unary(
+        &mut self,
+        op: UnOp,
+        operand: &Expr,
+        ty: Type,
+        span: Span,
+    ) -> Result<Type, CodegenError> {
+        if op == UnOp::Not {
+            self.condition(operand)?;
+            self.push(Instruction::I32Eqz);
+            return Ok(Type::BOOLEAN);
+        }
+        let from = self.expr(operand)?;
+        if op == UnOp::Len {
+            if !matches!(
+                from,
+                Type::Primitive(Primitive::String) | Type::Enum(_) | Type::Array(_)
+            ) {
+                return Err(unsupported(span, "'#' on tables is"));
+            }
+            self.push(Instruction::I32Load(WORD));
+            self.push(Instruction::I64ExtendI32U);
+            return Ok(Type::INTEGER);
+        }
+        self.convert(&from, &ty, false, span)?;
+        let instructions = match (op, self.lower(&ty, span)?) {
+            (UnOp::Neg, ValType::I32) => [Instruction::I32Const(-1), Instruction::I32Mul],
+            (UnOp::Neg, ValType::I64) => [Instruction::I64Const(-1), Instruction::I64Mul],
+            (UnOp::Neg, ValType::F32) => [Instruction::F32Neg, Instruction::Nop],
+            (UnOp::Neg, ValType::F64) => [Instruction::F64Neg, Instruction::Nop],
+            (UnOp::BitNot, ValType::I32) => [Instruction::I32Const(-1), Instruction::I32Xor],
+            (UnOp::BitNot, ValType::I64) => [Instruction::I64Const(-1), Instruction::I64Xor],
+            _ => {
+                return Err(unsupported(
+                    span,
+                    format!("'{op}' on values of type {ty} is"),
+                ));
+            }
+        };
+        for instruction in instructions {
+            if !matches!(instruction, Instruction::Nop) {
+                self.push(instruction);
+            }
+        }
+        Ok(ty)
+    }
+
+    fn binary(
+        &mut self,
+        op: BinOp,
+        lhs: &Expr,
+        rhs: &Expr,
+        ty: Type,
+        span: Span,
+    ) -> Result<Type, CodegenError> {
+        match op {
+            BinOp::And | BinOp::Or => {
+                let both_boolean = [lhs, rhs]
+                    .iter()
+                    .all(|e| matches!(self.info.expr(e), Type::Primitive(Primitive::Boolean)));
+                if !both_boolean {
+                    return Err(unsupported(
+                        span,
+                        format!("'{op}' on values other than booleans is"),
+                    ));
+                }
+                // short-circuiting
+                self.expr(lhs)?;
+                self.enter(Instruction::If(BlockType::Result(ValType::I32)), false);
+                match op {
+                    BinOp::And => {
+                        self.expr(rhs)?;
+                        self.push(Instruction::Else);
+                        self.push(Instruction::I32Const(0));
+                    }
+                    _ => {
+                        self.push(Instruction::I32Const(1));
+                        self.push(Instruction::Else);
+                        self.expr(rhs)?;
+                    }
+                }
+                self.end();
+                return Ok(Type::BOOLEAN);
+            }
+            BinOp::Concat => {
+                for operand in [lhs, rhs] {
+                    let from = self.expr(operand)?;
+                    if !matches!(
+                        from,
+                        Type::Primitive(Primitive::String | Primitive::Nil) | Type::Enum(_)
+                    ) {
+                        return Err(unsupported(operand.span, "'..' on numbers is"));
+                    }
+                    self.convert(&from, &Type::STRING, false, operand.span)?;
+                }
+                self.helper(Helper::Concat);
+                return Ok(Type::STRING);
+            }
+            _ => {}
+        }
+
+        let (lhs_type, rhs_type) = (self.info.expr(lhs), self.info.expr(rhs));
+        if op.is_comparison() {
+            let strings = [&lhs_type, &rhs_type].iter().all(|t| {
+                matches!(
+                    t,
+                    Type::Primitive(Primitive::String | Primitive::Nil) | Type::Enum(_)
+                )
+            });
+            if strings {
+                if !matches!(op, BinOp::Eq | BinOp::Ne) {
+                    return Err(unsupported(span, "ordering strings is"));
+                }
+                self.expr_as(lhs, &Type::STRING)?;
+                self.expr_as(rhs, &Type::STRING)?;
+                self.helper(Helper::StrEq);
+                if op == BinOp::Ne {
+                    self.push(Instruction::I32Eqz);
+                }
+                return Ok(Type::BOOLEAN);
+            }
+            let operands = common(&lhs_type, &rhs_type);
+            self.expr_as(lhs, &operands)?;
+            self.expr_as(rhs, &operands)?;
+            let instruction = comparison(op, self.lower(&operands, span)?, is_unsigned(&operands));
+            self.push(instruction);
+            return Ok(Type::BOOLEAN);
+        }
+
+        // floor division and modulo are computed on 64 bits
+        let operands = match op {
+            BinOp::IntDiv | BinOp::Mod if ty.is_integer() => Type::INTEGER,
+            BinOp::IntDiv | BinOp::Mod | BinOp::Div | BinOp::Pow => Type::NUMBER,
+            _ => ty.clone(),
+        };
+        self.expr_as(lhs, &operands)?;
+        self.expr_as(rhs, &operands)?;
+        let val_type = self.lower(&operands, span)?;
+        match (op, val_type) {
+            (BinOp::IntDiv, ValType::I64) => self.helper(Helper::IntDiv),
+            (BinOp::Mod, ValType::I64) => self.helper(Helper::IntMod),
+            (BinOp::Mod, ValType::F64) => self.helper(Helper::FloatMod),
+            (BinOp::IntDiv, ValType::F64) => {
+                self.push(Instruction::F64Div);
+                self.push(Instruction::F64Floor);
+            }
+            (BinOp::Pow, _) => {
+                let pow = self.import(
+                    HOST_MODULE,
+                    "pow",
+                    &[ValType::F64, ValType::F64],
+                    &[ValType::F64],
+                );
+                self.push(Instruction::Call(pow as u32));
+            }
+            (op, val_type) => match arithmetic(op, val_type) {
+                Some(instruction) => self.push(instruction),
+                None => {
+                    return Err(unsupported(
+                        span,
+                        format!("'{op}' on values of type {ty} is"),
+                    ));
+                }
+            },
+        }
+        self.convert(&operands, &ty, false, span)?;
+        Ok(ty)
+    }
+
+    /// Push the values returned by the call `expr`, returning their types.
+    /// Values of the host's functions of type `any` are only supported if
+    /// `discard`ed.
+    fn call(&mut self, expr: &Expr, discard: bool) -> Result<Vec<Type>, CodegenError> {
+        let (callee, args) = match &expr.kind {
+            ExprKind::Call { callee, args } => (callee, args),
+            ExprKind::MethodCall {
+                receiver,
+                method,
+                args,
+            } => return self.method_call(expr, receiver, method, args, discard),
+            _ => return Err(unsupported(expr.span, "calling this expression is")),
+        };
+        if let Some((slot, loaded)) = self.required(callee, args) {
+            // modules run the first time they are required
+            self.push(Instruction::GlobalGet(loaded));
+            self.push(Instruction::I32Eqz);
+            self.enter(Instruction::If(BlockType::Empty), false);
+            self.push(Instruction::I32Const(1));
+            self.push(Instruction::GlobalSet(loaded));
+            self.push(Instruction::Call(slot as u32));
+            self.end();
+            return Ok(Vec::new());
+        }
+        // `module.name(...)` calls a function of a module of the host, unless
+        // `module` is a variable
+        let (module, name) = match &callee.kind {
+            ExprKind::Name(name) => (HOST_MODULE, name),
+            ExprKind::Field { target, name } => match &target.kind {
+                ExprKind::Name(module) if self.lookup(&module.name).is_none() => {
+                    (module.name.as_str(), name)
+                }
+                _ => return Err(unsupported(callee.span, "calling function values is")),
+            },
+            _ => return Err(unsupported(callee.span, "calling function values is")),
+        };
+        if let Some(returns) = self.intrinsic(module, &name.name, args, expr)? {
+            return Ok(returns);
+        }
+        let declared = match module {
+            HOST_MODULE => self.lookup(&name.name).cloned(),
+            _ => None,
+        };
+        match declared {
+            Some(Var::Function { slot, sig }) => {
+                self.values(args, &sig.params)?;
+                self.push(Instruction::Call(slot as u32));
+                Ok(sig.returns.clone())
+            }
+            Some(_) => Err(unsupported(callee.span, "calling function values is")),
+            None => {
+                let Type::Function(sig) = self.info.expr(callee) else {
+                    return Err(unsupported(
+                        callee.span,
+                        format!(
+                            "calling '{}' of type {} is",
+                            name.name,
+                            self.info.expr(callee)
+                        ),
+                    ));
+                };
+                self.import_call(module, &name.name, &sig, args, discard, expr.span)
+            }
+        }
+    }
+
+    /// `receiver:method(args)`, supported on strings as
+    /// `string.method(receiver, args)`.
+    fn method_call(
+        &mut self,
+        expr: &Expr,
+        receiver: &Expr,
+        method: &Name,
+        args: &[Expr],
+        discard: bool,
+    ) -> Result<Vec<Type>, CodegenError> {
+        let receiver_type = self.info.expr(receiver);
+        let (Type::Primitive(Primitive::String) | Type::Enum(_), Type::Function(sig)) =
+            (&receiver_type, self.info.name(method))
+        else {
+            return Err(unsupported(expr.span, "methods are"));
+        };
+        let args: Vec<Expr> = std::iter::once(receiver).chain(args).cloned().collect();
+        if let Some(returns) = self.intrinsic("string", &method.name, &args, expr)? {
+            return Ok(returns);
+        }
+        self.import_call("string", &method.name, &sig, &args, discard, expr.span)
+    }
+
+    /// Call the function `name` of the host module `module`, of signature
+    /// `sig`, importing it first if needed.
+    fn import_call(
+        &mut self,
+        module: &str,
+        name: &str,
+        sig: &FunctionSig,
+        args: &[Expr],
+        discard: bool,
+        span: Span,
+    ) -> Result<Vec<Type>, CodegenError> {
+        // parameters of type `any` take the type of their argument, and the
+        // import is named after them, as are those of overloaded functions
+        // and of varargs, whose number varies
+        let mut specialized = !sig.overloads.is_empty() || sig.varargs.is_some();
+        let sig = sig.for_arity(args.len());
+        let count = match sig.varargs {
+            Some(_) => args.len().max(sig.params.len()),
+            None => sig.params.len(),
+        };
+        let mut params = Vec::with_capacity(count);
+        for i in 0..count {
+            let declared = sig.params.get(i).or(sig.varargs.as_ref());
+            let ty = match (declared, args.get(i)) {
+                (Some(ty), _) if val_type(ty).is_some() => ty.clone(),
+                (_, Some(arg)) => {
+                    specialized = true;
+                    self.info.expr(arg)
+                }
+                _ => Type::Unknown,
+            };
+            params.push(ty);
+        }
+        let import = match specialized {
+            true => {
+                let types: Vec<String> = params.iter().map(Type::to_string).collect();
+                format!("{name}({})", types.join(","))
+            }
+            false => name.to_owned(),
+        };
+        let mut returns = sig.returns.clone();
+        if discard && returns.iter().any(|ty| val_type(ty).is_none()) {
+            returns.clear();
+        }
+        let lowered_params = self.lower_all(&params, span)?;
+        let lowered_returns = self.lower_all(&returns, span)?;
+        let slot = self.import(module, &import, &lowered_params, &lowered_returns);
+        self.values(args, &params)?;
+        self.push(Instruction::Call(slot as u32));
+        Ok(returns)
+    }
+
+    /// Push the results of the call `expr` of the function `name` of the
+    /// standard library `library` with `args`, if it is lowered to
+    /// instructions rather than imported.
+    fn intrinsic(
+        &mut self,
+        library: &str,
+        name: &str,
+        args: &[Expr],
+        expr: &Expr,
+    ) -> Result<Option<Vec<Type>>, CodegenError> {
+        use Instruction::*;
+        let ty = self.info.expr(expr);
+        let returns = match (library, name, args) {
+            ("math", "floor" | "ceil", [x]) => {
+                self.expr_as(x, &Type::NUMBER)?;
+                self.push(if name == "floor" { F64Floor } else { F64Ceil });
+                self.push(I64TruncSatF64S);
+                vec![Type::INTEGER]
+            }
+            ("math", "sqrt", [x]) => {
+                self.expr_as(x, &Type::NUMBER)?;
+                self.push(F64Sqrt);
+                vec![Type::NUMBER]
+            }
+            ("math", "abs", [x]) => {
+                let val_type = self.lower(&ty, expr.span)?;
+                self.expr_as(x, &ty)?;
+                match val_type {
+                    ValType::F32 => self.push(F32Abs),
+                    ValType::F64 => self.push(F64Abs),
+                    _ => {
+                        // `-x` if `x < 0`
+                        let value = self.local(val_type);
+                        self.push(LocalSet(value));
+                        self.constant(&ty, 0.0);
+                        self.push(LocalGet(value));
+                        self.push(arithmetic(BinOp::Sub, val_type).unwrap());
+                        self.push(LocalGet(value));
+                        self.push(LocalGet(value));
+                        self.constant(&ty, 0.0);
+                        self.push(comparison(BinOp::Lt, val_type, is_unsigned(&ty)));
+                        self.push(Select);
+                    }
+                }
+                vec![ty]
+            }
+            ("math", "min" | "max", [first, rest @ ..]) => {
+                let val_type = self.lower(&ty, expr.span)?;
+                let op = if name == "min" { BinOp::Lt } else { BinOp::Gt };
+                let op = comparison(op, val_type, is_unsigned(&ty));
+                let (a, b) = (self.local(val_type), self.local(val_type));
+                self.expr_as(first, &ty)?;
+                for arg in rest {
+                    self.push(LocalSet(a));
+                    self.expr_as(arg, &ty)?;
+                    self.push(LocalSet(b));
+                    self.push(LocalGet(a));
+                    self.push(LocalGet(b));
+                    self.push(LocalGet(a));
+                    self.push(LocalGet(b));
+                    self.push(op.clone());
+                    self.push(Select);
+                }
+                vec![ty]
+            }
+            ("string", "len", [s]) => {
+                self.expr_as(s, &Type::STRING)?;
+                self.push(I32Load(WORD));
+                self.push(I64ExtendI32U);
+                vec![Type::INTEGER]
+            }
+            ("table", "insert", [array, value]) => {
+                self.insert(array, None, value)?;
+                Vec::new()
+            }
+            ("table", "insert", [array, position, value]) => {
+                self.insert(array, Some(position), value)?;
+                Vec::new()
+            }
+            ("table", "sort", [array]) => {
+                self.sort(array)?;
+                Vec::new()
+            }
+            ("table", "remove", [array]) => vec![self.remove(array, None)?],
+            ("table", "remove", [array, position]) => vec![self.remove(array, Some(position))?],
+            _ => return Ok(None),
+        };
+        Ok(Some(returns))
+    }
+
+    /// `table.insert(array, position, value)`: replace the array in the
+    /// variable `array` by a copy with `value` inserted at `position`, after
+    /// the last element by default. Positions out of bounds trap.
+    fn insert(
+        &mut self,
+        array: &Expr,
+        position: Option<&Expr>,
+        value: &Expr,
+    ) -> Result<(), CodegenError> {
+        use Instruction::*;
+        let var = self.variable(array)?;
+        let Type::Array(element) = self.info.expr(array) else {
+            return Err(unsupported(array.span, "table.insert on tables is"));
+        };
+        let ty = self.lower(&element, array.span)?;
+        let (address, at) = (self.local(ValType::I32), self.local(ValType::I32));
+        let (copy, slot) = (self.local(ValType::I32), self.local(ValType::I32));
+        self.expr(array)?;
+        self.push(LocalSet(address));
+        match position {
+            Some(position) => {
+                // positions from 1 to one past the last element
+                let offset = self.local(ValType::I64);
+                self.expr_as(position, &Type::INTEGER)?;
+                self.push(I64Const(1));
+                self.push(I64Sub);
+                self.push(LocalTee(offset));
+                self.push(LocalGet(address));
+                self.push(I32Load(WORD));
+                self.push(I64ExtendI32U);
+                self.push(I64GtU);
+                self.enter(If(BlockType::Empty), false);
+                self.push(Unreachable);
+                self.end();
+                self.push(LocalGet(offset));
+                self.push(I32WrapI64);
+            }
+            None => {
+                self.push(LocalGet(address));
+                self.push(I32Load(WORD));
+            }
+        }
+        self.push(LocalSet(at));
+        self.push(LocalGet(address));
+        self.helper(Helper::Append {
+            wide: width(ty) == 8,
+        });
+        self.push(LocalTee(copy));
+        self.set(&var);
+        self.push(LocalGet(copy));
+        self.push(LocalGet(at));
+        self.push(I32Const(width(ty)));
+        self.push(I32Mul);
+        self.push(I32Add);
+        self.push(LocalSet(slot));
+        if position.is_some() {
+            // shift the elements from `position` up by one
+            self.push(LocalGet(slot));
+            self.push(I32Const(4 + width(ty)));
+            self.push(I32Add);
+            self.push(LocalGet(slot));
+            self.push(I32Const(4));
+            self.push(I32Add);
+            self.push(LocalGet(address));
+            self.push(I32Load(WORD));
+            self.push(LocalGet(at));
+            self.push(I32Sub);
+            self.push(I32Const(width(ty)));
+            self.push(I32Mul);
+            self.push(MemoryCopy {
+                src_mem: 0,
+                dst_mem: 0,
+            });
+        }
+        self.push(LocalGet(slot));
+        self.expr_as(value, &element)?;
+        self.push(store(ty, ELEMENTS));
+        Ok(())
+    }
+
+    /// `table.sort(array)`: replace the array in the variable `array` by a
+    /// sorted copy, which the library module `table` imports as
+    /// `sort({element})`.
+    fn sort(&mut self, array: &Expr) -> Result<(), CodegenError> {
+        let var = self.variable(array)?;
+        let ty = self.info.expr(array);
+        let Type::Array(element) = &ty else {
+            return Err(unsupported(array.span, "table.sort on tables is"));
+        };
+        let comparable = matches!(
+            **element,
+            Type::Primitive(primitive) if primitive != Primitive::Boolean && primitive != Primitive::Nil
+        );
+        if !comparable && !matches!(**element, Type::Enum(_)) {
+            return Err(unsupported(
+                array.span,
+                format!("table.sort on arrays of {element} is"),
+            ));
+        }
+        let slot = self.import(
+            "table",
+            &format!("sort({ty})"),
+            &[ValType::I32],
+            &[ValType::I32],
+        );
+        self.expr(array)?;
+        self.push(Instruction::Call(slot as u32));
+        self.set(&var);
+        Ok(())
+    }
+
+    /// `table.remove(array, position)`: replace the array in the variable
+    /// `array` by a copy without the element at `position`, the last by
+    /// default, and push that element. Positions out of bounds remove nothing
+    /// and push the zero value.
+    fn remove(&mut self, array: &Expr, position: Option<&Expr>) -> Result<Type, CodegenError> {
+        let var = self.variable(array)?;
+        let Type::Array(element) = self.info.expr(array) else {
+            return Err(unsupported(array.span, "table.remove on tables is"));
+        };
+        let ty = self.lower(&element, array.span)?;
+        let (address, at) = (self.local(ValType::I32), self.local(ValType::I64));
+        self.expr(array)?;
+        self.push(Instruction::LocalSet(address));
+        match position {
+            Some(position) => self.expr_as(position, &Type::INTEGER)?,
+            None => {
+                self.push(Instruction::LocalGet(address));
+                self.push(Instruction::I32Load(WORD));
+                self.push(Instruction::I64ExtendI32U);
+            }
+        }
+        self.push(Instruction::I64Const(1));
+        self.push(Instruction::I64Sub);
+        self.push(Instruction::LocalTee(at));
+        self.push(Instruction::LocalGet(address));
+        self.push(Instruction::I32Load(WORD));
+        self.push(Instruction::I64ExtendI32U);
+        self.push(Instruction::I64LtU);
+        self.enter(Instruction::If(BlockType::Result(ty)), false);
+        self.push(Instruction::LocalGet(address));
+        self.push(Instruction::LocalGet(at));
+        self.push(Instruction::I32WrapI64);
+        self.push(Instruction::I32Const(width(ty)));
+        self.push(Instruction::I32Mul);
+        self.push(Instruction::I32Add);
+        self.push(load(ty, ELEMENTS));
+        self.push(Instruction::LocalGet(address));
+        self.push(Instruction::LocalGet(at));
+        self.push(Instruction::I32WrapI64);
+        self.helper(Helper::Remove {
+            wide: width(ty) == 8,
+        });
+        self.set(&var);
+        self.push(Instruction::Else);
+        self.constant(&element, 0.0);
+        self.end();
+        Ok((*element).clone())
+    }
+
+    /// The function running the module of the program `require`d by the call
+    /// of `callee` with `args`, and the global recording whether it ran, if
+    /// the call is one.
+    fn required(&self, callee: &Expr, args: &[Expr]) -> Option<(usize, u32)> {
+        let ExprKind::Name(name) = &callee.kind else {
+            return None;
+        };
+        if name.name != "require" || self.lookup(&name.name).is_some() {
+            return None;
+        }
+        match args {
+            [
+                Expr {
+                    kind: ExprKind::String(module),
+                    ..
+                },
+            ] => self.modules.get(module).copied(),
+            _ => None,
+        }
+    }
+
+    /// Call the runtime function `helper`, emitting it first if needed.
+    fn helper(&mut self, helper: Helper) {
+        let slot = self.helper_slot(helper);
+        self.push(Instruction::Call(slot as u32));
+    }
+
+    /// The runtime function `helper`, emitted first if needed.
+    fn helper_slot(&mut self, helper: Helper) -> usize {
+        if let Some(&slot) = self.helpers.get(&helper) {
+            return slot;
+        }
+        let slot = self.emit_helper(helper);
+        self.helpers.insert(helper, slot);
+        slot
+    }
+
+    fn emit_helper(&mut self, helper: Helper) -> usize {
+        use Instruction::*;
+
+        let (a, b) = (LocalGet(0), LocalGet(1));
+        let word = MemArg {
+            offset: 0,
+            align: 2,
+            memory_index: 0,
+        };
+        let byte = MemArg {
+            offset: 4,
+            align: 0,
+            memory_index: 0,
+        };
+        let (params, results, locals, body) = match helper {
+            Helper::IntDiv | Helper::IntMod => {
+                let (i64x2, i64x1) = (vec![ValType::I64; 2], vec![ValType::I64]);
+                // the quotient or remainder truncated towards zero, corrected
+                // when the operands have different signs
+                let r = LocalGet(2);
+                let (truncate, corrected, test) = match helper {
+                    Helper::IntDiv => (
+                        I64DivS,
+                        vec![r.clone(), I64Const(1), I64Sub],
+                        vec![a.clone(), b.clone(), I64Xor],
+                    ),
+                    _ => (
+                        I64RemS,
+                        vec![r.clone(), b.clone(), I64Add],
+                        vec![r.clone(), b.clone(), I64Xor],
+                    ),
+                };
+                let mut body = vec![a.clone(), b.clone(), truncate, LocalSet(2)];
+                body.extend([a.clone(), b.clone(), I64RemS, I64Const(0), I64Ne]);
+                body.extend(test);
+                body.extend([I64Const(0), I64LtS, I32And]);
+                body.push(If(BlockType::Result(ValType::I64)));
+                body.extend(corrected);
+                body.extend([Else, r, End]);
+                (i64x2, i64x1, vec![ValType::I64], body)
+            }
+            Helper::FloatMod => {
+                let body = vec![a.clone(), a, b.clone(), F64Div, F64Floor, b, F64Mul, F64Sub];
+                (vec![ValType::F64; 2], vec![ValType::F64], vec![], body)
+            }
+            Helper::StrEq => {
+                let (len, i) = (2, 3);
+                let body = vec![
+                    a.clone(),
+                    b.clone(),
+                    I32Eq,
+                    If(BlockType::Empty),
+                    I32Const(1),
+                    Return,
+                    End,
+                    a.clone(),
+                    I32Load(word),
+                    LocalTee(len),
+                    b.clone(),
+                    I32Load(word),
+                    I32Ne,
+                    If(BlockType::Empty),
+                    I32Const(0),
+                    Return,
+                    End,
+                    Block(BlockType::Empty),
+                    Loop(BlockType::Empty),
+                    LocalGet(i),
+                    LocalGet(len),
+                    I32GeU,
+                    BrIf(1),
+                    a,
+                    LocalGet(i),
+                    I32Add,
+                    I32Load8U(byte),
+                    b,
+                    LocalGet(i),
+                    I32Add,
+                    I32Load8U(byte),
+                    I32Ne,
+                    If(BlockType::Empty),
+                    I32Const(0),
+                    Return,
+                    End,
+                    LocalGet(i),
+                    I32Const(1),
+                    I32Add,
+                    LocalSet(i),
+                    Br(0),
+                    End,
+                    End,
+                    I32Const(1),
+                ];
+                (
+                    vec![ValType::I32; 2],
+                    vec![ValType::I32],
+                    vec![ValType::I32; 2],
+                    body,
+                )
+            }
+            Helper::Alloc => {
+                let heap = *self.heap.get_or_insert_with(|| {
+                    self.globals.push(ValType::I32);
+                    let index = self.globals.len() as u32 - 1;
+                    self.global_names.push((index, "heap".to_owned()));
+                    index
+                });
+                let p = 1;
+                let body = vec![
+                    GlobalGet(heap),
+                    LocalSet(p),
+                    // grow the memory to fit the allocation
+                    LocalGet(p),
+                    a.clone(),
+                    I32Add,
+                    MemorySize(0),
+                    I32Const(16),
+                    I32Shl,
+                    I32GtU,
+                    If(BlockType::Empty),
+                    LocalGet(p),
+                    a.clone(),
+                    I32Add,
+                    I32Const(PAGE_SIZE as i32 - 1),
+                    I32Add,
+                    I32Const(16),
+                    I32ShrU,
+                    MemorySize(0),
+                    I32Sub,
+                    MemoryGrow(0),
+                    Drop,
+                    End,
+                    LocalGet(p),
+                    a,
+                    I32Add,
+                    I32Const(3),
+                    I32Add,
+                    I32Const(-4),
+                    I32And,
+                    GlobalSet(heap),
+                    LocalGet(p),
+                ];
+                (
+                    vec![ValType::I32],
+                    vec![ValType::I32],
+                    vec![ValType::I32],
+                    body,
+                )
+            }
+            Helper::Concat => {
+                let alloc = self.helper_slot(Helper::Alloc) as u32;
+                let (la, lb, p) = (2, 3, 4);
+                let body = vec![
+                    a.clone(),
+                    I32Load(word),
+                    LocalSet(la),
+                    b.clone(),
+                    I32Load(word),
+                    LocalSet(lb),
+                    I32Const(4),
+                    LocalGet(la),
+                    I32Add,
+                    LocalGet(lb),
+                    I32Add,
+                    Call(alloc),
+                    LocalSet(p),
+                    LocalGet(p),
+                    LocalGet(la),
+                    LocalGet(lb),
+                    I32Add,
+                    I32Store(word),
+                    LocalGet(p),
+                    I32Const(4),
+                    I32Add,
+                    a,
+                    I32Const(4),
+                    I32Add,
+                    LocalGet(la),
+                    MemoryCopy {
+                        src_mem: 0,
+                        dst_mem: 0,
+                    },
+                    LocalGet(p),
+                    I32Const(4),
+                    I32Add,
+                    LocalGet(la),
+                    I32Add,
+                    b,
+                    I32Const(4),
+                    I32Add,
+                    LocalGet(lb),
+                    MemoryCopy {
+                        src_mem: 0,
+                        dst_mem: 0,
+                    },
+                    LocalGet(p),
+                ];
+                (
+                    vec![ValType::I32; 2],
+                    vec![ValType::I32],
+                    vec![ValType::I32; 3],
+                    body,
+                )
+            }
+            Helper::Append { wide } => {
+                let alloc = self.helper_slot(Helper::Alloc) as u32;
+                let w = if wide { 8 } else { 4 };
+                let (len, p) = (1, 2);
+                let body = vec![
+                    a.clone(),
+                    I32Load(word),
+                    LocalSet(len),
+                    I32Const(4),
+                    LocalGet(len),
+                    I32Const(1),
+                    I32Add,
+                    I32Const(w),
+                    I32Mul,
+                    I32Add,
+                    Call(alloc),
+                    LocalSet(p),
+                    LocalGet(p),
+                    LocalGet(len),
+                    I32Const(1),
+                    I32Add,
+                    I32Store(word),
+                    LocalGet(p),
+                    I32Const(4),
+                    I32Add,
+                    a,
+                    I32Const(4),
+                    I32Add,
+                    LocalGet(len),
+                    I32Const(w),
+                    I32Mul,
+                    MemoryCopy {
+                        src_mem: 0,
+                        dst_mem: 0,
+                    },
+                    LocalGet(p),
+                ];
+                (
+                    vec![ValType::I32],
+                    vec![ValType::I32],
+                    vec![ValType::I32; 2],
+                    body,
+                )
+            }
+            Helper::Remove { wide } => {
+                let alloc = self.helper_slot(Helper::Alloc) as u32;
+                let w = if wide { 8 } else { 4 };
+                // the length of the copy
+                let (len, p) = (2, 3);
+                let body = vec![
+                    a.clone(),
+                    I32Load(word),
+                    I32Const(1),
+                    I32Sub,
+                    LocalSet(len),
+                    I32Const(4),
+                    LocalGet(len),
+                    I32Const(w),
+                    I32Mul,
+                    I32Add,
+                    Call(alloc),
+                    LocalSet(p),
+                    LocalGet(p),
+                    LocalGet(len),
+                    I32Store(word),
+                    // the elements before the removed one
+                    LocalGet(p),
+                    I32Const(4),
+                    I32Add,
+                    a.clone(),
+                    I32Const(4),
+                    I32Add,
+                    b.clone(),
+                    I32Const(w),
+                    I32Mul,
+                    MemoryCopy {
+                        src_mem: 0,
+                        dst_mem: 0,
+                    },
+                    // and those after it
+                    LocalGet(p),
+                    I32Const(4),
+                    I32Add,
+                    b.clone(),
+                    I32Const(w),
+                    I32Mul,
+                    I32Add,
+                    a,
+                    I32Const(4 + w),
+                    I32Add,
+                    b.clone(),
+                    I32Const(w),
+                    I32Mul,
+                    I32Add,
+                    LocalGet(len),
+                    b,
+                    I32Sub,
+                    I32Const(w),
+                    I32Mul,
+                    MemoryCopy {
+                        src_mem: 0,
+                        dst_mem: 0,
+                    },
+                    LocalGet(p),
+                ];
+                (
+                    vec![ValType::I32; 2],
+                    vec![ValType::I32],
+                    vec![ValType::I32; 2],
+                    body,
+                )
+            }
+        };
+        let ty = self.type_index(&params, &results);
+        let builder = Builder {
+            params: params.len(),
+            locals: [params, locals].concat(),
+            body,
+            ..Builder::default()
+        };
+        self.functions.push(Slot {
+            ty,
+            name: helper.label().to_owned(),
+            kind: SlotKind::Defined {
+                export: None,
+                body: Some(builder),
+            },
+        });
+        self.functions.len() - 1
+    }
+}
+
+impl Builder {
+    /// The function, with calls referring to the final `indices` of the
+    /// functions, and the offset in its body of each instruction mapped to a
+    /// span.
+    fn into_function(self, indices: &[u32]) -> (Function, Vec<(u32, Span)>) {
+        let mut locals: Vec<(u32, ValType)> = Vec::new();
+        for &ty in &self.locals[self.params..] {
+            match locals.last_mut() {
+                Some((count, last)) if *last == ty => *count += 1,
+                _ => locals.push((1, ty)),
+            }
+        }
+        let mut function = Function::new(locals);
+        let mut offsets = Vec::new();
+        for (i, instruction) in self.body.into_iter().enumerate() {
+            if let Some(&span) = self.spans.get(i) {
+                offsets.push((function.byte_len() as u32, span));
+            }
+            match instruction {
+                Instruction::Call(slot) => {
+                    function.instruction(&Instruction::Call(indices[slot as usize]))
+                }
+                instruction => function.instruction(&instruction),
+            };
+        }
+        function.instruction(&Instruction::End);
+        (function, offsets)
+    }
+}
+
+impl Helper {
+    /// The name of the helper in the name section.
+    fn label(&self) -> &'static str {
+        match self {
+            Self::IntDiv => "int_div",
+            Self::IntMod => "int_mod",
+            Self::FloatMod => "float_mod",
+            Self::StrEq => "str_eq",
+            Self::Alloc => "alloc",
+            Self::Concat => "concat",
+            Self::Append { wide: false } => "array_append_4",
+            Self::Append { wide: true } => "array_append_8",
+            Self::Remove { wide: false } => "array_remove_4",
+            Self::Remove { wide: true } => "array_remove_8",
+        }
+    }
+}
+
+/// The name section naming the functions, their locals, and the globals, by
+/// index.
+fn names(
+    mut functions: Vec<(u32, String)>,
+    mut locals: Vec<(u32, Vec<(u32, String)>)>,
+    mut globals: Vec<(u32, String)>,
+) -> NameSection {
+    // name maps must be in increasing order of index
+    fn name_map(names: &mut [(u32, String)]) -> NameMap {
+        names.sort_by_key(|&(index, _)| index);
+        let mut map = NameMap::new();
+        for (index, name) in names.iter() {
+            map.append(*index, name);
+        }
+        map
+    }
+
+    let mut section = NameSection::new();
+    section.functions(&name_map(&mut functions));
+    locals.sort_by_key(|&(index, _)| index);
+    let mut indirect = IndirectNameMap::new();
+    for (function, names) in &mut locals {
+        indirect.append(*function, &name_map(names));
+    }
+    section.locals(&indirect);
+    section.globals(&name_map(&mut globals));
+    section
+}
+
+/// The type `ty` is lowered to, if supported.
+fn val_type(ty: &Type) -> Option<ValType> {
+    match ty {
+        Type::Primitive(primitive) => match primitive {
+            Primitive::Nil => None,
+            Primitive::Integer | Primitive::I64 | Primitive::U64 => Some(ValType::I64),
+            Primitive::Number | Primitive::F64 => Some(ValType::F64),
+            Primitive::F32 | Primitive::F16 | Primitive::BF16 => Some(ValType::F32),
+            // booleans, strings, and the smaller integers
+            _ => Some(ValType::I32),
+        },
+        Type::Enum(_) => Some(ValType::I32),
+        // by the address of their length followed by their elements
+        Type::Array(element) => val_type(element).map(|_| ValType::I32),
+        _ => None,
+    }
+}
+
+fn var_type(var: &Var) -> &Type {
+    match var {
+        Var::Local { ty, .. } | Var::Global { ty, .. } => ty,
+        Var::Function { .. } => &Type::Unknown,
+    }
+}
+
+fn is_unsigned(ty: &Type) -> bool {
+    matches!(
+        ty,
+        Type::Primitive(Primitive::U8 | Primitive::U16 | Primitive::U32 | Primitive::U64)
+    )
+}
+
+/// The type both operands of a comparison are converted to.
+fn common(lhs: &Type, rhs: &Type) -> Type {
+    match (lhs, rhs) {
+        (Type::Primitive(Primitive::Nil), other) | (other, Type::Primitive(Primitive::Nil)) => {
+            other.clone()
+        }
+        (a, b) if a.same(b) => a.clone(),
+        (a, b) if a.is_integer() && b.is_integer() => Type::INTEGER,
+        (a, b) if a.is_numeric() && b.is_numeric() => Type::NUMBER,
+        (a, _) => a.clone(),
+    }
+}
+
+/// Returns whether the `step` of a numeric for loop is negative, if constant.
+fn constant_sign(step: &Expr) -> Option<bool> {
+    match &step.kind {
+        ExprKind::Integer(value) => Some(*value < 0),
+        ExprKind::Number(value) => Some(*value < 0.0),
+        ExprKind::Unary {
+            op: UnOp::Neg,
+            operand,
+        } => constant_sign(operand).map(|negative| !negative),
+        ExprKind::Paren(inner) => constant_sign(inner),
+        _ => None,
+    }
+}
+
+/// The `<=`, `>=`, and `>` instructions on `ty`.
+fn comparisons(ty: ValType) -> [Instruction<'static>; 3] {
+    [BinOp::Le, BinOp::Ge, BinOp::Gt].map(|op| comparison(op, ty, false))
+}
+
+fn comparison(op: BinOp, ty: ValType, unsigned: bool) -> Instruction<'static> {
+    use Instruction::*;
+
+    match (ty, op, unsigned) {
+        (ValType::I64, BinOp::Eq, _) => I64Eq,
+        (ValType::I64, BinOp::Ne, _) => I64Ne,
+        (ValType::I64, BinOp::Lt, false) => I64LtS,
+        (ValType::I64, BinOp::Lt, true) => I64LtU,
+        (ValType::I64, BinOp::Gt, false) => I64GtS,
+        (ValType::I64, BinOp::Gt, true) => I64GtU,
+        (ValType::I64, BinOp::Le, false) => I64LeS,
+        (ValType::I64, BinOp::Le, true) => I64LeU,
+        (ValType::I64, BinOp::Ge, false) => I64GeS,
+        (ValType::I64, BinOp::Ge, true) => I64GeU,
+        (ValType::F64, BinOp::Eq, _) => F64Eq,
+        (ValType::F64, BinOp::Ne, _) => F64Ne,
+        (ValType::F64, BinOp::Lt, _) => F64Lt,
+        (ValType::F64, BinOp::Gt, _) => F64Gt,
+        (ValType::F64, BinOp::Le, _) => F64Le,
+        (ValType::F64, BinOp::Ge, _) => F64Ge,
+        (ValType::F32, BinOp::Eq, _) => F32Eq,
+        (ValType::F32, BinOp::Ne, _) => F32Ne,
+        (ValType::F32, BinOp::Lt, _) => F32Lt,
+        (ValType::F32, BinOp::Gt, _) => F32Gt,
+        (ValType::F32, BinOp::Le, _) => F32Le,
+        (ValType::F32, BinOp::Ge, _) => F32Ge,
+        (_, BinOp::Eq, _) => I32Eq,
+        (_, BinOp::Ne, _) => I32Ne,
+        (_, BinOp::Lt, false) => I32LtS,
+        (_, BinOp::Lt, true) => I32LtU,
+        (_, BinOp::Gt, false) => I32GtS,
+        (_, BinOp::Gt, true) => I32GtU,
+        (_, BinOp::Le, false) => I32LeS,
+        (_, BinOp::Le, true) => I32LeU,
+        (_, BinOp::Ge, false) => I32GeS,
+        (_, _, _) => I32GeU,
+    }
+}
+
+/// The instruction computing `op` on two `ty`s, for operators mapping to one.
+fn arithmetic(op: BinOp, ty: ValType) -> Option<Instruction<'static>> {
+    use Instruction::*;
+
+    Some(match (ty, op) {
+        (ValType::I32, BinOp::Add) => I32Add,
+        (ValType::I32, BinOp::Sub) => I32Sub,
+        (ValType::I32, BinOp::Mul) => I32Mul,
+        (ValType::I32, BinOp::BitAnd) => I32And,
+        (ValType::I32, BinOp::BitOr) => I32Or,
+        (ValType::I32, BinOp::BitXor) => I32Xor,
+        (ValType::I32, BinOp::Shl) => I32Shl,
+        (ValType::I32, BinOp::Shr) => I32ShrU,
+        (ValType::I64, BinOp::Add) => I64Add,
+        (ValType::I64, BinOp::Sub) => I64Sub,
+        (ValType::I64, BinOp::Mul) => I64Mul,
+        (ValType::I64, BinOp::BitAnd) => I64And,
+        (ValType::I64, BinOp::BitOr) => I64Or,
+        (ValType::I64, BinOp::BitXor) => I64Xor,
+        (ValType::I64, BinOp::Shl) => I64Shl,
+        (ValType::I64, BinOp::Shr) => I64ShrU,
+        (ValType::F32, BinOp::Add) => F32Add,
+        (ValType::F32, BinOp::Sub) => F32Sub,
+        (ValType::F32, BinOp::Mul) => F32Mul,
+        (ValType::F32, BinOp::Div) => F32Div,
+        (ValType::F64, BinOp::Add) => F64Add,
+        (ValType::F64, BinOp::Sub) => F64Sub,
+        (ValType::F64, BinOp::Mul) => F64Mul,
+        (ValType::F64, BinOp::Div) => F64Div,
+        _ => return None,
+    })
+}
+
+/// The size in bytes of an array element of type `ty`.
+fn width(ty: ValType) -> i32 {
+    match ty {
+        ValType::I64 | ValType::F64 => 8,
+        _ => 4,
+    }
+}
+
+fn load(ty: ValType, at: MemArg) -> Instruction<'static> {
+    match ty {
+        ValType::I64 => Instruction::I64Load(at),
+        ValType::F32 => Instruction::F32Load(at),
+        ValType::F64 => Instruction::F64Load(at),
+        _ => Instruction::I32Load(at),
+    }
+}
+
+fn store(ty: ValType, at: MemArg) -> Instruction<'static> {
+    match ty {
+        ValType::I64 => Instruction::I64Store(at),
+        ValType::F32 => Instruction::F32Store(at),
+        ValType::F64 => Instruction::F64Store(at),
+        _ => Instruction::I32Store(at),
+    }
+}
+
+/// The length of `value` encoded as an unsigned LEB128.
+fn leb128_len(value: usize) -> usize {
+    (usize::BITS - value.leading_zeros()).div_ceil(7).max(1) as usize
+}
+
+/// `address` rounded up to a multiple of 4.
+fn align(address: u32) -> u32 {
+    address.next_multiple_of(4)
+}
+
+fn unsupported(span: Span, what: impl fmt::Display) -> CodegenError {
+    CodegenError::Unsupported {
+        message: format!("{what} not supported by the WASM backend yet"),
+        span,
+    }
+}
+
+impl fmt::Display for CodegenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "Failed to write WASM module: {e}"),
+            Self::Unsupported { message, span } => {
+                write!(f, "{message} at {}..{}", span.start, span.end)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CodegenError {}
+
+impl From<std::io::Error> for CodegenError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
@@ -0,0 +1,278 @@
+//! Errors and warnings found compiling Teal source, collected across passes
+//! and rendered with the lines they point at.
+//!
+//! Every pass reports into the same [`Diagnostics`], so that a single compile
+//! reports as much as it can. Tools show them [rendered](Diagnostics::render),
+//! while editors read their [`Span`]s and [`Location`]s, eg. to underline
+//! them:
+//!
+//! ```text
+//! error: in local declaration: got string, expected integer
+//!  --> script.tl:3:18
+//!   |
+//! 3 | local n: integer = "three"
+//!   |                    ^^^^^^^
+//! ```
+
+use std::fmt::{self, Write as _};
+
+use crate::codegen::wasm::CodegenError;
+use crate::teal::ParseError;
+use crate::teal::ast::Span;
+use crate::teal::check::TypeError;
+
+/// How bad a [`Diagnostic`] is.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// The source cannot be compiled.
+    Error,
+    /// The source compiles, but likely not as intended.
+    Warning,
+}
+
+/// Something wrong with a part of the source.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub span: Span,
+}
+
+/// The diagnostics of one compile, in the order they were found.
+#[derive(Clone, Debug, Default)]
+pub struct Diagnostics {
+    diagnostics: Vec<Diagnostic>,
+}
+
+/// Source text, indexed by line to locate [`Span`]s.
+pub struct SourceFile<'a> {
+    /// The name of the file in rendered diagnostics, eg. its path.
+    name: &'a str,
+    text: &'a str,
+    /// Offset of the start of each line.
+    lines: Vec<usize>,
+}
+
+/// A 1-based line and column, in characters.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Location {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Severity {
+    pub const ALL: [Self; 2] = [Self::Error, Self::Warning];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+        }
+    }
+}
+
+impl Diagnostic {
+    pub fn error(span: Span, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            message: message.into(),
+            span,
+        }
+    }
+
+    pub fn warning(span: Span, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            message: message.into(),
+            span,
+        }
+    }
+
+    /// Append the diagnostic to `out`, with the lines of `source` it covers.
+    pub fn render_to(&self, source: &SourceFile, out: &mut String) {
+        let start = source.location(self.span.start);
+        let end = source.location(self.span.end.max(self.span.start + 1) - 1);
+        let _ = writeln!(out, "{}: {}", self.severity.label(), self.message);
+
+        let gutter = start.line.to_string().len();
+        let _ = writeln!(
+            out,
+            "{:gutter$}--> {}:{}:{}",
+            "", source.name, start.line, start.column
+        );
+        // Spans over several lines, eg. of a whole function, are only
+        // underlined to the end of their first line.
+        let text = source.line(start.line);
+        let last = match end.line == start.line {
+            true => end.column,
+            false => text.chars().count().max(start.column),
+        };
+        let _ = writeln!(out, "{:gutter$} |", "");
+        let _ = writeln!(out, "{} | {text}", start.line);
+        let _ = writeln!(
+            out,
+            "{:gutter$} | {}{}",
+            "",
+            " ".repeat(start.column - 1),
+            "^".repeat(last + 1 - start.column)
+        );
+    }
+}
+
+impl Diagnostics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, diagnostic: impl Into<Diagnostic>) {
+        self.diagnostics.push(diagnostic.into());
+    }
+
+    pub fn error(&mut self, span: Span, message: impl Into<String>) {
+        self.push(Diagnostic::error(span, message));
+    }
+
+    pub fn warning(&mut self, span: Span, message: impl Into<String>) {
+        self.push(Diagnostic::warning(span, message));
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Diagnostic> {
+        self.diagnostics.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.diagnostics.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+
+    /// The number of diagnostics of `severity`.
+    pub fn count(&self, severity: Severity) -> usize {
+        self.iter().filter(|d| d.severity == severity).count()
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.count(Severity::Error) > 0
+    }
+
+    /// Forget every diagnostic, eg. before compiling edited source again.
+    pub fn clear(&mut self) {
+        self.diagnostics.clear();
+    }
+
+    /// Every diagnostic with the lines of `source` it covers, then a summary.
+    pub fn render(&self, source: &SourceFile) -> String {
+        let mut out = String::new();
+        for diagnostic in self.iter() {
+            diagnostic.render_to(source, &mut out);
+            out.push('\n');
+        }
+        let (errors, warnings) = (self.count(Severity::Error), self.count(Severity::Warning));
+        summarize(errors, warnings, &mut out);
+        out
+    }
+}
+
+/// Append the counts of diagnostics to `out`, unless there are none.
+pub(crate) fn summarize(errors: usize, warnings: usize, out: &mut String) {
+    if errors + warnings > 0 {
+        let plural = |n: usize| if n == 1 { "" } else { "s" };
+        let _ = writeln!(
+            out,
+            "{errors} error{}, {warnings} warning{}",
+            plural(errors),
+            plural(warnings)
+        );
+    }
+}
+
+impl<'a> IntoIterator for &'a Diagnostics {
+    type Item = &'a Diagnostic;
+    type IntoIter = std::slice::Iter<'a, Diagnostic>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a> SourceFile<'a> {
+    pub fn new(name: &'a str, text: &'a str) -> Self {
+        let lines = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self { name, text, lines }
+    }
+
+    pub fn name(&self) -> &str {
+        self.name
+    }
+
+    pub fn text(&self) -> &str {
+        self.text
+    }
+
+    /// The location of the byte `offset`, clamped to the end of the text.
+    pub fn location(&self, offset: usize) -> Location {
+        let offset = offset.min(self.text.len());
+        let line = self.lines.partition_point(|&start| start <= offset);
+        let start = self.lines[line - 1];
+        let column = match self.text.get(start..offset) {
+            Some(before) => before.chars().count() + 1,
+            // within a character
+            None => offset - start + 1,
+        };
+        Location { line, column }
+    }
+
+    /// The text of the 1-based `line`, without its line break.
+    pub fn line(&self, line: usize) -> &str {
+        let start = self.lines[line - 1];
+        let end = self
+            .lines
+            .get(line)
+            .map_or(self.text.len(), |&next| next - 1);
+        self.text[start..end].trim_end_matches('\r')
+    }
+}
+
+impl From<ParseError> for Diagnostic {
+    fn from(e: ParseError) -> Self {
+        Self::error(e.span, e.message)
+    }
+}
+
+impl From<TypeError> for Diagnostic {
+    fn from(e: TypeError) -> Self {
+        Self::error(e.span, e.message)
+    }
+}
+
+impl From<CodegenError> for Diagnostic {
+    fn from(e: CodegenError) -> Self {
+        match e {
+            CodegenError::Unsupported { message, span } => Self::error(span, message),
+            CodegenError::Io(e) => Self::error(Span::default(), e.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} at {}..{}",
+            self.severity.label(),
+            self.message,
+            self.span.start,
+            self.span.end
+        )
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
@@ -0,0 +1,559 @@
+//! # Host bindings
+//!
+//! Compiled scripts call into the host through functions imported from
+//! modules named after the global they are reached through, eg.
+//! `graph.node_count()` imports `node_count` from the module `graph`. A
+//! [`HostRegistry`] lists those functions with their types, declares them to
+//! the [`Checker`], and calls the Rust callback an import is bound to,
+//! marshalling its values through the guest's [linear memory](GuestMemory):
+//! - `boolean`s are passed as `i32`, `integer`s as `i64`, and `number`s as
+//!   `f64`
+//! - strings are passed as the `i32` address of their length as a
+//!   little-endian `u32` followed by their UTF-8 bytes, the empty string being
+//!   at address 0
+//! - arrays of strings are passed as the `i32` address of their length as a
+//!   little-endian `u32` followed by the address of each string
+//!
+//! Strings and arrays returned to the guest are written to memory it
+//! allocates through its export [`ALLOC_EXPORT`]. [`graph_api()`] binds the
+//! `graph` and `selection` modules to a [`GraphApi`], eg. the engine's script
+//! host:
+//!
+//! ```ignore
+//! let registry = host::graph_api::<ScriptHost>();
+//! let mut checker = Checker::new();
+//! registry.declare(&mut checker);
+//! // from the runtime's import of `graph.add_edge`
+//! let result = registry.call("graph", "add_edge", &mut script_host, &mut memory, &args)?;
+//! ```
+
+use std::fmt;
+use std::rc::Rc;
+
+use crate::teal::check::{Checker, FunctionSig, RecordType, Type};
+
+pub use crate::codegen::wasm::ALLOC_EXPORT;
+
+/// The type of a value passed between the guest and the host.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum HostType {
+    Boolean,
+    Integer,
+    Number,
+    String,
+    /// `{string}`
+    Strings,
+}
+
+/// A value passed between the guest and the host.
+#[derive(Clone, Debug, PartialEq)]
+pub enum HostValue {
+    /// No value, passed as the zero value of the type expected.
+    Nil,
+    Boolean(bool),
+    Integer(i64),
+    Number(f64),
+    String(String),
+    Strings(Vec<String>),
+}
+
+/// A WASM value, as a runtime passes it to or from an import.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum RawValue {
+    I32(i32),
+    I64(i64),
+    F64(f64),
+}
+
+/// The linear memory of the guest, as exposed by the runtime running it.
+pub trait GuestMemory {
+    fn bytes(&self) -> &[u8];
+
+    fn bytes_mut(&mut self) -> &mut [u8];
+
+    /// Allocate `size` bytes by calling the guest's [`ALLOC_EXPORT`],
+    /// returning their address.
+    fn alloc(&mut self, size: u32) -> Result<u32, HostError>;
+}
+
+/// What a host function does to the host state, given its arguments. It is a
+/// plain function rather than a closure, for registries to be built for host
+/// states which are only borrowed while a script runs.
+pub type Callback<T> = fn(&mut T, &[HostValue]) -> Result<HostValue, String>;
+
+/// A function of the host, see the [module documentation](self).
+pub struct HostFunction<T> {
+    pub module: String,
+    pub name: String,
+    pub params: Vec<HostType>,
+    pub returns: Option<HostType>,
+    callback: Callback<T>,
+}
+
+/// The functions of the host, by module and name, calling into a `T`.
+pub struct HostRegistry<T> {
+    functions: Vec<HostFunction<T>>,
+}
+
+/// The graph API bound by [`graph_api()`]. Nodes are referred to by name.
+pub trait GraphApi {
+    fn node_count(&self) -> usize;
+
+    fn edge_count(&self) -> usize;
+
+    /// Names of every node.
+    fn nodes(&self) -> Vec<String>;
+
+    fn has_node(&mut self, node: &str) -> bool;
+
+    /// Names of the nodes adjacent to `node`, or `None` if there is no such
+    /// node.
+    fn neighbors(&mut self, node: &str) -> Option<Vec<String>>;
+
+    /// Add an edge from `source` to `target`. Returns `false` if either node
+    /// does not exist.
+    fn add_edge(&mut self, source: &str, target: &str) -> bool;
+
+    /// The attribute `key` of `node` as text, if set.
+    fn attribute(&mut self, node: &str, key: &str) -> Option<String>;
+
+    /// Set the attribute `key` of `node` to the text `value`. Returns `false`
+    /// if there is no such node.
+    fn set_attribute(&mut self, node: &str, key: &str, value: &str) -> bool;
+
+    /// Names of the selected nodes.
+    fn selected(&self) -> Vec<String>;
+
+    /// Select the named `nodes`, ignoring unknown names. Returns the number of
+    /// nodes found.
+    fn select(&mut self, nodes: &[&str]) -> usize;
+
+    fn clear_selection(&mut self);
+}
+
+/// Errors which can occur while calling a host function from the guest.
+#[derive(Debug)]
+pub enum HostError {
+    /// No function named `name` is registered in `module`.
+    Unknown { module: String, name: String },
+    /// The function was called with, or returned, values of other types than
+    /// it is registered with.
+    Signature { function: String, message: String },
+    /// A string or array extends past the end of the guest's memory.
+    OutOfBounds { address: u32 },
+    /// A string passed by the guest is not valid UTF-8.
+    InvalidUtf8 { address: u32 },
+    /// The guest failed to allocate memory for a returned value.
+    Alloc(String),
+    /// The callback of `function` failed.
+    Failed { function: String, message: String },
+}
+
+/// Bind the functions of [`GraphApi`] as the modules `graph` and `selection`.
+pub fn graph_api<T: GraphApi>() -> HostRegistry<T> {
+    use HostType::*;
+
+    let mut registry = HostRegistry::new();
+    registry
+        .register("graph", "node_count", &[], Some(Integer), |g: &mut T, _| {
+            Ok(HostValue::Integer(g.node_count() as i64))
+        })
+        .register("graph", "edge_count", &[], Some(Integer), |g: &mut T, _| {
+            Ok(HostValue::Integer(g.edge_count() as i64))
+        })
+        .register("graph", "nodes", &[], Some(Strings), |g: &mut T, _| {
+            Ok(HostValue::Strings(g.nodes()))
+        })
+        .register(
+            "graph",
+            "has_node",
+            &[String],
+            Some(Boolean),
+            |g: &mut T, args| Ok(HostValue::Boolean(g.has_node(args[0].as_str()))),
+        )
+        .register(
+            "graph",
+            "neighbors",
+            &[String],
+            Some(Strings),
+            |g: &mut T, args| {
+                let node = args[0].as_str();
+                g.neighbors(node)
+                    .map(HostValue::Strings)
+                    .ok_or_else(|| format!("No node named '{node}'"))
+            },
+        )
+        .register(
+            "graph",
+            "add_edge",
+            &[String, String],
+            Some(Boolean),
+            |g: &mut T, args| {
+                Ok(HostValue::Boolean(
+                    g.add_edge(args[0].as_str(), args[1].as_str()),
+                ))
+            },
+        )
+        .register(
+            "graph",
+            "attribute",
+            &[String, String],
+            Some(String),
+            |g: &mut T, args| {
+                let value = g.attribute(args[0].as_str(), args[1].as_str());
+                Ok(value.map_or(HostValue::Nil, HostValue::String))
+            },
+        )
+        .register(
+            "graph",
+            "set_attribute",
+            &[String, String, String],
+            Some(Boolean),
+            |g: &mut T, args| {
+                let (node, key) = (args[0].as_str(), args[1].as_str());
+                Ok(HostValue::Boolean(g.set_attribute(
+                    node,
+                    key,
+                    args[2].as_str(),
+                )))
+            },
+        )
+        .register("selection", "ids", &[], Some(Strings), |g: &mut T, _| {
+            Ok(HostValue::Strings(g.selected()))
+        })
+        .register(
+            "selection",
+            "select",
+            &[Strings],
+            Some(Integer),
+            |g: &mut T, args| {
+                let nodes: Vec<&str> = match &args[0] {
+                    HostValue::Strings(nodes) => nodes.iter().map(|n| n.as_str()).collect(),
+                    _ => Vec::new(),
+                };
+                Ok(HostValue::Integer(g.select(&nodes) as i64))
+            },
+        )
+        .register("selection", "clear", &[], None, |g: &mut T, _| {
+            g.clear_selection();
+            Ok(HostValue::Nil)
+        });
+    registry
+}
+
+impl HostType {
+    pub const ALL: [Self; 5] = [
+        Self::Boolean,
+        Self::Integer,
+        Self::Number,
+        Self::String,
+        Self::Strings,
+    ];
+
+    /// The Teal type.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Boolean => "boolean",
+            Self::Integer => "integer",
+            Self::Number => "number",
+            Self::String => "string",
+            Self::Strings => "{string}",
+        }
+    }
+
+    /// The checked Teal type.
+    pub fn ty(&self) -> Type {
+        match self {
+            Self::Boolean => Type::BOOLEAN,
+            Self::Integer => Type::INTEGER,
+            Self::Number => Type::NUMBER,
+            Self::String => Type::STRING,
+            Self::Strings => Type::Array(Box::new(Type::STRING)),
+        }
+    }
+
+    /// The value of this type passed by the guest as `raw` to `function`.
+    pub(crate) fn decode(
+        &self,
+        raw: RawValue,
+        memory: &dyn GuestMemory,
+        function: &str,
+    ) -> Result<HostValue, HostError> {
+        let value = match (self, raw) {
+            (Self::Boolean, RawValue::I32(value)) => HostValue::Boolean(value != 0),
+            (Self::Integer, RawValue::I64(value)) => HostValue::Integer(value),
+            (Self::Number, RawValue::F64(value)) => HostValue::Number(value),
+            (Self::String, RawValue::I32(address)) => {
+                HostValue::String(read_string(memory, address as u32)?)
+            }
+            (Self::Strings, RawValue::I32(address)) => {
+                let address = address as u32;
+                let len = read_u32(memory, address)?;
+                let mut strings = Vec::new();
+                for i in 0..len {
+                    let element = read_u32(memory, address + 4 + 4 * i)?;
+                    strings.push(read_string(memory, element)?);
+                }
+                HostValue::Strings(strings)
+            }
+            (_, raw) => {
+                return Err(HostError::Signature {
+                    function: function.to_owned(),
+                    message: format!("got {raw:?}, expected {}", self.label()),
+                });
+            }
+        };
+        Ok(value)
+    }
+
+    /// Pass `value` of this type returned by `function` to the guest,
+    /// allocating it if needed.
+    pub(crate) fn encode(
+        &self,
+        value: HostValue,
+        memory: &mut dyn GuestMemory,
+        function: &str,
+    ) -> Result<RawValue, HostError> {
+        let raw = match (self, value) {
+            (Self::Boolean | Self::String | Self::Strings, HostValue::Nil) => RawValue::I32(0),
+            (Self::Integer, HostValue::Nil) => RawValue::I64(0),
+            (Self::Number, HostValue::Nil) => RawValue::F64(0.0),
+            (Self::Boolean, HostValue::Boolean(value)) => RawValue::I32(value as i32),
+            (Self::Integer, HostValue::Integer(value)) => RawValue::I64(value),
+            (Self::Number, HostValue::Number(value)) => RawValue::F64(value),
+            (Self::Number, HostValue::Integer(value)) => RawValue::F64(value as f64),
+            (Self::String, HostValue::String(value)) => {
+                RawValue::I32(write_string(memory, &value)? as i32)
+            }
+            (Self::Strings, HostValue::Strings(values)) => {
+                let mut words = vec![values.len() as u32];
+                for value in &values {
+                    words.push(write_string(memory, value)?);
+                }
+                let bytes: Vec<u8> = words.iter().flat_map(|w| w.to_le_bytes()).collect();
+                RawValue::I32(write_bytes(memory, &bytes)? as i32)
+            }
+            (_, value) => {
+                return Err(HostError::Signature {
+                    function: function.to_owned(),
+                    message: format!("returned {value:?}, expected {}", self.label()),
+                });
+            }
+        };
+        Ok(raw)
+    }
+}
+
+impl HostValue {
+    /// The string, or the empty string for other values.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::String(value) => value,
+            _ => "",
+        }
+    }
+}
+
+impl<T> HostFunction<T> {
+    /// The Teal type of the function.
+    pub fn sig(&self) -> FunctionSig {
+        FunctionSig {
+            params: self.params.iter().map(HostType::ty).collect(),
+            optional: vec![false; self.params.len()],
+            returns: self.returns.iter().map(HostType::ty).collect(),
+            ..FunctionSig::default()
+        }
+    }
+
+    /// Call the function on `state` with the `args` passed by the guest,
+    /// returning the value to pass back, if any.
+    pub fn call(
+        &self,
+        state: &mut T,
+        memory: &mut dyn GuestMemory,
+        args: &[RawValue],
+    ) -> Result<Option<RawValue>, HostError> {
+        let function = format!("{}.{}", self.module, self.name);
+        if args.len() != self.params.len() {
+            return Err(HostError::Signature {
+                function,
+                message: format!(
+                    "got {} arguments, expected {}",
+                    args.len(),
+                    self.params.len()
+                ),
+            });
+        }
+        let values = self
+            .params
+            .iter()
+            .zip(args)
+            .map(|(ty, &raw)| ty.decode(raw, memory, &function))
+            .collect::<Result<Vec<_>, _>>()?;
+        let value = match (self.callback)(state, &values) {
+            Ok(value) => value,
+            Err(message) => return Err(HostError::Failed { function, message }),
+        };
+        match self.returns {
+            Some(ty) => ty.encode(value, memory, &function).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+impl<T> HostRegistry<T> {
+    pub fn new() -> Self {
+        Self {
+            functions: Vec::new(),
+        }
+    }
+
+    /// Bind the function `name` of `module` to `callback`, replacing any
+    /// function registered under that name. The callback is passed arguments
+    /// of the `params` types, and must return a value of the `returns` type
+    /// or [`HostValue::Nil`].
+    pub fn register(
+        &mut self,
+        module: &str,
+        name: &str,
+        params: &[HostType],
+        returns: Option<HostType>,
+        callback: Callback<T>,
+    ) -> &mut Self {
+        self.functions
+            .retain(|f| !(f.module == module && f.name == name));
+        self.functions.push(HostFunction {
+            module: module.to_owned(),
+            name: name.to_owned(),
+            params: params.to_vec(),
+            returns,
+            callback,
+        });
+        self
+    }
+
+    pub fn get(&self, module: &str, name: &str) -> Option<&HostFunction<T>> {
+        self.functions
+            .iter()
+            .find(|f| f.module == module && f.name == name)
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, HostFunction<T>> {
+        self.functions.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.functions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.functions.is_empty()
+    }
+
+    /// Names of the modules, in the order they were first registered.
+    pub fn modules(&self) -> Vec<&str> {
+        let mut modules: Vec<&str> = Vec::new();
+        for function in &self.functions {
+            if !modules.contains(&function.module.as_str()) {
+                modules.push(&function.module);
+            }
+        }
+        modules
+    }
+
+    /// Declare each module to `checker` as a global record of its functions.
+    pub fn declare(&self, checker: &mut Checker) {
+        for module in self.modules() {
+            let record = RecordType::new(module, false, Vec::new());
+            let fields = self
+                .functions
+                .iter()
+                .filter(|f| f.module == module)
+                .map(|f| (f.name.clone(), Type::Function(Rc::new(f.sig()))));
+            record.fields.borrow_mut().extend(fields);
+            checker.declare_global(module, Type::Record(Rc::new(record), Vec::new()));
+        }
+    }
+
+    /// Call the function `name` of `module`, see [`HostFunction::call()`].
+    pub fn call(
+        &self,
+        module: &str,
+        name: &str,
+        state: &mut T,
+        memory: &mut dyn GuestMemory,
+        args: &[RawValue],
+    ) -> Result<Option<RawValue>, HostError> {
+        let function = self.get(module, name).ok_or_else(|| HostError::Unknown {
+            module: module.to_owned(),
+            name: name.to_owned(),
+        })?;
+        function.call(state, memory, args)
+    }
+}
+
+impl<T> Default for HostRegistry<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub(crate) fn read_u32(memory: &dyn GuestMemory, address: u32) -> Result<u32, HostError> {
+    let start = address as usize;
+    let bytes = memory
+        .bytes()
+        .get(start..start + 4)
+        .ok_or(HostError::OutOfBounds { address })?;
+    Ok(u32::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+fn read_string(memory: &dyn GuestMemory, address: u32) -> Result<String, HostError> {
+    let len = read_u32(memory, address)? as usize;
+    let start = address as usize + 4;
+    let bytes = memory
+        .bytes()
+        .get(start..start + len)
+        .ok_or(HostError::OutOfBounds { address })?;
+    String::from_utf8(bytes.to_vec()).map_err(|_| HostError::InvalidUtf8 { address })
+}
+
+/// Allocate `value` in the guest's memory, returning its address.
+fn write_string(memory: &mut dyn GuestMemory, value: &str) -> Result<u32, HostError> {
+    if value.is_empty() {
+        return Ok(0);
+    }
+    let mut bytes = (value.len() as u32).to_le_bytes().to_vec();
+    bytes.extend(value.as_bytes());
+    write_bytes(memory, &bytes)
+}
+
+/// Allocate `bytes` in the guest's memory, returning their address.
+pub(crate) fn write_bytes(memory: &mut dyn GuestMemory, bytes: &[u8]) -> Result<u32, HostError> {
+    let address = memory.alloc(bytes.len() as u32)?;
+    let start = address as usize;
+    memory
+        .bytes_mut()
+        .get_mut(start..start + bytes.len())
+        .ok_or(HostError::OutOfBounds { address })?
+        .copy_from_slice(bytes);
+    Ok(address)
+}
+
+impl fmt::Display for HostError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unknown { module, name } => write!(f, "No host function '{module}.{name}'"),
+            Self::Signature { function, message } => {
+                write!(f, "In call to '{function}': {message}")
+            }
+            Self::OutOfBounds { address } => {
+                write!(f, "Value at {address} extends past the end of memory")
+            }
+            Self::InvalidUtf8 { address } => write!(f, "String at {address} is not valid UTF-8"),
+            Self::Alloc(e) => write!(f, "Failed to allocate guest memory: {e}"),
+            Self::Failed { function, message } => write!(f, "'{function}' failed: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for HostError {}
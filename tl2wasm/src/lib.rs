@@ -1,8 +1,120 @@
-
-mod teal;
+pub mod codegen;
+pub mod diagnostics;
+pub mod host;
+pub mod modules;
+pub mod repl;
+pub mod stdlib;
+pub mod teal;
 
 use wasm_bindgen::prelude::*;
 
 use teal::TealParser;
 
+use diagnostics::{Diagnostic, Diagnostics};
+use teal::ast::Block;
+use teal::check::{Checker, TypeInfo};
+
+/// Parse and type check `source`, reporting every error and warning found to
+/// `diagnostics`. The checked chunk is returned if there were no errors.
+pub fn check(source: &str, diagnostics: &mut Diagnostics) -> Option<(Block, TypeInfo)> {
+    check_with(source, &mut Checker::new(), diagnostics)
+}
+
+/// [`check()`] `source` with the globals declared to `checker`, eg. the
+/// [host bindings](host::HostRegistry::declare).
+pub fn check_with(
+    source: &str,
+    checker: &mut Checker,
+    diagnostics: &mut Diagnostics,
+) -> Option<(Block, TypeInfo)> {
+    let chunk = match TealParser::parse_chunk(source) {
+        Ok(chunk) => chunk,
+        Err(e) => {
+            diagnostics.push(e);
+            return None;
+        }
+    };
+    let types = check_chunk(&chunk, checker, diagnostics)?;
+    Some((chunk, types))
+}
+
+/// Type check the parsed `chunk`, see [`check_with()`].
+pub(crate) fn check_chunk(
+    chunk: &Block,
+    checker: &mut Checker,
+    diagnostics: &mut Diagnostics,
+) -> Option<TypeInfo> {
+    let result = checker.check(chunk);
+    let warnings = checker.take_warnings();
+    let types = match result {
+        Ok(types) => Some(types),
+        Err(errors) => {
+            errors.into_iter().for_each(|e| diagnostics.push(e));
+            None
+        }
+    };
+    for w in warnings {
+        diagnostics.push(Diagnostic::warning(w.span, w.message));
+    }
+    types
+}
+
+/// Compile `source` to a WebAssembly module, reporting every error and
+/// warning found to `diagnostics`.
+pub fn compile(source: &str, diagnostics: &mut Diagnostics) -> Option<Vec<u8>> {
+    compile_with(source, &mut Checker::new(), diagnostics)
+}
+
+/// [`compile()`] `source` with the globals declared to `checker`, see
+/// [`check_with()`].
+pub fn compile_with(
+    source: &str,
+    checker: &mut Checker,
+    diagnostics: &mut Diagnostics,
+) -> Option<Vec<u8>> {
+    let (chunk, types) = check_with(source, checker, diagnostics)?;
+    codegen::wasm::emit(&chunk, &types)
+        .map_err(|e| diagnostics.push(e))
+        .ok()
+}
+
+/// A [`Repl`](repl::Repl) without host bindings, for the browser's console.
+#[wasm_bindgen]
+pub struct TealRepl(repl::Repl, host::HostRegistry<()>);
+
+#[wasm_bindgen]
+impl TealRepl {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        let registry = host::HostRegistry::new();
+        Self(repl::Repl::new(&registry), registry)
+    }
+
+    /// Run the entry `source`, returning the lines it printed followed by its
+    /// values, if any.
+    pub fn eval(&mut self, source: &str) -> Result<String, JsError> {
+        let (mut lines, result) = self.0.eval(source, &self.1, &mut ());
+        let source_file = diagnostics::SourceFile::new("<console>", source);
+        match result {
+            Ok(value) => lines.extend(value),
+            Err(repl::ReplError::Compile(diagnostics)) => {
+                return Err(JsError::new(&diagnostics.render(&source_file)));
+            }
+            Err(e) => return Err(JsError::new(&e.to_string())),
+        }
+        Ok(lines.join("\n"))
+    }
+
+    /// Returns `false` if `source` needs more lines to be run, see
+    /// [`repl::is_complete()`].
+    #[wasm_bindgen(js_name = isComplete)]
+    pub fn is_complete(&self, source: &str) -> bool {
+        repl::is_complete(source)
+    }
+}
 
+impl Default for TealRepl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,605 @@
+//! `tl2wasm`: compiles Teal source files to WebAssembly modules, eg. from build
+//! scripts. Only the standard library's file I/O is used, so the compiler
+//! itself also runs under WASI.
+//!
+//! `tl2wasm compile` links the modules the input `require`s into the same
+//! module, see [`tl2wasm::modules`].
+//!
+//! `tl2wasm repl` reads entries from stdin and runs them one after the other,
+//! see [`tl2wasm::repl`].
+//!
+//! `tl2wasm snapshot` compiles every fixture of a directory to WAT and compares
+//! it to the snapshot next to it, as a regression test of the code generator.
+//! Fixtures with an expected output are also run, as a conformance test of the
+//! [standard library](tl2wasm::stdlib).
+//!
+//! `tl2wasm fmt` rewrites Teal sources in the canonical layout of
+//! [`tl2wasm::teal::format`].
+//!
+//! `tl2wasm corpus` parses every file of a directory, as a conformance test of
+//! the grammar over examples of the Teal language, eg. those of `corpus/`.
+
+use tl2wasm::codegen::{source_map, wasm};
+use tl2wasm::diagnostics::{Diagnostics, SourceFile};
+use tl2wasm::host::HostRegistry;
+use tl2wasm::modules::{ModuleResolver, Program};
+use tl2wasm::repl::{self, Repl, ReplError};
+use tl2wasm::teal::{TealParser, format};
+
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Usage: tl2wasm compile <input.tl> [options]
+       tl2wasm snapshot <dir> [--bless]
+       tl2wasm corpus <dir>
+       tl2wasm fmt <input.tl>... [--check]
+       tl2wasm repl
+
+Options:
+  -o, --output <file>       Where to write the output (default: <input>.wasm
+                            for WASM, stdout otherwise)
+      --emit <kind>         What to output: wasm, wat, or ast (default: wasm)
+      --check               Only parse and type check, writing nothing
+      --source-map          Also write a source map of the WASM output to
+                            <output>.map, linked from the module
+      --path <dir>          Look for required modules in <dir>, before the
+                            directory of the input (repeatable)
+
+Repl runs the entries read from stdin, keeping what they declare, and prints
+the values of those which are expressions. Entries span several lines until
+complete, or until an empty line.
+
+Snapshot compiles every <name>.tl in <dir>, with the modules it requires from
+<dir>, to WAT and compares it to <name>.wat, or its errors to <name>.err if it
+does not compile. Those with a <name>.out are also run, and what they print
+compared to it.
+
+Corpus parses every <name>.tl in <dir>, reporting those which fail to.
+
+Fmt rewrites every input in the canonical layout: indented by 3 spaces, with
+spaced operators and parenthesized calls, keeping comments.
+
+Snapshot options:
+      --bless               Write the output of every fixture as its snapshot,
+                            and what it prints to its existing <name>.out,
+                            instead of comparing them
+
+Fmt options:
+      --check               Only report the inputs which are not formatted,
+                            writing nothing
+
+Exit codes:
+  0  Success
+  1  The input has errors, snapshots differ, corpus files fail to parse, or
+     inputs are not formatted
+  2  Invalid arguments
+  3  Failed to read the input or write the output
+";
+
+/// The input has syntax, type, or unsupported feature errors, snapshots
+/// differ, corpus files fail to parse, or inputs are not formatted.
+const EXIT_ERRORS: u8 = 1;
+const EXIT_USAGE: u8 = 2;
+const EXIT_IO: u8 = 3;
+
+/// What `compile` writes.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Emit {
+    Wasm,
+    Wat,
+    Ast,
+}
+
+/// The options of `compile`.
+struct Options {
+    input: PathBuf,
+    output: Option<PathBuf>,
+    emit: Emit,
+    check: bool,
+    source_map: bool,
+    /// Where to look for modules, before the directory of the input.
+    paths: Vec<PathBuf>,
+}
+
+/// The options of `snapshot`.
+struct SnapshotOptions {
+    dir: PathBuf,
+    bless: bool,
+}
+
+/// The options of `fmt`.
+struct FormatOptions {
+    inputs: Vec<PathBuf>,
+    check: bool,
+}
+
+/// Why a command failed, mapping to an exit code.
+enum Failure {
+    /// The input has errors, or snapshots differ, already reported.
+    Errors,
+    Io(String),
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let Some((command, rest)) = args.split_first() else {
+        eprint!("{USAGE}");
+        return ExitCode::from(EXIT_USAGE);
+    };
+    let result = match command.as_str() {
+        "compile" => match Options::parse(rest) {
+            Ok(options) => compile(&options),
+            Err(e) => return usage_error(&e),
+        },
+        "snapshot" => match SnapshotOptions::parse(rest) {
+            Ok(options) => snapshot(&options),
+            Err(e) => return usage_error(&e),
+        },
+        "corpus" => match rest {
+            [dir] => corpus(Path::new(dir)),
+            [] => return usage_error("Missing <dir>"),
+            [_, extra, ..] => return usage_error(&format!("Unexpected argument '{extra}'")),
+        },
+        "fmt" => match FormatOptions::parse(rest) {
+            Ok(options) => format_inputs(&options),
+            Err(e) => return usage_error(&e),
+        },
+        "repl" => match rest {
+            [] => run_repl(),
+            [extra, ..] => return usage_error(&format!("Unexpected argument '{extra}'")),
+        },
+        "help" | "--help" | "-h" => {
+            print!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        other => return usage_error(&format!("Unknown command '{other}'")),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(Failure::Errors) => ExitCode::from(EXIT_ERRORS),
+        Err(Failure::Io(e)) => {
+            eprintln!("error: {e}");
+            ExitCode::from(EXIT_IO)
+        }
+    }
+}
+
+fn usage_error(message: &str) -> ExitCode {
+    eprintln!("error: {message}\n\n{USAGE}");
+    ExitCode::from(EXIT_USAGE)
+}
+
+impl Emit {
+    const ALL: [Self; 3] = [Self::Wasm, Self::Wat, Self::Ast];
+
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Wasm => "wasm",
+            Self::Wat => "wat",
+            Self::Ast => "ast",
+        }
+    }
+}
+
+impl Options {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut input = None;
+        let mut output = None;
+        let mut emit = Emit::Wasm;
+        let mut check = false;
+        let mut source_map = false;
+        let mut paths = Vec::new();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| {
+                args.next()
+                    .ok_or_else(|| format!("Option '{name}' needs a value"))
+            };
+            match arg.as_str() {
+                "-o" | "--output" => output = Some(PathBuf::from(value(arg)?)),
+                "--emit" => {
+                    let kind = value(arg)?;
+                    emit = Emit::ALL
+                        .into_iter()
+                        .find(|e| e.label() == kind)
+                        .ok_or_else(|| format!("Unknown output kind '{kind}'"))?;
+                }
+                "--check" => check = true,
+                "--source-map" => source_map = true,
+                "--path" => paths.push(PathBuf::from(value(arg)?)),
+                option if option.starts_with('-') => {
+                    return Err(format!("Unknown option '{option}'"));
+                }
+                path if input.is_none() => input = Some(PathBuf::from(path)),
+                extra => return Err(format!("Unexpected argument '{extra}'")),
+            }
+        }
+        if source_map && emit != Emit::Wasm {
+            return Err("Option '--source-map' needs '--emit wasm'".into());
+        }
+        Ok(Self {
+            input: input.ok_or("Missing <input.tl>")?,
+            output,
+            emit,
+            check,
+            source_map,
+            paths,
+        })
+    }
+}
+
+impl SnapshotOptions {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut dir = None;
+        let mut bless = false;
+        for arg in args {
+            match arg.as_str() {
+                "--bless" => bless = true,
+                option if option.starts_with('-') => {
+                    return Err(format!("Unknown option '{option}'"));
+                }
+                path if dir.is_none() => dir = Some(PathBuf::from(path)),
+                extra => return Err(format!("Unexpected argument '{extra}'")),
+            }
+        }
+        Ok(Self {
+            dir: dir.ok_or("Missing <dir>")?,
+            bless,
+        })
+    }
+}
+
+impl FormatOptions {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut inputs = Vec::new();
+        let mut check = false;
+        for arg in args {
+            match arg.as_str() {
+                "--check" => check = true,
+                option if option.starts_with('-') => {
+                    return Err(format!("Unknown option '{option}'"));
+                }
+                path => inputs.push(PathBuf::from(path)),
+            }
+        }
+        if inputs.is_empty() {
+            return Err("Missing <input.tl>".into());
+        }
+        Ok(Self { inputs, check })
+    }
+}
+
+fn compile(options: &Options) -> Result<(), Failure> {
+    let path = &options.input;
+    let source = read(path)?;
+    let name = path.display().to_string();
+    let mut diagnostics = Diagnostics::new();
+    let report = |diagnostics: &Diagnostics| {
+        eprint!("{}", diagnostics.render(&SourceFile::new(&name, &source)));
+    };
+
+    if options.emit == Emit::Ast && !options.check {
+        let chunk = TealParser::parse_chunk(&source).map_err(|e| {
+            diagnostics.push(e);
+            report(&diagnostics);
+            Failure::Errors
+        })?;
+        return write(options, format!("{chunk:#?}\n").into_bytes());
+    }
+
+    let mut resolver = ModuleResolver::new();
+    for dir in &options.paths {
+        resolver.add_path(dir);
+    }
+    resolver.add_path(path.parent().unwrap_or(Path::new("")));
+    let mut program = Program::load(path, source.clone(), &resolver);
+    let types = program.check();
+    let emitted = match types {
+        Some(_) if options.check => None,
+        // a single file keeps its source map
+        Some(types) if program.units().len() == 1 => {
+            let entry = program.entry_mut();
+            wasm::emit_with_source_map(entry.chunk.as_ref().unwrap(), &types[0])
+                .map_err(|e| entry.diagnostics.push(e))
+                .ok()
+                .map(|(bytes, map)| (bytes, Some(map)))
+        }
+        Some(types) => program.emit(&types).map(|bytes| (bytes, None)),
+        None => None,
+    };
+    eprint!("{}", program.render(Path::new("")));
+    if program.has_errors() {
+        return Err(Failure::Errors);
+    }
+    let Some((mut bytes, map)) = emitted else {
+        return Ok(());
+    };
+
+    if options.source_map {
+        let Some(map) = map else {
+            eprintln!("error: source maps of programs requiring modules are not supported");
+            return Err(Failure::Errors);
+        };
+        let path = output_path(options).unwrap().with_added_extension("map");
+        let url = path.file_name().unwrap().to_string_lossy();
+        source_map::append_url(&mut bytes, &url);
+        let json = map.to_json(&SourceFile::new(&name, &source));
+        std::fs::write(&path, json)
+            .map_err(|e| Failure::Io(format!("Failed to write '{}': {e}", path.display())))?;
+    }
+    let bytes = match options.emit {
+        Emit::Wasm => bytes,
+        Emit::Wat => wat(&bytes)?.into_bytes(),
+        Emit::Ast => unreachable!(),
+    };
+    write(options, bytes)
+}
+
+/// Run the entries read from stdin until its end, printing what they print
+/// and their values.
+fn run_repl() -> Result<(), Failure> {
+    use std::io::{BufRead, Write};
+
+    let io_error = |e: std::io::Error| Failure::Io(format!("Failed to read the input: {e}"));
+    let registry = HostRegistry::new();
+    let mut repl = Repl::new(&registry);
+    let mut lines = std::io::stdin().lock();
+    let mut entry = String::new();
+    loop {
+        print!("{}", if entry.is_empty() { "> " } else { "... " });
+        std::io::stdout().flush().map_err(io_error)?;
+        let mut line = String::new();
+        if lines.read_line(&mut line).map_err(io_error)? == 0 {
+            println!();
+            return Ok(());
+        }
+        entry.push_str(&line);
+        if entry.trim().is_empty() {
+            entry.clear();
+            continue;
+        }
+        // an empty line runs the entry as it is
+        if !line.trim().is_empty() && !repl::is_complete(&entry) {
+            continue;
+        }
+
+        let (printed, result) = repl.eval(&entry, &registry, &mut ());
+        for line in printed {
+            println!("{line}");
+        }
+        match result {
+            Ok(Some(value)) => println!("{value}"),
+            Ok(None) => {}
+            Err(ReplError::Compile(diagnostics)) => {
+                eprint!(
+                    "{}",
+                    diagnostics.render(&SourceFile::new("<stdin>", &entry))
+                );
+            }
+            Err(e) => eprintln!("error: {e}"),
+        }
+        entry.clear();
+    }
+}
+
+/// Compare the output of every fixture of `options.dir` to its snapshot, or
+/// write it as the snapshot if blessing.
+fn snapshot(options: &SnapshotOptions) -> Result<(), Failure> {
+    let fixtures = sources(&options.dir)?;
+
+    let mut failed = 0;
+    for fixture in &fixtures {
+        let (extension, actual) = snapshot_output(fixture)?;
+        let path = fixture.with_extension(extension);
+        let stale = fixture.with_extension(if extension == "wat" { "err" } else { "wat" });
+        if options.bless {
+            std::fs::write(&path, actual)
+                .map_err(|e| Failure::Io(format!("Failed to write '{}': {e}", path.display())))?;
+            if stale.exists() {
+                std::fs::remove_file(&stale).map_err(|e| {
+                    Failure::Io(format!("Failed to remove '{}': {e}", stale.display()))
+                })?;
+            }
+            continue;
+        }
+        let Ok(expected) = std::fs::read_to_string(&path) else {
+            failed += 1;
+            eprintln!(
+                "{}: missing snapshot '{}'",
+                fixture.display(),
+                path.display()
+            );
+            continue;
+        };
+        if !matches(&path, &expected, &actual) {
+            failed += 1;
+        }
+    }
+    // fixtures with an expected output run, to check what they print
+    for fixture in &fixtures {
+        let path = fixture.with_extension("out");
+        let Ok(expected) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        let actual = run_output(fixture)?;
+        if options.bless {
+            std::fs::write(&path, actual)
+                .map_err(|e| Failure::Io(format!("Failed to write '{}': {e}", path.display())))?;
+        } else if !matches(&path, &expected, &actual) {
+            failed += 1;
+        }
+    }
+
+    match options.bless {
+        true => eprintln!("Blessed {} snapshots", fixtures.len()),
+        false => eprintln!("{} snapshots, {failed} differ", fixtures.len()),
+    }
+    match failed {
+        0 => Ok(()),
+        _ => Err(Failure::Errors),
+    }
+}
+
+/// Parse every source of `dir`, reporting the errors of those which fail to.
+fn corpus(dir: &Path) -> Result<(), Failure> {
+    let files = sources(dir)?;
+    let mut failed = 0;
+    for file in &files {
+        let source = read(file)?;
+        if let Err(e) = TealParser::parse_chunk(&source) {
+            failed += 1;
+            let mut diagnostics = Diagnostics::new();
+            diagnostics.push(e);
+            let name = file.display().to_string();
+            eprint!("{}", diagnostics.render(&SourceFile::new(&name, &source)));
+        }
+    }
+    eprintln!("{} files, {failed} fail to parse", files.len());
+    match failed {
+        0 => Ok(()),
+        _ => Err(Failure::Errors),
+    }
+}
+
+/// Rewrite every input of `options` in the canonical layout, or only report
+/// those which are not in it if checking.
+fn format_inputs(options: &FormatOptions) -> Result<(), Failure> {
+    let mut failed = 0;
+    let mut changed = 0;
+    for input in &options.inputs {
+        let source = read(input)?;
+        let formatted = match format::format(&source) {
+            Ok(formatted) => formatted,
+            Err(e) => {
+                failed += 1;
+                let mut diagnostics = Diagnostics::new();
+                diagnostics.push(e);
+                let name = input.display().to_string();
+                eprint!("{}", diagnostics.render(&SourceFile::new(&name, &source)));
+                continue;
+            }
+        };
+        if formatted == source {
+            continue;
+        }
+        changed += 1;
+        if options.check {
+            failed += 1;
+            eprintln!("{}: not formatted", input.display());
+            continue;
+        }
+        std::fs::write(input, formatted)
+            .map_err(|e| Failure::Io(format!("Failed to write '{}': {e}", input.display())))?;
+    }
+    match options.check {
+        true => eprintln!("{} files, {changed} not formatted", options.inputs.len()),
+        false => eprintln!("{} files, {changed} reformatted", options.inputs.len()),
+    }
+    match failed {
+        0 => Ok(()),
+        _ => Err(Failure::Errors),
+    }
+}
+
+/// The Teal sources of `dir`, sorted.
+fn sources(dir: &Path) -> Result<Vec<PathBuf>, Failure> {
+    let read_error = |e| Failure::Io(format!("Failed to read '{}': {e}", dir.display()));
+    let mut sources = std::fs::read_dir(dir)
+        .and_then(|entries| {
+            entries
+                .map(|e| e.map(|e| e.path()))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .map_err(read_error)?;
+    sources.retain(|path| path.extension().is_some_and(|e| e == "tl"));
+    sources.sort();
+    Ok(sources)
+}
+
+/// The extension of the snapshot of `fixture` and its expected content: the
+/// WAT it compiles to, or its rendered errors.
+fn snapshot_output(fixture: &Path) -> Result<(&'static str, String), Failure> {
+    let source = read(fixture)?;
+    let dir = fixture.parent().unwrap();
+    let mut resolver = ModuleResolver::new();
+    resolver.add_path(dir);
+    let mut program = Program::load(fixture, source, &resolver);
+    match program.compile() {
+        Some(bytes) if !program.has_errors() => Ok(("wat", wat(&bytes)?)),
+        // relative, so that snapshots do not depend on where they are checked
+        _ => Ok(("err", program.render(dir))),
+    }
+}
+
+/// What running `fixture` in a [`Repl`] prints, then the error it stops at, if
+/// any.
+fn run_output(fixture: &Path) -> Result<String, Failure> {
+    let source = read(fixture)?;
+    let registry = HostRegistry::new();
+    let mut repl = Repl::new(&registry);
+    let (printed, result) = repl.eval(&source, &registry, &mut ());
+    let mut out: String = printed.iter().map(|line| format!("{line}\n")).collect();
+    if let Err(e) = result {
+        out.push_str(&format!("error: {e}\n"));
+    }
+    Ok(out)
+}
+
+/// Returns `true` if `actual` is the `expected` content of the snapshot at
+/// `path`, reporting their first difference otherwise.
+fn matches(path: &Path, expected: &str, actual: &str) -> bool {
+    let Some((line, expected, actual)) = first_difference(expected, actual) else {
+        return true;
+    };
+    eprintln!("{}:{line}: differs from the snapshot", path.display());
+    eprintln!("  - {expected}\n  + {actual}");
+    false
+}
+
+/// The 1-based number and text of the first line `actual` differs from
+/// `expected` at, if any, with that of `expected`.
+fn first_difference<'a>(expected: &'a str, actual: &'a str) -> Option<(usize, &'a str, &'a str)> {
+    let (mut expected, mut actual) = (expected.lines(), actual.lines());
+    for line in 1.. {
+        match (expected.next(), actual.next()) {
+            (None, None) => return None,
+            (e, a) if e != a => return Some((line, e.unwrap_or(""), a.unwrap_or(""))),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn read(path: &Path) -> Result<String, Failure> {
+    std::fs::read_to_string(path)
+        .map_err(|e| Failure::Io(format!("Failed to read '{}': {e}", path.display())))
+}
+
+/// The module `bytes` as WebAssembly text.
+fn wat(bytes: &[u8]) -> Result<String, Failure> {
+    wasmprinter::print_bytes(bytes)
+        .map_err(|e| Failure::Io(format!("Failed to print the module: {e}")))
+}
+
+/// The file `options` write to, or `None` for stdout.
+fn output_path(options: &Options) -> Option<PathBuf> {
+    match (&options.output, options.emit) {
+        (Some(path), _) => Some(path.clone()),
+        (None, Emit::Wasm) => Some(options.input.with_extension("wasm")),
+        (None, _) => None,
+    }
+}
+
+/// Write `bytes` to the output of `options`.
+fn write(options: &Options, bytes: Vec<u8>) -> Result<(), Failure> {
+    let Some(path) = output_path(options) else {
+        use std::io::Write;
+
+        return std::io::stdout()
+            .write_all(&bytes)
+            .map_err(|e| Failure::Io(format!("Failed to write the output: {e}")));
+    };
+    std::fs::write(&path, bytes)
+        .map_err(|e| Failure::Io(format!("Failed to write '{}': {e}", path.display())))
+}
@@ -0,0 +1,325 @@
+//! Programs of several files, linked by `require`.
+//!
+//! `require("graph.util")` runs the module `graph/util.tl`, or
+//! `graph/util/init.tl`, from the first search path of the [`ModuleResolver`]
+//! having it, the first time it is called. Modules share the globals they
+//! declare rather than returning values, so `require` returns nothing.
+//!
+//! A [`Program`] loads an entry file and every module it requires, reporting
+//! unknown modules and cyclic requires at the `require` at fault, then
+//! compiles them all to one WASM module, see
+//! [`emit_program()`](crate::codegen::wasm::emit_program):
+//!
+//! ```ignore
+//! let mut resolver = ModuleResolver::new();
+//! resolver.add_path("scripts").add_path("lib");
+//! let mut program = Program::load("scripts/main.tl", source, &resolver);
+//! let bytes = program.compile();
+//! eprint!("{}", program.render(Path::new("")));
+//! ```
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use crate::codegen::wasm::{self, ProgramUnit};
+use crate::diagnostics::{self, Diagnostics, Severity, SourceFile};
+use crate::teal::TealParser;
+use crate::teal::ast::*;
+use crate::teal::check::{Checker, FunctionSig, Type, TypeInfo};
+use crate::teal::visit::{self, Visitor};
+
+/// Finds the files of modules in a list of directories.
+#[derive(Clone, Debug, Default)]
+pub struct ModuleResolver {
+    paths: Vec<PathBuf>,
+}
+
+/// A file of a [`Program`].
+#[derive(Debug)]
+pub struct Unit {
+    /// The name the unit is required by, empty for the entry.
+    pub name: String,
+    pub path: PathBuf,
+    pub source: String,
+    /// The parsed chunk, unless it has syntax errors.
+    pub chunk: Option<Block>,
+    pub diagnostics: Diagnostics,
+}
+
+/// An entry file and the modules it requires, see the
+/// [module documentation](self).
+#[derive(Debug)]
+pub struct Program {
+    /// Every unit after those it requires, so the entry is last.
+    units: Vec<Unit>,
+}
+
+/// Loads the units of a [`Program`], depth first.
+struct Loader<'a> {
+    resolver: &'a ModuleResolver,
+    units: Vec<Unit>,
+    /// The units being loaded, by name and path, each required by the previous.
+    loading: Vec<(String, PathBuf)>,
+    /// The modules loaded or failed to, by name.
+    seen: HashSet<String>,
+}
+
+/// Collects the calls of `require` of a chunk.
+#[derive(Default)]
+struct Requires {
+    /// Where each call is, and the module it names, or `None` if it is not a
+    /// string literal.
+    calls: Vec<(Span, Option<String>)>,
+}
+
+impl ModuleResolver {
+    /// A resolver without search paths, finding nothing.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Look for modules in `path` after the paths added before.
+    pub fn add_path(&mut self, path: impl Into<PathBuf>) -> &mut Self {
+        self.paths.push(path.into());
+        self
+    }
+
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+
+    /// The file of the module `name`, eg. `a/b.tl` or `a/b/init.tl` for `a.b`,
+    /// in the first search path having one.
+    pub fn resolve(&self, name: &str) -> Option<PathBuf> {
+        let valid = |part: &str| !part.is_empty() && !part.contains(['/', '\\']);
+        if !name.split('.').all(valid) {
+            return None;
+        }
+        let relative: PathBuf = name.split('.').collect();
+        self.paths
+            .iter()
+            .flat_map(|dir| {
+                let base = dir.join(&relative);
+                [base.with_extension("tl"), base.join("init.tl")]
+            })
+            .find(|path| path.is_file())
+    }
+}
+
+impl Unit {
+    fn parse(name: String, path: PathBuf, source: String) -> Self {
+        let mut diagnostics = Diagnostics::new();
+        let chunk = TealParser::parse_chunk(&source)
+            .map_err(|e| diagnostics.push(e))
+            .ok();
+        Self {
+            name,
+            path,
+            source,
+            chunk,
+            diagnostics,
+        }
+    }
+
+    /// The name of the unit in rendered diagnostics: its path, relative to
+    /// `base` if under it.
+    pub fn file_name(&self, base: &Path) -> String {
+        let path = self.path.strip_prefix(base).unwrap_or(&self.path);
+        path.display().to_string()
+    }
+}
+
+impl Program {
+    /// The program run by the file at `path`, of text `source`, loading the
+    /// modules it requires through `resolver`.
+    pub fn load(path: impl Into<PathBuf>, source: String, resolver: &ModuleResolver) -> Self {
+        let mut loader = Loader {
+            resolver,
+            units: Vec::new(),
+            loading: Vec::new(),
+            seen: HashSet::new(),
+        };
+        loader.load(String::new(), path.into(), source);
+        Self {
+            units: loader.units,
+        }
+    }
+
+    /// Every unit after those it requires, so the entry is last.
+    pub fn units(&self) -> &[Unit] {
+        &self.units
+    }
+
+    pub fn entry(&self) -> &Unit {
+        self.units.last().unwrap()
+    }
+
+    pub fn entry_mut(&mut self) -> &mut Unit {
+        self.units.last_mut().unwrap()
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.units.iter().any(|unit| unit.diagnostics.has_errors())
+    }
+
+    /// The diagnostics of every unit with the lines they cover, then a summary.
+    /// Paths are shown relative to `base`, see [`Unit::file_name()`].
+    pub fn render(&self, base: &Path) -> String {
+        let mut out = String::new();
+        let (mut errors, mut warnings) = (0, 0);
+        for unit in &self.units {
+            let name = unit.file_name(base);
+            let source = SourceFile::new(&name, &unit.source);
+            for diagnostic in &unit.diagnostics {
+                diagnostic.render_to(&source, &mut out);
+                out.push('\n');
+            }
+            errors += unit.diagnostics.count(Severity::Error);
+            warnings += unit.diagnostics.count(Severity::Warning);
+        }
+        diagnostics::summarize(errors, warnings, &mut out);
+        out
+    }
+
+    /// Type check every unit, see [`Self::check_with()`].
+    pub fn check(&mut self) -> Option<Vec<TypeInfo>> {
+        self.check_with(&mut Checker::new())
+    }
+
+    /// Type check every unit after those it requires, with the globals
+    /// declared to `checker` and by those units. Returns the types of each
+    /// unit, or `None` if any has errors.
+    pub fn check_with(&mut self, checker: &mut Checker) -> Option<Vec<TypeInfo>> {
+        if self.has_errors() {
+            return None;
+        }
+        let require = FunctionSig {
+            params: vec![Type::STRING],
+            optional: vec![false],
+            ..FunctionSig::default()
+        };
+        checker.declare_global("require", Type::Function(Rc::new(require)));
+        let mut types = Vec::new();
+        for unit in &mut self.units {
+            let chunk = unit.chunk.as_ref().unwrap();
+            // later units would report the globals it failed to declare
+            types.push(crate::check_chunk(chunk, checker, &mut unit.diagnostics)?);
+        }
+        Some(types)
+    }
+
+    /// Lower the units, checked into `types`, to one WASM module. Returns
+    /// `None` if any uses a feature the backend does not support, reported to
+    /// its diagnostics.
+    pub fn emit(&mut self, types: &[TypeInfo]) -> Option<Vec<u8>> {
+        let units: Vec<ProgramUnit> = self
+            .units
+            .iter()
+            .zip(types)
+            .map(|(unit, info)| ProgramUnit {
+                name: &unit.name,
+                chunk: unit.chunk.as_ref().unwrap(),
+                info,
+            })
+            .collect();
+        match wasm::emit_program(&units) {
+            Ok(bytes) => Some(bytes),
+            Err((i, e)) => {
+                self.units[i].diagnostics.push(e);
+                None
+            }
+        }
+    }
+
+    /// Compile the program to a WebAssembly module, reporting every error and
+    /// warning found to the diagnostics of the unit at fault.
+    pub fn compile(&mut self) -> Option<Vec<u8>> {
+        self.compile_with(&mut Checker::new())
+    }
+
+    /// [`Self::compile()`] with the globals declared to `checker`, see
+    /// [`Self::check_with()`].
+    pub fn compile_with(&mut self, checker: &mut Checker) -> Option<Vec<u8>> {
+        let types = self.check_with(checker)?;
+        self.emit(&types)
+    }
+}
+
+impl Loader<'_> {
+    /// Load the unit `name` and the modules it requires, before it.
+    fn load(&mut self, name: String, path: PathBuf, source: String) {
+        let mut unit = Unit::parse(name, path, source);
+        let mut requires = Requires::default();
+        if let Some(chunk) = &unit.chunk {
+            requires.visit_block(chunk);
+        }
+        self.loading.push((unit.name.clone(), unit.path.clone()));
+        for (span, module) in requires.calls {
+            let Some(module) = module else {
+                unit.diagnostics
+                    .error(span, "require needs the module name as a string literal");
+                continue;
+            };
+            let Some(path) = self.resolver.resolve(&module) else {
+                let message = format!("module '{module}' not found in the search paths");
+                unit.diagnostics.error(span, message);
+                continue;
+            };
+            if let Some(start) = self.cycle_start(&path) {
+                let mut chain: Vec<String> = self.loading[start..]
+                    .iter()
+                    .map(|(name, path)| match name.is_empty() {
+                        true => path.display().to_string(),
+                        false => name.clone(),
+                    })
+                    .collect();
+                chain.push(module);
+                let message = format!("cyclic require: {}", chain.join(" -> "));
+                unit.diagnostics.error(span, message);
+                continue;
+            }
+            if !self.seen.insert(module.clone()) {
+                continue;
+            }
+            match std::fs::read_to_string(&path) {
+                Ok(source) => self.load(module, path, source),
+                Err(e) => {
+                    let message = format!("failed to read '{}': {e}", path.display());
+                    unit.diagnostics.error(span, message);
+                }
+            }
+        }
+        self.loading.pop();
+        self.units.push(unit);
+    }
+
+    /// The index in [`Self::loading`] of the unit at `path`, if it is being
+    /// loaded.
+    fn cycle_start(&self, path: &Path) -> Option<usize> {
+        let canonical = |path: &Path| path.canonicalize().unwrap_or_else(|_| path.to_owned());
+        let path = canonical(path);
+        self.loading.iter().position(|(_, p)| canonical(p) == path)
+    }
+}
+
+impl Visitor for Requires {
+    fn visit_expr(&mut self, expr: &Expr) {
+        if let ExprKind::Call { callee, args } = &expr.kind
+            && let ExprKind::Name(name) = &callee.kind
+            && name.name == "require"
+        {
+            let module = match args.as_slice() {
+                [
+                    Expr {
+                        kind: ExprKind::String(module),
+                        ..
+                    },
+                ] => Some(module.clone()),
+                _ => None,
+            };
+            self.calls.push((expr.span, module));
+        }
+        visit::walk_expr(self, expr);
+    }
+}
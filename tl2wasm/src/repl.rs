@@ -0,0 +1,671 @@
+//! # REPL
+//!
+//! A [`Repl`] compiles each entry typed by the user to a module linked to the
+//! modules of the entries before it, [in a session](wasm::emit_in_session),
+//! and runs it in the same store, so that what an entry declares is kept for
+//! the next ones: its globals and global functions, as well as its top-level
+//! locals, which are made global. An entry which is an expression is
+//! evaluated, and its values returned as text.
+//!
+//! Entries run on [wasmi](https://docs.rs/wasmi), which also runs in the
+//! browser, and can call `print`, the [standard library](crate::stdlib), and
+//! the functions of the [`HostRegistry`] the REPL is created with. An entry
+//! calling a host function is suspended while [`Repl::eval()`] calls it on
+//! the host state it is passed, which is only borrowed for the entry. Entries
+//! run within [`Limits`], failing rather than looping or allocating forever:
+//!
+//! ```ignore
+//! let registry = host::graph_api();
+//! let mut repl = Repl::new(&registry);
+//! repl.eval("local n = graph.node_count()", &registry, &mut graph);
+//! let (printed, value) = repl.eval("n * 2", &registry, &mut graph);
+//! assert_eq!(value?, Some("84".to_owned()));
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::rc::Rc;
+
+use wasmi::core::{Pages, TrapCode};
+use wasmi::{
+    AsContextMut, Caller, Config, Engine, Extern, ExternType, Func, FuncType, Global, Linker,
+    Memory, MemoryType, Module, Mutability, Store, StoreLimits, StoreLimitsBuilder,
+    TypedResumableCall, Val,
+};
+
+use crate::codegen::wasm::{
+    self, HEAP_EXPORT, HOST_MODULE, MEMORY_EXPORT, PAGE_SIZE, SESSION_MODULE, Session,
+};
+use crate::diagnostics::{Diagnostic, Diagnostics};
+use crate::host::{GuestMemory, HostError, HostRegistry, HostType, HostValue, RawValue};
+use crate::stdlib;
+use crate::teal::TealParser;
+use crate::teal::ast::{Block, ExprKind, Primitive, Scope, Span, StatementKind};
+use crate::teal::check::{Checker, FunctionSig, Type};
+
+/// The host function an expression entry is passed to, for its values to be
+/// returned.
+const RESULT_FUNCTION: &str = "__result";
+
+/// Runs entries one after the other, see the [module documentation](self).
+pub struct Repl {
+    /// Knows the globals declared by the entries so far.
+    checker: Checker,
+    session: Session,
+    limits: Limits,
+    store: Store<State>,
+    memory: Memory,
+    heap: Global,
+    /// What the modules of entries import, by module and name: the memory,
+    /// the heap, and what the entries before exported, in [`SESSION_MODULE`],
+    /// and the host functions used so far.
+    imports: HashMap<(String, String), Extern>,
+}
+
+/// How much an entry may compute and allocate.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Limits {
+    /// The fuel an entry may consume, about one unit per instruction run.
+    pub fuel: u64,
+    /// The size in bytes the memory of the session may grow to.
+    pub memory: usize,
+}
+
+/// Errors which can occur while running an entry.
+#[derive(Debug)]
+pub enum ReplError {
+    /// The entry has errors, with spans in the entry.
+    Compile(Diagnostics),
+    /// The entry calls a function the host does not provide.
+    Link(String),
+    /// The entry trapped, ran out of fuel or memory, or a host function it
+    /// called failed.
+    Trap(String),
+}
+
+/// The data of the store entries run in.
+struct State {
+    printed: Vec<String>,
+    /// The values of the expression entry being run.
+    result: Option<String>,
+    limits: StoreLimits,
+}
+
+/// A call to a host function, at which the entry making it is suspended for
+/// [`Repl::eval()`] to make it.
+#[derive(Debug)]
+struct HostCall {
+    module: String,
+    name: String,
+    args: Vec<RawValue>,
+}
+
+/// The memory of the session, as seen from the host through the store or a
+/// [`Caller`].
+struct Shared<C> {
+    ctx: C,
+    memory: Memory,
+    heap: Global,
+}
+
+impl Repl {
+    /// A REPL whose entries can call the functions of `registry`, within the
+    /// default [`Limits`].
+    pub fn new<T>(registry: &HostRegistry<T>) -> Self {
+        Self::with_limits(registry, Limits::default())
+    }
+
+    /// A REPL whose entries can call the functions of `registry`, within
+    /// `limits`.
+    pub fn with_limits<T>(registry: &HostRegistry<T>, limits: Limits) -> Self {
+        let mut checker = Checker::new();
+        registry.declare(&mut checker);
+        let result = FunctionSig {
+            varargs: Some(Type::Any),
+            ..FunctionSig::default()
+        };
+        checker.declare_global(RESULT_FUNCTION, Type::Function(Rc::new(result)));
+
+        let session = Session::new();
+        let state = State {
+            printed: Vec::new(),
+            result: None,
+            limits: StoreLimitsBuilder::new()
+                .memory_size(limits.memory)
+                .instances(usize::MAX)
+                .tables(usize::MAX)
+                .trap_on_grow_failure(true)
+                .build(),
+        };
+        let mut config = Config::default();
+        config.consume_fuel(true);
+        let mut store = Store::new(&Engine::new(&config), state);
+        store.limiter(|state| &mut state.limits);
+        let memory_type = MemoryType::new(1, None).unwrap();
+        let memory = Memory::new(&mut store, memory_type).unwrap();
+        let heap = Global::new(&mut store, Val::I32(session.heap as i32), Mutability::Var);
+        let imports = HashMap::from([
+            (
+                (SESSION_MODULE.to_owned(), MEMORY_EXPORT.to_owned()),
+                memory.into(),
+            ),
+            (
+                (SESSION_MODULE.to_owned(), HEAP_EXPORT.to_owned()),
+                heap.into(),
+            ),
+        ]);
+        Self {
+            checker,
+            session,
+            limits,
+            store,
+            memory,
+            heap,
+            imports,
+        }
+    }
+
+    /// Run the entry `source`, keeping what it declares for the next ones.
+    /// The functions it calls from the host are those of `registry`, which
+    /// has the functions of the one the REPL was created with, called on
+    /// `host`. Returns what it printed, and its values as text if it is an
+    /// expression, or the error it failed with.
+    pub fn eval<T>(
+        &mut self,
+        source: &str,
+        registry: &HostRegistry<T>,
+        host: &mut T,
+    ) -> (Vec<String>, Result<Option<String>, ReplError>) {
+        let result = match self.compile(source) {
+            Ok((bytes, session)) => self.run(&bytes, session, registry, host),
+            Err(diagnostics) => Err(ReplError::Compile(diagnostics)),
+        };
+        let printed = std::mem::take(&mut self.store.data_mut().printed);
+        (printed, result)
+    }
+
+    /// Compile `source` as expressions if it is some, and as statements
+    /// otherwise, into a module and the session after it.
+    fn compile(&mut self, source: &str) -> Result<(Vec<u8>, Session), Diagnostics> {
+        if let Some(wrapped) = wrap_expressions(source) {
+            match self.compile_chunk(&wrapped) {
+                Ok(compiled) => return Ok(compiled),
+                // report the errors of the expression if the entry is not
+                // statements either
+                Err(diagnostics) if TealParser::parse_chunk(source).is_err() => {
+                    let offset = RESULT_FUNCTION.len() + 1;
+                    return Err(unwrap_spans(&diagnostics, offset, source.len()));
+                }
+                Err(_) => {}
+            }
+        }
+        self.compile_chunk(source)
+    }
+
+    fn compile_chunk(&mut self, source: &str) -> Result<(Vec<u8>, Session), Diagnostics> {
+        let mut diagnostics = Diagnostics::new();
+        let mut chunk = match TealParser::parse_chunk(source) {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                diagnostics.push(e);
+                return Err(diagnostics);
+            }
+        };
+        promote(&mut chunk);
+        let result = self.checker.check(&chunk);
+        // unused locals are expected at the top level of entries
+        self.checker.take_warnings();
+        let types = match result {
+            Ok(types) => types,
+            Err(errors) => {
+                errors.into_iter().for_each(|e| diagnostics.push(e));
+                return Err(diagnostics);
+            }
+        };
+
+        let mut session = self.session.clone();
+        if let Val::I32(heap) = self.heap.get(&self.store) {
+            session.heap = heap as u32;
+        }
+        match wasm::emit_in_session(&chunk, &types, &mut session) {
+            Ok(bytes) => Ok((bytes, session)),
+            Err(e) => {
+                diagnostics.push(e);
+                Err(diagnostics)
+            }
+        }
+    }
+
+    /// Instantiate and run the module of an entry, after which the session is
+    /// `session`, making the host calls it is suspended at.
+    fn run<T>(
+        &mut self,
+        bytes: &[u8],
+        session: Session,
+        registry: &HostRegistry<T>,
+        host: &mut T,
+    ) -> Result<Option<String>, ReplError> {
+        let link_error = |e: wasmi::Error| ReplError::Link(e.to_string());
+        self.store
+            .set_fuel(self.limits.fuel)
+            .map_err(|e| ReplError::Link(e.to_string()))?;
+        let module = Module::new(self.store.engine(), bytes).map_err(link_error)?;
+        let mut linker = Linker::new(self.store.engine());
+        for import in module.imports() {
+            let item = self.import(import.module(), import.name(), import.ty(), registry)?;
+            linker
+                .define(import.module(), import.name(), item)
+                .map_err(|e| ReplError::Link(e.to_string()))?;
+        }
+        reserve(&mut self.store, self.memory, session.heap).map_err(ReplError::Link)?;
+        let instance = linker
+            .instantiate(&mut self.store, &module)
+            .and_then(|instance| instance.start(&mut self.store))
+            .map_err(link_error)?;
+        self.heap
+            .set(&mut self.store, Val::I32(session.heap as i32))
+            .map_err(|e| ReplError::Link(e.to_string()))?;
+        for export in instance.exports(&self.store) {
+            if matches!(export.name(), wasm::ENTRY_POINT | wasm::ALLOC_EXPORT) {
+                continue;
+            }
+            let key = (SESSION_MODULE.to_owned(), export.name().to_owned());
+            self.imports.insert(key, export.into_extern());
+        }
+        self.session = session;
+
+        let main = instance
+            .get_typed_func::<(), ()>(&self.store, wasm::ENTRY_POINT)
+            .map_err(link_error)?;
+        self.store.data_mut().result = None;
+        let mut call = main.call_resumable(&mut self.store, ()).map_err(trap)?;
+        while let TypedResumableCall::Resumable(invocation) = call {
+            let Some(request) = invocation.host_error().downcast_ref::<HostCall>() else {
+                return Err(ReplError::Trap(invocation.host_error().to_string()));
+            };
+            let returned = self.call_host(request, registry, host)?;
+            call = invocation
+                .resume(&mut self.store, &returned)
+                .map_err(trap)?;
+        }
+        Ok(self.store.data_mut().result.take())
+    }
+
+    /// Make the host call `request` of an entry, returning the values to
+    /// resume it with. A panicking host function fails the entry.
+    fn call_host<T>(
+        &mut self,
+        request: &HostCall,
+        registry: &HostRegistry<T>,
+        host: &mut T,
+    ) -> Result<Vec<Val>, ReplError> {
+        let mut shared = Shared {
+            ctx: &mut self.store,
+            memory: self.memory,
+            heap: self.heap,
+        };
+        let (module, name) = (&request.module, &request.name);
+        let call = || registry.call(module, name, host, &mut shared, &request.args);
+        match panic::catch_unwind(AssertUnwindSafe(call)) {
+            Ok(Ok(returned)) => Ok(returned.map(val).into_iter().collect()),
+            Ok(Err(e)) => Err(ReplError::Trap(e.to_string())),
+            Err(_) => Err(ReplError::Trap(format!("'{module}.{name}' panicked"))),
+        }
+    }
+
+    /// The item a module imports as `name` from `module`, of type `ty`.
+    fn import<T>(
+        &mut self,
+        module: &str,
+        name: &str,
+        ty: &ExternType,
+        registry: &HostRegistry<T>,
+    ) -> Result<Extern, ReplError> {
+        let key = (module.to_owned(), name.to_owned());
+        if let Some(&item) = self.imports.get(&key) {
+            return Ok(item);
+        }
+        let (ExternType::Func(ty), false) = (ty, module == SESSION_MODULE) else {
+            return Err(ReplError::Link(format!("'{module}.{name}' is not defined")));
+        };
+        let function = match module {
+            HOST_MODULE => self.builtin(name, ty.clone())?,
+            _ if stdlib::LIBRARIES.contains(&module) => self.library(module, name, ty.clone())?,
+            _ => self.binding(module, name, ty.clone(), registry)?,
+        };
+        self.imports.insert(key, function.into());
+        Ok(function.into())
+    }
+
+    /// The function of [`HOST_MODULE`] named `name`: `pow`, or `print`,
+    /// `tostring`, or [`RESULT_FUNCTION`] followed by the types of their
+    /// arguments.
+    fn builtin(&mut self, name: &str, ty: FuncType) -> Result<Func, ReplError> {
+        if name == "pow" {
+            return Ok(Func::wrap(&mut self.store, |a: f64, b: f64| a.powf(b)));
+        }
+        let (function, types) = match name.split_once('(') {
+            Some((function, types)) => (function, types.trim_end_matches(')')),
+            None => (name, ""),
+        };
+        if function == "tostring" {
+            return Ok(self.tostring(types, ty));
+        }
+        let is_result = match function {
+            "print" => false,
+            RESULT_FUNCTION => true,
+            _ => return Err(ReplError::Link(format!("'{function}' is not available"))),
+        };
+        let types: Vec<String> = types
+            .split(',')
+            .filter(|ty| !ty.is_empty())
+            .map(str::to_owned)
+            .collect();
+        let (memory, heap) = (self.memory, self.heap);
+        let show_all = move |mut caller: Caller<'_, State>, params: &[Val], _: &mut [Val]| {
+            let shared = Shared {
+                ctx: &mut caller,
+                memory,
+                heap,
+            };
+            let mut values = Vec::with_capacity(params.len());
+            for (param, ty) in params.iter().zip(&types) {
+                let value = show(ty, raw_value(param), &shared);
+                values.push(value.map_err(|e| wasmi::Error::new(e.to_string()))?);
+            }
+            let line = values.join("\t");
+            let state = caller.data_mut();
+            match is_result {
+                true => state.result = Some(line),
+                false => state.printed.push(line),
+            }
+            Ok(())
+        };
+        Ok(Func::new(&mut self.store, ty, show_all))
+    }
+
+    /// `tostring` of a value of the Teal type `ty`, showing it as `print`
+    /// does.
+    fn tostring(&mut self, ty: &str, func_type: FuncType) -> Func {
+        let ty = ty.to_owned();
+        let (memory, heap) = (self.memory, self.heap);
+        let call = move |mut caller: Caller<'_, State>, params: &[Val], results: &mut [Val]| {
+            let mut shared = Shared {
+                ctx: &mut caller,
+                memory,
+                heap,
+            };
+            let error = |e: HostError| wasmi::Error::new(e.to_string());
+            let shown = show(&ty, raw_value(&params[0]), &shared).map_err(error)?;
+            let value = HostType::String.encode(HostValue::String(shown), &mut shared, "tostring");
+            results[0] = val(value.map_err(error)?);
+            Ok(())
+        };
+        Func::new(&mut self.store, func_type, call)
+    }
+
+    /// The function `name` of the standard library `module`, see [`stdlib`].
+    fn library(&mut self, module: &str, name: &str, ty: FuncType) -> Result<Func, ReplError> {
+        if !stdlib::provides(module, name) {
+            return Err(ReplError::Link(format!(
+                "'{module}.{name}' is not available"
+            )));
+        }
+        let (module, name) = (module.to_owned(), name.to_owned());
+        let (memory, heap) = (self.memory, self.heap);
+        let call = move |mut caller: Caller<'_, State>, params: &[Val], results: &mut [Val]| {
+            let args: Vec<RawValue> = params.iter().map(raw_value).collect();
+            let mut shared = Shared {
+                ctx: &mut caller,
+                memory,
+                heap,
+            };
+            let returned = stdlib::call(&module, &name, &mut shared, &args);
+            let returned = returned.map_err(|e| wasmi::Error::new(e.to_string()))?;
+            for (result, value) in results.iter_mut().zip(returned) {
+                *result = val(value);
+            }
+            Ok(())
+        };
+        Ok(Func::new(&mut self.store, ty, call))
+    }
+
+    /// The function `name` of the host's `module`, which suspends the entry
+    /// calling it at a [`HostCall`], if `registry` has it.
+    fn binding<T>(
+        &mut self,
+        module: &str,
+        name: &str,
+        ty: FuncType,
+        registry: &HostRegistry<T>,
+    ) -> Result<Func, ReplError> {
+        if registry.get(module, name).is_none() {
+            return Err(ReplError::Link(format!(
+                "'{module}.{name}' is not available"
+            )));
+        }
+        let (module, name) = (module.to_owned(), name.to_owned());
+        let call = move |_: Caller<'_, State>, params: &[Val], _: &mut [Val]| {
+            Err(wasmi::Error::host(HostCall {
+                module: module.clone(),
+                name: name.clone(),
+                args: params.iter().map(raw_value).collect(),
+            }))
+        };
+        Ok(Func::new(&mut self.store, ty, call))
+    }
+}
+
+impl Default for Limits {
+    /// Up to a second or so of computing, and 64 MiB of memory.
+    fn default() -> Self {
+        Self {
+            fuel: 100_000_000,
+            memory: 64 << 20,
+        }
+    }
+}
+
+impl fmt::Display for HostCall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Call to '{}.{}'", self.module, self.name)
+    }
+}
+
+impl wasmi::core::HostError for HostCall {}
+
+impl<C: AsContextMut<Data = State>> GuestMemory for Shared<C> {
+    fn bytes(&self) -> &[u8] {
+        self.memory.data(self.ctx.as_context())
+    }
+
+    fn bytes_mut(&mut self) -> &mut [u8] {
+        self.memory.data_mut(self.ctx.as_context_mut())
+    }
+
+    /// Allocate as the guest's [`wasm::ALLOC_EXPORT`] does, from the heap of
+    /// the session.
+    fn alloc(&mut self, size: u32) -> Result<u32, HostError> {
+        let Val::I32(address) = self.heap.get(self.ctx.as_context()) else {
+            return Err(HostError::Alloc("the heap is not an i32".into()));
+        };
+        let address = address as u32;
+        let end = address
+            .checked_add(size)
+            .ok_or_else(|| HostError::Alloc("out of memory".into()))?;
+        reserve(&mut self.ctx, self.memory, end).map_err(HostError::Alloc)?;
+        let next = Val::I32(end.next_multiple_of(4) as i32);
+        self.heap
+            .set(self.ctx.as_context_mut(), next)
+            .map_err(|e| HostError::Alloc(e.to_string()))?;
+        Ok(address)
+    }
+}
+
+impl fmt::Display for ReplError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Compile(diagnostics) => {
+                for (i, diagnostic) in diagnostics.iter().enumerate() {
+                    if i > 0 {
+                        writeln!(f)?;
+                    }
+                    write!(f, "{diagnostic}")?;
+                }
+                Ok(())
+            }
+            Self::Link(message) => write!(f, "Failed to link the entry: {message}"),
+            Self::Trap(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for ReplError {}
+
+/// Returns `false` if `source` ends before what it opened, eg. a function or
+/// a parenthesis, for a REPL to read more lines of the entry.
+pub fn is_complete(source: &str) -> bool {
+    match TealParser::parse_chunk(source) {
+        Ok(_) => true,
+        Err(_) if wrap_expressions(source).is_some() => true,
+        // the error is at the end, eg. `record R` without its fields
+        Err(e) => e.span.end < source.trim_end().len(),
+    }
+}
+
+/// `source` passed to [`RESULT_FUNCTION`], if it is a list of expressions.
+fn wrap_expressions(source: &str) -> Option<String> {
+    // on its own line, in case the entry ends with a comment
+    let wrapped = format!("{RESULT_FUNCTION}({source}\n)");
+    let chunk = TealParser::parse_chunk(&wrapped).ok()?;
+    let [statement] = &chunk.statements[..] else {
+        return None;
+    };
+    let StatementKind::Call(call) = &statement.kind else {
+        return None;
+    };
+    let ExprKind::Call { callee, .. } = &call.kind else {
+        return None;
+    };
+    match &callee.kind {
+        ExprKind::Name(name) if name.name == RESULT_FUNCTION && chunk.ret.is_none() => {
+            Some(wrapped)
+        }
+        _ => None,
+    }
+}
+
+/// Make the locals declared at the top level of `chunk` global, for the next
+/// entries to see them.
+fn promote(chunk: &mut Block) {
+    for statement in &mut chunk.statements {
+        match &mut statement.kind {
+            StatementKind::Variables { scope, .. } | StatementKind::Type { scope, .. } => {
+                *scope = Scope::Global;
+            }
+            StatementKind::Function(decl) if decl.scope == Some(Scope::Local) => {
+                decl.scope = Some(Scope::Global);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// `diagnostics` of an entry of `len` bytes compiled at `offset` in a wrapper,
+/// with spans in the entry.
+fn unwrap_spans(diagnostics: &Diagnostics, offset: usize, len: usize) -> Diagnostics {
+    let unwrap = |at: usize| at.saturating_sub(offset).min(len);
+    let mut unwrapped = Diagnostics::new();
+    for diagnostic in diagnostics {
+        unwrapped.push(Diagnostic {
+            span: Span {
+                start: unwrap(diagnostic.span.start),
+                end: unwrap(diagnostic.span.end),
+            },
+            ..diagnostic.clone()
+        });
+    }
+    unwrapped
+}
+
+/// Grow `memory` to hold the addresses up to `end`.
+fn reserve(mut ctx: impl AsContextMut, memory: Memory, end: u32) -> Result<(), String> {
+    let pages = u32::from(memory.current_pages(ctx.as_context()));
+    let needed = end.div_ceil(PAGE_SIZE);
+    if needed > pages {
+        let additional = Pages::new(needed - pages).ok_or("out of memory")?;
+        memory
+            .grow(ctx.as_context_mut(), additional)
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// The error an entry failed with while running.
+fn trap(e: wasmi::Error) -> ReplError {
+    match e.as_trap_code() {
+        Some(TrapCode::OutOfFuel) => ReplError::Trap("The entry ran for too long".to_owned()),
+        Some(TrapCode::GrowthOperationLimited) => {
+            ReplError::Trap("The entry ran out of memory".to_owned())
+        }
+        Some(TrapCode::UnreachableCodeReached) => ReplError::Trap(
+            "The entry indexed an array out of bounds, or a function ended without returning"
+                .to_owned(),
+        ),
+        _ => ReplError::Trap(e.to_string()),
+    }
+}
+
+fn raw_value(value: &Val) -> RawValue {
+    match value {
+        Val::I64(value) => RawValue::I64(*value),
+        Val::F64(value) => RawValue::F64(f64::from(*value)),
+        Val::F32(value) => RawValue::F64(f32::from(*value) as f64),
+        Val::I32(value) => RawValue::I32(*value),
+        _ => RawValue::I32(0),
+    }
+}
+
+fn val(value: RawValue) -> Val {
+    match value {
+        RawValue::I32(value) => Val::I32(value),
+        RawValue::I64(value) => Val::I64(value),
+        RawValue::F64(value) => Val::F64(value.into()),
+    }
+}
+
+/// The value `raw` of the Teal type `ty` as `print` shows it.
+pub(crate) fn show(ty: &str, raw: RawValue, memory: &dyn GuestMemory) -> Result<String, HostError> {
+    let host_type = match (ty, raw) {
+        (_, RawValue::I64(value)) if ty == "u64" => return Ok((value as u64).to_string()),
+        (_, RawValue::I64(value)) => return Ok(value.to_string()),
+        (_, RawValue::F64(value)) => return Ok(number(value)),
+        ("boolean", _) => HostType::Boolean,
+        ("{string}", _) => HostType::Strings,
+        (_, RawValue::I32(value)) => match Primitive::from_label(ty) {
+            Some(Primitive::U32) => return Ok((value as u32).to_string()),
+            Some(primitive) if primitive.is_integer() => return Ok(value.to_string()),
+            // strings and enums
+            _ => HostType::String,
+        },
+    };
+    let shown = match host_type.decode(raw, memory, "print")? {
+        HostValue::Boolean(value) => value.to_string(),
+        HostValue::Strings(values) => format!("{{{}}}", values.join(", ")),
+        value => value.as_str().to_owned(),
+    };
+    Ok(shown)
+}
+
+/// `value` as Lua shows numbers, with a fractional part if integral and an
+/// exponent if large.
+fn number(value: f64) -> String {
+    match value {
+        _ if value.is_nan() => "nan".to_owned(),
+        _ if value.is_infinite() => format!("{}inf", if value < 0.0 { "-" } else { "" }),
+        _ if value.abs() >= 1e16 => format!("{value:e}"),
+        _ if value.fract() == 0.0 => format!("{value:.1}"),
+        _ => value.to_string(),
+    }
+}
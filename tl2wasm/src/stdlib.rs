@@ -0,0 +1,660 @@
+//! # Standard library
+//!
+//! Compiled scripts get the functions of Teal's `string`, `math`, and `table`
+//! libraries in two ways. Those simple enough, such as `math.floor` or
+//! `table.insert`, are lowered to instructions, see [`crate::codegen::wasm`].
+//! The others are imported from modules named after their library, eg.
+//! `string.upper` imports `upper` from the module `string`. Functions taking
+//! values of any type have the types of the arguments appended to the name of
+//! the import, eg. `format(string,integer)`, as do those with several
+//! signatures or varargs, eg. `random(integer,integer)` or `char(integer)`.
+//! [`call()`] runs these imports on the values passed by the guest, as the
+//! runtimes bind them:
+//!
+//! ```ignore
+//! if stdlib::provides(module, name) {
+//!     let results = stdlib::call(module, name, &mut memory, &args)?;
+//! }
+//! ```
+//!
+//! `string.format` follows Lua's: `%d`, `%i`, `%c`, `%o`, `%x`, `%X`, `%e`,
+//! `%E`, `%f`, `%F`, `%g`, `%G`, `%s`, `%q`, and `%%`, with the flags `-`,
+//! `+`, space, `#`, and `0`, a width, and a precision.
+//!
+//! `string.find` only finds plain substrings: patterns with magic characters
+//! fail unless its `plain` argument is `true`. As `nil` is lowered to the zero
+//! value, `string.find` returns `0, 0` if it finds nothing and `string.byte`
+//! `0` out of bounds. `table.sort` sorts numbers and strings in ascending
+//! order, without a comparator.
+
+use std::cell::Cell;
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+
+use crate::host::{self, GuestMemory, HostError, HostType, HostValue, RawValue};
+use crate::repl;
+use crate::teal::ast::Primitive;
+
+/// The libraries whose functions are imported from modules of their names.
+pub const LIBRARIES: [&str; 3] = ["string", "math", "table"];
+
+/// The functions of each of [`LIBRARIES`] imported rather than lowered to
+/// instructions.
+const IMPORTED: [(&str, &[&str]); 3] = [
+    (
+        "string",
+        &[
+            "upper", "lower", "rep", "reverse", "sub", "format", "find", "byte", "char",
+        ],
+    ),
+    (
+        "math",
+        &[
+            "exp",
+            "log",
+            "sin",
+            "cos",
+            "tan",
+            "asin",
+            "acos",
+            "atan",
+            "random",
+            "randomseed",
+        ],
+    ),
+    ("table", &["concat", "sort"]),
+];
+
+/// The characters making a pattern of `string.find` more than a substring.
+const MAGIC: &str = "^$*+?.([%-";
+
+thread_local! {
+    /// The state of `math.random`, an xorshift generator.
+    static RANDOM: Cell<u64> = Cell::new(RandomState::new().hash_one(0u64) | 1);
+}
+
+/// The arguments passed by the guest to a function of the library.
+struct Args<'a> {
+    function: &'a str,
+    raw: &'a [RawValue],
+    memory: &'a dyn GuestMemory,
+}
+
+/// A conversion of [`format()`], eg. `%-5.2f`.
+#[derive(Default)]
+struct Spec {
+    left: bool,
+    plus: bool,
+    space: bool,
+    alternate: bool,
+    zero: bool,
+    width: usize,
+    precision: Option<usize>,
+    conversion: char,
+}
+
+/// Returns `true` if [`call()`] runs the function `import` of `module`.
+pub fn provides(module: &str, import: &str) -> bool {
+    let name = import.split_once('(').map_or(import, |(name, _)| name);
+    IMPORTED
+        .iter()
+        .any(|(library, names)| *library == module && names.contains(&name))
+}
+
+/// Call the function imported as `import` from the library `module` with the
+/// `args` passed by the guest, returning the values to pass back.
+pub fn call(
+    module: &str,
+    import: &str,
+    memory: &mut dyn GuestMemory,
+    args: &[RawValue],
+) -> Result<Vec<RawValue>, HostError> {
+    let (name, types) = match import.split_once('(') {
+        Some((name, types)) => (name, types.trim_end_matches(')')),
+        None => (import, ""),
+    };
+    if !provides(module, name) {
+        return Err(HostError::Unknown {
+            module: module.to_owned(),
+            name: import.to_owned(),
+        });
+    }
+    let function = format!("{module}.{name}");
+    let args = Args {
+        function: &function,
+        raw: args,
+        memory,
+    };
+    let failed = |message: String| HostError::Failed {
+        function: function.clone(),
+        message,
+    };
+    let value = match (module, name) {
+        ("string", "upper") => HostValue::String(args.string(0)?.to_ascii_uppercase()),
+        ("string", "lower") => HostValue::String(args.string(0)?.to_ascii_lowercase()),
+        ("string", "reverse") => HostValue::String(args.string(0)?.chars().rev().collect()),
+        ("string", "rep") => {
+            let (s, n) = (args.string(0)?, args.integer(1)?.max(0) as usize);
+            if s.len().saturating_mul(n) > u32::MAX as usize {
+                return Err(failed("resulting string too large".to_owned()));
+            }
+            HostValue::String(s.repeat(n))
+        }
+        ("string", "sub") => {
+            let s = args.string(0)?;
+            // `j` is 0 when omitted, as `nil`
+            let (i, j) = (args.integer(1)?, args.integer(2)?);
+            let j = if j == 0 { -1 } else { j };
+            let len = s.len() as i64;
+            let start = match i {
+                _ if i < 0 => (len + i).max(0),
+                0 => 0,
+                _ => i - 1,
+            };
+            let end = if j < 0 { len + j + 1 } else { j.min(len) };
+            let bytes = s.as_bytes().get(start as usize..end.max(start) as usize);
+            HostValue::String(String::from_utf8_lossy(bytes.unwrap_or_default()).into_owned())
+        }
+        ("string", "format") => {
+            let types: Vec<&str> = types.split(',').collect();
+            HostValue::String(format(&args, &types).map_err(failed)?)
+        }
+        ("string", "find") => {
+            let (s, pattern) = (args.string(0)?, args.string(1)?);
+            // `init` is 0 and `plain` false when omitted, as `nil`
+            let (init, plain) = (args.integer(2)?, args.boolean(3)?);
+            if !plain && pattern.contains(|c| MAGIC.contains(c)) {
+                return Err(failed(
+                    "patterns are not supported, pass 'plain' as true".to_owned(),
+                ));
+            }
+            let found = position(init, s.len()).and_then(|start| {
+                let rest = &s.as_bytes()[start..];
+                match pattern.is_empty() {
+                    true => Some(start),
+                    false => rest
+                        .windows(pattern.len())
+                        .position(|window| window == pattern.as_bytes())
+                        .map(|at| start + at),
+                }
+            });
+            let (first, last) = match found {
+                Some(at) => ((at + 1) as i64, (at + pattern.len()) as i64),
+                None => (0, 0),
+            };
+            return Ok(vec![RawValue::I64(first), RawValue::I64(last)]);
+        }
+        ("string", "byte") => {
+            let s = args.string(0)?;
+            let i = match args.integer(1)? {
+                0 => 1,
+                i => i,
+            };
+            let byte = match i {
+                _ if i < 0 => s.len().checked_sub(i.unsigned_abs() as usize),
+                _ => Some(i as usize - 1),
+            };
+            let byte = byte.and_then(|i| s.as_bytes().get(i)).copied();
+            HostValue::Integer(byte.map_or(0, i64::from))
+        }
+        ("string", "char") => {
+            let mut bytes = Vec::with_capacity(args.raw.len());
+            for i in 0..args.raw.len() {
+                let code = args.integer(i)?;
+                let byte = u8::try_from(code).map_err(|_| {
+                    failed(format!(
+                        "bad argument #{} to 'char' (value out of range)",
+                        i + 1
+                    ))
+                })?;
+                bytes.push(byte);
+            }
+            let s = String::from_utf8(bytes)
+                .map_err(|_| failed("resulting string is not valid UTF-8".to_owned()))?;
+            HostValue::String(s)
+        }
+        ("math", "randomseed") => {
+            // scrambled by splitmix64, as xorshift is stuck at 0
+            let mut seed = (args.integer(0)? as u64).wrapping_add(0x9e37_79b9_7f4a_7c15);
+            seed = (seed ^ (seed >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            seed = (seed ^ (seed >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            RANDOM.set((seed ^ (seed >> 31)) | 1);
+            return Ok(Vec::new());
+        }
+        ("math", "random") => {
+            let random = random();
+            let (m, n) = match args.raw.len() {
+                0 => {
+                    return Ok(vec![RawValue::F64(
+                        (random >> 11) as f64 / (1u64 << 53) as f64,
+                    )]);
+                }
+                1 => (1, args.integer(0)?),
+                _ => (args.integer(0)?, args.integer(1)?),
+            };
+            if m > n {
+                return Err(failed(
+                    "bad argument to 'random' (interval is empty)".to_owned(),
+                ));
+            }
+            let span = (n as i128 - m as i128 + 1) as u128;
+            HostValue::Integer((m as i128 + (random as u128 % span) as i128) as i64)
+        }
+        ("table", "sort") => {
+            let element = types.trim_start_matches('{').trim_end_matches('}');
+            let sorted = sort(&args, element).map_err(failed)?;
+            return match sorted {
+                Some(bytes) => Ok(vec![RawValue::I32(
+                    host::write_bytes(memory, &bytes)? as i32
+                )]),
+                None => Ok(vec![args.get(0)?]),
+            };
+        }
+        ("math", _) => {
+            let x = args.number(0)?;
+            HostValue::Number(match name {
+                "exp" => x.exp(),
+                "log" => x.ln(),
+                "sin" => x.sin(),
+                "cos" => x.cos(),
+                "tan" => x.tan(),
+                "asin" => x.asin(),
+                "acos" => x.acos(),
+                _ => x.atan(),
+            })
+        }
+        _ => {
+            let HostValue::Strings(list) = args.decode(0, HostType::Strings)? else {
+                unreachable!();
+            };
+            HostValue::String(list.join(&args.string(1)?))
+        }
+    };
+    let ty = match value {
+        HostValue::Integer(_) => HostType::Integer,
+        HostValue::Number(_) => HostType::Number,
+        _ => HostType::String,
+    };
+    Ok(vec![ty.encode(value, memory, &function)?])
+}
+
+/// The offset of the position `init` of `string.find` in a string of `len`
+/// bytes, counting from the end if negative, `None` if past its end.
+fn position(init: i64, len: usize) -> Option<usize> {
+    match init {
+        0 => Some(0),
+        _ if init < 0 => Some(len.saturating_sub(init.unsigned_abs() as usize)),
+        _ => Some(init as usize - 1).filter(|&start| start <= len),
+    }
+}
+
+/// The next value of `math.random`'s generator.
+fn random() -> u64 {
+    let mut x = RANDOM.get();
+    x ^= x << 13;
+    x ^= x >> 7;
+    x ^= x << 17;
+    RANDOM.set(x);
+    x
+}
+
+/// The bytes of a sorted copy of the array passed to `table.sort`, of
+/// elements of the Teal type `element`, or `None` if it is empty.
+fn sort(args: &Args, element: &str) -> Result<Option<Vec<u8>>, String> {
+    let RawValue::I32(address) = args.get(0).map_err(|e| e.to_string())? else {
+        return Err("bad argument #1 to 'sort' (table expected)".to_owned());
+    };
+    let memory = args.memory.bytes();
+    let len = host::read_u32(args.memory, address as u32).map_err(|e| e.to_string())? as usize;
+    if len == 0 {
+        return Ok(None);
+    }
+    let primitive = Primitive::from_label(element);
+    let wide = matches!(
+        primitive,
+        Some(
+            Primitive::Integer
+                | Primitive::I64
+                | Primitive::U64
+                | Primitive::Number
+                | Primitive::F64
+        )
+    );
+    let width = if wide { 8 } else { 4 };
+    let start = address as usize + 4;
+    let elements = memory
+        .get(start..start + len * width)
+        .ok_or_else(|| format!("array at {address} out of bounds"))?;
+    let mut elements: Vec<&[u8]> = elements.chunks(width).collect();
+    let word = |bytes: &[u8]| u32::from_le_bytes(bytes[..4].try_into().unwrap());
+    let long = |bytes: &[u8]| u64::from_le_bytes(bytes.try_into().unwrap());
+    match primitive {
+        Some(Primitive::Integer | Primitive::I64) => elements.sort_by_key(|e| long(e) as i64),
+        Some(Primitive::U64) => elements.sort_by_key(|e| long(e)),
+        Some(Primitive::Number | Primitive::F64) => {
+            elements.sort_by(|a, b| f64::from_bits(long(a)).total_cmp(&f64::from_bits(long(b))))
+        }
+        Some(Primitive::F32 | Primitive::F16 | Primitive::BF16) => {
+            elements.sort_by(|a, b| f32::from_bits(word(a)).total_cmp(&f32::from_bits(word(b))))
+        }
+        Some(Primitive::U32) => elements.sort_by_key(|e| word(e)),
+        Some(primitive) if primitive.is_integer() => elements.sort_by_key(|e| word(e) as i32),
+        Some(Primitive::Boolean | Primitive::Nil) => {
+            return Err(format!("attempt to compare two {element} values"));
+        }
+        // strings and enums, by their bytes
+        _ => {
+            let mut strings = Vec::with_capacity(len);
+            for e in elements {
+                let string = word(e) as usize;
+                let bytes = host::read_u32(args.memory, string as u32)
+                    .ok()
+                    .and_then(|n| memory.get(string + 4..string + 4 + n as usize))
+                    .ok_or_else(|| format!("string at {string} out of bounds"))?;
+                strings.push((bytes, e));
+            }
+            strings.sort_by_key(|(bytes, _)| *bytes);
+            elements = strings.into_iter().map(|(_, e)| e).collect();
+        }
+    }
+    let mut bytes = (len as u32).to_le_bytes().to_vec();
+    bytes.extend(elements.concat());
+    Ok(Some(bytes))
+}
+
+impl Args<'_> {
+    fn get(&self, i: usize) -> Result<RawValue, HostError> {
+        self.raw
+            .get(i)
+            .copied()
+            .ok_or_else(|| HostError::Signature {
+                function: self.function.to_owned(),
+                message: format!("missing argument #{}", i + 1),
+            })
+    }
+
+    fn decode(&self, i: usize, ty: HostType) -> Result<HostValue, HostError> {
+        ty.decode(self.get(i)?, self.memory, self.function)
+    }
+
+    fn string(&self, i: usize) -> Result<String, HostError> {
+        Ok(self.decode(i, HostType::String)?.as_str().to_owned())
+    }
+
+    fn integer(&self, i: usize) -> Result<i64, HostError> {
+        match self.decode(i, HostType::Integer)? {
+            HostValue::Integer(value) => Ok(value),
+            _ => unreachable!(),
+        }
+    }
+
+    fn boolean(&self, i: usize) -> Result<bool, HostError> {
+        match self.decode(i, HostType::Boolean)? {
+            HostValue::Boolean(value) => Ok(value),
+            _ => unreachable!(),
+        }
+    }
+
+    fn number(&self, i: usize) -> Result<f64, HostError> {
+        match self.decode(i, HostType::Number)? {
+            HostValue::Number(value) => Ok(value),
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// `string.format` of the `args` of Teal types `types`, the first being the
+/// format string.
+fn format(args: &Args, types: &[&str]) -> Result<String, String> {
+    let template = args.string(0).map_err(|e| e.to_string())?;
+    let mut out = String::new();
+    let mut chars = template.chars().peekable();
+    let mut next = 1;
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue;
+        }
+        if chars.next_if_eq(&'%').is_some() {
+            out.push('%');
+            continue;
+        }
+        let mut spec = Spec::default();
+        while let Some(flag) = chars.next_if(|c| "-+ #0".contains(*c)) {
+            match flag {
+                '-' => spec.left = true,
+                '+' => spec.plus = true,
+                ' ' => spec.space = true,
+                '#' => spec.alternate = true,
+                _ => spec.zero = true,
+            }
+        }
+        while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+            spec.width = spec.width * 10 + digit as usize - '0' as usize;
+        }
+        if chars.next_if_eq(&'.').is_some() {
+            let mut precision = 0;
+            while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+                precision = precision * 10 + digit as usize - '0' as usize;
+            }
+            spec.precision = Some(precision);
+        }
+        if spec.width > 99 || spec.precision.is_some_and(|p| p > 99) {
+            return Err("invalid conversion (width or precision too long)".to_owned());
+        }
+        spec.conversion = chars
+            .next()
+            .ok_or("invalid conversion '%' at the end of the format")?;
+        let (Some(&ty), Some(&raw)) = (types.get(next), args.raw.get(next)) else {
+            return Err(format!("bad argument #{} to 'format' (no value)", next + 1));
+        };
+        next += 1;
+        let bad = |expected: &str| {
+            format!("bad argument #{next} to 'format' ({expected} expected, got {ty})")
+        };
+        let formatted = match spec.conversion {
+            'd' | 'i' | 'c' | 'o' | 'x' | 'X' => {
+                let value = match raw {
+                    RawValue::I64(value) => value,
+                    RawValue::F64(value) if value.fract() == 0.0 && value.abs() < 2f64.powi(63) => {
+                        value as i64
+                    }
+                    RawValue::F64(_) => {
+                        return Err(format!(
+                            "bad argument #{next} to 'format' (number has no integer representation)"
+                        ));
+                    }
+                    RawValue::I32(value)
+                        if Primitive::from_label(ty).is_some_and(|p| p.is_integer()) =>
+                    {
+                        match ty {
+                            "u32" => value as u32 as i64,
+                            _ => value as i64,
+                        }
+                    }
+                    RawValue::I32(_) => return Err(bad("number")),
+                };
+                spec.integer(value)
+            }
+            'e' | 'E' | 'f' | 'F' | 'g' | 'G' => match raw {
+                RawValue::F64(value) => spec.float(value),
+                RawValue::I64(value) => spec.float(value as f64),
+                RawValue::I32(_) => return Err(bad("number")),
+            },
+            's' => {
+                let shown = repl::show(ty, raw, args.memory).map_err(|e| e.to_string())?;
+                match spec.precision {
+                    Some(precision) => shown.chars().take(precision).collect(),
+                    None => shown,
+                }
+            }
+            'q' => match raw {
+                RawValue::I32(_) if ty != "boolean" => {
+                    let value = repl::show(ty, raw, args.memory).map_err(|e| e.to_string())?;
+                    quote(&value)
+                }
+                _ => repl::show(ty, raw, args.memory).map_err(|e| e.to_string())?,
+            },
+            other => return Err(format!("invalid conversion '%{other}' to 'format'")),
+        };
+        out.push_str(&spec.pad(formatted));
+    }
+    Ok(out)
+}
+
+impl Spec {
+    /// `value` by a conversion of integers, without padding.
+    fn integer(&self, value: i64) -> String {
+        let digits = match self.conversion {
+            'c' => {
+                return char::from_u32(value as u32)
+                    .unwrap_or('\u{fffd}')
+                    .to_string();
+            }
+            'o' => format!("{:o}", value as u64),
+            'x' => format!("{:x}", value as u64),
+            'X' => format!("{:X}", value as u64),
+            _ => value.unsigned_abs().to_string(),
+        };
+        let digits = match self.precision {
+            Some(precision) if digits.len() < precision => {
+                format!("{}{digits}", "0".repeat(precision - digits.len()))
+            }
+            _ => digits,
+        };
+        let prefix = match self.conversion {
+            'o' if self.alternate && !digits.starts_with('0') => "0",
+            'x' if self.alternate && value != 0 => "0x",
+            'X' if self.alternate && value != 0 => "0X",
+            'd' | 'i' => self.sign(value < 0),
+            _ => "",
+        };
+        format!("{prefix}{digits}")
+    }
+
+    /// `value` by a conversion of floats, without padding.
+    fn float(&self, value: f64) -> String {
+        let upper = self.conversion.is_ascii_uppercase();
+        let sign = self.sign(value.is_sign_negative());
+        let magnitude = value.abs();
+        if !value.is_finite() {
+            let text = if value.is_nan() { "nan" } else { "inf" };
+            let text = if upper {
+                text.to_uppercase()
+            } else {
+                text.to_owned()
+            };
+            return format!("{sign}{text}");
+        }
+        let precision = self.precision.unwrap_or(6);
+        let text = match self.conversion.to_ascii_lowercase() {
+            'f' => format!("{magnitude:.precision$}"),
+            'e' => exponent(magnitude, precision),
+            _ => {
+                // the shorter of `%e` and `%f`, with `precision` significant
+                // digits
+                let precision = precision.max(1);
+                let scientific = exponent(magnitude, precision - 1);
+                let power: i32 = scientific.rsplit('e').next().unwrap().parse().unwrap();
+                let text = if power < -4 || power >= precision as i32 {
+                    scientific
+                } else {
+                    let decimals = (precision as i32 - 1 - power) as usize;
+                    format!("{magnitude:.decimals$}")
+                };
+                match self.alternate {
+                    true => text,
+                    false => strip_zeros(&text),
+                }
+            }
+        };
+        let text = match self.alternate && !text.contains('.') {
+            true => match text.split_once('e') {
+                Some((mantissa, power)) => format!("{mantissa}.e{power}"),
+                None => format!("{text}."),
+            },
+            false => text,
+        };
+        let text = if upper { text.to_uppercase() } else { text };
+        format!("{sign}{text}")
+    }
+
+    /// The sign of a number, negative if `negative`.
+    fn sign(&self, negative: bool) -> &'static str {
+        match () {
+            _ if negative => "-",
+            _ if self.plus => "+",
+            _ if self.space => " ",
+            _ => "",
+        }
+    }
+
+    /// `text` padded to the width, with zeros after its sign or prefix if
+    /// asked for a number.
+    fn pad(&self, text: String) -> String {
+        let len = text.chars().count();
+        if len >= self.width {
+            return text;
+        }
+        let padding = self.width - len;
+        let numeric = !matches!(self.conversion, 's' | 'q' | 'c');
+        let zeroed = self.zero && !self.left && numeric && !text.ends_with(['n', 'f', 'N', 'F']);
+        match () {
+            _ if self.left => format!("{text}{}", " ".repeat(padding)),
+            _ if zeroed => {
+                // after the sign and a `0x` prefix, before the digits
+                let sign = text.starts_with(['-', '+', ' ']) as usize;
+                let hex = text[sign..].starts_with("0x") || text[sign..].starts_with("0X");
+                let split = sign + if hex { 2 } else { 0 };
+                format!(
+                    "{}{}{}",
+                    &text[..split],
+                    "0".repeat(padding),
+                    &text[split..]
+                )
+            }
+            _ => format!("{}{text}", " ".repeat(padding)),
+        }
+    }
+}
+
+/// `value` as `%e` shows it, with `precision` decimals and at least two
+/// digits of exponent.
+fn exponent(value: f64, precision: usize) -> String {
+    let text = format!("{value:.precision$e}");
+    let (mantissa, power) = text.split_once('e').unwrap();
+    let power: i32 = power.parse().unwrap();
+    let sign = if power < 0 { '-' } else { '+' };
+    format!("{mantissa}e{sign}{:02}", power.abs())
+}
+
+/// `text` without the trailing zeros of its fraction, nor its point if then
+/// last.
+fn strip_zeros(text: &str) -> String {
+    let (mantissa, power) = match text.split_once('e') {
+        Some((mantissa, power)) => (mantissa, format!("e{power}")),
+        None => (text, String::new()),
+    };
+    let mantissa = match mantissa.contains('.') {
+        true => mantissa.trim_end_matches('0').trim_end_matches('.'),
+        false => mantissa,
+    };
+    format!("{mantissa}{power}")
+}
+
+/// `value` as `%q` shows a string, in double quotes, escaped to read back.
+fn quote(value: &str) -> String {
+    let mut quoted = String::from('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\0' => quoted.push_str("\\0"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
@@ -0,0 +1,613 @@
+//! Typed syntax tree of Teal source, built by [`TealParser`](super::TealParser).
+//! Every node carries the [`Span`] of the source it was parsed from.
+
+use std::fmt;
+
+/// Byte range of a node in the source it was parsed from.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// A sequence of statements, eg. a chunk or a function's body.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Block {
+    pub statements: Vec<Statement>,
+    /// The final `return`, if any.
+    pub ret: Option<Return>,
+    pub span: Span,
+}
+
+/// `return values`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Return {
+    pub values: Vec<Expr>,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Statement {
+    pub kind: StatementKind,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum StatementKind {
+    /// `targets = values`, the targets being names, fields or indexes.
+    Assign {
+        targets: Vec<Expr>,
+        values: Vec<Expr>,
+    },
+    /// A call whose results are discarded.
+    Call(Expr),
+    /// `::name::`
+    Label(Name),
+    Break,
+    Goto(Name),
+    Do(Block),
+    While {
+        condition: Expr,
+        body: Block,
+    },
+    Repeat {
+        body: Block,
+        condition: Expr,
+    },
+    /// `if ... elseif ... else ... end`, with a branch per condition.
+    If {
+        branches: Vec<IfBranch>,
+        otherwise: Option<Block>,
+    },
+    /// `for var = start, limit, step do ... end`
+    NumericFor {
+        var: Name,
+        start: Expr,
+        limit: Expr,
+        step: Option<Expr>,
+        body: Block,
+    },
+    /// `for names in values do ... end`
+    GenericFor {
+        names: Vec<Name>,
+        values: Vec<Expr>,
+        body: Block,
+    },
+    /// `local x: T = value` or `global x: T = value`.
+    Variables {
+        scope: Scope,
+        names: Vec<AttName>,
+        types: Vec<TypeAnnotation>,
+        values: Vec<Expr>,
+    },
+    Function(FunctionDecl),
+    /// `local record R ... end`, `local enum E ... end` or `local type T = ...`,
+    /// or global ones.
+    Type {
+        scope: Scope,
+        name: Name,
+        def: TypeDef,
+    },
+}
+
+/// A condition of an `if` statement, and the block run if it holds.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IfBranch {
+    pub condition: Expr,
+    pub body: Block,
+    pub span: Span,
+}
+
+/// Where a declaration is visible.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Scope {
+    /// `local`, until the end of the enclosing block.
+    Local,
+    /// `global`, everywhere once declared.
+    Global,
+}
+
+/// A declared variable, eg. `x <const>`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AttName {
+    pub name: Name,
+    /// `const`, `close` or `total`.
+    pub attrib: Option<Name>,
+}
+
+/// A named function, eg. `function a.b:c() ... end` or `local function f()
+/// ... end`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FunctionDecl {
+    /// `None` for `function path()`, which assigns to an existing variable
+    /// or field.
+    pub scope: Option<Scope>,
+    /// The name, preceded by the tables it is a field of, if any.
+    pub path: Vec<Name>,
+    /// The method name after `:`, taking an implicit `self`.
+    pub method: Option<Name>,
+    pub body: FunctionBody,
+    pub span: Span,
+}
+
+/// The parameters, return types and body of a function.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FunctionBody {
+    /// Generic type parameters, eg. `<T>`.
+    pub type_params: Vec<Name>,
+    pub params: Vec<Param>,
+    /// `...`, with the type of the extra arguments if given.
+    pub varargs: Option<Option<TypeAnnotation>>,
+    pub returns: Vec<TypeAnnotation>,
+    /// Returns `true` if the last return type repeats, as in `: string...`.
+    pub variadic_return: bool,
+    pub block: Block,
+    pub span: Span,
+}
+
+/// A function parameter, eg. `x?: number`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Param {
+    pub name: Name,
+    pub optional: bool,
+    pub ty: Option<TypeAnnotation>,
+    pub span: Span,
+}
+
+/// What a type declaration defines.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TypeDef {
+    Record(Record),
+    Enum(Enum),
+    /// Another name for a type.
+    Alias(TypeAnnotation),
+    /// `require("module").path`, a type exported by another module.
+    Require {
+        module: String,
+        path: Vec<Name>,
+        span: Span,
+    },
+}
+
+/// `record R ... end` or `interface I ... end`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Record {
+    pub name: Name,
+    pub interface: bool,
+    pub type_params: Vec<Name>,
+    /// The interfaces after `is`.
+    pub interfaces: Vec<TypeAnnotation>,
+    /// The condition after `where`, telling the record apart in `is` checks.
+    pub where_clause: Option<Expr>,
+    pub entries: Vec<RecordEntry>,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum RecordEntry {
+    /// `userdata`, marking the record as opaque.
+    Userdata(Span),
+    Field(RecordField),
+    /// A nested type, eg. `record Inner ... end` or `type T = ...`.
+    Type {
+        name: Name,
+        def: TypeDef,
+        span: Span,
+    },
+}
+
+/// A field of a record, eg. `x: number` or `metamethod __add: function(...)`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecordField {
+    /// The field's name, also for keys written as strings, eg. `["end"]`.
+    pub name: Name,
+    pub metamethod: bool,
+    pub ty: TypeAnnotation,
+    pub span: Span,
+}
+
+/// `enum E "a" "b" end`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Enum {
+    pub name: Name,
+    /// The values, as names spanning their string literal.
+    pub values: Vec<Name>,
+    pub span: Span,
+}
+
+/// A type as written in the source, eg. in a declaration or cast.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TypeAnnotation {
+    pub kind: TypeKind,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TypeKind {
+    Any,
+    Primitive(Primitive),
+    /// A declared type, eg. `Point` or `module.Map<K, V>`.
+    Nominal {
+        path: Vec<Name>,
+        args: Vec<TypeAnnotation>,
+    },
+    /// `{T}`
+    Array(Box<TypeAnnotation>),
+    /// `{T, U}`
+    Tuple(Vec<TypeAnnotation>),
+    /// `{K: V}`
+    Map {
+        key: Box<TypeAnnotation>,
+        value: Box<TypeAnnotation>,
+    },
+    Function(Box<FunctionType>),
+    /// `T | U`
+    Union(Vec<TypeAnnotation>),
+}
+
+/// `function<T>(x: T): T`
+#[derive(Clone, Debug, PartialEq)]
+pub struct FunctionType {
+    pub type_params: Vec<Name>,
+    pub params: Vec<ParamType>,
+    /// `...`, with the type of the extra arguments if given.
+    pub varargs: Option<Option<TypeAnnotation>>,
+    pub returns: Vec<TypeAnnotation>,
+    pub variadic_return: bool,
+}
+
+/// A parameter of a function type, named or not.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParamType {
+    pub name: Option<Name>,
+    pub optional: bool,
+    pub ty: TypeAnnotation,
+}
+
+/// A built-in type, named by a keyword.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Primitive {
+    String,
+    Boolean,
+    Nil,
+    Number,
+    F64,
+    F32,
+    F16,
+    BF16,
+    Integer,
+    U8,
+    I8,
+    U16,
+    I16,
+    U32,
+    I32,
+    U64,
+    I64,
+}
+
+/// An expression, eg. `a.b + f(c)`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Expr {
+    pub kind: ExprKind,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ExprKind {
+    Nil,
+    Boolean(bool),
+    /// A number literal without a fraction or exponent, eg. `42` or `0xff`.
+    Integer(i64),
+    /// Any other number literal, eg. `1.5` or `1e9`.
+    Number(f64),
+    /// A string literal, with its escapes resolved.
+    String(String),
+    /// `...`
+    Varargs,
+    /// `function (...) ... end`
+    Function(Box<FunctionBody>),
+    Name(Name),
+    /// An expression in parentheses, kept as it truncates multiple values to
+    /// one.
+    Paren(Box<Expr>),
+    Table(Vec<TableField>),
+    Unary {
+        op: UnOp,
+        operand: Box<Expr>,
+    },
+    Binary {
+        op: BinOp,
+        lhs: Box<Expr>,
+        rhs: Box<Expr>,
+    },
+    /// `callee(args)`, `callee "arg"` or `callee { ... }`.
+    Call {
+        callee: Box<Expr>,
+        args: Vec<Expr>,
+    },
+    /// `receiver:method(args)`
+    MethodCall {
+        receiver: Box<Expr>,
+        method: Name,
+        args: Vec<Expr>,
+    },
+    /// `target[index]`
+    Index {
+        target: Box<Expr>,
+        index: Box<Expr>,
+    },
+    /// `target.name`
+    Field {
+        target: Box<Expr>,
+        name: Name,
+    },
+    /// `expr as T`, or `expr as (T, U)` for several values.
+    Cast {
+        expr: Box<Expr>,
+        types: Vec<TypeAnnotation>,
+    },
+    /// `expr is T`
+    Is {
+        expr: Box<Expr>,
+        ty: TypeAnnotation,
+    },
+}
+
+/// An identifier, eg. a variable or field name.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Name {
+    pub name: String,
+    pub span: Span,
+}
+
+/// A field of a table constructor.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TableField {
+    pub key: FieldKey,
+    pub value: Expr,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum FieldKey {
+    /// `{ value }`, keyed by its position.
+    Positional,
+    /// `{ name = value }` or `{ name: T = value }`.
+    Name(Name, Option<TypeAnnotation>),
+    /// `{ [key] = value }`
+    Expr(Expr),
+}
+
+/// A binary operator, by increasing precedence as in [`Self::precedence()`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum BinOp {
+    Or,
+    And,
+    Lt,
+    Gt,
+    Le,
+    Ge,
+    Ne,
+    Eq,
+    BitOr,
+    BitXor,
+    BitAnd,
+    Shl,
+    Shr,
+    Concat,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    IntDiv,
+    Mod,
+    Pow,
+}
+
+/// A unary operator, all binding tighter than binary operators but `^`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum UnOp {
+    /// `-`
+    Neg,
+    /// `not`
+    Not,
+    /// `#`
+    Len,
+    /// `~`
+    BitNot,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+
+    /// The span from the start of `self` to the end of `other`.
+    pub fn to(self, other: Span) -> Self {
+        Self {
+            start: self.start.min(other.start),
+            end: self.end.max(other.end),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+impl From<pest::Span<'_>> for Span {
+    fn from(span: pest::Span) -> Self {
+        Self::new(span.start(), span.end())
+    }
+}
+
+impl Primitive {
+    pub const ALL: [Primitive; 17] = [
+        Primitive::String,
+        Primitive::Boolean,
+        Primitive::Nil,
+        Primitive::Number,
+        Primitive::F64,
+        Primitive::F32,
+        Primitive::F16,
+        Primitive::BF16,
+        Primitive::Integer,
+        Primitive::U8,
+        Primitive::I8,
+        Primitive::U16,
+        Primitive::I16,
+        Primitive::U32,
+        Primitive::I32,
+        Primitive::U64,
+        Primitive::I64,
+    ];
+
+    /// The keyword naming the type.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Primitive::String => "string",
+            Primitive::Boolean => "boolean",
+            Primitive::Nil => "nil",
+            Primitive::Number => "number",
+            Primitive::F64 => "f64",
+            Primitive::F32 => "f32",
+            Primitive::F16 => "f16",
+            Primitive::BF16 => "bf16",
+            Primitive::Integer => "integer",
+            Primitive::U8 => "u8",
+            Primitive::I8 => "i8",
+            Primitive::U16 => "u16",
+            Primitive::I16 => "i16",
+            Primitive::U32 => "u32",
+            Primitive::I32 => "i32",
+            Primitive::U64 => "u64",
+            Primitive::I64 => "i64",
+        }
+    }
+
+    /// The type named `keyword`.
+    pub fn from_label(keyword: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|p| p.label() == keyword)
+    }
+}
+
+impl Scope {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Scope::Local => "local",
+            Scope::Global => "global",
+        }
+    }
+}
+
+impl BinOp {
+    pub const ALL: [BinOp; 21] = [
+        BinOp::Or,
+        BinOp::And,
+        BinOp::Lt,
+        BinOp::Gt,
+        BinOp::Le,
+        BinOp::Ge,
+        BinOp::Ne,
+        BinOp::Eq,
+        BinOp::BitOr,
+        BinOp::BitXor,
+        BinOp::BitAnd,
+        BinOp::Shl,
+        BinOp::Shr,
+        BinOp::Concat,
+        BinOp::Add,
+        BinOp::Sub,
+        BinOp::Mul,
+        BinOp::Div,
+        BinOp::IntDiv,
+        BinOp::Mod,
+        BinOp::Pow,
+    ];
+
+    /// The operator as written in the source.
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            BinOp::Or => "or",
+            BinOp::And => "and",
+            BinOp::Lt => "<",
+            BinOp::Gt => ">",
+            BinOp::Le => "<=",
+            BinOp::Ge => ">=",
+            BinOp::Ne => "~=",
+            BinOp::Eq => "==",
+            BinOp::BitOr => "|",
+            BinOp::BitXor => "~",
+            BinOp::BitAnd => "&",
+            BinOp::Shl => "<<",
+            BinOp::Shr => ">>",
+            BinOp::Concat => "..",
+            BinOp::Add => "+",
+            BinOp::Sub => "-",
+            BinOp::Mul => "*",
+            BinOp::Div => "/",
+            BinOp::IntDiv => "//",
+            BinOp::Mod => "%",
+            BinOp::Pow => "^",
+        }
+    }
+
+    /// How tightly the operator binds, from 1 for `or` to 12 for `^`. Unary
+    /// operators bind at 11.
+    pub fn precedence(&self) -> u8 {
+        match self {
+            BinOp::Or => 1,
+            BinOp::And => 2,
+            BinOp::Lt | BinOp::Gt | BinOp::Le | BinOp::Ge | BinOp::Ne | BinOp::Eq => 3,
+            BinOp::BitOr => 4,
+            BinOp::BitXor => 5,
+            BinOp::BitAnd => 6,
+            BinOp::Shl | BinOp::Shr => 7,
+            BinOp::Concat => 8,
+            BinOp::Add | BinOp::Sub => 9,
+            BinOp::Mul | BinOp::Div | BinOp::IntDiv | BinOp::Mod => 10,
+            BinOp::Pow => 12,
+        }
+    }
+
+    /// Returns `true` for `..` and `^`, which group from the right.
+    pub fn is_right_associative(&self) -> bool {
+        matches!(self, BinOp::Concat | BinOp::Pow)
+    }
+
+    /// Returns `true` for operators yielding a boolean.
+    pub fn is_comparison(&self) -> bool {
+        self.precedence() == 3
+    }
+}
+
+impl UnOp {
+    pub const ALL: [UnOp; 4] = [UnOp::Neg, UnOp::Not, UnOp::Len, UnOp::BitNot];
+
+    /// The operator as written in the source.
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            UnOp::Neg => "-",
+            UnOp::Not => "not",
+            UnOp::Len => "#",
+            UnOp::BitNot => "~",
+        }
+    }
+}
+
+impl fmt::Display for BinOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.symbol())
+    }
+}
+
+impl fmt::Display for UnOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.symbol())
+    }
+}
@@ -0,0 +1,2156 @@
+//! Type checking of a [syntax tree](super::ast), before code generation.
+//!
+//! [`Checker::check()`] resolves the type of every expression and declared
+//! name of a chunk into a [`TypeInfo`] code generation reads, or reports every
+//! [`TypeError`] found. As in Teal:
+//! - `nil` is a value of every type
+//! - records and enums are nominal, and table constructors are checked against
+//!   the type they are expected to have, eg. a record's
+//! - `integer` values are `number`s, but not the other way around
+//! - `x is T` narrows the variable `x` to `T` in the branch it guards
+//! - generic functions are instantiated from their arguments at each call
+//!
+//! Unlike Teal, `any` values convert to every type, as builtins such as
+//! `tostring` and `pairs` are only typed loosely yet. The `string`, `math`,
+//! and `table` libraries are typed as far as code generation supports them.
+//!
+//! ```ignore
+//! let chunk = TealParser::parse_chunk(source)?;
+//! let types = Checker::new().check(&chunk)?;
+//! ```
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+
+use super::ast::*;
+
+/// The type of a value.
+#[derive(Clone, Debug)]
+pub enum Type {
+    /// The type of expressions that failed to check. It is compatible with
+    /// every type, so that each mistake is reported once.
+    Unknown,
+    Any,
+    Primitive(Primitive),
+    /// `{T}`
+    Array(Box<Type>),
+    /// `{T, U}`
+    Tuple(Vec<Type>),
+    /// `{K: V}`
+    Map(Box<Type>, Box<Type>),
+    Function(Rc<FunctionSig>),
+    /// `T | U`, with at least two members, none of them unions.
+    Union(Vec<Type>),
+    /// A record or interface, with its type arguments if generic.
+    Record(Rc<RecordType>, Vec<Type>),
+    Enum(Rc<EnumType>),
+    /// A type parameter, eg. `T` in `function<T>(x: T): T`.
+    Param(String),
+}
+
+/// The type of a function.
+#[derive(Clone, Debug, Default)]
+pub struct FunctionSig {
+    pub type_params: Vec<String>,
+    /// The parameters' types, starting with `self` for methods.
+    pub params: Vec<Type>,
+    /// Whether each parameter may be omitted.
+    pub optional: Vec<bool>,
+    /// The type of the extra arguments, if it takes any.
+    pub varargs: Option<Type>,
+    pub returns: Vec<Type>,
+    /// Returns `true` if the last return type repeats.
+    pub variadic_return: bool,
+    /// The signatures of other numbers of arguments, eg. `table.insert(t, pos,
+    /// value)`, see [`Self::for_arity()`].
+    pub overloads: Vec<Rc<FunctionSig>>,
+}
+
+/// A declared record or interface, or the type inferred for a table
+/// constructor with named fields.
+pub struct RecordType {
+    pub name: String,
+    pub interface: bool,
+    /// Returns `true` for the type of a table constructor, which converts to
+    /// any record with compatible fields.
+    pub anonymous: bool,
+    pub type_params: Vec<String>,
+    /// Fields in declaration order, including methods and those inherited
+    /// from interfaces. Functions declared on the record later add to them.
+    pub fields: RefCell<Vec<(String, Type)>>,
+    pub metamethods: RefCell<HashMap<String, Type>>,
+    /// Types declared inside the record, eg. `Outer.Inner`.
+    pub types: RefCell<HashMap<String, Type>>,
+    pub interfaces: RefCell<Vec<Type>>,
+    /// The type of the elements of records implementing an array, eg. `{T}`.
+    pub element: RefCell<Option<Type>>,
+}
+
+/// A declared enum, a string restricted to some values.
+#[derive(Debug)]
+pub struct EnumType {
+    pub name: String,
+    pub values: Vec<String>,
+}
+
+/// Why a chunk failed to check, and where.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TypeError {
+    pub message: String,
+    pub span: Span,
+}
+
+/// The types resolved by a [`Checker`], by the span of the node they are of.
+#[derive(Clone, Debug, Default)]
+pub struct TypeInfo {
+    exprs: HashMap<Span, Type>,
+    names: HashMap<Span, Type>,
+}
+
+/// Checks chunks, see the [module documentation](self). Globals declared by a
+/// chunk are kept for the next ones, eg. for a REPL.
+pub struct Checker {
+    /// Declarations by block, the first holding the globals.
+    frames: Vec<Frame>,
+    /// The functions being checked, innermost last.
+    functions: Vec<FunctionFrame>,
+    errors: Vec<TypeError>,
+    warnings: Vec<TypeError>,
+    info: TypeInfo,
+}
+
+#[derive(Default)]
+struct Frame {
+    values: HashMap<String, Binding>,
+    types: HashMap<String, Type>,
+}
+
+#[derive(Clone)]
+struct Binding {
+    ty: Type,
+    /// Returns `true` for `<const>` and `<close>` variables.
+    constant: bool,
+    /// Where the local was declared and what it is, to warn about it if it
+    /// is never read.
+    unused: Option<(Span, &'static str)>,
+}
+
+struct FunctionFrame {
+    /// The declared return types, and whether the last one repeats, or `None`
+    /// to infer them from the `return` statements.
+    returns: Option<(Vec<Type>, bool)>,
+    inferred: Option<Vec<Type>>,
+    varargs: Option<Type>,
+    loops: usize,
+}
+
+impl Type {
+    pub const NIL: Type = Type::Primitive(Primitive::Nil);
+    pub const BOOLEAN: Type = Type::Primitive(Primitive::Boolean);
+    pub const STRING: Type = Type::Primitive(Primitive::String);
+    pub const NUMBER: Type = Type::Primitive(Primitive::Number);
+    pub const INTEGER: Type = Type::Primitive(Primitive::Integer);
+
+    /// The union of `types`, flattened and without duplicates, or the only
+    /// one of them.
+    pub fn union(types: impl IntoIterator<Item = Type>) -> Type {
+        let mut members: Vec<Type> = Vec::new();
+        for ty in types {
+            let flattened = match ty {
+                Type::Union(inner) => inner,
+                Type::Any | Type::Unknown => return ty,
+                ty => vec![ty],
+            };
+            for ty in flattened {
+                if !members.iter().any(|m| m.same(&ty)) {
+                    members.push(ty);
+                }
+            }
+        }
+        match members.len() {
+            0 => Type::NIL,
+            1 => members.pop().unwrap(),
+            _ => Type::Union(members),
+        }
+    }
+
+    /// Returns `true` if both are the same type.
+    pub fn same(&self, other: &Type) -> bool {
+        match (self, other) {
+            (Type::Unknown, Type::Unknown) | (Type::Any, Type::Any) => true,
+            (Type::Primitive(a), Type::Primitive(b)) => a == b,
+            (Type::Array(a), Type::Array(b)) => a.same(b),
+            (Type::Tuple(a), Type::Tuple(b)) | (Type::Union(a), Type::Union(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.same(b))
+            }
+            (Type::Map(ak, av), Type::Map(bk, bv)) => ak.same(bk) && av.same(bv),
+            (Type::Function(a), Type::Function(b)) => Rc::ptr_eq(a, b),
+            (Type::Record(a, aargs), Type::Record(b, bargs)) => {
+                Rc::ptr_eq(a, b)
+                    && aargs.len() == bargs.len()
+                    && aargs.iter().zip(bargs).all(|(a, b)| a.same(b))
+            }
+            (Type::Enum(a), Type::Enum(b)) => Rc::ptr_eq(a, b),
+            (Type::Param(a), Type::Param(b)) => a == b,
+            _ => false,
+        }
+    }
+
+    /// Returns `true` for `number`, `integer`, and the sized number types.
+    pub fn is_numeric(&self) -> bool {
+        matches!(self, Type::Primitive(p) if p.is_numeric())
+    }
+
+    /// Returns `true` for `integer` and the sized integer types.
+    pub fn is_integer(&self) -> bool {
+        matches!(self, Type::Primitive(p) if p.is_integer())
+    }
+
+    /// Returns `true` for types every operator accepts.
+    fn is_dynamic(&self) -> bool {
+        matches!(self, Type::Any | Type::Unknown)
+    }
+}
+
+impl Primitive {
+    /// Returns `true` for `number`, `integer`, and the sized number types.
+    pub fn is_numeric(&self) -> bool {
+        !matches!(
+            self,
+            Primitive::String | Primitive::Boolean | Primitive::Nil
+        )
+    }
+
+    /// Returns `true` for `integer` and the sized integer types.
+    pub fn is_integer(&self) -> bool {
+        self.is_numeric() && !self.is_float()
+    }
+
+    /// Returns `true` for `number` and the sized floating point types.
+    pub fn is_float(&self) -> bool {
+        matches!(
+            self,
+            Primitive::Number | Primitive::F64 | Primitive::F32 | Primitive::F16 | Primitive::BF16
+        )
+    }
+}
+
+/// Returns `true` if a value of type `from` can be used as a `to`.
+pub fn is_assignable(from: &Type, to: &Type) -> bool {
+    match (from, to) {
+        (Type::Unknown | Type::Any, _) | (_, Type::Unknown | Type::Any) => true,
+        (Type::Primitive(Primitive::Nil), _) => true,
+        (Type::Primitive(a), Type::Primitive(b)) => {
+            a == b
+                // number literals are `integer` or `number`
+                || (*a == Primitive::Integer && b.is_numeric())
+                || (*a == Primitive::Number && b.is_float())
+                || (a.is_integer() && matches!(b, Primitive::Integer | Primitive::Number))
+                || (a.is_float() && *b == Primitive::Number)
+        }
+        (Type::Union(members), _) => members.iter().all(|m| is_assignable(m, to)),
+        (_, Type::Union(members)) => members.iter().any(|m| is_assignable(from, m)),
+        (Type::Enum(_), Type::Primitive(Primitive::String)) => true,
+        (Type::Enum(a), Type::Enum(b)) => Rc::ptr_eq(a, b),
+        (Type::Array(a), Type::Array(b)) => is_assignable(a, b),
+        (Type::Tuple(a), Type::Tuple(b)) => {
+            a.len() <= b.len() && a.iter().zip(b).all(|(a, b)| is_assignable(a, b))
+        }
+        (Type::Tuple(members), Type::Array(element)) => {
+            members.iter().all(|m| is_assignable(m, element))
+        }
+        (Type::Map(ak, av), Type::Map(bk, bv)) => is_assignable(ak, bk) && is_assignable(av, bv),
+        (Type::Array(element), Type::Map(key, value)) => {
+            is_assignable(&Type::INTEGER, key) && is_assignable(element, value)
+        }
+        (Type::Function(a), Type::Function(b)) => {
+            // parameters are contravariant, returns covariant
+            a.params
+                .iter()
+                .zip(&b.params)
+                .all(|(a, b)| is_assignable(b, a))
+                && a.returns
+                    .iter()
+                    .zip(&b.returns)
+                    .all(|(a, b)| is_assignable(a, b))
+        }
+        (Type::Record(a, aargs), Type::Record(b, bargs)) => {
+            if Rc::ptr_eq(a, b) {
+                return aargs.iter().zip(bargs).all(|(a, b)| is_assignable(a, b));
+            }
+            if a.anonymous {
+                // a table constructor has no more fields than the record
+                return a.fields.borrow().iter().all(|(name, ty)| {
+                    field_type(b, bargs, name).is_some_and(|field| is_assignable(ty, &field))
+                });
+            }
+            a.interfaces
+                .borrow()
+                .iter()
+                .any(|interface| is_assignable(interface, to))
+        }
+        (Type::Record(record, _), Type::Array(element)) => record
+            .element
+            .borrow()
+            .as_ref()
+            .is_some_and(|e| is_assignable(e, element)),
+        (Type::Param(a), Type::Param(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// The type of the field `name` of a record with type arguments `args`.
+fn field_type(record: &RecordType, args: &[Type], name: &str) -> Option<Type> {
+    let fields = record.fields.borrow();
+    let (_, ty) = fields.iter().find(|(field, _)| field == name)?;
+    Some(substitute(ty, &record.bindings(args)))
+}
+
+/// `ty` with the type parameters in `bindings` replaced.
+fn substitute(ty: &Type, bindings: &HashMap<String, Type>) -> Type {
+    if bindings.is_empty() {
+        return ty.clone();
+    }
+    let all = |types: &[Type]| types.iter().map(|t| substitute(t, bindings)).collect();
+    match ty {
+        Type::Param(name) => bindings.get(name).cloned().unwrap_or_else(|| ty.clone()),
+        Type::Array(element) => Type::Array(Box::new(substitute(element, bindings))),
+        Type::Tuple(members) => Type::Tuple(all(members)),
+        Type::Union(members) => Type::union(members.iter().map(|t| substitute(t, bindings))),
+        Type::Map(key, value) => Type::Map(
+            Box::new(substitute(key, bindings)),
+            Box::new(substitute(value, bindings)),
+        ),
+        Type::Function(sig) => Type::Function(Rc::new(FunctionSig {
+            params: all(&sig.params),
+            varargs: sig.varargs.as_ref().map(|t| substitute(t, bindings)),
+            returns: all(&sig.returns),
+            ..(**sig).clone()
+        })),
+        Type::Record(record, args) => Type::Record(record.clone(), all(args)),
+        _ => ty.clone(),
+    }
+}
+
+/// Returns `true` if `ty` refers to one of the type parameters `params`.
+fn mentions(ty: &Type, params: &[String]) -> bool {
+    let any = |types: &[Type]| types.iter().any(|t| mentions(t, params));
+    match ty {
+        Type::Param(name) => params.contains(name),
+        Type::Array(element) => mentions(element, params),
+        Type::Tuple(members) | Type::Union(members) | Type::Record(_, members) => any(members),
+        Type::Map(key, value) => mentions(key, params) || mentions(value, params),
+        Type::Function(sig) => {
+            any(&sig.params)
+                || any(&sig.returns)
+                || sig.varargs.as_ref().is_some_and(|t| mentions(t, params))
+        }
+        _ => false,
+    }
+}
+
+/// Bind the type parameters `params` found in `param` to the matching parts of
+/// `arg`, the type of an argument passed for it.
+fn infer(param: &Type, arg: &Type, params: &[String], bindings: &mut HashMap<String, Type>) {
+    match (param, arg) {
+        (Type::Param(name), _) if params.contains(name) => {
+            let bound = bindings.entry(name.clone()).or_insert_with(|| arg.clone());
+            if bound.same(&Type::NIL) {
+                *bound = arg.clone();
+            }
+        }
+        (Type::Array(p), Type::Array(a)) => infer(p, a, params, bindings),
+        (Type::Map(pk, pv), Type::Map(ak, av)) => {
+            infer(pk, ak, params, bindings);
+            infer(pv, av, params, bindings);
+        }
+        (Type::Tuple(p), Type::Tuple(a)) | (Type::Record(_, p), Type::Record(_, a)) => {
+            for (p, a) in p.iter().zip(a) {
+                infer(p, a, params, bindings);
+            }
+        }
+        (Type::Function(p), Type::Function(a)) => {
+            for (p, a) in p.params.iter().zip(&a.params) {
+                infer(p, a, params, bindings);
+            }
+            for (p, a) in p.returns.iter().zip(&a.returns) {
+                infer(p, a, params, bindings);
+            }
+        }
+        _ => {}
+    }
+}
+
+impl FunctionSig {
+    /// The first of the signature and its [overloads](Self::overloads) taking
+    /// `count` arguments, the signature itself if none does.
+    pub fn for_arity(&self, count: usize) -> &FunctionSig {
+        std::iter::once(self)
+            .chain(self.overloads.iter().map(|sig| &**sig))
+            .find(|sig| {
+                let required = sig.optional.iter().rposition(|o| !o).map_or(0, |i| i + 1);
+                required <= count && (count <= sig.params.len() || sig.varargs.is_some())
+            })
+            .unwrap_or(self)
+    }
+}
+
+impl RecordType {
+    pub fn new(name: impl Into<String>, interface: bool, type_params: Vec<String>) -> Self {
+        Self {
+            name: name.into(),
+            interface,
+            anonymous: false,
+            type_params,
+            fields: RefCell::default(),
+            metamethods: RefCell::default(),
+            types: RefCell::default(),
+            interfaces: RefCell::default(),
+            element: RefCell::default(),
+        }
+    }
+
+    /// The type parameters bound to `args`.
+    fn bindings(&self, args: &[Type]) -> HashMap<String, Type> {
+        self.type_params
+            .iter()
+            .cloned()
+            .zip(args.iter().cloned())
+            .collect()
+    }
+
+    /// The type of the record's values, generic over its own parameters.
+    fn instance(self: &Rc<Self>) -> Type {
+        let args = self.type_params.iter().cloned().map(Type::Param).collect();
+        Type::Record(self.clone(), args)
+    }
+}
+
+impl TypeInfo {
+    /// The type of `expr`, that of its first value for calls.
+    pub fn expr(&self, expr: &Expr) -> Type {
+        self.exprs.get(&expr.span).cloned().unwrap_or(Type::Unknown)
+    }
+
+    /// The type of the variable, parameter, or function declared as `name`,
+    /// or of the method called as `name`.
+    pub fn name(&self, name: &Name) -> Type {
+        self.names.get(&name.span).cloned().unwrap_or(Type::Unknown)
+    }
+}
+
+impl Checker {
+    /// A checker knowing only the globals of Teal's standard library.
+    pub fn new() -> Self {
+        let mut checker = Self {
+            frames: vec![Frame::default()],
+            functions: Vec::new(),
+            errors: Vec::new(),
+            warnings: Vec::new(),
+            info: TypeInfo::default(),
+        };
+        let function = |params: Vec<Type>, varargs: Option<Type>, returns: Vec<Type>| {
+            Type::Function(Rc::new(FunctionSig {
+                optional: vec![false; params.len()],
+                params,
+                varargs,
+                returns,
+                ..FunctionSig::default()
+            }))
+        };
+        let prelude = [
+            ("print", function(vec![], Some(Type::Any), vec![])),
+            (
+                "tostring",
+                function(vec![Type::Any], None, vec![Type::STRING]),
+            ),
+            (
+                "tonumber",
+                function(vec![Type::Any], None, vec![Type::NUMBER]),
+            ),
+            ("type", function(vec![Type::Any], None, vec![Type::STRING])),
+            ("error", function(vec![Type::Any], Some(Type::Any), vec![])),
+            (
+                "assert",
+                function(vec![Type::Any], Some(Type::Any), vec![Type::Any]),
+            ),
+            ("ipairs", function(vec![Type::Any], None, vec![Type::Any])),
+            ("pairs", function(vec![Type::Any], None, vec![Type::Any])),
+        ];
+        for (name, ty) in prelude.into_iter().chain(library()) {
+            checker.declare_global(name, ty);
+        }
+        checker
+    }
+
+    /// Declare the global `name`, eg. a function provided by the host.
+    pub fn declare_global(&mut self, name: &str, ty: Type) {
+        let binding = Binding {
+            ty,
+            constant: false,
+            unused: None,
+        };
+        self.frames[0].values.insert(name.to_owned(), binding);
+    }
+
+    /// Declare the global type `name`, eg. a record provided by the host.
+    pub fn declare_global_type(&mut self, name: &str, ty: Type) {
+        self.frames[0].types.insert(name.to_owned(), ty);
+    }
+
+    /// The type of the global `name`, if declared.
+    pub fn global(&self, name: &str) -> Option<Type> {
+        self.frames[0].values.get(name).map(|b| b.ty.clone())
+    }
+
+    /// Check `chunk`, returning the types of its nodes or every error found.
+    pub fn check(&mut self, chunk: &Block) -> Result<TypeInfo, Vec<TypeError>> {
+        self.frames.truncate(1);
+        self.functions = vec![FunctionFrame {
+            returns: None,
+            inferred: None,
+            varargs: Some(Type::STRING),
+            loops: 0,
+        }];
+        self.block(chunk);
+        self.functions.clear();
+        let info = std::mem::take(&mut self.info);
+        match self.errors.is_empty() {
+            true => Ok(info),
+            false => Err(std::mem::take(&mut self.errors)),
+        }
+    }
+
+    /// The warnings found since the last call, such as unused locals, which
+    /// do not fail [`Self::check()`].
+    pub fn take_warnings(&mut self) -> Vec<TypeError> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    fn error(&mut self, span: Span, message: impl Into<String>) {
+        self.errors.push(TypeError {
+            message: message.into(),
+            span,
+        });
+    }
+
+    /// Report `got` not being a `expected`, in `context`.
+    fn expect(&mut self, got: &Type, expected: &Type, span: Span, context: &str) {
+        if !is_assignable(got, expected) {
+            self.error(span, format!("{context}: got {got}, expected {expected}"));
+        }
+    }
+
+    fn lookup(&self, name: &str) -> Option<&Binding> {
+        self.frames.iter().rev().find_map(|f| f.values.get(name))
+    }
+
+    fn lookup_type(&self, name: &str) -> Option<Type> {
+        self.frames
+            .iter()
+            .rev()
+            .find_map(|f| f.types.get(name).cloned())
+    }
+
+    /// Declare the variable `name` in `scope`.
+    fn declare(&mut self, scope: Scope, name: &Name, ty: Type, constant: bool) {
+        self.info.names.insert(name.span, ty.clone());
+        let frame = match scope {
+            Scope::Global => &mut self.frames[0],
+            Scope::Local => self.frames.last_mut().unwrap(),
+        };
+        let binding = Binding {
+            ty,
+            constant,
+            unused: None,
+        };
+        frame.values.insert(name.name.clone(), binding);
+    }
+
+    /// Warn about the local `name` of the current frame, a `kind`, if it is
+    /// never read.
+    fn track(&mut self, name: &Name, kind: &'static str) {
+        if name.name.starts_with('_') {
+            return;
+        }
+        let frame = self.frames.last_mut().unwrap();
+        if let Some(binding) = frame.values.get_mut(&name.name) {
+            binding.unused = Some((name.span, kind));
+        }
+    }
+
+    /// Mark the variable `name` as read.
+    fn read(&mut self, name: &str) -> Option<&Binding> {
+        let binding = self
+            .frames
+            .iter_mut()
+            .rev()
+            .find_map(|f| f.values.get_mut(name))?;
+        binding.unused = None;
+        Some(binding)
+    }
+
+    /// Leave the innermost frame, warning about its unused locals.
+    fn pop_frame(&mut self) {
+        let frame = self.frames.pop().unwrap();
+        let mut unused: Vec<_> = frame
+            .values
+            .into_iter()
+            .filter_map(|(name, binding)| Some((binding.unused?, name)))
+            .collect();
+        unused.sort_unstable_by_key(|((span, _), _)| span.start);
+        for ((span, kind), name) in unused {
+            self.warnings.push(TypeError {
+                message: format!("unused {kind} {name}"),
+                span,
+            });
+        }
+    }
+
+    /// Declare the type `name` in the frame at `frame`.
+    fn declare_type(&mut self, frame: usize, name: &Name, ty: Type) {
+        self.frames[frame].types.insert(name.name.clone(), ty);
+    }
+
+    fn block(&mut self, block: &Block) {
+        self.frames.push(Frame::default());
+        self.statements(block);
+        self.pop_frame();
+    }
+
+    /// Check the statements of `block` in the current frame.
+    fn statements(&mut self, block: &Block) {
+        for statement in &block.statements {
+            self.statement(statement);
+        }
+        if let Some(ret) = &block.ret {
+            self.ret(ret);
+        }
+    }
+
+    fn ret(&mut self, ret: &Return) {
+        let declared = self.functions.last().unwrap().returns.clone();
+        let expected = declared.as_ref().map_or(&[][..], |(types, _)| types);
+        let (types, _) = self.values(&ret.values, expected);
+        let Some((expected, variadic)) = declared else {
+            let frame = self.functions.last_mut().unwrap();
+            frame.inferred = Some(match frame.inferred.take() {
+                Some(inferred) if inferred.len() == types.len() => inferred
+                    .into_iter()
+                    .zip(types)
+                    .map(|(a, b)| Type::union([a, b]))
+                    .collect(),
+                Some(inferred) if inferred.len() > types.len() => inferred,
+                _ => types,
+            });
+            return;
+        };
+        if types.len() > expected.len() && !variadic {
+            self.error(
+                ret.span,
+                format!(
+                    "in return: excess return values, expected {} but got {}",
+                    expected.len(),
+                    types.len()
+                ),
+            );
+        }
+        for (i, got) in types.iter().enumerate() {
+            let Some(want) = expected
+                .get(i)
+                .or(variadic.then(|| expected.last()).flatten())
+            else {
+                break;
+            };
+            let span = ret.values.get(i).map_or(ret.span, |e| e.span);
+            self.expect(got, want, span, "in return value");
+        }
+    }
+
+    fn statement(&mut self, statement: &Statement) {
+        match &statement.kind {
+            StatementKind::Assign { targets, values } => {
+                let targets: Vec<Type> = targets.iter().map(|t| self.target(t)).collect();
+                let (types, _) = self.values(values, &targets);
+                for (i, target) in targets.iter().enumerate() {
+                    let got = types.get(i).cloned().unwrap_or(Type::NIL);
+                    let span = values.get(i).map_or(statement.span, |e| e.span);
+                    self.expect(&got, target, span, "in assignment");
+                }
+            }
+            StatementKind::Call(call) => {
+                self.expr_multi(call, None);
+            }
+            StatementKind::Label(_) | StatementKind::Goto(_) => {}
+            StatementKind::Break => {
+                if self.functions.last().unwrap().loops == 0 {
+                    self.error(statement.span, "'break' outside a loop");
+                }
+            }
+            StatementKind::Do(body) => self.block(body),
+            StatementKind::While { condition, body } => {
+                self.expr(condition, None);
+                self.in_loop(|checker| checker.block(body));
+            }
+            StatementKind::Repeat { body, condition } => {
+                // the condition sees the body's locals
+                self.frames.push(Frame::default());
+                self.in_loop(|checker| checker.statements(body));
+                self.expr(condition, None);
+                self.pop_frame();
+            }
+            StatementKind::If {
+                branches,
+                otherwise,
+            } => {
+                for branch in branches {
+                    self.expr(&branch.condition, None);
+                    self.frames.push(Frame::default());
+                    self.narrow(&branch.condition);
+                    self.statements(&branch.body);
+                    self.pop_frame();
+                }
+                if let Some(body) = otherwise {
+                    self.block(body);
+                }
+            }
+            StatementKind::NumericFor {
+                var,
+                start,
+                limit,
+                step,
+                body,
+            } => {
+                let mut integer = true;
+                for bound in [Some(start), Some(limit), step.as_ref()]
+                    .into_iter()
+                    .flatten()
+                {
+                    let ty = self.expr(bound, None);
+                    self.expect(&ty, &Type::NUMBER, bound.span, "in for loop");
+                    integer &= ty.is_integer();
+                }
+                let ty = if integer { Type::INTEGER } else { Type::NUMBER };
+                self.frames.push(Frame::default());
+                self.declare(Scope::Local, var, ty, false);
+                self.in_loop(|checker| checker.statements(body));
+                self.pop_frame();
+            }
+            StatementKind::GenericFor {
+                names,
+                values,
+                body,
+            } => {
+                let (types, _) = self.values(values, &[]);
+                let vars = self.iteration(values, &types);
+                self.frames.push(Frame::default());
+                for (i, name) in names.iter().enumerate() {
+                    let ty = vars.get(i).cloned().unwrap_or(Type::Any);
+                    self.declare(Scope::Local, name, ty, false);
+                }
+                self.in_loop(|checker| checker.statements(body));
+                self.pop_frame();
+            }
+            StatementKind::Variables {
+                scope,
+                names,
+                types,
+                values,
+            } => {
+                let declared: Vec<Type> = types.iter().map(|t| self.resolve(t)).collect();
+                let (types, _) = self.values(values, &declared);
+                let context = format!("in {} declaration", scope.label());
+                for (i, name) in names.iter().enumerate() {
+                    let span = values.get(i).map_or(name.name.span, |e| e.span);
+                    let ty = match (declared.get(i), types.get(i)) {
+                        (Some(declared), Some(value)) => {
+                            self.expect(value, declared, span, &context);
+                            declared.clone()
+                        }
+                        (Some(declared), None) => declared.clone(),
+                        (None, Some(value)) if !value.same(&Type::NIL) => value.clone(),
+                        (None, _) => Type::Any,
+                    };
+                    let constant = match &name.attrib {
+                        Some(attrib) => match attrib.name.as_str() {
+                            "const" | "close" => true,
+                            "total" => false,
+                            other => {
+                                self.error(attrib.span, format!("unknown attribute '{other}'"));
+                                false
+                            }
+                        },
+                        None => false,
+                    };
+                    self.declare(*scope, &name.name, ty, constant);
+                    if *scope == Scope::Local {
+                        self.track(&name.name, "variable");
+                    }
+                }
+            }
+            StatementKind::Function(decl) => self.function_decl(decl),
+            StatementKind::Type { scope, name, def } => {
+                let frame = match scope {
+                    Scope::Global => 0,
+                    Scope::Local => self.frames.len() - 1,
+                };
+                let ty = self.type_def(name, def, frame, None);
+                // records and enums are also values, eg. to call `Point.new()`
+                if matches!(def, TypeDef::Record(_) | TypeDef::Enum(_)) {
+                    self.declare(*scope, name, ty, true);
+                }
+            }
+        }
+    }
+
+    /// Run `check` on the body of a loop.
+    fn in_loop(&mut self, check: impl FnOnce(&mut Self)) {
+        self.functions.last_mut().unwrap().loops += 1;
+        check(self);
+        self.functions.last_mut().unwrap().loops -= 1;
+    }
+
+    /// Declare the variable `x` in `x is T` as a `T`, for the branch it
+    /// guards.
+    fn narrow(&mut self, condition: &Expr) {
+        let ExprKind::Is { expr, ty } = &condition.kind else {
+            return;
+        };
+        let ExprKind::Name(name) = &expr.kind else {
+            return;
+        };
+        let Some(binding) = self.lookup(&name.name).cloned() else {
+            return;
+        };
+        let ty = self.resolve(ty);
+        let frame = self.frames.last_mut().unwrap();
+        frame.values.insert(
+            name.name.clone(),
+            Binding {
+                ty,
+                constant: binding.constant,
+                unused: None,
+            },
+        );
+    }
+
+    /// The types of the variables of a `for ... in` loop over `values`.
+    fn iteration(&mut self, values: &[Expr], types: &[Type]) -> Vec<Type> {
+        if let Some(ExprKind::Call { callee, args }) = values.first().map(|e| &e.kind) {
+            let iterator = match &callee.kind {
+                ExprKind::Name(name) if name.name == "ipairs" || name.name == "pairs" => {
+                    Some(name.name.as_str())
+                }
+                _ => None,
+            };
+            if let (Some(iterator), Some(arg)) = (iterator, args.first()) {
+                let element = |ty: &Type| match ty {
+                    Type::Array(element) => Some((**element).clone()),
+                    Type::Tuple(members) => Some(Type::union(members.iter().cloned())),
+                    Type::Record(record, _) => record.element.borrow().clone(),
+                    _ => None,
+                };
+                let ty = self.info.expr(arg);
+                return match (iterator, &ty) {
+                    ("pairs", Type::Map(key, value)) => vec![(**key).clone(), (**value).clone()],
+                    ("pairs", Type::Record(_, _)) if element(&ty).is_none() => {
+                        vec![Type::STRING, Type::Any]
+                    }
+                    _ => vec![Type::INTEGER, element(&ty).unwrap_or(Type::Any)],
+                };
+            }
+        }
+        match types.first() {
+            Some(Type::Function(iterator)) => iterator.returns.clone(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// The type of the assignment target `target`.
+    fn target(&mut self, target: &Expr) -> Type {
+        let ExprKind::Name(name) = &target.kind else {
+            return self.expr(target, None);
+        };
+        let ty = match self.lookup(&name.name).cloned() {
+            Some(binding) if binding.constant => {
+                self.error(
+                    target.span,
+                    format!("cannot assign to <const> variable '{}'", name.name),
+                );
+                binding.ty
+            }
+            Some(binding) => binding.ty,
+            None => {
+                self.error(target.span, format!("unknown variable: {}", name.name));
+                Type::Unknown
+            }
+        };
+        self.info.exprs.insert(target.span, ty.clone());
+        ty
+    }
+
+    fn function_decl(&mut self, decl: &FunctionDecl) {
+        let key = decl
+            .method
+            .as_ref()
+            .unwrap_or_else(|| decl.path.last().unwrap());
+        if let Some(scope) = decl.scope {
+            self.function(&decl.body, None, None, Some((scope, key)));
+            return;
+        }
+        if decl.path.len() == 1 && decl.method.is_none() {
+            let Some(binding) = self.lookup(&key.name).cloned() else {
+                self.error(
+                    key.span,
+                    format!(
+                        "functions need an explicit 'local' or 'global': {}",
+                        key.name
+                    ),
+                );
+                self.function(&decl.body, None, None, None);
+                return;
+            };
+            let sig = self.function(&decl.body, None, None, None);
+            self.info
+                .names
+                .insert(key.span, Type::Function(sig.clone()));
+            self.expect(
+                &Type::Function(sig),
+                &binding.ty,
+                key.span,
+                "in function declaration",
+            );
+            return;
+        }
+
+        // `function a.b.c()` or `function a.b:c()`, declaring a field of `a.b`
+        let owner_path = match decl.method {
+            Some(_) => &decl.path[..],
+            None => &decl.path[..decl.path.len() - 1],
+        };
+        let mut owner = match self.read(&owner_path[0].name) {
+            Some(binding) => binding.ty.clone(),
+            None => {
+                let name = &owner_path[0];
+                self.error(name.span, format!("unknown variable: {}", name.name));
+                Type::Unknown
+            }
+        };
+        for name in &owner_path[1..] {
+            owner = self.member(&owner, name);
+        }
+        let receiver = decl.method.as_ref().map(|_| owner.clone());
+        let declared = match &owner {
+            Type::Record(record, args) => field_type(record, args, &key.name),
+            _ => None,
+        };
+        // methods declared by an interface take the implementing record
+        let declared = match (declared, &receiver) {
+            (Some(Type::Function(sig)), Some(receiver)) if !sig.params.is_empty() => {
+                let mut params = sig.params.clone();
+                params[0] = receiver.clone();
+                Some(Type::Function(Rc::new(FunctionSig {
+                    params,
+                    ..(*sig).clone()
+                })))
+            }
+            (declared, _) => declared,
+        };
+        let hint = match &declared {
+            Some(Type::Function(sig)) => Some(sig.clone()),
+            _ => None,
+        };
+        let sig = self.function(&decl.body, receiver, hint.as_deref(), None);
+        self.info
+            .names
+            .insert(key.span, Type::Function(sig.clone()));
+        match (&owner, declared) {
+            (_, Some(declared)) => self.expect(
+                &Type::Function(sig),
+                &declared,
+                key.span,
+                "in function declaration",
+            ),
+            (Type::Record(record, _), None) => {
+                let field = (key.name.clone(), Type::Function(sig));
+                record.fields.borrow_mut().push(field);
+            }
+            (Type::Any | Type::Unknown | Type::Map(..), None) => {}
+            (other, None) => self.error(
+                key.span,
+                format!("cannot declare a function on a value of type {other}"),
+            ),
+        }
+    }
+
+    /// Check the function `body`, returning its type. Its parameters without
+    /// types take those of `hint`, eg. the function type a callback is
+    /// expected to have, and the function is declared as `name` before its
+    /// body is checked so that it can call itself.
+    fn function(
+        &mut self,
+        body: &FunctionBody,
+        receiver: Option<Type>,
+        hint: Option<&FunctionSig>,
+        name: Option<(Scope, &Name)>,
+    ) -> Rc<FunctionSig> {
+        let mut frame = Frame::default();
+        for param in &body.type_params {
+            let ty = Type::Param(param.name.clone());
+            frame.types.insert(param.name.clone(), ty);
+        }
+        self.frames.push(frame);
+
+        let type_params = body.type_params.iter().map(|p| p.name.clone()).collect();
+        let mut params = Vec::new();
+        let mut optional = Vec::new();
+        if let Some(receiver) = receiver {
+            let name = Name {
+                name: "self".to_owned(),
+                span: body.span,
+            };
+            self.declare(Scope::Local, &name, receiver.clone(), false);
+            params.push(receiver);
+            optional.push(false);
+        }
+        for param in &body.params {
+            let ty = match &param.ty {
+                Some(ty) => self.resolve(ty),
+                None => hint
+                    .and_then(|hint| hint.params.get(params.len()).cloned())
+                    .unwrap_or(Type::Any),
+            };
+            self.declare(Scope::Local, &param.name, ty.clone(), false);
+            params.push(ty);
+            optional.push(param.optional);
+        }
+        let varargs = body
+            .varargs
+            .as_ref()
+            .map(|ty| ty.as_ref().map_or(Type::Any, |ty| self.resolve(ty)));
+        let declared = match body.returns.is_empty() {
+            true => None,
+            false => Some(body.returns.iter().map(|t| self.resolve(t)).collect()),
+        };
+
+        let mut sig = FunctionSig {
+            type_params,
+            params,
+            optional,
+            varargs: varargs.clone(),
+            returns: declared.clone().unwrap_or_default(),
+            variadic_return: body.variadic_return,
+            overloads: Vec::new(),
+        };
+        if let Some((scope, name)) = name {
+            // outside the function's own frame
+            let ty = Type::Function(Rc::new(sig.clone()));
+            let frame = self.frames.pop().unwrap();
+            self.declare(scope, name, ty, false);
+            self.frames.push(frame);
+        }
+        self.functions.push(FunctionFrame {
+            returns: declared.map(|types| (types, body.variadic_return)),
+            inferred: None,
+            varargs,
+            loops: 0,
+        });
+        self.statements(&body.block);
+        let frame = self.functions.pop().unwrap();
+        self.pop_frame();
+
+        if let Some(inferred) = frame.inferred {
+            sig.returns = inferred;
+        }
+        let sig = Rc::new(sig);
+        if let Some((scope, name)) = name {
+            self.declare(scope, name, Type::Function(sig.clone()), false);
+            if scope == Scope::Local {
+                self.track(name, "function");
+            }
+        }
+        sig
+    }
+
+    /// Define the type `name` as `def`, declaring it in the frame at `frame`,
+    /// and in `owner` for types nested in a record.
+    fn type_def(
+        &mut self,
+        name: &Name,
+        def: &TypeDef,
+        frame: usize,
+        owner: Option<&Rc<RecordType>>,
+    ) -> Type {
+        let declare = |checker: &mut Self, ty: &Type| {
+            checker.declare_type(frame, name, ty.clone());
+            if let Some(owner) = owner {
+                owner
+                    .types
+                    .borrow_mut()
+                    .insert(name.name.clone(), ty.clone());
+            }
+        };
+        let ty = match def {
+            TypeDef::Record(record) => {
+                let type_params = record.type_params.iter().map(|p| p.name.clone());
+                let shell = Rc::new(RecordType::new(
+                    &name.name,
+                    record.interface,
+                    type_params.collect(),
+                ));
+                // declared before its fields, which may refer to it
+                declare(self, &shell.instance());
+                self.record(&shell, record);
+                return shell.instance();
+            }
+            TypeDef::Enum(enumeration) => Type::Enum(Rc::new(EnumType {
+                name: name.name.clone(),
+                values: enumeration.values.iter().map(|v| v.name.clone()).collect(),
+            })),
+            TypeDef::Alias(ty) => self.resolve(ty),
+            // resolved by the module loader
+            TypeDef::Require { .. } => Type::Any,
+        };
+        declare(self, &ty);
+        ty
+    }
+
+    /// Resolve the interfaces, fields, and nested types of `def` into `record`.
+    fn record(&mut self, record: &Rc<RecordType>, def: &Record) {
+        let mut frame = Frame::default();
+        for param in &record.type_params {
+            frame
+                .types
+                .insert(param.clone(), Type::Param(param.clone()));
+        }
+        // `function(self)` fields take the record
+        frame.types.insert("self".to_owned(), record.instance());
+        self.frames.push(frame);
+        let frame = self.frames.len() - 1;
+
+        for interface in &def.interfaces {
+            let ty = self.resolve(interface);
+            match &ty {
+                Type::Record(base, args) if base.interface => {
+                    let bindings = base.bindings(args);
+                    let inherited = base.fields.borrow().clone();
+                    let mut fields = record.fields.borrow_mut();
+                    for (name, field) in inherited {
+                        fields.push((name, substitute(&field, &bindings)));
+                    }
+                }
+                Type::Array(element) => *record.element.borrow_mut() = Some((**element).clone()),
+                Type::Unknown => {}
+                other => self.error(interface.span, format!("{other} is not an interface")),
+            }
+            record.interfaces.borrow_mut().push(ty);
+        }
+        for entry in &def.entries {
+            match entry {
+                RecordEntry::Userdata(_) => {}
+                RecordEntry::Type { name, def, .. } => {
+                    self.type_def(name, def, frame, Some(record));
+                }
+                RecordEntry::Field(field) => {
+                    let ty = self.resolve(&field.ty);
+                    if field.metamethod {
+                        let mut metamethods = record.metamethods.borrow_mut();
+                        metamethods.insert(field.name.name.clone(), ty);
+                        continue;
+                    }
+                    let duplicate = record
+                        .fields
+                        .borrow()
+                        .iter()
+                        .any(|(name, _)| *name == field.name.name);
+                    if duplicate {
+                        self.error(
+                            field.name.span,
+                            format!("duplicate field '{}'", field.name.name),
+                        );
+                    }
+                    record
+                        .fields
+                        .borrow_mut()
+                        .push((field.name.name.clone(), ty));
+                }
+            }
+        }
+        if let Some(condition) = &def.where_clause {
+            let name = Name {
+                name: "self".to_owned(),
+                span: condition.span,
+            };
+            self.declare(Scope::Local, &name, record.instance(), false);
+            self.expr(condition, None);
+        }
+        self.pop_frame();
+    }
+
+    /// The type written as `ty`.
+    fn resolve(&mut self, ty: &TypeAnnotation) -> Type {
+        match &ty.kind {
+            TypeKind::Any => Type::Any,
+            TypeKind::Primitive(primitive) => Type::Primitive(*primitive),
+            TypeKind::Nominal { path, args } => {
+                let args: Vec<Type> = args.iter().map(|arg| self.resolve(arg)).collect();
+                let full_name = || {
+                    let names: Vec<&str> = path.iter().map(|n| n.name.as_str()).collect();
+                    names.join(".")
+                };
+                let mut found = self.lookup_type(&path[0].name);
+                for name in &path[1..] {
+                    found = match &found {
+                        Some(Type::Record(record, _)) => {
+                            record.types.borrow().get(&name.name).cloned()
+                        }
+                        _ => None,
+                    };
+                }
+                let Some(found) = found else {
+                    self.error(ty.span, format!("unknown type {}", full_name()));
+                    return Type::Unknown;
+                };
+                if args.is_empty() {
+                    return found;
+                }
+                match found {
+                    Type::Record(record, _) if record.type_params.len() == args.len() => {
+                        Type::Record(record, args)
+                    }
+                    _ => {
+                        self.error(
+                            ty.span,
+                            format!("wrong number of type arguments for {}", full_name()),
+                        );
+                        Type::Unknown
+                    }
+                }
+            }
+            TypeKind::Array(element) => Type::Array(Box::new(self.resolve(element))),
+            TypeKind::Tuple(members) => {
+                Type::Tuple(members.iter().map(|m| self.resolve(m)).collect())
+            }
+            TypeKind::Map { key, value } => {
+                Type::Map(Box::new(self.resolve(key)), Box::new(self.resolve(value)))
+            }
+            TypeKind::Union(members) => {
+                Type::union(members.iter().map(|m| self.resolve(m)).collect::<Vec<_>>())
+            }
+            TypeKind::Function(function) => {
+                let mut frame = Frame::default();
+                for param in &function.type_params {
+                    let ty = Type::Param(param.name.clone());
+                    frame.types.insert(param.name.clone(), ty);
+                }
+                self.frames.push(frame);
+                let sig = FunctionSig {
+                    type_params: function
+                        .type_params
+                        .iter()
+                        .map(|p| p.name.clone())
+                        .collect(),
+                    params: function
+                        .params
+                        .iter()
+                        .map(|p| self.resolve(&p.ty))
+                        .collect(),
+                    optional: function.params.iter().map(|p| p.optional).collect(),
+                    varargs: function
+                        .varargs
+                        .as_ref()
+                        .map(|ty| ty.as_ref().map_or(Type::Any, |ty| self.resolve(ty))),
+                    returns: function.returns.iter().map(|t| self.resolve(t)).collect(),
+                    variadic_return: function.variadic_return,
+                    overloads: Vec::new(),
+                };
+                self.pop_frame();
+                Type::Function(Rc::new(sig))
+            }
+        }
+    }
+
+    /// The types of `exprs`, expanding the values of the last one, and whether
+    /// it has an unknown number of them. Each is checked as `expected`'s at
+    /// its position.
+    fn values(&mut self, exprs: &[Expr], expected: &[Type]) -> (Vec<Type>, bool) {
+        let mut types = Vec::with_capacity(exprs.len());
+        let mut open = false;
+        for (i, expr) in exprs.iter().enumerate() {
+            if i + 1 < exprs.len() {
+                types.push(self.expr(expr, expected.get(i)));
+                continue;
+            }
+            let (last, last_open) = self.expr_multi(expr, expected.get(i));
+            types.extend(last);
+            open = last_open;
+        }
+        (types, open)
+    }
+
+    /// The type of the first value of `expr`, checked as a `expected`.
+    fn expr(&mut self, expr: &Expr, expected: Option<&Type>) -> Type {
+        let (types, _) = self.expr_multi(expr, expected);
+        types.into_iter().next().unwrap_or(Type::NIL)
+    }
+
+    /// The types of the values of `expr`, and whether it has an unknown number
+    /// of them.
+    fn expr_multi(&mut self, expr: &Expr, expected: Option<&Type>) -> (Vec<Type>, bool) {
+        let (types, open) = match &expr.kind {
+            ExprKind::Call { callee, args } => {
+                let callee = self.expr(callee, None);
+                self.call(callee, args, None, expr.span)
+            }
+            ExprKind::MethodCall {
+                receiver,
+                method,
+                args,
+            } => {
+                let receiver = self.expr(receiver, None);
+                let ty = self.member(&receiver, method);
+                self.info.names.insert(method.span, ty.clone());
+                self.call(ty, args, Some(receiver), expr.span)
+            }
+            ExprKind::Varargs => match self.functions.last().unwrap().varargs.clone() {
+                Some(ty) => (vec![ty], true),
+                None => {
+                    self.error(expr.span, "cannot use '...' outside a vararg function");
+                    (vec![Type::Unknown], true)
+                }
+            },
+            ExprKind::Cast { expr: inner, types } => {
+                self.expr_multi(inner, None);
+                (types.iter().map(|t| self.resolve(t)).collect(), false)
+            }
+            _ => (vec![self.single(expr, expected)], false),
+        };
+        let first = types.first().cloned().unwrap_or(Type::NIL);
+        self.info.exprs.insert(expr.span, first);
+        (types, open)
+    }
+
+    /// The type of an expression with a single value.
+    fn single(&mut self, expr: &Expr, expected: Option<&Type>) -> Type {
+        match &expr.kind {
+            ExprKind::Nil => Type::NIL,
+            ExprKind::Boolean(_) => Type::BOOLEAN,
+            ExprKind::Integer(_) => Type::INTEGER,
+            ExprKind::Number(_) => Type::NUMBER,
+            ExprKind::String(value) => match expected.and_then(|e| enum_with(e, value)) {
+                Some(enumeration) => enumeration,
+                None => Type::STRING,
+            },
+            ExprKind::Function(body) => {
+                let hint = match expected {
+                    Some(Type::Function(sig)) => Some(sig.clone()),
+                    _ => None,
+                };
+                Type::Function(self.function(body, None, hint.as_deref(), None))
+            }
+            ExprKind::Name(name) => match self.read(&name.name) {
+                Some(binding) => binding.ty.clone(),
+                None => {
+                    self.error(expr.span, format!("unknown variable: {}", name.name));
+                    Type::Unknown
+                }
+            },
+            ExprKind::Paren(inner) => self.expr(inner, expected),
+            ExprKind::Table(fields) => self.table(fields, expected),
+            ExprKind::Unary { op, operand } => {
+                let ty = self.expr(operand, None);
+                self.unary(*op, ty, expr.span)
+            }
+            ExprKind::Binary { op, lhs, rhs } => {
+                let lhs = self.expr(lhs, None);
+                let rhs = self.expr(rhs, None);
+                self.binary(*op, lhs, rhs, expr.span)
+            }
+            ExprKind::Index { target, index } => {
+                let target = self.expr(target, None);
+                self.index(target, index, expr.span)
+            }
+            ExprKind::Field { target, name } => {
+                let target = self.expr(target, None);
+                self.member(&target, name)
+            }
+            ExprKind::Is { expr, ty } => {
+                self.expr(expr, None);
+                self.resolve(ty);
+                Type::BOOLEAN
+            }
+            ExprKind::Call { .. }
+            | ExprKind::MethodCall { .. }
+            | ExprKind::Varargs
+            | ExprKind::Cast { .. } => self.expr(expr, expected),
+        }
+    }
+
+    /// The values returned by calling a `callee`, with `receiver` as `self`
+    /// for method calls.
+    fn call(
+        &mut self,
+        callee: Type,
+        args: &[Expr],
+        receiver: Option<Type>,
+        span: Span,
+    ) -> (Vec<Type>, bool) {
+        let (sig, receiver) = match &callee {
+            Type::Function(sig) => (sig.clone(), receiver),
+            Type::Record(record, _) if record.metamethods.borrow().contains_key("__call") => {
+                match record.metamethods.borrow()["__call"].clone() {
+                    Type::Function(sig) => (sig, Some(callee.clone())),
+                    _ => (Rc::new(FunctionSig::default()), None),
+                }
+            }
+            Type::Any | Type::Unknown => {
+                self.values(args, &[]);
+                return (vec![callee], true);
+            }
+            other => {
+                self.values(args, &[]);
+                self.error(span, format!("cannot call a value of type {other}"));
+                return (vec![Type::Unknown], true);
+            }
+        };
+
+        let offset = usize::from(receiver.is_some());
+        let sig = match sig.overloads.is_empty() {
+            true => sig,
+            false => Rc::new(sig.for_arity(args.len() + offset).clone()),
+        };
+        let params = sig.params.get(offset..).unwrap_or_default();
+        let mut bindings = HashMap::new();
+        if let (Some(receiver), Some(param)) = (&receiver, sig.params.first()) {
+            infer(param, receiver, &sig.type_params, &mut bindings);
+        }
+        let mut types = Vec::with_capacity(args.len());
+        let mut open = false;
+        for (i, arg) in args.iter().enumerate() {
+            // arguments are expected to have their parameter's type once the
+            // type parameters in it are bound by the previous arguments
+            let expected = params
+                .get(i)
+                .map(|param| substitute(param, &bindings))
+                .filter(|param| !mentions(param, &sig.type_params));
+            let (values, values_open) = match i + 1 < args.len() {
+                true => (vec![self.expr(arg, expected.as_ref())], false),
+                false => self.expr_multi(arg, expected.as_ref()),
+            };
+            for (param, value) in params.iter().skip(i).zip(&values) {
+                infer(param, value, &sig.type_params, &mut bindings);
+            }
+            types.extend(values);
+            open = values_open;
+        }
+        for param in &sig.type_params {
+            bindings.entry(param.clone()).or_insert(Type::Any);
+        }
+
+        let optional = sig.optional.get(offset..).unwrap_or_default();
+        let required = optional.iter().rposition(|o| !o).map_or(0, |i| i + 1);
+        let too_few = types.len() < required && !open;
+        let too_many = args.len() > params.len() && sig.varargs.is_none();
+        if too_few || too_many {
+            self.error(
+                span,
+                format!(
+                    "wrong number of arguments (given {}, expects {})",
+                    args.len(),
+                    params.len()
+                ),
+            );
+        }
+        for (i, arg) in types.iter().enumerate() {
+            let Some(param) = params.get(i).or(sig.varargs.as_ref()) else {
+                break;
+            };
+            let param = substitute(param, &bindings);
+            let span = args.get(i).map_or(span, |a| a.span);
+            self.expect(arg, &param, span, &format!("argument {}", i + 1));
+        }
+        let returns = sig.returns.iter().map(|r| substitute(r, &bindings));
+        (returns.collect(), sig.variadic_return)
+    }
+
+    /// The type of the field `name` of a `target`.
+    fn member(&mut self, target: &Type, name: &Name) -> Type {
+        match target {
+            Type::Any | Type::Unknown => target.clone(),
+            Type::Record(record, args) => {
+                if let Some(ty) = field_type(record, args, &name.name) {
+                    return ty;
+                }
+                // nested types are fields of the record's table
+                if let Some(ty) = record.types.borrow().get(&name.name) {
+                    return ty.clone();
+                }
+                self.error(
+                    name.span,
+                    format!("invalid key '{}' in record '{}'", name.name, record.name),
+                );
+                Type::Unknown
+            }
+            Type::Map(key, value) if is_assignable(&Type::STRING, key) => (**value).clone(),
+            // the string library, through the string metatable
+            Type::Primitive(Primitive::String) | Type::Enum(_) => match self.global("string") {
+                Some(Type::Record(record, args)) => {
+                    field_type(&record, &args, &name.name).unwrap_or(Type::Any)
+                }
+                _ => Type::Any,
+            },
+            Type::Union(_) => {
+                self.error(
+                    name.span,
+                    format!("cannot index a value of union type {target}, narrow it with 'is'"),
+                );
+                Type::Unknown
+            }
+            other => {
+                self.error(name.span, format!("cannot index a value of type {other}"));
+                Type::Unknown
+            }
+        }
+    }
+
+    /// The type of `target[index]`.
+    fn index(&mut self, target: Type, index: &Expr, span: Span) -> Type {
+        let key = self.expr(index, None);
+        match &target {
+            Type::Any | Type::Unknown => target,
+            Type::Array(element) => {
+                self.expect(&key, &Type::INTEGER, index.span, "in array index");
+                (**element).clone()
+            }
+            Type::Tuple(members) => match index.kind {
+                ExprKind::Integer(i) if i >= 1 && i as usize <= members.len() => {
+                    members[i as usize - 1].clone()
+                }
+                _ => {
+                    self.expect(&key, &Type::INTEGER, index.span, "in tuple index");
+                    Type::union(members.iter().cloned())
+                }
+            },
+            Type::Map(key_type, value) => {
+                self.expect(&key, key_type, index.span, "in map key");
+                (**value).clone()
+            }
+            Type::Record(record, args) => {
+                if let ExprKind::String(name) = &index.kind {
+                    let name = Name {
+                        name: name.clone(),
+                        span: index.span,
+                    };
+                    return self.member(&target, &name);
+                }
+                let element = record.element.borrow().clone();
+                match element {
+                    Some(element) => {
+                        self.expect(&key, &Type::INTEGER, index.span, "in array index");
+                        substitute(&element, &record.bindings(args))
+                    }
+                    None => {
+                        self.error(
+                            index.span,
+                            format!("cannot index record '{}' with {key}", record.name),
+                        );
+                        Type::Unknown
+                    }
+                }
+            }
+            other => {
+                self.error(span, format!("cannot index a value of type {other}"));
+                Type::Unknown
+            }
+        }
+    }
+
+    /// The type of a table constructor with `fields`.
+    fn table(&mut self, fields: &[TableField], expected: Option<&Type>) -> Type {
+        let expected = expected.and_then(table_member);
+        match &expected {
+            Some(Type::Record(record, args)) => {
+                for field in fields {
+                    let ty = match &field.key {
+                        FieldKey::Name(name, _) => match field_type(record, args, &name.name) {
+                            Some(ty) => Some(ty),
+                            None => {
+                                self.error(
+                                    name.span,
+                                    format!(
+                                        "unknown field '{}' in record '{}'",
+                                        name.name, record.name
+                                    ),
+                                );
+                                None
+                            }
+                        },
+                        FieldKey::Positional => record.element.borrow().clone(),
+                        FieldKey::Expr(key) => {
+                            self.expr(key, None);
+                            None
+                        }
+                    };
+                    let got = self.expr(&field.value, ty.as_ref());
+                    if let Some(ty) = ty {
+                        self.expect(&got, &ty, field.value.span, "in record field");
+                    }
+                }
+                return expected.unwrap();
+            }
+            Some(Type::Array(element)) => {
+                for field in fields {
+                    if !matches!(field.key, FieldKey::Positional) {
+                        self.error(field.span, "in array: unexpected keyed field");
+                    }
+                    let got = self.expr(&field.value, Some(element));
+                    self.expect(&got, element, field.value.span, "in array element");
+                }
+                return expected.unwrap();
+            }
+            Some(Type::Map(key, value)) => {
+                for field in fields {
+                    let (got, span) = match &field.key {
+                        FieldKey::Positional => (Type::INTEGER, field.span),
+                        FieldKey::Name(name, _) => (Type::STRING, name.span),
+                        FieldKey::Expr(expr) => (self.expr(expr, Some(key)), expr.span),
+                    };
+                    self.expect(&got, key, span, "in map key");
+                    let got = self.expr(&field.value, Some(value));
+                    self.expect(&got, value, field.value.span, "in map value");
+                }
+                return expected.unwrap();
+            }
+            Some(Type::Tuple(members)) => {
+                for (i, field) in fields.iter().enumerate() {
+                    let member = members.get(i);
+                    let got = self.expr(&field.value, member);
+                    match member {
+                        Some(member) => {
+                            self.expect(&got, member, field.value.span, "in tuple element")
+                        }
+                        None => self.error(field.span, "in tuple: too many elements"),
+                    }
+                }
+                return expected.unwrap();
+            }
+            _ => {}
+        }
+
+        // inferred from the fields
+        if fields.is_empty() {
+            return Type::Map(Box::new(Type::Any), Box::new(Type::Any));
+        }
+        if fields.iter().all(|f| matches!(f.key, FieldKey::Positional)) {
+            let types: Vec<Type> = fields.iter().map(|f| self.expr(&f.value, None)).collect();
+            let first = &types[0];
+            return match types.iter().all(|t| t.same(first)) {
+                true => Type::Array(Box::new(first.clone())),
+                false => Type::Tuple(types),
+            };
+        }
+        if fields.iter().all(|f| matches!(f.key, FieldKey::Name(..))) {
+            let mut record = RecordType::new("{...}", false, Vec::new());
+            record.anonymous = true;
+            for field in fields {
+                let FieldKey::Name(name, annotation) = &field.key else {
+                    continue;
+                };
+                let declared = annotation.as_ref().map(|t| self.resolve(t));
+                let got = self.expr(&field.value, declared.as_ref());
+                let ty = match declared {
+                    Some(declared) => {
+                        self.expect(&got, &declared, field.value.span, "in table field");
+                        declared
+                    }
+                    None => got,
+                };
+                record.fields.get_mut().push((name.name.clone(), ty));
+            }
+            return Type::Record(Rc::new(record), Vec::new());
+        }
+        let mut keys = Vec::new();
+        let mut values = Vec::new();
+        for field in fields {
+            keys.push(match &field.key {
+                FieldKey::Positional => Type::INTEGER,
+                FieldKey::Name(..) => Type::STRING,
+                FieldKey::Expr(key) => self.expr(key, None),
+            });
+            values.push(self.expr(&field.value, None));
+        }
+        Type::Map(Box::new(Type::union(keys)), Box::new(Type::union(values)))
+    }
+
+    fn unary(&mut self, op: UnOp, ty: Type, span: Span) -> Type {
+        if ty.is_dynamic() {
+            return match op {
+                UnOp::Not => Type::BOOLEAN,
+                _ => ty,
+            };
+        }
+        let valid = match op {
+            UnOp::Not => return Type::BOOLEAN,
+            UnOp::Neg => ty.is_numeric(),
+            UnOp::BitNot => ty.is_integer(),
+            UnOp::Len => match &ty {
+                Type::Primitive(Primitive::String)
+                | Type::Enum(_)
+                | Type::Array(_)
+                | Type::Tuple(_)
+                | Type::Map(..) => return Type::INTEGER,
+                Type::Record(record, _) => record.element.borrow().is_some(),
+                _ => false,
+            },
+        };
+        if valid {
+            return match op {
+                UnOp::Len => Type::INTEGER,
+                _ => ty,
+            };
+        }
+        let metamethod = match op {
+            UnOp::Neg => "__unm",
+            UnOp::Len => "__len",
+            UnOp::BitNot => "__bnot",
+            UnOp::Not => unreachable!(),
+        };
+        if let Some(result) = metamethod_result(&ty, &ty, metamethod) {
+            return result;
+        }
+        self.error(span, format!("cannot use operator '{op}' on {ty}"));
+        Type::Unknown
+    }
+
+    fn binary(&mut self, op: BinOp, lhs: Type, rhs: Type, span: Span) -> Type {
+        match op {
+            BinOp::And => return rhs,
+            BinOp::Or => {
+                return match (is_assignable(&lhs, &rhs), is_assignable(&rhs, &lhs)) {
+                    (true, _) => rhs,
+                    (_, true) => lhs,
+                    _ => Type::union([lhs, rhs]),
+                };
+            }
+            BinOp::Eq | BinOp::Ne => return Type::BOOLEAN,
+            _ => {}
+        }
+        let dynamic = lhs.is_dynamic() || rhs.is_dynamic();
+        let both = |test: fn(&Type) -> bool| test(&lhs) && test(&rhs);
+        let result = match op {
+            BinOp::Lt | BinOp::Gt | BinOp::Le | BinOp::Ge => {
+                let string = |t: &Type| matches!(t, Type::Primitive(Primitive::String));
+                (dynamic || both(Type::is_numeric) || both(string)).then_some(Type::BOOLEAN)
+            }
+            _ if dynamic => Some(Type::Any),
+            BinOp::Add | BinOp::Sub | BinOp::Mul | BinOp::Mod => {
+                both(Type::is_numeric).then(|| match both(Type::is_integer) {
+                    true if lhs.same(&rhs) => lhs.clone(),
+                    true => Type::INTEGER,
+                    false => Type::NUMBER,
+                })
+            }
+            BinOp::Div | BinOp::Pow => both(Type::is_numeric).then_some(Type::NUMBER),
+            BinOp::IntDiv => both(Type::is_numeric).then(|| match both(Type::is_integer) {
+                true => Type::INTEGER,
+                false => Type::NUMBER,
+            }),
+            BinOp::Concat => {
+                let text = |t: &Type| {
+                    t.is_numeric()
+                        || matches!(t, Type::Primitive(Primitive::String) | Type::Enum(_))
+                };
+                both(text).then_some(Type::STRING)
+            }
+            BinOp::BitOr | BinOp::BitXor | BinOp::BitAnd | BinOp::Shl | BinOp::Shr => {
+                both(Type::is_integer).then_some(Type::INTEGER)
+            }
+            BinOp::And | BinOp::Or | BinOp::Eq | BinOp::Ne => unreachable!(),
+        };
+        if let Some(result) = result {
+            return result;
+        }
+        if let Some(result) = metamethod_result(&lhs, &rhs, metamethod(op)) {
+            return result;
+        }
+        self.error(
+            span,
+            format!("cannot use operator '{op}' on {lhs} and {rhs}"),
+        );
+        Type::Unknown
+    }
+}
+
+/// The `string`, `math`, and `table` libraries, as far as they are typed.
+fn library() -> [(&'static str, Type); 3] {
+    let generic =
+        |params: Vec<Type>, optional: usize, varargs: Option<Type>, returns: Vec<Type>| {
+            let mentioned = params
+                .iter()
+                .chain(&returns)
+                .any(|ty| mentions(ty, &["T".to_owned()]));
+            let required = params.len() - optional;
+            Type::Function(Rc::new(FunctionSig {
+                type_params: if mentioned {
+                    vec!["T".to_owned()]
+                } else {
+                    Vec::new()
+                },
+                optional: (0..params.len()).map(|i| i >= required).collect(),
+                params,
+                varargs,
+                returns,
+                ..FunctionSig::default()
+            }))
+        };
+    let function = |params: Vec<Type>, returns: Vec<Type>| generic(params, 0, None, returns);
+    let record = |name: &str, fields: Vec<(&str, Type)>| {
+        let record = RecordType::new(name, false, Vec::new());
+        let fields = fields.into_iter().map(|(name, ty)| (name.to_owned(), ty));
+        record.fields.borrow_mut().extend(fields);
+        Type::Record(Rc::new(record), Vec::new())
+    };
+    let overloaded = |sig: Type, overloads: Vec<Type>| match sig {
+        Type::Function(sig) => {
+            let overloads = overloads.into_iter().filter_map(|ty| match ty {
+                Type::Function(overload) => Some(overload),
+                _ => None,
+            });
+            Type::Function(Rc::new(FunctionSig {
+                overloads: overloads.collect(),
+                ..(*sig).clone()
+            }))
+        }
+        other => other,
+    };
+    let (string, number, integer) = (Type::STRING, Type::NUMBER, Type::INTEGER);
+    let param = Type::Param("T".to_owned());
+    let array = Type::Array(Box::new(param.clone()));
+    let real = || function(vec![Type::NUMBER], vec![Type::NUMBER]);
+    [
+        (
+            "string",
+            record(
+                "string",
+                vec![
+                    ("len", function(vec![string.clone()], vec![integer.clone()])),
+                    (
+                        "sub",
+                        generic(
+                            vec![string.clone(), integer.clone(), integer.clone()],
+                            1,
+                            None,
+                            vec![string.clone()],
+                        ),
+                    ),
+                    (
+                        "upper",
+                        function(vec![string.clone()], vec![string.clone()]),
+                    ),
+                    (
+                        "lower",
+                        function(vec![string.clone()], vec![string.clone()]),
+                    ),
+                    (
+                        "rep",
+                        function(vec![string.clone(), integer.clone()], vec![string.clone()]),
+                    ),
+                    (
+                        "reverse",
+                        function(vec![string.clone()], vec![string.clone()]),
+                    ),
+                    (
+                        "format",
+                        generic(
+                            vec![string.clone()],
+                            0,
+                            Some(Type::Any),
+                            vec![string.clone()],
+                        ),
+                    ),
+                    (
+                        "find",
+                        generic(
+                            vec![
+                                string.clone(),
+                                string.clone(),
+                                integer.clone(),
+                                Type::BOOLEAN,
+                            ],
+                            2,
+                            None,
+                            vec![integer.clone(), integer.clone()],
+                        ),
+                    ),
+                    (
+                        "byte",
+                        generic(
+                            vec![string.clone(), integer.clone()],
+                            1,
+                            None,
+                            vec![integer.clone()],
+                        ),
+                    ),
+                    (
+                        "char",
+                        generic(Vec::new(), 0, Some(integer.clone()), vec![string.clone()]),
+                    ),
+                ],
+            ),
+        ),
+        (
+            "math",
+            record(
+                "math",
+                vec![
+                    (
+                        "floor",
+                        function(vec![number.clone()], vec![integer.clone()]),
+                    ),
+                    (
+                        "ceil",
+                        function(vec![number.clone()], vec![integer.clone()]),
+                    ),
+                    ("abs", function(vec![param.clone()], vec![param.clone()])),
+                    (
+                        "min",
+                        generic(
+                            vec![param.clone()],
+                            0,
+                            Some(param.clone()),
+                            vec![param.clone()],
+                        ),
+                    ),
+                    (
+                        "max",
+                        generic(
+                            vec![param.clone()],
+                            0,
+                            Some(param.clone()),
+                            vec![param.clone()],
+                        ),
+                    ),
+                    ("sqrt", real()),
+                    ("exp", real()),
+                    ("log", real()),
+                    ("sin", real()),
+                    ("cos", real()),
+                    ("tan", real()),
+                    ("asin", real()),
+                    ("acos", real()),
+                    ("atan", real()),
+                    (
+                        "random",
+                        overloaded(
+                            function(Vec::new(), vec![number.clone()]),
+                            vec![
+                                function(vec![integer.clone()], vec![integer.clone()]),
+                                function(
+                                    vec![integer.clone(), integer.clone()],
+                                    vec![integer.clone()],
+                                ),
+                            ],
+                        ),
+                    ),
+                    ("randomseed", function(vec![integer.clone()], Vec::new())),
+                    ("pi", number.clone()),
+                    ("huge", number),
+                    ("maxinteger", integer.clone()),
+                    ("mininteger", integer.clone()),
+                ],
+            ),
+        ),
+        (
+            "table",
+            record(
+                "table",
+                vec![
+                    (
+                        "insert",
+                        overloaded(
+                            function(vec![array.clone(), param.clone()], vec![]),
+                            vec![function(
+                                vec![array.clone(), integer.clone(), param.clone()],
+                                vec![],
+                            )],
+                        ),
+                    ),
+                    ("sort", function(vec![array.clone()], vec![])),
+                    (
+                        "remove",
+                        generic(vec![array, integer], 1, None, vec![param]),
+                    ),
+                    (
+                        "concat",
+                        generic(
+                            vec![Type::Array(Box::new(string.clone())), string.clone()],
+                            1,
+                            None,
+                            vec![string],
+                        ),
+                    ),
+                ],
+            ),
+        ),
+    ]
+}
+
+/// The member of `expected` a table constructor can be.
+fn table_member(expected: &Type) -> Option<Type> {
+    match expected {
+        Type::Record(..) | Type::Array(_) | Type::Map(..) | Type::Tuple(_) => {
+            Some(expected.clone())
+        }
+        Type::Union(members) => members.iter().find_map(table_member),
+        _ => None,
+    }
+}
+
+/// The enum in `expected` having `value`, for a string literal.
+fn enum_with(expected: &Type, value: &str) -> Option<Type> {
+    match expected {
+        Type::Enum(enumeration) if enumeration.values.iter().any(|v| v == value) => {
+            Some(expected.clone())
+        }
+        Type::Union(members) => members.iter().find_map(|m| enum_with(m, value)),
+        _ => None,
+    }
+}
+
+/// The metamethod implementing `op` on records.
+fn metamethod(op: BinOp) -> &'static str {
+    match op {
+        BinOp::Add => "__add",
+        BinOp::Sub => "__sub",
+        BinOp::Mul => "__mul",
+        BinOp::Div => "__div",
+        BinOp::IntDiv => "__idiv",
+        BinOp::Mod => "__mod",
+        BinOp::Pow => "__pow",
+        BinOp::Concat => "__concat",
+        BinOp::BitAnd => "__band",
+        BinOp::BitOr => "__bor",
+        BinOp::BitXor => "__bxor",
+        BinOp::Shl => "__shl",
+        BinOp::Shr => "__shr",
+        BinOp::Lt | BinOp::Gt => "__lt",
+        BinOp::Le | BinOp::Ge => "__le",
+        BinOp::Eq | BinOp::Ne => "__eq",
+        BinOp::And | BinOp::Or => "",
+    }
+}
+
+/// The result of the metamethod `name` of a record operand.
+fn metamethod_result(lhs: &Type, rhs: &Type, name: &str) -> Option<Type> {
+    [lhs, rhs].into_iter().find_map(|operand| match operand {
+        Type::Record(record, _) => match record.metamethods.borrow().get(name) {
+            Some(Type::Function(sig)) => Some(sig.returns.first().cloned().unwrap_or(Type::NIL)),
+            _ => None,
+        },
+        _ => None,
+    })
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let list = |f: &mut fmt::Formatter<'_>, types: &[Type], separator: &str| {
+            for (i, ty) in types.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(separator)?;
+                }
+                write!(f, "{ty}")?;
+            }
+            Ok(())
+        };
+        match self {
+            Type::Unknown => f.write_str("<unknown>"),
+            Type::Any => f.write_str("any"),
+            Type::Primitive(primitive) => f.write_str(primitive.label()),
+            Type::Array(element) => write!(f, "{{{element}}}"),
+            Type::Tuple(members) => {
+                f.write_str("{")?;
+                list(f, members, ", ")?;
+                f.write_str("}")
+            }
+            Type::Map(key, value) => write!(f, "{{{key}: {value}}}"),
+            Type::Function(sig) => write!(f, "{sig}"),
+            Type::Union(members) => list(f, members, " | "),
+            Type::Record(record, args) => {
+                f.write_str(&record.name)?;
+                if !args.is_empty() {
+                    f.write_str("<")?;
+                    list(f, args, ", ")?;
+                    f.write_str(">")?;
+                }
+                Ok(())
+            }
+            Type::Enum(enumeration) => f.write_str(&enumeration.name),
+            Type::Param(name) => f.write_str(name),
+        }
+    }
+}
+
+impl fmt::Display for FunctionSig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("function")?;
+        if !self.type_params.is_empty() {
+            write!(f, "<{}>", self.type_params.join(", "))?;
+        }
+        f.write_str("(")?;
+        for (i, param) in self.params.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{param}")?;
+        }
+        if let Some(varargs) = &self.varargs {
+            if !self.params.is_empty() {
+                f.write_str(", ")?;
+            }
+            write!(f, "{varargs}...")?;
+        }
+        f.write_str(")")?;
+        if !self.returns.is_empty() {
+            f.write_str(": ")?;
+            for (i, ty) in self.returns.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(", ")?;
+                }
+                write!(f, "{ty}")?;
+            }
+            if self.variadic_return {
+                f.write_str("...")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for RecordType {
+    // records may refer to themselves, so only their name is shown
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RecordType")
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
+
+impl fmt::Display for TypeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at {}..{}",
+            self.message, self.span.start, self.span.end
+        )
+    }
+}
+
+impl std::error::Error for TypeError {}
+
+impl std::default::Default for Checker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,787 @@
+//! Formatting of Teal source: [`format()`] prints a chunk back in a canonical
+//! layout, so that formatting its output again changes nothing.
+//!
+//! - blocks are indented by 3 spaces, with a statement per line, keeping
+//!   single blank lines between statements
+//! - operators, commas, and `=` are spaced, and calls parenthesized, eg.
+//!   `print "x"` becomes `print("x")`
+//! - literals are kept as written, eg. `0xff` or `[[text]]`
+//! - records and enums have an entry per line, as have tables which span
+//!   several lines in the source or have a function as a field
+//! - comments are kept: those on their own line before the statement or field
+//!   they precede, and those after code at the end of its line. Comments
+//!   within an expression move to the end of its line.
+//!
+//! ```ignore
+//! let formatted = format::format("local x=f'a'..b -- c")?;
+//! assert_eq!(formatted, "local x = f('a') .. b -- c\n");
+//! ```
+
+use super::ast::*;
+use super::parser::{ParseError, TealParser};
+
+/// The indentation of each nested block.
+const INDENT: &str = "   ";
+
+/// `source` in the canonical layout, see the module documentation.
+pub fn format(source: &str) -> Result<String, ParseError> {
+    let chunk = TealParser::parse_chunk(source)?;
+    Ok(format_chunk(&chunk, source))
+}
+
+/// `chunk`, parsed from `source`, in the canonical layout with the comments of
+/// `source`.
+pub fn format_chunk(chunk: &Block, source: &str) -> String {
+    let mut printer = Printer {
+        source,
+        comments: comments(source),
+        next_comment: 0,
+        out: String::new(),
+        depth: 0,
+        last: 0,
+        opened: true,
+    };
+    printer.statements(chunk);
+    printer.comments_before(source.len());
+    if !printer.out.is_empty() {
+        printer.out.push('\n');
+    }
+    printer.out
+}
+
+struct Printer<'a> {
+    source: &'a str,
+    /// The spans of the comments of the source, in order.
+    comments: Vec<Span>,
+    /// The first comment not printed yet.
+    next_comment: usize,
+    out: String,
+    /// How many blocks the current line is nested in.
+    depth: usize,
+    /// The end in the source of what was printed last.
+    last: usize,
+    /// Returns `true` right after the line opening a block, where blank lines
+    /// are dropped.
+    opened: bool,
+}
+
+impl Printer<'_> {
+    /// Start a line for what starts at `start` in the source, after the
+    /// comments before it.
+    fn line(&mut self, start: usize) {
+        self.comments_before(start);
+        self.newline(start);
+    }
+
+    /// Start a line, after a blank one if there is one in the source since
+    /// what was printed last.
+    fn newline(&mut self, start: usize) {
+        if !self.out.is_empty() {
+            let gap = self.source.get(self.last..start).unwrap_or("");
+            if !self.opened && gap.matches('\n').count() > 1 {
+                self.out.push('\n');
+            }
+            self.out.push('\n');
+        }
+        self.opened = false;
+        for _ in 0..self.depth {
+            self.out.push_str(INDENT);
+        }
+    }
+
+    /// Print the comments before `end` in the source not printed yet: at the
+    /// end of the current line if they follow code in the source, on their own
+    /// lines otherwise.
+    fn comments_before(&mut self, end: usize) {
+        while let Some(&comment) = self.comments.get(self.next_comment)
+            && comment.start < end
+        {
+            self.next_comment += 1;
+            let line_start = self.source[..comment.start]
+                .rfind('\n')
+                .map_or(0, |i| i + 1);
+            let follows_code = !self.source[line_start..comment.start].trim().is_empty();
+            let current_line = &self.out[self.out.rfind('\n').map_or(0, |i| i + 1)..];
+            if follows_code && !current_line.trim().is_empty() {
+                self.out.push(' ');
+            } else {
+                self.newline(comment.start);
+            }
+            self.out.push_str(&self.source[comment.start..comment.end]);
+            self.last = comment.end;
+        }
+    }
+
+    /// Returns `true` if a comment not printed yet starts before `end`.
+    fn has_comment_before(&self, end: usize) -> bool {
+        self.comments
+            .get(self.next_comment)
+            .is_some_and(|comment| comment.start < end)
+    }
+
+    /// The start of `keyword`, the last token before `end` in the source.
+    fn keyword_before(&self, end: usize, keyword: &str) -> usize {
+        let mut end = end;
+        loop {
+            end = self.source[..end].trim_end().len();
+            match self.comments.iter().find(|comment| comment.end == end) {
+                Some(comment) => end = comment.start,
+                None => return end - keyword.len(),
+            }
+        }
+    }
+
+    /// Print `block` one level deeper, its comments being those before `end`
+    /// in the source, where the keyword closing it is.
+    fn block(&mut self, block: &Block, end: usize) {
+        self.depth += 1;
+        self.opened = true;
+        self.statements(block);
+        self.comments_before(end);
+        self.depth -= 1;
+    }
+
+    /// Print `keyword` closing a block, on its own line.
+    fn close(&mut self, keyword: &str) {
+        self.out.push('\n');
+        for _ in 0..self.depth {
+            self.out.push_str(INDENT);
+        }
+        self.out.push_str(keyword);
+        self.opened = false;
+    }
+
+    fn statements(&mut self, block: &Block) {
+        for statement in &block.statements {
+            self.line(statement.span.start);
+            let start = self.out.len();
+            self.statement(statement);
+            // not to be read as a call of the previous statement
+            if self.out[start..].starts_with('(') {
+                self.out.insert(start, ';');
+            }
+            self.last = statement.span.end;
+        }
+        if let Some(ret) = &block.ret {
+            self.line(ret.span.start);
+            self.out.push_str("return");
+            if !ret.values.is_empty() {
+                self.out.push(' ');
+                self.exprs(&ret.values);
+            }
+            self.last = ret.span.end;
+        }
+    }
+
+    fn statement(&mut self, statement: &Statement) {
+        let end = statement.span.end.saturating_sub("end".len());
+        match &statement.kind {
+            StatementKind::Assign { targets, values } => {
+                self.exprs(targets);
+                self.out.push_str(" = ");
+                self.exprs(values);
+            }
+            StatementKind::Call(call) => self.expr(call),
+            StatementKind::Label(name) => {
+                self.out.push_str(&format!("::{}::", name.name));
+            }
+            StatementKind::Break => self.out.push_str("break"),
+            StatementKind::Goto(name) => {
+                self.out.push_str("goto ");
+                self.out.push_str(&name.name);
+            }
+            StatementKind::Do(body) => {
+                self.out.push_str("do");
+                self.block(body, end);
+                self.close("end");
+            }
+            StatementKind::While { condition, body } => {
+                self.out.push_str("while ");
+                self.expr(condition);
+                self.out.push_str(" do");
+                self.block(body, end);
+                self.close("end");
+            }
+            StatementKind::Repeat { body, condition } => {
+                self.out.push_str("repeat");
+                self.block(body, self.keyword_before(condition.span.start, "until"));
+                self.close("until");
+                self.out.push(' ');
+                self.expr(condition);
+            }
+            StatementKind::If {
+                branches,
+                otherwise,
+            } => {
+                let else_start = otherwise
+                    .as_ref()
+                    .map(|block| self.keyword_before(block.span.start, "else"));
+                for (i, branch) in branches.iter().enumerate() {
+                    match i {
+                        0 => self.out.push_str("if "),
+                        _ => {
+                            self.close("elseif");
+                            self.out.push(' ');
+                        }
+                    }
+                    self.expr(&branch.condition);
+                    self.out.push_str(" then");
+                    let branch_end = match branches.get(i + 1) {
+                        Some(next) => next.span.start,
+                        None => else_start.unwrap_or(end),
+                    };
+                    self.block(&branch.body, branch_end);
+                }
+                if let Some(otherwise) = otherwise {
+                    self.close("else");
+                    self.block(otherwise, end);
+                }
+                self.close("end");
+            }
+            StatementKind::NumericFor {
+                var,
+                start,
+                limit,
+                step,
+                body,
+            } => {
+                self.out.push_str(&format!("for {} = ", var.name));
+                self.expr(start);
+                self.out.push_str(", ");
+                self.expr(limit);
+                if let Some(step) = step {
+                    self.out.push_str(", ");
+                    self.expr(step);
+                }
+                self.out.push_str(" do");
+                self.block(body, end);
+                self.close("end");
+            }
+            StatementKind::GenericFor {
+                names,
+                values,
+                body,
+            } => {
+                self.out.push_str("for ");
+                self.names(names, ", ");
+                self.out.push_str(" in ");
+                self.exprs(values);
+                self.out.push_str(" do");
+                self.block(body, end);
+                self.close("end");
+            }
+            StatementKind::Variables {
+                scope,
+                names,
+                types,
+                values,
+            } => {
+                self.out.push_str(scope.label());
+                for (i, name) in names.iter().enumerate() {
+                    self.out.push_str(if i == 0 { " " } else { ", " });
+                    self.out.push_str(&name.name.name);
+                    if let Some(attrib) = &name.attrib {
+                        self.out.push_str(&format!(" <{}>", attrib.name));
+                    }
+                }
+                if !types.is_empty() {
+                    self.out.push_str(": ");
+                    self.types(types, true);
+                }
+                if !values.is_empty() {
+                    self.out.push_str(" = ");
+                    self.exprs(values);
+                }
+            }
+            StatementKind::Function(function) => {
+                if let Some(scope) = function.scope {
+                    self.out.push_str(scope.label());
+                    self.out.push(' ');
+                }
+                self.out.push_str("function ");
+                self.names(&function.path, ".");
+                if let Some(method) = &function.method {
+                    self.out.push(':');
+                    self.out.push_str(&method.name);
+                }
+                self.function_body(&function.body);
+            }
+            StatementKind::Type { scope, name, def } => {
+                self.out.push_str(scope.label());
+                self.out.push(' ');
+                self.type_def(name, def);
+            }
+        }
+    }
+
+    /// Print the declaration of `name` as `def`, without its scope.
+    fn type_def(&mut self, name: &Name, def: &TypeDef) {
+        match def {
+            TypeDef::Record(record) => self.record(record),
+            TypeDef::Enum(enumeration) => {
+                self.out.push_str("enum ");
+                self.out.push_str(&name.name);
+                self.depth += 1;
+                self.opened = true;
+                for value in &enumeration.values {
+                    self.line(value.span.start);
+                    self.out
+                        .push_str(&self.source[value.span.start..value.span.end]);
+                    self.last = value.span.end;
+                }
+                self.comments_before(enumeration.span.end - "end".len());
+                self.depth -= 1;
+                self.close("end");
+            }
+            TypeDef::Alias(ty) => {
+                self.out.push_str(&format!("type {} = ", name.name));
+                self.ty(ty, true);
+            }
+            TypeDef::Require { path, span, .. } => {
+                self.out.push_str(&format!("type {} = require(", name.name));
+                let text = &self.source[span.start..span.end];
+                let literal = text.find(['"', '\'', '[']).unwrap();
+                let literal = &text[literal..string_end(text, literal)];
+                self.out.push_str(literal);
+                self.out.push(')');
+                for name in path {
+                    self.out.push('.');
+                    self.out.push_str(&name.name);
+                }
+            }
+        }
+    }
+
+    fn record(&mut self, record: &Record) {
+        self.out.push_str(if record.interface {
+            "interface "
+        } else {
+            "record "
+        });
+        self.out.push_str(&record.name.name);
+        self.type_params(&record.type_params);
+        if !record.interfaces.is_empty() {
+            self.out.push_str(" is ");
+            self.types(&record.interfaces, true);
+        }
+        if let Some(condition) = &record.where_clause {
+            self.out.push_str(" where ");
+            self.expr(condition);
+        }
+        self.depth += 1;
+        self.opened = true;
+        for entry in &record.entries {
+            let span = match entry {
+                RecordEntry::Userdata(span) => *span,
+                RecordEntry::Field(field) => field.span,
+                RecordEntry::Type { span, .. } => *span,
+            };
+            self.line(span.start);
+            match entry {
+                RecordEntry::Userdata(_) => self.out.push_str("userdata"),
+                RecordEntry::Field(field) => {
+                    if field.metamethod {
+                        self.out.push_str("metamethod ");
+                    }
+                    let key = &self.source[field.name.span.start..field.name.span.end];
+                    match key.starts_with(['"', '\'', '[']) {
+                        true => self.out.push_str(&format!("[{key}]")),
+                        false => self.out.push_str(key),
+                    }
+                    self.out.push_str(": ");
+                    self.ty(&field.ty, true);
+                }
+                RecordEntry::Type { name, def, .. } => self.type_def(name, def),
+            }
+            self.last = span.end;
+        }
+        self.comments_before(record.span.end - "end".len());
+        self.depth -= 1;
+        self.close("end");
+    }
+
+    /// Print the parameters, return types and block of `body`.
+    fn function_body(&mut self, body: &FunctionBody) {
+        self.type_params(&body.type_params);
+        self.out.push('(');
+        for (i, param) in body.params.iter().enumerate() {
+            if i > 0 {
+                self.out.push_str(", ");
+            }
+            self.out.push_str(&param.name.name);
+            if param.optional {
+                self.out.push('?');
+            }
+            if let Some(ty) = &param.ty {
+                self.out.push_str(": ");
+                self.ty(ty, false);
+            }
+        }
+        if let Some(varargs) = &body.varargs {
+            if !body.params.is_empty() {
+                self.out.push_str(", ");
+            }
+            self.varargs(varargs);
+        }
+        self.out.push(')');
+        self.returns(&body.returns, body.variadic_return, true);
+        let end = body.span.end - "end".len();
+        if body.block.statements.is_empty()
+            && body.block.ret.is_none()
+            && !self.has_comment_before(end)
+        {
+            self.out.push_str(" end");
+            return;
+        }
+        self.block(&body.block, end);
+        self.close("end");
+    }
+
+    /// `...`, and its type if given.
+    fn varargs(&mut self, ty: &Option<TypeAnnotation>) {
+        self.out.push_str("...");
+        if let Some(ty) = ty {
+            self.out.push_str(": ");
+            self.ty(ty, true);
+        }
+    }
+
+    /// `: returns`, parenthesized unless `last`, as nothing follows.
+    fn returns(&mut self, returns: &[TypeAnnotation], variadic: bool, last: bool) {
+        if returns.is_empty() {
+            return;
+        }
+        self.out.push_str(": ");
+        if !last {
+            self.out.push('(');
+        }
+        self.types(returns, true);
+        if variadic {
+            self.out.push_str("...");
+        }
+        if !last {
+            self.out.push(')');
+        }
+    }
+
+    fn type_params(&mut self, params: &[Name]) {
+        if !params.is_empty() {
+            self.out.push('<');
+            self.names(params, ", ");
+            self.out.push('>');
+        }
+    }
+
+    fn names(&mut self, names: &[Name], separator: &str) {
+        for (i, name) in names.iter().enumerate() {
+            if i > 0 {
+                self.out.push_str(separator);
+            }
+            self.out.push_str(&name.name);
+        }
+    }
+
+    /// Print `types` separated by commas, the last one as by [`Self::ty()`].
+    fn types(&mut self, types: &[TypeAnnotation], last: bool) {
+        for (i, ty) in types.iter().enumerate() {
+            if i > 0 {
+                self.out.push_str(", ");
+            }
+            self.ty(ty, last && i + 1 == types.len());
+        }
+    }
+
+    /// Print `ty`, the return types of function types parenthesized unless
+    /// `last`, as nothing follows that they would take in.
+    fn ty(&mut self, ty: &TypeAnnotation, last: bool) {
+        match &ty.kind {
+            TypeKind::Any => self.out.push_str("any"),
+            TypeKind::Primitive(primitive) => self.out.push_str(primitive.label()),
+            TypeKind::Nominal { path, args } => {
+                self.names(path, ".");
+                if !args.is_empty() {
+                    self.out.push('<');
+                    self.types(args, true);
+                    self.out.push('>');
+                }
+            }
+            TypeKind::Array(element) => {
+                self.out.push('{');
+                self.ty(element, true);
+                self.out.push('}');
+            }
+            TypeKind::Tuple(elements) => {
+                self.out.push('{');
+                self.types(elements, true);
+                self.out.push('}');
+            }
+            TypeKind::Map { key, value } => {
+                self.out.push('{');
+                self.ty(key, false);
+                self.out.push_str(": ");
+                self.ty(value, true);
+                self.out.push('}');
+            }
+            TypeKind::Function(function) => {
+                self.out.push_str("function");
+                self.type_params(&function.type_params);
+                self.out.push('(');
+                for (i, param) in function.params.iter().enumerate() {
+                    if i > 0 {
+                        self.out.push_str(", ");
+                    }
+                    match &param.name {
+                        Some(name) => {
+                            self.out.push_str(&name.name);
+                            if param.optional {
+                                self.out.push('?');
+                            }
+                            self.out.push_str(": ");
+                        }
+                        None if param.optional => self.out.push('?'),
+                        None => {}
+                    }
+                    self.ty(&param.ty, false);
+                }
+                if let Some(varargs) = &function.varargs {
+                    if !function.params.is_empty() {
+                        self.out.push_str(", ");
+                    }
+                    self.varargs(varargs);
+                }
+                self.out.push(')');
+                self.returns(&function.returns, function.variadic_return, last);
+            }
+            TypeKind::Union(members) => {
+                for (i, member) in members.iter().enumerate() {
+                    if i > 0 {
+                        self.out.push_str(" | ");
+                    }
+                    let nested = matches!(member.kind, TypeKind::Union(_));
+                    if nested {
+                        self.out.push('(');
+                    }
+                    self.ty(member, last && i + 1 == members.len());
+                    if nested {
+                        self.out.push(')');
+                    }
+                }
+            }
+        }
+    }
+
+    fn exprs(&mut self, exprs: &[Expr]) {
+        for (i, expr) in exprs.iter().enumerate() {
+            if i > 0 {
+                self.out.push_str(", ");
+            }
+            self.expr(expr);
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        match &expr.kind {
+            ExprKind::Nil => self.out.push_str("nil"),
+            ExprKind::Boolean(value) => self.out.push_str(if *value { "true" } else { "false" }),
+            // as written, eg. `0xff` or `[[text]]`
+            ExprKind::Integer(_) | ExprKind::Number(_) | ExprKind::String(_) => {
+                self.out
+                    .push_str(&self.source[expr.span.start..expr.span.end]);
+            }
+            ExprKind::Varargs => self.out.push_str("..."),
+            ExprKind::Function(body) => {
+                self.out.push_str("function");
+                self.function_body(body);
+            }
+            ExprKind::Name(name) => self.out.push_str(&name.name),
+            ExprKind::Paren(inner) => {
+                self.out.push('(');
+                self.expr(inner);
+                self.out.push(')');
+            }
+            ExprKind::Table(fields) => self.table(fields, expr.span),
+            ExprKind::Unary { op, operand } => {
+                self.out.push_str(op.symbol());
+                if *op == UnOp::Not {
+                    self.out.push(' ');
+                }
+                let start = self.out.len();
+                self.expr(operand);
+                // not to be read as a comment
+                if *op == UnOp::Neg && self.out[start..].starts_with('-') {
+                    self.out.insert(start, ' ');
+                }
+            }
+            ExprKind::Binary { op, lhs, rhs } => {
+                self.expr(lhs);
+                self.out.push_str(&format!(" {op} "));
+                self.expr(rhs);
+            }
+            ExprKind::Call { callee, args } => {
+                self.expr(callee);
+                self.args(args);
+            }
+            ExprKind::MethodCall {
+                receiver,
+                method,
+                args,
+            } => {
+                self.expr(receiver);
+                self.out.push(':');
+                self.out.push_str(&method.name);
+                self.args(args);
+            }
+            ExprKind::Index { target, index } => {
+                self.expr(target);
+                self.out.push('[');
+                self.expr(index);
+                self.out.push(']');
+            }
+            ExprKind::Field { target, name } => {
+                self.expr(target);
+                self.out.push('.');
+                self.out.push_str(&name.name);
+            }
+            ExprKind::Cast { expr, types } => {
+                self.expr(expr);
+                self.out.push_str(" as ");
+                match types.as_slice() {
+                    [ty] => self.ty(ty, false),
+                    _ => {
+                        self.out.push('(');
+                        self.types(types, true);
+                        self.out.push(')');
+                    }
+                }
+            }
+            ExprKind::Is { expr, ty } => {
+                self.expr(expr);
+                self.out.push_str(" is ");
+                self.ty(ty, false);
+            }
+        }
+    }
+
+    fn args(&mut self, args: &[Expr]) {
+        self.out.push('(');
+        self.exprs(args);
+        self.out.push(')');
+    }
+
+    /// Print a table constructor on one line, or with a field per line if it
+    /// spans several in the source or has a function as a field.
+    fn table(&mut self, fields: &[TableField], span: Span) {
+        if fields.is_empty() {
+            self.out.push_str("{}");
+            return;
+        }
+        let multiline = self.source[span.start..span.end].contains('\n')
+            || fields
+                .iter()
+                .any(|field| matches!(field.value.kind, ExprKind::Function(_)));
+        if !multiline {
+            // `{1, 2}` but `{ x = 1 }`
+            let spaced = fields
+                .iter()
+                .any(|field| !matches!(field.key, FieldKey::Positional));
+            self.out.push_str(if spaced { "{ " } else { "{" });
+            for (i, field) in fields.iter().enumerate() {
+                if i > 0 {
+                    self.out.push_str(", ");
+                }
+                self.field(field);
+            }
+            self.out.push_str(if spaced { " }" } else { "}" });
+            return;
+        }
+        self.out.push('{');
+        self.depth += 1;
+        self.opened = true;
+        for field in fields {
+            self.line(field.span.start);
+            self.field(field);
+            self.out.push(',');
+            self.last = field.span.end;
+        }
+        self.comments_before(span.end - "}".len());
+        self.depth -= 1;
+        self.close("}");
+    }
+
+    fn field(&mut self, field: &TableField) {
+        match &field.key {
+            FieldKey::Positional => {}
+            FieldKey::Name(name, ty) => {
+                self.out.push_str(&name.name);
+                if let Some(ty) = ty {
+                    self.out.push_str(": ");
+                    self.ty(ty, false);
+                }
+                self.out.push_str(" = ");
+            }
+            FieldKey::Expr(key) => {
+                self.out.push('[');
+                self.expr(key);
+                self.out.push_str("] = ");
+            }
+        }
+        self.expr(&field.value);
+    }
+}
+
+/// The spans of the comments of `source`, in order.
+fn comments(source: &str) -> Vec<Span> {
+    let bytes = source.as_bytes();
+    let mut comments = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'"' | b'\'' | b'[' => i = string_end(source, i).max(i + 1),
+            b'-' if bytes.get(i + 1) == Some(&b'-') => {
+                let end = match long_bracket_end(source, i + 2) {
+                    Some(end) => end,
+                    None => source[i..].find('\n').map_or(source.len(), |n| i + n),
+                };
+                comments.push(Span::new(i, i + source[i..end].trim_end().len()));
+                i = end;
+            }
+            _ => i += 1,
+        }
+    }
+    comments
+}
+
+/// The end of the string literal at `start` in `source`, quoted or long, or
+/// `start` if there is none.
+fn string_end(source: &str, start: usize) -> usize {
+    let bytes = source.as_bytes();
+    match bytes[start] {
+        quote @ (b'"' | b'\'') => {
+            let mut i = start + 1;
+            while i < bytes.len() {
+                match bytes[i] {
+                    b'\\' => i += 2,
+                    b if b == quote => return i + 1,
+                    _ => i += 1,
+                }
+            }
+            bytes.len()
+        }
+        _ => long_bracket_end(source, start).unwrap_or(start),
+    }
+}
+
+/// The end of the long bracket at `start` in `source`, eg. `[==[...]==]`, if
+/// there is one.
+fn long_bracket_end(source: &str, start: usize) -> Option<usize> {
+    let rest = source.get(start..)?.strip_prefix('[')?;
+    let level = rest.len() - rest.trim_start_matches('=').len();
+    let content = rest[level..].strip_prefix('[')?;
+    let close = format!("]{}]", "=".repeat(level));
+    let content_start = source.len() - content.len();
+    Some(
+        content
+            .find(&close)
+            .map_or(source.len(), |i| content_start + i + close.len()),
+    )
+}
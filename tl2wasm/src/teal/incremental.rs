@@ -0,0 +1,213 @@
+//! Re-parsing of edited source, for editors checking it as it is typed.
+//!
+//! [`TealParser::reparse_chunk()`] parses again only the top-level statements
+//! an [`Edit`] touches, and their neighbours in case the edit joins them, eg.
+//! by opening a parenthesis. The other statements are kept as they were, with
+//! their spans moved by the change in length. When the statements around the
+//! edit do not parse on their own, eg. as the edit opened a string or a block,
+//! the whole source is parsed again, so that the result and errors are always
+//! those of [`TealParser::parse_chunk()`].
+//!
+//! ```ignore
+//! let mut chunk = TealParser::parse_chunk(&source)?;
+//! source.replace_range(10..12, "total");
+//! let edit = Edit::new(Span::new(10, 12), "total".len());
+//! chunk = TealParser::reparse_chunk(&chunk, &source, edit)?;
+//! ```
+
+use super::ast::*;
+use super::parser::{ParseError, TealParser};
+use super::visit::mutable::{self, VisitorMut};
+
+/// A change to source: the bytes `span` replaced by `inserted` bytes.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Edit {
+    /// The bytes replaced, in the source before the edit.
+    pub span: Span,
+    /// The length of the text replacing them.
+    pub inserted: usize,
+}
+
+/// Moves every span of the nodes it visits by `delta` bytes.
+struct Shift(isize);
+
+impl Edit {
+    pub fn new(span: Span, inserted: usize) -> Self {
+        Self { span, inserted }
+    }
+
+    /// The change in length of the source.
+    pub fn delta(&self) -> isize {
+        self.inserted as isize - self.span.len() as isize
+    }
+}
+
+impl TealParser {
+    /// Parse `source`, which `previous` was parsed from before `edit`, reusing
+    /// the statements it did not touch, see the
+    /// [module documentation](super::incremental).
+    pub fn reparse_chunk(previous: &Block, source: &str, edit: Edit) -> Result<Block, ParseError> {
+        match Self::reparse_statements(previous, source, edit) {
+            Some(chunk) => Ok(chunk),
+            None => Self::parse_chunk(source),
+        }
+    }
+
+    /// The chunk with the statements around `edit` parsed again, or `None` if
+    /// they do not parse on their own.
+    fn reparse_statements(previous: &Block, source: &str, edit: Edit) -> Option<Block> {
+        // statements span their trailing whitespace and comments, so that
+        // they tile the chunk
+        let mut items: Vec<Span> = previous.statements.iter().map(|s| s.span).collect();
+        items.extend(previous.ret.as_ref().map(|r| r.span));
+        let last = items.len().checked_sub(1)?;
+        let touched = |s: &Span| s.start <= edit.span.end && s.end >= edit.span.start;
+        let (first, end) = match items.iter().position(touched) {
+            Some(first) => (first, items.iter().rposition(touched).unwrap()),
+            None => {
+                let next = items.iter().position(|s| s.start > edit.span.end);
+                let next = next.unwrap_or(items.len());
+                (next.saturating_sub(1), next.min(last))
+            }
+        };
+        let (first, end) = (first.saturating_sub(1), (end + 1).min(last));
+
+        let delta = edit.delta();
+        // leading comments are parsed again with the first statement
+        let start = match first {
+            0 => 0,
+            _ => items[first].start.min(edit.span.start),
+        };
+        let old_end = items[end].end.max(edit.span.end);
+        let new_end = old_end.checked_add_signed(delta)?;
+        let text = source.get(start..new_end)?;
+        // an unterminated long bracket, eg. `--[[` or `[==[`, comments out
+        // one line, so opening or closing a long comment or string may change
+        // statements outside the region
+        if ["[[", "[=", "]]", "]="].iter().any(|b| text.contains(b)) {
+            return None;
+        }
+        let mut region = Self::parse_chunk(text).ok()?;
+        let at_end = end == last;
+        if region.ret.is_some() && !at_end {
+            return None;
+        }
+        Shift(start as isize).visit_block(&mut region);
+
+        let mut statements = previous.statements[..first].to_vec();
+        statements.append(&mut region.statements);
+        let mut after = Block {
+            statements: previous.statements[(end + 1).min(previous.statements.len())..].to_vec(),
+            ret: previous.ret.clone().filter(|_| !at_end),
+            span: Span::default(),
+        };
+        Shift(delta).visit_block(&mut after);
+        statements.append(&mut after.statements);
+
+        let span = Span {
+            start: match first {
+                0 => region.span.start,
+                _ => previous.span.start,
+            },
+            end: match at_end {
+                true => region.span.end,
+                false => previous.span.end.checked_add_signed(delta)?,
+            },
+        };
+        Some(Block {
+            statements,
+            ret: region.ret.or(after.ret),
+            span,
+        })
+    }
+}
+
+impl Shift {
+    fn span(&self, span: &mut Span) {
+        span.start = span.start.wrapping_add_signed(self.0);
+        span.end = span.end.wrapping_add_signed(self.0);
+    }
+
+    fn type_def(&self, def: &mut TypeDef) {
+        if let TypeDef::Require { span, .. } = def {
+            self.span(span);
+        }
+    }
+}
+
+impl VisitorMut for Shift {
+    fn visit_block(&mut self, block: &mut Block) {
+        self.span(&mut block.span);
+        if let Some(ret) = &mut block.ret {
+            self.span(&mut ret.span);
+        }
+        mutable::walk_block(self, block);
+    }
+
+    fn visit_statement(&mut self, statement: &mut Statement) {
+        self.span(&mut statement.span);
+        match &mut statement.kind {
+            StatementKind::If { branches, .. } => {
+                for branch in branches {
+                    self.span(&mut branch.span);
+                }
+            }
+            StatementKind::Variables { names, .. } => {
+                for attrib in names.iter_mut().filter_map(|n| n.attrib.as_mut()) {
+                    self.span(&mut attrib.span);
+                }
+            }
+            StatementKind::Function(decl) => self.span(&mut decl.span),
+            StatementKind::Type { def, .. } => self.type_def(def),
+            _ => {}
+        }
+        mutable::walk_statement(self, statement);
+    }
+
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        self.span(&mut expr.span);
+        if let ExprKind::Table(fields) = &mut expr.kind {
+            for field in fields {
+                self.span(&mut field.span);
+            }
+        }
+        mutable::walk_expr(self, expr);
+    }
+
+    fn visit_type(&mut self, ty: &mut TypeAnnotation) {
+        self.span(&mut ty.span);
+        mutable::walk_type(self, ty);
+    }
+
+    fn visit_function(&mut self, body: &mut FunctionBody) {
+        self.span(&mut body.span);
+        for param in &mut body.params {
+            self.span(&mut param.span);
+        }
+        mutable::walk_function(self, body);
+    }
+
+    fn visit_record(&mut self, record: &mut Record) {
+        self.span(&mut record.span);
+        for entry in &mut record.entries {
+            match entry {
+                RecordEntry::Userdata(span) => self.span(span),
+                RecordEntry::Field(field) => self.span(&mut field.span),
+                RecordEntry::Type { def, span, .. } => {
+                    self.span(span);
+                    self.type_def(def);
+                }
+            }
+        }
+        mutable::walk_record(self, record);
+    }
+
+    fn visit_enum(&mut self, enumeration: &mut Enum) {
+        self.span(&mut enumeration.span);
+        mutable::walk_enum(self, enumeration);
+    }
+
+    fn visit_name(&mut self, name: &mut Name) {
+        self.span(&mut name.span);
+    }
+}
@@ -1,5 +1,10 @@
-
 // mod lexer;
+pub mod ast;
+pub mod check;
+pub mod format;
+pub mod incremental;
 pub mod parser;
+pub mod visit;
 
-pub use parser::TealParser;
+pub use incremental::Edit;
+pub use parser::{ParseError, TealParser};
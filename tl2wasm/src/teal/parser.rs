@@ -1,19 +1,909 @@
-
 use pest_derive::Parser;
 
 use pest::{
-    iterators::Pairs,
-    pratt_parser::{Assoc::*, Op, PrattParser},
     Parser,
+    error::{Error as PestError, InputLocation},
+    iterators::{Pair, Pairs},
+    pratt_parser::{Assoc::*, Op, PrattParser},
+};
+use std::fmt;
+use std::sync::LazyLock;
+
+use super::ast::{
+    AttName, BinOp, Block, Enum, Expr, ExprKind, FieldKey, FunctionBody, FunctionDecl,
+    FunctionType, IfBranch, Name, Param, ParamType, Primitive, Record, RecordEntry, RecordField,
+    Return, Scope, Span, Statement, StatementKind, TableField, TypeAnnotation, TypeDef, TypeKind,
+    UnOp,
 };
-use std::io::{stdin, stdout, Write};
 
 #[derive(Parser)]
 #[grammar = "teal.pest"]
 pub struct TealParser;
 
+/// Teal's operators, from the loosest to the tightest binding, as in Lua but
+/// for `is` checks binding as comparisons and `as` casts as postfix
+/// operators.
+static PRATT: LazyLock<PrattParser<Rule>> = LazyLock::new(|| {
+    PrattParser::new()
+        .op(Op::infix(Rule::or, Left))
+        .op(Op::infix(Rule::and, Left))
+        .op(Op::infix(Rule::lt, Left)
+            | Op::infix(Rule::gt, Left)
+            | Op::infix(Rule::le, Left)
+            | Op::infix(Rule::ge, Left)
+            | Op::infix(Rule::ne, Left)
+            | Op::infix(Rule::eq, Left)
+            | Op::postfix(Rule::is_check))
+        .op(Op::infix(Rule::bor, Left))
+        .op(Op::infix(Rule::bxor, Left))
+        .op(Op::infix(Rule::band, Left))
+        .op(Op::infix(Rule::shl, Left) | Op::infix(Rule::shr, Left))
+        .op(Op::infix(Rule::concat, Right))
+        .op(Op::infix(Rule::add, Left) | Op::infix(Rule::sub, Left))
+        .op(Op::infix(Rule::mul, Left)
+            | Op::infix(Rule::div, Left)
+            | Op::infix(Rule::idiv, Left)
+            | Op::infix(Rule::modulo, Left))
+        .op(Op::prefix(Rule::neg)
+            | Op::prefix(Rule::not)
+            | Op::prefix(Rule::len)
+            | Op::prefix(Rule::bnot))
+        .op(Op::infix(Rule::pow, Right))
+        .op(Op::postfix(Rule::cast))
+        .op(Op::postfix(Rule::call)
+            | Op::postfix(Rule::method_call)
+            | Op::postfix(Rule::index)
+            | Op::postfix(Rule::field_access))
+});
+
+/// Why source failed to parse, and where.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub message: String,
+    pub span: Span,
+}
+
 impl TealParser {
-    fn parse(pairs: Pairs<Rule>, pratt: &PrattParser<Rule>) { todo!() }
+    /// Parse `source` as a chunk, eg. the contents of a file.
+    pub fn parse_chunk(source: &str) -> Result<Block, ParseError> {
+        let chunk = <Self as Parser<Rule>>::parse(Rule::chunk, source)?
+            .next()
+            .unwrap();
+        Self::block(chunk.into_inner().next().unwrap())
+    }
+
+    /// Parse `source` as a single expression.
+    pub fn parse_expr(source: &str) -> Result<Expr, ParseError> {
+        let expression = <Self as Parser<Rule>>::parse(Rule::expression, source)?
+            .next()
+            .unwrap();
+        Self::expr(expression.into_inner().next().unwrap())
+    }
+
+    /// The expression of an `expr` pair.
+    pub(crate) fn expr(pair: Pair<Rule>) -> Result<Expr, ParseError> {
+        Self::parse_pairs(pair.into_inner(), &PRATT)
+    }
+
+    /// Structure the operands and operators of an `expr` pair by precedence.
+    fn parse_pairs(pairs: Pairs<Rule>, pratt: &PrattParser<Rule>) -> Result<Expr, ParseError> {
+        pratt
+            .map_primary(Self::primary)
+            .map_prefix(|op, operand| {
+                let operand = operand?;
+                let span = Span::from(op.as_span()).to(operand.span);
+                let op = match op.as_rule() {
+                    Rule::neg => UnOp::Neg,
+                    Rule::not => UnOp::Not,
+                    Rule::len => UnOp::Len,
+                    Rule::bnot => UnOp::BitNot,
+                    rule => unreachable!("{rule:?} is not a prefix operator"),
+                };
+                let operand = Box::new(operand);
+                Ok(Expr {
+                    kind: ExprKind::Unary { op, operand },
+                    span,
+                })
+            })
+            .map_postfix(|lhs, op| Self::postfix(lhs?, op))
+            .map_infix(|lhs, op, rhs| {
+                let (lhs, rhs) = (lhs?, rhs?);
+                let span = lhs.span.to(rhs.span);
+                let op = match op.as_rule() {
+                    Rule::or => BinOp::Or,
+                    Rule::and => BinOp::And,
+                    Rule::lt => BinOp::Lt,
+                    Rule::gt => BinOp::Gt,
+                    Rule::le => BinOp::Le,
+                    Rule::ge => BinOp::Ge,
+                    Rule::ne => BinOp::Ne,
+                    Rule::eq => BinOp::Eq,
+                    Rule::bor => BinOp::BitOr,
+                    Rule::bxor => BinOp::BitXor,
+                    Rule::band => BinOp::BitAnd,
+                    Rule::shl => BinOp::Shl,
+                    Rule::shr => BinOp::Shr,
+                    Rule::concat => BinOp::Concat,
+                    Rule::add => BinOp::Add,
+                    Rule::sub => BinOp::Sub,
+                    Rule::mul => BinOp::Mul,
+                    Rule::div => BinOp::Div,
+                    Rule::idiv => BinOp::IntDiv,
+                    Rule::modulo => BinOp::Mod,
+                    Rule::pow => BinOp::Pow,
+                    rule => unreachable!("{rule:?} is not a binary operator"),
+                };
+                let (lhs, rhs) = (Box::new(lhs), Box::new(rhs));
+                Ok(Expr {
+                    kind: ExprKind::Binary { op, lhs, rhs },
+                    span,
+                })
+            })
+            .parse(pairs)
+    }
+
+    fn primary(pair: Pair<Rule>) -> Result<Expr, ParseError> {
+        let span = Span::from(pair.as_span());
+        let kind = match pair.as_rule() {
+            Rule::nil => ExprKind::Nil,
+            Rule::boolean => ExprKind::Boolean(pair.as_str() == "true"),
+            Rule::number => number(pair.as_str()).ok_or_else(|| ParseError {
+                message: format!("malformed number '{}'", pair.as_str()),
+                span,
+            })?,
+            Rule::string => ExprKind::String(unescape(pair.as_str(), span)?),
+            Rule::varargs => ExprKind::Varargs,
+            Rule::functiondef => {
+                let body = Self::function_body(pair.into_inner().next().unwrap())?;
+                ExprKind::Function(Box::new(body))
+            }
+            Rule::tableconstructor => ExprKind::Table(Self::table(pair)?),
+            Rule::ident => ExprKind::Name(ident(pair)),
+            Rule::paren => {
+                let inner = Self::expr(pair.into_inner().next().unwrap())?;
+                ExprKind::Paren(Box::new(inner))
+            }
+            rule => unreachable!("{rule:?} is not a primary expression"),
+        };
+        Ok(Expr { kind, span })
+    }
+
+    /// Apply the postfix operator `op`, eg. a call, to `lhs`.
+    fn postfix(lhs: Expr, op: Pair<Rule>) -> Result<Expr, ParseError> {
+        let span = lhs.span.to(op.as_span().into());
+        let rule = op.as_rule();
+        let mut inner = op.into_inner();
+        let lhs = Box::new(lhs);
+        let kind = match rule {
+            Rule::call => ExprKind::Call {
+                callee: lhs,
+                args: Self::args(inner.next().unwrap())?,
+            },
+            Rule::method_call => ExprKind::MethodCall {
+                receiver: lhs,
+                method: ident(inner.next().unwrap()),
+                args: Self::args(inner.next().unwrap())?,
+            },
+            Rule::index => ExprKind::Index {
+                target: lhs,
+                index: Box::new(Self::expr(inner.next().unwrap())?),
+            },
+            Rule::field_access => ExprKind::Field {
+                target: lhs,
+                name: ident(inner.next().unwrap()),
+            },
+            Rule::cast => {
+                // skip `as`
+                let ty = inner.nth(1).unwrap();
+                let types = match ty.as_rule() {
+                    Rule::typelist => ty.into_inner().map(Self::ty).collect(),
+                    _ => vec![Self::ty(ty)],
+                };
+                ExprKind::Cast { expr: lhs, types }
+            }
+            Rule::is_check => ExprKind::Is {
+                expr: lhs,
+                ty: Self::ty(inner.nth(1).unwrap()),
+            },
+            rule => unreachable!("{rule:?} is not a postfix operator"),
+        };
+        Ok(Expr { kind, span })
+    }
+
+    /// The arguments of a call, from an `args` pair.
+    fn args(pair: Pair<Rule>) -> Result<Vec<Expr>, ParseError> {
+        let Some(inner) = pair.into_inner().next() else {
+            // `()`
+            return Ok(Vec::new());
+        };
+        match inner.as_rule() {
+            Rule::explist => inner.into_inner().map(Self::expr).collect(),
+            // `f "text"` or `f { ... }`
+            _ => Ok(vec![Self::primary(inner)?]),
+        }
+    }
+
+    /// The fields of a `tableconstructor` pair.
+    fn table(pair: Pair<Rule>) -> Result<Vec<TableField>, ParseError> {
+        let Some(fields) = pair.into_inner().next() else {
+            return Ok(Vec::new());
+        };
+        fields.into_inner().map(Self::field).collect()
+    }
+
+    fn field(pair: Pair<Rule>) -> Result<TableField, ParseError> {
+        let span = Span::from(pair.as_span());
+        let mut inner = pair.into_inner();
+        let first = inner.next().unwrap();
+        let key = match first.as_rule() {
+            Rule::ident => {
+                let ty = match inner.peek() {
+                    Some(ty) if ty.as_rule() == Rule::r#type => {
+                        inner.next();
+                        Some(Self::ty(ty))
+                    }
+                    _ => None,
+                };
+                FieldKey::Name(ident(first), ty)
+            }
+            _ if inner.peek().is_some() => FieldKey::Expr(Self::expr(first)?),
+            _ => {
+                return Ok(TableField {
+                    key: FieldKey::Positional,
+                    value: Self::expr(first)?,
+                    span,
+                });
+            }
+        };
+        let value = Self::expr(inner.next().unwrap())?;
+        Ok(TableField { key, value, span })
+    }
+
+    fn block(pair: Pair<Rule>) -> Result<Block, ParseError> {
+        let span = Span::from(pair.as_span());
+        let mut statements = Vec::new();
+        let mut ret = None;
+        for pair in pair.into_inner() {
+            if pair.as_rule() != Rule::retstat {
+                statements.push(Self::statement(pair)?);
+                continue;
+            }
+            let span = Span::from(pair.as_span());
+            let values = match pair.into_inner().next() {
+                Some(values) => Self::explist(values)?,
+                None => Vec::new(),
+            };
+            ret = Some(Return { values, span });
+        }
+        Ok(Block {
+            statements,
+            ret,
+            span,
+        })
+    }
+
+    /// The expressions of an `explist` or `varlist` pair.
+    fn explist(pair: Pair<Rule>) -> Result<Vec<Expr>, ParseError> {
+        pair.into_inner().map(Self::expr).collect()
+    }
+
+    fn statement(pair: Pair<Rule>) -> Result<Statement, ParseError> {
+        let span = Span::from(pair.as_span());
+        let rule = pair.as_rule();
+        let mut inner = pair.into_inner();
+        let kind = match rule {
+            Rule::label => StatementKind::Label(ident(inner.next().unwrap())),
+            Rule::break_stat => StatementKind::Break,
+            Rule::goto_stat => StatementKind::Goto(ident(inner.next().unwrap())),
+            Rule::do_stat => StatementKind::Do(Self::block(inner.next().unwrap())?),
+            Rule::while_stat => StatementKind::While {
+                condition: Self::expr(inner.next().unwrap())?,
+                body: Self::block(inner.next().unwrap())?,
+            },
+            Rule::repeat_stat => StatementKind::Repeat {
+                body: Self::block(inner.next().unwrap())?,
+                condition: Self::expr(inner.next().unwrap())?,
+            },
+            Rule::if_stat => {
+                let condition = Self::expr(inner.next().unwrap())?;
+                let body = Self::block(inner.next().unwrap())?;
+                let mut branches = vec![IfBranch {
+                    span: Span::new(span.start, body.span.end),
+                    condition,
+                    body,
+                }];
+                let mut otherwise = None;
+                for clause in inner {
+                    let clause_span = Span::from(clause.as_span());
+                    let is_else = clause.as_rule() == Rule::else_clause;
+                    let mut parts = clause.into_inner();
+                    if is_else {
+                        otherwise = Some(Self::block(parts.next().unwrap())?);
+                        continue;
+                    }
+                    branches.push(IfBranch {
+                        condition: Self::expr(parts.next().unwrap())?,
+                        body: Self::block(parts.next().unwrap())?,
+                        span: clause_span,
+                    });
+                }
+                StatementKind::If {
+                    branches,
+                    otherwise,
+                }
+            }
+            Rule::numeric_for => {
+                let var = ident(inner.next().unwrap());
+                let start = Self::expr(inner.next().unwrap())?;
+                let limit = Self::expr(inner.next().unwrap())?;
+                let mut next = inner.next().unwrap();
+                let mut step = None;
+                if next.as_rule() == Rule::expr {
+                    step = Some(Self::expr(next)?);
+                    next = inner.next().unwrap();
+                }
+                StatementKind::NumericFor {
+                    var,
+                    start,
+                    limit,
+                    step,
+                    body: Self::block(next)?,
+                }
+            }
+            Rule::generic_for => StatementKind::GenericFor {
+                names: inner.next().unwrap().into_inner().map(ident).collect(),
+                values: Self::explist(inner.next().unwrap())?,
+                body: Self::block(inner.next().unwrap())?,
+            },
+            Rule::function_stat => {
+                let mut path = Vec::new();
+                let mut method = None;
+                for part in inner.next().unwrap().into_inner() {
+                    match part.as_rule() {
+                        Rule::ident => path.push(ident(part)),
+                        _ => method = Some(ident(part.into_inner().next().unwrap())),
+                    }
+                }
+                StatementKind::Function(FunctionDecl {
+                    scope: None,
+                    path,
+                    method,
+                    body: Self::function_body(inner.next().unwrap())?,
+                    span,
+                })
+            }
+            Rule::scoped_function => StatementKind::Function(FunctionDecl {
+                scope: Some(scope(inner.next().unwrap())),
+                path: vec![ident(inner.next().unwrap())],
+                method: None,
+                body: Self::function_body(inner.next().unwrap())?,
+                span,
+            }),
+            Rule::record_decl => {
+                let scope = scope(inner.next().unwrap());
+                let interface = inner.next().unwrap().as_str() == "interface";
+                let name = ident(inner.next().unwrap());
+                let record = Self::record(name.clone(), interface, inner.next().unwrap(), span)?;
+                StatementKind::Type {
+                    scope,
+                    name,
+                    def: TypeDef::Record(record),
+                }
+            }
+            Rule::enum_decl => {
+                let scope = scope(inner.next().unwrap());
+                let name = ident(inner.next().unwrap());
+                let enumeration = Self::enumeration(name.clone(), inner.next().unwrap(), span)?;
+                StatementKind::Type {
+                    scope,
+                    name,
+                    def: TypeDef::Enum(enumeration),
+                }
+            }
+            Rule::type_decl => {
+                let scope = scope(inner.next().unwrap());
+                let name = ident(inner.next().unwrap());
+                let def = Self::type_def(name.clone(), inner.next().unwrap())?;
+                StatementKind::Type { scope, name, def }
+            }
+            Rule::var_decl => {
+                let scope = scope(inner.next().unwrap());
+                let names = inner
+                    .next()
+                    .unwrap()
+                    .into_inner()
+                    .map(|attname| {
+                        let mut parts = attname.into_inner();
+                        AttName {
+                            name: ident(parts.next().unwrap()),
+                            attrib: parts.next().map(|a| ident(a.into_inner().next().unwrap())),
+                        }
+                    })
+                    .collect();
+                let mut types = Vec::new();
+                let mut values = Vec::new();
+                for part in inner {
+                    match part.as_rule() {
+                        Rule::typelist => types = part.into_inner().map(Self::ty).collect(),
+                        _ => values = Self::explist(part)?,
+                    }
+                }
+                StatementKind::Variables {
+                    scope,
+                    names,
+                    types,
+                    values,
+                }
+            }
+            Rule::assignment => {
+                let targets = Self::explist(inner.next().unwrap())?;
+                let invalid = targets.iter().find(|target| {
+                    !matches!(
+                        target.kind,
+                        ExprKind::Name(_) | ExprKind::Field { .. } | ExprKind::Index { .. }
+                    )
+                });
+                if let Some(target) = invalid {
+                    return Err(ParseError {
+                        message: "cannot assign to this expression".to_owned(),
+                        span: target.span,
+                    });
+                }
+                StatementKind::Assign {
+                    targets,
+                    values: Self::explist(inner.next().unwrap())?,
+                }
+            }
+            Rule::call_stat => {
+                let call = Self::expr(inner.next().unwrap())?;
+                if !matches!(
+                    call.kind,
+                    ExprKind::Call { .. } | ExprKind::MethodCall { .. }
+                ) {
+                    return Err(ParseError {
+                        message: "syntax error, expected a statement".to_owned(),
+                        span,
+                    });
+                }
+                StatementKind::Call(call)
+            }
+            rule => unreachable!("{rule:?} is not a statement"),
+        };
+        Ok(Statement { kind, span })
+    }
+
+    /// The parameters, return types, and block of a `funcbody` pair.
+    fn function_body(pair: Pair<Rule>) -> Result<FunctionBody, ParseError> {
+        let span = Span::from(pair.as_span());
+        let mut parts: Vec<Pair<Rule>> = pair.into_inner().collect();
+        let block = Self::block(parts.pop().unwrap())?;
+        let mut body = FunctionBody {
+            type_params: Vec::new(),
+            params: Vec::new(),
+            varargs: None,
+            returns: Vec::new(),
+            variadic_return: false,
+            block,
+            span,
+        };
+        for part in parts {
+            match part.as_rule() {
+                Rule::typeargs => body.type_params = part.into_inner().map(ident).collect(),
+                Rule::parlist => {
+                    for params in part.into_inner() {
+                        match params.as_rule() {
+                            Rule::parnamelist => {
+                                body.params = params.into_inner().map(Self::param).collect()
+                            }
+                            _ => body.varargs = Some(params.into_inner().next().map(Self::ty)),
+                        }
+                    }
+                }
+                Rule::retlist => (body.returns, body.variadic_return) = Self::returns(part),
+                rule => unreachable!("{rule:?} is not part of a function"),
+            }
+        }
+        Ok(body)
+    }
+
+    /// The parameter of a `parname` pair.
+    fn param(pair: Pair<Rule>) -> Param {
+        let span = Span::from(pair.as_span());
+        let mut parts = pair.into_inner();
+        let mut param = Param {
+            name: ident(parts.next().unwrap()),
+            optional: false,
+            ty: None,
+            span,
+        };
+        for part in parts {
+            match part.as_rule() {
+                Rule::optional => param.optional = true,
+                _ => param.ty = Some(Self::ty(part)),
+            }
+        }
+        param
+    }
+
+    /// The return types of a `retlist` pair, and whether the last one repeats.
+    fn returns(pair: Pair<Rule>) -> (Vec<TypeAnnotation>, bool) {
+        let mut returns = (Vec::new(), false);
+        for part in pair.into_inner() {
+            match part.as_rule() {
+                Rule::typelist => returns.0 = part.into_inner().map(Self::ty).collect(),
+                _ => returns.1 = true,
+            }
+        }
+        returns
+    }
+
+    /// The type of a `type` pair, or of one of its members.
+    fn ty(pair: Pair<Rule>) -> TypeAnnotation {
+        let span = Span::from(pair.as_span());
+        let rule = pair.as_rule();
+        let text = pair.as_str();
+        let mut inner = pair.into_inner();
+        let kind = match rule {
+            Rule::r#type => {
+                let mut members: Vec<TypeAnnotation> = inner.map(Self::ty).collect();
+                if members.len() == 1 {
+                    return members.pop().unwrap();
+                }
+                TypeKind::Union(members)
+            }
+            Rule::primitive => TypeKind::Primitive(Primitive::from_label(text).unwrap()),
+            Rule::anytype => TypeKind::Any,
+            Rule::nominal => {
+                let mut path = Vec::new();
+                let mut args = Vec::new();
+                for part in inner {
+                    match part.as_rule() {
+                        Rule::ident => path.push(ident(part)),
+                        _ => args = part.into_inner().map(Self::ty).collect(),
+                    }
+                }
+                TypeKind::Nominal { path, args }
+            }
+            Rule::arraytype => {
+                let mut members: Vec<TypeAnnotation> = inner.map(Self::ty).collect();
+                match members.len() {
+                    1 => TypeKind::Array(Box::new(members.pop().unwrap())),
+                    _ => TypeKind::Tuple(members),
+                }
+            }
+            Rule::maptype => TypeKind::Map {
+                key: Box::new(Self::ty(inner.next().unwrap())),
+                value: Box::new(Self::ty(inner.next().unwrap())),
+            },
+            Rule::functiontype => {
+                let mut function = FunctionType {
+                    type_params: Vec::new(),
+                    params: Vec::new(),
+                    varargs: None,
+                    returns: Vec::new(),
+                    variadic_return: false,
+                };
+                for part in inner {
+                    match part.as_rule() {
+                        Rule::typeargs => {
+                            function.type_params = part.into_inner().map(ident).collect()
+                        }
+                        Rule::partypelist => {
+                            for param in part.into_inner() {
+                                let mut name = None;
+                                let mut optional = false;
+                                let mut ty = None;
+                                for part in param.into_inner() {
+                                    match part.as_rule() {
+                                        Rule::ident => name = Some(ident(part)),
+                                        Rule::optional => optional = true,
+                                        Rule::varparam => {
+                                            function.varargs =
+                                                Some(part.into_inner().next().map(Self::ty))
+                                        }
+                                        _ => ty = Some(Self::ty(part)),
+                                    }
+                                }
+                                if let Some(ty) = ty {
+                                    function.params.push(ParamType { name, optional, ty });
+                                }
+                            }
+                        }
+                        _ => (function.returns, function.variadic_return) = Self::returns(part),
+                    }
+                }
+                TypeKind::Function(Box::new(function))
+            }
+            rule => unreachable!("{rule:?} is not a type"),
+        };
+        TypeAnnotation { kind, span }
+    }
+
+    /// What a `typedef` pair defines as `name`.
+    fn type_def(name: Name, pair: Pair<Rule>) -> Result<TypeDef, ParseError> {
+        let span = Span::from(pair.as_span());
+        let mut parts = pair.into_inner();
+        let first = parts.next().unwrap();
+        Ok(match first.as_rule() {
+            Rule::record_kind => {
+                let interface = first.as_str() == "interface";
+                TypeDef::Record(Self::record(name, interface, parts.next().unwrap(), span)?)
+            }
+            Rule::enumbody => TypeDef::Enum(Self::enumeration(name, first, span)?),
+            Rule::require_type => {
+                let span = Span::from(first.as_span());
+                let mut parts = first.into_inner();
+                let module = parts.next().unwrap();
+                TypeDef::Require {
+                    module: unescape(module.as_str(), module.as_span().into())?,
+                    path: parts.map(ident).collect(),
+                    span,
+                }
+            }
+            _ => TypeDef::Alias(Self::ty(first)),
+        })
+    }
+
+    /// The record `name` with the entries of a `recordbody` pair.
+    fn record(
+        name: Name,
+        interface: bool,
+        body: Pair<Rule>,
+        span: Span,
+    ) -> Result<Record, ParseError> {
+        let mut record = Record {
+            name,
+            interface,
+            type_params: Vec::new(),
+            interfaces: Vec::new(),
+            where_clause: None,
+            entries: Vec::new(),
+            span,
+        };
+        for part in body.into_inner() {
+            match part.as_rule() {
+                Rule::typeargs => record.type_params = part.into_inner().map(ident).collect(),
+                Rule::is_kw => {}
+                Rule::interfacelist => {
+                    record.interfaces = part.into_inner().map(Self::ty).collect()
+                }
+                Rule::where_clause => {
+                    record.where_clause = Some(Self::expr(part.into_inner().next().unwrap())?)
+                }
+                _ => record.entries.push(Self::record_entry(part)?),
+            }
+        }
+        Ok(record)
+    }
+
+    fn record_entry(pair: Pair<Rule>) -> Result<RecordEntry, ParseError> {
+        let span = Span::from(pair.as_span());
+        let mut parts = pair.into_inner();
+        let first = parts.next().unwrap();
+        Ok(match first.as_rule() {
+            Rule::userdata => RecordEntry::Userdata(span),
+            Rule::metamethod | Rule::recordkey => {
+                let metamethod = first.as_rule() == Rule::metamethod;
+                let key = if metamethod {
+                    parts.next().unwrap()
+                } else {
+                    first
+                };
+                let key = key.into_inner().next().unwrap();
+                let name = match key.as_rule() {
+                    Rule::ident => ident(key),
+                    // `["key"]`
+                    _ => {
+                        let key_span = Span::from(key.as_span());
+                        Name {
+                            name: unescape(key.as_str(), key_span)?,
+                            span: key_span,
+                        }
+                    }
+                };
+                RecordEntry::Field(RecordField {
+                    name,
+                    metamethod,
+                    ty: Self::ty(parts.next().unwrap()),
+                    span,
+                })
+            }
+            Rule::record_kind => {
+                let interface = first.as_str() == "interface";
+                let name = ident(parts.next().unwrap());
+                let record = Self::record(name.clone(), interface, parts.next().unwrap(), span)?;
+                RecordEntry::Type {
+                    name,
+                    def: TypeDef::Record(record),
+                    span,
+                }
+            }
+            _ => {
+                let name = ident(first);
+                let def = parts.next().unwrap();
+                let def = match def.as_rule() {
+                    Rule::enumbody => TypeDef::Enum(Self::enumeration(name.clone(), def, span)?),
+                    _ => Self::type_def(name.clone(), def)?,
+                };
+                RecordEntry::Type { name, def, span }
+            }
+        })
+    }
+
+    /// The enum `name` with the values of an `enumbody` pair.
+    fn enumeration(name: Name, body: Pair<Rule>, span: Span) -> Result<Enum, ParseError> {
+        let values = body
+            .into_inner()
+            .map(|value| {
+                let span = Span::from(value.as_span());
+                Ok(Name {
+                    name: unescape(value.as_str(), span)?,
+                    span,
+                })
+            })
+            .collect::<Result<_, ParseError>>()?;
+        Ok(Enum { name, values, span })
+    }
+}
+
+fn scope(pair: Pair<Rule>) -> Scope {
+    match pair.as_str() {
+        "global" => Scope::Global,
+        _ => Scope::Local,
+    }
+}
+
+fn ident(pair: Pair<Rule>) -> Name {
+    Name {
+        name: pair.as_str().to_owned(),
+        span: pair.as_span().into(),
+    }
+}
+
+/// The value of a number literal: an integer unless it has a fraction or an
+/// exponent, wrapping around for hexadecimal and binary ones as in Lua.
+fn number(text: &str) -> Option<ExprKind> {
+    let text = text.to_ascii_lowercase();
+    let radix = match (text.strip_prefix("0x"), text.strip_prefix("0b")) {
+        (Some(digits), _) => Some((digits.to_owned(), 16)),
+        (_, Some(digits)) => Some((digits.replace('_', ""), 2)),
+        _ => None,
+    };
+    if let Some((digits, radix)) = radix {
+        if digits.contains(['.', 'p']) {
+            return radix_float(&digits, radix).map(ExprKind::Number);
+        }
+        let value = digits.chars().try_fold(0u64, |value, c| {
+            let digit = c.to_digit(radix)? as u64;
+            Some(value.wrapping_mul(radix as u64).wrapping_add(digit))
+        })?;
+        return Some(ExprKind::Integer(value as i64));
+    }
+    if text.contains(['.', 'e']) {
+        return text.parse().ok().map(ExprKind::Number);
+    }
+    match text.parse() {
+        Ok(value) => Some(ExprKind::Integer(value)),
+        // too large for an integer
+        Err(_) => text.parse().ok().map(ExprKind::Number),
+    }
+}
+
+/// The value of `digits` in `radix`, with an optional fraction and, for
+/// hexadecimal, binary exponent after `p`.
+fn radix_float(digits: &str, radix: u32) -> Option<f64> {
+    let (mantissa, exponent) = match digits.split_once('p') {
+        Some((mantissa, exponent)) => (mantissa, exponent.parse::<i32>().ok()?),
+        None => (digits, 0),
+    };
+    let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let base = radix as f64;
+    let mut value = 0.0;
+    for c in integer.chars() {
+        value = value * base + c.to_digit(radix)? as f64;
+    }
+    let mut scale = 1.0;
+    for c in fraction.chars() {
+        scale /= base;
+        value += c.to_digit(radix)? as f64 * scale;
+    }
+    Some(value * 2f64.powi(exponent))
 }
 
+/// The contents of a string literal, including its quotes or long brackets,
+/// with its escapes resolved. The grammar only accepts well-formed escapes.
+fn unescape(literal: &str, span: Span) -> Result<String, ParseError> {
+    if let Some(rest) = literal.strip_prefix('[') {
+        // `[==[` and `]==]`, and the line break right after the opening
+        let level = rest.find('[').unwrap() + 2;
+        let body = &literal[level..literal.len() - level];
+        let body = ["\r\n", "\n\r", "\n", "\r"]
+            .iter()
+            .find_map(|newline| body.strip_prefix(newline))
+            .unwrap_or(body);
+        return Ok(body.to_owned());
+    }
+    let body = &literal[1..literal.len() - 1];
+    let mut text = String::with_capacity(body.len());
+    let mut chars = body.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => text.push('\n'),
+            Some('r') => text.push('\r'),
+            Some('t') => text.push('\t'),
+            Some('a') => text.push('\x07'),
+            Some('b') => text.push('\x08'),
+            Some('f') => text.push('\x0c'),
+            Some('v') => text.push('\x0b'),
+            Some('x') => {
+                let hex: String = chars.by_ref().take(2).collect();
+                let byte = u8::from_str_radix(&hex, 16).unwrap();
+                text.push(char::from(byte));
+            }
+            Some('u') => {
+                let hex: String = chars.by_ref().skip(1).take_while(|c| *c != '}').collect();
+                let code = u32::from_str_radix(&hex, 16).unwrap();
+                let c = char::from_u32(code).ok_or_else(|| ParseError {
+                    message: format!("invalid unicode escape '\\u{{{hex}}}'"),
+                    span,
+                })?;
+                text.push(c);
+            }
+            Some(digit @ '0'..='9') => {
+                let mut code = digit.to_digit(10).unwrap();
+                for _ in 0..2 {
+                    match chars.next_if(char::is_ascii_digit) {
+                        Some(digit) => code = code * 10 + digit.to_digit(10).unwrap(),
+                        None => break,
+                    }
+                }
+                let byte = u8::try_from(code).map_err(|_| ParseError {
+                    message: format!("decimal escape '\\{code}' too large"),
+                    span,
+                })?;
+                text.push(char::from(byte));
+            }
+            // skips the whitespace after it, to break long strings
+            Some('z') => while chars.next_if(|c| c.is_ascii_whitespace()).is_some() {},
+            // an escaped line break, `\r\n` or `\n\r` counting as one
+            Some(newline @ ('\n' | '\r')) => {
+                let other = if newline == '\n' { '\r' } else { '\n' };
+                chars.next_if_eq(&other);
+                text.push('\n');
+            }
+            // `\\`, `\"` and `\'`
+            Some(c) => text.push(c),
+            None => unreachable!("the grammar rejects a trailing '\\'"),
+        }
+    }
+    Ok(text)
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at {}..{}",
+            self.message, self.span.start, self.span.end
+        )
+    }
+}
 
+impl std::error::Error for ParseError {}
+
+impl From<PestError<Rule>> for ParseError {
+    fn from(e: PestError<Rule>) -> Self {
+        let span = match e.location {
+            InputLocation::Pos(pos) => Span::new(pos, pos),
+            InputLocation::Span((start, end)) => Span::new(start, end),
+        };
+        Self {
+            message: e.variant.message().into_owned(),
+            span,
+        }
+    }
+}
@@ -0,0 +1,352 @@
+//! Traversal of the [syntax tree](super::ast), for passes such as type checking
+//! and code generation.
+//!
+//! A [`Visitor`] method is called for every node of its kind. Its default
+//! visits the node's children through the matching `walk_*` function, which an
+//! override calls to keep descending. [`mutable::VisitorMut`] does the same
+//! with mutable references, eg. to rewrite nodes in place.
+//!
+//! ```ignore
+//! struct Calls(usize);
+//!
+//! impl Visitor for Calls {
+//!     fn visit_expr(&mut self, expr: &Expr) {
+//!         if let ExprKind::Call { .. } | ExprKind::MethodCall { .. } = expr.kind {
+//!             self.0 += 1;
+//!         }
+//!         walk_expr(self, expr);
+//!     }
+//! }
+//! ```
+
+use super::ast::*;
+
+/// Define a visitor trait and its `walk_*` functions, taking references or,
+/// given `mut`, mutable ones.
+macro_rules! visitor {
+    ($(#[$attr:meta])* $Visitor:ident $(, $m:tt)?) => {
+        $(#[$attr])*
+        pub trait $Visitor {
+            fn visit_block(&mut self, block: &$($m)? Block) {
+                walk_block(self, block)
+            }
+
+            fn visit_statement(&mut self, statement: &$($m)? Statement) {
+                walk_statement(self, statement)
+            }
+
+            fn visit_expr(&mut self, expr: &$($m)? Expr) {
+                walk_expr(self, expr)
+            }
+
+            fn visit_type(&mut self, ty: &$($m)? TypeAnnotation) {
+                walk_type(self, ty)
+            }
+
+            /// Visit the body of a function, named or not.
+            fn visit_function(&mut self, body: &$($m)? FunctionBody) {
+                walk_function(self, body)
+            }
+
+            fn visit_record(&mut self, record: &$($m)? Record) {
+                walk_record(self, record)
+            }
+
+            fn visit_enum(&mut self, enumeration: &$($m)? Enum) {
+                walk_enum(self, enumeration)
+            }
+
+            /// Visit a name, declared or used.
+            fn visit_name(&mut self, _name: &$($m)? Name) {}
+        }
+
+        pub fn walk_block<V: $Visitor + ?Sized>(v: &mut V, block: &$($m)? Block) {
+            for statement in &$($m)? block.statements {
+                v.visit_statement(statement);
+            }
+            if let Some(ret) = &$($m)? block.ret {
+                for value in &$($m)? ret.values {
+                    v.visit_expr(value);
+                }
+            }
+        }
+
+        pub fn walk_statement<V: $Visitor + ?Sized>(v: &mut V, statement: &$($m)? Statement) {
+            match &$($m)? statement.kind {
+                StatementKind::Assign { targets, values } => {
+                    for target in targets {
+                        v.visit_expr(target);
+                    }
+                    for value in values {
+                        v.visit_expr(value);
+                    }
+                }
+                StatementKind::Call(call) => v.visit_expr(call),
+                StatementKind::Label(name) | StatementKind::Goto(name) => v.visit_name(name),
+                StatementKind::Break => {}
+                StatementKind::Do(body) => v.visit_block(body),
+                StatementKind::While { condition, body } => {
+                    v.visit_expr(condition);
+                    v.visit_block(body);
+                }
+                StatementKind::Repeat { body, condition } => {
+                    v.visit_block(body);
+                    v.visit_expr(condition);
+                }
+                StatementKind::If { branches, otherwise } => {
+                    for branch in branches {
+                        v.visit_expr(&$($m)? branch.condition);
+                        v.visit_block(&$($m)? branch.body);
+                    }
+                    if let Some(body) = otherwise {
+                        v.visit_block(body);
+                    }
+                }
+                StatementKind::NumericFor {
+                    var,
+                    start,
+                    limit,
+                    step,
+                    body,
+                } => {
+                    v.visit_name(var);
+                    v.visit_expr(start);
+                    v.visit_expr(limit);
+                    if let Some(step) = step {
+                        v.visit_expr(step);
+                    }
+                    v.visit_block(body);
+                }
+                StatementKind::GenericFor { names, values, body } => {
+                    for name in names {
+                        v.visit_name(name);
+                    }
+                    for value in values {
+                        v.visit_expr(value);
+                    }
+                    v.visit_block(body);
+                }
+                StatementKind::Variables {
+                    names,
+                    types,
+                    values,
+                    ..
+                } => {
+                    for name in names {
+                        v.visit_name(&$($m)? name.name);
+                    }
+                    for ty in types {
+                        v.visit_type(ty);
+                    }
+                    for value in values {
+                        v.visit_expr(value);
+                    }
+                }
+                StatementKind::Function(decl) => {
+                    for name in &$($m)? decl.path {
+                        v.visit_name(name);
+                    }
+                    if let Some(method) = &$($m)? decl.method {
+                        v.visit_name(method);
+                    }
+                    v.visit_function(&$($m)? decl.body);
+                }
+                StatementKind::Type { name, def, .. } => {
+                    v.visit_name(name);
+                    walk_type_def(v, def);
+                }
+            }
+        }
+
+        pub fn walk_type_def<V: $Visitor + ?Sized>(v: &mut V, def: &$($m)? TypeDef) {
+            match def {
+                TypeDef::Record(record) => v.visit_record(record),
+                TypeDef::Enum(enumeration) => v.visit_enum(enumeration),
+                TypeDef::Alias(ty) => v.visit_type(ty),
+                TypeDef::Require { path, .. } => {
+                    for name in path {
+                        v.visit_name(name);
+                    }
+                }
+            }
+        }
+
+        pub fn walk_record<V: $Visitor + ?Sized>(v: &mut V, record: &$($m)? Record) {
+            v.visit_name(&$($m)? record.name);
+            for param in &$($m)? record.type_params {
+                v.visit_name(param);
+            }
+            for interface in &$($m)? record.interfaces {
+                v.visit_type(interface);
+            }
+            if let Some(condition) = &$($m)? record.where_clause {
+                v.visit_expr(condition);
+            }
+            for entry in &$($m)? record.entries {
+                match entry {
+                    RecordEntry::Userdata(_) => {}
+                    RecordEntry::Field(field) => {
+                        v.visit_name(&$($m)? field.name);
+                        v.visit_type(&$($m)? field.ty);
+                    }
+                    RecordEntry::Type { name, def, .. } => {
+                        v.visit_name(name);
+                        walk_type_def(v, def);
+                    }
+                }
+            }
+        }
+
+        pub fn walk_enum<V: $Visitor + ?Sized>(v: &mut V, enumeration: &$($m)? Enum) {
+            v.visit_name(&$($m)? enumeration.name);
+            for value in &$($m)? enumeration.values {
+                v.visit_name(value);
+            }
+        }
+
+        pub fn walk_function<V: $Visitor + ?Sized>(v: &mut V, body: &$($m)? FunctionBody) {
+            for param in &$($m)? body.type_params {
+                v.visit_name(param);
+            }
+            for param in &$($m)? body.params {
+                v.visit_name(&$($m)? param.name);
+                if let Some(ty) = &$($m)? param.ty {
+                    v.visit_type(ty);
+                }
+            }
+            if let Some(Some(ty)) = &$($m)? body.varargs {
+                v.visit_type(ty);
+            }
+            for ty in &$($m)? body.returns {
+                v.visit_type(ty);
+            }
+            v.visit_block(&$($m)? body.block);
+        }
+
+        pub fn walk_expr<V: $Visitor + ?Sized>(v: &mut V, expr: &$($m)? Expr) {
+            match &$($m)? expr.kind {
+                ExprKind::Nil
+                | ExprKind::Boolean(_)
+                | ExprKind::Integer(_)
+                | ExprKind::Number(_)
+                | ExprKind::String(_)
+                | ExprKind::Varargs => {}
+                ExprKind::Function(body) => v.visit_function(body),
+                ExprKind::Name(name) => v.visit_name(name),
+                ExprKind::Paren(inner) => v.visit_expr(inner),
+                ExprKind::Table(fields) => {
+                    for field in fields {
+                        match &$($m)? field.key {
+                            FieldKey::Positional => {}
+                            FieldKey::Name(name, ty) => {
+                                v.visit_name(name);
+                                if let Some(ty) = ty {
+                                    v.visit_type(ty);
+                                }
+                            }
+                            FieldKey::Expr(key) => v.visit_expr(key),
+                        }
+                        v.visit_expr(&$($m)? field.value);
+                    }
+                }
+                ExprKind::Unary { operand, .. } => v.visit_expr(operand),
+                ExprKind::Binary { lhs, rhs, .. } => {
+                    v.visit_expr(lhs);
+                    v.visit_expr(rhs);
+                }
+                ExprKind::Call { callee, args } => {
+                    v.visit_expr(callee);
+                    for arg in args {
+                        v.visit_expr(arg);
+                    }
+                }
+                ExprKind::MethodCall {
+                    receiver,
+                    method,
+                    args,
+                } => {
+                    v.visit_expr(receiver);
+                    v.visit_name(method);
+                    for arg in args {
+                        v.visit_expr(arg);
+                    }
+                }
+                ExprKind::Index { target, index } => {
+                    v.visit_expr(target);
+                    v.visit_expr(index);
+                }
+                ExprKind::Field { target, name } => {
+                    v.visit_expr(target);
+                    v.visit_name(name);
+                }
+                ExprKind::Cast { expr, types } => {
+                    v.visit_expr(expr);
+                    for ty in types {
+                        v.visit_type(ty);
+                    }
+                }
+                ExprKind::Is { expr, ty } => {
+                    v.visit_expr(expr);
+                    v.visit_type(ty);
+                }
+            }
+        }
+
+        pub fn walk_type<V: $Visitor + ?Sized>(v: &mut V, ty: &$($m)? TypeAnnotation) {
+            match &$($m)? ty.kind {
+                TypeKind::Any | TypeKind::Primitive(_) => {}
+                TypeKind::Nominal { path, args } => {
+                    for name in path {
+                        v.visit_name(name);
+                    }
+                    for arg in args {
+                        v.visit_type(arg);
+                    }
+                }
+                TypeKind::Array(element) => v.visit_type(element),
+                TypeKind::Tuple(members) | TypeKind::Union(members) => {
+                    for member in members {
+                        v.visit_type(member);
+                    }
+                }
+                TypeKind::Map { key, value } => {
+                    v.visit_type(key);
+                    v.visit_type(value);
+                }
+                TypeKind::Function(function) => {
+                    for param in &$($m)? function.type_params {
+                        v.visit_name(param);
+                    }
+                    for param in &$($m)? function.params {
+                        if let Some(name) = &$($m)? param.name {
+                            v.visit_name(name);
+                        }
+                        v.visit_type(&$($m)? param.ty);
+                    }
+                    if let Some(Some(ty)) = &$($m)? function.varargs {
+                        v.visit_type(ty);
+                    }
+                    for ty in &$($m)? function.returns {
+                        v.visit_type(ty);
+                    }
+                }
+            }
+        }
+    };
+}
+
+visitor!(
+    /// Visits the nodes of a syntax tree, see the [module documentation](self).
+    Visitor
+);
+
+pub mod mutable {
+    use super::super::ast::*;
+
+    visitor!(
+        /// Visits the nodes of a syntax tree mutably, see the
+        /// [module documentation](super).
+        VisitorMut,
+        mut
+    );
+}
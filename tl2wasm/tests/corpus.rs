@@ -0,0 +1,27 @@
+//! Every example of the Teal language in `corpus/` parses.
+
+use std::path::Path;
+
+use tl2wasm::teal::TealParser;
+
+#[test]
+fn corpus_parses() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("corpus");
+    let mut files: Vec<_> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .filter(|p| p.extension().is_some_and(|e| e == "tl"))
+        .collect();
+    files.sort();
+    assert!(!files.is_empty());
+
+    let failed: Vec<String> = files
+        .iter()
+        .filter_map(|file| {
+            let source = std::fs::read_to_string(file).unwrap();
+            let e = TealParser::parse_chunk(&source).err()?;
+            Some(format!("{}: {e}", file.display()))
+        })
+        .collect();
+    assert!(failed.is_empty(), "{}", failed.join("\n"));
+}
@@ -0,0 +1,17 @@
+//! The code generator still emits the snapshots of `snapshots/`.
+
+use std::path::Path;
+use std::process::Command;
+
+#[test]
+fn snapshots_match() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("snapshots");
+    let output = Command::new(env!("CARGO_BIN_EXE_tl2wasm"))
+        .arg("snapshot")
+        .arg(&dir)
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{stderr}");
+    assert!(stderr.trim_end().ends_with(", 0 differ"), "{stderr}");
+}
@@ -0,0 +1,72 @@
+//! The standard library prints what the fixtures of `snapshots/` expect.
+
+use std::path::Path;
+
+use tl2wasm::host::HostRegistry;
+use tl2wasm::repl::Repl;
+
+#[test]
+fn stdlib_conformance() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("snapshots");
+    let mut fixtures: Vec<_> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .filter(|p| {
+            p.file_name()
+                .unwrap()
+                .to_str()
+                .unwrap()
+                .starts_with("stdlib_")
+        })
+        .filter(|p| p.extension().is_some_and(|e| e == "tl"))
+        .collect();
+    fixtures.sort();
+    assert!(!fixtures.is_empty());
+
+    for fixture in &fixtures {
+        let source = std::fs::read_to_string(fixture).unwrap();
+        let expected = std::fs::read_to_string(fixture.with_extension("out")).unwrap();
+        let registry = HostRegistry::new();
+        let mut repl = Repl::new(&registry);
+        let (printed, result) = repl.eval(&source, &registry, &mut ());
+        if let Err(e) = result {
+            panic!("{}: {e}", fixture.display());
+        }
+        let actual: String = printed.iter().map(|line| format!("{line}\n")).collect();
+        assert_eq!(actual, expected, "{}", fixture.display());
+    }
+}
+
+/// The error of running `source` in a fresh REPL.
+fn error(source: &str) -> String {
+    let registry = HostRegistry::new();
+    let mut repl = Repl::new(&registry);
+    let (_, result) = repl.eval(source, &registry, &mut ());
+    result.unwrap_err().to_string()
+}
+
+#[test]
+fn out_of_bounds_reads_trap() {
+    let message = error("local t = {1, 2, 3}\nprint(t[100])");
+    assert!(message.contains("out of bounds"), "{message}");
+    let message = error("local t = {1, 2, 3}\nprint(t[0])");
+    assert!(message.contains("out of bounds"), "{message}");
+}
+
+#[test]
+fn out_of_bounds_insert_traps() {
+    let message = error("local t = {1, 2, 3}\ntable.insert(t, 5, 4)");
+    assert!(message.contains("out of bounds"), "{message}");
+}
+
+#[test]
+fn find_rejects_patterns() {
+    let message = error("print(string.find(\"a.b\", \".\"))");
+    assert!(message.contains("patterns are not supported"), "{message}");
+}
+
+#[test]
+fn random_rejects_empty_intervals() {
+    let message = error("print(math.random(2, 1))");
+    assert!(message.contains("interval is empty"), "{message}");
+}